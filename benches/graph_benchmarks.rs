@@ -2,9 +2,8 @@ use criterion::{black_box, criterion_group, criterion_main, Criterion, Benchmark
 use graphy::{
     GraphDescription, NodeInstance, Connection, Pin, PinInstance, PinType,
     DataType, NodeTypes, PropertyValue, ConnectionType, Position,
-    DataResolver, ExecutionRouting, NodeMetadata, ParamInfo, NodeMetadataProvider,
+    DataResolver, ExecutionRouting, NodeMetadata, ParamInfo, NodeRegistry,
 };
-use std::collections::HashMap;
 
 // Initialize thread pool once for all benchmarks
 fn init_benchmark_environment() {
@@ -26,104 +25,82 @@ fn init_benchmark_environment() {
 // Mock Metadata Provider for Benchmarks
 // ============================================================================
 
-struct BenchmarkMetadataProvider {
-    metadata: HashMap<String, NodeMetadata>,
-}
-
-impl BenchmarkMetadataProvider {
-    fn new() -> Self {
-        let mut metadata = HashMap::new();
-
-        // Pure math nodes
-        metadata.insert(
-            "math.add".to_string(),
-            NodeMetadata::new("add", NodeTypes::pure, "Math")
-                .with_params(vec![
-                    ParamInfo::new("a", "f64"),
-                    ParamInfo::new("b", "f64"),
-                ])
-                .with_return_type("f64")
-                .with_source("a + b"),
-        );
-
-        metadata.insert(
-            "math.constant".to_string(),
-            NodeMetadata::new("constant", NodeTypes::pure, "Math")
-                .with_return_type("f64")
-                .with_source("value"),
-        );
-
-        metadata.insert(
-            "math.multiply".to_string(),
-            NodeMetadata::new("multiply", NodeTypes::pure, "Math")
-                .with_params(vec![
-                    ParamInfo::new("a", "f64"),
-                    ParamInfo::new("b", "f64"),
-                ])
-                .with_return_type("f64")
-                .with_source("a * b"),
-        );
-
-        metadata.insert(
-            "math.sqrt".to_string(),
-            NodeMetadata::new("sqrt", NodeTypes::pure, "Math")
-                .with_params(vec![ParamInfo::new("value", "f64")])
-                .with_return_type("f64")
-                .with_source("value.sqrt()"),
-        );
-
-        // Function nodes (with side effects)
-        metadata.insert(
-            "print".to_string(),
-            NodeMetadata::new("print", NodeTypes::fn_, "IO")
-                .with_params(vec![ParamInfo::new("value", "String")])
-                .with_exec_outputs(vec!["then".to_string()])
-                .with_source(r#"println!("{}", value)"#),
-        );
-
-        // Control flow
-        metadata.insert(
-            "branch".to_string(),
-            NodeMetadata::new("branch", NodeTypes::control_flow, "Flow")
-                .with_params(vec![ParamInfo::new("condition", "bool")])
-                .with_exec_outputs(vec!["true".to_string(), "false".to_string()]),
-        );
-
-        metadata.insert(
-            "for_loop".to_string(),
-            NodeMetadata::new("for_loop", NodeTypes::control_flow, "Flow")
-                .with_params(vec![
-                    ParamInfo::new("start", "i32"),
-                    ParamInfo::new("end", "i32"),
-                ])
-                .with_exec_outputs(vec!["body".to_string(), "completed".to_string()]),
-        );
-
-        // Event
-        metadata.insert(
-            "event.start".to_string(),
-            NodeMetadata::new("start", NodeTypes::event, "Events")
-                .with_exec_outputs(vec!["exec".to_string()]),
-        );
-
-        Self { metadata }
-    }
-}
-
-impl NodeMetadataProvider for BenchmarkMetadataProvider {
-    fn get_node_metadata(&self, node_type: &str) -> Option<&NodeMetadata> {
-        self.metadata.get(node_type)
-    }
-
-    fn get_all_nodes(&self) -> Vec<&NodeMetadata> {
-        self.metadata.values().collect()
-    }
-
-    fn get_nodes_by_category(&self, category: &str) -> Vec<&NodeMetadata> {
-        self.metadata.values()
-            .filter(|m| m.category == category)
-            .collect()
-    }
+fn benchmark_provider() -> NodeRegistry {
+    let mut registry = NodeRegistry::new();
+
+    // Pure math nodes
+    registry.register_as(
+        "math.add",
+        NodeMetadata::new("add", NodeTypes::pure, "Math")
+            .with_params(vec![
+                ParamInfo::new("a", "f64"),
+                ParamInfo::new("b", "f64"),
+            ])
+            .with_return_type("f64")
+            .with_source("a + b"),
+    );
+
+    registry.register_as(
+        "math.constant",
+        NodeMetadata::new("constant", NodeTypes::pure, "Math")
+            .with_return_type("f64")
+            .with_source("value"),
+    );
+
+    registry.register_as(
+        "math.multiply",
+        NodeMetadata::new("multiply", NodeTypes::pure, "Math")
+            .with_params(vec![
+                ParamInfo::new("a", "f64"),
+                ParamInfo::new("b", "f64"),
+            ])
+            .with_return_type("f64")
+            .with_source("a * b"),
+    );
+
+    registry.register_as(
+        "math.sqrt",
+        NodeMetadata::new("sqrt", NodeTypes::pure, "Math")
+            .with_params(vec![ParamInfo::new("value", "f64")])
+            .with_return_type("f64")
+            .with_source("value.sqrt()"),
+    );
+
+    // Function nodes (with side effects)
+    registry.register_as(
+        "print",
+        NodeMetadata::new("print", NodeTypes::fn_, "IO")
+            .with_params(vec![ParamInfo::new("value", "String")])
+            .with_exec_outputs(vec!["then".to_string()])
+            .with_source(r#"println!("{}", value)"#),
+    );
+
+    // Control flow
+    registry.register_as(
+        "branch",
+        NodeMetadata::new("branch", NodeTypes::control_flow, "Flow")
+            .with_params(vec![ParamInfo::new("condition", "bool")])
+            .with_exec_outputs(vec!["true".to_string(), "false".to_string()]),
+    );
+
+    registry.register_as(
+        "for_loop",
+        NodeMetadata::new("for_loop", NodeTypes::control_flow, "Flow")
+            .with_params(vec![
+                ParamInfo::new("start", "i32"),
+                ParamInfo::new("end", "i32"),
+            ])
+            .with_exec_outputs(vec!["body".to_string(), "completed".to_string()]),
+    );
+
+    // Event
+    registry.register_as(
+        "event.start",
+        NodeMetadata::new("start", NodeTypes::event, "Events")
+            .with_exec_outputs(vec!["exec".to_string()]),
+    );
+
+    registry
 }
 
 // ============================================================================
@@ -169,13 +146,13 @@ fn create_linear_chain(length: usize) -> GraphDescription {
         let source = if i == 0 { "const_0" } else { &format!("add_{}", i - 1) };
         let source_pin = if i == 0 { "value" } else { "result" };
 
-        graph.add_connection(Connection {
-            source_node: source.to_string(),
-            source_pin: source_pin.to_string(),
-            target_node: node_id.clone(),
-            target_pin: "a".to_string(),
-            connection_type: ConnectionType::Data,
-        });
+        graph.add_connection(Connection::new(
+            source.to_string(),
+            source_pin.to_string(),
+            node_id.clone(),
+            "a".to_string(),
+            ConnectionType::Data,
+        ));
     }
 
     graph
@@ -210,21 +187,21 @@ fn create_wide_graph(width: usize) -> GraphDescription {
         graph.add_node(node);
 
         // Connect constants
-        graph.add_connection(Connection {
-            source_node: format!("const_{}", i),
-            source_pin: "value".to_string(),
-            target_node: node_id.clone(),
-            target_pin: "a".to_string(),
-            connection_type: ConnectionType::Data,
-        });
+        graph.add_connection(Connection::new(
+            format!("const_{}", i),
+            "value".to_string(),
+            node_id.clone(),
+            "a".to_string(),
+            ConnectionType::Data,
+        ));
 
-        graph.add_connection(Connection {
-            source_node: format!("const_{}", i + 1),
-            source_pin: "value".to_string(),
-            target_node: node_id.clone(),
-            target_pin: "b".to_string(),
-            connection_type: ConnectionType::Data,
-        });
+        graph.add_connection(Connection::new(
+            format!("const_{}", i + 1),
+            "value".to_string(),
+            node_id.clone(),
+            "b".to_string(),
+            ConnectionType::Data,
+        ));
     }
 
     // Final convergence node
@@ -236,21 +213,21 @@ fn create_wide_graph(width: usize) -> GraphDescription {
 
     // Connect first and last operation to final
     if width >= 2 {
-        graph.add_connection(Connection {
-            source_node: "op_0".to_string(),
-            source_pin: "result".to_string(),
-            target_node: "final_add".to_string(),
-            target_pin: "a".to_string(),
-            connection_type: ConnectionType::Data,
-        });
+        graph.add_connection(Connection::new(
+            "op_0".to_string(),
+            "result".to_string(),
+            "final_add".to_string(),
+            "a".to_string(),
+            ConnectionType::Data,
+        ));
 
-        graph.add_connection(Connection {
-            source_node: format!("op_{}", width - 2),
-            source_pin: "result".to_string(),
-            target_node: "final_add".to_string(),
-            target_pin: "b".to_string(),
-            connection_type: ConnectionType::Data,
-        });
+        graph.add_connection(Connection::new(
+            format!("op_{}", width - 2),
+            "result".to_string(),
+            "final_add".to_string(),
+            "b".to_string(),
+            ConnectionType::Data,
+        ));
     }
 
     graph
@@ -264,7 +241,6 @@ fn create_dependency_tree(depth: usize) -> GraphDescription {
 
     fn add_tree_level(
         graph: &mut GraphDescription,
-        depth: usize,
         current_depth: usize,
         _parent_id: &str,
         _is_left: bool,
@@ -291,31 +267,31 @@ fn create_dependency_tree(depth: usize) -> GraphDescription {
 
             // Create children
             let spacing = 100.0 * 2_f64.powi(current_depth as i32);
-            let left_child = add_tree_level(graph, depth, current_depth - 1, &node_id, true, counter, x_offset - spacing, y_pos + 150.0);
-            let right_child = add_tree_level(graph, depth, current_depth - 1, &node_id, false, counter, x_offset + spacing, y_pos + 150.0);
+            let left_child = add_tree_level(graph, current_depth - 1, &node_id, true, counter, x_offset - spacing, y_pos + 150.0);
+            let right_child = add_tree_level(graph, current_depth - 1, &node_id, false, counter, x_offset + spacing, y_pos + 150.0);
 
             // Connect children
-            graph.add_connection(Connection {
-                source_node: left_child,
-                source_pin: if current_depth == 1 { "value" } else { "result" }.to_string(),
-                target_node: node_id.clone(),
-                target_pin: "a".to_string(),
-                connection_type: ConnectionType::Data,
-            });
-
-            graph.add_connection(Connection {
-                source_node: right_child,
-                source_pin: if current_depth == 1 { "value" } else { "result" }.to_string(),
-                target_node: node_id.clone(),
-                target_pin: "b".to_string(),
-                connection_type: ConnectionType::Data,
-            });
+            graph.add_connection(Connection::new(
+                left_child,
+                if current_depth == 1 { "value" } else { "result" }.to_string(),
+                node_id.clone(),
+                "a".to_string(),
+                ConnectionType::Data,
+            ));
+
+            graph.add_connection(Connection::new(
+                right_child,
+                if current_depth == 1 { "value" } else { "result" }.to_string(),
+                node_id.clone(),
+                "b".to_string(),
+                ConnectionType::Data,
+            ));
         }
 
         node_id
     }
 
-    add_tree_level(&mut graph, depth, depth, "root", false, &mut node_counter, 1000.0, 0.0);
+    add_tree_level(&mut graph, depth, "root", false, &mut node_counter, 1000.0, 0.0);
     graph
 }
 
@@ -345,13 +321,13 @@ fn create_control_flow_graph(num_branches: usize) -> GraphDescription {
         let source_node = if i == 0 { "start" } else { &format!("print_false_{}", i - 1) };
         let source_pin = if i == 0 { "exec" } else { "then" };
 
-        graph.add_connection(Connection {
-            source_node: source_node.to_string(),
-            source_pin: source_pin.to_string(),
-            target_node: branch_id.clone(),
-            target_pin: "exec".to_string(),
-            connection_type: ConnectionType::Execution,
-        });
+        graph.add_connection(Connection::new(
+            source_node.to_string(),
+            source_pin.to_string(),
+            branch_id.clone(),
+            "exec".to_string(),
+            ConnectionType::Execution,
+        ));
 
         // Create print nodes for true and false paths
         let print_true_id = format!("print_true_{}", i);
@@ -371,21 +347,21 @@ fn create_control_flow_graph(num_branches: usize) -> GraphDescription {
         graph.add_node(print_false);
 
         // Connect branches to prints
-        graph.add_connection(Connection {
-            source_node: branch_id.clone(),
-            source_pin: "true".to_string(),
-            target_node: print_true_id,
-            target_pin: "exec".to_string(),
-            connection_type: ConnectionType::Execution,
-        });
+        graph.add_connection(Connection::new(
+            branch_id.clone(),
+            "true".to_string(),
+            print_true_id,
+            "exec".to_string(),
+            ConnectionType::Execution,
+        ));
 
-        graph.add_connection(Connection {
-            source_node: branch_id,
-            source_pin: "false".to_string(),
-            target_node: print_false_id,
-            target_pin: "exec".to_string(),
-            connection_type: ConnectionType::Execution,
-        });
+        graph.add_connection(Connection::new(
+            branch_id,
+            "false".to_string(),
+            print_false_id,
+            "exec".to_string(),
+            ConnectionType::Execution,
+        ));
     }
 
     graph
@@ -414,24 +390,24 @@ fn create_monster_graph(scale: usize) -> GraphDescription {
 
             // Connect to left neighbor
             if col > 0 {
-                graph.add_connection(Connection {
-                    source_node: format!("grid_{}_{}", row, col - 1),
-                    source_pin: "result".to_string(),
-                    target_node: node_id.clone(),
-                    target_pin: "a".to_string(),
-                    connection_type: ConnectionType::Data,
-                });
+                graph.add_connection(Connection::new(
+                    format!("grid_{}_{}", row, col - 1),
+                    "result".to_string(),
+                    node_id.clone(),
+                    "a".to_string(),
+                    ConnectionType::Data,
+                ));
             }
 
             // Connect to top neighbor
             if row > 0 {
-                graph.add_connection(Connection {
-                    source_node: format!("grid_{}_{}", row - 1, col),
-                    source_pin: "result".to_string(),
-                    target_node: node_id.clone(),
-                    target_pin: "b".to_string(),
-                    connection_type: ConnectionType::Data,
-                });
+                graph.add_connection(Connection::new(
+                    format!("grid_{}_{}", row - 1, col),
+                    "result".to_string(),
+                    node_id.clone(),
+                    "b".to_string(),
+                    ConnectionType::Data,
+                ));
             }
         }
     }
@@ -447,7 +423,7 @@ fn bench_linear_chain(c: &mut Criterion) {
     init_benchmark_environment(); // Pre-warm thread pool
     
     let mut group = c.benchmark_group("linear_chain_analysis");
-    let provider = BenchmarkMetadataProvider::new();
+    let provider = benchmark_provider();
 
     for size in [10, 50, 100, 500, 1000].iter() {
         group.throughput(Throughput::Elements(*size as u64));
@@ -475,7 +451,7 @@ fn bench_wide_graph(c: &mut Criterion) {
     init_benchmark_environment(); // Pre-warm thread pool
     
     let mut group = c.benchmark_group("wide_graph_analysis");
-    let provider = BenchmarkMetadataProvider::new();
+    let provider = benchmark_provider();
 
     for width in [10, 25, 50, 100, 200].iter() {
         group.throughput(Throughput::Elements(*width as u64));
@@ -501,7 +477,7 @@ fn bench_wide_graph(c: &mut Criterion) {
 
 fn bench_dependency_tree(c: &mut Criterion) {
     let mut group = c.benchmark_group("dependency_tree_analysis");
-    let provider = BenchmarkMetadataProvider::new();
+    let provider = benchmark_provider();
 
     for depth in [3, 5, 7, 9, 10].iter() {
         let num_nodes = 2_usize.pow(*depth as u32 + 1) - 1;
@@ -540,7 +516,7 @@ fn bench_monster_graph(c: &mut Criterion) {
     
     let mut group = c.benchmark_group("monster_graph_analysis");
     group.sample_size(10); // Reduce sample size for large graphs
-    let provider = BenchmarkMetadataProvider::new();
+    let provider = benchmark_provider();
 
     for scale in [10, 20, 30, 40, 50].iter() {
         let num_nodes = scale * scale;
@@ -592,7 +568,7 @@ fn bench_graph_serialization(c: &mut Criterion) {
 fn bench_full_pipeline(c: &mut Criterion) {
     let mut group = c.benchmark_group("full_pipeline");
     group.sample_size(10);
-    let provider = BenchmarkMetadataProvider::new();
+    let provider = benchmark_provider();
 
     for size in [50, 100, 250, 500].iter() {
         group.throughput(Throughput::Elements(*size as u64));
@@ -614,7 +590,7 @@ fn bench_parallel_scaling(c: &mut Criterion) {
     
     let mut group = c.benchmark_group("parallel_scaling");
     group.sample_size(20);
-    let provider = BenchmarkMetadataProvider::new();
+    let provider = benchmark_provider();
 
     // Test where parallel really shines: massive grids
     for scale in [20, 40, 60, 80, 100].iter() {
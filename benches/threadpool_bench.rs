@@ -2,50 +2,31 @@ use criterion::{black_box, criterion_group, criterion_main, Criterion, Benchmark
 use graphy::{
     GraphDescription, NodeInstance, Connection, Pin, PinInstance, PinType,
     DataType, NodeTypes, PropertyValue, ConnectionType, Position,
-    DataResolver, NodeMetadata, ParamInfo, NodeMetadataProvider,
+    DataResolver, NodeMetadata, ParamInfo, NodeRegistry,
 };
-use std::collections::HashMap;
 
 // Minimal metadata provider
-struct BenchProvider {
-    metadata: HashMap<String, NodeMetadata>,
-}
-
-impl BenchProvider {
-    fn new() -> Self {
-        let mut metadata = HashMap::new();
-        metadata.insert(
-            "math.add".to_string(),
-            NodeMetadata::new("add", NodeTypes::pure, "Math")
-                .with_params(vec![
-                    ParamInfo::new("a", "f64"),
-                    ParamInfo::new("b", "f64"),
-                ])
-                .with_return_type("f64"),
-        );
-        metadata.insert(
-            "math.multiply".to_string(),
-            NodeMetadata::new("multiply", NodeTypes::pure, "Math")
-                .with_params(vec![
-                    ParamInfo::new("a", "f64"),
-                    ParamInfo::new("b", "f64"),
-                ])
-                .with_return_type("f64"),
-        );
-        Self { metadata }
-    }
-}
-
-impl NodeMetadataProvider for BenchProvider {
-    fn get_node_metadata(&self, node_type: &str) -> Option<&NodeMetadata> {
-        self.metadata.get(node_type)
-    }
-    fn get_all_nodes(&self) -> Vec<&NodeMetadata> {
-        self.metadata.values().collect()
-    }
-    fn get_nodes_by_category(&self, category: &str) -> Vec<&NodeMetadata> {
-        self.metadata.values().filter(|m| m.category == category).collect()
-    }
+fn bench_provider() -> NodeRegistry {
+    let mut registry = NodeRegistry::new();
+    registry.register_as(
+        "math.add",
+        NodeMetadata::new("add", NodeTypes::pure, "Math")
+            .with_params(vec![
+                ParamInfo::new("a", "f64"),
+                ParamInfo::new("b", "f64"),
+            ])
+            .with_return_type("f64"),
+    );
+    registry.register_as(
+        "math.multiply",
+        NodeMetadata::new("multiply", NodeTypes::pure, "Math")
+            .with_params(vec![
+                ParamInfo::new("a", "f64"),
+                ParamInfo::new("b", "f64"),
+            ])
+            .with_return_type("f64"),
+    );
+    registry
 }
 
 fn create_grid(size: usize) -> GraphDescription {
@@ -76,25 +57,25 @@ fn create_grid(size: usize) -> GraphDescription {
     // Connections
     for row in 0..size {
         for col in 1..size {
-            graph.add_connection(Connection {
-                source_node: format!("n_{}_{}", row, col - 1),
-                source_pin: "result".to_string(),
-                target_node: format!("n_{}_{}", row, col),
-                target_pin: "a".to_string(),
-                connection_type: ConnectionType::Data,
-            });
+            graph.add_connection(Connection::new(
+                format!("n_{}_{}", row, col - 1),
+                "result".to_string(),
+                format!("n_{}_{}", row, col),
+                "a".to_string(),
+                ConnectionType::Data,
+            ));
         }
     }
     
     for row in 1..size {
         for col in 0..size {
-            graph.add_connection(Connection {
-                source_node: format!("n_{}_{}", row - 1, col),
-                source_pin: "result".to_string(),
-                target_node: format!("n_{}_{}", row, col),
-                target_pin: "b".to_string(),
-                connection_type: ConnectionType::Data,
-            });
+            graph.add_connection(Connection::new(
+                format!("n_{}_{}", row - 1, col),
+                "result".to_string(),
+                format!("n_{}_{}", row, col),
+                "b".to_string(),
+                ConnectionType::Data,
+            ));
         }
     }
     
@@ -103,7 +84,7 @@ fn create_grid(size: usize) -> GraphDescription {
 
 fn bench_cold_vs_warm(c: &mut Criterion) {
     let mut group = c.benchmark_group("threadpool_warmup");
-    let provider = BenchProvider::new();
+    let provider = bench_provider();
     let graph = create_grid(50);
     
     // Benchmark WITHOUT pre-warming (cold start)
@@ -142,7 +123,7 @@ fn bench_scaling_with_threadpool(c: &mut Criterion) {
     let config = ThreadPoolConfig::new();
     let _ = init_thread_pool(config);
     
-    let provider = BenchProvider::new();
+    let provider = bench_provider();
     
     for size in [30, 50, 70, 100].iter() {
         let graph = create_grid(*size);
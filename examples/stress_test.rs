@@ -5,67 +5,44 @@
 use graphy::{
     GraphDescription, NodeInstance, Connection, Pin, PinInstance, PinType,
     DataType, NodeTypes, PropertyValue, ConnectionType, Position,
-    DataResolver, ExecutionRouting, NodeMetadata, ParamInfo, NodeMetadataProvider,
+    DataResolver, ExecutionRouting, NodeMetadata, ParamInfo, NodeRegistry,
 };
-use std::collections::HashMap;
 use std::time::Instant;
 
 // Simple metadata provider for stress testing
-struct StressTestProvider {
-    metadata: HashMap<String, NodeMetadata>,
-}
-
-impl StressTestProvider {
-    fn new() -> Self {
-        let mut metadata = HashMap::new();
-
-        metadata.insert(
-            "math.add".to_string(),
-            NodeMetadata::new("add", NodeTypes::pure, "Math")
-                .with_params(vec![
-                    ParamInfo::new("a", "f64"),
-                    ParamInfo::new("b", "f64"),
-                ])
-                .with_return_type("f64")
-                .with_source("a + b"),
-        );
-
-        metadata.insert(
-            "math.multiply".to_string(),
-            NodeMetadata::new("multiply", NodeTypes::pure, "Math")
-                .with_params(vec![
-                    ParamInfo::new("a", "f64"),
-                    ParamInfo::new("b", "f64"),
-                ])
-                .with_return_type("f64")
-                .with_source("a * b"),
-        );
-
-        metadata.insert(
-            "math.constant".to_string(),
-            NodeMetadata::new("constant", NodeTypes::pure, "Math")
-                .with_return_type("f64")
-                .with_source("value"),
-        );
-
-        Self { metadata }
-    }
-}
-
-impl NodeMetadataProvider for StressTestProvider {
-    fn get_node_metadata(&self, node_type: &str) -> Option<&NodeMetadata> {
-        self.metadata.get(node_type)
-    }
-
-    fn get_all_nodes(&self) -> Vec<&NodeMetadata> {
-        self.metadata.values().collect()
-    }
-
-    fn get_nodes_by_category(&self, category: &str) -> Vec<&NodeMetadata> {
-        self.metadata.values()
-            .filter(|m| m.category == category)
-            .collect()
-    }
+fn stress_test_provider() -> NodeRegistry {
+    let mut registry = NodeRegistry::new();
+
+    registry.register_as(
+        "math.add",
+        NodeMetadata::new("add", NodeTypes::pure, "Math")
+            .with_params(vec![
+                ParamInfo::new("a", "f64"),
+                ParamInfo::new("b", "f64"),
+            ])
+            .with_return_type("f64")
+            .with_source("a + b"),
+    );
+
+    registry.register_as(
+        "math.multiply",
+        NodeMetadata::new("multiply", NodeTypes::pure, "Math")
+            .with_params(vec![
+                ParamInfo::new("a", "f64"),
+                ParamInfo::new("b", "f64"),
+            ])
+            .with_return_type("f64")
+            .with_source("a * b"),
+    );
+
+    registry.register_as(
+        "math.constant",
+        NodeMetadata::new("constant", NodeTypes::pure, "Math")
+            .with_return_type("f64")
+            .with_source("value"),
+    );
+
+    registry
 }
 
 /// Create a massive interconnected grid of nodes
@@ -104,13 +81,13 @@ fn create_stress_grid(width: usize, height: usize) -> GraphDescription {
     // Connect horizontally
     for row in 0..height {
         for col in 1..width {
-            graph.add_connection(Connection {
-                source_node: format!("n_{}_{}", row, col - 1),
-                source_pin: "result".to_string(),
-                target_node: format!("n_{}_{}", row, col),
-                target_pin: "a".to_string(),
-                connection_type: ConnectionType::Data,
-            });
+            graph.add_connection(Connection::new(
+                format!("n_{}_{}", row, col - 1),
+                "result".to_string(),
+                format!("n_{}_{}", row, col),
+                "a".to_string(),
+                ConnectionType::Data,
+            ));
             connection_count += 1;
         }
     }
@@ -118,13 +95,13 @@ fn create_stress_grid(width: usize, height: usize) -> GraphDescription {
     // Connect vertically
     for row in 1..height {
         for col in 0..width {
-            graph.add_connection(Connection {
-                source_node: format!("n_{}_{}", row - 1, col),
-                source_pin: "result".to_string(),
-                target_node: format!("n_{}_{}", row, col),
-                target_pin: "b".to_string(),
-                connection_type: ConnectionType::Data,
-            });
+            graph.add_connection(Connection::new(
+                format!("n_{}_{}", row - 1, col),
+                "result".to_string(),
+                format!("n_{}_{}", row, col),
+                "b".to_string(),
+                ConnectionType::Data,
+            ));
             connection_count += 1;
         }
     }
@@ -133,13 +110,13 @@ fn create_stress_grid(width: usize, height: usize) -> GraphDescription {
     for row in 1..height {
         for col in 1..width {
             if (row + col) % 3 == 0 {
-                graph.add_connection(Connection {
-                    source_node: format!("n_{}_{}", row - 1, col - 1),
-                    source_pin: "result".to_string(),
-                    target_node: format!("n_{}_{}", row, col),
-                    target_pin: if row % 2 == 0 { "a" } else { "b" }.to_string(),
-                    connection_type: ConnectionType::Data,
-                });
+                graph.add_connection(Connection::new(
+                    format!("n_{}_{}", row - 1, col - 1),
+                    "result".to_string(),
+                    format!("n_{}_{}", row, col),
+                    if row % 2 == 0 { "a" } else { "b" }.to_string(),
+                    ConnectionType::Data,
+                ));
                 connection_count += 1;
             }
         }
@@ -149,7 +126,7 @@ fn create_stress_grid(width: usize, height: usize) -> GraphDescription {
     graph
 }
 
-fn run_stress_test(name: &str, graph: &GraphDescription, provider: &StressTestProvider) {
+fn run_stress_test(name: &str, graph: &GraphDescription, provider: &NodeRegistry) {
     println!("\n========== {} ==========", name);
     println!("  Nodes: {}", graph.nodes.len());
     println!("  Connections: {}", graph.connections.len());
@@ -167,10 +144,12 @@ fn run_stress_test(name: &str, graph: &GraphDescription, provider: &StressTestPr
 
     // Test data flow analysis - Sequential
     let start = Instant::now();
-    match DataResolver::build(&graph, provider) {
-        Ok(_resolver) => {
+    let mut resolver_memory = None;
+    match DataResolver::build(graph, provider) {
+        Ok(resolver) => {
             let analysis_time = start.elapsed();
             println!("  ✅ Data Flow Analysis (Sequential): {:?}", analysis_time);
+            resolver_memory = Some(resolver.memory_stats().total_bytes());
         }
         Err(e) => {
             let analysis_time = start.elapsed();
@@ -180,7 +159,7 @@ fn run_stress_test(name: &str, graph: &GraphDescription, provider: &StressTestPr
 
     // Test data flow analysis - Parallel
     let start = Instant::now();
-    match DataResolver::build_parallel(&graph, provider) {
+    match DataResolver::build_parallel(graph, provider) {
         Ok(_resolver) => {
             let analysis_time = start.elapsed();
             println!("  ⚡ Data Flow Analysis (Parallel): {:?}", analysis_time);
@@ -193,13 +172,15 @@ fn run_stress_test(name: &str, graph: &GraphDescription, provider: &StressTestPr
 
     // Test execution routing
     let start = Instant::now();
-    let _routing = ExecutionRouting::build_from_graph(&graph);
+    let _routing = ExecutionRouting::build_from_graph(graph);
     let routing_time = start.elapsed();
     println!("  ✅ Execution Routing: {:?}", routing_time);
 
-    // Memory estimation
-    let estimated_memory = (graph.nodes.len() * 200 + graph.connections.len() * 100) / 1024;
-    println!("  💾 Est. Memory: ~{} KB", estimated_memory);
+    // Memory usage
+    println!("  💾 Graph Memory: ~{} KB", graph.approx_memory_bytes() / 1024);
+    if let Some(bytes) = resolver_memory {
+        println!("  💾 Resolver Memory: ~{} KB", bytes / 1024);
+    }
 }
 
 fn main() {
@@ -220,7 +201,7 @@ fn main() {
     init_thread_pool(config).expect("Failed to initialize thread pool");
     println!("✅ Thread pool ready with {} threads\n", num_cpus);
 
-    let provider = StressTestProvider::new();
+    let provider = stress_test_provider();
 
     // Test 1: Small warm-up
     let graph_10x10 = create_stress_grid(10, 10);
@@ -0,0 +1,96 @@
+/*
+ * Graphy C API
+ *
+ * Embeds the Graphy graph compiler in C and C++ applications. Link against
+ * the graphy_ffi library built by `cargo build -p graphy-ffi --release`.
+ *
+ * Strings passed in are NUL-terminated UTF-8 and only borrowed for the call.
+ * Strings returned are owned by the context (or, for graphy_last_error, the
+ * calling thread) and must not be freed.
+ *
+ * See graphy-ffi/src/lib.rs for the full documentation of each function.
+ */
+
+#ifndef GRAPHY_H
+#define GRAPHY_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum GraphyStatus {
+    GRAPHY_OK = 0,
+    GRAPHY_INVALID_ARGUMENT = 1,
+    GRAPHY_FAILED = 2,
+    GRAPHY_PANICKED = 3
+} GraphyStatus;
+
+typedef enum GraphyNodeType {
+    GRAPHY_NODE_PURE = 0,
+    GRAPHY_NODE_FUNCTION = 1,
+    GRAPHY_NODE_CONTROL_FLOW = 2,
+    GRAPHY_NODE_EVENT = 3
+} GraphyNodeType;
+
+typedef enum GraphySourceKind {
+    GRAPHY_SOURCE_RUST = 0,
+    GRAPHY_SOURCE_TEMPLATE = 1
+} GraphySourceKind;
+
+typedef enum GraphySeverity {
+    GRAPHY_SEVERITY_ERROR = 0,
+    GRAPHY_SEVERITY_WARNING = 1
+} GraphySeverity;
+
+typedef struct GraphyContext GraphyContext;
+
+typedef struct GraphyParamDesc {
+    const char *name;
+    const char *type_name;
+} GraphyParamDesc;
+
+typedef struct GraphyNodeDesc {
+    const char *name;
+    GraphyNodeType node_type;
+    const char *category;
+    const GraphyParamDesc *params;
+    size_t param_count;
+    const char *return_type;          /* nullable */
+    const char *const *exec_outputs;
+    size_t exec_output_count;
+    const char *source;               /* nullable */
+    GraphySourceKind source_kind;
+} GraphyNodeDesc;
+
+/* Contexts */
+GraphyContext *graphy_context_new(void);
+void graphy_context_free(GraphyContext *ctx);
+const char *graphy_last_error(void);
+
+/* Metadata */
+GraphyStatus graphy_register_node(GraphyContext *ctx, const GraphyNodeDesc *desc);
+GraphyStatus graphy_register_nodes_json(GraphyContext *ctx, const char *json);
+size_t graphy_node_count(const GraphyContext *ctx);
+
+/* Graphs */
+GraphyStatus graphy_load_graph_json(GraphyContext *ctx, const char *json);
+
+/* Analysis */
+GraphyStatus graphy_analyze(GraphyContext *ctx);
+size_t graphy_diagnostic_count(const GraphyContext *ctx);
+GraphySeverity graphy_diagnostic_severity(const GraphyContext *ctx, size_t index);
+const char *graphy_diagnostic_message(const GraphyContext *ctx, size_t index);
+const char *graphy_diagnostic_node(const GraphyContext *ctx, size_t index);
+
+/* Code generation */
+GraphyStatus graphy_generate_c(GraphyContext *ctx, const char *name);
+const char *graphy_generated_source(const GraphyContext *ctx);
+const char *graphy_generated_header(const GraphyContext *ctx);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* GRAPHY_H */
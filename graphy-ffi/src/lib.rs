@@ -0,0 +1,688 @@
+//! # Graphy C Bindings
+//!
+//! A C API for embedding Graphy in native applications, such as a C++
+//! editor that would otherwise run the compiler as a separate process. The
+//! declarations are in `include/graphy.h`.
+//!
+//! All state lives in an opaque [`GraphyContext`]: node metadata, the loaded
+//! graph, the diagnostics of the last analysis, and the last generated code.
+//!
+//! 1. create a context with [`graphy_context_new`]
+//! 2. describe the available nodes with [`graphy_register_node`] (or
+//!    [`graphy_register_nodes_json`])
+//! 3. load a graph with [`graphy_load_graph_json`]
+//! 4. check it with [`graphy_analyze`] and read the diagnostics
+//! 5. generate C with [`graphy_generate_c`] and fetch it with
+//!    [`graphy_generated_source`] and [`graphy_generated_header`]
+//! 6. free the context with [`graphy_context_free`]
+//!
+//! Functions return a [`GraphyStatus`]. On failure, [`graphy_last_error`]
+//! describes what went wrong on the calling thread. Strings passed in are
+//! NUL-terminated UTF-8 and only borrowed for the call; strings returned are
+//! owned by the context and stay valid until the next call that changes the
+//! same data, or until the context is freed.
+//!
+//! # Example
+//!
+//! ```c
+//! GraphyContext *ctx = graphy_context_new();
+//!
+//! GraphyParamDesc params[] = {{"message", "String"}};
+//! GraphyNodeDesc print = {0};
+//! print.name = "print";
+//! print.node_type = GRAPHY_NODE_FUNCTION;
+//! print.category = "IO";
+//! print.params = params;
+//! print.param_count = 1;
+//! graphy_register_node(ctx, &print);
+//!
+//! if (graphy_load_graph_json(ctx, json) != GRAPHY_OK) {
+//!     fprintf(stderr, "%s\n", graphy_last_error());
+//! } else if (graphy_analyze(ctx) != GRAPHY_OK) {
+//!     for (size_t i = 0; i < graphy_diagnostic_count(ctx); i++) {
+//!         fprintf(stderr, "%s\n", graphy_diagnostic_message(ctx, i));
+//!     }
+//! } else if (graphy_generate_c(ctx, "level") == GRAPHY_OK) {
+//!     puts(graphy_generated_source(ctx));
+//! }
+//!
+//! graphy_context_free(ctx);
+//! ```
+
+use graphy::analysis::Reachability;
+use graphy::generation::targets::{compile_c, CGenerator};
+use graphy::io::load_with_migration;
+use graphy::{
+    DataResolver, GraphDescription, GraphyError, NodeMetadata, NodeRegistry, NodeTypes, ParamInfo, SourceKind,
+};
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+thread_local! {
+    /// Message of the last failed call on this thread
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Result of a call.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphyStatus {
+    /// The call succeeded
+    Ok = 0,
+
+    /// A required pointer was null or a string wasn't UTF-8
+    InvalidArgument = 1,
+
+    /// Parsing, analysis, or code generation failed
+    Failed = 2,
+
+    /// Graphy panicked; the context may be inconsistent and should be freed
+    Panicked = 3,
+}
+
+/// Kind of a node, mirroring [`NodeTypes`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphyNodeType {
+    /// Pure function node
+    Pure = 0,
+
+    /// Function node with side effects
+    Function = 1,
+
+    /// Control flow node
+    ControlFlow = 2,
+
+    /// Event node
+    Event = 3,
+}
+
+impl From<GraphyNodeType> for NodeTypes {
+    fn from(node_type: GraphyNodeType) -> Self {
+        match node_type {
+            GraphyNodeType::Pure => NodeTypes::pure,
+            GraphyNodeType::Function => NodeTypes::fn_,
+            GraphyNodeType::ControlFlow => NodeTypes::control_flow,
+            GraphyNodeType::Event => NodeTypes::event,
+        }
+    }
+}
+
+/// How the source of a node is turned into code, mirroring [`SourceKind`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphySourceKind {
+    /// Rust source; the C backend calls a function named after the node
+    Rust = 0,
+
+    /// A text template inlined by the C backend
+    Template = 1,
+}
+
+/// Severity of a diagnostic.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphySeverity {
+    /// The graph can't be compiled
+    Error = 0,
+
+    /// The graph compiles, but probably not as intended
+    Warning = 1,
+}
+
+/// A node parameter.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct GraphyParamDesc {
+    /// Parameter name
+    pub name: *const c_char,
+
+    /// Parameter type, e.g. `"f64"`
+    pub type_name: *const c_char,
+}
+
+/// Metadata of a node type.
+///
+/// Only `name` and `category` are required; null arrays must have a zero
+/// count. The enum fields must hold one of their listed values.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct GraphyNodeDesc {
+    /// Node type name
+    pub name: *const c_char,
+
+    /// Kind of node
+    pub node_type: GraphyNodeType,
+
+    /// Category for organization in the UI
+    pub category: *const c_char,
+
+    /// Parameters, in order
+    pub params: *const GraphyParamDesc,
+
+    /// Number of parameters
+    pub param_count: usize,
+
+    /// Return type, or null for none
+    pub return_type: *const c_char,
+
+    /// Execution output pin names
+    pub exec_outputs: *const *const c_char,
+
+    /// Number of execution outputs
+    pub exec_output_count: usize,
+
+    /// Source code or template, or null for none
+    pub source: *const c_char,
+
+    /// How `source` is turned into code
+    pub source_kind: GraphySourceKind,
+}
+
+/// A problem found by [`graphy_analyze`] or [`graphy_generate_c`].
+#[derive(Debug)]
+struct Diagnostic {
+    severity: GraphySeverity,
+    message: CString,
+    node: Option<CString>,
+}
+
+impl Diagnostic {
+    fn new(severity: GraphySeverity, message: impl Into<String>, node: Option<&str>) -> Self {
+        Self {
+            severity,
+            message: to_c_string(message.into()),
+            node: node.map(|node| to_c_string(node.to_string())),
+        }
+    }
+
+    /// An error diagnostic, attributed to the node the error names
+    fn from_error(error: &GraphyError) -> Self {
+        Self::new(GraphySeverity::Error, error.to_string(), error.node())
+    }
+}
+
+/// Opaque compilation state owned by the host.
+#[derive(Debug, Default)]
+pub struct GraphyContext {
+    /// Metadata of the registered node types
+    registry: NodeRegistry,
+
+    /// The loaded graph
+    graph: Option<GraphDescription>,
+
+    /// Diagnostics of the last analysis or generation
+    diagnostics: Vec<Diagnostic>,
+
+    /// Header generated by the last successful [`graphy_generate_c`]
+    header: Option<CString>,
+
+    /// Source generated by the last successful [`graphy_generate_c`]
+    source: Option<CString>,
+}
+
+impl GraphyContext {
+    /// The loaded graph
+    fn graph(&self) -> Result<&GraphDescription, GraphyError> {
+        self.graph
+            .as_ref()
+            .ok_or_else(|| GraphyError::Custom("No graph is loaded".to_string()))
+    }
+
+    /// Replaces the diagnostics, returning whether any of them is an error
+    fn set_diagnostics(&mut self, diagnostics: Vec<Diagnostic>) -> bool {
+        self.diagnostics = diagnostics;
+        self.diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity == GraphySeverity::Error)
+    }
+
+    /// Analyzes the loaded graph
+    fn analyze(&self) -> Result<Vec<Diagnostic>, GraphyError> {
+        let graph = self.graph()?;
+        let versions: Vec<Diagnostic> = graph
+            .check_node_versions(&self.registry)
+            .into_iter()
+            .map(|issue| Diagnostic::from_error(&issue.into()))
+            .collect();
+        if !versions.is_empty() {
+            return Ok(versions);
+        }
+        let resolver = match DataResolver::build(graph, &self.registry) {
+            Ok(resolver) => resolver,
+            Err(error) => return Ok(vec![Diagnostic::from_error(&error)]),
+        };
+
+        // Editors open graphs using node types that aren't registered yet
+        let mut diagnostics: Vec<Diagnostic> = graph
+            .unknown_nodes(&self.registry)
+            .into_iter()
+            .map(|node| {
+                let error = GraphyError::UnknownNodeType {
+                    node: node.id.clone(),
+                    node_type: node.node_type.clone(),
+                };
+                Diagnostic::new(GraphySeverity::Warning, error.to_string(), Some(&node.id))
+            })
+            .collect();
+        diagnostics.extend(
+            resolver
+                .unconnected_required_inputs(graph, &self.registry)
+                .into_iter()
+                .map(|(node, pin)| Diagnostic::from_error(&GraphyError::UnconnectedInput { node, pin })),
+        );
+        diagnostics.extend(
+            graph
+                .check_pin_multiplicity(&self.registry)
+                .into_iter()
+                .map(|issue| Diagnostic::from_error(&issue.into())),
+        );
+        let reachability = Reachability::compute(graph, &self.registry);
+        diagnostics.extend(reachability.unreachable_nodes(graph).into_iter().map(|node| {
+            Diagnostic::new(
+                GraphySeverity::Warning,
+                format!("Node '{}' is never executed", node),
+                Some(&node),
+            )
+        }));
+        Ok(diagnostics)
+    }
+}
+
+/// Error for an invalid argument
+#[derive(Debug)]
+struct InvalidArgument(String);
+
+/// Outcome of a call before it's turned into a status
+enum CallError {
+    InvalidArgument(String),
+    Failed(String),
+}
+
+impl From<InvalidArgument> for CallError {
+    fn from(error: InvalidArgument) -> Self {
+        CallError::InvalidArgument(error.0)
+    }
+}
+
+impl From<GraphyError> for CallError {
+    fn from(error: GraphyError) -> Self {
+        CallError::Failed(error.to_string())
+    }
+}
+
+/// Converts a string to a C string, dropping interior NULs
+fn to_c_string(value: String) -> CString {
+    CString::new(value).unwrap_or_else(|error| {
+        let mut bytes = error.into_vec();
+        bytes.retain(|&byte| byte != 0);
+        CString::new(bytes).expect("NUL bytes were removed")
+    })
+}
+
+fn set_last_error(message: String) {
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(to_c_string(message)));
+}
+
+/// Runs a call, recording its error and catching panics
+fn call(body: impl FnOnce() -> Result<(), CallError>) -> GraphyStatus {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+    match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => GraphyStatus::Ok,
+        Ok(Err(CallError::InvalidArgument(message))) => {
+            set_last_error(message);
+            GraphyStatus::InvalidArgument
+        }
+        Ok(Err(CallError::Failed(message))) => {
+            set_last_error(message);
+            GraphyStatus::Failed
+        }
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            set_last_error(format!("Graphy panicked: {}", message));
+            GraphyStatus::Panicked
+        }
+    }
+}
+
+/// Borrows the context behind a pointer
+///
+/// # Safety
+///
+/// `ctx` must be null or a live pointer from [`graphy_context_new`].
+unsafe fn context<'a>(ctx: *mut GraphyContext) -> Result<&'a mut GraphyContext, InvalidArgument> {
+    ctx.as_mut()
+        .ok_or_else(|| InvalidArgument("The context is null".to_string()))
+}
+
+/// Reads a required string argument
+///
+/// # Safety
+///
+/// `value` must be null or a NUL-terminated string.
+unsafe fn required_str<'a>(value: *const c_char, what: &str) -> Result<&'a str, InvalidArgument> {
+    optional_str(value, what)?.ok_or_else(|| InvalidArgument(format!("The {} is null", what)))
+}
+
+/// Reads an optional string argument
+///
+/// # Safety
+///
+/// `value` must be null or a NUL-terminated string.
+unsafe fn optional_str<'a>(value: *const c_char, what: &str) -> Result<Option<&'a str>, InvalidArgument> {
+    if value.is_null() {
+        return Ok(None);
+    }
+    CStr::from_ptr(value)
+        .to_str()
+        .map(Some)
+        .map_err(|_| InvalidArgument(format!("The {} is not valid UTF-8", what)))
+}
+
+/// Borrows an array argument
+///
+/// # Safety
+///
+/// `items` must be null with a zero `count`, or point to `count` items.
+unsafe fn array<'a, T>(items: *const T, count: usize, what: &str) -> Result<&'a [T], InvalidArgument> {
+    match (items.is_null(), count) {
+        (_, 0) => Ok(&[]),
+        (true, _) => Err(InvalidArgument(format!("The {} array is null", what))),
+        (false, _) => Ok(std::slice::from_raw_parts(items, count)),
+    }
+}
+
+/// Converts a node description to metadata
+///
+/// # Safety
+///
+/// The pointers in `desc` must be valid as documented on [`GraphyNodeDesc`].
+unsafe fn node_metadata(desc: &GraphyNodeDesc) -> Result<NodeMetadata, InvalidArgument> {
+    let source_kind = match desc.source_kind {
+        GraphySourceKind::Rust => SourceKind::Rust,
+        GraphySourceKind::Template => SourceKind::Template,
+    };
+
+    let mut params = Vec::with_capacity(desc.param_count);
+    for param in array(desc.params, desc.param_count, "params")? {
+        params.push(ParamInfo::new(
+            required_str(param.name, "param name")?,
+            required_str(param.type_name, "param type")?,
+        ));
+    }
+    let mut exec_outputs = Vec::with_capacity(desc.exec_output_count);
+    for &output in array(desc.exec_outputs, desc.exec_output_count, "exec outputs")? {
+        exec_outputs.push(required_str(output, "exec output")?.to_string());
+    }
+
+    let mut metadata = NodeMetadata::new(
+        required_str(desc.name, "node name")?,
+        desc.node_type.into(),
+        required_str(desc.category, "category")?,
+    )
+    .with_params(params)
+    .with_exec_outputs(exec_outputs)
+    .with_source(optional_str(desc.source, "source")?.unwrap_or_default())
+    .with_source_kind(source_kind);
+    if let Some(return_type) = optional_str(desc.return_type, "return type")? {
+        metadata = metadata.with_return_type(return_type);
+    }
+    Ok(metadata)
+}
+
+/// Returns a string owned by the context, or null
+fn c_str_ptr(value: Option<&CString>) -> *const c_char {
+    value.map_or(ptr::null(), |value| value.as_ptr())
+}
+
+// ===========================================================================
+// Contexts
+// ===========================================================================
+
+/// Creates an empty context.
+///
+/// Free it with [`graphy_context_free`].
+#[no_mangle]
+pub extern "C" fn graphy_context_new() -> *mut GraphyContext {
+    Box::into_raw(Box::default())
+}
+
+/// Frees a context and every string it returned.
+///
+/// # Safety
+///
+/// `ctx` must be null or a pointer from [`graphy_context_new`] that wasn't
+/// freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn graphy_context_free(ctx: *mut GraphyContext) {
+    if !ctx.is_null() {
+        drop(Box::from_raw(ctx));
+    }
+}
+
+/// Returns the message of the last failed call on this thread, or null.
+///
+/// The message stays valid until the next call on this thread.
+#[no_mangle]
+pub extern "C" fn graphy_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| c_str_ptr(last.borrow().as_ref()))
+}
+
+// ===========================================================================
+// Metadata
+// ===========================================================================
+
+/// Registers a node type, replacing any with the same name.
+///
+/// # Safety
+///
+/// `ctx` must be a live context and `desc` must point to a valid
+/// [`GraphyNodeDesc`].
+#[no_mangle]
+pub unsafe extern "C" fn graphy_register_node(ctx: *mut GraphyContext, desc: *const GraphyNodeDesc) -> GraphyStatus {
+    call(|| {
+        let ctx = context(ctx)?;
+        let desc = desc
+            .as_ref()
+            .ok_or_else(|| InvalidArgument("The node description is null".to_string()))?;
+        ctx.registry.register(node_metadata(desc)?);
+        Ok(())
+    })
+}
+
+/// Registers every node type of a registry serialized as JSON.
+///
+/// Accepts the output of [`NodeRegistry::to_json`], replacing node types
+/// with the same names.
+///
+/// # Safety
+///
+/// `ctx` must be a live context and `json` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn graphy_register_nodes_json(ctx: *mut GraphyContext, json: *const c_char) -> GraphyStatus {
+    call(|| {
+        let ctx = context(ctx)?;
+        let registry = NodeRegistry::from_json(required_str(json, "JSON")?)?;
+        for (node_type, metadata) in registry.iter() {
+            ctx.registry.register_as(node_type, metadata.clone());
+        }
+        Ok(())
+    })
+}
+
+/// Returns the number of registered node types.
+///
+/// # Safety
+///
+/// `ctx` must be null or a live context.
+#[no_mangle]
+pub unsafe extern "C" fn graphy_node_count(ctx: *const GraphyContext) -> usize {
+    ctx.as_ref().map_or(0, |ctx| ctx.registry.len())
+}
+
+// ===========================================================================
+// Graphs
+// ===========================================================================
+
+/// Loads a graph from JSON, replacing the loaded graph.
+///
+/// Older graph formats are migrated. Clears the diagnostics and generated
+/// code.
+///
+/// # Safety
+///
+/// `ctx` must be a live context and `json` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn graphy_load_graph_json(ctx: *mut GraphyContext, json: *const c_char) -> GraphyStatus {
+    call(|| {
+        let ctx = context(ctx)?;
+        let graph = load_with_migration(required_str(json, "JSON")?)?;
+        ctx.graph = Some(graph);
+        ctx.diagnostics.clear();
+        ctx.header = None;
+        ctx.source = None;
+        Ok(())
+    })
+}
+
+// ===========================================================================
+// Analysis
+// ===========================================================================
+
+/// Analyzes the loaded graph, replacing the diagnostics.
+///
+/// Returns [`GraphyStatus::Failed`] if any diagnostic is an error; warnings
+/// alone succeed. Read the diagnostics with [`graphy_diagnostic_count`] and
+/// its siblings.
+///
+/// # Safety
+///
+/// `ctx` must be a live context.
+#[no_mangle]
+pub unsafe extern "C" fn graphy_analyze(ctx: *mut GraphyContext) -> GraphyStatus {
+    call(|| {
+        let ctx = context(ctx)?;
+        let diagnostics = ctx.analyze()?;
+        let count = diagnostics.len();
+        if ctx.set_diagnostics(diagnostics) {
+            return Err(CallError::Failed(format!("Analysis found {} problem(s)", count)));
+        }
+        Ok(())
+    })
+}
+
+/// Returns the number of diagnostics.
+///
+/// # Safety
+///
+/// `ctx` must be null or a live context.
+#[no_mangle]
+pub unsafe extern "C" fn graphy_diagnostic_count(ctx: *const GraphyContext) -> usize {
+    ctx.as_ref().map_or(0, |ctx| ctx.diagnostics.len())
+}
+
+/// Returns the severity of a diagnostic, or [`GraphySeverity::Error`] if
+/// the index is out of range.
+///
+/// # Safety
+///
+/// `ctx` must be null or a live context.
+#[no_mangle]
+pub unsafe extern "C" fn graphy_diagnostic_severity(ctx: *const GraphyContext, index: usize) -> GraphySeverity {
+    ctx.as_ref()
+        .and_then(|ctx| ctx.diagnostics.get(index))
+        .map_or(GraphySeverity::Error, |diagnostic| diagnostic.severity)
+}
+
+/// Returns the message of a diagnostic, or null if the index is out of range.
+///
+/// # Safety
+///
+/// `ctx` must be null or a live context.
+#[no_mangle]
+pub unsafe extern "C" fn graphy_diagnostic_message(ctx: *const GraphyContext, index: usize) -> *const c_char {
+    c_str_ptr(
+        ctx.as_ref()
+            .and_then(|ctx| ctx.diagnostics.get(index))
+            .map(|diagnostic| &diagnostic.message),
+    )
+}
+
+/// Returns the ID of the node a diagnostic is about, or null if it isn't
+/// about one node or the index is out of range.
+///
+/// # Safety
+///
+/// `ctx` must be null or a live context.
+#[no_mangle]
+pub unsafe extern "C" fn graphy_diagnostic_node(ctx: *const GraphyContext, index: usize) -> *const c_char {
+    c_str_ptr(
+        ctx.as_ref()
+            .and_then(|ctx| ctx.diagnostics.get(index))
+            .and_then(|diagnostic| diagnostic.node.as_ref()),
+    )
+}
+
+// ===========================================================================
+// Code generation
+// ===========================================================================
+
+/// Compiles the loaded graph to C.
+///
+/// `name` is the base name of the files and the prefix of the generated
+/// functions. On success, fetch the code with [`graphy_generated_source`]
+/// and [`graphy_generated_header`]. On failure, the error is also the only
+/// diagnostic.
+///
+/// # Safety
+///
+/// `ctx` must be a live context and `name` a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn graphy_generate_c(ctx: *mut GraphyContext, name: *const c_char) -> GraphyStatus {
+    call(|| {
+        let ctx = context(ctx)?;
+        let mut generator = CGenerator::new(required_str(name, "name")?);
+        ctx.header = None;
+        ctx.source = None;
+        match compile_c(ctx.graph()?, &ctx.registry, &mut generator) {
+            Ok(sources) => {
+                ctx.diagnostics.clear();
+                ctx.header = Some(to_c_string(sources.header));
+                ctx.source = Some(to_c_string(sources.source));
+                Ok(())
+            }
+            Err(error) => {
+                ctx.set_diagnostics(vec![Diagnostic::from_error(&error)]);
+                Err(error.into())
+            }
+        }
+    })
+}
+
+/// Returns the C source of the last successful [`graphy_generate_c`], or
+/// null.
+///
+/// # Safety
+///
+/// `ctx` must be null or a live context.
+#[no_mangle]
+pub unsafe extern "C" fn graphy_generated_source(ctx: *const GraphyContext) -> *const c_char {
+    c_str_ptr(ctx.as_ref().and_then(|ctx| ctx.source.as_ref()))
+}
+
+/// Returns the C header of the last successful [`graphy_generate_c`], or
+/// null.
+///
+/// # Safety
+///
+/// `ctx` must be null or a live context.
+#[no_mangle]
+pub unsafe extern "C" fn graphy_generated_header(ctx: *const GraphyContext) -> *const c_char {
+    c_str_ptr(ctx.as_ref().and_then(|ctx| ctx.header.as_ref()))
+}
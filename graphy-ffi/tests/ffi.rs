@@ -0,0 +1,350 @@
+//! Tests for the C API, called through its Rust declarations.
+
+use graphy::*;
+use graphy_ffi::*;
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+
+/// Owns a context for the duration of a test
+struct Context(*mut GraphyContext);
+
+impl Context {
+    fn new() -> Self {
+        Self(graphy_context_new())
+    }
+
+    fn register(&self, metadata: &NodeMetadata) -> GraphyStatus {
+        let name = CString::new(metadata.name.as_str()).unwrap();
+        let category = CString::new(metadata.category.as_str()).unwrap();
+        let param_strings: Vec<(CString, CString)> = metadata
+            .params
+            .iter()
+            .map(|param| (CString::new(param.name.as_str()).unwrap(), CString::new(param.param_type.as_str()).unwrap()))
+            .collect();
+        let params: Vec<GraphyParamDesc> = param_strings
+            .iter()
+            .map(|(name, type_name)| GraphyParamDesc {
+                name: name.as_ptr(),
+                type_name: type_name.as_ptr(),
+            })
+            .collect();
+        let output_strings: Vec<CString> =
+            metadata.exec_outputs.iter().map(|output| CString::new(output.as_str()).unwrap()).collect();
+        let outputs: Vec<*const c_char> = output_strings.iter().map(|output| output.as_ptr()).collect();
+        let return_type = metadata
+            .return_type
+            .as_ref()
+            .map(|return_type| CString::new(return_type.type_string.as_str()).unwrap());
+        let source = CString::new(metadata.function_source.as_str()).unwrap();
+
+        let desc = GraphyNodeDesc {
+            name: name.as_ptr(),
+            node_type: match metadata.node_type {
+                NodeTypes::pure => GraphyNodeType::Pure,
+                NodeTypes::fn_ => GraphyNodeType::Function,
+                NodeTypes::control_flow => GraphyNodeType::ControlFlow,
+                NodeTypes::event => GraphyNodeType::Event,
+            },
+            category: category.as_ptr(),
+            params: params.as_ptr(),
+            param_count: params.len(),
+            return_type: return_type.as_ref().map_or(ptr::null(), |return_type| return_type.as_ptr()),
+            exec_outputs: outputs.as_ptr(),
+            exec_output_count: outputs.len(),
+            source: source.as_ptr(),
+            source_kind: match metadata.source_kind {
+                SourceKind::Rust => GraphySourceKind::Rust,
+                SourceKind::Template => GraphySourceKind::Template,
+            },
+        };
+        unsafe { graphy_register_node(self.0, &desc) }
+    }
+
+    fn load(&self, graph: &GraphDescription) -> GraphyStatus {
+        let json = CString::new(serde_json::to_string(graph).unwrap()).unwrap();
+        unsafe { graphy_load_graph_json(self.0, json.as_ptr()) }
+    }
+
+    fn diagnostics(&self) -> Vec<(GraphySeverity, String, Option<String>)> {
+        let count = unsafe { graphy_diagnostic_count(self.0) };
+        (0..count)
+            .map(|index| unsafe {
+                (
+                    graphy_diagnostic_severity(self.0, index),
+                    string(graphy_diagnostic_message(self.0, index)).unwrap(),
+                    string(graphy_diagnostic_node(self.0, index)),
+                )
+            })
+            .collect()
+    }
+}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        unsafe { graphy_context_free(self.0) }
+    }
+}
+
+/// Copies a returned string
+fn string(value: *const c_char) -> Option<String> {
+    (!value.is_null()).then(|| unsafe { CStr::from_ptr(value) }.to_str().unwrap().to_string())
+}
+
+fn last_error() -> Option<String> {
+    string(graphy_last_error())
+}
+
+fn nodes() -> Vec<NodeMetadata> {
+    vec![
+        NodeMetadata::new("on_tick", NodeTypes::event, "Events")
+            .with_params(vec![ParamInfo::new("delta_time", "f32")])
+            .with_exec_outputs(vec!["exec".into()]),
+        NodeMetadata::new("log", NodeTypes::fn_, "Debug")
+            .with_params(vec![ParamInfo::new("message", "String")])
+            .with_source("printf(\"%s\\n\", {{message}});")
+            .with_source_kind(SourceKind::Template)
+            .with_exec_outputs(vec!["then".into()]),
+        NodeMetadata::new("write_pin", NodeTypes::fn_, "Hardware")
+            .with_params(vec![ParamInfo::new("pin", "u8"), ParamInfo::new("high", "bool")])
+            .with_exec_outputs(vec!["then".into()]),
+    ]
+}
+
+/// A context knowing [`nodes`]
+fn context() -> Context {
+    let ctx = Context::new();
+    for metadata in nodes() {
+        assert_eq!(ctx.register(&metadata), GraphyStatus::Ok);
+    }
+    ctx
+}
+
+fn exec_node(graph: &mut GraphDescription, id: &str, node_type: &str) {
+    let mut node = NodeInstance::new(id, node_type, Position::zero());
+    node.add_input_pin("exec_in", DataType::Execution);
+    node.add_output_pin("then", DataType::Execution);
+    graph.add_node(node);
+}
+
+/// Sets a constant input of a node
+fn set_input(graph: &mut GraphDescription, id: &str, pin: &str, value: PropertyValue) {
+    let node = graph.nodes.get_mut(id).unwrap();
+    node.add_input_pin(pin, DataType::Any);
+    node.set_property(pin, value);
+}
+
+/// on_tick -> log("tick") -> write_pin(13, true)
+fn build_tick_graph() -> GraphDescription {
+    let mut graph = GraphDescription::new("blinky");
+    let mut tick = NodeInstance::new("tick", "on_tick", Position::zero());
+    tick.add_output_pin("exec", DataType::Execution);
+    graph.add_node(tick);
+
+    exec_node(&mut graph, "log_1", "log");
+    set_input(&mut graph, "log_1", "message", PropertyValue::String("tick".into()));
+    exec_node(&mut graph, "write_1", "write_pin");
+    set_input(&mut graph, "write_1", "pin", PropertyValue::Integer(13));
+    set_input(&mut graph, "write_1", "high", PropertyValue::Boolean(true));
+
+    graph.add_connection(Connection::execution("tick", "exec", "log_1", "exec_in"));
+    graph.add_connection(Connection::execution("log_1", "then", "write_1", "exec_in"));
+    graph
+}
+
+// ===========================================================================
+// Metadata
+// ===========================================================================
+
+#[test]
+fn node_descriptions_are_registered() {
+    let ctx = context();
+    assert_eq!(unsafe { graphy_node_count(ctx.0) }, 3);
+
+    // Registering a node again replaces it
+    assert_eq!(ctx.register(&nodes()[1]), GraphyStatus::Ok);
+    assert_eq!(unsafe { graphy_node_count(ctx.0) }, 3);
+}
+
+#[test]
+fn registries_are_registered_from_json() {
+    let mut registry = NodeRegistry::new();
+    for metadata in nodes() {
+        registry.register(metadata);
+    }
+    let json = CString::new(registry.to_json().unwrap()).unwrap();
+
+    let ctx = Context::new();
+    assert_eq!(unsafe { graphy_register_nodes_json(ctx.0, json.as_ptr()) }, GraphyStatus::Ok);
+    assert_eq!(unsafe { graphy_node_count(ctx.0) }, 3);
+    assert_eq!(ctx.load(&build_tick_graph()), GraphyStatus::Ok);
+    assert_eq!(unsafe { graphy_analyze(ctx.0) }, GraphyStatus::Ok);
+}
+
+#[test]
+fn invalid_arguments_are_rejected() {
+    let ctx = context();
+    assert_eq!(unsafe { graphy_register_node(ctx.0, ptr::null()) }, GraphyStatus::InvalidArgument);
+    assert_eq!(last_error().as_deref(), Some("The node description is null"));
+
+    let nameless = GraphyNodeDesc {
+        name: ptr::null(),
+        node_type: GraphyNodeType::Pure,
+        category: c"Math".as_ptr(),
+        params: ptr::null(),
+        param_count: 0,
+        return_type: ptr::null(),
+        exec_outputs: ptr::null(),
+        exec_output_count: 0,
+        source: ptr::null(),
+        source_kind: GraphySourceKind::Rust,
+    };
+    assert_eq!(unsafe { graphy_register_node(ctx.0, &nameless) }, GraphyStatus::InvalidArgument);
+    assert_eq!(last_error().as_deref(), Some("The node name is null"));
+    let missing_params = GraphyNodeDesc {
+        name: c"add".as_ptr(),
+        param_count: 2,
+        ..nameless
+    };
+    assert_eq!(unsafe { graphy_register_node(ctx.0, &missing_params) }, GraphyStatus::InvalidArgument);
+
+    assert_eq!(unsafe { graphy_analyze(ptr::null_mut()) }, GraphyStatus::InvalidArgument);
+    assert_eq!(unsafe { graphy_node_count(ctx.0) }, 3);
+
+    // A successful call clears the error
+    assert_eq!(ctx.load(&build_tick_graph()), GraphyStatus::Ok);
+    assert_eq!(last_error(), None);
+}
+
+// ===========================================================================
+// Analysis
+// ===========================================================================
+
+#[test]
+fn invalid_graph_json_fails_to_load() {
+    let ctx = context();
+    assert_eq!(unsafe { graphy_load_graph_json(ctx.0, c"{ not json".as_ptr()) }, GraphyStatus::Failed);
+    assert!(last_error().is_some());
+    assert_eq!(unsafe { graphy_analyze(ctx.0) }, GraphyStatus::Failed);
+    assert_eq!(last_error().as_deref(), Some("No graph is loaded"));
+}
+
+#[test]
+fn analysis_of_a_valid_graph_has_no_diagnostics() {
+    let ctx = context();
+    assert_eq!(ctx.load(&build_tick_graph()), GraphyStatus::Ok);
+    assert_eq!(unsafe { graphy_analyze(ctx.0) }, GraphyStatus::Ok);
+    assert!(ctx.diagnostics().is_empty());
+    assert_eq!(unsafe { graphy_diagnostic_message(ctx.0, 0) }, ptr::null());
+}
+
+#[test]
+fn unreachable_nodes_are_warnings() {
+    let ctx = context();
+    let mut graph = build_tick_graph();
+    exec_node(&mut graph, "stray", "log");
+    assert_eq!(ctx.load(&graph), GraphyStatus::Ok);
+
+    assert_eq!(unsafe { graphy_analyze(ctx.0) }, GraphyStatus::Ok);
+    assert_eq!(
+        ctx.diagnostics(),
+        [(
+            GraphySeverity::Warning,
+            "Node 'stray' is never executed".to_string(),
+            Some("stray".to_string())
+        )]
+    );
+}
+
+#[test]
+fn unknown_node_types_are_warnings() {
+    let ctx = context();
+    let mut graph = build_tick_graph();
+    exec_node(&mut graph, "stray", "plugin.spawn");
+    assert_eq!(ctx.load(&graph), GraphyStatus::Ok);
+
+    assert_eq!(unsafe { graphy_analyze(ctx.0) }, GraphyStatus::Ok);
+    assert_eq!(
+        ctx.diagnostics()[0],
+        (
+            GraphySeverity::Warning,
+            "Node stray has unknown type 'plugin.spawn'".to_string(),
+            Some("stray".to_string())
+        )
+    );
+}
+
+#[test]
+fn unconnected_required_inputs_are_errors() {
+    let ctx = context();
+    let mut registry = NodeRegistry::new();
+    registry.register(
+        NodeMetadata::new("write_pin", NodeTypes::fn_, "Hardware")
+            .with_params(vec![ParamInfo::new("pin", "u8").with_required(true)])
+            .with_exec_outputs(vec!["then".into()]),
+    );
+    let json = CString::new(registry.to_json().unwrap()).unwrap();
+    assert_eq!(unsafe { graphy_register_nodes_json(ctx.0, json.as_ptr()) }, GraphyStatus::Ok);
+    assert_eq!(ctx.load(&build_tick_graph()), GraphyStatus::Ok);
+
+    assert_eq!(unsafe { graphy_analyze(ctx.0) }, GraphyStatus::Failed);
+    let diagnostics = ctx.diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].0, GraphySeverity::Error);
+    assert_eq!(diagnostics[0].2.as_deref(), Some("write_1"));
+    assert!(diagnostics[0].1.contains("pin"), "{}", diagnostics[0].1);
+}
+
+#[test]
+fn inputs_with_too_many_connections_are_errors() {
+    let ctx = context();
+    let mut graph = build_tick_graph();
+    for source in ["tick", "log_1"] {
+        graph.add_connection(Connection::data(source, "value", "write_1", "pin"));
+    }
+    assert_eq!(ctx.load(&graph), GraphyStatus::Ok);
+
+    assert_eq!(unsafe { graphy_analyze(ctx.0) }, GraphyStatus::Failed);
+    assert!(ctx.diagnostics().contains(&(
+        GraphySeverity::Error,
+        "Input write_1.pin has 2 connections, but accepts at most 1".to_string(),
+        Some("write_1".to_string())
+    )));
+}
+
+// ===========================================================================
+// Code generation
+// ===========================================================================
+
+#[test]
+fn generated_c_is_returned() {
+    let ctx = context();
+    assert_eq!(ctx.load(&build_tick_graph()), GraphyStatus::Ok);
+    assert_eq!(unsafe { graphy_generated_source(ctx.0) }, ptr::null());
+
+    assert_eq!(unsafe { graphy_generate_c(ctx.0, c"blinky".as_ptr()) }, GraphyStatus::Ok);
+    let source = string(unsafe { graphy_generated_source(ctx.0) }).unwrap();
+    let header = string(unsafe { graphy_generated_header(ctx.0) }).unwrap();
+    assert!(source.contains("printf(\"%s\\n\", \"tick\");"), "{}", source);
+    assert!(source.contains("write_pin(13, true);"), "{}", source);
+    assert!(header.contains("void write_pin("), "{}", header);
+
+    // Loading another graph drops the code
+    assert_eq!(ctx.load(&build_tick_graph()), GraphyStatus::Ok);
+    assert_eq!(unsafe { graphy_generated_source(ctx.0) }, ptr::null());
+}
+
+#[test]
+fn generation_errors_become_diagnostics() {
+    let ctx = context();
+    let mut graph = build_tick_graph();
+    exec_node(&mut graph, "unknown_1", "teleport");
+    graph.add_connection(Connection::execution("write_1", "then", "unknown_1", "exec_in"));
+    assert_eq!(ctx.load(&graph), GraphyStatus::Ok);
+
+    assert_eq!(unsafe { graphy_generate_c(ctx.0, c"blinky".as_ptr()) }, GraphyStatus::Failed);
+    let diagnostics = ctx.diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].0, GraphySeverity::Error);
+    assert_eq!(Some(diagnostics[0].1.clone()), last_error());
+    assert_eq!(unsafe { graphy_generated_source(ctx.0) }, ptr::null());
+}
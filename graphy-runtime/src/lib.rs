@@ -0,0 +1,76 @@
+//! # Graphy Runtime
+//!
+//! Support code for programs generated by Graphy. Generated code only
+//! depends on this crate when a build calls back into the host.
+//!
+//! Builds with value probes (`CompileOptions::with_probes`) report the
+//! values of watched pins to a [`ValueSink`] as they are computed:
+//!
+//! ```ignore
+//! let node_add_1_result = add(2, 3);
+//! graphy_runtime::sink().on_value("add_1", "result", &node_add_1_result);
+//! ```
+//!
+//! By default they report to the global sink, which ignores values until
+//! the host installs one with [`set_sink`].
+//!
+//! # Example
+//!
+//! ```
+//! use std::fmt::Debug;
+//!
+//! graphy_runtime::set_sink(|node: &str, pin: &str, value: &dyn Debug| {
+//!     eprintln!("{}.{} = {:?}", node, pin, value);
+//! });
+//!
+//! // What the generated code does
+//! graphy_runtime::sink().on_value("add_1", "result", &5);
+//! ```
+
+use std::fmt::Debug;
+use std::sync::OnceLock;
+
+/// Receives the values of watched pins
+///
+/// Implemented for closures taking the same arguments as
+/// [`on_value`](Self::on_value).
+pub trait ValueSink: Send + Sync {
+    /// Called right after the value of a watched pin is computed
+    fn on_value(&self, node: &str, pin: &str, value: &dyn Debug);
+}
+
+impl<F> ValueSink for F
+where
+    F: Fn(&str, &str, &dyn Debug) + Send + Sync,
+{
+    fn on_value(&self, node: &str, pin: &str, value: &dyn Debug) {
+        self(node, pin, value)
+    }
+}
+
+/// Sink ignoring every value
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopSink;
+
+impl ValueSink for NoopSink {
+    fn on_value(&self, _node: &str, _pin: &str, _value: &dyn Debug) {}
+}
+
+static SINK: OnceLock<Box<dyn ValueSink>> = OnceLock::new();
+
+/// Install the global sink
+///
+/// The sink can only be installed once; returns `false`, leaving the
+/// installed one in place, if there is one already. Hosts that need to
+/// switch sinks install one forwarding to the current target.
+pub fn set_sink(sink: impl ValueSink + 'static) -> bool {
+    SINK.set(Box::new(sink)).is_ok()
+}
+
+/// The global sink, or a [`NoopSink`] until one is installed
+pub fn sink() -> &'static dyn ValueSink {
+    match SINK.get() {
+        Some(sink) => sink.as_ref(),
+        None => &NoopSink,
+    }
+}
@@ -0,0 +1,59 @@
+//! Tests for the value sinks generated code reports to.
+
+use graphy_runtime::{set_sink, sink, NoopSink, ValueSink};
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+/// Records every value as `node.pin = value`
+#[derive(Default)]
+struct Recorder(Mutex<Vec<String>>);
+
+impl ValueSink for Recorder {
+    fn on_value(&self, node: &str, pin: &str, value: &dyn Debug) {
+        self.0.lock().unwrap().push(format!("{}.{} = {:?}", node, pin, value));
+    }
+}
+
+// ============================================================================
+// Sinks
+// ============================================================================
+
+#[test]
+fn sink_values_are_formatted_with_debug() {
+    let recorder = Recorder::default();
+    recorder.on_value("add_1", "result", &5);
+    recorder.on_value("greet_1", "message", &"hi");
+    recorder.on_value("spawn_1", "position", &(1.5, -2.0));
+
+    assert_eq!(
+        *recorder.0.lock().unwrap(),
+        ["add_1.result = 5", "greet_1.message = \"hi\"", "spawn_1.position = (1.5, -2.0)"]
+    );
+}
+
+#[test]
+fn sink_closures_are_sinks() {
+    let seen = Mutex::new(Vec::new());
+    let closure = |node: &str, pin: &str, value: &dyn Debug| seen.lock().unwrap().push(format!("{} {} {:?}", node, pin, value));
+    closure.on_value("add_1", "result", &[1, 2]);
+    NoopSink.on_value("add_1", "result", &3);
+
+    assert_eq!(*seen.lock().unwrap(), ["add_1 result [1, 2]"]);
+}
+
+// ============================================================================
+// Global sink
+// ============================================================================
+
+#[test]
+fn sink_is_installed_once() {
+    let values = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&values);
+    assert!(set_sink(move |node: &str, pin: &str, value: &dyn Debug| {
+        recorded.lock().unwrap().push(format!("{}.{} = {:?}", node, pin, value));
+    }));
+    assert!(!set_sink(NoopSink));
+
+    sink().on_value("add_1", "result", &5);
+    assert_eq!(*values.lock().unwrap(), ["add_1.result = 5"]);
+}
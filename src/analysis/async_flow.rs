@@ -0,0 +1,116 @@
+//! # Async Analysis
+//!
+//! Finds the events that have to be generated as async functions.
+//!
+//! A node is async if its metadata sets
+//! [`is_async`](crate::NodeMetadata::is_async). An event is async if any node
+//! it runs is async: a node on its execution flow, or a pure node feeding
+//! one of those nodes (directly or transitively).
+//!
+//! # Example
+//!
+//! ```ignore
+//! let analysis = AsyncAnalysis::compute(&graph, &provider, &exec_routing, &data_resolver);
+//! if analysis.is_async_event("on_click") {
+//!     // emit `async fn on_click()`
+//! }
+//! ```
+
+use super::{DataResolver, ExecutionRouting};
+use crate::core::{GraphDescription, NodeMetadataProvider, NodeTypes};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+/// Async nodes used by each event.
+#[derive(Debug, Clone, Default)]
+pub struct AsyncAnalysis {
+    /// Async nodes run by at least one event
+    async_nodes: FxHashSet<String>,
+
+    /// Maps event -> async nodes it runs (sorted). Only async events are present.
+    async_events: FxHashMap<String, Vec<String>>,
+}
+
+impl AsyncAnalysis {
+    /// Computes which events run async nodes.
+    ///
+    /// Nodes no event runs are ignored, since no code is generated for them.
+    pub fn compute<P: NodeMetadataProvider>(
+        graph: &GraphDescription,
+        metadata_provider: &P,
+        exec_routing: &ExecutionRouting,
+        data_resolver: &DataResolver,
+    ) -> Self {
+        let metadata_of = |node_id: &str| {
+            graph
+                .nodes
+                .get(node_id)
+                .and_then(|node| metadata_provider.metadata_for_node(node))
+        };
+
+        let mut analysis = AsyncAnalysis::default();
+        for (event_id, node) in &graph.nodes {
+            let is_event = metadata_provider
+                .metadata_for_node(node)
+                .is_some_and(|metadata| metadata.node_type == NodeTypes::event);
+            if !is_event {
+                continue;
+            }
+
+            let mut used: FxHashSet<String> = FxHashSet::default();
+            for exec_node in exec_routing.reachable_nodes(event_id) {
+                used.extend(data_resolver.get_transitive_dependencies(&exec_node));
+                used.insert(exec_node);
+            }
+
+            let mut async_nodes: Vec<String> = used
+                .into_iter()
+                .filter(|node_id| metadata_of(node_id).is_some_and(|metadata| metadata.is_async))
+                .collect();
+            if async_nodes.is_empty() {
+                continue;
+            }
+
+            async_nodes.sort_unstable();
+            analysis.async_nodes.extend(async_nodes.iter().cloned());
+            analysis.async_events.insert(event_id.clone(), async_nodes);
+        }
+
+        tracing::debug!(
+            "[ASYNC] {} async event(s), {} async node(s)",
+            analysis.async_events.len(),
+            analysis.async_nodes.len()
+        );
+
+        analysis
+    }
+
+    /// Checks if no event runs async nodes.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.async_nodes.is_empty()
+    }
+
+    /// Checks if an event runs any async node.
+    #[inline]
+    pub fn is_async_event(&self, event_id: &str) -> bool {
+        self.async_events.contains_key(event_id)
+    }
+
+    /// Checks if a node is async and run by some event.
+    #[inline]
+    pub fn is_async_node(&self, node_id: &str) -> bool {
+        self.async_nodes.contains(node_id)
+    }
+
+    /// Returns the async events, sorted by ID.
+    pub fn async_events(&self) -> Vec<&str> {
+        let mut events: Vec<&str> = self.async_events.keys().map(String::as_str).collect();
+        events.sort_unstable();
+        events
+    }
+
+    /// Returns the async nodes an event runs, sorted by ID.
+    pub fn async_nodes_of(&self, event_id: &str) -> &[String] {
+        self.async_events.get(event_id).map(Vec::as_slice).unwrap_or_default()
+    }
+}
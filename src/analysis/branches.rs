@@ -0,0 +1,389 @@
+//! # Isomorphic Branches
+//!
+//! Finds exec branches that generate the same code up to their constants.
+//!
+//! Procedurally generated graphs often repeat one branch hundreds of times,
+//! e.g. a `sequence` whose every output spawns an entity at a different
+//! position. A branch starts at an exec edge leaving a fan-out point (a node
+//! with two or more outgoing exec edges) and holds the exec nodes reachable
+//! from that edge's target, its root, plus the pure nodes they read. Two
+//! branches are isomorphic if their nodes map one to one with the same node
+//! types, properties, pins, and wiring, so only the constants fed to their
+//! inputs differ.
+//!
+//! [`IsomorphicBranches::compute`] groups isomorphic branches into
+//! [`BranchClass`]es. Constants that differ between the instances of a class
+//! become its [`BranchParam`]s, so a code generator can emit the branch once
+//! as a function of them (see
+//! [`CompileOptions::with_branch_helpers`](crate::generation::CompileOptions::with_branch_helpers)).
+//!
+//! Only self-contained branches are candidates:
+//!
+//! - Execution enters through the root only, and never loops back.
+//! - Every input is a constant, a default, or an output of a node of the
+//!   branch; graph inputs and event params belong to the enclosing scope.
+//! - Nothing outside the branch reads a value computed inside it.
+//! - No node is async or builtin (variable reads and writes touch state).
+//!
+//! Classes are claimed largest first, so a branch nested in a larger
+//! matched branch is left to it.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let branches = IsomorphicBranches::compute(&graph, &registry, &exec_routing, &data_resolver, 4);
+//! for class in branches.classes() {
+//!     println!("{} copies of {} nodes", class.instances.len(), class.node_count());
+//! }
+//! ```
+
+use super::{DataResolver, DataSource, ExecutionRouting};
+use crate::core::{GraphDescription, NodeMetadataProvider, NodeTypes, PropertyValue};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::fmt::Write;
+
+/// Isomorphic exec branches of a graph.
+#[derive(Debug, Clone, Default)]
+pub struct IsomorphicBranches {
+    /// Sorted by the root of their first instance
+    classes: Vec<BranchClass>,
+}
+
+/// Branches of a graph generating the same code up to their constants.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BranchClass {
+    /// At least two, sorted by root ID
+    pub instances: Vec<BranchInstance>,
+
+    /// Constants that differ between the instances, in node order
+    pub params: Vec<BranchParam>,
+}
+
+/// One occurrence of a [`BranchClass`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BranchInstance {
+    /// The node the branch's incoming exec edge leads to
+    pub root: String,
+
+    /// Nodes of the branch, starting with the root; the node at an index
+    /// corresponds to the node at the same index of every other instance
+    pub nodes: Vec<String>,
+
+    /// Constant of each param of the class
+    pub arguments: Vec<PropertyValue>,
+}
+
+/// An input fed a different constant by the instances of a [`BranchClass`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchParam {
+    /// Index of the node in [`BranchInstance::nodes`]
+    pub node: usize,
+
+    /// Input pin of the node
+    pub pin: String,
+
+    /// Type of the input
+    pub param_type: String,
+}
+
+impl BranchClass {
+    /// Returns the number of nodes in each instance.
+    #[inline]
+    pub fn node_count(&self) -> usize {
+        self.instances.first().map_or(0, |instance| instance.nodes.len())
+    }
+
+    /// Returns the instance code is generated from.
+    #[inline]
+    pub fn representative(&self) -> &BranchInstance {
+        &self.instances[0]
+    }
+}
+
+/// A self-contained branch, with everything that decides its code
+struct Candidate<'g> {
+    root: &'g str,
+    nodes: Vec<&'g str>,
+
+    /// `(node index, pin, type, value)` of every constant input
+    constants: Vec<(usize, String, String, &'g PropertyValue)>,
+
+    /// Node types, pins, properties, and wiring in node order
+    key: String,
+}
+
+impl IsomorphicBranches {
+    /// Finds the isomorphic branches of an analyzed graph.
+    ///
+    /// Branches with fewer than `min_nodes` nodes are ignored, since a call
+    /// wouldn't be much shorter than their code.
+    pub fn compute<P: NodeMetadataProvider>(
+        graph: &GraphDescription,
+        metadata_provider: &P,
+        exec_routing: &ExecutionRouting,
+        data_resolver: &DataResolver,
+        min_nodes: usize,
+    ) -> Self {
+        let mut roots: Vec<&str> = graph
+            .nodes
+            .keys()
+            .map(|node_id| exec_routing.get_outgoing(node_id))
+            .filter(|edges| edges.len() >= 2)
+            .flatten()
+            .filter(|edge| !exec_routing.is_back_edge(&edge.source_node, &edge.source_pin, &edge.target_node))
+            .map(|edge| edge.target_node.as_str())
+            .collect();
+        roots.sort_unstable();
+        roots.dedup();
+
+        let mut by_key: FxHashMap<String, Vec<Candidate<'_>>> = FxHashMap::default();
+        for root in roots {
+            let Some(mut candidate) = candidate(graph, metadata_provider, exec_routing, data_resolver, root) else {
+                continue;
+            };
+            if candidate.nodes.len() >= min_nodes {
+                by_key.entry(std::mem::take(&mut candidate.key)).or_default().push(candidate);
+            }
+        }
+
+        let mut groups: Vec<Vec<Candidate<'_>>> = by_key.into_values().filter(|group| group.len() >= 2).collect();
+        groups.sort_by(|a, b| {
+            b[0].nodes
+                .len()
+                .cmp(&a[0].nodes.len())
+                .then_with(|| a[0].root.cmp(b[0].root))
+        });
+
+        let mut claimed: FxHashSet<&str> = FxHashSet::default();
+        let mut classes = Vec::new();
+        for group in groups {
+            let mut kept: Vec<Candidate<'_>> = Vec::with_capacity(group.len());
+            for candidate in group {
+                if candidate.nodes.iter().any(|node| claimed.contains(node)) {
+                    continue;
+                }
+                claimed.extend(candidate.nodes.iter().copied());
+                kept.push(candidate);
+            }
+            if kept.len() >= 2 {
+                classes.push(class_of(kept));
+            } else {
+                // A lone instance is generated in place, so its nodes are free again
+                for candidate in &kept {
+                    for node in &candidate.nodes {
+                        claimed.remove(node);
+                    }
+                }
+            }
+        }
+        classes.sort_by(|a, b| a.representative().root.cmp(&b.representative().root));
+
+        tracing::debug!("[BRANCHES] Found {} class(es) of isomorphic branches", classes.len());
+
+        Self { classes }
+    }
+
+    /// Returns the classes, sorted by the root of their first instance.
+    #[inline]
+    pub fn classes(&self) -> &[BranchClass] {
+        &self.classes
+    }
+
+    /// Returns the class and instance a node is the root of.
+    pub fn instance_at(&self, root: &str) -> Option<(&BranchClass, &BranchInstance)> {
+        self.classes.iter().find_map(|class| {
+            class
+                .instances
+                .iter()
+                .find(|instance| instance.root == root)
+                .map(|instance| (class, instance))
+        })
+    }
+
+    /// Returns the number of classes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.classes.len()
+    }
+
+    /// Checks if no branches are isomorphic.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.classes.is_empty()
+    }
+}
+
+/// Turn the matching candidates into a class, keeping differing constants as params
+fn class_of(candidates: Vec<Candidate<'_>>) -> BranchClass {
+    let first = &candidates[0];
+    let varying: Vec<usize> = (0..first.constants.len())
+        .filter(|&slot| {
+            candidates
+                .iter()
+                .any(|candidate| candidate.constants[slot].3 != first.constants[slot].3)
+        })
+        .collect();
+
+    let params = varying
+        .iter()
+        .map(|&slot| {
+            let (node, pin, param_type, _) = &first.constants[slot];
+            BranchParam {
+                node: *node,
+                pin: pin.clone(),
+                param_type: param_type.clone(),
+            }
+        })
+        .collect();
+    let instances = candidates
+        .iter()
+        .map(|candidate| BranchInstance {
+            root: candidate.root.to_string(),
+            nodes: candidate.nodes.iter().map(|node| node.to_string()).collect(),
+            arguments: varying
+                .iter()
+                .map(|&slot| candidate.constants[slot].3.clone())
+                .collect(),
+        })
+        .collect();
+
+    BranchClass { instances, params }
+}
+
+/// The branch starting at `root`, or `None` if it isn't self-contained
+///
+/// Nodes are numbered in an order decided by the structure alone: exec
+/// nodes depth first from the root, then the pure nodes they read, breadth
+/// first. The key describes every node in that order, so two branches with
+/// the same key map one to one by index.
+fn candidate<'g, P: NodeMetadataProvider>(
+    graph: &'g GraphDescription,
+    metadata_provider: &P,
+    exec_routing: &'g ExecutionRouting,
+    data_resolver: &'g DataResolver,
+    root: &'g str,
+) -> Option<Candidate<'g>> {
+    let mut index: FxHashMap<&str, usize> = FxHashMap::default();
+    let mut nodes: Vec<&str> = Vec::new();
+
+    let mut stack: Vec<&str> = vec![root];
+    while let Some(node_id) = stack.pop() {
+        if index.contains_key(node_id) {
+            continue;
+        }
+        let metadata = metadata_provider.metadata_for_node(graph.nodes.get(node_id)?)?;
+        if !matches!(metadata.node_type, NodeTypes::fn_ | NodeTypes::control_flow) || metadata.is_async {
+            return None;
+        }
+        index.insert(node_id, nodes.len());
+        nodes.push(node_id);
+
+        for edge in exec_routing.get_outgoing(node_id).iter().rev() {
+            if exec_routing.is_back_edge(node_id, &edge.source_pin, &edge.target_node) {
+                return None;
+            }
+            stack.push(&edge.target_node);
+        }
+    }
+    let exec_nodes = nodes.len();
+
+    // Execution enters through the root's incoming edge only
+    let entered_elsewhere = nodes.iter().enumerate().any(|(position, node_id)| {
+        exec_routing
+            .get_incoming(node_id)
+            .iter()
+            .any(|edge| index.contains_key(edge.source_node.as_str()) == (position == 0))
+    });
+    if entered_elsewhere {
+        return None;
+    }
+
+    let mut constants = Vec::new();
+    let mut key = String::new();
+    let mut position = 0;
+    while position < nodes.len() {
+        let node_id = nodes[position];
+        let node = graph.nodes.get(node_id)?;
+        let metadata = metadata_provider.metadata_for_node(node)?;
+        if position >= exec_nodes && (metadata.node_type != NodeTypes::pure || metadata.is_async) {
+            return None;
+        }
+
+        let _ = write!(
+            key,
+            "{} {} {} {:?} |",
+            position, node.node_type, node.variadic_count, node.version_req
+        );
+        for pin in node.inputs.iter().chain(&node.outputs) {
+            let _ = write!(key, " {}:{:?}", pin.id, pin.pin.data_type);
+        }
+
+        let mut constant_pins: Vec<String> = Vec::new();
+        key.push_str(" |");
+        for param in metadata.instance_params(node) {
+            if data_resolver.get_input_sources_multi(node_id, &param.name).len() > 1 {
+                return None;
+            }
+            match data_resolver.get_input_source(node_id, &param.name) {
+                Some(DataSource::Connection { source_node_id, source_pin }) => {
+                    let source = match index.get(source_node_id.as_str()) {
+                        Some(&source) => source,
+                        None => {
+                            index.insert(source_node_id, nodes.len());
+                            nodes.push(source_node_id);
+                            nodes.len() - 1
+                        }
+                    };
+                    let _ = write!(key, " {}<{}.{}", param.name, source, source_pin);
+                }
+                Some(DataSource::Constant(value, _)) => {
+                    let _ = write!(key, " {}=const {}", param.name, param.param_type);
+                    constants.push((position, param.name.clone(), param.param_type.clone(), value));
+                    constant_pins.push(param.name.clone());
+                }
+                Some(DataSource::Default) | None => {
+                    let _ = write!(key, " {}=default {}", param.name, param.param_type);
+                }
+                Some(DataSource::GraphInput { .. }) | Some(DataSource::EventParam { .. }) => return None,
+            }
+        }
+
+        // Constant inputs are read from properties, but vary between instances
+        let mut properties: Vec<(&str, String)> = node
+            .properties
+            .iter()
+            .filter(|(name, _)| !constant_pins.contains(name))
+            .map(|(name, value)| (name.as_str(), format!("{:?}", value)))
+            .collect();
+        properties.sort_unstable();
+        key.push_str(" |");
+        for (name, value) in properties {
+            let _ = write!(key, " {}={}", name, value);
+        }
+
+        key.push_str(" |");
+        for edge in exec_routing.get_outgoing(node_id) {
+            let _ = write!(key, " {}>{}", edge.source_pin, index.get(edge.target_node.as_str())?);
+        }
+        key.push('\n');
+        position += 1;
+    }
+
+    // Values read outside would have to be computed where the branch isn't
+    let escapes = nodes.iter().any(|node_id| {
+        data_resolver
+            .get_dependents(node_id)
+            .iter()
+            .any(|reader| !index.contains_key(reader.as_str()))
+    });
+    if escapes {
+        return None;
+    }
+
+    Some(Candidate {
+        root,
+        nodes,
+        constants,
+        key,
+    })
+}
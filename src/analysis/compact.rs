@@ -0,0 +1,276 @@
+//! # Compact Graph
+//!
+//! Index-based view of a [`GraphDescription`] for analysis passes.
+//!
+//! [`CompactGraph`] numbers nodes `0..n` in node ID order and stores the
+//! data and execution edges in compressed sparse row (CSR) form, in both
+//! directions. Neighbour queries are slice lookups instead of hash map
+//! lookups keyed by strings, and the whole structure takes a few `u32`s per
+//! edge, which matters for graphs with 100k+ nodes.
+//!
+//! Build it once per compilation and hand it to the `*_compact` entry points
+//! of the analyses ([`DataResolver::build_from_compact`],
+//! [`ExecutionRouting::build_from_compact`],
+//! [`Reachability::compute_compact`], [`find_cycles_compact`]). The
+//! string-based entry points build one internally.
+//!
+//! IDs named only by connections (dangling connections) get an index too,
+//! but no [`NodeInstance`](crate::NodeInstance).
+//!
+//! [`DataResolver::build_from_compact`]: super::DataResolver::build_from_compact
+//! [`ExecutionRouting::build_from_compact`]: super::ExecutionRouting::build_from_compact
+//! [`Reachability::compute_compact`]: super::Reachability::compute_compact
+//! [`find_cycles_compact`]: super::find_cycles_compact
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::analysis::CompactGraph;
+//! use graphy::{Connection, ConnectionType, GraphDescription, NodeInstance, Position};
+//!
+//! let mut graph = GraphDescription::new("example");
+//! graph.add_node(NodeInstance::new("a", "add", Position::zero()));
+//! graph.add_node(NodeInstance::new("b", "print", Position::zero()));
+//! graph.add_connection(Connection::data("a", "result", "b", "value"));
+//!
+//! let compact = CompactGraph::from_graph(&graph);
+//! let a = compact.index_of("a").unwrap();
+//! let b = compact.index_of("b").unwrap();
+//! assert_eq!(compact.successors(ConnectionType::Data, a), [b]);
+//! assert_eq!(compact.predecessors(ConnectionType::Data, b), [a]);
+//! assert_eq!(compact.node_id(b), "b");
+//! ```
+
+use crate::core::{Connection, ConnectionType, GraphDescription, NodeInstance};
+use rustc_hash::FxHashMap;
+
+/// Edges of one direction in compressed sparse row form
+///
+/// The neighbours of node `v` are `targets[offsets[v]..offsets[v + 1]]`, in
+/// connection order; `connections` holds the matching connection indices.
+#[derive(Debug, Clone, Default)]
+struct Csr {
+    offsets: Vec<u32>,
+    targets: Vec<u32>,
+    connections: Vec<u32>,
+}
+
+impl Csr {
+    /// Build from `(from, to, connection)` edges with a counting sort
+    fn build(node_count: usize, edges: &[(u32, u32, u32)]) -> Self {
+        let mut offsets = vec![0u32; node_count + 1];
+        for &(from, _, _) in edges {
+            offsets[from as usize + 1] += 1;
+        }
+        for v in 0..node_count {
+            offsets[v + 1] += offsets[v];
+        }
+
+        // Stable placement keeps connection order per node
+        let mut next: Vec<u32> = offsets[..node_count].to_vec();
+        let mut targets = vec![0u32; edges.len()];
+        let mut connections = vec![0u32; edges.len()];
+        for &(from, to, connection) in edges {
+            let slot = next[from as usize] as usize;
+            targets[slot] = to;
+            connections[slot] = connection;
+            next[from as usize] += 1;
+        }
+
+        Self {
+            offsets,
+            targets,
+            connections,
+        }
+    }
+
+    #[inline(always)]
+    fn range(&self, v: u32) -> std::ops::Range<usize> {
+        self.offsets[v as usize] as usize..self.offsets[v as usize + 1] as usize
+    }
+}
+
+/// Both directions of one connection type
+#[derive(Debug, Clone, Default)]
+struct Adjacency {
+    forward: Csr,
+    backward: Csr,
+}
+
+impl Adjacency {
+    fn build(node_count: usize, mut edges: Vec<(u32, u32, u32)>) -> Self {
+        let forward = Csr::build(node_count, &edges);
+        for edge in &mut edges {
+            *edge = (edge.1, edge.0, edge.2);
+        }
+        let backward = Csr::build(node_count, &edges);
+        Self { forward, backward }
+    }
+}
+
+/// Index-based view of a graph.
+///
+/// Node indices are `u32`s in `0..node_count()`, assigned in node ID order,
+/// so iterating indices visits nodes deterministically.
+#[derive(Debug, Clone)]
+pub struct CompactGraph<'g> {
+    graph: &'g GraphDescription,
+
+    /// Node IDs by index, sorted
+    ids: Vec<&'g str>,
+
+    /// Nodes by index (`None` for IDs only named by connections)
+    nodes: Vec<Option<&'g NodeInstance>>,
+
+    /// Maps node ID -> index
+    index: FxHashMap<&'g str, u32>,
+
+    data: Adjacency,
+    exec: Adjacency,
+}
+
+impl<'g> CompactGraph<'g> {
+    /// Builds the compact view of a graph.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the graph has more than `u32::MAX` nodes or connections.
+    pub fn from_graph(graph: &'g GraphDescription) -> Self {
+        let mut ids: Vec<&str> = graph
+            .nodes
+            .keys()
+            .map(String::as_str)
+            .chain(
+                graph
+                    .connections
+                    .iter()
+                    .flat_map(|c| [c.source_node.as_str(), c.target_node.as_str()]),
+            )
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+
+        let to_u32 = |n: usize| u32::try_from(n).expect("graph too large for a compact graph");
+        let index: FxHashMap<&str, u32> = ids.iter().enumerate().map(|(i, id)| (*id, to_u32(i))).collect();
+        let nodes = ids.iter().map(|id| graph.nodes.get(*id)).collect();
+
+        let mut data_edges = Vec::new();
+        let mut exec_edges = Vec::new();
+        for (i, connection) in graph.connections.iter().enumerate() {
+            let source = index[connection.source_node.as_str()];
+            let target = index[connection.target_node.as_str()];
+            let edges = match connection.connection_type {
+                ConnectionType::Data => &mut data_edges,
+                ConnectionType::Execution => &mut exec_edges,
+            };
+            edges.push((source, target, to_u32(i)));
+        }
+
+        let node_count = ids.len();
+        Self {
+            graph,
+            ids,
+            nodes,
+            index,
+            data: Adjacency::build(node_count, data_edges),
+            exec: Adjacency::build(node_count, exec_edges),
+        }
+    }
+
+    /// Returns the graph this view was built from.
+    #[inline]
+    pub fn graph(&self) -> &'g GraphDescription {
+        self.graph
+    }
+
+    /// Returns the number of indexed IDs (nodes and dangling connection endpoints).
+    #[inline]
+    pub fn node_count(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Returns the number of edges of a connection type.
+    #[inline]
+    pub fn edge_count(&self, kind: ConnectionType) -> usize {
+        self.adjacency(kind).forward.targets.len()
+    }
+
+    /// Returns the index of a node, if it exists.
+    #[inline]
+    pub fn index_of(&self, node_id: &str) -> Option<u32> {
+        self.index.get(node_id).copied()
+    }
+
+    /// Returns the ID of a node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of range.
+    #[inline]
+    pub fn node_id(&self, index: u32) -> &'g str {
+        self.ids[index as usize]
+    }
+
+    /// Returns a node, or `None` if its ID is only named by connections.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of range.
+    #[inline]
+    pub fn node(&self, index: u32) -> Option<&'g NodeInstance> {
+        self.nodes[index as usize]
+    }
+
+    /// Returns all node indices in node ID order.
+    #[inline]
+    pub fn indices(&self) -> std::ops::Range<u32> {
+        // `from_graph` checked that the count fits
+        0..self.ids.len() as u32
+    }
+
+    /// Returns the nodes a node's connections of a type lead to, in connection order.
+    ///
+    /// A node connected several times appears several times.
+    #[inline]
+    pub fn successors(&self, kind: ConnectionType, index: u32) -> &[u32] {
+        let csr = &self.adjacency(kind).forward;
+        &csr.targets[csr.range(index)]
+    }
+
+    /// Returns the nodes whose connections of a type lead to a node, in connection order.
+    #[inline]
+    pub fn predecessors(&self, kind: ConnectionType, index: u32) -> &[u32] {
+        let csr = &self.adjacency(kind).backward;
+        &csr.targets[csr.range(index)]
+    }
+
+    /// Returns the connections of a type leaving a node, in connection order.
+    ///
+    /// The `i`-th connection leads to `successors(kind, index)[i]`.
+    pub fn outgoing(&self, kind: ConnectionType, index: u32) -> impl Iterator<Item = &'g Connection> + '_ {
+        let csr = &self.adjacency(kind).forward;
+        let graph = self.graph;
+        csr.connections[csr.range(index)]
+            .iter()
+            .map(move |&connection| &graph.connections[connection as usize])
+    }
+
+    /// Returns the connections of a type entering a node, in connection order.
+    ///
+    /// The `i`-th connection comes from `predecessors(kind, index)[i]`.
+    pub fn incoming(&self, kind: ConnectionType, index: u32) -> impl Iterator<Item = &'g Connection> + '_ {
+        let csr = &self.adjacency(kind).backward;
+        let graph = self.graph;
+        csr.connections[csr.range(index)]
+            .iter()
+            .map(move |&connection| &graph.connections[connection as usize])
+    }
+
+    #[inline(always)]
+    fn adjacency(&self, kind: ConnectionType) -> &Adjacency {
+        match kind {
+            ConnectionType::Data => &self.data,
+            ConnectionType::Execution => &self.exec,
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! # Cost Analysis
+//!
+//! Estimates how expensive each event is from the
+//! [`cost_hint`](crate::NodeMetadata::cost_hint) of its nodes.
+//!
+//! An event runs the nodes on its execution flow and the pure nodes feeding
+//! them (like in [`AsyncAnalysis`](super::AsyncAnalysis)). Its **total**
+//! cost is the sum of their hints. Its **critical path** is the most
+//! expensive chain of nodes that have to run one after another: each node
+//! on it either follows the previous one in the execution flow or reads its
+//! data. Shortening anything off the critical path doesn't shorten it.
+//!
+//! Costs are estimates: loop bodies and both sides of a branch are counted
+//! once, and nodes without a hint (or unknown to the provider) cost 0.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let costs = CostAnalysis::compute(&graph, &provider, &exec_routing, &data_resolver);
+//! for event in costs.over_budget(FRAME_BUDGET) {
+//!     let cost = costs.event_cost(event).unwrap();
+//!     println!("{} costs {}, mostly {:?}", event, cost.total, cost.critical_path);
+//! }
+//! ```
+
+use super::{DataResolver, ExecutionRouting};
+use crate::core::{GraphDescription, NodeMetadataProvider, NodeTypes};
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Estimated cost of an event.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventCost {
+    /// Sum of the cost hints of every node the event runs
+    pub total: u64,
+
+    /// Sum of the cost hints on the critical path
+    pub critical_path_cost: u64,
+
+    /// Most expensive chain of dependent nodes, in the order they run
+    /// (empty if every node is free)
+    pub critical_path: Vec<String>,
+}
+
+/// Estimated costs of every event of a graph.
+#[derive(Debug, Clone, Default)]
+pub struct CostAnalysis {
+    /// Maps event -> its cost
+    events: FxHashMap<String, EventCost>,
+}
+
+impl CostAnalysis {
+    /// Computes the cost of every event.
+    pub fn compute<P: NodeMetadataProvider>(
+        graph: &GraphDescription,
+        metadata_provider: &P,
+        exec_routing: &ExecutionRouting,
+        data_resolver: &DataResolver,
+    ) -> Self {
+        let cost_of = |node_id: &str| {
+            graph
+                .nodes
+                .get(node_id)
+                .and_then(|node| metadata_provider.metadata_for_node(node))
+                .map_or(0, |metadata| u64::from(metadata.cost_hint))
+        };
+
+        let mut analysis = CostAnalysis::default();
+        for (event_id, node) in &graph.nodes {
+            let is_event = metadata_provider
+                .metadata_for_node(node)
+                .is_some_and(|metadata| metadata.node_type == NodeTypes::event);
+            if !is_event {
+                continue;
+            }
+
+            let mut used: FxHashSet<String> = FxHashSet::default();
+            for exec_node in exec_routing.reachable_nodes(event_id) {
+                used.extend(data_resolver.get_transitive_dependencies(&exec_node));
+                used.insert(exec_node);
+            }
+            let mut used: Vec<String> = used.into_iter().collect();
+            used.sort_unstable();
+
+            let cost = event_cost(&used, &cost_of, exec_routing, data_resolver);
+            analysis.events.insert(event_id.clone(), cost);
+        }
+
+        tracing::debug!("[COST] Estimated {} event(s)", analysis.events.len());
+
+        analysis
+    }
+
+    /// Returns the cost of an event, or `None` if it isn't an event.
+    #[inline]
+    pub fn event_cost(&self, event_id: &str) -> Option<&EventCost> {
+        self.events.get(event_id)
+    }
+
+    /// Returns the events, sorted by ID.
+    pub fn events(&self) -> Vec<&str> {
+        let mut events: Vec<&str> = self.events.keys().map(String::as_str).collect();
+        events.sort_unstable();
+        events
+    }
+
+    /// Returns the events whose total cost exceeds a budget, sorted by ID.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// // Warn about tick events that won't fit in a 16ms frame
+    /// for event in costs.over_budget(16_000) {
+    ///     warn!("{} exceeds the frame budget", event);
+    /// }
+    /// ```
+    pub fn over_budget(&self, budget: u64) -> Vec<&str> {
+        let mut events: Vec<&str> = self
+            .events
+            .iter()
+            .filter(|(_, cost)| cost.total > budget)
+            .map(|(event, _)| event.as_str())
+            .collect();
+        events.sort_unstable();
+        events
+    }
+}
+
+/// Total and critical path of the nodes an event runs (sorted by ID)
+fn event_cost(
+    nodes: &[String],
+    cost_of: &impl Fn(&str) -> u64,
+    exec_routing: &ExecutionRouting,
+    data_resolver: &DataResolver,
+) -> EventCost {
+    let index: FxHashMap<&str, usize> = nodes.iter().enumerate().map(|(i, id)| (id.as_str(), i)).collect();
+    let costs: Vec<u64> = nodes.iter().map(|id| cost_of(id)).collect();
+
+    // Readers of each node's data, and the nodes running after it (back
+    // edges excluded, so the edges form a DAG)
+    let mut successors: Vec<Vec<usize>> = vec![Vec::new(); nodes.len()];
+    let mut in_degree = vec![0usize; nodes.len()];
+    for (source, id) in nodes.iter().enumerate() {
+        let readers = data_resolver.get_dependents(id).iter().map(String::as_str);
+        let next = exec_routing
+            .get_outgoing(id)
+            .iter()
+            .filter(|edge| !exec_routing.is_back_edge(id, &edge.source_pin, &edge.target_node))
+            .map(|edge| edge.target_node.as_str());
+        for target in readers.chain(next).filter_map(|target| index.get(target).copied()) {
+            successors[source].push(target);
+            in_degree[target] += 1;
+        }
+    }
+
+    // Longest path ending at each node, in topological order
+    let mut longest = costs.clone();
+    let mut previous: Vec<Option<usize>> = vec![None; nodes.len()];
+    let mut ready: VecDeque<usize> = (0..nodes.len()).filter(|&i| in_degree[i] == 0).collect();
+    let mut end: Option<usize> = None;
+    while let Some(node) = ready.pop_front() {
+        if longest[node] > end.map_or(0, |end| longest[end]) {
+            end = Some(node);
+        }
+        for &target in &successors[node] {
+            if longest[node] + costs[target] > longest[target] {
+                longest[target] = longest[node] + costs[target];
+                previous[target] = Some(node);
+            }
+            in_degree[target] -= 1;
+            if in_degree[target] == 0 {
+                ready.push_back(target);
+            }
+        }
+    }
+
+    let mut critical_path: Vec<String> = std::iter::successors(end, |&node| previous[node])
+        .map(|node| nodes[node].clone())
+        .collect();
+    critical_path.reverse();
+
+    EventCost {
+        total: costs.iter().sum(),
+        critical_path_cost: end.map_or(0, |end| longest[end]),
+        critical_path,
+    }
+}
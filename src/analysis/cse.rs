@@ -0,0 +1,186 @@
+//! # Common Subexpression Elimination
+//!
+//! Finds pure nodes that compute the same value.
+//!
+//! Two pure nodes are equivalent if they have the same node type, the same
+//! properties, and every input comes from the same source: the same
+//! constant, graph input, or event param, or an output of equivalent nodes.
+//! Equivalence is decided in evaluation order, so whole subtrees merge: two
+//! `sin(a + b)` chains built from separate nodes collapse into one.
+//!
+//! [`CommonSubexpressions::compute`] groups the equivalent nodes and keeps
+//! the one with the smallest ID of each group.
+//! [`DataResolver::apply_common_subexpressions`] then rewires the analysis
+//! results to the kept nodes, so code generators evaluate each value once
+//! (and hoist it into a variable when it is read several times).
+//!
+//! Pure nodes that may return different values for the same inputs, like
+//! `random`, opt out with [`NodeMetadata::with_volatile`](crate::NodeMetadata::with_volatile).
+//!
+//! # Example
+//!
+//! ```ignore
+//! let mut resolver = DataResolver::build(&graph, &provider)?;
+//! let cse = CommonSubexpressions::compute(&graph, &provider, &resolver);
+//! resolver.apply_common_subexpressions(&cse);
+//!
+//! for duplicate in cse.duplicates() {
+//!     println!("{} reuses {}", duplicate, cse.representative(duplicate));
+//! }
+//! ```
+
+use super::{DataResolver, DataSource};
+use crate::core::{GraphDescription, NodeMetadataProvider};
+use rustc_hash::FxHashMap;
+
+/// Equivalent pure nodes of a graph.
+#[derive(Debug, Clone, Default)]
+pub struct CommonSubexpressions {
+    /// Maps duplicate node -> equivalent node kept in its place
+    replacements: FxHashMap<String, String>,
+}
+
+/// Where an input of a candidate node comes from
+#[derive(PartialEq, Eq, Hash)]
+enum InputKey<'a> {
+    /// Output of the nodes of an equivalence class
+    Class(usize, &'a str),
+
+    /// Output of a node that isn't a candidate (functions, variable reads)
+    Node(&'a str, &'a str),
+
+    GraphInput(&'a str),
+    EventParam(&'a str, &'a str),
+
+    /// Constant as debug text, like properties
+    Constant(String),
+    Default,
+}
+
+/// Everything that determines the value of a pure node
+#[derive(PartialEq, Eq, Hash)]
+struct NodeKey<'a> {
+    node_type: &'a str,
+    inputs: Vec<InputKey<'a>>,
+
+    /// Properties as `(name, debug text)`, sorted by name
+    properties: Vec<(&'a str, String)>,
+}
+
+impl CommonSubexpressions {
+    /// Finds the equivalent pure nodes of an analyzed graph.
+    ///
+    /// Only nodes in the resolver's
+    /// [pure evaluation order](DataResolver::get_pure_evaluation_order) are
+    /// candidates; volatile nodes are never merged (nor, through them, the
+    /// nodes reading them).
+    pub fn compute<P: NodeMetadataProvider>(
+        graph: &GraphDescription,
+        metadata_provider: &P,
+        data_resolver: &DataResolver,
+    ) -> Self {
+        let mut class_of: FxHashMap<&str, usize> = FxHashMap::default();
+        let mut classes: FxHashMap<NodeKey<'_>, usize> = FxHashMap::default();
+        let mut members: Vec<Vec<&str>> = Vec::new();
+
+        for node_id in data_resolver.get_pure_evaluation_order() {
+            let Some(node) = graph.nodes.get(node_id) else {
+                continue;
+            };
+            let Some(metadata) = metadata_provider.metadata_for_node(node) else {
+                continue;
+            };
+
+            let class = if metadata.is_volatile {
+                members.len()
+            } else {
+                let inputs = metadata
+                    .instance_params(node)
+                    .iter()
+                    .map(|param| match data_resolver.get_input_source(node_id, &param.name) {
+                        Some(DataSource::Connection { source_node_id, source_pin }) => {
+                            match class_of.get(source_node_id.as_str()) {
+                                Some(&class) => InputKey::Class(class, source_pin),
+                                None => InputKey::Node(source_node_id, source_pin),
+                            }
+                        }
+                        Some(DataSource::GraphInput { name }) => InputKey::GraphInput(name),
+                        Some(DataSource::EventParam { event_node, param }) => InputKey::EventParam(event_node, param),
+                        Some(DataSource::Constant(value, _)) => InputKey::Constant(format!("{:?}", value)),
+                        Some(DataSource::Default) | None => InputKey::Default,
+                    })
+                    .collect();
+
+                let mut properties: Vec<(&str, String)> = node
+                    .properties
+                    .iter()
+                    .map(|(name, value)| (name.as_str(), format!("{:?}", value)))
+                    .collect();
+                properties.sort_unstable();
+
+                let key = NodeKey {
+                    node_type: &node.node_type,
+                    inputs,
+                    properties,
+                };
+                *classes.entry(key).or_insert(members.len())
+            };
+
+            if class == members.len() {
+                members.push(Vec::new());
+            }
+            members[class].push(node_id);
+            class_of.insert(node_id, class);
+        }
+
+        let mut replacements = FxHashMap::default();
+        for nodes in &members {
+            let Some(&kept) = nodes.iter().min() else {
+                continue;
+            };
+            for &node in nodes.iter().filter(|&&node| node != kept) {
+                replacements.insert(node.to_string(), kept.to_string());
+            }
+        }
+
+        Self { replacements }
+    }
+
+    /// Returns the node computing a node's value: the equivalent node kept
+    /// in its place, or the node itself.
+    pub fn representative<'a>(&'a self, node_id: &'a str) -> &'a str {
+        self.replacements.get(node_id).map_or(node_id, String::as_str)
+    }
+
+    /// Returns the equivalent node replacing a duplicate, `None` for nodes
+    /// that are kept.
+    #[inline]
+    pub fn replacement(&self, node_id: &str) -> Option<&str> {
+        self.replacements.get(node_id).map(String::as_str)
+    }
+
+    /// Checks if a node is replaced by an equivalent node.
+    #[inline]
+    pub fn is_duplicate(&self, node_id: &str) -> bool {
+        self.replacements.contains_key(node_id)
+    }
+
+    /// Returns all replaced nodes, sorted by ID.
+    pub fn duplicates(&self) -> Vec<&str> {
+        let mut duplicates: Vec<&str> = self.replacements.keys().map(String::as_str).collect();
+        duplicates.sort_unstable();
+        duplicates
+    }
+
+    /// Returns the number of replaced nodes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.replacements.len()
+    }
+
+    /// Checks if no node is replaced.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.replacements.is_empty()
+    }
+}
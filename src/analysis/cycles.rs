@@ -0,0 +1,130 @@
+//! # Cycle Detection
+//!
+//! Finds data dependency cycles between pure nodes.
+//!
+//! Pure nodes are evaluated as expressions, so a cycle in their data
+//! connections has no valid evaluation order. [`DataResolver::build`] fails
+//! with the first cycle found; [`find_cycles`] reports all of them, which is
+//! useful for highlighting every offending loop in an editor at once.
+//!
+//! [`DataResolver::build`]: super::DataResolver::build
+//!
+//! # Example
+//!
+//! ```ignore
+//! use graphy::analysis::find_cycles;
+//!
+//! for cycle in find_cycles(&graph, &provider) {
+//!     println!("cycle: {}", cycle.join(" -> "));
+//! }
+//! ```
+
+use super::CompactGraph;
+use crate::core::{ConnectionType, GraphDescription, NodeInstance, NodeMetadataProvider, NodeTypes};
+
+/// Finds data dependency cycles between pure nodes.
+///
+/// Each cycle is returned as a path in data flow order (each node feeds the
+/// next, and the last feeds the first). One cycle is reported per DFS
+/// back-edge, so every strongly connected component with a cycle is covered
+/// at least once. Traversal runs in sorted node order, so results are
+/// deterministic.
+pub fn find_cycles<P: NodeMetadataProvider>(graph: &GraphDescription, metadata_provider: &P) -> Vec<Vec<String>> {
+    find_cycles_compact(&CompactGraph::from_graph(graph), metadata_provider)
+}
+
+/// Finds data dependency cycles between pure nodes of a compact graph.
+///
+/// See [`find_cycles`].
+pub fn find_cycles_compact<P: NodeMetadataProvider>(
+    compact: &CompactGraph<'_>,
+    metadata_provider: &P,
+) -> Vec<Vec<String>> {
+    // Compact indices are in ID order, so pure nodes stay sorted
+    let pure: Vec<u32> = compact
+        .indices()
+        .filter(|&index| compact.node(index).is_some_and(|node| is_pure_value_node(node, metadata_provider)))
+        .collect();
+
+    let mut local = vec![usize::MAX; compact.node_count()];
+    for (i, &index) in pure.iter().enumerate() {
+        local[index as usize] = i;
+    }
+
+    // adjacency[source] = targets fed by source's output
+    let adjacency: Vec<Vec<usize>> = pure
+        .iter()
+        .map(|&index| {
+            let mut targets: Vec<usize> = compact
+                .successors(ConnectionType::Data, index)
+                .iter()
+                .map(|&target| local[target as usize])
+                .filter(|&target| target != usize::MAX)
+                .collect();
+            targets.sort_unstable();
+            targets.dedup();
+            targets
+        })
+        .collect();
+
+    find_cycles_in(&adjacency)
+        .into_iter()
+        .map(|cycle| cycle.into_iter().map(|v| compact.node_id(pure[v]).to_string()).collect())
+        .collect()
+}
+
+/// Checks if a node takes part in pure evaluation ordering
+#[inline]
+pub(crate) fn is_pure_value_node<P: NodeMetadataProvider>(node: &NodeInstance, metadata_provider: &P) -> bool {
+    metadata_provider
+        .metadata_for_node(node)
+        .is_some_and(|meta| meta.node_type == NodeTypes::pure && meta.return_type.is_some())
+}
+
+/// Iterative DFS that extracts the path closed by each back-edge
+fn find_cycles_in(adjacency: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    const UNVISITED: usize = usize::MAX;
+    const DONE: usize = usize::MAX - 1;
+
+    // position[v] = index of v in `path` while on it, UNVISITED, or DONE
+    let mut position = vec![UNVISITED; adjacency.len()];
+    let mut path: Vec<usize> = Vec::new();
+    let mut cycles = Vec::new();
+
+    for start in 0..adjacency.len() {
+        if position[start] != UNVISITED {
+            continue;
+        }
+
+        position[start] = path.len();
+        path.push(start);
+
+        // Call stack of (node, next adjacency position)
+        let mut call_stack: Vec<(usize, usize)> = vec![(start, 0)];
+
+        while let Some((v, next)) = call_stack.last_mut() {
+            let v = *v;
+
+            if let Some(&w) = adjacency[v].get(*next) {
+                *next += 1;
+
+                match position[w] {
+                    UNVISITED => {
+                        position[w] = path.len();
+                        path.push(w);
+                        call_stack.push((w, 0));
+                    }
+                    DONE => {}
+                    on_path => cycles.push(path[on_path..].to_vec()),
+                }
+                continue;
+            }
+
+            position[v] = DONE;
+            path.pop();
+            call_stack.pop();
+        }
+    }
+
+    cycles
+}
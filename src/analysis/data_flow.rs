@@ -10,19 +10,37 @@
 //! # Performance
 //!
 //! Two implementations are provided:
-//! - **Sequential** (`build`): Best for graphs < 5,000 nodes (default)
+//! - **Sequential** (`build`): Best for graphs < 5,000 nodes
 //! - **Parallel** (`build_parallel`): Best for graphs ≥ 5,000 nodes (1.5-2x speedup)
 //!
+//! Prefer [`DataResolver::build_auto`], which picks one from the size of
+//! the graph and the available threads (see [`ParallelThreshold`]), over
+//! calling either directly. The compilation driver does the same with
+//! [`CompileOptions::parallel_threshold`](crate::generation::CompileOptions::parallel_threshold).
+//!
+//! Without the `parallel` feature (e.g. on `wasm32-unknown-unknown`, which
+//! has no threads), `build_parallel` and its variants run the sequential
+//! build.
+//!
+//! The parallel build sorts pure nodes layer by layer (every node whose
+//! dependencies are all evaluated forms the next layer), ordering each layer
+//! by node ID, so its evaluation order is the same on every run.
+//!
+//! # Stable Order
+//!
+//! By default a new node can move unrelated nodes in the evaluation order,
+//! which makes diffs of the generated code large. With
+//! [`EvaluationOrder::ById`] or [`EvaluationOrder::Previous`] (see
+//! [`DataResolver::build_with_order`]), nodes that don't depend on an edited
+//! node keep their relative order between builds.
+//!
 //! # Example
 //!
 //! ```ignore
 //! use graphy::{DataResolver, GraphDescription};
 //!
-//! // Small graph: use sequential
-//! let resolver = DataResolver::build(&graph, &provider)?;
-//!
-//! // Large graph: use parallel
-//! let resolver = DataResolver::build_parallel(&graph, &provider)?;
+//! // Sequential below 5,000 nodes, parallel above
+//! let resolver = DataResolver::build_auto(&graph, &provider, &ParallelThreshold::default())?;
 //!
 //! // Query data sources
 //! if let Some(source) = resolver.get_input_source("node_1", "input_a") {
@@ -30,17 +48,32 @@
 //! }
 //! ```
 
+use super::{CommonSubexpressions, CompactGraph, SymbolId, SymbolTable};
 use crate::core::*;
+#[cfg(feature = "heap-stats")]
+use crate::utils::heap_stats::{HeapScope, HeapUsage};
+use crate::utils::{
+    CancellationToken, HeapSize, LanguageProfile, MemoryStats, NoProgress, PhaseProgress, ProgressSink, RustProfile,
+    PHASE_DATA_FLOW, PHASE_EVALUATION_ORDER,
+};
 use crate::GraphyError;
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
-use rustc_hash::FxHashMap;
-use std::collections::{HashSet, VecDeque};
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet, VecDeque};
+#[cfg(feature = "parallel")]
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 /// Data source for a node input.
 ///
 /// Describes where an input pin gets its value from:
 /// - Connected from another node's output
-/// - Constant value from node properties
+/// - A declared graph input (via a `graph.input` pseudo-node)
+/// - A parameter of an event node (e.g. `delta_time` of `on_tick`)
+/// - Constant value from node properties, or the metadata default of the param
 /// - Default value for the type
 #[derive(Debug, Clone)]
 pub enum DataSource {
@@ -53,23 +86,259 @@ pub enum DataSource {
         source_pin: String,
     },
 
-    /// Constant value from node properties (as string literal)
-    Constant(String),
+    /// Connected to a `graph.input` pseudo-node (a graph function parameter)
+    GraphInput {
+        /// Name of the declared graph input
+        name: String,
+    },
+
+    /// Connected to a parameter of an event node
+    ///
+    /// Event params are exposed as output data pins named after the param.
+    /// The value is bound to a local variable inside the event handler (see
+    /// [`DataResolver::get_event_param_variable`]).
+    EventParam {
+        /// ID of the event node
+        event_node: String,
+
+        /// Name of the event parameter
+        param: String,
+    },
+
+    /// Constant value from node properties or the param's metadata default,
+    /// and the type of the input it feeds
+    ///
+    /// The type is the metadata param's, or the pin's when the node has no
+    /// metadata for it; `None` if neither is known. Kept typed: the code
+    /// generator writes the value as a literal of its target language (see
+    /// [`CodeGenerator::generate_literal`](crate::generation::CodeGenerator::generate_literal)
+    /// and [`constant_literal`](Self::constant_literal)).
+    Constant(PropertyValue, Option<TypeInfo>),
 
     /// Use default value for this type (calls `Default::default()`)
     Default,
 }
 
+impl DataSource {
+    /// Format a constant as a literal, as the resolver used to store it
+    ///
+    /// Uses the constant's type when it has one, so `Number(2.0)` feeding
+    /// an `f64` input is `2.0` in Rust and `2` for an `i64`. Returns `None`
+    /// for other sources.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::CodeGeneration`] if the language can't
+    /// represent the value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::core::{PropertyValue, TypeInfo};
+    /// use graphy::utils::RustProfile;
+    /// use graphy::DataSource;
+    ///
+    /// let source = DataSource::Constant(PropertyValue::Number(2.0), Some(TypeInfo::new("f64")));
+    /// assert_eq!(source.constant_literal(&RustProfile).unwrap().as_deref(), Some("2.0"));
+    /// assert_eq!(DataSource::Default.constant_literal(&RustProfile).unwrap(), None);
+    /// ```
+    pub fn constant_literal(&self, profile: &dyn LanguageProfile) -> Result<Option<String>, GraphyError> {
+        match self {
+            DataSource::Constant(value, Some(value_type)) => {
+                profile.format_typed_literal(value, &value_type.type_string).map(Some)
+            }
+            DataSource::Constant(value, None) => profile.format_literal(value).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// How [`DataResolver`] orders pure nodes that are ready at the same time.
+///
+/// Every order evaluates a node after the nodes it reads. With the stable
+/// orders ([`ById`](Self::ById) and [`Previous`](Self::Previous)), an edit
+/// only moves the edited nodes and the nodes depending on them: the others
+/// keep their relative order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvaluationOrder {
+    /// Breadth-first from the nodes without pure inputs, starting in node ID
+    /// order; an added node can move unrelated ones
+    #[default]
+    BreadthFirst,
+
+    /// The ready node with the smallest ID first
+    ById,
+
+    /// The ready node that came first in a previous evaluation order (e.g.
+    /// [`get_pure_evaluation_order`](DataResolver::get_pure_evaluation_order)
+    /// of the last build); nodes new since come as soon as they're ready, by
+    /// ID, so a node inserted upstream doesn't move its dependents to the end
+    Previous(Vec<String>),
+}
+
+/// How a [`DataResolver`] is built.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BuildStrategy {
+    /// [`DataResolver::build`] and its variants
+    Sequential,
+
+    /// [`DataResolver::build_parallel`] and its variants
+    Parallel,
+}
+
+/// When [`DataResolver::build_auto`] builds in parallel.
+///
+/// The parallel build pays for spreading work over threads, which only
+/// pays off on large graphs. A graph is built in parallel when it reaches
+/// either count and more than one thread is available: the threads of the
+/// [shared pool](crate::parallel) once it's initialized, the available
+/// parallelism of the machine before. Without the `parallel` feature every
+/// build is sequential.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ParallelThreshold {
+    /// Minimum number of nodes for a parallel build
+    pub min_nodes: usize,
+
+    /// Minimum number of connections for a parallel build
+    pub min_connections: usize,
+}
+
+impl Default for ParallelThreshold {
+    fn default() -> Self {
+        Self::new(5_000)
+    }
+}
+
+impl ParallelThreshold {
+    /// Build graphs of at least `min_nodes` nodes, or twice as many
+    /// connections, in parallel
+    #[inline]
+    pub fn new(min_nodes: usize) -> Self {
+        Self {
+            min_nodes,
+            min_connections: min_nodes.saturating_mul(2),
+        }
+    }
+
+    /// Always build sequentially
+    #[inline]
+    pub fn never() -> Self {
+        Self {
+            min_nodes: usize::MAX,
+            min_connections: usize::MAX,
+        }
+    }
+
+    /// Set the minimum number of connections for a parallel build
+    #[inline]
+    pub fn with_min_connections(mut self, min_connections: usize) -> Self {
+        self.min_connections = min_connections;
+        self
+    }
+
+    /// Strategy for a graph of the given size, given the number of threads
+    pub fn strategy(&self, node_count: usize, connection_count: usize, threads: usize) -> BuildStrategy {
+        let large = node_count >= self.min_nodes || connection_count >= self.min_connections;
+        if large && threads > 1 {
+            BuildStrategy::Parallel
+        } else {
+            BuildStrategy::Sequential
+        }
+    }
+
+    /// Strategy for a graph, with the threads available to this process
+    pub fn select(&self, graph: &GraphDescription) -> BuildStrategy {
+        self.strategy(graph.nodes.len(), graph.connections.len(), available_threads())
+    }
+}
+
+/// Threads a parallel build would run on
+#[cfg(feature = "parallel")]
+fn available_threads() -> usize {
+    if crate::parallel::is_initialized() {
+        crate::parallel::num_threads()
+    } else {
+        crate::parallel::ThreadPoolConfig::new().get_num_threads()
+    }
+}
+
+/// Threads a parallel build would run on
+#[cfg(not(feature = "parallel"))]
+fn available_threads() -> usize {
+    1
+}
+
+impl EvaluationOrder {
+    /// Priority of each node (lower first), or `None` for breadth-first
+    fn ranks(&self, compact: &CompactGraph<'_>) -> Option<Vec<usize>> {
+        match self {
+            Self::BreadthFirst => None,
+            Self::ById => Some(vec![0; compact.indices().len()]),
+            Self::Previous(previous) => {
+                // New nodes rank 0, before every previous one
+                let positions: FxHashMap<&str, usize> =
+                    previous.iter().enumerate().map(|(position, id)| (id.as_str(), position + 1)).collect();
+                let ranks = compact
+                    .indices()
+                    .map(|index| positions.get(compact.node_id(index)).copied().unwrap_or(0))
+                    .collect();
+                Some(ranks)
+            }
+        }
+    }
+}
+
+/// Pure nodes whose dependencies are all evaluated, in [`EvaluationOrder`]
+enum ReadyNodes {
+    Queue(VecDeque<u32>),
+
+    /// Lowest `(rank, index)` first; compact indices are in ID order
+    Ranked(BinaryHeap<Reverse<(usize, u32)>>, Vec<usize>),
+}
+
+impl ReadyNodes {
+    fn new(ranks: Option<Vec<usize>>) -> Self {
+        match ranks {
+            None => Self::Queue(VecDeque::new()),
+            Some(ranks) => Self::Ranked(BinaryHeap::new(), ranks),
+        }
+    }
+
+    fn push(&mut self, index: u32) {
+        match self {
+            Self::Queue(queue) => queue.push_back(index),
+            Self::Ranked(heap, ranks) => heap.push(Reverse((ranks[index as usize], index))),
+        }
+    }
+
+    fn pop(&mut self) -> Option<u32> {
+        match self {
+            Self::Queue(queue) => queue.pop_front(),
+            Self::Ranked(heap, _) => heap.pop().map(|Reverse((_, index))| index),
+        }
+    }
+}
+
 /// Data flow resolver.
 ///
 /// Analyzes a graph to determine:
 /// - Where each input gets its data from
 /// - What variable names to use for node results
 /// - What order to evaluate pure nodes in
+/// - Which nodes depend on which (directly and transitively)
+///
+/// Nodes whose type the metadata provider doesn't know are left out of the
+/// evaluation order; check for them first with an
+/// [`UnknownNodePolicy`](super::UnknownNodePolicy).
 ///
 /// # Performance
 ///
 /// Uses `FxHashMap` (faster, non-cryptographic hashing) internally for better performance.
+/// Input sources and event param variables are keyed by interned
+/// `(node, pin)` [`SymbolId`]s, so building the tables doesn't clone names
+/// and lookups don't allocate.
 ///
 /// # Thread Safety
 ///
@@ -77,23 +346,50 @@ pub enum DataSource {
 /// once per compilation and used from a single thread. For parallel compilation
 /// of multiple graphs, create separate resolvers.
 pub struct DataResolver {
+    /// Interned node IDs and pin names of the graph
+    symbols: SymbolTable,
+
     /// Maps (node_id, input_pin) -> DataSource
     /// Uses FxHashMap for ~2x faster lookups than HashMap
-    input_sources: FxHashMap<(String, String), DataSource>,
+    input_sources: FxHashMap<(SymbolId, SymbolId), DataSource>,
+
+    /// Maps (node_id, input_pin) -> every connected source, in connection
+    /// order, for inputs accepting several connections
+    multi_input_sources: FxHashMap<(SymbolId, SymbolId), Vec<DataSource>>,
 
     /// Maps node_id -> unique variable name for its result
     /// Uses FxHashMap for ~2x faster lookups than HashMap
     result_variables: FxHashMap<String, String>,
 
+    /// Maps (event_node, param) -> local variable, for params read by other nodes
+    event_param_variables: FxHashMap<(SymbolId, SymbolId), String>,
+
+    /// Maps fallible node_id -> variable holding its error, for errors read by other nodes
+    error_variables: FxHashMap<String, String>,
+
     /// Topologically sorted list of pure node IDs
     pure_evaluation_order: Vec<String>,
+
+    /// Maps node_id -> nodes feeding its inputs (deduplicated, connection order)
+    dependencies: FxHashMap<String, Vec<String>>,
+
+    /// Maps node_id -> nodes reading its outputs (deduplicated, connection order)
+    dependents: FxHashMap<String, Vec<String>>,
+
+    /// Version of the metadata provider the resolver was built with
+    provider_version: Option<ProviderVersion>,
+
+    /// Allocations made while building the resolver
+    #[cfg(feature = "heap-stats")]
+    build_heap: Option<HeapUsage>,
 }
 
 impl DataResolver {
     /// Builds a data resolver from a graph using sequential processing.
     ///
-    /// This is the recommended method for most use cases. Use [`build_parallel`](Self::build_parallel)
-    /// only for very large graphs (5,000+ nodes).
+    /// Prefer [`build_auto`](Self::build_auto), which switches to
+    /// [`build_parallel`](Self::build_parallel) for very large graphs
+    /// (5,000+ nodes by default).
     ///
     /// # Process
     ///
@@ -104,7 +400,12 @@ impl DataResolver {
     /// # Errors
     ///
     /// Returns [`GraphyError::CyclicDependency`] if the graph contains cycles
-    /// in data dependencies between pure nodes.
+    /// in data dependencies between pure nodes. The error lists the nodes of
+    /// one such cycle; use [`find_cycles`](super::find_cycles) to get all of them.
+    ///
+    /// Returns [`GraphyError::InvalidConnection`] if a data connection joins
+    /// a node that doesn't exist, see
+    /// [`check_connection_nodes`](GraphDescription::check_connection_nodes).
     ///
     /// # Example
     ///
@@ -121,35 +422,235 @@ impl DataResolver {
     ///
     /// - Small graphs (< 1,000 nodes): ~1-2ms
     /// - Medium graphs (1,000-5,000 nodes): ~5-20ms
-    /// - Large graphs (5,000+ nodes): Consider using `build_parallel`
+    /// - Large graphs (5,000+ nodes): Consider using `build_auto`
     pub fn build<P: NodeMetadataProvider>(
         graph: &GraphDescription,
         metadata_provider: &P,
     ) -> Result<Self, GraphyError> {
+        Self::build_with_profile(graph, metadata_provider, &RustProfile)
+    }
+
+    /// Builds a data resolver with an [`EvaluationOrder`] for the pure nodes.
+    ///
+    /// Same as [`build`](Self::build). Pass [`EvaluationOrder::ById`], or the
+    /// order of the last build as [`EvaluationOrder::Previous`], to keep
+    /// unrelated nodes in place across edits.
+    ///
+    /// # Errors
+    ///
+    /// See [`build`](Self::build).
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let resolver = DataResolver::build(&graph, &provider)?;
+    /// let previous = EvaluationOrder::Previous(resolver.get_pure_evaluation_order().to_vec());
+    ///
+    /// graph.add_node(NodeInstance::new("add_2", "add", Position::zero()));
+    /// let resolver = DataResolver::build_with_order(&graph, &provider, &previous)?;
+    /// ```
+    pub fn build_with_order<P: NodeMetadataProvider>(
+        graph: &GraphDescription,
+        metadata_provider: &P,
+        order: &EvaluationOrder,
+    ) -> Result<Self, GraphyError> {
+        let compact = CompactGraph::from_graph(graph);
+        let cancellation = CancellationToken::new();
+        Self::build_from_compact_with_order(&compact, metadata_provider, &RustProfile, &cancellation, &NoProgress, order)
+    }
+
+    /// Builds a data resolver producing names and literals for a target language.
+    ///
+    /// Same as [`build`](Self::build), but result variables and constant
+    /// inputs follow the given [`LanguageProfile`] instead of Rust.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// use graphy::utils::WgslProfile;
+    ///
+    /// let resolver = DataResolver::build_with_profile(&graph, &provider, &WgslProfile)?;
+    /// ```
+    pub fn build_with_profile<P: NodeMetadataProvider>(
+        graph: &GraphDescription,
+        metadata_provider: &P,
+        profile: &dyn LanguageProfile,
+    ) -> Result<Self, GraphyError> {
+        Self::build_from_compact(&CompactGraph::from_graph(graph), metadata_provider, profile)
+    }
+
+    /// Builds a data resolver from a compact graph.
+    ///
+    /// Same as [`build_with_profile`](Self::build_with_profile), for callers
+    /// that share one [`CompactGraph`] between several analyses.
+    ///
+    /// # Errors
+    ///
+    /// See [`build_with_profile`](Self::build_with_profile).
+    pub fn build_from_compact<P: NodeMetadataProvider>(
+        compact: &CompactGraph<'_>,
+        metadata_provider: &P,
+        profile: &dyn LanguageProfile,
+    ) -> Result<Self, GraphyError> {
+        Self::build_from_compact_with_cancellation(compact, metadata_provider, profile, &CancellationToken::new())
+    }
+
+    /// Builds a data resolver that stops when `cancellation` is cancelled.
+    ///
+    /// Same as [`build_with_profile`](Self::build_with_profile); the token is
+    /// checked between phases and for every node.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::Cancelled`] once the token is cancelled, and
+    /// fails like [`build_with_profile`](Self::build_with_profile) otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let token = CancellationToken::new();
+    /// let job_token = token.clone();
+    /// let job = std::thread::spawn(move || {
+    ///     DataResolver::build_with_cancellation(&graph, &provider, &RustProfile, &job_token)
+    /// });
+    /// token.cancel(); // superseded by a newer edit
+    /// ```
+    pub fn build_with_cancellation<P: NodeMetadataProvider>(
+        graph: &GraphDescription,
+        metadata_provider: &P,
+        profile: &dyn LanguageProfile,
+        cancellation: &CancellationToken,
+    ) -> Result<Self, GraphyError> {
+        Self::build_with_progress(graph, metadata_provider, profile, cancellation, &NoProgress)
+    }
+
+    /// Builds a data resolver from a compact graph, stopping when
+    /// `cancellation` is cancelled.
+    ///
+    /// # Errors
+    ///
+    /// See [`build_with_cancellation`](Self::build_with_cancellation).
+    pub fn build_from_compact_with_cancellation<P: NodeMetadataProvider>(
+        compact: &CompactGraph<'_>,
+        metadata_provider: &P,
+        profile: &dyn LanguageProfile,
+        cancellation: &CancellationToken,
+    ) -> Result<Self, GraphyError> {
+        Self::build_from_compact_with_progress(compact, metadata_provider, profile, cancellation, &NoProgress)
+    }
+
+    /// Builds a cancellable data resolver, reporting progress to `progress`.
+    ///
+    /// Same as [`build_with_cancellation`](Self::build_with_cancellation).
+    /// Reports the [`PHASE_DATA_FLOW`] phase over all nodes, then the
+    /// [`PHASE_EVALUATION_ORDER`] phase over the pure nodes.
+    ///
+    /// # Errors
+    ///
+    /// See [`build_with_cancellation`](Self::build_with_cancellation).
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let progress = |phase: &str, processed: usize, total: usize| {
+    ///     progress_bar.set(phase, processed, total);
+    /// };
+    /// let resolver =
+    ///     DataResolver::build_with_progress(&graph, &provider, &RustProfile, &CancellationToken::new(), &progress)?;
+    /// ```
+    pub fn build_with_progress<P: NodeMetadataProvider>(
+        graph: &GraphDescription,
+        metadata_provider: &P,
+        profile: &dyn LanguageProfile,
+        cancellation: &CancellationToken,
+        progress: &dyn ProgressSink,
+    ) -> Result<Self, GraphyError> {
+        cancellation.check()?;
+        let compact = CompactGraph::from_graph(graph);
+        Self::build_from_compact_with_progress(&compact, metadata_provider, profile, cancellation, progress)
+    }
+
+    /// Builds a cancellable data resolver from a compact graph, reporting
+    /// progress to `progress`.
+    ///
+    /// # Errors
+    ///
+    /// See [`build_with_cancellation`](Self::build_with_cancellation).
+    pub fn build_from_compact_with_progress<P: NodeMetadataProvider>(
+        compact: &CompactGraph<'_>,
+        metadata_provider: &P,
+        profile: &dyn LanguageProfile,
+        cancellation: &CancellationToken,
+        progress: &dyn ProgressSink,
+    ) -> Result<Self, GraphyError> {
+        let order = EvaluationOrder::default();
+        Self::build_from_compact_with_order(compact, metadata_provider, profile, cancellation, progress, &order)
+    }
+
+    /// Builds a cancellable data resolver from a compact graph with an
+    /// [`EvaluationOrder`], reporting progress to `progress`.
+    ///
+    /// # Errors
+    ///
+    /// See [`build_with_cancellation`](Self::build_with_cancellation).
+    pub fn build_from_compact_with_order<P: NodeMetadataProvider>(
+        compact: &CompactGraph<'_>,
+        metadata_provider: &P,
+        profile: &dyn LanguageProfile,
+        cancellation: &CancellationToken,
+        progress: &dyn ProgressSink,
+        order: &EvaluationOrder,
+    ) -> Result<Self, GraphyError> {
+        #[cfg(feature = "heap-stats")]
+        let heap = HeapScope::start();
+        let graph = compact.graph();
+        graph.check_connection_nodes(ConnectionType::Data)?;
+
         // Pre-allocate with estimated capacity for better performance
         let node_count = graph.nodes.len();
         let connection_count = graph.connections.len();
         
         let mut resolver = DataResolver {
+            symbols: SymbolTable::from_graph(graph),
             input_sources: FxHashMap::with_capacity_and_hasher(
                 connection_count * 2, 
                 Default::default()
             ),
+            multi_input_sources: FxHashMap::default(),
             result_variables: FxHashMap::with_capacity_and_hasher(
                 node_count, 
                 Default::default()
             ),
             pure_evaluation_order: Vec::with_capacity(node_count / 4), // Estimate ~25% pure nodes
+            event_param_variables: FxHashMap::default(),
+            error_variables: FxHashMap::default(),
+            dependencies: FxHashMap::default(),
+            dependents: FxHashMap::default(),
+            provider_version: metadata_provider.version(),
+            #[cfg(feature = "heap-stats")]
+            build_heap: None,
         };
 
         // Phase 1: Map all data connections
-        resolver.map_data_connections(graph)?;
+        resolver.map_data_connections(graph, metadata_provider, cancellation, progress)?;
 
         // Phase 2: Generate variable names for node results
-        resolver.generate_variable_names(graph);
+        cancellation.check()?;
+        resolver.generate_variable_names(graph, profile);
+        resolver.name_event_params(profile);
+        resolver.name_error_values(graph, metadata_provider, profile);
 
         // Phase 3: Determine evaluation order for pure nodes
-        resolver.compute_pure_evaluation_order(graph, metadata_provider)?;
+        cancellation.check()?;
+        resolver.compute_pure_evaluation_order(compact, metadata_provider, order, cancellation, progress)?;
+
+        // Phase 4: Record node-level dependency edges
+        cancellation.check()?;
+        resolver.map_dependencies(compact);
+        #[cfg(feature = "heap-stats")]
+        {
+            resolver.build_heap = heap.finish();
+        }
 
         Ok(resolver)
     }
@@ -158,17 +659,23 @@ impl DataResolver {
     ///
     /// Significantly faster for large graphs (5,000+ nodes) but has overhead
     /// for smaller graphs. Provides 1.5-2x speedup on large graphs with multiple cores.
+    /// Prefer [`build_auto`](Self::build_auto), which only uses it when it
+    /// pays off.
     ///
     /// # Thread Pool
     ///
-    /// Uses a pre-warmed thread pool from [`crate::parallel::init_thread_pool`].
+    /// Uses a pre-warmed thread pool from `crate::parallel::init_thread_pool`.
     /// If not initialized, a pool will be created automatically with some startup cost.
+    /// Without the `parallel` feature, this runs the sequential [`build`](Self::build).
     ///
     /// # Process
     ///
     /// 1. Maps data connections in parallel across multiple threads
     /// 2. Generates variable names in parallel
-    /// 3. Performs topological sort sequentially (not parallelizable)
+    /// 3. Performs a layered topological sort, processing each layer in parallel
+    ///
+    /// The evaluation order is deterministic: layers are emitted in order,
+    /// each sorted by node ID.
     ///
     /// # Errors
     ///
@@ -196,139 +703,435 @@ impl DataResolver {
     /// - Graph has 5,000+ nodes
     /// - Multiple CPU cores available
     /// - Maximum throughput needed
+    ///
+    /// [`build_auto`](Self::build_auto) checks the first two for you.
     pub fn build_parallel<P: NodeMetadataProvider + Sync>(
         graph: &GraphDescription,
         metadata_provider: &P,
     ) -> Result<Self, GraphyError> {
+        Self::build_parallel_with_profile(graph, metadata_provider, &RustProfile)
+    }
+
+    /// Builds a data resolver sequentially or in parallel, whichever suits
+    /// the graph.
+    ///
+    /// Uses [`build_parallel`](Self::build_parallel) when `threshold`
+    /// [selects](ParallelThreshold::select) it and [`build`](Self::build)
+    /// otherwise. Both are deterministic, but they order independent pure
+    /// nodes differently: the parallel build sorts each layer by node ID.
+    ///
+    /// # Errors
+    ///
+    /// See [`build`](Self::build).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::analysis::{DataResolver, ParallelThreshold};
+    /// use graphy::{GraphDescription, NodeRegistry};
+    ///
+    /// let graph = GraphDescription::new("empty");
+    /// let threshold = ParallelThreshold::new(10_000);
+    /// let resolver = DataResolver::build_auto(&graph, &NodeRegistry::new(), &threshold).unwrap();
+    /// assert!(resolver.get_pure_evaluation_order().is_empty());
+    /// ```
+    pub fn build_auto<P: NodeMetadataProvider + Sync>(
+        graph: &GraphDescription,
+        metadata_provider: &P,
+        threshold: &ParallelThreshold,
+    ) -> Result<Self, GraphyError> {
+        match threshold.select(graph) {
+            BuildStrategy::Parallel => Self::build_parallel(graph, metadata_provider),
+            BuildStrategy::Sequential => Self::build(graph, metadata_provider),
+        }
+    }
+
+    /// Parallel version of [`build_with_profile`](Self::build_with_profile).
+    pub fn build_parallel_with_profile<P: NodeMetadataProvider + Sync>(
+        graph: &GraphDescription,
+        metadata_provider: &P,
+        profile: &dyn LanguageProfile,
+    ) -> Result<Self, GraphyError> {
+        Self::build_parallel_with_cancellation(graph, metadata_provider, profile, &CancellationToken::new())
+    }
+
+    /// Parallel version of [`build_with_cancellation`](Self::build_with_cancellation).
+    ///
+    /// The token is checked between phases, by every worker, and for every
+    /// layer of the evaluation order.
+    ///
+    /// # Errors
+    ///
+    /// See [`build_with_cancellation`](Self::build_with_cancellation).
+    pub fn build_parallel_with_cancellation<P: NodeMetadataProvider + Sync>(
+        graph: &GraphDescription,
+        metadata_provider: &P,
+        profile: &dyn LanguageProfile,
+        cancellation: &CancellationToken,
+    ) -> Result<Self, GraphyError> {
+        Self::build_parallel_with_progress(graph, metadata_provider, profile, cancellation, &NoProgress)
+    }
+
+    /// Parallel version of [`build_with_progress`](Self::build_with_progress).
+    ///
+    /// Workers don't report progress: the [`PHASE_DATA_FLOW`] phase only
+    /// reports its start and end, and the [`PHASE_EVALUATION_ORDER`] phase
+    /// reports once per layer.
+    ///
+    /// # Errors
+    ///
+    /// See [`build_with_cancellation`](Self::build_with_cancellation).
+    pub fn build_parallel_with_progress<P: NodeMetadataProvider + Sync>(
+        graph: &GraphDescription,
+        metadata_provider: &P,
+        profile: &dyn LanguageProfile,
+        cancellation: &CancellationToken,
+        progress: &dyn ProgressSink,
+    ) -> Result<Self, GraphyError> {
+        #[cfg(feature = "parallel")]
+        {
+            Self::build_in_thread_pool(graph, metadata_provider, profile, cancellation, progress)
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            Self::build_with_progress(graph, metadata_provider, profile, cancellation, progress)
+        }
+    }
+
+    /// Parallel build on the shared thread pool
+    #[cfg(feature = "parallel")]
+    fn build_in_thread_pool<P: NodeMetadataProvider + Sync>(
+        graph: &GraphDescription,
+        metadata_provider: &P,
+        profile: &dyn LanguageProfile,
+        cancellation: &CancellationToken,
+        progress: &dyn ProgressSink,
+    ) -> Result<Self, GraphyError> {
+        cancellation.check()?;
+        graph.check_connection_nodes(ConnectionType::Data)?;
+        #[cfg(feature = "heap-stats")]
+        let heap = HeapScope::start();
+
         // Pre-allocate with estimated capacity for better performance
         let node_count = graph.nodes.len();
         let connection_count = graph.connections.len();
         
         let mut resolver = DataResolver {
+            symbols: SymbolTable::from_graph(graph),
             input_sources: FxHashMap::with_capacity_and_hasher(
                 connection_count * 2, 
                 Default::default()
             ),
+            multi_input_sources: FxHashMap::default(),
             result_variables: FxHashMap::with_capacity_and_hasher(
                 node_count, 
                 Default::default()
             ),
             pure_evaluation_order: Vec::with_capacity(node_count / 4), // Estimate ~25% pure nodes
+            event_param_variables: FxHashMap::default(),
+            error_variables: FxHashMap::default(),
+            dependencies: FxHashMap::default(),
+            dependents: FxHashMap::default(),
+            provider_version: metadata_provider.version(),
+            #[cfg(feature = "heap-stats")]
+            build_heap: None,
         };
 
+        let compact = CompactGraph::from_graph(graph);
+
         // Use the pre-warmed thread pool
         let pool = crate::parallel::get_thread_pool();
-        
+
         pool.install(|| {
             // Phase 1: Map all data connections (parallel)
-            resolver.map_data_connections_parallel(graph)?;
+            let mapping = PhaseProgress::start(progress, PHASE_DATA_FLOW, graph.nodes.len());
+            resolver.map_data_connections_parallel(graph, metadata_provider, cancellation)?;
+            mapping.finish();
 
             // Phase 2: Generate variable names (parallel)
-            resolver.generate_variable_names_parallel(graph);
+            cancellation.check()?;
+            resolver.generate_variable_names_parallel(graph, profile);
+
+            // Phase 3: Determine evaluation order for pure nodes (parallel layers)
+            cancellation.check()?;
+            resolver.compute_pure_evaluation_order_parallel(&compact, metadata_provider, cancellation, progress)?;
 
             Ok::<(), GraphyError>(())
         })?;
+        resolver.name_event_params(profile);
+        resolver.name_error_values(graph, metadata_provider, profile);
 
-        // Phase 3: Determine evaluation order for pure nodes (sequential)
-        resolver.compute_pure_evaluation_order(graph, metadata_provider)?;
+        // Phase 4: Record node-level dependency edges (sequential)
+        cancellation.check()?;
+        resolver.map_dependencies(&compact);
+        #[cfg(feature = "heap-stats")]
+        {
+            resolver.build_heap = heap.finish();
+        }
 
         Ok(resolver)
     }
 
     /// Map all data connections in the graph
-    fn map_data_connections(&mut self, graph: &GraphDescription) -> Result<(), GraphyError> {
+    fn map_data_connections<P: NodeMetadataProvider>(
+        &mut self,
+        graph: &GraphDescription,
+        metadata_provider: &P,
+        cancellation: &CancellationToken,
+        progress: &dyn ProgressSink,
+    ) -> Result<(), GraphyError> {
+        let mut progress = PhaseProgress::start(progress, PHASE_DATA_FLOW, graph.nodes.len());
         for connection in &graph.connections {
             if matches!(connection.connection_type, ConnectionType::Data) {
-                let key = (connection.target_node.clone(), connection.target_pin.clone());
-                let source = DataSource::Connection {
-                    source_node_id: connection.source_node.clone(),
-                    source_pin: connection.source_pin.clone(),
-                };
-
-                self.input_sources.insert(key, source);
+                let key = self.symbols.intern_pair(&connection.target_node, &connection.target_pin);
+                self.input_sources
+                    .insert(key, connection_source(graph, metadata_provider, connection));
             }
         }
 
         // For inputs not connected, check properties or use defaults
         for (node_id, node) in &graph.nodes {
+            cancellation.check()?;
+            let metadata = metadata_provider.metadata_for_node(node);
             for pin_instance in &node.inputs {
                 let pin_name = &pin_instance.id;
-                let key = (node_id.clone(), pin_name.clone());
+                let key = self.symbols.intern_pair(node_id, pin_name);
 
-                self.input_sources.entry(key).or_insert_with(|| {
+                if let Entry::Vacant(entry) = self.input_sources.entry(key) {
                     // Check if there's a property value
-                    if let Some(prop_value) = node.properties.get(pin_name) {
-                        DataSource::Constant(property_value_to_string(prop_value))
-                    } else {
-                        DataSource::Default
-                    }
-                });
+                    entry.insert(property_source(node, pin_instance, input_param(metadata, pin_name)));
+                }
             }
+            progress.advance();
         }
 
+        self.map_multi_input_sources(graph, metadata_provider);
+        self.map_undeclared_defaults(graph, metadata_provider);
+        progress.finish();
         Ok(())
     }
 
+    /// Map every source of inputs accepting several connections
+    fn map_multi_input_sources<P: NodeMetadataProvider>(&mut self, graph: &GraphDescription, metadata_provider: &P) {
+        for connection in &graph.connections {
+            if connection.connection_type != ConnectionType::Data {
+                continue;
+            }
+            let Some(target) = graph.nodes.get(&connection.target_node) else {
+                continue;
+            };
+            let multiplicity =
+                target.input_multiplicity(&connection.target_pin, metadata_provider.metadata_for_node(target));
+            if multiplicity.accepts_several() {
+                let key = self.symbols.intern_pair(&connection.target_node, &connection.target_pin);
+                self.multi_input_sources
+                    .entry(key)
+                    .or_default()
+                    .push(connection_source(graph, metadata_provider, connection));
+            }
+        }
+    }
+
+    /// Map metadata defaults of params the node instances don't declare as pins
+    fn map_undeclared_defaults<P: NodeMetadataProvider>(&mut self, graph: &GraphDescription, metadata_provider: &P) {
+        for (node_id, node) in &graph.nodes {
+            let Some(metadata) = metadata_provider.metadata_for_node(node) else {
+                continue;
+            };
+            if metadata.node_type == NodeTypes::event {
+                continue;
+            }
+
+            for param in &metadata.params {
+                let Some(default) = &param.default else {
+                    continue;
+                };
+                if node.inputs.iter().any(|pin| pin.id == param.name) {
+                    continue;
+                }
+                let key = self.symbols.intern_pair(node_id, &param.name);
+                if let Entry::Vacant(entry) = self.input_sources.entry(key) {
+                    entry.insert(DataSource::Constant(default.clone(), Some(TypeInfo::new(&param.param_type))));
+                }
+            }
+        }
+    }
+
+    /// Checks that every required input is connected.
+    ///
+    /// Required inputs ([`ParamInfo::required`]) must be fed by a
+    /// connection; a property or metadata default doesn't count. Params of
+    /// event nodes are outputs and are never checked.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::UnconnectedInput`] for the first unconnected
+    /// required input, by node ID and param order.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let resolver = DataResolver::build(&graph, &provider)?;
+    /// resolver.validate_required_inputs(&graph, &provider)?;
+    /// ```
+    pub fn validate_required_inputs<P: NodeMetadataProvider>(
+        &self,
+        graph: &GraphDescription,
+        metadata_provider: &P,
+    ) -> Result<(), GraphyError> {
+        match self.unconnected_required_inputs(graph, metadata_provider).into_iter().next() {
+            Some((node, pin)) => Err(GraphyError::UnconnectedInput { node, pin }),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns all unconnected required inputs as `(node_id, param)` pairs,
+    /// sorted by node ID and in param order.
+    ///
+    /// See [`validate_required_inputs`](Self::validate_required_inputs).
+    pub fn unconnected_required_inputs<P: NodeMetadataProvider>(
+        &self,
+        graph: &GraphDescription,
+        metadata_provider: &P,
+    ) -> Vec<(String, String)> {
+        let mut node_ids: Vec<&String> = graph.nodes.keys().collect();
+        node_ids.sort_unstable();
+
+        let mut missing = Vec::new();
+        for node_id in node_ids {
+            let node = &graph.nodes[node_id];
+            let Some(metadata) = metadata_provider.metadata_for_node(node) else {
+                continue;
+            };
+            if metadata.node_type == NodeTypes::event {
+                continue;
+            }
+
+            for param in metadata.params.iter().filter(|param| param.required) {
+                let connected = matches!(
+                    self.get_input_source(node_id, &param.name),
+                    Some(DataSource::Connection { .. } | DataSource::GraphInput { .. } | DataSource::EventParam { .. })
+                );
+                if !connected {
+                    missing.push((node_id.clone(), param.name.clone()));
+                }
+            }
+        }
+        missing
+    }
+
     /// Generate unique variable names for each node's result
-    fn generate_variable_names(&mut self, graph: &GraphDescription) {
+    fn generate_variable_names(&mut self, graph: &GraphDescription, profile: &dyn LanguageProfile) {
         for node_id in graph.nodes.keys() {
-            let var_name = format!("node_{}_result", sanitize_var_name(node_id));
+            let var_name = profile.result_variable_name(node_id);
             self.result_variables.insert(node_id.clone(), var_name);
         }
     }
 
+    /// Assign local variable names to event params read by other nodes
+    fn name_event_params(&mut self, profile: &dyn LanguageProfile) {
+        for source in self.input_sources.values().chain(self.multi_input_sources.values().flatten()) {
+            if let DataSource::EventParam { event_node, param } = source {
+                self.event_param_variables
+                    .entry(self.symbols.intern_pair(event_node, param))
+                    .or_insert_with(|| profile.sanitize_identifier(&format!("event_{}_{}", event_node, param)));
+            }
+        }
+    }
+
+    /// Assign variable names to the errors of fallible nodes read by other nodes
+    fn name_error_values<P: NodeMetadataProvider>(
+        &mut self,
+        graph: &GraphDescription,
+        metadata_provider: &P,
+        profile: &dyn LanguageProfile,
+    ) {
+        for source in self.input_sources.values().chain(self.multi_input_sources.values().flatten()) {
+            let DataSource::Connection { source_node_id, source_pin } = source else {
+                continue;
+            };
+            let is_fallible = graph
+                .nodes
+                .get(source_node_id)
+                .and_then(|node| metadata_provider.metadata_for_node(node))
+                .is_some_and(NodeMetadata::is_fallible);
+
+            if source_pin == ERROR_VALUE_PIN && is_fallible {
+                self.error_variables
+                    .entry(source_node_id.clone())
+                    .or_insert_with(|| profile.sanitize_identifier(&format!("node_{}_error", source_node_id)));
+            }
+        }
+    }
+
     /// Parallel version: Map data connections using rayon
-    fn map_data_connections_parallel(&mut self, graph: &GraphDescription) -> Result<(), GraphyError> {
+    #[cfg(feature = "parallel")]
+    fn map_data_connections_parallel<P: NodeMetadataProvider + Sync>(
+        &mut self,
+        graph: &GraphDescription,
+        metadata_provider: &P,
+        cancellation: &CancellationToken,
+    ) -> Result<(), GraphyError> {
+        // Names are interned up front so workers only read the table
+        let symbols = &self.symbols;
+        let key = |node_id: &str, pin_name: &str| {
+            symbols
+                .get_pair(node_id, pin_name)
+                .expect("graph names are interned before mapping")
+        };
+
         // Process data connections in parallel
         let data_sources: Vec<_> = graph.connections
             .par_iter()
             .filter(|c| matches!(c.connection_type, ConnectionType::Data))
             .map(|connection| {
-                let key = (connection.target_node.clone(), connection.target_pin.clone());
-                let source = DataSource::Connection {
-                    source_node_id: connection.source_node.clone(),
-                    source_pin: connection.source_pin.clone(),
-                };
-                (key, source)
+                let key = key(&connection.target_node, &connection.target_pin);
+                (key, connection_source(graph, metadata_provider, connection))
             })
             .collect();
 
         self.input_sources.extend(data_sources);
+        cancellation.check()?;
 
         // Process unconnected inputs in parallel
         let default_sources: Vec<_> = graph.nodes
             .par_iter()
-            .flat_map(|(node_id, node)| {
-                node.inputs
+            .map(|(node_id, node)| {
+                cancellation.check()?;
+                let metadata = metadata_provider.metadata_for_node(node);
+                Ok(node.inputs
                     .par_iter()
-                    .filter_map(|pin_instance| {
+                    .map(|pin_instance| {
                         let pin_name = &pin_instance.id;
-                        let key = (node_id.clone(), pin_name.clone());
-                        
-                        if let Some(prop_value) = node.properties.get(pin_name) {
-                            Some((key, DataSource::Constant(property_value_to_string(prop_value))))
-                        } else {
-                            Some((key, DataSource::Default))
-                        }
+                        let param = input_param(metadata, pin_name);
+                        (key(node_id, pin_name), property_source(node, pin_instance, param))
                     })
-                    .collect::<Vec<_>>()
+                    .collect::<Vec<_>>())
             })
+            .collect::<Result<Vec<_>, GraphyError>>()?
+            .into_iter()
+            .flatten()
             .collect();
 
         // Only insert defaults that don't exist
         for (key, source) in default_sources {
-            self.input_sources.entry(key).or_insert(source);
+            if let Entry::Vacant(entry) = self.input_sources.entry(key) {
+                entry.insert(source);
+            }
         }
-        
+
+        self.map_multi_input_sources(graph, metadata_provider);
+        self.map_undeclared_defaults(graph, metadata_provider);
         Ok(())
     }
 
     /// Parallel version: Generate variable names using rayon
-    fn generate_variable_names_parallel(&mut self, graph: &GraphDescription) {
+    #[cfg(feature = "parallel")]
+    fn generate_variable_names_parallel(&mut self, graph: &GraphDescription, profile: &dyn LanguageProfile) {
         let var_names: Vec<_> = graph.nodes
             .par_iter()
             .map(|(node_id, _node)| {
-                let var_name = format!("node_{}_result", sanitize_var_name(node_id));
+                let var_name = profile.result_variable_name(node_id);
                 (node_id.clone(), var_name)
             })
             .collect();
@@ -336,101 +1139,184 @@ impl DataResolver {
         self.result_variables.extend(var_names);
     }
 
+    /// Record which nodes feed which through data connections
+    fn map_dependencies(&mut self, compact: &CompactGraph<'_>) {
+        // Neighbours in connection order, each once
+        let unique_ids = |neighbors: &[u32]| {
+            let mut seen = FxHashSet::default();
+            neighbors
+                .iter()
+                .filter(|&&neighbor| seen.insert(neighbor))
+                .map(|&neighbor| compact.node_id(neighbor).to_string())
+                .collect::<Vec<_>>()
+        };
+
+        for index in compact.indices() {
+            let node_id = compact.node_id(index);
+
+            let sources = compact.predecessors(ConnectionType::Data, index);
+            if !sources.is_empty() {
+                self.dependencies.insert(node_id.to_string(), unique_ids(sources));
+            }
+            let targets = compact.successors(ConnectionType::Data, index);
+            if !targets.is_empty() {
+                self.dependents.insert(node_id.to_string(), unique_ids(targets));
+            }
+        }
+    }
+
     /// Compute evaluation order for pure nodes using topological sort
+    ///
+    /// Ready nodes are taken in `order`; the initial ones are queued in node
+    /// ID order, so the order is deterministic.
     fn compute_pure_evaluation_order<P: NodeMetadataProvider>(
         &mut self,
-        graph: &GraphDescription,
+        compact: &CompactGraph<'_>,
         metadata_provider: &P,
+        order: &EvaluationOrder,
+        cancellation: &CancellationToken,
+        progress: &dyn ProgressSink,
     ) -> Result<(), GraphyError> {
-        let node_count = graph.nodes.len();
-        
-        // Build dependency graph for pure nodes with pre-allocated capacity
-        let mut dependencies: FxHashMap<String, Vec<String>> = 
-            FxHashMap::with_capacity_and_hasher(node_count / 2, Default::default());
-        let mut pure_nodes: HashSet<String> = HashSet::with_capacity(node_count / 2);
+        let is_pure: Vec<bool> = compact
+            .indices()
+            .map(|index| compact.node(index).is_some_and(|node| super::is_pure_value_node(node, metadata_provider)))
+            .collect();
+        let pure_count = is_pure.iter().filter(|&&pure| pure).count();
+        let mut progress = PhaseProgress::start(progress, PHASE_EVALUATION_ORDER, pure_count);
 
-        // Identify pure nodes
-        for (node_id, node) in &graph.nodes {
-            if let Some(node_meta) = metadata_provider.get_node_metadata(&node.node_type) {
-                if node_meta.node_type == NodeTypes::pure && node_meta.return_type.is_some() {
-                    pure_nodes.insert(node_id.clone());
-                    dependencies.insert(node_id.clone(), Vec::new());
-                }
+        // One edge per data connection between pure nodes
+        let mut in_degree: Vec<usize> = compact
+            .indices()
+            .map(|index| {
+                compact
+                    .predecessors(ConnectionType::Data, index)
+                    .iter()
+                    .filter(|&&source| is_pure[source as usize])
+                    .count()
+            })
+            .collect();
+
+        // Topological sort using Kahn's algorithm
+        let mut ready = ReadyNodes::new(order.ranks(compact));
+        for index in compact.indices() {
+            if is_pure[index as usize] && in_degree[index as usize] == 0 {
+                ready.push(index);
             }
         }
 
-        // Build dependency edges
-        for connection in &graph.connections {
-            if matches!(connection.connection_type, ConnectionType::Data)
-                && pure_nodes.contains(&connection.target_node)
-                    && pure_nodes.contains(&connection.source_node)
-                {
-                    dependencies
-                        .entry(connection.target_node.clone())
-                        .or_default()
-                        .push(connection.source_node.clone());
-                }
-        }
+        while let Some(index) = ready.pop() {
+            cancellation.check()?;
+            self.pure_evaluation_order.push(compact.node_id(index).to_string());
+            progress.advance();
 
-        // Build reverse dependency map with pre-allocated capacity
-        let mut dependents: FxHashMap<String, Vec<String>> = 
-            FxHashMap::with_capacity_and_hasher(pure_nodes.len(), Default::default());
-        for (target, sources) in &dependencies {
-            for source in sources {
-                dependents
-                    .entry(source.clone())
-                    .or_default()
-                    .push(target.clone());
+            for &dependent in compact.successors(ConnectionType::Data, index) {
+                if !is_pure[dependent as usize] {
+                    continue;
+                }
+                in_degree[dependent as usize] -= 1;
+                if in_degree[dependent as usize] == 0 {
+                    ready.push(dependent);
+                }
             }
         }
 
-        // Topological sort using Kahn's algorithm
-        let mut in_degree: FxHashMap<String, usize> = 
-            FxHashMap::with_capacity_and_hasher(pure_nodes.len(), Default::default());
-        for node_id in &pure_nodes {
-            let num_deps = dependencies.get(node_id).map(|v| v.len()).unwrap_or(0);
-            in_degree.insert(node_id.clone(), num_deps);
+        // Check for cycles
+        if self.pure_evaluation_order.len() != pure_count {
+            return Self::cycle_error(compact, metadata_provider);
         }
 
-        let mut queue: VecDeque<String> = in_degree
-            .iter()
-            .filter(|(_, &deg)| deg == 0)
-            .map(|(id, _)| id.clone())
+        progress.finish();
+        Ok(())
+    }
+
+    /// Parallel version: Kahn's algorithm over layers of ready nodes
+    ///
+    /// Each layer's dependents are processed in parallel with atomic
+    /// in-degree decrements; nodes reaching zero form the next layer, which
+    /// is sorted by node ID to keep the order deterministic.
+    #[cfg(feature = "parallel")]
+    fn compute_pure_evaluation_order_parallel<P: NodeMetadataProvider + Sync>(
+        &mut self,
+        compact: &CompactGraph<'_>,
+        metadata_provider: &P,
+        cancellation: &CancellationToken,
+        progress: &dyn ProgressSink,
+    ) -> Result<(), GraphyError> {
+        let is_pure: Vec<bool> = compact
+            .indices()
+            .into_par_iter()
+            .map(|index| compact.node(index).is_some_and(|node| super::is_pure_value_node(node, metadata_provider)))
             .collect();
+        let pure_count = is_pure.iter().filter(|&&pure| pure).count();
+        let mut progress = PhaseProgress::start(progress, PHASE_EVALUATION_ORDER, pure_count);
 
-        while let Some(node_id) = queue.pop_front() {
-            self.pure_evaluation_order.push(node_id.clone());
-
-            if let Some(dependent_nodes) = dependents.get(&node_id) {
-                for dependent in dependent_nodes {
-                    if let Some(degree) = in_degree.get_mut(dependent) {
-                        *degree -= 1;
-                        if *degree == 0 {
-                            queue.push_back(dependent.clone());
-                        }
-                    }
-                }
-            }
+        // One edge per data connection between pure nodes, like the sequential sort
+        let in_degree: Vec<AtomicUsize> = compact
+            .indices()
+            .into_par_iter()
+            .map(|index| {
+                let count = compact
+                    .predecessors(ConnectionType::Data, index)
+                    .iter()
+                    .filter(|&&source| is_pure[source as usize])
+                    .count();
+                AtomicUsize::new(count)
+            })
+            .collect();
+
+        // Compact indices are in ID order, so sorting indices sorts IDs
+        let mut layer: Vec<u32> = compact
+            .indices()
+            .into_par_iter()
+            .filter(|&index| is_pure[index as usize] && in_degree[index as usize].load(Ordering::Relaxed) == 0)
+            .collect();
+
+        while !layer.is_empty() {
+            cancellation.check()?;
+            self.pure_evaluation_order
+                .extend(layer.iter().map(|&index| compact.node_id(index).to_string()));
+            progress.advance_by(layer.len());
+
+            // Exactly one decrement brings each node to zero, so no node is
+            // added twice
+            let mut next: Vec<u32> = layer
+                .par_iter()
+                .flat_map_iter(|&index| compact.successors(ConnectionType::Data, index).iter().copied())
+                .filter(|&dependent| {
+                    is_pure[dependent as usize]
+                        && in_degree[dependent as usize].fetch_sub(1, Ordering::AcqRel) == 1
+                })
+                .collect();
+            next.par_sort_unstable();
+            layer = next;
         }
 
-        // Check for cycles
-        if self.pure_evaluation_order.len() != pure_nodes.len() {
-            return Self::cycle_error();
+        if self.pure_evaluation_order.len() != pure_count {
+            return Self::cycle_error(compact, metadata_provider);
         }
 
+        progress.finish();
         Ok(())
     }
 
     /// Helper for cyclic dependency error (cold path)
+    ///
+    /// Re-runs cycle detection to report the nodes forming the first cycle.
     #[cold]
     #[inline(never)]
-    fn cycle_error() -> Result<(), GraphyError> {
-        Err(GraphyError::CyclicDependency)
+    fn cycle_error<P: NodeMetadataProvider>(compact: &CompactGraph<'_>, metadata_provider: &P) -> Result<(), GraphyError> {
+        let nodes = super::find_cycles_compact(compact, metadata_provider)
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        Err(GraphyError::CyclicDependency { nodes })
     }
 
     /// Retrieves the data source for a specific node input.
     ///
-    /// Returns `None` if the input doesn't exist or wasn't analyzed.
+    /// Returns `None` if the input doesn't exist or wasn't analyzed. An input
+    /// with several connections gives the last one; use
+    /// [`get_input_sources_multi`](Self::get_input_sources_multi) for all.
     ///
     /// # Example
     ///
@@ -441,8 +1327,14 @@ impl DataResolver {
     ///     Some(DataSource::Connection { source_node_id, source_pin }) => {
     ///         println!("Connected from {}.{}", source_node_id, source_pin);
     ///     }
-    ///     Some(DataSource::Constant(value)) => {
-    ///         println!("Constant value: {}", value);
+    ///     Some(DataSource::GraphInput { name }) => {
+    ///         println!("Graph parameter: {}", name);
+    ///     }
+    ///     Some(DataSource::EventParam { event_node, param }) => {
+    ///         println!("Event parameter: {}.{}", event_node, param);
+    ///     }
+    ///     Some(DataSource::Constant(value, _)) => {
+    ///         println!("Constant value: {:?}", value);
     ///     }
     ///     Some(DataSource::Default) => {
     ///         println!("Using default value");
@@ -454,7 +1346,48 @@ impl DataResolver {
     /// ```
     #[inline(always)]
     pub fn get_input_source(&self, node_id: &str, pin_name: &str) -> Option<&DataSource> {
-        self.input_sources.get(&(node_id.to_string(), pin_name.to_string()))
+        self.get_input_source_by_symbols(self.symbols.get_pair(node_id, pin_name)?)
+    }
+
+    /// Retrieves every data source of an input.
+    ///
+    /// For inputs accepting several connections (see [`PinMultiplicity`]),
+    /// returns all connected sources in connection order. Otherwise, and for
+    /// such inputs left unconnected, returns the one source
+    /// [`get_input_source`](Self::get_input_source) does, or none for an
+    /// unknown input.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let parts: Vec<String> = resolver
+    ///     .get_input_sources_multi("concat_1", "parts")
+    ///     .iter()
+    ///     .map(|source| render(source))
+    ///     .collect();
+    /// ```
+    pub fn get_input_sources_multi(&self, node_id: &str, pin_name: &str) -> &[DataSource] {
+        let Some(key) = self.symbols.get_pair(node_id, pin_name) else {
+            return &[];
+        };
+        match self.multi_input_sources.get(&key) {
+            Some(sources) => sources,
+            None => self.input_sources.get(&key).map(std::slice::from_ref).unwrap_or_default(),
+        }
+    }
+
+    /// Retrieves the data source for an input by interned `(node, pin)`.
+    ///
+    /// Symbols come from [`symbols`](Self::symbols).
+    #[inline(always)]
+    pub fn get_input_source_by_symbols(&self, key: (SymbolId, SymbolId)) -> Option<&DataSource> {
+        self.input_sources.get(&key)
+    }
+
+    /// Returns the interned node IDs and pin names of the analyzed graph.
+    #[inline]
+    pub fn symbols(&self) -> &SymbolTable {
+        &self.symbols
     }
 
     /// Retrieves the generated variable name for a node's result.
@@ -473,6 +1406,34 @@ impl DataResolver {
         self.result_variables.get(node_id)
     }
 
+    /// Retrieves the local variable bound to an event parameter.
+    ///
+    /// Only params read through a data connection get a variable; returns
+    /// `None` for unused params.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// // Inside the on_tick handler
+    /// if let Some(var_name) = resolver.get_event_param_variable("tick_1", "delta_time") {
+    ///     println!("let {} = delta_time;", var_name);
+    /// }
+    /// ```
+    #[inline]
+    pub fn get_event_param_variable(&self, event_node: &str, param: &str) -> Option<&String> {
+        self.event_param_variables
+            .get(&self.symbols.get_pair(event_node, param)?)
+    }
+
+    /// Retrieves the variable bound to the error of a fallible node.
+    ///
+    /// Only errors read through a data connection from the node's
+    /// [`ERROR_VALUE_PIN`] get a variable; returns `None` otherwise.
+    #[inline]
+    pub fn get_error_variable(&self, node_id: &str) -> Option<&String> {
+        self.error_variables.get(node_id)
+    }
+
     /// Returns the evaluation order for pure nodes.
     ///
     /// Pure nodes are sorted topologically so that dependencies are
@@ -490,32 +1451,283 @@ impl DataResolver {
     pub fn get_pure_evaluation_order(&self) -> &[String] {
         &self.pure_evaluation_order
     }
+
+    /// Returns the [version](NodeMetadataProvider::version) of the metadata
+    /// provider the resolver was built with.
+    #[inline]
+    pub fn provider_version(&self) -> Option<ProviderVersion> {
+        self.provider_version
+    }
+
+    /// Checks if the provider changed since the resolver was built.
+    ///
+    /// Stale resolvers may name nodes whose metadata changed (e.g. a removed
+    /// parameter) and should be rebuilt. Resolvers built with a provider that
+    /// doesn't track changes are never stale.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// if resolver.is_stale(&registry) {
+    ///     resolver = DataResolver::build(&graph, &registry)?;
+    /// }
+    /// ```
+    pub fn is_stale<P: NodeMetadataProvider + ?Sized>(&self, metadata_provider: &P) -> bool {
+        self.provider_version.is_some() && metadata_provider.version() != self.provider_version
+    }
+
+    /// Retrieves the data source feeding a declared graph output.
+    ///
+    /// Looks up the input pin named `output_name` on the graph's
+    /// `graph.output` pseudo-node. Returns `None` if the graph has no
+    /// output node or the pin doesn't exist.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// for output in &graph.graph_outputs {
+    ///     let source = resolver.get_graph_output_source(&graph, &output.name);
+    /// }
+    /// ```
+    pub fn get_graph_output_source(&self, graph: &GraphDescription, output_name: &str) -> Option<&DataSource> {
+        graph
+            .nodes
+            .values()
+            .filter(|node| node.is_graph_output())
+            .find_map(|node| self.get_input_source(&node.id, output_name))
+    }
+
+    /// Returns the nodes directly feeding a node's inputs.
+    ///
+    /// Each source node appears once, in connection order. Returns an empty
+    /// slice for unknown nodes or nodes without data inputs.
+    #[inline]
+    pub fn get_dependencies(&self, node_id: &str) -> &[String] {
+        self.dependencies.get(node_id).map(|v| v.as_slice()).unwrap_or(&[])
+    }
+
+    /// Returns the nodes directly reading a node's outputs.
+    ///
+    /// Each target node appears once, in connection order.
+    #[inline]
+    pub fn get_dependents(&self, node_id: &str) -> &[String] {
+        self.dependents.get(node_id).map(|v| v.as_slice()).unwrap_or(&[])
+    }
+
+    /// Returns every node a node's value depends on, directly or indirectly.
+    ///
+    /// Nodes are listed nearest first (breadth-first). The node itself is
+    /// never included, even if it is part of a cycle.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// // Everything that must be evaluated before previewing "mul_1"
+    /// let inputs = resolver.get_transitive_dependencies("mul_1");
+    /// ```
+    pub fn get_transitive_dependencies(&self, node_id: &str) -> Vec<String> {
+        transitive_closure(&self.dependencies, node_id)
+    }
+
+    /// Returns every node affected by a node's value, directly or indirectly.
+    ///
+    /// Nodes are listed nearest first (breadth-first). The node itself is
+    /// never included, even if it is part of a cycle.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// // Nodes to re-evaluate after the user edits "const_1"
+    /// let stale = resolver.get_transitive_dependents("const_1");
+    /// ```
+    pub fn get_transitive_dependents(&self, node_id: &str) -> Vec<String> {
+        transitive_closure(&self.dependents, node_id)
+    }
+
+    /// Rewires the results to reuse equivalent pure nodes.
+    ///
+    /// Inputs reading a duplicate found by [`CommonSubexpressions`] read the
+    /// node kept in its place instead, duplicates leave the pure evaluation
+    /// order, and their dependents move to the kept node. Code generated
+    /// from the resolver then evaluates each value once.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let mut resolver = DataResolver::build(&graph, &provider)?;
+    /// resolver.apply_common_subexpressions(&CommonSubexpressions::compute(&graph, &provider, &resolver));
+    /// ```
+    pub fn apply_common_subexpressions(&mut self, cse: &CommonSubexpressions) {
+        if cse.is_empty() {
+            return;
+        }
+
+        let multi_sources = self.multi_input_sources.values_mut().flatten();
+        for source in self.input_sources.values_mut().chain(multi_sources) {
+            if let DataSource::Connection { source_node_id, .. } = source {
+                if let Some(kept) = cse.replacement(source_node_id) {
+                    *source_node_id = kept.to_string();
+                }
+            }
+        }
+        self.pure_evaluation_order.retain(|node_id| !cse.is_duplicate(node_id));
+
+        // Neighbours renamed to the kept nodes, each once
+        let remap = |node_ids: &mut Vec<String>| {
+            let mut seen = FxHashSet::default();
+            let mapped: Vec<String> = node_ids
+                .iter()
+                .map(|node_id| cse.representative(node_id).to_string())
+                .filter(|node_id| seen.insert(node_id.clone()))
+                .collect();
+            *node_ids = mapped;
+        };
+
+        self.dependencies.retain(|node_id, _| !cse.is_duplicate(node_id));
+        self.dependencies.values_mut().for_each(remap);
+
+        for duplicate in cse.duplicates() {
+            if let Some(readers) = self.dependents.remove(duplicate) {
+                self.dependents
+                    .entry(cse.representative(duplicate).to_string())
+                    .or_default()
+                    .extend(readers);
+            }
+        }
+        self.dependents.values_mut().for_each(remap);
+    }
+
+    /// Returns the approximate memory used by the resolver's tables.
+    ///
+    /// Components are named after the tables (`"symbols"`,
+    /// `"input_sources"`, ...); `"resolver"` is the struct itself. See
+    /// [`utils::memory`](crate::utils::memory) for what is counted.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let stats = resolver.memory_stats();
+    /// println!("{}", stats);
+    /// assert!(stats.get("input_sources").is_some());
+    /// ```
+    pub fn memory_stats(&self) -> MemoryStats {
+        let mut stats = MemoryStats::new();
+        stats.add("resolver", std::mem::size_of::<Self>());
+        stats.add("symbols", self.symbols.heap_bytes());
+        stats.add("input_sources", self.input_sources.heap_bytes());
+        stats.add("multi_input_sources", self.multi_input_sources.heap_bytes());
+        stats.add("result_variables", self.result_variables.heap_bytes());
+        stats.add("event_param_variables", self.event_param_variables.heap_bytes());
+        stats.add("error_variables", self.error_variables.heap_bytes());
+        stats.add("pure_evaluation_order", self.pure_evaluation_order.heap_bytes());
+        stats.add("dependencies", self.dependencies.heap_bytes());
+        stats.add("dependents", self.dependents.heap_bytes());
+        stats
+    }
+
+    /// Returns the allocations made while building the resolver.
+    ///
+    /// `None` unless a [`TrackingAllocator`](crate::utils::heap_stats::TrackingAllocator)
+    /// is installed as the global allocator.
+    #[cfg(feature = "heap-stats")]
+    pub fn build_heap_usage(&self) -> Option<HeapUsage> {
+        self.build_heap
+    }
 }
 
-/// Convert a property value to a string representation
-fn property_value_to_string(value: &PropertyValue) -> String {
-    match value {
-        PropertyValue::String(s) => format!("\"{}\"", s.escape_default()),
-        PropertyValue::Number(n) => {
-            // Format number appropriately
-            if n.fract() == 0.0 {
-                format!("{}", *n as i64)
-            } else {
-                n.to_string()
+impl HeapSize for DataSource {
+    fn heap_bytes(&self) -> usize {
+        match self {
+            DataSource::Connection {
+                source_node_id,
+                source_pin,
+            } => source_node_id.heap_bytes() + source_pin.heap_bytes(),
+            DataSource::GraphInput { name } => name.heap_bytes(),
+            DataSource::EventParam { event_node, param } => event_node.heap_bytes() + param.heap_bytes(),
+            DataSource::Constant(value, value_type) => value.heap_bytes() + value_type.heap_bytes(),
+            DataSource::Default => 0,
+        }
+    }
+}
+
+/// Breadth-first closure over a node -> neighbours map, excluding the start
+fn transitive_closure(edges: &FxHashMap<String, Vec<String>>, start: &str) -> Vec<String> {
+    let mut visited: HashSet<&str> = HashSet::new();
+    visited.insert(start);
+
+    let mut result = Vec::new();
+    let mut queue: VecDeque<&str> = VecDeque::from([start]);
+
+    while let Some(node_id) = queue.pop_front() {
+        for next in edges.get(node_id).into_iter().flatten() {
+            if visited.insert(next) {
+                result.push(next.clone());
+                queue.push_back(next);
             }
         }
-        PropertyValue::Boolean(b) => b.to_string(),
-        PropertyValue::Vector2(x, y) => format!("({}, {})", x, y),
-        PropertyValue::Vector3(x, y, z) => format!("({}, {}, {})", x, y, z),
-        PropertyValue::Color(r, g, b, a) => format!("({}, {}, {}, {})", r, g, b, a),
+    }
+
+    result
+}
+
+/// Determine the data source for a data connection
+#[inline]
+fn connection_source<P: NodeMetadataProvider>(
+    graph: &GraphDescription,
+    metadata_provider: &P,
+    connection: &Connection,
+) -> DataSource {
+    let Some(source) = graph.nodes.get(&connection.source_node) else {
+        return DataSource::Connection {
+            source_node_id: connection.source_node.clone(),
+            source_pin: connection.source_pin.clone(),
+        };
+    };
+
+    let is_event_param = metadata_provider
+        .metadata_for_node(source)
+        .is_some_and(|metadata| metadata.event_param(&connection.source_pin).is_some());
+
+    if source.is_graph_input() {
+        DataSource::GraphInput {
+            name: connection.source_pin.clone(),
+        }
+    } else if is_event_param {
+        DataSource::EventParam {
+            event_node: connection.source_node.clone(),
+            param: connection.source_pin.clone(),
+        }
+    } else {
+        DataSource::Connection {
+            source_node_id: connection.source_node.clone(),
+            source_pin: connection.source_pin.clone(),
+        }
+    }
+}
+
+/// Source for an unconnected input: the node's property, the metadata default, or the type default
+fn property_source(node: &NodeInstance, pin: &PinInstance, param: Option<&ParamInfo>) -> DataSource {
+    match node
+        .properties
+        .get(&pin.id)
+        .or_else(|| param.and_then(|param| param.default.as_ref()))
+    {
+        Some(prop_value) => {
+            let value_type = match param {
+                Some(param) => Some(TypeInfo::new(&param.param_type)),
+                None => pin.pin.data_type.type_string().map(TypeInfo::new),
+            };
+            DataSource::Constant(prop_value.clone(), value_type)
+        }
+        None => DataSource::Default,
     }
 }
 
-/// Sanitize a string to be a valid variable name
-fn sanitize_var_name(name: &str) -> String {
-    name.chars()
-        .map(|c| if c.is_alphanumeric() || c == '_' { c } else { '_' })
-        .collect()
+/// The metadata param behind an input pin (event params are outputs)
+fn input_param<'m>(metadata: Option<&'m NodeMetadata>, pin_name: &str) -> Option<&'m ParamInfo> {
+    metadata
+        .filter(|metadata| metadata.node_type != NodeTypes::event)
+        .and_then(|metadata| metadata.params.iter().find(|param| param.name == pin_name))
 }
 
 #[cfg(test)]
@@ -564,7 +1776,7 @@ mod tests {
         let a_source = resolver.get_input_source("add_1", "a").unwrap();
         let b_source = resolver.get_input_source("add_1", "b").unwrap();
 
-        assert!(matches!(a_source, DataSource::Constant(_)));
-        assert!(matches!(b_source, DataSource::Constant(_)));
+        assert!(matches!(a_source, DataSource::Constant(_, Some(value_type)) if value_type.type_string == "i64"));
+        assert!(matches!(b_source, DataSource::Constant(..)));
     }
 }
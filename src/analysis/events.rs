@@ -0,0 +1,195 @@
+//! # Event Analysis
+//!
+//! Enumerates the entry points of a graph.
+//!
+//! Every event node becomes an [`EventEntry`]: its type, the params its
+//! handler receives, and the nodes it runs, i.e. the nodes on its execution
+//! flow and the nodes they read from (directly or transitively), like
+//! [`AsyncAnalysis`](super::AsyncAnalysis) counts them. Hosts use it to
+//! stitch the generated handlers together, e.g. with an
+//! [`EventDispatcher`](crate::generation::EventDispatcher).
+//!
+//! # Example
+//!
+//! ```ignore
+//! let events = EventAnalysis::compute(&graph, &provider, &exec_routing, &data_resolver);
+//! for event in events.events() {
+//!     println!("{} ({}) runs {} nodes", event.node_id, event.node_type, event.nodes.len());
+//! }
+//! ```
+
+use super::{AnalysisContext, AnalysisPass, DataResolver, ExecutionRouting};
+use crate::core::{GraphDescription, NodeMetadataProvider, NodeTypes, ParamInfo};
+use crate::GraphyError;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+/// One event node and the part of the graph it runs.
+#[derive(Debug, Clone)]
+pub struct EventEntry {
+    /// ID of the event node
+    pub node_id: String,
+
+    /// Type of the event node
+    pub node_type: String,
+
+    /// Params the handler receives, from the event's metadata
+    pub params: Vec<ParamInfo>,
+
+    /// Nodes the event runs, the event itself included, sorted by ID
+    pub nodes: Vec<String>,
+
+    /// Whether the event runs async nodes, so its handler is async
+    pub is_async: bool,
+}
+
+impl EventEntry {
+    /// Checks if the event runs a node.
+    #[inline]
+    pub fn runs(&self, node_id: &str) -> bool {
+        self.nodes.binary_search_by(|id| id.as_str().cmp(node_id)).is_ok()
+    }
+}
+
+/// The event nodes of a graph, in ID order (the order they're generated in).
+#[derive(Debug, Clone, Default)]
+pub struct EventAnalysis {
+    events: Vec<EventEntry>,
+}
+
+impl EventAnalysis {
+    /// Finds the event nodes of an analyzed graph and the nodes each runs.
+    ///
+    /// Nodes whose type is unknown to the metadata provider are never
+    /// treated as events.
+    pub fn compute<P: NodeMetadataProvider>(
+        graph: &GraphDescription,
+        metadata_provider: &P,
+        exec_routing: &ExecutionRouting,
+        data_resolver: &DataResolver,
+    ) -> Self {
+        let mut events: Vec<EventEntry> = graph
+            .nodes
+            .values()
+            .filter_map(|node| {
+                let metadata = metadata_provider
+                    .metadata_for_node(node)
+                    .filter(|metadata| metadata.node_type == NodeTypes::event)?;
+
+                let mut used: FxHashSet<String> = FxHashSet::default();
+                for exec_node in exec_routing.reachable_nodes(&node.id) {
+                    used.extend(data_resolver.get_transitive_dependencies(&exec_node));
+                    used.insert(exec_node);
+                }
+                used.insert(node.id.clone());
+
+                let mut nodes: Vec<String> = used.into_iter().collect();
+                nodes.sort_unstable();
+                let is_async = nodes.iter().any(|node_id| {
+                    graph
+                        .nodes
+                        .get(node_id)
+                        .and_then(|node| metadata_provider.metadata_for_node(node))
+                        .is_some_and(|metadata| metadata.is_async)
+                });
+
+                Some(EventEntry {
+                    node_id: node.id.clone(),
+                    node_type: node.node_type.clone(),
+                    params: metadata.params.clone(),
+                    nodes,
+                    is_async,
+                })
+            })
+            .collect();
+        events.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+
+        tracing::debug!("[EVENTS] Found {} event(s)", events.len());
+        EventAnalysis { events }
+    }
+
+    /// Returns the events, in ID order.
+    #[inline]
+    pub fn events(&self) -> &[EventEntry] {
+        &self.events
+    }
+
+    /// Returns the entry of an event node.
+    pub fn get(&self, node_id: &str) -> Option<&EventEntry> {
+        self.events
+            .binary_search_by(|event| event.node_id.as_str().cmp(node_id))
+            .ok()
+            .map(|index| &self.events[index])
+    }
+
+    /// Returns the events running a node, in ID order.
+    pub fn events_running(&self, node_id: &str) -> Vec<&str> {
+        self.events
+            .iter()
+            .filter(|event| event.runs(node_id))
+            .map(|event| event.node_id.as_str())
+            .collect()
+    }
+
+    /// Returns the nodes run by more than one event, sorted by ID.
+    ///
+    /// Their code is generated once per event, unless the host shares it.
+    pub fn shared_nodes(&self) -> Vec<String> {
+        let mut counts: FxHashMap<&str, usize> = FxHashMap::default();
+        for node_id in self.events.iter().flat_map(|event| &event.nodes) {
+            *counts.entry(node_id.as_str()).or_default() += 1;
+        }
+        let mut shared: Vec<String> = counts
+            .into_iter()
+            .filter(|&(_, count)| count > 1)
+            .map(|(node_id, _)| node_id.to_string())
+            .collect();
+        shared.sort_unstable();
+        shared
+    }
+
+    /// Returns the number of events.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Checks if the graph has no events.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+/// Analysis pass that computes an [`EventAnalysis`].
+///
+/// Reads the [`DataResolver`] and [`ExecutionRouting`] of earlier
+/// [`DataFlowPass`](super::DataFlowPass) and
+/// [`ExecutionRoutingPass`](super::ExecutionRoutingPass) runs.
+pub struct EventAnalysisPass<'p, P: NodeMetadataProvider> {
+    metadata_provider: &'p P,
+}
+
+impl<'p, P: NodeMetadataProvider> EventAnalysisPass<'p, P> {
+    /// Creates an event analysis pass using the given metadata provider.
+    #[inline]
+    pub fn new(metadata_provider: &'p P) -> Self {
+        Self { metadata_provider }
+    }
+}
+
+impl<P: NodeMetadataProvider> AnalysisPass for EventAnalysisPass<'_, P> {
+    fn name(&self) -> &str {
+        "events"
+    }
+
+    fn run(&self, graph: &GraphDescription, ctx: &mut AnalysisContext) -> Result<(), GraphyError> {
+        let (Some(data_resolver), Some(exec_routing)) = (ctx.get::<DataResolver>(), ctx.get::<ExecutionRouting>()) else {
+            return Err(GraphyError::Custom(
+                "The events pass requires the data_flow and exec_routing passes to run first".to_string(),
+            ));
+        };
+        let events = EventAnalysis::compute(graph, self.metadata_provider, exec_routing, data_resolver);
+        ctx.insert(events);
+        Ok(())
+    }
+}
@@ -6,12 +6,85 @@
 //! execute after others. Essential for generating proper control flow in
 //! the compiled output.
 //!
+//! # Loops
+//!
+//! Execution flow may contain cycles (e.g. a "while" node whose body loops
+//! back to it). The routing table detects these as strongly connected
+//! components and classifies the edges that close each cycle as
+//! **back-edges**, so code generators can emit `loop`/`while` constructs
+//! instead of recursing forever.
+//!
+//! # Queries
+//!
+//! Besides forward routing, the table keeps a reverse map of incoming edges
+//! and predecessors, so generators can tell a node reached from several
+//! places ([`incoming_exec_count`](ExecutionRouting::incoming_exec_count))
+//! without scanning the connections, and answers path questions: which paths an event can take
+//! ([`trace_from`](ExecutionRouting::trace_from)), whether one node can run
+//! after another ([`is_reachable`](ExecutionRouting::is_reachable)), and which
+//! nodes no event reaches
+//! ([`find_unreachable_nodes`](ExecutionRouting::find_unreachable_nodes)).
+//!
 //! # Performance
 //!
-//! Uses `FxHashMap` for faster routing table lookups.
+//! Uses `FxHashMap` for faster routing table lookups. Routes are keyed by
+//! interned `(node, pin)` [`SymbolId`]s, so building the table doesn't clone
+//! names and lookups don't allocate.
+
+use super::{CompactGraph, SymbolId, SymbolTable};
+use crate::core::{GraphDescription, ConnectionType, ERROR_EXEC_PIN};
+use crate::utils::{logging, NoProgress, PhaseProgress, ProgressSink, PHASE_EXEC_ROUTING};
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// A single execution edge (source output pin -> target node).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ExecEdge {
+    /// ID of the source node
+    pub source_node: String,
+
+    /// Execution output pin on the source node
+    pub source_pin: String,
+
+    /// ID of the target node
+    pub target_node: String,
+}
+
+/// One execution path from a start node to where the flow ends.
+///
+/// `nodes[i]` continues to `nodes[i + 1]` through output pin `pins[i]`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecPath {
+    /// Nodes in execution order, starting with the traced node
+    pub nodes: Vec<String>,
+
+    /// Output pin taken from each node (one fewer than `nodes`)
+    pub pins: Vec<String>,
+
+    /// Loop header the path jumps back to, if it ends on a back-edge
+    pub loops_back_to: Option<String>,
+}
+
+impl ExecPath {
+    /// Returns the first node of the path, or `None` if it is empty.
+    #[inline]
+    pub fn start(&self) -> Option<&str> {
+        self.nodes.first().map(String::as_str)
+    }
+
+    /// Returns the last node of the path, or `None` if it is empty.
+    #[inline]
+    pub fn end(&self) -> Option<&str> {
+        self.nodes.last().map(String::as_str)
+    }
 
-use crate::core::{GraphDescription, ConnectionType};
-use rustc_hash::FxHashMap;
+    /// Checks if the path passes through a node.
+    #[inline]
+    pub fn contains(&self, node_id: &str) -> bool {
+        self.nodes.iter().any(|id| id == node_id)
+    }
+}
 
 /// Execution routing table.
 ///
@@ -21,8 +94,29 @@ use rustc_hash::FxHashMap;
 ///
 /// Uses `FxHashMap` internally for ~2x faster lookups than standard HashMap.
 pub struct ExecutionRouting {
+    /// Interned node IDs and pin names of the graph
+    symbols: SymbolTable,
+
     /// Maps (source_node, output_pin) -> Vec of target nodes
-    routes: FxHashMap<(String, String), Vec<String>>,
+    routes: FxHashMap<(SymbolId, SymbolId), Vec<String>>,
+
+    /// Edges that close an execution cycle (target is a loop header)
+    back_edges: Vec<ExecEdge>,
+
+    /// Strongly connected components with cycles, each sorted by node ID
+    loops: Vec<Vec<String>>,
+
+    /// Maps node_id -> index into `loops`
+    node_loops: FxHashMap<String, usize>,
+
+    /// Maps node_id -> execution edges leaving it, in connection order
+    outgoing: FxHashMap<String, Vec<ExecEdge>>,
+
+    /// Maps node_id -> execution edges entering it, in connection order
+    incoming: FxHashMap<String, Vec<ExecEdge>>,
+
+    /// Maps node_id -> nodes with an execution edge to it (deduplicated, connection order)
+    predecessors: FxHashMap<String, Vec<String>>,
 }
 
 impl ExecutionRouting {
@@ -42,30 +136,94 @@ impl ExecutionRouting {
     /// let next_nodes = routing.get_connected_nodes("start", "exec");
     /// ```
     pub fn build_from_graph(graph: &GraphDescription) -> Self {
+        Self::build_from_compact(&CompactGraph::from_graph(graph))
+    }
+
+    /// Builds the routing table from a compact graph.
+    ///
+    /// See [`build_from_graph`](Self::build_from_graph).
+    pub fn build_from_compact(compact: &CompactGraph<'_>) -> Self {
+        Self::build_from_compact_with_progress(compact, &NoProgress)
+    }
+
+    /// Builds the routing table from a compact graph, reporting progress.
+    ///
+    /// Reports the [`PHASE_EXEC_ROUTING`] phase over all connections.
+    pub fn build_from_compact_with_progress(compact: &CompactGraph<'_>, progress: &dyn ProgressSink) -> Self {
+        let graph = compact.graph();
+        let mut progress = PhaseProgress::start(progress, PHASE_EXEC_ROUTING, graph.connections.len());
+
         // Pre-allocate with estimated capacity
         let connection_count = graph.connections.len();
-        let mut routes: FxHashMap<(String, String), Vec<String>> = 
+        let mut symbols = SymbolTable::from_graph(graph);
+        let mut routes: FxHashMap<(SymbolId, SymbolId), Vec<String>> =
             FxHashMap::with_capacity_and_hasher(connection_count / 2, Default::default());
+        let mut outgoing: FxHashMap<String, Vec<ExecEdge>> = FxHashMap::default();
+        let mut incoming: FxHashMap<String, Vec<ExecEdge>> = FxHashMap::default();
+        let mut predecessors: FxHashMap<String, Vec<String>> = FxHashMap::default();
 
         for connection in &graph.connections {
             if matches!(connection.connection_type, ConnectionType::Execution) {
-                let key = (
-                    connection.source_node.clone(),
-                    connection.source_pin.clone(),
-                );
+                let key = symbols.intern_pair(&connection.source_node, &connection.source_pin);
                 routes
                     .entry(key)
                     .or_default()
                     .push(connection.target_node.clone());
+
+                let edge = ExecEdge {
+                    source_node: connection.source_node.clone(),
+                    source_pin: connection.source_pin.clone(),
+                    target_node: connection.target_node.clone(),
+                };
+                let sources = predecessors.entry(edge.target_node.clone()).or_default();
+                if !sources.contains(&edge.source_node) {
+                    sources.push(edge.source_node.clone());
+                }
+                outgoing.entry(edge.source_node.clone()).or_default().push(edge.clone());
+                incoming.entry(edge.target_node.clone()).or_default().push(edge);
             }
+            progress.advance();
         }
 
-        tracing::info!("[ROUTING] Built execution routing table with {} routes", routes.len());
-        for ((node_id, pin_name), targets) in &routes {
-            tracing::info!("[ROUTING]   ({}, {}) -> {:?}", node_id, pin_name, targets);
+        tracing::debug!("[ROUTING] Built execution routing table with {} routes", routes.len());
+        if logging::is_verbose() {
+            for ((node_id, pin_name), targets) in &routes {
+                tracing::trace!(
+                    "[ROUTING]   ({}, {}) -> {:?}",
+                    symbols.resolve(*node_id),
+                    symbols.resolve(*pin_name),
+                    targets
+                );
+            }
+        }
+
+        let (back_edges, loops) = analyze_loops(compact);
+        let mut node_loops = FxHashMap::default();
+        for (index, members) in loops.iter().enumerate() {
+            for node_id in members {
+                node_loops.insert(node_id.clone(), index);
+            }
         }
 
-        ExecutionRouting { routes }
+        if !loops.is_empty() {
+            tracing::debug!(
+                "[ROUTING] Found {} execution loops with {} back-edges",
+                loops.len(),
+                back_edges.len()
+            );
+        }
+
+        progress.finish();
+        ExecutionRouting {
+            symbols,
+            routes,
+            back_edges,
+            loops,
+            node_loops,
+            outgoing,
+            incoming,
+            predecessors,
+        }
     }
 
     /// Retrieves all nodes connected to a specific execution output pin.
@@ -77,28 +235,369 @@ impl ExecutionRouting {
     /// This is an O(1) lookup thanks to hash table storage.
     #[inline(always)]
     pub fn get_connected_nodes(&self, node_id: &str, output_pin: &str) -> &[String] {
-        self.routes
-            .get(&(node_id.to_string(), output_pin.to_string()))
-            .map(|v| v.as_slice())
-            .unwrap_or(&[])
+        self.symbols
+            .get_pair(node_id, output_pin)
+            .map_or(&[], |key| self.get_connected_nodes_by_symbols(key))
+    }
+
+    /// Retrieves the nodes connected to an output pin by interned `(node, pin)`.
+    ///
+    /// Symbols come from [`symbols`](Self::symbols).
+    #[inline(always)]
+    pub fn get_connected_nodes_by_symbols(&self, key: (SymbolId, SymbolId)) -> &[String] {
+        self.routes.get(&key).map(|v| v.as_slice()).unwrap_or(&[])
+    }
+
+    /// Returns the interned node IDs and pin names of the routed graph.
+    #[inline]
+    pub fn symbols(&self) -> &SymbolTable {
+        &self.symbols
     }
 
     /// Checks if a node has any outgoing execution connections.
     #[inline(always)]
     pub fn has_execution_outputs(&self, node_id: &str) -> bool {
-        self.routes.keys().any(|(id, _)| id == node_id)
+        self.outgoing.contains_key(node_id)
     }
 
     /// Returns all execution output pin names for a node.
     ///
     /// Useful for iterating over all branches in control flow nodes.
     pub fn get_output_pins(&self, node_id: &str) -> Vec<String> {
+        let Some(node) = self.symbols.get(node_id) else {
+            return Vec::new();
+        };
         self.routes
             .keys()
-            .filter(|(id, _)| id == node_id)
-            .map(|(_, pin)| pin.clone())
+            .filter(|(id, _)| *id == node)
+            .map(|(_, pin)| self.symbols.resolve(*pin).to_string())
             .collect()
     }
+
+    /// Checks if the [`ERROR_EXEC_PIN`] of a node leads anywhere.
+    ///
+    /// Failures of fallible nodes without an error flow are ignored by the
+    /// generated code.
+    pub fn has_error_flow(&self, node_id: &str) -> bool {
+        !self.get_connected_nodes(node_id, ERROR_EXEC_PIN).is_empty()
+    }
+
+    /// Returns all back-edges in the execution graph.
+    ///
+    /// A back-edge closes a cycle: following it returns to a node that is
+    /// already being executed higher up in the flow (the loop header).
+    /// Code generators should emit a `continue` (or equivalent) for these
+    /// edges rather than generating the target again.
+    #[inline]
+    pub fn get_back_edges(&self) -> &[ExecEdge] {
+        &self.back_edges
+    }
+
+    /// Checks if the edge `(node_id, output_pin) -> target_node` is a back-edge.
+    pub fn is_back_edge(&self, node_id: &str, output_pin: &str, target_node: &str) -> bool {
+        self.back_edges.iter().any(|edge| {
+            edge.source_node == node_id && edge.source_pin == output_pin && edge.target_node == target_node
+        })
+    }
+
+    /// Returns targets of an execution output pin, excluding back-edges.
+    ///
+    /// Following only forward edges from any node always terminates.
+    pub fn get_forward_connected_nodes(&self, node_id: &str, output_pin: &str) -> Vec<&String> {
+        self.get_connected_nodes(node_id, output_pin)
+            .iter()
+            .filter(|target| !self.is_back_edge(node_id, output_pin, target))
+            .collect()
+    }
+
+    /// Checks if a node is the target of any back-edge.
+    pub fn is_loop_header(&self, node_id: &str) -> bool {
+        self.back_edges.iter().any(|edge| edge.target_node == node_id)
+    }
+
+    /// Returns all execution loops (strongly connected components with a cycle).
+    ///
+    /// Each loop lists its member node IDs in sorted order.
+    #[inline]
+    pub fn get_loops(&self) -> &[Vec<String>] {
+        &self.loops
+    }
+
+    /// Returns the members of the loop containing a node, if any.
+    pub fn get_loop(&self, node_id: &str) -> Option<&[String]> {
+        self.node_loops
+            .get(node_id)
+            .map(|&index| self.loops[index].as_slice())
+    }
+
+    /// Checks if a node is part of an execution loop.
+    #[inline]
+    pub fn is_in_loop(&self, node_id: &str) -> bool {
+        self.node_loops.contains_key(node_id)
+    }
+
+    /// Returns all execution edges entering a node, in connection order.
+    #[inline]
+    pub fn get_incoming(&self, node_id: &str) -> &[ExecEdge] {
+        self.incoming.get(node_id).map(|v| v.as_slice()).unwrap_or(&[])
+    }
+
+    /// Returns the nodes with an execution edge to a node.
+    ///
+    /// Each predecessor is listed once, in the order of its first edge, even
+    /// when several of its output pins lead to the node. Loop bodies
+    /// jumping back to their header count as predecessors of the header.
+    #[inline]
+    pub fn get_predecessors(&self, node_id: &str) -> &[String] {
+        self.predecessors.get(node_id).map(|v| v.as_slice()).unwrap_or(&[])
+    }
+
+    /// Returns the number of execution edges entering a node.
+    ///
+    /// A node entered more than once is reached by several execution
+    /// paths, so generators may emit it as a shared function instead of
+    /// inline. Back-edges are counted; subtract the ones
+    /// [`is_back_edge`](Self::is_back_edge) reports to get the forward
+    /// entries only.
+    #[inline]
+    pub fn incoming_exec_count(&self, node_id: &str) -> usize {
+        self.get_incoming(node_id).len()
+    }
+
+    /// Returns all execution edges leaving a node, in connection order.
+    #[inline]
+    pub fn get_outgoing(&self, node_id: &str) -> &[ExecEdge] {
+        self.outgoing.get(node_id).map(|v| v.as_slice()).unwrap_or(&[])
+    }
+
+    /// Enumerates every execution path starting at a node.
+    ///
+    /// Paths follow forward edges until a node without execution outputs,
+    /// or stop at a back-edge (recorded in [`ExecPath::loops_back_to`]), so
+    /// each loop is traversed at most once per path. Paths are returned in
+    /// depth-first order following connection order.
+    ///
+    /// The number of paths grows exponentially with sequential branches; use
+    /// [`is_reachable`](Self::is_reachable) for simple reachability checks.
+    pub fn trace_from(&self, start_node: &str) -> Vec<ExecPath> {
+        let mut paths = Vec::new();
+        let mut nodes = vec![start_node.to_string()];
+        let mut pins: Vec<String> = Vec::new();
+
+        // Call stack of (node, next outgoing edge index)
+        let mut call_stack: Vec<(&str, usize)> = vec![(start_node, 0)];
+
+        while let Some((node_id, next)) = call_stack.last_mut() {
+            let edges = self.get_outgoing(node_id);
+
+            if edges.is_empty() {
+                paths.push(ExecPath {
+                    nodes: nodes.clone(),
+                    pins: pins.clone(),
+                    loops_back_to: None,
+                });
+            } else if let Some(edge) = edges.get(*next) {
+                *next += 1;
+
+                if self.is_back_edge(&edge.source_node, &edge.source_pin, &edge.target_node) {
+                    let mut path_pins = pins.clone();
+                    path_pins.push(edge.source_pin.clone());
+                    paths.push(ExecPath {
+                        nodes: nodes.clone(),
+                        pins: path_pins,
+                        loops_back_to: Some(edge.target_node.clone()),
+                    });
+                } else {
+                    nodes.push(edge.target_node.clone());
+                    pins.push(edge.source_pin.clone());
+                    call_stack.push((&edge.target_node, 0));
+                }
+                continue;
+            }
+
+            call_stack.pop();
+            nodes.pop();
+            pins.pop();
+        }
+
+        paths
+    }
+
+    /// Checks if execution can flow from `from` to `to` (including through loops).
+    ///
+    /// A node is always reachable from itself.
+    pub fn is_reachable(&self, from: &str, to: &str) -> bool {
+        if from == to {
+            return true;
+        }
+        self.reachable_from([from]).contains(to)
+    }
+
+    /// Returns every node execution can reach from `start`, including `start`.
+    ///
+    /// The result is sorted by node ID.
+    pub fn reachable_nodes(&self, start: &str) -> Vec<String> {
+        let mut reachable: Vec<String> = self.reachable_from([start]).into_iter().map(str::to_string).collect();
+        reachable.sort_unstable();
+        reachable
+    }
+
+    /// Returns nodes in the execution flow that none of the given events reach.
+    ///
+    /// Only nodes with at least one execution connection are considered; use
+    /// [`Reachability`](super::Reachability) to include isolated nodes.
+    /// The result is sorted by node ID.
+    pub fn find_unreachable_nodes<'e>(&self, events: impl IntoIterator<Item = &'e str>) -> Vec<String> {
+        let reachable = self.reachable_from(events);
+
+        let mut unreachable: Vec<String> = self
+            .outgoing
+            .keys()
+            .chain(self.incoming.keys())
+            .filter(|id| !reachable.contains(id.as_str()))
+            .cloned()
+            .collect();
+        unreachable.sort_unstable();
+        unreachable.dedup();
+        unreachable
+    }
+
+    /// Breadth-first search over all execution edges
+    fn reachable_from<'s, 'e>(&'s self, roots: impl IntoIterator<Item = &'e str>) -> FxHashSet<&'s str>
+    where
+        'e: 's,
+    {
+        let mut visited: FxHashSet<&str> = FxHashSet::default();
+        let mut queue: VecDeque<&str> = VecDeque::new();
+
+        for root in roots {
+            if visited.insert(root) {
+                queue.push_back(root);
+            }
+        }
+
+        while let Some(node_id) = queue.pop_front() {
+            for edge in self.get_outgoing(node_id) {
+                if visited.insert(&edge.target_node) {
+                    queue.push_back(&edge.target_node);
+                }
+            }
+        }
+
+        visited
+    }
+}
+
+/// Find back-edges and cyclic strongly connected components.
+///
+/// Runs an iterative Tarjan's algorithm (stack-safe for long chains) that
+/// also classifies DFS back-edges. Traversal starts from nodes without
+/// incoming execution edges, in sorted order, so results are deterministic.
+fn analyze_loops(compact: &CompactGraph<'_>) -> (Vec<ExecEdge>, Vec<Vec<String>>) {
+    const EXEC: ConnectionType = ConnectionType::Execution;
+
+    if compact.edge_count(EXEC) == 0 {
+        return (Vec::new(), Vec::new());
+    }
+
+    let node_count = compact.node_count();
+    let has_incoming = |v: usize| !compact.predecessors(EXEC, v as u32).is_empty();
+    let has_outgoing = |v: usize| !compact.successors(EXEC, v as u32).is_empty();
+
+    // Only nodes that take part in execution flow, indices are in ID order
+    let starts = (0..node_count)
+        .filter(|&v| !has_incoming(v) && has_outgoing(v))
+        .chain((0..node_count).filter(|&v| has_incoming(v)));
+
+    const UNVISITED: usize = usize::MAX;
+    let mut order = vec![UNVISITED; node_count];
+    let mut low = vec![0usize; node_count];
+    let mut on_scc_stack = vec![false; node_count];
+    let mut on_path = vec![false; node_count];
+    let mut scc_stack: Vec<usize> = Vec::new();
+    let mut counter = 0usize;
+
+    let mut back_edges = Vec::new();
+    let mut loops = Vec::new();
+
+    for start in starts {
+        if order[start] != UNVISITED {
+            continue;
+        }
+
+        order[start] = counter;
+        low[start] = counter;
+        counter += 1;
+        scc_stack.push(start);
+        on_scc_stack[start] = true;
+        on_path[start] = true;
+
+        // Call stack of (node, next adjacency position)
+        let mut call_stack: Vec<(usize, usize)> = vec![(start, 0)];
+
+        while let Some((v, position)) = call_stack.last_mut() {
+            let v = *v;
+
+            if let Some(&w) = compact.successors(EXEC, v as u32).get(*position) {
+                let w = w as usize;
+                *position += 1;
+
+                if on_path[w] {
+                    let connection = compact
+                        .outgoing(EXEC, v as u32)
+                        .nth(*position - 1)
+                        .expect("successors and outgoing connections line up");
+                    back_edges.push(ExecEdge {
+                        source_node: connection.source_node.clone(),
+                        source_pin: connection.source_pin.clone(),
+                        target_node: connection.target_node.clone(),
+                    });
+                }
+
+                if order[w] == UNVISITED {
+                    order[w] = counter;
+                    low[w] = counter;
+                    counter += 1;
+                    scc_stack.push(w);
+                    on_scc_stack[w] = true;
+                    on_path[w] = true;
+                    call_stack.push((w, 0));
+                } else if on_scc_stack[w] {
+                    low[v] = low[v].min(order[w]);
+                }
+                continue;
+            }
+
+            call_stack.pop();
+            on_path[v] = false;
+            if let Some(&(parent, _)) = call_stack.last() {
+                low[parent] = low[parent].min(low[v]);
+            }
+
+            if low[v] == order[v] {
+                let mut members = Vec::new();
+                while let Some(w) = scc_stack.pop() {
+                    on_scc_stack[w] = false;
+                    members.push(w);
+                    if w == v {
+                        break;
+                    }
+                }
+
+                let self_loop = compact.successors(EXEC, v as u32).contains(&(v as u32));
+                if members.len() > 1 || self_loop {
+                    let mut members: Vec<String> =
+                        members.into_iter().map(|m| compact.node_id(m as u32).to_string()).collect();
+                    members.sort_unstable();
+                    loops.push(members);
+                }
+            }
+        }
+    }
+
+    loops.sort_unstable();
+
+    (back_edges, loops)
 }
 
 #[cfg(test)]
@@ -0,0 +1,345 @@
+//! # Execution Order Explanation
+//!
+//! Why nodes run in the order they do, for debugging views.
+//!
+//! [`explain`] turns the analysis results into an [`ExecutionExplanation`]:
+//!
+//! - for every event, the nodes its execution flow runs in the order code is
+//!   generated, each with the execution output leading to it, and the edges
+//!   that loop back
+//! - the evaluation order of pure nodes, each with the inputs that make it
+//!   wait for other nodes
+//!
+//! The explanation serializes for editors, renders as indented text with
+//! [`to_text`](ExecutionExplanation::to_text), and answers "why does this
+//! run before that" with
+//! [`reason_before`](ExecutionExplanation::reason_before).
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::analysis::{explain, DataResolver, ExecutionRouting, OrderReason};
+//! use graphy::{Connection, DataType, GraphDescription, NodeInstance, NodeRegistry, Position};
+//!
+//! let mut graph = GraphDescription::new("graph");
+//! let mut start = NodeInstance::new("start", "on_start", Position::zero());
+//! start.add_output_pin("exec", DataType::Execution);
+//! let mut print = NodeInstance::new("print", "print", Position::zero());
+//! print.add_input_pin("exec_in", DataType::Execution);
+//! graph.add_node(start);
+//! graph.add_node(print);
+//! graph.add_connection(Connection::execution("start", "exec", "print", "exec_in"));
+//!
+//! let resolver = DataResolver::build(&graph, &NodeRegistry::new()).unwrap();
+//! let routing = ExecutionRouting::build_from_graph(&graph);
+//! let explanation = explain(&graph, &resolver, &routing);
+//!
+//! assert_eq!(explanation.events[0].steps[1].node, "print");
+//! assert_eq!(
+//!     explanation.reason_before("start", "print"),
+//!     Some(OrderReason::ExecutionFlow { event: "start".into(), chain: vec!["start".into(), "print".into()] })
+//! );
+//! ```
+
+use super::{DataResolver, DataSource, ExecEdge, ExecutionRouting};
+use crate::core::GraphDescription;
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::Write;
+
+/// Why each node runs when it does, see [`explain`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionExplanation {
+    /// Execution flow of every event, by event ID
+    pub events: Vec<EventExplanation>,
+
+    /// Pure nodes in evaluation order
+    pub pure_order: Vec<PureStep>,
+}
+
+/// The nodes an event runs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventExplanation {
+    /// ID of the event node
+    pub event: String,
+
+    /// Nodes in the order code is generated, starting with the event
+    ///
+    /// Each node is listed once, where the flow first reaches it.
+    pub steps: Vec<ExecStep>,
+
+    /// Edges that jump back to a loop header instead of continuing
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub loop_edges: Vec<ExecEdge>,
+}
+
+/// A node run by an event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecStep {
+    /// ID of the node
+    pub node: String,
+
+    /// Execution outputs between the event and the node
+    pub depth: usize,
+
+    /// Edge the flow reaches the node through, `None` for the event
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reached_by: Option<ExecEdge>,
+}
+
+/// A pure node and what it waits for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PureStep {
+    /// ID of the node
+    pub node: String,
+
+    /// Inputs reading other nodes, which are evaluated first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub waits_for: Vec<DataDependency>,
+}
+
+/// An input reading another node's output.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DataDependency {
+    /// Input pin of the waiting node
+    pub input: String,
+
+    /// ID of the node read
+    pub source_node: String,
+
+    /// Output pin read (the param name for event params)
+    pub source_pin: String,
+}
+
+/// Why one node runs before another, see
+/// [`ExecutionExplanation::reason_before`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderReason {
+    /// The later node reads the earlier one, directly or through the nodes
+    /// of the chain (first node first)
+    DataFlow { chain: Vec<String> },
+
+    /// The event's execution flow reaches the later node through the earlier
+    /// one and the nodes of the chain (first node first)
+    ExecutionFlow { event: String, chain: Vec<String> },
+}
+
+/// Explains the execution order of an analyzed graph.
+///
+/// Events are the nodes with an execution output but no execution input,
+/// as [`NodeInstance::from_metadata`](crate::NodeInstance::from_metadata)
+/// creates event nodes. Their flow follows execution outputs in connection
+/// order, depth first, like code generation; back-edges end the flow (see
+/// [`ExecutionRouting::get_back_edges`]).
+pub fn explain(graph: &GraphDescription, resolver: &DataResolver, routing: &ExecutionRouting) -> ExecutionExplanation {
+    let mut events: Vec<&str> = graph
+        .nodes
+        .values()
+        .filter(|node| {
+            !node.inputs.iter().any(|pin| pin.pin.data_type.is_execution())
+                && node.outputs.iter().any(|pin| pin.pin.data_type.is_execution())
+        })
+        .map(|node| node.id.as_str())
+        .collect();
+    events.sort_unstable();
+
+    ExecutionExplanation {
+        events: events.into_iter().map(|event| explain_event(event, routing)).collect(),
+        pure_order: resolver
+            .get_pure_evaluation_order()
+            .iter()
+            .map(|node_id| PureStep {
+                node: node_id.clone(),
+                waits_for: data_dependencies(graph, resolver, node_id),
+            })
+            .collect(),
+    }
+}
+
+/// Depth-first walk of an event's execution flow
+fn explain_event(event: &str, routing: &ExecutionRouting) -> EventExplanation {
+    let mut steps = Vec::new();
+    let mut loop_edges = Vec::new();
+    let mut visited: FxHashSet<&str> = FxHashSet::default();
+    let mut stack: Vec<(&str, usize, Option<&ExecEdge>)> = vec![(event, 0, None)];
+
+    while let Some((node, depth, reached_by)) = stack.pop() {
+        if !visited.insert(node) {
+            continue;
+        }
+        steps.push(ExecStep {
+            node: node.to_string(),
+            depth,
+            reached_by: reached_by.cloned(),
+        });
+
+        // Reversed, so the first edge is walked first
+        for edge in routing.get_outgoing(node).iter().rev() {
+            if routing.is_back_edge(&edge.source_node, &edge.source_pin, &edge.target_node) {
+                loop_edges.push(edge.clone());
+            } else {
+                stack.push((&edge.target_node, depth + 1, Some(edge)));
+            }
+        }
+    }
+
+    // Collected in reverse order within each node
+    loop_edges.sort_by_key(|edge| steps.iter().position(|step| step.node == edge.source_node));
+    EventExplanation {
+        event: event.to_string(),
+        steps,
+        loop_edges,
+    }
+}
+
+/// The inputs of a node reading other nodes, in pin order
+fn data_dependencies(graph: &GraphDescription, resolver: &DataResolver, node_id: &str) -> Vec<DataDependency> {
+    let Some(node) = graph.nodes.get(node_id) else {
+        return Vec::new();
+    };
+    let mut dependencies = Vec::new();
+    for input in &node.inputs {
+        for source in resolver.get_input_sources_multi(node_id, &input.id) {
+            let (source_node, source_pin) = match source {
+                DataSource::Connection { source_node_id, source_pin } => (source_node_id, source_pin),
+                DataSource::EventParam { event_node, param } => (event_node, param),
+                _ => continue,
+            };
+            dependencies.push(DataDependency {
+                input: input.id.clone(),
+                source_node: source_node.clone(),
+                source_pin: source_pin.clone(),
+            });
+        }
+    }
+    dependencies
+}
+
+impl ExecutionExplanation {
+    /// Explains why `first` runs before `second`.
+    ///
+    /// Data flow between pure nodes comes first, then the execution flow of
+    /// the first event running both. Returns `None` if nothing orders them,
+    /// e.g. nodes of different events, or pure nodes that don't read each
+    /// other.
+    pub fn reason_before(&self, first: &str, second: &str) -> Option<OrderReason> {
+        if let Some(chain) = self.data_chain(first, second) {
+            return Some(OrderReason::DataFlow { chain });
+        }
+        self.events.iter().find_map(|event| {
+            let chain = exec_chain(event, first, second)?;
+            Some(OrderReason::ExecutionFlow {
+                event: event.event.clone(),
+                chain,
+            })
+        })
+    }
+
+    /// Shortest chain of data dependencies from `first` to `second`
+    fn data_chain(&self, first: &str, second: &str) -> Option<Vec<String>> {
+        let steps: FxHashMap<&str, &PureStep> = self.pure_order.iter().map(|step| (step.node.as_str(), step)).collect();
+        steps.get(second)?;
+
+        // Breadth first from `second` towards the nodes it reads
+        let mut next_towards_second: FxHashMap<&str, &str> = FxHashMap::default();
+        let mut queue = VecDeque::from([second]);
+        while let Some(node) = queue.pop_front() {
+            if node == first {
+                let mut chain = vec![first.to_string()];
+                let mut current = first;
+                while let Some(&next) = next_towards_second.get(current) {
+                    chain.push(next.to_string());
+                    current = next;
+                }
+                return Some(chain);
+            }
+            let Some(step) = steps.get(node) else {
+                continue;
+            };
+            for dependency in &step.waits_for {
+                let source = dependency.source_node.as_str();
+                if source != second && !next_towards_second.contains_key(source) {
+                    next_towards_second.insert(source, node);
+                    queue.push_back(source);
+                }
+            }
+        }
+        None
+    }
+
+    /// Renders the explanation as indented text.
+    ///
+    /// Each event lists its nodes indented by depth, with the output leading
+    /// to them; pure nodes are numbered in evaluation order with the inputs
+    /// they wait for:
+    ///
+    /// ```text
+    /// Event tick
+    ///   tick
+    ///     read_1 (tick.exec)
+    ///
+    /// Pure evaluation order
+    ///   1. greater_1 (a <- read_1.result)
+    /// ```
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for event in &self.events {
+            let _ = writeln!(out, "Event {}", event.event);
+            for step in &event.steps {
+                let _ = write!(out, "{:indent$}{}", "", step.node, indent = 2 + 2 * step.depth);
+                if let Some(edge) = &step.reached_by {
+                    let _ = write!(out, " ({}.{})", edge.source_node, edge.source_pin);
+                }
+                out.push('\n');
+            }
+            for edge in &event.loop_edges {
+                let _ = writeln!(
+                    out,
+                    "  loops back: {}.{} -> {}",
+                    edge.source_node, edge.source_pin, edge.target_node
+                );
+            }
+            out.push('\n');
+        }
+
+        out.push_str("Pure evaluation order\n");
+        for (index, step) in self.pure_order.iter().enumerate() {
+            let _ = write!(out, "  {}. {}", index + 1, step.node);
+            if !step.waits_for.is_empty() {
+                let inputs: Vec<String> = step
+                    .waits_for
+                    .iter()
+                    .map(|dependency| {
+                        format!("{} <- {}.{}", dependency.input, dependency.source_node, dependency.source_pin)
+                    })
+                    .collect();
+                let _ = write!(out, " ({})", inputs.join(", "));
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Chain of steps from `first` to `second` in an event's flow
+fn exec_chain(event: &EventExplanation, first: &str, second: &str) -> Option<Vec<String>> {
+    let parents: FxHashMap<&str, &str> = event
+        .steps
+        .iter()
+        .filter_map(|step| Some((step.node.as_str(), step.reached_by.as_ref()?.source_node.as_str())))
+        .collect();
+
+    let mut chain = vec![second.to_string()];
+    let mut current = second;
+    while current != first {
+        current = parents.get(current)?;
+        chain.push(current.to_string());
+    }
+    if chain.len() < 2 {
+        return None;
+    }
+    chain.reverse();
+    Some(chain)
+}
@@ -2,8 +2,40 @@
 //!
 //! Analysis passes for understanding graph structure and dependencies.
 
+mod async_flow;
+mod branches;
+mod compact;
+mod cost;
+mod cse;
+mod cycles;
+mod events;
 mod data_flow;
 mod exec_flow;
+mod explain;
+mod ordering;
+mod pass;
+mod properties;
+mod reachability;
+mod symbols;
+mod type_check;
+mod unknown_nodes;
+mod variables;
 
+pub use async_flow::*;
+pub use branches::*;
+pub use compact::*;
+pub use cost::*;
+pub use cse::*;
+pub use cycles::*;
+pub use events::*;
 pub use data_flow::*;
 pub use exec_flow::*;
+pub use explain::*;
+pub use ordering::*;
+pub use pass::*;
+pub use properties::*;
+pub use reachability::*;
+pub use symbols::*;
+pub use type_check::*;
+pub use unknown_nodes::*;
+pub use variables::*;
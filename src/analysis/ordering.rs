@@ -0,0 +1,329 @@
+//! # Side-Effect Ordering
+//!
+//! Checks that values produced by running a node are only read after it ran.
+//!
+//! Function and control flow nodes produce their results when execution
+//! reaches them, and event params are bound when the event starts. Pure
+//! nodes are evaluated lazily, wherever a node on the execution flow needs
+//! them. A pure getter reading the result of a setter is therefore only
+//! correct if every node consuming the getter runs after the setter.
+//!
+//! [`SideEffectOrdering::compute`] records the data edges crossing from
+//! these nodes into pure nodes, works out which produced values every node
+//! on the execution flow reads (directly or through pure nodes), and checks
+//! that each value is available on every forward path from the event:
+//!
+//! - a result is available after its node ran on all of those paths
+//! - the result of a fallible node is only available after its regular exec
+//!   outputs, its error value only after [`ERROR_EXEC_PIN`]
+//! - event params are only available in their own event
+//!
+//! Reads that aren't guaranteed, like a getter consumed on the other branch
+//! of a `branch` node or before its setter runs, are reported as
+//! [`OrderingViolation`]s. The compilation driver fails on the first one and
+//! re-evaluates pure nodes whenever a node they read runs again.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let ordering = SideEffectOrdering::compute(&graph, &provider, &exec_routing, &data_resolver);
+//! for violation in ordering.violations() {
+//!     println!("{}", violation);
+//! }
+//! ordering.validate()?;
+//! ```
+
+use super::{DataResolver, DataSource, ExecutionRouting};
+use crate::core::{ConnectionType, GraphDescription, NodeMetadataProvider, NodeTypes, ERROR_EXEC_PIN, ERROR_VALUE_PIN};
+use crate::utils::CancellationToken;
+use crate::GraphyError;
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::collections::VecDeque;
+use std::fmt;
+
+/// A data edge from a node with side effects (or an event) into a pure node.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CrossingEdge {
+    /// ID of the node producing the value
+    pub source_node: String,
+
+    /// Output pin on the producing node
+    pub source_pin: String,
+
+    /// ID of the pure node reading the value
+    pub target_node: String,
+
+    /// Input pin on the pure node
+    pub target_pin: String,
+}
+
+/// A read of a value that isn't always produced before the reader runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderingViolation {
+    /// Event whose execution flow runs the reader
+    pub event: String,
+
+    /// Node on the execution flow reading the value
+    pub node: String,
+
+    /// Node producing the value
+    pub producer: String,
+
+    /// Output pin on the producing node
+    pub pin: String,
+}
+
+impl OrderingViolation {
+    /// Converts the violation into a [`GraphyError::AmbiguousOrdering`].
+    pub fn to_error(&self) -> GraphyError {
+        GraphyError::AmbiguousOrdering {
+            node: self.node.clone(),
+            producer: self.producer.clone(),
+            pin: self.pin.clone(),
+            event: self.event.clone(),
+        }
+    }
+}
+
+impl fmt::Display for OrderingViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} reads {}.{} without it running first in {}",
+            self.node, self.producer, self.pin, self.event
+        )
+    }
+}
+
+/// A produced value: `(producer, output pin)`
+type Value<'a> = (&'a str, &'a str);
+
+/// Ordering of produced values and their readers in a graph.
+#[derive(Debug, Clone, Default)]
+pub struct SideEffectOrdering {
+    /// Data edges from nodes with side effects into pure nodes, sorted
+    crossing_edges: Vec<CrossingEdge>,
+
+    /// Unguaranteed reads, sorted by event, then node
+    violations: Vec<OrderingViolation>,
+}
+
+impl SideEffectOrdering {
+    /// Checks the order of produced values and their readers in every event.
+    ///
+    /// Data sources are taken from the resolver, so merged common
+    /// subexpressions are respected. Back-edges are ignored: a loop body
+    /// only sees the values produced before the loop or earlier in the body.
+    pub fn compute<P: NodeMetadataProvider>(
+        graph: &GraphDescription,
+        metadata_provider: &P,
+        exec_routing: &ExecutionRouting,
+        data_resolver: &DataResolver,
+    ) -> Self {
+        // A token nobody else holds is never cancelled
+        Self::compute_with_cancellation(graph, metadata_provider, exec_routing, data_resolver, &CancellationToken::new())
+            .unwrap_or_default()
+    }
+
+    /// Checks the order like [`compute`](Self::compute), stopping when
+    /// `cancellation` is cancelled.
+    ///
+    /// The token is checked for every pure node, every event, and every node
+    /// reached from an event.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::Cancelled`] once the token is cancelled.
+    pub fn compute_with_cancellation<P: NodeMetadataProvider>(
+        graph: &GraphDescription,
+        metadata_provider: &P,
+        exec_routing: &ExecutionRouting,
+        data_resolver: &DataResolver,
+        cancellation: &CancellationToken,
+    ) -> Result<Self, GraphyError> {
+        let metadata_of = |node_id: &str| {
+            graph
+                .nodes
+                .get(node_id)
+                .and_then(|node| metadata_provider.metadata_for_node(node))
+        };
+        let produces_values =
+            |node_id: &str| metadata_of(node_id).is_some_and(|metadata| metadata.node_type != NodeTypes::pure);
+        let is_fallible = |node_id: &str| metadata_of(node_id).is_some_and(|metadata| metadata.is_fallible());
+
+        let mut input_pins: FxHashMap<&str, Vec<&str>> = FxHashMap::default();
+        for connection in &graph.connections {
+            if connection.connection_type == ConnectionType::Data {
+                input_pins
+                    .entry(connection.target_node.as_str())
+                    .or_default()
+                    .push(connection.target_pin.as_str());
+            }
+        }
+        let sources_of = |node_id: &str| -> Vec<(Value<'_>, &str)> {
+            input_pins
+                .get(node_id)
+                .into_iter()
+                .flatten()
+                .filter_map(|&pin| match data_resolver.get_input_source(node_id, pin)? {
+                    DataSource::Connection { source_node_id, source_pin } => {
+                        Some(((source_node_id.as_str(), source_pin.as_str()), pin))
+                    }
+                    DataSource::EventParam { event_node, param } => {
+                        Some(((event_node.as_str(), param.as_str()), pin))
+                    }
+                    _ => None,
+                })
+                .collect()
+        };
+
+        // Produced values each pure node reads, directly or through other pure nodes
+        let mut ordering = SideEffectOrdering::default();
+        let mut pure_reads: FxHashMap<&str, Vec<Value<'_>>> = FxHashMap::default();
+        for node_id in data_resolver.get_pure_evaluation_order() {
+            cancellation.check()?;
+            let mut reads = Vec::new();
+            for ((source, source_pin), pin) in sources_of(node_id) {
+                if let Some(source_reads) = pure_reads.get(source) {
+                    reads.extend_from_slice(source_reads);
+                } else if produces_values(source) {
+                    reads.push((source, source_pin));
+                    ordering.crossing_edges.push(CrossingEdge {
+                        source_node: source.to_string(),
+                        source_pin: source_pin.to_string(),
+                        target_node: node_id.clone(),
+                        target_pin: pin.to_string(),
+                    });
+                }
+            }
+            reads.sort_unstable();
+            reads.dedup();
+            pure_reads.insert(node_id, reads);
+        }
+        ordering.crossing_edges.sort_by(|a, b| {
+            (&a.target_node, &a.target_pin, &a.source_node).cmp(&(&b.target_node, &b.target_pin, &b.source_node))
+        });
+
+        let mut events: Vec<&str> = graph
+            .nodes
+            .iter()
+            .filter(|(_, node)| {
+                metadata_provider
+                    .metadata_for_node(node)
+                    .is_some_and(|metadata| metadata.node_type == NodeTypes::event)
+            })
+            .map(|(id, _)| id.as_str())
+            .collect();
+        events.sort_unstable();
+
+        for event in events {
+            cancellation.check()?;
+            let reachable: FxHashSet<String> = exec_routing.reachable_nodes(event).into_iter().collect();
+            let forward_incoming = |node_id: &str| {
+                exec_routing.get_incoming(node_id).iter().filter(|edge| {
+                    reachable.contains(&edge.source_node)
+                        && !exec_routing.is_back_edge(&edge.source_node, &edge.source_pin, &edge.target_node)
+                })
+            };
+
+            // Values available when each node runs: `(producer, is error value)`,
+            // intersected over every forward edge reaching it
+            let mut available: FxHashMap<&str, FxHashSet<(&str, bool)>> = FxHashMap::default();
+            let mut pending: FxHashMap<&str, usize> = reachable
+                .iter()
+                .map(|node_id| (node_id.as_str(), forward_incoming(node_id).count()))
+                .collect();
+            let mut queue: VecDeque<&str> = VecDeque::from([event]);
+            let mut violations = Vec::new();
+
+            while let Some(node_id) = queue.pop_front() {
+                cancellation.check()?;
+                let mut values: Option<FxHashSet<(&str, bool)>> = None;
+                for edge in forward_incoming(node_id) {
+                    let mut through = available.get(edge.source_node.as_str()).cloned().unwrap_or_default();
+                    let is_error = is_fallible(&edge.source_node) && edge.source_pin == ERROR_EXEC_PIN;
+                    through.insert((edge.source_node.as_str(), is_error));
+                    values = Some(match values {
+                        Some(values) => values.intersection(&through).copied().collect(),
+                        None => through,
+                    });
+                }
+                let values = values.unwrap_or_default();
+
+                let mut reads: Vec<Value<'_>> = Vec::new();
+                for (source, _) in sources_of(node_id) {
+                    if let Some(source_reads) = pure_reads.get(source.0) {
+                        reads.extend_from_slice(source_reads);
+                    } else if produces_values(source.0) {
+                        reads.push(source);
+                    }
+                }
+                reads.sort_unstable();
+                reads.dedup();
+                for (producer, pin) in reads {
+                    if !values.contains(&(producer, pin == ERROR_VALUE_PIN)) {
+                        violations.push(OrderingViolation {
+                            event: event.to_string(),
+                            node: node_id.to_string(),
+                            producer: producer.to_string(),
+                            pin: pin.to_string(),
+                        });
+                    }
+                }
+
+                for edge in exec_routing.get_outgoing(node_id) {
+                    let Some(count) = pending.get_mut(edge.target_node.as_str()) else {
+                        continue;
+                    };
+                    if exec_routing.is_back_edge(&edge.source_node, &edge.source_pin, &edge.target_node) {
+                        continue;
+                    }
+                    *count -= 1;
+                    if *count == 0 {
+                        queue.push_back(edge.target_node.as_str());
+                    }
+                }
+                available.insert(node_id, values);
+            }
+
+            violations.sort_by(|a, b| (&a.node, &a.producer, &a.pin).cmp(&(&b.node, &b.producer, &b.pin)));
+            ordering.violations.extend(violations);
+        }
+
+        tracing::debug!(
+            "[ORDERING] {} crossing edge(s), {} violation(s)",
+            ordering.crossing_edges.len(),
+            ordering.violations.len()
+        );
+
+        Ok(ordering)
+    }
+
+    /// Returns the data edges from nodes with side effects (or events) into
+    /// pure nodes, sorted by target node and pin.
+    #[inline]
+    pub fn crossing_edges(&self) -> &[CrossingEdge] {
+        &self.crossing_edges
+    }
+
+    /// Returns the reads of values that aren't always produced before the
+    /// reader runs, sorted by event, then node.
+    #[inline]
+    pub fn violations(&self) -> &[OrderingViolation] {
+        &self.violations
+    }
+
+    /// Checks if every value is produced before it is read.
+    #[inline]
+    pub fn is_ordered(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Fails with the first violation, if any.
+    pub fn validate(&self) -> Result<(), GraphyError> {
+        match self.violations.first() {
+            Some(violation) => Err(violation.to_error()),
+            None => Ok(()),
+        }
+    }
+}
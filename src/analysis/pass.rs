@@ -0,0 +1,320 @@
+//! # Analysis Passes
+//!
+//! Pluggable analysis passes and a manager for running them in order.
+//!
+//! An [`AnalysisPass`] inspects a graph and stores its result in an
+//! [`AnalysisContext`], a typed map keyed by the result type. Later passes
+//! (and code generators) can query earlier results by type.
+//!
+//! The built-in analyses are available as passes:
+//! - [`DataFlowPass`] stores a [`DataResolver`]
+//! - [`ExecutionRoutingPass`] stores an [`ExecutionRouting`]
+//! - [`CommonSubexpressionPass`] stores [`CommonSubexpressions`] and applies
+//!   them to the stored [`DataResolver`]
+//!
+//! # Example
+//!
+//! ```ignore
+//! use graphy::analysis::{DataFlowPass, ExecutionRoutingPass, PassManager};
+//! use graphy::{DataResolver, ExecutionRouting};
+//!
+//! let mut manager = PassManager::new();
+//! manager.add_pass(DataFlowPass::new(&provider));
+//! manager.add_pass(ExecutionRoutingPass);
+//!
+//! let analysis = manager.run(&graph)?;
+//! let resolver = analysis.get::<DataResolver>().unwrap();
+//! let routing = analysis.get::<ExecutionRouting>().unwrap();
+//! ```
+
+use super::{CommonSubexpressions, DataResolver, ExecutionRouting};
+use crate::core::{ConnectionType, GraphDescription, NodeMetadataProvider};
+use crate::utils::timing::Stopwatch;
+use crate::GraphyError;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use std::any::{Any, TypeId};
+use std::time::Duration;
+
+/// How long an analysis pass took.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PassTiming {
+    /// Name of the pass
+    pub name: String,
+
+    /// Wall time of the pass
+    pub duration: Duration,
+}
+
+impl PassTiming {
+    /// Creates a pass timing.
+    #[inline]
+    pub fn new(name: impl Into<String>, duration: Duration) -> Self {
+        Self {
+            name: name.into(),
+            duration,
+        }
+    }
+}
+
+/// Typed storage for analysis results.
+///
+/// Each result is keyed by its Rust type, so a pass stores at most one
+/// value of any given type. Passes read the results of earlier passes
+/// through [`get`](Self::get).
+#[derive(Default)]
+pub struct AnalysisContext {
+    /// Maps result type -> boxed result value
+    results: FxHashMap<TypeId, Box<dyn Any>>,
+
+    /// Names of passes that have completed, in run order
+    completed_passes: Vec<String>,
+
+    /// Durations of completed passes, in run order
+    pass_timings: Vec<PassTiming>,
+}
+
+impl AnalysisContext {
+    /// Creates an empty analysis context.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores a result, replacing (and returning) any previous value of the same type.
+    pub fn insert<T: Any>(&mut self, value: T) -> Option<T> {
+        self.results
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .and_then(|old| old.downcast::<T>().ok())
+            .map(|old| *old)
+    }
+
+    /// Retrieves a result by type.
+    ///
+    /// Returns `None` if no pass has stored a value of this type.
+    #[inline]
+    pub fn get<T: Any>(&self) -> Option<&T> {
+        self.results
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref::<T>())
+    }
+
+    /// Retrieves a mutable reference to a result by type.
+    #[inline]
+    pub fn get_mut<T: Any>(&mut self) -> Option<&mut T> {
+        self.results
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_mut::<T>())
+    }
+
+    /// Removes a result by type, returning it if present.
+    pub fn remove<T: Any>(&mut self) -> Option<T> {
+        self.results
+            .remove(&TypeId::of::<T>())
+            .and_then(|value| value.downcast::<T>().ok())
+            .map(|value| *value)
+    }
+
+    /// Checks if a result of the given type is present.
+    #[inline]
+    pub fn contains<T: Any>(&self) -> bool {
+        self.results.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Returns the names of all passes that have run, in order.
+    #[inline]
+    pub fn completed_passes(&self) -> &[String] {
+        &self.completed_passes
+    }
+
+    /// Returns how long each pass run by a [`PassManager`] took, in run order.
+    #[inline]
+    pub fn pass_timings(&self) -> &[PassTiming] {
+        &self.pass_timings
+    }
+}
+
+/// Trait for custom analysis passes.
+///
+/// Implement this trait to add your own analysis to the compilation
+/// pipeline. A pass reads the graph (and any earlier results in the
+/// context) and stores its own result in the context.
+///
+/// # Example
+///
+/// ```
+/// use graphy::analysis::{AnalysisContext, AnalysisPass};
+/// use graphy::{GraphDescription, GraphyError};
+///
+/// struct NodeCount(usize);
+///
+/// struct NodeCountPass;
+///
+/// impl AnalysisPass for NodeCountPass {
+///     fn name(&self) -> &str {
+///         "node_count"
+///     }
+///
+///     fn run(&self, graph: &GraphDescription, ctx: &mut AnalysisContext) -> Result<(), GraphyError> {
+///         ctx.insert(NodeCount(graph.nodes.len()));
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait AnalysisPass {
+    /// Human-readable name of the pass (used for logging and diagnostics)
+    fn name(&self) -> &str;
+
+    /// Runs the pass over the graph, storing results in the context.
+    fn run(&self, graph: &GraphDescription, ctx: &mut AnalysisContext) -> Result<(), GraphyError>;
+}
+
+/// Runs registered analysis passes in order.
+///
+/// Passes are executed in registration order and share a single
+/// [`AnalysisContext`]. The first failing pass aborts the run. The duration
+/// of each pass is recorded in [`AnalysisContext::pass_timings`].
+#[derive(Default)]
+pub struct PassManager<'a> {
+    passes: Vec<Box<dyn AnalysisPass + 'a>>,
+}
+
+impl<'a> PassManager<'a> {
+    /// Creates an empty pass manager.
+    #[inline]
+    pub fn new() -> Self {
+        Self { passes: Vec::new() }
+    }
+
+    /// Registers a pass to run after all previously registered passes.
+    pub fn add_pass(&mut self, pass: impl AnalysisPass + 'a) -> &mut Self {
+        self.passes.push(Box::new(pass));
+        self
+    }
+
+    /// Returns the number of registered passes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.passes.len()
+    }
+
+    /// Checks if no passes are registered.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.passes.is_empty()
+    }
+
+    /// Returns the names of all registered passes, in run order.
+    pub fn pass_names(&self) -> Vec<&str> {
+        self.passes.iter().map(|pass| pass.name()).collect()
+    }
+
+    /// Runs all passes over the graph with a fresh context.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the first pass that fails.
+    pub fn run(&self, graph: &GraphDescription) -> Result<AnalysisContext, GraphyError> {
+        let mut ctx = AnalysisContext::new();
+        self.run_with_context(graph, &mut ctx)?;
+        Ok(ctx)
+    }
+
+    /// Runs all passes over the graph using an existing context.
+    ///
+    /// Useful for seeding the context with results computed elsewhere.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the first pass that fails.
+    pub fn run_with_context(
+        &self,
+        graph: &GraphDescription,
+        ctx: &mut AnalysisContext,
+    ) -> Result<(), GraphyError> {
+        for pass in &self.passes {
+            tracing::debug!("[PASS] Running '{}'", pass.name());
+            let start = Stopwatch::start();
+            pass.run(graph, ctx)?;
+            ctx.pass_timings.push(PassTiming::new(pass.name(), start.elapsed()));
+            ctx.completed_passes.push(pass.name().to_string());
+        }
+
+        Ok(())
+    }
+}
+
+/// Analysis pass that builds a [`DataResolver`].
+pub struct DataFlowPass<'p, P: NodeMetadataProvider> {
+    metadata_provider: &'p P,
+}
+
+impl<'p, P: NodeMetadataProvider> DataFlowPass<'p, P> {
+    /// Creates a data flow pass using the given metadata provider.
+    #[inline]
+    pub fn new(metadata_provider: &'p P) -> Self {
+        Self { metadata_provider }
+    }
+}
+
+impl<P: NodeMetadataProvider> AnalysisPass for DataFlowPass<'_, P> {
+    fn name(&self) -> &str {
+        "data_flow"
+    }
+
+    fn run(&self, graph: &GraphDescription, ctx: &mut AnalysisContext) -> Result<(), GraphyError> {
+        let resolver = DataResolver::build(graph, self.metadata_provider)?;
+        ctx.insert(resolver);
+        Ok(())
+    }
+}
+
+/// Analysis pass that builds an [`ExecutionRouting`] table.
+///
+/// Fails with a [`GraphyError::InvalidConnection`] if an execution
+/// connection joins a node that doesn't exist.
+pub struct ExecutionRoutingPass;
+
+impl AnalysisPass for ExecutionRoutingPass {
+    fn name(&self) -> &str {
+        "exec_routing"
+    }
+
+    fn run(&self, graph: &GraphDescription, ctx: &mut AnalysisContext) -> Result<(), GraphyError> {
+        graph.check_connection_nodes(ConnectionType::Execution)?;
+        ctx.insert(ExecutionRouting::build_from_graph(graph));
+        Ok(())
+    }
+}
+
+/// Analysis pass that merges equivalent pure nodes.
+///
+/// Computes [`CommonSubexpressions`] from the [`DataResolver`] of an
+/// earlier [`DataFlowPass`], applies them to it, and stores them.
+pub struct CommonSubexpressionPass<'p, P: NodeMetadataProvider> {
+    metadata_provider: &'p P,
+}
+
+impl<'p, P: NodeMetadataProvider> CommonSubexpressionPass<'p, P> {
+    /// Creates a common subexpression pass using the given metadata provider.
+    #[inline]
+    pub fn new(metadata_provider: &'p P) -> Self {
+        Self { metadata_provider }
+    }
+}
+
+impl<P: NodeMetadataProvider> AnalysisPass for CommonSubexpressionPass<'_, P> {
+    fn name(&self) -> &str {
+        "cse"
+    }
+
+    fn run(&self, graph: &GraphDescription, ctx: &mut AnalysisContext) -> Result<(), GraphyError> {
+        let resolver = ctx
+            .get_mut::<DataResolver>()
+            .ok_or_else(|| GraphyError::Custom("The cse pass requires the data_flow pass to run first".to_string()))?;
+        let cse = CommonSubexpressions::compute(graph, self.metadata_provider, resolver);
+        resolver.apply_common_subexpressions(&cse);
+        ctx.insert(cse);
+        Ok(())
+    }
+}
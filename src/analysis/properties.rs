@@ -0,0 +1,467 @@
+//! # Property Checking
+//!
+//! Validates property values against the types of the params they set.
+//!
+//! Properties are the values of unconnected inputs, keyed by param name.
+//! Nothing stops an editor from storing `PropertyValue::String("hi")` for an
+//! `i64` param, and such a value only fails once the generated code is
+//! compiled. [`check_properties`] (and [`PropertyCheckPass`]) compares every
+//! property with its [`ParamInfo::param_type`](crate::ParamInfo::param_type)
+//! and reports a [`PropertyIssue`] per bad value:
+//!
+//! - numbers must fit integer params (whole and in range), strings need a
+//!   string param, and booleans a `bool` one
+//! - vectors and colors need a tuple param with as many components
+//! - array items, fixed array lengths, and map values are checked too
+//!
+//! A value of another type is accepted if the [`TypeCoercion`] registry
+//! converts its type into the param's, like for connections. Params of
+//! types the check doesn't know (generics, structs, enums) accept any value,
+//! and so do inputs with a connection, since their property is unused.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::analysis::{check_properties, PropertyIssue};
+//! use graphy::core::TypeCoercion;
+//! use graphy::{GraphDescription, NodeMetadata, NodeRegistry, NodeTypes, ParamInfo, PropertyValue};
+//!
+//! let mut registry = NodeRegistry::new();
+//! registry.register(
+//!     NodeMetadata::new("repeat", NodeTypes::pure, "Text")
+//!         .with_params(vec![ParamInfo::new("times", "u8")])
+//!         .with_return_type("String"),
+//! );
+//! let mut graph = GraphDescription::new("graph");
+//! graph.add_node_of_type("repeat", "repeat_1", &registry).unwrap();
+//! graph.get_node_mut("repeat_1").unwrap().set_property("times", PropertyValue::Integer(300));
+//!
+//! let issues = check_properties(&graph, &registry, &TypeCoercion::rust());
+//! assert_eq!(issues[0].to_string(), "Property repeat_1.times is 300, which doesn't fit in u8");
+//! ```
+
+use super::{AnalysisContext, AnalysisPass};
+use crate::core::{
+    ConnectionType, DataType, GraphDescription, NodeMetadataProvider, NodeTypes, PropertyValue, TypeCoercion, TypeInfo,
+};
+use crate::GraphyError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A property value that doesn't fit the param it sets.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PropertyIssue {
+    /// The value has a type the param doesn't accept
+    TypeMismatch {
+        node: String,
+        property: String,
+        expected: String,
+        actual: String,
+    },
+
+    /// A vector, color, or array has the wrong number of components
+    ArityMismatch {
+        node: String,
+        property: String,
+        expected_type: String,
+        expected: usize,
+        actual: usize,
+    },
+
+    /// A number doesn't fit the integer type of the param
+    OutOfRange {
+        node: String,
+        property: String,
+        expected: String,
+        value: String,
+    },
+}
+
+impl PropertyIssue {
+    /// Returns the ID of the node the property belongs to.
+    pub fn node(&self) -> &str {
+        match self {
+            PropertyIssue::TypeMismatch { node, .. }
+            | PropertyIssue::ArityMismatch { node, .. }
+            | PropertyIssue::OutOfRange { node, .. } => node,
+        }
+    }
+
+    /// Returns the name of the property.
+    pub fn property(&self) -> &str {
+        match self {
+            PropertyIssue::TypeMismatch { property, .. }
+            | PropertyIssue::ArityMismatch { property, .. }
+            | PropertyIssue::OutOfRange { property, .. } => property,
+        }
+    }
+
+    /// What's wrong with the value, without the property
+    fn message(&self) -> String {
+        match self {
+            PropertyIssue::TypeMismatch { expected, actual, .. } => {
+                format!("is {}, but the param is {}", actual, expected)
+            }
+            PropertyIssue::ArityMismatch {
+                expected_type,
+                expected,
+                actual,
+                ..
+            } => format!("has {} components, but {} has {}", actual, expected_type, expected),
+            PropertyIssue::OutOfRange { expected, value, .. } => {
+                format!("is {}, which doesn't fit in {}", value, expected)
+            }
+        }
+    }
+}
+
+impl fmt::Display for PropertyIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Property {}.{} {}", self.node(), self.property(), self.message())
+    }
+}
+
+impl From<PropertyIssue> for GraphyError {
+    fn from(issue: PropertyIssue) -> Self {
+        GraphyError::InvalidProperty {
+            message: issue.message(),
+            node: issue.node().to_string(),
+            property: issue.property().to_string(),
+        }
+    }
+}
+
+/// Checks every property of a graph against the type of its param.
+///
+/// Issues are sorted by node ID, then in param order. Nodes unknown to the
+/// provider and event nodes (whose params are outputs) are skipped.
+pub fn check_properties<P: NodeMetadataProvider>(
+    graph: &GraphDescription,
+    metadata_provider: &P,
+    coercion: &TypeCoercion,
+) -> Vec<PropertyIssue> {
+    let mut node_ids: Vec<&String> = graph.nodes.keys().collect();
+    node_ids.sort();
+
+    let mut issues = Vec::new();
+    for node_id in node_ids {
+        let node = &graph.nodes[node_id];
+        let Some(metadata) = metadata_provider.metadata_for_node(node) else {
+            continue;
+        };
+        if metadata.node_type == NodeTypes::event {
+            continue;
+        }
+
+        for param in metadata.instance_params(node) {
+            let Some(value) = node.get_property(&param.name) else {
+                continue;
+            };
+            let connected = graph
+                .connections_to(node_id, &param.name)
+                .any(|c| c.connection_type == ConnectionType::Data);
+            if connected {
+                continue;
+            }
+
+            if let Err(mismatch) = check_value(value, &param.param_type, coercion) {
+                issues.push(mismatch.into_issue(node_id.clone(), param.name.clone()));
+            }
+        }
+    }
+
+    tracing::debug!("[PROPERTIES] Found {} invalid property value(s)", issues.len());
+
+    issues
+}
+
+/// Analysis pass wrapper for [`check_properties`].
+///
+/// Stores the [`PropertyIssue`]s in the context, or fails with the first
+/// one as a [`GraphyError::InvalidProperty`] if
+/// [`deny_issues`](Self::deny_issues) is set.
+pub struct PropertyCheckPass<'p, P: NodeMetadataProvider> {
+    metadata_provider: &'p P,
+    coercion: &'p TypeCoercion,
+    deny_issues: bool,
+}
+
+impl<'p, P: NodeMetadataProvider> PropertyCheckPass<'p, P> {
+    /// Creates a property check pass using the given provider and coercion rules.
+    #[inline]
+    pub fn new(metadata_provider: &'p P, coercion: &'p TypeCoercion) -> Self {
+        Self {
+            metadata_provider,
+            coercion,
+            deny_issues: false,
+        }
+    }
+
+    /// Fails the pass on the first invalid property.
+    #[inline]
+    #[must_use]
+    pub fn deny_issues(mut self) -> Self {
+        self.deny_issues = true;
+        self
+    }
+}
+
+impl<P: NodeMetadataProvider> AnalysisPass for PropertyCheckPass<'_, P> {
+    fn name(&self) -> &str {
+        "property_check"
+    }
+
+    fn run(&self, graph: &GraphDescription, ctx: &mut AnalysisContext) -> Result<(), GraphyError> {
+        let issues = check_properties(graph, self.metadata_provider, self.coercion);
+        if self.deny_issues {
+            if let Some(issue) = issues.first() {
+                return Err(issue.clone().into());
+            }
+        }
+        ctx.insert(issues);
+        Ok(())
+    }
+}
+
+/// Why a value doesn't fit a type
+enum Mismatch {
+    Type { expected: String, actual: String },
+    Arity {
+        expected_type: String,
+        expected: usize,
+        actual: usize,
+    },
+    Range { expected: String, value: String },
+}
+
+impl Mismatch {
+    fn into_issue(self, node: String, property: String) -> PropertyIssue {
+        match self {
+            Mismatch::Type { expected, actual } => PropertyIssue::TypeMismatch {
+                node,
+                property,
+                expected,
+                actual,
+            },
+            Mismatch::Arity {
+                expected_type,
+                expected,
+                actual,
+            } => PropertyIssue::ArityMismatch {
+                node,
+                property,
+                expected_type,
+                expected,
+                actual,
+            },
+            Mismatch::Range { expected, value } => PropertyIssue::OutOfRange {
+                node,
+                property,
+                expected,
+                value,
+            },
+        }
+    }
+}
+
+/// The kinds of param types values are checked against
+enum ParamKind<'t> {
+    Integer(&'t str),
+    Float,
+    Bool,
+    Text,
+    Tuple(Vec<&'t str>),
+    List { item: &'t str, len: Option<usize> },
+    Map { key: &'t str, value: &'t str },
+    /// Anything else, which accepts every value
+    Other,
+}
+
+const INTEGER_TYPES: [&str; 12] = [
+    "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+];
+
+fn param_kind(param_type: &str) -> ParamKind<'_> {
+    let ty = param_type.trim();
+    let generic = |prefixes: &[&str]| {
+        let inner = ty.strip_suffix('>')?;
+        prefixes.iter().find_map(|prefix| inner.strip_prefix(prefix)?.strip_prefix('<'))
+    };
+
+    if let Some(integer) = INTEGER_TYPES.iter().find(|integer| **integer == ty) {
+        return ParamKind::Integer(integer);
+    }
+    match ty {
+        "f32" | "f64" => return ParamKind::Float,
+        "bool" => return ParamKind::Bool,
+        "String" | "str" | "&str" | "&'static str" => return ParamKind::Text,
+        _ => {}
+    }
+
+    if let Some(inner) = ty.strip_prefix('(').and_then(|ty| ty.strip_suffix(')')) {
+        let items: Vec<&str> = split_top_level(inner).into_iter().filter(|item| !item.is_empty()).collect();
+        return match items.len() {
+            // `()` and `(T)` aren't tuples
+            0 => ParamKind::Other,
+            1 if !inner.trim_end().ends_with(',') => param_kind(items[0]),
+            _ => ParamKind::Tuple(items),
+        };
+    }
+    if let Some(item) = generic(&["Vec", "std::vec::Vec"]) {
+        return ParamKind::List { item: item.trim(), len: None };
+    }
+    if let Some(inner) = ty.trim_start_matches('&').strip_prefix('[').and_then(|ty| ty.strip_suffix(']')) {
+        return match split_top_level_on(inner, ';').as_slice() {
+            [item] => ParamKind::List { item: item.trim(), len: None },
+            [item, len] => match len.trim().parse() {
+                Ok(len) => ParamKind::List {
+                    item: item.trim(),
+                    len: Some(len),
+                },
+                Err(_) => ParamKind::Other,
+            },
+            _ => ParamKind::Other,
+        };
+    }
+    let maps = [
+        "HashMap",
+        "BTreeMap",
+        "std::collections::HashMap",
+        "std::collections::BTreeMap",
+    ];
+    if let Some([key, value]) = generic(&maps).map(split_top_level).as_deref() {
+        return ParamKind::Map {
+            key: key.trim(),
+            value: value.trim(),
+        };
+    }
+    ParamKind::Other
+}
+
+/// Splits a type list on commas outside of nested brackets
+fn split_top_level(list: &str) -> Vec<&str> {
+    split_top_level_on(list, ',')
+}
+
+fn split_top_level_on(list: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0usize, 0);
+    for (index, c) in list.char_indices() {
+        match c {
+            '(' | '<' | '[' => depth += 1,
+            ')' | '>' | ']' => depth = depth.saturating_sub(1),
+            c if c == separator && depth == 0 => {
+                parts.push(list[start..index].trim());
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(list[start..].trim());
+    parts
+}
+
+/// Type of the literal generated for a value, for messages and coercion
+fn value_type(value: &PropertyValue) -> String {
+    match value {
+        PropertyValue::String(_) => "String".to_string(),
+        PropertyValue::Number(_) => "f64".to_string(),
+        PropertyValue::Boolean(_) => "bool".to_string(),
+        PropertyValue::Integer(_) => "i64".to_string(),
+        PropertyValue::Vector2(..) => "(f64, f64)".to_string(),
+        PropertyValue::Vector3(..) => "(f64, f64, f64)".to_string(),
+        PropertyValue::Color(..) => "(f64, f64, f64, f64)".to_string(),
+        PropertyValue::Array(items) => format!("Vec<{}>", items.first().map_or("_".to_string(), value_type)),
+        PropertyValue::Map(_) => "HashMap<String, _>".to_string(),
+        PropertyValue::Enum { variant, .. } => variant
+            .rsplit_once("::")
+            .map_or_else(|| variant.clone(), |(path, _)| path.to_string()),
+    }
+}
+
+/// Checks that a value can be used for a param of a type
+fn check_value(value: &PropertyValue, param_type: &str, coercion: &TypeCoercion) -> Result<(), Mismatch> {
+    let mismatch = || {
+        let actual = value_type(value);
+        let coerces = coercion.can_coerce(
+            &DataType::Typed(TypeInfo::new(actual.clone())),
+            &DataType::Typed(TypeInfo::new(param_type)),
+        );
+        match coerces {
+            true => Ok(()),
+            false => Err(Mismatch::Type {
+                expected: param_type.to_string(),
+                actual,
+            }),
+        }
+    };
+
+    let components = match value {
+        PropertyValue::Vector2(x, y) => Some(vec![*x, *y]),
+        PropertyValue::Vector3(x, y, z) => Some(vec![*x, *y, *z]),
+        PropertyValue::Color(r, g, b, a) => Some(vec![*r, *g, *b, *a]),
+        _ => None,
+    };
+
+    match (param_kind(param_type), value) {
+        (ParamKind::Other, _) => Ok(()),
+        (ParamKind::Integer(integer), PropertyValue::Integer(n)) => check_range(*n as f64, integer),
+        (ParamKind::Integer(integer), PropertyValue::Number(n)) if n.fract() == 0.0 => check_range(*n, integer),
+        (ParamKind::Float, PropertyValue::Number(_)) => Ok(()),
+        (ParamKind::Bool, PropertyValue::Boolean(_)) => Ok(()),
+        (ParamKind::Text, PropertyValue::String(_)) => Ok(()),
+        (ParamKind::Tuple(items), _) => {
+            let Some(components) = components else {
+                return mismatch();
+            };
+            if items.len() != components.len() {
+                return Err(Mismatch::Arity {
+                    expected_type: param_type.to_string(),
+                    expected: items.len(),
+                    actual: components.len(),
+                });
+            }
+            items
+                .iter()
+                .zip(components)
+                .try_for_each(|(item, component)| check_value(&PropertyValue::Number(component), item, coercion))
+        }
+        (ParamKind::List { item, len }, PropertyValue::Array(values)) => {
+            if let Some(len) = len.filter(|len| *len != values.len()) {
+                return Err(Mismatch::Arity {
+                    expected_type: param_type.to_string(),
+                    expected: len,
+                    actual: values.len(),
+                });
+            }
+            values.iter().try_for_each(|value| check_value(value, item, coercion))
+        }
+        (ParamKind::Map { key, value: value_type }, PropertyValue::Map(entries))
+            if matches!(param_kind(key), ParamKind::Text) =>
+        {
+            entries.values().try_for_each(|value| check_value(value, value_type, coercion))
+        }
+        _ => mismatch(),
+    }
+}
+
+/// Checks that a whole number fits an integer type
+fn check_range(value: f64, integer: &str) -> Result<(), Mismatch> {
+    let (min, max) = match integer {
+        "i8" => (i8::MIN as f64, i8::MAX as f64),
+        "i16" => (i16::MIN as f64, i16::MAX as f64),
+        "i32" => (i32::MIN as f64, i32::MAX as f64),
+        "i64" | "isize" | "i128" => (i64::MIN as f64, i64::MAX as f64),
+        "u8" => (0.0, u8::MAX as f64),
+        "u16" => (0.0, u16::MAX as f64),
+        "u32" => (0.0, u32::MAX as f64),
+        _ => (0.0, u64::MAX as f64),
+    };
+    if (min..=max).contains(&value) {
+        return Ok(());
+    }
+    Err(Mismatch::Range {
+        expected: integer.to_string(),
+        value: value.to_string(),
+    })
+}
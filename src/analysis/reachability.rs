@@ -0,0 +1,181 @@
+//! # Reachability Analysis
+//!
+//! Determines which nodes can affect the compiled output.
+//!
+//! A node is **reachable** if it is:
+//! 1. An event node (execution entry point)
+//! 2. The target of an execution connection from a reachable node
+//! 3. The source of a data connection into a reachable node
+//!
+//! Everything else is dead code: orphaned nodes, pure nodes whose results
+//! are never consumed, and function nodes that are never executed.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use graphy::analysis::Reachability;
+//!
+//! let reachability = Reachability::compute(&graph, &provider);
+//! for node_id in reachability.unreachable_nodes(&graph) {
+//!     println!("Dead node: {}", node_id);
+//! }
+//!
+//! // Or remove dead nodes directly
+//! let removed = graph.prune_unreachable(&provider);
+//! ```
+
+use super::{AnalysisContext, AnalysisPass, CompactGraph};
+use crate::core::{ConnectionType, GraphDescription, NodeMetadataProvider, NodeTypes};
+use crate::GraphyError;
+use rustc_hash::FxHashSet;
+
+/// Set of nodes reachable from the graph's event nodes.
+#[derive(Debug, Clone, Default)]
+pub struct Reachability {
+    /// IDs of all reachable nodes
+    reachable: FxHashSet<String>,
+}
+
+impl Reachability {
+    /// Computes reachability from all event nodes in the graph.
+    ///
+    /// Nodes whose type is unknown to the metadata provider are never
+    /// treated as entry points, but can still be reached through connections.
+    pub fn compute<P: NodeMetadataProvider>(graph: &GraphDescription, metadata_provider: &P) -> Self {
+        Self::compute_compact(&CompactGraph::from_graph(graph), metadata_provider)
+    }
+
+    /// Computes reachability from all event nodes of a compact graph.
+    ///
+    /// See [`compute`](Self::compute).
+    pub fn compute_compact<P: NodeMetadataProvider>(compact: &CompactGraph<'_>, metadata_provider: &P) -> Self {
+        let roots = compact.indices().filter(|&index| {
+            compact.node(index).is_some_and(|node| {
+                metadata_provider
+                    .metadata_for_node(node)
+                    .is_some_and(|meta| meta.node_type == NodeTypes::event)
+            })
+        });
+
+        Self::compute_from_compact(compact, roots)
+    }
+
+    /// Computes reachability from an explicit set of root nodes.
+    ///
+    /// Root IDs that don't exist in the graph are ignored.
+    pub fn compute_from<'g>(graph: &'g GraphDescription, roots: impl IntoIterator<Item = &'g str>) -> Self {
+        let compact = CompactGraph::from_graph(graph);
+        let roots: Vec<u32> = roots.into_iter().filter_map(|id| compact.index_of(id)).collect();
+        Self::compute_from_compact(&compact, roots)
+    }
+
+    /// Computes reachability from root nodes given by compact graph index.
+    ///
+    /// Indices without a node instance (dangling connection endpoints) are
+    /// neither visited nor traversed.
+    pub fn compute_from_compact(compact: &CompactGraph<'_>, roots: impl IntoIterator<Item = u32>) -> Self {
+        let mut visited = vec![false; compact.node_count()];
+        let mut stack: Vec<u32> = roots.into_iter().collect();
+        let mut reachable = FxHashSet::default();
+
+        while let Some(index) = stack.pop() {
+            if std::mem::replace(&mut visited[index as usize], true) || compact.node(index).is_none() {
+                continue;
+            }
+            reachable.insert(compact.node_id(index).to_string());
+
+            // Forward execution edges and backward data edges
+            let next = compact
+                .successors(ConnectionType::Execution, index)
+                .iter()
+                .chain(compact.predecessors(ConnectionType::Data, index));
+            stack.extend(next.filter(|&&neighbor| !visited[neighbor as usize]));
+        }
+
+        Reachability { reachable }
+    }
+
+    /// Checks if a node is reachable.
+    #[inline(always)]
+    pub fn is_reachable(&self, node_id: &str) -> bool {
+        self.reachable.contains(node_id)
+    }
+
+    /// Returns the number of reachable nodes.
+    #[inline]
+    pub fn reachable_count(&self) -> usize {
+        self.reachable.len()
+    }
+
+    /// Returns the IDs of all reachable nodes (in arbitrary order).
+    pub fn reachable_nodes(&self) -> impl Iterator<Item = &str> {
+        self.reachable.iter().map(String::as_str)
+    }
+
+    /// Returns the IDs of all unreachable nodes in the graph, sorted.
+    pub fn unreachable_nodes(&self, graph: &GraphDescription) -> Vec<String> {
+        let mut dead: Vec<String> = graph
+            .nodes
+            .keys()
+            .filter(|id| !self.reachable.contains(*id))
+            .cloned()
+            .collect();
+        dead.sort();
+        dead
+    }
+}
+
+impl GraphDescription {
+    /// Removes all nodes that are unreachable from event nodes.
+    ///
+    /// Connections to or from removed nodes are removed as well.
+    /// Returns the IDs of the removed nodes, sorted.
+    ///
+    /// Note that a graph without any event nodes has no reachable nodes,
+    /// so pruning it removes everything.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let removed = graph.prune_unreachable(&provider);
+    /// println!("Removed {} dead nodes", removed.len());
+    /// ```
+    pub fn prune_unreachable<P: NodeMetadataProvider>(&mut self, metadata_provider: &P) -> Vec<String> {
+        let reachability = Reachability::compute(self, metadata_provider);
+        let dead = reachability.unreachable_nodes(self);
+
+        if dead.is_empty() {
+            return dead;
+        }
+
+        self.remove_nodes(&dead);
+
+        tracing::debug!("[REACHABILITY] Pruned {} unreachable nodes", dead.len());
+
+        dead
+    }
+}
+
+/// Analysis pass that computes [`Reachability`].
+pub struct ReachabilityPass<'p, P: NodeMetadataProvider> {
+    metadata_provider: &'p P,
+}
+
+impl<'p, P: NodeMetadataProvider> ReachabilityPass<'p, P> {
+    /// Creates a reachability pass using the given metadata provider.
+    #[inline]
+    pub fn new(metadata_provider: &'p P) -> Self {
+        Self { metadata_provider }
+    }
+}
+
+impl<P: NodeMetadataProvider> AnalysisPass for ReachabilityPass<'_, P> {
+    fn name(&self) -> &str {
+        "reachability"
+    }
+
+    fn run(&self, graph: &GraphDescription, ctx: &mut AnalysisContext) -> Result<(), GraphyError> {
+        ctx.insert(Reachability::compute(graph, self.metadata_provider));
+        Ok(())
+    }
+}
@@ -0,0 +1,152 @@
+//! # Symbol Interning
+//!
+//! Compact IDs for the node IDs and pin names of a graph.
+//!
+//! Analysis tables keyed by `(node, pin)` would otherwise clone two strings
+//! per entry. A [`SymbolTable`] maps every name to a [`SymbolId`] once while
+//! an analysis is built; tables are then keyed by `(SymbolId, SymbolId)` and
+//! lookups by `&str` resolve the names first, without allocating.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::analysis::SymbolTable;
+//!
+//! let mut symbols = SymbolTable::new();
+//! let add = symbols.intern("add_1");
+//! assert_eq!(symbols.intern("add_1"), add);
+//! assert_eq!(symbols.get("add_1"), Some(add));
+//! assert_eq!(symbols.resolve(add), "add_1");
+//! assert_eq!(symbols.get("missing"), None);
+//! ```
+
+use crate::core::GraphDescription;
+use crate::utils::HeapSize;
+use rustc_hash::FxHashMap;
+use std::sync::Arc;
+
+/// Interned name, valid for the [`SymbolTable`] that produced it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SymbolId(u32);
+
+impl SymbolId {
+    /// Returns the position of the symbol in its table.
+    #[inline(always)]
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Two-way map between names and [`SymbolId`]s.
+///
+/// IDs are assigned in interning order, starting at 0.
+#[derive(Debug, Clone, Default)]
+pub struct SymbolTable {
+    /// Names by symbol index (shared with `ids`, so each name is stored once)
+    names: Vec<Arc<str>>,
+
+    /// Maps name -> symbol
+    ids: FxHashMap<Arc<str>, SymbolId>,
+}
+
+impl SymbolTable {
+    /// Creates an empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Interns every node ID and pin name of a graph.
+    ///
+    /// Covers the pins declared on nodes as well as the pins named by
+    /// connections, so every `(node, pin)` key of the graph resolves.
+    pub fn from_graph(graph: &GraphDescription) -> Self {
+        let mut symbols = Self::new();
+
+        for (node_id, node) in &graph.nodes {
+            symbols.intern(node_id);
+            for pin in node.inputs.iter().chain(&node.outputs) {
+                symbols.intern(&pin.id);
+            }
+        }
+        for connection in &graph.connections {
+            symbols.intern(&connection.source_node);
+            symbols.intern(&connection.source_pin);
+            symbols.intern(&connection.target_node);
+            symbols.intern(&connection.target_pin);
+        }
+
+        symbols
+    }
+
+    /// Returns the symbol for a name, adding it if needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the table already holds `u32::MAX` symbols.
+    pub fn intern(&mut self, name: &str) -> SymbolId {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+
+        let id = SymbolId(u32::try_from(self.names.len()).expect("symbol table overflow"));
+        let name: Arc<str> = Arc::from(name);
+        self.names.push(Arc::clone(&name));
+        self.ids.insert(name, id);
+        id
+    }
+
+    /// Interns a `(node, pin)` pair.
+    #[inline]
+    pub fn intern_pair(&mut self, node_id: &str, pin_name: &str) -> (SymbolId, SymbolId) {
+        (self.intern(node_id), self.intern(pin_name))
+    }
+
+    /// Returns the symbol for a name, if it was interned.
+    #[inline]
+    pub fn get(&self, name: &str) -> Option<SymbolId> {
+        self.ids.get(name).copied()
+    }
+
+    /// Returns the name of a symbol.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the symbol comes from a different, smaller table.
+    #[inline]
+    pub fn resolve(&self, id: SymbolId) -> &str {
+        &self.names[id.index()]
+    }
+
+    /// Returns the symbols for a `(node, pin)` pair, if both were interned.
+    #[inline]
+    pub fn get_pair(&self, node_id: &str, pin_name: &str) -> Option<(SymbolId, SymbolId)> {
+        Some((self.get(node_id)?, self.get(pin_name)?))
+    }
+
+    /// Returns the number of interned names.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Returns `true` if no names are interned.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+impl HeapSize for SymbolId {
+    #[inline(always)]
+    fn heap_bytes(&self) -> usize {
+        0
+    }
+}
+
+impl HeapSize for SymbolTable {
+    fn heap_bytes(&self) -> usize {
+        // Keys of `ids` share the allocations of `names`, so only the table is counted for them
+        let shared: usize = self.ids.keys().map(HeapSize::heap_bytes).sum();
+        self.names.heap_bytes() + self.ids.heap_bytes() - shared
+    }
+}
@@ -0,0 +1,150 @@
+//! # Type Checking
+//!
+//! Validates every connection in a graph and records required coercions.
+//!
+//! [`TypeCheckPass`] checks each connection the same way as
+//! [`GraphDescription::validate_connection`], but accepts data connections
+//! between different types when the [`TypeCoercion`] registry has a rule for
+//! them. Those connections are recorded in a [`CoercionTable`] so code
+//! generators can wrap the source expression.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use graphy::analysis::{CoercionTable, PassManager, TypeCheckPass};
+//! use graphy::core::TypeCoercion;
+//!
+//! let coercion = TypeCoercion::rust();
+//! let mut manager = PassManager::new();
+//! manager.add_pass(TypeCheckPass::new(&provider, &coercion));
+//!
+//! let analysis = manager.run(&graph)?;
+//! let table = analysis.get::<CoercionTable>().unwrap();
+//! let expr = table.apply("mul_1", "a", "node_add_1_result");
+//! ```
+
+use super::{AnalysisContext, AnalysisPass};
+use crate::core::{data_type_name, CoercionRule, ConnectionErrorReason, DataType, GraphDescription, NodeMetadataProvider, TypeCoercion};
+use crate::GraphyError;
+use rustc_hash::FxHashMap;
+use std::borrow::Cow;
+
+/// A coercion required by one connection.
+#[derive(Debug, Clone)]
+pub struct RequiredCoercion {
+    /// Type of the source pin
+    pub from: DataType,
+
+    /// Type of the target pin
+    pub to: DataType,
+
+    /// Rule that converts `from` into `to`
+    pub rule: CoercionRule,
+}
+
+/// Coercions required by a graph's data connections.
+///
+/// Keyed by the target input pin, since each input has at most one source.
+#[derive(Debug, Clone, Default)]
+pub struct CoercionTable {
+    /// Maps (target_node, target_pin) -> required coercion
+    coercions: FxHashMap<(String, String), RequiredCoercion>,
+}
+
+impl CoercionTable {
+    /// Returns the coercion required for an input pin, if any.
+    #[inline]
+    pub fn get(&self, node_id: &str, pin_name: &str) -> Option<&RequiredCoercion> {
+        self.coercions.get(&(node_id.to_string(), pin_name.to_string()))
+    }
+
+    /// Wraps a source expression for an input pin, if it needs coercion.
+    pub fn apply<'e>(&self, node_id: &str, pin_name: &str, expr: &'e str) -> Cow<'e, str> {
+        match self.get(node_id, pin_name) {
+            Some(coercion) => Cow::Owned(coercion.rule.apply(expr)),
+            None => Cow::Borrowed(expr),
+        }
+    }
+
+    /// Returns the number of coerced connections.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.coercions.len()
+    }
+
+    /// Checks if no connections need coercion.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.coercions.is_empty()
+    }
+}
+
+/// Analysis pass that type-checks all connections.
+///
+/// Stores a [`CoercionTable`] in the analysis context.
+///
+/// # Errors
+///
+/// Fails with a [`GraphyError::InvalidConnection`] for the first invalid
+/// connection: missing nodes or pins, wrong pin directions, or a type
+/// mismatch that no coercion rule can bridge.
+pub struct TypeCheckPass<'p, P: NodeMetadataProvider> {
+    metadata_provider: &'p P,
+    coercion: &'p TypeCoercion,
+}
+
+impl<'p, P: NodeMetadataProvider> TypeCheckPass<'p, P> {
+    /// Creates a type check pass using the given provider and coercion rules.
+    #[inline]
+    pub fn new(metadata_provider: &'p P, coercion: &'p TypeCoercion) -> Self {
+        Self {
+            metadata_provider,
+            coercion,
+        }
+    }
+
+    /// Type-checks the graph and returns the required coercions.
+    pub fn check(&self, graph: &GraphDescription) -> Result<CoercionTable, GraphyError> {
+        let mut table = CoercionTable::default();
+
+        for connection in &graph.connections {
+            let (from, to) = graph.resolve_connection_types(connection, self.metadata_provider)?;
+
+            if from.is_compatible_with(&to) {
+                continue;
+            }
+
+            let rule = self.coercion.find_rule(&from, &to).ok_or_else(|| {
+                graph.connection_error(
+                    connection,
+                    ConnectionErrorReason::TypeMismatch {
+                        expected: data_type_name(&to),
+                        actual: data_type_name(&from),
+                    },
+                )
+            })?;
+
+            table.coercions.insert(
+                (connection.target_node.clone(), connection.target_pin.clone()),
+                RequiredCoercion {
+                    from,
+                    to,
+                    rule: rule.clone(),
+                },
+            );
+        }
+
+        Ok(table)
+    }
+}
+
+impl<P: NodeMetadataProvider> AnalysisPass for TypeCheckPass<'_, P> {
+    fn name(&self) -> &str {
+        "type_check"
+    }
+
+    fn run(&self, graph: &GraphDescription, ctx: &mut AnalysisContext) -> Result<(), GraphyError> {
+        ctx.insert(self.check(graph)?);
+        Ok(())
+    }
+}
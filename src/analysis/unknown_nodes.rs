@@ -0,0 +1,87 @@
+//! # Unknown Node Types
+//!
+//! Handling of nodes whose type the metadata provider doesn't know.
+//!
+//! Without metadata the [`DataResolver`](super::DataResolver) can't tell
+//! whether a node is pure, so it leaves the node out of the evaluation order,
+//! and none of its inputs can be checked. Code generated from such a graph
+//! reads results that are never computed. An [`UnknownNodePolicy`] decides
+//! what to do instead: production builds fail loudly, while editors can still
+//! open incomplete graphs (e.g. with a plugin missing).
+//!
+//! Compilation enforces the policy of
+//! [`CompileOptions::on_unknown_node`](crate::generation::CompileOptions::on_unknown_node),
+//! [`UnknownNodePolicy::Error`] by default. Builtin nodes
+//! ([`NodeInstance::is_builtin`](crate::NodeInstance::is_builtin)) are never
+//! unknown.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::analysis::UnknownNodePolicy;
+//! use graphy::core::NodeRegistry;
+//! use graphy::{GraphDescription, GraphyError, NodeInstance, Position};
+//!
+//! let mut graph = GraphDescription::new("graph");
+//! graph.add_node(NodeInstance::new("blur_1", "image.blur", Position::zero()));
+//! let registry = NodeRegistry::new();
+//!
+//! let error = UnknownNodePolicy::Error.enforce(&graph, &registry).unwrap_err();
+//! assert!(matches!(error, GraphyError::UnknownNodeType { .. }));
+//! assert_eq!(error.to_string(), "Node blur_1 has unknown type 'image.blur'");
+//!
+//! // Editors carry on
+//! assert!(UnknownNodePolicy::Skip.enforce(&graph, &registry).is_ok());
+//! ```
+
+use crate::core::{GraphDescription, NodeMetadataProvider};
+use crate::GraphyError;
+use serde::{Deserialize, Serialize};
+
+/// What to do with nodes whose type the metadata provider doesn't know.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnknownNodePolicy {
+    /// Fail with [`GraphyError::UnknownNodeType`] for the first unknown node
+    #[default]
+    Error,
+
+    /// Log a warning for every unknown node and carry on without them
+    Warn,
+
+    /// Carry on without the unknown nodes
+    Skip,
+}
+
+impl UnknownNodePolicy {
+    /// Applies the policy to the unknown nodes of a graph, by node ID.
+    ///
+    /// # Errors
+    ///
+    /// With [`Error`](Self::Error), returns [`GraphyError::UnknownNodeType`]
+    /// for the first unknown node. The other policies never fail.
+    pub fn enforce<P: NodeMetadataProvider + ?Sized>(
+        self,
+        graph: &GraphDescription,
+        metadata_provider: &P,
+    ) -> Result<(), GraphyError> {
+        if self == UnknownNodePolicy::Skip {
+            return Ok(());
+        }
+
+        for node in graph.unknown_nodes(metadata_provider) {
+            if self == UnknownNodePolicy::Error {
+                return Err(GraphyError::UnknownNodeType {
+                    node: node.id.clone(),
+                    node_type: node.node_type.clone(),
+                });
+            }
+            tracing::warn!(
+                "[ANALYSIS] Node {} has unknown type '{}', it is left out of evaluation",
+                node.id,
+                node.node_type
+            );
+        }
+        Ok(())
+    }
+}
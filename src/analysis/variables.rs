@@ -0,0 +1,157 @@
+//! # Variable Analysis
+//!
+//! Validates reads and writes of graph variables against their declarations.
+//!
+//! Graph variables are declared in [`GraphDescription::variables`] and
+//! accessed through the builtin `graph.get_variable` and `graph.set_variable`
+//! nodes (see [`GraphDescription::create_get_variable_node`] and
+//! [`GraphDescription::create_set_variable_node`]). [`VariableUsage::analyze`]
+//! checks that every such node names a declared variable with a matching
+//! value pin type, and records which nodes read and write each variable.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::analysis::VariableUsage;
+//! use graphy::core::VariableDecl;
+//! use graphy::{GraphDescription, Position};
+//!
+//! let mut graph = GraphDescription::new("player");
+//! graph.add_variable("health", VariableDecl::new("i64"));
+//! let get = graph.create_get_variable_node("get_1", "health", Position::zero()).unwrap();
+//! graph.add_node(get);
+//!
+//! let usage = VariableUsage::analyze(&graph).unwrap();
+//! assert_eq!(usage.get_readers("health"), ["get_1".to_string()]);
+//! assert!(usage.get_writers("health").is_empty());
+//! ```
+
+use super::{AnalysisContext, AnalysisPass};
+use crate::core::{
+    data_type_name, DataType, GraphDescription, NodeInstance, PinInstance, VARIABLE_NAME_PROPERTY,
+    VARIABLE_VALUE_PIN,
+};
+use crate::GraphyError;
+use rustc_hash::FxHashMap;
+
+/// Nodes reading and writing each graph variable.
+#[derive(Debug, Clone, Default)]
+pub struct VariableUsage {
+    /// Maps variable -> `graph.get_variable` nodes (sorted by ID)
+    readers: FxHashMap<String, Vec<String>>,
+
+    /// Maps variable -> `graph.set_variable` nodes (sorted by ID)
+    writers: FxHashMap<String, Vec<String>>,
+}
+
+impl VariableUsage {
+    /// Validates all variable nodes and records their usage.
+    ///
+    /// # Errors
+    ///
+    /// - [`GraphyError::Custom`] if a variable node has no variable name
+    /// - [`GraphyError::UndeclaredVariable`] if it names an undeclared variable
+    /// - [`GraphyError::TypeMismatch`] if its value pin type doesn't match
+    ///   the declared type
+    pub fn analyze(graph: &GraphDescription) -> Result<Self, GraphyError> {
+        let mut usage = VariableUsage::default();
+
+        let mut node_ids: Vec<&String> = graph.nodes.keys().collect();
+        node_ids.sort();
+
+        for node_id in node_ids {
+            let node = &graph.nodes[node_id];
+            let (pins, map) = if node.is_get_variable() {
+                (&node.outputs, &mut usage.readers)
+            } else if node.is_set_variable() {
+                (&node.inputs, &mut usage.writers)
+            } else {
+                continue;
+            };
+
+            let variable = check_variable_node(graph, node, pins)?;
+            map.entry(variable.to_string()).or_default().push(node.id.clone());
+        }
+
+        tracing::debug!(
+            "[VARIABLES] {} variable(s) read, {} written",
+            usage.readers.len(),
+            usage.writers.len()
+        );
+
+        Ok(usage)
+    }
+
+    /// Returns the nodes reading a variable, sorted by ID.
+    pub fn get_readers(&self, variable: &str) -> &[String] {
+        self.readers.get(variable).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Returns the nodes writing a variable, sorted by ID.
+    pub fn get_writers(&self, variable: &str) -> &[String] {
+        self.writers.get(variable).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Returns declared variables that are never read or written, sorted.
+    pub fn unused_variables<'g>(&self, graph: &'g GraphDescription) -> Vec<&'g str> {
+        let mut unused: Vec<&str> = graph
+            .variables
+            .keys()
+            .filter(|name| !self.readers.contains_key(*name) && !self.writers.contains_key(*name))
+            .map(String::as_str)
+            .collect();
+        unused.sort_unstable();
+        unused
+    }
+}
+
+/// Check one variable node, returning the variable it accesses
+fn check_variable_node<'n>(
+    graph: &GraphDescription,
+    node: &'n NodeInstance,
+    value_pins: &[PinInstance],
+) -> Result<&'n str, GraphyError> {
+    let variable = node.variable_name().ok_or_else(|| {
+        GraphyError::Custom(format!(
+            "Variable node {} has no '{}' property",
+            node.id, VARIABLE_NAME_PROPERTY
+        ))
+    })?;
+
+    let decl = graph
+        .get_variable(variable)
+        .ok_or_else(|| GraphyError::UndeclaredVariable {
+            node: node.id.clone(),
+            variable: variable.to_string(),
+        })?;
+
+    // A missing value pin is resolved from the declaration later
+    if let Some(pin) = value_pins.iter().find(|pin| pin.id == VARIABLE_VALUE_PIN) {
+        let declared = DataType::Typed(decl.type_info.clone());
+        if !pin.pin.data_type.is_compatible_with(&declared) {
+            return Err(GraphyError::TypeMismatch {
+                expected: data_type_name(&declared),
+                actual: data_type_name(&pin.pin.data_type),
+            });
+        }
+    }
+
+    Ok(variable)
+}
+
+/// Analysis pass wrapper for [`VariableUsage::analyze`].
+///
+/// Stores a [`VariableUsage`] in the context.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VariableCheckPass;
+
+impl AnalysisPass for VariableCheckPass {
+    fn name(&self) -> &str {
+        "variable_check"
+    }
+
+    fn run(&self, graph: &GraphDescription, ctx: &mut AnalysisContext) -> Result<(), GraphyError> {
+        ctx.insert(VariableUsage::analyze(graph)?);
+        Ok(())
+    }
+}
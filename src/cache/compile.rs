@@ -0,0 +1,48 @@
+//! Cached compilation.
+
+use super::{provider_fingerprint, CacheKey, CachedOutput, CompilationCache};
+use crate::core::{GraphDescription, NodeMetadataProvider};
+use crate::generation::{compile_with_source_map, CodeGenerator};
+use crate::GraphyError;
+
+/// Compile a graph, reusing the cached output if the graph hasn't changed
+///
+/// On a hit, the generator isn't called at all. On a miss, the graph is
+/// compiled with [`compile_with_source_map`] and the output is stored in
+/// the cache. Changing the provider's metadata changes the key, so entries
+/// compiled with older metadata are never returned; the cache's
+/// [fingerprint memo](CompilationCache::fingerprint_memo) detects the change
+/// from the provider's version.
+///
+/// # Errors
+///
+/// Fails if the cache fails, or if compilation fails on a miss (see
+/// [`compile`](crate::generation::compile)). Failed compilations aren't
+/// cached.
+pub fn compile_cached<P, G, C>(
+    graph: &GraphDescription,
+    metadata_provider: &P,
+    generator: &mut G,
+    cache: &mut C,
+) -> Result<CachedOutput, GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+    C: CompilationCache + ?Sized,
+{
+    let fingerprint = match cache.fingerprint_memo() {
+        Some(memo) => memo.fingerprint(metadata_provider),
+        None => provider_fingerprint(metadata_provider),
+    };
+    let key = CacheKey::from_parts(graph.content_hash(), fingerprint, generator.cache_id());
+    if let Some(output) = cache.get(&key)? {
+        tracing::debug!("[CACHE] Hit for '{}' ({})", graph.metadata.name, key.file_stem());
+        return Ok(output);
+    }
+
+    tracing::debug!("[CACHE] Miss for '{}' ({})", graph.metadata.name, key.file_stem());
+    let (code, source_map) = compile_with_source_map(graph, metadata_provider, generator)?;
+    let output = CachedOutput::new(code, source_map);
+    cache.put(&key, &output)?;
+    Ok(output)
+}
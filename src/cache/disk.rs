@@ -0,0 +1,160 @@
+//! On-disk compilation cache.
+
+use super::{CacheKey, CachedOutput, CompilationCache, FingerprintMemo};
+use crate::GraphyError;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Extension of entry files
+const ENTRY_EXTENSION: &str = "json";
+
+/// Compilation cache storing one JSON file per entry in a directory.
+///
+/// Entries are written to a temporary file and renamed into place, so
+/// concurrent builds sharing a directory never read a partial entry.
+/// Entries written by a different Graphy version, and entries that fail to
+/// parse, are treated as misses.
+#[derive(Debug, Clone)]
+pub struct DiskCache {
+    dir: PathBuf,
+    memo: FingerprintMemo,
+}
+
+/// Contents of an entry file
+#[derive(Serialize, Deserialize)]
+struct DiskEntry {
+    /// Graphy version that wrote the entry
+    graphy_version: String,
+
+    /// Full key, to detect file name collisions
+    key: CacheKey,
+
+    output: CachedOutput,
+}
+
+impl DiskCache {
+    /// Opens a cache directory, creating it if needed.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the directory can't be created.
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self, GraphyError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).map_err(|e| io_error(&dir, e))?;
+        Ok(Self {
+            dir,
+            memo: FingerprintMemo::new(),
+        })
+    }
+
+    /// Returns the cache directory.
+    #[inline]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the path of the file storing an entry.
+    pub fn entry_path(&self, key: &CacheKey) -> PathBuf {
+        self.dir.join(format!("{}.{}", key.file_stem(), ENTRY_EXTENSION))
+    }
+
+    /// Returns the paths of all entry files.
+    fn entry_paths(&self) -> Result<Vec<PathBuf>, GraphyError> {
+        let entries = fs::read_dir(&self.dir).map_err(|e| io_error(&self.dir, e))?;
+        let mut paths = Vec::new();
+        for entry in entries {
+            let path = entry.map_err(|e| io_error(&self.dir, e))?.path();
+            if path.extension().is_some_and(|extension| extension == ENTRY_EXTENSION) {
+                paths.push(path);
+            }
+        }
+        Ok(paths)
+    }
+
+    /// Returns the number of entries.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the directory can't be read.
+    pub fn len(&self) -> Result<usize, GraphyError> {
+        self.entry_paths().map(|paths| paths.len())
+    }
+
+    /// Checks if the cache has no entries.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the directory can't be read.
+    pub fn is_empty(&self) -> Result<bool, GraphyError> {
+        self.len().map(|len| len == 0)
+    }
+}
+
+impl CompilationCache for DiskCache {
+    fn get(&self, key: &CacheKey) -> Result<Option<CachedOutput>, GraphyError> {
+        let path = self.entry_path(key);
+        let json = match fs::read_to_string(&path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(io_error(&path, e)),
+        };
+
+        match serde_json::from_str::<DiskEntry>(&json) {
+            Ok(entry) if entry.graphy_version == env!("CARGO_PKG_VERSION") && entry.key == *key => {
+                Ok(Some(entry.output))
+            }
+            Ok(_) => Ok(None),
+            Err(e) => {
+                tracing::warn!("[CACHE] Ignoring unreadable entry {}: {}", path.display(), e);
+                Ok(None)
+            }
+        }
+    }
+
+    fn put(&mut self, key: &CacheKey, output: &CachedOutput) -> Result<(), GraphyError> {
+        let entry = DiskEntry {
+            graphy_version: env!("CARGO_PKG_VERSION").to_string(),
+            key: key.clone(),
+            output: output.clone(),
+        };
+        let json = serde_json::to_string(&entry).map_err(|e| GraphyError::Serialization(e.to_string()))?;
+
+        let path = self.entry_path(key);
+        let temp = self.dir.join(format!("{}.{}.tmp", key.file_stem(), std::process::id()));
+        fs::write(&temp, json).map_err(|e| io_error(&temp, e))?;
+        fs::rename(&temp, &path).map_err(|e| {
+            let _ = fs::remove_file(&temp);
+            io_error(&path, e)
+        })
+    }
+
+    fn remove(&mut self, key: &CacheKey) -> Result<bool, GraphyError> {
+        let path = self.entry_path(key);
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(io_error(&path, e)),
+        }
+    }
+
+    fn clear(&mut self) -> Result<(), GraphyError> {
+        for path in self.entry_paths()? {
+            match fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => return Err(io_error(&path, e)),
+            }
+        }
+        Ok(())
+    }
+
+    fn fingerprint_memo(&mut self) -> Option<&mut FingerprintMemo> {
+        Some(&mut self.memo)
+    }
+}
+
+fn io_error(path: &Path, error: std::io::Error) -> GraphyError {
+    GraphyError::Io(format!("{}: {}", path.display(), error))
+}
@@ -0,0 +1,131 @@
+//! Cache keys and provider fingerprints.
+
+use crate::core::{GraphDescription, NodeMetadataProvider, ProviderVersion, StableHasher};
+use crate::generation::CodeGenerator;
+use serde::{Deserialize, Serialize};
+
+/// Identifies one compilation in a [`CompilationCache`](super::CompilationCache).
+///
+/// Compiling the same graph (up to [semantic
+/// equality](crate::GraphDescription::semantically_equals)) with the same
+/// node metadata and the same generator produces the same code, so all
+/// three are part of the key.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CacheKey {
+    /// [`GraphDescription::content_hash`] of the graph
+    pub graph_hash: u64,
+
+    /// [`provider_fingerprint`] of the metadata provider
+    pub provider_fingerprint: u64,
+
+    /// [`CodeGenerator::cache_id`] of the generator
+    pub generator_id: String,
+}
+
+impl CacheKey {
+    /// Creates the key for compiling a graph with a provider and generator.
+    pub fn new<P, G>(graph: &GraphDescription, metadata_provider: &P, generator: &G) -> Self
+    where
+        P: NodeMetadataProvider,
+        G: CodeGenerator,
+    {
+        Self::from_parts(graph.content_hash(), provider_fingerprint(metadata_provider), generator.cache_id())
+    }
+
+    /// Creates a key from precomputed parts.
+    ///
+    /// Useful when compiling many graphs with the same provider, since the
+    /// fingerprint only needs to be computed once.
+    #[inline]
+    pub fn from_parts(graph_hash: u64, provider_fingerprint: u64, generator_id: impl Into<String>) -> Self {
+        Self {
+            graph_hash,
+            provider_fingerprint,
+            generator_id: generator_id.into(),
+        }
+    }
+
+    /// Returns a file name stem unique to this key (up to hash collisions).
+    ///
+    /// The generator ID is hashed, so the stem only contains hex digits and
+    /// dashes.
+    pub fn file_stem(&self) -> String {
+        let mut hasher = StableHasher::new();
+        hasher.write_str(&self.generator_id);
+        format!(
+            "{:016x}-{:016x}-{:016x}",
+            self.graph_hash,
+            self.provider_fingerprint,
+            hasher.finish()
+        )
+    }
+}
+
+/// A provider fingerprint, recomputed only when the provider changes.
+///
+/// Fingerprinting hashes all metadata a provider offers, which adds up when
+/// compiling many graphs. The memo records the provider's
+/// [version](NodeMetadataProvider::version) with the fingerprint and reuses
+/// it until the version changes, e.g. after a plugin updated its node types.
+/// Providers that don't track changes are fingerprinted every time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FingerprintMemo {
+    entry: Option<(ProviderVersion, u64)>,
+}
+
+impl FingerprintMemo {
+    /// Creates an empty memo.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the [`provider_fingerprint`] of a provider.
+    pub fn fingerprint<P: NodeMetadataProvider>(&mut self, metadata_provider: &P) -> u64 {
+        let version = metadata_provider.version();
+        if let (Some(version), Some((memo_version, fingerprint))) = (version, self.entry) {
+            if version == memo_version {
+                return fingerprint;
+            }
+        }
+
+        let fingerprint = provider_fingerprint(metadata_provider);
+        self.entry = version.map(|version| (version, fingerprint));
+        fingerprint
+    }
+
+    /// Returns the provider version the memoized fingerprint belongs to.
+    #[inline]
+    pub fn version(&self) -> Option<ProviderVersion> {
+        self.entry.map(|(version, _)| version)
+    }
+}
+
+/// Returns a stable hash of all node metadata a provider offers.
+///
+/// Any change to any node's metadata (a parameter, the return type, the
+/// function source, ...) changes the fingerprint, as does a change to the
+/// variants of the enum a switch node's parameter is declared with.
+/// Metadata order doesn't matter.
+pub fn provider_fingerprint<P: NodeMetadataProvider>(metadata_provider: &P) -> u64 {
+    let mut nodes = metadata_provider.get_all_nodes();
+    nodes.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+
+    let mut hasher = StableHasher::new();
+    hasher.write_len(nodes.len());
+    for node in nodes {
+        // Metadata only contains vectors and scalars, so its JSON is canonical
+        let json = serde_json::to_string(node).unwrap_or_default();
+        hasher.write_str(&json);
+
+        let variants = node
+            .switch_value()
+            .and_then(|param| metadata_provider.enum_variants(&param.param_type))
+            .unwrap_or_default();
+        hasher.write_len(variants.len());
+        for variant in variants {
+            hasher.write_str(variant);
+        }
+    }
+    hasher.finish()
+}
@@ -0,0 +1,59 @@
+//! In-memory compilation cache.
+
+use super::{CacheKey, CachedOutput, CompilationCache, FingerprintMemo};
+use crate::GraphyError;
+use rustc_hash::FxHashMap;
+
+/// Compilation cache that lives as long as the process.
+///
+/// Useful for editors that recompile on every change: undoing an edit
+/// brings back the previous graph, which is then served from the cache.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryCache {
+    entries: FxHashMap<CacheKey, CachedOutput>,
+    memo: FingerprintMemo,
+}
+
+impl MemoryCache {
+    /// Creates an empty cache.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of entries.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Checks if the cache has no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl CompilationCache for MemoryCache {
+    fn get(&self, key: &CacheKey) -> Result<Option<CachedOutput>, GraphyError> {
+        Ok(self.entries.get(key).cloned())
+    }
+
+    fn put(&mut self, key: &CacheKey, output: &CachedOutput) -> Result<(), GraphyError> {
+        self.entries.insert(key.clone(), output.clone());
+        Ok(())
+    }
+
+    fn remove(&mut self, key: &CacheKey) -> Result<bool, GraphyError> {
+        Ok(self.entries.remove(key).is_some())
+    }
+
+    fn clear(&mut self) -> Result<(), GraphyError> {
+        self.entries.clear();
+        Ok(())
+    }
+
+    fn fingerprint_memo(&mut self) -> Option<&mut FingerprintMemo> {
+        Some(&mut self.memo)
+    }
+}
@@ -0,0 +1,43 @@
+//! # Compilation Cache
+//!
+//! Skips compiling graphs that haven't changed since the last build.
+//!
+//! A [`CacheKey`] identifies a compilation by the graph's
+//! [content hash](crate::GraphDescription::content_hash), a fingerprint of
+//! the node metadata the provider offers, and the generator's
+//! [`cache_id`](crate::generation::CodeGenerator::cache_id). A
+//! [`CompilationCache`] maps keys to the generated code and its source map.
+//!
+//! Two caches are provided:
+//! - [`MemoryCache`] keeps entries for the lifetime of the process
+//! - [`DiskCache`] stores one JSON file per entry in a directory, so
+//!   incremental builds can skip unchanged graphs entirely
+//!
+//! [`compile_cached`] looks up a graph and only compiles it on a miss. Both
+//! caches keep a [`FingerprintMemo`], so providers are only fingerprinted
+//! again after their [version](crate::NodeMetadataProvider::version)
+//! changes.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use graphy::cache::{compile_cached, DiskCache};
+//!
+//! let mut cache = DiskCache::open("target/graphy-cache")?;
+//! for graph in &graphs {
+//!     let output = compile_cached(graph, &provider, &mut generator, &mut cache)?;
+//!     std::fs::write(format!("{}.rs", graph.metadata.name), output.code)?;
+//! }
+//! ```
+
+pub mod compile;
+pub mod disk;
+pub mod key;
+pub mod memory;
+pub mod store;
+
+pub use compile::*;
+pub use disk::*;
+pub use key::*;
+pub use memory::*;
+pub use store::*;
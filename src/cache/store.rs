@@ -0,0 +1,58 @@
+//! The cache trait and cached entries.
+
+use super::{CacheKey, FingerprintMemo};
+use crate::generation::SourceMap;
+use crate::GraphyError;
+use serde::{Deserialize, Serialize};
+
+/// Output of a cached compilation.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedOutput {
+    /// Generated code
+    pub code: String,
+
+    /// Source map from the code back to the nodes
+    pub source_map: SourceMap,
+}
+
+impl CachedOutput {
+    /// Creates a cached output.
+    #[inline]
+    pub fn new(code: impl Into<String>, source_map: SourceMap) -> Self {
+        Self {
+            code: code.into(),
+            source_map,
+        }
+    }
+}
+
+/// Storage for compiled graphs, keyed by [`CacheKey`].
+///
+/// Implement this trait to keep entries somewhere other than memory or a
+/// local directory (a shared build cache, a database, ...).
+pub trait CompilationCache {
+    /// Looks up an entry.
+    ///
+    /// Returns `Ok(None)` on a miss. Entries that can't be read back (e.g.
+    /// corrupt files) should be reported as misses rather than errors, so a
+    /// damaged cache only costs a recompile.
+    fn get(&self, key: &CacheKey) -> Result<Option<CachedOutput>, GraphyError>;
+
+    /// Stores an entry, replacing any previous entry with the same key.
+    fn put(&mut self, key: &CacheKey, output: &CachedOutput) -> Result<(), GraphyError>;
+
+    /// Removes an entry, returning `true` if it was present.
+    fn remove(&mut self, key: &CacheKey) -> Result<bool, GraphyError>;
+
+    /// Removes all entries.
+    fn clear(&mut self) -> Result<(), GraphyError>;
+
+    /// Returns the memo [`compile_cached`](super::compile_cached) uses for
+    /// provider fingerprints.
+    ///
+    /// Caches that keep one skip fingerprinting providers that haven't
+    /// changed since the last lookup. The default keeps none.
+    fn fingerprint_memo(&mut self) -> Option<&mut FingerprintMemo> {
+        None
+    }
+}
@@ -0,0 +1,108 @@
+//! # Graph Builder
+//!
+//! Strict graph construction with validation on every insertion.
+//!
+//! [`GraphDescription::add_connection`] accepts anything, deferring problems
+//! to analysis (or to confusing generated code). [`GraphBuilder`] wraps a
+//! graph and a metadata provider and rejects invalid nodes and connections
+//! as soon as they are added.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use graphy::core::GraphBuilder;
+//!
+//! let mut builder = GraphBuilder::new("my_graph", &provider);
+//! builder.add_node(add_node)?.add_node(print_node)?;
+//! builder.connect_data("add_1", "result", "print_1", "message")?;
+//! let graph = builder.build();
+//! ```
+
+use super::{Connection, GraphDescription, NodeInstance, NodeMetadataProvider};
+use crate::GraphyError;
+
+/// Builder that validates nodes and connections as they are added.
+///
+/// Every connection is checked with [`GraphDescription::validate_connection`],
+/// and node IDs must be unique. Failed insertions leave the graph unchanged.
+pub struct GraphBuilder<'p, P: NodeMetadataProvider> {
+    graph: GraphDescription,
+    metadata_provider: &'p P,
+}
+
+impl<'p, P: NodeMetadataProvider> GraphBuilder<'p, P> {
+    /// Creates a builder for a new empty graph.
+    #[inline]
+    pub fn new(name: impl Into<String>, metadata_provider: &'p P) -> Self {
+        Self::from_graph(GraphDescription::new(name), metadata_provider)
+    }
+
+    /// Creates a builder that continues from an existing graph.
+    ///
+    /// The existing contents are not re-validated.
+    #[inline]
+    pub fn from_graph(graph: GraphDescription, metadata_provider: &'p P) -> Self {
+        Self {
+            graph,
+            metadata_provider,
+        }
+    }
+
+    /// Adds a node.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::Custom`] if a node with the same ID already exists.
+    pub fn add_node(&mut self, node: NodeInstance) -> Result<&mut Self, GraphyError> {
+        if self.graph.nodes.contains_key(&node.id) {
+            return Err(GraphyError::Custom(format!("Duplicate node ID: {}", node.id)));
+        }
+        self.graph.add_node(node);
+        Ok(self)
+    }
+
+    /// Adds a connection after validating it.
+    ///
+    /// # Errors
+    ///
+    /// See [`GraphDescription::validate_connection`].
+    pub fn add_connection(&mut self, connection: Connection) -> Result<&mut Self, GraphyError> {
+        self.graph.try_add_connection(connection, self.metadata_provider)?;
+        Ok(self)
+    }
+
+    /// Adds a validated data connection.
+    pub fn connect_data(
+        &mut self,
+        source_node: impl Into<String>,
+        source_pin: impl Into<String>,
+        target_node: impl Into<String>,
+        target_pin: impl Into<String>,
+    ) -> Result<&mut Self, GraphyError> {
+        self.add_connection(Connection::data(source_node, source_pin, target_node, target_pin))
+    }
+
+    /// Adds a validated execution connection.
+    pub fn connect_execution(
+        &mut self,
+        source_node: impl Into<String>,
+        source_pin: impl Into<String>,
+        target_node: impl Into<String>,
+        target_pin: impl Into<String>,
+    ) -> Result<&mut Self, GraphyError> {
+        self.add_connection(Connection::execution(source_node, source_pin, target_node, target_pin))
+    }
+
+    /// Returns the graph built so far.
+    #[inline]
+    pub fn graph(&self) -> &GraphDescription {
+        &self.graph
+    }
+
+    /// Finishes building and returns the graph.
+    #[inline]
+    #[must_use]
+    pub fn build(self) -> GraphDescription {
+        self.graph
+    }
+}
@@ -0,0 +1,200 @@
+//! # Node Catalog
+//!
+//! A documentation-oriented snapshot of a provider's node types.
+//!
+//! [`export_catalog`] collects every node of a [`NodeMetadataProvider`] into
+//! a [`NodeCatalog`]: nodes grouped by category, each with its kind, params,
+//! return type, execution outputs, and description, plus the list of types
+//! the nodes use. The catalog serializes to JSON for editors and renders to
+//! Markdown or HTML with [`catalog_to_markdown`](crate::export::catalog_to_markdown)
+//! and [`catalog_to_html`](crate::export::catalog_to_html), so a node
+//! reference can be published from the same metadata Graphy compiles with.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::core::export_catalog;
+//! use graphy::{NodeMetadata, NodeRegistry, NodeTypes, ParamInfo};
+//!
+//! let mut registry = NodeRegistry::new();
+//! registry.register(
+//!     NodeMetadata::new("add", NodeTypes::pure, "Math")
+//!         .with_params(vec![ParamInfo::new("a", "f64"), ParamInfo::new("b", "f64")])
+//!         .with_return_type("f64")
+//!         .with_description("Adds two numbers"),
+//! );
+//!
+//! let catalog = export_catalog(&registry);
+//! assert_eq!(catalog.categories[0].name, "Math");
+//! assert_eq!(catalog.node("add").unwrap().description.as_deref(), Some("Adds two numbers"));
+//! assert_eq!(catalog.types, ["f64"]);
+//! ```
+
+use super::{NodeMetadata, NodeMetadataProvider, NodeTypes, ParamInfo, PinMultiplicity, PropertyValue, VariadicGroup, ERROR_EXEC_PIN};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Documentation of all node types of a provider.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeCatalog {
+    /// Categories sorted by name, each with its nodes sorted by name
+    pub categories: Vec<CatalogCategory>,
+
+    /// Every param and return type used by the nodes, sorted
+    pub types: Vec<String>,
+}
+
+/// A category of the catalog and its nodes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CatalogCategory {
+    /// Category name
+    pub name: String,
+
+    /// Nodes of the category, sorted by name
+    pub nodes: Vec<CatalogNode>,
+}
+
+/// Documentation of one node type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CatalogNode {
+    /// Node type name
+    pub name: String,
+
+    /// Node classification
+    pub node_type: NodeTypes,
+
+    /// What the node does
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Params of the node
+    ///
+    /// Inputs for most nodes; for event nodes the values the event provides.
+    pub params: Vec<CatalogParam>,
+
+    /// Inputs after the params whose number is chosen per node
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variadic: Option<VariadicGroup>,
+
+    /// Return type, `None` for nodes without a value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_type: Option<String>,
+
+    /// Execution outputs, including [`ERROR_EXEC_PIN`] for fallible nodes
+    pub exec_outputs: Vec<String>,
+
+    /// Whether calls to the node are awaited
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_async: bool,
+
+    /// Whether the node matches on its first param
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_switch: bool,
+}
+
+/// Documentation of one node param.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CatalogParam {
+    /// Param name
+    pub name: String,
+
+    /// Rust type of the param
+    pub param_type: String,
+
+    /// What the param is for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Value used when the input is neither connected nor set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<PropertyValue>,
+
+    /// Whether the input must be connected
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub required: bool,
+
+    /// How many data connections the input accepts
+    #[serde(default, skip_serializing_if = "PinMultiplicity::is_single")]
+    pub multiplicity: PinMultiplicity,
+}
+
+impl NodeCatalog {
+    /// Looks up a node by name.
+    pub fn node(&self, name: &str) -> Option<&CatalogNode> {
+        self.nodes().find(|node| node.name == name)
+    }
+
+    /// Iterates over all nodes, category by category.
+    pub fn nodes(&self) -> impl Iterator<Item = &CatalogNode> {
+        self.categories.iter().flat_map(|category| &category.nodes)
+    }
+
+    /// Returns the total number of nodes.
+    pub fn node_count(&self) -> usize {
+        self.categories.iter().map(|category| category.nodes.len()).sum()
+    }
+}
+
+impl From<&NodeMetadata> for CatalogNode {
+    fn from(metadata: &NodeMetadata) -> Self {
+        let mut exec_outputs = metadata.exec_outputs.clone();
+        if metadata.is_fallible() && !exec_outputs.iter().any(|output| output == ERROR_EXEC_PIN) {
+            exec_outputs.push(ERROR_EXEC_PIN.to_string());
+        }
+
+        Self {
+            name: metadata.name.clone(),
+            node_type: metadata.node_type,
+            description: metadata.description.clone(),
+            params: metadata.params.iter().map(CatalogParam::from).collect(),
+            variadic: metadata.variadic.clone(),
+            return_type: metadata.return_type.as_ref().map(|t| t.type_string.clone()),
+            exec_outputs,
+            is_async: metadata.is_async,
+            is_switch: metadata.is_switch,
+        }
+    }
+}
+
+impl From<&ParamInfo> for CatalogParam {
+    fn from(param: &ParamInfo) -> Self {
+        Self {
+            name: param.name.clone(),
+            param_type: param.param_type.clone(),
+            description: param.description.clone(),
+            default: param.default.clone(),
+            required: param.required,
+            multiplicity: param.multiplicity,
+        }
+    }
+}
+
+/// Collects the documentation of every node of a provider.
+///
+/// Categories and the nodes within them are sorted by name, so the catalog
+/// doesn't depend on the provider's iteration order.
+pub fn export_catalog<P: NodeMetadataProvider + ?Sized>(provider: &P) -> NodeCatalog {
+    let mut nodes = provider.get_all_nodes();
+    nodes.sort_by(|a, b| a.category.cmp(&b.category).then_with(|| a.name.cmp(&b.name)));
+
+    let mut categories: Vec<CatalogCategory> = Vec::new();
+    let mut types = BTreeSet::new();
+    for metadata in nodes {
+        types.extend(metadata.params.iter().map(|param| param.param_type.clone()));
+        types.extend(metadata.variadic.as_ref().map(|group| group.param_type.clone()));
+        types.extend(metadata.return_type.as_ref().map(|t| t.type_string.clone()));
+
+        match categories.last_mut() {
+            Some(category) if category.name == metadata.category => category.nodes.push(metadata.into()),
+            _ => categories.push(CatalogCategory {
+                name: metadata.category.clone(),
+                nodes: vec![metadata.into()],
+            }),
+        }
+    }
+
+    NodeCatalog {
+        categories,
+        types: types.into_iter().collect(),
+    }
+}
@@ -0,0 +1,179 @@
+//! # Type Coercion
+//!
+//! Implicit conversion rules between pin types.
+//!
+//! A [`TypeCoercion`] registry maps `(from, to)` type pairs to expression
+//! templates. Validation uses it to accept connections between different but
+//! convertible types, and code generators use it to wrap the source
+//! expression (e.g. `x` becomes `(x) as f64`).
+//!
+//! Registries are per target language: [`TypeCoercion::rust`] provides the
+//! standard Rust numeric widenings and `Display`-based string conversion,
+//! while other backends can start from [`TypeCoercion::new`] and register
+//! their own rules.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::core::TypeCoercion;
+//! use graphy::DataType;
+//!
+//! let rust = TypeCoercion::rust();
+//! let from = DataType::Typed("i64".into());
+//! let to = DataType::Typed("f64".into());
+//! assert_eq!(rust.coerce_expression("a + b", &from, &to).unwrap(), "(a + b) as f64");
+//!
+//! // WGSL-style constructor conversion
+//! let mut wgsl = TypeCoercion::new();
+//! wgsl.register("i32", "f32", "f32({expr})");
+//! ```
+
+use super::DataType;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+
+/// Placeholder for the source expression in coercion templates.
+pub const COERCION_EXPR_PLACEHOLDER: &str = "{expr}";
+
+/// A single coercion rule.
+///
+/// The template must contain [`COERCION_EXPR_PLACEHOLDER`], which is
+/// replaced with the source expression.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CoercionRule {
+    /// Expression template, e.g. `"({expr}) as f64"`
+    pub template: String,
+}
+
+impl CoercionRule {
+    /// Creates a new rule from an expression template.
+    #[inline]
+    pub fn new(template: impl Into<String>) -> Self {
+        Self {
+            template: template.into(),
+        }
+    }
+
+    /// Applies the rule to a source expression.
+    #[inline]
+    pub fn apply(&self, expr: &str) -> String {
+        self.template.replace(COERCION_EXPR_PLACEHOLDER, expr)
+    }
+}
+
+/// Registry of implicit type coercions for one target language.
+#[derive(Debug, Clone, Default)]
+pub struct TypeCoercion {
+    /// Maps (from, to) normalized type strings -> rule
+    rules: FxHashMap<(String, String), CoercionRule>,
+
+    /// Maps target type -> rule that accepts any source type
+    any_source_rules: FxHashMap<String, CoercionRule>,
+}
+
+impl TypeCoercion {
+    /// Creates an empty registry (only identical types are compatible).
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry with the standard Rust coercions:
+    ///
+    /// - Lossless integer widening (e.g. `i32` -> `i64`, `u8` -> `i16`)
+    /// - Integer -> `f32`/`f64` and `f32` -> `f64`
+    /// - Any type -> `String` via `Display` (`.to_string()`)
+    pub fn rust() -> Self {
+        let mut coercion = Self::new();
+
+        const SIGNED: [&str; 5] = ["i8", "i16", "i32", "i64", "i128"];
+        const UNSIGNED: [&str; 5] = ["u8", "u16", "u32", "u64", "u128"];
+
+        for (i, from) in SIGNED.iter().enumerate() {
+            for to in &SIGNED[i + 1..] {
+                coercion.register_cast(from, to);
+            }
+        }
+
+        for (i, from) in UNSIGNED.iter().enumerate() {
+            for to in &UNSIGNED[i + 1..] {
+                coercion.register_cast(from, to);
+            }
+            // Unsigned fits in any strictly wider signed type
+            for to in &SIGNED[i + 1..] {
+                coercion.register_cast(from, to);
+            }
+        }
+
+        for from in SIGNED.iter().chain(&UNSIGNED).chain(["isize", "usize"].iter()) {
+            coercion.register_cast(from, "f32");
+            coercion.register_cast(from, "f64");
+        }
+        coercion.register_cast("f32", "f64");
+
+        coercion.register_any_to("String", "({expr}).to_string()");
+
+        coercion
+    }
+
+    /// Registers a coercion from `from` to `to` using an expression template.
+    ///
+    /// Replaces any existing rule for the same pair.
+    pub fn register(&mut self, from: &str, to: &str, template: impl Into<String>) -> &mut Self {
+        self.rules
+            .insert((normalize_type(from), normalize_type(to)), CoercionRule::new(template));
+        self
+    }
+
+    /// Registers an `as` cast from `from` to `to` (`({expr}) as to`).
+    pub fn register_cast(&mut self, from: &str, to: &str) -> &mut Self {
+        self.register(from, to, format!("({}) as {}", COERCION_EXPR_PLACEHOLDER, to))
+    }
+
+    /// Registers a coercion from any data type to `to`.
+    ///
+    /// Exact `(from, to)` rules take precedence over these.
+    pub fn register_any_to(&mut self, to: &str, template: impl Into<String>) -> &mut Self {
+        self.any_source_rules.insert(normalize_type(to), CoercionRule::new(template));
+        self
+    }
+
+    /// Looks up the rule that converts `from` into `to`.
+    ///
+    /// Returns `None` if the types are already compatible or no rule applies.
+    pub fn find_rule(&self, from: &DataType, to: &DataType) -> Option<&CoercionRule> {
+        if from.is_compatible_with(to) {
+            return None;
+        }
+
+        let (from, to) = (from.type_string()?, to.type_string()?);
+        let to = normalize_type(to);
+
+        self.rules
+            .get(&(normalize_type(from), to.clone()))
+            .or_else(|| self.any_source_rules.get(&to))
+    }
+
+    /// Checks if a value of type `from` can flow into a pin of type `to`,
+    /// either directly or through a registered coercion.
+    pub fn can_coerce(&self, from: &DataType, to: &DataType) -> bool {
+        from.is_compatible_with(to) || self.find_rule(from, to).is_some()
+    }
+
+    /// Converts a source expression of type `from` into type `to`.
+    ///
+    /// Returns the expression unchanged if the types are compatible, the
+    /// rewritten expression if a rule applies, and `None` otherwise.
+    pub fn coerce_expression(&self, expr: &str, from: &DataType, to: &DataType) -> Option<String> {
+        if from.is_compatible_with(to) {
+            return Some(expr.to_string());
+        }
+        self.find_rule(from, to).map(|rule| rule.apply(expr))
+    }
+}
+
+/// Strip whitespace so `(f32, f32)` and `(f32,f32)` share one key
+#[inline]
+fn normalize_type(type_string: &str) -> String {
+    type_string.chars().filter(|c| !c.is_whitespace()).collect()
+}
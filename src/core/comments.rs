@@ -0,0 +1,125 @@
+//! # Comment Anchoring
+//!
+//! Keeping comments around the nodes they describe.
+//!
+//! A [`GraphComment`] attached to nodes frames them: its box is the
+//! bounding rectangle of the attached nodes plus some padding, see
+//! [`GraphDescription::comment_bounds`]. Nodes carry no size, so a
+//! [`CommentFit`] supplies the size of a node box. After nodes move,
+//! [`GraphDescription::fit_comments`] moves and resizes every attached
+//! comment to frame its nodes again.
+//!
+//! Attachments follow the nodes through editing operations: removing a node
+//! detaches it, [duplicating](GraphDescription::duplicate_nodes) every node
+//! of a comment duplicates the comment, and
+//! [extracting](crate::utils::extract_subgraph) them moves it into the
+//! sub-graph.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::core::{CommentFit, GraphComment};
+//! use graphy::{GraphDescription, NodeInstance, Position};
+//!
+//! let mut graph = GraphDescription::new("graph");
+//! graph.add_node(NodeInstance::new("spawn_1", "spawn", Position::new(0.0, 0.0)));
+//! graph.add_node(NodeInstance::new("print_1", "print", Position::new(300.0, 0.0)));
+//! graph.add_comment(
+//!     GraphComment::new("Spawning", Position::zero(), (0.0, 0.0)).with_attached_nodes(["spawn_1", "print_1"]),
+//! );
+//!
+//! graph.get_node_mut("print_1").unwrap().position = Position::new(500.0, 100.0);
+//! assert_eq!(graph.fit_comments(&CommentFit::new((200.0, 100.0), 20.0)), 1);
+//!
+//! let comment = &graph.comments[0];
+//! assert_eq!(comment.position, Position::new(-20.0, -20.0));
+//! assert_eq!(comment.size, (740.0, 240.0));
+//! ```
+
+use super::{GraphComment, GraphDescription, Position};
+use serde::{Deserialize, Serialize};
+
+/// How comments are fitted around their attached nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CommentFit {
+    /// Size of a node box (width, height), from its position
+    pub node_size: (f64, f64),
+
+    /// Space between the node boxes and the comment's border
+    pub padding: f64,
+}
+
+impl CommentFit {
+    /// Fits comments around node boxes of `node_size`, `padding` away from
+    /// the border
+    #[inline]
+    pub fn new(node_size: (f64, f64), padding: f64) -> Self {
+        Self { node_size, padding }
+    }
+}
+
+impl Default for CommentFit {
+    /// 200 by 100 node boxes with 20 units of padding
+    fn default() -> Self {
+        Self::new((200.0, 100.0), 20.0)
+    }
+}
+
+impl GraphDescription {
+    /// Adds a comment to the graph.
+    #[inline]
+    pub fn add_comment(&mut self, comment: GraphComment) {
+        self.comments.push(comment);
+    }
+
+    /// Returns the comments attached to a node.
+    pub fn comments_attached_to<'a>(&'a self, node_id: &'a str) -> impl Iterator<Item = &'a GraphComment> + 'a {
+        self.comments.iter().filter(move |comment| comment.is_attached_to(node_id))
+    }
+
+    /// Computes the box framing a comment's attached nodes.
+    ///
+    /// Returns the top-left corner and size of the bounding rectangle of the
+    /// attached node boxes, grown by the padding on every side, or `None` if
+    /// none of the attached nodes is in the graph.
+    pub fn comment_bounds(&self, comment: &GraphComment, fit: &CommentFit) -> Option<(Position, (f64, f64))> {
+        let (width, height) = fit.node_size;
+        let mut positions = comment
+            .attached_node_ids
+            .iter()
+            .filter_map(|id| self.nodes.get(id))
+            .map(|node| node.position);
+
+        let first = positions.next()?;
+        let (mut min, mut max) = (first, Position::new(first.x + width, first.y + height));
+        for position in positions {
+            min = Position::new(min.x.min(position.x), min.y.min(position.y));
+            max = Position::new(max.x.max(position.x + width), max.y.max(position.y + height));
+        }
+
+        let corner = Position::new(min.x - fit.padding, min.y - fit.padding);
+        let size = (max.x - min.x + 2.0 * fit.padding, max.y - min.y + 2.0 * fit.padding);
+        Some((corner, size))
+    }
+
+    /// Moves and resizes every attached comment to frame its nodes.
+    ///
+    /// Comments without attached nodes in the graph are left as they are.
+    /// Returns the number of comments that changed.
+    pub fn fit_comments(&mut self, fit: &CommentFit) -> usize {
+        let bounds: Vec<_> = self.comments.iter().map(|comment| self.comment_bounds(comment, fit)).collect();
+
+        let mut changed = 0;
+        for (comment, bounds) in self.comments.iter_mut().zip(bounds) {
+            let Some((position, size)) = bounds else {
+                continue;
+            };
+            if comment.position != position || comment.size != size {
+                comment.position = position;
+                comment.size = size;
+                changed += 1;
+            }
+        }
+        changed
+    }
+}
@@ -0,0 +1,246 @@
+//! # Build Conditions
+//!
+//! Conditional compilation inside graphs.
+//!
+//! Nodes and groups can carry a [`BuildCondition`] on enabled features and
+//! the target platform, like `#[cfg]` attributes. A [`CompileConfig`] names
+//! the enabled features and the platform, and
+//! [`prune_disabled`](GraphDescription::prune_disabled) removes the nodes
+//! whose condition (or whose group's condition) doesn't hold, with their
+//! execution and data connections. The compile pipeline prunes the graph
+//! with [`CompileOptions::with_config`](crate::generation::CompileOptions::with_config)
+//! before analysis, so one graph serves every platform.
+//!
+//! Flows through a pruned node stop there, and inputs it fed become
+//! unconnected: they use their constant property or default value, or fail
+//! compilation if required.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::core::{BuildCondition, CompileConfig};
+//! use graphy::{GraphDescription, NodeInstance, Position};
+//!
+//! let mut graph = GraphDescription::new("input");
+//! let mut rumble = NodeInstance::new("rumble_1", "rumble", Position::zero());
+//! rumble.condition = Some(BuildCondition::platform("console") & !BuildCondition::feature("no_haptics"));
+//! graph.add_node(rumble);
+//!
+//! let mut desktop = graph.clone();
+//! assert_eq!(desktop.prune_disabled(&CompileConfig::new().with_platform("windows")), ["rumble_1"]);
+//!
+//! let mut console = graph.clone();
+//! assert!(console.prune_disabled(&CompileConfig::new().with_platform("console")).is_empty());
+//! ```
+
+use super::GraphDescription;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+use std::ops::{BitAnd, BitOr, Not};
+
+/// A condition on the build configuration, see [`CompileConfig`].
+///
+/// Conditions combine with `&`, `|`, and `!`, and display like `#[cfg]`
+/// predicates (`all(platform = "console", not(feature = "no_haptics"))`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BuildCondition {
+    /// Holds if the feature is enabled
+    Feature(String),
+
+    /// Holds if compiling for the platform
+    Platform(String),
+
+    /// Holds if every condition holds (or there are none)
+    All(Vec<BuildCondition>),
+
+    /// Holds if any condition holds
+    Any(Vec<BuildCondition>),
+
+    /// Holds if the condition doesn't
+    Not(Box<BuildCondition>),
+}
+
+impl BuildCondition {
+    /// Creates a condition holding if a feature is enabled.
+    #[inline]
+    pub fn feature(name: impl Into<String>) -> Self {
+        BuildCondition::Feature(name.into())
+    }
+
+    /// Creates a condition holding when compiling for a platform.
+    #[inline]
+    pub fn platform(name: impl Into<String>) -> Self {
+        BuildCondition::Platform(name.into())
+    }
+
+    /// Checks if the condition holds in a configuration.
+    pub fn evaluate(&self, config: &CompileConfig) -> bool {
+        match self {
+            BuildCondition::Feature(name) => config.enabled_features.contains(name),
+            BuildCondition::Platform(name) => config.target_platform.as_deref() == Some(name.as_str()),
+            BuildCondition::All(conditions) => conditions.iter().all(|condition| condition.evaluate(config)),
+            BuildCondition::Any(conditions) => conditions.iter().any(|condition| condition.evaluate(config)),
+            BuildCondition::Not(condition) => !condition.evaluate(config),
+        }
+    }
+}
+
+impl fmt::Display for BuildCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |f: &mut fmt::Formatter<'_>, name: &str, conditions: &[BuildCondition]| {
+            let conditions: Vec<String> = conditions.iter().map(ToString::to_string).collect();
+            write!(f, "{}({})", name, conditions.join(", "))
+        };
+        match self {
+            BuildCondition::Feature(name) => write!(f, "feature = {:?}", name),
+            BuildCondition::Platform(name) => write!(f, "platform = {:?}", name),
+            BuildCondition::All(conditions) => list(f, "all", conditions),
+            BuildCondition::Any(conditions) => list(f, "any", conditions),
+            BuildCondition::Not(condition) => write!(f, "not({})", condition),
+        }
+    }
+}
+
+impl BitAnd for BuildCondition {
+    type Output = BuildCondition;
+
+    /// Both conditions, flattening nested `all`s
+    fn bitand(self, other: BuildCondition) -> BuildCondition {
+        match (self, other) {
+            (BuildCondition::All(mut conditions), BuildCondition::All(others)) => {
+                conditions.extend(others);
+                BuildCondition::All(conditions)
+            }
+            (BuildCondition::All(mut conditions), other) => {
+                conditions.push(other);
+                BuildCondition::All(conditions)
+            }
+            (first, second) => BuildCondition::All(vec![first, second]),
+        }
+    }
+}
+
+impl BitOr for BuildCondition {
+    type Output = BuildCondition;
+
+    /// Either condition, flattening nested `any`s
+    fn bitor(self, other: BuildCondition) -> BuildCondition {
+        match (self, other) {
+            (BuildCondition::Any(mut conditions), BuildCondition::Any(others)) => {
+                conditions.extend(others);
+                BuildCondition::Any(conditions)
+            }
+            (BuildCondition::Any(mut conditions), other) => {
+                conditions.push(other);
+                BuildCondition::Any(conditions)
+            }
+            (first, second) => BuildCondition::Any(vec![first, second]),
+        }
+    }
+}
+
+impl Not for BuildCondition {
+    type Output = BuildCondition;
+
+    fn not(self) -> BuildCondition {
+        match self {
+            BuildCondition::Not(condition) => *condition,
+            condition => BuildCondition::Not(Box::new(condition)),
+        }
+    }
+}
+
+/// The features and platform a graph is compiled for.
+///
+/// The default enables no features and targets no platform, so only nodes
+/// without conditions (or with conditions holding anyway, like
+/// `not(feature = "x")`) are compiled.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompileConfig {
+    /// Enabled features
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub enabled_features: BTreeSet<String>,
+
+    /// Platform compiled for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_platform: Option<String>,
+}
+
+impl CompileConfig {
+    /// Creates a configuration without features or platform.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enables a feature.
+    #[inline]
+    #[must_use]
+    pub fn with_feature(mut self, feature: impl Into<String>) -> Self {
+        self.enabled_features.insert(feature.into());
+        self
+    }
+
+    /// Sets the platform compiled for.
+    #[inline]
+    #[must_use]
+    pub fn with_platform(mut self, platform: impl Into<String>) -> Self {
+        self.target_platform = Some(platform.into());
+        self
+    }
+}
+
+impl GraphDescription {
+    /// Checks if a node is compiled in a configuration.
+    ///
+    /// A node is enabled if its [`condition`](super::NodeInstance::condition)
+    /// and the condition of its group hold. Returns `false` for nodes that
+    /// don't exist.
+    pub fn is_node_enabled(&self, node_id: &str, config: &CompileConfig) -> bool {
+        let Some(node) = self.nodes.get(node_id) else {
+            return false;
+        };
+        node.condition.as_ref().is_none_or(|condition| condition.evaluate(config))
+            && self
+                .group_of(node_id)
+                .and_then(|group| group.condition.as_ref())
+                .is_none_or(|condition| condition.evaluate(config))
+    }
+
+    /// Removes the nodes disabled in a configuration.
+    ///
+    /// Connections to and from the removed nodes are removed, and the nodes
+    /// are taken out of their groups. Returns the IDs of the removed nodes,
+    /// sorted.
+    pub fn prune_disabled(&mut self, config: &CompileConfig) -> Vec<String> {
+        let has_conditions = self.nodes.values().any(|node| node.condition.is_some())
+            || self.groups.iter().any(|group| group.condition.is_some());
+        if !has_conditions {
+            return Vec::new();
+        }
+
+        let mut removed: Vec<String> = self
+            .nodes
+            .keys()
+            .filter(|id| !self.is_node_enabled(id, config))
+            .cloned()
+            .collect();
+        removed.sort_unstable();
+        if removed.is_empty() {
+            return removed;
+        }
+
+        let removed_set: HashSet<&str> = removed.iter().map(String::as_str).collect();
+        self.nodes.retain(|id, _| !removed_set.contains(id.as_str()));
+        self.connections.retain(|connection| {
+            !removed_set.contains(connection.source_node.as_str())
+                && !removed_set.contains(connection.target_node.as_str())
+        });
+        for group in &mut self.groups {
+            group.node_ids.retain(|id| !removed_set.contains(id.as_str()));
+        }
+        removed
+    }
+}
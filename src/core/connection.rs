@@ -17,13 +17,63 @@
 //! // Execution connection
 //! let exec_conn = Connection::execution("start", "exec", "print_1", "exec");
 //! ```
+//!
+//! # Editor metadata
+//!
+//! Connections can also carry an ID, reroute waypoints, a label, and a
+//! color tag. None of them affect analysis or code generation, and all are
+//! omitted from serialized graphs when unset, so files written before they
+//! existed still load. The ID gives a connection a stable identity: diffs
+//! report a connection whose ID is kept but whose endpoints or metadata
+//! changed as modified rather than removed and re-added, and error messages
+//! name it.
+//!
+//! # Errors
+//!
+//! Errors about a single connection are
+//! [`GraphyError::InvalidConnection`]: a [`ConnectionRef`] naming the
+//! endpoints, the position in the graph, and the
+//! [`stable_id`](Connection::stable_id), with a [`ConnectionErrorReason`]
+//! tools can match on to highlight the wire.
+//!
+//! ```
+//! use graphy::core::ConnectionErrorReason;
+//! use graphy::{Connection, GraphDescription, GraphyError, NodeInstance, Position};
+//! use graphy::core::NodeRegistry;
+//!
+//! let mut graph = GraphDescription::new("demo");
+//! graph.add_node(NodeInstance::new("print_1", "print", Position::zero()));
+//! let conn = Connection::data("ghost", "result", "print_1", "value");
+//!
+//! match graph.validate_connection(&conn, &NodeRegistry::new()) {
+//!     Err(GraphyError::InvalidConnection { connection, reason }) => {
+//!         assert_eq!(connection.id, conn.stable_id());
+//!         assert_eq!(reason, ConnectionErrorReason::MissingNode { node: "ghost".into() });
+//!     }
+//!     other => panic!("{:?}", other),
+//! }
+//! ```
+//!
+//! ```
+//! use graphy::{Connection, Position};
+//!
+//! let conn = Connection::data("add_1", "result", "print_1", "value")
+//!     .with_id("c1")
+//!     .with_waypoints(vec![Position::new(120.0, 40.0)])
+//!     .with_label("sum")
+//!     .with_color("#ff8800");
+//! assert_eq!(conn.to_string(), "c1: add_1.result -> print_1.value");
+//! ```
 
+use super::{PinType, Position, StableHasher};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::hash::{Hash, Hasher};
 
 /// Type of connection between nodes.
 ///
 /// Determines whether the connection carries data values or execution flow.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ConnectionType {
     /// Data connection that passes values between nodes
     ///
@@ -43,7 +93,7 @@ pub enum ConnectionType {
 /// - Both pins exist  
 /// - Pin types are compatible
 /// - No circular dependencies (for data connections)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Connection {
     /// ID of the source node
     pub source_node: String,
@@ -59,6 +109,58 @@ pub struct Connection {
 
     /// Type of connection (data or execution)
     pub connection_type: ConnectionType,
+
+    /// Stable identifier, used to track the connection across edits
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+
+    /// Reroute points the editor draws the wire through, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub waypoints: Vec<Position>,
+
+    /// User label shown on the wire
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+
+    /// Color tag (e.g. `"#ff8800"` or an editor theme color name)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+// Waypoints are floats, so `Eq` and `Hash` can't be derived. Coordinates are
+// hashed by bit pattern, with -0.0 folded into 0.0 to agree with `==`.
+impl Eq for Connection {}
+
+impl Hash for Connection {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.source_node.hash(state);
+        self.source_pin.hash(state);
+        self.target_node.hash(state);
+        self.target_pin.hash(state);
+        self.connection_type.hash(state);
+        self.id.hash(state);
+        self.waypoints.len().hash(state);
+        for waypoint in &self.waypoints {
+            (waypoint.x + 0.0).to_bits().hash(state);
+            (waypoint.y + 0.0).to_bits().hash(state);
+        }
+        self.label.hash(state);
+        self.color.hash(state);
+    }
+}
+
+impl fmt::Display for Connection {
+    /// Formats as `source.pin -> target.pin`, prefixed by the ID if set.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(id) = &self.id {
+            write!(f, "{}: ", id)?;
+        }
+        write!(
+            f,
+            "{}.{} -> {}.{}",
+            self.source_node, self.source_pin, self.target_node, self.target_pin
+        )
+    }
 }
 
 impl Connection {
@@ -89,6 +191,10 @@ impl Connection {
             target_node: target_node.into(),
             target_pin: target_pin.into(),
             connection_type,
+            id: None,
+            waypoints: Vec::new(),
+            label: None,
+            color: None,
         }
     }
 
@@ -133,4 +239,230 @@ impl Connection {
     ) -> Self {
         Self::new(source_node, source_pin, target_node, target_pin, ConnectionType::Execution)
     }
+
+    /// Sets the stable identifier.
+    #[inline]
+    #[must_use]
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets the reroute points the wire passes through.
+    #[inline]
+    #[must_use]
+    pub fn with_waypoints(mut self, waypoints: Vec<Position>) -> Self {
+        self.waypoints = waypoints;
+        self
+    }
+
+    /// Sets the label shown on the wire.
+    #[inline]
+    #[must_use]
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Sets the color tag.
+    #[inline]
+    #[must_use]
+    pub fn with_color(mut self, color: impl Into<String>) -> Self {
+        self.color = Some(color.into());
+        self
+    }
+
+    /// Checks if two connections join the same pins with the same type.
+    ///
+    /// Ignores the ID and editor metadata.
+    #[inline]
+    pub fn same_endpoints(&self, other: &Connection) -> bool {
+        self.source_node == other.source_node
+            && self.source_pin == other.source_pin
+            && self.target_node == other.target_node
+            && self.target_pin == other.target_pin
+            && self.connection_type == other.connection_type
+    }
+
+    /// Returns the ID error messages name the connection by.
+    ///
+    /// That's the [`id`](Self::id) if set, and otherwise `c` followed by 12
+    /// hex digits hashed from the endpoints and connection type, so the same
+    /// wire gets the same ID across runs and machines.
+    pub fn stable_id(&self) -> String {
+        if let Some(id) = &self.id {
+            return id.clone();
+        }
+        let mut hasher = StableHasher::new();
+        hasher.write_str(&self.source_node);
+        hasher.write_str(&self.source_pin);
+        hasher.write_str(&self.target_node);
+        hasher.write_str(&self.target_pin);
+        hasher.write_u8(match self.connection_type {
+            ConnectionType::Data => 0,
+            ConnectionType::Execution => 1,
+        });
+        format!("c{:012x}", hasher.finish() >> 16)
+    }
+}
+
+/// The connection an error is about, see [`GraphyError::InvalidConnection`].
+///
+/// [`GraphyError::InvalidConnection`]: crate::GraphyError::InvalidConnection
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ConnectionRef {
+    /// [`Connection::stable_id`] of the connection
+    pub id: String,
+
+    /// Position in [`connections`](super::GraphDescription::connections),
+    /// `None` if the connection isn't in the graph (yet)
+    pub index: Option<usize>,
+
+    /// ID of the source node
+    pub source_node: String,
+
+    /// ID of the output pin on the source node
+    pub source_pin: String,
+
+    /// ID of the target node
+    pub target_node: String,
+
+    /// ID of the input pin on the target node
+    pub target_pin: String,
+
+    /// Type of connection (data or execution)
+    pub connection_type: ConnectionType,
+}
+
+impl ConnectionRef {
+    /// Refers to a connection at a position in the graph's connections.
+    pub fn new(connection: &Connection, index: Option<usize>) -> Self {
+        Self {
+            id: connection.stable_id(),
+            index,
+            source_node: connection.source_node.clone(),
+            source_pin: connection.source_pin.clone(),
+            target_node: connection.target_node.clone(),
+            target_pin: connection.target_pin.clone(),
+            connection_type: connection.connection_type,
+        }
+    }
+}
+
+impl fmt::Display for ConnectionRef {
+    /// Formats as `id: source.pin -> target.pin`, followed by the index if
+    /// known.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}.{} -> {}.{}",
+            self.id, self.source_node, self.source_pin, self.target_node, self.target_pin
+        )?;
+        if let Some(index) = self.index {
+            write!(f, " (#{})", index)?;
+        }
+        Ok(())
+    }
+}
+
+/// Why a connection is invalid, see [`GraphyError::InvalidConnection`].
+///
+/// [`GraphyError::InvalidConnection`]: crate::GraphyError::InvalidConnection
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ConnectionErrorReason {
+    /// An endpoint node doesn't exist
+    MissingNode {
+        /// ID of the missing node
+        node: String,
+    },
+
+    /// An endpoint pin doesn't exist
+    MissingPin {
+        /// ID of the node
+        node: String,
+
+        /// ID of the missing pin
+        pin: String,
+    },
+
+    /// An endpoint pin exists, but in the other direction
+    WrongDirection {
+        /// ID of the node
+        node: String,
+
+        /// ID of the pin
+        pin: String,
+
+        /// Direction the connection needs
+        expected: PinType,
+    },
+
+    /// The connection is drawn backwards: its source is an input and its
+    /// target an output
+    Reversed,
+
+    /// A data connection joins execution pins, or the other way around
+    ConnectionTypeMismatch {
+        /// Type of the source pin
+        source: String,
+
+        /// Type of the target pin
+        target: String,
+    },
+
+    /// The data types of the pins are incompatible
+    TypeMismatch {
+        /// Type of the target pin
+        expected: String,
+
+        /// Type of the source pin
+        actual: String,
+    },
+
+    /// The target input would have more data connections than it accepts
+    TooManyConnections {
+        /// Data connections the input would have
+        connections: usize,
+
+        /// Data connections it accepts
+        max: usize,
+    },
+
+    /// The connection isn't in the graph
+    NotFound,
+}
+
+impl fmt::Display for ConnectionErrorReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let direction = |direction: &PinType| match direction {
+            PinType::Input => "input",
+            PinType::Output => "output",
+        };
+        match self {
+            ConnectionErrorReason::MissingNode { node } => write!(f, "node {} doesn't exist", node),
+            ConnectionErrorReason::MissingPin { node, pin } => write!(f, "pin {}.{} doesn't exist", node, pin),
+            ConnectionErrorReason::WrongDirection { node, pin, expected } => write!(
+                f,
+                "pin {}.{} is an {}, expected an {}",
+                node,
+                pin,
+                direction(&expected.opposite()),
+                direction(expected)
+            ),
+            ConnectionErrorReason::Reversed => write!(f, "drawn backwards, from an input to an output"),
+            ConnectionErrorReason::ConnectionTypeMismatch { source, target } => {
+                write!(f, "joins {} and {} pins", source, target)
+            }
+            ConnectionErrorReason::TypeMismatch { expected, actual } => {
+                write!(f, "type mismatch, expected {}, got {}", expected, actual)
+            }
+            ConnectionErrorReason::TooManyConnections { connections, max } => write!(
+                f,
+                "the input would have {} connections, but accepts at most {}",
+                connections, max
+            ),
+            ConnectionErrorReason::NotFound => write!(f, "not found in the graph"),
+        }
+    }
 }
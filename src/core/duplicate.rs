@@ -0,0 +1,184 @@
+//! # Node Duplication
+//!
+//! Copying a selection of nodes within a graph, as editors do for copy and
+//! paste or duplicate commands.
+//!
+//! Copies get fresh IDs from an [`IdStrategy`]. IDs are deterministic: the
+//! same graph and selection always produce the same IDs, and they never
+//! collide with a node already in the graph.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::{Connection, GraphDescription, IdStrategy, NodeInstance, Position};
+//!
+//! let mut graph = GraphDescription::new("graph");
+//! graph.add_node(NodeInstance::new("add_1", "math.add", Position::zero()));
+//! graph.add_node(NodeInstance::new("print_1", "print", Position::new(200.0, 0.0)));
+//! graph.add_connection(Connection::data("add_1", "result", "print_1", "value"));
+//!
+//! let copies = graph
+//!     .duplicate_nodes(&["add_1", "print_1"], &IdStrategy::Suffix, Position::new(0.0, 100.0))
+//!     .unwrap();
+//! assert_eq!(copies, ["add_2", "print_2"]);
+//! assert_eq!(graph.nodes["print_2"].position, Position::new(200.0, 100.0));
+//!
+//! // The connection between the copies is copied too
+//! assert!(graph
+//!     .connections
+//!     .iter()
+//!     .any(|c| c.source_node == "add_2" && c.target_node == "print_2"));
+//! ```
+
+use super::{GraphDescription, Position, StableHasher};
+use crate::GraphyError;
+use std::collections::HashMap;
+
+/// How [`GraphDescription::duplicate_nodes`] names copies.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum IdStrategy {
+    /// The original ID without its numeric suffix, plus the first free
+    /// number: `add_1` and `add` both become `add_2` if `add_1` is taken
+    #[default]
+    Suffix,
+
+    /// A UUID in the standard hyphenated form
+    ///
+    /// Derived from the graph name, the original ID, and the node count, so
+    /// it's reproducible rather than random.
+    Uuid,
+}
+
+impl IdStrategy {
+    /// Returns an ID for a copy of `base` that no node in the graph uses.
+    pub fn generate(&self, graph: &GraphDescription, base: &str) -> String {
+        match self {
+            IdStrategy::Suffix => {
+                let stem = strip_numeric_suffix(base);
+                (1u64..)
+                    .map(|n| format!("{}_{}", stem, n))
+                    .find(|id| !graph.nodes.contains_key(id))
+                    .unwrap_or_default()
+            }
+            IdStrategy::Uuid => (0u64..)
+                .map(|attempt| deterministic_uuid(&graph.metadata.name, base, graph.nodes.len(), attempt))
+                .find(|id| !graph.nodes.contains_key(id))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// `add_12` -> `add`; IDs without a numeric suffix are returned unchanged
+fn strip_numeric_suffix(id: &str) -> &str {
+    match id.rsplit_once('_') {
+        Some((stem, digits)) if !stem.is_empty() && !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) => {
+            stem
+        }
+        _ => id,
+    }
+}
+
+/// A version 4 formatted UUID hashed from its inputs
+fn deterministic_uuid(graph_name: &str, base: &str, node_count: usize, attempt: u64) -> String {
+    let half = |lane: u8| {
+        let mut hasher = StableHasher::new();
+        hasher.write_u8(lane);
+        hasher.write_str(graph_name);
+        hasher.write_str(base);
+        hasher.write_len(node_count);
+        hasher.write_u64(attempt);
+        hasher.finish()
+    };
+    let high = half(0);
+    // Version 4, RFC 4122 variant
+    let high = (high & !0xf000) | 0x4000;
+    let low = (half(1) & !(0b11 << 62)) | (0b10 << 62);
+
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        high >> 32,
+        (high >> 16) & 0xffff,
+        high & 0xffff,
+        low >> 48,
+        low & 0xffff_ffff_ffff
+    )
+}
+
+impl GraphDescription {
+    /// Copies nodes within the graph, returning the IDs of the copies in the
+    /// order given.
+    ///
+    /// Copies keep the type, pins, and properties of their originals, get
+    /// IDs from `id_strategy`, and are moved by `offset`. Connections
+    /// between two selected nodes are copied to connect the copies;
+    /// connections to unselected nodes are not. Copied connections have no
+    /// ID (see [`assign_connection_ids`](Self::assign_connection_ids)), and
+    /// copies aren't added to groups. Comments attached only to selected
+    /// nodes are copied too, moved by `offset` and attached to the copies.
+    /// IDs listed twice are copied once.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::NodeNotFound`] if a node doesn't exist (the
+    /// graph is unchanged).
+    pub fn duplicate_nodes<S: AsRef<str>>(
+        &mut self,
+        ids: &[S],
+        id_strategy: &IdStrategy,
+        offset: Position,
+    ) -> Result<Vec<String>, GraphyError> {
+        if let Some(missing) = ids.iter().find(|id| !self.nodes.contains_key(id.as_ref())) {
+            return Err(GraphyError::NodeNotFound(missing.as_ref().to_string()));
+        }
+
+        let mut new_ids: HashMap<String, String> = HashMap::with_capacity(ids.len());
+        let mut copies = Vec::with_capacity(ids.len());
+        for id in ids.iter().map(AsRef::as_ref) {
+            if new_ids.contains_key(id) {
+                continue;
+            }
+
+            let mut node = self.nodes[id].clone();
+            node.id = id_strategy.generate(self, id);
+            node.position = Position::new(node.position.x + offset.x, node.position.y + offset.y);
+            new_ids.insert(id.to_string(), node.id.clone());
+            copies.push(node.id.clone());
+            self.add_node(node);
+        }
+
+        let copied_connections: Vec<_> = self
+            .connections
+            .iter()
+            .filter_map(|connection| {
+                let source = new_ids.get(&connection.source_node)?;
+                let target = new_ids.get(&connection.target_node)?;
+                let mut copy = connection.clone();
+                copy.source_node = source.clone();
+                copy.target_node = target.clone();
+                copy.id = None;
+                copy.waypoints = copy
+                    .waypoints
+                    .iter()
+                    .map(|point| Position::new(point.x + offset.x, point.y + offset.y))
+                    .collect();
+                Some(copy)
+            })
+            .collect();
+        self.connections.extend(copied_connections);
+
+        let copied_comments: Vec<_> = self
+            .comments
+            .iter()
+            .filter(|comment| comment.is_attached() && comment.attached_node_ids.iter().all(|id| new_ids.contains_key(id)))
+            .map(|comment| {
+                let mut copy = comment.clone();
+                copy.position = Position::new(copy.position.x + offset.x, copy.position.y + offset.y);
+                copy.attached_node_ids = copy.attached_node_ids.iter().map(|id| new_ids[id].clone()).collect();
+                copy
+            })
+            .collect();
+        self.comments.extend(copied_comments);
+
+        Ok(copies)
+    }
+}
@@ -0,0 +1,286 @@
+//! # External Declarations
+//!
+//! Engine functions and types that generated code uses but that aren't
+//! nodes.
+//!
+//! A graph lists the externals its code needs in
+//! [`GraphDescription::externals`]: each [`ExternalDecl`] names a function
+//! or type, the module or crate providing it, and for functions the
+//! signature. Code generators emit them as imports (`use` statements in
+//! Rust, see [`CodeGenerator::generate_externals`](crate::generation::CodeGenerator::generate_externals))
+//! or extern declarations (C), so node libraries don't need to hardcode
+//! [`imports`](super::NodeMetadata::imports) strings.
+//!
+//! An [`ExternalRegistry`] lists what the engine provides.
+//! [`check_externals`](GraphDescription::check_externals) reports
+//! declarations the registry doesn't provide or provides with another
+//! signature, and
+//! [`CompileOptions::with_externals`](crate::generation::CompileOptions::with_externals)
+//! fails compilation on them.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::core::{ExternalDecl, ExternalRegistry};
+//! use graphy::GraphDescription;
+//!
+//! let registry = ExternalRegistry::new()
+//!     .with_external(ExternalDecl::function("engine::audio", "play_sound").with_param("SoundId"))
+//!     .with_external(ExternalDecl::type_("engine::audio", "SoundId"));
+//!
+//! let mut graph = GraphDescription::new("door");
+//! graph.add_external(ExternalDecl::function("engine::audio", "play_sound").with_param("SoundId"));
+//! assert!(graph.check_externals(&registry).is_empty());
+//!
+//! graph.add_external(ExternalDecl::function("engine::audio", "stop_sound"));
+//! assert_eq!(graph.check_externals(&registry).len(), 1);
+//! ```
+
+use super::{GraphDescription, TypeInfo};
+use crate::GraphyError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// What an external declaration names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExternalKind {
+    /// A function called by generated code
+    Function,
+
+    /// A type used by generated code
+    Type,
+}
+
+/// A function or type provided outside the graph.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExternalDecl {
+    /// Name of the function or type
+    pub name: String,
+
+    /// Crate or module providing it (e.g. `"engine::audio"`), empty if it
+    /// needs no import
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub module: String,
+
+    /// Whether it's a function or a type
+    pub kind: ExternalKind,
+
+    /// Parameter types of a function
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub params: Vec<TypeInfo>,
+
+    /// Return type of a function, `None` for functions returning nothing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_type: Option<TypeInfo>,
+}
+
+impl ExternalDecl {
+    /// Creates a declaration of a function taking no params and returning
+    /// nothing.
+    #[inline]
+    pub fn function(module: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            module: module.into(),
+            kind: ExternalKind::Function,
+            params: Vec::new(),
+            return_type: None,
+        }
+    }
+
+    /// Creates a declaration of a type.
+    #[inline]
+    pub fn type_(module: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            module: module.into(),
+            kind: ExternalKind::Type,
+            params: Vec::new(),
+            return_type: None,
+        }
+    }
+
+    /// Adds a parameter type.
+    #[inline]
+    #[must_use]
+    pub fn with_param(mut self, param_type: impl Into<TypeInfo>) -> Self {
+        self.params.push(param_type.into());
+        self
+    }
+
+    /// Sets the return type.
+    #[inline]
+    #[must_use]
+    pub fn with_return_type(mut self, return_type: impl Into<TypeInfo>) -> Self {
+        self.return_type = Some(return_type.into());
+        self
+    }
+
+    /// Returns `<module>::<name>`, or the name if there's no module.
+    pub fn path(&self) -> String {
+        if self.module.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}::{}", self.module, self.name)
+        }
+    }
+
+    /// Returns the signature shown in errors: `fn(<params>) -> <return>`
+    /// for functions, `type` for types.
+    pub fn signature(&self) -> String {
+        match self.kind {
+            ExternalKind::Type => "type".to_string(),
+            ExternalKind::Function => {
+                let params: Vec<&str> = self.params.iter().map(|param| param.type_string.as_str()).collect();
+                match &self.return_type {
+                    Some(return_type) => format!("fn({}) -> {}", params.join(", "), return_type),
+                    None => format!("fn({})", params.join(", ")),
+                }
+            }
+        }
+    }
+}
+
+/// The externals an engine provides, by path.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExternalRegistry {
+    externals: BTreeMap<String, ExternalDecl>,
+}
+
+impl ExternalRegistry {
+    /// Creates an empty registry.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an external, replacing one with the same path.
+    #[inline]
+    #[must_use]
+    pub fn with_external(mut self, external: ExternalDecl) -> Self {
+        self.register(external);
+        self
+    }
+
+    /// Adds an external, replacing one with the same path.
+    pub fn register(&mut self, external: ExternalDecl) {
+        self.externals.insert(external.path(), external);
+    }
+
+    /// Returns the external with a path.
+    #[inline]
+    pub fn get(&self, path: &str) -> Option<&ExternalDecl> {
+        self.externals.get(path)
+    }
+
+    /// Returns the externals, sorted by path.
+    pub fn iter(&self) -> impl Iterator<Item = &ExternalDecl> {
+        self.externals.values()
+    }
+
+    /// Returns the number of externals.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.externals.len()
+    }
+
+    /// Returns `true` if the registry has no externals.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.externals.is_empty()
+    }
+}
+
+/// A declaration a registry doesn't satisfy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExternalIssue {
+    /// The registry has nothing with the declaration's path
+    Unknown {
+        /// Path of the declaration
+        path: String,
+    },
+
+    /// The registry provides the path with another kind or signature
+    Mismatch {
+        /// Path of the declaration
+        path: String,
+
+        /// Signature declared by the graph
+        declared: String,
+
+        /// Signature provided by the registry
+        provided: String,
+    },
+}
+
+impl fmt::Display for ExternalIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", GraphyError::from(self.clone()))
+    }
+}
+
+impl From<ExternalIssue> for GraphyError {
+    fn from(issue: ExternalIssue) -> Self {
+        match issue {
+            ExternalIssue::Unknown { path } => GraphyError::UnknownExternal { path },
+            ExternalIssue::Mismatch {
+                path,
+                declared,
+                provided,
+            } => GraphyError::ExternalMismatch {
+                path,
+                declared,
+                provided,
+            },
+        }
+    }
+}
+
+impl GraphDescription {
+    /// Declares an external, replacing a declaration with the same path.
+    ///
+    /// Returns the replaced declaration, if any.
+    pub fn add_external(&mut self, external: ExternalDecl) -> Option<ExternalDecl> {
+        let path = external.path();
+        match self.externals.iter_mut().find(|existing| existing.path() == path) {
+            Some(existing) => Some(std::mem::replace(existing, external)),
+            None => {
+                self.externals.push(external);
+                None
+            }
+        }
+    }
+
+    /// Removes the external declaration with a path.
+    pub fn remove_external(&mut self, path: &str) -> Option<ExternalDecl> {
+        let index = self.externals.iter().position(|external| external.path() == path)?;
+        Some(self.externals.remove(index))
+    }
+
+    /// Gets the external declaration with a path.
+    pub fn external(&self, path: &str) -> Option<&ExternalDecl> {
+        self.externals.iter().find(|external| external.path() == path)
+    }
+
+    /// Lists the external declarations a registry doesn't satisfy, in
+    /// declaration order.
+    pub fn check_externals(&self, registry: &ExternalRegistry) -> Vec<ExternalIssue> {
+        self.externals
+            .iter()
+            .filter_map(|external| {
+                let path = external.path();
+                match registry.get(&path) {
+                    None => Some(ExternalIssue::Unknown { path }),
+                    Some(provided) if provided != external => Some(ExternalIssue::Mismatch {
+                        path,
+                        declared: external.signature(),
+                        provided: provided.signature(),
+                    }),
+                    Some(_) => None,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,277 @@
+//! # Metadata From Source
+//!
+//! Derives [`NodeMetadata`] from annotated Rust functions.
+//!
+//! A node function is marked with a `#[graphy::node(...)]` (or `#[node(...)]`)
+//! attribute. Its parameters become the node params, its return type the node
+//! return type, and `exec_output!("Label")` calls in the body its execution
+//! outputs. The function itself (without the attribute) is kept as the
+//! metadata's `function_source`.
+//!
+//! Attribute arguments, all optional:
+//!
+//! - `category = "Math"` (default `"General"`)
+//! - `name = "add"` (default: the function name)
+//! - one of `pure`, `function` (default), `control_flow`, `event`
+//! - `import = "use std::io::Write;"`, repeatable
+//!
+//! Function and event nodes without `exec_output!` calls get a single
+//! execution output, `then` and `exec` respectively. `async fn`s produce
+//! async nodes. The function's doc comment becomes the node description.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::{NodeMetadata, NodeTypes};
+//!
+//! let source = r#"
+//!     #[graphy::node(category = "Flow", control_flow)]
+//!     fn branch(condition: bool) {
+//!         if condition {
+//!             exec_output!("True");
+//!         } else {
+//!             exec_output!("False");
+//!         }
+//!     }
+//! "#;
+//!
+//! let metadata = NodeMetadata::from_fn_source(source).unwrap();
+//! assert_eq!(metadata.name, "branch");
+//! assert_eq!(metadata.node_type, NodeTypes::control_flow);
+//! assert_eq!(metadata.params[0].param_type, "bool");
+//! assert_eq!(metadata.exec_outputs, vec!["True", "False"]);
+//! ```
+
+use super::{NodeMetadata, NodeTypes, ParamInfo};
+use crate::utils::extract_exec_output_labels;
+use crate::GraphyError;
+use quote::ToTokens;
+use syn::{Attribute, FnArg, Item, ItemFn, LitStr, Pat, ReturnType, Type};
+
+/// Category used when the attribute doesn't name one
+const DEFAULT_CATEGORY: &str = "General";
+
+impl NodeMetadata {
+    /// Derives metadata from the source of a single function.
+    ///
+    /// The `#[graphy::node]` attribute is optional here; without it the
+    /// node is a function node in the default category.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::AstParsing`] if the source isn't a function,
+    /// the attribute is malformed, or a parameter isn't a plain `name: Type`.
+    pub fn from_fn_source(source: &str) -> Result<Self, GraphyError> {
+        let item_fn = syn::parse_str::<ItemFn>(source)
+            .map_err(|e| GraphyError::AstParsing(format!("Failed to parse function: {}", e)))?;
+        metadata_from_fn(item_fn)
+    }
+
+    /// Derives metadata from every `#[graphy::node]` function in a source file.
+    ///
+    /// Functions without the attribute are ignored. Metadata is returned in
+    /// source order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::AstParsing`] if the file doesn't parse or any
+    /// node function is invalid (see [`from_fn_source`](Self::from_fn_source)).
+    pub fn from_source_file(source: &str) -> Result<Vec<Self>, GraphyError> {
+        let file = syn::parse_file(source)
+            .map_err(|e| GraphyError::AstParsing(format!("Failed to parse source file: {}", e)))?;
+
+        let metadata = file
+            .items
+            .into_iter()
+            .filter_map(|item| match item {
+                Item::Fn(item_fn) if item_fn.attrs.iter().any(is_node_attribute) => Some(item_fn),
+                _ => None,
+            })
+            .map(metadata_from_fn)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        tracing::debug!("[METADATA] Derived {} node(s) from source", metadata.len());
+        Ok(metadata)
+    }
+}
+
+/// Arguments of a `#[graphy::node]` attribute
+struct NodeAttribute {
+    name: Option<String>,
+    category: String,
+    node_type: NodeTypes,
+    imports: Vec<String>,
+}
+
+impl Default for NodeAttribute {
+    fn default() -> Self {
+        Self {
+            name: None,
+            category: DEFAULT_CATEGORY.to_string(),
+            node_type: NodeTypes::fn_,
+            imports: Vec::new(),
+        }
+    }
+}
+
+fn is_node_attribute(attr: &Attribute) -> bool {
+    let segments: Vec<String> = attr.path().segments.iter().map(|s| s.ident.to_string()).collect();
+    matches!(segments.as_slice(), [node] if node == "node")
+        || matches!(segments.as_slice(), [graphy, node] if graphy == "graphy" && node == "node")
+}
+
+fn parse_node_attribute(attr: &Attribute) -> Result<NodeAttribute, syn::Error> {
+    let mut parsed = NodeAttribute::default();
+
+    // `#[graphy::node]` without arguments uses the defaults
+    if matches!(attr.meta, syn::Meta::Path(_)) {
+        return Ok(parsed);
+    }
+
+    attr.parse_nested_meta(|meta| {
+        let kind = [
+            ("pure", NodeTypes::pure),
+            ("function", NodeTypes::fn_),
+            ("control_flow", NodeTypes::control_flow),
+            ("event", NodeTypes::event),
+        ]
+        .into_iter()
+        .find(|(flag, _)| meta.path.is_ident(flag));
+
+        if let Some((_, node_type)) = kind {
+            parsed.node_type = node_type;
+        } else if meta.path.is_ident("category") {
+            parsed.category = meta.value()?.parse::<LitStr>()?.value();
+        } else if meta.path.is_ident("name") {
+            parsed.name = Some(meta.value()?.parse::<LitStr>()?.value());
+        } else if meta.path.is_ident("import") {
+            parsed.imports.push(meta.value()?.parse::<LitStr>()?.value());
+        } else {
+            return Err(meta.error("unknown node attribute argument"));
+        }
+        Ok(())
+    })?;
+
+    Ok(parsed)
+}
+
+fn metadata_from_fn(mut item_fn: ItemFn) -> Result<NodeMetadata, GraphyError> {
+    let fn_name = item_fn.sig.ident.to_string();
+    let attribute_error = |e: syn::Error| GraphyError::AstParsing(format!("Invalid node attribute on {}: {}", fn_name, e));
+
+    let attribute = match item_fn.attrs.iter().position(is_node_attribute) {
+        Some(index) => parse_node_attribute(&item_fn.attrs.remove(index)).map_err(attribute_error)?,
+        None => NodeAttribute::default(),
+    };
+
+    let params = item_fn
+        .sig
+        .inputs
+        .iter()
+        .map(|input| match input {
+            FnArg::Typed(pat_type) => match pat_type.pat.as_ref() {
+                Pat::Ident(pat_ident) => Ok(ParamInfo::new(pat_ident.ident.to_string(), type_string(&pat_type.ty))),
+                _ => Err(GraphyError::AstParsing(format!(
+                    "Parameter of {} must be a plain identifier",
+                    fn_name
+                ))),
+            },
+            FnArg::Receiver(_) => Err(GraphyError::AstParsing(format!(
+                "Node function {} can't take self",
+                fn_name
+            ))),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let return_type = match &item_fn.sig.output {
+        ReturnType::Type(_, ty) if !matches!(ty.as_ref(), Type::Tuple(tuple) if tuple.elems.is_empty()) => {
+            Some(type_string(ty))
+        }
+        _ => None,
+    };
+
+    let description = doc_comment(&item_fn.attrs);
+    let is_async = item_fn.sig.asyncness.is_some();
+    let function_source = item_fn.to_token_stream().to_string();
+
+    let mut exec_outputs = Vec::new();
+    if attribute.node_type != NodeTypes::pure {
+        for label in extract_exec_output_labels(&function_source)? {
+            if !exec_outputs.contains(&label) {
+                exec_outputs.push(label);
+            }
+        }
+    }
+    if exec_outputs.is_empty() {
+        match attribute.node_type {
+            NodeTypes::fn_ => exec_outputs.push("then".to_string()),
+            NodeTypes::event => exec_outputs.push("exec".to_string()),
+            NodeTypes::pure | NodeTypes::control_flow => {}
+        }
+    }
+
+    let mut metadata = NodeMetadata::new(attribute.name.unwrap_or(fn_name), attribute.node_type, attribute.category)
+        .with_params(params)
+        .with_exec_outputs(exec_outputs)
+        .with_imports(attribute.imports)
+        .with_source(function_source)
+        .with_async(is_async);
+    if let Some(return_type) = return_type {
+        metadata = metadata.with_return_type(return_type);
+    }
+    if let Some(description) = description {
+        metadata = metadata.with_description(description);
+    }
+
+    Ok(metadata)
+}
+
+/// Join the `///` lines of a doc comment, `None` if there are none
+fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(text), .. }),
+                ..
+            }) => Some(text.value().trim().to_string()),
+            _ => None,
+        })
+        .collect();
+
+    let description = lines.join("\n").trim().to_string();
+    (!description.is_empty()).then_some(description)
+}
+
+/// Normalize the spelling of a type (`Vec < u8 >` -> `Vec<u8>`)
+///
+/// Text that doesn't parse as a type is returned trimmed.
+pub(crate) fn normalize_type(type_text: &str) -> String {
+    match syn::parse_str::<Type>(type_text) {
+        Ok(ty) => type_string(&ty),
+        Err(_) => type_text.trim().to_string(),
+    }
+}
+
+/// Render a type without the spacing `to_token_stream` inserts
+///
+/// Tokens are joined directly unless both sides are identifier characters
+/// (`&mut T`, `dyn Trait`); commas are followed by a space.
+fn type_string(ty: &Type) -> String {
+    let tokens = ty.to_token_stream().to_string();
+    let mut out = String::with_capacity(tokens.len());
+
+    for token in tokens.split_whitespace() {
+        let previous = out.chars().last();
+        let next = token.chars().next();
+        let is_ident = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+
+        if previous == Some(',') || (is_ident(previous) && is_ident(next)) {
+            out.push(' ');
+        }
+        out.push_str(token);
+    }
+
+    out
+}
@@ -6,6 +6,10 @@
 //! - **Nodes**: Computational or control flow units
 //! - **Connections**: Data or execution flow links between nodes
 //! - **Comments**: Visual annotations for documentation
+//! - **Groups**: Named, collapsible regions of nodes
+//! - **Interface**: Optional declared inputs/outputs, so the whole graph
+//!   can compile to a callable function
+//! - **Externals**: Engine functions and types the generated code uses
 //!
 //! # Example
 //!
@@ -19,18 +23,48 @@
 //! graph.add_node(node);
 //!
 //! // Add a connection
-//! graph.add_connection(Connection {
-//!     source_node: "add_1".to_string(),
-//!     source_pin: "result".to_string(),
-//!     target_node: "print_1".to_string(),
-//!     target_pin: "value".to_string(),
-//!     connection_type: ConnectionType::Data,
-//! });
+//! graph.add_connection(Connection::new("add_1", "result", "print_1", "value", ConnectionType::Data));
 //! ```
 
-use super::{Connection, NodeInstance};
+use super::{
+    is_implicit_switch_output, BuildCondition, Connection, ConnectionErrorReason, ConnectionRef, ConnectionType, DataType, ExternalDecl, NodeInstance,
+    NodeMetadataProvider, NodeTypeId, NodeTypes, PinType, Position, PropertyValue, TypeInfo, ERROR_EXEC_PIN, ERROR_VALUE_PIN,
+    EXEC_INPUT_PIN, RESULT_PIN,
+};
+use crate::GraphyError;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+/// Node type of the builtin pseudo-node that exposes graph inputs.
+///
+/// Each output pin of a `graph.input` node corresponds to one declared
+/// graph input with the same name.
+pub const GRAPH_INPUT_NODE_TYPE: &str = "graph.input";
+
+/// Node type of the builtin pseudo-node that collects graph outputs.
+///
+/// Each input pin of a `graph.output` node corresponds to one declared
+/// graph output with the same name.
+pub const GRAPH_OUTPUT_NODE_TYPE: &str = "graph.output";
+
+/// Node type of the builtin node that reads a graph variable.
+///
+/// The variable name is stored in the [`VARIABLE_NAME_PROPERTY`] property and
+/// the value is exposed on the [`VARIABLE_VALUE_PIN`] output.
+pub const GET_VARIABLE_NODE_TYPE: &str = "graph.get_variable";
+
+/// Node type of the builtin node that writes a graph variable.
+///
+/// The variable name is stored in the [`VARIABLE_NAME_PROPERTY`] property and
+/// the new value is read from the [`VARIABLE_VALUE_PIN`] input. Execution
+/// enters through `exec_in` and continues from `exec_out`.
+pub const SET_VARIABLE_NODE_TYPE: &str = "graph.set_variable";
+
+/// Property of variable nodes holding the variable name.
+pub const VARIABLE_NAME_PROPERTY: &str = "variable";
+
+/// Data pin of variable nodes carrying the value.
+pub const VARIABLE_VALUE_PIN: &str = "value";
 
 /// Metadata about a graph.
 ///
@@ -81,6 +115,15 @@ impl GraphMetadata {
     }
 }
 
+/// Serialized format version written by this version of Graphy.
+pub const CURRENT_FORMAT_VERSION: u32 = 2;
+
+/// Format version of graphs saved before versioning was introduced.
+#[inline]
+fn legacy_format_version() -> u32 {
+    1
+}
+
 /// Complete graph description containing all nodes, connections, and metadata.
 ///
 /// This is the primary data structure that gets serialized/deserialized
@@ -92,6 +135,14 @@ impl GraphMetadata {
 /// For graphs with 1000+ nodes, this provides significant performance benefits.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GraphDescription {
+    /// Version of the serialized format
+    ///
+    /// Graphs saved before the format was versioned have no version and are
+    /// read as version 1. Use [`crate::io::load_with_migration`] to upgrade
+    /// older graphs on load.
+    #[serde(default = "legacy_format_version")]
+    pub format_version: u32,
+
     /// Metadata about the graph
     pub metadata: GraphMetadata,
 
@@ -103,13 +154,104 @@ pub struct GraphDescription {
 
     /// Visual comments for documentation in editors
     pub comments: Vec<GraphComment>,
+
+    /// Declared graph inputs (parameters when compiled as a function)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub graph_inputs: Vec<GraphParam>,
+
+    /// Declared graph outputs (return values when compiled as a function)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub graph_outputs: Vec<GraphParam>,
+
+    /// Collapsible node groups
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<GraphGroup>,
+
+    /// Declared graph variables (mutable state shared by all events)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub variables: HashMap<String, VariableDecl>,
+
+    /// Functions and types from outside the graph that its code uses
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub externals: Vec<ExternalDecl>,
+}
+
+/// Declaration of a graph variable.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VariableDecl {
+    /// Type of the variable
+    pub type_info: TypeInfo,
+
+    /// Initial value (the type's default value if not set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<PropertyValue>,
+}
+
+impl VariableDecl {
+    /// Creates a variable declaration without an initial value.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::core::VariableDecl;
+    /// use graphy::PropertyValue;
+    ///
+    /// let health = VariableDecl::new("i64").with_default(PropertyValue::Integer(100));
+    /// assert_eq!(health.type_info.type_string, "i64");
+    /// ```
+    #[inline]
+    pub fn new(type_info: impl Into<TypeInfo>) -> Self {
+        Self {
+            type_info: type_info.into(),
+            default: None,
+        }
+    }
+
+    /// Sets the initial value.
+    #[inline]
+    pub fn with_default(mut self, value: PropertyValue) -> Self {
+        self.default = Some(value);
+        self
+    }
+}
+
+/// A declared graph input or output.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphParam {
+    /// Parameter name (used as pin ID and variable name)
+    pub name: String,
+
+    /// Type of the parameter
+    pub type_info: TypeInfo,
+}
+
+impl GraphParam {
+    /// Creates a new graph parameter.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::core::GraphParam;
+    ///
+    /// let param = GraphParam::new("speed", "f64");
+    /// assert_eq!(param.type_info.type_string, "f64");
+    /// ```
+    #[inline]
+    pub fn new(name: impl Into<String>, type_info: impl Into<TypeInfo>) -> Self {
+        Self {
+            name: name.into(),
+            type_info: type_info.into(),
+        }
+    }
 }
 
 /// A visual comment in the graph for documentation purposes.
 ///
 /// Comments appear as text boxes in visual editors and are preserved
-/// during serialization but don't affect code generation.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// during serialization but don't affect code generation. A comment can be
+/// attached to nodes, so it follows them when they move (see
+/// [`GraphDescription::fit_comments`]).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GraphComment {
     /// The comment text content
     pub text: String,
@@ -119,6 +261,145 @@ pub struct GraphComment {
     
     /// Size of the comment box (width, height)
     pub size: (f64, f64),
+
+    /// IDs of the nodes the comment frames, empty for a free-standing comment
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attached_node_ids: Vec<String>,
+}
+
+impl GraphComment {
+    /// Creates a free-standing comment.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::core::GraphComment;
+    /// use graphy::Position;
+    ///
+    /// let comment = GraphComment::new("Spawning", Position::new(0.0, -40.0), (300.0, 200.0))
+    ///     .with_attached_nodes(["spawn_1"]);
+    /// assert!(comment.is_attached_to("spawn_1"));
+    /// ```
+    #[inline]
+    pub fn new(text: impl Into<String>, position: super::Position, size: (f64, f64)) -> Self {
+        Self {
+            text: text.into(),
+            position,
+            size,
+            attached_node_ids: Vec::new(),
+        }
+    }
+
+    /// Attaches the comment to nodes, in addition to those it's attached to.
+    #[inline]
+    pub fn with_attached_nodes<I, S>(mut self, node_ids: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        for node_id in node_ids {
+            self.attach(node_id);
+        }
+        self
+    }
+
+    /// Attaches the comment to a node, returning `false` if it already was.
+    pub fn attach(&mut self, node_id: impl Into<String>) -> bool {
+        let node_id = node_id.into();
+        if self.is_attached_to(&node_id) {
+            return false;
+        }
+        self.attached_node_ids.push(node_id);
+        true
+    }
+
+    /// Detaches the comment from a node, returning `false` if it wasn't
+    /// attached.
+    pub fn detach(&mut self, node_id: &str) -> bool {
+        let before = self.attached_node_ids.len();
+        self.attached_node_ids.retain(|id| id != node_id);
+        self.attached_node_ids.len() != before
+    }
+
+    /// Checks if the comment is attached to a node.
+    #[inline]
+    pub fn is_attached_to(&self, node_id: &str) -> bool {
+        self.attached_node_ids.iter().any(|id| id == node_id)
+    }
+
+    /// Checks if the comment is attached to any node.
+    #[inline]
+    pub fn is_attached(&self) -> bool {
+        !self.attached_node_ids.is_empty()
+    }
+}
+
+/// A named group of nodes, shown as a collapsible region in editors.
+///
+/// Unlike comments, groups own a set of nodes: a node belongs to at most one
+/// group, and a group can be turned into a sub-graph with
+/// [`GraphDescription::group_to_subgraph`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphGroup {
+    /// Unique identifier for this group within the graph
+    pub id: String,
+
+    /// Title shown in the editor
+    pub title: String,
+
+    /// Display color (e.g. `"#3A7BD5"`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+
+    /// IDs of the nodes in this group
+    pub node_ids: Vec<String>,
+
+    /// Build configurations the group's nodes are compiled in, `None` for all
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<BuildCondition>,
+}
+
+impl GraphGroup {
+    /// Creates a new empty group.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::core::GraphGroup;
+    ///
+    /// let group = GraphGroup::new("physics", "Physics").with_color("#3A7BD5");
+    /// assert!(group.node_ids.is_empty());
+    /// ```
+    #[inline]
+    pub fn new(id: impl Into<String>, title: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            title: title.into(),
+            color: None,
+            node_ids: Vec::new(),
+            condition: None,
+        }
+    }
+
+    /// Sets the display color.
+    #[inline]
+    pub fn with_color(mut self, color: impl Into<String>) -> Self {
+        self.color = Some(color.into());
+        self
+    }
+
+    /// Sets the build configurations the group's nodes are compiled in.
+    #[inline]
+    pub fn with_condition(mut self, condition: BuildCondition) -> Self {
+        self.condition = Some(condition);
+        self
+    }
+
+    /// Checks if the group contains a node.
+    #[inline]
+    pub fn contains(&self, node_id: &str) -> bool {
+        self.node_ids.iter().any(|id| id == node_id)
+    }
 }
 
 impl GraphDescription {
@@ -137,10 +418,16 @@ impl GraphDescription {
     #[must_use]
     pub fn new(name: impl Into<String>) -> Self {
         Self {
+            format_version: CURRENT_FORMAT_VERSION,
             metadata: GraphMetadata::new(name),
             nodes: HashMap::new(),
             connections: Vec::new(),
             comments: Vec::new(),
+            graph_inputs: Vec::new(),
+            graph_outputs: Vec::new(),
+            groups: Vec::new(),
+            variables: HashMap::new(),
+            externals: Vec::new(),
         }
     }
 
@@ -163,14 +450,319 @@ impl GraphDescription {
         self.nodes.insert(node.id.clone(), node);
     }
 
+    /// Adds a node of a registered type, with the pins its metadata declares.
+    ///
+    /// The node is created by [`NodeInstance::from_metadata`] at the origin
+    /// and keeps `node_type` as its type, which may differ from the
+    /// metadata name for types registered under another name. Switch nodes
+    /// also get a case per variant of their value type, if the provider
+    /// knows it. Returns the node, so it can be moved or given properties.
+    ///
+    /// A type with a version requirement, like `math.add@1.2` (see
+    /// [`NodeTypeId`](super::NodeTypeId)), gets the newest matching version,
+    /// and the node keeps the requirement.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::Custom`] if the provider has no metadata for
+    /// the type (or none satisfying the requirement), the requirement is
+    /// invalid, or a node with the same ID exists (the graph is unchanged).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::{GraphDescription, NodeMetadata, NodeRegistry, NodeTypes, ParamInfo, Position};
+    ///
+    /// let mut registry = NodeRegistry::new();
+    /// registry.register_as(
+    ///     "math.add",
+    ///     NodeMetadata::new("add", NodeTypes::pure, "Math")
+    ///         .with_params(vec![ParamInfo::new("a", "f64"), ParamInfo::new("b", "f64")])
+    ///         .with_return_type("f64"),
+    /// );
+    ///
+    /// let mut graph = GraphDescription::new("graph");
+    /// let add = graph.add_node_of_type("math.add", "add_1", &registry).unwrap();
+    /// add.position = Position::new(100.0, 0.0);
+    ///
+    /// assert_eq!(graph.nodes["add_1"].node_type, "math.add");
+    /// assert_eq!(graph.nodes["add_1"].inputs.len(), 2);
+    /// assert_eq!(graph.nodes["add_1"].outputs[0].id, "result");
+    /// ```
+    pub fn add_node_of_type<P: NodeMetadataProvider>(
+        &mut self,
+        node_type: &str,
+        id: impl Into<String>,
+        metadata_provider: &P,
+    ) -> Result<&mut NodeInstance, GraphyError> {
+        let id = id.into();
+        if self.nodes.contains_key(&id) {
+            return Err(GraphyError::Custom(format!("Duplicate node ID: {}", id)));
+        }
+        let type_id = match node_type.contains('@') {
+            true => NodeTypeId::parse(node_type)?,
+            false => NodeTypeId::new("", node_type),
+        };
+        let metadata = match &type_id.version {
+            Some(requirement) => metadata_provider.get_node_metadata_matching(&type_id.qualified_name(), requirement),
+            None => metadata_provider.get_node_metadata(node_type),
+        }
+        .ok_or_else(|| GraphyError::Custom(format!("Unknown node type: {}", node_type)))?;
+
+        let mut node = NodeInstance::from_metadata(id.clone(), metadata, Position::zero());
+        node.node_type = type_id.qualified_name();
+        if type_id.version.is_some() {
+            node.version_req = type_id.version;
+        }
+        let is_switch = metadata.switch_value().is_some();
+        self.add_node(node);
+        if is_switch {
+            // Value types that aren't known enums keep no cases until the editor adds some
+            let _ = self.sync_switch_cases(&id, metadata_provider);
+        }
+        Ok(self.nodes.get_mut(&id).expect("node added above"))
+    }
+
     /// Adds a connection to the graph.
     ///
     /// Connections are validated during analysis phases, not at insertion time.
+    /// Use [`try_add_connection`](Self::try_add_connection) to validate eagerly.
     #[inline(always)]
     pub fn add_connection(&mut self, connection: Connection) {
         self.connections.push(connection);
     }
 
+    /// Validates a connection and adds it to the graph.
+    ///
+    /// The graph is unchanged if validation fails. See
+    /// [`validate_connection`](Self::validate_connection) for the rules.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// graph.try_add_connection(Connection::data("add_1", "result", "print_1", "value"), &provider)?;
+    /// ```
+    pub fn try_add_connection<P: NodeMetadataProvider>(
+        &mut self,
+        connection: Connection,
+        metadata_provider: &P,
+    ) -> Result<(), GraphyError> {
+        self.validate_connection(&connection, metadata_provider)?;
+        self.connections.push(connection);
+        Ok(())
+    }
+
+    /// Checks that a connection is valid for this graph.
+    ///
+    /// Pins are looked up on the node instances first. If a node doesn't
+    /// declare the pin, its metadata is consulted: parameters act as data
+    /// inputs and `exec_outputs` act as execution outputs. Switch nodes
+    /// also expose the variants of their value type and the default case.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::InvalidConnection`] with the
+    /// [`ConnectionErrorReason`]:
+    ///
+    /// - `MissingNode` or `MissingPin` if either endpoint doesn't exist
+    /// - `Reversed` if the source is an input and the target an output
+    /// - `WrongDirection` if a pin exists in the other direction
+    /// - `ConnectionTypeMismatch` if the connection type doesn't match the
+    ///   pins (data vs execution)
+    /// - `TypeMismatch` if the data types are incompatible
+    /// - `TooManyConnections` if the target input already has as many data
+    ///   connections as its [`PinMultiplicity`](super::PinMultiplicity) accepts
+    pub fn validate_connection<P: NodeMetadataProvider>(
+        &self,
+        connection: &Connection,
+        metadata_provider: &P,
+    ) -> Result<(), GraphyError> {
+        let (source_type, target_type) = self.resolve_connection_types(connection, metadata_provider)?;
+
+        if !source_type.is_compatible_with(&target_type) {
+            return Err(self.connection_error(
+                connection,
+                ConnectionErrorReason::TypeMismatch {
+                    expected: data_type_name(&target_type),
+                    actual: data_type_name(&source_type),
+                },
+            ));
+        }
+
+        if connection.connection_type == ConnectionType::Data {
+            let target = &self.nodes[&connection.target_node];
+            let multiplicity =
+                target.input_multiplicity(&connection.target_pin, metadata_provider.metadata_for_node(target));
+            // The connection itself doesn't count if it's already in the graph
+            let others = self
+                .connections
+                .iter()
+                .filter(|other| {
+                    other.connection_type == ConnectionType::Data
+                        && other.target_node == connection.target_node
+                        && other.target_pin == connection.target_pin
+                        && *other != connection
+                })
+                .count();
+            if !multiplicity.accepts(others + 1) {
+                return Err(self.connection_error(
+                    connection,
+                    ConnectionErrorReason::TooManyConnections {
+                        connections: others + 1,
+                        max: multiplicity.max_connections().unwrap_or(usize::MAX),
+                    },
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Resolves the data types of a connection's source and target pins.
+    ///
+    /// Performs every check of [`validate_connection`](Self::validate_connection)
+    /// except type compatibility, so callers can apply their own rules
+    /// (e.g. [`TypeCoercion`](super::TypeCoercion)).
+    ///
+    /// Returns `(source_type, target_type)`.
+    pub fn resolve_connection_types<P: NodeMetadataProvider>(
+        &self,
+        connection: &Connection,
+        metadata_provider: &P,
+    ) -> Result<(DataType, DataType), GraphyError> {
+        let resolve = |node_id: &str, pin_id: &str, direction: PinType| {
+            let node = self
+                .get_node(node_id)
+                .ok_or_else(|| ConnectionErrorReason::MissingNode { node: node_id.to_string() })?;
+            resolve_pin_type(self, node, pin_id, direction, metadata_provider)
+        };
+        let types = match resolve(&connection.source_node, &connection.source_pin, PinType::Output) {
+            Err(ConnectionErrorReason::WrongDirection { .. })
+                if resolve(&connection.target_node, &connection.target_pin, PinType::Output).is_ok() =>
+            {
+                Err(ConnectionErrorReason::Reversed)
+            }
+            source => source,
+        };
+        let types = types.and_then(|source_type| {
+            let target_type = resolve(&connection.target_node, &connection.target_pin, PinType::Input)?;
+            let expects_execution = connection.connection_type == ConnectionType::Execution;
+            if source_type.is_execution() != expects_execution || target_type.is_execution() != expects_execution {
+                return Err(ConnectionErrorReason::ConnectionTypeMismatch {
+                    source: data_type_name(&source_type),
+                    target: data_type_name(&target_type),
+                });
+            }
+            Ok((source_type, target_type))
+        });
+
+        types.map_err(|reason| self.connection_error(connection, reason))
+    }
+
+    /// Checks that every connection of a type joins existing nodes.
+    ///
+    /// Data flow and execution routing skip connections to missing nodes,
+    /// so compilation checks them first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::InvalidConnection`] with
+    /// [`ConnectionErrorReason::MissingNode`] for the first connection with
+    /// a missing source or target node.
+    pub fn check_connection_nodes(&self, connection_type: ConnectionType) -> Result<(), GraphyError> {
+        for (index, connection) in self.connections.iter().enumerate() {
+            if connection.connection_type != connection_type {
+                continue;
+            }
+            let missing = [&connection.source_node, &connection.target_node]
+                .into_iter()
+                .find(|node| !self.nodes.contains_key(*node));
+            if let Some(node) = missing {
+                return Err(GraphyError::InvalidConnection {
+                    connection: Box::new(ConnectionRef::new(connection, Some(index))),
+                    reason: ConnectionErrorReason::MissingNode { node: node.clone() },
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that every execution connection leaves an exec output.
+    ///
+    /// Execution routing only looks at node IDs and source pins, so nothing
+    /// else catches an exec connection drawn from a pin the node doesn't
+    /// have. A source pin must be an execution output: one of the metadata's
+    /// `exec_outputs` (or the implicit error and switch case outputs), or an
+    /// output of [`DataType::Execution`] declared on the node. Exec inputs
+    /// are implicit, so target pins are only checked for not being an
+    /// output or a data input. Fan-in and fan-out are unrestricted.
+    ///
+    /// Connections to missing nodes are left to
+    /// [`check_connection_nodes`](Self::check_connection_nodes), and source
+    /// pins of nodes the provider doesn't know aren't checked unless the
+    /// node declares them.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::InvalidConnection`] for the first invalid
+    /// connection, with [`ConnectionErrorReason::Reversed`] if it is drawn
+    /// backwards, or `MissingPin`, `WrongDirection`, or
+    /// `ConnectionTypeMismatch`.
+    pub fn check_exec_connections<P: NodeMetadataProvider>(&self, metadata_provider: &P) -> Result<(), GraphyError> {
+        for connection in self.connections.iter().filter(|c| c.connection_type == ConnectionType::Execution) {
+            let (Some(source), Some(target)) = (self.get_node(&connection.source_node), self.get_node(&connection.target_node))
+            else {
+                continue;
+            };
+            let source_known = metadata_provider.metadata_for_node(source).is_some()
+                || source.is_builtin()
+                || source.inputs.iter().chain(&source.outputs).any(|pin| pin.id == connection.source_pin);
+            if !source_known {
+                continue;
+            }
+
+            let reason = match resolve_pin_type(self, source, &connection.source_pin, PinType::Output, metadata_provider) {
+                Err(ConnectionErrorReason::WrongDirection { .. })
+                    if resolve_pin_type(self, target, &connection.target_pin, PinType::Output, metadata_provider)
+                        .is_ok() =>
+                {
+                    Some(ConnectionErrorReason::Reversed)
+                }
+                Err(reason) => Some(reason),
+                Ok(source_type) if !source_type.is_execution() => Some(ConnectionErrorReason::ConnectionTypeMismatch {
+                    source: data_type_name(&source_type),
+                    target: data_type_name(&DataType::Execution),
+                }),
+                Ok(_) => match resolve_pin_type(self, target, &connection.target_pin, PinType::Input, metadata_provider) {
+                    Err(reason @ ConnectionErrorReason::WrongDirection { .. }) => Some(reason),
+                    Ok(target_type) if !target_type.is_execution() => {
+                        Some(ConnectionErrorReason::ConnectionTypeMismatch {
+                            source: data_type_name(&DataType::Execution),
+                            target: data_type_name(&target_type),
+                        })
+                    }
+                    // Exec inputs don't have to be declared
+                    _ => None,
+                },
+            };
+            if let Some(reason) = reason {
+                return Err(self.connection_error(connection, reason));
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates a [`GraphyError::InvalidConnection`] for a connection.
+    ///
+    /// The error gets the connection's index if it's in the graph.
+    pub fn connection_error(&self, connection: &Connection, reason: ConnectionErrorReason) -> GraphyError {
+        let index = self.connections.iter().position(|other| other == connection);
+        GraphyError::InvalidConnection {
+            connection: Box::new(ConnectionRef::new(connection, index)),
+            reason,
+        }
+    }
+
     /// Gets an immutable reference to a node by ID.
     ///
     /// Returns `None` if the node doesn't exist.
@@ -194,4 +786,621 @@ impl GraphDescription {
     pub fn get_node_mut(&mut self, id: &str) -> Option<&mut NodeInstance> {
         self.nodes.get_mut(id)
     }
+
+    /// Declares a graph input.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::GraphDescription;
+    ///
+    /// let mut graph = GraphDescription::new("lerp");
+    /// graph.add_graph_input("t", "f64");
+    /// assert!(graph.is_function_graph());
+    /// ```
+    #[inline]
+    pub fn add_graph_input(&mut self, name: impl Into<String>, type_info: impl Into<TypeInfo>) {
+        self.graph_inputs.push(GraphParam::new(name, type_info));
+    }
+
+    /// Declares a graph output.
+    #[inline]
+    pub fn add_graph_output(&mut self, name: impl Into<String>, type_info: impl Into<TypeInfo>) {
+        self.graph_outputs.push(GraphParam::new(name, type_info));
+    }
+
+    /// Checks if the graph declares any inputs or outputs.
+    #[inline]
+    pub fn is_function_graph(&self) -> bool {
+        !self.graph_inputs.is_empty() || !self.graph_outputs.is_empty()
+    }
+
+    /// Creates a `graph.input` pseudo-node with one output pin per declared input.
+    ///
+    /// The node is returned rather than added, so callers can position it first.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::{GraphDescription, Position};
+    ///
+    /// let mut graph = GraphDescription::new("lerp");
+    /// graph.add_graph_input("t", "f64");
+    ///
+    /// let inputs = graph.create_graph_input_node("inputs", Position::zero());
+    /// assert_eq!(inputs.outputs[0].id, "t");
+    /// graph.add_node(inputs);
+    /// ```
+    pub fn create_graph_input_node(&self, id: impl Into<String>, position: Position) -> NodeInstance {
+        let mut node = NodeInstance::new(id, GRAPH_INPUT_NODE_TYPE, position);
+        for param in &self.graph_inputs {
+            node.add_output_pin(param.name.clone(), DataType::Typed(param.type_info.clone()));
+        }
+        node
+    }
+
+    /// Creates a `graph.output` pseudo-node with one input pin per declared output.
+    pub fn create_graph_output_node(&self, id: impl Into<String>, position: Position) -> NodeInstance {
+        let mut node = NodeInstance::new(id, GRAPH_OUTPUT_NODE_TYPE, position);
+        for param in &self.graph_outputs {
+            node.add_input_pin(param.name.clone(), DataType::Typed(param.type_info.clone()));
+        }
+        node
+    }
+
+    /// Declares a graph variable, replacing any previous declaration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::core::VariableDecl;
+    /// use graphy::GraphDescription;
+    ///
+    /// let mut graph = GraphDescription::new("player");
+    /// graph.add_variable("health", VariableDecl::new("i64"));
+    /// assert!(graph.get_variable("health").is_some());
+    /// ```
+    #[inline]
+    pub fn add_variable(&mut self, name: impl Into<String>, decl: VariableDecl) {
+        self.variables.insert(name.into(), decl);
+    }
+
+    /// Removes a variable declaration.
+    ///
+    /// Nodes reading or writing the variable are left in place.
+    #[inline]
+    pub fn remove_variable(&mut self, name: &str) -> Option<VariableDecl> {
+        self.variables.remove(name)
+    }
+
+    /// Gets a variable declaration by name.
+    #[inline]
+    pub fn get_variable(&self, name: &str) -> Option<&VariableDecl> {
+        self.variables.get(name)
+    }
+
+    /// Creates a node reading a declared variable.
+    ///
+    /// The node is returned rather than added, so callers can position it first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::UndeclaredVariable`] if the variable isn't declared.
+    pub fn create_get_variable_node(
+        &self,
+        id: impl Into<String>,
+        variable: &str,
+        position: Position,
+    ) -> Result<NodeInstance, GraphyError> {
+        let mut node = self.create_variable_node(id.into(), GET_VARIABLE_NODE_TYPE, variable, position)?;
+        let decl = &self.variables[variable];
+        node.add_output_pin(VARIABLE_VALUE_PIN, DataType::Typed(decl.type_info.clone()));
+        Ok(node)
+    }
+
+    /// Creates a node writing a declared variable.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::UndeclaredVariable`] if the variable isn't declared.
+    pub fn create_set_variable_node(
+        &self,
+        id: impl Into<String>,
+        variable: &str,
+        position: Position,
+    ) -> Result<NodeInstance, GraphyError> {
+        let mut node = self.create_variable_node(id.into(), SET_VARIABLE_NODE_TYPE, variable, position)?;
+        let decl = &self.variables[variable];
+        node.add_input_pin(EXEC_INPUT_PIN, DataType::Execution);
+        node.add_input_pin(VARIABLE_VALUE_PIN, DataType::Typed(decl.type_info.clone()));
+        node.add_output_pin("exec_out", DataType::Execution);
+        Ok(node)
+    }
+
+    fn create_variable_node(
+        &self,
+        id: String,
+        node_type: &str,
+        variable: &str,
+        position: Position,
+    ) -> Result<NodeInstance, GraphyError> {
+        if !self.variables.contains_key(variable) {
+            return Err(GraphyError::UndeclaredVariable {
+                node: id,
+                variable: variable.to_string(),
+            });
+        }
+
+        let mut node = NodeInstance::new(id, node_type, position);
+        node.set_property(VARIABLE_NAME_PROPERTY, PropertyValue::String(variable.to_string()));
+        Ok(node)
+    }
+
+    /// Removes a node along with every connection to or from it.
+    ///
+    /// The node is also removed from its group and detached from comments.
+    /// Returns the node, or `None` if it doesn't exist (the graph is
+    /// unchanged).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::{Connection, GraphDescription, NodeInstance, Position};
+    ///
+    /// let mut graph = GraphDescription::new("test");
+    /// graph.add_node(NodeInstance::new("a", "math.add", Position::zero()));
+    /// graph.add_node(NodeInstance::new("b", "math.add", Position::zero()));
+    /// graph.add_connection(Connection::data("a", "result", "b", "a"));
+    ///
+    /// assert!(graph.remove_node("a").is_some());
+    /// assert!(graph.connections.is_empty());
+    /// ```
+    pub fn remove_node(&mut self, id: &str) -> Option<NodeInstance> {
+        self.remove_nodes(&[id]).pop()
+    }
+
+    /// Removes several nodes along with their connections, group entries, and
+    /// comment attachments.
+    ///
+    /// Returns the removed nodes in the order given. IDs that don't exist
+    /// are skipped.
+    pub fn remove_nodes<S: AsRef<str>>(&mut self, ids: &[S]) -> Vec<NodeInstance> {
+        let removed: Vec<NodeInstance> = ids.iter().filter_map(|id| self.nodes.remove(id.as_ref())).collect();
+        if removed.is_empty() {
+            return removed;
+        }
+
+        let removed_ids: HashSet<&str> = removed.iter().map(|node| node.id.as_str()).collect();
+        self.connections.retain(|c| {
+            !removed_ids.contains(c.source_node.as_str()) && !removed_ids.contains(c.target_node.as_str())
+        });
+        for group in &mut self.groups {
+            group.node_ids.retain(|id| !removed_ids.contains(id.as_str()));
+        }
+        for comment in &mut self.comments {
+            comment.attached_node_ids.retain(|id| !removed_ids.contains(id.as_str()));
+        }
+
+        removed
+    }
+
+    /// Removes the connections between two pins.
+    ///
+    /// Returns `true` if any connection was removed.
+    pub fn remove_connection(&mut self, source_node: &str, source_pin: &str, target_node: &str, target_pin: &str) -> bool {
+        let before = self.connections.len();
+        self.connections.retain(|c| {
+            !(c.source_node == source_node
+                && c.source_pin == source_pin
+                && c.target_node == target_node
+                && c.target_pin == target_pin)
+        });
+        self.connections.len() != before
+    }
+
+    /// Gets a connection by its ID.
+    pub fn get_connection(&self, id: &str) -> Option<&Connection> {
+        self.connections.iter().find(|c| c.id.as_deref() == Some(id))
+    }
+
+    /// Gets a mutable reference to a connection by its ID.
+    pub fn get_connection_mut(&mut self, id: &str) -> Option<&mut Connection> {
+        self.connections.iter_mut().find(|c| c.id.as_deref() == Some(id))
+    }
+
+    /// Removes the connection with an ID, returning it.
+    pub fn remove_connection_by_id(&mut self, id: &str) -> Option<Connection> {
+        let index = self.connections.iter().position(|c| c.id.as_deref() == Some(id))?;
+        Some(self.connections.remove(index))
+    }
+
+    /// Gives every connection without an ID a unique one.
+    ///
+    /// IDs are `conn_1`, `conn_2`, ..., skipping IDs already in use.
+    /// Returns the number of IDs assigned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::{Connection, GraphDescription};
+    ///
+    /// let mut graph = GraphDescription::new("graph");
+    /// graph.add_connection(Connection::data("a", "result", "b", "x").with_id("conn_1"));
+    /// graph.add_connection(Connection::data("a", "result", "c", "x"));
+    ///
+    /// assert_eq!(graph.assign_connection_ids(), 1);
+    /// assert_eq!(graph.connections[1].id.as_deref(), Some("conn_2"));
+    /// ```
+    pub fn assign_connection_ids(&mut self) -> usize {
+        let mut used: HashSet<String> = self.connections.iter().filter_map(|c| c.id.clone()).collect();
+        let mut next = 1;
+        let mut assigned = 0;
+
+        for connection in self.connections.iter_mut().filter(|c| c.id.is_none()) {
+            let id = loop {
+                let candidate = format!("conn_{}", next);
+                next += 1;
+                if !used.contains(&candidate) {
+                    break candidate;
+                }
+            };
+            used.insert(id.clone());
+            connection.id = Some(id);
+            assigned += 1;
+        }
+
+        assigned
+    }
+
+    /// Removes every connection to or from a pin, returning them.
+    pub fn disconnect_pin(&mut self, node_id: &str, pin_id: &str) -> Vec<Connection> {
+        let (removed, kept) = std::mem::take(&mut self.connections).into_iter().partition(|c| {
+            (c.source_node == node_id && c.source_pin == pin_id) || (c.target_node == node_id && c.target_pin == pin_id)
+        });
+        self.connections = kept;
+        removed
+    }
+
+    /// Changes a node's type, renaming its pins and connections.
+    ///
+    /// `pin_mapping` maps pin IDs of the old type to pin IDs of the new type.
+    /// Mapped pins, their connections, and properties keyed by them (input
+    /// constants) are renamed. Unmapped pins are dropped together with their
+    /// connections; other properties are kept.
+    ///
+    /// Returns the connections that were dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::NodeNotFound`] if the node doesn't exist.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::{Connection, DataType, GraphDescription, NodeInstance, Position};
+    ///
+    /// let mut graph = GraphDescription::new("test");
+    /// let mut add = NodeInstance::new("op", "math.add", Position::zero());
+    /// add.add_output_pin("result", DataType::Typed("f64".into()));
+    /// graph.add_node(add);
+    /// graph.add_node(NodeInstance::new("print", "print", Position::zero()));
+    /// graph.add_connection(Connection::data("op", "result", "print", "value"));
+    ///
+    /// let dropped = graph.replace_node("op", "math.sub", &[("result", "difference")]).unwrap();
+    /// assert!(dropped.is_empty());
+    /// assert_eq!(graph.nodes["op"].node_type, "math.sub");
+    /// assert_eq!(graph.connections[0].source_pin, "difference");
+    /// ```
+    pub fn replace_node(
+        &mut self,
+        id: &str,
+        new_type: impl Into<String>,
+        pin_mapping: &[(&str, &str)],
+    ) -> Result<Vec<Connection>, GraphyError> {
+        let node = self
+            .nodes
+            .get_mut(id)
+            .ok_or_else(|| GraphyError::NodeNotFound(id.to_string()))?;
+        let mapped = |pin: &str| pin_mapping.iter().find(|(old, _)| *old == pin).map(|(_, new)| *new);
+
+        node.node_type = new_type.into();
+        for pins in [&mut node.inputs, &mut node.outputs] {
+            pins.retain(|pin| mapped(&pin.id).is_some());
+            for pin in pins.iter_mut() {
+                let new_id = mapped(&pin.id).unwrap_or_default().to_string();
+                if pin.pin.name == pin.id {
+                    pin.pin.name = new_id.clone();
+                }
+                pin.pin.id = new_id.clone();
+                pin.id = new_id;
+            }
+        }
+        for (old, new) in pin_mapping {
+            if let Some(value) = node.properties.remove(*old) {
+                node.properties.insert(new.to_string(), value);
+            }
+        }
+
+        let mut dropped = Vec::new();
+        let mut kept = Vec::with_capacity(self.connections.len());
+        for mut connection in self.connections.drain(..) {
+            let endpoints = [
+                (connection.source_node == id, &mut connection.source_pin),
+                (connection.target_node == id, &mut connection.target_pin),
+            ];
+            let mut keep = true;
+            for (on_node, pin) in endpoints {
+                if on_node {
+                    match mapped(pin) {
+                        Some(new) => *pin = new.to_string(),
+                        None => keep = false,
+                    }
+                }
+            }
+
+            if keep {
+                kept.push(connection);
+            } else {
+                dropped.push(connection);
+            }
+        }
+        self.connections = kept;
+
+        Ok(dropped)
+    }
+
+    /// Moves every connection leaving one output pin to another output pin.
+    ///
+    /// Returns the number of connections that were moved.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::NodeNotFound`] if the new source node doesn't
+    /// exist (the graph is unchanged).
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// // Everything fed by the old constant now reads from the new one
+    /// graph.rewire(("const_old", "value"), ("const_new", "value"))?;
+    /// ```
+    pub fn rewire(&mut self, old_source: (&str, &str), new_source: (&str, &str)) -> Result<usize, GraphyError> {
+        if !self.nodes.contains_key(new_source.0) {
+            return Err(GraphyError::NodeNotFound(new_source.0.to_string()));
+        }
+
+        let mut moved = 0;
+        for connection in &mut self.connections {
+            if connection.source_node == old_source.0 && connection.source_pin == old_source.1 {
+                connection.source_node = new_source.0.to_string();
+                connection.source_pin = new_source.1.to_string();
+                moved += 1;
+            }
+        }
+        Ok(moved)
+    }
+
+    /// Adds a group to the graph.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::Custom`] if a group with the same ID exists, or
+    /// [`GraphyError::NodeNotFound`] if the group references a missing node.
+    /// Nodes already in another group are moved to the new one.
+    pub fn add_group(&mut self, group: GraphGroup) -> Result<(), GraphyError> {
+        if self.get_group(&group.id).is_some() {
+            return Err(GraphyError::Custom(format!("Duplicate group ID: {}", group.id)));
+        }
+        if let Some(missing) = group.node_ids.iter().find(|id| !self.nodes.contains_key(*id)) {
+            return Err(GraphyError::NodeNotFound(missing.clone()));
+        }
+
+        for other in &mut self.groups {
+            other.node_ids.retain(|id| !group.contains(id));
+        }
+        self.groups.push(group);
+        Ok(())
+    }
+
+    /// Removes a group, returning it. The grouped nodes stay in the graph.
+    pub fn remove_group(&mut self, group_id: &str) -> Option<GraphGroup> {
+        let index = self.groups.iter().position(|g| g.id == group_id)?;
+        Some(self.groups.remove(index))
+    }
+
+    /// Gets a group by ID.
+    #[inline]
+    pub fn get_group(&self, group_id: &str) -> Option<&GraphGroup> {
+        self.groups.iter().find(|g| g.id == group_id)
+    }
+
+    /// Gets a mutable reference to a group by ID.
+    #[inline]
+    pub fn get_group_mut(&mut self, group_id: &str) -> Option<&mut GraphGroup> {
+        self.groups.iter_mut().find(|g| g.id == group_id)
+    }
+
+    /// Returns the group containing a node, if any.
+    #[inline]
+    pub fn group_of(&self, node_id: &str) -> Option<&GraphGroup> {
+        self.groups.iter().find(|g| g.contains(node_id))
+    }
+
+    /// Adds a node to a group, moving it out of any other group.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::NodeNotFound`] if the node doesn't exist, or
+    /// [`GraphyError::Custom`] if the group doesn't exist.
+    pub fn add_node_to_group(&mut self, group_id: &str, node_id: &str) -> Result<(), GraphyError> {
+        if !self.nodes.contains_key(node_id) {
+            return Err(GraphyError::NodeNotFound(node_id.to_string()));
+        }
+        if self.get_group(group_id).is_none() {
+            return Err(GraphyError::Custom(format!("Group not found: {}", group_id)));
+        }
+
+        for group in &mut self.groups {
+            if group.id == group_id {
+                if !group.contains(node_id) {
+                    group.node_ids.push(node_id.to_string());
+                }
+            } else {
+                group.node_ids.retain(|id| id != node_id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes a node from a group.
+    ///
+    /// Returns `true` if the node was in the group.
+    pub fn remove_node_from_group(&mut self, group_id: &str, node_id: &str) -> bool {
+        match self.get_group_mut(group_id) {
+            Some(group) => {
+                let before = group.node_ids.len();
+                group.node_ids.retain(|id| id != node_id);
+                group.node_ids.len() != before
+            }
+            None => false,
+        }
+    }
+
+    /// Copies a group's nodes into a new standalone graph.
+    ///
+    /// The sub-graph is named after the group title and contains the grouped
+    /// nodes plus every connection between them, and the comments attached
+    /// only to grouped nodes. Connections crossing the group boundary are not
+    /// included. The original graph is unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::Custom`] if the group doesn't exist.
+    pub fn group_to_subgraph(&self, group_id: &str) -> Result<GraphDescription, GraphyError> {
+        let group = self
+            .get_group(group_id)
+            .ok_or_else(|| GraphyError::Custom(format!("Group not found: {}", group_id)))?;
+
+        let mut subgraph = GraphDescription::new(group.title.clone());
+        for node_id in &group.node_ids {
+            if let Some(node) = self.nodes.get(node_id) {
+                subgraph.add_node(node.clone());
+            }
+        }
+
+        subgraph.connections = self
+            .connections
+            .iter()
+            .filter(|c| group.contains(&c.source_node) && group.contains(&c.target_node))
+            .cloned()
+            .collect();
+        subgraph.comments = self
+            .comments
+            .iter()
+            .filter(|comment| comment.is_attached() && comment.attached_node_ids.iter().all(|id| group.contains(id)))
+            .cloned()
+            .collect();
+
+        Ok(subgraph)
+    }
+}
+
+/// Find the data type of a pin, checking the node instance and then its metadata
+fn resolve_pin_type<P: NodeMetadataProvider>(
+    graph: &GraphDescription,
+    node: &NodeInstance,
+    pin_id: &str,
+    direction: PinType,
+    metadata_provider: &P,
+) -> Result<DataType, ConnectionErrorReason> {
+    let (pins, opposite) = match direction {
+        PinType::Input => (&node.inputs, &node.outputs),
+        PinType::Output => (&node.outputs, &node.inputs),
+    };
+
+    if let Some(pin) = pins.iter().find(|p| p.id == pin_id) {
+        return Ok(pin.pin.data_type.clone());
+    }
+
+    if opposite.iter().any(|p| p.id == pin_id) {
+        return Err(ConnectionErrorReason::WrongDirection {
+            node: node.id.clone(),
+            pin: pin_id.to_string(),
+            expected: direction,
+        });
+    }
+
+    if let Some(data_type) = variable_pin_type(graph, node, pin_id, direction) {
+        return Ok(data_type);
+    }
+
+    let from_metadata = metadata_provider
+        .metadata_for_node(node)
+        .and_then(|meta| match direction {
+            // Event params are outputs, not inputs
+            PinType::Input if meta.event_param(pin_id).is_some() => None,
+            PinType::Input if pin_id == EXEC_INPUT_PIN && !matches!(meta.node_type, NodeTypes::pure | NodeTypes::event) => {
+                Some(DataType::Execution)
+            }
+            PinType::Input => meta
+                .params
+                .iter()
+                .find(|param| param.name == pin_id)
+                .map(|param| DataType::Typed(TypeInfo::new(param.param_type.clone()))),
+            PinType::Output if meta.is_fallible() && pin_id == ERROR_EXEC_PIN => Some(DataType::Execution),
+            PinType::Output if meta.is_fallible() && pin_id == ERROR_VALUE_PIN => Some(
+                meta.error_type()
+                    .map_or(DataType::Any, |error_type| DataType::Typed(TypeInfo::new(error_type))),
+            ),
+            PinType::Output if pin_id == RESULT_PIN && meta.result_type().is_some() => {
+                meta.result_type().map(|result_type| DataType::Typed(TypeInfo::new(result_type)))
+            }
+            PinType::Output if is_implicit_switch_output(node, meta, metadata_provider, pin_id) => {
+                Some(DataType::Execution)
+            }
+            PinType::Output => meta
+                .exec_outputs
+                .iter()
+                .any(|exec| exec == pin_id)
+                .then_some(DataType::Execution)
+                .or_else(|| {
+                    meta.event_param(pin_id)
+                        .map(|param| DataType::Typed(TypeInfo::new(param.param_type.clone())))
+                }),
+        });
+
+    from_metadata.ok_or_else(|| ConnectionErrorReason::MissingPin {
+        node: node.id.clone(),
+        pin: pin_id.to_string(),
+    })
+}
+
+/// Type of a builtin variable node pin, from the variable declaration
+fn variable_pin_type(
+    graph: &GraphDescription,
+    node: &NodeInstance,
+    pin_id: &str,
+    direction: PinType,
+) -> Option<DataType> {
+    let value_type = || {
+        let decl = graph.get_variable(node.variable_name()?)?;
+        Some(DataType::Typed(decl.type_info.clone()))
+    };
+
+    match (direction, pin_id) {
+        (PinType::Output, VARIABLE_VALUE_PIN) if node.is_get_variable() => value_type(),
+        (PinType::Input, VARIABLE_VALUE_PIN) if node.is_set_variable() => value_type(),
+        (PinType::Input, EXEC_INPUT_PIN) | (PinType::Output, "exec_out") if node.is_set_variable() => {
+            Some(DataType::Execution)
+        }
+        _ => None,
+    }
+}
+
+/// Human-readable name of a data type for error messages
+pub(crate) fn data_type_name(data_type: &DataType) -> String {
+    data_type
+        .type_string()
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{:?}", data_type))
 }
@@ -0,0 +1,238 @@
+//! # Editor Hints
+//!
+//! Value ranges, display hints, and inspector queries for editors.
+//!
+//! Node metadata carries everything an editor needs to render an inspector
+//! for a node: descriptions and tooltips, the allowed range of numeric
+//! inputs, and how each input should be displayed. [`GraphDescription::inspector_fields`]
+//! combines that with a node instance (its properties and connections) into
+//! one [`InspectorField`] per input.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::core::{DisplayHint, NodeRegistry, ValueRange};
+//! use graphy::{GraphDescription, NodeInstance, NodeMetadata, NodeTypes, ParamInfo, Position, PropertyValue};
+//!
+//! let mut registry = NodeRegistry::new();
+//! registry.register(
+//!     NodeMetadata::new("play_sound", NodeTypes::fn_, "Audio").with_params(vec![
+//!         ParamInfo::new("volume", "f32")
+//!             .with_range(ValueRange::new(0.0, 1.0))
+//!             .with_default(PropertyValue::Number(0.8))
+//!             .with_tooltip("Playback volume"),
+//!         ParamInfo::new("caption", "String").with_display(DisplayHint::Multiline),
+//!     ]),
+//! );
+//!
+//! let mut graph = GraphDescription::new("audio");
+//! graph.add_node(NodeInstance::new("play_1", "play_sound", Position::zero()));
+//!
+//! let fields = graph.inspector_fields("play_1", &registry).unwrap();
+//! assert_eq!(fields[0].display, DisplayHint::Slider);
+//! assert_eq!(fields[0].value, Some(&PropertyValue::Number(0.8)));
+//! assert_eq!(fields[0].tooltip, Some("Playback volume"));
+//! assert_eq!(fields[1].display, DisplayHint::Multiline);
+//! ```
+
+use super::{ConnectionType, GraphDescription, NodeMetadataProvider, NodeTypes, ParamInfo, PropertyValue};
+use crate::GraphyError;
+use serde::{Deserialize, Serialize};
+
+/// Allowed values of a numeric input.
+///
+/// Either bound may be open. Editors use the step as the increment of
+/// sliders and spinners.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ValueRange {
+    /// Smallest allowed value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+
+    /// Largest allowed value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+
+    /// Increment between values
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<f64>,
+}
+
+impl ValueRange {
+    /// Creates a range bounded on both sides.
+    #[inline]
+    pub fn new(min: f64, max: f64) -> Self {
+        Self {
+            min: Some(min),
+            max: Some(max),
+            step: None,
+        }
+    }
+
+    /// Creates a range with only a lower bound.
+    #[inline]
+    pub fn at_least(min: f64) -> Self {
+        Self {
+            min: Some(min),
+            ..Self::default()
+        }
+    }
+
+    /// Creates a range with only an upper bound.
+    #[inline]
+    pub fn at_most(max: f64) -> Self {
+        Self {
+            max: Some(max),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the increment between values.
+    #[inline]
+    #[must_use]
+    pub fn with_step(mut self, step: f64) -> Self {
+        self.step = Some(step);
+        self
+    }
+
+    /// Checks if both bounds are set.
+    #[inline]
+    pub fn is_bounded(&self) -> bool {
+        self.min.is_some() && self.max.is_some()
+    }
+
+    /// Checks if a value lies within the bounds.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::core::ValueRange;
+    ///
+    /// let range = ValueRange::at_least(0.0);
+    /// assert!(range.contains(10.0));
+    /// assert!(!range.contains(-1.0));
+    /// ```
+    pub fn contains(&self, value: f64) -> bool {
+        self.min.is_none_or(|min| value >= min) && self.max.is_none_or(|max| value <= max)
+    }
+
+    /// Clamps a value to the bounds.
+    pub fn clamp(&self, value: f64) -> f64 {
+        let value = self.min.map_or(value, |min| value.max(min));
+        self.max.map_or(value, |max| value.min(max))
+    }
+}
+
+/// How an editor should display an input's value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisplayHint {
+    /// A plain input field for the value's type
+    #[default]
+    Field,
+
+    /// A slider over the input's [`ValueRange`]
+    Slider,
+
+    /// A color picker
+    ColorPicker,
+
+    /// A multi-line text box
+    Multiline,
+}
+
+impl DisplayHint {
+    /// Checks if this is the plain [`Field`](Self::Field) hint.
+    #[inline]
+    pub fn is_field(&self) -> bool {
+        *self == Self::Field
+    }
+}
+
+/// One input of a node, as an inspector shows it.
+#[derive(Debug, Clone)]
+pub struct InspectorField<'a> {
+    /// The input's metadata
+    pub param: &'a ParamInfo,
+
+    /// Current value: the node's property, or else the metadata default
+    pub value: Option<&'a PropertyValue>,
+
+    /// Whether the node sets the value itself (as a property)
+    pub is_set: bool,
+
+    /// Whether a data connection feeds the input, overriding the value
+    pub connected: bool,
+
+    /// How to display the value, see [`ParamInfo::display_hint`]
+    pub display: DisplayHint,
+
+    /// Hover text, see [`ParamInfo::tooltip_text`]
+    pub tooltip: Option<&'a str>,
+}
+
+impl InspectorField<'_> {
+    /// Checks if the current value lies within the input's range.
+    ///
+    /// Values without a range, missing values, and non-numeric values are
+    /// always in range.
+    pub fn is_in_range(&self) -> bool {
+        let value = match self.value {
+            Some(PropertyValue::Number(number)) => *number,
+            Some(PropertyValue::Integer(integer)) => *integer as f64,
+            _ => return true,
+        };
+        self.param.range.is_none_or(|range| range.contains(value))
+    }
+}
+
+impl GraphDescription {
+    /// Returns one inspector field per input of a node, in param order.
+    ///
+    /// Event nodes have no inputs (their params are outputs), so they
+    /// return an empty list.
+    ///
+    /// # Errors
+    ///
+    /// - [`GraphyError::NodeNotFound`] if the node doesn't exist
+    /// - [`GraphyError::Custom`] if the provider doesn't know the node type
+    pub fn inspector_fields<'a, P: NodeMetadataProvider>(
+        &'a self,
+        node_id: &str,
+        metadata_provider: &'a P,
+    ) -> Result<Vec<InspectorField<'a>>, GraphyError> {
+        let node = self
+            .nodes
+            .get(node_id)
+            .ok_or_else(|| GraphyError::NodeNotFound(node_id.to_string()))?;
+        let metadata = metadata_provider
+            .metadata_for_node(node)
+            .ok_or_else(|| GraphyError::Custom(format!("Unknown node type: {}", node.node_type)))?;
+        if metadata.node_type == NodeTypes::event {
+            return Ok(Vec::new());
+        }
+
+        let fields = metadata
+            .params
+            .iter()
+            .map(|param| {
+                let property = node.properties.get(&param.name);
+                let connected = self.connections.iter().any(|connection| {
+                    connection.connection_type == ConnectionType::Data
+                        && connection.target_node == node.id
+                        && connection.target_pin == param.name
+                });
+                InspectorField {
+                    param,
+                    value: property.or(param.default.as_ref()),
+                    is_set: property.is_some(),
+                    connected,
+                    display: param.display_hint(),
+                    tooltip: param.tooltip_text(),
+                }
+            })
+            .collect();
+
+        Ok(fields)
+    }
+}
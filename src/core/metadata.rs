@@ -8,6 +8,29 @@
 //! - Node classification (pure, function, control flow, event)
 //! - Code generation information (imports, source code)
 //!
+//! # Fallible nodes
+//!
+//! Function nodes returning a `Result` are fallible. Besides their regular
+//! outputs they expose an [`ERROR_EXEC_PIN`] execution output, taken when the
+//! call fails, and an [`ERROR_VALUE_PIN`] data output carrying the error.
+//! The success value is the node's regular result.
+//!
+//! # Editor hints
+//!
+//! Nodes and their params can carry a description and a tooltip; params can
+//! also carry a [`ValueRange`] and a [`DisplayHint`]. Editors read them
+//! through [`GraphDescription::inspector_fields`](crate::GraphDescription::inspector_fields)
+//! to render inspectors. None of them affect compilation.
+//!
+//! # Switch nodes
+//!
+//! Control flow nodes marked with [`NodeMetadata::with_switch`] match on
+//! their first parameter. Their cases aren't listed in `exec_outputs`: each
+//! case is an execution output pin of the node instance, named after the
+//! matched variant, or is derived from the value's type through
+//! [`NodeMetadataProvider::enum_variants`]. A [`SWITCH_DEFAULT_PIN`] output
+//! is taken when no case matches.
+//!
 //! # Example
 //!
 //! ```
@@ -22,8 +45,48 @@
 //!     .with_source("a + b");
 //! ```
 
-use super::{NodeTypes, TypeInfo};
+use super::{DisplayHint, NodeInstance, NodeTypes, PinMultiplicity, PropertyValue, TypeInfo, ValueRange};
+use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Execution input of function and control flow nodes.
+pub const EXEC_INPUT_PIN: &str = "exec_in";
+
+/// Data output carrying the return value of a node.
+pub const RESULT_PIN: &str = "result";
+
+/// Execution output of fallible nodes taken when the call fails.
+pub const ERROR_EXEC_PIN: &str = "on_error";
+
+/// Data output of fallible nodes carrying the error of a failed call.
+pub const ERROR_VALUE_PIN: &str = "error";
+
+/// Execution output of switch nodes taken when no case matches.
+pub const SWITCH_DEFAULT_PIN: &str = "default";
+
+/// How a node's `function_source` is turned into code.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceKind {
+    /// A Rust function (or, for pure nodes, expression), inlined through its AST
+    #[default]
+    Rust,
+
+    /// A text template with `{{param}}` and `{{exec:Label}}` placeholders,
+    /// for sources in other languages; see
+    /// [`SourceTemplate`](crate::generation::SourceTemplate)
+    Template,
+}
+
+impl SourceKind {
+    /// Checks if this is the default, [`SourceKind::Rust`].
+    #[inline]
+    pub fn is_rust(&self) -> bool {
+        *self == SourceKind::Rust
+    }
+}
 
 /// Parameter definition for a node input.
 ///
@@ -35,6 +98,39 @@ pub struct ParamInfo {
     
     /// Rust type string (e.g., "f64", "String", "&str")
     pub param_type: String,
+
+    /// Value used when the input is neither connected nor set on the node
+    ///
+    /// Without one, such inputs get the default value of their type.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<PropertyValue>,
+
+    /// Whether the input must be connected
+    ///
+    /// Unconnected required inputs fail validation, see
+    /// [`DataResolver::validate_required_inputs`](crate::DataResolver::validate_required_inputs).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub required: bool,
+
+    /// How many data connections the input accepts
+    #[serde(default, skip_serializing_if = "PinMultiplicity::is_single")]
+    pub multiplicity: PinMultiplicity,
+
+    /// What the input is for, shown in editor documentation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Short hover text (falls back to the description)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tooltip: Option<String>,
+
+    /// Allowed values of a numeric input
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<ValueRange>,
+
+    /// How an editor should display the input's value
+    #[serde(default, skip_serializing_if = "DisplayHint::is_field")]
+    pub display: DisplayHint,
 }
 
 impl ParamInfo {
@@ -52,8 +148,129 @@ impl ParamInfo {
         Self {
             name: name.into(),
             param_type: param_type.into(),
+            default: None,
+            required: false,
+            multiplicity: PinMultiplicity::Single,
+            description: None,
+            tooltip: None,
+            range: None,
+            display: DisplayHint::Field,
+        }
+    }
+
+    /// Sets the description of the input.
+    #[inline]
+    #[must_use]
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Sets the hover text of the input.
+    #[inline]
+    #[must_use]
+    pub fn with_tooltip(mut self, tooltip: impl Into<String>) -> Self {
+        self.tooltip = Some(tooltip.into());
+        self
+    }
+
+    /// Sets the allowed values of a numeric input.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::core::{DisplayHint, ValueRange};
+    /// use graphy::ParamInfo;
+    ///
+    /// let param = ParamInfo::new("volume", "f32").with_range(ValueRange::new(0.0, 1.0).with_step(0.05));
+    /// assert_eq!(param.display_hint(), DisplayHint::Slider);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_range(mut self, range: ValueRange) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    /// Sets how an editor should display the input's value.
+    #[inline]
+    #[must_use]
+    pub fn with_display(mut self, display: DisplayHint) -> Self {
+        self.display = display;
+        self
+    }
+
+    /// Returns the hover text: the tooltip, or else the description.
+    pub fn tooltip_text(&self) -> Option<&str> {
+        self.tooltip.as_deref().or(self.description.as_deref())
+    }
+
+    /// Returns how an editor should display the input's value.
+    ///
+    /// An explicit hint wins. Otherwise inputs with a range bounded on both
+    /// sides are shown as sliders, and all others as plain fields.
+    pub fn display_hint(&self) -> DisplayHint {
+        match self.display {
+            DisplayHint::Field if self.range.is_some_and(|range| range.is_bounded()) => DisplayHint::Slider,
+            display => display,
         }
     }
+
+    /// Sets the value used when the input is neither connected nor set on the node.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::{ParamInfo, PropertyValue};
+    ///
+    /// let param = ParamInfo::new("scale", "f64").with_default(PropertyValue::Number(1.0));
+    /// assert_eq!(param.default, Some(PropertyValue::Number(1.0)));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_default(mut self, default: PropertyValue) -> Self {
+        self.default = Some(default);
+        self
+    }
+
+    /// Marks the input as required (it must be connected) or optional.
+    ///
+    /// Inputs are optional by default.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::ParamInfo;
+    ///
+    /// let param = ParamInfo::new("target", "Entity").with_required(true);
+    /// assert!(param.required);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_required(mut self, required: bool) -> Self {
+        self.required = required;
+        self
+    }
+
+    /// Sets how many data connections the input accepts.
+    ///
+    /// Inputs accept a single connection by default.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::core::PinMultiplicity;
+    /// use graphy::ParamInfo;
+    ///
+    /// let param = ParamInfo::new("parts", "String").with_multiplicity(PinMultiplicity::Multi);
+    /// assert!(param.multiplicity.accepts_several());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_multiplicity(mut self, multiplicity: PinMultiplicity) -> Self {
+        self.multiplicity = multiplicity;
+        self
+    }
 }
 
 /// Complete metadata for a node type.
@@ -75,9 +292,19 @@ pub struct NodeMetadata {
     /// Category for organization in UI (e.g., "Math", "String", "Flow Control")
     pub category: String,
 
-    /// Input parameters with types
+    /// Parameters with types
+    ///
+    /// For most nodes these are inputs. For event nodes they are the values
+    /// the event provides (e.g. `delta_time` of `on_tick`), exposed as output
+    /// data pins named after each param.
     pub params: Vec<ParamInfo>,
 
+    /// Inputs after the params whose number is chosen per node instance
+    ///
+    /// See [`VariadicGroup`](super::VariadicGroup).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variadic: Option<super::VariadicGroup>,
+
     /// Return type (for pure nodes and functions)
     pub return_type: Option<TypeInfo>,
 
@@ -86,9 +313,19 @@ pub struct NodeMetadata {
     /// Examples: `vec!["then"]` for simple flow, `vec!["true", "false"]` for branches
     pub exec_outputs: Vec<String>,
 
+    /// Values passed along execution outputs, by output label
+    ///
+    /// Lists what an `exec_output!("label", ...)` call passes after its
+    /// label, in argument order (e.g. the `index` of a loop's `body`).
+    /// Outputs without payloads aren't listed.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub exec_payloads: BTreeMap<String, Vec<ParamInfo>>,
+
     /// Required imports for code generation
     ///
-    /// Example: `vec!["use std::io::Write;"]`
+    /// Example: `vec!["use std::io::Write;"]`. Engine APIs used by a graph
+    /// are better declared as [externals](super::ExternalDecl), which are
+    /// checked and emitted for any target language.
     pub imports: Vec<String>,
 
     /// Source code of the function for inlining
@@ -96,6 +333,62 @@ pub struct NodeMetadata {
     /// For pure nodes, this can be an expression like "a + b".
     /// For functions, include the full function body.
     pub function_source: String,
+
+    /// How `function_source` is turned into code
+    #[serde(default, skip_serializing_if = "SourceKind::is_rust")]
+    pub source_kind: SourceKind,
+
+    /// Whether calls to this node must be awaited
+    ///
+    /// Events running an async node (directly or through its data inputs)
+    /// are generated as async functions; see
+    /// [`AsyncAnalysis`](crate::analysis::AsyncAnalysis).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_async: bool,
+
+    /// Whether this control flow node matches on its first parameter
+    ///
+    /// Switch cases are execution outputs of the node instance (or derived
+    /// from the value's type), not `exec_outputs`; see
+    /// [`NodeInstance::switch_cases`](crate::NodeInstance::switch_cases).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_switch: bool,
+
+    /// Whether this pure node may return different values for the same inputs
+    ///
+    /// Volatile nodes (e.g. `random`) are never merged with other nodes by
+    /// [`CommonSubexpressions`](crate::analysis::CommonSubexpressions).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_volatile: bool,
+
+    /// Whether generated code caches the results of this pure node by input
+    ///
+    /// Meant for expensive nodes (e.g. noise functions) evaluated with the
+    /// same inputs over and over. Ignored for volatile and async nodes, and
+    /// for nodes whose input or return types can't be cached; see
+    /// [`CodeGenerator::memo_key`](crate::generation::CodeGenerator::memo_key).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub memoize: bool,
+
+    /// Approximate cost of running the node once, in units the node library
+    /// chooses (e.g. microseconds); 0 if negligible or unknown
+    ///
+    /// Summed per event by [`CostAnalysis`](crate::analysis::CostAnalysis).
+    #[serde(default, skip_serializing_if = "super::node::is_zero")]
+    pub cost_hint: u32,
+
+    /// What the node does, shown in editor documentation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Short hover text (falls back to the description)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tooltip: Option<String>,
+
+    /// Version of the node type, matched against the version requirements
+    /// of nodes (see [`NodeTypeId`](super::NodeTypeId))
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<Version>,
 }
 
 impl NodeMetadata {
@@ -117,10 +410,21 @@ impl NodeMetadata {
             node_type,
             category: category.into(),
             params: Vec::new(),
+            variadic: None,
             return_type: None,
             exec_outputs: Vec::new(),
+            exec_payloads: BTreeMap::new(),
             imports: Vec::new(),
             function_source: String::new(),
+            source_kind: SourceKind::Rust,
+            is_async: false,
+            is_switch: false,
+            is_volatile: false,
+            memoize: false,
+            cost_hint: 0,
+            description: None,
+            tooltip: None,
+            version: None,
         }
     }
 
@@ -144,6 +448,26 @@ impl NodeMetadata {
         self
     }
 
+    /// Finds a param exposed as an output data pin of an event node.
+    ///
+    /// Returns `None` for non-event nodes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::{NodeMetadata, NodeTypes, ParamInfo};
+    ///
+    /// let meta = NodeMetadata::new("on_tick", NodeTypes::event, "Events")
+    ///     .with_params(vec![ParamInfo::new("delta_time", "f64")]);
+    /// assert!(meta.event_param("delta_time").is_some());
+    /// ```
+    pub fn event_param(&self, name: &str) -> Option<&ParamInfo> {
+        if self.node_type != NodeTypes::event {
+            return None;
+        }
+        self.params.iter().find(|param| param.name == name)
+    }
+
     /// Sets the return type for this node.
     ///
     /// # Example
@@ -181,6 +505,32 @@ impl NodeMetadata {
         self
     }
 
+    /// Sets the values an execution output passes to the connected flow.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::{NodeMetadata, NodeTypes, ParamInfo};
+    ///
+    /// // for i in start..end { exec_output!("body", i); }
+    /// let meta = NodeMetadata::new("for_loop", NodeTypes::control_flow, "Flow")
+    ///     .with_exec_outputs(vec!["body".to_string(), "completed".to_string()])
+    ///     .with_exec_payload("body", vec![ParamInfo::new("index", "i64")]);
+    /// assert_eq!(meta.exec_payload("body")[0].name, "index");
+    /// assert!(meta.exec_payload("completed").is_empty());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_exec_payload(mut self, label: impl Into<String>, payloads: Vec<ParamInfo>) -> Self {
+        self.exec_payloads.insert(label.into(), payloads);
+        self
+    }
+
+    /// Returns the values an execution output passes, empty if it passes none.
+    pub fn exec_payload(&self, label: &str) -> &[ParamInfo] {
+        self.exec_payloads.get(label).map(Vec::as_slice).unwrap_or_default()
+    }
+
     /// Sets the required imports for code generation.
     ///
     /// # Example
@@ -217,6 +567,286 @@ impl NodeMetadata {
         self.function_source = source.into();
         self
     }
+
+    /// Sets how the function source is turned into code.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::{NodeMetadata, NodeTypes, SourceKind};
+    ///
+    /// let meta = NodeMetadata::new("py_print", NodeTypes::fn_, "Python")
+    ///     .with_source("print({{message}})\n{{exec:then}}")
+    ///     .with_source_kind(SourceKind::Template);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_source_kind(mut self, source_kind: SourceKind) -> Self {
+        self.source_kind = source_kind;
+        self
+    }
+
+    /// Marks calls to this node as async.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::{NodeMetadata, NodeTypes};
+    ///
+    /// let meta = NodeMetadata::new("http_get", NodeTypes::fn_, "Network").with_async(true);
+    /// assert!(meta.is_async);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_async(mut self, is_async: bool) -> Self {
+        self.is_async = is_async;
+        self
+    }
+
+    /// Sets the description of the node.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::{NodeMetadata, NodeTypes};
+    ///
+    /// let meta = NodeMetadata::new("lerp", NodeTypes::pure, "Math")
+    ///     .with_description("Linearly interpolates between two values.");
+    /// assert_eq!(meta.tooltip_text(), Some("Linearly interpolates between two values."));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Sets the hover text of the node.
+    #[inline]
+    #[must_use]
+    pub fn with_tooltip(mut self, tooltip: impl Into<String>) -> Self {
+        self.tooltip = Some(tooltip.into());
+        self
+    }
+
+    /// Sets the version of the node type.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::core::Version;
+    /// use graphy::{NodeMetadata, NodeTypes};
+    ///
+    /// let meta = NodeMetadata::new("lerp", NodeTypes::pure, "Math").with_version(Version::new(1, 2, 0));
+    /// assert_eq!(meta.version.unwrap().to_string(), "1.2.0");
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_version(mut self, version: Version) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// Returns the hover text: the tooltip, or else the description.
+    pub fn tooltip_text(&self) -> Option<&str> {
+        self.tooltip.as_deref().or(self.description.as_deref())
+    }
+
+    /// Finds a parameter by name.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::{NodeMetadata, NodeTypes, ParamInfo};
+    ///
+    /// let meta = NodeMetadata::new("add", NodeTypes::pure, "Math")
+    ///     .with_params(vec![ParamInfo::new("a", "f64").with_tooltip("Left operand")]);
+    /// assert_eq!(meta.param("a").unwrap().tooltip_text(), Some("Left operand"));
+    /// assert!(meta.param("c").is_none());
+    /// ```
+    pub fn param(&self, name: &str) -> Option<&ParamInfo> {
+        self.params.iter().find(|param| param.name == name)
+    }
+
+    /// Marks this control flow node as a switch on its first parameter.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::{NodeMetadata, NodeTypes, ParamInfo};
+    ///
+    /// let meta = NodeMetadata::new("switch", NodeTypes::control_flow, "Flow")
+    ///     .with_params(vec![ParamInfo::new("value", "Direction")])
+    ///     .with_switch(true);
+    /// assert_eq!(meta.switch_value().unwrap().name, "value");
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_switch(mut self, is_switch: bool) -> Self {
+        self.is_switch = is_switch;
+        self
+    }
+
+    /// Marks a pure node as volatile: it may return different values for the
+    /// same inputs, so equal-looking nodes must not be merged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::{NodeMetadata, NodeTypes};
+    ///
+    /// let meta = NodeMetadata::new("random", NodeTypes::pure, "Math")
+    ///     .with_return_type("f64")
+    ///     .with_volatile(true);
+    /// assert!(meta.is_volatile);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_volatile(mut self, is_volatile: bool) -> Self {
+        self.is_volatile = is_volatile;
+        self
+    }
+
+    /// Marks a pure node as memoized: generated code caches its results by
+    /// input, so repeated evaluations with the same inputs are lookups.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::{NodeMetadata, NodeTypes, ParamInfo};
+    ///
+    /// let meta = NodeMetadata::new("perlin", NodeTypes::pure, "Noise")
+    ///     .with_params(vec![ParamInfo::new("x", "f64"), ParamInfo::new("y", "f64")])
+    ///     .with_return_type("f64")
+    ///     .with_memoize(true);
+    /// assert!(meta.memoize);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_memoize(mut self, memoize: bool) -> Self {
+        self.memoize = memoize;
+        self
+    }
+
+    /// Sets the approximate cost of running the node once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::{NodeMetadata, NodeTypes};
+    ///
+    /// let meta = NodeMetadata::new("raycast", NodeTypes::fn_, "Physics").with_cost_hint(250);
+    /// assert_eq!(meta.cost_hint, 250);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_cost_hint(mut self, cost_hint: u32) -> Self {
+        self.cost_hint = cost_hint;
+        self
+    }
+
+    /// Returns the parameter a switch node matches on.
+    ///
+    /// Returns `None` for nodes that aren't switches and for switches
+    /// without parameters.
+    pub fn switch_value(&self) -> Option<&ParamInfo> {
+        if !self.is_switch || self.node_type != NodeTypes::control_flow {
+            return None;
+        }
+        self.params.first()
+    }
+
+    /// Checks if this is a function node returning a `Result`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::{NodeMetadata, NodeTypes};
+    ///
+    /// let meta = NodeMetadata::new("read_file", NodeTypes::fn_, "IO")
+    ///     .with_return_type("Result<String, std::io::Error>");
+    /// assert!(meta.is_fallible());
+    /// assert_eq!(meta.error_type(), Some("std::io::Error"));
+    /// ```
+    pub fn is_fallible(&self) -> bool {
+        self.node_type == NodeTypes::fn_
+            && self
+                .return_type
+                .as_ref()
+                .is_some_and(|return_type| return_type.result_types().is_some())
+    }
+
+    /// Returns the error type of a fallible node.
+    ///
+    /// Returns `None` for infallible nodes and for `Result` aliases that
+    /// don't name their error type (e.g. `std::io::Result<T>`).
+    pub fn error_type(&self) -> Option<&str> {
+        if !self.is_fallible() {
+            return None;
+        }
+        self.return_type.as_ref()?.result_types()?.1
+    }
+
+    /// Returns the type of the [`RESULT_PIN`] output.
+    ///
+    /// This is the return type, or its `Ok` type for fallible nodes. Returns
+    /// `None` for nodes that return nothing (or `()`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::{NodeMetadata, NodeTypes};
+    ///
+    /// let meta = NodeMetadata::new("read_file", NodeTypes::fn_, "IO")
+    ///     .with_return_type("Result<String, std::io::Error>");
+    /// assert_eq!(meta.result_type(), Some("String"));
+    /// ```
+    pub fn result_type(&self) -> Option<&str> {
+        let return_type = self.return_type.as_ref()?;
+        let result_type = if self.is_fallible() {
+            return_type.result_types()?.0
+        } else {
+            return_type.type_string.as_str()
+        };
+        Some(result_type.trim()).filter(|ty| !ty.is_empty() && *ty != "()")
+    }
+}
+
+/// Version of the metadata a provider offers.
+///
+/// Providers whose metadata can change at runtime (e.g. when a plugin is
+/// hot-reloaded) return a new version from
+/// [`NodeMetadataProvider::version`] after every change, so results derived
+/// from older metadata can be detected as stale. Versions only compare for
+/// equality. Versions made with [`fresh`](Self::fresh) are unique within the
+/// process, so versions of different providers don't collide either.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ProviderVersion(u64);
+
+impl ProviderVersion {
+    /// Creates a version from a raw value.
+    #[inline]
+    pub const fn new(value: u64) -> Self {
+        Self(value)
+    }
+
+    /// Returns the raw value.
+    #[inline]
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+
+    /// Returns a version no other call in this process returned.
+    pub fn fresh() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl std::fmt::Display for ProviderVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "v{}", self.0)
+    }
 }
 
 /// Trait for providing node type metadata.
@@ -277,4 +907,77 @@ pub trait NodeMetadataProvider {
     /// let math_nodes = provider.get_nodes_by_category("Math");
     /// ```
     fn get_nodes_by_category(&self, category: &str) -> Vec<&NodeMetadata>;
+
+    /// Returns the variants of an enum type, in declaration order.
+    ///
+    /// Used to derive the cases of switch nodes from the type of the value
+    /// they match on. Returns `None` for types that aren't known enums,
+    /// which is the default.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let variants = provider.enum_variants("Direction");
+    /// assert_eq!(variants, Some(&["North".to_string(), "South".to_string()][..]));
+    /// ```
+    fn enum_variants(&self, _type_name: &str) -> Option<&[String]> {
+        None
+    }
+
+    /// Returns the version of the metadata, if the provider tracks changes.
+    ///
+    /// Providers that can change return a new version after every change
+    /// (see [`ProviderVersion`]). The default, `None`, means the provider
+    /// doesn't track changes, and results derived from it are never
+    /// reported as stale.
+    fn version(&self) -> Option<ProviderVersion> {
+        None
+    }
+
+    /// Retrieves the newest metadata of a node type satisfying a version
+    /// requirement.
+    ///
+    /// The default accepts the metadata [`get_node_metadata`](Self::get_node_metadata)
+    /// returns if its version satisfies the requirement; unversioned metadata
+    /// satisfies none. Providers holding several versions of a node type
+    /// (like [`NodeRegistry`](super::NodeRegistry)) pick the newest match.
+    fn get_node_metadata_matching(&self, node_type: &str, requirement: &VersionReq) -> Option<&NodeMetadata> {
+        self.get_node_metadata(node_type)
+            .filter(|metadata| metadata.version.as_ref().is_some_and(|version| requirement.matches(version)))
+    }
+
+    /// Returns the versions of a node type the provider has, oldest first.
+    ///
+    /// Used to explain unsatisfied version requirements. The default
+    /// returns the version of [`get_node_metadata`](Self::get_node_metadata),
+    /// if any.
+    fn node_versions(&self, node_type: &str) -> Vec<Version> {
+        self.get_node_metadata(node_type)
+            .and_then(|metadata| metadata.version.clone())
+            .into_iter()
+            .collect()
+    }
+
+    /// Retrieves the metadata of a node, honoring its version requirement.
+    ///
+    /// Nodes without a [`version_req`](NodeInstance::version_req) get the
+    /// metadata of their type; others get the newest version satisfying it
+    /// (see [`get_node_metadata_matching`](Self::get_node_metadata_matching)).
+    fn metadata_for_node(&self, node: &NodeInstance) -> Option<&NodeMetadata> {
+        match &node.version_req {
+            Some(requirement) => self.get_node_metadata_matching(&node.node_type, requirement),
+            None => self.get_node_metadata(&node.node_type),
+        }
+    }
+
+    /// Returns the provider as one that can be shared between threads.
+    ///
+    /// Lets the compilation driver, which accepts any provider, build the
+    /// [`DataResolver`](crate::analysis::DataResolver) of large graphs in
+    /// parallel (see [`ParallelThreshold`](crate::analysis::ParallelThreshold)).
+    /// The default, `None`, keeps every build sequential; `Sync` providers
+    /// return `Some(self)`.
+    fn as_sync(&self) -> Option<&(dyn NodeMetadataProvider + Sync)> {
+        None
+    }
 }
@@ -0,0 +1,378 @@
+//! # Metadata Validation
+//!
+//! Cross-checks node metadata against its `function_source`.
+//!
+//! Metadata written by hand easily drifts from the function it describes:
+//! an `exec_output!` label is added to the source but not to
+//! `exec_outputs`, a parameter is renamed in one place only, or the return
+//! type changes. Such mismatches compile into wrong code without any error.
+//! [`NodeMetadata::validate`] finds them by parsing the source, and
+//! [`validate_all`] checks every node of a provider.
+//!
+//! Checks:
+//!
+//! - every `exec_output!` label in the source is declared in `exec_outputs`,
+//!   and every declared output is emitted (except the implicit `then` of
+//!   function nodes and `exec` of event nodes)
+//! - the function's parameters match `params` by name, order, and type
+//! - the function's return type matches `return_type`
+//! - param names (and the name of the variadic group) and execution outputs
+//!   are unique
+//!
+//! Nodes without source are only checked for duplicates. Pure nodes may use
+//! a bare expression as source, which skips the signature checks, and so do
+//! nodes with [variadic inputs](super::VariadicGroup), since no Rust function
+//! takes a varying number of arguments. Text templates
+//! ([`SourceKind::Template`]) are checked for their `{{exec:Label}}` outputs
+//! and `{{param}}` names only; the variadic group and its inputs count as
+//! declared params.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::core::MetadataIssue;
+//! use graphy::{NodeMetadata, NodeTypes, ParamInfo};
+//!
+//! let meta = NodeMetadata::new("branch", NodeTypes::control_flow, "Flow")
+//!     .with_params(vec![ParamInfo::new("condition", "bool")])
+//!     .with_exec_outputs(vec!["True".into(), "False".into()])
+//!     .with_source(r#"fn branch(condition: bool) {
+//!         if condition { exec_output!("True"); } else { exec_output!("Else"); }
+//!     }"#);
+//!
+//! let issues = meta.validate();
+//! assert!(issues.contains(&MetadataIssue::ExecOutputNotDeclared {
+//!     node: "branch".into(),
+//!     label: "Else".into(),
+//! }));
+//! assert!(issues.contains(&MetadataIssue::ExecOutputNotInSource {
+//!     node: "branch".into(),
+//!     label: "False".into(),
+//! }));
+//! ```
+
+use super::from_source::normalize_type;
+use super::{NodeMetadata, NodeMetadataProvider, NodeTypes, SourceKind};
+use crate::generation::SourceTemplate;
+use crate::utils::extract_exec_output_labels;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+
+/// A mismatch between a node's metadata and its source.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MetadataIssue {
+    /// The source doesn't parse as a function (or, for pure nodes, an expression)
+    InvalidSource { node: String, message: String },
+
+    /// The source calls `exec_output!` with a label missing from `exec_outputs`
+    ExecOutputNotDeclared { node: String, label: String },
+
+    /// A declared execution output is never emitted by the source
+    ExecOutputNotInSource { node: String, label: String },
+
+    /// An execution output is declared more than once
+    DuplicateExecOutput { node: String, label: String },
+
+    /// The function takes a parameter missing from `params`
+    ParamNotDeclared { node: String, param: String },
+
+    /// A declared param is not a parameter of the function
+    ParamNotInSource { node: String, param: String },
+
+    /// A param name is declared more than once
+    DuplicateParam { node: String, param: String },
+
+    /// The params are declared in a different order than the function takes them
+    ParamOrderMismatch { node: String },
+
+    /// A param's declared type differs from the function's parameter type
+    ParamTypeMismatch {
+        node: String,
+        param: String,
+        declared: String,
+        source: String,
+    },
+
+    /// The declared return type differs from the function's (`None` = no return value)
+    ReturnTypeMismatch {
+        node: String,
+        declared: Option<String>,
+        source: Option<String>,
+    },
+}
+
+impl MetadataIssue {
+    /// Returns the name of the node the issue belongs to.
+    pub fn node(&self) -> &str {
+        match self {
+            MetadataIssue::InvalidSource { node, .. }
+            | MetadataIssue::ExecOutputNotDeclared { node, .. }
+            | MetadataIssue::ExecOutputNotInSource { node, .. }
+            | MetadataIssue::DuplicateExecOutput { node, .. }
+            | MetadataIssue::ParamNotDeclared { node, .. }
+            | MetadataIssue::ParamNotInSource { node, .. }
+            | MetadataIssue::DuplicateParam { node, .. }
+            | MetadataIssue::ParamOrderMismatch { node }
+            | MetadataIssue::ParamTypeMismatch { node, .. }
+            | MetadataIssue::ReturnTypeMismatch { node, .. } => node,
+        }
+    }
+}
+
+impl fmt::Display for MetadataIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let type_text = |ty: &Option<String>| ty.clone().unwrap_or_else(|| "()".to_string());
+        match self {
+            MetadataIssue::InvalidSource { node, message } => write!(f, "{}: invalid source: {}", node, message),
+            MetadataIssue::ExecOutputNotDeclared { node, label } => {
+                write!(f, "{}: source emits exec output \"{}\" missing from exec_outputs", node, label)
+            }
+            MetadataIssue::ExecOutputNotInSource { node, label } => {
+                write!(f, "{}: exec output \"{}\" is never emitted by the source", node, label)
+            }
+            MetadataIssue::DuplicateExecOutput { node, label } => {
+                write!(f, "{}: exec output \"{}\" is declared more than once", node, label)
+            }
+            MetadataIssue::ParamNotDeclared { node, param } => {
+                write!(f, "{}: source parameter {} is missing from params", node, param)
+            }
+            MetadataIssue::ParamNotInSource { node, param } => {
+                write!(f, "{}: param {} is not a parameter of the source function", node, param)
+            }
+            MetadataIssue::DuplicateParam { node, param } => {
+                write!(f, "{}: param {} is declared more than once", node, param)
+            }
+            MetadataIssue::ParamOrderMismatch { node } => {
+                write!(f, "{}: params are declared in a different order than the source function's", node)
+            }
+            MetadataIssue::ParamTypeMismatch {
+                node,
+                param,
+                declared,
+                source,
+            } => write!(f, "{}: param {} is declared as {} but the source takes {}", node, param, declared, source),
+            MetadataIssue::ReturnTypeMismatch { node, declared, source } => write!(
+                f,
+                "{}: return type is declared as {} but the source returns {}",
+                node,
+                type_text(declared),
+                type_text(source)
+            ),
+        }
+    }
+}
+
+impl NodeMetadata {
+    /// Cross-checks the metadata against its `function_source`.
+    ///
+    /// Checks execution output labels, params, and the return type, and
+    /// looks for duplicate names. Returns every issue found, empty if the
+    /// metadata is consistent.
+    pub fn validate(&self) -> Vec<MetadataIssue> {
+        let mut issues = Vec::new();
+        let node = || self.name.clone();
+
+        let group = self.variadic.iter().map(|group| group.name.as_str());
+        for param in duplicates(self.params.iter().map(|p| p.name.as_str()).chain(group)) {
+            issues.push(MetadataIssue::DuplicateParam { node: node(), param });
+        }
+        for label in duplicates(self.exec_outputs.iter().map(String::as_str)) {
+            issues.push(MetadataIssue::DuplicateExecOutput { node: node(), label });
+        }
+
+        if self.function_source.trim().is_empty() {
+            return issues;
+        }
+
+        if self.source_kind == SourceKind::Template {
+            self.check_template(&mut issues);
+            return issues;
+        }
+
+        let derived = match NodeMetadata::from_fn_source(&self.function_source) {
+            Ok(derived) => derived,
+            // Pure nodes may be a bare expression like `a + b`
+            Err(_) if self.node_type == NodeTypes::pure && syn::parse_str::<syn::Expr>(&self.function_source).is_ok() => {
+                return issues;
+            }
+            Err(e) => {
+                issues.push(MetadataIssue::InvalidSource {
+                    node: node(),
+                    message: e.to_string(),
+                });
+                return issues;
+            }
+        };
+
+        // Switch cases are execution outputs of node instances, not metadata
+        if !self.is_switch {
+            // Already parsed as a function, so extraction can't fail
+            let labels = extract_exec_output_labels(&self.function_source).unwrap_or_default();
+            self.check_exec_outputs(labels, &mut issues);
+        }
+        if self.variadic.is_none() {
+            self.check_params(&derived, &mut issues);
+        }
+
+        let declared = self.return_type.as_ref().map(|t| normalize_type(&t.type_string));
+        let source = derived.return_type.map(|t| t.type_string);
+        if declared != source {
+            issues.push(MetadataIssue::ReturnTypeMismatch {
+                node: node(),
+                declared,
+                source,
+            });
+        }
+
+        issues
+    }
+
+    fn check_template(&self, issues: &mut Vec<MetadataIssue>) {
+        let template = match SourceTemplate::parse(&self.function_source) {
+            Ok(template) => template,
+            Err(e) => {
+                issues.push(MetadataIssue::InvalidSource {
+                    node: self.name.clone(),
+                    message: e.to_string(),
+                });
+                return;
+            }
+        };
+
+        if !self.is_switch {
+            let labels = template.exec_labels().into_iter().map(String::from).collect();
+            self.check_exec_outputs(labels, issues);
+        }
+
+        let used = template.params();
+        let variadic = |param: &str| {
+            self.variadic
+                .as_ref()
+                .is_some_and(|group| group.name == param || group.pin_index(param).is_some())
+        };
+        for &param in &used {
+            if self.param(param).is_none() && !variadic(param) {
+                issues.push(MetadataIssue::ParamNotDeclared {
+                    node: self.name.clone(),
+                    param: param.to_string(),
+                });
+            }
+        }
+        for param in &self.params {
+            if !used.contains(&param.name.as_str()) {
+                issues.push(MetadataIssue::ParamNotInSource {
+                    node: self.name.clone(),
+                    param: param.name.clone(),
+                });
+            }
+        }
+    }
+
+    fn check_exec_outputs(&self, mut labels: Vec<String>, issues: &mut Vec<MetadataIssue>) {
+        let mut seen = HashSet::new();
+        labels.retain(|label| seen.insert(label.clone()));
+
+        for label in &labels {
+            if !self.exec_outputs.contains(label) {
+                issues.push(MetadataIssue::ExecOutputNotDeclared {
+                    node: self.name.clone(),
+                    label: label.clone(),
+                });
+            }
+        }
+
+        let implicit = match self.node_type {
+            NodeTypes::fn_ => Some("then"),
+            NodeTypes::event => Some("exec"),
+            NodeTypes::pure | NodeTypes::control_flow => None,
+        };
+        let only_implicit = matches!(self.exec_outputs.as_slice(), [output] if Some(output.as_str()) == implicit);
+        if labels.is_empty() && only_implicit {
+            return;
+        }
+
+        for label in &self.exec_outputs {
+            if !labels.contains(label) {
+                issues.push(MetadataIssue::ExecOutputNotInSource {
+                    node: self.name.clone(),
+                    label: label.clone(),
+                });
+            }
+        }
+    }
+
+    fn check_params(&self, derived: &NodeMetadata, issues: &mut Vec<MetadataIssue>) {
+        for source_param in &derived.params {
+            match self.param(&source_param.name) {
+                Some(param) => {
+                    let declared = normalize_type(&param.param_type);
+                    if declared != source_param.param_type {
+                        issues.push(MetadataIssue::ParamTypeMismatch {
+                            node: self.name.clone(),
+                            param: param.name.clone(),
+                            declared,
+                            source: source_param.param_type.clone(),
+                        });
+                    }
+                }
+                None => issues.push(MetadataIssue::ParamNotDeclared {
+                    node: self.name.clone(),
+                    param: source_param.name.clone(),
+                }),
+            }
+        }
+
+        for param in &self.params {
+            if derived.param(&param.name).is_none() {
+                issues.push(MetadataIssue::ParamNotInSource {
+                    node: self.name.clone(),
+                    param: param.name.clone(),
+                });
+            }
+        }
+
+        // Order only matters once the names agree
+        let declared: Vec<&str> = self.params.iter().map(|p| p.name.as_str()).collect();
+        let source: Vec<&str> = derived.params.iter().map(|p| p.name.as_str()).collect();
+        let same_names = declared.len() == source.len() && source.iter().all(|name| declared.contains(name));
+        if same_names && declared != source {
+            issues.push(MetadataIssue::ParamOrderMismatch { node: self.name.clone() });
+        }
+    }
+}
+
+/// Validates every node of a provider.
+///
+/// Returns the issues of all nodes, grouped by node in name order.
+///
+/// # Example
+///
+/// ```
+/// use graphy::core::{validate_all, NodeRegistry};
+/// use graphy::{NodeMetadata, NodeTypes, ParamInfo};
+///
+/// let mut registry = NodeRegistry::new();
+/// registry.register(
+///     NodeMetadata::new("add", NodeTypes::pure, "Math")
+///         .with_params(vec![ParamInfo::new("a", "f64"), ParamInfo::new("b", "f64")])
+///         .with_return_type("f64")
+///         .with_source("fn add(a: f64, b: f64) -> f64 { a + b }"),
+/// );
+/// assert!(validate_all(&registry).is_empty());
+/// ```
+pub fn validate_all<P: NodeMetadataProvider + ?Sized>(provider: &P) -> Vec<MetadataIssue> {
+    let mut nodes = provider.get_all_nodes();
+    nodes.sort_by(|a, b| a.name.cmp(&b.name));
+    nodes.iter().flat_map(|metadata| metadata.validate()).collect()
+}
+
+/// Names that occur more than once, each reported once in first-seen order
+fn duplicates<'a>(names: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut reported = HashSet::new();
+    names
+        .filter(|name| !seen.insert(*name) && reported.insert(*name))
+        .map(str::to_string)
+        .collect()
+}
@@ -2,14 +2,58 @@
 //!
 //! Fundamental types for representing node graphs.
 
+mod builder;
+mod catalog;
+mod coercion;
+mod comments;
+mod condition;
+mod duplicate;
+mod externals;
+mod from_source;
 mod graph;
+mod hints;
 mod node;
+mod pattern;
+mod project;
 mod connection;
 mod types;
 mod metadata;
+mod metadata_validation;
+mod multiplicity;
+mod providers;
+mod query;
+mod registry;
+mod semantic;
+mod serialization;
+mod switch;
+mod type_id;
+mod variadic;
 
+pub use builder::*;
+pub use catalog::*;
+pub use coercion::*;
+pub use comments::*;
+pub use condition::*;
+pub use duplicate::*;
+pub use externals::*;
 pub use graph::*;
+pub use hints::*;
 pub use node::*;
+pub use pattern::*;
+pub use project::*;
 pub use connection::*;
 pub use types::*;
 pub use metadata::*;
+pub use metadata_validation::*;
+pub use multiplicity::*;
+pub use providers::*;
+pub use registry::*;
+pub use serialization::*;
+pub use switch::*;
+pub use type_id::*;
+pub use variadic::*;
+
+/// Semver types of node versions, see [`NodeTypeId`]
+pub use semver::{Version, VersionReq};
+
+pub(crate) use semantic::{property_hash, StableHasher};
@@ -0,0 +1,178 @@
+//! # Pin Multiplicity
+//!
+//! How many data connections an input accepts.
+//!
+//! Most inputs take one value, so they accept a single connection. Variadic
+//! nodes (e.g. one appending any number of strings) declare their input as
+//! [`PinMultiplicity::Multi`], or cap it with [`PinMultiplicity::AtMost`].
+//! The multiplicity is declared on the [`ParamInfo`](super::ParamInfo) and
+//! copied to the [`Pin`](super::Pin) of node instances, so nodes without
+//! metadata can declare it too.
+//!
+//! [`GraphDescription::try_add_connection`] refuses connections beyond an
+//! input's limit, and [`GraphDescription::check_pin_multiplicity`] lists the
+//! inputs already over it; compilation fails with
+//! [`GraphyError::TooManyConnections`] for the first of them. The
+//! [`DataResolver`](crate::DataResolver) returns every source of an input
+//! accepting several with
+//! [`get_input_sources_multi`](crate::DataResolver::get_input_sources_multi).
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::core::{NodeRegistry, PinMultiplicity};
+//! use graphy::{Connection, GraphDescription, NodeMetadata, NodeTypes, ParamInfo};
+//!
+//! let mut registry = NodeRegistry::new();
+//! registry.register(NodeMetadata::new("literal", NodeTypes::pure, "Text").with_return_type("String"));
+//! registry.register(
+//!     NodeMetadata::new("concat", NodeTypes::pure, "Text")
+//!         .with_params(vec![ParamInfo::new("parts", "String").with_multiplicity(PinMultiplicity::Multi)])
+//!         .with_return_type("String"),
+//! );
+//!
+//! let mut graph = GraphDescription::new("graph");
+//! for id in ["hello", "world", "concat_1"] {
+//!     let node_type = if id == "concat_1" { "concat" } else { "literal" };
+//!     graph.add_node_of_type(node_type, id, &registry).unwrap();
+//! }
+//! graph.try_add_connection(Connection::data("hello", "result", "concat_1", "parts"), &registry).unwrap();
+//! graph.try_add_connection(Connection::data("world", "result", "concat_1", "parts"), &registry).unwrap();
+//! assert!(graph.check_pin_multiplicity(&registry).is_empty());
+//! ```
+
+use super::{ConnectionType, GraphDescription, NodeInstance, NodeMetadata, NodeMetadataProvider, NodeTypes};
+use crate::GraphyError;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How many data connections an input accepts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PinMultiplicity {
+    /// At most one connection
+    #[default]
+    Single,
+
+    /// Any number of connections
+    Multi,
+
+    /// Up to the given number of connections
+    AtMost(usize),
+}
+
+impl PinMultiplicity {
+    /// Returns the most connections accepted, `None` if unlimited.
+    #[inline]
+    pub fn max_connections(self) -> Option<usize> {
+        match self {
+            PinMultiplicity::Single => Some(1),
+            PinMultiplicity::Multi => None,
+            PinMultiplicity::AtMost(max) => Some(max),
+        }
+    }
+
+    /// Checks if an input can have this many connections.
+    #[inline]
+    pub fn accepts(self, connections: usize) -> bool {
+        self.max_connections().is_none_or(|max| connections <= max)
+    }
+
+    /// Checks if more than one connection is accepted.
+    #[inline]
+    pub fn accepts_several(self) -> bool {
+        self.accepts(2)
+    }
+
+    /// Checks if this is the default [`Single`](Self::Single).
+    #[inline]
+    pub fn is_single(&self) -> bool {
+        *self == PinMultiplicity::Single
+    }
+}
+
+impl NodeInstance {
+    /// Returns how many data connections an input pin accepts.
+    ///
+    /// The param in the node's metadata decides if there is one, since
+    /// metadata may be newer than a saved graph; otherwise the pin itself.
+    /// Unknown pins, and params of event nodes (which are outputs), accept a
+    /// single connection.
+    pub fn input_multiplicity(&self, pin: &str, metadata: Option<&NodeMetadata>) -> PinMultiplicity {
+        let param = metadata
+            .filter(|metadata| metadata.node_type != NodeTypes::event)
+            .and_then(|metadata| metadata.params.iter().find(|param| param.name == pin));
+        match param {
+            Some(param) => param.multiplicity,
+            None => self
+                .inputs
+                .iter()
+                .find(|input| input.id == pin)
+                .map(|input| input.pin.multiplicity)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// An input with more data connections than its multiplicity accepts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiplicityIssue {
+    /// ID of the node
+    pub node: String,
+
+    /// ID of the input pin
+    pub pin: String,
+
+    /// Connections the input accepts
+    pub multiplicity: PinMultiplicity,
+
+    /// Data connections feeding the input
+    pub connections: usize,
+}
+
+impl fmt::Display for MultiplicityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", GraphyError::from(self.clone()))
+    }
+}
+
+impl From<MultiplicityIssue> for GraphyError {
+    fn from(issue: MultiplicityIssue) -> Self {
+        GraphyError::TooManyConnections {
+            node: issue.node,
+            pin: issue.pin,
+            connections: issue.connections,
+            max: issue.multiplicity.max_connections().unwrap_or(usize::MAX),
+        }
+    }
+}
+
+impl GraphDescription {
+    /// Lists the inputs with more data connections than they accept, sorted
+    /// by node ID and pin.
+    ///
+    /// Connections to nodes that don't exist are ignored.
+    pub fn check_pin_multiplicity<P: NodeMetadataProvider + ?Sized>(&self, provider: &P) -> Vec<MultiplicityIssue> {
+        let mut counts: FxHashMap<(&str, &str), usize> = FxHashMap::default();
+        for connection in self.connections.iter().filter(|c| c.connection_type == ConnectionType::Data) {
+            *counts.entry((&connection.target_node, &connection.target_pin)).or_default() += 1;
+        }
+
+        let mut issues: Vec<MultiplicityIssue> = counts
+            .into_iter()
+            .filter_map(|((node_id, pin), connections)| {
+                let node = self.nodes.get(node_id)?;
+                let multiplicity = node.input_multiplicity(pin, provider.metadata_for_node(node));
+                (!multiplicity.accepts(connections)).then(|| MultiplicityIssue {
+                    node: node_id.to_string(),
+                    pin: pin.to_string(),
+                    multiplicity,
+                    connections,
+                })
+            })
+            .collect();
+        issues.sort_unstable_by(|a, b| (&a.node, &a.pin).cmp(&(&b.node, &b.pin)));
+        issues
+    }
+}
@@ -19,15 +19,20 @@
 //! node.set_property("default_a", PropertyValue::Number(0.0));
 //! ```
 
-use super::{DataType, Position, PropertyValue};
+use super::{
+    BuildCondition, DataType, NodeMetadata, NodeTypes, PinMultiplicity, Position, PropertyValue, TypeInfo, ERROR_EXEC_PIN, ERROR_VALUE_PIN,
+    EXEC_INPUT_PIN, GET_VARIABLE_NODE_TYPE, GRAPH_INPUT_NODE_TYPE, GRAPH_OUTPUT_NODE_TYPE, RESULT_PIN,
+    SET_VARIABLE_NODE_TYPE, SWITCH_DEFAULT_PIN, VARIABLE_NAME_PROPERTY,
+};
+use semver::{Comparator, Op, Version, VersionReq};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
 /// A pin definition template.
 ///
 /// Pins are the connection points on nodes. They have a type (input/output)
 /// and a data type that determines what kind of values can flow through them.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Pin {
     /// Unique identifier for this pin
     pub id: String,
@@ -40,6 +45,10 @@ pub struct Pin {
     
     /// Whether this is an input or output pin
     pub pin_type: PinType,
+
+    /// How many data connections the pin accepts, if it is an input
+    #[serde(default, skip_serializing_if = "PinMultiplicity::is_single")]
+    pub multiplicity: PinMultiplicity,
 }
 
 impl Pin {
@@ -59,8 +68,19 @@ impl Pin {
             name: name.into(),
             data_type,
             pin_type,
+            multiplicity: PinMultiplicity::Single,
         }
     }
+
+    /// Sets how many data connections the pin accepts.
+    ///
+    /// See [`PinMultiplicity`].
+    #[inline]
+    #[must_use]
+    pub fn with_multiplicity(mut self, multiplicity: PinMultiplicity) -> Self {
+        self.multiplicity = multiplicity;
+        self
+    }
 }
 
 /// Direction of data flow for a pin.
@@ -73,11 +93,23 @@ pub enum PinType {
     Output,
 }
 
+impl PinType {
+    /// Returns the opposite direction.
+    #[inline]
+    #[must_use]
+    pub const fn opposite(self) -> Self {
+        match self {
+            PinType::Input => PinType::Output,
+            PinType::Output => PinType::Input,
+        }
+    }
+}
+
 /// A pin instance on a specific node.
 ///
 /// While [`Pin`] is a template, `PinInstance` represents an actual pin
 /// on a node instance in the graph.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PinInstance {
     /// Instance-specific ID (may differ from pin template ID)
     pub id: String,
@@ -106,7 +138,7 @@ impl PinInstance {
 ///
 /// Properties are stored in a `HashMap` for O(1) access. For nodes with
 /// many properties (10+), this is significantly faster than linear search.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct NodeInstance {
     /// Unique identifier for this node instance within the graph
     pub id: String,
@@ -126,6 +158,48 @@ pub struct NodeInstance {
 
     /// Constant property values (defaults, configuration, etc.)
     pub properties: HashMap<String, PropertyValue>,
+
+    /// Versions of the node type this node works with (e.g. `^1.2`)
+    ///
+    /// `None` accepts any version. See
+    /// [`NodeMetadataProvider::metadata_for_node`](super::NodeMetadataProvider::metadata_for_node).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_req: Option<VersionReq>,
+
+    /// Number of inputs in the variadic group of the node type
+    ///
+    /// Zero for node types without one. See
+    /// [`GraphDescription::set_variadic_count`](super::GraphDescription::set_variadic_count).
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub variadic_count: usize,
+
+    /// Build configurations the node is compiled in, `None` for all
+    ///
+    /// See [`GraphDescription::prune_disabled`](super::GraphDescription::prune_disabled).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<BuildCondition>,
+
+    /// Pause here in debug builds
+    ///
+    /// Only compiled with [`CompileOptions::with_debug`](crate::generation::CompileOptions::with_debug).
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub breakpoint: bool,
+
+    /// Report the node's input values in debug builds
+    ///
+    /// Only compiled with [`CompileOptions::with_debug`](crate::generation::CompileOptions::with_debug).
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub trace: bool,
+
+    /// Output pins whose values are reported as they are computed
+    ///
+    /// Only compiled with [`CompileOptions::with_probes`](crate::generation::CompileOptions::with_probes).
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub watched_pins: BTreeSet<String>,
+}
+
+pub(super) fn is_zero<T: Default + PartialEq>(count: &T) -> bool {
+    *count == T::default()
 }
 
 impl NodeInstance {
@@ -149,9 +223,95 @@ impl NodeInstance {
             inputs: Vec::new(),
             outputs: Vec::new(),
             properties: HashMap::new(),
+            version_req: None,
+            variadic_count: 0,
+            condition: None,
+            breakpoint: false,
+            trace: false,
+            watched_pins: BTreeSet::new(),
         }
     }
 
+    /// Creates a node instance with the pins its metadata declares.
+    ///
+    /// The node type is the metadata name. Pins are added as follows:
+    ///
+    /// - events: an execution output per `exec_outputs` entry, then a typed
+    ///   data output per param
+    /// - other nodes: an [`EXEC_INPUT_PIN`] (except pure nodes) and a typed
+    ///   data input per param, with the param's multiplicity, then the
+    ///   `min` inputs of the variadic group, an execution output per
+    ///   `exec_outputs` entry and a [`RESULT_PIN`] of the return type, unless
+    ///   it is `()`
+    /// - fallible nodes: the result is the `Ok` type, followed by an
+    ///   [`ERROR_EXEC_PIN`] and an [`ERROR_VALUE_PIN`] of the error type
+    /// - switch nodes: a [`SWITCH_DEFAULT_PIN`]; the cases depend on the
+    ///   value type, see [`GraphDescription::add_node_of_type`](super::GraphDescription::add_node_of_type)
+    ///
+    /// Nodes of versioned metadata require a compatible version (`^` the
+    /// metadata version).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::{DataType, NodeInstance, NodeMetadata, NodeTypes, ParamInfo, Position};
+    ///
+    /// let print = NodeMetadata::new("print", NodeTypes::fn_, "IO")
+    ///     .with_params(vec![ParamInfo::new("message", "String")])
+    ///     .with_exec_outputs(vec!["then".into()]);
+    ///
+    /// let node = NodeInstance::from_metadata("print_1", &print, Position::zero());
+    /// assert_eq!(node.node_type, "print");
+    /// assert_eq!(node.inputs[0].id, "exec_in");
+    /// assert_eq!(node.inputs[1].pin.data_type, DataType::Typed("String".into()));
+    /// assert_eq!(node.outputs[0].pin.data_type, DataType::Execution);
+    /// ```
+    pub fn from_metadata(id: impl Into<String>, metadata: &NodeMetadata, position: Position) -> Self {
+        let mut node = Self::new(id, metadata.name.clone(), position);
+        node.version_req = metadata.version.as_ref().map(compatible_with);
+        let typed = |type_string: &str| DataType::Typed(TypeInfo::new(type_string));
+
+        if metadata.node_type == NodeTypes::event {
+            for output in &metadata.exec_outputs {
+                node.add_output_pin(output.clone(), DataType::Execution);
+            }
+            for param in &metadata.params {
+                node.add_output_pin(param.name.clone(), typed(&param.param_type));
+            }
+            return node;
+        }
+
+        if metadata.node_type != NodeTypes::pure {
+            node.add_input_pin(EXEC_INPUT_PIN, DataType::Execution);
+        }
+        for param in &metadata.params {
+            let pin = Pin::new(param.name.clone(), param.name.clone(), typed(&param.param_type), PinType::Input)
+                .with_multiplicity(param.multiplicity);
+            node.inputs.push(PinInstance::new(param.name.clone(), pin));
+        }
+        if let Some(group) = &metadata.variadic {
+            for index in 0..group.min {
+                node.add_input_pin(group.pin_name(index), typed(&group.param_type));
+            }
+            node.variadic_count = group.min;
+        }
+        for output in &metadata.exec_outputs {
+            node.add_output_pin(output.clone(), DataType::Execution);
+        }
+
+        if let Some(result_type) = metadata.result_type() {
+            node.add_output_pin(RESULT_PIN, typed(result_type));
+        }
+        if metadata.is_fallible() {
+            node.add_output_pin(ERROR_EXEC_PIN, DataType::Execution);
+            node.add_output_pin(ERROR_VALUE_PIN, metadata.error_type().map_or(DataType::Any, typed));
+        }
+        if metadata.switch_value().is_some() {
+            node.add_output_pin(SWITCH_DEFAULT_PIN, DataType::Execution);
+        }
+        node
+    }
+
     /// Adds an input pin to this node.
     ///
     /// The pin ID and name will be the same. For custom names, create a [`Pin`] directly.
@@ -210,4 +370,92 @@ impl NodeInstance {
     pub fn get_property(&self, key: &str) -> Option<&PropertyValue> {
         self.properties.get(key)
     }
+
+    /// Reports the values of an output pin in builds with value probes.
+    ///
+    /// Returns `false` if the pin was already watched.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::{NodeInstance, Position};
+    ///
+    /// let mut node = NodeInstance::new("add_1", "math.add", Position::zero());
+    /// node.watch_pin("result");
+    /// assert!(node.is_watched("result"));
+    /// ```
+    pub fn watch_pin(&mut self, pin: impl Into<String>) -> bool {
+        self.watched_pins.insert(pin.into())
+    }
+
+    /// Stops reporting the values of an output pin.
+    ///
+    /// Returns `false` if the pin wasn't watched.
+    pub fn unwatch_pin(&mut self, pin: &str) -> bool {
+        self.watched_pins.remove(pin)
+    }
+
+    /// Checks if the values of an output pin are reported.
+    #[inline]
+    pub fn is_watched(&self, pin: &str) -> bool {
+        self.watched_pins.contains(pin)
+    }
+
+    /// Checks if this is a builtin `graph.input` pseudo-node.
+    #[inline]
+    pub fn is_graph_input(&self) -> bool {
+        self.node_type == GRAPH_INPUT_NODE_TYPE
+    }
+
+    /// Checks if this is a builtin `graph.output` pseudo-node.
+    #[inline]
+    pub fn is_graph_output(&self) -> bool {
+        self.node_type == GRAPH_OUTPUT_NODE_TYPE
+    }
+
+    /// Checks if this is a builtin `graph.get_variable` node.
+    #[inline]
+    pub fn is_get_variable(&self) -> bool {
+        self.node_type == GET_VARIABLE_NODE_TYPE
+    }
+
+    /// Checks if this is a builtin `graph.set_variable` node.
+    #[inline]
+    pub fn is_set_variable(&self) -> bool {
+        self.node_type == SET_VARIABLE_NODE_TYPE
+    }
+
+    /// Checks if this is one of the builtin nodes (graph inputs and outputs,
+    /// variable nodes), which have no metadata.
+    #[inline]
+    pub fn is_builtin(&self) -> bool {
+        self.is_graph_input() || self.is_graph_output() || self.is_get_variable() || self.is_set_variable()
+    }
+
+    /// Returns the variable read or written by a variable node.
+    ///
+    /// Returns `None` for other nodes, or if the name property is missing or
+    /// not a string.
+    pub fn variable_name(&self) -> Option<&str> {
+        if !self.is_get_variable() && !self.is_set_variable() {
+            return None;
+        }
+        match self.properties.get(VARIABLE_NAME_PROPERTY) {
+            Some(PropertyValue::String(name)) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+/// `^version`
+fn compatible_with(version: &Version) -> VersionReq {
+    VersionReq {
+        comparators: vec![Comparator {
+            op: Op::Caret,
+            major: version.major,
+            minor: Some(version.minor),
+            patch: Some(version.patch),
+            pre: version.pre.clone(),
+        }],
+    }
 }
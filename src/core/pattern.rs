@@ -0,0 +1,492 @@
+//! # Pattern Matching
+//!
+//! Finds every place where a structural pattern occurs in a graph.
+//!
+//! A [`Pattern`] is a small graph of named [`PatternNode`]s, each
+//! constraining the type, properties, or anything else of the node it
+//! matches, joined by [`PatternConnection`]s that must exist between the
+//! matched nodes. Every match binds each pattern node to a distinct graph
+//! node. Connections not mentioned in the pattern are allowed.
+//!
+//! Matches are found by backtracking from the first pattern node, following
+//! connections to the others, and are returned in a deterministic order.
+//! Symmetric patterns match once per symmetry: a pattern of two constants
+//! feeding any input of a multiply matches twice, once for each way to
+//! assign them. Fix the target pins to tell them apart.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::core::{Pattern, PatternConnection, PatternNode};
+//! use graphy::{Connection, GraphDescription, NodeInstance, Position};
+//!
+//! let mut graph = GraphDescription::new("graph");
+//! for (id, node_type) in [("x", "math.constant"), ("y", "math.constant"), ("mul", "math.multiply")] {
+//!     graph.add_node(NodeInstance::new(id, node_type, Position::zero()));
+//! }
+//! graph.add_connection(Connection::data("x", "result", "mul", "a"));
+//! graph.add_connection(Connection::data("y", "result", "mul", "b"));
+//!
+//! // A multiply fed by two constants
+//! let pattern = Pattern::new()
+//!     .with_node("mul", PatternNode::of_type("math.multiply"))
+//!     .with_node("lhs", PatternNode::of_type("math.constant"))
+//!     .with_node("rhs", PatternNode::of_type("math.constant"))
+//!     .with_connection(PatternConnection::new("lhs", "mul").to_pin("a"))
+//!     .with_connection(PatternConnection::new("rhs", "mul").to_pin("b"));
+//!
+//! let matches = pattern.find_matches(&graph);
+//! assert_eq!(matches.len(), 1);
+//! assert_eq!(matches[0].node("lhs"), Some("x"));
+//! assert_eq!(matches[0].node("rhs"), Some("y"));
+//! ```
+
+use super::{Connection, ConnectionType, GraphDescription, NodeInstance, PropertyValue};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::Arc;
+
+/// Predicate on the nodes a [`PatternNode`] matches
+type NodePredicate = Arc<dyn Fn(&NodeInstance) -> bool + Send + Sync>;
+
+/// Constraints on the graph node a pattern node binds to.
+#[derive(Clone, Default)]
+pub struct PatternNode {
+    /// Required node type
+    node_type: Option<String>,
+
+    /// Required property values
+    properties: Vec<(String, PropertyValue)>,
+
+    /// Extra check on the node
+    predicate: Option<NodePredicate>,
+}
+
+impl PatternNode {
+    /// Matches any node.
+    #[inline]
+    pub fn any() -> Self {
+        Self::default()
+    }
+
+    /// Matches nodes of a type.
+    #[inline]
+    pub fn of_type(node_type: impl Into<String>) -> Self {
+        Self {
+            node_type: Some(node_type.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Also requires a property to have a value.
+    #[must_use]
+    pub fn with_property(mut self, key: impl Into<String>, value: PropertyValue) -> Self {
+        self.properties.push((key.into(), value));
+        self
+    }
+
+    /// Also requires a predicate to hold.
+    ///
+    /// Replaces any earlier predicate.
+    #[must_use]
+    pub fn with_predicate(
+        mut self,
+        predicate: impl Fn(&NodeInstance) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.predicate = Some(Arc::new(predicate));
+        self
+    }
+
+    /// Checks if a node satisfies every constraint.
+    pub fn matches(&self, node: &NodeInstance) -> bool {
+        self.node_type
+            .as_ref()
+            .is_none_or(|node_type| node.node_type == *node_type)
+            && self
+                .properties
+                .iter()
+                .all(|(key, value)| node.get_property(key) == Some(value))
+            && self
+                .predicate
+                .as_ref()
+                .is_none_or(|predicate| predicate(node))
+    }
+}
+
+impl fmt::Debug for PatternNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PatternNode")
+            .field("node_type", &self.node_type)
+            .field("properties", &self.properties)
+            .field("predicate", &self.predicate.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+/// A connection that must exist between two matched nodes.
+///
+/// Pins and the connection type are unconstrained unless set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternConnection {
+    /// Name of the source pattern node
+    pub source: String,
+
+    /// Required source pin
+    pub source_pin: Option<String>,
+
+    /// Name of the target pattern node
+    pub target: String,
+
+    /// Required target pin
+    pub target_pin: Option<String>,
+
+    /// Required connection type
+    pub connection_type: Option<ConnectionType>,
+}
+
+impl PatternConnection {
+    /// Requires a connection from `source` to `target`, by pattern node name.
+    pub fn new(source: impl Into<String>, target: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            source_pin: None,
+            target: target.into(),
+            target_pin: None,
+            connection_type: None,
+        }
+    }
+
+    /// Requires the connection to leave this output pin.
+    #[must_use]
+    pub fn from_pin(mut self, pin: impl Into<String>) -> Self {
+        self.source_pin = Some(pin.into());
+        self
+    }
+
+    /// Requires the connection to enter this input pin.
+    #[must_use]
+    pub fn to_pin(mut self, pin: impl Into<String>) -> Self {
+        self.target_pin = Some(pin.into());
+        self
+    }
+
+    /// Requires the connection to be of a type.
+    #[must_use]
+    pub fn of_type(mut self, connection_type: ConnectionType) -> Self {
+        self.connection_type = Some(connection_type);
+        self
+    }
+
+    /// Checks if a graph connection between the bound nodes satisfies the pins and type.
+    fn matches(&self, connection: &Connection) -> bool {
+        self.source_pin
+            .as_ref()
+            .is_none_or(|pin| connection.source_pin == *pin)
+            && self
+                .target_pin
+                .as_ref()
+                .is_none_or(|pin| connection.target_pin == *pin)
+            && self
+                .connection_type
+                .is_none_or(|connection_type| connection.connection_type == connection_type)
+    }
+}
+
+/// A structural pattern of named nodes and the connections between them.
+#[derive(Debug, Clone, Default)]
+pub struct Pattern {
+    /// Pattern nodes, in declaration order
+    nodes: Vec<(String, PatternNode)>,
+
+    /// Required connections
+    connections: Vec<PatternConnection>,
+}
+
+impl Pattern {
+    /// Creates an empty pattern, which matches nothing.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a named node, replacing any node with the same name.
+    ///
+    /// Matching starts from the first node added, so a selective first node
+    /// (e.g. with a type) keeps matching fast.
+    #[must_use]
+    pub fn with_node(mut self, name: impl Into<String>, node: PatternNode) -> Self {
+        let name = name.into();
+        match self
+            .nodes
+            .iter_mut()
+            .find(|(existing, _)| *existing == name)
+        {
+            Some((_, existing)) => *existing = node,
+            None => self.nodes.push((name, node)),
+        }
+        self
+    }
+
+    /// Adds a required connection.
+    ///
+    /// A connection naming a node that isn't in the pattern never matches.
+    #[must_use]
+    pub fn with_connection(mut self, connection: PatternConnection) -> Self {
+        self.connections.push(connection);
+        self
+    }
+
+    /// Returns the names of the pattern nodes, in declaration order.
+    pub fn node_names(&self) -> impl Iterator<Item = &str> {
+        self.nodes.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Returns the required connections.
+    #[inline]
+    pub fn connections(&self) -> &[PatternConnection] {
+        &self.connections
+    }
+
+    /// Returns every match in the graph.
+    pub fn find_matches(&self, graph: &GraphDescription) -> Vec<PatternMatch> {
+        let mut matches = Vec::new();
+        Matcher::new(self, graph).search(&mut |bindings| {
+            matches.push(self.bind(bindings));
+            true
+        });
+        matches
+    }
+
+    /// Returns the first match in the graph, if any.
+    pub fn find_first(&self, graph: &GraphDescription) -> Option<PatternMatch> {
+        let mut found = None;
+        Matcher::new(self, graph).search(&mut |bindings| {
+            found = Some(self.bind(bindings));
+            false
+        });
+        found
+    }
+
+    /// Checks if the pattern occurs in the graph.
+    #[inline]
+    pub fn is_match(&self, graph: &GraphDescription) -> bool {
+        self.find_first(graph).is_some()
+    }
+
+    fn bind(&self, bindings: &[Option<&str>]) -> PatternMatch {
+        PatternMatch {
+            nodes: self
+                .nodes
+                .iter()
+                .zip(bindings)
+                .map(|((name, _), node)| (name.clone(), node.unwrap_or_default().to_string()))
+                .collect(),
+        }
+    }
+}
+
+/// Where a pattern matched: the graph node bound to each pattern node.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PatternMatch {
+    /// (pattern node name, graph node ID), in pattern declaration order
+    nodes: Vec<(String, String)>,
+}
+
+impl PatternMatch {
+    /// Returns the ID of the graph node bound to a pattern node.
+    pub fn node(&self, name: &str) -> Option<&str> {
+        self.nodes
+            .iter()
+            .find(|(pattern_name, _)| pattern_name == name)
+            .map(|(_, node_id)| node_id.as_str())
+    }
+
+    /// Returns the (pattern node name, graph node ID) pairs, in pattern order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.nodes
+            .iter()
+            .map(|(name, node_id)| (name.as_str(), node_id.as_str()))
+    }
+
+    /// Returns the IDs of the matched graph nodes, in pattern order.
+    pub fn node_ids(&self) -> impl Iterator<Item = &str> {
+        self.nodes.iter().map(|(_, node_id)| node_id.as_str())
+    }
+
+    /// Checks if a graph node is part of the match.
+    pub fn contains_node(&self, node_id: &str) -> bool {
+        self.node_ids().any(|id| id == node_id)
+    }
+}
+
+/// Backtracking search state for one pattern over one graph
+struct Matcher<'p, 'g> {
+    pattern: &'p Pattern,
+    graph: &'g GraphDescription,
+
+    /// Pattern node indices in binding order: breadth-first over connections
+    order: Vec<usize>,
+
+    /// Pattern connections by their (source, target) node indices; `None`
+    /// for connections naming unknown nodes
+    connections: Vec<Option<(usize, usize)>>,
+
+    /// Graph connections by source node
+    outgoing: HashMap<&'g str, Vec<&'g Connection>>,
+
+    /// Graph connections by target node
+    incoming: HashMap<&'g str, Vec<&'g Connection>>,
+
+    /// All graph node IDs, sorted
+    node_ids: Vec<&'g str>,
+}
+
+impl<'p, 'g> Matcher<'p, 'g> {
+    fn new(pattern: &'p Pattern, graph: &'g GraphDescription) -> Self {
+        let index = |name: &str| pattern.nodes.iter().position(|(node, _)| node == name);
+        let connections: Vec<Option<(usize, usize)>> = pattern
+            .connections
+            .iter()
+            .map(|c| Some((index(&c.source)?, index(&c.target)?)))
+            .collect();
+
+        let mut order = Vec::with_capacity(pattern.nodes.len());
+        let mut visited = vec![false; pattern.nodes.len()];
+        for start in 0..pattern.nodes.len() {
+            if visited[start] {
+                continue;
+            }
+            visited[start] = true;
+            let mut queue = VecDeque::from([start]);
+            while let Some(current) = queue.pop_front() {
+                order.push(current);
+                for &(source, target) in connections.iter().flatten() {
+                    let next = match (source == current, target == current) {
+                        (true, _) => target,
+                        (_, true) => source,
+                        _ => continue,
+                    };
+                    if !visited[next] {
+                        visited[next] = true;
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+
+        let mut outgoing: HashMap<&str, Vec<&Connection>> = HashMap::new();
+        let mut incoming: HashMap<&str, Vec<&Connection>> = HashMap::new();
+        for connection in &graph.connections {
+            outgoing
+                .entry(connection.source_node.as_str())
+                .or_default()
+                .push(connection);
+            incoming
+                .entry(connection.target_node.as_str())
+                .or_default()
+                .push(connection);
+        }
+        let mut node_ids: Vec<&str> = graph.nodes.keys().map(String::as_str).collect();
+        node_ids.sort_unstable();
+
+        Self {
+            pattern,
+            graph,
+            order,
+            connections,
+            outgoing,
+            incoming,
+            node_ids,
+        }
+    }
+
+    /// Reports each complete binding until `found` returns `false`
+    fn search(&self, found: &mut dyn FnMut(&[Option<&'g str>]) -> bool) {
+        if self.pattern.nodes.is_empty() || self.connections.iter().any(Option::is_none) {
+            return;
+        }
+        let mut bindings = vec![None; self.pattern.nodes.len()];
+        self.extend(0, &mut bindings, found);
+    }
+
+    /// Binds the pattern node at `depth` in the order; returns `false` to stop
+    fn extend(
+        &self,
+        depth: usize,
+        bindings: &mut Vec<Option<&'g str>>,
+        found: &mut dyn FnMut(&[Option<&'g str>]) -> bool,
+    ) -> bool {
+        let Some(&current) = self.order.get(depth) else {
+            return found(bindings);
+        };
+        let constraint = &self.pattern.nodes[current].1;
+
+        for candidate in self.candidates(current, bindings) {
+            if bindings.contains(&Some(candidate))
+                || !constraint.matches(&self.graph.nodes[candidate])
+            {
+                continue;
+            }
+            bindings[current] = Some(candidate);
+            if self.connections_hold(current, bindings) && !self.extend(depth + 1, bindings, found)
+            {
+                bindings[current] = None;
+                return false;
+            }
+            bindings[current] = None;
+        }
+        true
+    }
+
+    /// Graph nodes a pattern node can bind to, given the nodes bound so far
+    fn candidates(&self, current: usize, bindings: &[Option<&'g str>]) -> Vec<&'g str> {
+        for (pattern_connection, &(source, target)) in self
+            .pattern
+            .connections
+            .iter()
+            .zip(self.connections.iter().flatten())
+        {
+            let (neighbors, toward_target) = match (bindings[source], bindings[target]) {
+                (Some(bound), None) if target == current => (self.outgoing.get(bound), true),
+                (None, Some(bound)) if source == current => (self.incoming.get(bound), false),
+                _ => continue,
+            };
+
+            let mut candidates: Vec<&str> = neighbors
+                .into_iter()
+                .flatten()
+                .filter(|connection| pattern_connection.matches(connection))
+                .map(|connection| match toward_target {
+                    true => connection.target_node.as_str(),
+                    false => connection.source_node.as_str(),
+                })
+                .filter(|node_id| self.graph.nodes.contains_key(*node_id))
+                .collect();
+            candidates.sort_unstable();
+            candidates.dedup();
+            return candidates;
+        }
+        self.node_ids.clone()
+    }
+
+    /// Checks the pattern connections between `current` and bound nodes
+    fn connections_hold(&self, current: usize, bindings: &[Option<&'g str>]) -> bool {
+        self.pattern
+            .connections
+            .iter()
+            .zip(self.connections.iter().flatten())
+            .filter(|(_, &(source, target))| source == current || target == current)
+            .all(|(pattern_connection, &(source, target))| {
+                match (bindings[source], bindings[target]) {
+                    (Some(source_id), Some(target_id)) => {
+                        self.outgoing.get(source_id).is_some_and(|connections| {
+                            connections.iter().any(|c| {
+                                c.target_node == target_id && pattern_connection.matches(c)
+                            })
+                        })
+                    }
+                    _ => true,
+                }
+            })
+    }
+}
@@ -0,0 +1,262 @@
+//! # Graph Projects
+//!
+//! Several named graphs that call each other.
+//!
+//! A [`GraphProject`] holds the graphs of an application by name. A graph
+//! with a declared interface (see
+//! [`add_graph_input`](GraphDescription::add_graph_input)) can be called from
+//! the other graphs of the project: a node whose type is the graph's name
+//! invokes it, taking the graph inputs as data inputs and producing its
+//! outputs as result. The node type's metadata comes from
+//! [`GraphDescription::call_metadata`], and
+//! [`provider`](GraphProject::provider) adds it to a node library.
+//!
+//! [`dependency_order`](GraphProject::dependency_order) sorts the graphs so
+//! that every graph comes after the graphs it calls, and fails with
+//! [`GraphyError::CyclicGraphReference`] if graphs call each other in a
+//! cycle. [`compile_project`](crate::generation::compile_project) compiles
+//! every graph in that order.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::core::GraphProject;
+//! use graphy::{GraphDescription, NodeRegistry};
+//!
+//! let mut lerp = GraphDescription::new("lerp");
+//! lerp.add_graph_input("a", "f64");
+//! lerp.add_graph_input("t", "f64");
+//! lerp.add_graph_output("result", "f64");
+//!
+//! let mut project = GraphProject::new("game").with_graph(lerp);
+//! let library = NodeRegistry::new();
+//! let mut player = GraphDescription::new("player");
+//! player.add_node_of_type("lerp", "lerp_1", &project.provider(&library)).unwrap();
+//! project.add_graph(player);
+//!
+//! assert_eq!(project.dependencies("player"), ["lerp"]);
+//! assert_eq!(project.dependency_order().unwrap(), ["lerp", "player"]);
+//! ```
+
+use super::{ConnectionType, GraphDescription, NodeMetadata, NodeMetadataProvider, NodeTypes, OverlayProvider, ParamInfo};
+use crate::GraphyError;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Category of the node types calling project graphs
+pub const GRAPH_CALL_CATEGORY: &str = "Graphs";
+
+/// Named graphs that may call each other.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GraphProject {
+    /// Name of the project
+    pub name: String,
+
+    /// Graphs by name (their [`metadata.name`](super::GraphMetadata::name))
+    pub graphs: BTreeMap<String, GraphDescription>,
+}
+
+/// A node calling another graph of its project.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct GraphReference {
+    /// Name of the calling graph
+    pub graph: String,
+
+    /// ID of the calling node
+    pub node: String,
+
+    /// Name of the called graph
+    pub target: String,
+}
+
+impl GraphProject {
+    /// Creates an empty project.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            graphs: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a graph under its name, replacing a graph of the same name.
+    #[must_use]
+    pub fn with_graph(mut self, graph: GraphDescription) -> Self {
+        self.add_graph(graph);
+        self
+    }
+
+    /// Adds a graph under its name.
+    ///
+    /// Returns the graph it replaces, if any.
+    pub fn add_graph(&mut self, graph: GraphDescription) -> Option<GraphDescription> {
+        self.graphs.insert(graph.metadata.name.clone(), graph)
+    }
+
+    /// Removes a graph.
+    ///
+    /// Nodes calling it are kept; compiling them fails until they are removed
+    /// or the graph is added back.
+    pub fn remove_graph(&mut self, name: &str) -> Option<GraphDescription> {
+        self.graphs.remove(name)
+    }
+
+    /// Returns a graph by name.
+    #[inline]
+    pub fn graph(&self, name: &str) -> Option<&GraphDescription> {
+        self.graphs.get(name)
+    }
+
+    /// Returns a graph by name for editing.
+    #[inline]
+    pub fn graph_mut(&mut self, name: &str) -> Option<&mut GraphDescription> {
+        self.graphs.get_mut(name)
+    }
+
+    /// Returns the number of graphs.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.graphs.len()
+    }
+
+    /// Returns `true` if the project has no graphs.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.graphs.is_empty()
+    }
+
+    /// Adds the node types calling the project's graphs to a node library.
+    ///
+    /// Graph names take precedence over node types of the library.
+    pub fn provider<P: NodeMetadataProvider>(&self, base: P) -> OverlayProvider<P> {
+        self.graphs
+            .values()
+            .fold(OverlayProvider::new(base), |provider, graph| provider.with_override(graph.call_metadata()))
+    }
+
+    /// Lists the nodes calling other graphs of the project, sorted by graph
+    /// and node.
+    pub fn references(&self) -> Vec<GraphReference> {
+        let mut references: Vec<GraphReference> = self
+            .graphs
+            .iter()
+            .flat_map(|(name, graph)| {
+                graph
+                    .nodes
+                    .values()
+                    .filter(|node| self.graphs.contains_key(&node.node_type))
+                    .map(move |node| GraphReference {
+                        graph: name.clone(),
+                        node: node.id.clone(),
+                        target: node.node_type.clone(),
+                    })
+            })
+            .collect();
+        references.sort_unstable();
+        references
+    }
+
+    /// Returns the graphs a graph calls, sorted by name.
+    pub fn dependencies(&self, name: &str) -> Vec<&str> {
+        let Some(graph) = self.graphs.get(name) else {
+            return Vec::new();
+        };
+        let targets: BTreeSet<&str> = graph
+            .nodes
+            .values()
+            .filter_map(|node| self.graphs.get_key_value(&node.node_type).map(|(target, _)| target.as_str()))
+            .collect();
+        targets.into_iter().collect()
+    }
+
+    /// Returns the graphs calling a graph, sorted by name.
+    pub fn dependents(&self, name: &str) -> Vec<&str> {
+        self.graphs
+            .iter()
+            .filter(|(_, graph)| graph.nodes.values().any(|node| node.node_type == name))
+            .map(|(caller, _)| caller.as_str())
+            .collect()
+    }
+
+    /// Sorts the graphs so that each comes after the graphs it calls.
+    ///
+    /// The order is stable: graphs are taken by name, each preceded by the
+    /// graphs it calls that aren't listed yet.
+    ///
+    /// # Errors
+    ///
+    /// [`GraphyError::CyclicGraphReference`] if graphs call each other in a
+    /// cycle, including a graph calling itself.
+    pub fn dependency_order(&self) -> Result<Vec<&str>, GraphyError> {
+        let mut order = Vec::with_capacity(self.graphs.len());
+        let mut done: BTreeSet<&str> = BTreeSet::new();
+
+        for root in self.graphs.keys() {
+            if done.contains(root.as_str()) {
+                continue;
+            }
+            // Depth first, with the graphs being visited and their next dependency
+            let mut stack: Vec<(&str, Vec<&str>, usize)> = vec![(root, self.dependencies(root), 0)];
+            while let Some((name, dependencies, next)) = stack.last_mut() {
+                let Some(&dependency) = dependencies.get(*next) else {
+                    done.insert(name);
+                    order.push(*name);
+                    stack.pop();
+                    continue;
+                };
+                *next += 1;
+                if done.contains(dependency) {
+                    continue;
+                }
+                if let Some(start) = stack.iter().position(|(visiting, _, _)| *visiting == dependency) {
+                    return Err(GraphyError::CyclicGraphReference {
+                        graphs: stack[start..].iter().map(|(visiting, _, _)| visiting.to_string()).collect(),
+                    });
+                }
+                stack.push((dependency, self.dependencies(dependency), 0));
+            }
+        }
+
+        Ok(order)
+    }
+}
+
+impl GraphDescription {
+    /// Returns the metadata of nodes calling this graph.
+    ///
+    /// The node type is the graph's name, its params are the graph inputs,
+    /// and it returns the graph output (a tuple if there are several, like
+    /// [`graph_function_signature`](crate::generation::graph_function_signature)).
+    /// Graphs with execution connections are called by function nodes with a
+    /// `then` output, other graphs by pure nodes.
+    pub fn call_metadata(&self) -> NodeMetadata {
+        let has_execution = self
+            .connections
+            .iter()
+            .any(|connection| connection.connection_type == ConnectionType::Execution);
+        let mut metadata = if has_execution {
+            NodeMetadata::new(&self.metadata.name, NodeTypes::fn_, GRAPH_CALL_CATEGORY)
+                .with_exec_outputs(vec!["then".to_string()])
+        } else {
+            NodeMetadata::new(&self.metadata.name, NodeTypes::pure, GRAPH_CALL_CATEGORY)
+        };
+
+        metadata = metadata.with_params(
+            self.graph_inputs
+                .iter()
+                .map(|input| ParamInfo::new(&input.name, input.type_info.to_string()))
+                .collect(),
+        );
+        match self.graph_outputs.as_slice() {
+            [] => {}
+            [single] => metadata = metadata.with_return_type(single.type_info.clone()),
+            outputs => {
+                let types: Vec<String> = outputs.iter().map(|output| output.type_info.to_string()).collect();
+                metadata = metadata.with_return_type(format!("({})", types.join(", ")));
+            }
+        }
+        if !self.metadata.description.is_empty() {
+            metadata = metadata.with_description(self.metadata.description.clone());
+        }
+        metadata
+    }
+}
@@ -0,0 +1,506 @@
+//! # Provider Combinators
+//!
+//! Building [`NodeMetadataProvider`]s out of other providers.
+//!
+//! - [`CompositeProvider`] chains several providers; the first (by priority)
+//!   that knows a node type answers for it.
+//! - [`OverlayProvider`] replaces or hides individual node types of a base
+//!   provider.
+//! - [`CachedProvider`] memoizes lookups of a provider it borrows, for
+//!   providers whose lookups are expensive (e.g. deep composites).
+//!
+//! References, `Box`es, and `Arc`s of providers are providers themselves, so
+//! combinators can own or borrow what they wrap. Combinators have a
+//! [version](NodeMetadataProvider::version) that changes whenever they or
+//! one of the providers they wrap change.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::core::{CompositeProvider, OverlayProvider};
+//! use graphy::{NodeMetadata, NodeMetadataProvider, NodeTypes};
+//!
+//! let core_nodes = OverlayProvider::new(CompositeProvider::new())
+//!     .with_override(NodeMetadata::new("add", NodeTypes::pure, "Math"));
+//! let plugin_nodes = OverlayProvider::new(CompositeProvider::new())
+//!     .with_override(NodeMetadata::new("add", NodeTypes::pure, "Plugin"))
+//!     .with_override(NodeMetadata::new("print", NodeTypes::fn_, "IO"));
+//!
+//! // The core library wins where both define a node
+//! let provider = CompositeProvider::new()
+//!     .with_provider(10, core_nodes)
+//!     .with_provider(0, plugin_nodes);
+//!
+//! assert_eq!(provider.get_node_metadata("add").unwrap().category, "Math");
+//! assert_eq!(provider.get_node_metadata("print").unwrap().category, "IO");
+//! assert_eq!(provider.get_all_nodes().len(), 2);
+//! ```
+
+use super::{NodeMetadata, NodeMetadataProvider, ProviderVersion, StableHasher};
+use rustc_hash::{FxHashMap, FxHashSet};
+use semver::{Version, VersionReq};
+use std::sync::{Arc, RwLock};
+
+impl<P: NodeMetadataProvider + ?Sized> NodeMetadataProvider for &P {
+    fn get_node_metadata(&self, node_type: &str) -> Option<&NodeMetadata> {
+        (**self).get_node_metadata(node_type)
+    }
+
+    fn get_all_nodes(&self) -> Vec<&NodeMetadata> {
+        (**self).get_all_nodes()
+    }
+
+    fn get_nodes_by_category(&self, category: &str) -> Vec<&NodeMetadata> {
+        (**self).get_nodes_by_category(category)
+    }
+
+    fn enum_variants(&self, type_name: &str) -> Option<&[String]> {
+        (**self).enum_variants(type_name)
+    }
+
+    fn version(&self) -> Option<ProviderVersion> {
+        (**self).version()
+    }
+
+    fn get_node_metadata_matching(&self, node_type: &str, requirement: &VersionReq) -> Option<&NodeMetadata> {
+        (**self).get_node_metadata_matching(node_type, requirement)
+    }
+
+    fn node_versions(&self, node_type: &str) -> Vec<Version> {
+        (**self).node_versions(node_type)
+    }
+
+    fn as_sync(&self) -> Option<&(dyn NodeMetadataProvider + Sync)> {
+        (**self).as_sync()
+    }
+}
+
+impl<P: NodeMetadataProvider + ?Sized> NodeMetadataProvider for Box<P> {
+    fn get_node_metadata(&self, node_type: &str) -> Option<&NodeMetadata> {
+        (**self).get_node_metadata(node_type)
+    }
+
+    fn get_all_nodes(&self) -> Vec<&NodeMetadata> {
+        (**self).get_all_nodes()
+    }
+
+    fn get_nodes_by_category(&self, category: &str) -> Vec<&NodeMetadata> {
+        (**self).get_nodes_by_category(category)
+    }
+
+    fn enum_variants(&self, type_name: &str) -> Option<&[String]> {
+        (**self).enum_variants(type_name)
+    }
+
+    fn version(&self) -> Option<ProviderVersion> {
+        (**self).version()
+    }
+
+    fn get_node_metadata_matching(&self, node_type: &str, requirement: &VersionReq) -> Option<&NodeMetadata> {
+        (**self).get_node_metadata_matching(node_type, requirement)
+    }
+
+    fn node_versions(&self, node_type: &str) -> Vec<Version> {
+        (**self).node_versions(node_type)
+    }
+
+    fn as_sync(&self) -> Option<&(dyn NodeMetadataProvider + Sync)> {
+        (**self).as_sync()
+    }
+}
+
+impl<P: NodeMetadataProvider + ?Sized> NodeMetadataProvider for Arc<P> {
+    fn get_node_metadata(&self, node_type: &str) -> Option<&NodeMetadata> {
+        (**self).get_node_metadata(node_type)
+    }
+
+    fn get_all_nodes(&self) -> Vec<&NodeMetadata> {
+        (**self).get_all_nodes()
+    }
+
+    fn get_nodes_by_category(&self, category: &str) -> Vec<&NodeMetadata> {
+        (**self).get_nodes_by_category(category)
+    }
+
+    fn enum_variants(&self, type_name: &str) -> Option<&[String]> {
+        (**self).enum_variants(type_name)
+    }
+
+    fn version(&self) -> Option<ProviderVersion> {
+        (**self).version()
+    }
+
+    fn get_node_metadata_matching(&self, node_type: &str, requirement: &VersionReq) -> Option<&NodeMetadata> {
+        (**self).get_node_metadata_matching(node_type, requirement)
+    }
+
+    fn node_versions(&self, node_type: &str) -> Vec<Version> {
+        (**self).node_versions(node_type)
+    }
+
+    fn as_sync(&self) -> Option<&(dyn NodeMetadataProvider + Sync)> {
+        (**self).as_sync()
+    }
+}
+
+/// Version of a combinator, from its own changes and the versions of the providers it wraps
+fn combined_version(own: ProviderVersion, parts: impl IntoIterator<Item = Option<ProviderVersion>>) -> ProviderVersion {
+    let mut hasher = StableHasher::new();
+    hasher.write_u64(own.get());
+    for part in parts {
+        match part {
+            Some(version) => {
+                hasher.write_u8(1);
+                hasher.write_u64(version.get());
+            }
+            None => hasher.write_u8(0),
+        }
+    }
+    ProviderVersion::new(hasher.finish())
+}
+
+/// Keep the first metadata for each node name
+fn dedup_by_name<'a>(nodes: impl Iterator<Item = &'a NodeMetadata>) -> Vec<&'a NodeMetadata> {
+    let mut seen = FxHashSet::default();
+    nodes.filter(|meta| seen.insert(meta.name.as_str())).collect()
+}
+
+// ============================================================================
+// CompositeProvider
+// ============================================================================
+
+/// Boxed provider that can be shared across threads
+pub type SharedProvider = Box<dyn NodeMetadataProvider + Send + Sync>;
+
+/// Chains several providers with priorities.
+///
+/// Lookups try providers from highest to lowest priority (providers with the
+/// same priority in the order they were added) and return the first match.
+/// Listings contain each node type once, as defined by the provider that
+/// wins its lookups.
+pub struct CompositeProvider {
+    /// Providers sorted by descending priority
+    providers: Vec<(i32, SharedProvider)>,
+
+    /// Replaced whenever a provider is added
+    version: ProviderVersion,
+}
+
+impl Default for CompositeProvider {
+    fn default() -> Self {
+        Self {
+            providers: Vec::new(),
+            version: ProviderVersion::fresh(),
+        }
+    }
+}
+
+impl CompositeProvider {
+    /// Creates an empty composite provider.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a provider with the given priority.
+    pub fn with_provider<P>(mut self, priority: i32, provider: P) -> Self
+    where
+        P: NodeMetadataProvider + Send + Sync + 'static,
+    {
+        self.add_provider(priority, provider);
+        self
+    }
+
+    /// Adds a provider with the given priority.
+    pub fn add_provider<P>(&mut self, priority: i32, provider: P)
+    where
+        P: NodeMetadataProvider + Send + Sync + 'static,
+    {
+        // Insert after every provider with the same or higher priority
+        let index = self.providers.partition_point(|(existing, _)| *existing >= priority);
+        self.providers.insert(index, (priority, Box::new(provider)));
+        self.version = ProviderVersion::fresh();
+    }
+
+    /// Returns the number of chained providers.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.providers.len()
+    }
+
+    /// Returns `true` if no providers are chained.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+}
+
+impl NodeMetadataProvider for CompositeProvider {
+    fn get_node_metadata(&self, node_type: &str) -> Option<&NodeMetadata> {
+        self.providers
+            .iter()
+            .find_map(|(_, provider)| provider.get_node_metadata(node_type))
+    }
+
+    fn get_all_nodes(&self) -> Vec<&NodeMetadata> {
+        dedup_by_name(self.providers.iter().flat_map(|(_, provider)| provider.get_all_nodes()))
+    }
+
+    fn get_nodes_by_category(&self, category: &str) -> Vec<&NodeMetadata> {
+        // A node shadowed by a higher priority provider may have moved category
+        self.get_all_nodes()
+            .into_iter()
+            .filter(|meta| meta.category == category)
+            .collect()
+    }
+
+    fn enum_variants(&self, type_name: &str) -> Option<&[String]> {
+        self.providers
+            .iter()
+            .find_map(|(_, provider)| provider.enum_variants(type_name))
+    }
+
+    fn version(&self) -> Option<ProviderVersion> {
+        let parts = self.providers.iter().map(|(_, provider)| provider.version());
+        Some(combined_version(self.version, parts))
+    }
+
+    fn get_node_metadata_matching(&self, node_type: &str, requirement: &VersionReq) -> Option<&NodeMetadata> {
+        self.providers
+            .iter()
+            .find_map(|(_, provider)| provider.get_node_metadata_matching(node_type, requirement))
+    }
+
+    fn node_versions(&self, node_type: &str) -> Vec<Version> {
+        let mut versions: Vec<Version> = self
+            .providers
+            .iter()
+            .flat_map(|(_, provider)| provider.node_versions(node_type))
+            .collect();
+        versions.sort_unstable();
+        versions.dedup();
+        versions
+    }
+
+    fn as_sync(&self) -> Option<&(dyn NodeMetadataProvider + Sync)> {
+        Some(self)
+    }
+}
+
+// ============================================================================
+// OverlayProvider
+// ============================================================================
+
+/// Overrides individual node types of a base provider.
+///
+/// Overrides replace (or add) node types by name; hidden node types are
+/// removed from lookups and listings.
+pub struct OverlayProvider<P> {
+    base: P,
+    overrides: FxHashMap<String, NodeMetadata>,
+    hidden: FxHashSet<String>,
+
+    /// Replaced whenever an override is set, removed, or hidden
+    version: ProviderVersion,
+}
+
+impl<P: NodeMetadataProvider> OverlayProvider<P> {
+    /// Creates an overlay without overrides.
+    pub fn new(base: P) -> Self {
+        Self {
+            base,
+            overrides: FxHashMap::default(),
+            hidden: FxHashSet::default(),
+            version: ProviderVersion::fresh(),
+        }
+    }
+
+    /// Overrides the node type named by `metadata.name`.
+    pub fn with_override(mut self, metadata: NodeMetadata) -> Self {
+        self.set_override(metadata);
+        self
+    }
+
+    /// Hides a node type of the base provider.
+    pub fn with_hidden(mut self, node_type: impl Into<String>) -> Self {
+        self.hide(node_type);
+        self
+    }
+
+    /// Overrides the node type named by `metadata.name`, un-hiding it.
+    ///
+    /// Returns the previous override, if any.
+    pub fn set_override(&mut self, metadata: NodeMetadata) -> Option<NodeMetadata> {
+        self.version = ProviderVersion::fresh();
+        self.hidden.remove(&metadata.name);
+        self.overrides.insert(metadata.name.clone(), metadata)
+    }
+
+    /// Removes an override, restoring the base provider's node type.
+    pub fn remove_override(&mut self, node_type: &str) -> Option<NodeMetadata> {
+        self.version = ProviderVersion::fresh();
+        self.overrides.remove(node_type)
+    }
+
+    /// Hides a node type, dropping any override for it.
+    pub fn hide(&mut self, node_type: impl Into<String>) {
+        let node_type = node_type.into();
+        self.version = ProviderVersion::fresh();
+        self.overrides.remove(&node_type);
+        self.hidden.insert(node_type);
+    }
+
+    /// Returns the base provider.
+    #[inline]
+    pub fn base(&self) -> &P {
+        &self.base
+    }
+}
+
+impl<P: NodeMetadataProvider> NodeMetadataProvider for OverlayProvider<P> {
+    fn get_node_metadata(&self, node_type: &str) -> Option<&NodeMetadata> {
+        if let Some(metadata) = self.overrides.get(node_type) {
+            return Some(metadata);
+        }
+        if self.hidden.contains(node_type) {
+            return None;
+        }
+        self.base.get_node_metadata(node_type)
+    }
+
+    fn get_all_nodes(&self) -> Vec<&NodeMetadata> {
+        let base = self
+            .base
+            .get_all_nodes()
+            .into_iter()
+            .filter(|meta| !self.hidden.contains(&meta.name));
+        dedup_by_name(self.overrides.values().chain(base))
+    }
+
+    fn get_nodes_by_category(&self, category: &str) -> Vec<&NodeMetadata> {
+        self.get_all_nodes()
+            .into_iter()
+            .filter(|meta| meta.category == category)
+            .collect()
+    }
+
+    fn enum_variants(&self, type_name: &str) -> Option<&[String]> {
+        self.base.enum_variants(type_name)
+    }
+
+    fn version(&self) -> Option<ProviderVersion> {
+        Some(combined_version(self.version, [self.base.version()]))
+    }
+
+    fn get_node_metadata_matching(&self, node_type: &str, requirement: &VersionReq) -> Option<&NodeMetadata> {
+        if let Some(metadata) = self.overrides.get(node_type) {
+            return metadata
+                .version
+                .as_ref()
+                .is_some_and(|version| requirement.matches(version))
+                .then_some(metadata);
+        }
+        if self.hidden.contains(node_type) {
+            return None;
+        }
+        self.base.get_node_metadata_matching(node_type, requirement)
+    }
+
+    fn node_versions(&self, node_type: &str) -> Vec<Version> {
+        if let Some(metadata) = self.overrides.get(node_type) {
+            return metadata.version.clone().into_iter().collect();
+        }
+        if self.hidden.contains(node_type) {
+            return Vec::new();
+        }
+        self.base.node_versions(node_type)
+    }
+}
+
+// ============================================================================
+// CachedProvider
+// ============================================================================
+
+/// Memoizes lookups of a borrowed provider.
+///
+/// Results (including misses) are cached per node type and category, so
+/// repeated lookups cost one hash map access. The cache is behind a lock and
+/// can be shared by parallel analysis.
+///
+/// The wrapped provider must not change while the cache is alive, which the
+/// shared borrow guarantees for providers without interior mutability.
+pub struct CachedProvider<'p, P: ?Sized> {
+    inner: &'p P,
+    nodes: RwLock<FxHashMap<String, Option<&'p NodeMetadata>>>,
+    categories: RwLock<FxHashMap<String, Vec<&'p NodeMetadata>>>,
+}
+
+impl<'p, P: NodeMetadataProvider + ?Sized> CachedProvider<'p, P> {
+    /// Wraps a provider with an empty cache.
+    pub fn new(inner: &'p P) -> Self {
+        Self {
+            inner,
+            nodes: RwLock::new(FxHashMap::default()),
+            categories: RwLock::new(FxHashMap::default()),
+        }
+    }
+
+    /// Returns the wrapped provider.
+    #[inline]
+    pub fn inner(&self) -> &'p P {
+        self.inner
+    }
+
+    /// Drops all cached lookups.
+    pub fn clear(&self) {
+        self.nodes.write().unwrap_or_else(|e| e.into_inner()).clear();
+        self.categories.write().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+impl<P: NodeMetadataProvider + ?Sized> NodeMetadataProvider for CachedProvider<'_, P> {
+    fn get_node_metadata(&self, node_type: &str) -> Option<&NodeMetadata> {
+        if let Some(cached) = self.nodes.read().unwrap_or_else(|e| e.into_inner()).get(node_type) {
+            return *cached;
+        }
+
+        let metadata = self.inner.get_node_metadata(node_type);
+        self.nodes
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(node_type.to_string(), metadata);
+        metadata
+    }
+
+    fn get_all_nodes(&self) -> Vec<&NodeMetadata> {
+        self.inner.get_all_nodes()
+    }
+
+    fn get_nodes_by_category(&self, category: &str) -> Vec<&NodeMetadata> {
+        if let Some(cached) = self.categories.read().unwrap_or_else(|e| e.into_inner()).get(category) {
+            return cached.clone();
+        }
+
+        let nodes = self.inner.get_nodes_by_category(category);
+        self.categories
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(category.to_string(), nodes.clone());
+        nodes
+    }
+
+    fn enum_variants(&self, type_name: &str) -> Option<&[String]> {
+        self.inner.enum_variants(type_name)
+    }
+
+    fn version(&self) -> Option<ProviderVersion> {
+        self.inner.version()
+    }
+
+    fn get_node_metadata_matching(&self, node_type: &str, requirement: &VersionReq) -> Option<&NodeMetadata> {
+        self.inner.get_node_metadata_matching(node_type, requirement)
+    }
+
+    fn node_versions(&self, node_type: &str) -> Vec<Version> {
+        self.inner.node_versions(node_type)
+    }
+}
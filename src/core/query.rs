@@ -0,0 +1,93 @@
+//! # Graph Queries
+//!
+//! Lookups over nodes and connections for tools that inspect graphs, such
+//! as lint rules. For structural patterns spanning several nodes, see
+//! [`Pattern`](super::Pattern).
+//!
+//! Queries return nodes sorted by ID and connections in graph order, so
+//! their results are deterministic.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::{Connection, GraphDescription, NodeInstance, Position};
+//!
+//! let mut graph = GraphDescription::new("graph");
+//! graph.add_node(NodeInstance::new("add_1", "math.add", Position::zero()));
+//! graph.add_node(NodeInstance::new("print_1", "print", Position::zero()));
+//! graph.add_connection(Connection::data("add_1", "result", "print_1", "value"));
+//!
+//! let adds = graph.find_nodes(|node| node.node_type == "math.add");
+//! assert_eq!(adds[0].id, "add_1");
+//! assert_eq!(graph.connections_from("add_1").count(), 1);
+//! assert_eq!(graph.connections_to("print_1", "value").next().unwrap().source_node, "add_1");
+//! ```
+
+use super::{Connection, GraphDescription, NodeInstance, NodeMetadataProvider};
+
+impl GraphDescription {
+    /// Returns the nodes matching a predicate, sorted by ID.
+    pub fn find_nodes(&self, predicate: impl Fn(&NodeInstance) -> bool) -> Vec<&NodeInstance> {
+        let mut nodes: Vec<&NodeInstance> =
+            self.nodes.values().filter(|node| predicate(node)).collect();
+        nodes.sort_unstable_by(|a, b| a.id.cmp(&b.id));
+        nodes
+    }
+
+    /// Returns the nodes of a type, sorted by ID.
+    pub fn nodes_of_type(&self, node_type: &str) -> Vec<&NodeInstance> {
+        self.find_nodes(|node| node.node_type == node_type)
+    }
+
+    /// Returns the nodes the provider has no metadata for, sorted by ID.
+    ///
+    /// Builtin nodes ([`NodeInstance::is_builtin`]) need no metadata and are
+    /// never returned. Nodes with a version requirement count as unknown if
+    /// no version satisfies it.
+    pub fn unknown_nodes<P: NodeMetadataProvider + ?Sized>(&self, provider: &P) -> Vec<&NodeInstance> {
+        self.find_nodes(|node| !node.is_builtin() && provider.metadata_for_node(node).is_none())
+    }
+
+    /// Returns the connections leaving a node, from any of its outputs.
+    pub fn connections_from<'a>(
+        &'a self,
+        node_id: &'a str,
+    ) -> impl Iterator<Item = &'a Connection> + 'a {
+        self.connections
+            .iter()
+            .filter(move |c| c.source_node == node_id)
+    }
+
+    /// Returns the connections leaving one output pin.
+    pub fn connections_from_pin<'a>(
+        &'a self,
+        node_id: &'a str,
+        pin_id: &'a str,
+    ) -> impl Iterator<Item = &'a Connection> + 'a {
+        self.connections_from(node_id)
+            .filter(move |c| c.source_pin == pin_id)
+    }
+
+    /// Returns the connections entering a node, into any of its inputs.
+    pub fn connections_to_node<'a>(
+        &'a self,
+        node_id: &'a str,
+    ) -> impl Iterator<Item = &'a Connection> + 'a {
+        self.connections
+            .iter()
+            .filter(move |c| c.target_node == node_id)
+    }
+
+    /// Returns the connections entering one input pin.
+    ///
+    /// Data inputs have at most one source in valid graphs; execution
+    /// inputs can have several.
+    pub fn connections_to<'a>(
+        &'a self,
+        node_id: &'a str,
+        pin_id: &'a str,
+    ) -> impl Iterator<Item = &'a Connection> + 'a {
+        self.connections_to_node(node_id)
+            .filter(move |c| c.target_pin == pin_id)
+    }
+}
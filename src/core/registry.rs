@@ -0,0 +1,454 @@
+//! # Node Registry
+//!
+//! A ready-made [`NodeMetadataProvider`] backed by a hash map.
+//!
+//! Node types are registered at runtime, usually under their metadata name,
+//! or under a separate node type string with
+//! [`register_as`](NodeRegistry::register_as) (e.g. `"math.add"` for a node
+//! named `add`). A category index keeps category listings cheap.
+//!
+//! Several versions of a node type can be registered side by side with
+//! [`register_versioned`](NodeRegistry::register_versioned). Plain lookups
+//! return the newest; nodes with a version requirement get the newest
+//! version satisfying it.
+//!
+//! Registries can be changed while the application runs (e.g. when a plugin
+//! is hot-reloaded). Every change moves the registry to a new
+//! [version](NodeMetadataProvider::version) and is reported to the
+//! [listeners](NodeRegistry::add_listener), so analysis results and caches
+//! built from older metadata can be rebuilt.
+//!
+//! Registries serialize to a JSON object mapping node types to metadata, so a
+//! node library can be saved and loaded. Enums registered for switch nodes
+//! with [`register_enum`](NodeRegistry::register_enum) aren't part of the
+//! JSON.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::core::NodeRegistry;
+//! use graphy::{NodeMetadata, NodeMetadataProvider, NodeTypes, ParamInfo};
+//!
+//! let mut registry = NodeRegistry::new();
+//! registry.register(
+//!     NodeMetadata::new("add", NodeTypes::pure, "Math")
+//!         .with_params(vec![ParamInfo::new("a", "f64"), ParamInfo::new("b", "f64")])
+//!         .with_return_type("f64"),
+//! );
+//! registry.register_as("io.print", NodeMetadata::new("print", NodeTypes::fn_, "IO"));
+//!
+//! assert!(registry.get_node_metadata("add").is_some());
+//! assert_eq!(registry.get_node_metadata("io.print").unwrap().name, "print");
+//! assert_eq!(registry.categories(), vec!["IO", "Math"]);
+//!
+//! let json = registry.to_json().unwrap();
+//! let loaded = NodeRegistry::from_json(&json).unwrap();
+//! assert_eq!(loaded.len(), 2);
+//! ```
+
+use super::{NodeMetadata, NodeMetadataProvider, ProviderVersion};
+use crate::GraphyError;
+use rustc_hash::FxHashMap;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Arc;
+
+/// A change to the metadata of a [`NodeRegistry`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetadataChange {
+    /// A node type was registered, possibly replacing its old metadata
+    Registered(String),
+
+    /// A node type was removed
+    Unregistered(String),
+
+    /// The variants of an enum type were registered
+    EnumRegistered(String),
+}
+
+impl MetadataChange {
+    /// Returns the node type or enum type that changed.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Registered(name) | Self::Unregistered(name) | Self::EnumRegistered(name) => name,
+        }
+    }
+}
+
+/// Gets notified after a [`NodeRegistry`] changes.
+///
+/// Closures taking the change and the registry's new version are listeners.
+pub trait MetadataListener: Send + Sync {
+    /// The registry changed and is now at `version`.
+    fn metadata_changed(&self, change: &MetadataChange, version: ProviderVersion);
+}
+
+impl<F> MetadataListener for F
+where
+    F: Fn(&MetadataChange, ProviderVersion) + Send + Sync,
+{
+    #[inline]
+    fn metadata_changed(&self, change: &MetadataChange, version: ProviderVersion) {
+        self(change, version)
+    }
+}
+
+impl fmt::Debug for dyn MetadataListener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MetadataListener")
+    }
+}
+
+/// Runtime registry of node types.
+#[derive(Debug, Default)]
+pub struct NodeRegistry {
+    /// Maps node type -> metadata
+    nodes: FxHashMap<String, NodeMetadata>,
+
+    /// Maps category -> node types in that category (sorted)
+    categories: FxHashMap<String, Vec<String>>,
+
+    /// Maps enum type -> variants, for switch nodes
+    enums: FxHashMap<String, Vec<String>>,
+
+    /// Maps unversioned node type -> (version, registered node type) of its
+    /// versioned metadata, sorted
+    versions: FxHashMap<String, Vec<(Version, String)>>,
+
+    /// Advanced by every change
+    version: ProviderVersion,
+
+    /// Notified after every change
+    listeners: Vec<Arc<dyn MetadataListener>>,
+}
+
+impl Clone for NodeRegistry {
+    /// Clones the node types and enums; the clone has no listeners.
+    fn clone(&self) -> Self {
+        Self {
+            nodes: self.nodes.clone(),
+            categories: self.categories.clone(),
+            enums: self.enums.clone(),
+            versions: self.versions.clone(),
+            version: self.version,
+            listeners: Vec::new(),
+        }
+    }
+}
+
+impl NodeRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a node type under its metadata name.
+    ///
+    /// Returns the metadata previously registered under that name, if any.
+    pub fn register(&mut self, metadata: NodeMetadata) -> Option<NodeMetadata> {
+        self.register_as(metadata.name.clone(), metadata)
+    }
+
+    /// Registers metadata under an explicit node type.
+    ///
+    /// Returns the metadata previously registered under that node type, if any.
+    pub fn register_as(&mut self, node_type: impl Into<String>, metadata: NodeMetadata) -> Option<NodeMetadata> {
+        let node_type = node_type.into();
+        let previous = self.remove(&node_type);
+
+        let members = self.categories.entry(metadata.category.clone()).or_default();
+        if let Err(index) = members.binary_search(&node_type) {
+            members.insert(index, node_type.clone());
+        }
+        if let Some(version) = &metadata.version {
+            let entry = (version.clone(), node_type.clone());
+            let versions = self.versions.entry(unversioned(&node_type).to_string()).or_default();
+            if let Err(index) = versions.binary_search(&entry) {
+                versions.insert(index, entry);
+            }
+        }
+        self.nodes.insert(node_type.clone(), metadata);
+        self.changed(MetadataChange::Registered(node_type));
+
+        previous
+    }
+
+    /// Registers versioned metadata alongside its other versions.
+    ///
+    /// The metadata is registered as `node_type@version` (e.g.
+    /// `math.add@2.0.0`), so several versions of a node type can be
+    /// registered at once. Nodes of type `node_type` get the newest version
+    /// satisfying their [`version_req`](crate::NodeInstance::version_req), or
+    /// the newest version if they have none. Unversioned metadata is
+    /// registered as `node_type`.
+    ///
+    /// Returns the metadata previously registered for that version, if any.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::core::{NodeRegistry, Version, VersionReq};
+    /// use graphy::{NodeMetadata, NodeMetadataProvider, NodeTypes};
+    ///
+    /// let mut registry = NodeRegistry::new();
+    /// for version in [Version::new(1, 4, 0), Version::new(2, 0, 0)] {
+    ///     registry.register_versioned("math.add", NodeMetadata::new("add", NodeTypes::pure, "Math").with_version(version));
+    /// }
+    ///
+    /// let v1 = registry.get_node_metadata_matching("math.add", &VersionReq::parse("^1.2").unwrap());
+    /// assert_eq!(v1.unwrap().version, Some(Version::new(1, 4, 0)));
+    /// assert_eq!(registry.get_node_metadata("math.add").unwrap().version, Some(Version::new(2, 0, 0)));
+    /// ```
+    pub fn register_versioned(&mut self, node_type: &str, metadata: NodeMetadata) -> Option<NodeMetadata> {
+        match &metadata.version {
+            Some(version) => self.register_as(format!("{}@{}", node_type, version), metadata),
+            None => self.register_as(node_type, metadata),
+        }
+    }
+
+    /// Removes a node type, returning its metadata.
+    pub fn unregister(&mut self, node_type: &str) -> Option<NodeMetadata> {
+        let metadata = self.remove(node_type)?;
+        self.changed(MetadataChange::Unregistered(node_type.to_string()));
+        Some(metadata)
+    }
+
+    /// Removes a node type without reporting the change
+    fn remove(&mut self, node_type: &str) -> Option<NodeMetadata> {
+        let metadata = self.nodes.remove(node_type)?;
+
+        if let Some(members) = self.categories.get_mut(&metadata.category) {
+            if let Ok(index) = members.binary_search_by(|member| member.as_str().cmp(node_type)) {
+                members.remove(index);
+            }
+            if members.is_empty() {
+                self.categories.remove(&metadata.category);
+            }
+        }
+        if let Some(versions) = self.versions.get_mut(unversioned(node_type)) {
+            versions.retain(|(_, registered)| registered != node_type);
+            if versions.is_empty() {
+                self.versions.remove(unversioned(node_type));
+            }
+        }
+
+        Some(metadata)
+    }
+
+    /// Registers the variants of an enum type, for switch nodes.
+    ///
+    /// Returns the variants previously registered for that type, if any.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::core::NodeRegistry;
+    /// use graphy::NodeMetadataProvider;
+    ///
+    /// let mut registry = NodeRegistry::new();
+    /// registry.register_enum("Direction", ["North", "South"]);
+    /// assert_eq!(registry.enum_variants("Direction").unwrap(), ["North", "South"]);
+    /// ```
+    pub fn register_enum<I, S>(&mut self, type_name: impl Into<String>, variants: I) -> Option<Vec<String>>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let type_name = type_name.into();
+        let previous = self
+            .enums
+            .insert(type_name.clone(), variants.into_iter().map(Into::into).collect());
+        self.changed(MetadataChange::EnumRegistered(type_name));
+        previous
+    }
+
+    /// Adds a listener notified after every change.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::core::{MetadataChange, NodeRegistry};
+    /// use graphy::{NodeMetadata, NodeMetadataProvider, NodeTypes, ProviderVersion};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let changes = Arc::new(Mutex::new(Vec::new()));
+    /// let mut registry = NodeRegistry::new();
+    /// let seen = Arc::clone(&changes);
+    /// registry.add_listener(move |change: &MetadataChange, version: ProviderVersion| {
+    ///     seen.lock().unwrap().push((change.clone(), version));
+    /// });
+    ///
+    /// registry.register(NodeMetadata::new("add", NodeTypes::pure, "Math"));
+    /// let changes = changes.lock().unwrap();
+    /// assert_eq!(changes[0].0, MetadataChange::Registered("add".into()));
+    /// assert_eq!(Some(changes[0].1), registry.version());
+    /// ```
+    pub fn add_listener(&mut self, listener: impl MetadataListener + 'static) {
+        self.listeners.push(Arc::new(listener));
+    }
+
+    /// Removes all listeners.
+    pub fn clear_listeners(&mut self) {
+        self.listeners.clear();
+    }
+
+    /// Advance the version and notify the listeners
+    fn changed(&mut self, change: MetadataChange) {
+        self.version = ProviderVersion::fresh();
+        tracing::debug!("[REGISTRY] {:?}, now at {}", change, self.version);
+        for listener in &self.listeners {
+            listener.metadata_changed(&change, self.version);
+        }
+    }
+
+    /// Checks if a node type is registered.
+    #[inline]
+    pub fn contains(&self, node_type: &str) -> bool {
+        self.nodes.contains_key(node_type)
+    }
+
+    /// Returns the number of registered node types.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if no node types are registered.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns all categories, sorted.
+    pub fn categories(&self) -> Vec<&str> {
+        let mut categories: Vec<&str> = self.categories.keys().map(String::as_str).collect();
+        categories.sort_unstable();
+        categories
+    }
+
+    /// Returns the node types in a category, sorted.
+    pub fn node_types_in_category(&self, category: &str) -> &[String] {
+        self.categories.get(category).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Iterates over `(node_type, metadata)` pairs in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &NodeMetadata)> {
+        self.nodes.iter().map(|(node_type, metadata)| (node_type.as_str(), metadata))
+    }
+
+    /// Parses a registry from JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::Serialization`] if the JSON is invalid.
+    pub fn from_json(json: &str) -> Result<Self, GraphyError> {
+        serde_json::from_str(json).map_err(|e| GraphyError::Serialization(e.to_string()))
+    }
+
+    /// Serializes the registry as pretty-printed JSON, sorted by node type.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::Serialization`] if serialization fails.
+    pub fn to_json(&self) -> Result<String, GraphyError> {
+        serde_json::to_string_pretty(self).map_err(|e| GraphyError::Serialization(e.to_string()))
+    }
+}
+
+impl NodeMetadataProvider for NodeRegistry {
+    #[inline]
+    fn get_node_metadata(&self, node_type: &str) -> Option<&NodeMetadata> {
+        self.nodes.get(node_type).or_else(|| {
+            let (_, newest) = self.versions.get(node_type)?.last()?;
+            self.nodes.get(newest)
+        })
+    }
+
+    fn get_all_nodes(&self) -> Vec<&NodeMetadata> {
+        self.nodes.values().collect()
+    }
+
+    fn get_nodes_by_category(&self, category: &str) -> Vec<&NodeMetadata> {
+        self.node_types_in_category(category)
+            .iter()
+            .map(|node_type| &self.nodes[node_type])
+            .collect()
+    }
+
+    fn enum_variants(&self, type_name: &str) -> Option<&[String]> {
+        self.enums.get(type_name).map(Vec::as_slice)
+    }
+
+    #[inline]
+    fn version(&self) -> Option<ProviderVersion> {
+        Some(self.version)
+    }
+
+    fn get_node_metadata_matching(&self, node_type: &str, requirement: &VersionReq) -> Option<&NodeMetadata> {
+        let (_, registered) = self
+            .versions
+            .get(node_type)?
+            .iter()
+            .rev()
+            .find(|(version, _)| requirement.matches(version))?;
+        self.nodes.get(registered)
+    }
+
+    fn node_versions(&self, node_type: &str) -> Vec<Version> {
+        let mut versions: Vec<Version> = self
+            .versions
+            .get(node_type)
+            .into_iter()
+            .flatten()
+            .map(|(version, _)| version.clone())
+            .collect();
+        versions.dedup();
+        versions
+    }
+
+    fn as_sync(&self) -> Option<&(dyn NodeMetadataProvider + Sync)> {
+        Some(self)
+    }
+}
+
+/// `math.add@2.0.0` -> `math.add`
+fn unversioned(node_type: &str) -> &str {
+    node_type.split_once('@').map_or(node_type, |(base, _)| base)
+}
+
+impl FromIterator<NodeMetadata> for NodeRegistry {
+    fn from_iter<I: IntoIterator<Item = NodeMetadata>>(iter: I) -> Self {
+        let mut registry = Self::new();
+        registry.extend(iter);
+        registry
+    }
+}
+
+impl Extend<NodeMetadata> for NodeRegistry {
+    fn extend<I: IntoIterator<Item = NodeMetadata>>(&mut self, iter: I) {
+        for metadata in iter {
+            self.register(metadata);
+        }
+    }
+}
+
+impl Serialize for NodeRegistry {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Sorted so saved libraries diff cleanly
+        let sorted: BTreeMap<&String, &NodeMetadata> = self.nodes.iter().collect();
+        sorted.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for NodeRegistry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let nodes = HashMap::<String, NodeMetadata>::deserialize(deserializer)?;
+        let mut registry = Self::new();
+        for (node_type, metadata) in nodes {
+            registry.register_as(node_type, metadata);
+        }
+        Ok(registry)
+    }
+}
@@ -0,0 +1,455 @@
+//! # Semantic Equality
+//!
+//! Compares graphs by the program they describe rather than by their
+//! editor state.
+//!
+//! Two graphs are semantically equal when they have the same nodes (type,
+//! pins, and properties), the same set of connections, the same graph
+//! inputs and outputs, and the same variables and externals. Everything that only affects
+//! the editor is ignored:
+//!
+//! - node positions
+//! - comments and groups (apart from group build conditions)
+//! - connection order (and duplicate connections)
+//! - connection IDs, waypoints, labels, and colors
+//! - graph metadata (name, description, version, timestamps)
+//! - the format version
+//!
+//! [`GraphDescription::content_hash`] hashes the same canonical content, so
+//! semantically equal graphs always have equal hashes. The hash is stable
+//! across runs, platforms, and Graphy versions with the same graph format,
+//! which makes it usable as a build cache key.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::{Connection, GraphDescription, NodeInstance, Position};
+//!
+//! let mut graph = GraphDescription::new("graph");
+//! graph.add_node(NodeInstance::new("add_1", "math.add", Position::zero()));
+//! graph.add_node(NodeInstance::new("print_1", "print", Position::zero()));
+//! graph.add_connection(Connection::data("add_1", "result", "print_1", "value"));
+//!
+//! // Moving a node is an editor change, not a program change
+//! let mut moved = graph.clone();
+//! moved.get_node_mut("add_1").unwrap().position = Position::new(300.0, 40.0);
+//!
+//! assert!(graph.semantically_equals(&moved));
+//! assert_eq!(graph, moved);
+//! assert_eq!(graph.content_hash(), moved.content_hash());
+//! ```
+
+use super::{
+    BuildCondition, Connection, ConnectionType, DataType, ExternalDecl, GraphDescription, GraphParam, NodeInstance, Pin, PinInstance,
+    PinMultiplicity, PinType, PropertyValue, VariableDecl,
+};
+use std::collections::HashMap;
+
+impl GraphDescription {
+    /// Checks if two graphs describe the same program.
+    ///
+    /// Compares nodes (type, pins, properties, build condition, and debug
+    /// flags), the set
+    /// of connections, graph inputs and outputs, variables, externals, and
+    /// group build conditions. Ignores node positions, comments, the rest of
+    /// groups, connection order, duplicates, and editor metadata, graph
+    /// metadata, and the format version. `==` on graphs uses this comparison.
+    pub fn semantically_equals(&self, other: &GraphDescription) -> bool {
+        self.nodes.len() == other.nodes.len()
+            && self.nodes.iter().all(|(id, node)| {
+                other
+                    .nodes
+                    .get(id)
+                    .is_some_and(|other_node| node.semantically_equals(other_node))
+            })
+            && same_connections(&self.connections, &other.connections)
+            && self.graph_inputs == other.graph_inputs
+            && self.graph_outputs == other.graph_outputs
+            && self.variables == other.variables
+            && self.externals == other.externals
+            && group_conditions(self) == group_conditions(other)
+    }
+
+    /// Returns a stable hash of the graph's semantic content.
+    ///
+    /// Semantically equal graphs (see
+    /// [`semantically_equals`](Self::semantically_equals)) have equal
+    /// hashes. The hash doesn't depend on hash map iteration order, the
+    /// platform, or the Rust version, so it can be persisted as a cache key.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::{GraphDescription, NodeInstance, Position, PropertyValue};
+    ///
+    /// let mut graph = GraphDescription::new("graph");
+    /// graph.add_node(NodeInstance::new("add_1", "math.add", Position::zero()));
+    /// let before = graph.content_hash();
+    ///
+    /// graph.get_node_mut("add_1").unwrap().set_property("a", PropertyValue::Integer(2));
+    /// assert_ne!(graph.content_hash(), before);
+    /// ```
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = StableHasher::new();
+
+        let mut nodes: Vec<&NodeInstance> = self.nodes.values().collect();
+        nodes.sort_unstable_by(|a, b| a.id.cmp(&b.id));
+        hasher.write_len(nodes.len());
+        for node in nodes {
+            hash_node(&mut hasher, node);
+        }
+
+        let connections = canonical_connections(&self.connections);
+        hasher.write_len(connections.len());
+        for connection in connections {
+            hash_connection(&mut hasher, connection);
+        }
+
+        for params in [&self.graph_inputs, &self.graph_outputs] {
+            hasher.write_len(params.len());
+            for param in params {
+                hash_param(&mut hasher, param);
+            }
+        }
+
+        let mut variables: Vec<(&String, &VariableDecl)> = self.variables.iter().collect();
+        variables.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        hasher.write_len(variables.len());
+        for (name, decl) in variables {
+            hasher.write_str(name);
+            hasher.write_str(&decl.type_info.type_string);
+            match &decl.default {
+                Some(value) => {
+                    hasher.write_u8(1);
+                    hash_property(&mut hasher, value);
+                }
+                None => hasher.write_u8(0),
+            }
+        }
+
+        // Like externals, only hashed if some group has a condition
+        let groups = group_conditions(self);
+        if !groups.is_empty() {
+            hasher.write_len(groups.len());
+            for (id, condition) in groups {
+                hasher.write_str(id);
+                hasher.write_str(&condition.to_string());
+            }
+        }
+
+        // Only graphs declaring externals hash them, so older hashes stay valid
+        if !self.externals.is_empty() {
+            hasher.write_len(self.externals.len());
+            for external in &self.externals {
+                hash_external(&mut hasher, external);
+            }
+        }
+
+        hasher.finish()
+    }
+}
+
+/// Compares graphs semantically, see [`GraphDescription::semantically_equals`].
+impl PartialEq for GraphDescription {
+    fn eq(&self, other: &Self) -> bool {
+        self.semantically_equals(other)
+    }
+}
+
+impl NodeInstance {
+    /// Checks if two nodes are the same apart from their position.
+    ///
+    /// Unlike `==`, which compares every field, this is the node comparison
+    /// used by [`GraphDescription::semantically_equals`].
+    pub fn semantically_equals(&self, other: &NodeInstance) -> bool {
+        self.id == other.id
+            && self.node_type == other.node_type
+            && self.version_req == other.version_req
+            && self.variadic_count == other.variadic_count
+            && self.condition == other.condition
+            && self.breakpoint == other.breakpoint
+            && self.trace == other.trace
+            && self.watched_pins == other.watched_pins
+            && self.inputs == other.inputs
+            && self.outputs == other.outputs
+            && self.properties == other.properties
+    }
+}
+
+/// Conditions of the groups that have one, by group ID
+///
+/// Groups are editor state, except for their conditions, which decide what
+/// gets compiled.
+fn group_conditions(graph: &GraphDescription) -> Vec<(&str, &BuildCondition)> {
+    let mut conditions: Vec<(&str, &BuildCondition)> = graph
+        .groups
+        .iter()
+        .filter_map(|group| Some((group.id.as_str(), group.condition.as_ref()?)))
+        .collect();
+    conditions.sort_unstable_by_key(|(id, _)| *id);
+    conditions
+}
+
+/// Connections sorted and deduplicated
+fn canonical_connections(connections: &[Connection]) -> Vec<&Connection> {
+    let mut sorted: Vec<&Connection> = connections.iter().collect();
+    sorted.sort_unstable_by(|a, b| {
+        (&a.source_node, &a.source_pin, &a.target_node, &a.target_pin, connection_tag(a.connection_type)).cmp(&(
+            &b.source_node,
+            &b.source_pin,
+            &b.target_node,
+            &b.target_pin,
+            connection_tag(b.connection_type),
+        ))
+    });
+    sorted.dedup_by(|a, b| a.same_endpoints(b));
+    sorted
+}
+
+/// Compares connection sets by endpoints only
+fn same_connections(a: &[Connection], b: &[Connection]) -> bool {
+    let (a, b) = (canonical_connections(a), canonical_connections(b));
+    a.len() == b.len() && a.iter().zip(&b).all(|(a, b)| a.same_endpoints(b))
+}
+
+fn connection_tag(connection_type: ConnectionType) -> u8 {
+    match connection_type {
+        ConnectionType::Data => 0,
+        ConnectionType::Execution => 1,
+    }
+}
+
+fn hash_node(hasher: &mut StableHasher, node: &NodeInstance) {
+    hasher.write_str(&node.id);
+    hasher.write_str(&node.node_type);
+    for pins in [&node.inputs, &node.outputs] {
+        hasher.write_len(pins.len());
+        for pin in pins {
+            hash_pin(hasher, pin);
+        }
+    }
+    hash_properties(hasher, &node.properties);
+    // Only when set, so hashes of unversioned nodes stay as they were
+    if let Some(requirement) = &node.version_req {
+        hasher.write_str(&requirement.to_string());
+    }
+    // Likewise for nodes without variadic inputs
+    if node.variadic_count > 0 {
+        hasher.write_u8(b'v');
+        hasher.write_len(node.variadic_count);
+    }
+    // And for unconditional nodes
+    if let Some(condition) = &node.condition {
+        hasher.write_u8(b'c');
+        hasher.write_str(&condition.to_string());
+    }
+    // And for nodes without debug flags, which change debug builds
+    if node.breakpoint || node.trace {
+        hasher.write_u8(b'd');
+        hasher.write_u8(node.breakpoint as u8 | (node.trace as u8) << 1);
+    }
+    // And for nodes without watched pins, which change builds with probes
+    if !node.watched_pins.is_empty() {
+        hasher.write_u8(b'w');
+        hasher.write_len(node.watched_pins.len());
+        for pin in &node.watched_pins {
+            hasher.write_str(pin);
+        }
+    }
+}
+
+fn hash_pin(hasher: &mut StableHasher, pin: &PinInstance) {
+    let PinInstance {
+        id,
+        pin: Pin {
+            id: pin_id,
+            name,
+            data_type,
+            pin_type,
+            multiplicity,
+        },
+    } = pin;
+    hasher.write_str(id);
+    hasher.write_str(pin_id);
+    hasher.write_str(name);
+    hash_data_type(hasher, data_type);
+    hasher.write_u8(match pin_type {
+        PinType::Input => 0,
+        PinType::Output => 1,
+    });
+    // Only when set, so hashes of single-connection pins stay as they were
+    match multiplicity {
+        PinMultiplicity::Single => {}
+        PinMultiplicity::Multi => hasher.write_u8(1),
+        PinMultiplicity::AtMost(max) => {
+            hasher.write_u8(2);
+            hasher.write_len(*max);
+        }
+    }
+}
+
+fn hash_data_type(hasher: &mut StableHasher, data_type: &DataType) {
+    let tag = match data_type {
+        DataType::Execution => 0,
+        DataType::Typed(type_info) => {
+            hasher.write_u8(1);
+            hasher.write_str(&type_info.type_string);
+            return;
+        }
+        DataType::Number => 2,
+        DataType::String => 3,
+        DataType::Boolean => 4,
+        DataType::Vector2 => 5,
+        DataType::Vector3 => 6,
+        DataType::Color => 7,
+        DataType::Any => 8,
+    };
+    hasher.write_u8(tag);
+}
+
+fn hash_connection(hasher: &mut StableHasher, connection: &Connection) {
+    hasher.write_str(&connection.source_node);
+    hasher.write_str(&connection.source_pin);
+    hasher.write_str(&connection.target_node);
+    hasher.write_str(&connection.target_pin);
+    hasher.write_u8(connection_tag(connection.connection_type));
+}
+
+fn hash_param(hasher: &mut StableHasher, param: &GraphParam) {
+    hasher.write_str(&param.name);
+    hasher.write_str(&param.type_info.type_string);
+}
+
+fn hash_external(hasher: &mut StableHasher, external: &ExternalDecl) {
+    hasher.write_str(&external.name);
+    hasher.write_str(&external.module);
+    hasher.write_str(&external.signature());
+}
+
+fn hash_properties(hasher: &mut StableHasher, properties: &HashMap<String, PropertyValue>) {
+    let mut entries: Vec<(&String, &PropertyValue)> = properties.iter().collect();
+    entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+    hasher.write_len(entries.len());
+    for (key, value) in entries {
+        hasher.write_str(key);
+        hash_property(hasher, value);
+    }
+}
+
+fn hash_property(hasher: &mut StableHasher, value: &PropertyValue) {
+    match value {
+        PropertyValue::String(text) => {
+            hasher.write_u8(0);
+            hasher.write_str(text);
+        }
+        PropertyValue::Number(number) => {
+            hasher.write_u8(1);
+            hasher.write_f64(*number);
+        }
+        PropertyValue::Boolean(flag) => {
+            hasher.write_u8(2);
+            hasher.write_u8(*flag as u8);
+        }
+        PropertyValue::Vector2(x, y) => {
+            hasher.write_u8(3);
+            for component in [x, y] {
+                hasher.write_f64(*component);
+            }
+        }
+        PropertyValue::Vector3(x, y, z) => {
+            hasher.write_u8(4);
+            for component in [x, y, z] {
+                hasher.write_f64(*component);
+            }
+        }
+        PropertyValue::Color(r, g, b, a) => {
+            hasher.write_u8(5);
+            for component in [r, g, b, a] {
+                hasher.write_f64(*component);
+            }
+        }
+        PropertyValue::Integer(integer) => {
+            hasher.write_u8(6);
+            hasher.write_u64(*integer as u64);
+        }
+        PropertyValue::Array(values) => {
+            hasher.write_u8(7);
+            hasher.write_len(values.len());
+            for value in values {
+                hash_property(hasher, value);
+            }
+        }
+        PropertyValue::Map(values) => {
+            hasher.write_u8(8);
+            hash_properties(hasher, values);
+        }
+        PropertyValue::Enum { variant, payload } => {
+            hasher.write_u8(9);
+            hasher.write_str(variant);
+            match payload {
+                Some(payload) => {
+                    hasher.write_u8(1);
+                    hash_property(hasher, payload);
+                }
+                None => hasher.write_u8(0),
+            }
+        }
+    }
+}
+
+/// Stable hash of a property value, consistent with its `PartialEq`
+pub(crate) fn property_hash(value: &PropertyValue) -> u64 {
+    let mut hasher = StableHasher::new();
+    hash_property(&mut hasher, value);
+    hasher.finish()
+}
+
+/// 64-bit FNV-1a over a platform-independent encoding
+///
+/// `std`'s hashers may change between Rust releases and hash `usize` by
+/// platform width, so they can't be used for persisted hashes.
+pub(crate) struct StableHasher(u64);
+
+impl StableHasher {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    pub(crate) fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    pub(crate) fn write_u8(&mut self, value: u8) {
+        self.write(&[value]);
+    }
+
+    pub(crate) fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    pub(crate) fn write_len(&mut self, len: usize) {
+        self.write_u64(len as u64);
+    }
+
+    /// Length-prefixed, so `("ab", "c")` and `("a", "bc")` differ
+    pub(crate) fn write_str(&mut self, text: &str) {
+        self.write_len(text.len());
+        self.write(text.as_bytes());
+    }
+
+    /// Hashes `-0.0` like `0.0`, since they compare equal
+    pub(crate) fn write_f64(&mut self, value: f64) {
+        let value = if value == 0.0 { 0.0 } else { value };
+        self.write_u64(value.to_bits());
+    }
+
+    pub(crate) fn finish(&self) -> u64 {
+        self.0
+    }
+}
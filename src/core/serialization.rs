@@ -0,0 +1,296 @@
+//! # Streaming Serialization
+//!
+//! Reading and writing graphs directly from/to I/O streams.
+//!
+//! [`GraphDescription::from_reader`] and [`GraphDescription::to_writer`]
+//! (de)serialize without first buffering the whole JSON document in memory.
+//!
+//! For very large files, [`GraphDescription::stream_from_reader`] goes one step
+//! further: it hands each node, connection, and comment to a [`GraphVisitor`]
+//! as soon as it is parsed, so callers can filter, index, or forward graph
+//! elements without ever materializing the full graph.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::core::GraphVisitor;
+//! use graphy::{Connection, GraphDescription, GraphyError, NodeInstance, Position};
+//!
+//! let mut graph = GraphDescription::new("big");
+//! graph.add_node(NodeInstance::new("a", "math.add", Position::zero()));
+//!
+//! let mut bytes = Vec::new();
+//! graph.to_writer(&mut bytes).unwrap();
+//!
+//! // Count nodes without building a GraphDescription
+//! struct Counter(usize);
+//!
+//! impl GraphVisitor for Counter {
+//!     fn visit_node(&mut self, _node: NodeInstance) -> Result<(), GraphyError> {
+//!         self.0 += 1;
+//!         Ok(())
+//!     }
+//!
+//!     fn visit_connection(&mut self, _connection: Connection) -> Result<(), GraphyError> {
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let mut counter = Counter(0);
+//! GraphDescription::stream_from_reader(bytes.as_slice(), &mut counter).unwrap();
+//! assert_eq!(counter.0, 1);
+//! ```
+
+use super::{Connection, GraphComment, GraphDescription, GraphGroup, GraphMetadata, GraphParam, NodeInstance};
+use crate::GraphyError;
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::Deserializer;
+use std::fmt;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::marker::PhantomData;
+
+/// Receives graph elements one at a time while a graph is being parsed.
+///
+/// Elements are delivered in document order. Returning an error from any
+/// method aborts parsing and the error is returned unchanged from
+/// [`GraphDescription::stream_from_reader`].
+pub trait GraphVisitor {
+    /// Called once with the graph metadata.
+    fn visit_metadata(&mut self, _metadata: GraphMetadata) -> Result<(), GraphyError> {
+        Ok(())
+    }
+
+    /// Called for every node.
+    fn visit_node(&mut self, node: NodeInstance) -> Result<(), GraphyError>;
+
+    /// Called for every connection.
+    fn visit_connection(&mut self, connection: Connection) -> Result<(), GraphyError>;
+
+    /// Called for every comment.
+    fn visit_comment(&mut self, _comment: GraphComment) -> Result<(), GraphyError> {
+        Ok(())
+    }
+
+    /// Called for every declared graph input.
+    fn visit_graph_input(&mut self, _param: GraphParam) -> Result<(), GraphyError> {
+        Ok(())
+    }
+
+    /// Called for every declared graph output.
+    fn visit_graph_output(&mut self, _param: GraphParam) -> Result<(), GraphyError> {
+        Ok(())
+    }
+
+    /// Called for every node group.
+    fn visit_group(&mut self, _group: GraphGroup) -> Result<(), GraphyError> {
+        Ok(())
+    }
+}
+
+impl GraphDescription {
+    /// Deserializes a graph from a JSON reader.
+    ///
+    /// The reader is buffered internally and parsed directly into the graph
+    /// structures, without an intermediate string or `serde_json::Value`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::Serialization`] if reading or parsing fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use graphy::GraphDescription;
+    /// use std::fs::File;
+    ///
+    /// let graph = GraphDescription::from_reader(File::open("graph.json")?)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, GraphyError> {
+        serde_json::from_reader(BufReader::new(reader)).map_err(serialization_error)
+    }
+
+    /// Serializes the graph as JSON to a writer.
+    ///
+    /// The writer is buffered internally and flushed before returning.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::Serialization`] if writing fails.
+    pub fn to_writer<W: Write>(&self, writer: W) -> Result<(), GraphyError> {
+        let mut writer = BufWriter::new(writer);
+        serde_json::to_writer(&mut writer, self).map_err(serialization_error)?;
+        writer
+            .flush()
+            .map_err(|e| GraphyError::Serialization(e.to_string()))
+    }
+
+    /// Parses a graph from a JSON reader, streaming elements to a visitor.
+    ///
+    /// Nodes, connections, and comments are passed to the visitor as soon as
+    /// each one is parsed and are never collected, so memory usage stays
+    /// bounded by the largest single element. Unknown top-level fields are
+    /// skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns the visitor's error if it aborts, otherwise
+    /// [`GraphyError::Serialization`] if reading or parsing fails.
+    pub fn stream_from_reader<R: Read, V: GraphVisitor>(reader: R, visitor: &mut V) -> Result<(), GraphyError> {
+        let mut state = StreamState {
+            visitor,
+            error: None,
+        };
+
+        let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(reader));
+        let result = deserializer
+            .deserialize_map(GraphStreamVisitor { state: &mut state })
+            .and_then(|_| deserializer.end());
+
+        // Prefer the visitor's own error over serde's wrapped copy
+        if let Some(error) = state.error {
+            return Err(error);
+        }
+
+        result.map_err(serialization_error)
+    }
+}
+
+#[cold]
+fn serialization_error(error: serde_json::Error) -> GraphyError {
+    GraphyError::Serialization(error.to_string())
+}
+
+/// Visitor plus the first error it returned (if any).
+struct StreamState<'v, V> {
+    visitor: &'v mut V,
+    error: Option<GraphyError>,
+}
+
+impl<V: GraphVisitor> StreamState<'_, V> {
+    /// Forward an element to the visitor, stashing any error for later.
+    fn forward<T, E: de::Error>(
+        &mut self,
+        value: T,
+        visit: fn(&mut V, T) -> Result<(), GraphyError>,
+    ) -> Result<(), E> {
+        visit(self.visitor, value).map_err(|error| {
+            let message = error.to_string();
+            self.error = Some(error);
+            E::custom(message)
+        })
+    }
+}
+
+/// Visits the top-level graph object.
+struct GraphStreamVisitor<'s, 'v, V> {
+    state: &'s mut StreamState<'v, V>,
+}
+
+impl<'de, V: GraphVisitor> Visitor<'de> for GraphStreamVisitor<'_, '_, V> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a graph object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "metadata" => {
+                    let metadata: GraphMetadata = map.next_value()?;
+                    self.state.forward(metadata, V::visit_metadata)?;
+                }
+                "nodes" => map.next_value_seed(NodeMapSeed { state: &mut *self.state })?,
+                "connections" => map.next_value_seed(ElementSeqSeed {
+                    state: &mut *self.state,
+                    visit: V::visit_connection,
+                    marker: PhantomData,
+                })?,
+                "comments" => map.next_value_seed(ElementSeqSeed {
+                    state: &mut *self.state,
+                    visit: V::visit_comment,
+                    marker: PhantomData,
+                })?,
+                "graph_inputs" => map.next_value_seed(ElementSeqSeed {
+                    state: &mut *self.state,
+                    visit: V::visit_graph_input,
+                    marker: PhantomData,
+                })?,
+                "graph_outputs" => map.next_value_seed(ElementSeqSeed {
+                    state: &mut *self.state,
+                    visit: V::visit_graph_output,
+                    marker: PhantomData,
+                })?,
+                "groups" => map.next_value_seed(ElementSeqSeed {
+                    state: &mut *self.state,
+                    visit: V::visit_group,
+                    marker: PhantomData,
+                })?,
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Streams the `nodes` object (node_id -> node) to the visitor.
+struct NodeMapSeed<'s, 'v, V> {
+    state: &'s mut StreamState<'v, V>,
+}
+
+impl<'de, V: GraphVisitor> DeserializeSeed<'de> for NodeMapSeed<'_, '_, V> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, V: GraphVisitor> Visitor<'de> for NodeMapSeed<'_, '_, V> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a map of nodes")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some((_, node)) = map.next_entry::<IgnoredAny, NodeInstance>()? {
+            self.state.forward(node, V::visit_node)?;
+        }
+        Ok(())
+    }
+}
+
+/// Streams a JSON array of `T` to one visitor method.
+struct ElementSeqSeed<'s, 'v, V, T> {
+    state: &'s mut StreamState<'v, V>,
+    visit: fn(&mut V, T) -> Result<(), GraphyError>,
+    marker: PhantomData<T>,
+}
+
+impl<'de, V: GraphVisitor, T: DeserializeOwned> DeserializeSeed<'de> for ElementSeqSeed<'_, '_, V, T> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, V: GraphVisitor, T: DeserializeOwned> Visitor<'de> for ElementSeqSeed<'_, '_, V, T> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("an array of graph elements")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(element) = seq.next_element::<T>()? {
+            self.state.forward(element, self.visit)?;
+        }
+        Ok(())
+    }
+}
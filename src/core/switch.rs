@@ -0,0 +1,186 @@
+//! # Switch Nodes
+//!
+//! Cases of control flow nodes that match on a value.
+//!
+//! A switch node ([`NodeMetadata::with_switch`]) has one execution output
+//! per case. Since the cases depend on the type of the matched value, they
+//! can't be listed in the static `exec_outputs` of the metadata. Instead:
+//!
+//! - cases declared as execution output pins on the node instance (see
+//!   [`NodeInstance::add_switch_case`]) are used as-is, in pin order
+//! - otherwise, the cases are the variants of the value's type, from
+//!   [`NodeMetadataProvider::enum_variants`]
+//!
+//! [`GraphDescription::sync_switch_cases`] turns the second form into the
+//! first, which is what an editor wants when the user picks a type.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::core::{resolve_switch_cases, NodeRegistry};
+//! use graphy::{DataType, NodeInstance, NodeMetadata, NodeTypes, ParamInfo, Position};
+//!
+//! let switch = NodeMetadata::new("switch", NodeTypes::control_flow, "Flow")
+//!     .with_params(vec![ParamInfo::new("value", "Direction")])
+//!     .with_switch(true);
+//! let mut registry = NodeRegistry::new();
+//! registry.register_enum("Direction", ["North", "South"]);
+//!
+//! // Cases derived from the value type
+//! let node = NodeInstance::new("switch_1", "switch", Position::zero());
+//! assert_eq!(resolve_switch_cases(&node, &switch, &registry), vec!["North", "South"]);
+//!
+//! // Cases declared on the instance win
+//! let mut node = node;
+//! node.add_switch_case("South");
+//! assert_eq!(resolve_switch_cases(&node, &switch, &registry), vec!["South"]);
+//! ```
+
+use super::{
+    DataType, GraphDescription, NodeInstance, NodeMetadata, NodeMetadataProvider, PinType, SWITCH_DEFAULT_PIN,
+};
+use crate::GraphyError;
+
+impl NodeInstance {
+    /// Adds an execution output pin for a switch case.
+    ///
+    /// Returns `false` (and adds nothing) if the node already has an output
+    /// pin with that name.
+    pub fn add_switch_case(&mut self, case: impl Into<String>) -> bool {
+        let case = case.into();
+        if self.outputs.iter().any(|pin| pin.id == case) {
+            return false;
+        }
+        self.add_output_pin(case, DataType::Execution);
+        true
+    }
+
+    /// Returns the switch cases declared on this instance, in pin order.
+    ///
+    /// These are the execution output pins, except [`SWITCH_DEFAULT_PIN`].
+    pub fn switch_cases(&self) -> Vec<&str> {
+        self.outputs
+            .iter()
+            .filter(|pin| pin.pin.data_type == DataType::Execution && pin.id != SWITCH_DEFAULT_PIN)
+            .map(|pin| pin.id.as_str())
+            .collect()
+    }
+
+    /// Returns the type of the value a switch node matches on.
+    ///
+    /// A typed input pin for the value overrides the parameter type, so one
+    /// switch node type can match on different enums. Returns `None` if the
+    /// metadata isn't a switch.
+    pub fn switch_value_type<'a>(&'a self, metadata: &'a NodeMetadata) -> Option<&'a str> {
+        let param = metadata.switch_value()?;
+        let pin_type = self.inputs.iter().find_map(|pin| match &pin.pin.data_type {
+            DataType::Typed(type_info) if pin.id == param.name => Some(type_info.type_string.as_str()),
+            _ => None,
+        });
+        Some(pin_type.unwrap_or(&param.param_type))
+    }
+}
+
+/// Returns the cases of a switch node, in order.
+///
+/// Cases declared on the instance come first; without any, the variants of
+/// the value's type are used. Returns an empty list for nodes that aren't
+/// switches or whose value type isn't a known enum.
+pub fn resolve_switch_cases<'a, P>(node: &'a NodeInstance, metadata: &'a NodeMetadata, metadata_provider: &'a P) -> Vec<&'a str>
+where
+    P: NodeMetadataProvider + ?Sized,
+{
+    let Some(value_type) = node.switch_value_type(metadata) else {
+        return Vec::new();
+    };
+
+    let declared = node.switch_cases();
+    if !declared.is_empty() {
+        return declared;
+    }
+
+    metadata_provider
+        .enum_variants(value_type)
+        .map(|variants| variants.iter().map(String::as_str).collect())
+        .unwrap_or_default()
+}
+
+/// Checks if a pin is an execution output a switch node exposes without
+/// declaring it: the default case or a variant of the value's type.
+pub(crate) fn is_implicit_switch_output<P>(
+    node: &NodeInstance,
+    metadata: &NodeMetadata,
+    metadata_provider: &P,
+    pin_id: &str,
+) -> bool
+where
+    P: NodeMetadataProvider + ?Sized,
+{
+    let Some(value_type) = node.switch_value_type(metadata) else {
+        return false;
+    };
+    pin_id == SWITCH_DEFAULT_PIN
+        || metadata_provider
+            .enum_variants(value_type)
+            .is_some_and(|variants| variants.iter().any(|variant| variant == pin_id))
+}
+
+impl GraphDescription {
+    /// Replaces the case pins of a switch node with the variants of its value type.
+    ///
+    /// Missing variants are added as execution outputs (in declaration
+    /// order, after the cases that are kept). Case pins that aren't
+    /// variants are removed along with their connections. The
+    /// [`SWITCH_DEFAULT_PIN`] is left alone.
+    ///
+    /// Returns the removed cases.
+    ///
+    /// # Errors
+    ///
+    /// - [`GraphyError::NodeNotFound`] if the node doesn't exist
+    /// - [`GraphyError::Custom`] if the node isn't a switch, or its value
+    ///   type isn't an enum known to the provider
+    pub fn sync_switch_cases<P: NodeMetadataProvider>(
+        &mut self,
+        node_id: &str,
+        metadata_provider: &P,
+    ) -> Result<Vec<String>, GraphyError> {
+        let node = self
+            .nodes
+            .get(node_id)
+            .ok_or_else(|| GraphyError::NodeNotFound(node_id.to_string()))?;
+        let metadata = metadata_provider
+            .metadata_for_node(node)
+            .filter(|metadata| metadata.switch_value().is_some())
+            .ok_or_else(|| GraphyError::Custom(format!("Node '{}' is not a switch", node_id)))?;
+        let value_type = node.switch_value_type(metadata).unwrap_or_default();
+        let variants = metadata_provider
+            .enum_variants(value_type)
+            .ok_or_else(|| {
+                GraphyError::Custom(format!(
+                    "Switch node '{}' matches on '{}', which is not a known enum",
+                    node_id, value_type
+                ))
+            })?
+            .to_vec();
+
+        let stale: Vec<String> = node
+            .switch_cases()
+            .into_iter()
+            .filter(|case| !variants.iter().any(|variant| variant == case))
+            .map(str::to_string)
+            .collect();
+        for case in &stale {
+            self.disconnect_pin(node_id, case);
+        }
+
+        let node = self.nodes.get_mut(node_id).expect("node checked above");
+        node.outputs
+            .retain(|pin| !(pin.pin.pin_type == PinType::Output && stale.contains(&pin.id)));
+        for variant in variants {
+            node.add_switch_case(variant);
+        }
+
+        Ok(stale)
+    }
+}
@@ -0,0 +1,245 @@
+//! # Node Type Identifiers
+//!
+//! Structured node types with namespaces and version requirements.
+//!
+//! Node types are plain strings like `"math.add"`. A [`NodeTypeId`] splits
+//! one into a namespace (`math`) and a name (`add`), so plugin libraries can
+//! keep their nodes apart, and adds an optional semver requirement:
+//! `"math.add@1.2"` is any version of `math.add` compatible with 1.2.
+//!
+//! Nodes store their requirement in
+//! [`version_req`](NodeInstance::version_req) next to their `node_type`.
+//! Metadata declares its [`version`](super::NodeMetadata::version), and
+//! providers pick the newest version satisfying a node's requirement (see
+//! [`NodeMetadataProvider::metadata_for_node`]). A
+//! [`NodeRegistry`](super::NodeRegistry) can hold several versions of a node
+//! type at once with [`register_versioned`](super::NodeRegistry::register_versioned).
+//!
+//! [`GraphDescription::check_node_versions`] lists the nodes whose
+//! requirement the provider can't satisfy; compilation fails with
+//! [`GraphyError::UnsatisfiedVersion`] for the first of them.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::core::{NodeRegistry, NodeTypeId, Version};
+//! use graphy::{GraphDescription, NodeInstance, NodeMetadata, NodeTypes, Position};
+//!
+//! let id: NodeTypeId = "math.add@1.2".parse().unwrap();
+//! assert_eq!((id.namespace.as_str(), id.name.as_str()), ("math", "add"));
+//! assert_eq!(id.to_string(), "math.add@^1.2");
+//!
+//! let mut registry = NodeRegistry::new();
+//! registry.register_versioned(
+//!     "math.add",
+//!     NodeMetadata::new("add", NodeTypes::pure, "Math").with_version(Version::new(2, 0, 0)),
+//! );
+//!
+//! let mut node = NodeInstance::new("add_1", "", Position::zero());
+//! node.set_type_id(&id);
+//! let mut graph = GraphDescription::new("graph");
+//! graph.add_node(node);
+//!
+//! let issues = graph.check_node_versions(&registry);
+//! assert_eq!(issues[0].to_string(), "Node add_1 requires math.add ^1.2, but only 2.0.0 available");
+//! ```
+
+use super::{GraphDescription, NodeInstance, NodeMetadataProvider};
+use crate::GraphyError;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// A node type split into namespace and name, with an optional version
+/// requirement.
+///
+/// Parses from and displays as `namespace.name@requirement`. The namespace
+/// is everything before the last `.` and may itself contain dots; it is
+/// empty for types without one. Serializes as a string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct NodeTypeId {
+    /// Library the node type belongs to (e.g. `math`), or empty
+    pub namespace: String,
+
+    /// Name within the namespace (e.g. `add`)
+    pub name: String,
+
+    /// Versions accepted, any if `None`
+    pub version: Option<VersionReq>,
+}
+
+impl NodeTypeId {
+    /// Creates an ID accepting any version.
+    pub fn new(namespace: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            namespace: namespace.into(),
+            name: name.into(),
+            version: None,
+        }
+    }
+
+    /// Requires a version.
+    #[must_use]
+    pub fn with_version(mut self, requirement: VersionReq) -> Self {
+        self.version = Some(requirement);
+        self
+    }
+
+    /// Parses `namespace.name`, optionally followed by `@requirement`.
+    ///
+    /// Requirements use Cargo's syntax: `1.2` means `^1.2`, and `=1.2.3`,
+    /// `>=1, <3`, or `*` work as usual.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::Custom`] if the name or a namespace segment is
+    /// empty, or the requirement is missing or invalid.
+    pub fn parse(text: &str) -> Result<Self, GraphyError> {
+        let invalid = |reason: String| GraphyError::Custom(format!("Invalid node type ID '{}': {}", text, reason));
+
+        let (qualified, version) = match text.split_once('@') {
+            Some((qualified, requirement)) => {
+                let requirement = VersionReq::parse(requirement.trim())
+                    .map_err(|e| invalid(format!("bad version requirement: {}", e)))?;
+                (qualified, Some(requirement))
+            }
+            None => (text, None),
+        };
+        if qualified.split('.').any(str::is_empty) {
+            return Err(invalid("empty name or namespace segment".to_string()));
+        }
+
+        let mut id = Self::from_node_type(qualified);
+        id.version = version;
+        Ok(id)
+    }
+
+    /// Splits a node type at its last `.`, without a requirement
+    fn from_node_type(node_type: &str) -> Self {
+        match node_type.rsplit_once('.') {
+            Some((namespace, name)) => Self::new(namespace, name),
+            None => Self::new("", node_type),
+        }
+    }
+
+    /// Returns the node type without the requirement: `namespace.name`, or
+    /// `name` without a namespace.
+    pub fn qualified_name(&self) -> String {
+        if self.namespace.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}.{}", self.namespace, self.name)
+        }
+    }
+
+    /// Checks if a version satisfies the requirement.
+    ///
+    /// Every version does if there is no requirement.
+    pub fn accepts(&self, version: &Version) -> bool {
+        self.version.as_ref().is_none_or(|requirement| requirement.matches(version))
+    }
+}
+
+impl fmt::Display for NodeTypeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.namespace.is_empty() {
+            write!(f, "{}.", self.namespace)?;
+        }
+        f.write_str(&self.name)?;
+        if let Some(requirement) = &self.version {
+            write!(f, "@{}", requirement)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for NodeTypeId {
+    type Err = GraphyError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Self::parse(text)
+    }
+}
+
+impl TryFrom<String> for NodeTypeId {
+    type Error = GraphyError;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        Self::parse(&text)
+    }
+}
+
+impl From<NodeTypeId> for String {
+    fn from(id: NodeTypeId) -> Self {
+        id.to_string()
+    }
+}
+
+impl NodeInstance {
+    /// Returns the node's type and version requirement as a [`NodeTypeId`].
+    pub fn type_id(&self) -> NodeTypeId {
+        let mut id = NodeTypeId::from_node_type(&self.node_type);
+        id.version = self.version_req.clone();
+        id
+    }
+
+    /// Sets the node's type and version requirement from a [`NodeTypeId`].
+    pub fn set_type_id(&mut self, id: &NodeTypeId) {
+        self.node_type = id.qualified_name();
+        self.version_req = id.version.clone();
+    }
+}
+
+/// A node requiring a version of its type that the provider doesn't have.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionIssue {
+    /// ID of the node
+    pub node: String,
+
+    /// The node's type and requirement
+    pub type_id: NodeTypeId,
+
+    /// Versions of the type the provider has, oldest first
+    pub available: Vec<Version>,
+}
+
+impl fmt::Display for VersionIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", GraphyError::from(self.clone()))
+    }
+}
+
+impl From<VersionIssue> for GraphyError {
+    fn from(issue: VersionIssue) -> Self {
+        GraphyError::UnsatisfiedVersion {
+            node: issue.node,
+            node_type: issue.type_id.qualified_name(),
+            requirement: issue.type_id.version.map(|requirement| requirement.to_string()).unwrap_or_default(),
+            available: issue.available.iter().map(Version::to_string).collect(),
+        }
+    }
+}
+
+impl GraphDescription {
+    /// Lists the nodes whose version requirement the provider can't satisfy,
+    /// sorted by node ID.
+    ///
+    /// Nodes without a requirement are never listed, even if their type is
+    /// unknown.
+    pub fn check_node_versions<P: NodeMetadataProvider + ?Sized>(&self, provider: &P) -> Vec<VersionIssue> {
+        let mut issues: Vec<VersionIssue> = self
+            .nodes
+            .values()
+            .filter(|node| node.version_req.is_some() && provider.metadata_for_node(node).is_none())
+            .map(|node| VersionIssue {
+                node: node.id.clone(),
+                type_id: node.type_id(),
+                available: provider.node_versions(&node.node_type),
+            })
+            .collect();
+        issues.sort_unstable_by(|a, b| a.node.cmp(&b.node));
+        issues
+    }
+}
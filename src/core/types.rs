@@ -22,6 +22,7 @@
 //! ```
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt;
 
 /// Data type for a pin.
@@ -59,6 +60,70 @@ pub enum DataType {
     Any,
 }
 
+impl DataType {
+    /// Returns the Rust type string carried by this data type.
+    ///
+    /// Legacy variants map to their Rust equivalents (e.g. `Number` -> `f64`).
+    /// Returns `None` for `Execution` and `Any`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::DataType;
+    ///
+    /// assert_eq!(DataType::Number.type_string(), Some("f64"));
+    /// assert_eq!(DataType::Typed("i64".into()).type_string(), Some("i64"));
+    /// assert_eq!(DataType::Any.type_string(), None);
+    /// ```
+    pub fn type_string(&self) -> Option<&str> {
+        match self {
+            DataType::Execution | DataType::Any => None,
+            DataType::Typed(info) => Some(&info.type_string),
+            DataType::Number => Some("f64"),
+            DataType::String => Some("String"),
+            DataType::Boolean => Some("bool"),
+            DataType::Vector2 => Some("(f64, f64)"),
+            DataType::Vector3 => Some("(f64, f64, f64)"),
+            DataType::Color => Some("(f64, f64, f64, f64)"),
+        }
+    }
+
+    /// Checks if this is an execution pin type.
+    #[inline(always)]
+    pub fn is_execution(&self) -> bool {
+        matches!(self, DataType::Execution)
+    }
+
+    /// Checks if a value of this type can flow into a pin of type `target` unchanged.
+    ///
+    /// - `Execution` is only compatible with `Execution`
+    /// - `Any` is compatible with every data type
+    /// - Otherwise the Rust type strings must match (ignoring whitespace)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::DataType;
+    ///
+    /// assert!(DataType::Number.is_compatible_with(&DataType::Typed("f64".into())));
+    /// assert!(DataType::Any.is_compatible_with(&DataType::String));
+    /// assert!(!DataType::Execution.is_compatible_with(&DataType::Any));
+    /// ```
+    pub fn is_compatible_with(&self, target: &DataType) -> bool {
+        if self.is_execution() || target.is_execution() {
+            return self.is_execution() && target.is_execution();
+        }
+
+        match (self.type_string(), target.type_string()) {
+            (Some(source), Some(target)) => {
+                source.chars().filter(|c| !c.is_whitespace()).eq(target.chars().filter(|c| !c.is_whitespace()))
+            }
+            // At least one side is Any
+            _ => true,
+        }
+    }
+}
+
 /// Type information for typed pins.
 ///
 /// Wraps a Rust type string (e.g., "i64", "String", "(f32, f32)")
@@ -88,6 +153,55 @@ impl TypeInfo {
             type_string: type_string.into(),
         }
     }
+
+    /// Splits a `Result` type into its success and error types.
+    ///
+    /// Accepts any path ending in `Result` (`Result<T, E>`,
+    /// `std::io::Result<T>`, ...). The error type is `None` for single
+    /// argument aliases that fix it. Returns `None` for other types.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::core::TypeInfo;
+    ///
+    /// let result = TypeInfo::new("Result<Vec<u8>, String>");
+    /// assert_eq!(result.result_types(), Some(("Vec<u8>", Some("String"))));
+    /// assert_eq!(TypeInfo::new("std::io::Result<()>").result_types(), Some(("()", None)));
+    /// assert_eq!(TypeInfo::new("Option<i64>").result_types(), None);
+    /// ```
+    pub fn result_types(&self) -> Option<(&str, Option<&str>)> {
+        let type_string = self.type_string.trim();
+        let (path, rest) = type_string.split_once('<')?;
+        let path = path.trim();
+        if path != "Result" && !path.ends_with("::Result") {
+            return None;
+        }
+        let args = rest.strip_suffix('>')?;
+
+        // Split at commas outside nested brackets
+        let mut depth = 0i32;
+        let mut parts = vec![];
+        let mut start = 0;
+        for (index, c) in args.char_indices() {
+            match c {
+                '<' | '(' | '[' => depth += 1,
+                '>' | ')' | ']' => depth -= 1,
+                ',' if depth == 0 => {
+                    parts.push(args[start..index].trim());
+                    start = index + 1;
+                }
+                _ => {}
+            }
+        }
+        parts.push(args[start..].trim());
+
+        match parts.as_slice() {
+            [ok] if !ok.is_empty() => Some((*ok, None)),
+            [ok, error] if !ok.is_empty() && !error.is_empty() => Some((*ok, Some(*error))),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for TypeInfo {
@@ -153,7 +267,10 @@ pub enum NodeTypes {
 ///
 /// Properties are constant values stored directly on nodes,
 /// typically used for defaults or configuration.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Values are serialized externally tagged (e.g. `{"Integer": 5}`), so graphs
+/// saved before a variant was added continue to load unchanged.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum PropertyValue {
     /// String value
     String(String),
@@ -172,13 +289,35 @@ pub enum PropertyValue {
     
     /// RGBA color (r, g, b, a) with values in [0, 1]
     Color(f64, f64, f64, f64),
+
+    /// Integer value (exact, unlike `Number`)
+    Integer(i64),
+
+    /// Ordered list of values
+    Array(Vec<PropertyValue>),
+
+    /// String-keyed map of values
+    Map(HashMap<String, PropertyValue>),
+
+    /// Enum variant with an optional payload
+    ///
+    /// `variant` is the path of the variant as it should appear in generated
+    /// code (e.g. `"Direction::Left"`).
+    Enum {
+        /// Variant path
+        variant: String,
+
+        /// Payload value for tuple-like variants
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        payload: Option<Box<PropertyValue>>,
+    },
 }
 
 /// 2D position in visual editor space.
 ///
 /// Used to track node placement in visual programming environments.
 /// Coordinates are in arbitrary editor units.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Position {
     /// X coordinate
     pub x: f64,
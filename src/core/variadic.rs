@@ -0,0 +1,191 @@
+//! # Variadic Inputs
+//!
+//! Nodes whose number of inputs is chosen per instance.
+//!
+//! A node type like `make_array` takes any number of elements. Its metadata
+//! declares a [`VariadicGroup`] with [`NodeMetadata::with_variadic`]: a name,
+//! the type of every element, and how many elements are allowed. Each node
+//! instance records how many it has in
+//! [`variadic_count`](NodeInstance::variadic_count) and has one input pin per
+//! element, named `<group>_0`, `<group>_1`, and so on.
+//!
+//! Elements come after the fixed params, in index order, wherever arguments
+//! are collected: see [`NodeMetadata::instance_params`]. Templates can use
+//! each element (`{{element_0}}`) or all of them joined with `, `
+//! (`{{element}}`).
+//!
+//! [`GraphDescription::set_variadic_count`] adds or removes elements, which
+//! is what an editor's "add pin" button does.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::core::{NodeRegistry, VariadicGroup};
+//! use graphy::{GraphDescription, NodeMetadata, NodeTypes};
+//!
+//! let mut registry = NodeRegistry::new();
+//! registry.register(
+//!     NodeMetadata::new("make_array", NodeTypes::pure, "Arrays")
+//!         .with_variadic(VariadicGroup::new("element", "i64").with_min(1))
+//!         .with_return_type("Vec<i64>"),
+//! );
+//!
+//! let mut graph = GraphDescription::new("graph");
+//! graph.add_node_of_type("make_array", "array_1", &registry).unwrap();
+//! assert_eq!(graph.nodes["array_1"].variadic_count, 1);
+//!
+//! graph.set_variadic_count("array_1", 3, &registry).unwrap();
+//! let pins: Vec<&str> = graph.nodes["array_1"].inputs.iter().map(|pin| pin.id.as_str()).collect();
+//! assert_eq!(pins, ["element_0", "element_1", "element_2"]);
+//! ```
+
+use super::{DataType, GraphDescription, NodeInstance, NodeMetadata, NodeMetadataProvider, ParamInfo, TypeInfo};
+use crate::GraphyError;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// A group of inputs whose size is chosen per node instance.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VariadicGroup {
+    /// Name of the group, and prefix of its pins (`<name>_<index>`)
+    pub name: String,
+
+    /// Rust type of every input of the group
+    pub param_type: String,
+
+    /// Fewest inputs a node can have, and how many new nodes start with
+    #[serde(default, skip_serializing_if = "super::node::is_zero")]
+    pub min: usize,
+
+    /// Most inputs a node can have, unlimited if `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<usize>,
+}
+
+impl VariadicGroup {
+    /// Creates a group of any number of inputs.
+    pub fn new(name: impl Into<String>, param_type: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            param_type: param_type.into(),
+            min: 0,
+            max: None,
+        }
+    }
+
+    /// Sets the fewest inputs a node can have.
+    #[must_use]
+    pub fn with_min(mut self, min: usize) -> Self {
+        self.min = min;
+        self
+    }
+
+    /// Sets the most inputs a node can have.
+    #[must_use]
+    pub fn with_max(mut self, max: usize) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    /// Checks if a node can have this many inputs in the group.
+    pub fn accepts(&self, count: usize) -> bool {
+        count >= self.min && self.max.is_none_or(|max| count <= max)
+    }
+
+    /// Returns the name of the input at an index: `<name>_<index>`.
+    pub fn pin_name(&self, index: usize) -> String {
+        format!("{}_{}", self.name, index)
+    }
+
+    /// Returns the index of an input of the group, `None` for other pins.
+    pub fn pin_index(&self, pin: &str) -> Option<usize> {
+        let digits = pin.strip_prefix(&self.name)?.strip_prefix('_')?;
+        let index: usize = digits.parse().ok()?;
+        // Exactly as `pin_name` writes it, so `element_01` isn't element 1
+        (index.to_string() == digits).then_some(index)
+    }
+
+    /// Returns the param of the input at an index.
+    pub fn param(&self, index: usize) -> ParamInfo {
+        ParamInfo::new(self.pin_name(index), self.param_type.clone())
+    }
+}
+
+impl NodeMetadata {
+    /// Declares a group of inputs whose size is chosen per node instance.
+    ///
+    /// The group's inputs come after the params. See [`VariadicGroup`].
+    #[inline]
+    #[must_use]
+    pub fn with_variadic(mut self, group: VariadicGroup) -> Self {
+        self.variadic = Some(group);
+        self
+    }
+
+    /// Returns the inputs of a node instance, in argument order: the params,
+    /// then one per element of the variadic group.
+    ///
+    /// For node types without a variadic group, these are just the params.
+    pub fn instance_params(&self, node: &NodeInstance) -> Vec<Cow<'_, ParamInfo>> {
+        let mut params: Vec<Cow<'_, ParamInfo>> = self.params.iter().map(Cow::Borrowed).collect();
+        if let Some(group) = &self.variadic {
+            params.extend((0..node.variadic_count).map(|index| Cow::Owned(group.param(index))));
+        }
+        params
+    }
+}
+
+impl GraphDescription {
+    /// Sets how many inputs a node has in its variadic group.
+    ///
+    /// New inputs are added after the existing ones. Removed inputs (the
+    /// last ones) lose their connections and properties.
+    ///
+    /// # Errors
+    ///
+    /// - [`GraphyError::NodeNotFound`] if the node doesn't exist
+    /// - [`GraphyError::Custom`] if the node type has no variadic group, or
+    ///   doesn't accept that many inputs
+    pub fn set_variadic_count<P: NodeMetadataProvider>(
+        &mut self,
+        node_id: &str,
+        count: usize,
+        metadata_provider: &P,
+    ) -> Result<(), GraphyError> {
+        let node = self
+            .nodes
+            .get(node_id)
+            .ok_or_else(|| GraphyError::NodeNotFound(node_id.to_string()))?;
+        let group = metadata_provider
+            .metadata_for_node(node)
+            .and_then(|metadata| metadata.variadic.clone())
+            .ok_or_else(|| GraphyError::Custom(format!("Node '{}' has no variadic inputs", node_id)))?;
+        if !group.accepts(count) {
+            let range = match group.max {
+                Some(max) => format!("{} to {}", group.min, max),
+                None => format!("at least {}", group.min),
+            };
+            return Err(GraphyError::Custom(format!(
+                "Node '{}' takes {} '{}' inputs, not {}",
+                node_id, range, group.name, count
+            )));
+        }
+
+        let removed: Vec<String> = (count..node.variadic_count).map(|index| group.pin_name(index)).collect();
+        for pin in &removed {
+            self.disconnect_pin(node_id, pin);
+        }
+
+        let node = self.nodes.get_mut(node_id).expect("node checked above");
+        node.inputs
+            .retain(|pin| group.pin_index(&pin.id).is_none_or(|index| index < count));
+        for pin in &removed {
+            node.properties.remove(pin);
+        }
+        for index in node.variadic_count..count {
+            node.add_input_pin(group.pin_name(index), DataType::Typed(TypeInfo::new(&group.param_type)));
+        }
+        node.variadic_count = count;
+        Ok(())
+    }
+}
@@ -0,0 +1,292 @@
+//! Node catalog export.
+//!
+//! Renders a [`NodeCatalog`] as a node reference in Markdown or HTML.
+
+use super::options::{kind_color, kind_name, property_text};
+use crate::core::{CatalogNode, NodeCatalog, NodeTypes, VariadicGroup};
+use std::fmt::Write;
+
+/// Renders a node catalog as a Markdown reference.
+///
+/// The document starts with a list of categories linking to their sections.
+/// Each node gets a heading, its kind, description, a table of params,
+/// variadic inputs, return type, and execution outputs. A list of all types
+/// used closes it.
+///
+/// # Example
+///
+/// ```
+/// use graphy::core::export_catalog;
+/// use graphy::export::catalog_to_markdown;
+/// use graphy::{NodeMetadata, NodeRegistry, NodeTypes};
+///
+/// let mut registry = NodeRegistry::new();
+/// registry.register(NodeMetadata::new("print", NodeTypes::fn_, "IO"));
+///
+/// let markdown = catalog_to_markdown(&export_catalog(&registry), "Nodes");
+/// assert!(markdown.starts_with("# Nodes\n"));
+/// assert!(markdown.contains("### `print`"));
+/// ```
+pub fn catalog_to_markdown(catalog: &NodeCatalog, title: &str) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}\n", title);
+
+    for category in &catalog.categories {
+        let _ = writeln!(
+            out,
+            "- [{}](#{}) ({})",
+            category.name,
+            markdown_anchor(&category.name),
+            category.nodes.len()
+        );
+    }
+
+    for category in &catalog.categories {
+        let _ = writeln!(out, "\n## {}", category.name);
+        for node in &category.nodes {
+            write_markdown_node(&mut out, node);
+        }
+    }
+
+    if !catalog.types.is_empty() {
+        out.push_str("\n## Types\n\n");
+        for type_name in &catalog.types {
+            let _ = writeln!(out, "- `{}`", type_name);
+        }
+    }
+
+    out
+}
+
+fn write_markdown_node(out: &mut String, node: &CatalogNode) {
+    let _ = writeln!(out, "\n### `{}`\n\n*{}*", node.name, kind_text(node));
+    if let Some(description) = &node.description {
+        let _ = writeln!(out, "\n{}", description);
+    }
+
+    if !node.params.is_empty() {
+        let _ = writeln!(out, "\n| {} | Type | Default | Description |", params_heading(node));
+        out.push_str("| --- | --- | --- | --- |\n");
+        for param in &node.params {
+            let default = match (&param.default, param.required) {
+                (Some(value), _) => format!("`{}`", table_cell(&property_text(value))),
+                (None, true) => "required".to_string(),
+                (None, false) => String::new(),
+            };
+            let _ = writeln!(
+                out,
+                "| `{}` | `{}` | {} | {} |",
+                param.name,
+                table_cell(&param.param_type),
+                default,
+                table_cell(param.description.as_deref().unwrap_or_default())
+            );
+        }
+    }
+
+    if let Some(group) = &node.variadic {
+        let _ = writeln!(
+            out,
+            "\n**Variadic inputs:** `{}_<n>` of type `{}`, {}",
+            group.name,
+            group.param_type,
+            variadic_count_text(group)
+        );
+    }
+    if let Some(return_type) = &node.return_type {
+        let _ = writeln!(out, "\n**Returns:** `{}`", return_type);
+    }
+    if !node.exec_outputs.is_empty() {
+        let outputs: Vec<String> = node.exec_outputs.iter().map(|output| format!("`{}`", output)).collect();
+        let _ = writeln!(out, "\n**Execution outputs:** {}", outputs.join(", "));
+    }
+}
+
+/// Renders a node catalog as a standalone HTML page.
+///
+/// The page has the same structure as [`catalog_to_markdown`]. Categories
+/// get the ID `category-<name>` and nodes `node-<name>`, so other pages can
+/// link to them; nodes are outlined in the colors of the graph exports.
+pub fn catalog_to_html(catalog: &NodeCatalog, title: &str) -> String {
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    let _ = writeln!(out, "<title>{}</title>", escape_html(title));
+    out.push_str("<style>\n");
+    out.push_str("body { font-family: sans-serif; max-width: 960px; margin: auto; }\n");
+    out.push_str("table { border-collapse: collapse; }\n");
+    out.push_str("th, td { border: 1px solid #ccc; padding: 4px 8px; text-align: left; }\n");
+    out.push_str(".node { border-left: 6px solid; padding-left: 12px; margin: 16px 0; }\n");
+    for kind in [NodeTypes::pure, NodeTypes::fn_, NodeTypes::control_flow, NodeTypes::event] {
+        let _ = writeln!(
+            out,
+            ".node.{} {{ border-color: {}; }}",
+            kind_name(Some(kind)),
+            kind_color(Some(kind))
+        );
+    }
+    out.push_str("</style>\n</head>\n<body>\n");
+    let _ = writeln!(out, "<h1>{}</h1>", escape_html(title));
+
+    out.push_str("<nav>\n<ul>\n");
+    for category in &catalog.categories {
+        let _ = writeln!(
+            out,
+            "<li><a href=\"#category-{}\">{}</a> ({})</li>",
+            html_id(&category.name),
+            escape_html(&category.name),
+            category.nodes.len()
+        );
+    }
+    out.push_str("</ul>\n</nav>\n");
+
+    for category in &catalog.categories {
+        let _ = writeln!(out, "<section id=\"category-{}\">", html_id(&category.name));
+        let _ = writeln!(out, "<h2>{}</h2>", escape_html(&category.name));
+        for node in &category.nodes {
+            write_html_node(&mut out, node);
+        }
+        out.push_str("</section>\n");
+    }
+
+    if !catalog.types.is_empty() {
+        out.push_str("<section id=\"types\">\n<h2>Types</h2>\n<ul>\n");
+        for type_name in &catalog.types {
+            let _ = writeln!(out, "<li><code>{}</code></li>", escape_html(type_name));
+        }
+        out.push_str("</ul>\n</section>\n");
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn write_html_node(out: &mut String, node: &CatalogNode) {
+    let _ = writeln!(
+        out,
+        "<article id=\"node-{}\" class=\"node {}\">",
+        html_id(&node.name),
+        kind_name(Some(node.node_type))
+    );
+    let _ = writeln!(
+        out,
+        "<h3><code>{}</code> <small>{}</small></h3>",
+        escape_html(&node.name),
+        kind_text(node)
+    );
+    if let Some(description) = &node.description {
+        let _ = writeln!(out, "<p>{}</p>", escape_html(description).replace('\n', "<br>"));
+    }
+
+    if !node.params.is_empty() {
+        let _ = writeln!(
+            out,
+            "<table>\n<tr><th>{}</th><th>Type</th><th>Default</th><th>Description</th></tr>",
+            params_heading(node)
+        );
+        for param in &node.params {
+            let default = match (&param.default, param.required) {
+                (Some(value), _) => format!("<code>{}</code>", escape_html(&property_text(value))),
+                (None, true) => "required".to_string(),
+                (None, false) => String::new(),
+            };
+            let _ = writeln!(
+                out,
+                "<tr><td><code>{}</code></td><td><code>{}</code></td><td>{}</td><td>{}</td></tr>",
+                escape_html(&param.name),
+                escape_html(&param.param_type),
+                default,
+                escape_html(param.description.as_deref().unwrap_or_default())
+            );
+        }
+        out.push_str("</table>\n");
+    }
+
+    if let Some(group) = &node.variadic {
+        let _ = writeln!(
+            out,
+            "<p><strong>Variadic inputs:</strong> <code>{}_&lt;n&gt;</code> of type <code>{}</code>, {}</p>",
+            escape_html(&group.name),
+            escape_html(&group.param_type),
+            variadic_count_text(group)
+        );
+    }
+    if let Some(return_type) = &node.return_type {
+        let _ = writeln!(out, "<p><strong>Returns:</strong> <code>{}</code></p>", escape_html(return_type));
+    }
+    if !node.exec_outputs.is_empty() {
+        let outputs: Vec<String> = node
+            .exec_outputs
+            .iter()
+            .map(|output| format!("<code>{}</code>", escape_html(output)))
+            .collect();
+        let _ = writeln!(out, "<p><strong>Execution outputs:</strong> {}</p>", outputs.join(", "));
+    }
+    out.push_str("</article>\n");
+}
+
+/// Kind of a node with its flags, e.g. "Function, async"
+fn kind_text(node: &CatalogNode) -> String {
+    let mut text = match node.node_type {
+        NodeTypes::pure => "Pure",
+        NodeTypes::fn_ => "Function",
+        NodeTypes::control_flow => "Control flow",
+        NodeTypes::event => "Event",
+    }
+    .to_string();
+    if node.is_async {
+        text.push_str(", async");
+    }
+    if node.is_switch {
+        text.push_str(", switch");
+    }
+    text
+}
+
+/// How many inputs a variadic group takes, e.g. "1 to 4"
+fn variadic_count_text(group: &VariadicGroup) -> String {
+    match (group.min, group.max) {
+        (0, None) => "any number".to_string(),
+        (min, None) => format!("at least {}", min),
+        (min, Some(max)) => format!("{} to {}", min, max),
+    }
+}
+
+/// Event params are values the event provides, all other params are inputs
+fn params_heading(node: &CatalogNode) -> &'static str {
+    match node.node_type {
+        NodeTypes::event => "Output",
+        _ => "Input",
+    }
+}
+
+/// Anchor GitHub generates for a Markdown heading
+fn markdown_anchor(heading: &str) -> String {
+    heading
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Escape text for a Markdown table cell
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', "<br>")
+}
+
+/// Turn a name into an HTML ID fragment
+fn html_id(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c.to_ascii_lowercase() } else { '-' })
+        .collect()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
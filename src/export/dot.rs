@@ -0,0 +1,109 @@
+//! Graphviz DOT export.
+
+use super::options::{kind_color, layout_clusters, Direction, ExportOptions};
+use crate::core::{ConnectionType, GraphDescription};
+use std::fmt::Write;
+
+/// Renders a graph as Graphviz DOT.
+///
+/// Nodes are filled by kind (pure, function, control flow, event), execution
+/// edges are dashed and data edges solid. Groups and comment boxes become
+/// `cluster_N` subgraphs. Nodes are written in ID order, so the output is
+/// deterministic.
+///
+/// Render with e.g. `dot -Tsvg graph.dot -o graph.svg`.
+pub fn to_dot(graph: &GraphDescription, options: &ExportOptions<'_>) -> String {
+    let mut out = String::new();
+    let rankdir = match options.direction {
+        Direction::LeftToRight => "LR",
+        Direction::TopToBottom => "TB",
+    };
+
+    let _ = writeln!(out, "digraph {} {{", quote(&graph.metadata.name));
+    let _ = writeln!(out, "    rankdir={};", rankdir);
+    out.push_str("    node [shape=box, style=\"rounded,filled\", fontname=\"Helvetica\"];\n");
+    out.push_str("    edge [fontname=\"Helvetica\", fontsize=10];\n");
+
+    let (clusters, unclustered) = layout_clusters(graph, options);
+    for (index, cluster) in clusters.iter().enumerate() {
+        let _ = writeln!(out, "\n    subgraph cluster_{} {{", index);
+        let _ = writeln!(out, "        label={};", quote(cluster.label));
+        if let Some(color) = cluster.color {
+            let _ = writeln!(out, "        color={};", quote(color));
+        }
+        for node_id in &cluster.node_ids {
+            write_node(&mut out, graph, options, node_id, "        ");
+        }
+        out.push_str("    }\n");
+    }
+
+    if !unclustered.is_empty() {
+        out.push('\n');
+    }
+    for node_id in &unclustered {
+        write_node(&mut out, graph, options, node_id, "    ");
+    }
+
+    if !graph.connections.is_empty() {
+        out.push('\n');
+    }
+    for connection in &graph.connections {
+        let mut attributes = Vec::new();
+        if connection.connection_type == ConnectionType::Execution {
+            attributes.push("style=dashed".to_string());
+        }
+        if options.show_pins {
+            attributes.push(format!("taillabel={}", quote(&connection.source_pin)));
+            attributes.push(format!("headlabel={}", quote(&connection.target_pin)));
+        }
+        if let Some(label) = &connection.label {
+            attributes.push(format!("label={}", quote(label)));
+        }
+        if let Some(color) = &connection.color {
+            attributes.push(format!("color={}", quote(color)));
+        }
+
+        let _ = write!(
+            out,
+            "    {} -> {}",
+            quote(&connection.source_node),
+            quote(&connection.target_node)
+        );
+        if !attributes.is_empty() {
+            let _ = write!(out, " [{}]", attributes.join(", "));
+        }
+        out.push_str(";\n");
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+fn write_node(out: &mut String, graph: &GraphDescription, options: &ExportOptions<'_>, node_id: &str, indent: &str) {
+    let node = &graph.nodes[node_id];
+    let label = options
+        .node_label_lines(node)
+        .iter()
+        .map(|line| escape(line))
+        .collect::<Vec<_>>()
+        .join("\\n");
+
+    let _ = writeln!(
+        out,
+        "{}{} [label=\"{}\", fillcolor=\"{}\"];",
+        indent,
+        quote(node_id),
+        label,
+        kind_color(options.node_kind(node))
+    );
+}
+
+/// Quote a DOT identifier
+fn quote(text: &str) -> String {
+    format!("\"{}\"", escape(text))
+}
+
+/// Escape text for a quoted DOT string
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
@@ -0,0 +1,119 @@
+//! Mermaid flowchart export.
+
+use super::options::{kind_color, kind_name, layout_clusters, Direction, ExportOptions};
+use crate::core::{ConnectionType, GraphDescription, NodeTypes};
+use rustc_hash::FxHashMap;
+use std::fmt::Write;
+
+/// Renders a graph as a Mermaid flowchart.
+///
+/// Mermaid identifiers are restricted, so nodes are named `n0`, `n1`, ... in
+/// ID order and labeled with their real ID. Nodes get a class per kind
+/// (`pure`, `function`, `control_flow`, `event`, `unknown`), execution edges
+/// are dotted and data edges solid, and groups and comment boxes become
+/// subgraphs.
+pub fn to_mermaid(graph: &GraphDescription, options: &ExportOptions<'_>) -> String {
+    let mut out = String::new();
+    let direction = match options.direction {
+        Direction::LeftToRight => "LR",
+        Direction::TopToBottom => "TB",
+    };
+    let _ = writeln!(out, "flowchart {}", direction);
+
+    let mut node_ids: Vec<&str> = graph.nodes.keys().map(String::as_str).collect();
+    node_ids.sort_unstable();
+    let aliases: FxHashMap<&str, String> = node_ids
+        .iter()
+        .enumerate()
+        .map(|(index, id)| (*id, format!("n{}", index)))
+        .collect();
+
+    let (clusters, unclustered) = layout_clusters(graph, options);
+    for (index, cluster) in clusters.iter().enumerate() {
+        let _ = writeln!(out, "    subgraph cluster_{} [\"{}\"]", index, escape(cluster.label));
+        for node_id in &cluster.node_ids {
+            write_node(&mut out, graph, options, &aliases, node_id, "        ");
+        }
+        out.push_str("    end\n");
+    }
+    for node_id in &unclustered {
+        write_node(&mut out, graph, options, &aliases, node_id, "    ");
+    }
+
+    for connection in &graph.connections {
+        // Connections to missing nodes can't be drawn
+        let (Some(source), Some(target)) = (
+            aliases.get(connection.source_node.as_str()),
+            aliases.get(connection.target_node.as_str()),
+        ) else {
+            continue;
+        };
+
+        let execution = connection.connection_type == ConnectionType::Execution;
+        let _ = match (execution, options.show_pins) {
+            (true, true) => writeln!(
+                out,
+                "    {} -. \"{} → {}\" .-> {}",
+                source,
+                escape(&connection.source_pin),
+                escape(&connection.target_pin),
+                target
+            ),
+            (true, false) => writeln!(out, "    {} -.-> {}", source, target),
+            (false, true) => writeln!(
+                out,
+                "    {} -- \"{} → {}\" --> {}",
+                source,
+                escape(&connection.source_pin),
+                escape(&connection.target_pin),
+                target
+            ),
+            (false, false) => writeln!(out, "    {} --> {}", source, target),
+        };
+    }
+
+    // One class per kind, assigned to the nodes of that kind
+    let kinds = [
+        Some(NodeTypes::event),
+        Some(NodeTypes::fn_),
+        Some(NodeTypes::control_flow),
+        Some(NodeTypes::pure),
+        None,
+    ];
+    for kind in kinds {
+        let members: Vec<&str> = node_ids
+            .iter()
+            .filter(|id| options.node_kind(&graph.nodes[**id]) == kind)
+            .map(|id| aliases[id].as_str())
+            .collect();
+        if members.is_empty() {
+            continue;
+        }
+        let _ = writeln!(out, "    classDef {} fill:{}", kind_name(kind), kind_color(kind));
+        let _ = writeln!(out, "    class {} {}", members.join(","), kind_name(kind));
+    }
+
+    out
+}
+
+fn write_node(
+    out: &mut String,
+    graph: &GraphDescription,
+    options: &ExportOptions<'_>,
+    aliases: &FxHashMap<&str, String>,
+    node_id: &str,
+    indent: &str,
+) {
+    let label = options
+        .node_label_lines(&graph.nodes[node_id])
+        .iter()
+        .map(|line| escape(line))
+        .collect::<Vec<_>>()
+        .join("<br/>");
+    let _ = writeln!(out, "{}{}[\"{}\"]", indent, aliases[node_id], label);
+}
+
+/// Escape text for a quoted Mermaid label
+fn escape(text: &str) -> String {
+    text.replace('"', "#quot;").replace('\n', "<br/>")
+}
@@ -0,0 +1,37 @@
+//! # Graph Export
+//!
+//! Renders graphs in text formats for viewing outside an editor.
+//!
+//! [`to_dot`] produces Graphviz DOT and [`to_mermaid`] produces a Mermaid
+//! flowchart. Both color nodes by [`NodeTypes`](crate::NodeTypes), draw
+//! execution edges dashed and data edges solid, and put each group (and each
+//! comment box) in its own cluster. [`ExportOptions`] controls the details.
+//!
+//! [`catalog_to_markdown`] and [`catalog_to_html`] render a
+//! [`NodeCatalog`](crate::core::NodeCatalog) as a node reference.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::export::{to_dot, ExportOptions};
+//! use graphy::{Connection, GraphDescription, NodeInstance, Position};
+//!
+//! let mut graph = GraphDescription::new("example");
+//! graph.add_node(NodeInstance::new("a", "math.add", Position::zero()));
+//! graph.add_node(NodeInstance::new("b", "print", Position::zero()));
+//! graph.add_connection(Connection::data("a", "result", "b", "value"));
+//!
+//! let dot = to_dot(&graph, &ExportOptions::new());
+//! assert!(dot.starts_with("digraph \"example\" {"));
+//! assert!(dot.contains("\"a\" -> \"b\""));
+//! ```
+
+pub mod catalog;
+pub mod dot;
+pub mod mermaid;
+pub mod options;
+
+pub use catalog::*;
+pub use dot::*;
+pub use mermaid::*;
+pub use options::*;
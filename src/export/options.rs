@@ -0,0 +1,226 @@
+//! Options and layout helpers shared by the export formats.
+
+use crate::core::{GraphDescription, NodeInstance, NodeMetadataProvider, NodeTypes, PropertyValue};
+use crate::utils::{LanguageProfile, RustProfile};
+use std::collections::HashSet;
+
+/// Layout direction of an exported graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Direction {
+    /// Execution flows left to right
+    #[default]
+    LeftToRight,
+
+    /// Execution flows top to bottom
+    TopToBottom,
+}
+
+/// Options for [`to_dot`](super::to_dot) and [`to_mermaid`](super::to_mermaid)
+#[derive(Clone, Copy)]
+pub struct ExportOptions<'p> {
+    /// Used to look up node kinds for coloring
+    ///
+    /// Without a provider (or for unknown node types) the kind is guessed
+    /// from the node's execution pins.
+    pub metadata_provider: Option<&'p dyn NodeMetadataProvider>,
+
+    /// Layout direction
+    pub direction: Direction,
+
+    /// Label edges with their source and target pins
+    pub show_pins: bool,
+
+    /// List node properties (input constants, settings) in node labels
+    pub show_properties: bool,
+
+    /// Draw groups and comment boxes as clusters
+    pub show_clusters: bool,
+}
+
+impl<'p> ExportOptions<'p> {
+    /// Creates options with pin labels and clusters enabled.
+    pub fn new() -> Self {
+        Self {
+            metadata_provider: None,
+            direction: Direction::LeftToRight,
+            show_pins: true,
+            show_properties: false,
+            show_clusters: true,
+        }
+    }
+
+    /// Sets the provider used to look up node kinds.
+    pub fn with_metadata_provider(mut self, provider: &'p dyn NodeMetadataProvider) -> Self {
+        self.metadata_provider = Some(provider);
+        self
+    }
+
+    /// Sets the layout direction.
+    pub fn with_direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Enables or disables pin labels on edges.
+    pub fn with_pins(mut self, show_pins: bool) -> Self {
+        self.show_pins = show_pins;
+        self
+    }
+
+    /// Enables or disables properties in node labels.
+    pub fn with_properties(mut self, show_properties: bool) -> Self {
+        self.show_properties = show_properties;
+        self
+    }
+
+    /// Enables or disables clusters for groups and comments.
+    pub fn with_clusters(mut self, show_clusters: bool) -> Self {
+        self.show_clusters = show_clusters;
+        self
+    }
+
+    /// Determines the kind of a node.
+    ///
+    /// Uses the metadata provider if it knows the node type. Otherwise nodes
+    /// without execution pins are pure, nodes with execution outputs but no
+    /// execution input are events, and nodes with an execution input are
+    /// functions (one execution output or none) or control flow (several).
+    /// Returns `None` for nodes without any pins and metadata.
+    pub fn node_kind(&self, node: &NodeInstance) -> Option<NodeTypes> {
+        if let Some(metadata) = self
+            .metadata_provider
+            .and_then(|provider| provider.metadata_for_node(node))
+        {
+            return Some(metadata.node_type);
+        }
+
+        let exec_in = node.inputs.iter().any(|pin| pin.pin.data_type.is_execution());
+        let exec_outs = node.outputs.iter().filter(|pin| pin.pin.data_type.is_execution()).count();
+        match (exec_in, exec_outs) {
+            (false, 0) if node.inputs.is_empty() && node.outputs.is_empty() => None,
+            (false, 0) => Some(NodeTypes::pure),
+            (false, _) => Some(NodeTypes::event),
+            (true, 0 | 1) => Some(NodeTypes::fn_),
+            (true, _) => Some(NodeTypes::control_flow),
+        }
+    }
+
+    /// Builds the label of a node: ID, type, and optionally its properties
+    pub(crate) fn node_label_lines(&self, node: &NodeInstance) -> Vec<String> {
+        let mut lines = vec![node.id.clone(), node.node_type.clone()];
+        if self.show_properties {
+            let mut keys: Vec<&String> = node.properties.keys().collect();
+            keys.sort();
+            for key in keys {
+                lines.push(format!("{} = {}", key, property_text(&node.properties[key])));
+            }
+        }
+        lines
+    }
+}
+
+impl Default for ExportOptions<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Fill color used for a node kind
+pub(crate) fn kind_color(kind: Option<NodeTypes>) -> &'static str {
+    match kind {
+        Some(NodeTypes::pure) => "#A5D6A7",
+        Some(NodeTypes::fn_) => "#90CAF9",
+        Some(NodeTypes::control_flow) => "#FFCC80",
+        Some(NodeTypes::event) => "#EF9A9A",
+        None => "#E0E0E0",
+    }
+}
+
+/// Short name of a node kind, used for Mermaid classes
+pub(crate) fn kind_name(kind: Option<NodeTypes>) -> &'static str {
+    match kind {
+        Some(NodeTypes::pure) => "pure",
+        Some(NodeTypes::fn_) => "function",
+        Some(NodeTypes::control_flow) => "control_flow",
+        Some(NodeTypes::event) => "event",
+        None => "unknown",
+    }
+}
+
+pub(crate) fn property_text(value: &PropertyValue) -> String {
+    RustProfile
+        .format_literal(value)
+        .unwrap_or_else(|_| format!("{:?}", value))
+}
+
+/// A group or comment box and the nodes drawn inside it
+pub(crate) struct Cluster<'g> {
+    pub label: &'g str,
+    pub color: Option<&'g str>,
+    pub node_ids: Vec<&'g str>,
+}
+
+/// Splits the graph's nodes into clusters and unclustered nodes
+///
+/// Each node is drawn at most once: in its group if it has one, otherwise in
+/// the first comment attached to it or, for free-standing comments, whose
+/// box contains its position. Empty clusters are skipped, and node IDs are
+/// sorted.
+pub(crate) fn layout_clusters<'g>(
+    graph: &'g GraphDescription,
+    options: &ExportOptions<'_>,
+) -> (Vec<Cluster<'g>>, Vec<&'g str>) {
+    let mut node_ids: Vec<&str> = graph.nodes.keys().map(String::as_str).collect();
+    node_ids.sort_unstable();
+
+    if !options.show_clusters {
+        return (Vec::new(), node_ids);
+    }
+
+    let mut clustered: HashSet<&str> = HashSet::new();
+    let mut clusters = Vec::new();
+
+    for group in &graph.groups {
+        let mut members: Vec<&str> = group
+            .node_ids
+            .iter()
+            .map(String::as_str)
+            .filter(|id| graph.nodes.contains_key(*id) && clustered.insert(id))
+            .collect();
+        members.sort_unstable();
+        if !members.is_empty() {
+            clusters.push(Cluster {
+                label: &group.title,
+                color: group.color.as_deref(),
+                node_ids: members,
+            });
+        }
+    }
+
+    for comment in &graph.comments {
+        let (x, y) = (comment.position.x, comment.position.y);
+        let (width, height) = comment.size;
+        let members: Vec<&str> = node_ids
+            .iter()
+            .copied()
+            .filter(|id| {
+                if comment.is_attached() {
+                    return comment.is_attached_to(id);
+                }
+                let position = graph.nodes[*id].position;
+                position.x >= x && position.x <= x + width && position.y >= y && position.y <= y + height
+            })
+            .filter(|id| clustered.insert(id))
+            .collect();
+        if !members.is_empty() {
+            clusters.push(Cluster {
+                label: &comment.text,
+                color: None,
+                node_ids: members,
+            });
+        }
+    }
+
+    node_ids.retain(|id| !clustered.contains(id));
+    (clusters, node_ids)
+}
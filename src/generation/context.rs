@@ -1,10 +1,165 @@
 //! # Code Generation Context
 //!
 //! Shared context and state for code generation.
+//!
+//! # Expression caching
+//!
+//! Pure node expressions are cached per node ID, so a pure node feeding many
+//! consumers is rendered once. The [`ExpressionPolicy`] decides whether a
+//! cached expression is inlined at every use or hoisted into a variable
+//! binding the first time it is needed. Whatever the policy, an expression
+//! nesting too many pure nodes, or growing too long, is bound too (see
+//! [`InlineLimits`]), so long chains become a series of bindings instead of
+//! one huge expression.
+//!
+//! Cache entries are scoped to the indentation level they were created at:
+//! [`pop_indent`](CodeGeneratorContext::pop_indent) drops entries from the
+//! closed block, so a binding hoisted inside one branch is never referenced
+//! from a sibling branch. Generators must use `push_indent`/`pop_indent` for
+//! every block they open for this to hold.
 
-use crate::analysis::{DataResolver, ExecutionRouting};
+use crate::analysis::{AnalysisContext, AsyncAnalysis, CoercionTable, DataResolver, ExecutionRouting};
 use crate::core::{GraphDescription, NodeMetadataProvider};
+use super::{CacheStats, CodeBuffer, DebugHooks, ExecStep, HelperPlan, ParallelCodegen, SourceMap, ValueProbes};
+use crate::utils::{CancellationToken, LanguageProfile, RustProfile};
+use crate::GraphyError;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::cell::Cell;
 use std::collections::HashSet;
+use std::sync::Arc;
+
+/// How pure node expressions are placed in the generated code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpressionPolicy {
+    /// Inline the expression at every use
+    AlwaysInline,
+
+    /// Bind every pure node to a variable the first time it is used
+    HoistToLet,
+
+    /// Hoist pure nodes read by at least `min_fan_out` distinct nodes
+    Auto {
+        /// Minimum number of consumer nodes before hoisting
+        min_fan_out: usize,
+    },
+}
+
+impl Default for ExpressionPolicy {
+    fn default() -> Self {
+        ExpressionPolicy::Auto { min_fan_out: 2 }
+    }
+}
+
+/// How deep and long inlined pure expressions may get before they are bound
+///
+/// A pure node whose inputs are all variables, constants, or defaults nests
+/// one pure node; a node reading inlined expressions nests one more than the
+/// deepest of them. A node reaching [`max_depth`](Self::max_depth), or whose
+/// expression is longer than [`max_length`](Self::max_length) bytes, is bound
+/// to its result variable, and its readers start over from there. A limit of
+/// `0` turns it off.
+///
+/// # Example
+///
+/// ```
+/// use graphy::generation::{CompileOptions, InlineLimits};
+///
+/// let options = CompileOptions::new().with_inline_limits(InlineLimits::new().with_max_depth(8));
+/// assert_eq!(options.inline_limits.max_length, InlineLimits::default().max_length);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InlineLimits {
+    /// Pure nodes nested in one expression, at most (default 32)
+    pub max_depth: usize,
+
+    /// Bytes in one inlined expression, at most (default 1024)
+    pub max_length: usize,
+}
+
+impl Default for InlineLimits {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InlineLimits {
+    /// The default limits: 32 nested pure nodes and 1024 bytes
+    pub fn new() -> Self {
+        Self {
+            max_depth: 32,
+            max_length: 1024,
+        }
+    }
+
+    /// No limits, inlining as the expression policy says
+    pub fn unlimited() -> Self {
+        Self {
+            max_depth: 0,
+            max_length: 0,
+        }
+    }
+
+    /// Bind expressions nesting `max_depth` pure nodes, or never with `0`
+    #[must_use]
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Bind expressions longer than `max_length` bytes, or never with `0`
+    #[must_use]
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = max_length;
+        self
+    }
+
+    /// Check if an expression nesting `depth` pure nodes has to be bound
+    pub fn exceeded_by(&self, depth: usize, expr: &str) -> bool {
+        (self.max_depth > 0 && depth >= self.max_depth) || (self.max_length > 0 && expr.len() > self.max_length)
+    }
+}
+
+/// A cached expression and the indentation level it is valid in
+#[derive(Debug, Clone)]
+struct CachedExpression {
+    expr: String,
+    scope: usize,
+
+    /// Pure nodes nested in the expression, `0` for variables
+    depth: usize,
+}
+
+/// Mutable code generation state, detached from a context between steps
+///
+/// Lets a [`CompilationSession`](super::CompilationSession) own its output
+/// and caches while no context (and none of its borrows) exists.
+#[derive(Debug, Default)]
+pub(crate) struct GenerationState {
+    visited: HashSet<String>,
+    indent_level: usize,
+    output: CodeBuffer,
+    expression_cache: FxHashMap<String, CachedExpression>,
+    expression_cache_hits: usize,
+    expression_cache_misses: usize,
+}
+
+impl GenerationState {
+    /// Expression cache hit and miss counts so far
+    pub(crate) fn expression_cache_stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.expression_cache_hits,
+            misses: self.expression_cache_misses,
+        }
+    }
+
+    /// Take the generated code and its source map
+    pub(crate) fn finish(self) -> (String, SourceMap) {
+        self.output.finish()
+    }
+}
 
 /// Context for code generation
 ///
@@ -22,11 +177,57 @@ pub struct CodeGeneratorContext<'a, P: NodeMetadataProvider> {
     /// Execution routing table
     pub exec_routing: &'a ExecutionRouting,
 
+    /// Results of custom analysis passes (if run through a `PassManager`)
+    pub analysis: Option<&'a AnalysisContext>,
+
     /// Visited nodes (for cycle detection)
     pub visited: HashSet<String>,
 
     /// Current indentation level
     pub indent_level: usize,
+
+    /// Generated code, with a source map back to the nodes
+    pub output: CodeBuffer,
+
+    /// How pure node expressions are placed
+    pub expression_policy: ExpressionPolicy,
+
+    /// How deep and long inlined expressions may get, whatever the policy
+    pub inline_limits: InlineLimits,
+
+    /// Identifier, default value, and literal rules of the target language
+    pub language: Arc<dyn LanguageProfile>,
+
+    /// Events and nodes that need async code
+    pub async_analysis: AsyncAnalysis,
+
+    /// Stops generation when cancelled, see [`check_cancelled`](Self::check_cancelled)
+    pub cancellation: CancellationToken,
+
+    /// Which pure inputs are evaluated concurrently, if any (see [`ParallelCodegen`])
+    pub parallel: Option<ParallelCodegen>,
+
+    /// Repeated branches generated as helper functions, if any
+    pub(crate) branch_helpers: Option<Arc<HelperPlan>>,
+
+    /// Index of the helper whose body is being generated
+    pub(crate) current_helper: Option<usize>,
+
+    /// Host functions called at flagged nodes, in debug builds (see [`DebugHooks`])
+    pub debug: Option<DebugHooks>,
+
+    /// Sink reporting the values of watched pins (see [`ValueProbes`])
+    pub probes: Option<ValueProbes>,
+
+    /// Maps node_id -> cached expression (or hoisted variable name)
+    expression_cache: FxHashMap<String, CachedExpression>,
+
+    /// Expression cache hits and misses (hits are counted behind `&self`)
+    expression_cache_hits: Cell<usize>,
+    expression_cache_misses: usize,
+
+    /// Steps scheduled by the generator hook running now
+    scheduled: Vec<ExecStep>,
 }
 
 impl<'a, P: NodeMetadataProvider> CodeGeneratorContext<'a, P> {
@@ -41,11 +242,136 @@ impl<'a, P: NodeMetadataProvider> CodeGeneratorContext<'a, P> {
             metadata_provider,
             data_resolver,
             exec_routing,
+            analysis: None,
             visited: HashSet::new(),
             indent_level: 0,
+            output: CodeBuffer::new(),
+            expression_policy: ExpressionPolicy::default(),
+            inline_limits: InlineLimits::default(),
+            language: Arc::new(RustProfile),
+            async_analysis: AsyncAnalysis::default(),
+            cancellation: CancellationToken::new(),
+            parallel: None,
+            branch_helpers: None,
+            current_helper: None,
+            debug: None,
+            probes: None,
+            expression_cache: FxHashMap::default(),
+            expression_cache_hits: Cell::new(0),
+            expression_cache_misses: 0,
+            scheduled: Vec::new(),
+        }
+    }
+
+    /// Continue from state detached with [`into_state`](Self::into_state)
+    pub(crate) fn with_state(mut self, state: GenerationState) -> Self {
+        self.visited = state.visited;
+        self.indent_level = state.indent_level;
+        self.output = state.output;
+        self.expression_cache = state.expression_cache;
+        self.expression_cache_hits = Cell::new(state.expression_cache_hits);
+        self.expression_cache_misses = state.expression_cache_misses;
+        self
+    }
+
+    /// Detach the mutable generation state, ending the context's borrows
+    pub(crate) fn into_state(self) -> GenerationState {
+        GenerationState {
+            visited: self.visited,
+            indent_level: self.indent_level,
+            output: self.output,
+            expression_cache: self.expression_cache,
+            expression_cache_hits: self.expression_cache_hits.get(),
+            expression_cache_misses: self.expression_cache_misses,
         }
     }
 
+    /// Create a context from the results of a `PassManager` run
+    ///
+    /// The analysis context must contain a `DataResolver` and an
+    /// `ExecutionRouting` (see `DataFlowPass` and `ExecutionRoutingPass`).
+    pub fn from_analysis(
+        graph: &'a GraphDescription,
+        metadata_provider: &'a P,
+        analysis: &'a AnalysisContext,
+    ) -> Result<Self, GraphyError> {
+        let data_resolver = analysis.get::<DataResolver>().ok_or_else(|| {
+            GraphyError::CodeGeneration("Analysis is missing DataResolver".to_string())
+        })?;
+        let exec_routing = analysis.get::<ExecutionRouting>().ok_or_else(|| {
+            GraphyError::CodeGeneration("Analysis is missing ExecutionRouting".to_string())
+        })?;
+
+        Ok(Self::new(graph, metadata_provider, data_resolver, exec_routing).with_analysis(analysis))
+    }
+
+    /// Attach custom analysis results to this context
+    pub fn with_analysis(mut self, analysis: &'a AnalysisContext) -> Self {
+        self.analysis = Some(analysis);
+        self
+    }
+
+    /// Get a custom analysis result by type
+    ///
+    /// Returns `None` if no analysis is attached or the result is missing.
+    pub fn get_analysis<T: Any>(&self) -> Option<&'a T> {
+        self.analysis.and_then(|analysis| analysis.get::<T>())
+    }
+
+    /// Apply any required type coercion to the expression feeding an input
+    ///
+    /// Uses the `CoercionTable` from a `TypeCheckPass`, if one was run.
+    /// Returns the expression unchanged otherwise.
+    pub fn coerce_input(&self, node_id: &str, pin_name: &str, expr: &str) -> String {
+        match self.get_analysis::<CoercionTable>() {
+            Some(table) => table.apply(node_id, pin_name, expr).into_owned(),
+            None => expr.to_string(),
+        }
+    }
+
+    /// Append generated code that doesn't belong to a single node
+    pub fn emit(&mut self, code: &str) {
+        self.output.push_str(code);
+    }
+
+    /// Append code generated by a node (and optionally one of its pins)
+    ///
+    /// The code is recorded in the source map so compiler errors and panics
+    /// in the output can be traced back to the node.
+    pub fn emit_for_node(&mut self, node_id: &str, pin_name: Option<&str>, code: &str) {
+        self.output.write_node(node_id, pin_name, code);
+    }
+
+    /// Get the source map for the code emitted so far
+    pub fn source_map(&self) -> &SourceMap {
+        self.output.source_map()
+    }
+
+    /// Take the generated code and its source map, leaving the output empty
+    pub fn take_output(&mut self) -> (String, SourceMap) {
+        std::mem::take(&mut self.output).finish()
+    }
+
+    /// Schedule execution flow steps to run after the current hook returns
+    ///
+    /// Control flow generators can schedule their branches (e.g. `Indent`,
+    /// an `Output` step per exec pin, `Dedent`, and `Emit` for the code in
+    /// between) instead of calling
+    /// [`generate_exec_output`](super::generate_exec_output) from inside the
+    /// hook. The driver then runs them in its own loop, so nesting doesn't
+    /// grow the stack either. Steps scheduled by `generate_event`,
+    /// `generate_function_node`, and `generate_control_flow` run right after
+    /// the hook, in order, inside the node's source map region; steps
+    /// scheduled by other hooks run after the code of the whole node.
+    pub fn schedule(&mut self, steps: impl IntoIterator<Item = ExecStep>) {
+        self.scheduled.extend(steps);
+    }
+
+    /// Take the steps scheduled so far
+    pub(crate) fn take_scheduled(&mut self) -> Vec<ExecStep> {
+        std::mem::take(&mut self.scheduled)
+    }
+
     /// Get current indentation string
     pub fn indent(&self) -> String {
         "    ".repeat(self.indent_level)
@@ -57,9 +383,180 @@ impl<'a, P: NodeMetadataProvider> CodeGeneratorContext<'a, P> {
     }
 
     /// Decrease indentation level
+    ///
+    /// Drops cached expressions created inside the closed block.
     pub fn pop_indent(&mut self) {
         if self.indent_level > 0 {
             self.indent_level -= 1;
+            let level = self.indent_level;
+            self.expression_cache.retain(|_, cached| cached.scope <= level);
+        }
+    }
+
+    /// Set the expression placement policy
+    pub fn with_expression_policy(mut self, policy: ExpressionPolicy) -> Self {
+        self.expression_policy = policy;
+        self
+    }
+
+    /// Set how deep and long inlined expressions may get
+    ///
+    /// See [`CompileOptions::with_inline_limits`](super::CompileOptions::with_inline_limits).
+    pub fn with_inline_limits(mut self, limits: InlineLimits) -> Self {
+        self.inline_limits = limits;
+        self
+    }
+
+    /// Set the target language profile
+    ///
+    /// The `DataResolver` should be built with the same profile so result
+    /// variables and constants match.
+    pub fn with_language(mut self, language: Arc<dyn LanguageProfile>) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// Set which events and nodes need async code
+    pub fn with_async_analysis(mut self, async_analysis: AsyncAnalysis) -> Self {
+        self.async_analysis = async_analysis;
+        self
+    }
+
+    /// Set the token that cancels generation
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// Evaluate expensive pure inputs concurrently, or not with `None`
+    ///
+    /// Only applies to generators that
+    /// [support it](super::CodeGenerator::supports_parallel).
+    pub fn with_parallel_codegen(mut self, parallel: Option<ParallelCodegen>) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    /// Emit debug hooks at flagged nodes, or not with `None`
+    ///
+    /// See [`CompileOptions::with_debug`](super::CompileOptions::with_debug).
+    pub fn with_debug(mut self, debug: Option<DebugHooks>) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    /// Report the values of watched pins, or not with `None`
+    ///
+    /// See [`CompileOptions::with_probes`](super::CompileOptions::with_probes).
+    pub fn with_probes(mut self, probes: Option<ValueProbes>) -> Self {
+        self.probes = probes;
+        self
+    }
+
+    /// Generate repeated branches as calls to the plan's helpers
+    pub(crate) fn with_branch_helpers(mut self, branch_helpers: Option<Arc<HelperPlan>>) -> Self {
+        self.branch_helpers = branch_helpers;
+        self
+    }
+
+    /// The parameter feeding an input of the helper body being generated
+    pub(crate) fn helper_argument(&self, node_id: &str, pin_name: &str) -> Option<&str> {
+        let helper = self.current_helper?;
+        self.branch_helpers.as_ref()?.argument(helper, node_id, pin_name)
+    }
+
+    /// Fail with [`GraphyError::Cancelled`] if generation was cancelled
+    ///
+    /// The driver checks before every node it generates; generators doing
+    /// expensive work of their own can check in between.
+    pub fn check_cancelled(&self) -> Result<(), GraphyError> {
+        self.cancellation.check()
+    }
+
+    /// Check if an event has to be generated as an async function
+    pub fn is_async_event(&self, event_id: &str) -> bool {
+        self.async_analysis.is_async_event(event_id)
+    }
+
+    /// Get the cached expression (or hoisted variable) for a node
+    ///
+    /// Found expressions count as cache hits.
+    pub fn cached_expression(&self, node_id: &str) -> Option<&str> {
+        let cached = self.expression_cache.get(node_id)?;
+        self.expression_cache_hits.set(self.expression_cache_hits.get() + 1);
+        Some(cached.expr.as_str())
+    }
+
+    /// Check if a node has a cached expression, without counting a hit
+    pub(crate) fn is_cached(&self, node_id: &str) -> bool {
+        self.expression_cache.contains_key(node_id)
+    }
+
+    /// Cache the expression for a node at the current indentation level
+    ///
+    /// Counts as a cache miss (the expression had to be generated).
+    pub fn cache_expression(&mut self, node_id: &str, expr: impl Into<String>) {
+        self.cache_inline_expression(node_id, expr, 0);
+    }
+
+    /// Cache an inlined expression nesting `depth` pure nodes
+    pub(crate) fn cache_inline_expression(&mut self, node_id: &str, expr: impl Into<String>, depth: usize) {
+        self.expression_cache_misses += 1;
+        self.expression_cache.insert(
+            node_id.to_string(),
+            CachedExpression {
+                expr: expr.into(),
+                scope: self.indent_level,
+                depth,
+            },
+        );
+    }
+
+    /// Pure nodes nested in a node's cached expression
+    ///
+    /// `0` for variables, and for nodes without a cached expression.
+    pub(crate) fn inline_depth(&self, node_id: &str) -> usize {
+        self.expression_cache.get(node_id).map_or(0, |cached| cached.depth)
+    }
+
+    /// Remove a node's cached expression
+    ///
+    /// Call this when something the expression depends on changes (e.g. a
+    /// variable it reads is reassigned).
+    pub fn invalidate_expression(&mut self, node_id: &str) {
+        self.expression_cache.remove(node_id);
+    }
+
+    /// Remove all cached expressions
+    pub fn clear_expression_cache(&mut self) {
+        self.expression_cache.clear();
+    }
+
+    /// Get the expression cache hit and miss counts so far
+    pub fn expression_cache_stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.expression_cache_hits.get(),
+            misses: self.expression_cache_misses,
+        }
+    }
+
+    /// Check if the value of a node's output pin is reported to the probes
+    ///
+    /// Generators bind the result of function nodes with a probed
+    /// [`RESULT_PIN`](crate::core::RESULT_PIN) to their result variable,
+    /// even if no node reads it.
+    pub fn is_probed(&self, node_id: &str, pin: &str) -> bool {
+        self.probes.is_some() && self.graph.nodes.get(node_id).is_some_and(|node| node.is_watched(pin))
+    }
+
+    /// Check if a pure node should be hoisted into a variable binding
+    pub fn should_hoist(&self, node_id: &str) -> bool {
+        match self.expression_policy {
+            ExpressionPolicy::AlwaysInline => false,
+            ExpressionPolicy::HoistToLet => true,
+            ExpressionPolicy::Auto { min_fan_out } => {
+                self.data_resolver.get_dependents(node_id).len() >= min_fan_out
+            }
         }
     }
 
@@ -0,0 +1,92 @@
+//! # Debug Builds
+//!
+//! Hooks into the host at the nodes an editor marks for debugging.
+//!
+//! With [`CompileOptions::with_debug`](super::CompileOptions::with_debug),
+//! the driver emits calls to host functions at function and control flow
+//! nodes with debug flags:
+//!
+//! - [`NodeInstance::breakpoint`]: a call to the hit function with the node
+//!   ID, right before the node runs, so the host can pause there
+//! - [`NodeInstance::trace`]: the node's inputs are bound to variables first
+//!   and passed by reference to the trace function, one call per input
+//!
+//! ```text
+//! let trace_print_1_message = format_score(score);
+//! __graphy_debug_trace("print_1", "message", &trace_print_1_message);
+//! __graphy_debug_hit("print_1");
+//! print(trace_print_1_message);
+//! ```
+//!
+//! The host provides both functions. Without the option, debug flags are
+//! ignored and the generated code is unchanged, so release builds pay
+//! nothing for them. Generators emitting other languages override
+//! [`CodeGenerator::generate_debug_hit`](super::CodeGenerator::generate_debug_hit)
+//! and [`CodeGenerator::generate_debug_trace`](super::CodeGenerator::generate_debug_trace).
+
+use crate::core::NodeInstance;
+use serde::{Deserialize, Serialize};
+
+/// Host functions called by debug builds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DebugHooks {
+    /// Called with the node ID before a node with a breakpoint runs
+    pub hit_function: String,
+
+    /// Called with the node ID, the pin name, and a reference to the value
+    /// for every input of a traced node
+    pub trace_function: String,
+}
+
+impl Default for DebugHooks {
+    /// `__graphy_debug_hit` and `__graphy_debug_trace`
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DebugHooks {
+    /// Call `__graphy_debug_hit` and `__graphy_debug_trace`
+    pub fn new() -> Self {
+        Self {
+            hit_function: "__graphy_debug_hit".to_string(),
+            trace_function: "__graphy_debug_trace".to_string(),
+        }
+    }
+
+    /// Name the function called at breakpoints
+    pub fn with_hit_function(mut self, hit_function: impl Into<String>) -> Self {
+        self.hit_function = hit_function.into();
+        self
+    }
+
+    /// Name the function called with traced values
+    pub fn with_trace_function(mut self, trace_function: impl Into<String>) -> Self {
+        self.trace_function = trace_function.into();
+        self
+    }
+
+    /// The call reporting that a node is about to run, e.g.
+    /// `__graphy_debug_hit("print_1")`
+    pub fn hit_call(&self, node: &NodeInstance) -> String {
+        format!("{}({})", self.hit_function, string_literal(&node.id))
+    }
+
+    /// The call reporting the value of a node's input, e.g.
+    /// `__graphy_debug_trace("print_1", "message", &value)`
+    pub fn trace_call(&self, node: &NodeInstance, pin_name: &str, variable: &str) -> String {
+        format!(
+            "{}({}, {}, &{})",
+            self.trace_function,
+            string_literal(&node.id),
+            string_literal(pin_name),
+            variable
+        )
+    }
+}
+
+/// A double-quoted literal with `\` and `"` escaped, valid in Rust and C
+pub(super) fn string_literal(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
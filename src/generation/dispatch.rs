@@ -0,0 +1,165 @@
+//! # Event Dispatchers
+//!
+//! Generates one entry point for all the events of a graph.
+//!
+//! With [`CompileOptions::with_dispatcher`](super::CompileOptions::with_dispatcher),
+//! the driver runs an [`EventAnalysis`] after the events and emits a struct
+//! implementing a host trait, with one method per event forwarding to its
+//! handler. The struct owns the graph's state, if it has variables:
+//!
+//! ```text
+//! pub trait GameEvents {
+//!     fn on_hit(&mut self, damage: i64);
+//!     fn on_start(&mut self);
+//! }
+//!
+//! pub struct GraphDispatcher {
+//!     pub state: GraphState,
+//! }
+//!
+//! impl GameEvents for GraphDispatcher {
+//!     fn on_hit(&mut self, damage: i64) {
+//!         on_hit(&mut self.state, damage)
+//!     }
+//!
+//!     fn on_start(&mut self) {
+//!         on_start(&mut self.state)
+//!     }
+//! }
+//! ```
+//!
+//! Methods and handlers are named after the event node IDs, the state is
+//! passed as the first argument, and the params follow in metadata order.
+//! Generators naming their handlers differently override
+//! [`CodeGenerator::generate_dispatcher`](super::CodeGenerator::generate_dispatcher).
+
+use crate::analysis::{EventAnalysis, EventEntry};
+use crate::core::GraphDescription;
+use crate::utils::{LanguageProfile, RustProfile};
+use serde::{Deserialize, Serialize};
+
+/// Options for generating an event dispatcher.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventDispatcher {
+    /// Trait the dispatcher implements, with one method per event
+    pub trait_name: String,
+
+    /// Name of the dispatcher struct
+    pub struct_name: String,
+
+    /// Declare the trait alongside the dispatcher instead of expecting the
+    /// host to
+    pub declare_trait: bool,
+}
+
+impl Default for EventDispatcher {
+    fn default() -> Self {
+        Self::new("GraphEvents")
+    }
+}
+
+impl EventDispatcher {
+    /// Dispatch events through a trait the host declares, with a struct
+    /// named `GraphDispatcher`
+    pub fn new(trait_name: impl Into<String>) -> Self {
+        Self {
+            trait_name: trait_name.into(),
+            struct_name: "GraphDispatcher".to_string(),
+            declare_trait: false,
+        }
+    }
+
+    /// Name the dispatcher struct
+    pub fn with_struct_name(mut self, struct_name: impl Into<String>) -> Self {
+        self.struct_name = struct_name.into();
+        self
+    }
+
+    /// Declare the trait alongside the dispatcher
+    pub fn with_trait_declaration(mut self, declare_trait: bool) -> Self {
+        self.declare_trait = declare_trait;
+        self
+    }
+}
+
+/// Helper for building a Rust dispatcher over a graph's events
+///
+/// Emits the trait (if [`EventDispatcher::declare_trait`] is set), a struct
+/// holding the `GraphState` of [`graph_state_struct`](super::graph_state_struct)
+/// if the graph declares variables, and an impl of the trait forwarding each
+/// method to the event's handler. Events running async nodes get `async`
+/// methods awaiting their handler.
+///
+/// # Example
+///
+/// ```
+/// use graphy::analysis::EventAnalysis;
+/// use graphy::generation::{event_dispatcher, EventDispatcher};
+/// use graphy::GraphDescription;
+///
+/// let graph = GraphDescription::new("empty");
+/// let dispatcher = EventDispatcher::new("GameEvents").with_trait_declaration(true);
+///
+/// let code = event_dispatcher(&graph, &EventAnalysis::default(), &dispatcher);
+/// assert!(code.contains("pub trait GameEvents {\n}\n"));
+/// assert!(code.contains("pub struct GraphDispatcher;\n"));
+/// assert!(code.contains("impl GameEvents for GraphDispatcher {\n}\n"));
+/// ```
+pub fn event_dispatcher(graph: &GraphDescription, events: &EventAnalysis, dispatcher: &EventDispatcher) -> String {
+    let has_state = !graph.variables.is_empty();
+    let mut code = String::new();
+
+    if dispatcher.declare_trait {
+        code.push_str(&format!("pub trait {} {{\n", dispatcher.trait_name));
+        for event in events.events() {
+            code.push_str(&format!("    {};\n", method_signature(event)));
+        }
+        code.push_str("}\n\n");
+    }
+
+    if has_state {
+        code.push_str(&format!(
+            "pub struct {} {{\n    pub state: GraphState,\n}}\n\n",
+            dispatcher.struct_name
+        ));
+    } else {
+        code.push_str(&format!("pub struct {};\n\n", dispatcher.struct_name));
+    }
+
+    code.push_str(&format!("impl {} for {} {{\n", dispatcher.trait_name, dispatcher.struct_name));
+    for (index, event) in events.events().iter().enumerate() {
+        if index > 0 {
+            code.push('\n');
+        }
+        let mut args: Vec<String> = Vec::with_capacity(event.params.len() + 1);
+        if has_state {
+            args.push("&mut self.state".to_string());
+        }
+        args.extend(event.params.iter().map(|param| RustProfile.sanitize_identifier(&param.name)));
+
+        let call = format!("{}({})", RustProfile.sanitize_identifier(&event.node_id), args.join(", "));
+        let awaited = if event.is_async { format!("{}.await", call) } else { call };
+        code.push_str(&format!("    {} {{\n        {}\n    }}\n", method_signature(event), awaited));
+    }
+    code.push_str("}\n");
+    code
+}
+
+/// `fn <event>(&mut self, <params>)`, async if the event is
+fn method_signature(event: &EventEntry) -> String {
+    let mut params = vec!["&mut self".to_string()];
+    params.extend(
+        event
+            .params
+            .iter()
+            .map(|param| format!("{}: {}", RustProfile.sanitize_identifier(&param.name), param.param_type)),
+    );
+    let asyncness = if event.is_async { "async " } else { "" };
+    format!(
+        "{}fn {}({})",
+        asyncness,
+        RustProfile.sanitize_identifier(&event.node_id),
+        params.join(", ")
+    )
+}
@@ -0,0 +1,1686 @@
+//! # Compilation Driver
+//!
+//! Runs the full pipeline from graph to code around a [`CodeGenerator`].
+//!
+//! [`compile`] performs sub-graph expansion, data flow resolution, and
+//! execution routing, then walks the execution flow from every event node
+//! and calls the generator's hooks. [`compile_with_options`] can also
+//! collect a [`CompilationReport`] of phase timings and counters.
+//! [`compile_event`] compiles only the part of the graph one event runs, for
+//! previews and hot reloading. Generators that need finer control can
+//! use [`generate_exec_output`], [`bind_event_params`], and
+//! [`resolve_node_arguments`] from their own hooks.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use graphy::generation::compile;
+//!
+//! let mut generator = MyRustGenerator::default();
+//! let code = compile(&graph, &provider, &mut generator)?;
+//! ```
+
+use super::{
+    parallel_sources, BranchHelpers, CodeGenerator, CodeGeneratorContext, CompilationReport, DebugHooks, EventDispatcher, ExecStep,
+    ExecWalk, HelperPlan, InlineLimits, MemoKey, MemoizedCall, OptLevel, ParallelCodegen, SourceMap, ValueProbes,
+};
+use crate::analysis::{
+    AsyncAnalysis, BuildStrategy, CommonSubexpressions, CompactGraph, DataResolver, DataSource, EvaluationOrder, EventAnalysis,
+    ExecutionRouting, ParallelThreshold, PassTiming, Reachability, SideEffectOrdering, UnknownNodePolicy, VariableUsage,
+};
+use crate::core::{
+    resolve_switch_cases, CompileConfig, ConnectionType, ExternalRegistry, GraphDescription, NodeInstance, NodeMetadata, NodeMetadataProvider,
+    NodeTypes, ParamInfo, ERROR_EXEC_PIN, ERROR_VALUE_PIN, RESULT_PIN, SWITCH_DEFAULT_PIN, VARIABLE_VALUE_PIN,
+};
+use crate::utils::logging::{self, Verbosity};
+use crate::utils::timing::Stopwatch;
+use crate::utils::{
+    apply_rules, CancellationToken, LanguageProfile, NoProgress, PhaseProgress, ProgressSink, RewriteRule,
+    SubGraphExpander, PHASE_CODEGEN, PHASE_EXPANSION,
+};
+use crate::io::Format;
+use crate::GraphyError;
+use rustc_hash::FxHashSet;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::borrow::Borrow;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Compile a graph to code with the given generator
+///
+/// # Errors
+///
+/// Fails if expansion or analysis fails (e.g. cyclic data dependencies,
+/// undeclared variables, unconnected required inputs, inputs with more
+/// connections than their [`PinMultiplicity`](crate::core::PinMultiplicity)
+/// accepts, or results read where
+/// their node may not have run), if a node on the execution path has no metadata,
+/// if a node requires a version of its type the provider doesn't have (see
+/// [`NodeTypeId`](crate::core::NodeTypeId)), if a node's type is unknown
+/// to the provider (see [`CompileOptions::with_unknown_node_policy`]), if the
+/// engine doesn't provide a declared external (see
+/// [`CompileOptions::with_externals`]), if an event runs async nodes but the generator doesn't support async code,
+/// or if a generator hook returns an error.
+pub fn compile<P, G>(graph: &GraphDescription, metadata_provider: &P, generator: &mut G) -> Result<String, GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    compile_with_source_map(graph, metadata_provider, generator).map(|(code, _)| code)
+}
+
+/// Compile a graph to code, also returning the source map for the output
+///
+/// See [`compile`].
+pub fn compile_with_source_map<P, G>(
+    graph: &GraphDescription,
+    metadata_provider: &P,
+    generator: &mut G,
+) -> Result<(String, SourceMap), GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    compile_with_options(graph, metadata_provider, generator, &CompileOptions::default())
+        .map(|output| (output.code, output.source_map))
+}
+
+/// Options for [`compile_with_options`]
+///
+/// The same options drive expansion, analysis, and generation. They
+/// serialize, so a project can commit them (e.g. as `graphy.toml`, see
+/// [`load`](Self::load)) and adjust them per call with the builders or
+/// [`with_overrides`](Self::with_overrides). Missing fields take their
+/// defaults and unknown fields are errors. The cancellation token, progress
+/// sink, and rewrite rules only exist at runtime and are never serialized.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CompileOptions {
+    /// Collect a [`CompilationReport`]
+    pub report: bool,
+
+    /// How much to log while compiling
+    pub verbosity: Verbosity,
+
+    /// Evaluate equivalent pure nodes once, see [`CommonSubexpressions`]
+    pub common_subexpressions: bool,
+
+    /// How much to optimize the IR, see [`OptLevel`]
+    pub opt_level: OptLevel,
+
+    /// Stops compilation when cancelled
+    #[serde(skip)]
+    pub cancellation: CancellationToken,
+
+    /// Receives progress updates while compiling
+    #[serde(skip)]
+    pub progress: Option<Arc<dyn ProgressSink>>,
+
+    /// Rules applied to the expanded graph before analysis, see [`apply_rules`]
+    #[serde(skip)]
+    pub rewrite_rules: Vec<RewriteRule>,
+
+    /// What to do with nodes of types the provider doesn't know
+    pub on_unknown_node: UnknownNodePolicy,
+
+    /// Externals the engine provides, checked against the graph's declarations
+    pub externals: Option<ExternalRegistry>,
+
+    /// Features and platform deciding which conditional nodes are compiled
+    pub config: CompileConfig,
+
+    /// How pure nodes ready at the same time are ordered
+    pub evaluation_order: EvaluationOrder,
+
+    /// When data flow analysis runs in parallel, see [`ParallelThreshold`]
+    pub parallel_threshold: ParallelThreshold,
+
+    /// Evaluate expensive independent pure inputs concurrently, see [`ParallelCodegen`]
+    pub parallel: Option<ParallelCodegen>,
+
+    /// Generate repeated branches once, as helper functions, see [`BranchHelpers`]
+    pub branch_helpers: Option<BranchHelpers>,
+
+    /// Generate a dispatcher over the events, see [`EventDispatcher`]
+    pub dispatcher: Option<EventDispatcher>,
+
+    /// Emit debug hooks at flagged nodes, see [`DebugHooks`]
+    pub debug: Option<DebugHooks>,
+
+    /// Report the values of watched pins, see [`ValueProbes`]
+    pub probes: Option<ValueProbes>,
+
+    /// How deep and long inlined pure expressions may get, see [`InlineLimits`]
+    pub inline_limits: InlineLimits,
+}
+
+impl CompileOptions {
+    /// Create the default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read options from a file, in the format of its extension
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::Io`] if reading fails, and
+    /// [`GraphyError::Serialization`] if the extension isn't a known
+    /// [`Format`], or the file isn't valid options.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, GraphyError> {
+        let path = path.as_ref();
+        let format = options_format(path)?;
+        let bytes = std::fs::read(path).map_err(|e| GraphyError::Io(format!("{}: {}", path.display(), e)))?;
+        format.deserialize(&bytes)
+    }
+
+    /// Write the options to a file, in the format of its extension
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::Serialization`] if the extension isn't a known
+    /// [`Format`] or serialization fails, and [`GraphyError::Io`] if writing
+    /// fails.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), GraphyError> {
+        let path = path.as_ref();
+        let bytes = options_format(path)?.serialize(self)?;
+        std::fs::write(path, bytes).map_err(|e| GraphyError::Io(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Override the fields present in a partial options document
+    ///
+    /// Nested tables are merged field by field, other values replace the
+    /// current ones, and `null` turns an optional feature off. Runtime-only
+    /// options are kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::Serialization`] if the merged document isn't
+    /// valid options, e.g. for an unknown field.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::generation::{CompileOptions, OptLevel};
+    /// use serde_json::json;
+    ///
+    /// let options = CompileOptions::new()
+    ///     .with_report(true)
+    ///     .with_overrides(&json!({ "opt_level": "basic", "config": { "target_platform": "web" } }))
+    ///     .unwrap();
+    /// assert!(options.report);
+    /// assert_eq!(options.opt_level, OptLevel::Basic);
+    /// assert_eq!(options.config.target_platform.as_deref(), Some("web"));
+    /// ```
+    pub fn with_overrides(self, overrides: &Value) -> Result<Self, GraphyError> {
+        let serialization_error = |error: serde_json::Error| GraphyError::Serialization(error.to_string());
+        let mut document = serde_json::to_value(&self).map_err(serialization_error)?;
+        merge_document(&mut document, overrides);
+        let options: Self = serde_json::from_value(document).map_err(serialization_error)?;
+        Ok(Self {
+            cancellation: self.cancellation,
+            progress: self.progress,
+            rewrite_rules: self.rewrite_rules,
+            ..options
+        })
+    }
+
+    /// Collect a [`CompilationReport`] (timings and counters) while compiling
+    pub fn with_report(mut self, enabled: bool) -> Self {
+        self.report = enabled;
+        self
+    }
+
+    /// Set how much to log while compiling
+    ///
+    /// [`Verbosity::Verbose`] adds per-item `trace` logs (every execution
+    /// route, every AST substitution); [`Verbosity::Quiet`] disables logging
+    /// for the compilation entirely. See [`crate::utils::logging`].
+    pub fn with_verbosity(mut self, verbosity: Verbosity) -> Self {
+        self.verbosity = verbosity;
+        self
+    }
+
+    /// Evaluate equivalent pure nodes once
+    ///
+    /// Pure nodes with the same type, properties, and inputs are merged (see
+    /// [`CommonSubexpressions`]), so their value is computed once and shared
+    /// by all their readers. Off by default; volatile nodes are never merged.
+    pub fn with_common_subexpressions(mut self, enabled: bool) -> Self {
+        self.common_subexpressions = enabled;
+        self
+    }
+
+    /// Set how much to optimize the IR
+    ///
+    /// Applies to programs lowered with
+    /// [`lower_graph_with_options`](super::ir::lower_graph_with_options);
+    /// generators driven by [`compile_with_options`] walk the graph directly
+    /// and aren't affected. [`OptLevel::None`] by default.
+    pub fn with_opt_level(mut self, level: OptLevel) -> Self {
+        self.opt_level = level;
+        self
+    }
+
+    /// Set the token that cancels compilation
+    ///
+    /// Analysis and generation check it for every node and fail with
+    /// [`GraphyError::Cancelled`] once it is cancelled, so superseded
+    /// background compilations stop promptly.
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// Report progress to a sink while compiling
+    ///
+    /// The sink gets the [`PHASE_EXPANSION`], data flow, evaluation order,
+    /// execution routing, and [`PHASE_CODEGEN`] phases in that order; see
+    /// [`crate::utils::progress`] for what each counts.
+    pub fn with_progress(mut self, progress: Arc<dyn ProgressSink>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Rewrite the graph with rules before analyzing it
+    ///
+    /// The rules run on the expanded graph, so they also see the contents of
+    /// sub-graphs; the caller's graph is never modified. Use them for
+    /// peephole optimizations and node library migrations. A failing rewrite
+    /// fails the compilation.
+    pub fn with_rewrite_rules(mut self, rules: Vec<RewriteRule>) -> Self {
+        self.rewrite_rules = rules;
+        self
+    }
+
+    /// Set what to do with nodes of types the provider doesn't know
+    ///
+    /// [`UnknownNodePolicy::Error`] by default, so a missing node library
+    /// fails the build instead of producing code that reads results never
+    /// computed. Tools compiling incomplete graphs can warn or skip instead;
+    /// unknown nodes are then left out of evaluation.
+    pub fn with_unknown_node_policy(mut self, policy: UnknownNodePolicy) -> Self {
+        self.on_unknown_node = policy;
+        self
+    }
+
+    /// Check the graph's external declarations against what the engine provides
+    ///
+    /// Compilation fails with [`GraphyError::UnknownExternal`] or
+    /// [`GraphyError::ExternalMismatch`] for the first declaration the
+    /// registry doesn't satisfy (see
+    /// [`check_externals`](GraphDescription::check_externals)). Without a
+    /// registry, declarations are emitted unchecked.
+    pub fn with_externals(mut self, registry: ExternalRegistry) -> Self {
+        self.externals = Some(registry);
+        self
+    }
+
+    /// Set the features and platform to compile for
+    ///
+    /// Nodes whose [build condition](crate::core::BuildCondition), or whose
+    /// group's condition, doesn't hold are removed with their connections
+    /// before analysis (see
+    /// [`prune_disabled`](GraphDescription::prune_disabled)). The default
+    /// configuration enables no features and targets no platform.
+    pub fn with_config(mut self, config: CompileConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Set how pure nodes ready at the same time are ordered
+    ///
+    /// [`EvaluationOrder::BreadthFirst`] by default. Editors recompiling
+    /// after every edit can pass [`EvaluationOrder::ById`], or the previous
+    /// compilation's order (from
+    /// [`SessionAnalysis::data_resolver`](super::SessionAnalysis::data_resolver))
+    /// as [`EvaluationOrder::Previous`], so an edit doesn't reorder unrelated
+    /// nodes. See [`DataResolver::build_with_order`].
+    pub fn with_evaluation_order(mut self, order: EvaluationOrder) -> Self {
+        self.evaluation_order = order;
+        self
+    }
+
+    /// Set when data flow analysis runs in parallel
+    ///
+    /// Graphs at or over the threshold get their [`DataResolver`] from the
+    /// parallel build when more than one thread is available, provided the
+    /// metadata provider can be [shared between threads](NodeMetadataProvider::as_sync)
+    /// and the [evaluation order](Self::with_evaluation_order) is the default
+    /// one; the stable orders always build sequentially. The parallel build
+    /// orders independent pure nodes layer by layer, by ID, so crossing the
+    /// threshold can reorder them. 5,000 nodes or 10,000 connections by
+    /// default; [`ParallelThreshold::never`] keeps analysis sequential.
+    pub fn with_parallel_threshold(mut self, threshold: ParallelThreshold) -> Self {
+        self.parallel_threshold = threshold;
+        self
+    }
+
+    /// Evaluate expensive, independent pure inputs concurrently
+    ///
+    /// Inputs of a node whose pure subtrees cost at least
+    /// [`ParallelCodegen::min_cost`] in cost hints are computed on tasks of
+    /// their own, with `rayon::join` by default. Only generators that
+    /// [support it](CodeGenerator::supports_parallel) are affected. Off by
+    /// default.
+    pub fn with_parallel_codegen(mut self, parallel: ParallelCodegen) -> Self {
+        self.parallel = Some(parallel);
+        self
+    }
+
+    /// Generate repeated exec branches once, as helper functions
+    ///
+    /// Self-contained branches of at least [`BranchHelpers::min_nodes`]
+    /// nodes that occur more than once, differing only in constants, are
+    /// emitted as one function taking the differing constants, and called
+    /// wherever they occur. Only generators that
+    /// [support it](CodeGenerator::supports_branch_helpers) are affected.
+    /// Off by default.
+    pub fn with_branch_helpers(mut self, branch_helpers: BranchHelpers) -> Self {
+        self.branch_helpers = Some(branch_helpers);
+        self
+    }
+
+    /// Generate a dispatcher forwarding to every event's handler
+    ///
+    /// After the events, generators emit a struct implementing
+    /// [`EventDispatcher::trait_name`] with one method per event, see
+    /// [`CodeGenerator::generate_dispatcher`]. Off by default.
+    pub fn with_dispatcher(mut self, dispatcher: EventDispatcher) -> Self {
+        self.dispatcher = Some(dispatcher);
+        self
+    }
+
+    /// Build for debugging, calling the host at flagged nodes
+    ///
+    /// Function and control flow nodes with a
+    /// [`breakpoint`](NodeInstance::breakpoint) call
+    /// [`DebugHooks::hit_function`] before they run, and those with
+    /// [`trace`](NodeInstance::trace) report every input to
+    /// [`DebugHooks::trace_function`]; see [`CodeGenerator::generate_debug_hit`].
+    /// Branch helpers are turned off, so every hook names the node that ran.
+    /// Off by default, which ignores the flags.
+    pub fn with_debug(mut self, debug: DebugHooks) -> Self {
+        self.debug = Some(debug);
+        self
+    }
+
+    /// Report the values of watched pins as they are computed
+    ///
+    /// Every output pin in a node's [`watched_pins`](NodeInstance::watched_pins)
+    /// is reported to the [`ValueProbes::sink`] right after its value is
+    /// computed; see [`CodeGenerator::generate_value_probe`]. Like debug
+    /// builds, this turns off branch helpers. Off by default, which ignores
+    /// watched pins.
+    pub fn with_probes(mut self, probes: ValueProbes) -> Self {
+        self.probes = Some(probes);
+        self
+    }
+
+    /// Set how deep and long inlined pure expressions may get
+    ///
+    /// Whatever the generator's [`ExpressionPolicy`], a pure node nesting
+    /// [`InlineLimits::max_depth`] pure nodes in its expression, or whose
+    /// expression is longer than [`InlineLimits::max_length`], is bound to
+    /// its result variable with [`CodeGenerator::generate_binding`]. A chain
+    /// of 1000 pure nodes becomes a binding every 32 nodes by default,
+    /// instead of one expression nested 1000 levels deep.
+    /// [`InlineLimits::unlimited`] inlines as the policy says.
+    pub fn with_inline_limits(mut self, limits: InlineLimits) -> Self {
+        self.inline_limits = limits;
+        self
+    }
+
+    /// The progress sink, or one ignoring updates
+    pub(crate) fn progress_sink(&self) -> &dyn ProgressSink {
+        self.progress.as_deref().unwrap_or(&NoProgress)
+    }
+}
+
+/// The format of an options file, from its extension
+fn options_format(path: &Path) -> Result<Format, GraphyError> {
+    Format::from_path(path)
+        .ok_or_else(|| GraphyError::Serialization(format!("Unknown options file format: {}", path.display())))
+}
+
+/// Merges the fields of `overrides` into `document`, table by table
+fn merge_document(document: &mut Value, overrides: &Value) {
+    match (document, overrides) {
+        (Value::Object(fields), Value::Object(overridden)) => {
+            for (key, value) in overridden {
+                match fields.get_mut(key) {
+                    Some(field) if field.is_object() && value.is_object() => merge_document(field, value),
+                    _ => {
+                        fields.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (document, overrides) => *document = overrides.clone(),
+    }
+}
+
+/// Result of [`compile_with_options`]
+#[derive(Debug, Clone)]
+pub struct CompileOutput {
+    /// Generated code
+    pub code: String,
+
+    /// Source map from the code back to the nodes
+    pub source_map: SourceMap,
+
+    /// Timings and counters, if enabled with [`CompileOptions::with_report`]
+    pub report: Option<CompilationReport>,
+}
+
+/// Compile a graph to code with options
+///
+/// See [`compile`]. Also fails with [`GraphyError::Cancelled`] once the
+/// [cancellation token](CompileOptions::with_cancellation) is cancelled, and
+/// reports progress to the [progress sink](CompileOptions::with_progress).
+///
+/// # Example
+///
+/// ```ignore
+/// let options = CompileOptions::new().with_report(true);
+/// let output = compile_with_options(&graph, &provider, &mut generator, &options)?;
+/// println!("{}", output.report.unwrap());
+/// ```
+pub fn compile_with_options<P, G>(
+    graph: &GraphDescription,
+    metadata_provider: &P,
+    generator: &mut G,
+    options: &CompileOptions,
+) -> Result<CompileOutput, GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    logging::with_verbosity(options.verbosity, || {
+        let _span = tracing::info_span!("compile", graph = %graph.metadata.name).entered();
+        run_compile(graph, metadata_provider, generator, options)
+    })
+}
+
+/// Compile the code of a single event
+///
+/// Only the event's subgraph is analyzed and generated: the nodes its
+/// execution flow reaches, and the nodes they read data from (see
+/// [`Reachability::compute_from`]). The program hooks still run, so the
+/// output is a complete program with one event handler, in the same form
+/// [`compile`] generates it. Editors can preview an event without paying
+/// for the whole graph, and hot reloading can rebuild just the event that
+/// changed.
+///
+/// # Errors
+///
+/// Fails with [`GraphyError::NodeNotFound`] if the graph has no node
+/// `event_node_id`, and with [`GraphyError::CodeGeneration`] if that node
+/// isn't an event. Otherwise fails like [`compile`], but only for problems
+/// in the event's subgraph; errors elsewhere in the graph are not reported.
+pub fn compile_event<P, G>(
+    graph: &GraphDescription,
+    metadata_provider: &P,
+    event_node_id: &str,
+    generator: &mut G,
+) -> Result<String, GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    compile_event_with_options(graph, metadata_provider, event_node_id, generator, &CompileOptions::default())
+        .map(|output| output.code)
+}
+
+/// Compile the code of a single event with options
+///
+/// See [`compile_event`] and [`compile_with_options`]. Report counters
+/// describe the event's subgraph rather than the whole graph.
+pub fn compile_event_with_options<P, G>(
+    graph: &GraphDescription,
+    metadata_provider: &P,
+    event_node_id: &str,
+    generator: &mut G,
+    options: &CompileOptions,
+) -> Result<CompileOutput, GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    logging::with_verbosity(options.verbosity, || {
+        let _span = tracing::info_span!("compile_event", graph = %graph.metadata.name, event = event_node_id).entered();
+        let subgraph = event_subgraph(graph, metadata_provider, event_node_id)?;
+        run_compile(&subgraph, metadata_provider, generator, options)
+    })
+}
+
+/// The part of a graph an event runs or reads from
+fn event_subgraph<P: NodeMetadataProvider>(
+    graph: &GraphDescription,
+    metadata_provider: &P,
+    event_node_id: &str,
+) -> Result<GraphDescription, GraphyError> {
+    let node = graph
+        .nodes
+        .get(event_node_id)
+        .ok_or_else(|| GraphyError::NodeNotFound(event_node_id.to_string()))?;
+    let is_event = metadata_provider
+        .metadata_for_node(node)
+        .is_some_and(|metadata| metadata.node_type == NodeTypes::event);
+    if !is_event {
+        return Err(GraphyError::CodeGeneration(format!("Node {} is not an event", event_node_id)));
+    }
+
+    let reachability = Reachability::compute_from(graph, [event_node_id]);
+    let mut subgraph = graph.clone();
+    let removed = subgraph.remove_nodes(&reachability.unreachable_nodes(graph));
+    tracing::debug!(
+        "[COMPILE] Event {} reaches {} of {} node(s)",
+        event_node_id,
+        subgraph.nodes.len(),
+        subgraph.nodes.len() + removed.len()
+    );
+    Ok(subgraph)
+}
+
+fn run_compile<P, G>(
+    graph: &GraphDescription,
+    metadata_provider: &P,
+    generator: &mut G,
+    options: &CompileOptions,
+) -> Result<CompileOutput, GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    let start = Stopwatch::start();
+    let mut report = options.report.then(|| CompilationReport::new(graph.metadata.name.as_str()));
+
+    let language = generator.language();
+    let analyzed = analyze_graph(graph, metadata_provider, language.as_ref(), options, &mut report)?;
+    check_async_support(generator, &analyzed.async_analysis)?;
+    let analysis_done = start.elapsed();
+
+    let GraphAnalysis {
+        expanded,
+        data_resolver,
+        exec_routing,
+        async_analysis,
+        expansion,
+        merged_nodes,
+    } = analyzed;
+    let branch_helpers = options
+        .branch_helpers
+        .filter(|_| generator.supports_branch_helpers() && options.debug.is_none() && options.probes.is_none())
+        .map(|branch_helpers| {
+            timed(&mut report, "branch_helpers", || {
+                let plan = HelperPlan::compute(
+                    branch_helpers,
+                    &expanded,
+                    metadata_provider,
+                    &exec_routing,
+                    &data_resolver,
+                    language.as_ref(),
+                );
+                Arc::new(plan)
+            })
+        });
+    let mut ctx = CodeGeneratorContext::new(&expanded, metadata_provider, &data_resolver, &exec_routing)
+        .with_expression_policy(generator.expression_policy())
+        .with_inline_limits(options.inline_limits)
+        .with_language(language)
+        .with_async_analysis(async_analysis)
+        .with_cancellation(options.cancellation.clone())
+        .with_parallel_codegen(options.parallel)
+        .with_branch_helpers(branch_helpers)
+        .with_debug(options.debug.clone())
+        .with_probes(options.probes.clone());
+
+    let events = event_nodes(&expanded, metadata_provider);
+    let event_count = events.len();
+
+    let generate_span = tracing::debug_span!("generate").entered();
+    tracing::debug!("[COMPILE] Generating {} event(s)", event_count);
+
+    let mut progress = PhaseProgress::start(options.progress_sink(), PHASE_CODEGEN, event_count);
+    generator.begin_program(&mut ctx)?;
+    if !expanded.externals.is_empty() {
+        generator.generate_externals(&mut ctx)?;
+    }
+    if !expanded.variables.is_empty() {
+        generator.generate_state(&mut ctx)?;
+    }
+    generate_branch_helpers(generator, &mut ctx)?;
+    for (node, metadata) in events {
+        generate_event_node(generator, &mut ctx, node, metadata)?;
+        progress.advance();
+    }
+    generate_dispatcher(generator, &mut ctx, options.dispatcher.as_ref())?;
+    generator.end_program(&mut ctx)?;
+    progress.finish();
+
+    let expression_cache = ctx.expression_cache_stats();
+    let (code, source_map) = ctx.take_output();
+    drop(generate_span);
+
+    if let Some(report) = &mut report {
+        report.total = start.elapsed();
+        report.expansion = expansion;
+        report.analysis = analysis_done - expansion;
+        report.generation = report.total - analysis_done;
+        report.input_nodes = graph.nodes.len();
+        report.expanded_nodes = expanded.nodes.len();
+        report.connections = expanded.connections.len();
+        report.pure_nodes = data_resolver.get_pure_evaluation_order().len();
+        report.merged_nodes = merged_nodes;
+        report.events = event_count;
+        report.generated_nodes = source_map
+            .mappings()
+            .iter()
+            .map(|mapping| mapping.node_id.as_str())
+            .collect::<FxHashSet<_>>()
+            .len();
+        report.expression_cache = expression_cache;
+        tracing::debug!("[COMPILE] {}", report);
+    }
+
+    Ok(CompileOutput {
+        code,
+        source_map,
+        report,
+    })
+}
+
+/// An expanded graph with the analysis results code generation reads
+pub(crate) struct GraphAnalysis {
+    pub(crate) expanded: GraphDescription,
+    pub(crate) data_resolver: DataResolver,
+    pub(crate) exec_routing: ExecutionRouting,
+    pub(crate) async_analysis: AsyncAnalysis,
+
+    /// How long sub-graph expansion took
+    pub(crate) expansion: Duration,
+
+    /// Pure nodes merged by common subexpression elimination
+    pub(crate) merged_nodes: usize,
+}
+
+/// Expand a graph and run the analyses code generation needs
+pub(crate) fn analyze_graph<P: NodeMetadataProvider>(
+    graph: &GraphDescription,
+    metadata_provider: &P,
+    language: &dyn LanguageProfile,
+    options: &CompileOptions,
+    report: &mut Option<CompilationReport>,
+) -> Result<GraphAnalysis, GraphyError> {
+    let cancellation = &options.cancellation;
+    let progress = options.progress_sink();
+    cancellation.check()?;
+    let start = Stopwatch::start();
+    let expand_progress = PhaseProgress::start(progress, PHASE_EXPANSION, graph.nodes.len());
+    let mut expanded = graph.clone();
+    // Before expansion for disabled sub-graph instances, after it for the nodes they bring in
+    let mut pruned = expanded.prune_disabled(&options.config).len();
+    tracing::debug_span!("expand").in_scope(|| SubGraphExpander::new().expand_all(&mut expanded))?;
+    pruned += expanded.prune_disabled(&options.config).len();
+    if pruned > 0 {
+        tracing::debug!("[CONFIG] Pruned {} disabled node(s)", pruned);
+    }
+    expand_progress.finish();
+    let expansion = start.elapsed();
+
+    if !options.rewrite_rules.is_empty() {
+        cancellation.check()?;
+        timed(report, "rewrite", || apply_rules(&mut expanded, &options.rewrite_rules))?;
+    }
+    if let Some(issue) = expanded.check_node_versions(metadata_provider).into_iter().next() {
+        return Err(issue.into());
+    }
+    options.on_unknown_node.enforce(&expanded, metadata_provider)?;
+    if let Some(issue) = options
+        .externals
+        .as_ref()
+        .and_then(|registry| expanded.check_externals(registry).into_iter().next())
+    {
+        return Err(issue.into());
+    }
+    if let Some(issue) = expanded.check_pin_multiplicity(metadata_provider).into_iter().next() {
+        return Err(issue.into());
+    }
+
+    cancellation.check()?;
+    timed(report, "variables", || VariableUsage::analyze(&expanded))?;
+
+    let compact = timed(report, "compact_graph", || CompactGraph::from_graph(&expanded));
+    let mut data_resolver = timed(report, "data_flow", || {
+        let parallel = options.evaluation_order == EvaluationOrder::BreadthFirst
+            && options.parallel_threshold.select(&expanded) == BuildStrategy::Parallel;
+        let resolver = match metadata_provider.as_sync() {
+            Some(provider) if parallel => {
+                DataResolver::build_parallel_with_progress(&expanded, &provider, language, cancellation, progress)?
+            }
+            _ => DataResolver::build_from_compact_with_order(
+                &compact,
+                metadata_provider,
+                language,
+                cancellation,
+                progress,
+                &options.evaluation_order,
+            )?,
+        };
+        resolver.validate_required_inputs(&expanded, metadata_provider)?;
+        Ok::<_, GraphyError>(resolver)
+    })?;
+    let merged_nodes = if options.common_subexpressions {
+        cancellation.check()?;
+        timed(report, "cse", || {
+            let cse = CommonSubexpressions::compute(&expanded, metadata_provider, &data_resolver);
+            data_resolver.apply_common_subexpressions(&cse);
+            cse.len()
+        })
+    } else {
+        0
+    };
+    cancellation.check()?;
+    expanded.check_connection_nodes(ConnectionType::Execution)?;
+    expanded.check_exec_connections(metadata_provider)?;
+    let exec_routing = timed(report, "exec_routing", || {
+        ExecutionRouting::build_from_compact_with_progress(&compact, progress)
+    });
+    timed(report, "ordering", || {
+        SideEffectOrdering::compute_with_cancellation(
+            &expanded,
+            metadata_provider,
+            &exec_routing,
+            &data_resolver,
+            cancellation,
+        )?
+        .validate()
+    })?;
+
+    cancellation.check()?;
+    let async_analysis = timed(report, "async", || {
+        AsyncAnalysis::compute(&expanded, metadata_provider, &exec_routing, &data_resolver)
+    });
+
+    Ok(GraphAnalysis {
+        expanded,
+        data_resolver,
+        exec_routing,
+        async_analysis,
+        expansion,
+        merged_nodes,
+    })
+}
+
+/// Fail if an event runs async nodes but the generator can't emit async code
+pub(crate) fn check_async_support<G: CodeGenerator>(
+    generator: &G,
+    async_analysis: &AsyncAnalysis,
+) -> Result<(), GraphyError> {
+    if generator.supports_async() {
+        return Ok(());
+    }
+    match async_analysis.async_events().first() {
+        Some(event) => Err(GraphyError::CodeGeneration(format!(
+            "Event {} runs async node {}, but the generator doesn't support async code",
+            event,
+            async_analysis.async_nodes_of(event)[0]
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// The event nodes of a graph, in ID order
+pub(crate) fn event_nodes<'g, P: NodeMetadataProvider>(
+    graph: &'g GraphDescription,
+    metadata_provider: &'g P,
+) -> Vec<(&'g NodeInstance, &'g NodeMetadata)> {
+    let mut events: Vec<(&NodeInstance, &NodeMetadata)> = graph
+        .nodes
+        .values()
+        .filter_map(|node| {
+            metadata_provider
+                .metadata_for_node(node)
+                .filter(|metadata| metadata.node_type == NodeTypes::event)
+                .map(|metadata| (node, metadata))
+        })
+        .collect();
+    events.sort_by(|a, b| a.0.id.cmp(&b.0.id));
+    events
+}
+
+/// Generate one event handler
+pub(crate) fn generate_event_node<P, G>(
+    generator: &mut G,
+    ctx: &mut CodeGeneratorContext<'_, P>,
+    node: &NodeInstance,
+    metadata: &NodeMetadata,
+) -> Result<(), GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    ctx.check_cancelled()?;
+    // Each event is its own scope, so bindings never leak between them
+    ctx.clear_expression_cache();
+    ctx.output.begin_node(node.id.as_str(), None);
+    let result = generator.generate_event(ctx, node, metadata).and_then(|()| {
+        let scheduled = ctx.take_scheduled();
+        run_exec_steps(generator, ctx, scheduled)
+    });
+    ctx.output.end_node();
+    result.map_err(|error| error.with_node(&node.id))
+}
+
+/// Generate the event dispatcher, if the options ask for one
+pub(crate) fn generate_dispatcher<P, G>(
+    generator: &mut G,
+    ctx: &mut CodeGeneratorContext<'_, P>,
+    dispatcher: Option<&EventDispatcher>,
+) -> Result<(), GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    let Some(dispatcher) = dispatcher else {
+        return Ok(());
+    };
+    ctx.check_cancelled()?;
+    let events = EventAnalysis::compute(ctx.graph, ctx.metadata_provider, ctx.exec_routing, ctx.data_resolver);
+    tracing::debug!("[COMPILE] Generating dispatcher {} for {} event(s)", dispatcher.struct_name, events.len());
+    generator.generate_dispatcher(ctx, &events, dispatcher)
+}
+
+/// Generate the helper functions of repeated branches, if any
+///
+/// Each body is generated from the first branch of its class, with the
+/// class's differing constants read from the helper's params.
+pub(crate) fn generate_branch_helpers<P, G>(generator: &mut G, ctx: &mut CodeGeneratorContext<'_, P>) -> Result<(), GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    let Some(plan) = ctx.branch_helpers.clone() else {
+        return Ok(());
+    };
+    tracing::debug!("[CODEGEN] Generating {} branch helper(s)", plan.len());
+
+    for (index, helper) in plan.helpers.iter().enumerate() {
+        ctx.check_cancelled()?;
+        // Helpers are scopes of their own, like events
+        ctx.clear_expression_cache();
+        ctx.current_helper = Some(index);
+        ctx.output.begin_node(helper.root.as_str(), None);
+        let result = generator
+            .begin_branch_helper(ctx, &helper.name, &helper.params)
+            .and_then(|()| {
+                ctx.push_indent();
+                let body = run_exec_steps(generator, ctx, [ExecStep::Node(helper.root.clone())]);
+                ctx.pop_indent();
+                body
+            })
+            .and_then(|()| generator.end_branch_helper(ctx, &helper.name));
+        ctx.output.end_node();
+        ctx.current_helper = None;
+        result.map_err(|error| error.with_node(&helper.root))?;
+    }
+    ctx.clear_expression_cache();
+    Ok(())
+}
+
+/// Emit the helper call replacing the branch starting at a node
+///
+/// Returns `false` if no branch helper starts there.
+fn generate_branch_helper_call<P, G>(
+    generator: &mut G,
+    ctx: &mut CodeGeneratorContext<'_, P>,
+    node_id: &str,
+) -> Result<bool, GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    // Helper bodies are generated in full
+    let Some(plan) = ctx.branch_helpers.clone().filter(|_| ctx.current_helper.is_none()) else {
+        return Ok(false);
+    };
+    let Some((helper, args)) = plan.call(node_id) else {
+        return Ok(false);
+    };
+
+    ctx.check_cancelled()?;
+    let graph = ctx.graph;
+    let root = graph
+        .nodes
+        .get(node_id)
+        .ok_or_else(|| GraphyError::NodeNotFound(node_id.to_string()))?;
+    ctx.output.begin_node(node_id, None);
+    let result = args
+        .iter()
+        .zip(&helper.params)
+        .map(|(value, param)| generator.generate_literal(ctx, value, &param.param_type))
+        .collect::<Result<Vec<_>, _>>()
+        .and_then(|args| generator.generate_branch_helper_call(ctx, root, &helper.name, &args));
+    ctx.output.end_node();
+    result.map(|()| true)
+}
+
+/// Run an analysis step in its own span, recording its duration in the report if one is collected
+fn timed<T>(report: &mut Option<CompilationReport>, pass: &str, step: impl FnOnce() -> T) -> T {
+    let _span = tracing::debug_span!("analysis", pass).entered();
+    let Some(report) = report else {
+        return step();
+    };
+    let start = Stopwatch::start();
+    let result = step();
+    report.passes.push(PassTiming::new(pass, start.elapsed()));
+    result
+}
+
+/// Generate the code for everything following an exec output pin
+///
+/// Back-edges are skipped, so the walk always terminates; generators that
+/// support loops should check [`ExecutionRouting::is_back_edge`] themselves.
+///
+/// Runs as an [`ExecWalk`], so chains of function nodes of any length are
+/// generated without recursion; see [`run_exec_steps`].
+pub fn generate_exec_output<P, G>(
+    generator: &mut G,
+    ctx: &mut CodeGeneratorContext<'_, P>,
+    node_id: &str,
+    output_pin: &str,
+) -> Result<(), GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    run_exec_steps(generator, ctx, [ExecStep::output(node_id, output_pin)])
+}
+
+/// Run execution flow steps in order, and the steps they lead to
+///
+/// Nodes are generated in a loop over an [`ExecWalk`]: the exec outputs of
+/// function nodes and the steps generators
+/// [schedule](CodeGeneratorContext::schedule) are pushed onto it rather than
+/// generated recursively. Only generators calling [`generate_exec_output`]
+/// from their hooks, and fallible and switch nodes, recurse once per level
+/// of nesting.
+pub fn run_exec_steps<P, G>(
+    generator: &mut G,
+    ctx: &mut CodeGeneratorContext<'_, P>,
+    steps: impl IntoIterator<Item = ExecStep>,
+) -> Result<(), GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    let mut walk = ExecWalk::new();
+    walk.push_steps(steps);
+    while let Some(step) = walk.pop() {
+        match step {
+            ExecStep::Output { node, pin } => {
+                let exec_routing = ctx.exec_routing;
+                let targets = exec_routing.get_forward_connected_nodes(&node, &pin);
+                walk.push_steps(targets.into_iter().map(|target| ExecStep::Node(target.clone())));
+            }
+            ExecStep::Node(node_id) => {
+                if generate_branch_helper_call(generator, ctx, &node_id).map_err(|error| error.with_node(&node_id))? {
+                    continue;
+                }
+                let next = generate_exec_node(generator, ctx, &node_id).map_err(|error| error.with_node(&node_id))?;
+                walk.push_steps(next);
+            }
+            ExecStep::Emit(code) => ctx.emit(&code),
+            ExecStep::Indent => ctx.push_indent(),
+            ExecStep::Dedent => ctx.pop_indent(),
+            ExecStep::EndNode => ctx.output.end_node(),
+        }
+    }
+    Ok(())
+}
+
+/// Generate a function or control flow node reached through execution flow
+///
+/// Returns the steps that follow it: the steps it scheduled, closing its
+/// source map region, then its exec outputs if execution continues linearly.
+fn generate_exec_node<P, G>(
+    generator: &mut G,
+    ctx: &mut CodeGeneratorContext<'_, P>,
+    node_id: &str,
+) -> Result<Vec<ExecStep>, GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    ctx.check_cancelled()?;
+    let graph = ctx.graph;
+    let node = graph
+        .nodes
+        .get(node_id)
+        .ok_or_else(|| GraphyError::NodeNotFound(node_id.to_string()))?;
+    if node.is_set_variable() {
+        return generate_set_variable(generator, ctx, node);
+    }
+
+    let metadata = node_metadata(ctx, node)?;
+    let mut args = resolve_node_arguments(generator, ctx, node, metadata)?;
+
+    ctx.output.begin_node(node.id.as_str(), None);
+    let params = metadata.instance_params(node);
+    let result = generate_debug_hooks(generator, ctx, node, &params, &mut args).and_then(|()| match metadata.node_type {
+        NodeTypes::fn_ if metadata.is_fallible() => generate_fallible_node(generator, ctx, node, metadata, &args),
+        NodeTypes::fn_ => generator
+            .generate_function_node(ctx, node, metadata, &args)
+            .and_then(|()| generate_result_probe(generator, ctx, node, metadata)),
+        NodeTypes::control_flow if metadata.is_switch => generate_switch_node(generator, ctx, node, metadata, &args),
+        NodeTypes::control_flow => generator.generate_control_flow(ctx, node, metadata, &args),
+        NodeTypes::pure | NodeTypes::event => Err(GraphyError::CodeGeneration(format!(
+            "Node '{}' of type '{}' cannot be connected to execution flow",
+            node.id, node.node_type
+        ))),
+    });
+    let mut steps = ctx.take_scheduled();
+    if let Err(error) = result {
+        ctx.output.end_node();
+        return Err(error);
+    }
+    invalidate_pure_readers(ctx, &node.id);
+    steps.push(ExecStep::EndNode);
+
+    // Function nodes continue linearly; control flow and fallible nodes emit their own branches
+    if metadata.node_type == NodeTypes::fn_ && !metadata.is_fallible() {
+        steps.extend(exec_output_steps(ctx, &node.id));
+    }
+
+    Ok(steps)
+}
+
+/// Report the result of a function node that just ran, if it is probed
+fn generate_result_probe<P, G>(
+    generator: &mut G,
+    ctx: &mut CodeGeneratorContext<'_, P>,
+    node: &NodeInstance,
+    metadata: &NodeMetadata,
+) -> Result<(), GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    if metadata.return_type.is_none() || !ctx.is_probed(&node.id, RESULT_PIN) {
+        return Ok(());
+    }
+    let variable = result_variable(ctx, &node.id)?;
+    generate_value_probe(generator, ctx, node, RESULT_PIN, &variable)
+}
+
+/// Emit the debug hooks of a node about to run, in debug builds
+///
+/// The arguments of a traced node are replaced with the variables they are
+/// bound to for reporting, so they are evaluated once. See
+/// [`CompileOptions::with_debug`].
+fn generate_debug_hooks<P, G>(
+    generator: &mut G,
+    ctx: &mut CodeGeneratorContext<'_, P>,
+    node: &NodeInstance,
+    params: &[impl Borrow<ParamInfo>],
+    args: &mut [String],
+) -> Result<(), GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    let Some(hooks) = ctx.debug.clone().filter(|_| node.breakpoint || node.trace) else {
+        return Ok(());
+    };
+
+    if node.trace {
+        for (param, arg) in params.iter().map(Borrow::borrow).zip(args.iter_mut()) {
+            let variable = ctx.language.sanitize_identifier(&format!("trace_{}_{}", node.id, param.name));
+            generator
+                .generate_debug_trace(ctx, node, param, &variable, arg, &hooks)
+                .map_err(|error| error.with_pin(&node.id, &param.name))?;
+            *arg = variable;
+        }
+    }
+    if node.breakpoint {
+        generator.generate_debug_hit(ctx, node, &hooks)?;
+    }
+    Ok(())
+}
+
+/// Drop the cached pure expressions computed from a node's results
+///
+/// The node just ran (again), so anything reading its results has to be
+/// evaluated after this point.
+fn invalidate_pure_readers<P: NodeMetadataProvider>(ctx: &mut CodeGeneratorContext<'_, P>, node_id: &str) {
+    let data_resolver = ctx.data_resolver;
+    let mut visited: FxHashSet<&str> = FxHashSet::default();
+    let mut pending: Vec<&str> = vec![node_id];
+    while let Some(id) = pending.pop() {
+        for dependent in data_resolver.get_dependents(id) {
+            let is_pure = ctx
+                .graph
+                .nodes
+                .get(dependent)
+                .and_then(|node| ctx.metadata_provider.metadata_for_node(node))
+                .is_some_and(|metadata| metadata.node_type == NodeTypes::pure);
+            if is_pure && visited.insert(dependent) {
+                ctx.invalidate_expression(dependent);
+                pending.push(dependent);
+            }
+        }
+    }
+}
+
+/// Generate a fallible function node with its success and error flows
+///
+/// The node's regular exec outputs run in the success branch, its
+/// [`ERROR_EXEC_PIN`] flow in the error branch.
+fn generate_fallible_node<P, G>(
+    generator: &mut G,
+    ctx: &mut CodeGeneratorContext<'_, P>,
+    node: &NodeInstance,
+    metadata: &NodeMetadata,
+    args: &[String],
+) -> Result<(), GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    let mut call = generator.generate_fallible_call(ctx, node, metadata, args)?;
+    if metadata.is_async {
+        call = generator.generate_await(ctx, node, &call)?;
+    }
+
+    let value_is_read = ctx.graph.connections.iter().any(|connection| {
+        connection.connection_type == ConnectionType::Data
+            && connection.source_node == node.id
+            && connection.source_pin != ERROR_VALUE_PIN
+    });
+    let value = if value_is_read || ctx.is_probed(&node.id, RESULT_PIN) {
+        Some(result_variable(ctx, &node.id)?)
+    } else {
+        None
+    };
+    // Named like the resolver names the errors it binds
+    let error = ctx.data_resolver.get_error_variable(&node.id).cloned().or_else(|| {
+        ctx.is_probed(&node.id, ERROR_VALUE_PIN)
+            .then(|| ctx.language.sanitize_identifier(&format!("node_{}_error", node.id)))
+    });
+
+    generator.begin_result_match(ctx, node, &call, value.as_deref())?;
+    if let Some(value) = &value {
+        generate_value_probe(generator, ctx, node, RESULT_PIN, value)?;
+    }
+    let mut output_pins = ctx.exec_routing.get_output_pins(&node.id);
+    output_pins.retain(|pin| pin != ERROR_EXEC_PIN);
+    output_pins.sort();
+    for pin in output_pins {
+        generate_exec_output(generator, ctx, &node.id, &pin)?;
+    }
+
+    generator.generate_error_arm(ctx, node, error.as_deref())?;
+    if let Some(error) = &error {
+        generate_value_probe(generator, ctx, node, ERROR_VALUE_PIN, error)?;
+    }
+    generate_exec_output(generator, ctx, &node.id, ERROR_EXEC_PIN)?;
+    generator.end_result_match(ctx, node)
+}
+
+/// Generate a switch node as a match with one arm per case
+///
+/// A `_` arm runs the [`SWITCH_DEFAULT_PIN`] flow; it is left out only when
+/// the cases cover every variant of the value's enum and nothing is
+/// connected to the default pin.
+fn generate_switch_node<P, G>(
+    generator: &mut G,
+    ctx: &mut CodeGeneratorContext<'_, P>,
+    node: &NodeInstance,
+    metadata: &NodeMetadata,
+    args: &[String],
+) -> Result<(), GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    let (Some(value_type), Some(value)) = (node.switch_value_type(metadata), args.first()) else {
+        return Err(GraphyError::CodeGeneration(format!(
+            "Switch node '{}' of type '{}' has no value to match on",
+            node.id, node.node_type
+        )));
+    };
+
+    let cases = resolve_switch_cases(node, metadata, ctx.metadata_provider);
+    let variants = ctx.metadata_provider.enum_variants(value_type);
+    if let Some(variants) = variants {
+        if let Some(case) = cases.iter().find(|case| !variants.iter().any(|variant| variant == *case)) {
+            return Err(GraphyError::CodeGeneration(format!(
+                "Switch node '{}' has case '{}', which is not a variant of '{}'",
+                node.id, case, value_type
+            )));
+        }
+    }
+    let exhaustive = variants.is_some_and(|variants| variants.iter().all(|variant| cases.contains(&variant.as_str())));
+    let has_default = !ctx.exec_routing.get_connected_nodes(&node.id, SWITCH_DEFAULT_PIN).is_empty();
+
+    generator.begin_switch(ctx, node, value)?;
+    for case in &cases {
+        let pattern = generator.switch_case_pattern(ctx, node, value_type, case)?;
+        generator.begin_switch_arm(ctx, node, &pattern)?;
+        generate_exec_output(generator, ctx, &node.id, case)?;
+        generator.end_switch_arm(ctx, node)?;
+    }
+    if has_default || !exhaustive {
+        generator.begin_switch_arm(ctx, node, "_")?;
+        generate_exec_output(generator, ctx, &node.id, SWITCH_DEFAULT_PIN)?;
+        generator.end_switch_arm(ctx, node)?;
+    }
+    generator.end_switch(ctx, node)
+}
+
+/// Steps generating all exec outputs of a node, in pin order
+fn exec_output_steps<P: NodeMetadataProvider>(ctx: &CodeGeneratorContext<'_, P>, node_id: &str) -> Vec<ExecStep> {
+    let mut output_pins = ctx.exec_routing.get_output_pins(node_id);
+    output_pins.sort();
+    output_pins.into_iter().map(|pin| ExecStep::output(node_id, pin)).collect()
+}
+
+/// Generate a `graph.set_variable` node, returning the steps after it
+fn generate_set_variable<P, G>(
+    generator: &mut G,
+    ctx: &mut CodeGeneratorContext<'_, P>,
+    node: &NodeInstance,
+) -> Result<Vec<ExecStep>, GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    let graph = ctx.graph;
+    let variable = declared_variable(graph, node)?;
+    let type_string = &graph.variables[variable].type_info.type_string;
+    let value = resolve_input(generator, ctx, node, VARIABLE_VALUE_PIN, type_string)
+        .map_err(|error| error.with_pin(&node.id, VARIABLE_VALUE_PIN))?;
+    let field = ctx.language.sanitize_identifier(variable);
+
+    ctx.output.begin_node(node.id.as_str(), None);
+    let mut args = [value];
+    let params = [ParamInfo::new(VARIABLE_VALUE_PIN, type_string.as_str())];
+    let result = generate_debug_hooks(generator, ctx, node, &params, &mut args)
+        .and_then(|()| generator.generate_variable_set(ctx, node, &field, &args[0]));
+    ctx.output.end_node();
+    result?;
+
+    // Cached reads (and everything computed from them) are stale now
+    for reader in graph.nodes.values() {
+        if reader.is_get_variable() && reader.variable_name() == Some(variable) {
+            ctx.invalidate_expression(&reader.id);
+            for dependent in ctx.data_resolver.get_transitive_dependents(&reader.id) {
+                ctx.invalidate_expression(&dependent);
+            }
+        }
+    }
+
+    Ok(exec_output_steps(ctx, &node.id))
+}
+
+/// Name of the declared variable a variable node accesses
+fn declared_variable<'g>(graph: &'g GraphDescription, node: &'g NodeInstance) -> Result<&'g str, GraphyError> {
+    node.variable_name()
+        .filter(|variable| graph.variables.contains_key(*variable))
+        .ok_or_else(|| GraphyError::UndeclaredVariable {
+            node: node.id.clone(),
+            variable: node.variable_name().unwrap_or_default().to_string(),
+        })
+}
+
+/// Bind the params of an event read by other nodes to local variables
+///
+/// Call this from [`CodeGenerator::generate_event`] after opening the
+/// handler, before generating its execution flow. Each used param is bound
+/// with [`CodeGenerator::generate_binding`] from the handler parameter of
+/// the same (sanitized) name; unused params get no binding. Watched params
+/// are reported to the [probes](CompileOptions::with_probes).
+///
+/// # Example
+///
+/// ```ignore
+/// ctx.emit(&format!("fn {}(delta_time: f64) {{\n", node.id));
+/// ctx.push_indent();
+/// bind_event_params(self, ctx, node, metadata)?;
+/// generate_exec_output(self, ctx, &node.id, "exec")?;
+/// ```
+pub fn bind_event_params<P, G>(
+    generator: &mut G,
+    ctx: &mut CodeGeneratorContext<'_, P>,
+    node: &NodeInstance,
+    metadata: &NodeMetadata,
+) -> Result<(), GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    for param in &metadata.params {
+        let value = ctx.language.sanitize_identifier(&param.name);
+        let variable = match ctx.data_resolver.get_event_param_variable(&node.id, &param.name) {
+            Some(variable) => {
+                let binding = generator.generate_binding(ctx, node, variable, &value)?;
+                ctx.emit_for_node(&node.id, Some(&param.name), &binding);
+                variable.clone()
+            }
+            None => value,
+        };
+        generate_value_probe(generator, ctx, node, &param.name, &variable)?;
+    }
+
+    Ok(())
+}
+
+/// Resolve one expression per input of a node, variadic ones included
+///
+/// Connected pure nodes are rendered via
+/// [`CodeGenerator::generate_pure_expression`] (cached, and hoisted into a
+/// binding if the context's [`ExpressionPolicy`](super::ExpressionPolicy)
+/// says so), other connected nodes are referenced by their result variable,
+/// and unconnected inputs use the node's property, the param's metadata
+/// default, or the type's default value. Required type coercions are applied.
+///
+/// Hoisted bindings, and the branches of inputs evaluated in parallel (see
+/// [`ParallelCodegen`]), are emitted immediately, so call this before
+/// emitting the statement that uses the arguments.
+pub fn resolve_node_arguments<P, G>(
+    generator: &mut G,
+    ctx: &mut CodeGeneratorContext<'_, P>,
+    node: &NodeInstance,
+    metadata: &NodeMetadata,
+) -> Result<Vec<String>, GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    if let Some(parallel) = ctx.parallel.filter(|_| generator.supports_parallel()) {
+        let sources = parallel_sources(ctx, node, metadata, parallel);
+        if !sources.is_empty() {
+            generate_parallel_inputs(generator, ctx, node, &sources)?;
+        }
+    }
+
+    metadata
+        .instance_params(node)
+        .iter()
+        .map(|param| {
+            resolve_input(generator, ctx, node, &param.name, &param.param_type)
+                .map_err(|error| error.with_pin(&node.id, &param.name))
+        })
+        .collect()
+}
+
+/// Evaluate pure sources of a node's inputs in parallel branches, caching
+/// their result variables for the inputs to read
+fn generate_parallel_inputs<P, G>(
+    generator: &mut G,
+    ctx: &mut CodeGeneratorContext<'_, P>,
+    node: &NodeInstance,
+    sources: &[String],
+) -> Result<(), GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    tracing::debug!("[CODEGEN] Evaluating {} input(s) of '{}' in parallel", sources.len(), node.id);
+    let variables = sources
+        .iter()
+        .map(|source| result_variable(ctx, source))
+        .collect::<Result<Vec<String>, GraphyError>>()?;
+
+    // The scaffolding belongs to the node, each branch to its input
+    ctx.output.begin_node(node.id.as_str(), None);
+    let mut result = Ok(());
+    for (index, source_node_id) in sources.iter().enumerate() {
+        result = generator.begin_parallel_branch(ctx, node, index, &variables);
+        if result.is_err() {
+            break;
+        }
+        ctx.push_indent();
+        ctx.output.begin_node(source_node_id.as_str(), None);
+        result = pure_expression(generator, ctx, source_node_id)
+            .and_then(|expr| generator.end_parallel_branch(ctx, node, index, &expr))
+            .map_err(|error| error.with_node(source_node_id));
+        ctx.output.end_node();
+        ctx.pop_indent();
+        if result.is_err() {
+            break;
+        }
+    }
+    let result = result.and_then(|()| generator.end_parallel(ctx, node, &variables));
+    ctx.output.end_node();
+    result?;
+
+    let graph = ctx.graph;
+    for (source_node_id, variable) in sources.iter().zip(variables) {
+        ctx.cache_expression(source_node_id, variable.as_str());
+        if let Some(source) = graph.nodes.get(source_node_id) {
+            generate_value_probe(generator, ctx, source, value_pin(source), &variable)?;
+        }
+    }
+    Ok(())
+}
+
+/// Resolve the expression feeding one input pin of a node
+fn resolve_input<P, G>(
+    generator: &mut G,
+    ctx: &mut CodeGeneratorContext<'_, P>,
+    node: &NodeInstance,
+    pin_name: &str,
+    type_string: &str,
+) -> Result<String, GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    let expr = match ctx.data_resolver.get_input_source(&node.id, pin_name) {
+        Some(DataSource::Connection { source_node_id, source_pin }) => {
+            match ctx.data_resolver.get_error_variable(source_node_id) {
+                Some(error) if source_pin == ERROR_VALUE_PIN => error.clone(),
+                _ => connected_expression(generator, ctx, source_node_id)
+                    .map_err(|error| error.with_node(source_node_id))?,
+            }
+        }
+        Some(DataSource::GraphInput { name }) => ctx.language.sanitize_identifier(name),
+        Some(DataSource::EventParam { event_node, param }) => ctx
+            .data_resolver
+            .get_event_param_variable(event_node, param)
+            .cloned()
+            .ok_or_else(|| {
+                GraphyError::CodeGeneration(format!("Event param '{}.{}' is not bound", event_node, param))
+            })?,
+        Some(DataSource::Constant(value, _)) => match ctx.helper_argument(&node.id, pin_name) {
+            Some(param) => param.to_string(),
+            None => generator.generate_literal(ctx, value, type_string)?,
+        },
+        Some(DataSource::Default) | None => ctx.language.default_value(type_string),
+    };
+    Ok(ctx.coerce_input(&node.id, pin_name, &expr))
+}
+
+/// Expression for the value produced by a connected source node
+fn connected_expression<P, G>(
+    generator: &mut G,
+    ctx: &mut CodeGeneratorContext<'_, P>,
+    source_node_id: &str,
+) -> Result<String, GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    let graph = ctx.graph;
+    let source = graph
+        .nodes
+        .get(source_node_id)
+        .ok_or_else(|| GraphyError::NodeNotFound(source_node_id.to_string()))?;
+
+    // Only pure nodes and variable reads are cached
+    if let Some(expr) = ctx.cached_expression(source_node_id) {
+        return Ok(expr.to_string());
+    }
+
+    let (expr, depth) = if source.is_get_variable() {
+        let field = ctx.language.sanitize_identifier(declared_variable(graph, source)?);
+        (generator.generate_variable_get(ctx, source, &field)?, 0)
+    } else if node_metadata(ctx, source)?.node_type != NodeTypes::pure {
+        return result_variable(ctx, source_node_id);
+    } else {
+        let expr = pure_expression(generator, ctx, source_node_id)?;
+        let depth = ctx
+            .data_resolver
+            .get_dependencies(source_node_id)
+            .iter()
+            .map(|dependency| ctx.inline_depth(dependency))
+            .max()
+            .unwrap_or(0);
+        (expr, depth + 1)
+    };
+
+    // Probed values need a variable to report, and long chains are broken up
+    let pin = value_pin(source);
+    if !ctx.should_hoist(source_node_id)
+        && !ctx.is_probed(source_node_id, pin)
+        && !ctx.inline_limits.exceeded_by(depth, &expr)
+    {
+        ctx.cache_inline_expression(source_node_id, expr.as_str(), depth);
+        return Ok(expr);
+    }
+
+    let variable = result_variable(ctx, source_node_id)?;
+    let binding = generator.generate_binding(ctx, source, &variable, &expr)?;
+    ctx.emit_for_node(source_node_id, None, &binding);
+    ctx.cache_expression(source_node_id, variable.as_str());
+    generate_value_probe(generator, ctx, source, pin, &variable)?;
+    Ok(variable)
+}
+
+/// Output pin holding the value of a pure node or variable read
+fn value_pin(node: &NodeInstance) -> &'static str {
+    if node.is_get_variable() {
+        VARIABLE_VALUE_PIN
+    } else {
+        RESULT_PIN
+    }
+}
+
+/// Report the value of an output pin held in `variable`, if it is probed
+///
+/// See [`CompileOptions::with_probes`].
+fn generate_value_probe<P, G>(
+    generator: &mut G,
+    ctx: &mut CodeGeneratorContext<'_, P>,
+    node: &NodeInstance,
+    pin: &str,
+    variable: &str,
+) -> Result<(), GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    let Some(probes) = ctx.probes.clone().filter(|_| node.is_watched(pin)) else {
+        return Ok(());
+    };
+    generator
+        .generate_value_probe(ctx, node, pin, variable, &probes)
+        .map_err(|error| error.with_pin(&node.id, pin))
+}
+
+/// Expression of a pure node on its resolved arguments, uncached
+fn pure_expression<P, G>(
+    generator: &mut G,
+    ctx: &mut CodeGeneratorContext<'_, P>,
+    node_id: &str,
+) -> Result<String, GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    let graph = ctx.graph;
+    let node = graph
+        .nodes
+        .get(node_id)
+        .ok_or_else(|| GraphyError::NodeNotFound(node_id.to_string()))?;
+    let metadata = node_metadata(ctx, node)?;
+
+    let args = resolve_node_arguments(generator, ctx, node, metadata)?;
+    let expr = match memoized_call(generator, ctx, node, metadata, &args)? {
+        Some(call) => generator.generate_memoized(ctx, node, &call)?,
+        None => generator.generate_pure_expression(ctx, node, metadata, &args)?,
+    };
+    if metadata.is_async {
+        generator.generate_await(ctx, node, &expr)
+    } else {
+        Ok(expr)
+    }
+}
+
+/// Render the call of a memoized pure node on bindings of its inputs
+///
+/// Returns `None` if the node isn't memoized or can't be.
+fn memoized_call<P, G>(
+    generator: &mut G,
+    ctx: &CodeGeneratorContext<'_, P>,
+    node: &NodeInstance,
+    metadata: &NodeMetadata,
+    args: &[String],
+) -> Result<Option<MemoizedCall>, GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    let Some(return_type) = metadata.return_type.as_ref() else {
+        return Ok(None);
+    };
+    if !metadata.memoize || metadata.is_volatile || metadata.is_async {
+        return Ok(None);
+    }
+
+    let bindings: Vec<String> = (0..args.len()).map(|index| format!("memo_{}", index)).collect();
+    let keys: Option<Vec<MemoKey>> = metadata
+        .instance_params(node)
+        .iter()
+        .zip(&bindings)
+        .map(|(param, binding)| generator.memo_key(&param.param_type, binding))
+        .collect();
+    let Some(keys) = keys else {
+        tracing::debug!("[CODEGEN] Node '{}' has inputs that can't be cached, not memoizing", node.id);
+        return Ok(None);
+    };
+
+    let call = generator.generate_pure_expression(ctx, node, metadata, &bindings)?;
+    Ok(Some(MemoizedCall {
+        args: args.to_vec(),
+        bindings,
+        keys,
+        return_type: return_type.to_string(),
+        call,
+    }))
+}
+
+fn result_variable<P: NodeMetadataProvider>(
+    ctx: &CodeGeneratorContext<'_, P>,
+    source_node_id: &str,
+) -> Result<String, GraphyError> {
+    ctx.data_resolver
+        .get_result_variable(source_node_id)
+        .cloned()
+        .ok_or_else(|| {
+            GraphyError::CodeGeneration(format!("Node '{}' does not produce a result", source_node_id))
+        })
+}
+
+fn node_metadata<'a, P: NodeMetadataProvider>(
+    ctx: &CodeGeneratorContext<'a, P>,
+    node: &NodeInstance,
+) -> Result<&'a NodeMetadata, GraphyError> {
+    ctx.metadata_provider.metadata_for_node(node).ok_or_else(|| {
+        GraphyError::CodeGeneration(format!("No metadata for node type '{}'", node.node_type))
+    })
+}
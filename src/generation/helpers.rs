@@ -0,0 +1,149 @@
+//! # Branch Helpers
+//!
+//! Generates repeated exec branches once, as helper functions.
+//!
+//! With [`CompileOptions::with_branch_helpers`](super::CompileOptions::with_branch_helpers),
+//! the driver looks for [isomorphic branches](crate::analysis::IsomorphicBranches):
+//! self-contained exec branches with the same nodes and wiring, differing
+//! only in the constants fed to their inputs. Each class of them is emitted
+//! once, before the events, as a function of the constants that differ, and
+//! every branch of the class becomes a call:
+//!
+//! ```text
+//! fn branch_helper_0(position_0: f64, health_1: i32) {
+//!     let node_spawn_1_result = spawn(position_0);
+//!     set_health(node_spawn_1_result, health_1);
+//! }
+//!
+//! fn on_start() {
+//!     branch_helper_0(1.0, 10);
+//!     branch_helper_0(2.0, 20);
+//! }
+//! ```
+//!
+//! The function body is generated from the class's first branch, so
+//! generators must not derive code from node IDs beyond naming locals.
+
+use crate::analysis::{BranchClass, DataResolver, ExecutionRouting, IsomorphicBranches};
+use crate::core::{GraphDescription, NodeMetadataProvider, PropertyValue};
+use crate::utils::LanguageProfile;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+
+/// Options for generating repeated branches as helper functions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BranchHelpers {
+    /// Minimum number of nodes in a branch before it gets a helper
+    pub min_nodes: usize,
+}
+
+impl BranchHelpers {
+    /// Generate branches of at least `min_nodes` nodes occurring twice or
+    /// more as helpers
+    #[inline]
+    pub fn new(min_nodes: usize) -> Self {
+        Self { min_nodes }
+    }
+}
+
+/// A parameter of a branch helper function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelperParam {
+    /// Identifier of the parameter
+    pub name: String,
+
+    /// Type of the input the parameter feeds
+    pub param_type: String,
+}
+
+/// A helper function generated for a class of branches
+#[derive(Debug)]
+pub(crate) struct BranchHelper {
+    pub(crate) name: String,
+
+    /// Root of the branch the body is generated from
+    pub(crate) root: String,
+    pub(crate) params: Vec<HelperParam>,
+
+    /// Maps (node, pin) of the generated branch -> parameter feeding it
+    inputs: FxHashMap<(String, String), String>,
+}
+
+/// The helpers of a graph and the branches calling them
+#[derive(Debug, Default)]
+pub(crate) struct HelperPlan {
+    pub(crate) helpers: Vec<BranchHelper>,
+
+    /// Maps branch root -> (helper index, arguments)
+    calls: FxHashMap<String, (usize, Vec<PropertyValue>)>,
+}
+
+impl HelperPlan {
+    /// Find the repeated branches of an analyzed graph and name their helpers
+    pub(crate) fn compute<P: NodeMetadataProvider>(
+        options: BranchHelpers,
+        graph: &GraphDescription,
+        metadata_provider: &P,
+        exec_routing: &ExecutionRouting,
+        data_resolver: &DataResolver,
+        language: &dyn LanguageProfile,
+    ) -> Self {
+        let branches =
+            IsomorphicBranches::compute(graph, metadata_provider, exec_routing, data_resolver, options.min_nodes);
+        let mut plan = Self::default();
+        for (index, class) in branches.classes().iter().enumerate() {
+            plan.add(index, class, language);
+        }
+        plan
+    }
+
+    fn add(&mut self, index: usize, class: &BranchClass, language: &dyn LanguageProfile) {
+        let representative = class.representative();
+        let mut inputs = FxHashMap::default();
+        let params = class
+            .params
+            .iter()
+            .enumerate()
+            .map(|(position, param)| {
+                let name = language.sanitize_identifier(&format!("{}_{}", param.pin, position));
+                let node = representative.nodes[param.node].clone();
+                inputs.insert((node, param.pin.clone()), name.clone());
+                HelperParam {
+                    name,
+                    param_type: param.param_type.clone(),
+                }
+            })
+            .collect();
+
+        for instance in &class.instances {
+            self.calls
+                .insert(instance.root.clone(), (self.helpers.len(), instance.arguments.clone()));
+        }
+        self.helpers.push(BranchHelper {
+            name: format!("branch_helper_{}", index),
+            root: representative.root.clone(),
+            params,
+            inputs,
+        });
+    }
+
+    /// The helper replacing the branch starting at a node, and its arguments
+    pub(crate) fn call(&self, root: &str) -> Option<(&BranchHelper, &[PropertyValue])> {
+        self.calls
+            .get(root)
+            .map(|(helper, arguments)| (&self.helpers[*helper], arguments.as_slice()))
+    }
+
+    /// The parameter feeding an input inside a helper body, if one does
+    pub(crate) fn argument(&self, helper: usize, node_id: &str, pin_name: &str) -> Option<&str> {
+        self.helpers[helper]
+            .inputs
+            .get(&(node_id.to_string(), pin_name.to_string()))
+            .map(String::as_str)
+    }
+
+    /// Number of helpers
+    pub(crate) fn len(&self) -> usize {
+        self.helpers.len()
+    }
+}
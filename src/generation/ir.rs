@@ -0,0 +1,826 @@
+//! # Intermediate Representation
+//!
+//! A target-independent tree of operations between analysis and code
+//! generation.
+//!
+//! [`IrProgram::lower`] walks the execution flow of every event once, using
+//! the [`DataResolver`] and [`ExecutionRouting`], and records what runs in
+//! which order:
+//!
+//! - [`IrOp::EmitEvent`]: an event handler and its body
+//! - [`IrOp::EvalPure`]: a pure node evaluated into its result variable,
+//!   placed right before its first reader in the block and re-evaluated
+//!   when a node it reads runs again or a variable it reads is set
+//! - [`IrOp::CallFunction`]: a function node
+//! - [`IrOp::Branch`]: a control flow, switch, or fallible node with the
+//!   lowered flow of each of its exec outputs
+//! - [`IrOp::Loop`] and [`IrOp::Continue`]: a loop header and the back-edges
+//!   into it
+//! - [`IrOp::SetVariable`]: a graph variable write
+//! - [`IrOp::Sequence`]: a straight-line run, only produced by
+//!   [`IrProgram::optimize`]
+//!
+//! Backends then only pretty-print the tree instead of re-implementing the
+//! traversal. Every pure node gets its own [`IrOp::EvalPure`]; backends that
+//! prefer inline expressions can substitute results read once. The
+//! [`Display`](fmt::Display) impl prints the tree for debugging.
+//!
+//! [`lower_graph`] runs the analysis the
+//! [compilation driver](super::compile) runs before lowering;
+//! [`lower_graph_with_options`] also applies common subexpression
+//! elimination and the optimization level of a [`CompileOptions`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! use graphy::generation::ir::{lower_graph, IrOp};
+//! use graphy::utils::RustProfile;
+//!
+//! let program = lower_graph(&graph, &provider, &RustProfile)?;
+//! for op in &program.ops {
+//!     if let IrOp::EmitEvent { event, body, .. } = op {
+//!         println!("{}: {} op(s)", event, body.len());
+//!     }
+//! }
+//! println!("{}", program);
+//! ```
+
+use super::{analyze_graph, CompileOptions, ExecWalk};
+use crate::analysis::{DataResolver, DataSource, ExecutionRouting};
+use crate::core::{
+    resolve_switch_cases, ConnectionType, GraphDescription, NodeInstance, NodeMetadata, NodeMetadataProvider, NodeTypes,
+    PropertyValue, ERROR_EXEC_PIN, ERROR_VALUE_PIN, SWITCH_DEFAULT_PIN, VARIABLE_VALUE_PIN,
+};
+use crate::utils::{LanguageProfile, RustProfile};
+use crate::GraphyError;
+use rustc_hash::FxHashSet;
+use std::fmt;
+
+/// Where an input of an operation gets its value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IrValue {
+    /// Result of a node that ran earlier ([`IrOp::EvalPure`],
+    /// [`IrOp::CallFunction`], or a fallible [`IrOp::Branch`])
+    Result {
+        /// ID of the node
+        node: String,
+
+        /// Variable holding the result
+        variable: String,
+    },
+
+    /// Error value of a fallible node, read in its error arm
+    Error {
+        /// ID of the fallible node
+        node: String,
+
+        /// Variable holding the error
+        variable: String,
+    },
+
+    /// Param of an event
+    EventParam {
+        /// ID of the event node
+        event: String,
+
+        /// Name of the param
+        param: String,
+
+        /// Variable the param is bound to
+        variable: String,
+    },
+
+    /// Declared graph input
+    GraphInput(String),
+
+    /// Graph variable, read where the input is used
+    Variable(String),
+
+    /// Constant from a node property or a metadata default, written as a
+    /// literal by the backend
+    Constant(PropertyValue),
+
+    /// Default value of a type
+    Default(String),
+}
+
+/// The lowered flow of one exec output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IrArm {
+    /// Exec output pin
+    pub pin: String,
+
+    /// Operations following the pin
+    pub body: Vec<IrOp>,
+}
+
+/// What kind of node an [`IrOp::Branch`] is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BranchKind {
+    /// A control flow node; which arms run, and how often, is up to the node
+    ControlFlow,
+
+    /// A switch node matching its first arg, with an arm per case and a
+    /// [`SWITCH_DEFAULT_PIN`] arm unless the cases are exhaustive
+    Switch {
+        /// Type of the matched value
+        value_type: String,
+    },
+
+    /// A fallible function node; the [`ERROR_EXEC_PIN`] arm runs on failure,
+    /// the others on success
+    Fallible {
+        /// Variable for the success value, if anything reads it
+        result: Option<String>,
+
+        /// Variable for the error value, if anything reads it
+        error: Option<String>,
+    },
+}
+
+/// An operation of the lowered program.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IrOp {
+    /// An event handler
+    EmitEvent {
+        /// ID of the event node
+        event: String,
+
+        /// Type of the event node
+        node_type: String,
+
+        /// Operations of the handler
+        body: Vec<IrOp>,
+    },
+
+    /// Evaluate a pure node into its result variable
+    EvalPure {
+        /// ID of the pure node
+        node: String,
+
+        /// Type of the pure node
+        node_type: String,
+
+        /// One value per metadata param, then per variadic input
+        args: Vec<IrValue>,
+
+        /// Variable holding the result
+        variable: String,
+    },
+
+    /// Call a function node
+    CallFunction {
+        /// ID of the function node
+        node: String,
+
+        /// Type of the function node
+        node_type: String,
+
+        /// One value per metadata param, then per variadic input
+        args: Vec<IrValue>,
+
+        /// Variable for the result, if the node returns one that's read
+        result: Option<String>,
+    },
+
+    /// Run a node with a lowered flow per exec output
+    Branch {
+        /// ID of the node
+        node: String,
+
+        /// Type of the node
+        node_type: String,
+
+        /// How the arms run
+        kind: BranchKind,
+
+        /// One value per metadata param, then per variadic input
+        args: Vec<IrValue>,
+
+        /// Lowered flow of each exec output
+        arms: Vec<IrArm>,
+    },
+
+    /// A loop header: `body` starts with the header node and runs again
+    /// whenever it reaches an [`IrOp::Continue`] for the loop; finishing it
+    /// otherwise leaves the loop
+    Loop {
+        /// ID of the loop header node
+        node: String,
+
+        /// Operations of the loop, starting with the header
+        body: Vec<IrOp>,
+    },
+
+    /// Jump back to the start of an enclosing [`IrOp::Loop`]
+    Continue {
+        /// ID of the loop header node
+        node: String,
+    },
+
+    /// A straight-line run of operations without branches, merged by
+    /// [`OptLevel::Aggressive`](super::OptLevel::Aggressive)
+    Sequence {
+        /// Operations of the run, in order
+        ops: Vec<IrOp>,
+    },
+
+    /// Write a graph variable
+    SetVariable {
+        /// ID of the `graph.set_variable` node
+        node: String,
+
+        /// Name of the variable
+        variable: String,
+
+        /// New value
+        value: IrValue,
+    },
+}
+
+/// A lowered graph: one [`IrOp::EmitEvent`] per event, in ID order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IrProgram {
+    /// Event handlers
+    pub ops: Vec<IrOp>,
+}
+
+impl IrProgram {
+    /// Lowers every event of an analyzed graph.
+    ///
+    /// The graph must already be expanded, and the resolver and routing
+    /// built from it.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a node on the execution path has no metadata, if a pure
+    /// node or event is connected to execution flow, if a variable node
+    /// accesses an undeclared variable, or if a switch has no value or a
+    /// case that isn't a variant of its value's enum.
+    pub fn lower<P: NodeMetadataProvider>(
+        graph: &GraphDescription,
+        metadata_provider: &P,
+        data_resolver: &DataResolver,
+        exec_routing: &ExecutionRouting,
+    ) -> Result<Self, GraphyError> {
+        let mut events: Vec<(&NodeInstance, &NodeMetadata)> = graph
+            .nodes
+            .values()
+            .filter_map(|node| {
+                metadata_provider
+                    .metadata_for_node(node)
+                    .filter(|metadata| metadata.node_type == NodeTypes::event)
+                    .map(|metadata| (node, metadata))
+            })
+            .collect();
+        events.sort_by(|a, b| a.0.id.cmp(&b.0.id));
+
+        let mut lowering = Lowering {
+            graph,
+            metadata_provider,
+            data_resolver,
+            exec_routing,
+            scopes: Vec::new(),
+        };
+        let mut ops = Vec::with_capacity(events.len());
+        for (node, metadata) in events {
+            lowering.scopes = vec![FxHashSet::default()];
+            let mut body = Vec::new();
+            for pin in &metadata.exec_outputs {
+                lowering.lower_output(&node.id, pin, &mut body)?;
+            }
+            ops.push(IrOp::EmitEvent {
+                event: node.id.clone(),
+                node_type: node.node_type.clone(),
+                body,
+            });
+        }
+
+        tracing::debug!("[IR] Lowered {} event(s)", ops.len());
+        Ok(Self { ops })
+    }
+}
+
+/// Expand and analyze a graph like [`compile`](super::compile), then lower it
+///
+/// Result variables and constants follow `language`. The program isn't
+/// optimized; see [`lower_graph_with_options`].
+///
+/// # Errors
+///
+/// Fails like [`compile`](super::compile) on expansion and analysis errors,
+/// and like [`IrProgram::lower`].
+pub fn lower_graph<P: NodeMetadataProvider>(
+    graph: &GraphDescription,
+    metadata_provider: &P,
+    language: &dyn LanguageProfile,
+) -> Result<IrProgram, GraphyError> {
+    lower_graph_with_options(graph, metadata_provider, language, &CompileOptions::default())
+}
+
+/// Lower a graph like [`lower_graph`], with options
+///
+/// Applies [`CompileOptions::common_subexpressions`] before lowering,
+/// optimizes the program at [`CompileOptions::opt_level`], and stops when
+/// [`CompileOptions::cancellation`] is cancelled.
+///
+/// # Errors
+///
+/// See [`lower_graph`]; also returns [`GraphyError::Cancelled`] once the
+/// token is cancelled.
+pub fn lower_graph_with_options<P: NodeMetadataProvider>(
+    graph: &GraphDescription,
+    metadata_provider: &P,
+    language: &dyn LanguageProfile,
+    options: &CompileOptions,
+) -> Result<IrProgram, GraphyError> {
+    let analyzed = analyze_graph(graph, metadata_provider, language, options, &mut None)?;
+    let mut program =
+        IrProgram::lower(&analyzed.expanded, metadata_provider, &analyzed.data_resolver, &analyzed.exec_routing)?;
+    program.optimize(metadata_provider, options.opt_level);
+    Ok(program)
+}
+
+/// A step of lowering execution flow, see [`Lowering::lower_flow`]
+enum LowerStep {
+    /// Everything following an exec output pin
+    Output { node: String, pin: String },
+
+    /// One exec connection, which may loop back or enter a loop
+    Edge { source: String, pin: String, target: String },
+
+    /// A node and what follows it
+    Node(String),
+}
+
+/// State of lowering one event
+struct Lowering<'a, P: NodeMetadataProvider> {
+    graph: &'a GraphDescription,
+    metadata_provider: &'a P,
+    data_resolver: &'a DataResolver,
+    exec_routing: &'a ExecutionRouting,
+
+    /// Pure nodes evaluated in each enclosing block, innermost last
+    scopes: Vec<FxHashSet<String>>,
+}
+
+impl<'a, P: NodeMetadataProvider> Lowering<'a, P> {
+    /// Lower everything following an exec output pin
+    fn lower_output(&mut self, node_id: &str, pin: &str, ops: &mut Vec<IrOp>) -> Result<(), GraphyError> {
+        self.lower_flow(
+            LowerStep::Output {
+                node: node_id.to_string(),
+                pin: pin.to_string(),
+            },
+            ops,
+        )
+    }
+
+    /// Lower a run of execution flow into one block
+    ///
+    /// Linear flow is walked with an [`ExecWalk`], so long chains don't
+    /// recurse; only loops and branch arms do, once per level of nesting.
+    fn lower_flow(&mut self, start: LowerStep, ops: &mut Vec<IrOp>) -> Result<(), GraphyError> {
+        let exec_routing = self.exec_routing;
+        let mut walk = ExecWalk::new();
+        walk.push_steps([start]);
+        while let Some(step) = walk.pop() {
+            match step {
+                LowerStep::Output { node, pin } => {
+                    walk.push_steps(exec_routing.get_connected_nodes(&node, &pin).iter().map(|target| {
+                        LowerStep::Edge {
+                            source: node.clone(),
+                            pin: pin.clone(),
+                            target: target.clone(),
+                        }
+                    }));
+                }
+                LowerStep::Edge { source, pin, target } => {
+                    if exec_routing.is_back_edge(&source, &pin, &target) {
+                        ops.push(IrOp::Continue { node: target });
+                    } else if exec_routing.is_loop_header(&target) {
+                        let body = self
+                            .lower_block(|lowering, body| lowering.lower_flow(LowerStep::Node(target.clone()), body))
+                            .map_err(|error| error.with_node(&target))?;
+                        ops.push(IrOp::Loop { node: target, body });
+                    } else {
+                        walk.push_steps([LowerStep::Node(target)]);
+                    }
+                }
+                LowerStep::Node(node_id) => {
+                    let pins = self.lower_node(&node_id, ops).map_err(|error| error.with_node(&node_id))?;
+                    walk.push_steps(pins.into_iter().map(|pin| LowerStep::Output {
+                        node: node_id.clone(),
+                        pin,
+                    }));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Lower into a new block, whose pure evaluations aren't visible outside it
+    fn lower_block(
+        &mut self,
+        lower: impl FnOnce(&mut Self, &mut Vec<IrOp>) -> Result<(), GraphyError>,
+    ) -> Result<Vec<IrOp>, GraphyError> {
+        self.scopes.push(FxHashSet::default());
+        let mut body = Vec::new();
+        let result = lower(self, &mut body);
+        self.scopes.pop();
+        result.map(|()| body)
+    }
+
+    /// Lower every exec output of a node into its own arm
+    fn lower_arms(&mut self, node_id: &str, pins: &[String]) -> Result<Vec<IrArm>, GraphyError> {
+        pins.iter()
+            .map(|pin| {
+                let body = self.lower_block(|lowering, body| lowering.lower_output(node_id, pin, body))?;
+                Ok(IrArm { pin: pin.clone(), body })
+            })
+            .collect()
+    }
+
+    /// Lower a node reached through execution flow
+    ///
+    /// Returns the exec output pins execution continues from in the same
+    /// block, in order.
+    fn lower_node(&mut self, node_id: &str, ops: &mut Vec<IrOp>) -> Result<Vec<String>, GraphyError> {
+        let graph = self.graph;
+        let node = graph
+            .nodes
+            .get(node_id)
+            .ok_or_else(|| GraphyError::NodeNotFound(node_id.to_string()))?;
+        if node.is_set_variable() {
+            return self.lower_set_variable(node, ops);
+        }
+
+        let metadata = self.node_metadata(node)?;
+        let args = self.lower_args(node, metadata, ops)?;
+        let mut output_pins = self.exec_routing.get_output_pins(&node.id);
+        output_pins.sort();
+
+        match metadata.node_type {
+            NodeTypes::fn_ if metadata.is_fallible() => {
+                output_pins.retain(|pin| pin != ERROR_EXEC_PIN);
+                output_pins.push(ERROR_EXEC_PIN.to_string());
+                let arms = self.lower_arms(&node.id, &output_pins)?;
+                let value_is_read = self.graph.connections.iter().any(|connection| {
+                    connection.connection_type == ConnectionType::Data
+                        && connection.source_node == node.id
+                        && connection.source_pin != ERROR_VALUE_PIN
+                });
+                let result = value_is_read
+                    .then(|| self.data_resolver.get_result_variable(&node.id).cloned())
+                    .flatten();
+                ops.push(IrOp::Branch {
+                    node: node.id.clone(),
+                    node_type: node.node_type.clone(),
+                    kind: BranchKind::Fallible {
+                        result,
+                        error: self.data_resolver.get_error_variable(&node.id).cloned(),
+                    },
+                    args,
+                    arms,
+                });
+                self.invalidate_pure_readers(&node.id);
+            }
+            NodeTypes::fn_ => {
+                let is_read = !self.data_resolver.get_dependents(&node.id).is_empty();
+                let result = match metadata.return_type {
+                    Some(_) if is_read => self.data_resolver.get_result_variable(&node.id).cloned(),
+                    _ => None,
+                };
+                ops.push(IrOp::CallFunction {
+                    node: node.id.clone(),
+                    node_type: node.node_type.clone(),
+                    args,
+                    result,
+                });
+                self.invalidate_pure_readers(&node.id);
+                return Ok(output_pins);
+            }
+            NodeTypes::control_flow if metadata.is_switch => {
+                let (value_type, pins) = self.switch_arms(node, metadata, &args)?;
+                let arms = self.lower_arms(&node.id, &pins)?;
+                ops.push(IrOp::Branch {
+                    node: node.id.clone(),
+                    node_type: node.node_type.clone(),
+                    kind: BranchKind::Switch { value_type },
+                    args,
+                    arms,
+                });
+                self.invalidate_pure_readers(&node.id);
+            }
+            NodeTypes::control_flow => {
+                let arms = self.lower_arms(&node.id, &metadata.exec_outputs)?;
+                ops.push(IrOp::Branch {
+                    node: node.id.clone(),
+                    node_type: node.node_type.clone(),
+                    kind: BranchKind::ControlFlow,
+                    args,
+                    arms,
+                });
+                self.invalidate_pure_readers(&node.id);
+            }
+            NodeTypes::pure | NodeTypes::event => {
+                return Err(GraphyError::CodeGeneration(format!(
+                    "Node '{}' of type '{}' cannot be connected to execution flow",
+                    node.id, node.node_type
+                )))
+            }
+        }
+        Ok(Vec::new())
+    }
+
+    /// Value type and arm pins of a switch node, as the driver emits them
+    fn switch_arms(
+        &self,
+        node: &NodeInstance,
+        metadata: &NodeMetadata,
+        args: &[IrValue],
+    ) -> Result<(String, Vec<String>), GraphyError> {
+        let (Some(value_type), false) = (node.switch_value_type(metadata), args.is_empty()) else {
+            return Err(GraphyError::CodeGeneration(format!(
+                "Switch node '{}' of type '{}' has no value to match on",
+                node.id, node.node_type
+            )));
+        };
+
+        let cases = resolve_switch_cases(node, metadata, self.metadata_provider);
+        let variants = self.metadata_provider.enum_variants(value_type);
+        if let Some(variants) = variants {
+            if let Some(case) = cases.iter().find(|case| !variants.iter().any(|variant| variant == *case)) {
+                return Err(GraphyError::CodeGeneration(format!(
+                    "Switch node '{}' has case '{}', which is not a variant of '{}'",
+                    node.id, case, value_type
+                )));
+            }
+        }
+        let exhaustive = variants.is_some_and(|variants| variants.iter().all(|variant| cases.contains(&variant.as_str())));
+        let has_default = !self.exec_routing.get_connected_nodes(&node.id, SWITCH_DEFAULT_PIN).is_empty();
+
+        let mut pins: Vec<String> = cases.into_iter().map(String::from).collect();
+        if has_default || !exhaustive {
+            pins.push(SWITCH_DEFAULT_PIN.to_string());
+        }
+        Ok((value_type.to_string(), pins))
+    }
+
+    /// Lower a `graph.set_variable` node, returning the pins execution continues from
+    fn lower_set_variable(&mut self, node: &NodeInstance, ops: &mut Vec<IrOp>) -> Result<Vec<String>, GraphyError> {
+        let graph = self.graph;
+        let variable = declared_variable(graph, node)?;
+        let type_string = &graph.variables[variable].type_info.type_string;
+        let value = self
+            .lower_input(node, VARIABLE_VALUE_PIN, type_string, ops)
+            .map_err(|error| error.with_pin(&node.id, VARIABLE_VALUE_PIN))?;
+        ops.push(IrOp::SetVariable {
+            node: node.id.clone(),
+            variable: variable.to_string(),
+            value,
+        });
+
+        // Everything computed from the old value has to be evaluated again
+        for reader in graph.nodes.values() {
+            if reader.is_get_variable() && reader.variable_name() == Some(variable) {
+                for dependent in self.data_resolver.get_transitive_dependents(&reader.id) {
+                    self.forget(&dependent);
+                }
+            }
+        }
+
+        let mut output_pins = self.exec_routing.get_output_pins(&node.id);
+        output_pins.sort();
+        Ok(output_pins)
+    }
+
+    /// Lower one value per input, variadic ones included
+    fn lower_args(
+        &mut self,
+        node: &NodeInstance,
+        metadata: &NodeMetadata,
+        ops: &mut Vec<IrOp>,
+    ) -> Result<Vec<IrValue>, GraphyError> {
+        metadata
+            .instance_params(node)
+            .iter()
+            .map(|param| {
+                self.lower_input(node, &param.name, &param.param_type, ops)
+                    .map_err(|error| error.with_pin(&node.id, &param.name))
+            })
+            .collect()
+    }
+
+    /// Lower the value feeding an input pin, evaluating pure nodes it needs
+    fn lower_input(
+        &mut self,
+        node: &NodeInstance,
+        pin_name: &str,
+        type_string: &str,
+        ops: &mut Vec<IrOp>,
+    ) -> Result<IrValue, GraphyError> {
+        let value = match self.data_resolver.get_input_source(&node.id, pin_name) {
+            Some(DataSource::Connection { source_node_id, source_pin }) => {
+                match self.data_resolver.get_error_variable(source_node_id) {
+                    Some(error) if source_pin == ERROR_VALUE_PIN => IrValue::Error {
+                        node: source_node_id.clone(),
+                        variable: error.clone(),
+                    },
+                    _ => self
+                        .lower_connected(source_node_id, ops)
+                        .map_err(|error| error.with_node(source_node_id))?,
+                }
+            }
+            Some(DataSource::GraphInput { name }) => IrValue::GraphInput(name.clone()),
+            Some(DataSource::EventParam { event_node, param }) => IrValue::EventParam {
+                event: event_node.clone(),
+                param: param.clone(),
+                variable: self
+                    .data_resolver
+                    .get_event_param_variable(event_node, param)
+                    .cloned()
+                    .ok_or_else(|| {
+                        GraphyError::CodeGeneration(format!("Event param '{}.{}' is not bound", event_node, param))
+                    })?,
+            },
+            Some(DataSource::Constant(value, _)) => IrValue::Constant(value.clone()),
+            Some(DataSource::Default) | None => IrValue::Default(type_string.to_string()),
+        };
+        Ok(value)
+    }
+
+    /// Value produced by a connected node, evaluating it first if it's pure
+    fn lower_connected(&mut self, source_node_id: &str, ops: &mut Vec<IrOp>) -> Result<IrValue, GraphyError> {
+        let graph = self.graph;
+        let source = graph
+            .nodes
+            .get(source_node_id)
+            .ok_or_else(|| GraphyError::NodeNotFound(source_node_id.to_string()))?;
+        if source.is_get_variable() {
+            return Ok(IrValue::Variable(declared_variable(graph, source)?.to_string()));
+        }
+
+        let variable = self
+            .data_resolver
+            .get_result_variable(source_node_id)
+            .cloned()
+            .ok_or_else(|| GraphyError::CodeGeneration(format!("Node '{}' does not produce a result", source_node_id)))?;
+        let result = IrValue::Result {
+            node: source_node_id.to_string(),
+            variable: variable.clone(),
+        };
+
+        let metadata = self.node_metadata(source)?;
+        if metadata.node_type != NodeTypes::pure || self.is_evaluated(source_node_id) {
+            return Ok(result);
+        }
+
+        let args = self.lower_args(source, metadata, ops)?;
+        ops.push(IrOp::EvalPure {
+            node: source.id.clone(),
+            node_type: source.node_type.clone(),
+            args,
+            variable,
+        });
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(source.id.clone());
+        }
+        Ok(result)
+    }
+
+    fn is_evaluated(&self, node_id: &str) -> bool {
+        self.scopes.iter().any(|scope| scope.contains(node_id))
+    }
+
+    fn forget(&mut self, node_id: &str) {
+        for scope in &mut self.scopes {
+            scope.remove(node_id);
+        }
+    }
+
+    /// Forget the evaluations of pure nodes computed from a node's results
+    fn invalidate_pure_readers(&mut self, node_id: &str) {
+        let data_resolver = self.data_resolver;
+        let mut visited: FxHashSet<&str> = FxHashSet::default();
+        let mut pending: Vec<&str> = vec![node_id];
+        while let Some(id) = pending.pop() {
+            for dependent in data_resolver.get_dependents(id) {
+                let is_pure = self
+                    .graph
+                    .nodes
+                    .get(dependent)
+                    .and_then(|node| self.metadata_provider.metadata_for_node(node))
+                    .is_some_and(|metadata| metadata.node_type == NodeTypes::pure);
+                if is_pure && visited.insert(dependent) {
+                    self.forget(dependent);
+                    pending.push(dependent);
+                }
+            }
+        }
+    }
+
+    fn node_metadata(&self, node: &NodeInstance) -> Result<&'a NodeMetadata, GraphyError> {
+        self.metadata_provider.metadata_for_node(node).ok_or_else(|| {
+            GraphyError::CodeGeneration(format!("No metadata for node type '{}'", node.node_type))
+        })
+    }
+}
+
+/// Name of the declared variable a variable node accesses
+fn declared_variable<'g>(graph: &'g GraphDescription, node: &'g NodeInstance) -> Result<&'g str, GraphyError> {
+    node.variable_name()
+        .filter(|variable| graph.variables.contains_key(*variable))
+        .ok_or_else(|| GraphyError::UndeclaredVariable {
+            node: node.id.clone(),
+            variable: node.variable_name().unwrap_or_default().to_string(),
+        })
+}
+
+// ============================================================================
+// Display
+// ============================================================================
+
+impl fmt::Display for IrValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IrValue::Result { node, .. } => write!(f, "{}", node),
+            IrValue::Error { node, .. } => write!(f, "{}.error", node),
+            IrValue::EventParam { event, param, .. } => write!(f, "{}.{}", event, param),
+            IrValue::GraphInput(name) => write!(f, "input.{}", name),
+            IrValue::Variable(name) => write!(f, "var.{}", name),
+            IrValue::Constant(value) => match RustProfile.format_literal(value) {
+                Ok(literal) => write!(f, "{}", literal),
+                Err(_) => write!(f, "{:?}", value),
+            },
+            IrValue::Default(type_string) => write!(f, "default<{}>", type_string),
+        }
+    }
+}
+
+impl fmt::Display for IrProgram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for op in &self.ops {
+            write_op(f, op, 0)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_args(f: &mut fmt::Formatter<'_>, node_type: &str, args: &[IrValue]) -> fmt::Result {
+    write!(f, "{}(", node_type)?;
+    for (i, arg) in args.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", arg)?;
+    }
+    write!(f, ")")
+}
+
+fn write_block(f: &mut fmt::Formatter<'_>, ops: &[IrOp], depth: usize) -> fmt::Result {
+    for op in ops {
+        write_op(f, op, depth)?;
+    }
+    Ok(())
+}
+
+fn write_op(f: &mut fmt::Formatter<'_>, op: &IrOp, depth: usize) -> fmt::Result {
+    let indent = "  ".repeat(depth);
+    match op {
+        IrOp::EmitEvent { event, node_type, body } => {
+            writeln!(f, "{}event {} ({})", indent, event, node_type)?;
+            write_block(f, body, depth + 1)
+        }
+        IrOp::EvalPure { node, node_type, args, .. } => {
+            write!(f, "{}eval {} = ", indent, node)?;
+            write_args(f, node_type, args)?;
+            writeln!(f)
+        }
+        IrOp::CallFunction { node, node_type, args, .. } => {
+            write!(f, "{}call {} = ", indent, node)?;
+            write_args(f, node_type, args)?;
+            writeln!(f)
+        }
+        IrOp::Branch { node, node_type, args, arms, .. } => {
+            write!(f, "{}branch {} = ", indent, node)?;
+            write_args(f, node_type, args)?;
+            writeln!(f)?;
+            for arm in arms {
+                writeln!(f, "{}  {}:", indent, arm.pin)?;
+                write_block(f, &arm.body, depth + 2)?;
+            }
+            Ok(())
+        }
+        IrOp::Loop { node, body } => {
+            writeln!(f, "{}loop {}", indent, node)?;
+            write_block(f, body, depth + 1)
+        }
+        IrOp::Continue { node } => writeln!(f, "{}continue {}", indent, node),
+        IrOp::Sequence { ops } => {
+            writeln!(f, "{}sequence", indent)?;
+            write_block(f, ops, depth + 1)
+        }
+        IrOp::SetVariable { variable, value, .. } => writeln!(f, "{}set var.{} = {}", indent, variable, value),
+    }
+}
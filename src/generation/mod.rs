@@ -3,7 +3,35 @@
 //! Extensible framework for generating code from node graphs.
 
 mod context;
+mod debug;
+mod dispatch;
+mod driver;
+mod helpers;
+mod optimize;
+mod parallel;
+mod preview;
+mod probes;
+mod project;
+mod report;
+mod session;
+mod source_map;
 mod strategies;
+mod template;
+pub mod ir;
+pub mod targets;
 
 pub use context::*;
+pub use debug::*;
+pub use dispatch::*;
+pub use driver::*;
+pub use helpers::*;
+pub use optimize::*;
+pub use parallel::*;
+pub use preview::*;
+pub use probes::*;
+pub use project::*;
+pub use report::*;
+pub use session::*;
+pub use source_map::*;
 pub use strategies::*;
+pub use template::*;
@@ -0,0 +1,356 @@
+//! # IR Optimization
+//!
+//! Passes over a lowered [`IrProgram`], selected by an [`OptLevel`].
+//!
+//! - **Branch simplification** ([`OptLevel::Basic`]): a switch on a
+//!   constant, or a control flow node whose Rust source is a plain
+//!   `if condition { exec_output!(..) } else { exec_output!(..) }` with a
+//!   constant condition, is replaced by the arm that runs.
+//! - **Dead store elimination** ([`OptLevel::Basic`]): pure evaluations
+//!   nothing reads are removed, and unread call and fallible results are no
+//!   longer bound.
+//! - **Straight-line merging** ([`OptLevel::Aggressive`]): loops that can no
+//!   longer repeat are unwrapped, and runs of consecutive function calls
+//!   (with the evaluations and variable writes between them) are merged into
+//!   one [`IrOp::Sequence`].
+//!
+//! Function calls and variable writes are never removed or reordered; only
+//! pure evaluations are, since they have no side effects.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use graphy::generation::ir::lower_graph;
+//! use graphy::generation::OptLevel;
+//!
+//! let mut program = lower_graph(&graph, &provider, &RustProfile)?;
+//! let stats = program.optimize(&provider, OptLevel::Basic);
+//! println!("{} branch(es) simplified", stats.simplified_branches);
+//! ```
+
+use super::ir::{BranchKind, IrArm, IrOp, IrProgram, IrValue};
+use crate::core::{NodeMetadataProvider, PropertyValue, SWITCH_DEFAULT_PIN};
+use crate::utils::{LanguageProfile, RustProfile};
+use rustc_hash::FxHashSet;
+use serde::{Deserialize, Serialize};
+use syn::{Block, Expr, ExprLit, ItemFn, Lit, LitStr, Stmt, UnOp};
+
+/// How much to optimize the IR.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OptLevel {
+    /// Keep the IR as lowered
+    #[default]
+    None,
+
+    /// Simplify constant branches and eliminate dead stores
+    Basic,
+
+    /// [`Basic`](Self::Basic), then merge straight-line code
+    Aggressive,
+}
+
+/// What [`IrProgram::optimize`] changed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OptimizationStats {
+    /// Branches replaced by the arm that runs
+    pub simplified_branches: usize,
+
+    /// Removed pure evaluations and unbound results
+    pub dead_stores: usize,
+
+    /// Loops unwrapped and call runs merged into sequences
+    pub merged_blocks: usize,
+}
+
+impl IrProgram {
+    /// Optimize the program in place
+    ///
+    /// Branch simplification reads the Rust source of control flow nodes
+    /// from `metadata_provider`.
+    pub fn optimize<P: NodeMetadataProvider>(&mut self, metadata_provider: &P, level: OptLevel) -> OptimizationStats {
+        let mut stats = OptimizationStats::default();
+        if level == OptLevel::None {
+            return stats;
+        }
+
+        simplify_branches(&mut self.ops, metadata_provider, &mut stats);
+        eliminate_dead_stores(&mut self.ops, &mut stats);
+        if level == OptLevel::Aggressive {
+            merge_straight_line(&mut self.ops, &mut stats);
+        }
+
+        tracing::debug!("[IR] Optimized at {:?}: {:?}", level, stats);
+        stats
+    }
+}
+
+/// Child blocks of an operation
+fn blocks_mut(op: &mut IrOp) -> Vec<&mut Vec<IrOp>> {
+    match op {
+        IrOp::EmitEvent { body, .. } | IrOp::Loop { body, .. } => vec![body],
+        IrOp::Sequence { ops } => vec![ops],
+        IrOp::Branch { arms, .. } => arms.iter_mut().map(|arm| &mut arm.body).collect(),
+        IrOp::EvalPure { .. } | IrOp::CallFunction { .. } | IrOp::Continue { .. } | IrOp::SetVariable { .. } => {
+            Vec::new()
+        }
+    }
+}
+
+fn blocks(op: &IrOp) -> Vec<&Vec<IrOp>> {
+    match op {
+        IrOp::EmitEvent { body, .. } | IrOp::Loop { body, .. } => vec![body],
+        IrOp::Sequence { ops } => vec![ops],
+        IrOp::Branch { arms, .. } => arms.iter().map(|arm| &arm.body).collect(),
+        IrOp::EvalPure { .. } | IrOp::CallFunction { .. } | IrOp::Continue { .. } | IrOp::SetVariable { .. } => {
+            Vec::new()
+        }
+    }
+}
+
+// ============================================================================
+// Branch simplification
+// ============================================================================
+
+fn simplify_branches<P: NodeMetadataProvider>(ops: &mut Vec<IrOp>, metadata_provider: &P, stats: &mut OptimizationStats) {
+    let mut simplified = Vec::with_capacity(ops.len());
+    for mut op in ops.drain(..) {
+        for block in blocks_mut(&mut op) {
+            simplify_branches(block, metadata_provider, stats);
+        }
+
+        let taken = match &op {
+            IrOp::Branch {
+                node_type,
+                kind,
+                args,
+                arms,
+                ..
+            } => taken_arm(node_type, kind, args, arms, metadata_provider),
+            _ => None,
+        };
+        match (op, taken) {
+            (IrOp::Branch { arms, .. }, Some(pin)) => {
+                stats.simplified_branches += 1;
+                if let Some(arm) = arms.into_iter().find(|arm| Some(arm.pin.as_str()) == pin.as_deref()) {
+                    simplified.extend(arm.body);
+                }
+            }
+            (op, _) => simplified.push(op),
+        }
+    }
+    *ops = simplified;
+}
+
+/// The pin of the arm a branch always takes: `Some(None)` if it never runs
+/// an arm, `None` if it can't be decided
+fn taken_arm<P: NodeMetadataProvider>(
+    node_type: &str,
+    kind: &BranchKind,
+    args: &[IrValue],
+    arms: &[IrArm],
+    metadata_provider: &P,
+) -> Option<Option<String>> {
+    match kind {
+        BranchKind::Switch { .. } => {
+            let IrValue::Constant(value) = args.first()? else {
+                return None;
+            };
+            // Cases are pins named after the Rust literal of their value
+            let value = RustProfile.format_literal(value).ok()?;
+            let case = arms.iter().find(|arm| {
+                arm.pin != SWITCH_DEFAULT_PIN
+                    && (*value == arm.pin || value.strip_suffix(arm.pin.as_str()).is_some_and(|path| path.ends_with("::")))
+            });
+            case.or_else(|| arms.iter().find(|arm| arm.pin == SWITCH_DEFAULT_PIN))
+                .map(|arm| Some(arm.pin.clone()))
+        }
+        BranchKind::ControlFlow => {
+            let metadata = metadata_provider.get_node_metadata(node_type)?;
+            if !metadata.source_kind.is_rust() || metadata.function_source.is_empty() {
+                return None;
+            }
+            let (condition, negated, then_pin, else_pin) = constant_if_shape(&metadata.function_source)?;
+            let index = metadata.params.iter().position(|param| param.name == condition)?;
+            let value = match args.get(index)? {
+                IrValue::Constant(PropertyValue::Boolean(value)) => *value,
+                _ => return None,
+            };
+            Some(if value != negated { then_pin } else { else_pin })
+        }
+        BranchKind::Fallible { .. } => None,
+    }
+}
+
+/// Match a function whose body is only
+/// `if [!]param { exec_output!("A"); } [else { exec_output!("B"); }]`,
+/// returning the param, whether it's negated, and the pins of both arms
+fn constant_if_shape(source: &str) -> Option<(String, bool, Option<String>, Option<String>)> {
+    let function = syn::parse_str::<ItemFn>(source).ok()?;
+    let [Stmt::Expr(Expr::If(if_expr), _)] = function.block.stmts.as_slice() else {
+        return None;
+    };
+
+    let (condition, negated) = match if_expr.cond.as_ref() {
+        Expr::Path(path) => (path.path.get_ident()?.to_string(), false),
+        Expr::Unary(unary) if matches!(unary.op, UnOp::Not(_)) => match unary.expr.as_ref() {
+            Expr::Path(path) => (path.path.get_ident()?.to_string(), true),
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    let then_pin = exec_output_only(&if_expr.then_branch)?;
+    let else_pin = match &if_expr.else_branch {
+        Some((_, else_expr)) => match else_expr.as_ref() {
+            Expr::Block(block) => exec_output_only(&block.block)?,
+            _ => return None,
+        },
+        None => None,
+    };
+    Some((condition, negated, then_pin, else_pin))
+}
+
+/// The label of a block that is a single payload-free `exec_output!`, or
+/// `Some(None)` for an empty block
+fn exec_output_only(block: &Block) -> Option<Option<String>> {
+    let mac = match block.stmts.as_slice() {
+        [] => return Some(None),
+        [Stmt::Macro(stmt)] => &stmt.mac,
+        [Stmt::Expr(Expr::Macro(expr), _)] => &expr.mac,
+        _ => return None,
+    };
+    if !mac.path.is_ident("exec_output") {
+        return None;
+    }
+    let label = mac
+        .parse_body::<LitStr>()
+        .ok()
+        .or_else(|| match mac.parse_body::<Expr>().ok()? {
+            Expr::Lit(ExprLit { lit: Lit::Str(label), .. }) => Some(label),
+            _ => None,
+        })?;
+    Some(Some(label.value()))
+}
+
+// ============================================================================
+// Dead store elimination
+// ============================================================================
+
+fn eliminate_dead_stores(ops: &mut [IrOp], stats: &mut OptimizationStats) {
+    // Removing an evaluation can leave the ones feeding it unread
+    loop {
+        let mut read = FxHashSet::default();
+        collect_reads(ops, &mut read);
+        let removed = remove_dead_stores(ops, &read);
+        stats.dead_stores += removed;
+        if removed == 0 {
+            break;
+        }
+    }
+}
+
+fn collect_reads(ops: &[IrOp], read: &mut FxHashSet<String>) {
+    for op in ops {
+        let values: &[IrValue] = match op {
+            IrOp::EvalPure { args, .. } | IrOp::CallFunction { args, .. } | IrOp::Branch { args, .. } => args,
+            IrOp::SetVariable { value, .. } => std::slice::from_ref(value),
+            _ => &[],
+        };
+        for value in values {
+            if let IrValue::Result { variable, .. } | IrValue::Error { variable, .. } = value {
+                read.insert(variable.clone());
+            }
+        }
+        for block in blocks(op) {
+            collect_reads(block, read);
+        }
+    }
+}
+
+/// Drop unread pure evaluations and result bindings, returning how many
+fn remove_dead_stores(ops: &mut [IrOp], read: &FxHashSet<String>) -> usize {
+    let mut removed = 0;
+    for op in ops.iter_mut() {
+        match op {
+            IrOp::CallFunction { result, .. } => removed += unbind(result, read),
+            IrOp::Branch {
+                kind: BranchKind::Fallible { result, error },
+                ..
+            } => removed += unbind(result, read) + unbind(error, read),
+            _ => {}
+        }
+        for block in blocks_mut(op) {
+            let before = block.len();
+            block.retain(|op| !matches!(op, IrOp::EvalPure { variable, .. } if !read.contains(variable)));
+            removed += before - block.len() + remove_dead_stores(block, read);
+        }
+    }
+    removed
+}
+
+fn unbind(variable: &mut Option<String>, read: &FxHashSet<String>) -> usize {
+    match variable {
+        Some(name) if !read.contains(name) => {
+            *variable = None;
+            1
+        }
+        _ => 0,
+    }
+}
+
+// ============================================================================
+// Straight-line merging
+// ============================================================================
+
+fn merge_straight_line(ops: &mut Vec<IrOp>, stats: &mut OptimizationStats) {
+    let mut flattened = Vec::with_capacity(ops.len());
+    for mut op in ops.drain(..) {
+        for block in blocks_mut(&mut op) {
+            merge_straight_line(block, stats);
+        }
+        match op {
+            // Nothing jumps back to the header any more, so the body runs once
+            IrOp::Loop { node, body } if !continues(&body, &node) => {
+                stats.merged_blocks += 1;
+                flattened.extend(body);
+            }
+            // Merged again below, together with its neighbours
+            IrOp::Sequence { ops } => flattened.extend(ops),
+            op => flattened.push(op),
+        }
+    }
+
+    let mut merged = Vec::with_capacity(flattened.len());
+    let mut run: Vec<IrOp> = Vec::new();
+    for op in flattened {
+        if matches!(op, IrOp::EvalPure { .. } | IrOp::CallFunction { .. } | IrOp::SetVariable { .. }) {
+            run.push(op);
+        } else {
+            flush_run(&mut run, &mut merged, stats);
+            merged.push(op);
+        }
+    }
+    flush_run(&mut run, &mut merged, stats);
+    *ops = merged;
+}
+
+/// Wrap a run holding at least two calls into a sequence
+fn flush_run(run: &mut Vec<IrOp>, merged: &mut Vec<IrOp>, stats: &mut OptimizationStats) {
+    let calls = run.iter().filter(|op| matches!(op, IrOp::CallFunction { .. })).count();
+    if calls < 2 {
+        merged.append(run);
+        return;
+    }
+    stats.merged_blocks += 1;
+    merged.push(IrOp::Sequence {
+        ops: std::mem::take(run),
+    });
+}
+
+fn continues(ops: &[IrOp], header: &str) -> bool {
+    ops.iter().any(|op| match op {
+        IrOp::Continue { node } => node == header,
+        op => blocks(op).into_iter().any(|block| continues(block, header)),
+    })
+}
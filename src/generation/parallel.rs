@@ -0,0 +1,136 @@
+//! # Parallel Code Generation
+//!
+//! Evaluates expensive, independent pure inputs of a node concurrently.
+//!
+//! With [`CompileOptions::with_parallel_codegen`](super::CompileOptions::with_parallel_codegen),
+//! the driver looks at the inputs of every node it generates. An input is
+//! evaluated on its own task if the pure nodes computing it (its subtree)
+//! add up to at least [`ParallelCodegen::min_cost`] in
+//! [cost hints](crate::NodeMetadata::cost_hint), and nothing outside the
+//! subtree reads them. When two or more inputs of a node qualify and share
+//! no nodes, their subtrees are generated through
+//! [`CodeGenerator::begin_parallel_branch`](super::CodeGenerator::begin_parallel_branch),
+//! which by default emits nested `rayon::join` calls:
+//!
+//! ```text
+//! let (node_noise_1_result, node_blur_1_result) = ::rayon::join(|| {
+//!     noise(x)
+//! }, || {
+//!     blur(image)
+//! });
+//! mix(node_noise_1_result, node_blur_1_result);
+//! ```
+//!
+//! Subtrees with async, volatile, or variable reading nodes are never
+//! parallelized: async nodes can't be awaited inside a closure, and the
+//! others may depend on state shared with the rest of the event.
+
+use super::CodeGeneratorContext;
+use crate::analysis::DataSource;
+use crate::core::{NodeInstance, NodeMetadata, NodeMetadataProvider, NodeTypes};
+use rustc_hash::FxHashSet;
+use serde::{Deserialize, Serialize};
+
+/// Options for evaluating expensive pure inputs concurrently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParallelCodegen {
+    /// Minimum summed cost hint of an input's pure subtree before it gets a
+    /// task of its own
+    pub min_cost: u64,
+}
+
+impl ParallelCodegen {
+    /// Parallelize inputs whose subtrees cost at least `min_cost`
+    #[inline]
+    pub fn new(min_cost: u64) -> Self {
+        Self { min_cost }
+    }
+}
+
+/// The pure nodes computing a node's input, and their summed cost
+struct Subtree {
+    nodes: Vec<String>,
+    cost: u64,
+}
+
+/// Sources of the inputs of a node to evaluate in parallel, in input order
+///
+/// Empty unless at least two inputs qualify.
+pub(crate) fn parallel_sources<P: NodeMetadataProvider>(
+    ctx: &CodeGeneratorContext<'_, P>,
+    node: &NodeInstance,
+    metadata: &NodeMetadata,
+    options: ParallelCodegen,
+) -> Vec<String> {
+    let mut claimed: FxHashSet<String> = FxHashSet::default();
+    let mut sources: Vec<String> = Vec::new();
+    for param in metadata.instance_params(node) {
+        let Some(DataSource::Connection { source_node_id, .. }) = ctx.data_resolver.get_input_source(&node.id, &param.name)
+        else {
+            continue;
+        };
+        if sources.contains(source_node_id) || ctx.is_cached(source_node_id) {
+            continue;
+        }
+        let Some(subtree) = pure_subtree(ctx, source_node_id) else {
+            continue;
+        };
+        if subtree.cost < options.min_cost || subtree.nodes.iter().any(|id| claimed.contains(id)) {
+            continue;
+        }
+        claimed.extend(subtree.nodes);
+        sources.push(source_node_id.clone());
+    }
+
+    if sources.len() < 2 {
+        sources.clear();
+    }
+    sources
+}
+
+/// The uncached pure nodes an input's value is computed from, or `None` if
+/// they can't run on another task
+fn pure_subtree<P: NodeMetadataProvider>(ctx: &CodeGeneratorContext<'_, P>, root: &str) -> Option<Subtree> {
+    let mut nodes: Vec<String> = Vec::new();
+    let mut seen: FxHashSet<&str> = FxHashSet::default();
+    let mut stack: Vec<&str> = vec![root];
+    let mut cost = 0u64;
+    while let Some(node_id) = stack.pop() {
+        if !seen.insert(node_id) || (node_id != root && ctx.is_cached(node_id)) {
+            continue;
+        }
+        let node = ctx.graph.nodes.get(node_id)?;
+        if node.is_get_variable() {
+            return None;
+        }
+        let metadata = ctx.metadata_provider.metadata_for_node(node)?;
+        if metadata.node_type != NodeTypes::pure {
+            // Results of function nodes are read from their variable
+            if node_id == root {
+                return None;
+            }
+            continue;
+        }
+        if metadata.is_async || metadata.is_volatile {
+            return None;
+        }
+
+        cost += u64::from(metadata.cost_hint);
+        nodes.push(node_id.to_string());
+        stack.extend(ctx.data_resolver.get_dependencies(node_id).iter().map(String::as_str));
+    }
+
+    // Values read elsewhere would have to be computed again after the join
+    let members: FxHashSet<&str> = nodes.iter().map(String::as_str).collect();
+    let escapes = nodes.iter().filter(|id| id.as_str() != root).any(|id| {
+        ctx.data_resolver
+            .get_dependents(id)
+            .iter()
+            .any(|reader| !members.contains(reader.as_str()))
+    });
+    if escapes {
+        return None;
+    }
+
+    Some(Subtree { nodes, cost })
+}
@@ -0,0 +1,285 @@
+//! # Code Preview Diffs
+//!
+//! Previews how an edit to a graph changes the generated code.
+//!
+//! [`preview_diff`] compiles the graph before and after an edit and diffs the
+//! two outputs line by line. Each [`CodeChunk`] is a run of removed and added
+//! lines, and every line carries the nodes that generated it (looked up in
+//! the [`SourceMap`] of its side), so an editor can show "this change to
+//! `add_1` alters these 6 generated lines" before the edit is committed.
+//!
+//! Pure nodes are inlined into the expressions of the nodes reading them and
+//! have no code of their own; their lines are attributed to those nodes.
+//!
+//! # Example
+//!
+//! ```ignore
+//! let preview = preview_diff(&graph, &edited, &registry, &mut generator)?;
+//! for chunk in &preview.chunks {
+//!     println!("{:?} changed at line {}", chunk.nodes(), chunk.new_start);
+//! }
+//! ```
+
+use super::{compile_with_source_map, CodeGenerator, SourceMap, SourceMapping};
+use crate::core::{GraphDescription, NodeMetadataProvider};
+use crate::GraphyError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Lines after trimming the unchanged start and end above which the changed
+/// region is reported as one chunk, rather than diffed line by line (the
+/// diff takes time and memory quadratic in the line count)
+pub const MAX_DIFFED_LINES: usize = 4000;
+
+/// A line of generated code that was removed or added.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangedLine {
+    /// Line number in its side's code, starting at 1
+    pub line: usize,
+
+    /// Text of the line, without the line break
+    pub text: String,
+
+    /// Nodes that generated the line, sorted
+    pub nodes: Vec<String>,
+}
+
+/// A run of changed lines, with the lines it replaces.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeChunk {
+    /// First line of the chunk in the old code (where lines were inserted,
+    /// if none were removed)
+    pub old_start: usize,
+
+    /// First line of the chunk in the new code (where lines were removed,
+    /// if none were added)
+    pub new_start: usize,
+
+    /// Lines of the old code that are gone
+    pub removed: Vec<ChangedLine>,
+
+    /// Lines of the new code that replace them
+    pub added: Vec<ChangedLine>,
+}
+
+impl CodeChunk {
+    /// Returns the nodes that generated any line of the chunk, on either side.
+    pub fn nodes(&self) -> BTreeSet<&str> {
+        self.lines().flat_map(|line| line.nodes.iter().map(String::as_str)).collect()
+    }
+
+    /// Returns the removed lines followed by the added ones.
+    pub fn lines(&self) -> impl Iterator<Item = &ChangedLine> {
+        self.removed.iter().chain(&self.added)
+    }
+}
+
+/// Differences between the code generated before and after an edit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreviewDiff {
+    /// Code generated for the old graph
+    pub old_code: String,
+
+    /// Code generated for the new graph
+    pub new_code: String,
+
+    /// Changed regions, in order
+    pub chunks: Vec<CodeChunk>,
+}
+
+impl PreviewDiff {
+    /// Checks if the edit leaves the generated code unchanged.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Returns every node that generated a changed line.
+    pub fn changed_nodes(&self) -> BTreeSet<&str> {
+        self.chunks.iter().flat_map(CodeChunk::nodes).collect()
+    }
+
+    /// Returns the chunks with a line generated by a node.
+    pub fn chunks_for_node<'s>(&'s self, node_id: &'s str) -> impl Iterator<Item = &'s CodeChunk> + 's {
+        self.chunks.iter().filter(move |chunk| chunk.nodes().contains(node_id))
+    }
+
+    /// Counts the removed and added lines generated by a node.
+    pub fn changed_lines(&self, node_id: &str) -> usize {
+        self.chunks
+            .iter()
+            .flat_map(CodeChunk::lines)
+            .filter(|line| line.nodes.iter().any(|node| node == node_id))
+            .count()
+    }
+}
+
+/// Compile two versions of a graph and diff the generated code
+///
+/// Both graphs are compiled with the same generator, old first. Fails with
+/// the first error either compilation reports.
+///
+/// Changed lines are attributed to the nodes whose code starts or ends on
+/// them, or else to the innermost node whose code spans them; so the header
+/// and closing lines of a branch belong to the branch, and the lines in
+/// between to the nodes of its bodies.
+pub fn preview_diff<P, G>(
+    old_graph: &GraphDescription,
+    new_graph: &GraphDescription,
+    metadata_provider: &P,
+    generator: &mut G,
+) -> Result<PreviewDiff, GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    let (old_code, old_map) = compile_with_source_map(old_graph, metadata_provider, generator)?;
+    let (new_code, new_map) = compile_with_source_map(new_graph, metadata_provider, generator)?;
+    let chunks = diff_chunks(&old_code, &old_map, &new_code, &new_map);
+    tracing::debug!("[PREVIEW] {} changed chunk(s)", chunks.len());
+
+    Ok(PreviewDiff {
+        old_code,
+        new_code,
+        chunks,
+    })
+}
+
+/// Line diff of the two outputs, grouped into runs of changed lines
+fn diff_chunks(old_code: &str, old_map: &SourceMap, new_code: &str, new_map: &SourceMap) -> Vec<CodeChunk> {
+    let old: Vec<&str> = old_code.lines().collect();
+    let new: Vec<&str> = new_code.lines().collect();
+
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    let mut chunks: Vec<CodeChunk> = Vec::new();
+    let mut current: Option<CodeChunk> = None;
+    for op in line_ops(old_middle, new_middle) {
+        let (i, j) = match op {
+            LineOp::Equal => {
+                chunks.extend(current.take());
+                continue;
+            }
+            LineOp::Remove(i, j) | LineOp::Add(i, j) => (prefix + i, prefix + j),
+        };
+        let chunk = current.get_or_insert_with(|| CodeChunk {
+            old_start: i + 1,
+            new_start: j + 1,
+            removed: Vec::new(),
+            added: Vec::new(),
+        });
+        if let LineOp::Remove(..) = op {
+            chunk.removed.push(changed_line(old_map, i + 1, old[i]));
+        } else {
+            chunk.added.push(changed_line(new_map, j + 1, new[j]));
+        }
+    }
+    chunks.extend(current);
+    chunks
+}
+
+/// An edit step; changes carry the positions of both sides before them
+#[derive(Debug, Clone, Copy)]
+enum LineOp {
+    Equal,
+    Remove(usize, usize),
+    Add(usize, usize),
+}
+
+/// Longest-common-subsequence edit script, or a single replacement for
+/// inputs over [`MAX_DIFFED_LINES`]
+fn line_ops(old: &[&str], new: &[&str]) -> Vec<LineOp> {
+    if old.len() + new.len() > MAX_DIFFED_LINES {
+        let removed = (0..old.len()).map(|i| LineOp::Remove(i, 0));
+        return removed.chain((0..new.len()).map(|j| LineOp::Add(old.len(), j))).collect();
+    }
+
+    // Longest common subsequence lengths of the suffixes
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(old.len() + new.len());
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            ops.push(LineOp::Equal);
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            ops.push(LineOp::Remove(i, j));
+            i += 1;
+        } else {
+            ops.push(LineOp::Add(i, j));
+            j += 1;
+        }
+    }
+    ops
+}
+
+fn changed_line(source_map: &SourceMap, line: usize, text: &str) -> ChangedLine {
+    ChangedLine {
+        line,
+        text: text.to_string(),
+        nodes: line_nodes(source_map, line),
+    }
+}
+
+/// Nodes whose code starts or ends on a line, or else the innermost node
+/// whose code spans it
+fn line_nodes(source_map: &SourceMap, line: usize) -> Vec<String> {
+    let spanning: Vec<&SourceMapping> = source_map
+        .mappings()
+        .iter()
+        .filter(|mapping| {
+            let (first, last) = line_range(mapping);
+            first <= line && line <= last
+        })
+        .collect();
+
+    let mut nodes: Vec<String> = spanning
+        .iter()
+        .filter(|mapping| {
+            let (first, last) = line_range(mapping);
+            first == line || last == line
+        })
+        .map(|mapping| mapping.node_id.clone())
+        .collect();
+    if nodes.is_empty() {
+        nodes.extend(
+            spanning
+                .iter()
+                .max_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)))
+                .map(|mapping| mapping.node_id.clone()),
+        );
+    }
+    nodes.sort();
+    nodes.dedup();
+    nodes
+}
+
+/// First and last line a region has code on (a region ending at the start of
+/// a line stops on the line before)
+fn line_range(mapping: &SourceMapping) -> (usize, usize) {
+    let last = if mapping.end.column == 1 && mapping.end.line > mapping.start.line {
+        mapping.end.line - 1
+    } else {
+        mapping.end.line
+    };
+    (mapping.start.line, last)
+}
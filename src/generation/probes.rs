@@ -0,0 +1,81 @@
+//! # Value Probes
+//!
+//! Reports the values of watched output pins to the host as they are
+//! computed.
+//!
+//! With [`CompileOptions::with_probes`](super::CompileOptions::with_probes),
+//! every output pin listed in [`NodeInstance::watched_pins`] gets a call to
+//! the sink's `on_value` right after its value is computed:
+//!
+//! ```text
+//! let node_add_1_result = add(2, 3);
+//! graphy_runtime::sink().on_value("add_1", "result", &node_add_1_result);
+//! print(node_add_1_result);
+//! ```
+//!
+//! The sink implements the `ValueSink` trait of the `graphy-runtime` crate,
+//! which the generated code depends on:
+//!
+//! ```ignore
+//! pub trait ValueSink: Send + Sync {
+//!     fn on_value(&self, node: &str, pin: &str, value: &dyn Debug);
+//! }
+//! ```
+//!
+//! Probed values are always bound to variables, so watched pure nodes are
+//! hoisted whatever the expression policy. The values covered are:
+//!
+//! - the result of pure nodes, variable reads, and function nodes (which
+//!   generators bind when [`CodeGeneratorContext::is_probed`](super::CodeGeneratorContext::is_probed))
+//! - the success and error values of fallible nodes, in their branches
+//! - event params, when bound with [`bind_event_params`](super::bind_event_params)
+//!
+//! Without the option, watched pins are ignored and the generated code is
+//! unchanged.
+
+use super::debug::string_literal;
+use crate::core::NodeInstance;
+use serde::{Deserialize, Serialize};
+
+/// Where value probes report to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ValueProbes {
+    /// Expression evaluating to the `graphy_runtime::ValueSink` to call,
+    /// in scope of the generated handlers
+    pub sink: String,
+}
+
+impl Default for ValueProbes {
+    /// Report to `graphy_runtime::sink()`
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ValueProbes {
+    /// Report to the global sink, `graphy_runtime::sink()`
+    pub fn new() -> Self {
+        Self {
+            sink: "graphy_runtime::sink()".to_string(),
+        }
+    }
+
+    /// Report to another sink, e.g. `state.probes`
+    pub fn with_sink(mut self, sink: impl Into<String>) -> Self {
+        self.sink = sink.into();
+        self
+    }
+
+    /// The call reporting the value of an output pin, e.g.
+    /// `graphy_runtime::sink().on_value("add_1", "result", &value)`
+    pub fn probe_call(&self, node: &NodeInstance, pin_name: &str, variable: &str) -> String {
+        format!(
+            "{}.on_value({}, {}, &{})",
+            self.sink,
+            string_literal(&node.id),
+            string_literal(pin_name),
+            variable
+        )
+    }
+}
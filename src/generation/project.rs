@@ -0,0 +1,104 @@
+//! # Project Compilation
+//!
+//! Compiles every graph of a [`GraphProject`] with one generator.
+//!
+//! Graphs are compiled in [dependency order](GraphProject::dependency_order),
+//! so the code of a graph comes after the code of the graphs it calls, and
+//! nodes calling other graphs get their metadata from the
+//! [project's provider](GraphProject::provider). Generators decide how a
+//! graph becomes callable, e.g. with
+//! [`graph_function_signature`](super::graph_function_signature) in
+//! [`begin_program`](super::CodeGenerator::begin_program).
+//!
+//! # Example
+//!
+//! ```ignore
+//! use graphy::generation::{compile_project, CompileOptions};
+//!
+//! let output = compile_project(&project, &registry, &mut generator, &CompileOptions::new())?;
+//! for compiled in &output.graphs {
+//!     std::fs::write(format!("{}.rs", compiled.name), &compiled.output.code)?;
+//! }
+//! ```
+
+use super::{compile_with_options, CodeGenerator, CompileOptions, CompileOutput};
+use crate::core::{GraphProject, NodeMetadataProvider};
+use crate::GraphyError;
+
+/// The compiled code of one graph of a project.
+#[derive(Debug, Clone)]
+pub struct CompiledGraph {
+    /// Name of the graph
+    pub name: String,
+
+    /// Code, source map, and report of the graph
+    pub output: CompileOutput,
+}
+
+/// Result of [`compile_project`]
+#[derive(Debug, Clone, Default)]
+pub struct ProjectOutput {
+    /// Compiled graphs, each after the graphs it calls
+    pub graphs: Vec<CompiledGraph>,
+}
+
+impl ProjectOutput {
+    /// Returns the output of a graph by name.
+    pub fn get(&self, name: &str) -> Option<&CompileOutput> {
+        self.graphs
+            .iter()
+            .find(|compiled| compiled.name == name)
+            .map(|compiled| &compiled.output)
+    }
+
+    /// Returns the code of every graph, in compilation order.
+    pub fn code(&self) -> String {
+        self.graphs.iter().map(|compiled| compiled.output.code.as_str()).collect()
+    }
+}
+
+/// Compile every graph of a project
+///
+/// Each graph is compiled like [`compile_with_options`], with the node types
+/// calling the project's graphs added to `metadata_provider`.
+///
+/// # Errors
+///
+/// - [`GraphyError::CyclicGraphReference`] if graphs call each other in a
+///   cycle, before anything is compiled
+/// - [`GraphyError::InGraph`] wrapping the error of the first graph that
+///   fails to compile
+/// - [`GraphyError::Cancelled`] once the options' cancellation token is
+///   cancelled
+pub fn compile_project<P, G>(
+    project: &GraphProject,
+    metadata_provider: &P,
+    generator: &mut G,
+    options: &CompileOptions,
+) -> Result<ProjectOutput, GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    let _span = tracing::info_span!("compile_project", project = %project.name).entered();
+    let order = project.dependency_order()?;
+    let provider = project.provider(metadata_provider);
+
+    let mut graphs = Vec::with_capacity(order.len());
+    for name in order {
+        let graph = &project.graphs[name];
+        let output = compile_with_options(graph, &provider, generator, options).map_err(|error| match error {
+            GraphyError::Cancelled => GraphyError::Cancelled,
+            error => GraphyError::InGraph {
+                graph: name.to_string(),
+                source: Box::new(error),
+            },
+        })?;
+        graphs.push(CompiledGraph {
+            name: name.to_string(),
+            output,
+        });
+    }
+
+    Ok(ProjectOutput { graphs })
+}
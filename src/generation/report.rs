@@ -0,0 +1,142 @@
+//! # Compilation Reports
+//!
+//! Timings and counters collected while compiling a graph.
+//!
+//! Enable reports with [`CompileOptions::with_report`] and read them from
+//! [`CompileOutput::report`]. Reports are serializable, so CI jobs can store
+//! them per graph and compare runs to catch regressions.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use graphy::generation::{compile_with_options, CompileOptions};
+//!
+//! let options = CompileOptions::new().with_report(true);
+//! let output = compile_with_options(&graph, &provider, &mut generator, &options)?;
+//!
+//! let report = output.report.unwrap();
+//! println!("{}", report);
+//! std::fs::write("report.json", serde_json::to_string(&report)?)?;
+//! ```
+//!
+//! [`CompileOptions::with_report`]: super::CompileOptions::with_report
+//! [`CompileOutput::report`]: super::CompileOutput::report
+
+use crate::analysis::PassTiming;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// Hit and miss counts of a cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheStats {
+    /// Lookups answered from the cache
+    pub hits: usize,
+
+    /// Values computed and added to the cache
+    pub misses: usize,
+}
+
+impl CacheStats {
+    /// Returns the fraction of lookups answered from the cache, in `[0, 1]`.
+    ///
+    /// Returns 0 if the cache was never used.
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+/// Timings and counters of one compilation.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CompilationReport {
+    /// Name of the compiled graph
+    pub graph_name: String,
+
+    /// Wall time of the whole compilation
+    pub total: Duration,
+
+    /// Sub-graph expansion time
+    pub expansion: Duration,
+
+    /// Analysis time (all passes)
+    pub analysis: Duration,
+
+    /// Code generation time
+    pub generation: Duration,
+
+    /// Analysis passes, in run order
+    pub passes: Vec<PassTiming>,
+
+    /// Nodes in the input graph
+    pub input_nodes: usize,
+
+    /// Nodes after sub-graph expansion
+    pub expanded_nodes: usize,
+
+    /// Connections after sub-graph expansion
+    pub connections: usize,
+
+    /// Pure nodes in the data flow evaluation order
+    pub pure_nodes: usize,
+
+    /// Pure nodes merged into an equivalent node
+    #[serde(default)]
+    pub merged_nodes: usize,
+
+    /// Event nodes generated
+    pub events: usize,
+
+    /// Distinct nodes that produced code
+    pub generated_nodes: usize,
+
+    /// Pure expression cache of the code generator
+    pub expression_cache: CacheStats,
+}
+
+impl CompilationReport {
+    /// Creates an empty report for a graph.
+    pub fn new(graph_name: impl Into<String>) -> Self {
+        Self {
+            graph_name: graph_name.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the duration of an analysis pass, if it ran.
+    pub fn pass_duration(&self, name: &str) -> Option<Duration> {
+        self.passes.iter().find(|pass| pass.name == name).map(|pass| pass.duration)
+    }
+}
+
+impl fmt::Display for CompilationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Compilation of '{}': {:?}", self.graph_name, self.total)?;
+        writeln!(f, "  expansion:  {:?}", self.expansion)?;
+        writeln!(f, "  analysis:   {:?}", self.analysis)?;
+        for pass in &self.passes {
+            writeln!(f, "    {}: {:?}", pass.name, pass.duration)?;
+        }
+        writeln!(f, "  generation: {:?}", self.generation)?;
+        write!(
+            f,
+            "  nodes: {} input, {} expanded, {} pure, {} generated ({} events)",
+            self.input_nodes, self.expanded_nodes, self.pure_nodes, self.generated_nodes, self.events
+        )?;
+        if self.merged_nodes > 0 {
+            write!(f, ", {} merged", self.merged_nodes)?;
+        }
+        writeln!(f)?;
+        write!(
+            f,
+            "  expression cache: {} hits, {} misses ({:.1}% hit rate)",
+            self.expression_cache.hits,
+            self.expression_cache.misses,
+            self.expression_cache.hit_rate() * 100.0
+        )
+    }
+}
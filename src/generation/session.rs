@@ -0,0 +1,450 @@
+//! # Compilation Sessions
+//!
+//! Owned, resumable compilation for background jobs.
+//!
+//! A [`CodeGeneratorContext`] borrows the graph, provider, and analysis
+//! results, so it can't outlive the call that created it. A
+//! [`CompilationSession`] owns everything instead: the immutable analysis
+//! results live in an [`Arc`]-shared [`SessionAnalysis`], and the mutable
+//! output and caches in the session itself. Sessions are `Send` when the
+//! provider is `Send + Sync`, so they can be moved to worker threads and
+//! resumed there.
+//!
+//! Generation runs in steps: [`step`](CompilationSession::step) generates the
+//! program prelude, then one event per call, then the epilogue. A context is
+//! only built for the duration of a step. Several sessions can share one
+//! analysis, e.g. to compile the same graph with different generators.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use graphy::generation::{CompilationSession, CompileOptions};
+//! use std::sync::Arc;
+//!
+//! let mut session = CompilationSession::new(&graph, Arc::new(registry), &generator, &CompileOptions::new())?;
+//! let job = std::thread::spawn(move || {
+//!     while session.step(&mut generator)? {
+//!         // check for cancellation between events
+//!     }
+//!     session.finish(&mut generator)
+//! });
+//! let output = job.join().unwrap()?;
+//! ```
+
+use super::{
+    analyze_graph, check_async_support, event_nodes, generate_branch_helpers, generate_dispatcher, generate_event_node,
+    BranchHelpers, CodeGenerator, CodeGeneratorContext, CompileOptions, CompileOutput, DebugHooks, EventDispatcher, ExpressionPolicy,
+    GenerationState, HelperPlan, InlineLimits, ParallelCodegen, ValueProbes,
+};
+use crate::analysis::{AsyncAnalysis, DataResolver, ExecutionRouting};
+use crate::core::{GraphDescription, NodeMetadataProvider};
+use crate::utils::{CancellationToken, LanguageProfile, ProgressSink, PHASE_CODEGEN};
+use crate::GraphyError;
+use std::sync::Arc;
+
+/// Analysis results of a graph, shared by the sessions compiling it.
+pub struct SessionAnalysis<P> {
+    /// The graph with sub-graphs expanded
+    graph: GraphDescription,
+    metadata_provider: Arc<P>,
+    data_resolver: DataResolver,
+    exec_routing: ExecutionRouting,
+    async_analysis: AsyncAnalysis,
+    language: Arc<dyn LanguageProfile>,
+
+    /// Event node IDs, in generation order
+    events: Vec<String>,
+}
+
+impl<P: NodeMetadataProvider> SessionAnalysis<P> {
+    /// Expand and analyze a graph like [`compile`](super::compile)
+    ///
+    /// Result variables and constants follow `language`. Only
+    /// [`CompileOptions::common_subexpressions`],
+    /// [`CompileOptions::evaluation_order`],
+    /// [`CompileOptions::cancellation`], and [`CompileOptions::progress`]
+    /// apply here; reports and verbosity are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Fails like [`compile`](super::compile) on expansion and analysis errors.
+    pub fn analyze(
+        graph: &GraphDescription,
+        metadata_provider: Arc<P>,
+        language: Arc<dyn LanguageProfile>,
+        options: &CompileOptions,
+    ) -> Result<Self, GraphyError> {
+        let _span = tracing::info_span!("session_analysis", graph = %graph.metadata.name).entered();
+        let analyzed = analyze_graph(graph, metadata_provider.as_ref(), language.as_ref(), options, &mut None)?;
+        let events = event_nodes(&analyzed.expanded, metadata_provider.as_ref())
+            .into_iter()
+            .map(|(node, _)| node.id.clone())
+            .collect();
+
+        Ok(Self {
+            graph: analyzed.expanded,
+            metadata_provider,
+            data_resolver: analyzed.data_resolver,
+            exec_routing: analyzed.exec_routing,
+            async_analysis: analyzed.async_analysis,
+            language,
+            events,
+        })
+    }
+
+    /// The analyzed graph, with sub-graphs expanded
+    pub fn graph(&self) -> &GraphDescription {
+        &self.graph
+    }
+
+    /// Get the metadata provider
+    pub fn metadata_provider(&self) -> &P {
+        &self.metadata_provider
+    }
+
+    /// Get the data flow resolver
+    pub fn data_resolver(&self) -> &DataResolver {
+        &self.data_resolver
+    }
+
+    /// Get the execution routing table
+    pub fn exec_routing(&self) -> &ExecutionRouting {
+        &self.exec_routing
+    }
+
+    /// Get the events and nodes that need async code
+    pub fn async_analysis(&self) -> &AsyncAnalysis {
+        &self.async_analysis
+    }
+
+    /// Get the language profile the analysis was built with
+    pub fn language(&self) -> &Arc<dyn LanguageProfile> {
+        &self.language
+    }
+
+    /// Event node IDs, in the order sessions generate them
+    pub fn events(&self) -> &[String] {
+        &self.events
+    }
+}
+
+/// What a session generates next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SessionStep {
+    /// `begin_program`, then `generate_externals` and `generate_state` if the
+    /// graph has externals and variables, then any branch helpers
+    Begin,
+
+    /// The event at this index of [`SessionAnalysis::events`]
+    Event(usize),
+
+    /// `end_program`
+    End,
+
+    Done,
+}
+
+/// An owned, resumable compilation of one graph.
+///
+/// See the [module docs](self).
+pub struct CompilationSession<P> {
+    analysis: Arc<SessionAnalysis<P>>,
+    expression_policy: ExpressionPolicy,
+    inline_limits: InlineLimits,
+
+    /// Moved into the context of each step and back
+    async_analysis: AsyncAnalysis,
+    cancellation: CancellationToken,
+    progress: Option<Arc<dyn ProgressSink>>,
+    parallel: Option<ParallelCodegen>,
+    branch_helpers: Option<BranchHelpers>,
+    dispatcher: Option<EventDispatcher>,
+    debug: Option<DebugHooks>,
+    probes: Option<ValueProbes>,
+
+    /// Planned by the first step, which knows the generator
+    helper_plan: Option<Arc<HelperPlan>>,
+    state: GenerationState,
+    next: SessionStep,
+}
+
+impl<P: NodeMetadataProvider> CompilationSession<P> {
+    /// Analyze a graph for a generator and start a session
+    ///
+    /// # Errors
+    ///
+    /// Fails like [`compile`](super::compile) on analysis errors, or if an
+    /// event runs async nodes but the generator doesn't support async code.
+    pub fn new<G: CodeGenerator>(
+        graph: &GraphDescription,
+        metadata_provider: Arc<P>,
+        generator: &G,
+        options: &CompileOptions,
+    ) -> Result<Self, GraphyError> {
+        let analysis = SessionAnalysis::analyze(graph, metadata_provider, generator.language(), options)?;
+        let mut session = Self::from_analysis(Arc::new(analysis), generator)?;
+        session.cancellation = options.cancellation.clone();
+        session.progress = options.progress.clone();
+        session.parallel = options.parallel;
+        session.branch_helpers = options.branch_helpers;
+        session.dispatcher = options.dispatcher.clone();
+        session.debug = options.debug.clone();
+        session.probes = options.probes.clone();
+        session.inline_limits = options.inline_limits;
+        Ok(session)
+    }
+
+    /// Start a session over an existing analysis
+    ///
+    /// The generator's expression policy applies; its language profile
+    /// doesn't, since the analysis was already built with one.
+    ///
+    /// # Errors
+    ///
+    /// Fails if an event runs async nodes but the generator doesn't support
+    /// async code.
+    pub fn from_analysis<G: CodeGenerator>(
+        analysis: Arc<SessionAnalysis<P>>,
+        generator: &G,
+    ) -> Result<Self, GraphyError> {
+        check_async_support(generator, &analysis.async_analysis)?;
+        Ok(Self {
+            async_analysis: analysis.async_analysis.clone(),
+            expression_policy: generator.expression_policy(),
+            inline_limits: InlineLimits::default(),
+            analysis,
+            cancellation: CancellationToken::new(),
+            progress: None,
+            parallel: None,
+            branch_helpers: None,
+            dispatcher: None,
+            debug: None,
+            probes: None,
+            helper_plan: None,
+            state: GenerationState::default(),
+            next: SessionStep::Begin,
+        })
+    }
+
+    /// Set the token that cancels generation
+    ///
+    /// Steps fail with [`GraphyError::Cancelled`] once it is cancelled.
+    /// Sessions started with [`new`](Self::new) use the token of their
+    /// [`CompileOptions`].
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// Report generated events to a progress sink
+    ///
+    /// Each step reports the [`PHASE_CODEGEN`] phase. Sessions started with
+    /// [`new`](Self::new) use the sink of their [`CompileOptions`], which
+    /// also gets the analysis phases.
+    pub fn with_progress(mut self, progress: Arc<dyn ProgressSink>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Evaluate expensive, independent pure inputs concurrently
+    ///
+    /// See [`CompileOptions::with_parallel_codegen`]. Sessions started with
+    /// [`new`](Self::new) use the setting of their [`CompileOptions`].
+    pub fn with_parallel_codegen(mut self, parallel: ParallelCodegen) -> Self {
+        self.parallel = Some(parallel);
+        self
+    }
+
+    /// Generate repeated exec branches once, as helper functions
+    ///
+    /// See [`CompileOptions::with_branch_helpers`]. Sessions started with
+    /// [`new`](Self::new) use the setting of their [`CompileOptions`]. Has
+    /// no effect once the first step has run.
+    pub fn with_branch_helpers(mut self, branch_helpers: BranchHelpers) -> Self {
+        self.branch_helpers = Some(branch_helpers);
+        self
+    }
+
+    /// Generate a dispatcher forwarding to every event's handler
+    ///
+    /// See [`CompileOptions::with_dispatcher`]. Sessions started with
+    /// [`new`](Self::new) use the setting of their [`CompileOptions`].
+    pub fn with_dispatcher(mut self, dispatcher: EventDispatcher) -> Self {
+        self.dispatcher = Some(dispatcher);
+        self
+    }
+
+    /// Build for debugging, calling the host at flagged nodes
+    ///
+    /// See [`CompileOptions::with_debug`]. Sessions started with
+    /// [`new`](Self::new) use the setting of their [`CompileOptions`]. Has
+    /// no effect once the first step has run.
+    pub fn with_debug(mut self, debug: DebugHooks) -> Self {
+        self.debug = Some(debug);
+        self
+    }
+
+    /// Report the values of watched pins
+    ///
+    /// See [`CompileOptions::with_probes`]. Sessions started with
+    /// [`new`](Self::new) use the setting of their [`CompileOptions`]. Has
+    /// no effect once the first step has run.
+    pub fn with_probes(mut self, probes: ValueProbes) -> Self {
+        self.probes = Some(probes);
+        self
+    }
+
+    /// Set how deep and long inlined pure expressions may get
+    ///
+    /// See [`CompileOptions::with_inline_limits`]. Sessions started with
+    /// [`new`](Self::new) use the setting of their [`CompileOptions`].
+    pub fn with_inline_limits(mut self, limits: InlineLimits) -> Self {
+        self.inline_limits = limits;
+        self
+    }
+
+    /// Get the shared analysis results
+    pub fn analysis(&self) -> &Arc<SessionAnalysis<P>> {
+        &self.analysis
+    }
+
+    /// Check if every step has run
+    pub fn is_finished(&self) -> bool {
+        self.next == SessionStep::Done
+    }
+
+    /// Number of events not generated yet
+    pub fn remaining_events(&self) -> usize {
+        let total = self.analysis.events.len();
+        match self.next {
+            SessionStep::Begin => total,
+            SessionStep::Event(index) => total - index,
+            SessionStep::End | SessionStep::Done => 0,
+        }
+    }
+
+    /// Run the next generation step
+    ///
+    /// Returns whether any steps remain. A failed step isn't advanced past,
+    /// but the code it emitted before failing stays in the output.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a generator hook returns an error, or with
+    /// [`GraphyError::Cancelled`] once the session's token is cancelled.
+    pub fn step<G: CodeGenerator>(&mut self, generator: &mut G) -> Result<bool, GraphyError> {
+        self.cancellation.check()?;
+        let analysis = Arc::clone(&self.analysis);
+        if self.next == SessionStep::Begin {
+            self.helper_plan = self
+                .branch_helpers
+                .filter(|_| generator.supports_branch_helpers() && self.debug.is_none() && self.probes.is_none())
+                .map(|branch_helpers| {
+                    Arc::new(HelperPlan::compute(
+                        branch_helpers,
+                        &analysis.graph,
+                        analysis.metadata_provider.as_ref(),
+                        &analysis.exec_routing,
+                        &analysis.data_resolver,
+                        analysis.language.as_ref(),
+                    ))
+                });
+        }
+        let mut ctx = CodeGeneratorContext::new(
+            &analysis.graph,
+            analysis.metadata_provider.as_ref(),
+            &analysis.data_resolver,
+            &analysis.exec_routing,
+        )
+        .with_expression_policy(self.expression_policy)
+        .with_inline_limits(self.inline_limits)
+        .with_language(Arc::clone(&analysis.language))
+        .with_async_analysis(std::mem::take(&mut self.async_analysis))
+        .with_cancellation(self.cancellation.clone())
+        .with_parallel_codegen(self.parallel)
+        .with_branch_helpers(self.helper_plan.clone())
+        .with_debug(self.debug.clone())
+        .with_probes(self.probes.clone())
+        .with_state(std::mem::take(&mut self.state));
+
+        let result = self.run_step(generator, &mut ctx);
+        self.async_analysis = std::mem::take(&mut ctx.async_analysis);
+        self.state = ctx.into_state();
+        self.next = result?;
+        if let Some(progress) = &self.progress {
+            let total = self.analysis.events.len();
+            progress.report(PHASE_CODEGEN, total - self.remaining_events(), total);
+        }
+        Ok(!self.is_finished())
+    }
+
+    /// Run the remaining steps and take the output
+    ///
+    /// The output has no report.
+    ///
+    /// # Errors
+    ///
+    /// Fails like [`step`](Self::step).
+    pub fn finish<G: CodeGenerator>(mut self, generator: &mut G) -> Result<CompileOutput, GraphyError> {
+        while self.step(generator)? {}
+        let expression_cache = self.state.expression_cache_stats();
+        tracing::debug!("[SESSION] Finished, expression cache: {:?}", expression_cache);
+
+        let (code, source_map) = self.state.finish();
+        Ok(CompileOutput {
+            code,
+            source_map,
+            report: None,
+        })
+    }
+
+    /// Run the next step, returning the one after it
+    fn run_step<G: CodeGenerator>(
+        &self,
+        generator: &mut G,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+    ) -> Result<SessionStep, GraphyError> {
+        let analysis = &self.analysis;
+        let after_events = |index: usize| {
+            if index < analysis.events.len() {
+                SessionStep::Event(index)
+            } else {
+                SessionStep::End
+            }
+        };
+
+        match self.next {
+            SessionStep::Begin => {
+                generator.begin_program(ctx)?;
+                if !analysis.graph.externals.is_empty() {
+                    generator.generate_externals(ctx)?;
+                }
+                if !analysis.graph.variables.is_empty() {
+                    generator.generate_state(ctx)?;
+                }
+                generate_branch_helpers(generator, ctx)?;
+                Ok(after_events(0))
+            }
+            SessionStep::Event(index) => {
+                let event_id = &analysis.events[index];
+                let node = analysis
+                    .graph
+                    .nodes
+                    .get(event_id)
+                    .ok_or_else(|| GraphyError::NodeNotFound(event_id.clone()))?;
+                let metadata = analysis.metadata_provider.metadata_for_node(node).ok_or_else(|| {
+                    GraphyError::CodeGeneration(format!("No metadata for node type '{}'", node.node_type))
+                })?;
+                tracing::debug!("[SESSION] Generating event {}", event_id);
+                generate_event_node(generator, ctx, node, metadata)?;
+                Ok(after_events(index + 1))
+            }
+            SessionStep::End => {
+                generate_dispatcher(generator, ctx, self.dispatcher.as_ref())?;
+                generator.end_program(ctx)?;
+                Ok(SessionStep::Done)
+            }
+            SessionStep::Done => Ok(SessionStep::Done),
+        }
+    }
+}
@@ -0,0 +1,262 @@
+//! # Source Mapping
+//!
+//! Maps regions of generated code back to the nodes that produced them.
+//!
+//! Generators write output through a [`CodeBuffer`], marking which node (and
+//! optionally which pin) each piece of code belongs to. The buffer tracks
+//! line/column positions and produces a [`SourceMap`] alongside the code.
+//! When the generated code fails to compile or panics, the reported location
+//! can be resolved back to a node with [`SourceMap::resolve`] or
+//! [`SourceMap::resolve_rustc_error`].
+//!
+//! Lines and columns are 1-based (matching rustc diagnostics); span ends
+//! are exclusive.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::generation::CodeBuffer;
+//!
+//! let mut buffer = CodeBuffer::new();
+//! buffer.push_str("fn main() {\n");
+//! buffer.write_node("add_1", None, "    let x = 1 + 2;\n");
+//! buffer.push_str("}\n");
+//!
+//! let (code, source_map) = buffer.finish();
+//! assert!(code.contains("1 + 2"));
+//!
+//! let mapping = source_map.resolve(2, 13).unwrap();
+//! assert_eq!(mapping.node_id, "add_1");
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+/// A position in generated code (1-based line and column).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct LineColumn {
+    /// Line number, starting at 1
+    pub line: usize,
+
+    /// Column number (in characters), starting at 1
+    pub column: usize,
+}
+
+impl LineColumn {
+    /// Creates a new position.
+    #[inline(always)]
+    pub const fn new(line: usize, column: usize) -> Self {
+        Self { line, column }
+    }
+}
+
+/// A mapping from a region of generated code to a node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceMapping {
+    /// First position of the region
+    pub start: LineColumn,
+
+    /// Position just past the end of the region
+    pub end: LineColumn,
+
+    /// ID of the node that generated this region
+    pub node_id: String,
+
+    /// Pin the region belongs to (e.g. an input expression or exec output)
+    pub pin: Option<String>,
+}
+
+impl SourceMapping {
+    /// Checks if a position falls inside this region.
+    #[inline]
+    pub fn contains(&self, position: LineColumn) -> bool {
+        self.start <= position && position < self.end
+    }
+}
+
+/// Mappings from generated code regions back to nodes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceMap {
+    /// All mappings, in the order their regions were closed
+    mappings: Vec<SourceMapping>,
+}
+
+impl SourceMap {
+    /// Creates an empty source map.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a mapping.
+    #[inline]
+    pub fn add(&mut self, mapping: SourceMapping) {
+        self.mappings.push(mapping);
+    }
+
+    /// Returns all mappings.
+    #[inline]
+    pub fn mappings(&self) -> &[SourceMapping] {
+        &self.mappings
+    }
+
+    /// Checks if the source map has no mappings.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.mappings.is_empty()
+    }
+
+    /// Finds the innermost mapping containing a position.
+    ///
+    /// When regions are nested (e.g. a pure expression inlined into a
+    /// function call), the most specific region wins.
+    pub fn resolve(&self, line: usize, column: usize) -> Option<&SourceMapping> {
+        let position = LineColumn::new(line, column);
+
+        self.mappings
+            .iter()
+            .filter(|mapping| mapping.contains(position))
+            .max_by(|a, b| a.start.cmp(&b.start).then(b.end.cmp(&a.end)))
+    }
+
+    /// Returns all mappings generated by a node.
+    pub fn mappings_for_node<'s>(&'s self, node_id: &'s str) -> impl Iterator<Item = &'s SourceMapping> + 's {
+        self.mappings.iter().filter(move |mapping| mapping.node_id == node_id)
+    }
+
+    /// Resolves the primary location of a rustc diagnostic to a node.
+    ///
+    /// Looks for the first `--> file:line:column` marker in the message
+    /// (the format used by both rustc errors and panic messages without the
+    /// arrow, e.g. `panicked at src/main.rs:12:5`).
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let stderr = String::from_utf8_lossy(&output.stderr);
+    /// if let Some(mapping) = source_map.resolve_rustc_error(&stderr) {
+    ///     editor.highlight_node(&mapping.node_id);
+    /// }
+    /// ```
+    pub fn resolve_rustc_error(&self, message: &str) -> Option<&SourceMapping> {
+        let (line, column) = parse_diagnostic_location(message)?;
+        self.resolve(line, column)
+    }
+}
+
+/// Extract `line:column` from a rustc diagnostic or panic message
+fn parse_diagnostic_location(message: &str) -> Option<(usize, usize)> {
+    let location = message
+        .lines()
+        .find_map(|line| {
+            let line = line.trim_start();
+            line.strip_prefix("--> ")
+                .or_else(|| line.split_once("panicked at ").map(|(_, rest)| rest))
+        })?
+        .trim()
+        .trim_end_matches(':');
+
+    // `path:line:column` - the path itself may contain ':' (e.g. on Windows)
+    let mut parts = location.rsplitn(3, ':');
+    let column = parts.next()?.trim().parse().ok()?;
+    let line = parts.next()?.trim().parse().ok()?;
+    Some((line, column))
+}
+
+/// Output buffer that records a [`SourceMap`] while code is written.
+#[derive(Debug, Clone)]
+pub struct CodeBuffer {
+    code: String,
+    position: LineColumn,
+    source_map: SourceMap,
+
+    /// Regions that have been opened but not closed yet
+    open_regions: Vec<(String, Option<String>, LineColumn)>,
+}
+
+impl CodeBuffer {
+    /// Creates an empty buffer.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            code: String::new(),
+            position: LineColumn::new(1, 1),
+            source_map: SourceMap::new(),
+            open_regions: Vec::new(),
+        }
+    }
+
+    /// Appends code that doesn't belong to a specific node (or to the
+    /// currently open region).
+    pub fn push_str(&mut self, code: &str) {
+        for c in code.chars() {
+            if c == '\n' {
+                self.position.line += 1;
+                self.position.column = 1;
+            } else {
+                self.position.column += 1;
+            }
+        }
+        self.code.push_str(code);
+    }
+
+    /// Opens a region belonging to a node (and optionally a pin).
+    ///
+    /// Regions may be nested; each must be closed with [`end_node`](Self::end_node).
+    pub fn begin_node(&mut self, node_id: impl Into<String>, pin: Option<&str>) {
+        self.open_regions
+            .push((node_id.into(), pin.map(str::to_string), self.position));
+    }
+
+    /// Closes the most recently opened region.
+    ///
+    /// Does nothing if no region is open.
+    pub fn end_node(&mut self) {
+        if let Some((node_id, pin, start)) = self.open_regions.pop() {
+            self.source_map.add(SourceMapping {
+                start,
+                end: self.position,
+                node_id,
+                pin,
+            });
+        }
+    }
+
+    /// Appends code belonging to a node as a single region.
+    pub fn write_node(&mut self, node_id: impl Into<String>, pin: Option<&str>, code: &str) {
+        self.begin_node(node_id, pin);
+        self.push_str(code);
+        self.end_node();
+    }
+
+    /// Returns the code written so far.
+    #[inline]
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// Returns the current write position.
+    #[inline]
+    pub fn position(&self) -> LineColumn {
+        self.position
+    }
+
+    /// Returns the source map built so far (closed regions only).
+    #[inline]
+    pub fn source_map(&self) -> &SourceMap {
+        &self.source_map
+    }
+
+    /// Closes any open regions and returns the code with its source map.
+    pub fn finish(mut self) -> (String, SourceMap) {
+        while !self.open_regions.is_empty() {
+            self.end_node();
+        }
+        (self.code, self.source_map)
+    }
+}
+
+impl Default for CodeBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -2,54 +2,852 @@
 //!
 //! Traits and utilities for implementing code generation strategies.
 
-use crate::core::{NodeInstance, NodeMetadata};
+use super::{event_dispatcher, CodeGeneratorContext, DebugHooks, EventDispatcher, ExpressionPolicy, HelperParam, ValueProbes};
+use crate::analysis::EventAnalysis;
+use crate::core::{
+    ExternalDecl, GraphDescription, NodeInstance, NodeMetadata, NodeMetadataProvider, ParamInfo, PropertyValue,
+};
+use crate::utils::{LanguageProfile, RustProfile};
 use crate::GraphyError;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::sync::Arc;
 
 /// Trait for code generation strategies
 ///
 /// Implement this trait to create custom code generators for different
-/// target languages or platforms.
-pub trait CodeGenerator {
-    /// Generate code for a pure node (inline expression)
-    fn generate_pure_node(
-        &self,
+/// target languages or platforms, then run it with [`compile`](super::compile).
+/// The driver handles analysis and traversal; generators only decide what
+/// code each kind of node turns into.
+///
+/// Statement hooks write their output with [`CodeGeneratorContext::emit`].
+/// The driver wraps every hook call in a source map region for the node,
+/// so generators don't need to track node IDs themselves.
+///
+/// # Traversal
+///
+/// - Event nodes are visited in ID order. [`generate_event`](Self::generate_event)
+///   is responsible for emitting the body of each of its exec outputs with
+///   [`generate_exec_output`](super::generate_exec_output).
+/// - After [`generate_function_node`](Self::generate_function_node), the
+///   driver continues with every exec output of the node automatically.
+/// - [`generate_control_flow`](Self::generate_control_flow) emits its own
+///   branches, like events.
+/// - Pure nodes are rendered through
+///   [`generate_pure_expression`](Self::generate_pure_expression) once per
+///   scope, then inlined or hoisted into a binding with
+///   [`generate_binding`](Self::generate_binding) depending on the
+///   [`expression_policy`](Self::expression_policy).
+///
+/// # Async
+///
+/// Graphs with async nodes ([`NodeMetadata::is_async`]) only compile if
+/// [`supports_async`](Self::supports_async) returns `true`. Generators then
+/// emit events for which [`CodeGeneratorContext::is_async_event`] holds as
+/// async functions, and await async function nodes in
+/// [`generate_function_node`](Self::generate_function_node). Async pure
+/// expressions are awaited by the driver through
+/// [`generate_await`](Self::generate_await).
+///
+/// # Fallible nodes
+///
+/// Fallible function nodes ([`NodeMetadata::is_fallible`]) don't go through
+/// [`generate_function_node`](Self::generate_function_node). The driver
+/// renders their call with
+/// [`generate_fallible_call`](Self::generate_fallible_call) and wraps it in
+/// a result match: [`begin_result_match`](Self::begin_result_match) opens
+/// the success branch holding the node's regular exec outputs,
+/// [`generate_error_arm`](Self::generate_error_arm) opens the branch holding
+/// its [`ERROR_EXEC_PIN`](crate::core::ERROR_EXEC_PIN) flow, and
+/// [`end_result_match`](Self::end_result_match) closes both. The defaults
+/// emit a Rust `match`.
+///
+/// # Switch nodes
+///
+/// Switch nodes ([`NodeMetadata::is_switch`]) don't go through
+/// [`generate_control_flow`](Self::generate_control_flow) either. The driver
+/// opens a match on their value with [`begin_switch`](Self::begin_switch),
+/// emits one arm per case (see
+/// [`resolve_switch_cases`](crate::core::resolve_switch_cases)) with
+/// [`begin_switch_arm`](Self::begin_switch_arm) and
+/// [`end_switch_arm`](Self::end_switch_arm), and closes it with
+/// [`end_switch`](Self::end_switch). A `_` arm holding the
+/// [`SWITCH_DEFAULT_PIN`](crate::core::SWITCH_DEFAULT_PIN) flow is added
+/// unless the cases cover every variant of the value's enum and the default
+/// flow is empty. The defaults emit a Rust `match`.
+///
+/// # Memoization
+///
+/// Pure nodes with [`NodeMetadata::memoize`] are rendered twice over: the
+/// driver asks [`memo_key`](Self::memo_key) how to cache each input, renders
+/// the call with [`generate_pure_expression`](Self::generate_pure_expression)
+/// on bindings of the inputs, and wraps it with
+/// [`generate_memoized`](Self::generate_memoized). The defaults emit a Rust
+/// block caching results in a thread-local `HashMap`. Nodes with an input
+/// `memo_key` rejects, or without a return type, are generated as plain
+/// expressions: to opt out for types that aren't hashable, leave them to the
+/// default `memo_key` or return `None` for them.
+///
+/// # Parallel inputs
+///
+/// With [`CompileOptions::with_parallel_codegen`](super::CompileOptions::with_parallel_codegen),
+/// generators for which [`supports_parallel`](Self::supports_parallel)
+/// returns `true` evaluate expensive, independent pure inputs of a node
+/// concurrently (see [`ParallelCodegen`](super::ParallelCodegen)). Before
+/// the node, the driver opens one branch per input with
+/// [`begin_parallel_branch`](Self::begin_parallel_branch), emits the
+/// bindings of the input's subtree one level deeper, ends the branch with
+/// its value through [`end_parallel_branch`](Self::end_parallel_branch),
+/// and closes them all with [`end_parallel`](Self::end_parallel); the node
+/// then reads each input from its result variable. The defaults emit nested
+/// `rayon::join` calls, so the generated crate needs `rayon`, and values
+/// the subtrees read must be `Sync`.
+///
+/// # Branch helpers
+///
+/// With [`CompileOptions::with_branch_helpers`](super::CompileOptions::with_branch_helpers),
+/// generators for which [`supports_branch_helpers`](Self::supports_branch_helpers)
+/// returns `true` emit repeated exec branches once (see
+/// [`BranchHelpers`](super::BranchHelpers)). After the state and before the
+/// events, the driver opens a function per class of branches with
+/// [`begin_branch_helper`](Self::begin_branch_helper), generates the first
+/// branch of the class one level deeper (its differing constants read from
+/// the [`HelperParam`]s), and closes it with
+/// [`end_branch_helper`](Self::end_branch_helper). Wherever a branch of the
+/// class starts, the driver emits
+/// [`generate_branch_helper_call`](Self::generate_branch_helper_call)
+/// instead. The defaults emit a Rust `fn` and a call to it.
+///
+/// # Event dispatchers
+///
+/// With [`CompileOptions::with_dispatcher`](super::CompileOptions::with_dispatcher),
+/// the driver calls [`generate_dispatcher`](Self::generate_dispatcher) once
+/// after the events and before [`end_program`](Self::end_program), with the
+/// graph's [`EventAnalysis`]. The default emits a Rust struct implementing
+/// the host's trait (see [`EventDispatcher`]), so it assumes handlers are
+/// named after their event node IDs and take the state first.
+///
+/// # Debug hooks
+///
+/// With [`CompileOptions::with_debug`](super::CompileOptions::with_debug),
+/// the driver calls into the host at function and control flow nodes with
+/// debug flags, and at variable writes, after resolving their arguments. For
+/// a [traced](NodeInstance::trace) node, it binds each argument to a variable
+/// and reports it with [`generate_debug_trace`](Self::generate_debug_trace),
+/// then passes the variable to the node instead. Then, for a node with a
+/// [breakpoint](NodeInstance::breakpoint), it emits
+/// [`generate_debug_hit`](Self::generate_debug_hit). The defaults emit Rust
+/// calls to the [`DebugHooks`] functions.
+///
+/// # Value probes
+///
+/// With [`CompileOptions::with_probes`](super::CompileOptions::with_probes),
+/// the driver reports each [watched](NodeInstance::watched_pins) output with
+/// [`generate_value_probe`](Self::generate_value_probe) once its value is in
+/// a variable: after hoisting a pure node or variable read, after a function
+/// node, at the start of a fallible node's success or error branch, and
+/// after binding event params. Watched pure nodes are always hoisted, and
+/// function nodes must bind their result when
+/// [`CodeGeneratorContext::is_probed`] says it is probed, even if it is
+/// never read.
+pub trait CodeGenerator: Sized {
+    /// How pure node expressions should be placed
+    fn expression_policy(&self) -> ExpressionPolicy {
+        ExpressionPolicy::default()
+    }
+
+    /// Rules of the language this generator emits
+    ///
+    /// Used for result variable names, default values, and constant
+    /// literals. Defaults to [`RustProfile`].
+    fn language(&self) -> Arc<dyn LanguageProfile> {
+        Arc::new(RustProfile)
+    }
+
+    /// Whether this generator can emit async events and await async nodes
+    fn supports_async(&self) -> bool {
+        false
+    }
+
+    /// Whether this generator can evaluate pure inputs concurrently
+    ///
+    /// Without it, [`ParallelCodegen`](super::ParallelCodegen) options are
+    /// ignored.
+    fn supports_parallel(&self) -> bool {
+        false
+    }
+
+    /// Whether this generator can emit repeated branches as helper functions
+    ///
+    /// Without it, [`BranchHelpers`](super::BranchHelpers) options are
+    /// ignored.
+    fn supports_branch_helpers(&self) -> bool {
+        false
+    }
+
+    /// Identifies this generator's output in a [compilation cache](crate::cache)
+    ///
+    /// Two generators with the same ID must produce the same code for the
+    /// same graph. Defaults to the type name; generators with options that
+    /// change their output should include those options (and a version, if
+    /// the output changes between releases).
+    fn cache_id(&self) -> String {
+        std::any::type_name::<Self>().to_string()
+    }
+
+    /// Await an expression produced by an async node
+    ///
+    /// Defaults to `<expr>.await`.
+    fn generate_await<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        expr: &str,
+    ) -> Result<String, GraphyError> {
+        Ok(format!("{}.await", expr))
+    }
+
+    /// How a memoized node's input of a type is cached
+    ///
+    /// `value` is the name the input is bound to. Return `None` if values of
+    /// the type can't be cached, so the node isn't memoized. Defaults to the
+    /// value itself for Rust integers, `bool`, and `char`, a clone for
+    /// `String`, and the bit pattern for `f32` and `f64` (so `0.0` and
+    /// `-0.0` are cached separately, and every NaN by its bits).
+    fn memo_key(&self, type_string: &str, value: &str) -> Option<MemoKey> {
+        let (key_type, expr) = match type_string {
+            "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64" | "u128" | "usize"
+            | "bool" | "char" => (type_string, value.to_string()),
+            "String" => ("String", format!("{}.clone()", value)),
+            "f32" => ("u32", format!("{}.to_bits()", value)),
+            "f64" => ("u64", format!("{}.to_bits()", value)),
+            _ => return None,
+        };
+        Some(MemoKey {
+            key_type: key_type.to_string(),
+            expr,
+        })
+    }
+
+    /// Wrap the call of a memoized pure node in its cache
+    ///
+    /// Defaults to [`memoized_block`] with a static named after the node.
+    fn generate_memoized<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        call: &MemoizedCall,
+    ) -> Result<String, GraphyError> {
+        let cache = format!("MEMO_{}", ctx.language.sanitize_identifier(&node.id).to_uppercase());
+        Ok(memoized_block(&cache, call, &ctx.indent()))
+    }
+
+    /// Open the branch computing input `index` of a node's parallel inputs
+    ///
+    /// `variables` are the result variables of all the inputs evaluated
+    /// together, in order. Defaults to [`rayon_join_opener`] at the current
+    /// indentation.
+    fn begin_parallel_branch<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        index: usize,
+        variables: &[String],
+    ) -> Result<(), GraphyError> {
+        let line = format!("{}{}\n", ctx.indent(), rayon_join_opener(index, variables));
+        ctx.emit(&line);
+        Ok(())
+    }
+
+    /// End a parallel branch with the expression of its input
+    ///
+    /// Called one level deeper than the branch was opened at. Defaults to
+    /// the expression on its own line, as the closure's value.
+    fn end_parallel_branch<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        _index: usize,
+        expr: &str,
+    ) -> Result<(), GraphyError> {
+        let line = format!("{}{}\n", ctx.indent(), expr);
+        ctx.emit(&line);
+        Ok(())
+    }
+
+    /// Close the parallel branches after the last one
+    ///
+    /// Defaults to closing the closure and every `rayon::join` call.
+    fn end_parallel<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        variables: &[String],
+    ) -> Result<(), GraphyError> {
+        let line = format!("{}}}{};\n", ctx.indent(), ")".repeat(variables.len().saturating_sub(1)));
+        ctx.emit(&line);
+        Ok(())
+    }
+
+    /// Open the helper function a class of repeated branches is generated into
+    ///
+    /// The body is generated one level deeper. Defaults to a Rust `fn`
+    /// taking the params, at the current indentation.
+    fn begin_branch_helper<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        name: &str,
+        params: &[HelperParam],
+    ) -> Result<(), GraphyError> {
+        let params: Vec<String> = params
+            .iter()
+            .map(|param| format!("{}: {}", param.name, param.param_type))
+            .collect();
+        let line = format!("{}fn {}({}) {{\n", ctx.indent(), name, params.join(", "));
+        ctx.emit(&line);
+        Ok(())
+    }
+
+    /// Close a helper function
+    ///
+    /// Defaults to a closing brace.
+    fn end_branch_helper<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        _name: &str,
+    ) -> Result<(), GraphyError> {
+        let line = format!("{}}}\n", ctx.indent());
+        ctx.emit(&line);
+        Ok(())
+    }
+
+    /// Generate the call replacing a branch that starts at `root`
+    ///
+    /// `args` holds the literal of the branch's constant for each param of
+    /// the helper, from [`generate_literal`](Self::generate_literal).
+    /// Defaults to `<name>(<args>);`.
+    fn generate_branch_helper_call<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        _root: &NodeInstance,
+        name: &str,
+        args: &[String],
+    ) -> Result<(), GraphyError> {
+        let line = format!("{}{}({});\n", ctx.indent(), name, args.join(", "));
+        ctx.emit(&line);
+        Ok(())
+    }
+
+    /// Emit code before any event (imports, type declarations, ...)
+    fn begin_program<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &mut CodeGeneratorContext<'_, P>,
+    ) -> Result<(), GraphyError> {
+        Ok(())
+    }
+
+    /// Emit code after all events
+    fn end_program<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &mut CodeGeneratorContext<'_, P>,
+    ) -> Result<(), GraphyError> {
+        Ok(())
+    }
+
+    /// Generate code for an event node (entry point)
+    fn generate_event<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
         node: &NodeInstance,
         metadata: &NodeMetadata,
-    ) -> Result<String, GraphyError>;
+    ) -> Result<(), GraphyError>;
 
     /// Generate code for a function node (statement with side effects)
-    fn generate_function_node(
-        &self,
+    ///
+    /// `args` holds one resolved expression per metadata parameter, then
+    /// one per variadic input. Calls to async nodes should be awaited, e.g.
+    /// with [`generate_await`](Self::generate_await).
+    fn generate_function_node<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
         node: &NodeInstance,
         metadata: &NodeMetadata,
-    ) -> Result<String, GraphyError>;
+        args: &[String],
+    ) -> Result<(), GraphyError>;
 
     /// Generate code for a control flow node (branching)
-    fn generate_control_flow_node(
-        &self,
+    ///
+    /// `args` holds one resolved expression per metadata parameter, then
+    /// one per variadic input. Branches can be generated with
+    /// [`generate_exec_output`](super::generate_exec_output), or
+    /// [scheduled](CodeGeneratorContext::schedule) as [`ExecStep`]s so deeply
+    /// nested flow doesn't recurse.
+    fn generate_control_flow<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
         node: &NodeInstance,
         metadata: &NodeMetadata,
-    ) -> Result<String, GraphyError>;
+        args: &[String],
+    ) -> Result<(), GraphyError>;
 
-    /// Generate code for an event node (entry point)
-    fn generate_event_node(
-        &self,
+    /// Generate an inline expression for a pure node
+    ///
+    /// `args` holds one resolved expression per metadata parameter, then
+    /// one per variadic input.
+    fn generate_pure_expression<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &CodeGeneratorContext<'_, P>,
         node: &NodeInstance,
         metadata: &NodeMetadata,
+        args: &[String],
     ) -> Result<String, GraphyError>;
 
-    /// Generate the complete program from a graph
-    fn generate_program(&self) -> Result<String, GraphyError>;
+    /// Generate the literal for a constant input
+    ///
+    /// Called for inputs fed by a node property or a metadata default, and
+    /// for the arguments of branch helper calls. `type_string` is the type
+    /// of the input. Defaults to [`LanguageProfile::format_typed_literal`]
+    /// of the context's language, so `Vector2(1.0, 2.0)` becomes
+    /// `Vec2::new(1.0, 2.0)` in Rust and `vec2<f32>(1.0, 2.0)` in WGSL.
+    fn generate_literal<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &CodeGeneratorContext<'_, P>,
+        value: &PropertyValue,
+        type_string: &str,
+    ) -> Result<String, GraphyError> {
+        ctx.language.format_typed_literal(value, type_string)
+    }
+
+    /// Generate the call expression of a fallible function node
+    ///
+    /// `args` holds one resolved expression per metadata parameter, then
+    /// one per variadic input. Defaults to `<name>(<args>)`; the driver
+    /// awaits it for async nodes.
+    fn generate_fallible_call<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        metadata: &NodeMetadata,
+        args: &[String],
+    ) -> Result<String, GraphyError> {
+        Ok(format!("{}({})", metadata.name, args.join(", ")))
+    }
+
+    /// Open the result match of a fallible call and its success branch
+    ///
+    /// `value` is the node's result variable, or `None` if nothing reads or
+    /// probes the success value. Defaults to `match <call> {` and an `Ok(<value>) => {`
+    /// arm, each indented one level.
+    fn begin_result_match<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        call: &str,
+        value: Option<&str>,
+    ) -> Result<(), GraphyError> {
+        let line = format!("{}match {} {{\n", ctx.indent(), call);
+        ctx.emit(&line);
+        ctx.push_indent();
+        let line = format!("{}Ok({}) => {{\n", ctx.indent(), value.unwrap_or("_"));
+        ctx.emit(&line);
+        ctx.push_indent();
+        Ok(())
+    }
+
+    /// Close the success branch and open the error branch
+    ///
+    /// `error` is the node's error variable, or `None` if nothing reads or
+    /// probes the error. Defaults to an `Err(<error>) => {` arm.
+    fn generate_error_arm<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        error: Option<&str>,
+    ) -> Result<(), GraphyError> {
+        ctx.pop_indent();
+        let line = format!("{}}}\n{}Err({}) => {{\n", ctx.indent(), ctx.indent(), error.unwrap_or("_"));
+        ctx.emit(&line);
+        ctx.push_indent();
+        Ok(())
+    }
+
+    /// Close the error branch and the result match
+    fn end_result_match<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+    ) -> Result<(), GraphyError> {
+        ctx.pop_indent();
+        let line = format!("{}}}\n", ctx.indent());
+        ctx.emit(&line);
+        ctx.pop_indent();
+        let line = format!("{}}}\n", ctx.indent());
+        ctx.emit(&line);
+        Ok(())
+    }
+
+    /// Open the match of a switch node
+    ///
+    /// `value` is the expression matched on. Defaults to `match <value> {`,
+    /// indented one level.
+    fn begin_switch<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        value: &str,
+    ) -> Result<(), GraphyError> {
+        let line = format!("{}match {} {{\n", ctx.indent(), value);
+        ctx.emit(&line);
+        ctx.push_indent();
+        Ok(())
+    }
+
+    /// Render the pattern of a switch case
+    ///
+    /// Defaults to `<value_type>::<case>` for identifiers (enum variants) and
+    /// to the case itself otherwise (literals, paths).
+    fn switch_case_pattern<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        value_type: &str,
+        case: &str,
+    ) -> Result<String, GraphyError> {
+        let is_identifier = case.starts_with(|c: char| c.is_alphabetic() || c == '_')
+            && case.chars().all(|c| c.is_alphanumeric() || c == '_');
+        if is_identifier {
+            Ok(format!("{}::{}", value_type, case))
+        } else {
+            Ok(case.to_string())
+        }
+    }
+
+    /// Open a switch arm
+    ///
+    /// `pattern` comes from [`switch_case_pattern`](Self::switch_case_pattern),
+    /// or is `_` for the default arm. Defaults to `<pattern> => {`.
+    fn begin_switch_arm<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        pattern: &str,
+    ) -> Result<(), GraphyError> {
+        let line = format!("{}{} => {{\n", ctx.indent(), pattern);
+        ctx.emit(&line);
+        ctx.push_indent();
+        Ok(())
+    }
+
+    /// Close a switch arm
+    fn end_switch_arm<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+    ) -> Result<(), GraphyError> {
+        ctx.pop_indent();
+        let line = format!("{}}}\n", ctx.indent());
+        ctx.emit(&line);
+        Ok(())
+    }
+
+    /// Close the match of a switch node
+    fn end_switch<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+    ) -> Result<(), GraphyError> {
+        ctx.pop_indent();
+        let line = format!("{}}}\n", ctx.indent());
+        ctx.emit(&line);
+        Ok(())
+    }
+
+    /// Declare the graph's [externals](crate::core::ExternalDecl)
+    ///
+    /// Called once after [`begin_program`](Self::begin_program) if the graph
+    /// declares externals. Defaults to the Rust `use` statements from
+    /// [`external_imports`].
+    fn generate_externals<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+    ) -> Result<(), GraphyError> {
+        let code = external_imports(ctx.graph);
+        ctx.emit(&code);
+        Ok(())
+    }
+
+    /// Generate the state holding the graph's variables
+    ///
+    /// Called once after [`begin_program`](Self::begin_program) and
+    /// [`generate_externals`](Self::generate_externals) if the graph
+    /// declares variables. Defaults to the Rust struct from
+    /// [`graph_state_struct`] named `GraphState`.
+    fn generate_state<P: NodeMetadataProvider>(&mut self, ctx: &mut CodeGeneratorContext<'_, P>) -> Result<(), GraphyError> {
+        let code = graph_state_struct(ctx.graph, "GraphState")?;
+        ctx.emit(&code);
+        Ok(())
+    }
+
+    /// Generate a dispatcher forwarding each event to its handler
+    ///
+    /// Called once after the events if the options ask for a dispatcher.
+    /// Defaults to the Rust code from [`event_dispatcher`].
+    fn generate_dispatcher<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        events: &EventAnalysis,
+        dispatcher: &EventDispatcher,
+    ) -> Result<(), GraphyError> {
+        let code = event_dispatcher(ctx.graph, events, dispatcher);
+        ctx.emit("\n");
+        ctx.emit(&code);
+        Ok(())
+    }
+
+    /// Generate the statement reporting that a node with a breakpoint is
+    /// about to run
+    ///
+    /// Defaults to a call to [`DebugHooks::hit_function`] with the node ID.
+    fn generate_debug_hit<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        hooks: &DebugHooks,
+    ) -> Result<(), GraphyError> {
+        let line = format!("{}{};\n", ctx.indent(), hooks.hit_call(node));
+        ctx.emit(&line);
+        Ok(())
+    }
+
+    /// Generate the statements binding an input of a traced node to a
+    /// variable and reporting its value
+    ///
+    /// `param` is the input's name and type, `expr` its resolved expression;
+    /// the node reads `variable` instead afterwards. Defaults to a Rust `let`
+    /// and a call to [`DebugHooks::trace_function`] with the node ID, the
+    /// pin name, and a reference to the variable.
+    fn generate_debug_trace<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        param: &ParamInfo,
+        variable: &str,
+        expr: &str,
+        hooks: &DebugHooks,
+    ) -> Result<(), GraphyError> {
+        let indent = ctx.indent();
+        let code = format!(
+            "{indent}let {variable} = {expr};\n{indent}{};\n",
+            hooks.trace_call(node, &param.name, variable)
+        );
+        ctx.emit_for_node(&node.id, Some(&param.name), &code);
+        Ok(())
+    }
+
+    /// Generate the statement reporting the value of a watched output pin
+    ///
+    /// `variable` holds the value. Defaults to a Rust call to the
+    /// [`ValueProbes::sink`]'s `on_value` with the node ID, the pin name,
+    /// and a reference to the variable.
+    fn generate_value_probe<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        pin: &str,
+        variable: &str,
+        probes: &ValueProbes,
+    ) -> Result<(), GraphyError> {
+        let line = format!("{}{};\n", ctx.indent(), probes.probe_call(node, pin, variable));
+        ctx.emit_for_node(&node.id, Some(pin), &line);
+        Ok(())
+    }
+
+    /// Generate an expression reading a graph variable
+    ///
+    /// `field` is the variable name sanitized for the target language.
+    /// Defaults to `state.<field>`, i.e. handlers receive the state as `state`.
+    fn generate_variable_get<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        field: &str,
+    ) -> Result<String, GraphyError> {
+        Ok(format!("state.{}", field))
+    }
+
+    /// Generate a statement writing a graph variable
+    ///
+    /// Defaults to `state.<field> = <value>;` at the current indentation.
+    fn generate_variable_set<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        field: &str,
+        value: &str,
+    ) -> Result<(), GraphyError> {
+        let line = format!("{}state.{} = {};\n", ctx.indent(), field, value);
+        ctx.emit(&line);
+        Ok(())
+    }
+
+    /// Generate a statement binding a hoisted pure expression to a variable
+    ///
+    /// Defaults to a Rust `let` at the current indentation.
+    fn generate_binding<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        variable: &str,
+        expr: &str,
+    ) -> Result<String, GraphyError> {
+        Ok(format!("{}let {} = {};\n", ctx.indent(), variable, expr))
+    }
+}
+
+/// How one input of a memoized node is cached, see [`CodeGenerator::memo_key`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoKey {
+    /// Type of the cache key part
+    pub key_type: String,
+
+    /// Expression of the cache key part, reading the input's binding
+    pub expr: String,
+}
+
+/// The call of a memoized pure node, see [`CodeGenerator::generate_memoized`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoizedCall {
+    /// Resolved expressions of the inputs, in argument order
+    pub args: Vec<String>,
+
+    /// Names the inputs are bound to, one per argument
+    pub bindings: Vec<String>,
+
+    /// How each input is cached
+    pub keys: Vec<MemoKey>,
+
+    /// Type of the cached result
+    pub return_type: String,
+
+    /// The node's expression, reading the inputs from their bindings
+    pub call: String,
+}
+
+/// Helper for building a Rust block caching a call's results
+///
+/// Declares the thread-local `cache`, a `HashMap` from the key tuple to the
+/// result, binds the arguments, and looks the call up, evaluating it on a
+/// miss. Continuation lines are indented from `indent`. The cache is never
+/// cleared, so memoize nodes whose inputs take a bounded set of values.
+///
+/// # Example
+///
+/// ```
+/// use graphy::generation::{memoized_block, MemoKey, MemoizedCall};
+///
+/// let call = MemoizedCall {
+///     args: vec!["x".into()],
+///     bindings: vec!["memo_0".into()],
+///     keys: vec![MemoKey { key_type: "u64".into(), expr: "memo_0.to_bits()".into() }],
+///     return_type: "f64".into(),
+///     call: "noise(memo_0)".into(),
+/// };
+/// let code = memoized_block("MEMO_NOISE_1", &call, "");
+/// assert!(code.contains("HashMap<(u64,), f64>"));
+/// assert!(code.contains("let (memo_0,) = (x,);"));
+/// assert!(code.contains(".entry((memo_0.to_bits(),)).or_insert_with(|| noise(memo_0)).clone()"));
+/// ```
+pub fn memoized_block(cache: &str, call: &MemoizedCall, indent: &str) -> String {
+    let key_types: Vec<&str> = call.keys.iter().map(|key| key.key_type.as_str()).collect();
+    let key_exprs: Vec<&str> = call.keys.iter().map(|key| key.expr.as_str()).collect();
+
+    let mut code = String::from("{\n");
+    let _ = writeln!(code, "{}    thread_local! {{", indent);
+    let _ = writeln!(
+        code,
+        "{}        static {}: ::std::cell::RefCell<::std::collections::HashMap<{}, {}>> = \
+         ::std::cell::RefCell::new(::std::collections::HashMap::new());",
+        indent,
+        cache,
+        tuple(&key_types),
+        call.return_type
+    );
+    let _ = writeln!(code, "{}    }}", indent);
+    if !call.bindings.is_empty() {
+        let _ = writeln!(
+            code,
+            "{}    let {} = {};",
+            indent,
+            tuple(&call.bindings),
+            tuple(&call.args)
+        );
+    }
+    let _ = write!(
+        code,
+        "{}    {}.with(|cache| cache.borrow_mut().entry({}).or_insert_with(|| {}).clone())\n{}}}",
+        indent,
+        cache,
+        tuple(&key_exprs),
+        call.call,
+        indent
+    );
+    code
+}
+
+/// Helper for the line opening a branch of nested `rayon::join` calls
+///
+/// The first branch binds every variable with a right-nested tuple pattern,
+/// the branches after it close the previous closure, and all but the last
+/// open another `join`, so `n` branches need `n - 1` closing parentheses
+/// after the last closure (see [`CodeGenerator::end_parallel`]).
+///
+/// # Example
+///
+/// ```
+/// use graphy::generation::rayon_join_opener;
+///
+/// let variables = ["a".to_string(), "b".to_string(), "c".to_string()];
+/// assert_eq!(rayon_join_opener(0, &variables), "let (a, (b, c)) = ::rayon::join(|| {");
+/// assert_eq!(rayon_join_opener(1, &variables), "}, || ::rayon::join(|| {");
+/// assert_eq!(rayon_join_opener(2, &variables), "}, || {");
+/// ```
+pub fn rayon_join_opener(index: usize, variables: &[String]) -> String {
+    if index == 0 {
+        let pattern = variables
+            .iter()
+            .rev()
+            .skip(1)
+            .fold(variables.last().cloned().unwrap_or_default(), |inner, variable| {
+                format!("({}, {})", variable, inner)
+            });
+        format!("let {} = ::rayon::join(|| {{", pattern)
+    } else if index + 1 < variables.len() {
+        "}, || ::rayon::join(|| {".to_string()
+    } else {
+        "}, || {".to_string()
+    }
+}
+
+/// A Rust tuple of the items, with the trailing comma of 1-tuples
+fn tuple<S: AsRef<str>>(items: &[S]) -> String {
+    let items: Vec<&str> = items.iter().map(AsRef::as_ref).collect();
+    match items.as_slice() {
+        [single] => format!("({},)", single),
+        items => format!("({})", items.join(", ")),
+    }
 }
 
 /// Helper for collecting node arguments
+///
+/// One argument per param, followed by one per input of the node's variadic
+/// group in index order (see [`NodeMetadata::instance_params`]).
 pub fn collect_node_arguments(
     node: &NodeInstance,
     metadata: &NodeMetadata,
 ) -> Result<Vec<String>, GraphyError> {
     let mut args = Vec::new();
 
-    for param in &metadata.params {
+    for param in metadata.instance_params(node) {
         // Look for property value or default
         if let Some(prop_value) = node.properties.get(&param.name) {
             // Convert property value to string
@@ -62,3 +860,239 @@ pub fn collect_node_arguments(
 
     Ok(args)
 }
+
+/// Helper for building a Rust function signature from a graph's interface
+///
+/// Declared graph inputs become parameters and declared graph outputs become
+/// the return type (a tuple if there are several). Names are sanitized.
+///
+/// # Example
+///
+/// ```
+/// use graphy::GraphDescription;
+/// use graphy::generation::graph_function_signature;
+///
+/// let mut graph = GraphDescription::new("lerp");
+/// graph.add_graph_input("a", "f64");
+/// graph.add_graph_input("t", "f64");
+/// graph.add_graph_output("result", "f64");
+///
+/// assert_eq!(graph_function_signature(&graph, "lerp"), "fn lerp(a: f64, t: f64) -> f64");
+/// ```
+pub fn graph_function_signature(graph: &GraphDescription, fn_name: &str) -> String {
+    let params: Vec<String> = graph
+        .graph_inputs
+        .iter()
+        .map(|param| format!("{}: {}", crate::utils::sanitize_name(&param.name), param.type_info))
+        .collect();
+
+    let signature = format!("fn {}({})", crate::utils::sanitize_name(fn_name), params.join(", "));
+
+    match graph.graph_outputs.as_slice() {
+        [] => signature,
+        [single] => format!("{} -> {}", signature, single.type_info),
+        outputs => {
+            let types: Vec<String> = outputs.iter().map(|param| param.type_info.to_string()).collect();
+            format!("{} -> ({})", signature, types.join(", "))
+        }
+    }
+}
+
+/// Helper for building the Rust `use` statements of a graph's externals
+///
+/// Emits one `use <module>::<name>;` per declared external with a module,
+/// sorted and without duplicates, followed by a blank line. Externals
+/// without a module need no import.
+///
+/// # Example
+///
+/// ```
+/// use graphy::core::ExternalDecl;
+/// use graphy::generation::external_imports;
+/// use graphy::GraphDescription;
+///
+/// let mut graph = GraphDescription::new("door");
+/// graph.add_external(ExternalDecl::function("engine::audio", "play_sound"));
+/// graph.add_external(ExternalDecl::type_("engine::audio", "SoundId"));
+///
+/// assert_eq!(external_imports(&graph), "use engine::audio::SoundId;\nuse engine::audio::play_sound;\n\n");
+/// ```
+pub fn external_imports(graph: &GraphDescription) -> String {
+    let paths: BTreeSet<String> = graph
+        .externals
+        .iter()
+        .filter(|external| !external.module.is_empty())
+        .map(ExternalDecl::path)
+        .collect();
+    if paths.is_empty() {
+        return String::new();
+    }
+
+    let mut code: String = paths.iter().map(|path| format!("use {};\n", path)).collect();
+    code.push('\n');
+    code
+}
+
+/// Helper for building a Rust struct holding a graph's variables
+///
+/// Emits one public field per declared variable (sorted by name) and a
+/// `Default` impl initializing each field to its declared default value, or
+/// the type's default if none is set.
+///
+/// # Errors
+///
+/// Returns [`GraphyError::CodeGeneration`] if a default value can't be
+/// written as a Rust literal.
+///
+/// # Example
+///
+/// ```
+/// use graphy::core::VariableDecl;
+/// use graphy::generation::graph_state_struct;
+/// use graphy::{GraphDescription, PropertyValue};
+///
+/// let mut graph = GraphDescription::new("player");
+/// graph.add_variable("health", VariableDecl::new("i64").with_default(PropertyValue::Integer(100)));
+///
+/// let code = graph_state_struct(&graph, "PlayerState").unwrap();
+/// assert!(code.contains("pub health: i64,"));
+/// assert!(code.contains("health: 100,"));
+/// ```
+pub fn graph_state_struct(graph: &GraphDescription, struct_name: &str) -> Result<String, GraphyError> {
+    let mut names: Vec<&String> = graph.variables.keys().collect();
+    names.sort();
+
+    let mut fields = String::new();
+    let mut defaults = String::new();
+    for name in names {
+        let decl = &graph.variables[name];
+        let field = RustProfile.sanitize_identifier(name);
+        let value = match &decl.default {
+            Some(value) => RustProfile.format_literal(value)?,
+            None => RustProfile.default_value(&decl.type_info.type_string),
+        };
+
+        fields.push_str(&format!("    pub {}: {},\n", field, decl.type_info));
+        defaults.push_str(&format!("            {}: {},\n", field, value));
+    }
+
+    Ok(format!(
+        "pub struct {name} {{\n{fields}}}\n\nimpl Default for {name} {{\n    fn default() -> Self {{\n        Self {{\n{defaults}        }}\n    }}\n}}\n",
+        name = struct_name,
+        fields = fields,
+        defaults = defaults,
+    ))
+}
+
+/// A step of a walk over execution flow, see [`ExecWalk`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecStep {
+    /// Generate everything following an exec output pin of a node
+    Output {
+        /// Node the pin belongs to
+        node: String,
+
+        /// Exec output pin
+        pin: String,
+    },
+
+    /// Generate a node reached through execution flow, then what follows it
+    Node(String),
+
+    /// Emit code as is
+    Emit(String),
+
+    /// Increase the indentation, opening a scope for cached expressions
+    Indent,
+
+    /// Decrease the indentation, dropping the expressions cached since the
+    /// matching [`Indent`](Self::Indent)
+    Dedent,
+
+    /// Close the source map region of the node whose steps ran last (added
+    /// by the driver after the steps a node schedules)
+    EndNode,
+}
+
+impl ExecStep {
+    /// Creates an [`Output`](Self::Output) step.
+    #[inline]
+    pub fn output(node: impl Into<String>, pin: impl Into<String>) -> Self {
+        Self::Output {
+            node: node.into(),
+            pin: pin.into(),
+        }
+    }
+}
+
+/// Explicit work stack for walking execution flow without recursion
+///
+/// Recursing from every node into the nodes after it needs a stack frame
+/// per node, so long exec chains overflow the stack. A walk keeps pending
+/// steps on the heap instead: pop a step, handle it, and push the steps it
+/// leads to. [`generate_exec_output`](super::generate_exec_output) walks
+/// [`ExecStep`]s this way, and control flow generators can
+/// [`schedule`](CodeGeneratorContext::schedule) the steps of their branches
+/// rather than generating them recursively. Walks over other step types
+/// work the same, e.g. the IR lowering's.
+///
+/// # Example
+///
+/// ```
+/// use graphy::generation::{ExecStep, ExecWalk};
+///
+/// let mut walk = ExecWalk::new();
+/// walk.push_steps([ExecStep::Node("a".into()), ExecStep::Node("c".into())]);
+/// assert_eq!(walk.pop(), Some(ExecStep::Node("a".into())));
+///
+/// // Steps pushed while handling `a` run before `c`
+/// walk.push_steps([ExecStep::Node("b".into())]);
+/// assert_eq!(walk.pop(), Some(ExecStep::Node("b".into())));
+/// assert_eq!(walk.pop(), Some(ExecStep::Node("c".into())));
+/// assert!(walk.is_empty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExecWalk<S = ExecStep> {
+    /// Pending steps, the next one last
+    stack: Vec<S>,
+}
+
+impl<S> ExecWalk<S> {
+    /// Creates an empty walk.
+    #[inline]
+    pub fn new() -> Self {
+        Self { stack: Vec::new() }
+    }
+
+    /// Schedules steps to run next, in the given order and before every
+    /// step pushed earlier.
+    pub fn push_steps(&mut self, steps: impl IntoIterator<Item = S>) {
+        let start = self.stack.len();
+        self.stack.extend(steps);
+        self.stack[start..].reverse();
+    }
+
+    /// Takes the next step.
+    #[inline]
+    pub fn pop(&mut self) -> Option<S> {
+        self.stack.pop()
+    }
+
+    /// Returns the number of pending steps.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Checks if no steps are pending.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+}
+
+impl<S> Default for ExecWalk<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
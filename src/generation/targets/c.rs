@@ -0,0 +1,766 @@
+//! # C Backend
+//!
+//! Compiles graphs to a C99 source file and its header, for running them on
+//! targets without a Rust toolchain.
+//!
+//! Every event becomes a `void <name>_<event id>(...)` function taking the
+//! event's params, preceded by a pointer to the graph state if the graph
+//! declares variables. Nodes become C code as follows:
+//!
+//! - nodes with a [text template](crate::generation::SourceTemplate) source
+//!   ([`SourceKind::Template`]) are inlined: function nodes as statements,
+//!   control flow nodes with the code of each `{{exec:Label}}` placed where
+//!   the placeholder is, and pure nodes as parenthesized expressions, or as
+//!   calls to `static inline` helpers with
+//!   [`with_static_helpers`](CGenerator::with_static_helpers) (except nodes
+//!   with [variadic inputs](crate::core::VariadicGroup), whose argument count
+//!   varies)
+//! - other function and pure nodes become calls to C functions named after
+//!   the node type, declared in the header for the application to implement;
+//!   variadic inputs are passed as `...`
+//! - graph variables are fields of a `<name>_state` struct, initialized by
+//!   `<name>_state_init`
+//! - [external](crate::core::ExternalDecl) functions are declared `extern`
+//!   in the header, and external types as opaque structs
+//!
+//! - in [debug builds](crate::generation::DebugHooks), the hook functions are
+//!   declared with the node implementations; traced values are passed as
+//!   `const void *`
+//!
+//! Graph types are mapped to C types by the generator's [`CProfile`].
+//! Control flow nodes need a template source, switch nodes become `switch`
+//! statements, and fallible nodes, [event dispatchers](crate::generation::EventDispatcher),
+//! and [value probes](crate::generation::ValueProbes) aren't supported.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use graphy::generation::targets::{compile_c, CGenerator};
+//! use graphy::utils::CProfile;
+//!
+//! let mut generator = CGenerator::new("blinky").with_profile(CProfile::new().with_type("Pin", "gpio_pin_t"));
+//! let sources = compile_c(&graph, &registry, &mut generator)?;
+//! std::fs::write(sources.header_file_name(), &sources.header)?;
+//! std::fs::write(sources.source_file_name(), &sources.source)?;
+//! ```
+
+use crate::analysis::EventAnalysis;
+use crate::core::{
+    ExternalKind, GraphDescription, NodeInstance, NodeMetadata, NodeMetadataProvider, NodeTypes, ParamInfo, SourceKind,
+};
+use crate::generation::{
+    bind_event_params, compile, generate_exec_output, CodeGenerator, CodeGeneratorContext, DebugHooks, EventDispatcher, ExecStep,
+    SourceTemplate, TemplateSegment, ValueProbes,
+};
+use crate::utils::{CProfile, LanguageProfile};
+use crate::GraphyError;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// A generated C source file and its header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CSources {
+    /// Base name of both files
+    pub name: String,
+
+    /// Contents of the header
+    pub header: String,
+
+    /// Contents of the source file
+    pub source: String,
+}
+
+impl CSources {
+    /// Returns `<name>.h`.
+    #[inline]
+    pub fn header_file_name(&self) -> String {
+        format!("{}.h", self.name)
+    }
+
+    /// Returns `<name>.c`.
+    #[inline]
+    pub fn source_file_name(&self) -> String {
+        format!("{}.c", self.name)
+    }
+}
+
+/// Code generator emitting C.
+///
+/// [`compile`] returns the source file; the header declarations are
+/// collected along the way and returned by [`header`](Self::header).
+/// [`compile_c`] returns both.
+#[derive(Debug, Clone)]
+pub struct CGenerator {
+    /// Base name of the files and prefix of generated names, sanitized
+    name: String,
+
+    /// Identifier, type, and literal rules
+    profile: CProfile,
+
+    /// Emit template pure nodes as `static inline` helpers
+    static_helpers: bool,
+
+    /// Extra headers included by the generated header
+    includes: Vec<String>,
+
+    /// State struct declarations of the last compilation
+    state_declaration: String,
+
+    /// Prototypes of the node implementations used by the last compilation
+    node_declarations: Vec<String>,
+
+    /// Declarations of the externals of the last compilation
+    external_declarations: Vec<String>,
+
+    /// Prototypes of the event functions of the last compilation
+    event_declarations: Vec<String>,
+}
+
+impl CGenerator {
+    /// Create a generator for files named `<name>.c` and `<name>.h`
+    ///
+    /// The name is sanitized into a C identifier and prefixes the event
+    /// functions and the state struct.
+    pub fn new(name: impl AsRef<str>) -> Self {
+        let profile = CProfile::new();
+        Self {
+            name: profile.sanitize_identifier(name.as_ref()),
+            profile,
+            static_helpers: false,
+            includes: Vec::new(),
+            state_declaration: String::new(),
+            node_declarations: Vec::new(),
+            external_declarations: Vec::new(),
+            event_declarations: Vec::new(),
+        }
+    }
+
+    /// Set the profile mapping graph types to C types
+    #[inline]
+    #[must_use]
+    pub fn with_profile(mut self, profile: CProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Emit template pure nodes as `static inline` helper functions
+    ///
+    /// Off by default, which inlines their expression at every use.
+    #[inline]
+    #[must_use]
+    pub fn with_static_helpers(mut self, enabled: bool) -> Self {
+        self.static_helpers = enabled;
+        self
+    }
+
+    /// Include another header from the generated header
+    ///
+    /// Use this for headers declaring the C types of the profile. Names in
+    /// angle brackets (`<math.h>`) are included as system headers.
+    #[inline]
+    #[must_use]
+    pub fn with_include(mut self, header: impl Into<String>) -> Self {
+        self.includes.push(header.into());
+        self
+    }
+
+    /// Get the sanitized base name
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the type mapping profile
+    #[inline]
+    pub fn profile(&self) -> &CProfile {
+        &self.profile
+    }
+
+    /// Build the header for the last compilation
+    ///
+    /// Declares the externals, the state struct, the node implementations
+    /// the source calls, and the event functions.
+    pub fn header(&self) -> String {
+        let guard = format!("{}_H", self.name.to_ascii_uppercase());
+        let mut header = format!(
+            "#ifndef {guard}\n#define {guard}\n\n#include <stdbool.h>\n#include <stddef.h>\n#include <stdint.h>\n",
+            guard = guard
+        );
+        for include in &self.includes {
+            if include.starts_with('<') {
+                header.push_str(&format!("#include {}\n", include));
+            } else {
+                header.push_str(&format!("#include \"{}\"\n", include));
+            }
+        }
+        header.push_str("\n#ifdef __cplusplus\nextern \"C\" {\n#endif\n");
+
+        if !self.external_declarations.is_empty() {
+            header.push_str("\n/* Externals, provided by the engine */\n");
+            for declaration in &self.external_declarations {
+                header.push_str(declaration);
+                header.push('\n');
+            }
+        }
+        if !self.state_declaration.is_empty() {
+            header.push('\n');
+            header.push_str(&self.state_declaration);
+        }
+        if !self.node_declarations.is_empty() {
+            header.push_str("\n/* Node implementations, provided by the application */\n");
+            for declaration in &self.node_declarations {
+                header.push_str(declaration);
+                header.push('\n');
+            }
+        }
+        if !self.event_declarations.is_empty() {
+            header.push_str("\n/* Events */\n");
+            for declaration in &self.event_declarations {
+                header.push_str(declaration);
+                header.push('\n');
+            }
+        }
+
+        header.push_str(&format!("\n#ifdef __cplusplus\n}}\n#endif\n\n#endif /* {} */\n", guard));
+        header
+    }
+
+    fn state_type(&self) -> String {
+        format!("{}_state", self.name)
+    }
+
+    fn return_type(&self, metadata: &NodeMetadata) -> String {
+        match &metadata.return_type {
+            Some(return_type) => self.profile.map_type(&return_type.type_string),
+            None => "void".to_string(),
+        }
+    }
+
+    /// `<return type> <name>(<params>)` for a node type
+    fn node_signature(&self, metadata: &NodeMetadata) -> String {
+        let mut params: Vec<String> = metadata
+            .params
+            .iter()
+            .map(|param| {
+                format!(
+                    "{} {}",
+                    self.profile.map_type(&param.param_type),
+                    self.profile.sanitize_identifier(&param.name)
+                )
+            })
+            .collect();
+        if metadata.variadic.is_some() {
+            params.push("...".to_string());
+        }
+        format!(
+            "{} {}({})",
+            self.return_type(metadata),
+            self.profile.sanitize_identifier(&metadata.name),
+            param_list(params)
+        )
+    }
+
+    /// Type of a variable bound by [`generate_binding`](CodeGenerator::generate_binding)
+    fn binding_type<P: NodeMetadataProvider>(
+        &self,
+        ctx: &CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        variable: &str,
+    ) -> Result<String, GraphyError> {
+        let type_string = if node.is_get_variable() {
+            node.variable_name()
+                .and_then(|name| ctx.graph.variables.get(name))
+                .map(|decl| decl.type_info.type_string.clone())
+        } else {
+            ctx.metadata_provider
+                .metadata_for_node(node)
+                .and_then(|metadata| match metadata.node_type {
+                    NodeTypes::event => metadata
+                        .params
+                        .iter()
+                        .find(|param| {
+                            ctx.data_resolver.get_event_param_variable(&node.id, &param.name).map(String::as_str)
+                                == Some(variable)
+                        })
+                        .map(|param| param.param_type.clone()),
+                    _ => metadata.return_type.as_ref().map(|t| t.type_string.clone()),
+                })
+        };
+
+        type_string.map(|t| self.profile.map_type(&t)).ok_or_else(|| {
+            GraphyError::CodeGeneration(format!("Node '{}' has no type to declare '{}' with", node.id, variable))
+        })
+    }
+
+    /// Emit a template line by line, with the code of its execution outputs
+    /// in place of `{{exec:Label}}` if `with_exec` is set
+    ///
+    /// The lines and outputs are scheduled as [`ExecStep`]s, so nested
+    /// control flow doesn't recurse.
+    fn emit_template<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        metadata: &NodeMetadata,
+        template: &SourceTemplate,
+        args: &[String],
+        with_exec: bool,
+    ) -> Result<(), GraphyError> {
+        let values = template_values(metadata, args);
+        let indent = ctx.indent();
+        let mut steps = Vec::new();
+        let mut line = String::new();
+        for segment in template.segments() {
+            match segment {
+                TemplateSegment::Text(text) => {
+                    let mut pieces = text.split('\n');
+                    line.push_str(pieces.next().unwrap_or_default());
+                    for piece in pieces {
+                        steps.extend(line_step(&indent, &std::mem::replace(&mut line, piece.to_string())));
+                    }
+                }
+                TemplateSegment::Param(name) => line.push_str(template_value(&values, metadata, name)?),
+                TemplateSegment::Exec(label) => {
+                    if !with_exec {
+                        continue;
+                    }
+                    steps.extend(line_step(&indent, &std::mem::take(&mut line)));
+                    steps.extend([ExecStep::Indent, ExecStep::output(node.id.as_str(), label.as_str()), ExecStep::Dedent]);
+                }
+            }
+        }
+        steps.extend(line_step(&indent, &line));
+        ctx.schedule(steps);
+        Ok(())
+    }
+}
+
+impl CodeGenerator for CGenerator {
+    fn language(&self) -> Arc<dyn LanguageProfile> {
+        Arc::new(self.profile.clone())
+    }
+
+    fn cache_id(&self) -> String {
+        format!(
+            "graphy::c/v1/{}/{}/{:?}/{:?}",
+            self.name,
+            self.static_helpers,
+            self.profile.types(),
+            self.includes
+        )
+    }
+
+    fn begin_program<P: NodeMetadataProvider>(&mut self, ctx: &mut CodeGeneratorContext<'_, P>) -> Result<(), GraphyError> {
+        self.state_declaration.clear();
+        self.node_declarations.clear();
+        self.external_declarations.clear();
+        self.event_declarations.clear();
+        ctx.emit(&format!("#include \"{}.h\"\n", self.name));
+
+        let node_types: BTreeMap<&str, &NodeMetadata> = ctx
+            .graph
+            .nodes
+            .values()
+            .filter_map(|node| ctx.metadata_provider.metadata_for_node(node))
+            .map(|metadata| (metadata.name.as_str(), metadata))
+            .collect();
+
+        for metadata in node_types.into_values() {
+            match (metadata.node_type, node_template(metadata)?) {
+                (NodeTypes::pure, Some(template)) if self.static_helpers && metadata.variadic.is_none() => {
+                    let values: HashMap<String, String> = metadata
+                        .params
+                        .iter()
+                        .map(|param| (param.name.clone(), self.profile.sanitize_identifier(&param.name)))
+                        .collect();
+                    let body = template.render(&values, &HashMap::new())?;
+                    ctx.emit(&format!(
+                        "\nstatic inline {} {{\n    return {};\n}}\n",
+                        self.node_signature(metadata),
+                        body
+                    ));
+                }
+                (NodeTypes::fn_ | NodeTypes::pure, None) if !metadata.is_fallible() => {
+                    self.node_declarations.push(format!("{};", self.node_signature(metadata)));
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(hooks) = &ctx.debug {
+            self.node_declarations.push(format!("void {}(const char *node_id);", hooks.hit_function));
+            self.node_declarations.push(format!(
+                "void {}(const char *node_id, const char *pin, const void *value);",
+                hooks.trace_function
+            ));
+        }
+        Ok(())
+    }
+
+    /// Types become opaque `typedef struct`s, declared before the functions'
+    /// `extern` prototypes that may use them.
+    fn generate_externals<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+    ) -> Result<(), GraphyError> {
+        let (types, functions): (Vec<_>, Vec<_>) =
+            ctx.graph.externals.iter().partition(|external| external.kind == ExternalKind::Type);
+        for external in types.into_iter().chain(functions) {
+            let name = self.profile.sanitize_identifier(&external.name);
+            let declaration = match external.kind {
+                ExternalKind::Type => format!("typedef struct {name} {name};", name = name),
+                ExternalKind::Function => {
+                    let params = external
+                        .params
+                        .iter()
+                        .map(|param| self.profile.map_type(&param.type_string))
+                        .collect();
+                    let return_type = match &external.return_type {
+                        Some(return_type) => self.profile.map_type(&return_type.type_string),
+                        None => "void".to_string(),
+                    };
+                    format!("extern {} {}({});", return_type, name, param_list(params))
+                }
+            };
+            self.external_declarations.push(declaration);
+        }
+        Ok(())
+    }
+
+    fn generate_state<P: NodeMetadataProvider>(&mut self, ctx: &mut CodeGeneratorContext<'_, P>) -> Result<(), GraphyError> {
+        let state_type = self.state_type();
+        let mut names: Vec<&String> = ctx.graph.variables.keys().collect();
+        names.sort();
+
+        let mut fields = String::new();
+        let mut defaults = String::new();
+        for name in names {
+            let decl = &ctx.graph.variables[name];
+            let field = self.profile.sanitize_identifier(name);
+            let value = match &decl.default {
+                Some(value) => self.profile.format_literal(value)?,
+                None => self.profile.default_value(&decl.type_info.type_string),
+            };
+            fields.push_str(&format!("    {} {};\n", self.profile.map_type(&decl.type_info.type_string), field));
+            defaults.push_str(&format!("    state->{} = {};\n", field, value));
+        }
+
+        self.state_declaration = format!(
+            "typedef struct {state} {{\n{fields}}} {state};\n\nvoid {state}_init({state} *state);\n",
+            state = state_type,
+            fields = fields
+        );
+        ctx.emit(&format!(
+            "\nvoid {state}_init({state} *state) {{\n{defaults}}}\n",
+            state = state_type,
+            defaults = defaults
+        ));
+        Ok(())
+    }
+
+    fn generate_event<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        metadata: &NodeMetadata,
+    ) -> Result<(), GraphyError> {
+        let mut params = Vec::new();
+        if !ctx.graph.variables.is_empty() {
+            params.push(format!("{} *state", self.state_type()));
+        }
+        for param in &metadata.params {
+            params.push(format!(
+                "{} {}",
+                self.profile.map_type(&param.param_type),
+                self.profile.sanitize_identifier(&param.name)
+            ));
+        }
+        let function = self.profile.sanitize_identifier(&format!("{}_{}", self.name, node.id));
+        let signature = format!("void {}({})", function, param_list(params));
+        self.event_declarations.push(format!("{};", signature));
+
+        ctx.emit(&format!("\n{} {{\n", signature));
+        ctx.push_indent();
+        bind_event_params(self, ctx, node, metadata)?;
+        for pin in &metadata.exec_outputs {
+            generate_exec_output(self, ctx, &node.id, pin)?;
+        }
+        ctx.pop_indent();
+        ctx.emit("}\n");
+        Ok(())
+    }
+
+    fn generate_function_node<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        metadata: &NodeMetadata,
+        args: &[String],
+    ) -> Result<(), GraphyError> {
+        // Exec outputs of function nodes always follow their statements
+        if let Some(template) = node_template(metadata)? {
+            return self.emit_template(ctx, node, metadata, &template, args, false);
+        }
+
+        let call = format!("{}({})", self.profile.sanitize_identifier(&metadata.name), args.join(", "));
+        let result_is_read = !ctx.data_resolver.get_dependents(&node.id).is_empty();
+        let line = match ctx.data_resolver.get_result_variable(&node.id) {
+            Some(variable) if result_is_read && metadata.return_type.is_some() => {
+                format!("{}{} {} = {};\n", ctx.indent(), self.return_type(metadata), variable, call)
+            }
+            _ => format!("{}{};\n", ctx.indent(), call),
+        };
+        ctx.emit(&line);
+        Ok(())
+    }
+
+    fn generate_control_flow<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        metadata: &NodeMetadata,
+        args: &[String],
+    ) -> Result<(), GraphyError> {
+        let Some(template) = node_template(metadata)? else {
+            return Err(GraphyError::CodeGeneration(format!(
+                "Control flow node '{}' of type '{}' needs a template source for the C backend",
+                node.id, node.node_type
+            )));
+        };
+        self.emit_template(ctx, node, metadata, &template, args, true)
+    }
+
+    fn generate_pure_expression<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        metadata: &NodeMetadata,
+        args: &[String],
+    ) -> Result<String, GraphyError> {
+        match node_template(metadata)? {
+            Some(template) if !self.static_helpers || metadata.variadic.is_some() => {
+                let values = template_values(metadata, args);
+                Ok(format!("({})", template.render(&values, &HashMap::new())?))
+            }
+            _ => Ok(format!("{}({})", self.profile.sanitize_identifier(&metadata.name), args.join(", "))),
+        }
+    }
+
+    fn generate_fallible_call<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        _metadata: &NodeMetadata,
+        _args: &[String],
+    ) -> Result<String, GraphyError> {
+        Err(GraphyError::CodeGeneration(format!(
+            "Node '{}' of type '{}' is fallible, which the C backend doesn't support",
+            node.id, node.node_type
+        )))
+    }
+
+    fn generate_dispatcher<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &mut CodeGeneratorContext<'_, P>,
+        _events: &EventAnalysis,
+        dispatcher: &EventDispatcher,
+    ) -> Result<(), GraphyError> {
+        Err(GraphyError::CodeGeneration(format!(
+            "The C backend can't implement the trait '{}': event dispatchers aren't supported",
+            dispatcher.trait_name
+        )))
+    }
+
+    fn generate_value_probe<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &mut CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        _pin: &str,
+        _variable: &str,
+        _probes: &ValueProbes,
+    ) -> Result<(), GraphyError> {
+        Err(GraphyError::CodeGeneration(
+            "The C backend can't report watched pins: value probes aren't supported".to_string(),
+        ))
+    }
+
+    fn begin_switch<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        value: &str,
+    ) -> Result<(), GraphyError> {
+        let line = format!("{}switch ({}) {{\n", ctx.indent(), value);
+        ctx.emit(&line);
+        ctx.push_indent();
+        Ok(())
+    }
+
+    /// Enum variants become `<C type>_<variant>`, the usual naming of C enum constants.
+    fn switch_case_pattern<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        value_type: &str,
+        case: &str,
+    ) -> Result<String, GraphyError> {
+        let is_identifier = case.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && case.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if is_identifier {
+            let enum_type = self.profile.sanitize_identifier(&self.profile.map_type(value_type));
+            Ok(format!("{}_{}", enum_type, case))
+        } else {
+            Ok(case.to_string())
+        }
+    }
+
+    fn begin_switch_arm<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        pattern: &str,
+    ) -> Result<(), GraphyError> {
+        let line = if pattern == "_" {
+            format!("{}default: {{\n", ctx.indent())
+        } else {
+            format!("{}case {}: {{\n", ctx.indent(), pattern)
+        };
+        ctx.emit(&line);
+        ctx.push_indent();
+        Ok(())
+    }
+
+    fn end_switch_arm<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+    ) -> Result<(), GraphyError> {
+        let line = format!("{}break;\n", ctx.indent());
+        ctx.emit(&line);
+        ctx.pop_indent();
+        let line = format!("{}}}\n", ctx.indent());
+        ctx.emit(&line);
+        Ok(())
+    }
+
+    fn generate_variable_get<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        field: &str,
+    ) -> Result<String, GraphyError> {
+        Ok(format!("state->{}", field))
+    }
+
+    fn generate_variable_set<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        field: &str,
+        value: &str,
+    ) -> Result<(), GraphyError> {
+        let line = format!("{}state->{} = {};\n", ctx.indent(), field, value);
+        ctx.emit(&line);
+        Ok(())
+    }
+
+    fn generate_binding<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        variable: &str,
+        expr: &str,
+    ) -> Result<String, GraphyError> {
+        let c_type = self.binding_type(ctx, node, variable)?;
+        Ok(format!("{}{} {} = {};\n", ctx.indent(), c_type, variable, expr))
+    }
+
+    /// Binds the value with the input's C type; the trace function gets a
+    /// `const void *` to it.
+    fn generate_debug_trace<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        param: &ParamInfo,
+        variable: &str,
+        expr: &str,
+        hooks: &DebugHooks,
+    ) -> Result<(), GraphyError> {
+        let indent = ctx.indent();
+        let code = format!(
+            "{indent}{} {variable} = {expr};\n{indent}{};\n",
+            self.profile.map_type(&param.param_type),
+            hooks.trace_call(node, &param.name, variable)
+        );
+        ctx.emit_for_node(&node.id, Some(&param.name), &code);
+        Ok(())
+    }
+}
+
+/// Compile a graph to a C source file and its header
+///
+/// # Errors
+///
+/// Fails like [`compile`], or if the graph uses something the C backend
+/// can't express (see [`CGenerator`]).
+pub fn compile_c<P: NodeMetadataProvider>(
+    graph: &GraphDescription,
+    metadata_provider: &P,
+    generator: &mut CGenerator,
+) -> Result<CSources, GraphyError> {
+    let source = compile(graph, metadata_provider, generator)?;
+    Ok(CSources {
+        name: generator.name.clone(),
+        header: generator.header(),
+        source,
+    })
+}
+
+/// The parsed template of a node, if its source is one
+fn node_template(metadata: &NodeMetadata) -> Result<Option<SourceTemplate>, GraphyError> {
+    match metadata.source_kind {
+        SourceKind::Template => SourceTemplate::parse(&metadata.function_source).map(Some),
+        SourceKind::Rust => Ok(None),
+    }
+}
+
+/// Pair each metadata param with its argument
+///
+/// Arguments after the params are the variadic inputs: each is available by
+/// its pin name, and the group name gives all of them separated by `, `.
+fn template_values(metadata: &NodeMetadata, args: &[String]) -> HashMap<String, String> {
+    let mut values: HashMap<String, String> =
+        metadata.params.iter().map(|param| param.name.clone()).zip(args.iter().cloned()).collect();
+    if let Some(group) = &metadata.variadic {
+        let elements = args.get(metadata.params.len()..).unwrap_or_default();
+        for (index, arg) in elements.iter().enumerate() {
+            values.insert(group.pin_name(index), arg.clone());
+        }
+        values.insert(group.name.clone(), elements.join(", "));
+    }
+    values
+}
+
+fn template_value<'a>(
+    values: &'a HashMap<String, String>,
+    metadata: &NodeMetadata,
+    name: &str,
+) -> Result<&'a str, GraphyError> {
+    values.get(name).map(String::as_str).ok_or_else(|| {
+        GraphyError::CodeGeneration(format!("Template of '{}' uses undeclared param '{}'", metadata.name, name))
+    })
+}
+
+/// Step emitting a line at `indent`, none for blank lines
+fn line_step(indent: &str, line: &str) -> Option<ExecStep> {
+    (!line.trim().is_empty()).then(|| ExecStep::Emit(format!("{}{}\n", indent, line.trim_end())))
+}
+
+/// Join C parameters, `void` if there are none
+fn param_list(params: Vec<String>) -> String {
+    if params.is_empty() {
+        "void".to_string()
+    } else {
+        params.join(", ")
+    }
+}
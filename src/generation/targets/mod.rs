@@ -0,0 +1,11 @@
+//! # Code Generation Targets
+//!
+//! Ready-made [`CodeGenerator`](super::CodeGenerator)s for languages other
+//! than Rust.
+//!
+//! - [`CGenerator`]: a C source/header pair, for targets without a Rust
+//!   toolchain
+
+mod c;
+
+pub use c::*;
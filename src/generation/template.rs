@@ -0,0 +1,220 @@
+//! # Text Templates
+//!
+//! A lightweight alternative to AST-based inlining, for node sources that
+//! aren't Rust (GLSL snippets, Python, ...).
+//!
+//! Nodes whose metadata sets [`SourceKind::Template`] keep a text template
+//! in `function_source`, with two kinds of placeholders:
+//!
+//! - `{{name}}` is replaced by the value of param `name`
+//! - `{{exec:Label}}` is replaced by the code connected to execution output
+//!   `Label`, or nothing if no code is connected
+//!
+//! Whitespace inside the braces is ignored, and `{{{{` stands for a literal
+//! `{{`. When an `{{exec:...}}` placeholder is the first thing on its line,
+//! every line of the connected code is indented like the placeholder, so
+//! indentation-sensitive targets keep their structure.
+//!
+//! [`inline_node_source`] picks between templates and
+//! [`inline_control_flow_function`] based on the node's source kind.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::generation::SourceTemplate;
+//! use std::collections::HashMap;
+//!
+//! let template = SourceTemplate::parse("if {{condition}}:\n    {{exec:True}}").unwrap();
+//! assert_eq!(template.params(), ["condition"]);
+//! assert_eq!(template.exec_labels(), ["True"]);
+//!
+//! let params = HashMap::from([("condition".to_string(), "x > 5".to_string())]);
+//! let exec = HashMap::from([("True".to_string(), "print(x)\nx = 0".to_string())]);
+//! assert_eq!(template.render(&params, &exec).unwrap(), "if x > 5:\n    print(x)\n    x = 0");
+//! ```
+
+use crate::core::{NodeMetadata, SourceKind};
+use crate::utils::inline_control_flow_function;
+use crate::GraphyError;
+use std::collections::HashMap;
+
+/// Prefix of execution output placeholders
+const EXEC_PREFIX: &str = "exec:";
+
+/// A piece of a parsed template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateSegment {
+    /// Literal text
+    Text(String),
+
+    /// `{{name}}`
+    Param(String),
+
+    /// `{{exec:Label}}`
+    Exec(String),
+}
+
+/// A parsed text template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceTemplate {
+    segments: Vec<TemplateSegment>,
+}
+
+impl SourceTemplate {
+    /// Parses a template.
+    ///
+    /// # Errors
+    ///
+    /// Fails on a placeholder without closing braces or without a name.
+    pub fn parse(source: &str) -> Result<Self, GraphyError> {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut rest = source;
+
+        while let Some(start) = rest.find("{{") {
+            text.push_str(&rest[..start]);
+            rest = &rest[start..];
+            if let Some(after) = rest.strip_prefix("{{{{") {
+                text.push_str("{{");
+                rest = after;
+                continue;
+            }
+
+            let offset = source.len() - rest.len();
+            let end = rest.find("}}").ok_or_else(|| {
+                GraphyError::CodeGeneration(format!("Unclosed template placeholder at byte {}", offset))
+            })?;
+            let name = rest[2..end].trim();
+            let segment = match name.strip_prefix(EXEC_PREFIX) {
+                Some(label) => TemplateSegment::Exec(label.trim().to_string()),
+                None => TemplateSegment::Param(name.to_string()),
+            };
+            if matches!(&segment, TemplateSegment::Param(name) | TemplateSegment::Exec(name) if name.is_empty()) {
+                return Err(GraphyError::CodeGeneration(format!(
+                    "Empty template placeholder at byte {}",
+                    offset
+                )));
+            }
+
+            if !text.is_empty() {
+                segments.push(TemplateSegment::Text(std::mem::take(&mut text)));
+            }
+            segments.push(segment);
+            rest = &rest[end + 2..];
+        }
+
+        text.push_str(rest);
+        if !text.is_empty() {
+            segments.push(TemplateSegment::Text(text));
+        }
+        Ok(Self { segments })
+    }
+
+    /// Returns the pieces of the template in order.
+    ///
+    /// Backends that emit the code connected to execution outputs
+    /// themselves walk these instead of calling [`render`](Self::render).
+    #[inline]
+    pub fn segments(&self) -> &[TemplateSegment] {
+        &self.segments
+    }
+
+    /// Returns the params the template uses, in order of first use.
+    pub fn params(&self) -> Vec<&str> {
+        let mut params: Vec<&str> = Vec::new();
+        for segment in &self.segments {
+            if let TemplateSegment::Param(name) = segment {
+                if !params.contains(&name.as_str()) {
+                    params.push(name);
+                }
+            }
+        }
+        params
+    }
+
+    /// Returns the execution output labels the template uses, in order of first use.
+    pub fn exec_labels(&self) -> Vec<&str> {
+        let mut labels: Vec<&str> = Vec::new();
+        for segment in &self.segments {
+            if let TemplateSegment::Exec(label) = segment {
+                if !labels.contains(&label.as_str()) {
+                    labels.push(label);
+                }
+            }
+        }
+        labels
+    }
+
+    /// Renders the template with param values and connected code.
+    ///
+    /// Execution outputs missing from `exec_replacements` render as nothing.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the template uses a param missing from `params`.
+    pub fn render(
+        &self,
+        params: &HashMap<String, String>,
+        exec_replacements: &HashMap<String, String>,
+    ) -> Result<String, GraphyError> {
+        let mut output = String::new();
+        for segment in &self.segments {
+            match segment {
+                TemplateSegment::Text(text) => output.push_str(text),
+                TemplateSegment::Param(name) => {
+                    let value = params.get(name).ok_or_else(|| {
+                        GraphyError::CodeGeneration(format!("Template param '{}' has no value", name))
+                    })?;
+                    output.push_str(value);
+                }
+                TemplateSegment::Exec(label) => {
+                    let Some(code) = exec_replacements.get(label) else {
+                        continue;
+                    };
+                    let line_start = &output[output.rfind('\n').map_or(0, |i| i + 1)..];
+                    let indent = if line_start.trim().is_empty() { line_start.to_string() } else { String::new() };
+                    for (i, line) in code.lines().enumerate() {
+                        if i > 0 {
+                            output.push('\n');
+                            if !line.is_empty() {
+                                output.push_str(&indent);
+                            }
+                        }
+                        output.push_str(line);
+                    }
+                }
+            }
+        }
+        Ok(output)
+    }
+}
+
+/// Render a text template with param values and connected code
+///
+/// Shorthand for [`SourceTemplate::parse`] followed by
+/// [`SourceTemplate::render`].
+pub fn render_template(
+    template: &str,
+    exec_replacements: &HashMap<String, String>,
+    param_substitutions: &HashMap<String, String>,
+) -> Result<String, GraphyError> {
+    SourceTemplate::parse(template)?.render(param_substitutions, exec_replacements)
+}
+
+/// Inline a node's `function_source` according to its source kind
+///
+/// Rust sources go through
+/// [`inline_control_flow_function`](crate::utils::inline_control_flow_function),
+/// templates through [`render_template`].
+pub fn inline_node_source(
+    metadata: &NodeMetadata,
+    exec_replacements: HashMap<String, String>,
+    param_substitutions: HashMap<String, String>,
+) -> Result<String, GraphyError> {
+    match metadata.source_kind {
+        SourceKind::Rust => {
+            inline_control_flow_function(&metadata.function_source, exec_replacements, param_substitutions)
+        }
+        SourceKind::Template => render_template(&metadata.function_source, &exec_replacements, &param_substitutions),
+    }
+}
@@ -0,0 +1,287 @@
+//! # File Formats
+//!
+//! Reading and writing graphs in formats other than JSON.
+//!
+//! JSON is always available; the other formats are behind Cargo features:
+//!
+//! | Format                    | Feature   | Extensions        |
+//! |---------------------------|-----------|-------------------|
+//! | [`Format::Json`]          | -         | `json`            |
+//! | [`Format::Yaml`]          | `yaml`    | `yaml`, `yml`     |
+//! | [`Format::MessagePack`]   | `msgpack` | `msgpack`, `mpk`  |
+//! | [`Format::Toml`]          | `toml`    | `toml`            |
+//!
+//! Every format stores the same document as JSON: values are converted to a
+//! [`serde_json::Value`] before they're written, and read back into one.
+//! This keeps files readable by any format and lets
+//! [`load_graph`] run the [format migrations](super::migrate) whatever the
+//! format. TOML has no null, so `None` fields are left out (and read back
+//! as `None`), and a TOML document must be a table at the top level.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use graphy::io::{load_graph, save_graph, Format};
+//!
+//! let yaml = save_graph(&graph, Format::Yaml)?;
+//! std::fs::write("level.yaml", &yaml)?;
+//!
+//! // Asset bundles store the compact binary form
+//! let packed = save_graph(&load_graph(&yaml, Format::Yaml)?, Format::MessagePack)?;
+//! ```
+
+use super::Migrator;
+use crate::core::GraphDescription;
+use crate::GraphyError;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+use std::path::Path;
+
+/// A serialization format for graph files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// JSON, pretty-printed
+    Json,
+
+    /// YAML (feature `yaml`)
+    Yaml,
+
+    /// MessagePack with named fields (feature `msgpack`)
+    MessagePack,
+
+    /// TOML (feature `toml`)
+    Toml,
+}
+
+impl Format {
+    /// All formats, available or not.
+    pub const ALL: [Format; 4] = [Format::Json, Format::Yaml, Format::MessagePack, Format::Toml];
+
+    /// Returns the format a file extension (without the dot) stands for.
+    ///
+    /// Matching ignores case.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "json" => Some(Format::Json),
+            "yaml" | "yml" => Some(Format::Yaml),
+            "msgpack" | "mpk" => Some(Format::MessagePack),
+            "toml" => Some(Format::Toml),
+            _ => None,
+        }
+    }
+
+    /// Returns the format of a file, from its extension.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        path.as_ref()
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(Self::from_extension)
+    }
+
+    /// Returns the usual file extension, without the dot.
+    #[inline]
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Yaml => "yaml",
+            Format::MessagePack => "msgpack",
+            Format::Toml => "toml",
+        }
+    }
+
+    /// Returns the Cargo feature the format needs, if any.
+    #[inline]
+    pub fn feature(&self) -> Option<&'static str> {
+        match self {
+            Format::Json => None,
+            Format::Yaml => Some("yaml"),
+            Format::MessagePack => Some("msgpack"),
+            Format::Toml => Some("toml"),
+        }
+    }
+
+    /// Checks if the format was compiled in.
+    #[inline]
+    pub fn is_available(&self) -> bool {
+        match self {
+            Format::Json => true,
+            Format::Yaml => cfg!(feature = "yaml"),
+            Format::MessagePack => cfg!(feature = "msgpack"),
+            Format::Toml => cfg!(feature = "toml"),
+        }
+    }
+
+    /// Checks if the format is binary rather than text.
+    #[inline]
+    pub fn is_binary(&self) -> bool {
+        *self == Format::MessagePack
+    }
+
+    /// Serializes a value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::Serialization`] if the format isn't available
+    /// or can't represent the value.
+    pub fn serialize<T: Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>, GraphyError> {
+        let document = serde_json::to_value(value).map_err(serialization_error)?;
+        self.write_value(&document)
+    }
+
+    /// Deserializes a value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::Serialization`] if the format isn't available
+    /// or the bytes aren't a valid document for `T`.
+    pub fn deserialize<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, GraphyError> {
+        serde_json::from_value(self.read_value(bytes)?).map_err(serialization_error)
+    }
+
+    /// Parses a document without interpreting it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::Serialization`] if the format isn't available
+    /// or the bytes aren't a valid document.
+    pub fn read_value(&self, bytes: &[u8]) -> Result<Value, GraphyError> {
+        match self {
+            Format::Json => serde_json::from_slice(bytes).map_err(serialization_error),
+            #[cfg(feature = "yaml")]
+            Format::Yaml => serde_yaml::from_slice(bytes).map_err(serialization_error),
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack => rmp_serde::from_slice(bytes).map_err(serialization_error),
+            #[cfg(feature = "toml")]
+            Format::Toml => {
+                let text = std::str::from_utf8(bytes).map_err(serialization_error)?;
+                toml::from_str(text).map_err(serialization_error)
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(self.unavailable()),
+        }
+    }
+
+    /// Writes a document.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::Serialization`] if the format isn't available
+    /// or can't represent the document.
+    pub fn write_value(&self, document: &Value) -> Result<Vec<u8>, GraphyError> {
+        match self {
+            Format::Json => serde_json::to_vec_pretty(document).map_err(serialization_error),
+            #[cfg(feature = "yaml")]
+            Format::Yaml => serde_yaml::to_string(document)
+                .map(String::into_bytes)
+                .map_err(serialization_error),
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack => rmp_serde::to_vec_named(document).map_err(serialization_error),
+            #[cfg(feature = "toml")]
+            Format::Toml => toml::to_string_pretty(&without_null_fields(document))
+                .map(String::into_bytes)
+                .map_err(serialization_error),
+            #[allow(unreachable_patterns)]
+            _ => Err(self.unavailable()),
+        }
+    }
+
+    /// Error for a format that wasn't compiled in
+    #[allow(dead_code)]
+    fn unavailable(&self) -> GraphyError {
+        GraphyError::Serialization(format!(
+            "The {} format requires the `{}` feature",
+            self,
+            self.feature().unwrap_or_default()
+        ))
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Format::Json => "JSON",
+            Format::Yaml => "YAML",
+            Format::MessagePack => "MessagePack",
+            Format::Toml => "TOML",
+        })
+    }
+}
+
+fn serialization_error(error: impl fmt::Display) -> GraphyError {
+    GraphyError::Serialization(error.to_string())
+}
+
+/// Drops null object fields, which TOML can't represent
+#[cfg(feature = "toml")]
+fn without_null_fields(document: &Value) -> Value {
+    match document {
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key.clone(), without_null_fields(value)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(without_null_fields).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Serializes a graph
+///
+/// # Errors
+///
+/// Returns [`GraphyError::Serialization`] if the format isn't available or
+/// can't represent the graph.
+pub fn save_graph(graph: &GraphDescription, format: Format) -> Result<Vec<u8>, GraphyError> {
+    format.serialize(graph)
+}
+
+/// Parses a graph, upgrading older format versions on the way
+///
+/// The format counterpart of [`load_with_migration`](super::load_with_migration).
+///
+/// # Errors
+///
+/// Returns [`GraphyError::Serialization`] if the format isn't available,
+/// parsing fails, or the graph can't be migrated.
+pub fn load_graph(bytes: &[u8], format: Format) -> Result<GraphDescription, GraphyError> {
+    let mut document = format.read_value(bytes)?;
+    Migrator::new().migrate(&mut document)?;
+    serde_json::from_value(document).map_err(serialization_error)
+}
+
+/// Writes a graph to a file, in the format of its extension
+///
+/// # Errors
+///
+/// Returns [`GraphyError::Serialization`] if the extension isn't a known
+/// format or serialization fails, and [`GraphyError::Io`] if writing fails.
+pub fn save_graph_file(graph: &GraphDescription, path: impl AsRef<Path>) -> Result<(), GraphyError> {
+    let path = path.as_ref();
+    let bytes = save_graph(graph, format_of(path)?)?;
+    std::fs::write(path, bytes).map_err(|e| GraphyError::Io(format!("{}: {}", path.display(), e)))
+}
+
+/// Reads a graph from a file, in the format of its extension
+///
+/// # Errors
+///
+/// Returns [`GraphyError::Io`] if reading fails, and
+/// [`GraphyError::Serialization`] if the extension isn't a known format or
+/// loading fails (see [`load_graph`]).
+pub fn load_graph_file(path: impl AsRef<Path>) -> Result<GraphDescription, GraphyError> {
+    let path = path.as_ref();
+    let format = format_of(path)?;
+    let bytes = std::fs::read(path).map_err(|e| GraphyError::Io(format!("{}: {}", path.display(), e)))?;
+    load_graph(&bytes, format)
+}
+
+/// The format of a file, from its extension
+fn format_of(path: &Path) -> Result<Format, GraphyError> {
+    Format::from_path(path).ok_or_else(|| {
+        GraphyError::Serialization(format!("Unknown graph file format: {}", path.display()))
+    })
+}
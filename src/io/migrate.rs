@@ -0,0 +1,322 @@
+//! # Format Migration
+//!
+//! Upgrades serialized graphs from older format versions.
+//!
+//! Every [`GraphDescription`] carries a `format_version`. When the format
+//! changes, a [`MigrationStep`] is registered that rewrites the JSON of the
+//! previous version into the next one. [`Migrator`] chains the steps, so a
+//! graph saved by any older version is upgraded to
+//! [`CURRENT_FORMAT_VERSION`] before it is deserialized.
+//!
+//! Steps work on the raw [`serde_json::Value`], so they can handle documents
+//! that no longer match the current structs (renamed or missing fields,
+//! removed enum variants).
+//!
+//! # Built-in steps
+//!
+//! - **1 → 2**: fills in fields that early graphs could omit (metadata
+//!   fields, `connections`, `comments`, node `properties`, pin names) and
+//!   replaces legacy `DataType` variants (`Number`, `String`, ...) with their
+//!   `Typed` equivalents.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::io::load_with_migration;
+//! use graphy::DataType;
+//!
+//! // A version 1 graph: no format version, legacy pin types, no comments
+//! let json = r#"{
+//!     "metadata": { "name": "old" },
+//!     "nodes": {
+//!         "a": {
+//!             "id": "a",
+//!             "node_type": "math.add",
+//!             "position": { "x": 0.0, "y": 0.0 },
+//!             "inputs": [
+//!                 { "id": "x", "pin": { "id": "x", "data_type": "Number", "pin_type": "Input" } }
+//!             ],
+//!             "outputs": []
+//!         }
+//!     }
+//! }"#;
+//!
+//! let graph = load_with_migration(json).unwrap();
+//! assert_eq!(graph.format_version, graphy::core::CURRENT_FORMAT_VERSION);
+//! assert_eq!(graph.nodes["a"].inputs[0].pin.data_type, DataType::Typed("f64".into()));
+//! ```
+
+use crate::core::{DataType, GraphDescription, CURRENT_FORMAT_VERSION};
+use crate::GraphyError;
+use serde_json::{Map, Value};
+
+/// Function rewriting a graph document from one format version to the next
+pub type MigrationFn = fn(&mut Value) -> Result<(), GraphyError>;
+
+/// A registered upgrade from `from_version` to `from_version + 1`
+#[derive(Debug, Clone, Copy)]
+pub struct MigrationStep {
+    /// Format version this step upgrades from
+    pub from_version: u32,
+
+    /// Short description of what the step changes
+    pub description: &'static str,
+
+    /// Rewrites the document in place
+    ///
+    /// The `format_version` field is updated by the [`Migrator`] afterwards.
+    pub migrate: MigrationFn,
+}
+
+impl MigrationStep {
+    /// Creates a new migration step.
+    #[inline]
+    pub const fn new(from_version: u32, description: &'static str, migrate: MigrationFn) -> Self {
+        Self {
+            from_version,
+            description,
+            migrate,
+        }
+    }
+}
+
+/// Chains migration steps to upgrade graph documents
+///
+/// [`Migrator::new`] includes all built-in steps; use
+/// [`register`](Self::register) to add steps for custom format versions.
+#[derive(Debug, Clone)]
+pub struct Migrator {
+    /// Steps sorted by `from_version`
+    steps: Vec<MigrationStep>,
+
+    /// Version documents are upgraded to
+    target_version: u32,
+}
+
+impl Migrator {
+    /// Creates a migrator with the built-in steps, targeting [`CURRENT_FORMAT_VERSION`].
+    pub fn new() -> Self {
+        Self {
+            steps: vec![MigrationStep::new(
+                1,
+                "Fill in missing fields and replace legacy data types",
+                migrate_v1_to_v2,
+            )],
+            target_version: CURRENT_FORMAT_VERSION,
+        }
+    }
+
+    /// Creates a migrator with no steps.
+    pub fn empty() -> Self {
+        Self {
+            steps: Vec::new(),
+            target_version: CURRENT_FORMAT_VERSION,
+        }
+    }
+
+    /// Sets the version documents are upgraded to.
+    pub fn with_target_version(mut self, version: u32) -> Self {
+        self.target_version = version;
+        self
+    }
+
+    /// Registers a migration step.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::Custom`] if a step from the same version is
+    /// already registered.
+    pub fn register(&mut self, step: MigrationStep) -> Result<(), GraphyError> {
+        match self.steps.binary_search_by_key(&step.from_version, |s| s.from_version) {
+            Ok(_) => Err(GraphyError::Custom(format!(
+                "A migration from format version {} is already registered",
+                step.from_version
+            ))),
+            Err(index) => {
+                self.steps.insert(index, step);
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the registered steps, ordered by version.
+    #[inline]
+    pub fn steps(&self) -> &[MigrationStep] {
+        &self.steps
+    }
+
+    /// Returns the version documents are upgraded to.
+    #[inline]
+    pub fn target_version(&self) -> u32 {
+        self.target_version
+    }
+
+    /// Upgrades a graph document in place to the target version.
+    ///
+    /// Returns the version the document had before migrating.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::Serialization`] if the document isn't a JSON
+    /// object, has an invalid `format_version`, is newer than the target
+    /// version, or no step upgrades from one of the versions in between.
+    /// Errors from the steps themselves are returned unchanged.
+    pub fn migrate(&self, document: &mut Value) -> Result<u32, GraphyError> {
+        let original = format_version_of(document)?;
+
+        if original > self.target_version {
+            return Err(GraphyError::Serialization(format!(
+                "Graph format version {} is newer than the supported version {}",
+                original, self.target_version
+            )));
+        }
+
+        let mut version = original;
+        while version < self.target_version {
+            let step = self
+                .steps
+                .iter()
+                .find(|step| step.from_version == version)
+                .ok_or_else(|| {
+                    GraphyError::Serialization(format!("No migration from graph format version {}", version))
+                })?;
+
+            tracing::debug!("[MIGRATE] v{} -> v{}: {}", version, version + 1, step.description);
+            (step.migrate)(document)?;
+
+            version += 1;
+            as_object_mut(document)?.insert("format_version".to_string(), Value::from(version));
+        }
+
+        Ok(original)
+    }
+
+    /// Parses a graph from JSON, upgrading it to the target version first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::Serialization`] if parsing or migration fails.
+    pub fn load(&self, json: &str) -> Result<GraphDescription, GraphyError> {
+        let mut document: Value = serde_json::from_str(json).map_err(|e| GraphyError::Serialization(e.to_string()))?;
+        self.migrate(&mut document)?;
+        serde_json::from_value(document).map_err(|e| GraphyError::Serialization(e.to_string()))
+    }
+}
+
+impl Default for Migrator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parses a graph from JSON, upgrading older format versions on the way
+///
+/// Equivalent to `Migrator::new().load(json)`.
+///
+/// # Errors
+///
+/// Returns [`GraphyError::Serialization`] if parsing fails, the graph was
+/// saved by a newer version of Graphy, or it can't be migrated.
+pub fn load_with_migration(json: &str) -> Result<GraphDescription, GraphyError> {
+    Migrator::new().load(json)
+}
+
+/// Reads the format version of a graph document
+///
+/// Documents without a `format_version` field are version 1.
+///
+/// # Errors
+///
+/// Returns [`GraphyError::Serialization`] if the document isn't a JSON
+/// object or the version isn't a non-negative integer.
+pub fn format_version_of(document: &Value) -> Result<u32, GraphyError> {
+    let object = document
+        .as_object()
+        .ok_or_else(|| GraphyError::Serialization("Graph document is not a JSON object".to_string()))?;
+
+    match object.get("format_version") {
+        None => Ok(1),
+        Some(value) => value
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| GraphyError::Serialization(format!("Invalid graph format version: {}", value))),
+    }
+}
+
+fn as_object_mut(value: &mut Value) -> Result<&mut Map<String, Value>, GraphyError> {
+    value
+        .as_object_mut()
+        .ok_or_else(|| GraphyError::Serialization("Graph document is not a JSON object".to_string()))
+}
+
+/// Insert a field if it is missing
+fn fill(object: &mut Map<String, Value>, key: &str, default: impl FnOnce() -> Value) {
+    object.entry(key).or_insert_with(default);
+}
+
+// ============================================================================
+// v1 -> v2
+// ============================================================================
+
+/// Fill in fields early graphs could omit and replace legacy data types
+fn migrate_v1_to_v2(document: &mut Value) -> Result<(), GraphyError> {
+    let graph = as_object_mut(document)?;
+
+    let metadata = graph
+        .entry("metadata")
+        .or_insert_with(|| Value::Object(Map::new()));
+    if let Some(metadata) = metadata.as_object_mut() {
+        fill(metadata, "name", || Value::from("Untitled"));
+        fill(metadata, "description", || Value::from(""));
+        fill(metadata, "version", || Value::from("1.0.0"));
+        fill(metadata, "created_at", || Value::from(""));
+        fill(metadata, "modified_at", || Value::from(""));
+    }
+
+    fill(graph, "nodes", || Value::Object(Map::new()));
+    fill(graph, "connections", || Value::Array(Vec::new()));
+    fill(graph, "comments", || Value::Array(Vec::new()));
+
+    if let Some(nodes) = graph.get_mut("nodes").and_then(Value::as_object_mut) {
+        for node in nodes.values_mut().filter_map(Value::as_object_mut) {
+            fill(node, "properties", || Value::Object(Map::new()));
+
+            for pins_key in ["inputs", "outputs"] {
+                let pins = node.entry(pins_key).or_insert_with(|| Value::Array(Vec::new()));
+                for pin_instance in pins.as_array_mut().into_iter().flatten() {
+                    if let Some(pin) = pin_instance.get_mut("pin").and_then(Value::as_object_mut) {
+                        migrate_pin_v1(pin);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Default a pin's name to its ID and make its data type explicit
+fn migrate_pin_v1(pin: &mut Map<String, Value>) {
+    if !pin.contains_key("name") {
+        if let Some(id) = pin.get("id").cloned() {
+            pin.insert("name".to_string(), id);
+        }
+    }
+
+    let Some(data_type) = pin.get_mut("data_type") else {
+        return;
+    };
+    let Ok(legacy) = serde_json::from_value::<DataType>(data_type.clone()) else {
+        return;
+    };
+
+    // `Any` has no typed equivalent and stays as it is
+    if matches!(legacy, DataType::Execution | DataType::Typed(_) | DataType::Any) {
+        return;
+    }
+    if let Some(type_string) = legacy.type_string() {
+        if let Ok(typed) = serde_json::to_value(DataType::Typed(type_string.into())) {
+            *data_type = typed;
+        }
+    }
+}
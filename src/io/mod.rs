@@ -0,0 +1,10 @@
+//! # Graph I/O
+//!
+//! Loading graphs saved by older versions of Graphy, and reading and writing
+//! them in formats other than JSON.
+
+pub mod format;
+pub mod migrate;
+
+pub use format::*;
+pub use migrate::*;
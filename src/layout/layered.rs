@@ -0,0 +1,342 @@
+//! Layered (Sugiyama-style) layout.
+//!
+//! The layout runs in four steps:
+//!
+//! 1. **Cycle removal**: edges closing a cycle (found by depth-first search)
+//!    are reversed so the graph is acyclic.
+//! 2. **Layering**: each node is placed one layer after its latest input
+//!    (longest path), then pulled right to sit just before its earliest
+//!    consumer, so pure nodes end up next to the node using their value.
+//!    Edges spanning several layers are split by virtual nodes.
+//! 3. **Ordering**: nodes within each layer are sorted by the average
+//!    position of their neighbors (barycenter heuristic), sweeping down and
+//!    up the layers to reduce crossings.
+//! 4. **Placement**: each node is moved as close to the average of its
+//!    neighbors as the layer order and minimum spacing allow.
+
+use super::LayoutOptions;
+use crate::core::{GraphDescription, Position};
+use crate::export::Direction;
+use std::collections::{HashMap, VecDeque};
+
+/// Computes a layered layout and writes the positions back to the nodes.
+///
+/// All nodes are moved, and comments attached to nodes are fitted around
+/// them again (see [`LayoutOptions::comment_fit`]); other comments and
+/// groups are left as they are. The result depends only on the graph
+/// structure and node IDs, so laying out the same graph twice gives the
+/// same positions.
+pub fn auto_layout(graph: &mut GraphDescription, options: LayoutOptions) {
+    for (id, position) in compute_layout(graph, &options) {
+        if let Some(node) = graph.nodes.get_mut(&id) {
+            node.position = position;
+        }
+    }
+    graph.fit_comments(&options.comment_fit);
+}
+
+/// Computes a layered layout without modifying the graph.
+///
+/// Returns the new position of every node, keyed by node ID. Editors can
+/// use this to animate nodes to their new positions.
+///
+/// # Example
+///
+/// ```
+/// use graphy::layout::{compute_layout, LayoutOptions};
+/// use graphy::{Connection, GraphDescription, NodeInstance, Position};
+///
+/// let mut graph = GraphDescription::new("example");
+/// graph.add_node(NodeInstance::new("a", "math.add", Position::zero()));
+/// graph.add_node(NodeInstance::new("b", "print", Position::zero()));
+/// graph.add_connection(Connection::data("a", "result", "b", "value"));
+///
+/// let positions = compute_layout(&graph, &LayoutOptions::new().with_spacing(200.0, 100.0));
+/// assert_eq!(positions["a"], Position::new(0.0, 0.0));
+/// assert_eq!(positions["b"], Position::new(200.0, 0.0));
+/// ```
+pub fn compute_layout(graph: &GraphDescription, options: &LayoutOptions) -> HashMap<String, Position> {
+    // Sorted so the layout doesn't depend on HashMap order
+    let mut ids: Vec<&str> = graph.nodes.keys().map(String::as_str).collect();
+    ids.sort_unstable();
+    let index: HashMap<&str, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+
+    let mut successors = vec![Vec::new(); ids.len()];
+    for connection in &graph.connections {
+        let source = index.get(connection.source_node.as_str());
+        let target = index.get(connection.target_node.as_str());
+        if let (Some(&source), Some(&target)) = (source, target) {
+            if source != target {
+                successors[source].push(target);
+            }
+        }
+    }
+    for targets in &mut successors {
+        targets.sort_unstable();
+        targets.dedup();
+    }
+
+    let successors = break_cycles(&successors);
+    let layer_of = assign_layers(&successors);
+    let mut layered = Layered::new(&successors, &layer_of);
+    layered.order(options.sweeps);
+    let offsets = layered.place(options.sweeps, options.node_spacing);
+
+    let min_offset = offsets.iter().take(ids.len()).copied().fold(f64::INFINITY, f64::min);
+    ids.iter()
+        .enumerate()
+        .map(|(node, id)| {
+            let along = layer_of[node] as f64 * options.layer_spacing;
+            let across = offsets[node] - min_offset;
+            let position = match options.direction {
+                Direction::LeftToRight => Position::new(options.origin.x + along, options.origin.y + across),
+                Direction::TopToBottom => Position::new(options.origin.x + across, options.origin.y + along),
+            };
+            (id.to_string(), position)
+        })
+        .collect()
+}
+
+/// DFS state of a node during cycle removal
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Visit {
+    New,
+    Active,
+    Done,
+}
+
+/// Reverses the edges that close cycles
+///
+/// The search starts from nodes without inputs, so in a loop body the edge
+/// back to the loop head is the one reversed.
+fn break_cycles(successors: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let count = successors.len();
+    let mut has_input = vec![false; count];
+    for &target in successors.iter().flatten() {
+        has_input[target] = true;
+    }
+
+    let mut state = vec![Visit::New; count];
+    let mut acyclic = vec![Vec::new(); count];
+    let roots = (0..count).filter(|&node| !has_input[node]).chain(0..count);
+
+    for root in roots {
+        if state[root] != Visit::New {
+            continue;
+        }
+        state[root] = Visit::Active;
+        let mut stack = vec![(root, 0)];
+
+        while let Some(&(node, next)) = stack.last() {
+            let Some(&child) = successors[node].get(next) else {
+                state[node] = Visit::Done;
+                stack.pop();
+                continue;
+            };
+            if let Some(top) = stack.last_mut() {
+                top.1 += 1;
+            }
+
+            match state[child] {
+                Visit::Active => acyclic[child].push(node),
+                Visit::Done => acyclic[node].push(child),
+                Visit::New => {
+                    acyclic[node].push(child);
+                    state[child] = Visit::Active;
+                    stack.push((child, 0));
+                }
+            }
+        }
+    }
+
+    for targets in &mut acyclic {
+        targets.sort_unstable();
+        targets.dedup();
+    }
+    acyclic
+}
+
+/// Assigns each node of an acyclic graph to a layer
+fn assign_layers(successors: &[Vec<usize>]) -> Vec<usize> {
+    let count = successors.len();
+    let mut in_degree = vec![0; count];
+    for &target in successors.iter().flatten() {
+        in_degree[target] += 1;
+    }
+
+    let mut queue: VecDeque<usize> = (0..count).filter(|&node| in_degree[node] == 0).collect();
+    let mut topological = Vec::with_capacity(count);
+    while let Some(node) = queue.pop_front() {
+        topological.push(node);
+        for &target in &successors[node] {
+            in_degree[target] -= 1;
+            if in_degree[target] == 0 {
+                queue.push_back(target);
+            }
+        }
+    }
+
+    // Longest path from the sources
+    let mut layer = vec![0; count];
+    for &node in &topological {
+        for &target in &successors[node] {
+            layer[target] = layer[target].max(layer[node] + 1);
+        }
+    }
+
+    // Pull nodes toward their earliest consumer; sinks keep their layer
+    for &node in topological.iter().rev() {
+        if let Some(earliest) = successors[node].iter().map(|&target| layer[target]).min() {
+            layer[node] = earliest - 1;
+        }
+    }
+
+    layer
+}
+
+/// Layers with virtual nodes on long edges
+///
+/// Vertices `0..n` are the graph's nodes, the rest are virtual.
+struct Layered {
+    /// Vertices of each layer, in order
+    layers: Vec<Vec<usize>>,
+
+    /// Neighbors of each vertex in the previous layer
+    up: Vec<Vec<usize>>,
+
+    /// Neighbors of each vertex in the next layer
+    down: Vec<Vec<usize>>,
+}
+
+impl Layered {
+    fn new(successors: &[Vec<usize>], layer_of: &[usize]) -> Self {
+        let layer_count = layer_of.iter().max().map_or(0, |max| max + 1);
+        let mut layers = vec![Vec::new(); layer_count];
+        for (node, &layer) in layer_of.iter().enumerate() {
+            layers[layer].push(node);
+        }
+
+        let mut up = vec![Vec::new(); layer_of.len()];
+        let mut down = vec![Vec::new(); layer_of.len()];
+        for (source, targets) in successors.iter().enumerate() {
+            for &target in targets {
+                let mut previous = source;
+                for layer in &mut layers[layer_of[source] + 1..layer_of[target]] {
+                    let virtual_node = up.len();
+                    up.push(vec![previous]);
+                    down.push(Vec::new());
+                    down[previous].push(virtual_node);
+                    layer.push(virtual_node);
+                    previous = virtual_node;
+                }
+                down[previous].push(target);
+                up[target].push(previous);
+            }
+        }
+
+        Self { layers, up, down }
+    }
+
+    /// Orders the layers to reduce crossings
+    fn order(&mut self, sweeps: usize) {
+        let mut rank = vec![0.0; self.up.len()];
+        self.update_ranks(&mut rank);
+
+        for _ in 0..sweeps {
+            for layer in 1..self.layers.len() {
+                sort_by_barycenter(&mut self.layers[layer], &self.up, &mut rank);
+            }
+            for layer in (0..self.layers.len().saturating_sub(1)).rev() {
+                sort_by_barycenter(&mut self.layers[layer], &self.down, &mut rank);
+            }
+        }
+    }
+
+    fn update_ranks(&self, rank: &mut [f64]) {
+        for layer in &self.layers {
+            for (position, &vertex) in layer.iter().enumerate() {
+                rank[vertex] = position as f64;
+            }
+        }
+    }
+
+    /// Computes the offset of every vertex within its layer
+    fn place(&self, sweeps: usize, spacing: f64) -> Vec<f64> {
+        let mut offset = vec![0.0; self.up.len()];
+        for layer in &self.layers {
+            for (position, &vertex) in layer.iter().enumerate() {
+                offset[vertex] = position as f64 * spacing;
+            }
+        }
+
+        for _ in 0..sweeps {
+            for layer in &self.layers {
+                align_layer(layer, &self.up, &mut offset, spacing);
+            }
+            for layer in self.layers.iter().rev() {
+                align_layer(layer, &self.down, &mut offset, spacing);
+            }
+        }
+
+        offset
+    }
+}
+
+/// Average of the neighbors' values, `None` without neighbors
+fn barycenter(neighbors: &[usize], values: &[f64]) -> Option<f64> {
+    (!neighbors.is_empty()).then(|| neighbors.iter().map(|&n| values[n]).sum::<f64>() / neighbors.len() as f64)
+}
+
+/// Sorts a layer by the barycenter of each vertex's neighbors
+///
+/// Vertices without neighbors keep their current position.
+fn sort_by_barycenter(layer: &mut [usize], neighbors: &[Vec<usize>], rank: &mut [f64]) {
+    let mut keyed: Vec<(f64, usize)> = layer
+        .iter()
+        .map(|&vertex| (barycenter(&neighbors[vertex], rank).unwrap_or(rank[vertex]), vertex))
+        .collect();
+    keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    for (position, (_, vertex)) in keyed.into_iter().enumerate() {
+        layer[position] = vertex;
+        rank[vertex] = position as f64;
+    }
+}
+
+/// Moves a layer's vertices toward their neighbors' average offset
+///
+/// Finds the offsets closest (least squares) to the targets that keep the
+/// layer order and spacing, by pooling adjacent vertices whose targets
+/// would violate the spacing.
+fn align_layer(layer: &[usize], neighbors: &[Vec<usize>], offset: &mut [f64], spacing: f64) {
+    // Subtracting `i * spacing` turns the spacing constraint into "non-decreasing"
+    let targets: Vec<f64> = layer
+        .iter()
+        .enumerate()
+        .map(|(i, &vertex)| barycenter(&neighbors[vertex], offset).unwrap_or(offset[vertex]) - i as f64 * spacing)
+        .collect();
+
+    // Blocks of (sum, count), each placed at its mean
+    let mut blocks: Vec<(f64, usize)> = Vec::with_capacity(layer.len());
+    for target in targets {
+        blocks.push((target, 1));
+        while let [.., (prev_sum, prev_count), (sum, count)] = blocks[..] {
+            if prev_sum / prev_count as f64 <= sum / count as f64 {
+                break;
+            }
+            blocks.pop();
+            if let Some(last) = blocks.last_mut() {
+                *last = (prev_sum + sum, prev_count + count);
+            }
+        }
+    }
+
+    let mut i = 0;
+    for (sum, count) in blocks {
+        let mean = sum / count as f64;
+        for _ in 0..count {
+            offset[layer[i]] = mean + i as f64 * spacing;
+            i += 1;
+        }
+    }
+}
@@ -0,0 +1,33 @@
+//! # Graph Layout
+//!
+//! Automatic node placement for graphs built in code.
+//!
+//! Graphs assembled programmatically usually have every node at
+//! [`Position::zero`](crate::Position::zero), which makes them unusable in a
+//! visual editor. [`auto_layout`] computes a layered (Sugiyama-style) layout
+//! from the graph's data and execution connections and writes the positions
+//! back to the nodes: events and other sources end up in the first layer,
+//! each node sits one layer after its inputs, and nodes within a layer are
+//! ordered to reduce edge crossings. [`LayoutOptions`] controls direction and
+//! spacing.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::layout::{auto_layout, LayoutOptions};
+//! use graphy::{Connection, GraphDescription, NodeInstance, Position};
+//!
+//! let mut graph = GraphDescription::new("example");
+//! graph.add_node(NodeInstance::new("start", "on_start", Position::zero()));
+//! graph.add_node(NodeInstance::new("print", "print", Position::zero()));
+//! graph.add_connection(Connection::execution("start", "exec", "print", "exec_in"));
+//!
+//! auto_layout(&mut graph, LayoutOptions::new());
+//! assert!(graph.get_node("start").unwrap().position.x < graph.get_node("print").unwrap().position.x);
+//! ```
+
+pub mod layered;
+pub mod options;
+
+pub use layered::*;
+pub use options::*;
@@ -0,0 +1,90 @@
+//! Options for automatic layout.
+
+use crate::core::{CommentFit, Position};
+use crate::export::Direction;
+
+/// Options for [`auto_layout`](super::auto_layout)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayoutOptions {
+    /// Direction the layers advance in
+    pub direction: Direction,
+
+    /// Distance between consecutive layers
+    pub layer_spacing: f64,
+
+    /// Minimum distance between nodes in the same layer
+    pub node_spacing: f64,
+
+    /// Position of the top-left corner of the layout
+    pub origin: Position,
+
+    /// Number of ordering and alignment sweeps
+    ///
+    /// More sweeps give fewer crossings and straighter edges on large
+    /// graphs, at linear cost.
+    pub sweeps: usize,
+
+    /// How comments attached to nodes are fitted around them afterwards
+    pub comment_fit: CommentFit,
+}
+
+impl LayoutOptions {
+    /// Creates left-to-right options with spacing suited to typical node sizes.
+    pub fn new() -> Self {
+        Self {
+            direction: Direction::LeftToRight,
+            layer_spacing: 250.0,
+            node_spacing: 120.0,
+            origin: Position::zero(),
+            sweeps: 8,
+            comment_fit: CommentFit::default(),
+        }
+    }
+
+    /// Sets the direction the layers advance in.
+    #[inline]
+    #[must_use]
+    pub fn with_direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Sets the distance between layers and between nodes within a layer.
+    #[inline]
+    #[must_use]
+    pub fn with_spacing(mut self, layer_spacing: f64, node_spacing: f64) -> Self {
+        self.layer_spacing = layer_spacing;
+        self.node_spacing = node_spacing;
+        self
+    }
+
+    /// Sets the position of the top-left corner of the layout.
+    #[inline]
+    #[must_use]
+    pub fn with_origin(mut self, origin: Position) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Sets the number of ordering and alignment sweeps.
+    #[inline]
+    #[must_use]
+    pub fn with_sweeps(mut self, sweeps: usize) -> Self {
+        self.sweeps = sweeps;
+        self
+    }
+
+    /// Sets how attached comments are fitted around their nodes.
+    #[inline]
+    #[must_use]
+    pub fn with_comment_fit(mut self, comment_fit: CommentFit) -> Self {
+        self.comment_fit = comment_fit;
+        self
+    }
+}
+
+impl Default for LayoutOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -67,21 +67,30 @@ pub mod core;
 pub mod analysis;
 pub mod generation;
 pub mod utils;
+pub mod io;
+pub mod export;
+pub mod layout;
+pub mod cache;
+pub mod watch;
+pub mod testing;
+#[cfg(feature = "parallel")]
 pub mod parallel;
 
 // Re-export commonly used types
 pub use core::{
     GraphDescription, NodeInstance, Connection, Pin, PinInstance,
     DataType, TypeInfo, NodeTypes, Position, ConnectionType, PropertyValue,
-    GraphMetadata, NodeMetadata, ParamInfo, NodeMetadataProvider, PinType,
+    GraphMetadata, NodeMetadata, ParamInfo, NodeMetadataProvider, NodeRegistry, PinType, GraphParam, GraphGroup,
+    SourceKind, ProviderVersion, IdStrategy,
 };
 
 pub use analysis::{
     DataResolver, ExecutionRouting, DataSource,
+    AnalysisPass, AnalysisContext, PassManager,
 };
 
 pub use generation::{
-    CodeGeneratorContext,
+    CodeGeneratorContext, CodeGenerator, SourceMap, compile,
 };
 
 pub use utils::{
@@ -103,11 +112,70 @@ pub enum GraphyError {
     #[error("Type mismatch: expected {expected}, got {actual}")]
     TypeMismatch { expected: String, actual: String },
 
-    #[error("Cyclic dependency detected, check your graph for looping code")]
-    CyclicDependency,
+    #[error(
+        "Cyclic dependency detected between nodes {}, check your graph for looping code",
+        format_cycle(.nodes)
+    )]
+    CyclicDependency { nodes: Vec<String> },
 
-    #[error("Invalid connection: {0}")]
-    InvalidConnection(String),
+    #[error("Graphs call each other in a cycle: {}", format_cycle(.graphs))]
+    CyclicGraphReference { graphs: Vec<String> },
+
+    #[error("Invalid connection {connection}: {reason}")]
+    InvalidConnection {
+        connection: Box<core::ConnectionRef>,
+        reason: core::ConnectionErrorReason,
+    },
+
+    #[error("Node {node} uses undeclared variable '{variable}'")]
+    UndeclaredVariable { node: String, variable: String },
+
+    #[error("Node {node} has unknown type '{node_type}'")]
+    UnknownNodeType { node: String, node_type: String },
+
+    #[error("Property {node}.{property} {message}")]
+    InvalidProperty {
+        node: String,
+        property: String,
+        message: String,
+    },
+
+    #[error("Required input {node}.{pin} is not connected")]
+    UnconnectedInput { node: String, pin: String },
+
+    #[error("Input {node}.{pin} has {connections} connections, but accepts at most {max}")]
+    TooManyConnections {
+        node: String,
+        pin: String,
+        connections: usize,
+        max: usize,
+    },
+
+    #[error("Node {node} reads {producer}.{pin}, but {producer} doesn't always run before it in {event}")]
+    AmbiguousOrdering {
+        node: String,
+        producer: String,
+        pin: String,
+        event: String,
+    },
+
+    #[error("Node {node} requires {node_type} {requirement}, but {}", format_versions(.available))]
+    UnsatisfiedVersion {
+        node: String,
+        node_type: String,
+        requirement: String,
+        available: Vec<String>,
+    },
+
+    #[error("External {path} is not provided by the engine")]
+    UnknownExternal { path: String },
+
+    #[error("External {path} is declared as {declared}, but the engine provides {provided}")]
+    ExternalMismatch {
+        path: String,
+        declared: String,
+        provided: String,
+    },
 
     #[error("Code generation error: {0}")]
     CodeGeneration(String),
@@ -118,6 +186,149 @@ pub enum GraphyError {
     #[error("Graph expansion error: {0}")]
     GraphExpansion(String),
 
+    #[error("Serialization error: {0}")]
+    Serialization(String),
+
+    #[error("I/O error: {0}")]
+    Io(String),
+
+    #[error("Operation was cancelled")]
+    Cancelled,
+
+    #[error("In graph '{graph}': {source}")]
+    InGraph {
+        graph: String,
+        #[source]
+        source: Box<GraphyError>,
+    },
+
+    #[error("At node {node}: {source}")]
+    AtNode {
+        node: String,
+        #[source]
+        source: Box<GraphyError>,
+    },
+
+    #[error("At pin {node}.{pin}: {source}")]
+    AtPin {
+        node: String,
+        pin: String,
+        #[source]
+        source: Box<GraphyError>,
+    },
+
     #[error("{0}")]
     Custom(String),
 }
+
+impl GraphyError {
+    /// Attributes the error to a node, see [`GraphyError::AtNode`].
+    ///
+    /// Errors already naming a node (see [`node`](Self::node)) are returned
+    /// unchanged, so the context names the innermost node that failed.
+    /// Cancellation isn't attributed to anything.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::GraphyError;
+    ///
+    /// let error = GraphyError::CodeGeneration("no template".into()).with_node("add_1").with_node("print_1");
+    /// assert_eq!(error.to_string(), "At node add_1: Code generation error: no template");
+    /// assert_eq!(error.node(), Some("add_1"));
+    /// ```
+    #[must_use]
+    pub fn with_node(self, node: impl Into<String>) -> Self {
+        if self.has_node_context() {
+            return self;
+        }
+        GraphyError::AtNode {
+            node: node.into(),
+            source: Box::new(self),
+        }
+    }
+
+    /// Attributes the error to a pin of a node, see [`GraphyError::AtPin`].
+    ///
+    /// Like [`with_node`](Self::with_node), errors already naming a node are
+    /// returned unchanged.
+    #[must_use]
+    pub fn with_pin(self, node: impl Into<String>, pin: impl Into<String>) -> Self {
+        if self.has_node_context() {
+            return self;
+        }
+        GraphyError::AtPin {
+            node: node.into(),
+            pin: pin.into(),
+            source: Box::new(self),
+        }
+    }
+
+    /// Returns the error without its graph, node, and pin contexts.
+    pub fn root(&self) -> &GraphyError {
+        match self {
+            GraphyError::InGraph { source, .. }
+            | GraphyError::AtNode { source, .. }
+            | GraphyError::AtPin { source, .. } => source.root(),
+            error => error,
+        }
+    }
+
+    /// Returns the node the error is about, if any.
+    ///
+    /// That's the node of the [`with_node`](Self::with_node) or
+    /// [`with_pin`](Self::with_pin) context, or else the node the error
+    /// itself names (the target node of an invalid connection).
+    pub fn node(&self) -> Option<&str> {
+        match self {
+            GraphyError::AtNode { node, .. } | GraphyError::AtPin { node, .. } => Some(node),
+            GraphyError::InGraph { source, .. } => source.node(),
+            GraphyError::NodeNotFound(node)
+            | GraphyError::PinNotFound { node, .. }
+            | GraphyError::UndeclaredVariable { node, .. }
+            | GraphyError::InvalidProperty { node, .. }
+            | GraphyError::UnconnectedInput { node, .. }
+            | GraphyError::TooManyConnections { node, .. }
+            | GraphyError::AmbiguousOrdering { node, .. }
+            | GraphyError::UnknownNodeType { node, .. }
+            | GraphyError::UnsatisfiedVersion { node, .. } => Some(node),
+            GraphyError::InvalidConnection { connection, .. } => Some(&connection.target_node),
+            _ => None,
+        }
+    }
+
+    /// Returns the pin the error is about, if any.
+    pub fn pin(&self) -> Option<&str> {
+        match self {
+            GraphyError::AtPin { pin, .. } => Some(pin),
+            GraphyError::AtNode { .. } => None,
+            GraphyError::InGraph { source, .. } => source.pin(),
+            GraphyError::PinNotFound { pin, .. }
+            | GraphyError::InvalidProperty { property: pin, .. }
+            | GraphyError::UnconnectedInput { pin, .. }
+            | GraphyError::TooManyConnections { pin, .. } => Some(pin),
+            GraphyError::InvalidConnection { connection, .. } => Some(&connection.target_pin),
+            _ => None,
+        }
+    }
+
+    fn has_node_context(&self) -> bool {
+        matches!(self, GraphyError::Cancelled) || self.node().is_some()
+    }
+}
+
+/// Format the available versions of a node type for error messages
+fn format_versions(versions: &[String]) -> String {
+    match versions {
+        [] => "no version is available".to_string(),
+        _ => format!("only {} available", versions.join(", ")),
+    }
+}
+
+/// Format a cycle as `a -> b -> a` for error messages
+fn format_cycle(nodes: &[String]) -> String {
+    match nodes.first() {
+        Some(first) => format!("{} -> {}", nodes.join(" -> "), first),
+        None => "(unknown)".to_string(),
+    }
+}
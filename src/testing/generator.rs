@@ -0,0 +1,230 @@
+//! Random well-formed graphs for fuzzing and property tests.
+
+use super::canonical_registry;
+use crate::core::{
+    Connection, DataType, GraphDescription, NodeInstance, NodeMetadata, NodeMetadataProvider, Position, PropertyValue,
+    EXEC_INPUT_PIN,
+};
+
+pub use arbitrary::{Arbitrary, Unstructured};
+
+/// Nodes a [`GraphGenerator`] creates at most, unless changed
+pub const DEFAULT_MAX_NODES: usize = 32;
+
+/// Bytes of pseudo-random data behind [`GraphGenerator::generate_seeded`]
+const SEEDED_BYTES: usize = 4096;
+
+/// Generates random graphs that are well-formed for a node library.
+///
+/// Every node is created from the library's metadata, so it has exactly the
+/// pins its type declares, and every connection joins an existing output to
+/// an existing input of the same type:
+///
+/// - each data input is connected at most once, or may get a constant
+///   property of its type (integers, floats, `bool`, and `String`)
+/// - execution outputs only lead to later nodes, so execution flow never
+///   loops and code generation always terminates
+/// - data flows only from earlier nodes to later ones, unless
+///   [`with_acyclic_data(false)`](Self::with_acyclic_data) allows cycles
+///
+/// Switch nodes are never generated, since their pins depend on the
+/// value type. The generator reads its choices from [`Unstructured`] data,
+/// so the same bytes always give the same graph and fuzzers can shrink
+/// failing inputs.
+///
+/// # Example
+///
+/// ```
+/// use graphy::analysis::DataResolver;
+/// use graphy::testing::{canonical_registry, GraphGenerator};
+///
+/// let registry = canonical_registry();
+/// let generator = GraphGenerator::new(&registry).with_max_nodes(16);
+/// for seed in 0..20 {
+///     let graph = generator.generate_seeded(seed).unwrap();
+///     assert!(DataResolver::build(&graph, &registry).is_ok());
+/// }
+/// ```
+pub struct GraphGenerator<'p, P> {
+    provider: &'p P,
+
+    /// Node types to pick from, sorted by name so choices are stable
+    node_types: Vec<&'p NodeMetadata>,
+
+    max_nodes: usize,
+    acyclic_data: bool,
+}
+
+impl<'p, P: NodeMetadataProvider> GraphGenerator<'p, P> {
+    /// Creates a generator using every node type of a provider, with up to
+    /// [`DEFAULT_MAX_NODES`] nodes and acyclic data flow.
+    pub fn new(provider: &'p P) -> Self {
+        let mut node_types: Vec<&NodeMetadata> = provider
+            .get_all_nodes()
+            .into_iter()
+            .filter(|metadata| metadata.switch_value().is_none())
+            .collect();
+        node_types.sort_by(|a, b| a.name.cmp(&b.name));
+        Self {
+            provider,
+            node_types,
+            max_nodes: DEFAULT_MAX_NODES,
+            acyclic_data: true,
+        }
+    }
+
+    /// Sets the most nodes a graph can have.
+    #[must_use]
+    pub fn with_max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = max_nodes;
+        self
+    }
+
+    /// Sets whether data must flow from earlier nodes to later ones.
+    ///
+    /// With `false`, inputs can also read later nodes, so generated graphs
+    /// may contain data cycles that analysis must reject.
+    #[must_use]
+    pub fn with_acyclic_data(mut self, acyclic_data: bool) -> Self {
+        self.acyclic_data = acyclic_data;
+        self
+    }
+
+    /// Returns the provider the node types come from.
+    #[inline]
+    pub fn provider(&self) -> &'p P {
+        self.provider
+    }
+
+    /// Generates a graph from unstructured data.
+    ///
+    /// Running out of data never fails: the remaining choices take their
+    /// defaults, so short inputs give small graphs.
+    ///
+    /// # Errors
+    ///
+    /// Only fails if the data itself is rejected by [`Unstructured`];
+    /// currently it never does.
+    pub fn generate(&self, u: &mut Unstructured<'_>) -> arbitrary::Result<GraphDescription> {
+        let mut graph = GraphDescription::new("arbitrary");
+        if self.node_types.is_empty() {
+            return Ok(graph);
+        }
+
+        let count = u.int_in_range(0..=self.max_nodes)?;
+        let mut ids = Vec::with_capacity(count);
+        for index in 0..count {
+            let metadata = *u.choose(&self.node_types)?;
+            let id = format!("{}_{}", metadata.name, index);
+            let position = Position::new((index % 8) as f64 * 200.0, (index / 8) as f64 * 150.0);
+            graph.add_node(NodeInstance::from_metadata(id.clone(), metadata, position));
+            ids.push(id);
+        }
+
+        for (target_index, target) in ids.iter().enumerate() {
+            let inputs: Vec<(String, DataType)> = graph.nodes[target]
+                .inputs
+                .iter()
+                .filter(|input| !input.pin.data_type.is_execution())
+                .map(|input| (input.id.clone(), input.pin.data_type.clone()))
+                .collect();
+            for (pin, data_type) in inputs {
+                let sources: Vec<(&String, &str)> = ids
+                    .iter()
+                    .enumerate()
+                    .filter(|&(index, _)| index != target_index && (!self.acyclic_data || index < target_index))
+                    .flat_map(|(_, source)| {
+                        graph.nodes[source]
+                            .outputs
+                            .iter()
+                            .filter(|output| output.pin.data_type == data_type)
+                            .map(move |output| (source, output.id.as_str()))
+                    })
+                    .collect();
+
+                if !sources.is_empty() && u.ratio(2, 3)? {
+                    let (source, source_pin) = *u.choose(&sources)?;
+                    let connection = Connection::data(source.clone(), source_pin, target.clone(), pin);
+                    graph.add_connection(connection);
+                } else if u.arbitrary::<bool>()? {
+                    if let Some(value) = arbitrary_property(&data_type, u)? {
+                        graph.nodes.get_mut(target).expect("node added above").set_property(&pin, value);
+                    }
+                }
+            }
+        }
+
+        for (source_index, source) in ids.iter().enumerate() {
+            let exec_outputs: Vec<String> = graph.nodes[source]
+                .outputs
+                .iter()
+                .filter(|output| output.pin.data_type.is_execution())
+                .map(|output| output.id.clone())
+                .collect();
+            let targets: Vec<&String> = ids[source_index + 1..]
+                .iter()
+                .filter(|target| graph.nodes[*target].inputs.iter().any(|input| input.id == EXEC_INPUT_PIN))
+                .collect();
+            for pin in exec_outputs {
+                if !targets.is_empty() && u.ratio(3, 4)? {
+                    let target = *u.choose(&targets)?;
+                    graph.add_connection(Connection::execution(source.clone(), pin, target.clone(), EXEC_INPUT_PIN));
+                }
+            }
+        }
+
+        Ok(graph)
+    }
+
+    /// Generates a graph from a seed, for property tests without a fuzzer.
+    ///
+    /// The data comes from a small deterministic pseudo-random generator, so
+    /// a seed always gives the same graph.
+    ///
+    /// # Errors
+    ///
+    /// See [`generate`](Self::generate).
+    pub fn generate_seeded(&self, seed: u64) -> arbitrary::Result<GraphDescription> {
+        // SplitMix64
+        let mut state = seed;
+        let mut bytes = Vec::with_capacity(SEEDED_BYTES);
+        while bytes.len() < SEEDED_BYTES {
+            state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            bytes.extend_from_slice(&(z ^ (z >> 31)).to_le_bytes());
+        }
+        self.generate(&mut Unstructured::new(&bytes))
+    }
+}
+
+/// Generates graphs of the [canonical node library](canonical_registry)
+/// with [`GraphGenerator`]'s defaults: up to [`DEFAULT_MAX_NODES`] nodes and
+/// acyclic data flow.
+///
+/// ```ignore
+/// // fuzz/fuzz_targets/analysis.rs
+/// libfuzzer_sys::fuzz_target!(|graph: graphy::GraphDescription| {
+///     let _ = graphy::analysis::DataResolver::build(&graph, &graphy::testing::canonical_registry());
+/// });
+/// ```
+impl<'a> Arbitrary<'a> for GraphDescription {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        GraphGenerator::new(&canonical_registry()).generate(u)
+    }
+}
+
+/// Returns a constant for an input of a type with a literal, `None` otherwise
+fn arbitrary_property(data_type: &DataType, u: &mut Unstructured<'_>) -> arbitrary::Result<Option<PropertyValue>> {
+    let value = match data_type.type_string().unwrap_or_default() {
+        "i8" | "i16" | "i32" | "i64" | "isize" | "u8" | "u16" | "u32" | "u64" | "usize" => {
+            PropertyValue::Integer(u.int_in_range(0..=100)?)
+        }
+        "f32" | "f64" => PropertyValue::Number(f64::from(u.int_in_range(-100i32..=100)?) / 4.0),
+        "bool" => PropertyValue::Boolean(u.arbitrary()?),
+        "String" | "&str" => PropertyValue::String(u.arbitrary()?),
+        _ => return Ok(None),
+    };
+    Ok(Some(value))
+}
@@ -0,0 +1,221 @@
+//! Golden file comparison.
+
+use super::canonical_graphs;
+use crate::core::NodeMetadataProvider;
+use crate::generation::{compile, CodeGenerator};
+use crate::GraphyError;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+/// Environment variable that makes golden checks write the actual output
+/// instead of comparing it, when set to anything but `0` or nothing.
+pub const UPDATE_GOLDEN_ENV: &str = "GRAPHY_UPDATE_GOLDEN";
+
+/// Unchanged lines shown around every change in a diff
+const DIFF_CONTEXT: usize = 2;
+
+/// Whether golden checks compare or update the golden files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GoldenMode {
+    /// Fail if the output differs from the golden file
+    #[default]
+    Compare,
+
+    /// Write the output to the golden file
+    Update,
+}
+
+impl GoldenMode {
+    /// Returns [`Update`](Self::Update) if [`UPDATE_GOLDEN_ENV`] is set,
+    /// [`Compare`](Self::Compare) otherwise.
+    pub fn from_env() -> Self {
+        match std::env::var(UPDATE_GOLDEN_ENV) {
+            Ok(value) if !value.is_empty() && value != "0" => GoldenMode::Update,
+            _ => GoldenMode::Compare,
+        }
+    }
+}
+
+/// Checks output against a golden file, or updates the file.
+///
+/// Line endings are normalized, so golden files checked out with `\r\n`
+/// still match. In [`GoldenMode::Update`], the file and its directory are
+/// created if needed, and the file is only written if it differs.
+///
+/// # Errors
+///
+/// In [`GoldenMode::Compare`], returns [`GraphyError::Custom`] with a diff
+/// if the output differs, and [`GraphyError::Io`] if the file is missing or
+/// can't be read. In [`GoldenMode::Update`], returns [`GraphyError::Io`] if
+/// the file can't be written.
+pub fn check_golden(path: impl AsRef<Path>, actual: &str, mode: GoldenMode) -> Result<(), GraphyError> {
+    let path = path.as_ref();
+    let io_error = |e: std::io::Error| GraphyError::Io(format!("{}: {}", path.display(), e));
+    let expected = match fs::read_to_string(path) {
+        Ok(expected) => Some(expected.replace("\r\n", "\n")),
+        Err(e) if e.kind() == ErrorKind::NotFound => None,
+        Err(e) => return Err(io_error(e)),
+    };
+    let actual = actual.replace("\r\n", "\n");
+    if expected.as_deref() == Some(actual.as_str()) {
+        return Ok(());
+    }
+
+    match (mode, expected) {
+        (GoldenMode::Update, _) => {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(io_error)?;
+            }
+            tracing::debug!("[GOLDEN] Updating {}", path.display());
+            fs::write(path, actual).map_err(io_error)
+        }
+        (GoldenMode::Compare, None) => Err(GraphyError::Io(format!(
+            "Golden file {} doesn't exist; run with {}=1 to create it",
+            path.display(),
+            UPDATE_GOLDEN_ENV
+        ))),
+        (GoldenMode::Compare, Some(expected)) => {
+            let mut diff = diff_lines(&expected, &actual);
+            if diff.is_empty() {
+                diff = "(only the final newline differs)\n".to_string();
+            }
+            Err(GraphyError::Custom(format!(
+                "Output differs from golden file {} (- golden, + actual):\n{}Run with {}=1 to update it",
+                path.display(),
+                diff,
+                UPDATE_GOLDEN_ENV
+            )))
+        }
+    }
+}
+
+/// Asserts that output matches a golden file, updating it instead if
+/// [`UPDATE_GOLDEN_ENV`] is set.
+///
+/// # Panics
+///
+/// Panics with a diff if the output differs, or if the file can't be read
+/// or written. See [`check_golden`].
+#[track_caller]
+pub fn assert_golden(path: impl AsRef<Path>, actual: &str) {
+    if let Err(error) = check_golden(path, actual, GoldenMode::from_env()) {
+        panic!("{}", error);
+    }
+}
+
+/// Compiles every [canonical graph](canonical_graphs) and checks each
+/// output against `<dir>/<graph name>.<extension>`.
+///
+/// The provider must know the [canonical node types](super::canonical_registry);
+/// pass the canonical registry, or wrap it to add sources the generator needs.
+/// Every graph is checked, even after a failure.
+///
+/// # Errors
+///
+/// Returns [`GraphyError::Custom`] listing every graph that failed to
+/// compile or to match its golden file.
+pub fn check_canonical_goldens<P, G>(
+    dir: impl AsRef<Path>,
+    extension: &str,
+    metadata_provider: &P,
+    generator: &mut G,
+    mode: GoldenMode,
+) -> Result<(), GraphyError>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    let dir = dir.as_ref();
+    let failures: Vec<String> = canonical_graphs()
+        .iter()
+        .filter_map(|graph| {
+            let name = &graph.metadata.name;
+            let checked = compile(graph, metadata_provider, generator)
+                .and_then(|code| check_golden(dir.join(format!("{}.{}", name, extension)), &code, mode));
+            checked.err().map(|error| format!("{}: {}", name, error))
+        })
+        .collect();
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(GraphyError::Custom(failures.join("\n\n")))
+    }
+}
+
+/// Asserts that every canonical graph compiles to its golden file,
+/// updating them instead if [`UPDATE_GOLDEN_ENV`] is set.
+///
+/// # Panics
+///
+/// Panics listing every failing graph. See [`check_canonical_goldens`].
+#[track_caller]
+pub fn assert_canonical_goldens<P, G>(dir: impl AsRef<Path>, extension: &str, metadata_provider: &P, generator: &mut G)
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+{
+    if let Err(error) = check_canonical_goldens(dir, extension, metadata_provider, generator, GoldenMode::from_env()) {
+        panic!("{}", error);
+    }
+}
+
+/// Returns a line diff of two texts: changed lines prefixed with `-`
+/// (expected) or `+` (actual), with a little unchanged context prefixed with
+/// a space. Hunks start with a `@@ line N @@` header, counting lines of the
+/// expected text from 1.
+///
+/// Returns an empty string if the texts are equal.
+pub fn diff_lines(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+
+    // Longest common subsequence lengths of the suffixes
+    let mut common = vec![vec![0usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            common[i][j] = if expected[i] == actual[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    // (tag, expected line number, text)
+    let mut lines: Vec<(char, usize, &str)> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            lines.push((' ', i + 1, expected[i]));
+            i += 1;
+            j += 1;
+        } else if i < expected.len() && (j == actual.len() || common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(('-', i + 1, expected[i]));
+            i += 1;
+        } else {
+            lines.push(('+', i + 1, actual[j]));
+            j += 1;
+        }
+    }
+
+    // Changed lines with their context, merged where they touch
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for index in (0..lines.len()).filter(|&index| lines[index].0 != ' ') {
+        let (start, end) = (index.saturating_sub(DIFF_CONTEXT), (index + DIFF_CONTEXT + 1).min(lines.len()));
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut diff = String::new();
+    for (start, end) in hunks {
+        diff.push_str(&format!("@@ line {} @@\n", lines[start].1));
+        for &(tag, _, text) in &lines[start..end] {
+            diff.push_str(&format!("{}{}\n", tag, text));
+        }
+    }
+    diff
+}
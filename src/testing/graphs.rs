@@ -0,0 +1,197 @@
+//! Canonical graphs and their node library.
+
+use crate::core::{
+    Connection, GraphDescription, NodeMetadata, NodeRegistry, NodeTypes, ParamInfo, Position, PropertyValue,
+    EXEC_INPUT_PIN, RESULT_PIN,
+};
+
+/// Returns the node library the canonical graphs use.
+///
+/// | Node       | Kind         | Params                   | Returns | Exec outputs          |
+/// |------------|--------------|--------------------------|---------|-----------------------|
+/// | `on_start` | event        |                          |         | `exec`                |
+/// | `add`      | pure         | `a: i64`, `b: i64`       | `i64`   |                       |
+/// | `multiply` | pure         | `a: i64`, `b: i64`       | `i64`   |                       |
+/// | `print`    | function     | `value: i64`             |         | `then`                |
+/// | `branch`   | control flow | `condition: bool`        |         | `True`, `False`       |
+/// | `for_loop` | control flow | `start: i64`, `end: i64` |         | `body`, `completed`   |
+///
+/// No node has a source. Backends that need one (e.g. templates for control
+/// flow) can add it with an [`OverlayProvider`](crate::core::OverlayProvider).
+pub fn canonical_registry() -> NodeRegistry {
+    let numbers = || vec![ParamInfo::new("a", "i64"), ParamInfo::new("b", "i64")];
+    let mut registry = NodeRegistry::new();
+    registry.register(NodeMetadata::new("on_start", NodeTypes::event, "Events").with_exec_outputs(vec!["exec".into()]));
+    registry.register(
+        NodeMetadata::new("add", NodeTypes::pure, "Math")
+            .with_params(numbers())
+            .with_return_type("i64"),
+    );
+    registry.register(
+        NodeMetadata::new("multiply", NodeTypes::pure, "Math")
+            .with_params(numbers())
+            .with_return_type("i64"),
+    );
+    registry.register(
+        NodeMetadata::new("print", NodeTypes::fn_, "IO")
+            .with_params(vec![ParamInfo::new("value", "i64")])
+            .with_exec_outputs(vec!["then".into()]),
+    );
+    registry.register(
+        NodeMetadata::new("branch", NodeTypes::control_flow, "Flow")
+            .with_params(vec![ParamInfo::new("condition", "bool")])
+            .with_exec_outputs(vec!["True".into(), "False".into()]),
+    );
+    registry.register(
+        NodeMetadata::new("for_loop", NodeTypes::control_flow, "Flow")
+            .with_params(vec![ParamInfo::new("start", "i64"), ParamInfo::new("end", "i64")])
+            .with_exec_outputs(vec!["body".into(), "completed".into()]),
+    );
+    registry
+}
+
+/// `start -> print(node_{n-1})`, where `node_0 = 0 + 1` and every other
+/// `node_i = node_{i-1} + 1`.
+///
+/// Named `linear_chain`. Exercises pure expressions nested `n` deep.
+pub fn linear_chain_graph(n: usize) -> GraphDescription {
+    let mut graph = CanonicalBuilder::new("linear_chain");
+    for i in 0..n {
+        let id = format!("node_{}", i);
+        graph.node(&id, "add", i + 1);
+        graph.constant(&id, "b", 1.0);
+        match i {
+            0 => graph.constant(&id, "a", 0.0),
+            _ => graph.data(&format!("node_{}", i - 1), &id, "a"),
+        }
+    }
+    graph.node("start", "on_start", 0);
+    graph.node("print_1", "print", n + 1);
+    graph.exec("start", "exec", "print_1");
+    if n > 0 {
+        graph.data(&format!("node_{}", n - 1), "print_1", "value");
+    }
+    graph.finish()
+}
+
+/// `start -> print(node_d)`, where `node_a = 1 + 2`,
+/// `node_b = node_a * 2`, `node_c = node_a * 3`, and
+/// `node_d = node_b + node_c`.
+///
+/// Named `diamond`. Exercises a pure node read by two others.
+pub fn diamond_graph() -> GraphDescription {
+    let mut graph = CanonicalBuilder::new("diamond");
+    graph.node("node_a", "add", 1);
+    graph.constant("node_a", "a", 1.0);
+    graph.constant("node_a", "b", 2.0);
+    for (id, factor) in [("node_b", 2.0), ("node_c", 3.0)] {
+        graph.node(id, "multiply", 2);
+        graph.data("node_a", id, "a");
+        graph.constant(id, "b", factor);
+    }
+    graph.node("node_d", "add", 3);
+    graph.data("node_b", "node_d", "a");
+    graph.data("node_c", "node_d", "b");
+
+    graph.node("start", "on_start", 0);
+    graph.node("print_1", "print", 4);
+    graph.exec("start", "exec", "print_1");
+    graph.data("node_d", "print_1", "value");
+    graph.finish()
+}
+
+/// `start -> branch(true)`, printing 1 on `True` and 0 on `False`.
+///
+/// Named `branch`. Exercises control flow with two exec outputs.
+pub fn branch_graph() -> GraphDescription {
+    let mut graph = CanonicalBuilder::new("branch");
+    graph.node("start", "on_start", 0);
+    graph.node("branch_1", "branch", 1);
+    graph.set("branch_1", "condition", PropertyValue::Boolean(true));
+    graph.exec("start", "exec", "branch_1");
+    for (id, pin, value) in [("print_true", "True", 1.0), ("print_false", "False", 0.0)] {
+        graph.node(id, "print", 2);
+        graph.constant(id, "value", value);
+        graph.exec("branch_1", pin, id);
+    }
+    graph.finish()
+}
+
+/// `start -> for_loop(0, 3)`, printing `2 * 21` in the body and 3 when
+/// completed.
+///
+/// Named `loop`. Exercises a control flow node whose outputs run several
+/// times, reading a pure node from the body.
+pub fn loop_graph() -> GraphDescription {
+    let mut graph = CanonicalBuilder::new("loop");
+    graph.node("start", "on_start", 0);
+    graph.node("loop_1", "for_loop", 1);
+    graph.constant("loop_1", "start", 0.0);
+    graph.constant("loop_1", "end", 3.0);
+    graph.exec("start", "exec", "loop_1");
+
+    graph.node("product", "multiply", 1);
+    graph.constant("product", "a", 2.0);
+    graph.constant("product", "b", 21.0);
+    graph.node("print_body", "print", 2);
+    graph.exec("loop_1", "body", "print_body");
+    graph.data("product", "print_body", "value");
+
+    graph.node("print_done", "print", 2);
+    graph.constant("print_done", "value", 3.0);
+    graph.exec("loop_1", "completed", "print_done");
+    graph.finish()
+}
+
+/// Returns every canonical graph: a linear chain of 4 nodes, the diamond,
+/// the branch, and the loop, in that order.
+///
+/// Graph names are unique, so they can name golden files.
+pub fn canonical_graphs() -> Vec<GraphDescription> {
+    vec![linear_chain_graph(4), diamond_graph(), branch_graph(), loop_graph()]
+}
+
+/// Builds a canonical graph from the canonical registry
+struct CanonicalBuilder {
+    graph: GraphDescription,
+    registry: NodeRegistry,
+}
+
+impl CanonicalBuilder {
+    fn new(name: &str) -> Self {
+        Self {
+            graph: GraphDescription::new(name),
+            registry: canonical_registry(),
+        }
+    }
+
+    /// Adds a node in a column, so the graph looks sensible in an editor
+    fn node(&mut self, id: &str, node_type: &str, column: usize) {
+        let row = self.graph.nodes.values().filter(|node| node.position.x == column as f64 * 200.0).count();
+        let node = self
+            .graph
+            .add_node_of_type(node_type, id, &self.registry)
+            .expect("canonical node types are registered");
+        node.position = Position::new(column as f64 * 200.0, row as f64 * 150.0);
+    }
+
+    fn set(&mut self, id: &str, pin: &str, value: PropertyValue) {
+        self.graph.nodes.get_mut(id).expect("node added before").set_property(pin, value);
+    }
+
+    fn constant(&mut self, id: &str, pin: &str, value: f64) {
+        self.set(id, pin, PropertyValue::Number(value));
+    }
+
+    fn data(&mut self, source: &str, target: &str, pin: &str) {
+        self.graph.add_connection(Connection::data(source, RESULT_PIN, target, pin));
+    }
+
+    fn exec(&mut self, source: &str, pin: &str, target: &str) {
+        self.graph.add_connection(Connection::execution(source, pin, target, EXEC_INPUT_PIN));
+    }
+
+    fn finish(self) -> GraphDescription {
+        self.graph
+    }
+}
@@ -0,0 +1,47 @@
+//! # Testing Backends
+//!
+//! Golden tests for [`CodeGenerator`](crate::generation::CodeGenerator)
+//! implementations, usable from downstream crates.
+//!
+//! The canonical graphs ([linear chain](linear_chain_graph),
+//! [diamond](diamond_graph), [branch](branch_graph), and [loop](loop_graph))
+//! cover pure expressions, shared results, and control flow, using the node
+//! library of [`canonical_registry`]. Compiling them with a backend and
+//! comparing the code with golden files checked into the repository gives a
+//! conformance suite that catches every change in the generated code.
+//!
+//! Mismatches fail with a line diff. Set [`UPDATE_GOLDEN_ENV`]
+//! (`GRAPHY_UPDATE_GOLDEN=1 cargo test`) to write the new output to the
+//! golden files instead, then review the changes with version control.
+//!
+//! With the `arbitrary` feature, [`GraphGenerator`] generates random
+//! well-formed graphs for fuzzing and property tests, and
+//! `GraphDescription` implements [`Arbitrary`] for fuzz targets.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use graphy::testing::{assert_canonical_goldens, assert_golden, canonical_registry, diamond_graph};
+//!
+//! #[test]
+//! fn conformance() {
+//!     let registry = canonical_registry();
+//!     assert_canonical_goldens("tests/golden", "rs", &registry, &mut MyRustGenerator::default());
+//! }
+//!
+//! #[test]
+//! fn diamond_with_options() {
+//!     let code = compile_with_options(&diamond_graph(), &canonical_registry(), &mut MyRustGenerator::default(), &options)?.code;
+//!     assert_golden("tests/golden/diamond_optimized.rs", &code);
+//! }
+//! ```
+
+#[cfg(feature = "arbitrary")]
+pub mod generator;
+pub mod golden;
+pub mod graphs;
+
+#[cfg(feature = "arbitrary")]
+pub use generator::*;
+pub use golden::*;
+pub use graphs::*;
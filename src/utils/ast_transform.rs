@@ -7,15 +7,100 @@
 //! - Replacing `exec_output!()` macro calls with actual code
 //! - Substituting parameter values in function bodies
 //! - Inlining control flow nodes
-
+//!
+//! # Replacements
+//!
+//! Replacement code may hold any number of statements, typically the whole
+//! chain of nodes connected to an execution output. An `exec_output!`
+//! statement is replaced by all of them, spliced into the enclosing block;
+//! in expression position the code becomes a block expression (or stays a
+//! single expression, if it is one). Replacement code is inserted as is,
+//! without looking for `exec_output!` calls inside it.
+//!
+//! # Payloads
+//!
+//! An `exec_output!` call can pass values to the connected flow after its
+//! label, like the index of a loop body:
+//!
+//! ```ignore
+//! fn for_loop(start: i64, end: i64) {
+//!     for i in start..end {
+//!         exec_output!("body", i);
+//!     }
+//!     exec_output!("completed");
+//! }
+//! ```
+//!
+//! [`extract_exec_outputs`] returns each call's payload expressions, and an
+//! [`ExecReplacement`] with bindings wraps its code in a block that binds
+//! the payloads to the given local names first:
+//!
+//! ```ignore
+//! let mut replacements = HashMap::new();
+//! replacements.insert("body".to_string(), ExecReplacement::new("print(index);").with_bindings(vec!["index".into()]));
+//! // for i in start..end { { let index = i; print(index); } }
+//! let code = inline_control_flow_with_payloads(source, replacements, HashMap::new())?;
+//! ```
+
+use super::logging;
 use crate::GraphyError;
 use std::collections::HashMap;
 use syn::{
+    punctuated::Punctuated,
     visit::{self, Visit},
     visit_mut::{self, VisitMut},
-    Block, Expr, ExprMacro, ItemFn, Stmt,
+    Block, Expr, ExprLit, ExprMacro, ItemFn, Lit, Macro, Stmt, Token,
 };
 
+/// An `exec_output!` call found in a function
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecOutputCall {
+    /// Label of the execution output
+    pub label: String,
+
+    /// Source of each payload expression passed after the label (usually a
+    /// variable name)
+    pub payloads: Vec<String>,
+}
+
+/// Code replacing an `exec_output!` call
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecReplacement {
+    /// Code of the connected flow
+    pub code: String,
+
+    /// Local names the call's payloads are bound to, in argument order
+    pub bindings: Vec<String>,
+}
+
+impl ExecReplacement {
+    /// Creates a replacement that doesn't bind any payload.
+    #[inline]
+    pub fn new(code: impl Into<String>) -> Self {
+        Self {
+            code: code.into(),
+            bindings: Vec::new(),
+        }
+    }
+
+    /// Sets the local names the payloads are bound to.
+    ///
+    /// A call may pass more payloads than there are bindings; the extra ones
+    /// are left unbound.
+    #[inline]
+    #[must_use]
+    pub fn with_bindings(mut self, bindings: Vec<String>) -> Self {
+        self.bindings = bindings;
+        self
+    }
+}
+
+impl From<String> for ExecReplacement {
+    fn from(code: String) -> Self {
+        Self::new(code)
+    }
+}
+
 /// Inline a control flow function with substitutions
 ///
 /// This function:
@@ -34,9 +119,37 @@ pub fn inline_control_flow_function(
     exec_replacements: HashMap<String, String>,
     param_substitutions: HashMap<String, String>,
 ) -> Result<String, GraphyError> {
-    tracing::info!("[AST] Inlining control flow function");
-    tracing::info!("[AST] Exec replacements: {:?}", exec_replacements);
-    tracing::info!("[AST] Param substitutions: {:?}", param_substitutions);
+    let exec_replacements = exec_replacements
+        .into_iter()
+        .map(|(label, code)| (label, ExecReplacement::new(code)))
+        .collect();
+    inline_control_flow_with_payloads(function_source, exec_replacements, param_substitutions)
+}
+
+/// Inline a control flow function, binding `exec_output!` payloads
+///
+/// Like [`inline_control_flow_function`], but each replacement can bind the
+/// payloads of the call it replaces to locals (see the
+/// [module docs](self#payloads)).
+///
+/// # Errors
+///
+/// Fails if the source doesn't parse, or if a replacement has more bindings
+/// than its call passes payloads.
+pub fn inline_control_flow_with_payloads(
+    function_source: &str,
+    exec_replacements: HashMap<String, ExecReplacement>,
+    param_substitutions: HashMap<String, String>,
+) -> Result<String, GraphyError> {
+    tracing::debug!(
+        "[AST] Inlining control flow function ({} exec replacements, {} param substitutions)",
+        exec_replacements.len(),
+        param_substitutions.len()
+    );
+    if logging::is_verbose() {
+        tracing::trace!("[AST] Exec replacements: {:?}", exec_replacements);
+        tracing::trace!("[AST] Param substitutions: {:?}", param_substitutions);
+    }
 
     // Parse the function
     let item_fn = parse_function(function_source)?;
@@ -77,90 +190,182 @@ fn extract_function_body(code: &str) -> Result<String, GraphyError> {
     ))
 }
 
+/// Parse the arguments of an `exec_output!` call: its label, then payloads
+fn parse_exec_output_args(mac: &Macro) -> Option<(String, Vec<Expr>)> {
+    let args = mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated).ok()?;
+    let mut args = args.into_iter();
+    let Some(Expr::Lit(ExprLit { lit: Lit::Str(label), .. })) = args.next() else {
+        return None;
+    };
+    Some((label.value(), args.collect()))
+}
+
 /// Replace `exec_output!()` calls with actual code
 struct ExecOutputReplacer {
-    replacements: HashMap<String, String>,
+    replacements: HashMap<String, ExecReplacement>,
+
+    /// First replacement that couldn't be applied
+    error: Option<GraphyError>,
 }
 
 impl ExecOutputReplacer {
-    pub fn new(replacements: HashMap<String, String>) -> Self {
-        Self { replacements }
+    pub fn new(replacements: HashMap<String, ExecReplacement>) -> Self {
+        Self {
+            replacements,
+            error: None,
+        }
     }
 
     pub fn replace_in_function(mut self, func: ItemFn) -> Result<ItemFn, GraphyError> {
         let mut func = func;
         self.visit_item_fn_mut(&mut func);
-        Ok(func)
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(func),
+        }
+    }
+
+    /// Find the replacement for an `exec_output!` call, with its payloads
+    fn replacement_for(&self, mac: &Macro) -> Option<(String, &ExecReplacement, Vec<Expr>)> {
+        if !mac.path.is_ident("exec_output") {
+            return None;
+        }
+        let (label, payloads) = parse_exec_output_args(mac)?;
+        let replacement = self.replacements.get(&label)?;
+        Some((label, replacement, payloads))
+    }
+
+    /// Statements replacing an `exec_output!` statement
+    ///
+    /// Returns `None` if the statement isn't a replaced `exec_output!` call.
+    /// Unless it binds payloads (which get their own block), the replacement
+    /// code is spliced into the enclosing block statement by statement.
+    fn replace_stmt(&mut self, stmt: &Stmt) -> Option<Vec<Stmt>> {
+        let Stmt::Macro(stmt_macro) = stmt else {
+            return None;
+        };
+        let (label_value, replacement, payloads) = self.replacement_for(&stmt_macro.mac)?;
+        let replacement = replacement.clone();
+        if logging::is_verbose() {
+            tracing::trace!(
+                "[AST] Replacing exec_output!(\"{}\") with: {}",
+                label_value,
+                replacement.code
+            );
+        }
+
+        if let Some(block) = self.binding_block(&label_value, &replacement, &payloads) {
+            let block = Expr::Block(syn::ExprBlock {
+                attrs: vec![],
+                label: None,
+                block,
+            });
+            return Some(vec![Stmt::Expr(block, None)]);
+        }
+        if !replacement.bindings.is_empty() {
+            return None;
+        }
+
+        match syn::parse_str::<Block>(&format!("{{{}}}", replacement.code)) {
+            Ok(block) => Some(block.stmts),
+            Err(e) => {
+                self.error.get_or_insert(GraphyError::AstParsing(format!(
+                    "Failed to parse replacement of exec_output!(\"{}\"): {}",
+                    label_value, e
+                )));
+                None
+            }
+        }
+    }
+
+    /// Wrap the replacement code in a block binding the payloads
+    ///
+    /// Returns `None` if the replacement doesn't bind anything.
+    fn binding_block(
+        &mut self,
+        label: &str,
+        replacement: &ExecReplacement,
+        payloads: &[Expr],
+    ) -> Option<Block> {
+        if replacement.bindings.is_empty() {
+            return None;
+        }
+        if replacement.bindings.len() > payloads.len() {
+            self.error.get_or_insert(GraphyError::AstParsing(format!(
+                "exec_output!(\"{}\") passes {} payload(s), but the replacement binds {}",
+                label,
+                payloads.len(),
+                replacement.bindings.len()
+            )));
+            return None;
+        }
+
+        let lets: String = replacement
+            .bindings
+            .iter()
+            .zip(payloads)
+            .map(|(name, payload)| format!("let {} = {};", name, quote::quote! { #payload }))
+            .collect();
+        match syn::parse_str::<Block>(&format!("{{{} {}}}", lets, replacement.code)) {
+            Ok(block) => Some(block),
+            Err(e) => {
+                self.error.get_or_insert(GraphyError::AstParsing(format!(
+                    "Failed to parse replacement of exec_output!(\"{}\"): {}",
+                    label, e
+                )));
+                None
+            }
+        }
     }
 }
 
 impl VisitMut for ExecOutputReplacer {
-    fn visit_stmt_mut(&mut self, stmt: &mut Stmt) {
-        match stmt {
-            Stmt::Expr(expr, _) => {
-                self.visit_expr_mut(expr);
-            }
-            Stmt::Macro(stmt_macro) => {
-                if stmt_macro.mac.path.is_ident("exec_output") {
-                    if let Ok(label) = syn::parse2::<syn::LitStr>(stmt_macro.mac.tokens.clone()) {
-                        let label_value = label.value();
-
-                        if let Some(replacement_code) = self.replacements.get(&label_value) {
-                            tracing::info!(
-                                "[AST] Replacing exec_output!(\"{}\") with: {}",
-                                label_value,
-                                replacement_code
-                            );
-
-                            // Parse replacement code and substitute
-                            if let Ok(parsed_stmts) =
-                                syn::parse_str::<syn::File>(&format!("fn dummy() {{{}}}", replacement_code))
-                            {
-                                if let Some(syn::Item::Fn(item_fn)) = parsed_stmts.items.first() {
-                                    if let Some(first_stmt) = item_fn.block.stmts.first() {
-                                        *stmt = first_stmt.clone();
-                                    }
-                                }
-                            }
-                        }
-                    }
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        let stmts = std::mem::take(&mut block.stmts);
+        for mut stmt in stmts {
+            match self.replace_stmt(&stmt) {
+                Some(replacement) => block.stmts.extend(replacement),
+                None => {
+                    self.visit_stmt_mut(&mut stmt);
+                    block.stmts.push(stmt);
                 }
             }
-            _ => {}
         }
-        visit_mut::visit_stmt_mut(self, stmt);
     }
 
     fn visit_expr_mut(&mut self, expr: &mut Expr) {
         if let Expr::Macro(ExprMacro { mac, .. }) = expr {
-            if mac.path.is_ident("exec_output") {
-                if let Ok(label) = syn::parse2::<syn::LitStr>(mac.tokens.clone()) {
-                    let label_value = label.value();
-
-                    if let Some(replacement_code) = self.replacements.get(&label_value) {
-                        tracing::info!(
-                            "[AST] Replacing exec_output!(\"{}\") expr with: {}",
-                            label_value,
-                            replacement_code
-                        );
-
-                        match syn::parse_str::<Expr>(replacement_code) {
-                            Ok(replacement_expr) => {
-                                *expr = replacement_expr;
-                                return;
-                            }
-                            Err(_) => {
-                                if let Ok(block) =
-                                    syn::parse_str::<Block>(&format!("{{{}}}", replacement_code))
-                                {
-                                    *expr = Expr::Block(syn::ExprBlock {
-                                        attrs: vec![],
-                                        label: None,
-                                        block,
-                                    });
-                                    return;
-                                }
-                            }
+            if let Some((label_value, replacement, payloads)) = self.replacement_for(mac) {
+                let replacement = replacement.clone();
+                if logging::is_verbose() {
+                    tracing::trace!(
+                        "[AST] Replacing exec_output!(\"{}\") expr with: {}",
+                        label_value,
+                        replacement.code
+                    );
+                }
+
+                if let Some(block) = self.binding_block(&label_value, &replacement, &payloads) {
+                    *expr = Expr::Block(syn::ExprBlock {
+                        attrs: vec![],
+                        label: None,
+                        block,
+                    });
+                    return;
+                }
+                match syn::parse_str::<Expr>(&replacement.code) {
+                    Ok(replacement_expr) => {
+                        *expr = replacement_expr;
+                        return;
+                    }
+                    Err(_) => {
+                        if let Ok(block) = syn::parse_str::<Block>(&format!("{{{}}}", replacement.code)) {
+                            *expr = Expr::Block(syn::ExprBlock {
+                                attrs: vec![],
+                                label: None,
+                                block,
+                            });
+                            return;
                         }
                     }
                 }
@@ -195,7 +400,9 @@ impl VisitMut for ParameterSubstitutor {
                 let ident_str = ident.to_string();
 
                 if let Some(replacement) = self.substitutions.get(&ident_str) {
-                    tracing::info!("[AST] Substituting {} with {}", ident_str, replacement);
+                    if logging::is_verbose() {
+                        tracing::trace!("[AST] Substituting {} with {}", ident_str, replacement);
+                    }
 
                     if let Ok(replacement_expr) = syn::parse_str::<Expr>(replacement) {
                         *expr = replacement_expr;
@@ -210,30 +417,52 @@ impl VisitMut for ParameterSubstitutor {
 
 /// Extract exec output labels from a function
 ///
-/// Parses the function and finds all `exec_output!("Label")` calls.
+/// Parses the function and finds all `exec_output!("Label")` calls
+/// (with or without payloads).
 pub fn extract_exec_output_labels(function_source: &str) -> Result<Vec<String>, GraphyError> {
+    Ok(extract_exec_outputs(function_source)?
+        .into_iter()
+        .map(|call| call.label)
+        .collect())
+}
+
+/// Extract exec output calls, with their payloads, from a function
+///
+/// Calls are listed in source order, one entry per call.
+pub fn extract_exec_outputs(function_source: &str) -> Result<Vec<ExecOutputCall>, GraphyError> {
     let item_fn = parse_function(function_source)?;
-    let mut extractor = ExecOutputLabelExtractor { labels: Vec::new() };
+    let mut extractor = ExecOutputLabelExtractor { calls: Vec::new() };
     extractor.visit_item_fn(&item_fn);
     
-    tracing::debug!("[AST] Extracted {} exec_output labels", extractor.labels.len());
+    tracing::debug!("[AST] Extracted {} exec_output labels", extractor.calls.len());
     
-    Ok(extractor.labels)
+    Ok(extractor.calls)
 }
 
 struct ExecOutputLabelExtractor {
-    labels: Vec<String>,
+    calls: Vec<ExecOutputCall>,
+}
+
+impl ExecOutputLabelExtractor {
+    fn record(&mut self, mac: &Macro) {
+        if !mac.path.is_ident("exec_output") {
+            return;
+        }
+        if let Some((label, payloads)) = parse_exec_output_args(mac) {
+            let payloads = payloads
+                .iter()
+                .map(|payload| quote::quote! { #payload }.to_string())
+                .collect();
+            self.calls.push(ExecOutputCall { label, payloads });
+        }
+    }
 }
 
 impl<'ast> Visit<'ast> for ExecOutputLabelExtractor {
     fn visit_stmt(&mut self, stmt: &'ast Stmt) {
         // Check for macro statements (exec_output! as a statement)
         if let Stmt::Macro(stmt_macro) = stmt {
-            if stmt_macro.mac.path.is_ident("exec_output") {
-                if let Ok(label) = syn::parse2::<syn::LitStr>(stmt_macro.mac.tokens.clone()) {
-                    self.labels.push(label.value());
-                }
-            }
+            self.record(&stmt_macro.mac);
         }
         
         // Continue visiting nested statements and expressions
@@ -243,11 +472,7 @@ impl<'ast> Visit<'ast> for ExecOutputLabelExtractor {
     fn visit_expr(&mut self, expr: &'ast Expr) {
         // Also check for macro expressions (exec_output! in expression position)
         if let Expr::Macro(ExprMacro { mac, .. }) = expr {
-            if mac.path.is_ident("exec_output") {
-                if let Ok(label) = syn::parse2::<syn::LitStr>(mac.tokens.clone()) {
-                    self.labels.push(label.value());
-                }
-            }
+            self.record(mac);
         }
         
         // Continue visiting nested expressions
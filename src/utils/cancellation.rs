@@ -0,0 +1,65 @@
+//! # Cancellation
+//!
+//! Cooperative cancellation of long-running analysis and code generation.
+//!
+//! A [`CancellationToken`] is a shared flag: clones observe the same state,
+//! so an editor can keep one clone and hand another to a background job.
+//! Once [`cancel`](CancellationToken::cancel) is called, cancellable
+//! operations return [`GraphyError::Cancelled`] at their next check, which
+//! happens at least once per node they process.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::utils::CancellationToken;
+//! use graphy::GraphyError;
+//!
+//! let token = CancellationToken::new();
+//! let job_token = token.clone();
+//! assert!(job_token.check().is_ok());
+//!
+//! token.cancel();
+//! assert!(matches!(job_token.check(), Err(GraphyError::Cancelled)));
+//! ```
+
+use crate::GraphyError;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Shared flag for cancelling work in progress.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Creates a token that isn't cancelled.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels every operation watching this token or one of its clones.
+    ///
+    /// Cancellation can't be undone; use a new token for the next job.
+    #[inline]
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Checks if the token has been cancelled.
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Fails with [`GraphyError::Cancelled`] if the token has been cancelled.
+    #[inline]
+    pub fn check(&self) -> Result<(), GraphyError> {
+        if self.is_cancelled() {
+            Err(GraphyError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! # Diagnostics
+//!
+//! Human-readable error reports pointing at the node that failed.
+//!
+//! Errors raised while generating a node carry it as context
+//! ([`GraphyError::with_node`] and [`GraphyError::with_pin`]), and most
+//! analysis errors name their node anyway. [`Diagnostics`] renders an error
+//! with that node's type and position and the connections around it, so the
+//! failing node can be found in a large graph without searching for its ID.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::utils::Diagnostics;
+//! use graphy::{Connection, GraphDescription, GraphyError, NodeInstance, Position};
+//!
+//! let mut graph = GraphDescription::new("demo");
+//! graph.add_node(NodeInstance::new("start", "on_start", Position::zero()));
+//! graph.add_node(NodeInstance::new("add_1", "add", Position::new(200.0, 0.0)));
+//! graph.add_connection(Connection::execution("start", "exec", "add_1", "exec_in"));
+//!
+//! let error = GraphyError::CodeGeneration("add is pure".into()).with_node("add_1");
+//! assert_eq!(
+//!     Diagnostics::new(&graph).render(&error),
+//!     "error: Code generation error: add is pure\n  \
+//!      --> add_1 (add) at (200, 0)\n  \
+//!      | in:  start.exec -> exec_in\n"
+//! );
+//! ```
+
+use crate::core::{Connection, GraphDescription};
+use crate::GraphyError;
+use std::fmt::Write;
+
+/// Renders errors with the context of the node they're about.
+#[derive(Debug, Clone, Copy)]
+pub struct Diagnostics<'g> {
+    graph: &'g GraphDescription,
+    max_connections: usize,
+}
+
+impl<'g> Diagnostics<'g> {
+    /// Connections listed per node by default
+    pub const DEFAULT_MAX_CONNECTIONS: usize = 8;
+
+    /// Creates a renderer looking up nodes in a graph.
+    ///
+    /// Pass the graph the error came from; for compile errors that's the
+    /// graph given to the compiler, since nodes of expanded sub-graphs keep
+    /// the IDs they're reported with.
+    #[inline]
+    pub fn new(graph: &'g GraphDescription) -> Self {
+        Self {
+            graph,
+            max_connections: Self::DEFAULT_MAX_CONNECTIONS,
+        }
+    }
+
+    /// Sets how many connections of the node are listed, the rest are
+    /// summarized.
+    #[inline]
+    #[must_use]
+    pub fn with_max_connections(mut self, max_connections: usize) -> Self {
+        self.max_connections = max_connections;
+        self
+    }
+
+    /// Renders an error.
+    ///
+    /// The first line is the error without its graph, node, and pin
+    /// contexts. If the error names a node, a `-->` line follows with the
+    /// node (and pin), its type, and its position, then one `|` line per
+    /// connection entering (`in:`) or leaving (`out:`) the node. Errors
+    /// inside a graph of a project end with an `= in graph` line.
+    pub fn render(&self, error: &GraphyError) -> String {
+        let mut out = format!("error: {}\n", error.root());
+        if let Some(node_id) = error.node() {
+            self.render_node(&mut out, node_id, error.pin());
+        }
+        if let GraphyError::InGraph { graph, .. } = error {
+            let _ = writeln!(out, "  = in graph '{}'", graph);
+        }
+        out
+    }
+
+    fn render_node(&self, out: &mut String, node_id: &str, pin: Option<&str>) {
+        let location = match pin {
+            Some(pin) => format!("{}.{}", node_id, pin),
+            None => node_id.to_string(),
+        };
+        let Some(node) = self.graph.get_node(node_id) else {
+            let _ = writeln!(out, "  --> {} (not in the graph)", location);
+            return;
+        };
+        let _ = writeln!(
+            out,
+            "  --> {} ({}) at ({}, {})",
+            location, node.node_type, node.position.x, node.position.y
+        );
+
+        let incoming = self.graph.connections_to_node(node_id).map(|c| ("in: ", incoming_edge(c)));
+        let outgoing = self.graph.connections_from(node_id).map(|c| ("out:", outgoing_edge(c)));
+        let mut edges = incoming.chain(outgoing);
+        for (direction, edge) in edges.by_ref().take(self.max_connections) {
+            let _ = writeln!(out, "  | {} {}", direction, edge);
+        }
+        let more = edges.count();
+        if more > 0 {
+            let _ = writeln!(out, "  | ... and {} more", more);
+        }
+    }
+}
+
+/// `source.pin -> target_pin`, for a connection entering the node
+fn incoming_edge(connection: &Connection) -> String {
+    format!(
+        "{}.{} -> {}",
+        connection.source_node, connection.source_pin, connection.target_pin
+    )
+}
+
+/// `source_pin -> target.pin`, for a connection leaving the node
+fn outgoing_edge(connection: &Connection) -> String {
+    format!(
+        "{} -> {}.{}",
+        connection.source_pin, connection.target_node, connection.target_pin
+    )
+}
@@ -0,0 +1,660 @@
+//! # Graph Diffing
+//!
+//! Structural differences between two versions of a graph.
+//!
+//! A [`GraphDiff`] records which nodes, connections, properties, comments,
+//! groups, and variable and external declarations were added, removed, or modified between an old and a new graph. Diffs are
+//! serializable, so editors can store compact edit logs, and invertible, so
+//! they can be used directly as undo/redo entries.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::{GraphDescription, NodeInstance, Position};
+//! use graphy::utils::diff::GraphDiff;
+//!
+//! let old = GraphDescription::new("graph");
+//! let mut new = old.clone();
+//! new.add_node(NodeInstance::new("add_1", "math.add", Position::zero()));
+//!
+//! let diff = GraphDiff::compute(&old, &new);
+//! assert_eq!(diff.added_nodes.len(), 1);
+//!
+//! // Replay the edit on the old graph
+//! let mut graph = old.clone();
+//! diff.apply(&mut graph).unwrap();
+//! assert!(graph.get_node("add_1").is_some());
+//!
+//! // Undo it again
+//! diff.invert().apply(&mut graph).unwrap();
+//! assert!(graph.nodes.is_empty());
+//! ```
+
+use crate::core::{
+    BuildCondition, Connection, ConnectionErrorReason, ExternalDecl, GraphComment, GraphDescription, GraphGroup, GraphParam, NodeInstance, PinInstance, Position, PropertyValue,
+    VariableDecl,
+};
+use crate::GraphyError;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+/// A change of a single value from `old` to `new`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange<T> {
+    /// Value before the change
+    pub old: T,
+
+    /// Value after the change
+    pub new: T,
+}
+
+impl<T: Clone> FieldChange<T> {
+    /// Returns the reverse change (`new` -> `old`).
+    #[inline]
+    pub fn invert(&self) -> Self {
+        Self {
+            old: self.new.clone(),
+            new: self.old.clone(),
+        }
+    }
+}
+
+/// A change to a single node property.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PropertyChange {
+    /// Property was added
+    Added {
+        key: String,
+        value: PropertyValue,
+    },
+
+    /// Property was removed
+    Removed {
+        key: String,
+        value: PropertyValue,
+    },
+
+    /// Property value changed
+    Modified {
+        key: String,
+        old: PropertyValue,
+        new: PropertyValue,
+    },
+}
+
+impl PropertyChange {
+    /// Returns the property key this change applies to.
+    #[inline]
+    pub fn key(&self) -> &str {
+        match self {
+            PropertyChange::Added { key, .. }
+            | PropertyChange::Removed { key, .. }
+            | PropertyChange::Modified { key, .. } => key,
+        }
+    }
+
+    /// Returns the reverse change.
+    pub fn invert(&self) -> Self {
+        match self {
+            PropertyChange::Added { key, value } => PropertyChange::Removed {
+                key: key.clone(),
+                value: value.clone(),
+            },
+            PropertyChange::Removed { key, value } => PropertyChange::Added {
+                key: key.clone(),
+                value: value.clone(),
+            },
+            PropertyChange::Modified { key, old, new } => PropertyChange::Modified {
+                key: key.clone(),
+                old: new.clone(),
+                new: old.clone(),
+            },
+        }
+    }
+}
+
+/// All changes to a node that exists in both graphs.
+///
+/// Fields that did not change are `None` (or empty for properties).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeChange {
+    /// ID of the modified node
+    pub node_id: String,
+
+    /// Change of node type
+    pub node_type: Option<FieldChange<String>>,
+
+    /// Change of editor position
+    pub position: Option<FieldChange<Position>>,
+
+    /// Change of input pins
+    pub inputs: Option<FieldChange<Vec<PinInstance>>>,
+
+    /// Change of output pins
+    pub outputs: Option<FieldChange<Vec<PinInstance>>>,
+
+    /// Per-key property changes, sorted by key
+    pub properties: Vec<PropertyChange>,
+
+    /// Change of build condition
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<FieldChange<Option<BuildCondition>>>,
+
+    /// Change of breakpoint flag
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub breakpoint: Option<FieldChange<bool>>,
+
+    /// Change of trace flag
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<FieldChange<bool>>,
+
+    /// Change of watched output pins
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watched_pins: Option<FieldChange<BTreeSet<String>>>,
+}
+
+impl NodeChange {
+    /// Computes the changes between two versions of the same node.
+    ///
+    /// Returns `None` if the nodes are identical.
+    pub fn compute(old: &NodeInstance, new: &NodeInstance) -> Option<Self> {
+        let mut properties = Vec::new();
+
+        for (key, old_value) in &old.properties {
+            match new.properties.get(key) {
+                Some(new_value) if new_value != old_value => properties.push(PropertyChange::Modified {
+                    key: key.clone(),
+                    old: old_value.clone(),
+                    new: new_value.clone(),
+                }),
+                Some(_) => {}
+                None => properties.push(PropertyChange::Removed {
+                    key: key.clone(),
+                    value: old_value.clone(),
+                }),
+            }
+        }
+
+        for (key, new_value) in &new.properties {
+            if !old.properties.contains_key(key) {
+                properties.push(PropertyChange::Added {
+                    key: key.clone(),
+                    value: new_value.clone(),
+                });
+            }
+        }
+
+        // HashMap iteration order is random; sort for stable output
+        properties.sort_by(|a, b| a.key().cmp(b.key()));
+
+        let change = NodeChange {
+            node_id: new.id.clone(),
+            node_type: field_change(&old.node_type, &new.node_type),
+            position: field_change(&old.position, &new.position),
+            inputs: field_change(&old.inputs, &new.inputs),
+            outputs: field_change(&old.outputs, &new.outputs),
+            properties,
+            condition: field_change(&old.condition, &new.condition),
+            breakpoint: field_change(&old.breakpoint, &new.breakpoint),
+            trace: field_change(&old.trace, &new.trace),
+            watched_pins: field_change(&old.watched_pins, &new.watched_pins),
+        };
+
+        (!change.is_empty()).then_some(change)
+    }
+
+    /// Checks if this change contains no modifications.
+    pub fn is_empty(&self) -> bool {
+        self.node_type.is_none()
+            && self.position.is_none()
+            && self.inputs.is_none()
+            && self.outputs.is_none()
+            && self.properties.is_empty()
+            && self.condition.is_none()
+            && self.breakpoint.is_none()
+            && self.trace.is_none()
+            && self.watched_pins.is_none()
+    }
+
+    /// Returns the reverse change.
+    pub fn invert(&self) -> Self {
+        NodeChange {
+            node_id: self.node_id.clone(),
+            node_type: self.node_type.as_ref().map(FieldChange::invert),
+            position: self.position.as_ref().map(FieldChange::invert),
+            inputs: self.inputs.as_ref().map(FieldChange::invert),
+            outputs: self.outputs.as_ref().map(FieldChange::invert),
+            properties: self.properties.iter().map(PropertyChange::invert).collect(),
+            condition: self.condition.as_ref().map(FieldChange::invert),
+            breakpoint: self.breakpoint.as_ref().map(FieldChange::invert),
+            trace: self.trace.as_ref().map(FieldChange::invert),
+            watched_pins: self.watched_pins.as_ref().map(FieldChange::invert),
+        }
+    }
+
+    /// Applies the new values of this change to a node.
+    fn apply_to(&self, node: &mut NodeInstance) {
+        if let Some(change) = &self.node_type {
+            node.node_type = change.new.clone();
+        }
+        if let Some(change) = &self.position {
+            node.position = change.new;
+        }
+        if let Some(change) = &self.inputs {
+            node.inputs = change.new.clone();
+        }
+        if let Some(change) = &self.outputs {
+            node.outputs = change.new.clone();
+        }
+        if let Some(change) = &self.condition {
+            node.condition = change.new.clone();
+        }
+        if let Some(change) = &self.breakpoint {
+            node.breakpoint = change.new;
+        }
+        if let Some(change) = &self.trace {
+            node.trace = change.new;
+        }
+        if let Some(change) = &self.watched_pins {
+            node.watched_pins = change.new.clone();
+        }
+        for property in &self.properties {
+            match property {
+                PropertyChange::Added { key, value } => {
+                    node.properties.insert(key.clone(), value.clone());
+                }
+                PropertyChange::Modified { key, new, .. } => {
+                    node.properties.insert(key.clone(), new.clone());
+                }
+                PropertyChange::Removed { key, .. } => {
+                    node.properties.remove(key);
+                }
+            }
+        }
+    }
+}
+
+/// Structural difference between two graphs.
+///
+/// Removed nodes, connections, and comments are stored in full so the diff
+/// can be inverted without access to the original graph.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphDiff {
+    /// Nodes present only in the new graph, sorted by ID
+    pub added_nodes: Vec<NodeInstance>,
+
+    /// Nodes present only in the old graph, sorted by ID
+    pub removed_nodes: Vec<NodeInstance>,
+
+    /// Nodes present in both graphs with differences, sorted by ID
+    pub modified_nodes: Vec<NodeChange>,
+
+    /// Connections present only in the new graph
+    pub added_connections: Vec<Connection>,
+
+    /// Connections present only in the old graph
+    pub removed_connections: Vec<Connection>,
+
+    /// Connections that kept their ID but changed endpoints or editor metadata
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modified_connections: Vec<FieldChange<Connection>>,
+
+    /// Comments present only in the new graph
+    pub added_comments: Vec<GraphComment>,
+
+    /// Comments present only in the old graph
+    pub removed_comments: Vec<GraphComment>,
+
+    /// Change of declared graph inputs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub graph_inputs: Option<FieldChange<Vec<GraphParam>>>,
+
+    /// Change of declared graph outputs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub graph_outputs: Option<FieldChange<Vec<GraphParam>>>,
+
+    /// Change of declared graph variables
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variables: Option<FieldChange<HashMap<String, VariableDecl>>>,
+
+    /// Change of declared externals
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub externals: Option<FieldChange<Vec<ExternalDecl>>>,
+
+    /// Groups present only in the new graph, sorted by ID
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub added_groups: Vec<GraphGroup>,
+
+    /// Groups present only in the old graph, sorted by ID
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub removed_groups: Vec<GraphGroup>,
+
+    /// Groups present in both graphs with differences (title, color, or members)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modified_groups: Vec<FieldChange<GraphGroup>>,
+}
+
+impl GraphDiff {
+    /// Computes the difference from `old` to `new`.
+    ///
+    /// Nodes and groups are matched by ID. Connections with the same ID in
+    /// both graphs are matched too; other connections and comments have no
+    /// identity of their own, so they are compared by value (as multisets).
+    pub fn compute(old: &GraphDescription, new: &GraphDescription) -> Self {
+        let mut diff = GraphDiff::default();
+
+        for (id, old_node) in &old.nodes {
+            match new.nodes.get(id) {
+                Some(new_node) => {
+                    if let Some(change) = NodeChange::compute(old_node, new_node) {
+                        diff.modified_nodes.push(change);
+                    }
+                }
+                None => diff.removed_nodes.push(old_node.clone()),
+            }
+        }
+
+        for (id, new_node) in &new.nodes {
+            if !old.nodes.contains_key(id) {
+                diff.added_nodes.push(new_node.clone());
+            }
+        }
+
+        // HashMap iteration order is random; sort for stable output
+        diff.added_nodes.sort_by(|a, b| a.id.cmp(&b.id));
+        diff.removed_nodes.sort_by(|a, b| a.id.cmp(&b.id));
+        diff.modified_nodes.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+
+        let (mut removed, mut added) = multiset_difference(&old.connections, &new.connections);
+        diff.modified_connections = take_matching_ids(&mut removed, &mut added);
+        diff.removed_connections = removed;
+        diff.added_connections = added;
+
+        // Comments contain floats, so they are matched with a linear scan
+        let mut unmatched: Vec<&GraphComment> = new.comments.iter().collect();
+        for comment in &old.comments {
+            match unmatched.iter().position(|c| *c == comment) {
+                Some(index) => {
+                    unmatched.swap_remove(index);
+                }
+                None => diff.removed_comments.push(comment.clone()),
+            }
+        }
+        diff.added_comments = new
+            .comments
+            .iter()
+            .filter(|c| unmatched.iter().any(|u| std::ptr::eq(*u, *c)))
+            .cloned()
+            .collect();
+
+        diff.graph_inputs = field_change(&old.graph_inputs, &new.graph_inputs);
+        diff.graph_outputs = field_change(&old.graph_outputs, &new.graph_outputs);
+        diff.variables = field_change(&old.variables, &new.variables);
+        diff.externals = field_change(&old.externals, &new.externals);
+
+        for old_group in &old.groups {
+            match new.get_group(&old_group.id) {
+                Some(new_group) => diff.modified_groups.extend(field_change(old_group, new_group)),
+                None => diff.removed_groups.push(old_group.clone()),
+            }
+        }
+        diff.added_groups = new
+            .groups
+            .iter()
+            .filter(|g| old.get_group(&g.id).is_none())
+            .cloned()
+            .collect();
+
+        diff.added_groups.sort_by(|a, b| a.id.cmp(&b.id));
+        diff.removed_groups.sort_by(|a, b| a.id.cmp(&b.id));
+        diff.modified_groups.sort_by(|a, b| a.old.id.cmp(&b.old.id));
+
+        diff
+    }
+
+    /// Checks if the diff contains no changes.
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.modified_nodes.is_empty()
+            && self.added_connections.is_empty()
+            && self.removed_connections.is_empty()
+            && self.modified_connections.is_empty()
+            && self.added_comments.is_empty()
+            && self.removed_comments.is_empty()
+            && self.graph_inputs.is_none()
+            && self.graph_outputs.is_none()
+            && self.variables.is_none()
+            && self.externals.is_none()
+            && self.added_groups.is_empty()
+            && self.removed_groups.is_empty()
+            && self.modified_groups.is_empty()
+    }
+
+    /// Returns the reverse diff, which undoes this diff when applied.
+    pub fn invert(&self) -> Self {
+        GraphDiff {
+            added_nodes: self.removed_nodes.clone(),
+            removed_nodes: self.added_nodes.clone(),
+            modified_nodes: self.modified_nodes.iter().map(NodeChange::invert).collect(),
+            added_connections: self.removed_connections.clone(),
+            removed_connections: self.added_connections.clone(),
+            modified_connections: self.modified_connections.iter().map(FieldChange::invert).collect(),
+            added_comments: self.removed_comments.clone(),
+            removed_comments: self.added_comments.clone(),
+            graph_inputs: self.graph_inputs.as_ref().map(FieldChange::invert),
+            graph_outputs: self.graph_outputs.as_ref().map(FieldChange::invert),
+            variables: self.variables.as_ref().map(FieldChange::invert),
+            externals: self.externals.as_ref().map(FieldChange::invert),
+            added_groups: self.removed_groups.clone(),
+            removed_groups: self.added_groups.clone(),
+            modified_groups: self.modified_groups.iter().map(FieldChange::invert).collect(),
+        }
+    }
+
+    /// Applies this diff to a graph.
+    ///
+    /// The graph is validated against the diff before any change is made,
+    /// so a failed apply leaves the graph untouched.
+    ///
+    /// # Errors
+    ///
+    /// - [`GraphyError::NodeNotFound`] if a removed or modified node is missing
+    /// - [`GraphyError::InvalidConnection`] if a removed or modified connection is
+    ///   missing, with [`ConnectionErrorReason::NotFound`]
+    /// - [`GraphyError::Custom`] if an added node or group already exists, or a
+    ///   removed comment or removed/modified group is missing
+    pub fn apply(&self, graph: &mut GraphDescription) -> Result<(), GraphyError> {
+        self.validate(graph)?;
+
+        for connection in &self.removed_connections {
+            if let Some(index) = graph.connections.iter().position(|c| c == connection) {
+                graph.connections.remove(index);
+            }
+        }
+
+        for node in &self.removed_nodes {
+            graph.nodes.remove(&node.id);
+        }
+
+        for change in &self.modified_nodes {
+            if let Some(node) = graph.nodes.get_mut(&change.node_id) {
+                change.apply_to(node);
+            }
+        }
+
+        for node in &self.added_nodes {
+            graph.add_node(node.clone());
+        }
+
+        for change in &self.modified_connections {
+            if let Some(connection) = graph.connections.iter_mut().find(|c| **c == change.old) {
+                *connection = change.new.clone();
+            }
+        }
+
+        graph.connections.extend(self.added_connections.iter().cloned());
+
+        for comment in &self.removed_comments {
+            if let Some(index) = graph.comments.iter().position(|c| c == comment) {
+                graph.comments.remove(index);
+            }
+        }
+
+        graph.comments.extend(self.added_comments.iter().cloned());
+
+        if let Some(change) = &self.graph_inputs {
+            graph.graph_inputs = change.new.clone();
+        }
+        if let Some(change) = &self.graph_outputs {
+            graph.graph_outputs = change.new.clone();
+        }
+        if let Some(change) = &self.variables {
+            graph.variables = change.new.clone();
+        }
+        if let Some(change) = &self.externals {
+            graph.externals = change.new.clone();
+        }
+
+        graph
+            .groups
+            .retain(|g| !self.removed_groups.iter().any(|removed| removed.id == g.id));
+        for change in &self.modified_groups {
+            if let Some(group) = graph.get_group_mut(&change.old.id) {
+                *group = change.new.clone();
+            }
+        }
+        graph.groups.extend(self.added_groups.iter().cloned());
+
+        Ok(())
+    }
+
+    /// Checks that every removal and modification refers to something in the graph.
+    fn validate(&self, graph: &GraphDescription) -> Result<(), GraphyError> {
+        for node in &self.removed_nodes {
+            if !graph.nodes.contains_key(&node.id) {
+                return Err(GraphyError::NodeNotFound(node.id.clone()));
+            }
+        }
+
+        for change in &self.modified_nodes {
+            if !graph.nodes.contains_key(&change.node_id) {
+                return Err(GraphyError::NodeNotFound(change.node_id.clone()));
+            }
+        }
+
+        for node in &self.added_nodes {
+            let replaces_removed = self.removed_nodes.iter().any(|n| n.id == node.id);
+            if graph.nodes.contains_key(&node.id) && !replaces_removed {
+                return Err(GraphyError::Custom(format!("Node already exists: {}", node.id)));
+            }
+        }
+
+        let expected: Vec<Connection> = self
+            .removed_connections
+            .iter()
+            .chain(self.modified_connections.iter().map(|change| &change.old))
+            .cloned()
+            .collect();
+        let (missing, _) = multiset_difference(&expected, &graph.connections);
+        if let Some(connection) = missing.first() {
+            return Err(graph.connection_error(connection, ConnectionErrorReason::NotFound));
+        }
+
+        let mut available: Vec<&GraphComment> = graph.comments.iter().collect();
+        for comment in &self.removed_comments {
+            match available.iter().position(|c| *c == comment) {
+                Some(index) => {
+                    available.swap_remove(index);
+                }
+                None => {
+                    return Err(GraphyError::Custom(format!("Comment not found: {:?}", comment.text)));
+                }
+            }
+        }
+
+        let existing_groups = self
+            .removed_groups
+            .iter()
+            .map(|g| &g.id)
+            .chain(self.modified_groups.iter().map(|c| &c.old.id));
+        for group_id in existing_groups {
+            if graph.get_group(group_id).is_none() {
+                return Err(GraphyError::Custom(format!("Group not found: {}", group_id)));
+            }
+        }
+
+        for group in &self.added_groups {
+            let replaces_removed = self.removed_groups.iter().any(|g| g.id == group.id);
+            if graph.get_group(&group.id).is_some() && !replaces_removed {
+                return Err(GraphyError::Custom(format!("Group already exists: {}", group.id)));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns `Some` change if the values differ.
+#[inline]
+fn field_change<T: Clone + PartialEq>(old: &T, new: &T) -> Option<FieldChange<T>> {
+    (old != new).then(|| FieldChange {
+        old: old.clone(),
+        new: new.clone(),
+    })
+}
+
+/// Pairs removed and added connections with the same ID as modifications.
+///
+/// Connections without an ID stay in the removed and added lists.
+fn take_matching_ids(removed: &mut Vec<Connection>, added: &mut Vec<Connection>) -> Vec<FieldChange<Connection>> {
+    let mut modified = Vec::new();
+    removed.retain(|old| {
+        let Some(id) = &old.id else {
+            return true;
+        };
+        match added.iter().position(|new| new.id.as_ref() == Some(id)) {
+            Some(index) => {
+                modified.push(FieldChange {
+                    old: old.clone(),
+                    new: added.remove(index),
+                });
+                false
+            }
+            None => true,
+        }
+    });
+    modified
+}
+
+/// Multiset difference of two connection lists.
+///
+/// Returns `(only_in_old, only_in_new)`, each in original order.
+fn multiset_difference(old: &[Connection], new: &[Connection]) -> (Vec<Connection>, Vec<Connection>) {
+    let mut counts: FxHashMap<&Connection, isize> = FxHashMap::default();
+    for connection in new {
+        *counts.entry(connection).or_default() += 1;
+    }
+
+    let mut only_in_old = Vec::new();
+    for connection in old {
+        match counts.get_mut(connection) {
+            Some(count) if *count > 0 => *count -= 1,
+            _ => only_in_old.push(connection.clone()),
+        }
+    }
+
+    // Remaining positive counts are connections only in `new`
+    let mut only_in_new = Vec::new();
+    for connection in new {
+        if let Some(count) = counts.get_mut(connection) {
+            if *count > 0 {
+                *count -= 1;
+                only_in_new.push(connection.clone());
+            }
+        }
+    }
+
+    (only_in_old, only_in_new)
+}
@@ -0,0 +1,190 @@
+//! # Heap Statistics
+//!
+//! Real allocation counts for profiling. Requires the `heap-stats` feature.
+//!
+//! [`TrackingAllocator`] wraps an allocator (the system allocator by
+//! default) and counts every allocation. Install it as the global allocator
+//! of the final binary, then measure code with [`track`] or a [`HeapScope`].
+//! Without the allocator installed, measurements return `None`.
+//!
+//! With the feature enabled, [`DataResolver`](crate::DataResolver) records
+//! the allocations made while it was built, see
+//! [`DataResolver::build_heap_usage`](crate::DataResolver::build_heap_usage).
+//!
+//! Counters are process-wide: allocations made by other threads while a
+//! scope is open are included in its usage.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use graphy::utils::heap_stats::{self, TrackingAllocator};
+//!
+//! #[global_allocator]
+//! static ALLOCATOR: TrackingAllocator = TrackingAllocator::system();
+//!
+//! let (resolver, usage) = heap_stats::track(|| DataResolver::build(&graph, &provider));
+//! if let Some(usage) = usage {
+//!     println!("{} allocations, {} bytes", usage.allocations, usage.allocated_bytes);
+//! }
+//! ```
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+static CURRENT_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// Global allocator that counts allocations.
+///
+/// Delegates to the wrapped allocator and updates process-wide counters,
+/// read with [`HeapSnapshot::now`].
+#[derive(Debug, Default)]
+pub struct TrackingAllocator<A = System> {
+    inner: A,
+}
+
+impl TrackingAllocator<System> {
+    /// Wraps the system allocator.
+    pub const fn system() -> Self {
+        Self { inner: System }
+    }
+}
+
+impl<A> TrackingAllocator<A> {
+    /// Wraps an allocator.
+    pub const fn new(inner: A) -> Self {
+        Self { inner }
+    }
+}
+
+#[inline(always)]
+fn record_alloc(size: usize) {
+    if !INSTALLED.load(Ordering::Relaxed) {
+        INSTALLED.store(true, Ordering::Relaxed);
+    }
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ALLOCATED_BYTES.fetch_add(size, Ordering::Relaxed);
+    let current = CURRENT_BYTES.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK_BYTES.fetch_max(current, Ordering::Relaxed);
+}
+
+#[inline(always)]
+fn record_dealloc(size: usize) {
+    CURRENT_BYTES.fetch_sub(size, Ordering::Relaxed);
+}
+
+// SAFETY: every call is forwarded unchanged to the wrapped allocator; the
+// counters don't allocate.
+unsafe impl<A: GlobalAlloc> GlobalAlloc for TrackingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout);
+        record_dealloc(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = self.inner.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            record_dealloc(layout.size());
+            record_alloc(new_size);
+        }
+        new_ptr
+    }
+}
+
+/// Returns `true` once a [`TrackingAllocator`] has served an allocation.
+pub fn is_installed() -> bool {
+    INSTALLED.load(Ordering::Relaxed)
+}
+
+/// Process-wide allocation counters at one point in time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HeapSnapshot {
+    /// Bytes currently allocated
+    pub current_bytes: usize,
+
+    /// Highest `current_bytes` seen so far
+    pub peak_bytes: usize,
+
+    /// Number of allocations so far (reallocations included)
+    pub allocations: usize,
+
+    /// Bytes allocated so far, ignoring frees
+    pub allocated_bytes: usize,
+}
+
+impl HeapSnapshot {
+    /// Reads the counters, or returns `None` if no [`TrackingAllocator`] is installed.
+    pub fn now() -> Option<Self> {
+        is_installed().then(|| Self {
+            current_bytes: CURRENT_BYTES.load(Ordering::Relaxed),
+            peak_bytes: PEAK_BYTES.load(Ordering::Relaxed),
+            allocations: ALLOCATIONS.load(Ordering::Relaxed),
+            allocated_bytes: ALLOCATED_BYTES.load(Ordering::Relaxed),
+        })
+    }
+}
+
+/// Allocations made between two snapshots.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HeapUsage {
+    /// Number of allocations (reallocations included)
+    pub allocations: usize,
+
+    /// Bytes allocated, ignoring frees
+    pub allocated_bytes: usize,
+
+    /// Change in allocated bytes (negative if more was freed than allocated)
+    pub retained_bytes: isize,
+}
+
+/// Measures the allocations made while it is open.
+#[derive(Debug, Clone, Copy)]
+pub struct HeapScope {
+    start: Option<HeapSnapshot>,
+}
+
+impl HeapScope {
+    /// Opens a scope.
+    pub fn start() -> Self {
+        Self {
+            start: HeapSnapshot::now(),
+        }
+    }
+
+    /// Closes the scope, or returns `None` if no [`TrackingAllocator`] is installed.
+    pub fn finish(self) -> Option<HeapUsage> {
+        let start = self.start?;
+        let end = HeapSnapshot::now()?;
+        Some(HeapUsage {
+            allocations: end.allocations - start.allocations,
+            allocated_bytes: end.allocated_bytes - start.allocated_bytes,
+            retained_bytes: end.current_bytes as isize - start.current_bytes as isize,
+        })
+    }
+}
+
+/// Runs a closure and returns its result with the allocations it made.
+pub fn track<R>(f: impl FnOnce() -> R) -> (R, Option<HeapUsage>) {
+    let scope = HeapScope::start();
+    let result = f();
+    (result, scope.finish())
+}
@@ -0,0 +1,411 @@
+//! # Graph History
+//!
+//! Undo/redo and time travel over a log of graph edits.
+//!
+//! A [`GraphHistory`] starts from a graph and records every edit as a
+//! [`GraphDiff`] with a label and a timestamp. Undo and redo apply the diffs
+//! (inverted, for undo) to the current graph; [`snapshot`](GraphHistory::snapshot)
+//! rebuilds the graph at any point of the history without moving there, and
+//! [`go_to`](GraphHistory::go_to) moves there. Every
+//! [`checkpoint_interval`](GraphHistory::checkpoint_interval) edits the
+//! graph is kept in full, so rebuilding a past graph replays at most that
+//! many diffs.
+//!
+//! The history serializes as the initial graph, the edits, and the current
+//! position, so editors can write it out as they go and recover the session
+//! after a crash with [`GraphHistory::load`]. Checkpoints are rebuilt on
+//! load.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::utils::GraphHistory;
+//! use graphy::{GraphDescription, NodeInstance, Position};
+//!
+//! let mut history = GraphHistory::new(GraphDescription::new("graph"));
+//!
+//! let mut edited = history.current().clone();
+//! edited.add_node(NodeInstance::new("add_1", "math.add", Position::zero()));
+//! history.record_change(&edited, "Add node").unwrap();
+//!
+//! assert_eq!(history.undo_label(), Some("Add node"));
+//! history.undo().unwrap();
+//! assert!(history.current().nodes.is_empty());
+//!
+//! history.redo().unwrap();
+//! assert!(history.current().get_node("add_1").is_some());
+//! ```
+
+use super::diff::GraphDiff;
+use super::timing::unix_millis;
+use crate::core::GraphDescription;
+use crate::io::Format;
+use crate::GraphyError;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Edits between two checkpoints, by default
+pub const DEFAULT_CHECKPOINT_INTERVAL: usize = 32;
+
+/// One recorded edit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// The change the edit made
+    pub diff: GraphDiff,
+
+    /// Name of the edit shown in the editor, e.g. "Add node"
+    pub label: String,
+
+    /// When the edit was recorded, in milliseconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+/// A graph with the history of its edits.
+///
+/// See the [module docs](self).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "HistoryFile")]
+pub struct GraphHistory {
+    /// The graph before the first entry
+    initial: GraphDescription,
+    entries: Vec<HistoryEntry>,
+
+    /// Number of entries applied to get the current graph
+    position: usize,
+    checkpoint_interval: usize,
+    current: GraphDescription,
+
+    /// Graphs after every `checkpoint_interval` entries, by position
+    checkpoints: BTreeMap<usize, GraphDescription>,
+}
+
+impl GraphHistory {
+    /// Starts a history at a graph.
+    pub fn new(graph: GraphDescription) -> Self {
+        Self {
+            current: graph.clone(),
+            initial: graph,
+            entries: Vec::new(),
+            position: 0,
+            checkpoint_interval: DEFAULT_CHECKPOINT_INTERVAL,
+            checkpoints: BTreeMap::new(),
+        }
+    }
+
+    /// Keeps the graph in full every `interval` edits (at least 1).
+    ///
+    /// Fewer edits between checkpoints make [`snapshot`](Self::snapshot) and
+    /// [`go_to`](Self::go_to) faster, at the cost of a graph copy each.
+    /// Checkpoints already taken are kept.
+    pub fn with_checkpoint_interval(mut self, interval: usize) -> Self {
+        self.checkpoint_interval = interval.max(1);
+        self
+    }
+
+    /// Returns the graph at the current position.
+    #[inline]
+    pub fn current(&self) -> &GraphDescription {
+        &self.current
+    }
+
+    /// Returns the graph the history started at.
+    #[inline]
+    pub fn initial(&self) -> &GraphDescription {
+        &self.initial
+    }
+
+    /// Returns the recorded edits, oldest first.
+    #[inline]
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    /// Returns the number of edits applied to get the current graph.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the number of recorded edits, undone ones included.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Checks if no edit has been recorded.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the number of edits between checkpoints.
+    #[inline]
+    pub fn checkpoint_interval(&self) -> usize {
+        self.checkpoint_interval
+    }
+
+    /// Records an edit of the current graph, timestamped now.
+    ///
+    /// The diff is applied to the current graph. Undone edits are dropped,
+    /// as editors do when a new edit follows an undo.
+    ///
+    /// # Errors
+    ///
+    /// Fails like [`GraphDiff::apply`] if the diff doesn't apply to the
+    /// current graph (the history is unchanged).
+    pub fn record(&mut self, diff: GraphDiff, label: impl Into<String>) -> Result<(), GraphyError> {
+        self.record_at(diff, label, unix_millis())
+    }
+
+    /// Records an edit of the current graph with a given timestamp.
+    ///
+    /// Same as [`record`](Self::record), for hosts with their own clock
+    /// (`record` uses zero on `wasm32-unknown-unknown`, which has none).
+    ///
+    /// # Errors
+    ///
+    /// Fails like [`record`](Self::record).
+    pub fn record_at(&mut self, diff: GraphDiff, label: impl Into<String>, timestamp: u64) -> Result<(), GraphyError> {
+        diff.apply(&mut self.current)?;
+
+        self.entries.truncate(self.position);
+        self.checkpoints.split_off(&(self.position + 1));
+        self.entries.push(HistoryEntry {
+            diff,
+            label: label.into(),
+            timestamp,
+        });
+        self.position += 1;
+        if self.position.is_multiple_of(self.checkpoint_interval) {
+            self.checkpoints.insert(self.position, self.current.clone());
+        }
+        Ok(())
+    }
+
+    /// Records the change from the current graph to `graph`.
+    ///
+    /// Returns `false` without recording anything if there's no change. Only
+    /// what a [`GraphDiff`] covers is recorded: edits of the graph metadata
+    /// alone aren't.
+    ///
+    /// # Errors
+    ///
+    /// Fails like [`record`](Self::record).
+    pub fn record_change(&mut self, graph: &GraphDescription, label: impl Into<String>) -> Result<bool, GraphyError> {
+        let diff = GraphDiff::compute(&self.current, graph);
+        if diff.is_empty() {
+            return Ok(false);
+        }
+        self.record(diff, label)?;
+        Ok(true)
+    }
+
+    /// Checks if there's an edit to undo.
+    #[inline]
+    pub fn can_undo(&self) -> bool {
+        self.position > 0
+    }
+
+    /// Checks if there's an undone edit to redo.
+    #[inline]
+    pub fn can_redo(&self) -> bool {
+        self.position < self.entries.len()
+    }
+
+    /// Returns the label of the edit [`undo`](Self::undo) would revert.
+    pub fn undo_label(&self) -> Option<&str> {
+        self.position.checked_sub(1).map(|index| self.entries[index].label.as_str())
+    }
+
+    /// Returns the label of the edit [`redo`](Self::redo) would apply.
+    pub fn redo_label(&self) -> Option<&str> {
+        self.entries.get(self.position).map(|entry| entry.label.as_str())
+    }
+
+    /// Reverts the last applied edit.
+    ///
+    /// Returns `false` if there's nothing to undo.
+    ///
+    /// # Errors
+    ///
+    /// Fails like [`GraphDiff::apply`] if the history doesn't match its
+    /// graphs, e.g. a hand-edited history file (the history is unchanged).
+    pub fn undo(&mut self) -> Result<bool, GraphyError> {
+        if !self.can_undo() {
+            return Ok(false);
+        }
+        self.entries[self.position - 1].diff.invert().apply(&mut self.current)?;
+        self.position -= 1;
+        Ok(true)
+    }
+
+    /// Applies the next undone edit again.
+    ///
+    /// Returns `false` if there's nothing to redo.
+    ///
+    /// # Errors
+    ///
+    /// Fails like [`undo`](Self::undo).
+    pub fn redo(&mut self) -> Result<bool, GraphyError> {
+        if !self.can_redo() {
+            return Ok(false);
+        }
+        self.entries[self.position].diff.apply(&mut self.current)?;
+        self.position += 1;
+        Ok(true)
+    }
+
+    /// Rebuilds the graph after the first `position` edits.
+    ///
+    /// Starts from the current graph or the closest checkpoint before
+    /// `position`, whichever needs fewer diffs applied.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::Custom`] if `position` is past the last edit,
+    /// and fails like [`undo`](Self::undo) if a diff doesn't apply.
+    pub fn snapshot(&self, position: usize) -> Result<GraphDescription, GraphyError> {
+        self.check_position(position)?;
+
+        let (start, graph) = self
+            .checkpoints
+            .range(..=position)
+            .next_back()
+            .map_or((0, &self.initial), |(start, graph)| (*start, graph));
+        if position.abs_diff(self.position) < position - start {
+            let mut graph = self.current.clone();
+            self.replay(&mut graph, self.position, position)?;
+            return Ok(graph);
+        }
+
+        let mut graph = graph.clone();
+        self.replay(&mut graph, start, position)?;
+        Ok(graph)
+    }
+
+    /// Moves to the graph after the first `position` edits.
+    ///
+    /// Later edits can still be redone.
+    ///
+    /// # Errors
+    ///
+    /// Fails like [`snapshot`](Self::snapshot) (the history is unchanged).
+    pub fn go_to(&mut self, position: usize) -> Result<(), GraphyError> {
+        if position != self.position {
+            self.current = self.snapshot(position)?;
+            self.position = position;
+        }
+        Ok(())
+    }
+
+    /// Reads a history from a file, in the format of its extension.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::Io`] if reading fails, and
+    /// [`GraphyError::Serialization`] if the extension isn't a known
+    /// [`Format`] or the file isn't a valid history.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, GraphyError> {
+        let path = path.as_ref();
+        let format = history_format(path)?;
+        let bytes = std::fs::read(path).map_err(|e| GraphyError::Io(format!("{}: {}", path.display(), e)))?;
+        format.deserialize(&bytes)
+    }
+
+    /// Writes the history to a file, in the format of its extension.
+    ///
+    /// The history is written next to the file first and then moved over
+    /// it, so a crash while saving leaves the previous file intact.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::Serialization`] if the extension isn't a known
+    /// [`Format`] or serialization fails, and [`GraphyError::Io`] if writing
+    /// fails.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), GraphyError> {
+        let path = path.as_ref();
+        let bytes = history_format(path)?.serialize(self)?;
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+
+        let io_error = |e: std::io::Error| GraphyError::Io(format!("{}: {}", path.display(), e));
+        std::fs::write(&temp, bytes).map_err(io_error)?;
+        std::fs::rename(&temp, path).map_err(io_error)
+    }
+
+    fn check_position(&self, position: usize) -> Result<(), GraphyError> {
+        if position > self.entries.len() {
+            return Err(GraphyError::Custom(format!(
+                "History position {} is past the last edit ({})",
+                position,
+                self.entries.len()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Moves `graph` from the graph at `from` to the graph at `to`
+    fn replay(&self, graph: &mut GraphDescription, from: usize, to: usize) -> Result<(), GraphyError> {
+        if from <= to {
+            for entry in &self.entries[from..to] {
+                entry.diff.apply(graph)?;
+            }
+        } else {
+            for entry in self.entries[to..from].iter().rev() {
+                entry.diff.invert().apply(graph)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The serialized form of a [`GraphHistory`]
+#[derive(Deserialize)]
+struct HistoryFile {
+    initial: GraphDescription,
+    entries: Vec<HistoryEntry>,
+    position: usize,
+    checkpoint_interval: usize,
+}
+
+/// [`HistoryFile`], borrowed for serializing
+#[derive(Serialize)]
+struct HistoryFileRef<'a> {
+    initial: &'a GraphDescription,
+    entries: &'a [HistoryEntry],
+    position: usize,
+    checkpoint_interval: usize,
+}
+
+impl Serialize for GraphHistory {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        HistoryFileRef {
+            initial: &self.initial,
+            entries: &self.entries,
+            position: self.position,
+            checkpoint_interval: self.checkpoint_interval,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl TryFrom<HistoryFile> for GraphHistory {
+    type Error = GraphyError;
+
+    /// Replays every entry, taking the checkpoints on the way
+    fn try_from(file: HistoryFile) -> Result<Self, Self::Error> {
+        let mut history = GraphHistory::new(file.initial).with_checkpoint_interval(file.checkpoint_interval);
+        for entry in file.entries {
+            history.record_at(entry.diff, entry.label, entry.timestamp)?;
+        }
+        history.go_to(file.position)?;
+        Ok(history)
+    }
+}
+
+/// The format of a history file, from its extension
+fn history_format(path: &Path) -> Result<Format, GraphyError> {
+    Format::from_path(path)
+        .ok_or_else(|| GraphyError::Serialization(format!("Unknown history file format: {}", path.display())))
+}
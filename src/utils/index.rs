@@ -0,0 +1,409 @@
+//! # Graph Index
+//!
+//! Fast lookups over large graphs for editors.
+//!
+//! The queries on [`GraphDescription`] scan every node or connection, which
+//! is too slow to run on every keystroke of an editor search over tens of
+//! thousands of nodes. A [`GraphIndex`] is built once and answers the same
+//! questions from hash maps: nodes by type and by property value,
+//! connections by endpoint, and text search over node IDs and comments.
+//!
+//! The index doesn't borrow the graph. After an edit, pass the edit's
+//! [`GraphDiff`] to [`GraphIndex::update`] instead of rebuilding it.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::{GraphDescription, NodeInstance, Position, PropertyValue};
+//! use graphy::utils::diff::GraphDiff;
+//! use graphy::utils::GraphIndex;
+//!
+//! let mut graph = GraphDescription::new("graph");
+//! let mut node = NodeInstance::new("spawn_enemy", "spawn", Position::zero());
+//! node.set_property("kind", PropertyValue::String("orc".into()));
+//! graph.add_node(node);
+//!
+//! let mut index = GraphIndex::build(&graph);
+//! assert_eq!(index.search("enemy"), ["spawn_enemy"]);
+//!
+//! // Keep the index in sync with an edit
+//! let old = graph.clone();
+//! graph.add_node(NodeInstance::new("spawn_boss", "spawn", Position::zero()));
+//! index.update(&GraphDiff::compute(&old, &graph));
+//!
+//! assert_eq!(index.nodes_of_type("spawn").collect::<Vec<_>>(), ["spawn_boss", "spawn_enemy"]);
+//! let orcs: Vec<&str> = index.nodes_with_property("kind", &PropertyValue::String("orc".into())).collect();
+//! assert_eq!(orcs, ["spawn_enemy"]);
+//! ```
+
+use super::diff::{GraphDiff, PropertyChange};
+use crate::core::{property_hash, Connection, GraphComment, GraphDescription, NodeInstance, PropertyValue};
+use rustc_hash::{FxHashMap, FxHashSet};
+use std::collections::{BTreeMap, BTreeSet};
+use std::hash::Hash;
+
+/// Lookup tables over the nodes, connections, and comments of a graph.
+///
+/// Node queries return IDs sorted by ID; connection and comment queries
+/// return them in graph order, like the scanning queries on
+/// [`GraphDescription`].
+#[derive(Debug, Clone, Default)]
+pub struct GraphIndex {
+    /// Node IDs by node type
+    by_type: FxHashMap<String, BTreeSet<String>>,
+
+    /// Node IDs by property key and value hash
+    by_property: FxHashMap<String, FxHashMap<u64, Vec<PropertyEntry>>>,
+
+    /// Connections by source node
+    outgoing: FxHashMap<String, Vec<Connection>>,
+
+    /// Connections by target node
+    incoming: FxHashMap<String, Vec<Connection>>,
+
+    /// Comments by slot, in graph order
+    comments: BTreeMap<usize, GraphComment>,
+
+    /// Slot of the next added comment
+    next_comment: usize,
+
+    /// Searchable node IDs
+    node_text: TextIndex<String>,
+
+    /// Searchable comment texts, by slot
+    comment_text: TextIndex<usize>,
+}
+
+/// Nodes sharing a property value
+#[derive(Debug, Clone)]
+struct PropertyEntry {
+    value: PropertyValue,
+    node_ids: BTreeSet<String>,
+}
+
+impl GraphIndex {
+    /// Indexes a graph.
+    pub fn build(graph: &GraphDescription) -> Self {
+        let mut index = Self::default();
+        for node in graph.nodes.values() {
+            index.insert_node(node);
+        }
+        for connection in &graph.connections {
+            index.insert_connection(connection);
+        }
+        for comment in &graph.comments {
+            index.insert_comment(comment);
+        }
+        index
+    }
+
+    /// Updates the index after `diff` was applied to the indexed graph.
+    ///
+    /// The diff must apply to the graph the index reflects, as checked by
+    /// [`GraphDiff::apply`]; otherwise the index no longer matches the graph.
+    pub fn update(&mut self, diff: &GraphDiff) {
+        for connection in &diff.removed_connections {
+            self.remove_connection(connection);
+        }
+
+        for node in &diff.removed_nodes {
+            self.remove_node(node);
+        }
+
+        for change in &diff.modified_nodes {
+            if let Some(node_type) = &change.node_type {
+                remove_from(&mut self.by_type, &node_type.old, &change.node_id);
+                insert_into(&mut self.by_type, &node_type.new, &change.node_id);
+            }
+            for property in &change.properties {
+                match property {
+                    PropertyChange::Added { key, value } => self.insert_property(&change.node_id, key, value),
+                    PropertyChange::Removed { key, value } => self.remove_property(&change.node_id, key, value),
+                    PropertyChange::Modified { key, old, new } => {
+                        self.remove_property(&change.node_id, key, old);
+                        self.insert_property(&change.node_id, key, new);
+                    }
+                }
+            }
+        }
+
+        for node in &diff.added_nodes {
+            self.insert_node(node);
+        }
+
+        for change in &diff.modified_connections {
+            if change.old.source_node == change.new.source_node && change.old.target_node == change.new.target_node {
+                replace_in(&mut self.outgoing, &change.old.source_node, &change.old, &change.new);
+                replace_in(&mut self.incoming, &change.old.target_node, &change.old, &change.new);
+            } else {
+                self.remove_connection(&change.old);
+                self.insert_connection(&change.new);
+            }
+        }
+
+        for connection in &diff.added_connections {
+            self.insert_connection(connection);
+        }
+
+        for comment in &diff.removed_comments {
+            let slot = self.comments.iter().find(|(_, c)| *c == comment).map(|(slot, _)| *slot);
+            if let Some(slot) = slot {
+                self.comments.remove(&slot);
+                self.comment_text.remove(&slot);
+            }
+        }
+
+        for comment in &diff.added_comments {
+            self.insert_comment(comment);
+        }
+    }
+
+    /// Returns the number of indexed nodes.
+    #[inline]
+    pub fn node_count(&self) -> usize {
+        self.node_text.len()
+    }
+
+    /// Checks if the index holds a node.
+    #[inline]
+    pub fn contains_node(&self, node_id: &str) -> bool {
+        self.node_text.contains(node_id)
+    }
+
+    /// Returns the IDs of the nodes of a type, sorted by ID.
+    pub fn nodes_of_type<'a>(&'a self, node_type: &str) -> impl Iterator<Item = &'a str> + 'a {
+        self.by_type.get(node_type).into_iter().flatten().map(String::as_str)
+    }
+
+    /// Returns the IDs of the nodes whose property `key` equals `value`, sorted by ID.
+    pub fn nodes_with_property<'a>(&'a self, key: &str, value: &PropertyValue) -> impl Iterator<Item = &'a str> + 'a {
+        let entry = self
+            .by_property
+            .get(key)
+            .and_then(|values| values.get(&property_hash(value)))
+            .and_then(|entries| entries.iter().find(|entry| entry.value == *value));
+        entry.into_iter().flat_map(|entry| &entry.node_ids).map(String::as_str)
+    }
+
+    /// Returns the connections leaving a node, from any of its outputs.
+    pub fn connections_from(&self, node_id: &str) -> &[Connection] {
+        self.outgoing.get(node_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the connections entering a node, into any of its inputs.
+    pub fn connections_to(&self, node_id: &str) -> &[Connection] {
+        self.incoming.get(node_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Searches node IDs and comments for a text, ignoring case.
+    ///
+    /// Returns the IDs of the nodes whose ID contains `query` or which are
+    /// attached to a comment containing it, sorted by ID. An empty query
+    /// matches every node.
+    pub fn search(&self, query: &str) -> Vec<&str> {
+        let mut found: BTreeSet<&str> = self.node_text.search(query).into_iter().map(String::as_str).collect();
+        for slot in self.comment_text.search(query) {
+            let attached = self.comments[slot].attached_node_ids.iter().map(String::as_str);
+            found.extend(attached.filter(|id| self.contains_node(id)));
+        }
+        found.into_iter().collect()
+    }
+
+    /// Returns the comments containing a text, ignoring case, in graph order.
+    pub fn search_comments(&self, query: &str) -> Vec<&GraphComment> {
+        let mut slots = self.comment_text.search(query);
+        slots.sort_unstable();
+        slots.into_iter().map(|slot| &self.comments[slot]).collect()
+    }
+
+    fn insert_node(&mut self, node: &NodeInstance) {
+        insert_into(&mut self.by_type, &node.node_type, &node.id);
+        for (key, value) in &node.properties {
+            self.insert_property(&node.id, key, value);
+        }
+        self.node_text.insert(node.id.clone(), &node.id);
+    }
+
+    fn remove_node(&mut self, node: &NodeInstance) {
+        remove_from(&mut self.by_type, &node.node_type, &node.id);
+        for (key, value) in &node.properties {
+            self.remove_property(&node.id, key, value);
+        }
+        self.node_text.remove(&node.id);
+    }
+
+    fn insert_property(&mut self, node_id: &str, key: &str, value: &PropertyValue) {
+        let entries = self
+            .by_property
+            .entry(key.to_string())
+            .or_default()
+            .entry(property_hash(value))
+            .or_default();
+        match entries.iter_mut().find(|entry| entry.value == *value) {
+            Some(entry) => {
+                entry.node_ids.insert(node_id.to_string());
+            }
+            None => entries.push(PropertyEntry {
+                value: value.clone(),
+                node_ids: BTreeSet::from([node_id.to_string()]),
+            }),
+        }
+    }
+
+    /// Removes a node from the entry holding it, so values that don't equal
+    /// themselves (`NaN`) are removed too
+    fn remove_property(&mut self, node_id: &str, key: &str, value: &PropertyValue) {
+        let hash = property_hash(value);
+        let Some(values) = self.by_property.get_mut(key) else {
+            return;
+        };
+        if let Some(entries) = values.get_mut(&hash) {
+            entries.retain_mut(|entry| !(entry.node_ids.remove(node_id) && entry.node_ids.is_empty()));
+            if entries.is_empty() {
+                values.remove(&hash);
+            }
+        }
+        if values.is_empty() {
+            self.by_property.remove(key);
+        }
+    }
+
+    fn insert_connection(&mut self, connection: &Connection) {
+        self.outgoing.entry(connection.source_node.clone()).or_default().push(connection.clone());
+        self.incoming.entry(connection.target_node.clone()).or_default().push(connection.clone());
+    }
+
+    fn remove_connection(&mut self, connection: &Connection) {
+        remove_connection_from(&mut self.outgoing, &connection.source_node, connection);
+        remove_connection_from(&mut self.incoming, &connection.target_node, connection);
+    }
+
+    fn insert_comment(&mut self, comment: &GraphComment) {
+        let slot = self.next_comment;
+        self.next_comment += 1;
+        self.comment_text.insert(slot, &comment.text);
+        self.comments.insert(slot, comment.clone());
+    }
+}
+
+fn insert_into(map: &mut FxHashMap<String, BTreeSet<String>>, key: &str, node_id: &str) {
+    map.entry(key.to_string()).or_default().insert(node_id.to_string());
+}
+
+fn remove_from(map: &mut FxHashMap<String, BTreeSet<String>>, key: &str, node_id: &str) {
+    if let Some(ids) = map.get_mut(key) {
+        ids.remove(node_id);
+        if ids.is_empty() {
+            map.remove(key);
+        }
+    }
+}
+
+fn remove_connection_from(map: &mut FxHashMap<String, Vec<Connection>>, node_id: &str, connection: &Connection) {
+    if let Some(connections) = map.get_mut(node_id) {
+        if let Some(position) = connections.iter().position(|c| c == connection) {
+            connections.remove(position);
+        }
+        if connections.is_empty() {
+            map.remove(node_id);
+        }
+    }
+}
+
+fn replace_in(map: &mut FxHashMap<String, Vec<Connection>>, node_id: &str, old: &Connection, new: &Connection) {
+    if let Some(connection) = map.get_mut(node_id).and_then(|connections| connections.iter_mut().find(|c| *c == old)) {
+        *connection = new.clone();
+    }
+}
+
+/// Case-insensitive substring search over short texts
+///
+/// Every text is split into its trigrams (runs of three characters). A
+/// query of three or more characters only checks the texts containing all
+/// of its trigrams, starting from the rarest; shorter queries scan every
+/// text.
+#[derive(Debug, Clone)]
+struct TextIndex<K> {
+    /// Lowercased texts by key
+    texts: FxHashMap<K, String>,
+
+    /// Keys of the texts containing each trigram
+    trigrams: FxHashMap<[char; 3], FxHashSet<K>>,
+}
+
+impl<K> Default for TextIndex<K> {
+    fn default() -> Self {
+        Self {
+            texts: FxHashMap::default(),
+            trigrams: FxHashMap::default(),
+        }
+    }
+}
+
+impl<K: Clone + Eq + Hash> TextIndex<K> {
+    fn len(&self) -> usize {
+        self.texts.len()
+    }
+
+    fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.texts.contains_key(key)
+    }
+
+    fn insert(&mut self, key: K, text: &str) {
+        self.remove(&key);
+        let text = text.to_lowercase();
+        for trigram in trigrams(&text) {
+            self.trigrams.entry(trigram).or_default().insert(key.clone());
+        }
+        self.texts.insert(key, text);
+    }
+
+    fn remove(&mut self, key: &K) {
+        let Some(text) = self.texts.remove(key) else {
+            return;
+        };
+        for trigram in trigrams(&text) {
+            if let Some(keys) = self.trigrams.get_mut(&trigram) {
+                keys.remove(key);
+                if keys.is_empty() {
+                    self.trigrams.remove(&trigram);
+                }
+            }
+        }
+    }
+
+    /// Returns the keys of the texts containing `query`, in no particular order
+    fn search(&self, query: &str) -> Vec<&K> {
+        let query = query.to_lowercase();
+        let mut postings = Vec::new();
+        for trigram in trigrams(&query) {
+            match self.trigrams.get(&trigram) {
+                Some(keys) => postings.push(keys),
+                None => return Vec::new(),
+            }
+        }
+
+        let Some(rarest) = postings.iter().min_by_key(|keys| keys.len()) else {
+            return self
+                .texts
+                .iter()
+                .filter(|(_, text)| text.contains(&query))
+                .map(|(key, _)| key)
+                .collect();
+        };
+        rarest
+            .iter()
+            .filter(|key| self.texts[*key].contains(&query))
+            .collect()
+    }
+}
+
+/// Returns the distinct trigrams of a text
+fn trigrams(text: &str) -> FxHashSet<[char; 3]> {
+    let chars: Vec<char> = text.chars().collect();
+    chars.windows(3).map(|window| [window[0], window[1], window[2]]).collect()
+}
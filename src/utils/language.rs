@@ -0,0 +1,621 @@
+//! # Target Language Profiles
+//!
+//! Language-specific rules used when turning graphs into code.
+//!
+//! A [`LanguageProfile`] describes what a valid identifier looks like, which
+//! words are reserved, how graph types are named, how the default value of a
+//! type is written, and how property values are formatted as literals. Variable naming
+//! ([`VariableNameGenerator`](super::VariableNameGenerator),
+//! [`DataResolver`](crate::DataResolver)) and the compile driver consult the
+//! profile, so a backend for another language only needs to pick (or
+//! implement) a profile to get valid names and literals.
+//!
+//! Three profiles are built in: [`RustProfile`] (the default everywhere),
+//! [`WgslProfile`], and [`CProfile`].
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::utils::{LanguageProfile, RustProfile, WgslProfile};
+//!
+//! assert_eq!(RustProfile.sanitize_identifier("type"), "type_");
+//! assert_eq!(RustProfile.result_variable_name("узел_1"), "node_узел_1_result");
+//! assert_eq!(WgslProfile.result_variable_name("узел_1"), "node______1_result");
+//! assert_eq!(WgslProfile.default_value("vec3<f32>"), "vec3<f32>()");
+//! ```
+
+use crate::core::PropertyValue;
+use crate::GraphyError;
+use std::collections::BTreeMap;
+
+/// Rules of a target language for names, defaults, and literals
+pub trait LanguageProfile: Send + Sync {
+    /// Name of the language (e.g. `"rust"`)
+    fn name(&self) -> &str;
+
+    /// Check if a character may start an identifier
+    fn is_identifier_start(&self, c: char) -> bool;
+
+    /// Check if a character may appear after the first in an identifier
+    fn is_identifier_continue(&self, c: char) -> bool;
+
+    /// Words that can't be used as identifiers
+    fn reserved_words(&self) -> &[&str];
+
+    /// Check if a word is reserved
+    fn is_reserved(&self, word: &str) -> bool {
+        self.reserved_words().contains(&word)
+    }
+
+    /// Turn an arbitrary string into a valid identifier
+    ///
+    /// Invalid characters become `_`, a `_` is prepended if the name can't
+    /// start with its first character (or is empty), and a `_` is appended
+    /// to reserved words.
+    fn sanitize_identifier(&self, name: &str) -> String {
+        let mut identifier: String = name
+            .chars()
+            .map(|c| if self.is_identifier_continue(c) { c } else { '_' })
+            .collect();
+
+        if !identifier.chars().next().is_some_and(|c| self.is_identifier_start(c)) {
+            identifier.insert(0, '_');
+        }
+        if self.is_reserved(&identifier) {
+            identifier.push('_');
+        }
+
+        identifier
+    }
+
+    /// Name of the variable holding a node's result
+    fn result_variable_name(&self, node_id: &str) -> String {
+        self.sanitize_identifier(&format!("node_{}_result", node_id))
+    }
+
+    /// Name of a graph type in the target language
+    ///
+    /// Graph types are written in Rust syntax (`i64`, `String`). Defaults to
+    /// the type unchanged.
+    fn map_type(&self, type_str: &str) -> String {
+        type_str.to_string()
+    }
+
+    /// Expression for the default value of a type
+    fn default_value(&self, type_str: &str) -> String;
+
+    /// Format a property value as a literal expression
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::CodeGeneration`] if the language can't
+    /// represent the value.
+    fn format_literal(&self, value: &PropertyValue) -> Result<String, GraphyError>;
+
+    /// Format a property value as a literal for an input of a given type
+    ///
+    /// `type_str` is the graph type of the input. Defaults to
+    /// [`format_literal`](Self::format_literal), ignoring the type.
+    ///
+    /// # Errors
+    ///
+    /// See [`format_literal`](Self::format_literal).
+    fn format_typed_literal(&self, value: &PropertyValue, _type_str: &str) -> Result<String, GraphyError> {
+        self.format_literal(value)
+    }
+}
+
+// ============================================================================
+// Rust
+// ============================================================================
+
+const RUST_RESERVED_WORDS: &[&str] = &[
+    "_", "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use",
+    "where", "while", "abstract", "become", "box", "do", "final", "gen", "macro", "override", "priv",
+    "try", "typeof", "unsized", "virtual", "yield",
+];
+
+/// Profile for generating Rust code
+///
+/// Vector and color properties become `glam`-style constructors
+/// (`Vec2::new(1.0, 2.0)`, `Vec4::new` for colors), so the generated code
+/// needs those types in scope. For inputs of a tuple type, they become
+/// tuples instead. Numbers follow the type of the input when it is known:
+/// `Number(2.0)` is `2.0` for an `f64` and `2` for an `i64`, and
+/// `Integer(2)` is `2.0` for an `f64`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RustProfile;
+
+impl LanguageProfile for RustProfile {
+    fn name(&self) -> &str {
+        "rust"
+    }
+
+    fn is_identifier_start(&self, c: char) -> bool {
+        c.is_alphabetic() || c == '_'
+    }
+
+    fn is_identifier_continue(&self, c: char) -> bool {
+        c.is_alphanumeric() || c == '_'
+    }
+
+    fn reserved_words(&self) -> &[&str] {
+        RUST_RESERVED_WORDS
+    }
+
+    fn default_value(&self, type_str: &str) -> String {
+        match type_str {
+            "f32" | "f64" => "0.0".to_string(),
+            "i8" | "i16" | "i32" | "i64" | "i128" | "isize" => "0".to_string(),
+            "u8" | "u16" | "u32" | "u64" | "u128" | "usize" => "0".to_string(),
+            "bool" => "false".to_string(),
+            "char" => "'\\0'".to_string(),
+            "String" => "String::new()".to_string(),
+            _ if type_str.starts_with('(') && type_str.ends_with(')') => {
+                // Tuple type
+                let inner = &type_str[1..type_str.len() - 1];
+                let defaults: Vec<String> = inner.split(',').map(|p| self.default_value(p.trim())).collect();
+                format!("({})", defaults.join(", "))
+            }
+            _ => "Default::default()".to_string(),
+        }
+    }
+
+    fn format_literal(&self, value: &PropertyValue) -> Result<String, GraphyError> {
+        let literal = match value {
+            PropertyValue::String(s) => format!("\"{}\"", s.escape_default()),
+            PropertyValue::Number(n) => {
+                // Whole numbers stay integers so they fit integer inputs
+                if n.fract() == 0.0 && n.abs() <= MAX_EXACT_INTEGER {
+                    format!("{}", *n as i64)
+                } else {
+                    rust_float(*n, "f64")
+                }
+            }
+            PropertyValue::Boolean(b) => b.to_string(),
+            // glam vectors hold `f32`s
+            PropertyValue::Vector2(x, y) => format!("Vec2::new({}, {})", rust_float(*x, "f32"), rust_float(*y, "f32")),
+            PropertyValue::Vector3(x, y, z) => format!(
+                "Vec3::new({}, {}, {})",
+                rust_float(*x, "f32"),
+                rust_float(*y, "f32"),
+                rust_float(*z, "f32")
+            ),
+            PropertyValue::Color(r, g, b, a) => format!(
+                "Vec4::new({}, {}, {}, {})",
+                rust_float(*r, "f32"),
+                rust_float(*g, "f32"),
+                rust_float(*b, "f32"),
+                rust_float(*a, "f32")
+            ),
+            PropertyValue::Integer(i) => i.to_string(),
+            PropertyValue::Array(items) => {
+                let items = items
+                    .iter()
+                    .map(|item| self.format_literal(item))
+                    .collect::<Result<Vec<_>, _>>()?;
+                format!("vec![{}]", items.join(", "))
+            }
+            PropertyValue::Map(entries) => {
+                // Sort keys so the generated code is deterministic
+                let mut keys: Vec<&String> = entries.keys().collect();
+                keys.sort();
+                let entries = keys
+                    .into_iter()
+                    .map(|key| {
+                        Ok(format!(
+                            "(String::from(\"{}\"), {})",
+                            key.escape_default(),
+                            self.format_literal(&entries[key])?
+                        ))
+                    })
+                    .collect::<Result<Vec<_>, GraphyError>>()?;
+                format!("std::collections::HashMap::from([{}])", entries.join(", "))
+            }
+            PropertyValue::Enum { variant, payload } => match payload {
+                Some(payload) => format!("{}({})", variant, self.format_literal(payload)?),
+                None => variant.clone(),
+            },
+        };
+
+        Ok(literal)
+    }
+
+    fn format_typed_literal(&self, value: &PropertyValue, type_str: &str) -> Result<String, GraphyError> {
+        if let (PropertyValue::Number(n), Some(range)) = (value, rust_integer_range(type_str)) {
+            return rust_integer(*n, range, type_str);
+        }
+        let components = match value {
+            PropertyValue::Number(n) if is_rust_float(type_str) => return Ok(rust_float(*n, type_str)),
+            PropertyValue::Integer(i) if is_rust_float(type_str) => return Ok(rust_float(*i as f64, type_str)),
+            PropertyValue::Vector2(x, y) => vec![*x, *y],
+            PropertyValue::Vector3(x, y, z) => vec![*x, *y, *z],
+            PropertyValue::Color(r, g, b, a) => vec![*r, *g, *b, *a],
+            _ => return self.format_literal(value),
+        };
+        let Some(inner) = type_str.strip_prefix('(').and_then(|inner| inner.strip_suffix(')')) else {
+            return self.format_literal(value);
+        };
+        let types: Vec<&str> = inner.split(',').map(str::trim).collect();
+        if types.len() != components.len() {
+            return self.format_literal(value);
+        }
+
+        let items = components
+            .into_iter()
+            .zip(types)
+            .map(|(component, component_type)| self.format_typed_literal(&PropertyValue::Number(component), component_type))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(format!("({})", items.join(", ")))
+    }
+}
+
+/// Check if a graph type is a Rust float
+fn is_rust_float(type_str: &str) -> bool {
+    matches!(type_str, "f32" | "f64")
+}
+
+/// Smallest and one past the largest value of a Rust integer type
+///
+/// Both bounds are powers of two, so they're exact as `f64`s. `isize` and
+/// `usize` are taken to be 64 bits wide.
+fn rust_integer_range(type_str: &str) -> Option<(f64, f64)> {
+    let (signed, bits) = match type_str {
+        "i8" => (true, 8),
+        "i16" => (true, 16),
+        "i32" => (true, 32),
+        "i64" | "isize" => (true, 64),
+        "i128" => (true, 128),
+        "u8" => (false, 8),
+        "u16" => (false, 16),
+        "u32" => (false, 32),
+        "u64" | "usize" => (false, 64),
+        "u128" => (false, 128),
+        _ => return None,
+    };
+    Some(if signed {
+        (-(2f64.powi(bits - 1)), 2f64.powi(bits - 1))
+    } else {
+        (0.0, 2f64.powi(bits))
+    })
+}
+
+/// Format a number as a literal of a Rust integer type with the given range
+///
+/// Fails if the number isn't whole or doesn't fit the type.
+fn rust_integer(n: f64, (min, end): (f64, f64), type_str: &str) -> Result<String, GraphyError> {
+    if n.fract() != 0.0 || n < min || n >= end {
+        return Err(GraphyError::CodeGeneration(format!("{} is not a valid {}", n, type_str)));
+    }
+    // The `+ 0.0` turns `-0.0` into `0.0`
+    Ok(format!("{:.0}", n + 0.0))
+}
+
+/// Largest whole `f64` every smaller whole number is exact below (2^53)
+const MAX_EXACT_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+/// Format a float as a Rust expression of `float_type` (`f32` or `f64`),
+/// always with a decimal point or exponent
+fn rust_float(n: f64, float_type: &str) -> String {
+    if n.is_nan() {
+        format!("{}::NAN", float_type)
+    } else if n == f64::INFINITY {
+        format!("{}::INFINITY", float_type)
+    } else if n == f64::NEG_INFINITY {
+        format!("{}::NEG_INFINITY", float_type)
+    } else {
+        // Debug formatting keeps the `.0` on whole numbers
+        format!("{:?}", n)
+    }
+}
+
+// ============================================================================
+// WGSL
+// ============================================================================
+
+const WGSL_RESERVED_WORDS: &[&str] = &[
+    // Keywords
+    "_", "alias", "break", "case", "const", "const_assert", "continue", "continuing", "default",
+    "diagnostic", "discard", "else", "enable", "false", "fn", "for", "if", "let", "loop", "override",
+    "requires", "return", "struct", "switch", "true", "var", "while",
+    // Reserved words
+    "NULL", "Self", "abstract", "active", "alignas", "alignof", "as", "asm", "async", "attribute",
+    "auto", "await", "become", "cast", "catch", "class", "co_await", "co_return", "co_yield", "coherent",
+    "column_major", "common", "compile", "concept", "const_cast", "consteval", "constexpr", "constinit",
+    "crate", "debugger", "decltype", "delete", "demote", "do", "dynamic_cast", "enum", "explicit",
+    "export", "extends", "extern", "external", "fallthrough", "filter", "final", "finally", "friend",
+    "from", "get", "goto", "highp", "impl", "implements", "import", "inline", "instanceof", "interface",
+    "layout", "lowp", "macro", "match", "mediump", "meta", "mod", "module", "move", "mut", "mutable",
+    "namespace", "new", "nil", "noexcept", "noinline", "null", "nullptr", "of", "operator", "package",
+    "partition", "pass", "patch", "precise", "precision", "priv", "protected", "pub", "public",
+    "readonly", "ref", "register", "reinterpret_cast", "require", "resource", "restrict", "self", "set",
+    "shared", "sizeof", "smooth", "static", "static_assert", "static_cast", "std", "subroutine", "super",
+    "target", "template", "this", "thread_local", "throw", "trait", "try", "type", "typedef", "typeid",
+    "typename", "typeof", "union", "unless", "unsafe", "unsized", "use", "using", "varying", "virtual",
+    "volatile", "wgsl", "where", "with", "writeonly", "yield",
+];
+
+/// Profile for generating WGSL shader code
+///
+/// Identifiers are restricted to ASCII, and names starting with `__` (which
+/// WGSL reserves) get a `v` prefix. Default values use zero-value
+/// constructors (`vec3<f32>()`), and vector properties become `vecN<f32>`
+/// constructors. WGSL has no strings or maps, so those properties can't be
+/// formatted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WgslProfile;
+
+impl LanguageProfile for WgslProfile {
+    fn name(&self) -> &str {
+        "wgsl"
+    }
+
+    fn is_identifier_start(&self, c: char) -> bool {
+        c.is_ascii_alphabetic() || c == '_'
+    }
+
+    fn is_identifier_continue(&self, c: char) -> bool {
+        c.is_ascii_alphanumeric() || c == '_'
+    }
+
+    fn reserved_words(&self) -> &[&str] {
+        WGSL_RESERVED_WORDS
+    }
+
+    fn sanitize_identifier(&self, name: &str) -> String {
+        let mut identifier: String = name
+            .chars()
+            .map(|c| if self.is_identifier_continue(c) { c } else { '_' })
+            .collect();
+
+        if !identifier.chars().next().is_some_and(|c| self.is_identifier_start(c)) {
+            identifier.insert(0, '_');
+        }
+        if self.is_reserved(&identifier) {
+            identifier.push('_');
+        }
+        if identifier.starts_with("__") {
+            identifier.insert(0, 'v');
+        }
+
+        identifier
+    }
+
+    fn default_value(&self, type_str: &str) -> String {
+        match type_str {
+            "f32" | "f16" => "0.0".to_string(),
+            "i32" => "0i".to_string(),
+            "u32" => "0u".to_string(),
+            "bool" => "false".to_string(),
+            // Every constructible WGSL type has a zero-value constructor
+            _ => format!("{}()", type_str),
+        }
+    }
+
+    fn format_literal(&self, value: &PropertyValue) -> Result<String, GraphyError> {
+        let literal = match value {
+            PropertyValue::Number(n) => wgsl_float(*n),
+            PropertyValue::Integer(i) => i.to_string(),
+            PropertyValue::Boolean(b) => b.to_string(),
+            PropertyValue::Vector2(x, y) => format!("vec2<f32>({}, {})", wgsl_float(*x), wgsl_float(*y)),
+            PropertyValue::Vector3(x, y, z) => {
+                format!("vec3<f32>({}, {}, {})", wgsl_float(*x), wgsl_float(*y), wgsl_float(*z))
+            }
+            PropertyValue::Color(r, g, b, a) => format!(
+                "vec4<f32>({}, {}, {}, {})",
+                wgsl_float(*r),
+                wgsl_float(*g),
+                wgsl_float(*b),
+                wgsl_float(*a)
+            ),
+            PropertyValue::Array(items) if !items.is_empty() => {
+                let items = items
+                    .iter()
+                    .map(|item| self.format_literal(item))
+                    .collect::<Result<Vec<_>, _>>()?;
+                format!("array({})", items.join(", "))
+            }
+            PropertyValue::Enum { variant, payload: None } => variant.clone(),
+            PropertyValue::String(_)
+            | PropertyValue::Map(_)
+            | PropertyValue::Array(_)
+            | PropertyValue::Enum { .. } => {
+                return Err(GraphyError::CodeGeneration(format!(
+                    "WGSL has no literal for property value {:?}",
+                    value
+                )))
+            }
+        };
+
+        Ok(literal)
+    }
+}
+
+/// Format a float so WGSL always reads it as a floating point literal
+fn wgsl_float(n: f64) -> String {
+    // Debug formatting keeps the `.0` on whole numbers
+    format!("{:?}", n)
+}
+
+// ============================================================================
+// C
+// ============================================================================
+
+const C_RESERVED_WORDS: &[&str] = &[
+    // C keywords
+    "auto", "break", "case", "char", "const", "continue", "default", "do", "double", "else", "enum",
+    "extern", "float", "for", "goto", "if", "inline", "int", "long", "register", "restrict", "return",
+    "short", "signed", "sizeof", "static", "struct", "switch", "typedef", "union", "unsigned", "void",
+    "volatile", "while", "_Alignas", "_Alignof", "_Atomic", "_Bool", "_Complex", "_Generic",
+    "_Imaginary", "_Noreturn", "_Static_assert", "_Thread_local",
+    // Macros from the standard headers the generated code includes
+    "bool", "true", "false", "NULL",
+    // C++ keywords, so headers stay usable from C++
+    "alignas", "alignof", "and", "asm", "catch", "class", "constexpr", "const_cast", "decltype",
+    "delete", "dynamic_cast", "explicit", "export", "friend", "mutable", "namespace", "new", "noexcept",
+    "not", "nullptr", "operator", "or", "private", "protected", "public", "reinterpret_cast",
+    "static_assert", "static_cast", "template", "this", "throw", "try", "typeid", "typename", "using",
+    "virtual", "xor",
+];
+
+/// Graph types and their C names in a new [`CProfile`]
+const C_TYPES: &[(&str, &str)] = &[
+    ("i8", "int8_t"),
+    ("i16", "int16_t"),
+    ("i32", "int32_t"),
+    ("i64", "int64_t"),
+    ("isize", "ptrdiff_t"),
+    ("u8", "uint8_t"),
+    ("u16", "uint16_t"),
+    ("u32", "uint32_t"),
+    ("u64", "uint64_t"),
+    ("usize", "size_t"),
+    ("f32", "float"),
+    ("f64", "double"),
+    ("bool", "bool"),
+    ("char", "char"),
+    ("String", "const char*"),
+    ("&str", "const char*"),
+    ("()", "void"),
+];
+
+/// Profile for generating C code
+///
+/// Identifiers are restricted to ASCII, and C++ keywords are reserved too,
+/// so generated headers can be included from C++. Graph types are mapped to
+/// C types through a table that starts with the fixed-width integers of
+/// `<stdint.h>`, `float`/`double`, `bool`, and `const char*` for strings;
+/// [`with_type`](Self::with_type) adds or replaces entries. Types missing
+/// from the table are used unchanged.
+///
+/// Vectors, colors, arrays, maps, and enum payloads have no C literal.
+///
+/// # Example
+///
+/// ```
+/// use graphy::utils::{CProfile, LanguageProfile};
+///
+/// let profile = CProfile::new().with_type("Vec3", "vec3_t");
+/// assert_eq!(profile.map_type("i64"), "int64_t");
+/// assert_eq!(profile.map_type("Vec3"), "vec3_t");
+/// assert_eq!(profile.default_value("Vec3"), "(vec3_t){0}");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CProfile {
+    types: BTreeMap<String, String>,
+}
+
+impl Default for CProfile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CProfile {
+    /// Create a profile with the default type table
+    pub fn new() -> Self {
+        Self {
+            types: C_TYPES
+                .iter()
+                .map(|(graph_type, c_type)| (graph_type.to_string(), c_type.to_string()))
+                .collect(),
+        }
+    }
+
+    /// Map a graph type to a C type
+    #[inline]
+    #[must_use]
+    pub fn with_type(mut self, graph_type: impl Into<String>, c_type: impl Into<String>) -> Self {
+        self.types.insert(graph_type.into(), c_type.into());
+        self
+    }
+
+    /// Get the type table, sorted by graph type
+    #[inline]
+    pub fn types(&self) -> &BTreeMap<String, String> {
+        &self.types
+    }
+}
+
+impl LanguageProfile for CProfile {
+    fn name(&self) -> &str {
+        "c"
+    }
+
+    fn is_identifier_start(&self, c: char) -> bool {
+        c.is_ascii_alphabetic() || c == '_'
+    }
+
+    fn is_identifier_continue(&self, c: char) -> bool {
+        c.is_ascii_alphanumeric() || c == '_'
+    }
+
+    fn reserved_words(&self) -> &[&str] {
+        C_RESERVED_WORDS
+    }
+
+    fn map_type(&self, type_str: &str) -> String {
+        self.types.get(type_str).cloned().unwrap_or_else(|| type_str.to_string())
+    }
+
+    fn default_value(&self, type_str: &str) -> String {
+        match self.map_type(type_str).as_str() {
+            "float" => "0.0f".to_string(),
+            "double" => "0.0".to_string(),
+            "bool" => "false".to_string(),
+            "char" => "'\\0'".to_string(),
+            "const char*" => "\"\"".to_string(),
+            "int8_t" | "int16_t" | "int32_t" | "int64_t" | "uint8_t" | "uint16_t" | "uint32_t" | "uint64_t"
+            | "size_t" | "ptrdiff_t" | "int" | "long" | "short" | "unsigned" => "0".to_string(),
+            // Compound literal zeroing every member
+            c_type => format!("({}){{0}}", c_type),
+        }
+    }
+
+    fn format_literal(&self, value: &PropertyValue) -> Result<String, GraphyError> {
+        let literal = match value {
+            PropertyValue::String(s) => c_string(s),
+            PropertyValue::Number(n) if n.is_finite() => format!("{:?}", n),
+            PropertyValue::Integer(i) => i.to_string(),
+            PropertyValue::Boolean(b) => b.to_string(),
+            PropertyValue::Enum { variant, payload: None } => variant.clone(),
+            PropertyValue::Number(_)
+            | PropertyValue::Vector2(..)
+            | PropertyValue::Vector3(..)
+            | PropertyValue::Color(..)
+            | PropertyValue::Array(_)
+            | PropertyValue::Map(_)
+            | PropertyValue::Enum { .. } => {
+                return Err(GraphyError::CodeGeneration(format!(
+                    "C has no literal for property value {:?}",
+                    value
+                )))
+            }
+        };
+
+        Ok(literal)
+    }
+}
+
+/// Format a string as a C string literal
+///
+/// Bytes outside printable ASCII use octal escapes, which (unlike hex
+/// escapes) can't swallow the characters after them.
+fn c_string(s: &str) -> String {
+    let mut literal = String::from("\"");
+    for byte in s.bytes() {
+        match byte {
+            b'"' => literal.push_str("\\\""),
+            b'\\' => literal.push_str("\\\\"),
+            b'\n' => literal.push_str("\\n"),
+            b'\r' => literal.push_str("\\r"),
+            b'\t' => literal.push_str("\\t"),
+            b' '..=b'~' => literal.push(byte as char),
+            _ => literal.push_str(&format!("\\{:03o}", byte)),
+        }
+    }
+    literal.push('"');
+    literal
+}
@@ -0,0 +1,80 @@
+//! # Logging Verbosity
+//!
+//! Controls how much Graphy logs, independently of the `tracing` filter.
+//!
+//! Phase summaries are logged at `debug` level inside per-phase spans.
+//! Per-item logs (every execution route, every AST substitution) are
+//! logged at `trace` level, and only inside a [`Verbosity::Verbose`] scope:
+//! outside one, their arguments aren't even formatted, which matters on
+//! graphs with tens of thousands of nodes.
+//!
+//! The compile driver opens a scope with the verbosity of its
+//! `CompileOptions`; code calling analyses directly can use
+//! [`with_verbosity`].
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::utils::logging::{self, Verbosity};
+//!
+//! assert!(!logging::is_verbose());
+//! logging::with_verbosity(Verbosity::Verbose, || {
+//!     assert!(logging::is_verbose());
+//! });
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+
+/// How much Graphy logs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verbosity {
+    /// No logs or spans at all
+    Quiet,
+
+    /// Phase spans and summaries
+    #[default]
+    Normal,
+
+    /// Phase spans and summaries plus per-item `trace` logs
+    Verbose,
+}
+
+thread_local! {
+    static VERBOSITY: Cell<Verbosity> = const { Cell::new(Verbosity::Normal) };
+}
+
+/// Returns the verbosity of the current thread.
+#[inline]
+pub fn verbosity() -> Verbosity {
+    VERBOSITY.with(Cell::get)
+}
+
+/// Checks if per-item logs should be emitted on the current thread.
+#[inline]
+pub fn is_verbose() -> bool {
+    verbosity() == Verbosity::Verbose
+}
+
+/// Runs a closure with the given verbosity on the current thread.
+///
+/// With [`Verbosity::Quiet`], the closure also runs without a `tracing`
+/// subscriber, so nothing it does is logged. The previous verbosity is
+/// restored afterwards, even if the closure panics.
+pub fn with_verbosity<R>(verbosity: Verbosity, f: impl FnOnce() -> R) -> R {
+    struct Restore(Verbosity);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            VERBOSITY.with(|cell| cell.set(self.0));
+        }
+    }
+
+    let _restore = Restore(VERBOSITY.with(|cell| cell.replace(verbosity)));
+    if verbosity == Verbosity::Quiet {
+        tracing::subscriber::with_default(tracing::subscriber::NoSubscriber::default(), f)
+    } else {
+        f()
+    }
+}
@@ -0,0 +1,359 @@
+//! # Memory Accounting
+//!
+//! Approximate heap usage of graphs and analysis results.
+//!
+//! [`HeapSize`] reports the bytes a value owns on the heap, following
+//! `String`s, `Vec`s and hash maps recursively. Sizes are computed from
+//! capacities and element layouts, so they're exact for vectors and
+//! strings and close for hash maps (whose bucket layout is an
+//! implementation detail of the standard library). Allocator overhead is
+//! not included.
+//!
+//! [`MemoryStats`] breaks a structure down into named components, for
+//! display in profilers. For real allocation counts, enable the
+//! `heap-stats` feature and see `utils::heap_stats`.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::{GraphDescription, NodeInstance, Position};
+//!
+//! let mut graph = GraphDescription::new("example");
+//! let empty = graph.approx_memory_bytes();
+//!
+//! graph.add_node(NodeInstance::new("add_1", "math.add", Position::zero()));
+//! assert!(graph.approx_memory_bytes() > empty);
+//! ```
+
+use crate::core::{
+    BuildCondition, Connection, ConnectionType, DataType, ExternalDecl, GraphComment, GraphDescription, GraphGroup, GraphMetadata, GraphParam,
+    NodeInstance, Pin, PinInstance, PinType, Position, PropertyValue, TypeInfo, VariableDecl,
+};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::mem::size_of;
+use std::sync::Arc;
+
+/// Values that can report the heap memory they own.
+pub trait HeapSize {
+    /// Returns the number of heap bytes owned by this value.
+    ///
+    /// Does not include `size_of_val(self)`; add it for values that are
+    /// themselves boxed or stored inline in a container.
+    fn heap_bytes(&self) -> usize;
+}
+
+/// Named breakdown of the memory used by a structure.
+///
+/// # Example
+///
+/// ```
+/// use graphy::utils::MemoryStats;
+///
+/// let mut stats = MemoryStats::new();
+/// stats.add("nodes", 1024);
+/// stats.add("connections", 512);
+/// assert_eq!(stats.total_bytes(), 1536);
+/// assert_eq!(stats.get("nodes"), Some(1024));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryStats {
+    /// `(component, bytes)` pairs, in insertion order
+    pub components: Vec<(&'static str, usize)>,
+}
+
+impl MemoryStats {
+    /// Creates empty stats.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a component.
+    pub fn add(&mut self, component: &'static str, bytes: usize) {
+        self.components.push((component, bytes));
+    }
+
+    /// Returns the bytes of a component, if present.
+    pub fn get(&self, component: &str) -> Option<usize> {
+        self.components
+            .iter()
+            .find(|(name, _)| *name == component)
+            .map(|&(_, bytes)| bytes)
+    }
+
+    /// Returns the sum of all components.
+    pub fn total_bytes(&self) -> usize {
+        self.components.iter().map(|&(_, bytes)| bytes).sum()
+    }
+}
+
+impl fmt::Display for MemoryStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, bytes) in &self.components {
+            writeln!(f, "{}: {} bytes", name, bytes)?;
+        }
+        write!(f, "total: {} bytes", self.total_bytes())
+    }
+}
+
+// ===== Standard library types =====
+
+macro_rules! impl_heap_size_inline {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl HeapSize for $ty {
+                #[inline(always)]
+                fn heap_bytes(&self) -> usize {
+                    0
+                }
+            }
+        )*
+    };
+}
+
+impl_heap_size_inline!(bool, u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64, char);
+impl_heap_size_inline!(Position, PinType, ConnectionType);
+
+impl HeapSize for String {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl HeapSize for Arc<str> {
+    /// Counts the shared allocation (two reference counts plus the text)
+    /// once per handle, so shared names are counted by every owner.
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        2 * size_of::<usize>() + self.len()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Option<T> {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        self.as_ref().map_or(0, HeapSize::heap_bytes)
+    }
+}
+
+impl<T: HeapSize> HeapSize for Box<T> {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        size_of::<T>() + (**self).heap_bytes()
+    }
+}
+
+impl<A: HeapSize, B: HeapSize> HeapSize for (A, B) {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        self.0.heap_bytes() + self.1.heap_bytes()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Vec<T> {
+    fn heap_bytes(&self) -> usize {
+        self.capacity() * size_of::<T>() + self.iter().map(HeapSize::heap_bytes).sum::<usize>()
+    }
+}
+
+/// Approximate table size of a hash map or set with the given capacity
+/// and entry size (buckets plus one control byte per bucket and a group
+/// of trailing control bytes).
+fn hash_table_bytes(capacity: usize, entry_size: usize) -> usize {
+    if capacity == 0 {
+        return 0;
+    }
+    let buckets = if capacity < 8 {
+        (capacity + 1).next_power_of_two()
+    } else {
+        (capacity * 8 / 7).next_power_of_two()
+    };
+    buckets * (entry_size + 1) + 16
+}
+
+impl<K: HeapSize, V: HeapSize, S> HeapSize for HashMap<K, V, S> {
+    fn heap_bytes(&self) -> usize {
+        hash_table_bytes(self.capacity(), size_of::<(K, V)>())
+            + self
+                .iter()
+                .map(|(key, value)| key.heap_bytes() + value.heap_bytes())
+                .sum::<usize>()
+    }
+}
+
+impl<T: HeapSize, S> HeapSize for HashSet<T, S> {
+    fn heap_bytes(&self) -> usize {
+        hash_table_bytes(self.capacity(), size_of::<T>()) + self.iter().map(HeapSize::heap_bytes).sum::<usize>()
+    }
+}
+
+// ===== Graph types =====
+
+impl HeapSize for TypeInfo {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        self.type_string.heap_bytes()
+    }
+}
+
+impl HeapSize for DataType {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        match self {
+            DataType::Typed(type_info) => type_info.heap_bytes(),
+            _ => 0,
+        }
+    }
+}
+
+impl HeapSize for PropertyValue {
+    fn heap_bytes(&self) -> usize {
+        match self {
+            PropertyValue::String(value) => value.heap_bytes(),
+            PropertyValue::Array(values) => values.heap_bytes(),
+            PropertyValue::Map(values) => values.heap_bytes(),
+            PropertyValue::Enum { variant, payload } => variant.heap_bytes() + payload.heap_bytes(),
+            PropertyValue::Number(_)
+            | PropertyValue::Boolean(_)
+            | PropertyValue::Vector2(..)
+            | PropertyValue::Vector3(..)
+            | PropertyValue::Color(..)
+            | PropertyValue::Integer(_) => 0,
+        }
+    }
+}
+
+impl HeapSize for Pin {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        self.id.heap_bytes() + self.name.heap_bytes() + self.data_type.heap_bytes()
+    }
+}
+
+impl HeapSize for PinInstance {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        self.id.heap_bytes() + self.pin.heap_bytes()
+    }
+}
+
+impl HeapSize for NodeInstance {
+    fn heap_bytes(&self) -> usize {
+        self.id.heap_bytes()
+            + self.node_type.heap_bytes()
+            + self.inputs.heap_bytes()
+            + self.outputs.heap_bytes()
+            + self.properties.heap_bytes()
+            + self.condition.heap_bytes()
+    }
+}
+
+impl HeapSize for BuildCondition {
+    fn heap_bytes(&self) -> usize {
+        match self {
+            BuildCondition::Feature(name) | BuildCondition::Platform(name) => name.heap_bytes(),
+            BuildCondition::All(conditions) | BuildCondition::Any(conditions) => conditions.heap_bytes(),
+            BuildCondition::Not(condition) => condition.heap_bytes(),
+        }
+    }
+}
+
+impl HeapSize for Connection {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        self.source_node.heap_bytes()
+            + self.source_pin.heap_bytes()
+            + self.target_node.heap_bytes()
+            + self.target_pin.heap_bytes()
+    }
+}
+
+impl HeapSize for GraphMetadata {
+    fn heap_bytes(&self) -> usize {
+        self.name.heap_bytes()
+            + self.description.heap_bytes()
+            + self.version.heap_bytes()
+            + self.created_at.heap_bytes()
+            + self.modified_at.heap_bytes()
+    }
+}
+
+impl HeapSize for GraphComment {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        self.text.heap_bytes() + self.attached_node_ids.heap_bytes()
+    }
+}
+
+impl HeapSize for GraphParam {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        self.name.heap_bytes() + self.type_info.heap_bytes()
+    }
+}
+
+impl HeapSize for GraphGroup {
+    fn heap_bytes(&self) -> usize {
+        self.id.heap_bytes() + self.title.heap_bytes() + self.color.heap_bytes() + self.node_ids.heap_bytes() + self.condition.heap_bytes()
+    }
+}
+
+impl HeapSize for VariableDecl {
+    #[inline]
+    fn heap_bytes(&self) -> usize {
+        self.type_info.heap_bytes() + self.default.heap_bytes()
+    }
+}
+
+impl HeapSize for ExternalDecl {
+    fn heap_bytes(&self) -> usize {
+        self.name.heap_bytes() + self.module.heap_bytes() + self.params.heap_bytes() + self.return_type.heap_bytes()
+    }
+}
+
+impl HeapSize for GraphDescription {
+    fn heap_bytes(&self) -> usize {
+        self.memory_stats().total_bytes() - size_of::<Self>()
+    }
+}
+
+impl GraphDescription {
+    /// Returns the approximate number of bytes used by the graph, including
+    /// the `GraphDescription` itself.
+    ///
+    /// See the [module docs](crate::utils::memory) for what is counted.
+    pub fn approx_memory_bytes(&self) -> usize {
+        self.memory_stats().total_bytes()
+    }
+
+    /// Returns the memory used by the graph, broken down by component.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use graphy::{Connection, GraphDescription};
+    ///
+    /// let mut graph = GraphDescription::new("example");
+    /// graph.add_connection(Connection::data("a", "result", "b", "value"));
+    ///
+    /// let stats = graph.memory_stats();
+    /// assert!(stats.get("connections").unwrap() > 0);
+    /// assert_eq!(stats.total_bytes(), graph.approx_memory_bytes());
+    /// ```
+    pub fn memory_stats(&self) -> MemoryStats {
+        let mut stats = MemoryStats::new();
+        stats.add("graph", size_of::<Self>() + self.metadata.heap_bytes());
+        stats.add("nodes", self.nodes.heap_bytes());
+        stats.add("connections", self.connections.heap_bytes());
+        stats.add("comments", self.comments.heap_bytes());
+        stats.add(
+            "params",
+            self.graph_inputs.heap_bytes() + self.graph_outputs.heap_bytes(),
+        );
+        stats.add("groups", self.groups.heap_bytes());
+        stats.add("variables", self.variables.heap_bytes());
+        stats.add("externals", self.externals.heap_bytes());
+        stats
+    }
+}
@@ -0,0 +1,563 @@
+//! # Graph Merging
+//!
+//! Three-way structural merge of graphs.
+//!
+//! [`merge_graphs`] combines two versions of a graph (`ours` and `theirs`)
+//! that both descend from a common `base`, the way a version control system
+//! merges text, but at the granularity of the graph itself:
+//!
+//! - Nodes are matched by ID. When both sides modify the same node, its type,
+//!   position, pins, and each property are merged independently.
+//! - Connections and comments are merged as multisets: additions and removals
+//!   from both sides are combined. Connections with an ID are also matched
+//!   by it, so edits to their endpoints or editor metadata merge like node
+//!   edits. Comments keep their attachments, except to nodes the merge
+//!   removed.
+//! - Groups and variables are matched by ID and name; graph inputs, outputs,
+//!   externals, and metadata fields are merged as whole values.
+//!
+//! A change made on only one side is always taken. A [`MergeConflict`] is
+//! reported when both sides change the same thing differently, when one side
+//! modifies a node the other removed, or when a connection ends up
+//! referring to a node that no longer exists.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::{GraphDescription, NodeInstance, Position, PropertyValue};
+//! use graphy::utils::merge_graphs;
+//!
+//! let mut base = GraphDescription::new("graph");
+//! base.add_node(NodeInstance::new("add_1", "math.add", Position::zero()));
+//!
+//! let mut ours = base.clone();
+//! ours.add_node(NodeInstance::new("print_1", "print", Position::zero()));
+//!
+//! let mut theirs = base.clone();
+//! theirs
+//!     .get_node_mut("add_1")
+//!     .unwrap()
+//!     .set_property("b", PropertyValue::Number(2.0));
+//!
+//! let merged = merge_graphs(&base, &ours, &theirs).unwrap();
+//! assert!(merged.get_node("print_1").is_some());
+//! assert_eq!(merged.get_node("add_1").unwrap().get_property("b"), Some(&PropertyValue::Number(2.0)));
+//! ```
+
+use super::diff::GraphDiff;
+use crate::core::{Connection, GraphDescription, NodeInstance, PropertyValue};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::hash::Hash;
+
+/// One of the two sides of a merge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MergeSide {
+    /// The local version (`ours`)
+    Ours,
+
+    /// The incoming version (`theirs`)
+    Theirs,
+}
+
+/// A change that can't be merged automatically.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MergeConflict {
+    /// Both sides added a node with the same ID but different contents
+    NodeAdded { node_id: String },
+
+    /// One side removed a node the other side modified
+    NodeRemoved { node_id: String, removed_by: MergeSide },
+
+    /// Both sides changed the same field of a node (`node_type`,
+    /// `position`, `inputs`, or `outputs`) differently
+    NodeField { node_id: String, field: String },
+
+    /// Both sides changed the same property differently
+    ///
+    /// `None` means the property is absent on that side.
+    Property {
+        node_id: String,
+        key: String,
+        base: Option<PropertyValue>,
+        ours: Option<PropertyValue>,
+        theirs: Option<PropertyValue>,
+    },
+
+    /// Both sides changed the connection with this ID differently, or one
+    /// side removed it and the other changed it
+    Connection { connection_id: String },
+
+    /// A connection refers to a node that isn't in the merged graph
+    DanglingConnection { connection: Connection, missing_node: String },
+
+    /// Both sides changed the same group differently
+    Group { group_id: String },
+
+    /// Both sides changed the same variable declaration differently
+    Variable { name: String },
+
+    /// Both sides changed a graph-level field (a metadata field,
+    /// `graph_inputs`, or `graph_outputs`) differently
+    GraphField { field: String },
+}
+
+impl fmt::Display for MergeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeConflict::NodeAdded { node_id } => {
+                write!(f, "Node {} was added on both sides with different contents", node_id)
+            }
+            MergeConflict::NodeRemoved { node_id, removed_by } => {
+                let (removed, modified) = match removed_by {
+                    MergeSide::Ours => ("ours", "theirs"),
+                    MergeSide::Theirs => ("theirs", "ours"),
+                };
+                write!(f, "Node {} was removed in {} but modified in {}", node_id, removed, modified)
+            }
+            MergeConflict::NodeField { node_id, field } => {
+                write!(f, "Both sides changed {} of node {}", field, node_id)
+            }
+            MergeConflict::Property { node_id, key, .. } => {
+                write!(f, "Both sides changed property {}.{}", node_id, key)
+            }
+            MergeConflict::Connection { connection_id } => {
+                write!(f, "Both sides changed connection {}", connection_id)
+            }
+            MergeConflict::DanglingConnection { connection, missing_node } => {
+                write!(f, "Connection {} refers to removed node {}", connection, missing_node)
+            }
+            MergeConflict::Group { group_id } => write!(f, "Both sides changed group {}", group_id),
+            MergeConflict::Variable { name } => write!(f, "Both sides changed variable {}", name),
+            MergeConflict::GraphField { field } => write!(f, "Both sides changed graph {}", field),
+        }
+    }
+}
+
+/// The result of a merge that resolves conflicts, see [`merge_graphs_with`].
+#[derive(Debug, Clone)]
+pub struct MergeOutcome {
+    /// The merged graph, with every conflict resolved toward the preferred side
+    pub graph: GraphDescription,
+
+    /// Conflicts that were resolved, in a stable order
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl MergeOutcome {
+    /// Checks if the merge had no conflicts.
+    #[inline]
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Merges two versions of a graph descending from a common base.
+///
+/// # Errors
+///
+/// Returns every [`MergeConflict`] if any part of the graph was changed
+/// differently on both sides.
+pub fn merge_graphs(
+    base: &GraphDescription,
+    ours: &GraphDescription,
+    theirs: &GraphDescription,
+) -> Result<GraphDescription, Vec<MergeConflict>> {
+    let outcome = merge_graphs_with(base, ours, theirs, MergeSide::Ours);
+    if outcome.is_clean() {
+        Ok(outcome.graph)
+    } else {
+        Err(outcome.conflicts)
+    }
+}
+
+/// Merges two versions of a graph, resolving conflicts toward one side.
+///
+/// Each conflict takes the preferred side's version of the conflicting
+/// item, so a modify/remove conflict keeps the node if the preferred side
+/// modified it and drops it if that side removed it. Connections to nodes
+/// missing from the merged graph are always dropped. The resolved
+/// conflicts are returned along with the graph so tools can show them.
+///
+/// # Example
+///
+/// ```
+/// use graphy::{GraphDescription, NodeInstance, Position};
+/// use graphy::utils::{merge_graphs_with, MergeSide};
+///
+/// let base = GraphDescription::new("graph");
+/// let mut ours = base.clone();
+/// ours.add_node(NodeInstance::new("node_1", "math.add", Position::zero()));
+/// let mut theirs = base.clone();
+/// theirs.add_node(NodeInstance::new("node_1", "math.sub", Position::zero()));
+///
+/// let outcome = merge_graphs_with(&base, &ours, &theirs, MergeSide::Theirs);
+/// assert_eq!(outcome.conflicts.len(), 1);
+/// assert_eq!(outcome.graph.get_node("node_1").unwrap().node_type, "math.sub");
+/// ```
+pub fn merge_graphs_with(
+    base: &GraphDescription,
+    ours: &GraphDescription,
+    theirs: &GraphDescription,
+    prefer: MergeSide,
+) -> MergeOutcome {
+    let mut merger = Merger {
+        prefer,
+        conflicts: Vec::new(),
+    };
+    let graph = merger.merge(base, ours, theirs);
+
+    MergeOutcome {
+        graph,
+        conflicts: merger.conflicts,
+    }
+}
+
+/// Collects conflicts while merging
+struct Merger {
+    prefer: MergeSide,
+    conflicts: Vec<MergeConflict>,
+}
+
+impl Merger {
+    fn merge(&mut self, base: &GraphDescription, ours: &GraphDescription, theirs: &GraphDescription) -> GraphDescription {
+        let mut merged = ours.clone();
+        merged.format_version = ours.format_version.max(theirs.format_version);
+
+        self.merge_metadata(&mut merged, base, theirs);
+
+        merged.nodes = self.merge_nodes(base, ours, theirs);
+
+        let ours_diff = GraphDiff::compute(base, ours);
+        let theirs_diff = GraphDiff::compute(base, theirs);
+        merged.connections = self.merge_connections(ours, &ours_diff, &theirs_diff);
+        merged.comments = merge_sequence(
+            &ours.comments,
+            &ours_diff.added_comments,
+            &theirs_diff.added_comments,
+            &theirs_diff.removed_comments,
+        );
+        self.drop_dangling_connections(&mut merged);
+        for comment in &mut merged.comments {
+            comment.attached_node_ids.retain(|id| merged.nodes.contains_key(id));
+        }
+
+        merged.graph_inputs = self
+            .merge_field("graph_inputs", &base.graph_inputs, &ours.graph_inputs, &theirs.graph_inputs)
+            .clone();
+        merged.graph_outputs = self
+            .merge_field("graph_outputs", &base.graph_outputs, &ours.graph_outputs, &theirs.graph_outputs)
+            .clone();
+
+        merged.externals = self
+            .merge_field("externals", &base.externals, &ours.externals, &theirs.externals)
+            .clone();
+
+        merged.variables = merge_keyed(&base.variables, &ours.variables, &theirs.variables, |name| {
+            self.choose(MergeConflict::Variable { name: name.clone() })
+        });
+
+        let groups = |graph: &GraphDescription| graph.groups.iter().map(|g| (g.id.clone(), g.clone())).collect();
+        let mut merged_groups = merge_keyed(&groups(base), &groups(ours), &groups(theirs), |group_id| {
+            self.choose(MergeConflict::Group {
+                group_id: group_id.clone(),
+            })
+        });
+        // Keep our order, then the groups only theirs has
+        merged.groups = ours
+            .groups
+            .iter()
+            .chain(&theirs.groups)
+            .filter_map(|g| merged_groups.remove(&g.id))
+            .collect();
+
+        merged
+    }
+
+    fn merge_metadata(&mut self, merged: &mut GraphDescription, base: &GraphDescription, theirs: &GraphDescription) {
+        let (base, ours, theirs) = (&base.metadata, merged.metadata.clone(), &theirs.metadata);
+        let metadata = &mut merged.metadata;
+
+        metadata.name = self.merge_field("name", &base.name, &ours.name, &theirs.name).clone();
+        metadata.description = self
+            .merge_field("description", &base.description, &ours.description, &theirs.description)
+            .clone();
+        metadata.version = self.merge_field("version", &base.version, &ours.version, &theirs.version).clone();
+        metadata.created_at = self
+            .merge_field("created_at", &base.created_at, &ours.created_at, &theirs.created_at)
+            .clone();
+
+        // ISO 8601 timestamps order as strings; the later edit wins
+        if theirs.modified_at > ours.modified_at {
+            metadata.modified_at = theirs.modified_at.clone();
+        }
+    }
+
+    fn merge_nodes(
+        &mut self,
+        base: &GraphDescription,
+        ours: &GraphDescription,
+        theirs: &GraphDescription,
+    ) -> HashMap<String, NodeInstance> {
+        // Sorted so conflicts are reported in a stable order
+        let ids: BTreeSet<&String> = base.nodes.keys().chain(ours.nodes.keys()).chain(theirs.nodes.keys()).collect();
+        let mut nodes = HashMap::with_capacity(ids.len());
+
+        for id in ids {
+            let (b, o, t) = (base.nodes.get(id), ours.nodes.get(id), theirs.nodes.get(id));
+            let node = match (b, o, t) {
+                _ if o == t => o.cloned(),
+                _ if b == o => t.cloned(),
+                _ if b == t => o.cloned(),
+                (Some(b), Some(o), Some(t)) => Some(self.merge_node(b, o, t)),
+                (None, Some(o), Some(t)) => {
+                    let side = self.choose(MergeConflict::NodeAdded { node_id: id.clone() });
+                    Some(side.pick(o, t).clone())
+                }
+                (Some(_), o, t) => {
+                    let removed_by = if o.is_none() { MergeSide::Ours } else { MergeSide::Theirs };
+                    self.choose(MergeConflict::NodeRemoved {
+                        node_id: id.clone(),
+                        removed_by,
+                    })
+                    .pick(o, t)
+                    .cloned()
+                }
+                // Covered by the equality arms
+                (None, _, _) => None,
+            };
+
+            if let Some(node) = node {
+                nodes.insert(id.clone(), node);
+            }
+        }
+
+        nodes
+    }
+
+    /// Merges a node modified on both sides, field by field
+    fn merge_node(&mut self, base: &NodeInstance, ours: &NodeInstance, theirs: &NodeInstance) -> NodeInstance {
+        let mut merged = ours.clone();
+        let id = &ours.id;
+
+        merged.node_type = self
+            .merge_node_field(id, "node_type", &base.node_type, &ours.node_type, &theirs.node_type)
+            .clone();
+        merged.position = *self.merge_node_field(id, "position", &base.position, &ours.position, &theirs.position);
+        merged.inputs = self.merge_node_field(id, "inputs", &base.inputs, &ours.inputs, &theirs.inputs).clone();
+        // The variadic count describes the inputs, so it comes from the same side
+        if merged.inputs != ours.inputs {
+            merged.variadic_count = theirs.variadic_count;
+        }
+        merged.outputs = self.merge_node_field(id, "outputs", &base.outputs, &ours.outputs, &theirs.outputs).clone();
+        merged.condition = self
+            .merge_node_field(id, "condition", &base.condition, &ours.condition, &theirs.condition)
+            .clone();
+        merged.breakpoint = *self.merge_node_field(id, "breakpoint", &base.breakpoint, &ours.breakpoint, &theirs.breakpoint);
+        merged.trace = *self.merge_node_field(id, "trace", &base.trace, &ours.trace, &theirs.trace);
+        merged.watched_pins = merge_set(&base.watched_pins, &ours.watched_pins, &theirs.watched_pins);
+
+        merged.properties = merge_keyed(&base.properties, &ours.properties, &theirs.properties, |key| {
+            self.choose(MergeConflict::Property {
+                node_id: id.clone(),
+                key: key.clone(),
+                base: base.properties.get(key).cloned(),
+                ours: ours.properties.get(key).cloned(),
+                theirs: theirs.properties.get(key).cloned(),
+            })
+        });
+
+        merged
+    }
+
+    fn merge_node_field<'v, T: PartialEq>(
+        &mut self,
+        node_id: &str,
+        field: &str,
+        base: &'v T,
+        ours: &'v T,
+        theirs: &'v T,
+    ) -> &'v T {
+        merge3(base, ours, theirs).unwrap_or_else(|| {
+            self.choose(MergeConflict::NodeField {
+                node_id: node_id.to_string(),
+                field: field.to_string(),
+            })
+            .pick(ours, theirs)
+        })
+    }
+
+    fn merge_field<'v, T: PartialEq>(&mut self, field: &str, base: &'v T, ours: &'v T, theirs: &'v T) -> &'v T {
+        merge3(base, ours, theirs)
+            .unwrap_or_else(|| self.choose(MergeConflict::GraphField { field: field.to_string() }).pick(ours, theirs))
+    }
+
+    /// Merges connections as multisets, then applies edits to connections with IDs
+    fn merge_connections(&mut self, ours: &GraphDescription, ours_diff: &GraphDiff, theirs_diff: &GraphDiff) -> Vec<Connection> {
+        let mut merged = merge_sequence(
+            &ours.connections,
+            &ours_diff.added_connections,
+            &theirs_diff.added_connections,
+            &theirs_diff.removed_connections,
+        );
+
+        for change in &theirs_diff.modified_connections {
+            if let Some(connection) = merged.iter_mut().find(|c| **c == change.old) {
+                *connection = change.new.clone();
+                continue;
+            }
+
+            // Ours changed or removed it too
+            let index = merged.iter().position(|c| c.id.is_some() && c.id == change.new.id);
+            if index.is_some_and(|index| merged[index] == change.new) {
+                continue;
+            }
+            let side = self.choose(MergeConflict::Connection {
+                connection_id: change.new.id.clone().unwrap_or_default(),
+            });
+            if side == MergeSide::Theirs {
+                match index {
+                    Some(index) => merged[index] = change.new.clone(),
+                    None => merged.push(change.new.clone()),
+                }
+            }
+        }
+
+        // Theirs removed a connection ours changed
+        for removed in theirs_diff.removed_connections.iter().filter(|c| c.id.is_some()) {
+            let Some(change) = ours_diff.modified_connections.iter().find(|change| change.old == *removed) else {
+                continue;
+            };
+            let side = self.choose(MergeConflict::Connection {
+                connection_id: removed.id.clone().unwrap_or_default(),
+            });
+            if side == MergeSide::Theirs {
+                if let Some(index) = merged.iter().position(|c| *c == change.new) {
+                    merged.remove(index);
+                }
+            }
+        }
+
+        merged
+    }
+
+    /// Drops connections whose nodes didn't survive the merge
+    fn drop_dangling_connections(&mut self, merged: &mut GraphDescription) {
+        let nodes = &merged.nodes;
+        let conflicts = &mut self.conflicts;
+        merged.connections.retain(|connection| {
+            let missing = [&connection.source_node, &connection.target_node]
+                .into_iter()
+                .find(|id| !nodes.contains_key(*id));
+            match missing {
+                Some(missing_node) => {
+                    conflicts.push(MergeConflict::DanglingConnection {
+                        connection: connection.clone(),
+                        missing_node: missing_node.clone(),
+                    });
+                    false
+                }
+                None => true,
+            }
+        });
+    }
+
+    /// Records a conflict and returns the side it resolves to
+    fn choose(&mut self, conflict: MergeConflict) -> MergeSide {
+        self.conflicts.push(conflict);
+        self.prefer
+    }
+}
+
+impl MergeSide {
+    /// Returns the value of this side.
+    #[inline]
+    fn pick<T>(self, ours: T, theirs: T) -> T {
+        match self {
+            MergeSide::Ours => ours,
+            MergeSide::Theirs => theirs,
+        }
+    }
+}
+
+/// Three-way merge of a single value, `None` on conflict
+#[inline]
+fn merge3<'v, T: PartialEq>(base: &'v T, ours: &'v T, theirs: &'v T) -> Option<&'v T> {
+    if ours == theirs || base == theirs {
+        Some(ours)
+    } else if base == ours {
+        Some(theirs)
+    } else {
+        None
+    }
+}
+
+/// Three-way merge of a map, key by key
+///
+/// `on_conflict` records the conflict for a key and picks the side to keep.
+fn merge_keyed<K, V>(
+    base: &HashMap<K, V>,
+    ours: &HashMap<K, V>,
+    theirs: &HashMap<K, V>,
+    mut on_conflict: impl FnMut(&K) -> MergeSide,
+) -> HashMap<K, V>
+where
+    K: Clone + Eq + Hash + Ord,
+    V: Clone + PartialEq,
+{
+    // Sorted so conflicts are reported in a stable order
+    let keys: BTreeSet<&K> = base.keys().chain(ours.keys()).chain(theirs.keys()).collect();
+    let mut merged = HashMap::with_capacity(keys.len());
+
+    for key in keys {
+        let (b, o, t) = (base.get(key), ours.get(key), theirs.get(key));
+        let value = merge3(&b, &o, &t).copied().unwrap_or_else(|| on_conflict(key).pick(o, t));
+        if let Some(value) = value {
+            merged.insert(key.clone(), value.clone());
+        }
+    }
+
+    merged
+}
+
+/// Three-way merge of a set, item by item
+///
+/// Each item is kept as the side that changed it left it; no conflicts.
+fn merge_set<T: Clone + Ord>(base: &BTreeSet<T>, ours: &BTreeSet<T>, theirs: &BTreeSet<T>) -> BTreeSet<T> {
+    base.iter()
+        .chain(ours)
+        .chain(theirs)
+        .filter(|item| {
+            let in_ours = ours.contains(*item);
+            if in_ours != base.contains(*item) { in_ours } else { theirs.contains(*item) }
+        })
+        .cloned()
+        .collect()
+}
+
+/// Three-way merge of a multiset, keeping our order
+///
+/// Starts from our items, removes what theirs removed, and appends what
+/// theirs added unless ours added the same item too.
+fn merge_sequence<T: Clone + PartialEq>(ours: &[T], ours_added: &[T], theirs_added: &[T], theirs_removed: &[T]) -> Vec<T> {
+    let mut merged = ours.to_vec();
+
+    for item in theirs_removed {
+        if let Some(index) = merged.iter().position(|m| m == item) {
+            merged.remove(index);
+        }
+    }
+
+    let mut ours_added: Vec<&T> = ours_added.iter().collect();
+    for item in theirs_added {
+        match ours_added.iter().position(|added| *added == item) {
+            Some(index) => {
+                ours_added.swap_remove(index);
+            }
+            None => merged.push(item.clone()),
+        }
+    }
+
+    merged
+}
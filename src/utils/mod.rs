@@ -3,9 +3,38 @@
 //! Helper functions and utilities for graph manipulation and code generation.
 
 pub mod ast_transform;
+pub mod cancellation;
+pub mod diagnostics;
+pub mod diff;
+#[cfg(feature = "heap-stats")]
+pub mod heap_stats;
+pub mod history;
+pub mod index;
+pub mod language;
+pub mod logging;
+pub mod memory;
+pub mod merge;
+pub mod progress;
+pub mod rewrite;
+pub mod sanitize;
 pub mod subgraph_expander;
+pub mod subgraph_extractor;
+pub(crate) mod timing;
 pub mod variable_gen;
 
 pub use ast_transform::*;
+pub use cancellation::*;
+pub use diagnostics::*;
+pub use diff::*;
+pub use history::*;
+pub use index::*;
+pub use language::*;
+pub use logging::*;
+pub use memory::*;
+pub use merge::*;
+pub use progress::*;
+pub use rewrite::*;
+pub use sanitize::*;
 pub use subgraph_expander::*;
+pub use subgraph_extractor::*;
 pub use variable_gen::*;
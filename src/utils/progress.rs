@@ -0,0 +1,118 @@
+//! # Progress Reporting
+//!
+//! Progress callbacks for long-running analysis and code generation.
+//!
+//! A [`ProgressSink`] receives the name of the running phase with the number
+//! of items processed so far and the phase total, so UIs can show a progress
+//! bar while a large graph compiles. Each phase reports `0` when it starts
+//! and its total when it ends, and in between at most about a hundred times,
+//! so sinks don't need to throttle updates themselves.
+//!
+//! Closures taking `(phase, processed, total)` are sinks.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::utils::{ProgressSink, PHASE_DATA_FLOW};
+//!
+//! let sink = |phase: &str, processed: usize, total: usize| {
+//!     println!("{}: {}/{}", phase, processed, total);
+//! };
+//! sink.report(PHASE_DATA_FLOW, 0, 10);
+//! ```
+
+use std::fmt;
+
+/// Sub-graph expansion, counting graph nodes
+pub const PHASE_EXPANSION: &str = "expansion";
+
+/// Data source mapping of the data resolver, counting nodes
+pub const PHASE_DATA_FLOW: &str = "data_flow";
+
+/// Evaluation order of pure nodes, counting pure nodes
+pub const PHASE_EVALUATION_ORDER: &str = "evaluation_order";
+
+/// Execution routing, counting connections
+pub const PHASE_EXEC_ROUTING: &str = "exec_routing";
+
+/// Code generation, counting events
+pub const PHASE_CODEGEN: &str = "codegen";
+
+/// Receives progress updates while a graph is analyzed or compiled.
+///
+/// Sinks may be called from worker threads.
+pub trait ProgressSink: Send + Sync {
+    /// `processed` of `total` items of `phase` are done.
+    fn report(&self, phase: &str, processed: usize, total: usize);
+}
+
+impl<F> ProgressSink for F
+where
+    F: Fn(&str, usize, usize) + Send + Sync,
+{
+    #[inline]
+    fn report(&self, phase: &str, processed: usize, total: usize) {
+        self(phase, processed, total)
+    }
+}
+
+impl fmt::Debug for dyn ProgressSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressSink")
+    }
+}
+
+/// Sink that ignores every update.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoProgress;
+
+impl ProgressSink for NoProgress {
+    #[inline]
+    fn report(&self, _phase: &str, _processed: usize, _total: usize) {}
+}
+
+/// Reports one phase to a sink, skipping updates between intervals.
+pub(crate) struct PhaseProgress<'a> {
+    sink: &'a dyn ProgressSink,
+    phase: &'static str,
+    total: usize,
+    interval: usize,
+    processed: usize,
+    next_report: usize,
+}
+
+impl<'a> PhaseProgress<'a> {
+    /// Reports that the phase started.
+    pub(crate) fn start(sink: &'a dyn ProgressSink, phase: &'static str, total: usize) -> Self {
+        sink.report(phase, 0, total);
+        let interval = (total / 100).max(1);
+        Self {
+            sink,
+            phase,
+            total,
+            interval,
+            processed: 0,
+            next_report: interval,
+        }
+    }
+
+    /// Counts one processed item.
+    #[inline]
+    pub(crate) fn advance(&mut self) {
+        self.advance_by(1);
+    }
+
+    /// Counts `count` processed items.
+    pub(crate) fn advance_by(&mut self, count: usize) {
+        self.processed += count;
+        if self.processed >= self.next_report && self.processed < self.total {
+            self.sink.report(self.phase, self.processed, self.total);
+            self.next_report = self.processed + self.interval;
+        }
+    }
+
+    /// Reports that the phase is done.
+    pub(crate) fn finish(self) {
+        self.sink.report(self.phase, self.total, self.total);
+    }
+}
@@ -0,0 +1,417 @@
+//! # Graph Rewriting
+//!
+//! Rule-based rewriting of graphs, for peephole optimizations and node
+//! library migrations.
+//!
+//! A [`RewriteRule`] pairs a [`Pattern`] with a [`Replacement`]: a list of
+//! steps (remove, bypass, retype, set a property, or anything else) applied
+//! to the nodes of a match. [`apply_rules`] applies the rules until none of
+//! them changes the graph any more:
+//!
+//! 1. Rules are tried in order; the first rule with a match that changes the
+//!    graph is applied, and the search starts over from the first rule.
+//! 2. A match whose replacement leaves the graph's
+//!    [content hash](GraphDescription::content_hash) unchanged is skipped,
+//!    so rules that only move nodes, or that already did their work, don't
+//!    loop forever.
+//! 3. Rules that undo each other are caught: reaching a graph that was
+//!    already seen is an error, as is exceeding the rewrite limit.
+//!
+//! The graph is only modified if every rewrite succeeds.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::core::{Pattern, PatternConnection, PatternNode};
+//! use graphy::utils::{apply_rules, Replacement, RewriteRule};
+//! use graphy::{Connection, GraphDescription, NodeInstance, Position, PropertyValue};
+//!
+//! let mut graph = GraphDescription::new("graph");
+//! let mut one = NodeInstance::new("one", "math.constant", Position::zero());
+//! one.set_property("value", PropertyValue::Number(1.0));
+//! graph.add_node(one);
+//! for (id, node_type) in [("x", "input"), ("mul", "math.multiply"), ("print", "print")] {
+//!     graph.add_node(NodeInstance::new(id, node_type, Position::zero()));
+//! }
+//! graph.add_connection(Connection::data("x", "result", "mul", "a"));
+//! graph.add_connection(Connection::data("one", "result", "mul", "b"));
+//! graph.add_connection(Connection::data("mul", "result", "print", "value"));
+//!
+//! // x * 1 => x
+//! let multiply_by_one = RewriteRule::new(
+//!     "multiply_by_one",
+//!     Pattern::new()
+//!         .with_node("mul", PatternNode::of_type("math.multiply"))
+//!         .with_node(
+//!             "one",
+//!             PatternNode::of_type("math.constant").with_property("value", PropertyValue::Number(1.0)),
+//!         )
+//!         .with_connection(PatternConnection::new("one", "mul").to_pin("b")),
+//!     Replacement::new().bypass("mul", "a", "result").remove("one"),
+//! );
+//!
+//! let applied = apply_rules(&mut graph, &[multiply_by_one]).unwrap();
+//! assert_eq!(applied.len(), 1);
+//! assert_eq!(graph.nodes.len(), 2);
+//! assert_eq!(graph.connections[0].source_node, "x");
+//! assert_eq!(graph.connections[0].target_node, "print");
+//! ```
+
+use crate::core::{GraphDescription, Pattern, PatternMatch, PropertyValue};
+use crate::GraphyError;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+
+/// Rewrites [`apply_rules`] performs before giving up
+pub const DEFAULT_REWRITE_LIMIT: usize = 10_000;
+
+/// Custom rewrite step, given the graph and the match
+type RewriteFn =
+    Arc<dyn Fn(&mut GraphDescription, &PatternMatch) -> Result<(), GraphyError> + Send + Sync>;
+
+/// One step of a [`Replacement`]. Nodes are named by their pattern node
+/// names.
+#[derive(Clone)]
+pub enum RewriteStep {
+    /// Remove a node and its connections
+    Remove {
+        /// Pattern node name
+        node: String,
+    },
+
+    /// Connect the readers of an output pin to whatever feeds an input pin,
+    /// then remove the node
+    Bypass {
+        /// Pattern node name
+        node: String,
+
+        /// Input pin whose source takes over
+        input: String,
+
+        /// Output pin whose readers are moved
+        output: String,
+    },
+
+    /// Change a node's type, renaming or dropping pins as in
+    /// [`GraphDescription::replace_node`]
+    Retype {
+        /// Pattern node name
+        node: String,
+
+        /// New node type
+        node_type: String,
+
+        /// (old pin ID, new pin ID) pairs; unmapped pins are dropped
+        pin_mapping: Vec<(String, String)>,
+    },
+
+    /// Set a property of a node
+    SetProperty {
+        /// Pattern node name
+        node: String,
+
+        /// Property key
+        key: String,
+
+        /// New value
+        value: PropertyValue,
+    },
+
+    /// Run a function on the graph and the match
+    Custom(RewriteFn),
+}
+
+impl fmt::Debug for RewriteStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RewriteStep::Remove { node } => f.debug_struct("Remove").field("node", node).finish(),
+            RewriteStep::Bypass {
+                node,
+                input,
+                output,
+            } => f
+                .debug_struct("Bypass")
+                .field("node", node)
+                .field("input", input)
+                .field("output", output)
+                .finish(),
+            RewriteStep::Retype {
+                node,
+                node_type,
+                pin_mapping,
+            } => f
+                .debug_struct("Retype")
+                .field("node", node)
+                .field("node_type", node_type)
+                .field("pin_mapping", pin_mapping)
+                .finish(),
+            RewriteStep::SetProperty { node, key, value } => f
+                .debug_struct("SetProperty")
+                .field("node", node)
+                .field("key", key)
+                .field("value", value)
+                .finish(),
+            RewriteStep::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+/// What a [`RewriteRule`] does to a match: its steps, in order.
+#[derive(Debug, Clone, Default)]
+pub struct Replacement {
+    /// Steps to apply
+    pub steps: Vec<RewriteStep>,
+}
+
+impl Replacement {
+    /// Creates a replacement that does nothing.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Removes a node and its connections.
+    #[must_use]
+    pub fn remove(mut self, node: impl Into<String>) -> Self {
+        self.steps.push(RewriteStep::Remove { node: node.into() });
+        self
+    }
+
+    /// Moves the readers of `output` to the source of `input`, then removes
+    /// the node.
+    ///
+    /// Fails when applied if `input` isn't connected.
+    #[must_use]
+    pub fn bypass(
+        mut self,
+        node: impl Into<String>,
+        input: impl Into<String>,
+        output: impl Into<String>,
+    ) -> Self {
+        self.steps.push(RewriteStep::Bypass {
+            node: node.into(),
+            input: input.into(),
+            output: output.into(),
+        });
+        self
+    }
+
+    /// Changes a node's type, keeping the mapped pins.
+    #[must_use]
+    pub fn retype(
+        mut self,
+        node: impl Into<String>,
+        node_type: impl Into<String>,
+        pin_mapping: &[(&str, &str)],
+    ) -> Self {
+        self.steps.push(RewriteStep::Retype {
+            node: node.into(),
+            node_type: node_type.into(),
+            pin_mapping: pin_mapping
+                .iter()
+                .map(|(old, new)| (old.to_string(), new.to_string()))
+                .collect(),
+        });
+        self
+    }
+
+    /// Sets a property of a node.
+    #[must_use]
+    pub fn set_property(
+        mut self,
+        node: impl Into<String>,
+        key: impl Into<String>,
+        value: PropertyValue,
+    ) -> Self {
+        self.steps.push(RewriteStep::SetProperty {
+            node: node.into(),
+            key: key.into(),
+            value,
+        });
+        self
+    }
+
+    /// Runs a function on the graph and the match.
+    #[must_use]
+    pub fn custom(
+        mut self,
+        step: impl Fn(&mut GraphDescription, &PatternMatch) -> Result<(), GraphyError>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.steps.push(RewriteStep::Custom(Arc::new(step)));
+        self
+    }
+
+    /// Applies the steps to a match of `rule`.
+    fn apply(
+        &self,
+        rule: &str,
+        graph: &mut GraphDescription,
+        found: &PatternMatch,
+    ) -> Result<(), GraphyError> {
+        let bound = |name: &str| {
+            found.node(name).map(str::to_string).ok_or_else(|| {
+                GraphyError::Custom(format!(
+                    "Rewrite rule '{}' refers to unknown pattern node '{}'",
+                    rule, name
+                ))
+            })
+        };
+
+        for step in &self.steps {
+            match step {
+                RewriteStep::Remove { node } => {
+                    graph.remove_node(&bound(node)?);
+                }
+                RewriteStep::Bypass {
+                    node,
+                    input,
+                    output,
+                } => {
+                    let id = bound(node)?;
+                    let source = graph
+                        .connections
+                        .iter()
+                        .find(|c| c.target_node == id && c.target_pin == *input)
+                        .map(|c| (c.source_node.clone(), c.source_pin.clone()))
+                        .ok_or_else(|| GraphyError::UnconnectedInput {
+                            node: id.clone(),
+                            pin: input.clone(),
+                        })?;
+                    graph.rewire((&id, output), (&source.0, &source.1))?;
+                    graph.remove_node(&id);
+                }
+                RewriteStep::Retype {
+                    node,
+                    node_type,
+                    pin_mapping,
+                } => {
+                    let mapping: Vec<(&str, &str)> = pin_mapping
+                        .iter()
+                        .map(|(old, new)| (old.as_str(), new.as_str()))
+                        .collect();
+                    graph.replace_node(&bound(node)?, node_type.clone(), &mapping)?;
+                }
+                RewriteStep::SetProperty { node, key, value } => {
+                    let id = bound(node)?;
+                    graph
+                        .get_node_mut(&id)
+                        .ok_or(GraphyError::NodeNotFound(id))?
+                        .set_property(key.clone(), value.clone());
+                }
+                RewriteStep::Custom(step) => step(graph, found)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A pattern and what to replace its matches with.
+#[derive(Debug, Clone)]
+pub struct RewriteRule {
+    /// Name used in results and errors
+    pub name: String,
+
+    /// What to look for
+    pub pattern: Pattern,
+
+    /// What to do with each match
+    pub replacement: Replacement,
+}
+
+impl RewriteRule {
+    /// Creates a rule.
+    pub fn new(name: impl Into<String>, pattern: Pattern, replacement: Replacement) -> Self {
+        Self {
+            name: name.into(),
+            pattern,
+            replacement,
+        }
+    }
+}
+
+/// A rewrite [`apply_rules`] performed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedRewrite {
+    /// Name of the rule
+    pub rule: String,
+
+    /// The nodes it matched, before the rewrite
+    pub matched: PatternMatch,
+}
+
+/// Applies rules until none changes the graph, returning the rewrites in the
+/// order they were performed.
+///
+/// See the [module documentation](self) for how rules are chosen. Stops
+/// after [`DEFAULT_REWRITE_LIMIT`] rewrites; use
+/// [`apply_rules_with_limit`] to change it.
+///
+/// # Errors
+///
+/// Fails if a replacement fails, if the rules cycle back to a graph they
+/// already produced, or if the limit is exceeded. The graph is unchanged.
+pub fn apply_rules(
+    graph: &mut GraphDescription,
+    rules: &[RewriteRule],
+) -> Result<Vec<AppliedRewrite>, GraphyError> {
+    apply_rules_with_limit(graph, rules, DEFAULT_REWRITE_LIMIT)
+}
+
+/// [`apply_rules`] with a maximum number of rewrites.
+pub fn apply_rules_with_limit(
+    graph: &mut GraphDescription,
+    rules: &[RewriteRule],
+    limit: usize,
+) -> Result<Vec<AppliedRewrite>, GraphyError> {
+    let mut working = graph.clone();
+    let mut current = working.content_hash();
+    let mut seen = HashSet::from([current]);
+    let mut applied = Vec::new();
+
+    'fixed_point: loop {
+        for rule in rules {
+            for found in rule.pattern.find_matches(&working) {
+                rule.replacement.apply(&rule.name, &mut working, &found)?;
+                let hash = working.content_hash();
+                if hash == current {
+                    continue;
+                }
+                if !seen.insert(hash) {
+                    return Err(GraphyError::Custom(format!(
+                        "Rewrite rule '{}' produced a graph seen before; the rules cycle",
+                        rule.name
+                    )));
+                }
+                if applied.len() == limit {
+                    return Err(GraphyError::Custom(format!(
+                        "Rewriting did not finish within {} rewrites",
+                        limit
+                    )));
+                }
+
+                tracing::trace!("[REWRITE] Applied '{}'", rule.name);
+                current = hash;
+                applied.push(AppliedRewrite {
+                    rule: rule.name.clone(),
+                    matched: found,
+                });
+                continue 'fixed_point;
+            }
+        }
+        break;
+    }
+
+    if !applied.is_empty() {
+        tracing::debug!(
+            "[REWRITE] Applied {} rewrite(s) to '{}'",
+            applied.len(),
+            graph.metadata.name
+        );
+    }
+    *graph = working;
+    Ok(applied)
+}
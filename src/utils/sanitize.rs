@@ -0,0 +1,266 @@
+//! # Graph Sanitization
+//!
+//! Cleans up graphs authored in other tools before they're compiled.
+//!
+//! Imported graphs often carry wiring the editor never produces: the same
+//! connection twice, nodes wired to themselves, connections to pins or nodes
+//! that don't exist, and pins whose data type disagrees with the node's
+//! metadata. [`sanitize`] repairs them in place and returns a
+//! [`SanitizeReport`] listing every [`SanitizeFix`] it made, so the import
+//! can be shown to the user rather than silently changed.
+//!
+//! Pin types are normalized first, so connections are then checked against
+//! the types the metadata declares.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::{Connection, GraphDescription, NodeMetadata, NodeRegistry, NodeTypes, ParamInfo};
+//! use graphy::utils::{sanitize, SanitizeOptions};
+//!
+//! let mut registry = NodeRegistry::new();
+//! registry.register(
+//!     NodeMetadata::new("negate", NodeTypes::pure, "Math")
+//!         .with_params(vec![ParamInfo::new("x", "f64")])
+//!         .with_return_type("f64"),
+//! );
+//!
+//! let mut graph = GraphDescription::new("imported");
+//! graph.add_node_of_type("negate", "a", &registry).unwrap();
+//! graph.add_node_of_type("negate", "b", &registry).unwrap();
+//! graph.add_connection(Connection::data("a", "result", "b", "x"));
+//! graph.add_connection(Connection::data("a", "result", "b", "x"));
+//! graph.add_connection(Connection::data("a", "result", "b", "missing"));
+//!
+//! let report = sanitize(&mut graph, &registry, SanitizeOptions::default());
+//! assert_eq!(report.fixes.len(), 2);
+//! assert_eq!(graph.connections.len(), 1);
+//! ```
+
+use crate::core::{
+    data_type_name, Connection, ConnectionErrorReason, ConnectionType, DataType, GraphDescription, NodeInstance,
+    NodeMetadataProvider, Position,
+};
+use crate::GraphyError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+
+/// Which repairs [`sanitize`] makes. Everything is enabled by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SanitizeOptions {
+    /// Remove connections joining the same pins as an earlier one
+    pub remove_duplicates: bool,
+
+    /// Remove connections from a node to itself
+    pub remove_self_loops: bool,
+
+    /// Remove connections to missing nodes or pins, to pins in the wrong
+    /// direction, and between data and execution pins
+    pub remove_dangling: bool,
+
+    /// Reset the data type of pins declared by the node's metadata to the
+    /// declared type
+    pub normalize_pin_types: bool,
+}
+
+impl Default for SanitizeOptions {
+    fn default() -> Self {
+        Self {
+            remove_duplicates: true,
+            remove_self_loops: true,
+            remove_dangling: true,
+            normalize_pin_types: true,
+        }
+    }
+}
+
+/// A repair made by [`sanitize`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SanitizeFix {
+    /// Removed a connection joining the same pins as an earlier one
+    DuplicateConnection { connection: Connection },
+
+    /// Removed a connection from a node to itself
+    SelfLoop { connection: Connection },
+
+    /// Removed a connection that can't be resolved
+    DanglingConnection {
+        connection: Connection,
+        reason: ConnectionErrorReason,
+    },
+
+    /// Changed the data type of a pin to the one its metadata declares
+    PinTypeNormalized {
+        node_id: String,
+        pin_id: String,
+        from: DataType,
+        to: DataType,
+    },
+}
+
+impl fmt::Display for SanitizeFix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SanitizeFix::DuplicateConnection { connection } => {
+                write!(f, "Removed duplicate connection {}", connection)
+            }
+            SanitizeFix::SelfLoop { connection } => write!(f, "Removed self-loop {}", connection),
+            SanitizeFix::DanglingConnection { connection, reason } => {
+                write!(f, "Removed connection {}: {}", connection, reason)
+            }
+            SanitizeFix::PinTypeNormalized {
+                node_id,
+                pin_id,
+                from,
+                to,
+            } => write!(
+                f,
+                "Changed type of {}.{} from {} to {}",
+                node_id,
+                pin_id,
+                data_type_name(from),
+                data_type_name(to)
+            ),
+        }
+    }
+}
+
+/// Everything [`sanitize`] repaired.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SanitizeReport {
+    /// Repairs, pin types first (by node ID), then connections in their
+    /// original order
+    pub fixes: Vec<SanitizeFix>,
+}
+
+impl SanitizeReport {
+    /// Checks if the graph needed no repairs.
+    #[inline]
+    pub fn is_clean(&self) -> bool {
+        self.fixes.is_empty()
+    }
+
+    /// Counts the connections that were removed.
+    pub fn removed_connections(&self) -> usize {
+        self.fixes
+            .iter()
+            .filter(|fix| !matches!(fix, SanitizeFix::PinTypeNormalized { .. }))
+            .count()
+    }
+}
+
+/// Repairs an imported graph in place
+///
+/// Nodes unknown to the provider keep their pins, and connections to them
+/// are only removed if they can't be resolved from those pins. Of duplicate
+/// connections, the first one is kept.
+pub fn sanitize<P: NodeMetadataProvider>(
+    graph: &mut GraphDescription,
+    metadata_provider: &P,
+    options: SanitizeOptions,
+) -> SanitizeReport {
+    let mut report = SanitizeReport::default();
+    if options.normalize_pin_types {
+        normalize_pin_types(graph, metadata_provider, &mut report);
+    }
+
+    let mut seen: HashSet<(&str, &str, &str, &str, ConnectionType)> = HashSet::new();
+    let mut keep = Vec::with_capacity(graph.connections.len());
+    for connection in &graph.connections {
+        let fix = if options.remove_self_loops && connection.source_node == connection.target_node {
+            Some(SanitizeFix::SelfLoop {
+                connection: connection.clone(),
+            })
+        } else if let Some(reason) = options
+            .remove_dangling
+            .then(|| dangling_reason(graph, connection, metadata_provider))
+            .flatten()
+        {
+            Some(SanitizeFix::DanglingConnection {
+                connection: connection.clone(),
+                reason,
+            })
+        } else if options.remove_duplicates
+            && !seen.insert((
+                &connection.source_node,
+                &connection.source_pin,
+                &connection.target_node,
+                &connection.target_pin,
+                connection.connection_type,
+            ))
+        {
+            Some(SanitizeFix::DuplicateConnection {
+                connection: connection.clone(),
+            })
+        } else {
+            None
+        };
+        keep.push(fix.is_none());
+        report.fixes.extend(fix);
+    }
+
+    let mut keep = keep.into_iter();
+    graph.connections.retain(|_| keep.next().unwrap_or(true));
+
+    tracing::debug!("[SANITIZE] Made {} fix(es)", report.fixes.len());
+
+    report
+}
+
+/// Why a connection can't be resolved, if it's one [`sanitize`] removes
+fn dangling_reason<P: NodeMetadataProvider>(
+    graph: &GraphDescription,
+    connection: &Connection,
+    metadata_provider: &P,
+) -> Option<ConnectionErrorReason> {
+    match graph.resolve_connection_types(connection, metadata_provider) {
+        Err(GraphyError::InvalidConnection { reason, .. }) => match reason {
+            ConnectionErrorReason::MissingNode { .. }
+            | ConnectionErrorReason::MissingPin { .. }
+            | ConnectionErrorReason::WrongDirection { .. }
+            | ConnectionErrorReason::Reversed
+            | ConnectionErrorReason::ConnectionTypeMismatch { .. } => Some(reason),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Resets pins to the types of the pins the metadata declares for them
+fn normalize_pin_types<P: NodeMetadataProvider>(
+    graph: &mut GraphDescription,
+    metadata_provider: &P,
+    report: &mut SanitizeReport,
+) {
+    let mut node_ids: Vec<String> = graph.nodes.keys().cloned().collect();
+    node_ids.sort_unstable();
+
+    for node_id in node_ids {
+        let Some(node) = graph.nodes.get_mut(&node_id) else {
+            continue;
+        };
+        let Some(metadata) = metadata_provider.metadata_for_node(node) else {
+            continue;
+        };
+        let declared = NodeInstance::from_metadata(node_id.as_str(), metadata, Position::zero());
+
+        let pins = node.inputs.iter_mut().zip(std::iter::repeat(&declared.inputs));
+        let pins = pins.chain(node.outputs.iter_mut().zip(std::iter::repeat(&declared.outputs)));
+        for (pin, declared_pins) in pins {
+            let Some(declared_pin) = declared_pins.iter().find(|declared| declared.id == pin.id) else {
+                continue;
+            };
+            if pin.pin.data_type == declared_pin.pin.data_type {
+                continue;
+            }
+            let from = std::mem::replace(&mut pin.pin.data_type, declared_pin.pin.data_type.clone());
+            report.fixes.push(SanitizeFix::PinTypeNormalized {
+                node_id: node_id.clone(),
+                pin_id: pin.id.clone(),
+                from,
+                to: declared_pin.pin.data_type.clone(),
+            });
+        }
+    }
+}
@@ -0,0 +1,433 @@
+//! # Sub-Graph Extraction
+//!
+//! Refactoring operation that collapses a set of nodes into a sub-graph.
+//!
+//! This is the inverse of [`SubGraphExpander`](super::SubGraphExpander):
+//! [`extract_subgraph`] moves the selected nodes into a new graph, infers its
+//! interface from the connections that cross the selection boundary, and
+//! replaces the selection in the original graph with a single composite node.
+//!
+//! # Boundary inference
+//!
+//! - Each distinct outside **data source** feeding the selection becomes a
+//!   graph input (one input even if it feeds several pins).
+//! - Each distinct inside **data output** read from outside becomes a graph
+//!   output.
+//! - Incoming and outgoing **execution** connections become execution pins
+//!   on the composite node and on the sub-graph's `graph.input` /
+//!   `graph.output` pseudo-nodes. They are not declared as graph parameters.
+//!
+//! Pin names are taken from the inner pins, with a numeric suffix when two
+//! boundary pins would share a name.
+//!
+//! # Example
+//!
+//! ```
+//! use graphy::utils::extract_subgraph;
+//! use graphy::{Connection, DataType, GraphDescription, NodeInstance, Position};
+//!
+//! let mut graph = GraphDescription::new("main");
+//! for id in ["source", "inner", "sink"] {
+//!     let mut node = NodeInstance::new(id, "math.add", Position::zero());
+//!     node.add_input_pin("a", DataType::Typed("i64".into()));
+//!     node.add_output_pin("result", DataType::Typed("i64".into()));
+//!     graph.add_node(node);
+//! }
+//! graph.add_connection(Connection::data("source", "result", "inner", "a"));
+//! graph.add_connection(Connection::data("inner", "result", "sink", "a"));
+//!
+//! let (subgraph, composite) = extract_subgraph(&mut graph, ["inner"]).unwrap();
+//!
+//! assert_eq!(subgraph.graph_inputs[0].name, "a");
+//! assert_eq!(subgraph.graph_outputs[0].name, "result");
+//! assert!(graph.get_node("inner").is_none());
+//! assert!(graph.get_node(&composite.id).is_some());
+//! ```
+
+use crate::core::{
+    Connection, ConnectionType, DataType, GraphDescription, NodeInstance, PinType, Position, TypeInfo,
+};
+use crate::GraphyError;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+/// Node type prefix for composite nodes that stand in for a sub-graph.
+///
+/// The full node type is `subgraph:<sub-graph name>`.
+pub const SUBGRAPH_NODE_TYPE_PREFIX: &str = "subgraph:";
+
+/// Horizontal gap between the extracted nodes and the pseudo-nodes
+const PSEUDO_NODE_OFFSET: f64 = 200.0;
+
+/// Extract a set of nodes into a new sub-graph
+///
+/// Returns the new sub-graph and a copy of the composite node that replaced
+/// the selection in `graph`. The sub-graph is named after the composite
+/// node's ID (`subgraph_1`, `subgraph_2`, ...), and the composite node's type
+/// is `subgraph:<name>`.
+///
+/// Extracted nodes are also removed from any groups they belonged to.
+/// Comments attached only to extracted nodes move into the sub-graph;
+/// comments also attached to nodes left behind are attached to the composite
+/// node instead of the extracted ones.
+///
+/// # Errors
+///
+/// - [`GraphyError::Custom`] if `node_ids` is empty or a boundary pin's type
+///   can't be inferred from either end of its connection
+/// - [`GraphyError::NodeNotFound`] if a node doesn't exist
+///
+/// On error the graph is unchanged.
+pub fn extract_subgraph<I, S>(
+    graph: &mut GraphDescription,
+    node_ids: I,
+) -> Result<(GraphDescription, NodeInstance), GraphyError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let node_ids: Vec<String> = node_ids.into_iter().map(|id| id.as_ref().to_string()).collect();
+    let name = unique_node_id(graph, "subgraph");
+    extract_named(graph, &node_ids, name)
+}
+
+/// Extract the nodes of a group into a new sub-graph
+///
+/// Works like [`extract_subgraph`], but names the sub-graph after the group
+/// title and removes the group itself.
+///
+/// # Errors
+///
+/// Returns [`GraphyError::Custom`] if the group doesn't exist or is empty,
+/// plus the errors of [`extract_subgraph`].
+pub fn extract_group(
+    graph: &mut GraphDescription,
+    group_id: &str,
+) -> Result<(GraphDescription, NodeInstance), GraphyError> {
+    let group = graph
+        .get_group(group_id)
+        .ok_or_else(|| GraphyError::Custom(format!("Group not found: {}", group_id)))?;
+
+    let node_ids = group.node_ids.clone();
+    let name = unique_node_id(graph, &super::sanitize_name(&group.title));
+    let result = extract_named(graph, &node_ids, name)?;
+
+    graph.remove_group(group_id);
+    Ok(result)
+}
+
+/// A boundary pin on the composite node, with the connections it replaces
+struct BoundaryPin {
+    name: String,
+    data_type: DataType,
+
+    /// Inner (node, pin) ends
+    inner: Vec<(String, String)>,
+
+    /// Outer (node, pin) ends
+    outer: Vec<(String, String)>,
+}
+
+fn extract_named(
+    graph: &mut GraphDescription,
+    node_ids: &[String],
+    name: String,
+) -> Result<(GraphDescription, NodeInstance), GraphyError> {
+    if node_ids.is_empty() {
+        return Err(GraphyError::Custom("Cannot extract an empty selection".to_string()));
+    }
+    if let Some(missing) = node_ids.iter().find(|id| !graph.nodes.contains_key(*id)) {
+        return Err(GraphyError::NodeNotFound(missing.clone()));
+    }
+
+    let selection: FxHashSet<&str> = node_ids.iter().map(String::as_str).collect();
+    let (inputs, outputs) = infer_boundary(graph, &selection)?;
+
+    tracing::debug!(
+        "[EXTRACT] Extracting {} node(s) into '{}' ({} inputs, {} outputs)",
+        selection.len(),
+        name,
+        inputs.len(),
+        outputs.len()
+    );
+
+    // Everything is validated; from here on the graph is modified
+    let mut subgraph = GraphDescription::new(name.clone());
+    let (min, max) = bounding_box(node_ids.iter().map(|id| graph.nodes[id].position));
+
+    let mut remaining = Vec::with_capacity(graph.connections.len());
+    for connection in graph.connections.drain(..) {
+        let source_inside = selection.contains(connection.source_node.as_str());
+        let target_inside = selection.contains(connection.target_node.as_str());
+        match (source_inside, target_inside) {
+            (true, true) => subgraph.connections.push(connection),
+            (false, false) => remaining.push(connection),
+            _ => {} // Boundary connections are rewired below
+        }
+    }
+    graph.connections = remaining;
+
+    for id in node_ids {
+        if let Some(node) = graph.nodes.remove(id) {
+            subgraph.add_node(node);
+        }
+    }
+    for group in &mut graph.groups {
+        group.node_ids.retain(|id| !selection.contains(id.as_str()));
+    }
+    let (inside, outside): (Vec<_>, Vec<_>) = graph.comments.drain(..).partition(|comment| {
+        comment.is_attached() && comment.attached_node_ids.iter().all(|id| selection.contains(id.as_str()))
+    });
+    subgraph.comments = inside;
+    graph.comments = outside;
+    for comment in &mut graph.comments {
+        let before = comment.attached_node_ids.len();
+        comment.attached_node_ids.retain(|id| !selection.contains(id.as_str()));
+        if comment.attached_node_ids.len() != before {
+            comment.attach(name.clone());
+        }
+    }
+
+    let center = Position::new((min.x + max.x) / 2.0, (min.y + max.y) / 2.0);
+    let composite_type = format!("{}{}", SUBGRAPH_NODE_TYPE_PREFIX, name);
+    let mut composite = NodeInstance::new(name.clone(), composite_type, center);
+
+    // Inputs: outer source -> composite pin, graph.input pin -> inner target
+    if !inputs.is_empty() {
+        for pin in inputs.iter().filter(|pin| !pin.data_type.is_execution()) {
+            subgraph.add_graph_input(pin.name.clone(), data_type_info(&pin.data_type));
+        }
+
+        let input_node_id = unique_node_id(&subgraph, "graph_input");
+        let position = Position::new(min.x - PSEUDO_NODE_OFFSET, center.y);
+        let mut input_node = subgraph.create_graph_input_node(input_node_id.clone(), position);
+
+        for pin in &inputs {
+            composite.add_input_pin(pin.name.clone(), pin.data_type.clone());
+            if pin.data_type.is_execution() {
+                input_node.add_output_pin(pin.name.clone(), DataType::Execution);
+            }
+
+            let connection_type = connection_type_of(&pin.data_type);
+            for (node, node_pin) in &pin.inner {
+                subgraph.add_connection(boundary_connection(
+                    &input_node_id,
+                    &pin.name,
+                    node,
+                    node_pin,
+                    connection_type,
+                ));
+            }
+            for (node, node_pin) in &pin.outer {
+                graph.add_connection(boundary_connection(node, node_pin, &name, &pin.name, connection_type));
+            }
+        }
+        subgraph.add_node(input_node);
+    }
+
+    // Outputs: inner source -> graph.output pin, composite pin -> outer target
+    if !outputs.is_empty() {
+        for pin in outputs.iter().filter(|pin| !pin.data_type.is_execution()) {
+            subgraph.add_graph_output(pin.name.clone(), data_type_info(&pin.data_type));
+        }
+
+        let output_node_id = unique_node_id(&subgraph, "graph_output");
+        let position = Position::new(max.x + PSEUDO_NODE_OFFSET, center.y);
+        let mut output_node = subgraph.create_graph_output_node(output_node_id.clone(), position);
+
+        for pin in &outputs {
+            composite.add_output_pin(pin.name.clone(), pin.data_type.clone());
+            if pin.data_type.is_execution() {
+                output_node.add_input_pin(pin.name.clone(), DataType::Execution);
+            }
+
+            let connection_type = connection_type_of(&pin.data_type);
+            for (node, node_pin) in &pin.inner {
+                subgraph.add_connection(boundary_connection(
+                    node,
+                    node_pin,
+                    &output_node_id,
+                    &pin.name,
+                    connection_type,
+                ));
+            }
+            for (node, node_pin) in &pin.outer {
+                graph.add_connection(boundary_connection(&name, &pin.name, node, node_pin, connection_type));
+            }
+        }
+        subgraph.add_node(output_node);
+    }
+
+    graph.add_node(composite.clone());
+    Ok((subgraph, composite))
+}
+
+/// Group connections crossing the selection into composite input/output pins
+fn infer_boundary(
+    graph: &GraphDescription,
+    selection: &FxHashSet<&str>,
+) -> Result<(Vec<BoundaryPin>, Vec<BoundaryPin>), GraphyError> {
+    let mut inputs: Vec<BoundaryPin> = Vec::new();
+    let mut outputs: Vec<BoundaryPin> = Vec::new();
+    let mut input_keys: FxHashMap<(&str, &str, bool), usize> = FxHashMap::default();
+    let mut output_keys: FxHashMap<(&str, &str), usize> = FxHashMap::default();
+    let mut input_names = FxHashSet::default();
+    let mut output_names = FxHashSet::default();
+
+    for connection in &graph.connections {
+        let source_inside = selection.contains(connection.source_node.as_str());
+        let target_inside = selection.contains(connection.target_node.as_str());
+        let is_execution = matches!(connection.connection_type, ConnectionType::Execution);
+
+        let inner_target = (connection.target_node.clone(), connection.target_pin.clone());
+        let outer_source = (connection.source_node.clone(), connection.source_pin.clone());
+
+        if !source_inside && target_inside {
+            // Execution inputs merge by inner target, data inputs by outer source
+            let key = if is_execution {
+                (connection.target_node.as_str(), connection.target_pin.as_str(), true)
+            } else {
+                (connection.source_node.as_str(), connection.source_pin.as_str(), false)
+            };
+
+            match input_keys.get(&key) {
+                Some(&index) => {
+                    let pin = &mut inputs[index];
+                    if is_execution {
+                        pin.outer.push(outer_source);
+                    } else {
+                        pin.inner.push(inner_target);
+                    }
+                }
+                None => {
+                    let data_type = if is_execution {
+                        DataType::Execution
+                    } else {
+                        boundary_type(graph, connection)?
+                    };
+                    input_keys.insert(key, inputs.len());
+                    inputs.push(BoundaryPin {
+                        name: unique_name(&connection.target_pin, &mut input_names),
+                        data_type,
+                        inner: vec![inner_target],
+                        outer: vec![outer_source],
+                    });
+                }
+            }
+        } else if source_inside && !target_inside {
+            // Outputs merge by inner source
+            let key = (connection.source_node.as_str(), connection.source_pin.as_str());
+            let outer_target = (connection.target_node.clone(), connection.target_pin.clone());
+
+            match output_keys.get(&key) {
+                Some(&index) => outputs[index].outer.push(outer_target),
+                None => {
+                    let data_type = if is_execution {
+                        DataType::Execution
+                    } else {
+                        boundary_type(graph, connection)?
+                    };
+                    output_keys.insert(key, outputs.len());
+                    outputs.push(BoundaryPin {
+                        name: unique_name(&connection.source_pin, &mut output_names),
+                        data_type,
+                        inner: vec![(connection.source_node.clone(), connection.source_pin.clone())],
+                        outer: vec![outer_target],
+                    });
+                }
+            }
+        }
+    }
+
+    Ok((inputs, outputs))
+}
+
+/// Data type of a connection, from the source pin or else the target pin
+fn boundary_type(graph: &GraphDescription, connection: &Connection) -> Result<DataType, GraphyError> {
+    let known = |data_type: &&DataType| data_type.type_string().is_some();
+
+    pin_data_type(graph, &connection.source_node, &connection.source_pin, PinType::Output)
+        .filter(known)
+        .or_else(|| {
+            pin_data_type(graph, &connection.target_node, &connection.target_pin, PinType::Input).filter(known)
+        })
+        .cloned()
+        .ok_or_else(|| {
+            GraphyError::Custom(format!("Cannot infer type of boundary connection {}", connection))
+        })
+}
+
+fn pin_data_type<'g>(
+    graph: &'g GraphDescription,
+    node_id: &str,
+    pin_id: &str,
+    direction: PinType,
+) -> Option<&'g DataType> {
+    let node = graph.nodes.get(node_id)?;
+    let pins = match direction {
+        PinType::Input => &node.inputs,
+        PinType::Output => &node.outputs,
+    };
+    pins.iter().find(|p| p.id == pin_id).map(|p| &p.pin.data_type)
+}
+
+fn data_type_info(data_type: &DataType) -> TypeInfo {
+    match data_type {
+        DataType::Typed(info) => info.clone(),
+        other => TypeInfo::new(other.type_string().unwrap_or_default()),
+    }
+}
+
+#[inline]
+fn connection_type_of(data_type: &DataType) -> ConnectionType {
+    if data_type.is_execution() {
+        ConnectionType::Execution
+    } else {
+        ConnectionType::Data
+    }
+}
+
+#[inline]
+fn boundary_connection(
+    source_node: &str,
+    source_pin: &str,
+    target_node: &str,
+    target_pin: &str,
+    connection_type: ConnectionType,
+) -> Connection {
+    Connection::new(source_node, source_pin, target_node, target_pin, connection_type)
+}
+
+/// `base`, or `base_2`, `base_3`, ... if already taken
+fn unique_name(base: &str, used: &mut FxHashSet<String>) -> String {
+    let mut name = base.to_string();
+    let mut suffix = 2;
+    while used.contains(&name) {
+        name = format!("{}_{}", base, suffix);
+        suffix += 1;
+    }
+    used.insert(name.clone());
+    name
+}
+
+/// `base_1`, `base_2`, ... (the first one not used by a node in the graph)
+fn unique_node_id(graph: &GraphDescription, base: &str) -> String {
+    (1..)
+        .map(|i| format!("{}_{}", base, i))
+        .find(|id| !graph.nodes.contains_key(id))
+        .unwrap_or_default()
+}
+
+fn bounding_box(positions: impl Iterator<Item = Position>) -> (Position, Position) {
+    positions.fold(
+        (
+            Position::new(f64::INFINITY, f64::INFINITY),
+            Position::new(f64::NEG_INFINITY, f64::NEG_INFINITY),
+        ),
+        |(min, max), p| {
+            (
+                Position::new(min.x.min(p.x), min.y.min(p.y)),
+                Position::new(max.x.max(p.x), max.y.max(p.y)),
+            )
+        },
+    )
+}
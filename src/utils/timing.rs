@@ -0,0 +1,58 @@
+//! # Timing
+//!
+//! Wall-clock timing for compilation reports and pass timings.
+//!
+//! `wasm32-unknown-unknown` has no clock: `std::time::Instant::now` panics
+//! there. On that target a [`Stopwatch`] measures nothing and every duration
+//! is zero, so reports still work in the browser, just without timings.
+//! [`unix_millis`] reads the system clock for timestamps, and is zero there
+//! too.
+
+use std::time::Duration;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Measures the time since it was started.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Stopwatch {
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    start: Instant,
+}
+
+impl Stopwatch {
+    /// Starts measuring.
+    #[inline]
+    pub(crate) fn start() -> Self {
+        Self {
+            #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+            start: Instant::now(),
+        }
+    }
+
+    /// Returns the time since [`start`](Self::start), or zero without a clock.
+    #[inline]
+    pub(crate) fn elapsed(&self) -> Duration {
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        {
+            self.start.elapsed()
+        }
+        #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+        {
+            Duration::ZERO
+        }
+    }
+}
+
+/// Milliseconds since the Unix epoch, or zero without a clock.
+pub(crate) fn unix_millis() -> u64 {
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis() as u64)
+    }
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    {
+        0
+    }
+}
@@ -2,14 +2,17 @@
 //!
 //! Utilities for generating unique, valid variable names.
 
+use super::{LanguageProfile, RustProfile};
 use std::collections::HashSet;
 
 /// Variable name generator
 ///
 /// Generates unique, sanitized variable names for nodes and intermediate results.
+/// Names follow the rules of a [`LanguageProfile`] (Rust by default).
 pub struct VariableNameGenerator {
     used_names: HashSet<String>,
     counter: usize,
+    profile: Box<dyn LanguageProfile>,
 }
 
 impl VariableNameGenerator {
@@ -17,13 +20,24 @@ impl VariableNameGenerator {
         Self {
             used_names: HashSet::new(),
             counter: 0,
+            profile: Box::new(RustProfile),
         }
     }
 
+    /// Generate names for a different target language
+    pub fn with_profile(mut self, profile: impl LanguageProfile + 'static) -> Self {
+        self.profile = Box::new(profile);
+        self
+    }
+
+    /// Get the language profile names are generated for
+    pub fn profile(&self) -> &dyn LanguageProfile {
+        self.profile.as_ref()
+    }
+
     /// Generate a unique variable name based on a node ID
     pub fn generate_for_node(&mut self, node_id: &str) -> String {
-        let base_name = sanitize_name(node_id);
-        let var_name = format!("node_{}_result", base_name);
+        let var_name = self.profile.result_variable_name(node_id);
 
         if self.used_names.contains(&var_name) {
             // Generate unique name with counter
@@ -66,6 +80,10 @@ impl Default for VariableNameGenerator {
 }
 
 /// Sanitize a string to be a valid Rust variable name
+///
+/// Only replaces invalid characters; see
+/// [`LanguageProfile::sanitize_identifier`] for reserved words and other
+/// languages.
 pub fn sanitize_name(name: &str) -> String {
     name.chars()
         .map(|c| if c.is_alphanumeric() || c == '_' { c } else { '_' })
@@ -73,26 +91,11 @@ pub fn sanitize_name(name: &str) -> String {
 }
 
 /// Get the default value expression for a data type
+///
+/// Uses Rust syntax; see [`LanguageProfile::default_value`] for other
+/// languages.
 pub fn get_default_value_for_type(type_str: &str) -> String {
-    match type_str {
-        "f32" | "f64" => "0.0".to_string(),
-        "i8" | "i16" | "i32" | "i64" | "i128" | "isize" => "0".to_string(),
-        "u8" | "u16" | "u32" | "u64" | "u128" | "usize" => "0".to_string(),
-        "bool" => "false".to_string(),
-        "char" => "'\\0'".to_string(),
-        "String" => "String::new()".to_string(),
-        _ if type_str.starts_with('(') && type_str.ends_with(')') => {
-            // Tuple type
-            let inner = &type_str[1..type_str.len() - 1];
-            let parts: Vec<&str> = inner.split(',').collect();
-            let defaults: Vec<String> = parts
-                .iter()
-                .map(|p| get_default_value_for_type(p.trim()))
-                .collect();
-            format!("({})", defaults.join(", "))
-        }
-        _ => "Default::default()".to_string(),
-    }
+    RustProfile.default_value(type_str)
 }
 
 #[cfg(test)]
@@ -0,0 +1,38 @@
+//! # Watching Graph Files
+//!
+//! Recompiles the graph files of a directory as they change, for editors,
+//! dev servers, and build tools running in watch mode.
+//!
+//! A [`WatchService`] owns the metadata provider, the generator, and a
+//! [compilation cache](crate::cache), and reports the outcome of every
+//! build as a [`WatchEvent`] over a channel. It only rebuilds graphs whose
+//! content changed. With the `watch` feature, a [`GraphWatcher`] runs the
+//! service on a background thread, fed by file system notifications from the
+//! `notify` crate; without it, call
+//! [`file_changed`](WatchService::file_changed) from your own loop.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use graphy::cache::MemoryCache;
+//! use graphy::watch::{GraphWatcher, WatchEvent, WatchService};
+//!
+//! let (service, events) = WatchService::new("graphs", registry, MyRustGenerator::default(), MemoryCache::new());
+//! let _watcher = GraphWatcher::start(service)?;
+//!
+//! for event in events {
+//!     match event {
+//!         WatchEvent::Compiled { path, output } => std::fs::write(path.with_extension("rs"), output.code)?,
+//!         WatchEvent::Failed { path, diagnostics } => eprintln!("{}: {}", path.display(), diagnostics[0]),
+//!         WatchEvent::Removed { path } => std::fs::remove_file(path.with_extension("rs"))?,
+//!     }
+//! }
+//! ```
+
+pub mod service;
+#[cfg(feature = "watch")]
+pub mod watcher;
+
+pub use service::*;
+#[cfg(feature = "watch")]
+pub use watcher::*;
@@ -0,0 +1,258 @@
+//! Rebuilding graph files as they change.
+
+use crate::cache::{compile_cached, CachedOutput, CompilationCache, MemoryCache};
+use crate::core::NodeMetadataProvider;
+use crate::generation::CodeGenerator;
+use crate::io::{load_graph_file, Format};
+use crate::GraphyError;
+use rustc_hash::FxHashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+
+/// Outcome of rebuilding a graph file, sent by a [`WatchService`].
+#[derive(Debug)]
+pub enum WatchEvent {
+    /// The graph compiled
+    Compiled {
+        /// Graph file
+        path: PathBuf,
+
+        /// Generated code and source map
+        output: CachedOutput,
+    },
+
+    /// The graph couldn't be loaded or compiled
+    Failed {
+        /// Graph file
+        path: PathBuf,
+
+        /// Errors that stopped the build
+        diagnostics: Vec<GraphyError>,
+    },
+
+    /// The graph file was deleted
+    Removed {
+        /// Graph file
+        path: PathBuf,
+    },
+}
+
+impl WatchEvent {
+    /// Returns the graph file the event is about.
+    pub fn path(&self) -> &Path {
+        match self {
+            WatchEvent::Compiled { path, .. } | WatchEvent::Failed { path, .. } | WatchEvent::Removed { path } => path,
+        }
+    }
+}
+
+/// Compiles the graph files of a directory and recompiles them as they change.
+///
+/// Every graph file (any [`Format`] this build can read) below the root is
+/// compiled with [`compile_cached`], and the outcome is sent as a
+/// [`WatchEvent`]. Files and directories whose name starts with `.` are
+/// ignored, so editor swap files and a hidden cache directory inside the
+/// root don't count as graphs.
+///
+/// Only affected graphs are rebuilt: a changed file whose
+/// [content hash](crate::GraphDescription::content_hash) is unchanged (e.g.
+/// only nodes moved) sends no event, and a graph changed back to an earlier
+/// state is served from the cache.
+///
+/// The service doesn't watch anything itself; call
+/// [`file_changed`](Self::file_changed) for every changed path, or run it in
+/// a [`GraphWatcher`](super::GraphWatcher) (`watch` feature).
+pub struct WatchService<P, G, C = MemoryCache> {
+    root: PathBuf,
+    provider: P,
+    generator: G,
+    cache: C,
+    events: Sender<WatchEvent>,
+
+    /// Content hash of every graph file last built, `None` if loading failed
+    graphs: FxHashMap<PathBuf, Option<u64>>,
+}
+
+impl<P, G, C> WatchService<P, G, C>
+where
+    P: NodeMetadataProvider,
+    G: CodeGenerator,
+    C: CompilationCache,
+{
+    /// Creates a service for the graph files below `root`, returning the
+    /// receiving end of its events.
+    ///
+    /// Nothing is compiled until [`scan`](Self::scan) or
+    /// [`file_changed`](Self::file_changed) is called. Events the receiver
+    /// never reads are dropped once it is gone.
+    pub fn new(root: impl Into<PathBuf>, provider: P, generator: G, cache: C) -> (Self, Receiver<WatchEvent>) {
+        let (events, receiver) = mpsc::channel();
+        let service = Self {
+            root: root.into(),
+            provider,
+            generator,
+            cache,
+            events,
+            graphs: FxHashMap::default(),
+        };
+        (service, receiver)
+    }
+
+    /// Returns the watched directory.
+    #[inline]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the metadata provider.
+    #[inline]
+    pub fn provider(&self) -> &P {
+        &self.provider
+    }
+
+    /// Returns the metadata provider, to change the node library.
+    ///
+    /// Call [`rebuild_all`](Self::rebuild_all) afterwards; the graphs aren't
+    /// rebuilt automatically.
+    #[inline]
+    pub fn provider_mut(&mut self) -> &mut P {
+        &mut self.provider
+    }
+
+    /// Returns the generator.
+    #[inline]
+    pub fn generator(&self) -> &G {
+        &self.generator
+    }
+
+    /// Returns the cache.
+    #[inline]
+    pub fn cache(&self) -> &C {
+        &self.cache
+    }
+
+    /// Checks if a path is a graph file the service builds.
+    pub fn is_graph_file(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+        let hidden = relative
+            .components()
+            .any(|component| component.as_os_str().to_str().is_some_and(|name| name.starts_with('.')));
+        !hidden && Format::from_path(path).is_some_and(|format| format.is_available())
+    }
+
+    /// Returns the graph files built so far, sorted.
+    pub fn graph_files(&self) -> Vec<&Path> {
+        let mut files: Vec<&Path> = self.graphs.keys().map(PathBuf::as_path).collect();
+        files.sort_unstable();
+        files
+    }
+
+    /// Builds every graph file below the root, in path order, returning how
+    /// many there are.
+    ///
+    /// Files already built are only rebuilt if they changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::Io`] if a directory can't be read. Graphs that
+    /// fail to build are reported as [`WatchEvent::Failed`], not errors.
+    pub fn scan(&mut self) -> Result<usize, GraphyError> {
+        let mut files = Vec::new();
+        self.collect_graph_files(&self.root, &mut files)?;
+        files.sort_unstable();
+        for path in &files {
+            self.file_changed(path);
+        }
+        Ok(files.len())
+    }
+
+    /// Rebuilds a changed file, if it is a graph file.
+    ///
+    /// A deleted graph file is forgotten and reported as
+    /// [`WatchEvent::Removed`]. Returns whether an event was sent.
+    pub fn file_changed(&mut self, path: &Path) -> bool {
+        if !self.is_graph_file(path) {
+            return false;
+        }
+        if !path.is_file() {
+            return match self.graphs.remove(path) {
+                Some(_) => {
+                    tracing::debug!("[WATCH] {} was removed", path.display());
+                    self.send(WatchEvent::Removed { path: path.to_path_buf() })
+                }
+                None => false,
+            };
+        }
+
+        let graph = match load_graph_file(path) {
+            Ok(graph) => graph,
+            Err(error) => {
+                self.graphs.insert(path.to_path_buf(), None);
+                return self.send(WatchEvent::Failed {
+                    path: path.to_path_buf(),
+                    diagnostics: vec![error],
+                });
+            }
+        };
+        let hash = graph.content_hash();
+        if self.graphs.insert(path.to_path_buf(), Some(hash)) == Some(Some(hash)) {
+            tracing::trace!("[WATCH] {} is unchanged", path.display());
+            return false;
+        }
+
+        tracing::debug!("[WATCH] Rebuilding {}", path.display());
+        let event = match compile_cached(&graph, &self.provider, &mut self.generator, &mut self.cache) {
+            Ok(output) => WatchEvent::Compiled {
+                path: path.to_path_buf(),
+                output,
+            },
+            Err(error) => WatchEvent::Failed {
+                path: path.to_path_buf(),
+                diagnostics: vec![error],
+            },
+        };
+        self.send(event)
+    }
+
+    /// Rebuilds every graph file built so far, changed or not, in path
+    /// order.
+    ///
+    /// Use it after changing the node library: graphs compiled with older
+    /// metadata are never served from the cache.
+    pub fn rebuild_all(&mut self) {
+        let mut files: Vec<PathBuf> = self.graphs.keys().cloned().collect();
+        files.sort_unstable();
+        for hash in self.graphs.values_mut() {
+            *hash = None;
+        }
+        for path in &files {
+            self.file_changed(path);
+        }
+    }
+
+    /// Collects the graph files below a directory
+    fn collect_graph_files(&self, dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), GraphyError> {
+        let entries = fs::read_dir(dir).map_err(|e| GraphyError::Io(format!("{}: {}", dir.display(), e)))?;
+        for entry in entries {
+            let path = entry.map_err(|e| GraphyError::Io(format!("{}: {}", dir.display(), e)))?.path();
+            if path.is_dir() {
+                if !path.file_name().and_then(|name| name.to_str()).is_some_and(|name| name.starts_with('.')) {
+                    self.collect_graph_files(&path, files)?;
+                }
+            } else if self.is_graph_file(&path) {
+                files.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends an event, returning `true`
+    fn send(&self, event: WatchEvent) -> bool {
+        // Nobody listening isn't an error: the service keeps building
+        let _ = self.events.send(event);
+        true
+    }
+}
@@ -0,0 +1,88 @@
+//! Watching a directory with the `notify` crate.
+
+use super::WatchService;
+use crate::cache::CompilationCache;
+use crate::core::NodeMetadataProvider;
+use crate::generation::CodeGenerator;
+use crate::GraphyError;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// How long a [`GraphWatcher`] waits for more changes before rebuilding
+pub const WATCH_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// Runs a [`WatchService`] on a background thread, rebuilding graph files as
+/// the file system reports changes.
+///
+/// The thread first [scans](WatchService::scan) the root, then rebuilds every
+/// changed graph file. Changes are collected until none arrives for
+/// [`WATCH_DEBOUNCE`], so a file written in several steps is rebuilt once,
+/// after the last one. Dropping the watcher stops watching and waits for
+/// the thread to finish.
+pub struct GraphWatcher {
+    watcher: Option<RecommendedWatcher>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl GraphWatcher {
+    /// Starts watching the service's root.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GraphyError::Io`] if the root can't be watched.
+    pub fn start<P, G, C>(mut service: WatchService<P, G, C>) -> Result<Self, GraphyError>
+    where
+        P: NodeMetadataProvider + Send + 'static,
+        G: CodeGenerator + Send + 'static,
+        C: CompilationCache + Send + 'static,
+    {
+        let io_error = |e: notify::Error| GraphyError::Io(format!("Can't watch {}: {}", service.root().display(), e));
+        let (changes, received) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(changes).map_err(io_error)?;
+        watcher.watch(service.root(), RecursiveMode::Recursive).map_err(io_error)?;
+
+        let worker = thread::spawn(move || {
+            if let Err(error) = service.scan() {
+                tracing::warn!("[WATCH] Scanning {} failed: {}", service.root().display(), error);
+            }
+            // Ends once the watcher is dropped
+            while let Ok(change) = received.recv() {
+                let mut paths = BTreeSet::new();
+                changed_paths(change, &mut paths);
+                while let Ok(change) = received.recv_timeout(WATCH_DEBOUNCE) {
+                    changed_paths(change, &mut paths);
+                }
+                for path in &paths {
+                    service.file_changed(path);
+                }
+            }
+        });
+
+        Ok(Self {
+            watcher: Some(watcher),
+            worker: Some(worker),
+        })
+    }
+}
+
+impl Drop for GraphWatcher {
+    fn drop(&mut self) {
+        drop(self.watcher.take());
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Adds the paths a notification reports as changed
+fn changed_paths(change: notify::Result<Event>, paths: &mut BTreeSet<PathBuf>) {
+    match change {
+        Ok(event) if !matches!(event.kind, EventKind::Access(_)) => paths.extend(event.paths),
+        Ok(_) => {}
+        Err(error) => tracing::warn!("[WATCH] {}", error),
+    }
+}
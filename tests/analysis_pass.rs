@@ -0,0 +1,150 @@
+//! Tests for AnalysisPass, AnalysisContext, and PassManager.
+
+mod common;
+
+use common::*;
+use graphy::analysis::{DataFlowPass, ExecutionRoutingPass};
+use graphy::*;
+
+struct NodeCount(usize);
+
+struct NodeCountPass;
+
+impl AnalysisPass for NodeCountPass {
+    fn name(&self) -> &str {
+        "node_count"
+    }
+
+    fn run(&self, graph: &GraphDescription, ctx: &mut AnalysisContext) -> Result<()> {
+        ctx.insert(NodeCount(graph.nodes.len()));
+        Ok(())
+    }
+}
+
+/// Pass that depends on the result of `NodeCountPass`.
+struct DoubledCountPass;
+
+impl AnalysisPass for DoubledCountPass {
+    fn name(&self) -> &str {
+        "doubled_count"
+    }
+
+    fn run(&self, _graph: &GraphDescription, ctx: &mut AnalysisContext) -> Result<()> {
+        let count = ctx
+            .get::<NodeCount>()
+            .ok_or_else(|| GraphyError::Custom("node_count has not run".to_string()))?
+            .0;
+        ctx.insert(count * 2);
+        Ok(())
+    }
+}
+
+// ===========================================================================
+// AnalysisContext
+// ===========================================================================
+
+#[test]
+fn analysis_context_insert_and_get() {
+    let mut ctx = AnalysisContext::new();
+    assert!(!ctx.contains::<NodeCount>());
+
+    assert!(ctx.insert(NodeCount(3)).is_none());
+    assert_eq!(ctx.get::<NodeCount>().unwrap().0, 3);
+
+    let previous = ctx.insert(NodeCount(5)).unwrap();
+    assert_eq!(previous.0, 3);
+    assert_eq!(ctx.get::<NodeCount>().unwrap().0, 5);
+}
+
+#[test]
+fn analysis_context_get_mut_and_remove() {
+    let mut ctx = AnalysisContext::new();
+    ctx.insert(NodeCount(1));
+
+    ctx.get_mut::<NodeCount>().unwrap().0 += 1;
+    assert_eq!(ctx.remove::<NodeCount>().unwrap().0, 2);
+    assert!(ctx.get::<NodeCount>().is_none());
+}
+
+// ===========================================================================
+// PassManager
+// ===========================================================================
+
+#[test]
+fn pass_manager_runs_passes_in_order() {
+    let graph = build_diamond_graph();
+
+    let mut manager = PassManager::new();
+    manager.add_pass(NodeCountPass).add_pass(DoubledCountPass);
+    assert_eq!(manager.pass_names(), vec!["node_count", "doubled_count"]);
+
+    let ctx = manager.run(&graph).unwrap();
+    assert_eq!(ctx.get::<NodeCount>().unwrap().0, 4);
+    assert_eq!(*ctx.get::<usize>().unwrap(), 8);
+    assert_eq!(ctx.completed_passes(), &["node_count", "doubled_count"]);
+}
+
+#[test]
+fn pass_manager_records_pass_timings() {
+    let graph = build_diamond_graph();
+
+    let mut manager = PassManager::new();
+    manager.add_pass(NodeCountPass).add_pass(DoubledCountPass);
+
+    let ctx = manager.run(&graph).unwrap();
+    let names: Vec<&str> = ctx.pass_timings().iter().map(|timing| timing.name.as_str()).collect();
+    assert_eq!(names, ctx.completed_passes());
+    assert!(AnalysisContext::new().pass_timings().is_empty());
+}
+
+#[test]
+fn pass_manager_stops_at_first_error() {
+    let graph = build_diamond_graph();
+
+    let mut manager = PassManager::new();
+    manager.add_pass(DoubledCountPass).add_pass(NodeCountPass);
+
+    assert!(manager.run(&graph).is_err());
+}
+
+#[test]
+fn pass_manager_builtin_passes() {
+    let graph = build_branch_graph();
+    let provider = TestMetadataProvider::comprehensive();
+
+    let mut manager = PassManager::new();
+    manager.add_pass(DataFlowPass::new(&provider));
+    manager.add_pass(ExecutionRoutingPass);
+
+    let analysis = manager.run(&graph).unwrap();
+    let routing = analysis.get::<ExecutionRouting>().unwrap();
+    assert_eq!(routing.get_connected_nodes("branch_1", "True"), &["print_true"]);
+    assert!(analysis.get::<DataResolver>().is_some());
+}
+
+#[test]
+fn code_generator_context_from_analysis() {
+    let graph = build_diamond_graph();
+    let provider = TestMetadataProvider::with_math_nodes();
+
+    let mut manager = PassManager::new();
+    manager.add_pass(DataFlowPass::new(&provider));
+    manager.add_pass(ExecutionRoutingPass);
+    manager.add_pass(NodeCountPass);
+
+    let analysis = manager.run(&graph).unwrap();
+    let ctx = CodeGeneratorContext::from_analysis(&graph, &provider, &analysis).unwrap();
+
+    assert_eq!(ctx.data_resolver.get_pure_evaluation_order().len(), 4);
+    assert_eq!(ctx.get_analysis::<NodeCount>().unwrap().0, 4);
+}
+
+#[test]
+fn code_generator_context_from_incomplete_analysis() {
+    let graph = build_diamond_graph();
+    let provider = TestMetadataProvider::with_math_nodes();
+
+    let analysis = AnalysisContext::new();
+    let result = CodeGeneratorContext::from_analysis(&graph, &provider, &analysis);
+    assert!(matches!(result, Err(GraphyError::CodeGeneration(_))));
+}
@@ -0,0 +1,157 @@
+//! Property tests on random graphs (`arbitrary` feature).
+#![cfg(feature = "arbitrary")]
+
+use graphy::analysis::DataResolver;
+use graphy::testing::*;
+use graphy::*;
+
+/// Seeds every property is checked on
+const SEEDS: u64 = 200;
+
+// ============================================================================
+// Generator
+// ============================================================================
+
+#[test]
+fn arbitrary_graphs_are_well_formed() {
+    let registry = canonical_registry();
+    let generator = GraphGenerator::new(&registry).with_max_nodes(24);
+    for seed in 0..SEEDS {
+        let graph = generator.generate_seeded(seed).unwrap();
+        assert!(graph.nodes.len() <= 24);
+
+        let mut connected_inputs = std::collections::HashSet::new();
+        for connection in &graph.connections {
+            let source = graph.nodes[&connection.source_node]
+                .outputs
+                .iter()
+                .find(|output| output.id == connection.source_pin)
+                .unwrap_or_else(|| panic!("seed {}: missing source pin of {:?}", seed, connection));
+            let target = graph.nodes[&connection.target_node]
+                .inputs
+                .iter()
+                .find(|input| input.id == connection.target_pin)
+                .unwrap_or_else(|| panic!("seed {}: missing target pin of {:?}", seed, connection));
+            assert_eq!(source.pin.data_type, target.pin.data_type, "seed {}", seed);
+            assert_ne!(connection.source_node, connection.target_node, "seed {}", seed);
+            if !target.pin.data_type.is_execution() {
+                assert!(
+                    connected_inputs.insert((&connection.target_node, &connection.target_pin)),
+                    "seed {}: input connected twice",
+                    seed
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn arbitrary_graphs_depend_only_on_their_data() {
+    let registry = canonical_registry();
+    let generator = GraphGenerator::new(&registry);
+    for seed in 0..20 {
+        let first = generator.generate_seeded(seed).unwrap();
+        let second = generator.generate_seeded(seed).unwrap();
+        assert_eq!(first.content_hash(), second.content_hash());
+    }
+
+    let graph = GraphDescription::arbitrary(&mut Unstructured::new(&[])).unwrap();
+    assert!(graph.nodes.is_empty());
+}
+
+#[test]
+fn arbitrary_graphs_vary() {
+    let registry = canonical_registry();
+    let generator = GraphGenerator::new(&registry);
+    let hashes: std::collections::HashSet<u64> =
+        (0..50).map(|seed| generator.generate_seeded(seed).unwrap().content_hash()).collect();
+    assert!(hashes.len() > 40, "only {} distinct graphs", hashes.len());
+}
+
+#[test]
+fn arbitrary_generator_without_node_types_gives_empty_graphs() {
+    let registry = NodeRegistry::new();
+    let graph = GraphGenerator::new(&registry).generate_seeded(7).unwrap();
+    assert!(graph.nodes.is_empty());
+}
+
+// ============================================================================
+// Analysis invariants
+// ============================================================================
+
+#[test]
+fn arbitrary_acyclic_graphs_resolve() {
+    let registry = canonical_registry();
+    let generator = GraphGenerator::new(&registry);
+    for seed in 0..SEEDS {
+        let graph = generator.generate_seeded(seed).unwrap();
+        let resolver = DataResolver::build(&graph, &registry)
+            .unwrap_or_else(|error| panic!("seed {}: {}", seed, error));
+
+        // Every pure node is evaluated after the pure nodes it reads
+        let order = resolver.get_pure_evaluation_order();
+        for connection in graph.connections.iter().filter(|c| c.connection_type == ConnectionType::Data) {
+            let source = order.iter().position(|id| *id == connection.source_node);
+            let target = order.iter().position(|id| *id == connection.target_node);
+            if let (Some(source), Some(target)) = (source, target) {
+                assert!(source < target, "seed {}: {:?}", seed, connection);
+            }
+        }
+    }
+}
+
+#[test]
+fn arbitrary_cyclic_graphs_are_rejected_not_looped() {
+    let registry = canonical_registry();
+    let generator = GraphGenerator::new(&registry).with_acyclic_data(false);
+    let cycles = (0..SEEDS)
+        .filter(|&seed| {
+            let graph = generator.generate_seeded(seed).unwrap();
+            matches!(DataResolver::build(&graph, &registry), Err(GraphyError::CyclicDependency { .. }))
+        })
+        .count();
+    assert!(cycles > 0, "no generated graph had a data cycle");
+}
+
+#[test]
+fn arbitrary_graphs_survive_serialization() {
+    let registry = canonical_registry();
+    let generator = GraphGenerator::new(&registry);
+    for seed in 0..50 {
+        let graph = generator.generate_seeded(seed).unwrap();
+        let json = serde_json::to_string(&graph).unwrap();
+        let loaded: GraphDescription = serde_json::from_str(&json).unwrap();
+        assert!(loaded.semantically_equals(&graph), "seed {}", seed);
+    }
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn arbitrary_parallel_build_matches_sequential() {
+    let registry = canonical_registry();
+    let generator = GraphGenerator::new(&registry);
+    for seed in 0..SEEDS {
+        let graph = generator.generate_seeded(seed).unwrap();
+        let sequential = DataResolver::build(&graph, &registry).unwrap();
+        let parallel = DataResolver::build_parallel(&graph, &registry).unwrap();
+
+        let mut sequential_order = sequential.get_pure_evaluation_order().to_vec();
+        let mut parallel_order = parallel.get_pure_evaluation_order().to_vec();
+        sequential_order.sort();
+        parallel_order.sort();
+        assert_eq!(sequential_order, parallel_order, "seed {}", seed);
+
+        for node in graph.nodes.values() {
+            for input in &node.inputs {
+                assert_eq!(
+                    format!("{:?}", sequential.get_input_source(&node.id, &input.id)),
+                    format!("{:?}", parallel.get_input_source(&node.id, &input.id)),
+                    "seed {}: {}.{}",
+                    seed,
+                    node.id,
+                    input.id
+                );
+            }
+        }
+    }
+}
@@ -1,6 +1,9 @@
-//! Tests for AST transformation: inline_control_flow_function, extract_exec_output_labels.
+//! Tests for AST transformation: inline_control_flow_function, extract_exec_output_labels, payloads.
 
-use graphy::utils::{inline_control_flow_function, extract_exec_output_labels};
+use graphy::utils::{
+    extract_exec_output_labels, extract_exec_outputs, inline_control_flow_function, inline_control_flow_with_payloads,
+    ExecOutputCall, ExecReplacement,
+};
 use std::collections::HashMap;
 
 // ===========================================================================
@@ -249,3 +252,209 @@ fn inline_nested_if_else() {
     let result = inline_control_flow_function(source, exec_replacements, HashMap::new());
     assert!(result.is_ok());
 }
+
+// ===========================================================================
+// inline_control_flow_function - Statement lists
+// ===========================================================================
+
+fn normalized(code: &str) -> String {
+    code.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[test]
+fn inline_splices_every_replacement_statement() {
+    let source = r#"
+        fn sequence() {
+            exec_output!("first");
+            exec_output!("second");
+        }
+    "#;
+
+    let mut exec_replacements = HashMap::new();
+    exec_replacements.insert("first".to_string(), "let x = load(); step_a(x); step_b(x);".to_string());
+    exec_replacements.insert("second".to_string(), "finish();".to_string());
+
+    let code = normalized(&inline_control_flow_function(source, exec_replacements, HashMap::new()).unwrap());
+    assert_eq!(code, "let x = load () ; step_a (x) ; step_b (x) ; finish () ;");
+}
+
+#[test]
+fn inline_splices_statements_into_nested_blocks() {
+    let source = r#"
+        fn branch(condition: bool) {
+            if condition {
+                exec_output!("True");
+            } else {
+                exec_output!("False");
+            }
+            exec_output!("After");
+        }
+    "#;
+
+    let mut exec_replacements = HashMap::new();
+    exec_replacements.insert("True".to_string(), "a(); b();".to_string());
+    exec_replacements.insert("False".to_string(), "c(); if d() { e(); }".to_string());
+    exec_replacements.insert("After".to_string(), "f(); g();".to_string());
+
+    let code = normalized(&inline_control_flow_function(source, exec_replacements, HashMap::new()).unwrap());
+    assert_eq!(code, "if condition { a () ; b () ; } else { c () ; if d () { e () ; } } f () ; g () ;");
+}
+
+#[test]
+fn inline_tail_expression_keeps_every_statement() {
+    let source = r#"
+        fn branch(condition: bool) {
+            if condition { exec_output!("True") } else { exec_output!("False") }
+        }
+    "#;
+
+    let mut exec_replacements = HashMap::new();
+    exec_replacements.insert("True".to_string(), "a(); b();".to_string());
+    exec_replacements.insert("False".to_string(), "c()".to_string());
+
+    let code = normalized(&inline_control_flow_function(source, exec_replacements, HashMap::new()).unwrap());
+    assert_eq!(code, "if condition { { a () ; b () ; } } else { c () }");
+}
+
+#[test]
+fn inline_empty_replacement_removes_call() {
+    let source = r#"
+        fn noop() {
+            exec_output!("Done");
+            finish();
+        }
+    "#;
+
+    let mut exec_replacements = HashMap::new();
+    exec_replacements.insert("Done".to_string(), String::new());
+
+    let code = normalized(&inline_control_flow_function(source, exec_replacements, HashMap::new()).unwrap());
+    assert_eq!(code, "finish () ;");
+}
+
+#[test]
+fn inline_invalid_replacement_fails() {
+    let source = r#"
+        fn noop() {
+            exec_output!("Done");
+        }
+    "#;
+
+    let mut exec_replacements = HashMap::new();
+    exec_replacements.insert("Done".to_string(), "let = ;".to_string());
+
+    let error = inline_control_flow_function(source, exec_replacements, HashMap::new()).unwrap_err();
+    assert!(error.to_string().contains("Done"), "{}", error);
+}
+
+// ===========================================================================
+// exec_output! payloads
+// ===========================================================================
+
+const FOR_LOOP: &str = r#"
+    fn for_loop(start: i64, end: i64) {
+        for i in start..end {
+            exec_output!("body", i);
+        }
+        exec_output!("completed");
+    }
+"#;
+
+#[test]
+fn extract_outputs_with_payloads() {
+    let calls = extract_exec_outputs(FOR_LOOP).unwrap();
+    assert_eq!(
+        calls,
+        vec![
+            ExecOutputCall {
+                label: "body".into(),
+                payloads: vec!["i".into()],
+            },
+            ExecOutputCall {
+                label: "completed".into(),
+                payloads: vec![],
+            },
+        ]
+    );
+    assert_eq!(extract_exec_output_labels(FOR_LOOP).unwrap(), vec!["body", "completed"]);
+}
+
+#[test]
+fn extract_outputs_with_expression_payloads() {
+    let source = r#"
+        fn pairs(count: i64) {
+            for i in 0..count {
+                exec_output!("pair", i, i * 2,);
+            }
+        }
+    "#;
+
+    let calls = extract_exec_outputs(source).unwrap();
+    assert_eq!(calls[0].payloads, vec!["i", "i * 2"]);
+}
+
+#[test]
+fn inline_binds_payloads_in_replacement_block() {
+    let mut exec_replacements = HashMap::new();
+    exec_replacements.insert(
+        "body".to_string(),
+        ExecReplacement::new("print(index); print(index + 1);").with_bindings(vec!["index".into()]),
+    );
+    exec_replacements.insert("completed".to_string(), ExecReplacement::new("done();"));
+
+    let mut param_substitutions = HashMap::new();
+    param_substitutions.insert("end".to_string(), "10".to_string());
+
+    let code = inline_control_flow_with_payloads(FOR_LOOP, exec_replacements, param_substitutions).unwrap();
+    let normalized = normalized(&code);
+    assert!(
+        normalized.contains("for i in start .. 10 { { let index = i ; print (index) ; print (index + 1) ; } }"),
+        "{}",
+        normalized
+    );
+    assert!(normalized.contains("done ()"), "{}", normalized);
+    assert!(!normalized.contains("exec_output"), "{}", normalized);
+}
+
+#[test]
+fn inline_binds_expression_payloads() {
+    let source = r#"
+        fn pairs(count: i64) {
+            for i in 0..count {
+                exec_output!("pair", i, i * 2);
+            }
+        }
+    "#;
+
+    let mut exec_replacements = HashMap::new();
+    exec_replacements.insert(
+        "pair".to_string(),
+        ExecReplacement::new("show(a, b);").with_bindings(vec!["a".into(), "b".into()]),
+    );
+
+    let code = inline_control_flow_with_payloads(source, exec_replacements, HashMap::new()).unwrap();
+    let normalized = normalized(&code);
+    assert!(normalized.contains("let a = i ; let b = i * 2 ; show (a , b) ;"), "{}", normalized);
+}
+
+#[test]
+fn inline_payloads_without_bindings_are_dropped() {
+    let mut exec_replacements = HashMap::new();
+    exec_replacements.insert("body".to_string(), "tick();".to_string());
+
+    let code = inline_control_flow_function(FOR_LOOP, exec_replacements, HashMap::new()).unwrap();
+    assert!(code.contains("tick ()"), "{}", code);
+    assert!(!code.contains("exec_output ! (\"body\""), "{}", code);
+}
+
+#[test]
+fn inline_more_bindings_than_payloads_fails() {
+    let mut exec_replacements = HashMap::new();
+    exec_replacements.insert(
+        "completed".to_string(),
+        ExecReplacement::new("done();").with_bindings(vec!["index".into()]),
+    );
+
+    let error = inline_control_flow_with_payloads(FOR_LOOP, exec_replacements, HashMap::new()).unwrap_err();
+    assert!(error.to_string().contains("completed"), "{}", error);
+}
@@ -0,0 +1,263 @@
+//! Tests for generating repeated exec branches as helper functions.
+
+mod common;
+
+use common::*;
+use graphy::analysis::{DataResolver, ExecutionRouting, IsomorphicBranches};
+use graphy::generation::{compile, compile_with_options, BranchHelpers, CompilationSession, CompileOptions, DebugHooks};
+use graphy::*;
+use std::sync::Arc;
+
+/// Generator binding the results of function nodes, with branch helpers if `helpers`
+fn call_generator(helpers: bool) -> CallGenerator {
+    CallGenerator::new().with_bound_results(true).with_branch_helpers(helpers)
+}
+
+/// `on_start`, a four-way `sequence`, `spawn` (an `f64` position, returning
+/// an `Entity`), `set_health` (an `Entity` and an `i32`), `log` (an
+/// `Entity`), and pure `double`
+fn registry() -> NodeRegistry {
+    let mut registry = NodeRegistry::new();
+    registry.register(NodeMetadata::new("on_start", NodeTypes::event, "Events").with_exec_outputs(vec!["exec".into()]));
+    registry.register(
+        NodeMetadata::new("sequence", NodeTypes::control_flow, "Flow")
+            .with_exec_outputs((0..4).map(|index| format!("then_{}", index)).collect()),
+    );
+    registry.register(
+        NodeMetadata::new("spawn", NodeTypes::fn_, "World")
+            .with_params(vec![ParamInfo::new("position", "f64")])
+            .with_return_type("Entity")
+            .with_exec_outputs(vec!["exec_out".into()]),
+    );
+    registry.register(
+        NodeMetadata::new("set_health", NodeTypes::fn_, "World")
+            .with_params(vec![ParamInfo::new("entity", "Entity"), ParamInfo::new("health", "i32")])
+            .with_exec_outputs(vec!["exec_out".into()]),
+    );
+    registry.register(
+        NodeMetadata::new("log", NodeTypes::fn_, "Debug")
+            .with_params(vec![ParamInfo::new("entity", "Entity")])
+            .with_exec_outputs(vec!["exec_out".into()]),
+    );
+    registry.register(
+        NodeMetadata::new("double", NodeTypes::pure, "Math")
+            .with_params(vec![ParamInfo::new("x", "f64")])
+            .with_return_type("f64"),
+    );
+    registry
+}
+
+/// `start -> sequence_1`, whose output `i` spawns an entity at `i` and
+/// sets its health to `healths[i]`
+fn spawner_graph(registry: &NodeRegistry, healths: &[i64]) -> GraphDescription {
+    let mut graph = GraphDescription::new("spawner");
+    graph.add_node_of_type("on_start", "start", registry).unwrap();
+    graph.add_node_of_type("sequence", "sequence_1", registry).unwrap();
+    graph.add_connection(Connection::execution("start", "exec", "sequence_1", "exec_in"));
+    for (index, health) in healths.iter().enumerate() {
+        let spawn = format!("spawn_{}", index);
+        let set_health = format!("set_health_{}", index);
+        graph.add_node_of_type("spawn", &spawn, registry).unwrap();
+        graph.add_node_of_type("set_health", &set_health, registry).unwrap();
+        graph
+            .get_node_mut(&spawn)
+            .unwrap()
+            .set_property("position", PropertyValue::Number(index as f64 + 0.5));
+        graph
+            .get_node_mut(&set_health)
+            .unwrap()
+            .set_property("health", PropertyValue::Integer(*health));
+        graph.add_connection(Connection::execution(
+            "sequence_1",
+            format!("then_{}", index).as_str(),
+            spawn.as_str(),
+            "exec_in",
+        ));
+        graph.add_connection(Connection::execution(spawn.as_str(), "exec_out", set_health.as_str(), "exec_in"));
+        graph.add_connection(Connection::data(spawn.as_str(), "result", set_health.as_str(), "entity"));
+    }
+    graph
+}
+
+fn options() -> CompileOptions {
+    CompileOptions::new().with_branch_helpers(BranchHelpers::new(2))
+}
+
+fn compile_helpers(graph: &GraphDescription, registry: &NodeRegistry) -> String {
+    compile_with_options(graph, registry, &mut call_generator(true), &options())
+        .unwrap()
+        .code
+}
+
+fn branches(graph: &GraphDescription, registry: &NodeRegistry, min_nodes: usize) -> IsomorphicBranches {
+    let data_resolver = DataResolver::build(graph, registry).unwrap();
+    let exec_routing = ExecutionRouting::build_from_graph(graph);
+    IsomorphicBranches::compute(graph, registry, &exec_routing, &data_resolver, min_nodes)
+}
+
+// ============================================================================
+// Analysis
+// ============================================================================
+
+#[test]
+fn branch_helpers_group_isomorphic_branches() {
+    let registry = registry();
+    let graph = spawner_graph(&registry, &[10, 20, 30]);
+    let branches = branches(&graph, &registry, 2);
+
+    assert_eq!(branches.len(), 1);
+    let class = &branches.classes()[0];
+    assert_eq!(class.node_count(), 2);
+    let roots: Vec<&str> = class.instances.iter().map(|instance| instance.root.as_str()).collect();
+    assert_eq!(roots, vec!["spawn_0", "spawn_1", "spawn_2"]);
+    assert_eq!(class.instances[2].nodes, vec!["spawn_2", "set_health_2"]);
+
+    let params: Vec<(usize, &str, &str)> = class
+        .params
+        .iter()
+        .map(|param| (param.node, param.pin.as_str(), param.param_type.as_str()))
+        .collect();
+    assert_eq!(params, vec![(0, "position", "f64"), (1, "health", "i32")]);
+    assert_eq!(class.instances[1].arguments, vec![PropertyValue::Number(1.5), PropertyValue::Integer(20)]);
+    assert_eq!(branches.instance_at("spawn_1").unwrap().1.root, "spawn_1");
+    assert!(branches.instance_at("set_health_1").is_none());
+}
+
+#[test]
+fn branch_helpers_keep_shared_constants_out_of_params() {
+    let registry = registry();
+    let graph = spawner_graph(&registry, &[10, 10, 10]);
+    let branches = branches(&graph, &registry, 2);
+
+    let class = &branches.classes()[0];
+    assert_eq!(class.params.len(), 1);
+    assert_eq!(class.params[0].pin, "position");
+}
+
+#[test]
+fn branch_helpers_need_matching_structure() {
+    let registry = registry();
+    let mut graph = spawner_graph(&registry, &[10, 20, 30]);
+    // The last branch doubles its position
+    graph.add_node_of_type("double", "double_2", &registry).unwrap();
+    graph.add_connection(Connection::data("double_2", "result", "spawn_2", "position"));
+
+    let branches = branches(&graph, &registry, 2);
+    let class = &branches.classes()[0];
+    let roots: Vec<&str> = class.instances.iter().map(|instance| instance.root.as_str()).collect();
+    assert_eq!(roots, vec!["spawn_0", "spawn_1"]);
+}
+
+#[test]
+fn branch_helpers_skip_branches_read_from_outside() {
+    let registry = registry();
+    let mut graph = spawner_graph(&registry, &[10, 20, 30]);
+    graph.add_node_of_type("log", "log_1", &registry).unwrap();
+    graph.add_connection(Connection::execution("sequence_1", "then_3", "log_1", "exec_in"));
+    graph.add_connection(Connection::data("spawn_0", "result", "log_1", "entity"));
+
+    let branches = branches(&graph, &registry, 2);
+    let class = &branches.classes()[0];
+    let roots: Vec<&str> = class.instances.iter().map(|instance| instance.root.as_str()).collect();
+    assert_eq!(roots, vec!["spawn_1", "spawn_2"]);
+}
+
+#[test]
+fn branch_helpers_ignore_small_branches() {
+    let registry = registry();
+    let graph = spawner_graph(&registry, &[10, 20, 30]);
+    assert!(branches(&graph, &registry, 3).is_empty());
+}
+
+// ============================================================================
+// Code generation
+// ============================================================================
+
+#[test]
+fn branch_helpers_emit_one_function_per_class() {
+    let registry = registry();
+    let graph = spawner_graph(&registry, &[10, 20, 30]);
+
+    assert_eq!(
+        compile_helpers(&graph, &registry),
+        "fn branch_helper_0(position_0: f64, health_1: i32) {\n    \
+         let node_spawn_0_result = spawn(position_0);\n    \
+         set_health(node_spawn_0_result, health_1);\n\
+         }\n\
+         branch_helper_0(0.5, 10);\n\
+         branch_helper_0(1.5, 20);\n\
+         branch_helper_0(2.5, 30);\n"
+    );
+}
+
+#[test]
+fn branch_helpers_generate_other_branches_in_place() {
+    let registry = registry();
+    let mut graph = spawner_graph(&registry, &[10, 20, 30]);
+    graph.add_node_of_type("double", "double_2", &registry).unwrap();
+    graph.add_connection(Connection::data("double_2", "result", "spawn_2", "position"));
+
+    let code = compile_helpers(&graph, &registry);
+    assert!(
+        code.ends_with(
+            "branch_helper_0(0.5, 10);\n\
+             branch_helper_0(1.5, 20);\n\
+             let node_spawn_2_result = spawn(double(0.0));\n\
+             set_health(node_spawn_2_result, 30);\n"
+        ),
+        "{}",
+        code
+    );
+}
+
+#[test]
+fn branch_helpers_are_opt_in() {
+    let registry = registry();
+    let graph = spawner_graph(&registry, &[10, 20]);
+    let plain = "let node_spawn_0_result = spawn(0.5);\n\
+                 set_health(node_spawn_0_result, 10);\n\
+                 let node_spawn_1_result = spawn(1.5);\n\
+                 set_health(node_spawn_1_result, 20);\n";
+
+    assert_eq!(compile(&graph, &registry, &mut call_generator(true)).unwrap(), plain);
+    let mut unsupported = call_generator(false);
+    assert_eq!(compile_with_options(&graph, &registry, &mut unsupported, &options()).unwrap().code, plain);
+}
+
+#[test]
+fn branch_helpers_are_off_in_debug_builds() {
+    let registry = registry();
+    let graph = spawner_graph(&registry, &[10, 20]);
+    let debug = options().with_debug(DebugHooks::new());
+    let code = compile_with_options(&graph, &registry, &mut call_generator(true), &debug).unwrap().code;
+
+    // Hooks name the nodes they run at, so every branch is generated in place
+    assert_eq!(code, compile(&graph, &registry, &mut call_generator(true)).unwrap());
+}
+
+// ============================================================================
+// Source maps and sessions
+// ============================================================================
+
+#[test]
+fn branch_helpers_map_calls_to_their_root() {
+    let registry = registry();
+    let graph = spawner_graph(&registry, &[10, 20]);
+    let mut generator = call_generator(true);
+    let output = compile_with_options(&graph, &registry, &mut generator, &options()).unwrap();
+
+    assert_eq!(output.source_map.resolve(2, 5).unwrap().node_id, "spawn_0");
+    assert_eq!(output.source_map.resolve(3, 5).unwrap().node_id, "set_health_0");
+    assert_eq!(output.source_map.resolve(6, 1).unwrap().node_id, "spawn_1");
+}
+
+#[test]
+fn branch_helpers_apply_to_sessions() {
+    let registry = registry();
+    let graph = spawner_graph(&registry, &[10, 20, 30]);
+    let mut generator = call_generator(true);
+    let session = CompilationSession::new(&graph, Arc::new(registry.clone()), &generator, &options()).unwrap();
+
+    let output = session.finish(&mut generator).unwrap();
+    assert_eq!(output.code, compile_helpers(&graph, &registry));
+}
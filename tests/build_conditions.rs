@@ -0,0 +1,161 @@
+//! Tests for build conditions and per-configuration pruning.
+
+mod common;
+
+use common::*;
+use graphy::core::{BuildCondition, CompileConfig, GraphGroup};
+use graphy::generation::{compile_with_options, CompileOptions};
+use graphy::utils::diff::GraphDiff;
+use graphy::*;
+
+/// Generator listing the function nodes it runs, one per line
+fn trace_generator() -> HookGenerator {
+    HookGenerator::new().with_statement(|node, _| Ok(node.id.clone()))
+}
+
+fn console() -> CompileConfig {
+    CompileConfig::new().with_platform("console")
+}
+
+/// The branch graph with `print_true` only on consoles
+fn build_conditional_graph() -> GraphDescription {
+    let mut graph = build_branch_graph();
+    graph.nodes.get_mut("print_true").unwrap().condition = Some(BuildCondition::platform("console"));
+    graph
+}
+
+fn compile_for(graph: &GraphDescription, config: CompileConfig) -> String {
+    let options = CompileOptions::new().with_config(config);
+    compile_with_options(graph, &TestMetadataProvider::comprehensive(), &mut trace_generator(), &options)
+        .unwrap()
+        .code
+}
+
+// ============================================================================
+// Conditions
+// ============================================================================
+
+#[test]
+fn condition_evaluates_features_and_platform() {
+    let config = CompileConfig::new().with_feature("haptics").with_platform("console");
+    assert!(BuildCondition::feature("haptics").evaluate(&config));
+    assert!(!BuildCondition::feature("vr").evaluate(&config));
+    assert!(BuildCondition::platform("console").evaluate(&config));
+    assert!(!BuildCondition::platform("windows").evaluate(&config));
+    assert!(!BuildCondition::platform("console").evaluate(&CompileConfig::new()));
+
+    assert!((BuildCondition::feature("haptics") & BuildCondition::platform("console")).evaluate(&config));
+    assert!((BuildCondition::feature("vr") | BuildCondition::platform("console")).evaluate(&config));
+    assert!((!BuildCondition::feature("vr")).evaluate(&config));
+    assert!(BuildCondition::All(Vec::new()).evaluate(&config));
+    assert!(!BuildCondition::Any(Vec::new()).evaluate(&config));
+}
+
+#[test]
+fn condition_operators_flatten_and_display() {
+    let condition = BuildCondition::platform("console") & BuildCondition::feature("a") & !BuildCondition::feature("b");
+    assert_eq!(
+        condition.to_string(),
+        "all(platform = \"console\", feature = \"a\", not(feature = \"b\"))"
+    );
+    assert_eq!(!!BuildCondition::feature("a"), BuildCondition::feature("a"));
+    assert_eq!(
+        (BuildCondition::feature("a") | BuildCondition::feature("b") | BuildCondition::feature("c")).to_string(),
+        "any(feature = \"a\", feature = \"b\", feature = \"c\")"
+    );
+}
+
+#[test]
+fn condition_serializes() {
+    let condition = BuildCondition::platform("console") & !BuildCondition::feature("b");
+    let json = serde_json::to_value(&condition).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({ "all": [{ "platform": "console" }, { "not": { "feature": "b" } }] })
+    );
+    assert_eq!(serde_json::from_value::<BuildCondition>(json).unwrap(), condition);
+
+    let graph = build_conditional_graph();
+    let json = serde_json::to_value(&graph).unwrap();
+    assert!(json["nodes"]["print_false"].get("condition").is_none());
+    let loaded: GraphDescription = serde_json::from_value(json).unwrap();
+    assert_eq!(loaded.nodes["print_true"].condition, Some(BuildCondition::platform("console")));
+}
+
+// ============================================================================
+// Pruning
+// ============================================================================
+
+#[test]
+fn prune_removes_disabled_nodes_and_their_connections() {
+    let mut graph = build_conditional_graph();
+    assert!(graph.is_node_enabled("print_true", &console()));
+    assert!(!graph.is_node_enabled("print_true", &CompileConfig::new()));
+    assert!(!graph.is_node_enabled("missing", &console()));
+
+    assert!(graph.clone().prune_disabled(&console()).is_empty());
+    assert_eq!(graph.prune_disabled(&CompileConfig::new()), ["print_true"]);
+    assert!(graph.get_node("print_true").is_none());
+    assert_eq!(graph.connections.len(), 2);
+    assert!(graph.connections.iter().all(|c| c.target_node != "print_true"));
+}
+
+#[test]
+fn prune_follows_group_conditions() {
+    let mut graph = build_branch_graph();
+    let mut group = GraphGroup::new("debug", "Debug").with_condition(BuildCondition::feature("debug"));
+    group.node_ids = vec!["print_true".into(), "print_false".into()];
+    graph.groups.push(group);
+
+    assert!(graph.is_node_enabled("print_false", &CompileConfig::new().with_feature("debug")));
+    assert_eq!(graph.prune_disabled(&CompileConfig::new()), ["print_false", "print_true"]);
+    assert!(graph.groups[0].node_ids.is_empty());
+    assert_eq!(graph.connections.len(), 1);
+}
+
+// ============================================================================
+// Compilation
+// ============================================================================
+
+#[test]
+fn compile_prunes_per_configuration() {
+    let graph = build_conditional_graph();
+    assert_eq!(compile_for(&graph, console()), "print_true\nprint_false\n");
+    assert_eq!(compile_for(&graph, CompileConfig::new()), "print_false\n");
+    // The caller's graph is untouched
+    assert!(graph.get_node("print_true").is_some());
+}
+
+// ============================================================================
+// Editing
+// ============================================================================
+
+#[test]
+fn conditions_are_part_of_semantics() {
+    let plain = build_branch_graph();
+    let conditional = build_conditional_graph();
+    assert!(!plain.semantically_equals(&conditional));
+    assert_ne!(plain.content_hash(), conditional.content_hash());
+
+    let mut grouped = plain.clone();
+    grouped.groups.push(GraphGroup::new("debug", "Debug"));
+    assert_eq!(plain.content_hash(), grouped.content_hash());
+    grouped.groups[0].condition = Some(BuildCondition::feature("debug"));
+    assert!(!plain.semantically_equals(&grouped));
+    assert_ne!(plain.content_hash(), grouped.content_hash());
+}
+
+#[test]
+fn conditions_diff_and_undo() {
+    let old = build_branch_graph();
+    let new = build_conditional_graph();
+    let diff = GraphDiff::compute(&old, &new);
+    assert_eq!(diff.modified_nodes.len(), 1);
+    assert!(diff.modified_nodes[0].condition.is_some());
+
+    let mut graph = old.clone();
+    diff.apply(&mut graph).unwrap();
+    assert_eq!(graph.nodes["print_true"].condition, Some(BuildCondition::platform("console")));
+    diff.invert().apply(&mut graph).unwrap();
+    assert!(graph.nodes["print_true"].condition.is_none());
+}
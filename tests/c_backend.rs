@@ -0,0 +1,345 @@
+//! Tests for the C code generation backend and its language profile.
+
+mod common;
+
+use common::*;
+use graphy::core::{ExternalDecl, VariableDecl};
+use graphy::generation::targets::{compile_c, CGenerator};
+use graphy::generation::{compile, compile_with_options, CodeGenerator, CompileOptions, DebugHooks};
+use graphy::utils::{CProfile, LanguageProfile, RustProfile};
+use graphy::*;
+
+/// Embedded-style nodes: templates for flow and math, externs for hardware
+fn c_provider() -> TestMetadataProvider {
+    let mut provider = TestMetadataProvider::new();
+    provider.add(
+        NodeMetadata::new("on_tick", NodeTypes::event, "Events")
+            .with_params(vec![ParamInfo::new("delta_time", "f32")])
+            .with_exec_outputs(vec!["exec".into()]),
+    );
+    provider.add(
+        NodeMetadata::new("read_sensor", NodeTypes::fn_, "Hardware")
+            .with_params(vec![ParamInfo::new("channel", "u8")])
+            .with_return_type("i32")
+            .with_exec_outputs(vec!["then".into()]),
+    );
+    provider.add(
+        NodeMetadata::new("write_pin", NodeTypes::fn_, "Hardware")
+            .with_params(vec![ParamInfo::new("pin", "u8"), ParamInfo::new("high", "bool")])
+            .with_exec_outputs(vec!["then".into()]),
+    );
+    provider.add(
+        NodeMetadata::new("log", NodeTypes::fn_, "Debug")
+            .with_params(vec![ParamInfo::new("message", "String")])
+            .with_source("printf(\"%s\\n\", {{message}});")
+            .with_source_kind(SourceKind::Template)
+            .with_exec_outputs(vec!["then".into()]),
+    );
+    provider.add(
+        NodeMetadata::new("greater", NodeTypes::pure, "Math")
+            .with_params(vec![ParamInfo::new("a", "i32"), ParamInfo::new("b", "i32")])
+            .with_return_type("bool")
+            .with_source("{{a}} > {{b}}")
+            .with_source_kind(SourceKind::Template),
+    );
+    provider.add(
+        NodeMetadata::new("clamp", NodeTypes::pure, "Math")
+            .with_params(vec![ParamInfo::new("value", "i32")])
+            .with_return_type("i32"),
+    );
+    provider.add(
+        NodeMetadata::new("branch", NodeTypes::control_flow, "Flow")
+            .with_params(vec![ParamInfo::new("condition", "bool")])
+            .with_source("if ({{condition}}) {\n    {{exec:True}}\n} else {\n    {{exec:False}}\n}")
+            .with_source_kind(SourceKind::Template)
+            .with_exec_outputs(vec!["True".into(), "False".into()]),
+    );
+    provider.add(
+        NodeMetadata::new("gate", NodeTypes::control_flow, "Flow")
+            .with_params(vec![ParamInfo::new("open", "bool")])
+            .with_exec_outputs(vec!["Open".into()]),
+    );
+    provider
+}
+
+/// on_tick -> read_sensor -> branch(reading > 100) -> write_pin(13, true) / log("low")
+fn build_sensor_graph() -> GraphDescription {
+    let mut graph = GraphDescription::new("sensor");
+    let mut tick = NodeInstance::new("tick", "on_tick", Position::zero());
+    tick.add_output_pin("exec", DataType::Execution);
+    graph.add_node(tick);
+
+    exec_node(&mut graph, "read_1", "read_sensor", &["then"]);
+    set_input(&mut graph, "read_1", "channel", PropertyValue::Integer(2));
+    exec_node(&mut graph, "branch_1", "branch", &["True", "False"]);
+    exec_node(&mut graph, "write_1", "write_pin", &["then"]);
+    set_input(&mut graph, "write_1", "pin", PropertyValue::Integer(13));
+    set_input(&mut graph, "write_1", "high", PropertyValue::Boolean(true));
+    exec_node(&mut graph, "log_1", "log", &["then"]);
+    set_input(&mut graph, "log_1", "message", PropertyValue::String("low".into()));
+
+    graph.add_node(NodeInstance::new("greater_1", "greater", Position::zero()));
+    set_input(&mut graph, "greater_1", "b", PropertyValue::Integer(100));
+
+    graph.add_connection(Connection::execution("tick", "exec", "read_1", "exec_in"));
+    graph.add_connection(Connection::execution("read_1", "then", "branch_1", "exec_in"));
+    graph.add_connection(Connection::execution("branch_1", "True", "write_1", "exec_in"));
+    graph.add_connection(Connection::execution("branch_1", "False", "log_1", "exec_in"));
+    graph.add_connection(Connection::data("read_1", "result", "greater_1", "a"));
+    graph.add_connection(Connection::data("greater_1", "result", "branch_1", "condition"));
+    graph
+}
+
+// ===========================================================================
+// Profile
+// ===========================================================================
+
+#[test]
+fn c_profile_maps_types() {
+    let profile = CProfile::new();
+    assert_eq!(profile.map_type("i64"), "int64_t");
+    assert_eq!(profile.map_type("u8"), "uint8_t");
+    assert_eq!(profile.map_type("f32"), "float");
+    assert_eq!(profile.map_type("String"), "const char*");
+    assert_eq!(profile.map_type("sensor_t"), "sensor_t");
+
+    let profile = profile.with_type("String", "char*").with_type("Vec3", "vec3_t");
+    assert_eq!(profile.map_type("String"), "char*");
+    assert_eq!(profile.map_type("Vec3"), "vec3_t");
+    assert_eq!(RustProfile.map_type("i64"), "i64");
+}
+
+#[test]
+fn c_profile_defaults_follow_mapped_types() {
+    let profile = CProfile::new().with_type("Vec3", "vec3_t").with_type("Tick", "uint32_t");
+    assert_eq!(profile.default_value("i64"), "0");
+    assert_eq!(profile.default_value("Tick"), "0");
+    assert_eq!(profile.default_value("f32"), "0.0f");
+    assert_eq!(profile.default_value("f64"), "0.0");
+    assert_eq!(profile.default_value("bool"), "false");
+    assert_eq!(profile.default_value("String"), "\"\"");
+    assert_eq!(profile.default_value("Vec3"), "(vec3_t){0}");
+}
+
+#[test]
+fn c_profile_formats_literals() {
+    let profile = CProfile::new();
+    let literal = |value| profile.format_literal(&value).unwrap();
+    assert_eq!(literal(PropertyValue::Integer(-3)), "-3");
+    assert_eq!(literal(PropertyValue::Number(2.0)), "2.0");
+    assert_eq!(literal(PropertyValue::Boolean(true)), "true");
+    assert_eq!(literal(PropertyValue::String("say \"hi\"\n".into())), "\"say \\\"hi\\\"\\n\"");
+    assert_eq!(literal(PropertyValue::String("é1".into())), "\"\\303\\2511\"");
+
+    for value in [
+        PropertyValue::Number(f64::NAN),
+        PropertyValue::Vector2(1.0, 2.0),
+        PropertyValue::Array(vec![PropertyValue::Integer(1)]),
+    ] {
+        assert!(profile.format_literal(&value).is_err(), "{:?}", value);
+    }
+}
+
+#[test]
+fn c_profile_sanitizes_identifiers() {
+    let profile = CProfile::new();
+    assert_eq!(profile.sanitize_identifier("int"), "int_");
+    assert_eq!(profile.sanitize_identifier("class"), "class_");
+    assert_eq!(profile.sanitize_identifier("café"), "caf_");
+    assert_eq!(profile.sanitize_identifier("value"), "value");
+}
+
+// ===========================================================================
+// Source file
+// ===========================================================================
+
+#[test]
+fn c_events_become_functions() {
+    let sources = compile_c(&build_sensor_graph(), &c_provider(), &mut CGenerator::new("sensor")).unwrap();
+    assert_eq!(sources.source_file_name(), "sensor.c");
+    assert!(sources.source.starts_with("#include \"sensor.h\"\n"), "{}", sources.source);
+    assert!(sources.source.contains("void sensor_tick(float delta_time) {\n"), "{}", sources.source);
+}
+
+#[test]
+fn c_extern_calls_store_read_results() {
+    let sources = compile_c(&build_sensor_graph(), &c_provider(), &mut CGenerator::new("sensor")).unwrap();
+    assert!(sources.source.contains("    int32_t node_read_1_result = read_sensor(2);\n"), "{}", sources.source);
+    assert!(sources.source.contains("write_pin(13, true);\n"), "{}", sources.source);
+}
+
+#[test]
+fn c_control_flow_templates_place_branches() {
+    let sources = compile_c(&build_sensor_graph(), &c_provider(), &mut CGenerator::new("sensor")).unwrap();
+    let expected = "    if ((node_read_1_result > 100)) {\n        write_pin(13, true);\n    } else {\n        printf(\"%s\\n\", \"low\");\n    }\n";
+    assert!(sources.source.contains(expected), "{}", sources.source);
+}
+
+#[test]
+fn c_static_helpers_for_pure_templates() {
+    let mut generator = CGenerator::new("sensor").with_static_helpers(true);
+    let sources = compile_c(&build_sensor_graph(), &c_provider(), &mut generator).unwrap();
+    assert!(
+        sources.source.contains("static inline bool greater(int32_t a, int32_t b) {\n    return a > b;\n}\n"),
+        "{}",
+        sources.source
+    );
+    assert!(sources.source.contains("if (greater(node_read_1_result, 100)) {"), "{}", sources.source);
+}
+
+#[test]
+fn c_hoisted_pure_nodes_are_typed() {
+    let mut graph = build_sensor_graph();
+    let mut clamp = NodeInstance::new("clamp_1", "clamp", Position::zero());
+    clamp.add_input_pin("value", DataType::Typed("i32".into()));
+    graph.add_node(clamp);
+    graph.connections.retain(|c| c.target_node != "greater_1" || c.target_pin != "a");
+    graph.add_connection(Connection::data("read_1", "result", "clamp_1", "value"));
+    graph.add_connection(Connection::data("clamp_1", "result", "greater_1", "a"));
+    graph.add_connection(Connection::data("clamp_1", "result", "write_1", "pin"));
+
+    let sources = compile_c(&graph, &c_provider(), &mut CGenerator::new("sensor")).unwrap();
+    assert!(
+        sources.source.contains("int32_t node_clamp_1_result = clamp(node_read_1_result);\n"),
+        "{}",
+        sources.source
+    );
+    assert!(sources.header.contains("int32_t clamp(int32_t value);\n"), "{}", sources.header);
+}
+
+#[test]
+fn c_event_params_are_bound() {
+    let mut graph = build_sensor_graph();
+    graph.connections.retain(|c| c.target_node != "greater_1" || c.target_pin != "b");
+    graph.add_connection(Connection::data("tick", "delta_time", "greater_1", "b"));
+
+    let sources = compile_c(&graph, &c_provider(), &mut CGenerator::new("sensor")).unwrap();
+    assert!(sources.source.contains("float "), "{}", sources.source);
+    assert!(sources.source.contains(" = delta_time;\n"), "{}", sources.source);
+}
+
+#[test]
+fn c_variables_live_in_state_struct() {
+    let mut graph = build_sensor_graph();
+    graph.add_variable("count", VariableDecl::new("u32").with_default(PropertyValue::Integer(5)));
+    let set = graph.create_set_variable_node("set_1", "count", Position::zero()).unwrap();
+    graph.add_node(set);
+    graph.nodes.get_mut("set_1").unwrap().set_property("value", PropertyValue::Integer(0));
+    let get = graph.create_get_variable_node("get_1", "count", Position::zero()).unwrap();
+    graph.add_node(get);
+    graph.connections.retain(|c| c.target_node != "greater_1" || c.target_pin != "b");
+    graph.add_connection(Connection::data("get_1", "value", "greater_1", "b"));
+    graph.connections.retain(|c| c.source_node != "branch_1" || c.source_pin != "False");
+    graph.add_connection(Connection::execution("branch_1", "False", "set_1", "exec_in"));
+
+    let sources = compile_c(&graph, &c_provider(), &mut CGenerator::new("sensor")).unwrap();
+    assert!(sources.header.contains("typedef struct sensor_state {\n    uint32_t count;\n} sensor_state;\n"));
+    assert!(sources.header.contains("void sensor_state_init(sensor_state *state);\n"));
+    assert!(sources.source.contains("void sensor_state_init(sensor_state *state) {\n    state->count = 5;\n}\n"));
+    assert!(sources.source.contains("void sensor_tick(sensor_state *state, float delta_time) {\n"));
+    assert!(sources.source.contains("if ((node_read_1_result > state->count)) {"), "{}", sources.source);
+    assert!(sources.source.contains("        state->count = 0;\n"), "{}", sources.source);
+}
+
+// ===========================================================================
+// Header
+// ===========================================================================
+
+#[test]
+fn c_header_declares_externs_and_events() {
+    let mut generator = CGenerator::new("sensor").with_include("board.h").with_include("<math.h>");
+    let sources = compile_c(&build_sensor_graph(), &c_provider(), &mut generator).unwrap();
+    let header = &sources.header;
+
+    assert_eq!(sources.header_file_name(), "sensor.h");
+    assert!(header.starts_with("#ifndef SENSOR_H\n#define SENSOR_H\n"), "{}", header);
+    assert!(header.contains("#include <stdint.h>\n#include \"board.h\"\n#include <math.h>\n"), "{}", header);
+    assert!(header.contains("extern \"C\" {"), "{}", header);
+    assert!(header.contains("int32_t read_sensor(uint8_t channel);\n"), "{}", header);
+    assert!(header.contains("void write_pin(uint8_t pin, bool high);\n"), "{}", header);
+    assert!(header.contains("void sensor_tick(float delta_time);\n"), "{}", header);
+    assert!(header.ends_with("#endif /* SENSOR_H */\n"), "{}", header);
+
+    // Templates are inlined, so nothing to declare
+    assert!(!header.contains("greater"), "{}", header);
+    assert!(!header.contains("log"), "{}", header);
+    assert!(!header.contains("branch"), "{}", header);
+}
+
+#[test]
+fn c_header_declares_externals() {
+    let mut graph = build_sensor_graph();
+    graph.add_external(
+        ExternalDecl::function("engine::audio", "play_sound")
+            .with_param("Sound")
+            .with_param("f32"),
+    );
+    graph.add_external(ExternalDecl::function("engine::time", "now").with_return_type("u64"));
+    graph.add_external(ExternalDecl::type_("engine::audio", "Sound"));
+    let sources = compile_c(&graph, &c_provider(), &mut CGenerator::new("sensor")).unwrap();
+
+    assert!(
+        sources.header.contains(
+            "/* Externals, provided by the engine */\ntypedef struct Sound Sound;\nextern void play_sound(Sound, float);\nextern uint64_t now(void);\n"
+        ),
+        "{}",
+        sources.header
+    );
+    assert!(!sources.source.contains("use "), "{}", sources.source);
+}
+
+#[test]
+fn c_header_matches_last_compilation() {
+    let provider = c_provider();
+    let mut generator = CGenerator::new("sensor");
+    compile(&build_sensor_graph(), &provider, &mut generator).unwrap();
+    compile(&GraphDescription::new("empty"), &provider, &mut generator).unwrap();
+    assert!(!generator.header().contains("read_sensor"));
+}
+
+#[test]
+fn c_debug_builds_declare_their_hooks() {
+    let mut graph = build_sensor_graph();
+    graph.get_node_mut("write_1").unwrap().breakpoint = true;
+    graph.get_node_mut("read_1").unwrap().trace = true;
+    let mut generator = CGenerator::new("sensor");
+    let options = CompileOptions::new().with_debug(DebugHooks::new());
+    let code = compile_with_options(&graph, &c_provider(), &mut generator, &options).unwrap().code;
+    let header = generator.header();
+
+    assert!(header.contains("void __graphy_debug_hit(const char *node_id);\n"), "{}", header);
+    assert!(
+        header.contains("void __graphy_debug_trace(const char *node_id, const char *pin, const void *value);\n"),
+        "{}",
+        header
+    );
+    assert!(code.contains("uint8_t trace_read_1_channel = 2;\n"), "{}", code);
+    assert!(code.contains("__graphy_debug_trace(\"read_1\", \"channel\", &trace_read_1_channel);\n"), "{}", code);
+    assert!(code.contains("read_sensor(trace_read_1_channel)"), "{}", code);
+    assert!(code.contains("__graphy_debug_hit(\"write_1\");\n"), "{}", code);
+}
+
+#[test]
+fn c_profile_types_are_configurable() {
+    let profile = CProfile::new().with_type("i32", "int").with_type("f32", "fix16_t");
+    let mut generator = CGenerator::new("sensor").with_profile(profile);
+    let sources = compile_c(&build_sensor_graph(), &c_provider(), &mut generator).unwrap();
+    assert!(sources.header.contains("int read_sensor(uint8_t channel);\n"), "{}", sources.header);
+    assert!(sources.header.contains("void sensor_tick(fix16_t delta_time);\n"), "{}", sources.header);
+    assert_ne!(generator.cache_id(), CGenerator::new("sensor").cache_id());
+}
+
+// ===========================================================================
+// Unsupported nodes
+// ===========================================================================
+
+#[test]
+fn c_control_flow_without_template_fails() {
+    let mut graph = build_sensor_graph();
+    exec_node(&mut graph, "gate_1", "gate", &["Open"]);
+    graph.connections.retain(|c| c.source_node != "read_1" || c.source_pin != "then");
+    graph.add_connection(Connection::execution("read_1", "then", "gate_1", "exec_in"));
+
+    let error = compile_c(&graph, &c_provider(), &mut CGenerator::new("sensor")).unwrap_err();
+    assert!(error.to_string().contains("needs a template source"), "{}", error);
+}
@@ -0,0 +1,284 @@
+//! Tests for compilation cache keys, caches, and cached compilation.
+
+mod common;
+
+use common::*;
+use graphy::cache::{compile_cached, provider_fingerprint, CacheKey, CachedOutput, CompilationCache, DiskCache, MemoryCache};
+use graphy::generation::{compile_with_source_map, CodeGenerator, SourceMap};
+use graphy::*;
+use std::path::PathBuf;
+
+/// Fresh, empty cache directory unique to a test
+fn cache_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("graphy-cache-{}-{}", test, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+// ===========================================================================
+// Cache keys
+// ===========================================================================
+
+#[test]
+fn key_ignores_editor_changes() {
+    let provider = TestMetadataProvider::comprehensive();
+    let generator = CountingGenerator::default();
+    let graph = build_branch_graph();
+
+    let mut moved = graph.clone();
+    moved.get_node_mut("branch_1").unwrap().position = Position::new(999.0, 999.0);
+
+    assert_eq!(
+        CacheKey::new(&graph, &provider, &generator),
+        CacheKey::new(&moved, &provider, &generator)
+    );
+}
+
+#[test]
+fn key_changes_with_graph_provider_and_generator() {
+    let provider = TestMetadataProvider::comprehensive();
+    let generator = CountingGenerator::default();
+    let graph = build_branch_graph();
+    let key = CacheKey::new(&graph, &provider, &generator);
+
+    let mut edited = graph.clone();
+    edited
+        .get_node_mut("print_true")
+        .unwrap()
+        .set_property("message", PropertyValue::String("changed".into()));
+    assert_ne!(CacheKey::new(&edited, &provider, &generator), key);
+
+    let mut changed_provider = TestMetadataProvider::comprehensive();
+    changed_provider.add(NodeMetadata::new("extra", NodeTypes::pure, "Test"));
+    assert_ne!(CacheKey::new(&graph, &changed_provider, &generator), key);
+
+    let other_generator = CountingGenerator {
+        id: Some("other".into()),
+        ..CountingGenerator::default()
+    };
+    assert_ne!(CacheKey::new(&graph, &provider, &other_generator), key);
+}
+
+#[test]
+fn provider_fingerprint_is_deterministic() {
+    let a = TestMetadataProvider::comprehensive();
+    let b = TestMetadataProvider::comprehensive();
+    assert_eq!(provider_fingerprint(&a), provider_fingerprint(&b));
+    assert_ne!(provider_fingerprint(&a), provider_fingerprint(&TestMetadataProvider::empty()));
+}
+
+#[test]
+fn file_stem_is_filesystem_safe() {
+    let key = CacheKey::from_parts(1, 2, "my::Generator<v2>/opts");
+    let stem = key.file_stem();
+    assert!(stem.starts_with("0000000000000001-0000000000000002-"));
+    assert!(stem.chars().all(|c| c.is_ascii_hexdigit() || c == '-'));
+    assert_ne!(stem, CacheKey::from_parts(1, 2, "other").file_stem());
+}
+
+#[test]
+fn default_cache_id_is_type_name() {
+    struct Plain;
+    impl CodeGenerator for Plain {
+        fn generate_event<P: NodeMetadataProvider>(
+            &mut self,
+            _ctx: &mut CodeGeneratorContext<'_, P>,
+            _node: &NodeInstance,
+            _metadata: &NodeMetadata,
+        ) -> Result<()> {
+            Ok(())
+        }
+        fn generate_function_node<P: NodeMetadataProvider>(
+            &mut self,
+            _ctx: &mut CodeGeneratorContext<'_, P>,
+            _node: &NodeInstance,
+            _metadata: &NodeMetadata,
+            _args: &[String],
+        ) -> Result<()> {
+            Ok(())
+        }
+        fn generate_control_flow<P: NodeMetadataProvider>(
+            &mut self,
+            _ctx: &mut CodeGeneratorContext<'_, P>,
+            _node: &NodeInstance,
+            _metadata: &NodeMetadata,
+            _args: &[String],
+        ) -> Result<()> {
+            Ok(())
+        }
+        fn generate_pure_expression<P: NodeMetadataProvider>(
+            &mut self,
+            _ctx: &CodeGeneratorContext<'_, P>,
+            _node: &NodeInstance,
+            _metadata: &NodeMetadata,
+            _args: &[String],
+        ) -> Result<String> {
+            Ok(String::new())
+        }
+    }
+
+    assert!(Plain.cache_id().ends_with("Plain"));
+}
+
+// ===========================================================================
+// Memory cache
+// ===========================================================================
+
+#[test]
+fn memory_cache_round_trip() {
+    let mut cache = MemoryCache::new();
+    let key = CacheKey::from_parts(1, 2, "gen");
+    let output = CachedOutput::new("fn main() {}", SourceMap::new());
+
+    assert_eq!(cache.get(&key).unwrap(), None);
+    cache.put(&key, &output).unwrap();
+    assert_eq!(cache.get(&key).unwrap(), Some(output));
+    assert_eq!(cache.len(), 1);
+
+    assert!(cache.remove(&key).unwrap());
+    assert!(!cache.remove(&key).unwrap());
+    assert!(cache.is_empty());
+}
+
+// ===========================================================================
+// Disk cache
+// ===========================================================================
+
+#[test]
+fn disk_cache_round_trip() {
+    let dir = cache_dir("round_trip");
+    let provider = TestMetadataProvider::comprehensive();
+    let mut generator = CountingGenerator::default();
+    let graph = build_branch_graph();
+    let (code, source_map) = compile_with_source_map(&graph, &provider, &mut generator).unwrap();
+    let output = CachedOutput::new(code, source_map);
+    let key = CacheKey::new(&graph, &provider, &generator);
+
+    let mut cache = DiskCache::open(&dir).unwrap();
+    assert_eq!(cache.get(&key).unwrap(), None);
+    cache.put(&key, &output).unwrap();
+    assert!(cache.entry_path(&key).exists());
+
+    // A new handle on the same directory sees the entry
+    let reopened = DiskCache::open(&dir).unwrap();
+    assert_eq!(reopened.get(&key).unwrap(), Some(output));
+    assert_eq!(reopened.len().unwrap(), 1);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn disk_cache_treats_corrupt_entries_as_misses() {
+    let dir = cache_dir("corrupt");
+    let cache = DiskCache::open(&dir).unwrap();
+    let key = CacheKey::from_parts(1, 2, "gen");
+    std::fs::write(cache.entry_path(&key), "not json").unwrap();
+
+    assert_eq!(cache.get(&key).unwrap(), None);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn disk_cache_remove_and_clear() {
+    let dir = cache_dir("clear");
+    let mut cache = DiskCache::open(&dir).unwrap();
+    let output = CachedOutput::new("code", SourceMap::new());
+    let first = CacheKey::from_parts(1, 2, "gen");
+    let second = CacheKey::from_parts(3, 4, "gen");
+    cache.put(&first, &output).unwrap();
+    cache.put(&second, &output).unwrap();
+    assert_eq!(cache.len().unwrap(), 2);
+
+    assert!(cache.remove(&first).unwrap());
+    assert!(!cache.remove(&first).unwrap());
+    assert_eq!(cache.len().unwrap(), 1);
+
+    cache.clear().unwrap();
+    assert!(cache.is_empty().unwrap());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+// ===========================================================================
+// compile_cached
+// ===========================================================================
+
+#[test]
+fn compile_cached_skips_generator_on_hit() {
+    let provider = TestMetadataProvider::comprehensive();
+    let graph = build_branch_graph();
+    let mut cache = MemoryCache::new();
+
+    let mut generator = CountingGenerator::default();
+    let first = compile_cached(&graph, &provider, &mut generator, &mut cache).unwrap();
+    assert_eq!(generator.events, 1);
+    assert!(first.code.contains("fn start()"));
+
+    let second = compile_cached(&graph, &provider, &mut generator, &mut cache).unwrap();
+    assert_eq!(generator.events, 1, "hit should not call the generator");
+    assert_eq!(first, second);
+}
+
+#[test]
+fn compile_cached_matches_uncached_output() {
+    let provider = TestMetadataProvider::comprehensive();
+    let graph = build_branch_graph();
+    let mut cache = MemoryCache::new();
+
+    let cached = compile_cached(&graph, &provider, &mut CountingGenerator::default(), &mut cache).unwrap();
+    let (code, source_map) = compile_with_source_map(&graph, &provider, &mut CountingGenerator::default()).unwrap();
+    assert_eq!(cached, CachedOutput::new(code, source_map));
+}
+
+#[test]
+fn compile_cached_recompiles_changed_graph() {
+    let provider = TestMetadataProvider::comprehensive();
+    let graph = build_branch_graph();
+    let mut cache = MemoryCache::new();
+    let mut generator = CountingGenerator::default();
+    compile_cached(&graph, &provider, &mut generator, &mut cache).unwrap();
+
+    let mut edited = graph.clone();
+    edited
+        .get_node_mut("print_false")
+        .unwrap()
+        .set_property("message", PropertyValue::String("edited".into()));
+    let output = compile_cached(&edited, &provider, &mut generator, &mut cache).unwrap();
+
+    assert_eq!(generator.events, 2);
+    assert_eq!(cache.len(), 2);
+    assert!(output.code.contains("edited"));
+}
+
+#[test]
+fn compile_cached_persists_across_disk_cache_handles() {
+    let dir = cache_dir("persist");
+    let provider = TestMetadataProvider::comprehensive();
+    let graph = build_branch_graph();
+
+    let mut generator = CountingGenerator::default();
+    let first = compile_cached(&graph, &provider, &mut generator, &mut DiskCache::open(&dir).unwrap()).unwrap();
+
+    // A later build with a fresh generator and cache handle skips compilation
+    let mut generator = CountingGenerator::default();
+    let second = compile_cached(&graph, &provider, &mut generator, &mut DiskCache::open(&dir).unwrap()).unwrap();
+    assert_eq!(generator.events, 0);
+    assert_eq!(first, second);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn compile_cached_does_not_cache_failures() {
+    let provider = TestMetadataProvider::comprehensive();
+    let graph = build_branch_graph();
+    let mut cache = MemoryCache::new();
+    let mut generator = CountingGenerator {
+        fail: true,
+        ..CountingGenerator::default()
+    };
+
+    assert!(compile_cached(&graph, &provider, &mut generator, &mut cache).is_err());
+    assert!(cache.is_empty());
+}
@@ -0,0 +1,195 @@
+//! Tests for cancelling analysis and code generation.
+
+mod common;
+
+use common::*;
+use graphy::analysis::SideEffectOrdering;
+use graphy::generation::{
+    compile_with_options, generate_exec_output, CodeGenerator, CompilationSession, CompileOptions,
+};
+use graphy::utils::{CancellationToken, RustProfile};
+use graphy::*;
+use std::sync::Arc;
+
+/// Counts function nodes, cancelling its token after the first one.
+struct CancellingGenerator {
+    token: CancellationToken,
+    nodes_generated: usize,
+}
+
+impl CodeGenerator for CancellingGenerator {
+    fn generate_event<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        metadata: &NodeMetadata,
+    ) -> Result<()> {
+        for pin in &metadata.exec_outputs {
+            generate_exec_output(self, ctx, &node.id, pin)?;
+        }
+        Ok(())
+    }
+
+    fn generate_function_node<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        _metadata: &NodeMetadata,
+        _args: &[String],
+    ) -> Result<()> {
+        self.nodes_generated += 1;
+        self.token.cancel();
+        ctx.emit(&format!("{}();\n", node.id));
+        for pin in &node.outputs {
+            if pin.pin.data_type == DataType::Execution {
+                generate_exec_output(self, ctx, &node.id, &pin.pin.name)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn generate_control_flow<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &mut CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        _metadata: &NodeMetadata,
+        _args: &[String],
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn generate_pure_expression<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        _metadata: &NodeMetadata,
+        _args: &[String],
+    ) -> Result<String> {
+        Ok(node.id.clone())
+    }
+}
+
+fn cancelled_token() -> CancellationToken {
+    let token = CancellationToken::new();
+    token.cancel();
+    token
+}
+
+/// An event running a chain of three prints
+fn build_event_chain() -> GraphDescription {
+    let mut graph = build_exec_chain(3);
+    let mut event = NodeInstance::new("start", "on_start", Position::zero());
+    event.add_output_pin("exec", DataType::Execution);
+    graph.add_node(event);
+    graph.add_connection(Connection::execution("start", "exec", "fn_0", "exec_in"));
+    graph
+}
+
+fn is_cancelled<T>(result: &Result<T>) -> bool {
+    matches!(result, Err(GraphyError::Cancelled))
+}
+
+// ===========================================================================
+// Tokens
+// ===========================================================================
+
+#[test]
+fn token_clones_share_state() {
+    let token = CancellationToken::new();
+    let clone = token.clone();
+    assert!(!clone.is_cancelled());
+    assert!(clone.check().is_ok());
+
+    token.cancel();
+    assert!(clone.is_cancelled());
+    assert!(is_cancelled(&clone.check()));
+    assert_eq!(GraphyError::Cancelled.to_string(), "Operation was cancelled");
+}
+
+// ===========================================================================
+// Analysis
+// ===========================================================================
+
+#[test]
+fn data_resolver_stops_when_cancelled() {
+    let provider = TestMetadataProvider::comprehensive();
+    let graph = build_linear_chain(50, &provider);
+
+    let result = DataResolver::build_with_cancellation(&graph, &provider, &RustProfile, &cancelled_token());
+    assert!(is_cancelled(&result));
+    let result = DataResolver::build_parallel_with_cancellation(&graph, &provider, &RustProfile, &cancelled_token());
+    assert!(is_cancelled(&result));
+
+    // A live token changes nothing
+    let token = CancellationToken::new();
+    let resolver = DataResolver::build_with_cancellation(&graph, &provider, &RustProfile, &token).unwrap();
+    let expected = DataResolver::build(&graph, &provider).unwrap();
+    assert_eq!(resolver.get_pure_evaluation_order(), expected.get_pure_evaluation_order());
+}
+
+#[test]
+fn ordering_stops_when_cancelled() {
+    let provider = TestMetadataProvider::comprehensive();
+    let graph = build_branch_graph();
+    let resolver = DataResolver::build(&graph, &provider).unwrap();
+    let routing = ExecutionRouting::build_from_graph(&graph);
+
+    let result =
+        SideEffectOrdering::compute_with_cancellation(&graph, &provider, &routing, &resolver, &cancelled_token());
+    assert!(is_cancelled(&result));
+    let ordering =
+        SideEffectOrdering::compute_with_cancellation(&graph, &provider, &routing, &resolver, &CancellationToken::new())
+            .unwrap();
+    assert!(ordering.validate().is_ok());
+}
+
+// ===========================================================================
+// Code generation
+// ===========================================================================
+
+#[test]
+fn compile_stops_when_cancelled_before_starting() {
+    let provider = TestMetadataProvider::comprehensive();
+    let mut generator = CancellingGenerator {
+        token: CancellationToken::new(),
+        nodes_generated: 0,
+    };
+    let options = CompileOptions::new().with_cancellation(cancelled_token());
+
+    let result = compile_with_options(&build_event_chain(), &provider, &mut generator, &options);
+    assert!(is_cancelled(&result));
+    assert_eq!(generator.nodes_generated, 0);
+}
+
+#[test]
+fn compile_stops_at_next_node_when_cancelled_midway() {
+    let provider = TestMetadataProvider::comprehensive();
+    let token = CancellationToken::new();
+    let mut generator = CancellingGenerator {
+        token: token.clone(),
+        nodes_generated: 0,
+    };
+    let options = CompileOptions::new().with_cancellation(token);
+
+    let result = compile_with_options(&build_event_chain(), &provider, &mut generator, &options);
+    assert!(is_cancelled(&result));
+    assert_eq!(generator.nodes_generated, 1);
+}
+
+#[test]
+fn session_step_stops_when_cancelled() {
+    let provider = Arc::new(TestMetadataProvider::comprehensive());
+    let token = CancellationToken::new();
+    let mut generator = CancellingGenerator {
+        token: CancellationToken::new(),
+        nodes_generated: 0,
+    };
+    let options = CompileOptions::new().with_cancellation(token.clone());
+    let mut session = CompilationSession::new(&build_event_chain(), provider, &generator, &options).unwrap();
+
+    assert!(session.step(&mut generator).unwrap());
+    token.cancel();
+    assert!(is_cancelled(&session.step(&mut generator)));
+    assert_eq!(session.remaining_events(), 1);
+    assert_eq!(generator.nodes_generated, 0);
+}
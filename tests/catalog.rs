@@ -0,0 +1,166 @@
+//! Tests for node catalog export and rendering.
+
+mod common;
+
+use common::*;
+use graphy::core::{export_catalog, NodeCatalog, ERROR_EXEC_PIN};
+use graphy::export::{catalog_to_html, catalog_to_markdown};
+use graphy::*;
+
+fn documented_registry() -> NodeRegistry {
+    let mut registry = NodeRegistry::new();
+    registry.register(
+        NodeMetadata::new("lerp", NodeTypes::pure, "Math")
+            .with_description("Blends between a and b")
+            .with_params(vec![
+                ParamInfo::new("a", "f64").with_required(true),
+                ParamInfo::new("b", "f64").with_description("Value at t = 1"),
+                ParamInfo::new("t", "f64").with_default(PropertyValue::Number(0.5)),
+            ])
+            .with_return_type("f64"),
+    );
+    registry.register(
+        NodeMetadata::new("read_file", NodeTypes::fn_, "IO")
+            .with_params(vec![ParamInfo::new("path", "String")])
+            .with_return_type("Result<String, std::io::Error>")
+            .with_exec_outputs(vec!["then".into()])
+            .with_async(true),
+    );
+    registry.register(
+        NodeMetadata::new("on_tick", NodeTypes::event, "Events")
+            .with_params(vec![ParamInfo::new("delta_time", "f64")])
+            .with_exec_outputs(vec!["exec".into()]),
+    );
+    registry
+}
+
+// ===========================================================================
+// Catalog
+// ===========================================================================
+
+#[test]
+fn catalog_groups_and_sorts_nodes() {
+    let provider = TestMetadataProvider::comprehensive();
+    let catalog = export_catalog(&provider);
+
+    let categories: Vec<&str> = catalog.categories.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(categories, ["events", "flow", "io", "math", "variables"]);
+
+    let math: Vec<&str> = catalog.categories[3].nodes.iter().map(|n| n.name.as_str()).collect();
+    assert_eq!(math, ["add", "multiply", "negate"]);
+    assert_eq!(catalog.node_count(), provider.get_all_nodes().len());
+    assert_eq!(catalog.types, ["String", "bool", "f64", "i64"]);
+}
+
+#[test]
+fn catalog_node_details() {
+    let catalog = export_catalog(&documented_registry());
+
+    let lerp = catalog.node("lerp").unwrap();
+    assert_eq!(lerp.node_type, NodeTypes::pure);
+    assert_eq!(lerp.description.as_deref(), Some("Blends between a and b"));
+    assert_eq!(lerp.return_type.as_deref(), Some("f64"));
+    assert!(lerp.params[0].required);
+    assert_eq!(lerp.params[1].description.as_deref(), Some("Value at t = 1"));
+    assert_eq!(lerp.params[2].default, Some(PropertyValue::Number(0.5)));
+    assert!(lerp.exec_outputs.is_empty());
+
+    // Fallible nodes list their error output
+    let read_file = catalog.node("read_file").unwrap();
+    assert!(read_file.is_async);
+    assert_eq!(read_file.exec_outputs, ["then", ERROR_EXEC_PIN]);
+
+    assert!(catalog.node("missing").is_none());
+}
+
+#[test]
+fn catalog_of_empty_provider() {
+    let catalog = export_catalog(&NodeRegistry::new());
+    assert!(catalog.categories.is_empty());
+    assert!(catalog.types.is_empty());
+    assert_eq!(catalog.node_count(), 0);
+}
+
+#[test]
+fn catalog_json_roundtrip() {
+    let catalog = export_catalog(&documented_registry());
+    let json = serde_json::to_string(&catalog).unwrap();
+    assert!(!json.contains("\"is_switch\""));
+
+    let restored: NodeCatalog = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, catalog);
+}
+
+// ===========================================================================
+// Markdown
+// ===========================================================================
+
+#[test]
+fn catalog_markdown_structure() {
+    let markdown = catalog_to_markdown(&export_catalog(&documented_registry()), "Node Reference");
+
+    assert!(markdown.starts_with("# Node Reference\n\n- [Events](#events) (1)\n- [IO](#io) (1)\n- [Math](#math) (1)\n"));
+    assert!(markdown.contains("\n## Math\n\n### `lerp`\n\n*Pure*\n\nBlends between a and b\n"));
+    assert!(markdown.contains("| `a` | `f64` | required |  |"));
+    assert!(markdown.contains("| `b` | `f64` |  | Value at t = 1 |"));
+    assert!(markdown.contains("| `t` | `f64` | `0.5` |  |"));
+    assert!(markdown.contains("**Returns:** `f64`"));
+    assert!(markdown.contains("*Function, async*"));
+    assert!(markdown.contains("**Execution outputs:** `then`, `on_error`"));
+    assert!(markdown.ends_with("## Types\n\n- `Result<String, std::io::Error>`\n- `String`\n- `f64`\n"));
+}
+
+#[test]
+fn catalog_markdown_event_params_are_outputs() {
+    let markdown = catalog_to_markdown(&export_catalog(&documented_registry()), "Nodes");
+    let events = &markdown[markdown.find("## Events").unwrap()..markdown.find("## IO").unwrap()];
+    assert!(events.contains("| Output | Type | Default | Description |"));
+    assert!(!events.contains("| Input |"));
+}
+
+#[test]
+fn catalog_markdown_escapes_table_cells() {
+    let mut registry = NodeRegistry::new();
+    registry.register(
+        NodeMetadata::new("either", NodeTypes::pure, "Logic Ops")
+            .with_params(vec![ParamInfo::new("mask", "u8").with_description("a | b\nbitwise")]),
+    );
+
+    let markdown = catalog_to_markdown(&export_catalog(&registry), "Nodes");
+    assert!(markdown.contains("- [Logic Ops](#logic-ops) (1)"));
+    assert!(markdown.contains("| a \\| b<br>bitwise |"));
+}
+
+// ===========================================================================
+// HTML
+// ===========================================================================
+
+#[test]
+fn catalog_html_structure() {
+    let html = catalog_to_html(&export_catalog(&documented_registry()), "Nodes & Types");
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<title>Nodes &amp; Types</title>"));
+    assert!(html.contains("<li><a href=\"#category-math\">Math</a> (1)</li>"));
+    assert!(html.contains("<section id=\"category-io\">"));
+    assert!(html.contains("<article id=\"node-read_file\" class=\"node function\">"));
+    assert!(html.contains("<p><strong>Returns:</strong> <code>Result&lt;String, std::io::Error&gt;</code></p>"));
+    assert!(html.contains("<tr><th>Output</th>"));
+    assert!(html.contains("<td>required</td>"));
+    assert!(html.trim_end().ends_with("</html>"));
+}
+
+#[test]
+fn catalog_html_escapes_text() {
+    let mut registry = NodeRegistry::new();
+    registry.register(
+        NodeMetadata::new("compare", NodeTypes::pure, "Logic")
+            .with_description("Returns a < b")
+            .with_params(vec![ParamInfo::new("items", "Vec<i64>")]),
+    );
+
+    let html = catalog_to_html(&export_catalog(&registry), "Nodes");
+    assert!(html.contains("<p>Returns a &lt; b</p>"));
+    assert!(html.contains("<code>Vec&lt;i64&gt;</code>"));
+    assert!(!html.contains("Vec<i64>"));
+}
@@ -0,0 +1,215 @@
+//! Tests for comments attached to nodes and fitted around them.
+
+use graphy::core::{CommentFit, GraphComment, GraphGroup};
+use graphy::layout::{auto_layout, LayoutOptions};
+use graphy::utils::diff::GraphDiff;
+use graphy::utils::{extract_subgraph, merge_graphs};
+use graphy::*;
+
+fn fit() -> CommentFit {
+    CommentFit::new((100.0, 50.0), 10.0)
+}
+
+fn note(text: &str) -> GraphComment {
+    GraphComment::new(text, Position::zero(), (0.0, 0.0))
+}
+
+/// `a` at the origin, `b` at (200, 100), `c` at (400, 0), chained by data
+fn build_graph() -> GraphDescription {
+    let mut graph = GraphDescription::new("comments");
+    for (id, x, y) in [("a", 0.0, 0.0), ("b", 200.0, 100.0), ("c", 400.0, 0.0)] {
+        let mut node = NodeInstance::new(id, "math.add", Position::new(x, y));
+        node.add_input_pin("a", DataType::Typed("i64".into()));
+        node.add_output_pin("result", DataType::Typed("i64".into()));
+        graph.add_node(node);
+    }
+    graph.add_connection(Connection::data("a", "result", "b", "a"));
+    graph.add_connection(Connection::data("b", "result", "c", "a"));
+    graph
+}
+
+// ============================================================================
+// Attachments
+// ============================================================================
+
+#[test]
+fn comments_attach_each_node_once() {
+    let mut comment = note("Math").with_attached_nodes(["a", "b", "a"]);
+    assert_eq!(comment.attached_node_ids, ["a", "b"]);
+
+    assert!(!comment.attach("b"));
+    assert!(comment.detach("a"));
+    assert!(!comment.detach("a"));
+    assert!(comment.is_attached_to("b"));
+    assert!(comment.is_attached());
+    assert!(!note("Free").is_attached());
+}
+
+#[test]
+fn comments_attached_to_a_node_are_listed() {
+    let mut graph = build_graph();
+    graph.add_comment(note("First").with_attached_nodes(["a", "b"]));
+    graph.add_comment(note("Second").with_attached_nodes(["b"]));
+
+    let texts: Vec<&str> = graph.comments_attached_to("b").map(|comment| comment.text.as_str()).collect();
+    assert_eq!(texts, ["First", "Second"]);
+    assert_eq!(graph.comments_attached_to("c").count(), 0);
+}
+
+#[test]
+fn comments_are_detached_from_removed_nodes() {
+    let mut graph = build_graph();
+    graph.add_comment(note("Math").with_attached_nodes(["a", "b"]));
+
+    graph.remove_node("a");
+    assert_eq!(graph.comments[0].attached_node_ids, ["b"]);
+}
+
+#[test]
+fn comments_serialize_attachments_only_when_present() {
+    let mut graph = build_graph();
+    graph.add_comment(note("Math").with_attached_nodes(["a"]));
+    graph.add_comment(note("Free"));
+
+    let json = serde_json::to_value(&graph).unwrap();
+    assert_eq!(json["comments"][0]["attached_node_ids"], serde_json::json!(["a"]));
+    assert!(json["comments"][1].get("attached_node_ids").is_none());
+
+    let loaded: GraphDescription = serde_json::from_value(json).unwrap();
+    assert_eq!(loaded.comments, graph.comments);
+}
+
+// ============================================================================
+// Fitting
+// ============================================================================
+
+#[test]
+fn comments_bounds_frame_the_attached_nodes() {
+    let graph = build_graph();
+
+    let bounds = graph.comment_bounds(&note("Math").with_attached_nodes(["a", "b"]), &fit());
+    assert_eq!(bounds, Some((Position::new(-10.0, -10.0), (320.0, 170.0))));
+
+    let single = graph.comment_bounds(&note("C").with_attached_nodes(["c"]), &fit());
+    assert_eq!(single, Some((Position::new(390.0, -10.0), (120.0, 70.0))));
+}
+
+#[test]
+fn comments_without_nodes_in_the_graph_have_no_bounds() {
+    let graph = build_graph();
+    assert_eq!(graph.comment_bounds(&note("Free"), &fit()), None);
+    assert_eq!(graph.comment_bounds(&note("Gone").with_attached_nodes(["missing"]), &fit()), None);
+}
+
+#[test]
+fn comments_fit_follows_moved_nodes() {
+    let mut graph = build_graph();
+    graph.add_comment(note("Math").with_attached_nodes(["a", "b"]));
+    let free = GraphComment::new("Free", Position::new(5.0, 5.0), (50.0, 50.0));
+    graph.add_comment(free.clone());
+
+    assert_eq!(graph.fit_comments(&fit()), 1);
+    assert_eq!(graph.fit_comments(&fit()), 0);
+
+    graph.get_node_mut("b").unwrap().position = Position::new(300.0, 300.0);
+    assert_eq!(graph.fit_comments(&fit()), 1);
+    assert_eq!(graph.comments[0].size, (420.0, 370.0));
+    assert_eq!(graph.comments[1], free);
+}
+
+#[test]
+fn comments_follow_auto_layout() {
+    let mut graph = build_graph();
+    graph.add_comment(note("Math").with_attached_nodes(["b", "c"]));
+
+    auto_layout(&mut graph, LayoutOptions::new().with_comment_fit(fit()));
+    let expected = graph.comment_bounds(&graph.comments[0], &fit()).unwrap();
+    assert_eq!((graph.comments[0].position, graph.comments[0].size), expected);
+}
+
+// ============================================================================
+// Editing operations
+// ============================================================================
+
+#[test]
+fn comments_on_duplicated_nodes_are_duplicated() {
+    let mut graph = build_graph();
+    let pair = GraphComment::new("Pair", Position::new(-10.0, -10.0), (320.0, 170.0));
+    graph.add_comment(pair.with_attached_nodes(["a", "b"]));
+    graph.add_comment(note("Chain").with_attached_nodes(["a", "b", "c"]));
+
+    let copies = graph
+        .duplicate_nodes(&["a", "b"], &IdStrategy::Suffix, Position::new(0.0, 500.0))
+        .unwrap();
+    assert_eq!(copies, ["a_1", "b_1"]);
+
+    assert_eq!(graph.comments.len(), 3);
+    let copy = &graph.comments[2];
+    assert_eq!(copy.text, "Pair");
+    assert_eq!(copy.attached_node_ids, ["a_1", "b_1"]);
+    assert_eq!(copy.position, Position::new(-10.0, 490.0));
+}
+
+#[test]
+fn comments_on_extracted_nodes_move_into_the_subgraph() {
+    let mut graph = build_graph();
+    graph.add_comment(note("Inner").with_attached_nodes(["b"]));
+    graph.add_comment(note("Across").with_attached_nodes(["a", "b"]));
+    graph.add_comment(note("Free"));
+
+    let (subgraph, composite) = extract_subgraph(&mut graph, ["b"]).unwrap();
+
+    assert_eq!(subgraph.comments.len(), 1);
+    assert_eq!(subgraph.comments[0].attached_node_ids, ["b"]);
+
+    let texts: Vec<&str> = graph.comments.iter().map(|comment| comment.text.as_str()).collect();
+    assert_eq!(texts, ["Across", "Free"]);
+    assert_eq!(graph.comments[0].attached_node_ids, ["a".to_string(), composite.id]);
+}
+
+#[test]
+fn comments_on_grouped_nodes_are_copied_with_the_group() {
+    let mut graph = build_graph();
+    let mut group = GraphGroup::new("math", "Math");
+    group.node_ids = vec!["a".into(), "b".into()];
+    graph.add_group(group).unwrap();
+    graph.add_comment(note("Inner").with_attached_nodes(["a", "b"]));
+    graph.add_comment(note("Across").with_attached_nodes(["b", "c"]));
+
+    let subgraph = graph.group_to_subgraph("math").unwrap();
+    assert_eq!(subgraph.comments.len(), 1);
+    assert_eq!(subgraph.comments[0].text, "Inner");
+}
+
+#[test]
+fn comments_keep_attachments_through_diffs() {
+    let old = build_graph();
+    let mut new = old.clone();
+    new.add_comment(note("Math").with_attached_nodes(["a", "c"]));
+
+    let diff = GraphDiff::compute(&old, &new);
+    assert_eq!(diff.added_comments[0].attached_node_ids, ["a", "c"]);
+
+    let mut applied = old.clone();
+    diff.apply(&mut applied).unwrap();
+    assert_eq!(applied.comments, new.comments);
+}
+
+#[test]
+fn comments_merge_without_attachments_to_removed_nodes() {
+    let mut base = build_graph();
+    base.add_comment(note("Math").with_attached_nodes(["a", "b"]));
+
+    let mut ours = base.clone();
+    ours.add_comment(note("Ours").with_attached_nodes(["c"]));
+    // A tool unaware of attachments removes the node but leaves the comment
+    let mut theirs = base.clone();
+    theirs.nodes.remove("b");
+    theirs.connections.retain(|connection| connection.source_node != "b" && connection.target_node != "b");
+
+    let merged = merge_graphs(&base, &ours, &theirs).unwrap();
+    let texts: Vec<&str> = merged.comments.iter().map(|comment| comment.text.as_str()).collect();
+    assert_eq!(texts, ["Math", "Ours"]);
+    assert_eq!(merged.comments[0].attached_node_ids, ["a"]);
+    assert_eq!(merged.comments[1].attached_node_ids, ["c"]);
+}
@@ -1,17 +1,21 @@
 //! Shared test helpers used across all integration test modules.
 
+#![allow(dead_code)]
+
+use graphy::generation::{generate_exec_output, CodeGenerator, ExpressionPolicy};
+use graphy::utils::{LanguageProfile, RustProfile};
 use graphy::*;
-use std::collections::HashMap;
+use std::sync::Arc;
 
 /// A test metadata provider with configurable node definitions.
 pub struct TestMetadataProvider {
-    pub metadata: HashMap<String, NodeMetadata>,
+    pub metadata: NodeRegistry,
 }
 
 impl TestMetadataProvider {
     pub fn new() -> Self {
         Self {
-            metadata: HashMap::new(),
+            metadata: NodeRegistry::new(),
         }
     }
 
@@ -124,40 +128,555 @@ impl TestMetadataProvider {
     /// Create a comprehensive provider with all node types.
     pub fn comprehensive() -> Self {
         let mut provider = Self::new();
-        for (_, meta) in Self::with_math_nodes().metadata {
-            provider.metadata.insert(meta.name.clone(), meta);
+        for (_, meta) in Self::with_math_nodes().metadata.iter() {
+            provider.add(meta.clone());
         }
-        for (_, meta) in Self::with_function_nodes().metadata {
-            provider.metadata.insert(meta.name.clone(), meta);
+        for (_, meta) in Self::with_function_nodes().metadata.iter() {
+            provider.add(meta.clone());
         }
-        for (_, meta) in Self::with_control_flow_nodes().metadata {
-            provider.metadata.insert(meta.name.clone(), meta);
+        for (_, meta) in Self::with_control_flow_nodes().metadata.iter() {
+            provider.add(meta.clone());
         }
-        for (_, meta) in Self::with_event_nodes().metadata {
-            provider.metadata.insert(meta.name.clone(), meta);
+        for (_, meta) in Self::with_event_nodes().metadata.iter() {
+            provider.add(meta.clone());
         }
         provider
     }
 
     pub fn add(&mut self, meta: NodeMetadata) {
-        self.metadata.insert(meta.name.clone(), meta);
+        self.metadata.register(meta);
+    }
+}
+
+impl Default for TestMetadataProvider {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl NodeMetadataProvider for TestMetadataProvider {
     fn get_node_metadata(&self, node_type: &str) -> Option<&NodeMetadata> {
-        self.metadata.get(node_type)
+        self.metadata.get_node_metadata(node_type)
     }
 
     fn get_all_nodes(&self) -> Vec<&NodeMetadata> {
-        self.metadata.values().collect()
+        self.metadata.get_all_nodes()
     }
 
     fn get_nodes_by_category(&self, category: &str) -> Vec<&NodeMetadata> {
-        self.metadata
-            .values()
-            .filter(|m| m.category == category)
-            .collect()
+        self.metadata.get_nodes_by_category(category)
+    }
+
+    fn enum_variants(&self, type_name: &str) -> Option<&[String]> {
+        self.metadata.enum_variants(type_name)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Code generator
+// ---------------------------------------------------------------------------
+
+/// Generator writing one `name(args);` line per function node and pure
+/// nodes as `name(args)`, following every exec output of events and
+/// control flow nodes.
+pub struct CallGenerator {
+    language: Arc<dyn LanguageProfile>,
+    policy: ExpressionPolicy,
+    bind_results: bool,
+    branch_helpers: bool,
+    suffix_f32: bool,
+    parallel: bool,
+}
+
+impl CallGenerator {
+    pub fn new() -> Self {
+        Self {
+            language: Arc::new(RustProfile),
+            policy: ExpressionPolicy::default(),
+            bind_results: false,
+            branch_helpers: false,
+            suffix_f32: false,
+            parallel: false,
+        }
+    }
+
+    /// Write literals in another language.
+    pub fn with_language(mut self, language: impl LanguageProfile + 'static) -> Self {
+        self.language = Arc::new(language);
+        self
+    }
+
+    pub fn with_policy(mut self, policy: ExpressionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Bind the result of function nodes with a return type to its variable.
+    pub fn with_bound_results(mut self, bind_results: bool) -> Self {
+        self.bind_results = bind_results;
+        self
+    }
+
+    /// Generate repeated exec branches as helper functions.
+    pub fn with_branch_helpers(mut self, branch_helpers: bool) -> Self {
+        self.branch_helpers = branch_helpers;
+        self
+    }
+
+    /// Write `f32` numbers with a type suffix.
+    pub fn with_f32_suffix(mut self, suffix_f32: bool) -> Self {
+        self.suffix_f32 = suffix_f32;
+        self
+    }
+
+    /// Use the default parallel scaffolding for expensive inputs.
+    pub fn with_parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+}
+
+impl Default for CallGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CodeGenerator for CallGenerator {
+    fn language(&self) -> Arc<dyn LanguageProfile> {
+        Arc::clone(&self.language)
+    }
+
+    fn expression_policy(&self) -> ExpressionPolicy {
+        self.policy
+    }
+
+    fn supports_branch_helpers(&self) -> bool {
+        self.branch_helpers
+    }
+
+    fn supports_parallel(&self) -> bool {
+        self.parallel
+    }
+
+    fn generate_event<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        metadata: &NodeMetadata,
+    ) -> Result<()> {
+        for pin in &metadata.exec_outputs {
+            generate_exec_output(self, ctx, &node.id, pin)?;
+        }
+        Ok(())
+    }
+
+    fn generate_function_node<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        metadata: &NodeMetadata,
+        args: &[String],
+    ) -> Result<()> {
+        let binding = match ctx.data_resolver.get_result_variable(&node.id) {
+            Some(variable) if self.bind_results && metadata.return_type.is_some() => format!("let {} = ", variable),
+            _ => String::new(),
+        };
+        let line = format!("{}{}{}({});\n", ctx.indent(), binding, metadata.name, args.join(", "));
+        ctx.emit(&line);
+        Ok(())
+    }
+
+    fn generate_control_flow<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        metadata: &NodeMetadata,
+        _args: &[String],
+    ) -> Result<()> {
+        for pin in &metadata.exec_outputs {
+            generate_exec_output(self, ctx, &node.id, pin)?;
+        }
+        Ok(())
+    }
+
+    fn generate_pure_expression<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        metadata: &NodeMetadata,
+        args: &[String],
+    ) -> Result<String> {
+        Ok(format!("{}({})", metadata.name, args.join(", ")))
+    }
+
+    fn generate_literal<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &CodeGeneratorContext<'_, P>,
+        value: &PropertyValue,
+        type_string: &str,
+    ) -> Result<String> {
+        match value {
+            PropertyValue::Number(n) if self.suffix_f32 && type_string == "f32" => Ok(format!("{:?}f32", n)),
+            _ => ctx.language.format_typed_literal(value, type_string),
+        }
+    }
+}
+
+/// Generator wrapping each event in `fn id() { .. }` and writing function
+/// nodes as `name(args);`, counting the events it generated.
+#[derive(Default)]
+pub struct CountingGenerator {
+    pub events: usize,
+    /// Overrides the `"counting"` cache id.
+    pub id: Option<String>,
+    /// Fail on the first event.
+    pub fail: bool,
+}
+
+impl CodeGenerator for CountingGenerator {
+    fn cache_id(&self) -> String {
+        self.id.clone().unwrap_or_else(|| "counting".to_string())
+    }
+
+    fn generate_event<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        metadata: &NodeMetadata,
+    ) -> Result<()> {
+        if self.fail {
+            return Err(GraphyError::CodeGeneration("requested failure".into()));
+        }
+        self.events += 1;
+        ctx.emit(&format!("fn {}() {{\n", node.id));
+        for pin in &metadata.exec_outputs {
+            generate_exec_output(self, ctx, &node.id, pin)?;
+        }
+        ctx.emit("}\n");
+        Ok(())
+    }
+
+    fn generate_function_node<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        _metadata: &NodeMetadata,
+        args: &[String],
+    ) -> Result<()> {
+        ctx.emit(&format!("{}({});\n", node.node_type, args.join(", ")));
+        Ok(())
+    }
+
+    fn generate_control_flow<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        _metadata: &NodeMetadata,
+        args: &[String],
+    ) -> Result<()> {
+        ctx.emit(&format!("if {} {{\n", args[0]));
+        generate_exec_output(self, ctx, &node.id, "True")?;
+        ctx.emit("} else {\n");
+        generate_exec_output(self, ctx, &node.id, "False")?;
+        ctx.emit("}\n");
+        Ok(())
+    }
+
+    fn generate_pure_expression<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        _metadata: &NodeMetadata,
+        _args: &[String],
+    ) -> Result<String> {
+        Ok(node.id.clone())
+    }
+}
+
+/// Generator that emits nothing, or only a `// name` header per graph.
+#[derive(Default)]
+pub struct NoopGenerator {
+    headers: bool,
+}
+
+impl NoopGenerator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write the graph name as a comment before each program.
+    pub fn with_headers(mut self, headers: bool) -> Self {
+        self.headers = headers;
+        self
+    }
+}
+
+impl CodeGenerator for NoopGenerator {
+    fn begin_program<P: NodeMetadataProvider>(&mut self, ctx: &mut CodeGeneratorContext<'_, P>) -> Result<()> {
+        if self.headers {
+            let header = format!("// {}\n", ctx.graph.metadata.name);
+            ctx.emit(&header);
+        }
+        Ok(())
+    }
+
+    fn generate_event<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &mut CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        _metadata: &NodeMetadata,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn generate_function_node<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &mut CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        _metadata: &NodeMetadata,
+        _args: &[String],
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn generate_control_flow<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &mut CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        _metadata: &NodeMetadata,
+        _args: &[String],
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn generate_pure_expression<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        _metadata: &NodeMetadata,
+        _args: &[String],
+    ) -> Result<String> {
+        Ok(node.id.clone())
+    }
+}
+
+/// Generator emitting one line per program hook and function node, and
+/// wrapping each event in `fn id() { .. }`.
+#[derive(Default)]
+pub struct LineGenerator {
+    /// Generate async events.
+    pub supports_async: bool,
+}
+
+impl CodeGenerator for LineGenerator {
+    fn supports_async(&self) -> bool {
+        self.supports_async
+    }
+
+    fn begin_program<P: NodeMetadataProvider>(&mut self, ctx: &mut CodeGeneratorContext<'_, P>) -> Result<()> {
+        ctx.emit("// begin\n");
+        Ok(())
+    }
+
+    fn generate_state<P: NodeMetadataProvider>(&mut self, ctx: &mut CodeGeneratorContext<'_, P>) -> Result<()> {
+        ctx.emit("// state\n");
+        Ok(())
+    }
+
+    fn end_program<P: NodeMetadataProvider>(&mut self, ctx: &mut CodeGeneratorContext<'_, P>) -> Result<()> {
+        ctx.emit("// end\n");
+        Ok(())
+    }
+
+    fn generate_event<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        metadata: &NodeMetadata,
+    ) -> Result<()> {
+        ctx.emit(&format!("fn {}() {{\n", node.id));
+        ctx.push_indent();
+        for pin in &metadata.exec_outputs {
+            generate_exec_output(self, ctx, &node.id, pin)?;
+        }
+        ctx.pop_indent();
+        ctx.emit("}\n");
+        Ok(())
+    }
+
+    fn generate_function_node<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        _metadata: &NodeMetadata,
+        args: &[String],
+    ) -> Result<()> {
+        let line = format!("{}{}({});\n", ctx.indent(), node.node_type, args.join(", "));
+        ctx.emit_for_node(&node.id, None, &line);
+        Ok(())
+    }
+
+    fn generate_control_flow<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &mut CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        _metadata: &NodeMetadata,
+        _args: &[String],
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn generate_pure_expression<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        _metadata: &NodeMetadata,
+        args: &[String],
+    ) -> Result<String> {
+        Ok(format!("{}({})", node.node_type, args.join(", ")))
+    }
+}
+
+/// How a [`HookGenerator`] writes control flow nodes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Branches {
+    /// Follow every exec output without writing anything.
+    Follow,
+    /// `if cond { True } else { False }`
+    IfElse,
+    /// `name(args) => pin { .. }` for every exec output.
+    Arms,
+}
+
+/// Statement a [`HookGenerator`] writes for a function node.
+pub type StatementHook = Box<dyn FnMut(&NodeInstance, &[String]) -> Result<String>>;
+
+/// Generator writing one indented statement per function node, from a hook
+/// defaulting to `name(args);`, and pure nodes as `name(args)`.
+pub struct HookGenerator {
+    statement: StatementHook,
+    event_functions: bool,
+    branches: Branches,
+}
+
+impl HookGenerator {
+    pub fn new() -> Self {
+        Self {
+            statement: Box::new(|node, args| Ok(format!("{}({});", node.node_type, args.join(", ")))),
+            event_functions: false,
+            branches: Branches::Follow,
+        }
+    }
+
+    /// Write function nodes with `statement` instead.
+    pub fn with_statement(
+        mut self,
+        statement: impl FnMut(&NodeInstance, &[String]) -> Result<String> + 'static,
+    ) -> Self {
+        self.statement = Box::new(statement);
+        self
+    }
+
+    /// Wrap each event in `fn id() { .. }`.
+    pub fn with_event_functions(mut self, event_functions: bool) -> Self {
+        self.event_functions = event_functions;
+        self
+    }
+
+    pub fn with_branches(mut self, branches: Branches) -> Self {
+        self.branches = branches;
+        self
+    }
+}
+
+impl Default for HookGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CodeGenerator for HookGenerator {
+    fn generate_event<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        metadata: &NodeMetadata,
+    ) -> Result<()> {
+        if !self.event_functions {
+            for pin in &metadata.exec_outputs {
+                generate_exec_output(self, ctx, &node.id, pin)?;
+            }
+            return Ok(());
+        }
+        ctx.emit(&format!("fn {}() {{\n", node.id));
+        ctx.push_indent();
+        for pin in &metadata.exec_outputs {
+            generate_exec_output(self, ctx, &node.id, pin)?;
+        }
+        ctx.pop_indent();
+        ctx.emit("}\n");
+        Ok(())
+    }
+
+    fn generate_function_node<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        _metadata: &NodeMetadata,
+        args: &[String],
+    ) -> Result<()> {
+        let line = format!("{}{}\n", ctx.indent(), (self.statement)(node, args)?);
+        ctx.emit(&line);
+        Ok(())
+    }
+
+    fn generate_control_flow<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        metadata: &NodeMetadata,
+        args: &[String],
+    ) -> Result<()> {
+        let indent = ctx.indent();
+        match self.branches {
+            Branches::Follow => {
+                for pin in &metadata.exec_outputs {
+                    generate_exec_output(self, ctx, &node.id, pin)?;
+                }
+            }
+            Branches::IfElse => {
+                ctx.emit(&format!("{}if {} {{\n", indent, args[0]));
+                ctx.push_indent();
+                generate_exec_output(self, ctx, &node.id, "True")?;
+                ctx.pop_indent();
+                ctx.emit(&format!("{}}} else {{\n", indent));
+                ctx.push_indent();
+                generate_exec_output(self, ctx, &node.id, "False")?;
+                ctx.pop_indent();
+                ctx.emit(&format!("{}}}\n", indent));
+            }
+            Branches::Arms => {
+                for pin in &metadata.exec_outputs {
+                    ctx.emit(&format!("{}{}({}) => {} {{\n", indent, node.node_type, args.join(", "), pin));
+                    ctx.push_indent();
+                    generate_exec_output(self, ctx, &node.id, pin)?;
+                    ctx.pop_indent();
+                    ctx.emit(&format!("{}}}\n", indent));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn generate_pure_expression<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        _metadata: &NodeMetadata,
+        args: &[String],
+    ) -> Result<String> {
+        Ok(format!("{}({})", node.node_type, args.join(", ")))
     }
 }
 
@@ -300,3 +819,119 @@ pub fn build_branch_graph() -> GraphDescription {
 
     graph
 }
+
+/// Build a `print_string` node printing `message`.
+pub fn print_node(id: &str, message: &str) -> NodeInstance {
+    let mut node = NodeInstance::new(id, "print_string", Position::zero());
+    node.add_input_pin("exec_in", DataType::Execution);
+    node.add_input_pin("message", DataType::Typed("String".into()));
+    node.add_output_pin("then", DataType::Execution);
+    node.set_property("message", PropertyValue::String(message.into()));
+    node
+}
+
+/// Build a graph with two events (`start` and `tick`), each printing its name.
+pub fn build_two_event_graph() -> GraphDescription {
+    let mut graph = GraphDescription::new("two_events");
+    for (event, event_type) in [("start", "on_start"), ("tick", "on_tick")] {
+        event_node(&mut graph, event, event_type);
+        let print = format!("print_{}", event);
+        graph.add_node(print_node(&print, event));
+        graph.add_connection(Connection::execution(event, "exec", print, "exec_in"));
+    }
+    graph
+}
+
+/// Add an event node with an `exec` output.
+pub fn event_node(graph: &mut GraphDescription, id: &str, event_type: &str) {
+    let mut node = NodeInstance::new(id, event_type, Position::zero());
+    node.add_output_pin("exec", DataType::Execution);
+    graph.add_node(node);
+}
+
+/// Add a node with an exec input and the given exec outputs.
+pub fn exec_node(graph: &mut GraphDescription, id: &str, node_type: &str, outputs: &[&str]) {
+    let mut node = NodeInstance::new(id, node_type, Position::zero());
+    node.add_input_pin("exec_in", DataType::Execution);
+    for output in outputs {
+        node.add_output_pin(*output, DataType::Execution);
+    }
+    graph.add_node(node);
+}
+
+/// Set a constant input of a node in the graph.
+pub fn set_input(graph: &mut GraphDescription, id: &str, pin: &str, value: PropertyValue) {
+    let node = graph.nodes.get_mut(id).unwrap();
+    node.add_input_pin(pin, DataType::Any);
+    node.set_property(pin, value);
+}
+
+/// Comprehensive provider plus functions reading (`read_value`, `try_read`)
+/// and using (`use_value`) an `i64`
+pub fn ordering_provider() -> TestMetadataProvider {
+    let mut provider = TestMetadataProvider::comprehensive();
+    provider.add(
+        NodeMetadata::new("read_value", NodeTypes::fn_, "io")
+            .with_return_type("i64")
+            .with_exec_outputs(vec!["then".into()]),
+    );
+    provider.add(
+        NodeMetadata::new("try_read", NodeTypes::fn_, "io")
+            .with_return_type("Result<i64, String>")
+            .with_exec_outputs(vec!["then".into()]),
+    );
+    provider.add(
+        NodeMetadata::new("use_value", NodeTypes::fn_, "io")
+            .with_params(vec![ParamInfo::new("value", "i64")])
+            .with_exec_outputs(vec!["then".into()]),
+    );
+    provider
+}
+
+/// Adds a function node with exec pins and a `value` input
+pub fn fn_node(graph: &mut GraphDescription, id: &str, node_type: &str) {
+    let mut node = NodeInstance::new(id, node_type, Position::zero());
+    node.add_input_pin("exec_in", DataType::Execution);
+    node.add_input_pin("value", DataType::Typed("i64".into()));
+    node.add_output_pin("then", DataType::Execution);
+    node.add_output_pin("result", DataType::Typed("i64".into()));
+    graph.add_node(node);
+}
+
+/// `start` with `read_1`, `use_1`, and the getter `add_1 = read_1.result + 1`
+/// between them, but no exec flow past the event
+pub fn build_getter_graph() -> GraphDescription {
+    let mut graph = GraphDescription::new("ordering");
+    let mut event = NodeInstance::new("start", "on_start", Position::zero());
+    event.add_output_pin("exec", DataType::Execution);
+    graph.add_node(event);
+    fn_node(&mut graph, "read_1", "read_value");
+    fn_node(&mut graph, "use_1", "use_value");
+
+    let mut add = NodeInstance::new("add_1", "add", Position::zero());
+    add.add_input_pin("a", DataType::Typed("i64".into()));
+    add.add_input_pin("b", DataType::Typed("i64".into()));
+    add.add_output_pin("result", DataType::Typed("i64".into()));
+    add.set_property("b", PropertyValue::Integer(1));
+    graph.add_node(add);
+    graph.add_connection(Connection::data("read_1", "result", "add_1", "a"));
+    graph.add_connection(Connection::data("add_1", "result", "use_1", "value"));
+    graph
+}
+
+/// Graph of unconnected `add` nodes with the given ids.
+pub fn graph_with_nodes<S: AsRef<str>>(ids: &[S]) -> GraphDescription {
+    let mut graph = GraphDescription::new("nodes");
+    for id in ids {
+        graph.add_node(NodeInstance::new(id.as_ref(), "add", Position::zero()));
+    }
+    graph
+}
+
+/// Fresh, empty directory named after `name` under the system temp dir.
+pub fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("graphy_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
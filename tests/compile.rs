@@ -0,0 +1,1173 @@
+//! Tests for the CodeGenerator trait and the compile driver.
+
+mod common;
+
+use common::*;
+use graphy::*;
+use graphy::analysis::{ParallelThreshold, UnknownNodePolicy};
+use graphy::core::{Pattern, PatternNode, VariableDecl, VersionReq};
+use graphy::generation::{
+    bind_event_params, compile, compile_event, compile_event_with_options, compile_with_options, compile_with_source_map,
+    generate_exec_output, CacheStats, CodeGenerator, CompilationReport, CompileOptions, ExpressionPolicy,
+};
+use graphy::utils::{Replacement, RewriteRule};
+
+/// Minimal Rust generator used to observe driver behavior.
+#[derive(Default)]
+struct TestGenerator {
+    prelude_calls: usize,
+    pure_calls: Vec<String>,
+    policy: Option<ExpressionPolicy>,
+    async_support: bool,
+}
+
+impl TestGenerator {
+    fn with_policy(policy: ExpressionPolicy) -> Self {
+        Self {
+            policy: Some(policy),
+            ..Self::default()
+        }
+    }
+}
+
+impl CodeGenerator for TestGenerator {
+    fn expression_policy(&self) -> ExpressionPolicy {
+        self.policy.unwrap_or_default()
+    }
+
+    fn supports_async(&self) -> bool {
+        self.async_support
+    }
+
+    fn begin_program<P: NodeMetadataProvider>(&mut self, ctx: &mut CodeGeneratorContext<'_, P>) -> Result<()> {
+        self.prelude_calls += 1;
+        ctx.emit("// generated\n");
+        Ok(())
+    }
+
+    fn generate_event<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        metadata: &NodeMetadata,
+    ) -> Result<()> {
+        let params: Vec<String> = metadata
+            .params
+            .iter()
+            .map(|param| format!("{}: {}", param.name, param.param_type))
+            .collect();
+        let asyncness = if ctx.is_async_event(&node.id) { "async " } else { "" };
+        ctx.emit(&format!("{}fn {}({}) {{\n", asyncness, node.id, params.join(", ")));
+        ctx.push_indent();
+        bind_event_params(self, ctx, node, metadata)?;
+        for pin in &metadata.exec_outputs {
+            generate_exec_output(self, ctx, &node.id, pin)?;
+        }
+        ctx.pop_indent();
+        ctx.emit("}\n");
+        Ok(())
+    }
+
+    fn generate_function_node<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        metadata: &NodeMetadata,
+        args: &[String],
+    ) -> Result<()> {
+        let mut call = format!("{}({})", node.node_type, args.join(", "));
+        if metadata.is_async {
+            call = self.generate_await(ctx, node, &call)?;
+        }
+        let line = format!("{}{};\n", ctx.indent(), call);
+        ctx.emit(&line);
+        Ok(())
+    }
+
+    fn generate_control_flow<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        _metadata: &NodeMetadata,
+        args: &[String],
+    ) -> Result<()> {
+        let indent = ctx.indent();
+        ctx.emit(&format!("{}if {} {{\n", indent, args[0]));
+        ctx.push_indent();
+        generate_exec_output(self, ctx, &node.id, "True")?;
+        ctx.pop_indent();
+        ctx.emit(&format!("{}}} else {{\n", indent));
+        ctx.push_indent();
+        generate_exec_output(self, ctx, &node.id, "False")?;
+        ctx.pop_indent();
+        ctx.emit(&format!("{}}}\n", indent));
+        Ok(())
+    }
+
+    fn generate_pure_expression<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        _metadata: &NodeMetadata,
+        args: &[String],
+    ) -> Result<String> {
+        self.pure_calls.push(node.id.clone());
+        if args.len() < 2 {
+            return Ok(node.node_type.clone());
+        }
+        let op = if node.node_type == "multiply" { "*" } else { "+" };
+        Ok(format!("({} {} {})", args[0], op, args[1]))
+    }
+}
+
+fn exec_pin_node(id: &str, node_type: &str) -> NodeInstance {
+    let mut node = NodeInstance::new(id, node_type, Position::zero());
+    node.add_input_pin("exec_in", DataType::Execution);
+    node.add_output_pin("exec_out", DataType::Execution);
+    node
+}
+
+// ===========================================================================
+// compile - Traversal
+// ===========================================================================
+
+#[test]
+fn compile_empty_graph_runs_program_hooks() {
+    let graph = GraphDescription::new("empty");
+    let provider = TestMetadataProvider::comprehensive();
+    let mut generator = TestGenerator::default();
+
+    let code = compile(&graph, &provider, &mut generator).unwrap();
+    assert_eq!(code, "// generated\n");
+    assert_eq!(generator.prelude_calls, 1);
+}
+
+#[test]
+fn compile_linear_exec_chain() {
+    let mut graph = build_exec_chain(3);
+    let mut event = NodeInstance::new("start", "on_start", Position::zero());
+    event.add_output_pin("exec", DataType::Execution);
+    graph.add_node(event);
+    graph.add_connection(Connection::execution("start", "exec", "fn_0", "exec_in"));
+
+    let provider = TestMetadataProvider::comprehensive();
+    let code = compile(&graph, &provider, &mut TestGenerator::default()).unwrap();
+
+    let first = code.find("step 0").unwrap();
+    let second = code.find("step 1").unwrap();
+    let third = code.find("step 2").unwrap();
+    assert!(code.contains("fn start() {\n"));
+    assert!(first < second && second < third);
+}
+
+#[test]
+fn compile_branch_emits_both_sides() {
+    let graph = build_branch_graph();
+    let provider = TestMetadataProvider::comprehensive();
+    let code = compile(&graph, &provider, &mut TestGenerator::default()).unwrap();
+
+    let if_pos = code.find("if true {").unwrap();
+    let true_pos = code.find("true branch").unwrap();
+    let else_pos = code.find("} else {").unwrap();
+    let false_pos = code.find("false branch").unwrap();
+    assert!(if_pos < true_pos && true_pos < else_pos && else_pos < false_pos);
+}
+
+#[test]
+fn compile_events_in_id_order() {
+    let mut graph = GraphDescription::new("events");
+    for id in ["zeta", "alpha"] {
+        let mut event = NodeInstance::new(id, "on_start", Position::zero());
+        event.add_output_pin("exec", DataType::Execution);
+        graph.add_node(event);
+    }
+
+    let provider = TestMetadataProvider::comprehensive();
+    let code = compile(&graph, &provider, &mut TestGenerator::default()).unwrap();
+    assert!(code.find("fn alpha").unwrap() < code.find("fn zeta").unwrap());
+}
+
+#[test]
+fn compile_skips_back_edges() {
+    let mut graph = GraphDescription::new("loop");
+    let mut event = NodeInstance::new("start", "on_start", Position::zero());
+    event.add_output_pin("exec", DataType::Execution);
+    graph.add_node(event);
+    graph.add_node(exec_pin_node("a", "print_string"));
+    graph.add_node(exec_pin_node("b", "print_string"));
+    graph.add_connection(Connection::execution("start", "exec", "a", "exec_in"));
+    graph.add_connection(Connection::execution("a", "exec_out", "b", "exec_in"));
+    graph.add_connection(Connection::execution("b", "exec_out", "a", "exec_in"));
+
+    let provider = TestMetadataProvider::comprehensive();
+    let code = compile(&graph, &provider, &mut TestGenerator::default()).unwrap();
+    assert_eq!(code.matches("print_string(").count(), 2);
+}
+
+// ===========================================================================
+// compile - Arguments
+// ===========================================================================
+
+#[test]
+fn compile_inlines_pure_inputs() {
+    let mut graph = GraphDescription::new("pure");
+    let mut event = NodeInstance::new("start", "on_start", Position::zero());
+    event.add_output_pin("exec", DataType::Execution);
+    graph.add_node(event);
+
+    let mut add = NodeInstance::new("add_1", "add", Position::zero());
+    add.add_input_pin("a", DataType::Typed("i64".into()));
+    add.add_input_pin("b", DataType::Typed("i64".into()));
+    add.add_output_pin("result", DataType::Typed("i64".into()));
+    add.set_property("a", PropertyValue::Integer(2));
+    add.set_property("b", PropertyValue::Integer(3));
+    graph.add_node(add);
+
+    let mut set = exec_pin_node("set_1", "set_variable");
+    set.add_input_pin("name", DataType::Typed("String".into()));
+    set.add_input_pin("value", DataType::Typed("i64".into()));
+    graph.add_node(set);
+
+    graph.add_connection(Connection::execution("start", "exec", "set_1", "exec_in"));
+    graph.add_connection(Connection::data("add_1", "result", "set_1", "value"));
+
+    let provider = TestMetadataProvider::comprehensive();
+    let mut generator = TestGenerator::default();
+    let code = compile(&graph, &provider, &mut generator).unwrap();
+
+    assert!(code.contains("set_variable(String::new(), (2 + 3));"), "{}", code);
+    assert_eq!(generator.pure_calls, vec!["add_1"]);
+}
+
+#[test]
+fn compile_uses_metadata_defaults_for_unconnected_inputs() {
+    let mut provider = TestMetadataProvider::comprehensive();
+    provider.add(
+        NodeMetadata::new("wait", NodeTypes::fn_, "time")
+            .with_params(vec![
+                ParamInfo::new("seconds", "f64").with_default(PropertyValue::Number(0.5)),
+                ParamInfo::new("label", "String"),
+            ])
+            .with_exec_outputs(vec!["then".to_string()]),
+    );
+
+    let mut graph = GraphDescription::new("defaults");
+    let mut event = NodeInstance::new("start", "on_start", Position::zero());
+    event.add_output_pin("exec", DataType::Execution);
+    graph.add_node(event);
+    graph.add_node(exec_pin_node("wait_1", "wait"));
+    graph.add_connection(Connection::execution("start", "exec", "wait_1", "exec_in"));
+
+    let code = compile(&graph, &provider, &mut TestGenerator::default()).unwrap();
+
+    assert!(code.contains("wait(0.5, String::new());"), "{}", code);
+}
+
+#[test]
+fn compile_unconnected_required_input_fails() {
+    let mut provider = TestMetadataProvider::comprehensive();
+    provider.add(
+        NodeMetadata::new("damage", NodeTypes::fn_, "game")
+            .with_params(vec![ParamInfo::new("target", "Entity").with_required(true)])
+            .with_exec_outputs(vec!["then".to_string()]),
+    );
+
+    let mut graph = GraphDescription::new("required");
+    let mut event = NodeInstance::new("start", "on_start", Position::zero());
+    event.add_output_pin("exec", DataType::Execution);
+    graph.add_node(event);
+    graph.add_node(exec_pin_node("damage_1", "damage"));
+    graph.add_connection(Connection::execution("start", "exec", "damage_1", "exec_in"));
+
+    let result = compile(&graph, &provider, &mut TestGenerator::default());
+    assert!(
+        matches!(result, Err(GraphyError::UnconnectedInput { ref node, ref pin }) if node == "damage_1" && pin == "target"),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn compile_missing_metadata_fails() {
+    let mut graph = GraphDescription::new("unknown");
+    let mut event = NodeInstance::new("start", "on_start", Position::zero());
+    event.add_output_pin("exec", DataType::Execution);
+    graph.add_node(event);
+    graph.add_node(exec_pin_node("mystery", "does_not_exist"));
+    graph.add_connection(Connection::execution("start", "exec", "mystery", "exec_in"));
+
+    let provider = TestMetadataProvider::comprehensive();
+    let result = compile(&graph, &provider, &mut TestGenerator::default());
+    assert!(
+        matches!(result, Err(GraphyError::UnknownNodeType { ref node, ref node_type }) if node == "mystery" && node_type == "does_not_exist"),
+        "{:?}",
+        result
+    );
+
+    // Skipped unknown nodes can't be generated when they are executed
+    let options = CompileOptions::new().with_unknown_node_policy(UnknownNodePolicy::Skip);
+    let error = compile_with_options(&graph, &provider, &mut TestGenerator::default(), &options).unwrap_err();
+    assert!(matches!(error.root(), GraphyError::CodeGeneration(_)), "{:?}", error);
+    assert_eq!(error.node(), Some("mystery"));
+}
+
+#[test]
+fn compile_unknown_pure_nodes_follow_the_policy() {
+    let mut graph = build_fan_out_graph();
+    let mut unknown = NodeInstance::new("noise_1", "noise", Position::zero());
+    unknown.add_output_pin("result", DataType::Typed("i64".into()));
+    graph.add_node(unknown);
+    let provider = TestMetadataProvider::comprehensive();
+
+    let result = compile(&graph, &provider, &mut TestGenerator::default());
+    assert!(matches!(result, Err(GraphyError::UnknownNodeType { ref node, .. }) if node == "noise_1"), "{:?}", result);
+
+    for policy in [UnknownNodePolicy::Warn, UnknownNodePolicy::Skip] {
+        let options = CompileOptions::new().with_unknown_node_policy(policy);
+        let output = compile_with_options(&graph, &provider, &mut TestGenerator::default(), &options).unwrap();
+        assert!(!output.code.contains("noise_1"), "{}", output.code);
+    }
+}
+
+#[test]
+fn compile_pure_node_in_exec_flow_fails() {
+    let mut graph = GraphDescription::new("bad");
+    let mut event = NodeInstance::new("start", "on_start", Position::zero());
+    event.add_output_pin("exec", DataType::Execution);
+    graph.add_node(event);
+    graph.add_node(exec_pin_node("add_1", "add"));
+    graph.add_connection(Connection::execution("start", "exec", "add_1", "exec_in"));
+
+    let provider = TestMetadataProvider::comprehensive();
+    let error = compile(&graph, &provider, &mut TestGenerator::default()).unwrap_err();
+    assert!(matches!(error.root(), GraphyError::CodeGeneration(_)), "{:?}", error);
+    assert_eq!(error.node(), Some("add_1"));
+}
+
+#[test]
+fn compile_rejects_exec_connections_from_undeclared_pins() {
+    let mut graph = build_branch_graph();
+    graph.add_connection(Connection::execution("branch_1", "Maybe", "print_true", "exec_in"));
+
+    let provider = TestMetadataProvider::comprehensive();
+    let error = compile(&graph, &provider, &mut TestGenerator::default()).unwrap_err();
+    assert!(
+        matches!(&error, GraphyError::InvalidConnection { connection, .. } if connection.index == Some(3)),
+        "{:?}",
+        error
+    );
+}
+
+#[test]
+fn compile_binds_used_event_params() {
+    let mut graph = GraphDescription::new("tick");
+    let mut event = NodeInstance::new("tick", "on_tick", Position::zero());
+    event.add_output_pin("exec", DataType::Execution);
+    event.add_output_pin("delta_time", DataType::Typed("f64".into()));
+    graph.add_node(event);
+
+    let mut set = exec_pin_node("set_1", "set_variable");
+    set.add_input_pin("name", DataType::Typed("String".into()));
+    set.add_input_pin("value", DataType::Typed("i64".into()));
+    graph.add_node(set);
+
+    graph.add_connection(Connection::execution("tick", "exec", "set_1", "exec_in"));
+    graph.add_connection(Connection::data("tick", "delta_time", "set_1", "value"));
+
+    let provider = TestMetadataProvider::comprehensive();
+    let (code, map) = compile_with_source_map(&graph, &provider, &mut TestGenerator::default()).unwrap();
+
+    assert!(code.contains("fn tick(delta_time: f64) {"), "{}", code);
+    assert!(code.contains("    let event_tick_delta_time = delta_time;\n"), "{}", code);
+    assert!(code.contains("set_variable(String::new(), event_tick_delta_time);"), "{}", code);
+
+    let line = code.lines().position(|l| l.contains("let event_tick")).unwrap() + 1;
+    assert_eq!(map.resolve(line, 5).unwrap().pin.as_deref(), Some("delta_time"));
+}
+
+#[test]
+fn compile_skips_unused_event_params() {
+    let mut graph = GraphDescription::new("tick");
+    let mut event = NodeInstance::new("tick", "on_tick", Position::zero());
+    event.add_output_pin("exec", DataType::Execution);
+    graph.add_node(event);
+
+    let provider = TestMetadataProvider::comprehensive();
+    let code = compile(&graph, &provider, &mut TestGenerator::default()).unwrap();
+    assert!(code.contains("fn tick(delta_time: f64) {"), "{}", code);
+    assert!(!code.contains("let "), "{}", code);
+}
+
+// ===========================================================================
+// compile - Expression caching
+// ===========================================================================
+
+/// `start -> set_1 -> set_2`, with `add_1 = 2 + 3` feeding both values
+fn build_fan_out_graph() -> GraphDescription {
+    let mut graph = GraphDescription::new("fan_out");
+    let mut event = NodeInstance::new("start", "on_start", Position::zero());
+    event.add_output_pin("exec", DataType::Execution);
+    graph.add_node(event);
+
+    let mut add = NodeInstance::new("add_1", "add", Position::zero());
+    add.add_input_pin("a", DataType::Typed("i64".into()));
+    add.add_input_pin("b", DataType::Typed("i64".into()));
+    add.add_output_pin("result", DataType::Typed("i64".into()));
+    add.set_property("a", PropertyValue::Integer(2));
+    add.set_property("b", PropertyValue::Integer(3));
+    graph.add_node(add);
+
+    for id in ["set_1", "set_2"] {
+        let mut set = exec_pin_node(id, "set_variable");
+        set.add_input_pin("name", DataType::Typed("String".into()));
+        set.add_input_pin("value", DataType::Typed("i64".into()));
+        graph.add_node(set);
+        graph.add_connection(Connection::data("add_1", "result", id, "value"));
+    }
+
+    graph.add_connection(Connection::execution("start", "exec", "set_1", "exec_in"));
+    graph.add_connection(Connection::execution("set_1", "exec_out", "set_2", "exec_in"));
+    graph
+}
+
+#[test]
+fn compile_auto_policy_hoists_fan_out() {
+    let graph = build_fan_out_graph();
+    let provider = TestMetadataProvider::comprehensive();
+    let mut generator = TestGenerator::default();
+    let (code, map) = compile_with_source_map(&graph, &provider, &mut generator).unwrap();
+
+    assert_eq!(code.matches("(2 + 3)").count(), 1, "{}", code);
+    let binding_line = code.lines().position(|l| l.trim_start().starts_with("let ")).unwrap() + 1;
+    let set_line = code.lines().position(|l| l.contains("set_variable(")).unwrap() + 1;
+    assert!(binding_line < set_line, "{}", code);
+    assert_eq!(map.resolve(binding_line, 5).unwrap().node_id, "add_1");
+    assert_eq!(generator.pure_calls, vec!["add_1"]);
+}
+
+#[test]
+fn compile_auto_policy_inlines_single_use() {
+    let mut generator = TestGenerator::with_policy(ExpressionPolicy::Auto { min_fan_out: 3 });
+    let provider = TestMetadataProvider::comprehensive();
+    let code = compile(&build_fan_out_graph(), &provider, &mut generator).unwrap();
+
+    assert_eq!(code.matches("(2 + 3)").count(), 2, "{}", code);
+    assert!(!code.contains("let "));
+    assert_eq!(generator.pure_calls, vec!["add_1"]);
+}
+
+#[test]
+fn compile_always_inline_reuses_cached_expression() {
+    let mut generator = TestGenerator::with_policy(ExpressionPolicy::AlwaysInline);
+    let provider = TestMetadataProvider::comprehensive();
+    let code = compile(&build_fan_out_graph(), &provider, &mut generator).unwrap();
+
+    assert_eq!(code.matches("set_variable(String::new(), (2 + 3));").count(), 2, "{}", code);
+    assert_eq!(generator.pure_calls, vec!["add_1"]);
+}
+
+#[test]
+fn compile_hoisted_binding_scoped_to_branch() {
+    let mut graph = build_fan_out_graph();
+    graph.connections.retain(|c| c.connection_type != ConnectionType::Execution);
+
+    let mut branch = exec_pin_node("branch_1", "branch");
+    branch.add_input_pin("condition", DataType::Typed("bool".into()));
+    branch.add_output_pin("True", DataType::Execution);
+    branch.add_output_pin("False", DataType::Execution);
+    graph.add_node(branch);
+
+    graph.add_connection(Connection::execution("start", "exec", "branch_1", "exec_in"));
+    graph.add_connection(Connection::execution("branch_1", "True", "set_1", "exec_in"));
+    graph.add_connection(Connection::execution("branch_1", "False", "set_2", "exec_in"));
+
+    let mut generator = TestGenerator::with_policy(ExpressionPolicy::HoistToLet);
+    let provider = TestMetadataProvider::comprehensive();
+    let code = compile(&graph, &provider, &mut generator).unwrap();
+
+    // Each branch binds its own copy instead of referencing the sibling's
+    assert_eq!(code.matches("let ").count(), 2, "{}", code);
+    assert_eq!(generator.pure_calls, vec!["add_1", "add_1"]);
+}
+
+#[test]
+fn context_expression_cache_invalidation() {
+    let graph = build_fan_out_graph();
+    let provider = TestMetadataProvider::comprehensive();
+    let resolver = DataResolver::build(&graph, &provider).unwrap();
+    let routing = ExecutionRouting::build_from_graph(&graph);
+    let mut ctx = CodeGeneratorContext::new(&graph, &provider, &resolver, &routing);
+
+    ctx.cache_expression("add_1", "(2 + 3)");
+    assert_eq!(ctx.cached_expression("add_1"), Some("(2 + 3)"));
+    ctx.invalidate_expression("add_1");
+    assert_eq!(ctx.cached_expression("add_1"), None);
+
+    ctx.push_indent();
+    ctx.cache_expression("add_1", "sum");
+    ctx.pop_indent();
+    assert_eq!(ctx.cached_expression("add_1"), None);
+
+    ctx.cache_expression("add_1", "sum");
+    ctx.clear_expression_cache();
+    assert_eq!(ctx.cached_expression("add_1"), None);
+}
+
+#[test]
+fn context_should_hoist_by_policy() {
+    let graph = build_fan_out_graph();
+    let provider = TestMetadataProvider::comprehensive();
+    let resolver = DataResolver::build(&graph, &provider).unwrap();
+    let routing = ExecutionRouting::build_from_graph(&graph);
+    let ctx = CodeGeneratorContext::new(&graph, &provider, &resolver, &routing);
+
+    assert!(ctx.should_hoist("add_1"));
+    let ctx = ctx.with_expression_policy(ExpressionPolicy::Auto { min_fan_out: 3 });
+    assert!(!ctx.should_hoist("add_1"));
+    let ctx = ctx.with_expression_policy(ExpressionPolicy::AlwaysInline);
+    assert!(!ctx.should_hoist("add_1"));
+    let ctx = ctx.with_expression_policy(ExpressionPolicy::HoistToLet);
+    assert!(ctx.should_hoist("set_1"));
+}
+
+// ===========================================================================
+// compile - Graph variables
+// ===========================================================================
+
+/// `start -> set_1`, writing `score = score + 1`
+fn build_counter_graph() -> GraphDescription {
+    let mut graph = GraphDescription::new("counter");
+    graph.add_variable("score", VariableDecl::new("i64").with_default(PropertyValue::Integer(10)));
+
+    let mut event = NodeInstance::new("start", "on_start", Position::zero());
+    event.add_output_pin("exec", DataType::Execution);
+    graph.add_node(event);
+
+    let get = graph.create_get_variable_node("get_1", "score", Position::zero()).unwrap();
+    graph.add_node(get);
+    let set = graph.create_set_variable_node("set_1", "score", Position::zero()).unwrap();
+    graph.add_node(set);
+
+    let mut add = NodeInstance::new("add_1", "add", Position::zero());
+    add.add_input_pin("a", DataType::Typed("i64".into()));
+    add.add_input_pin("b", DataType::Typed("i64".into()));
+    add.add_output_pin("result", DataType::Typed("i64".into()));
+    add.set_property("b", PropertyValue::Integer(1));
+    graph.add_node(add);
+
+    graph.add_connection(Connection::execution("start", "exec", "set_1", "exec_in"));
+    graph.add_connection(Connection::data("get_1", "value", "add_1", "a"));
+    graph.add_connection(Connection::data("add_1", "result", "set_1", "value"));
+    graph
+}
+
+#[test]
+fn compile_emits_state_struct_and_variable_access() {
+    let graph = build_counter_graph();
+    let provider = TestMetadataProvider::comprehensive();
+    let code = compile(&graph, &provider, &mut TestGenerator::default()).unwrap();
+
+    assert!(code.contains("pub struct GraphState {\n    pub score: i64,\n}"), "{}", code);
+    assert!(code.contains("            score: 10,\n"), "{}", code);
+    assert!(code.contains("    state.score = (state.score + 1);\n"), "{}", code);
+}
+
+#[test]
+fn compile_without_variables_has_no_state() {
+    let graph = build_branch_graph();
+    let provider = TestMetadataProvider::comprehensive();
+    let code = compile(&graph, &provider, &mut TestGenerator::default()).unwrap();
+    assert!(!code.contains("GraphState"));
+}
+
+#[test]
+fn compile_rereads_variable_after_write() {
+    // start -> set_1 -> set_2, both writing `score + 1`: the hoisted read
+    // must not be reused after the first write
+    let mut graph = build_counter_graph();
+    let set = graph.create_set_variable_node("set_2", "score", Position::zero()).unwrap();
+    graph.add_node(set);
+    graph.add_connection(Connection::execution("set_1", "exec_out", "set_2", "exec_in"));
+    graph.add_connection(Connection::data("get_1", "value", "set_2", "value"));
+
+    let provider = TestMetadataProvider::comprehensive();
+    let code = compile(&graph, &provider, &mut TestGenerator::default()).unwrap();
+
+    assert_eq!(code.matches("let node_get_1_result = state.score;").count(), 2, "{}", code);
+    assert!(code.contains("    state.score = node_get_1_result;\n"), "{}", code);
+}
+
+#[test]
+fn compile_undeclared_variable_fails() {
+    let mut graph = build_counter_graph();
+    graph.remove_variable("score");
+
+    let provider = TestMetadataProvider::comprehensive();
+    let result = compile(&graph, &provider, &mut TestGenerator::default());
+    assert!(matches!(result, Err(GraphyError::UndeclaredVariable { .. })));
+}
+
+// ===========================================================================
+// compile - Async nodes
+// ===========================================================================
+
+/// Comprehensive provider plus async `fetch` (function) and `read_sensor` (pure)
+fn async_provider() -> TestMetadataProvider {
+    let mut provider = TestMetadataProvider::comprehensive();
+    provider.add(
+        NodeMetadata::new("fetch", NodeTypes::fn_, "network")
+            .with_params(vec![ParamInfo::new("value", "i64")])
+            .with_exec_outputs(vec!["then".to_string()])
+            .with_async(true),
+    );
+    provider.add(
+        NodeMetadata::new("read_sensor", NodeTypes::pure, "io")
+            .with_return_type("i64")
+            .with_async(true),
+    );
+    provider
+}
+
+/// `start -> fetch_1` and a `tick` event with nothing connected
+fn build_async_graph() -> GraphDescription {
+    let mut graph = GraphDescription::new("async");
+    for (id, node_type) in [("start", "on_start"), ("tick", "on_tick")] {
+        let mut event = NodeInstance::new(id, node_type, Position::zero());
+        event.add_output_pin("exec", DataType::Execution);
+        graph.add_node(event);
+    }
+    graph.add_node(exec_pin_node("fetch_1", "fetch"));
+    graph.add_connection(Connection::execution("start", "exec", "fetch_1", "exec_in"));
+    graph
+}
+
+fn async_generator() -> TestGenerator {
+    TestGenerator {
+        async_support: true,
+        ..TestGenerator::default()
+    }
+}
+
+#[test]
+fn compile_async_function_node_makes_event_async() {
+    let graph = build_async_graph();
+    let code = compile(&graph, &async_provider(), &mut async_generator()).unwrap();
+
+    assert!(code.contains("async fn start() {\n    fetch(0).await;\n}"), "{}", code);
+    assert!(code.contains("\nfn tick(delta_time: f64) {"), "{}", code);
+}
+
+#[test]
+fn compile_async_pure_dependency_is_awaited() {
+    let mut graph = build_async_graph();
+    let mut sensor = NodeInstance::new("sensor_1", "read_sensor", Position::zero());
+    sensor.add_output_pin("result", DataType::Typed("i64".into()));
+    graph.add_node(sensor);
+
+    let mut fetch = exec_pin_node("fetch_2", "fetch");
+    fetch.add_input_pin("value", DataType::Typed("i64".into()));
+    graph.add_node(fetch);
+    graph.add_connection(Connection::execution("tick", "exec", "fetch_2", "exec_in"));
+    graph.add_connection(Connection::data("sensor_1", "result", "fetch_2", "value"));
+
+    let provider = async_provider();
+    let code = compile(&graph, &provider, &mut async_generator()).unwrap();
+    assert!(code.contains("async fn tick(delta_time: f64) {"), "{}", code);
+    assert!(code.contains("fetch(read_sensor.await).await;"), "{}", code);
+}
+
+#[test]
+fn compile_async_without_generator_support_fails() {
+    let graph = build_async_graph();
+    let result = compile(&graph, &async_provider(), &mut TestGenerator::default());
+    match result {
+        Err(GraphyError::CodeGeneration(message)) => {
+            assert!(message.contains("start") && message.contains("fetch_1"), "{}", message)
+        }
+        other => panic!("expected a code generation error, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn compile_unreachable_async_node_is_ignored() {
+    let mut graph = build_async_graph();
+    graph.remove_node("start");
+
+    let code = compile(&graph, &async_provider(), &mut TestGenerator::default()).unwrap();
+    assert!(!code.contains("async"), "{}", code);
+}
+
+#[test]
+fn async_analysis_reports_events_and_nodes() {
+    let graph = build_async_graph();
+    let provider = async_provider();
+    let resolver = DataResolver::build(&graph, &provider).unwrap();
+    let routing = ExecutionRouting::build_from_graph(&graph);
+
+    let analysis = graphy::analysis::AsyncAnalysis::compute(&graph, &provider, &routing, &resolver);
+    assert_eq!(analysis.async_events(), vec!["start"]);
+    assert_eq!(analysis.async_nodes_of("start"), ["fetch_1".to_string()]);
+    assert!(analysis.is_async_node("fetch_1"));
+    assert!(!analysis.is_async_event("tick"));
+}
+
+// ===========================================================================
+// compile - Fallible nodes
+// ===========================================================================
+
+/// Comprehensive provider plus a fallible `read_file`
+fn fallible_provider() -> TestMetadataProvider {
+    let mut provider = TestMetadataProvider::comprehensive();
+    provider.add(
+        NodeMetadata::new("read_file", NodeTypes::fn_, "io")
+            .with_params(vec![ParamInfo::new("path", "String")])
+            .with_return_type("Result<String, String>")
+            .with_exec_outputs(vec!["then".to_string()]),
+    );
+    provider
+}
+
+/// `start -> read_1`, printing the contents on success
+fn build_fallible_graph() -> GraphDescription {
+    let mut graph = GraphDescription::new("fallible");
+    let mut event = NodeInstance::new("start", "on_start", Position::zero());
+    event.add_output_pin("exec", DataType::Execution);
+    graph.add_node(event);
+
+    graph.add_node(exec_pin_node("read_1", "read_file"));
+    let mut print_ok = exec_pin_node("print_ok", "print_string");
+    print_ok.add_input_pin("message", DataType::Typed("String".into()));
+    graph.add_node(print_ok);
+
+    graph.add_connection(Connection::execution("start", "exec", "read_1", "exec_in"));
+    graph.add_connection(Connection::execution("read_1", "then", "print_ok", "exec_in"));
+    graph.add_connection(Connection::data("read_1", "result", "print_ok", "message"));
+    graph
+}
+
+#[test]
+fn compile_fallible_node_routes_error_flow() {
+    let mut graph = build_fallible_graph();
+    let mut print_err = exec_pin_node("print_err", "print_string");
+    print_err.add_input_pin("message", DataType::Typed("String".into()));
+    graph.add_node(print_err);
+    graph.add_connection(Connection::execution("read_1", core::ERROR_EXEC_PIN, "print_err", "exec_in"));
+    graph.add_connection(Connection::data("read_1", core::ERROR_VALUE_PIN, "print_err", "message"));
+
+    let code = compile(&graph, &fallible_provider(), &mut TestGenerator::default()).unwrap();
+    let expected = "\
+fn start() {
+    match read_file(String::new()) {
+        Ok(node_read_1_result) => {
+            print_string(node_read_1_result);
+        }
+        Err(node_read_1_error) => {
+            print_string(node_read_1_error);
+        }
+    }
+}
+";
+    assert!(code.contains(expected), "{}", code);
+}
+
+#[test]
+fn compile_fallible_node_without_error_flow() {
+    let mut graph = build_fallible_graph();
+    graph.remove_connection("read_1", "result", "print_ok", "message");
+
+    let code = compile(&graph, &fallible_provider(), &mut TestGenerator::default()).unwrap();
+    assert!(code.contains("        Ok(_) => {\n            print_string(String::new());\n"), "{}", code);
+    assert!(code.contains("        Err(_) => {\n        }\n"), "{}", code);
+}
+
+#[test]
+fn compile_async_fallible_node_is_awaited() {
+    let mut provider = fallible_provider();
+    let metadata = provider.metadata.get_node_metadata("read_file").unwrap().clone();
+    provider.add(metadata.with_async(true));
+
+    let code = compile(&build_fallible_graph(), &provider, &mut async_generator()).unwrap();
+    assert!(code.contains("async fn start() {"), "{}", code);
+    assert!(code.contains("match read_file(String::new()).await {"), "{}", code);
+}
+
+#[test]
+fn fallible_error_variable_only_when_read() {
+    let mut graph = build_fallible_graph();
+    let provider = fallible_provider();
+    let resolver = DataResolver::build(&graph, &provider).unwrap();
+    assert!(resolver.get_error_variable("read_1").is_none());
+    assert!(!ExecutionRouting::build_from_graph(&graph).has_error_flow("read_1"));
+
+    graph.add_connection(Connection::data("read_1", core::ERROR_VALUE_PIN, "print_ok", "message"));
+    graph.add_connection(Connection::execution("read_1", core::ERROR_EXEC_PIN, "print_ok", "exec_in"));
+    let resolver = DataResolver::build(&graph, &provider).unwrap();
+    assert_eq!(resolver.get_error_variable("read_1").unwrap(), "node_read_1_error");
+    assert!(ExecutionRouting::build_from_graph(&graph).has_error_flow("read_1"));
+}
+
+// ===========================================================================
+// compile - Common subexpressions
+// ===========================================================================
+
+/// Fan-out graph with a second `add_2 = 2 + 3` feeding `set_2` instead of `add_1`
+fn build_duplicate_graph() -> GraphDescription {
+    let mut graph = build_fan_out_graph();
+    let mut add = graph.nodes["add_1"].clone();
+    add.id = "add_2".to_string();
+    graph.add_node(add);
+    graph
+        .connections
+        .retain(|connection| connection.connection_type == ConnectionType::Execution || connection.target_node != "set_2");
+    graph.add_connection(Connection::data("add_2", "result", "set_2", "value"));
+    graph
+}
+
+#[test]
+fn compile_without_cse_evaluates_duplicates() {
+    let provider = TestMetadataProvider::comprehensive();
+    let mut generator = TestGenerator::default();
+    let code = compile(&build_duplicate_graph(), &provider, &mut generator).unwrap();
+
+    assert_eq!(code.matches("(2 + 3)").count(), 2, "{}", code);
+    assert_eq!(generator.pure_calls, vec!["add_1", "add_2"]);
+}
+
+#[test]
+fn compile_cse_reuses_one_variable() {
+    let provider = TestMetadataProvider::comprehensive();
+    let mut generator = TestGenerator::default();
+    let options = CompileOptions::new().with_common_subexpressions(true).with_report(true);
+    let output = compile_with_options(&build_duplicate_graph(), &provider, &mut generator, &options).unwrap();
+
+    // Both readers count toward the fan-out, so the value is hoisted
+    assert_eq!(output.code.matches("(2 + 3)").count(), 1, "{}", output.code);
+    assert!(output.code.contains("let node_add_1_result = (2 + 3);"), "{}", output.code);
+    assert_eq!(output.code.matches("set_variable(String::new(), node_add_1_result);").count(), 2);
+    assert_eq!(generator.pure_calls, vec!["add_1"]);
+
+    let report = output.report.unwrap();
+    assert_eq!((report.pure_nodes, report.merged_nodes), (1, 1));
+    assert!(report.pass_duration("cse").is_some());
+    assert!(report.to_string().contains("1 merged"));
+}
+
+#[test]
+fn compile_cse_keeps_volatile_nodes() {
+    let mut provider = TestMetadataProvider::comprehensive();
+    provider.add(
+        NodeMetadata::new("add", NodeTypes::pure, "math")
+            .with_params(vec![ParamInfo::new("a", "i64"), ParamInfo::new("b", "i64")])
+            .with_return_type("i64")
+            .with_volatile(true),
+    );
+    let mut generator = TestGenerator::default();
+    let options = CompileOptions::new().with_common_subexpressions(true);
+    let output = compile_with_options(&build_duplicate_graph(), &provider, &mut generator, &options).unwrap();
+
+    assert_eq!(output.code.matches("(2 + 3)").count(), 2, "{}", output.code);
+    assert_eq!(generator.pure_calls, vec!["add_1", "add_2"]);
+}
+
+#[test]
+fn compile_applies_rewrite_rules() {
+    let graph = build_fan_out_graph();
+    let provider = TestMetadataProvider::comprehensive();
+    let mut generator = TestGenerator::default();
+    let add_to_multiply = RewriteRule::new(
+        "add_to_multiply",
+        Pattern::new().with_node("add", PatternNode::of_type("add")),
+        Replacement::new().retype("add", "multiply", &[("a", "a"), ("b", "b"), ("result", "result")]),
+    );
+    let options = CompileOptions::new()
+        .with_rewrite_rules(vec![add_to_multiply])
+        .with_report(true);
+    let output = compile_with_options(&graph, &provider, &mut generator, &options).unwrap();
+
+    assert!(output.code.contains("(2 * 3)"), "{}", output.code);
+    assert!(!output.code.contains("(2 + 3)"), "{}", output.code);
+    assert!(output.report.unwrap().pass_duration("rewrite").is_some());
+    // The caller's graph is untouched
+    assert_eq!(graph.nodes["add_1"].node_type, "add");
+}
+
+#[test]
+fn compile_rejects_unsatisfied_node_versions() {
+    let mut graph = build_fan_out_graph();
+    let provider = TestMetadataProvider::comprehensive();
+    let mut generator = TestGenerator::default();
+    graph.nodes.get_mut("add_1").unwrap().version_req = Some(VersionReq::parse("^2").unwrap());
+
+    // The comprehensive provider's metadata is unversioned
+    let error = compile(&graph, &provider, &mut generator).unwrap_err();
+    assert!(
+        matches!(error, GraphyError::UnsatisfiedVersion { ref node, ref available, .. } if node == "add_1" && available.is_empty()),
+        "{:?}",
+        error
+    );
+}
+
+#[test]
+fn compile_rejects_inputs_with_too_many_connections() {
+    let mut graph = build_fan_out_graph();
+    let provider = TestMetadataProvider::comprehensive();
+    let mut generator = TestGenerator::default();
+    let mut other = NodeInstance::new("add_2", "add", Position::zero());
+    other.add_output_pin("result", DataType::Typed("i64".into()));
+    graph.add_node(other);
+    graph.add_connection(Connection::data("add_2", "result", "set_1", "value"));
+
+    let error = compile(&graph, &provider, &mut generator).unwrap_err();
+    assert!(
+        matches!(error, GraphyError::TooManyConnections { ref node, ref pin, connections: 2, max: 1 } if node == "set_1" && pin == "value"),
+        "{:?}",
+        error
+    );
+}
+
+// ===========================================================================
+// compile - Side-effect ordering
+// ===========================================================================
+
+#[test]
+fn compile_getter_after_setter() {
+    let mut graph = build_getter_graph();
+    graph.add_connection(Connection::execution("start", "exec", "read_1", "exec_in"));
+    graph.add_connection(Connection::execution("read_1", "then", "use_1", "exec_in"));
+
+    let code = compile(&graph, &ordering_provider(), &mut TestGenerator::default()).unwrap();
+    assert!(code.contains("    read_value();\n    use_value((node_read_1_result + 1));\n"), "{}", code);
+}
+
+#[test]
+fn compile_getter_before_setter_fails() {
+    let mut graph = build_getter_graph();
+    graph.add_connection(Connection::execution("start", "exec", "use_1", "exec_in"));
+    graph.add_connection(Connection::execution("use_1", "then", "read_1", "exec_in"));
+
+    let result = compile(&graph, &ordering_provider(), &mut TestGenerator::default());
+    match result {
+        Err(GraphyError::AmbiguousOrdering { node, producer, .. }) => {
+            assert_eq!((node.as_str(), producer.as_str()), ("use_1", "read_1"))
+        }
+        other => panic!("expected an ordering error, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn compile_reevaluates_getter_after_setter_runs_again() {
+    // `read_1 -> use_1` runs twice in a row: once from start, once from print_2
+    let mut graph = build_getter_graph();
+    graph.add_node(exec_pin_node("print_2", "print_string"));
+    graph.add_connection(Connection::execution("start", "exec", "read_1", "exec_in"));
+    graph.add_connection(Connection::execution("read_1", "then", "use_1", "exec_in"));
+    graph.add_connection(Connection::execution("start", "exec", "print_2", "exec_in"));
+    graph.add_connection(Connection::execution("print_2", "exec_out", "read_1", "exec_in"));
+
+    let mut generator = TestGenerator::with_policy(ExpressionPolicy::AlwaysInline);
+    let code = compile(&graph, &ordering_provider(), &mut generator).unwrap();
+    assert_eq!(code.matches("read_value();").count(), 2, "{}", code);
+    assert_eq!(generator.pure_calls, vec!["add_1", "add_1"]);
+}
+
+// ===========================================================================
+// compile_with_source_map
+// ===========================================================================
+
+#[test]
+fn compile_source_map_covers_nodes() {
+    let graph = build_branch_graph();
+    let provider = TestMetadataProvider::comprehensive();
+    let (code, map) = compile_with_source_map(&graph, &provider, &mut TestGenerator::default()).unwrap();
+
+    let line = code.lines().position(|l| l.contains("false branch")).unwrap() + 1;
+    assert_eq!(map.resolve(line, 10).unwrap().node_id, "print_false");
+
+    let if_line = code.lines().position(|l| l.contains("if true")).unwrap() + 1;
+    assert_eq!(map.resolve(if_line, 5).unwrap().node_id, "branch_1");
+    assert_eq!(map.mappings_for_node("start").count(), 1);
+}
+
+// ===========================================================================
+// compile_with_options - Reports
+// ===========================================================================
+
+#[test]
+fn compile_without_report_option_has_no_report() {
+    let graph = build_branch_graph();
+    let provider = TestMetadataProvider::comprehensive();
+    let output = compile_with_options(&graph, &provider, &mut TestGenerator::default(), &CompileOptions::new()).unwrap();
+
+    assert!(output.report.is_none());
+    assert_eq!(output.code, compile(&graph, &provider, &mut TestGenerator::default()).unwrap());
+}
+
+#[test]
+fn compile_report_counts_nodes_and_passes() {
+    let graph = build_branch_graph();
+    let provider = TestMetadataProvider::comprehensive();
+    let options = CompileOptions::new().with_report(true);
+    let output = compile_with_options(&graph, &provider, &mut TestGenerator::default(), &options).unwrap();
+    let report = output.report.unwrap();
+
+    assert_eq!(report.graph_name, graph.metadata.name);
+    assert_eq!((report.input_nodes, report.expanded_nodes), (4, 4));
+    assert_eq!(report.connections, graph.connections.len());
+    assert_eq!(report.events, 1);
+    assert_eq!(report.generated_nodes, 4);
+
+    let passes: Vec<&str> = report.passes.iter().map(|pass| pass.name.as_str()).collect();
+    assert_eq!(passes, ["variables", "compact_graph", "data_flow", "exec_routing", "ordering", "async"]);
+    assert!(report.pass_duration("data_flow").is_some());
+    assert!(report.total >= report.expansion + report.analysis);
+}
+
+#[test]
+fn compile_report_tracks_expression_cache() {
+    let provider = TestMetadataProvider::comprehensive();
+    let options = CompileOptions::new().with_report(true);
+    let mut generator = TestGenerator::with_policy(ExpressionPolicy::AlwaysInline);
+    let output = compile_with_options(&build_fan_out_graph(), &provider, &mut generator, &options).unwrap();
+    let report = output.report.unwrap();
+
+    assert_eq!(report.pure_nodes, 1);
+    assert_eq!(report.expression_cache, CacheStats { hits: 1, misses: 1 });
+    assert_eq!(report.expression_cache.hit_rate(), 0.5);
+    assert_eq!(CacheStats::default().hit_rate(), 0.0);
+}
+
+#[test]
+fn compile_report_serialization_roundtrip() {
+    let graph = build_branch_graph();
+    let provider = TestMetadataProvider::comprehensive();
+    let options = CompileOptions::new().with_report(true);
+    let report = compile_with_options(&graph, &provider, &mut TestGenerator::default(), &options)
+        .unwrap()
+        .report
+        .unwrap();
+
+    let json = serde_json::to_string(&report).unwrap();
+    let restored: CompilationReport = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, report);
+    assert!(report.to_string().contains("exec_routing"));
+}
+
+// ===========================================================================
+// compile_with_options - Parallel analysis
+// ===========================================================================
+
+#[test]
+fn compile_parallel_threshold_keeps_the_output() {
+    let graph = build_fan_out_graph();
+    // A registry can be shared between threads, unlike the test provider
+    let registry = &TestMetadataProvider::comprehensive().metadata;
+    let compile_at = |threshold: ParallelThreshold| {
+        let options = CompileOptions::new().with_parallel_threshold(threshold);
+        compile_with_options(&graph, registry, &mut TestGenerator::default(), &options).unwrap().code
+    };
+
+    assert_eq!(compile_at(ParallelThreshold::new(1)), compile_at(ParallelThreshold::never()));
+    assert_eq!(CompileOptions::new().parallel_threshold, ParallelThreshold::new(5_000));
+}
+
+// ===========================================================================
+// compile_event
+// ===========================================================================
+
+/// Events `alpha` and `beta`, each running one print with its own message
+fn build_two_events() -> GraphDescription {
+    let mut graph = GraphDescription::new("two_events");
+    for id in ["alpha", "beta"] {
+        let mut event = NodeInstance::new(id, "on_start", Position::zero());
+        event.add_output_pin("exec", DataType::Execution);
+        graph.add_node(event);
+
+        let print_id = format!("{}_print", id);
+        let mut print = exec_pin_node(&print_id, "print_string");
+        print.add_input_pin("message", DataType::Typed("String".into()));
+        print.set_property("message", PropertyValue::String(format!("{} says hi", id)));
+        graph.add_node(print);
+        graph.add_connection(Connection::execution(id, "exec", print_id.as_str(), "exec_in"));
+    }
+    graph
+}
+
+#[test]
+fn compile_event_generates_only_that_event() {
+    let graph = build_two_events();
+    let provider = TestMetadataProvider::comprehensive();
+    let mut generator = TestGenerator::default();
+
+    let code = compile_event(&graph, &provider, "beta", &mut generator).unwrap();
+    assert!(code.starts_with("// generated\n"), "{}", code);
+    assert!(code.contains("fn beta() {\n"), "{}", code);
+    assert!(code.contains("beta says hi"), "{}", code);
+    assert!(!code.contains("alpha"), "{}", code);
+    assert_eq!(generator.prelude_calls, 1);
+}
+
+#[test]
+fn compile_event_matches_compile_for_a_single_event() {
+    let graph = build_branch_graph();
+    let provider = TestMetadataProvider::comprehensive();
+
+    let full = compile(&graph, &provider, &mut TestGenerator::default()).unwrap();
+    let event = compile_event(&graph, &provider, "start", &mut TestGenerator::default()).unwrap();
+    assert_eq!(event, full);
+}
+
+#[test]
+fn compile_event_keeps_pure_inputs() {
+    let mut graph = build_fan_out_graph();
+    let provider = TestMetadataProvider::comprehensive();
+    let full = compile(&graph, &provider, &mut TestGenerator::default()).unwrap();
+
+    let mut other = NodeInstance::new("other", "on_start", Position::zero());
+    other.add_output_pin("exec", DataType::Execution);
+    graph.add_node(other);
+
+    let mut generator = TestGenerator::default();
+    let code = compile_event(&graph, &provider, "start", &mut generator).unwrap();
+    assert_eq!(code, full);
+    assert_eq!(generator.pure_calls, vec!["add_1"]);
+}
+
+#[test]
+fn compile_event_ignores_errors_elsewhere() {
+    let mut graph = build_two_events();
+    graph.add_node(exec_pin_node("mystery", "does_not_exist"));
+    graph.add_connection(Connection::execution("alpha_print", "exec_out", "mystery", "exec_in"));
+    let provider = TestMetadataProvider::comprehensive();
+
+    assert!(compile(&graph, &provider, &mut TestGenerator::default()).is_err());
+    let error = compile_event(&graph, &provider, "alpha", &mut TestGenerator::default()).unwrap_err();
+    assert!(matches!(error, GraphyError::UnknownNodeType { ref node, .. } if node == "mystery"), "{:?}", error);
+
+    let code = compile_event(&graph, &provider, "beta", &mut TestGenerator::default()).unwrap();
+    assert!(code.contains("beta says hi"), "{}", code);
+}
+
+#[test]
+fn compile_event_rejects_missing_and_non_event_nodes() {
+    let graph = build_two_events();
+    let provider = TestMetadataProvider::comprehensive();
+
+    let missing = compile_event(&graph, &provider, "gamma", &mut TestGenerator::default());
+    assert!(matches!(missing, Err(GraphyError::NodeNotFound(ref id)) if id == "gamma"), "{:?}", missing);
+
+    let not_event = compile_event(&graph, &provider, "alpha_print", &mut TestGenerator::default());
+    assert!(matches!(not_event, Err(GraphyError::CodeGeneration(_))), "{:?}", not_event);
+}
+
+#[test]
+fn compile_event_report_counts_the_subgraph() {
+    let graph = build_two_events();
+    let provider = TestMetadataProvider::comprehensive();
+    let options = CompileOptions::new().with_report(true);
+
+    let output = compile_event_with_options(&graph, &provider, "alpha", &mut TestGenerator::default(), &options).unwrap();
+    let report = output.report.unwrap();
+    assert_eq!(report.input_nodes, 2);
+    assert_eq!(report.events, 1);
+    assert!(output.source_map.mappings().iter().all(|mapping| mapping.node_id.starts_with("alpha")));
+}
@@ -0,0 +1,177 @@
+//! Tests for serializing and overriding compile options.
+
+mod common;
+
+use common::*;
+use graphy::analysis::{EvaluationOrder, ParallelThreshold, UnknownNodePolicy};
+use graphy::core::CompileConfig;
+use graphy::generation::{compile_with_options, BranchHelpers, CompileOptions, DebugHooks, EventDispatcher,
+    InlineLimits, OptLevel, ParallelCodegen, ValueProbes};
+use graphy::io::Format;
+use graphy::utils::logging::Verbosity;
+use graphy::utils::CancellationToken;
+use graphy::*;
+use serde_json::{json, Value};
+
+/// Options changing every serialized field
+fn custom_options() -> CompileOptions {
+    CompileOptions::new()
+        .with_report(true)
+        .with_verbosity(Verbosity::Verbose)
+        .with_common_subexpressions(true)
+        .with_opt_level(OptLevel::Aggressive)
+        .with_unknown_node_policy(UnknownNodePolicy::Warn)
+        .with_config(CompileConfig::new().with_feature("editor").with_platform("web"))
+        .with_evaluation_order(EvaluationOrder::Previous(vec!["b".into(), "a".into()]))
+        .with_parallel_threshold(ParallelThreshold::new(200))
+        .with_parallel_codegen(ParallelCodegen::new(40))
+        .with_branch_helpers(BranchHelpers::new(3))
+        .with_dispatcher(EventDispatcher::new("GameEvents").with_trait_declaration(true))
+        .with_debug(DebugHooks::new().with_hit_function("on_breakpoint"))
+        .with_probes(ValueProbes::new().with_sink("state.probes"))
+        .with_inline_limits(InlineLimits::new().with_max_depth(8).with_max_length(0))
+}
+
+fn document(options: &CompileOptions) -> Value {
+    serde_json::to_value(options).unwrap()
+}
+
+// ============================================================================
+// Serialization
+// ============================================================================
+
+#[test]
+fn compile_options_round_trip_in_every_format() {
+    let options = custom_options();
+    for format in Format::ALL.into_iter().filter(Format::is_available) {
+        let bytes = format.serialize(&options).unwrap();
+        let loaded: CompileOptions = format.deserialize(&bytes).unwrap();
+        assert_eq!(document(&loaded), document(&options), "{}", format);
+    }
+}
+
+#[test]
+fn compile_options_use_snake_case_names() {
+    let document = document(&custom_options());
+    assert_eq!(document["verbosity"], "verbose");
+    assert_eq!(document["opt_level"], "aggressive");
+    assert_eq!(document["on_unknown_node"], "warn");
+    assert_eq!(document["evaluation_order"], json!({ "previous": ["b", "a"] }));
+    assert_eq!(document["branch_helpers"], json!({ "min_nodes": 3 }));
+    assert!(document.get("cancellation").is_none());
+}
+
+#[test]
+fn compile_options_default_missing_fields() {
+    let options: CompileOptions = Format::Json.deserialize(br#"{ "opt_level": "basic" }"#).unwrap();
+    assert_eq!(options.opt_level, OptLevel::Basic);
+    assert_eq!(document(&options.clone().with_opt_level(OptLevel::None)), document(&CompileOptions::new()));
+}
+
+#[test]
+fn compile_options_reject_unknown_fields() {
+    let result = Format::Json.deserialize::<CompileOptions>(br#"{ "opt_levle": "basic" }"#);
+    assert!(matches!(result, Err(GraphyError::Serialization(ref message)) if message.contains("opt_levle")));
+}
+
+// ============================================================================
+// Files
+// ============================================================================
+
+#[test]
+fn compile_options_files_use_the_format_of_their_extension() {
+    let dir = temp_dir("compile_options");
+    let options = custom_options();
+
+    for format in Format::ALL.into_iter().filter(Format::is_available) {
+        let path = dir.join(format!("graphy.{}", format.extension()));
+        options.save(&path).unwrap();
+        assert_eq!(document(&CompileOptions::load(&path).unwrap()), document(&options));
+    }
+
+    assert!(matches!(options.save(dir.join("graphy.txt")), Err(GraphyError::Serialization(_))));
+    assert!(matches!(CompileOptions::load(dir.join("missing.json")), Err(GraphyError::Io(_))));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn compile_options_read_project_toml() {
+    let dir = temp_dir("compile_options_toml");
+    let path = dir.join("graphy.toml");
+    std::fs::write(
+        &path,
+        "opt_level = \"basic\"\n\
+         on_unknown_node = \"skip\"\n\
+         \n\
+         [config]\n\
+         enabled_features = [\"editor\"]\n\
+         \n\
+         [parallel]\n\
+         min_cost = 25\n",
+    )
+    .unwrap();
+
+    let options = CompileOptions::load(&path).unwrap();
+    assert_eq!(options.opt_level, OptLevel::Basic);
+    assert_eq!(options.on_unknown_node, UnknownNodePolicy::Skip);
+    assert!(options.config.enabled_features.contains("editor"));
+    assert_eq!(options.parallel, Some(ParallelCodegen::new(25)));
+    assert_eq!(options.branch_helpers, None);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+// ============================================================================
+// Overrides
+// ============================================================================
+
+#[test]
+fn compile_options_overrides_merge_tables() {
+    let options = custom_options()
+        .with_overrides(&json!({
+            "opt_level": "none",
+            "config": { "target_platform": "desktop" },
+            "branch_helpers": null,
+        }))
+        .unwrap();
+
+    assert_eq!(options.opt_level, OptLevel::None);
+    assert_eq!(options.config.target_platform.as_deref(), Some("desktop"));
+    assert!(options.config.enabled_features.contains("editor"));
+    assert_eq!(options.branch_helpers, None);
+    assert_eq!(options.parallel, Some(ParallelCodegen::new(40)));
+}
+
+#[test]
+fn compile_options_overrides_keep_runtime_options() {
+    let cancellation = CancellationToken::new();
+    let options = CompileOptions::new()
+        .with_cancellation(cancellation.clone())
+        .with_overrides(&json!({ "report": true }))
+        .unwrap();
+
+    cancellation.cancel();
+    assert!(options.cancellation.is_cancelled());
+    assert!(options.report);
+}
+
+#[test]
+fn compile_options_overrides_are_checked() {
+    let result = CompileOptions::new().with_overrides(&json!({ "opt_level": "maximum" }));
+    assert!(matches!(result, Err(GraphyError::Serialization(_))));
+}
+
+#[test]
+fn compile_options_loaded_options_drive_compilation() {
+    let mut graph = GraphDescription::new("unknown");
+    graph.add_node(NodeInstance::new("noise_1", "noise", Position::zero()));
+    let registry = NodeRegistry::new();
+
+    let strict: CompileOptions = Format::Json.deserialize(b"{}").unwrap();
+    let result = compile_with_options(&graph, &registry, &mut NoopGenerator::new(), &strict);
+    assert!(matches!(result, Err(GraphyError::UnknownNodeType { .. })));
+
+    let lenient = strict.with_overrides(&json!({ "on_unknown_node": "skip", "report": true })).unwrap();
+    let output = compile_with_options(&graph, &registry, &mut NoopGenerator::new(), &lenient).unwrap();
+    assert!(output.report.is_some());
+}
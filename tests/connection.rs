@@ -1,7 +1,19 @@
-//! Tests for Connection and ConnectionType.
+//! Tests for Connection, ConnectionType, and connection validation.
 
+mod common;
+
+use common::*;
+use graphy::core::ConnectionErrorReason;
 use graphy::*;
 
+/// The reason of an invalid connection error
+fn reason<T: std::fmt::Debug>(result: Result<T>) -> ConnectionErrorReason {
+    match result {
+        Err(GraphyError::InvalidConnection { reason, .. }) => reason,
+        other => panic!("expected an invalid connection, got {:?}", other),
+    }
+}
+
 // ===========================================================================
 // ConnectionType
 // ===========================================================================
@@ -112,3 +124,456 @@ fn connection_same_pin_names_different_nodes() {
     assert_eq!(c1.target_pin, "result");
     assert_ne!(c1.source_node, c1.target_node);
 }
+
+// ===========================================================================
+// Connection validation (try_add_connection)
+// ===========================================================================
+
+fn typed_node(id: &str, node_type: &str) -> NodeInstance {
+    let mut node = NodeInstance::new(id, node_type, Position::zero());
+    node.add_input_pin("exec_in", DataType::Execution);
+    node.add_input_pin("a", DataType::Typed("i64".into()));
+    node.add_input_pin("label", DataType::String);
+    node.add_output_pin("exec_out", DataType::Execution);
+    node.add_output_pin("result", DataType::Typed("i64".into()));
+    node.add_output_pin("ratio", DataType::Number);
+    node
+}
+
+fn two_node_graph() -> GraphDescription {
+    let mut graph = GraphDescription::new("validation");
+    graph.add_node(typed_node("n1", "add"));
+    graph.add_node(typed_node("n2", "add"));
+    graph
+}
+
+#[test]
+fn valid_data_and_exec_connections() {
+    let mut graph = two_node_graph();
+    let provider = TestMetadataProvider::with_math_nodes();
+
+    graph.try_add_connection(Connection::data("n1", "result", "n2", "a"), &provider).unwrap();
+    graph
+        .try_add_connection(Connection::execution("n1", "exec_out", "n2", "exec_in"), &provider)
+        .unwrap();
+    assert_eq!(graph.connections.len(), 2);
+}
+
+#[test]
+fn missing_node() {
+    let mut graph = two_node_graph();
+    let provider = TestMetadataProvider::empty();
+
+    let result = graph.try_add_connection(Connection::data("n1", "result", "ghost", "a"), &provider);
+    assert_eq!(reason(result), ConnectionErrorReason::MissingNode { node: "ghost".into() });
+    assert!(graph.connections.is_empty());
+}
+
+#[test]
+fn missing_pin() {
+    let graph = two_node_graph();
+    let provider = TestMetadataProvider::empty();
+
+    let result = graph.validate_connection(&Connection::data("n1", "nope", "n2", "a"), &provider);
+    assert_eq!(
+        reason(result),
+        ConnectionErrorReason::MissingPin {
+            node: "n1".into(),
+            pin: "nope".into()
+        }
+    );
+}
+
+#[test]
+fn wrong_pin_direction() {
+    let graph = two_node_graph();
+    let provider = TestMetadataProvider::empty();
+
+    let result = graph.validate_connection(&Connection::data("n1", "a", "n2", "a"), &provider);
+    assert_eq!(
+        reason(result),
+        ConnectionErrorReason::WrongDirection {
+            node: "n1".into(),
+            pin: "a".into(),
+            expected: PinType::Output,
+        }
+    );
+}
+
+#[test]
+fn connection_type_must_match_pins() {
+    let graph = two_node_graph();
+    let provider = TestMetadataProvider::empty();
+
+    let data_on_exec = Connection::data("n1", "exec_out", "n2", "exec_in");
+    assert!(matches!(
+        reason(graph.validate_connection(&data_on_exec, &provider)),
+        ConnectionErrorReason::ConnectionTypeMismatch { .. }
+    ));
+
+    let exec_on_data = Connection::execution("n1", "result", "n2", "a");
+    assert!(matches!(
+        reason(graph.validate_connection(&exec_on_data, &provider)),
+        ConnectionErrorReason::ConnectionTypeMismatch { .. }
+    ));
+}
+
+#[test]
+fn type_mismatch() {
+    let graph = two_node_graph();
+    let provider = TestMetadataProvider::empty();
+
+    let result = graph.validate_connection(&Connection::data("n1", "ratio", "n2", "a"), &provider);
+    assert_eq!(
+        reason(result),
+        ConnectionErrorReason::TypeMismatch {
+            expected: "i64".into(),
+            actual: "f64".into()
+        }
+    );
+}
+
+#[test]
+fn legacy_types_match_typed_equivalents() {
+    let mut graph = two_node_graph();
+    let mut sink = NodeInstance::new("sink", "any", Position::zero());
+    sink.add_input_pin("value", DataType::Typed("f64".into()));
+    sink.add_input_pin("anything", DataType::Any);
+    graph.add_node(sink);
+    let provider = TestMetadataProvider::empty();
+
+    assert!(graph.validate_connection(&Connection::data("n1", "ratio", "sink", "value"), &provider).is_ok());
+    assert!(graph.validate_connection(&Connection::data("n1", "result", "sink", "anything"), &provider).is_ok());
+}
+
+#[test]
+fn pins_resolved_from_metadata() {
+    let mut graph = GraphDescription::new("metadata_pins");
+    graph.add_node(typed_node("n1", "add"));
+    // No pins declared on the instance; metadata declares params a/b
+    graph.add_node(NodeInstance::new("bare", "add", Position::zero()));
+    let provider = TestMetadataProvider::with_math_nodes();
+
+    assert!(graph.validate_connection(&Connection::data("n1", "result", "bare", "b"), &provider).is_ok());
+    assert!(matches!(
+        reason(graph.validate_connection(&Connection::data("n1", "result", "bare", "c"), &provider)),
+        ConnectionErrorReason::MissingPin { .. }
+    ));
+}
+
+#[test]
+fn result_and_exec_in_pins_resolved_from_metadata() {
+    let mut provider = TestMetadataProvider::comprehensive();
+    provider.add(
+        NodeMetadata::new("print_value", NodeTypes::fn_, "io")
+            .with_params(vec![ParamInfo::new("value", "i64")])
+            .with_exec_outputs(vec!["then".to_string()]),
+    );
+    provider.add(NodeMetadata::new("parse", NodeTypes::fn_, "io").with_return_type("Result<i64, String>"));
+    let mut graph = GraphDescription::new("bare_pins");
+    for (id, node_type) in [("start", "on_start"), ("add_1", "add"), ("print_1", "print_value"), ("parse_1", "parse")] {
+        graph.add_node(NodeInstance::new(id, node_type, Position::zero()));
+    }
+
+    graph.try_add_connection(Connection::data("add_1", "result", "print_1", "value"), &provider).unwrap();
+    graph
+        .try_add_connection(Connection::execution("start", "exec", "print_1", "exec_in"), &provider)
+        .unwrap();
+    // Fallible nodes give the Ok type
+    graph.try_add_connection(Connection::data("parse_1", "result", "add_1", "a"), &provider).unwrap();
+
+    // Pure nodes have no exec input, nodes without a return type no result
+    let missing = |connection: Connection| {
+        matches!(reason(graph.validate_connection(&connection, &provider)), ConnectionErrorReason::MissingPin { .. })
+    };
+    assert!(missing(Connection::execution("start", "exec", "add_1", "exec_in")));
+    assert!(missing(Connection::data("print_1", "result", "add_1", "b")));
+
+    let mut builder = core::GraphBuilder::new("built", &provider);
+    builder.add_node(NodeInstance::new("add_1", "add", Position::zero())).unwrap();
+    builder.add_node(NodeInstance::new("print_1", "print_value", Position::zero())).unwrap();
+    builder.connect_data("add_1", "result", "print_1", "value").unwrap();
+}
+
+// ===========================================================================
+// Execution connections
+// ===========================================================================
+
+#[test]
+fn reversed_connection() {
+    let graph = two_node_graph();
+    let provider = TestMetadataProvider::empty();
+
+    let backwards = Connection::execution("n2", "exec_in", "n1", "exec_out");
+    let error = graph.validate_connection(&backwards, &provider).unwrap_err();
+    assert!(error.to_string().ends_with("drawn backwards, from an input to an output"), "{}", error);
+    assert_eq!(reason(Err::<(), _>(error)), ConnectionErrorReason::Reversed);
+    assert_eq!(
+        reason(graph.validate_connection(&Connection::data("n2", "a", "n1", "result"), &provider)),
+        ConnectionErrorReason::Reversed
+    );
+    assert_eq!(
+        serde_json::to_value(ConnectionErrorReason::Reversed).unwrap(),
+        serde_json::json!({ "kind": "reversed" })
+    );
+}
+
+#[test]
+fn exec_connections_allow_fan_in_and_fan_out() {
+    let mut graph = build_branch_graph();
+    graph.add_connection(Connection::execution("print_true", "exec_out", "print_false", "exec_in"));
+    graph.add_connection(Connection::execution("start", "exec", "print_false", "exec_in"));
+    // Exec outputs of the metadata don't have to be declared on the node
+    graph.add_node(NodeInstance::new("branch_2", "branch", Position::zero()));
+    graph.add_connection(Connection::execution("branch_2", "True", "print_true", "exec_in"));
+
+    assert!(graph.check_exec_connections(&TestMetadataProvider::comprehensive()).is_ok());
+}
+
+#[test]
+fn exec_connections_leave_exec_outputs() {
+    let provider = TestMetadataProvider::comprehensive();
+    let check = |connection: Connection| {
+        let mut graph = build_branch_graph();
+        graph.add_connection(connection);
+        reason(graph.check_exec_connections(&provider))
+    };
+
+    assert_eq!(
+        check(Connection::execution("branch_1", "Maybe", "print_true", "exec_in")),
+        ConnectionErrorReason::MissingPin {
+            node: "branch_1".into(),
+            pin: "Maybe".into()
+        }
+    );
+    assert_eq!(
+        check(Connection::execution("print_true", "exec_in", "branch_1", "True")),
+        ConnectionErrorReason::Reversed
+    );
+    assert!(matches!(
+        check(Connection::execution("start", "exec", "print_true", "exec_out")),
+        ConnectionErrorReason::WrongDirection { expected: PinType::Input, .. }
+    ));
+    assert!(matches!(
+        check(Connection::execution("start", "exec", "print_true", "message")),
+        ConnectionErrorReason::ConnectionTypeMismatch { .. }
+    ));
+}
+
+#[test]
+fn exec_connections_of_unknown_nodes_are_not_checked() {
+    let mut graph = build_branch_graph();
+    graph.add_node(NodeInstance::new("custom_1", "custom", Position::zero()));
+    graph.add_connection(Connection::execution("custom_1", "next", "print_true", "exec_in"));
+    graph.add_connection(Connection::execution("print_true", "exec_out", "ghost", "exec_in"));
+
+    assert!(graph.check_exec_connections(&TestMetadataProvider::comprehensive()).is_ok());
+}
+
+// ===========================================================================
+// GraphBuilder
+// ===========================================================================
+
+#[test]
+fn builder_accepts_valid_graph() {
+    let provider = TestMetadataProvider::with_math_nodes();
+    let mut builder = core::GraphBuilder::new("built", &provider);
+    builder.add_node(typed_node("n1", "add")).unwrap().add_node(typed_node("n2", "add")).unwrap();
+    builder.connect_data("n1", "result", "n2", "a").unwrap();
+    builder.connect_execution("n1", "exec_out", "n2", "exec_in").unwrap();
+
+    let graph = builder.build();
+    assert_eq!(graph.metadata.name, "built");
+    assert_eq!(graph.nodes.len(), 2);
+    assert_eq!(graph.connections.len(), 2);
+}
+
+#[test]
+fn builder_rejects_duplicate_nodes_and_bad_connections() {
+    let provider = TestMetadataProvider::empty();
+    let mut builder = core::GraphBuilder::new("strict", &provider);
+    builder.add_node(typed_node("n1", "add")).unwrap();
+
+    assert!(builder.add_node(typed_node("n1", "add")).is_err());
+    assert!(builder.connect_data("n1", "result", "n2", "a").is_err());
+    assert!(builder.graph().connections.is_empty());
+}
+
+// ===========================================================================
+// Connection - IDs and editor metadata
+// ===========================================================================
+
+#[test]
+fn connection_editor_metadata_serialization() {
+    let plain = Connection::data("a", "result", "b", "x");
+    let json = serde_json::to_string(&plain).unwrap();
+    for field in ["\"id\"", "waypoints", "label", "color"] {
+        assert!(!json.contains(field), "{} should be omitted", field);
+    }
+
+    // Files written before the fields existed still load
+    let old = r#"{"source_node":"a","source_pin":"result","target_node":"b","target_pin":"x","connection_type":"Data"}"#;
+    assert_eq!(serde_json::from_str::<Connection>(old).unwrap(), plain);
+
+    let decorated = plain
+        .with_id("c1")
+        .with_waypoints(vec![Position::new(10.0, 20.0), Position::new(30.0, 20.0)])
+        .with_label("speed")
+        .with_color("#00ff00");
+    let back: Connection = serde_json::from_str(&serde_json::to_string(&decorated).unwrap()).unwrap();
+    assert_eq!(back, decorated);
+}
+
+#[test]
+fn connection_hash_agrees_with_eq() {
+    use std::collections::HashSet;
+
+    let a = Connection::data("a", "result", "b", "x").with_waypoints(vec![Position::new(0.0, 5.0)]);
+    let b = Connection::data("a", "result", "b", "x").with_waypoints(vec![Position::new(-0.0, 5.0)]);
+    let moved = Connection::data("a", "result", "b", "x").with_waypoints(vec![Position::new(1.0, 5.0)]);
+    assert_eq!(a, b);
+
+    let set: HashSet<Connection> = [a, b, moved].into_iter().collect();
+    assert_eq!(set.len(), 2);
+}
+
+#[test]
+fn connection_display_and_endpoints() {
+    let conn = Connection::execution("start", "exec", "print", "exec_in");
+    assert_eq!(conn.to_string(), "start.exec -> print.exec_in");
+
+    let labeled = conn.clone().with_id("wire_7").with_label("go");
+    assert_eq!(labeled.to_string(), "wire_7: start.exec -> print.exec_in");
+    assert!(labeled.same_endpoints(&conn));
+    assert_ne!(labeled, conn);
+    assert!(!conn.same_endpoints(&Connection::data("start", "exec", "print", "exec_in")));
+}
+
+#[test]
+fn graph_connection_ids() {
+    let mut graph = build_diamond_graph();
+    graph.connections[1].id = Some("conn_2".into());
+
+    assert_eq!(graph.assign_connection_ids(), 3);
+    let ids: Vec<&str> = graph.connections.iter().map(|c| c.id.as_deref().unwrap()).collect();
+    assert_eq!(ids, ["conn_1", "conn_2", "conn_3", "conn_4"]);
+    assert_eq!(graph.assign_connection_ids(), 0);
+
+    assert_eq!(graph.get_connection("conn_3").unwrap().target_node, "node_d");
+    graph.get_connection_mut("conn_3").unwrap().label = Some("left".into());
+    assert_eq!(graph.get_connection("conn_3").unwrap().label.as_deref(), Some("left"));
+
+    let removed = graph.remove_connection_by_id("conn_3").unwrap();
+    assert_eq!(removed.id.as_deref(), Some("conn_3"));
+    assert!(graph.get_connection("conn_3").is_none());
+    assert!(graph.remove_connection_by_id("conn_3").is_none());
+}
+
+#[test]
+fn editor_metadata_is_not_semantic() {
+    let graph = build_diamond_graph();
+    let mut decorated = graph.clone();
+    decorated.assign_connection_ids();
+    decorated.connections[0].waypoints.push(Position::new(50.0, 50.0));
+    decorated.connections[2].color = Some("red".into());
+
+    assert!(graph.semantically_equals(&decorated));
+    assert_eq!(graph.content_hash(), decorated.content_hash());
+}
+
+#[test]
+fn connection_id_in_validation_errors() {
+    let provider = TestMetadataProvider::comprehensive();
+    let mut graph = GraphDescription::new("test");
+    let mut start = NodeInstance::new("start", "on_start", Position::zero());
+    start.add_output_pin("exec", DataType::Execution);
+    graph.add_node(start);
+    let mut add = NodeInstance::new("add", "add", Position::zero());
+    add.add_input_pin("a", DataType::Typed("i64".into()));
+    graph.add_node(add);
+
+    let err = graph
+        .try_add_connection(Connection::data("start", "exec", "add", "a").with_id("bad_wire"), &provider)
+        .unwrap_err();
+    assert!(err.to_string().contains("bad_wire: start.exec -> add.a"), "{}", err);
+}
+
+// ===========================================================================
+// Connection errors
+// ===========================================================================
+
+#[test]
+fn connection_stable_id_is_deterministic() {
+    let wire = Connection::data("n1", "result", "n2", "a");
+    let id = wire.stable_id();
+    assert_eq!(id, Connection::data("n1", "result", "n2", "a").with_label("sum").stable_id());
+    assert!(id.starts_with('c') && id.len() == 13, "{}", id);
+    assert!(id[1..].chars().all(|c| c.is_ascii_hexdigit()), "{}", id);
+
+    assert_ne!(id, Connection::data("n1", "result", "n2", "b").stable_id());
+    assert_ne!(id, Connection::execution("n1", "result", "n2", "a").stable_id());
+    assert_eq!(wire.with_id("w1").stable_id(), "w1");
+}
+
+#[test]
+fn connection_errors_name_the_edge() {
+    let mut graph = two_node_graph();
+    let provider = TestMetadataProvider::empty();
+    graph.add_connection(Connection::data("n1", "result", "n2", "a"));
+    graph.add_connection(Connection::data("n1", "ratio", "n2", "a"));
+
+    match graph.validate_connection(&graph.connections[1], &provider) {
+        Err(GraphyError::InvalidConnection { connection, reason }) => {
+            assert_eq!(connection.index, Some(1));
+            assert_eq!(connection.id, graph.connections[1].stable_id());
+            assert_eq!((connection.source_node.as_str(), connection.source_pin.as_str()), ("n1", "ratio"));
+            assert_eq!((connection.target_node.as_str(), connection.target_pin.as_str()), ("n2", "a"));
+            assert_eq!(connection.connection_type, ConnectionType::Data);
+            assert!(matches!(reason, ConnectionErrorReason::TypeMismatch { .. }));
+        }
+        other => panic!("expected an invalid connection, got {:?}", other),
+    }
+
+    let error = graph
+        .validate_connection(&Connection::data("n1", "nope", "n2", "a").with_id("w7"), &provider)
+        .unwrap_err();
+    assert_eq!(error.to_string(), "Invalid connection w7: n1.nope -> n2.a: pin n1.nope doesn't exist");
+}
+
+#[test]
+fn connection_error_reasons_serialize() {
+    let reason = ConnectionErrorReason::MissingPin {
+        node: "n1".into(),
+        pin: "nope".into(),
+    };
+    let json = serde_json::to_value(&reason).unwrap();
+    assert_eq!(json, serde_json::json!({ "kind": "missing_pin", "node": "n1", "pin": "nope" }));
+    assert_eq!(serde_json::from_value::<ConnectionErrorReason>(json).unwrap(), reason);
+    assert_eq!(
+        serde_json::to_value(ConnectionErrorReason::NotFound).unwrap(),
+        serde_json::json!({ "kind": "not_found" })
+    );
+}
+
+#[test]
+fn connection_errors_in_data_and_exec_flow() {
+    let provider = TestMetadataProvider::comprehensive();
+
+    let mut graph = build_branch_graph();
+    graph.add_connection(Connection::data("ghost", "value", "print_true", "message"));
+    assert!(matches!(
+        DataResolver::build(&graph, &provider),
+        Err(GraphyError::InvalidConnection { connection, reason: ConnectionErrorReason::MissingNode { node } })
+            if connection.index == Some(3) && node == "ghost"
+    ));
+
+    let mut graph = build_branch_graph();
+    graph.add_connection(Connection::execution("print_true", "exec_out", "ghost", "exec_in"));
+    assert!(DataResolver::build(&graph, &provider).is_ok());
+    let error = graph.check_connection_nodes(ConnectionType::Execution).unwrap_err();
+    assert!(
+        matches!(&error, GraphyError::InvalidConnection { connection, .. } if connection.index == Some(3)),
+        "{:?}",
+        error
+    );
+}
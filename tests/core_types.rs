@@ -51,6 +51,22 @@ fn typeinfo_ne() {
     assert_ne!(a, b);
 }
 
+#[test]
+fn typeinfo_result_types() {
+    let cases = [
+        ("Result<i64, String>", Some(("i64", Some("String")))),
+        ("Result<HashMap<String, (u8, u8)>, Box<dyn Error>>", Some(("HashMap<String, (u8, u8)>", Some("Box<dyn Error>")))),
+        ("std::io::Result<()>", Some(("()", None))),
+        ("anyhow::Result<Vec<u8>>", Some(("Vec<u8>", None))),
+        ("Option<Result<i64, String>>", None),
+        ("MyResult<i64>", None),
+        ("Result", None),
+    ];
+    for (type_string, expected) in cases {
+        assert_eq!(core::TypeInfo::new(type_string).result_types(), expected, "{}", type_string);
+    }
+}
+
 // ===========================================================================
 // DataType
 // ===========================================================================
@@ -221,6 +237,37 @@ fn property_value_clone() {
     }
 }
 
+#[test]
+fn property_value_integer_is_exact() {
+    let pv = PropertyValue::Integer(i64::MAX);
+    match &pv {
+        PropertyValue::Integer(i) => assert_eq!(*i, i64::MAX),
+        _ => panic!("expected Integer variant"),
+    }
+}
+
+#[test]
+fn property_value_nested_collections() {
+    let mut map = std::collections::HashMap::new();
+    map.insert("items".to_string(), PropertyValue::Array(vec![PropertyValue::Integer(1)]));
+    let pv = PropertyValue::Map(map);
+
+    match &pv {
+        PropertyValue::Map(m) => assert!(matches!(&m["items"], PropertyValue::Array(items) if items.len() == 1)),
+        _ => panic!("expected Map variant"),
+    }
+}
+
+#[test]
+fn property_value_equality() {
+    let a = PropertyValue::Enum {
+        variant: "Direction::Left".into(),
+        payload: None,
+    };
+    assert_eq!(a, a.clone());
+    assert_ne!(PropertyValue::Integer(1), PropertyValue::Number(1.0));
+}
+
 // ===========================================================================
 // Position
 // ===========================================================================
@@ -255,6 +302,7 @@ fn position_copy() {
 }
 
 #[test]
+#[allow(clippy::clone_on_copy)]
 fn position_clone() {
     let a = Position::new(3.0, 4.0);
     let b = a.clone();
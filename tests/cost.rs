@@ -0,0 +1,160 @@
+//! Tests for node cost hints and per-event cost analysis.
+
+mod common;
+
+use common::*;
+use graphy::analysis::{CostAnalysis, EventCost};
+use graphy::*;
+
+/// Comprehensive nodes plus costed ones: `noise` (40) and `scale` (5) are
+/// pure, `move` (10), `raycast` (100) and `blink` (1) are functions
+fn cost_provider() -> TestMetadataProvider {
+    let mut provider = TestMetadataProvider::comprehensive();
+    for (name, cost) in [("noise", 40), ("scale", 5)] {
+        provider.add(
+            NodeMetadata::new(name, NodeTypes::pure, "costly")
+                .with_params(vec![ParamInfo::new("value", "f64")])
+                .with_return_type("f64")
+                .with_cost_hint(cost),
+        );
+    }
+    for (name, cost) in [("move", 10), ("raycast", 100), ("blink", 1)] {
+        provider.add(
+            NodeMetadata::new(name, NodeTypes::fn_, "costly")
+                .with_params(vec![ParamInfo::new("value", "f64")])
+                .with_exec_outputs(vec!["exec_out".to_string()])
+                .with_cost_hint(cost),
+        );
+    }
+    provider
+}
+
+/// `tick` runs `move` then `raycast`; `move` reads `scale`, which reads
+/// `noise`. `idle` runs a print without a cost hint.
+fn build_tick_graph() -> GraphDescription {
+    let mut graph = GraphDescription::new("costs");
+    event_node(&mut graph, "tick", "on_start");
+    exec_node(&mut graph, "move", "move", &["exec_out"]);
+    exec_node(&mut graph, "raycast", "raycast", &["exec_out"]);
+    graph.add_node(NodeInstance::new("noise", "noise", Position::zero()));
+    graph.add_node(NodeInstance::new("scale", "scale", Position::zero()));
+    graph.add_connection(Connection::execution("tick", "exec", "move", "exec_in"));
+    graph.add_connection(Connection::execution("move", "exec_out", "raycast", "exec_in"));
+    graph.add_connection(Connection::data("noise", "result", "scale", "value"));
+    graph.add_connection(Connection::data("scale", "result", "move", "value"));
+
+    event_node(&mut graph, "idle", "on_start");
+    exec_node(&mut graph, "print", "print_string", &["exec_out"]);
+    graph.add_connection(Connection::execution("idle", "exec", "print", "exec_in"));
+    graph
+}
+
+fn analyze(graph: &GraphDescription, provider: &TestMetadataProvider) -> CostAnalysis {
+    let resolver = DataResolver::build(graph, provider).unwrap();
+    let routing = ExecutionRouting::build_from_graph(graph);
+    CostAnalysis::compute(graph, provider, &routing, &resolver)
+}
+
+// ===========================================================================
+// Cost hints
+// ===========================================================================
+
+#[test]
+fn cost_hint_serializes_only_when_set() {
+    let plain = NodeMetadata::new("print", NodeTypes::fn_, "io");
+    assert_eq!(plain.cost_hint, 0);
+    assert!(!serde_json::to_string(&plain).unwrap().contains("cost_hint"));
+
+    let costly = plain.with_cost_hint(250);
+    let json = serde_json::to_string(&costly).unwrap();
+    assert!(json.contains("\"cost_hint\":250"), "{}", json);
+    let restored: NodeMetadata = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.cost_hint, 250);
+}
+
+// ===========================================================================
+// CostAnalysis
+// ===========================================================================
+
+#[test]
+fn cost_totals_every_node_an_event_runs() {
+    let analysis = analyze(&build_tick_graph(), &cost_provider());
+
+    assert_eq!(analysis.events(), vec!["idle", "tick"]);
+    assert_eq!(analysis.event_cost("tick").unwrap().total, 155);
+    assert_eq!(analysis.event_cost("idle"), Some(&EventCost::default()));
+    assert_eq!(analysis.event_cost("move"), None);
+}
+
+#[test]
+fn cost_critical_path_follows_data_and_execution() {
+    let mut graph = build_tick_graph();
+    // A cheap input of the raycast doesn't lengthen the path
+    graph.add_node(NodeInstance::new("offset", "scale", Position::zero()));
+    graph.add_connection(Connection::data("offset", "result", "raycast", "value"));
+
+    let analysis = analyze(&graph, &cost_provider());
+    let tick = analysis.event_cost("tick").unwrap();
+    assert_eq!(tick.total, 160);
+    assert_eq!(tick.critical_path, vec!["noise", "scale", "move", "raycast"]);
+    assert_eq!(tick.critical_path_cost, 155);
+}
+
+#[test]
+fn cost_critical_path_takes_the_heaviest_branch() {
+    let mut graph = GraphDescription::new("branches");
+    event_node(&mut graph, "tick", "on_start");
+    let mut branch = NodeInstance::new("branch_1", "branch", Position::zero());
+    branch.add_input_pin("exec_in", DataType::Execution);
+    branch.add_input_pin("condition", DataType::Typed("bool".into()));
+    branch.add_output_pin("True", DataType::Execution);
+    branch.add_output_pin("False", DataType::Execution);
+    branch.set_property("condition", PropertyValue::Boolean(true));
+    graph.add_node(branch);
+    exec_node(&mut graph, "cheap", "blink", &["exec_out"]);
+    exec_node(&mut graph, "expensive", "raycast", &["exec_out"]);
+    graph.add_connection(Connection::execution("tick", "exec", "branch_1", "exec_in"));
+    graph.add_connection(Connection::execution("branch_1", "True", "cheap", "exec_in"));
+    graph.add_connection(Connection::execution("branch_1", "False", "expensive", "exec_in"));
+
+    let analysis = analyze(&graph, &cost_provider());
+    let tick = analysis.event_cost("tick").unwrap();
+    assert_eq!(tick.total, 101);
+    assert_eq!(tick.critical_path, vec!["expensive"]);
+    assert_eq!(tick.critical_path_cost, 100);
+}
+
+#[test]
+fn cost_counts_loop_bodies_once() {
+    let mut graph = GraphDescription::new("loop");
+    event_node(&mut graph, "tick", "on_start");
+    exec_node(&mut graph, "a", "move", &["exec_out"]);
+    exec_node(&mut graph, "b", "raycast", &["exec_out"]);
+    graph.add_connection(Connection::execution("tick", "exec", "a", "exec_in"));
+    graph.add_connection(Connection::execution("a", "exec_out", "b", "exec_in"));
+    graph.add_connection(Connection::execution("b", "exec_out", "a", "exec_in"));
+
+    let analysis = analyze(&graph, &cost_provider());
+    let tick = analysis.event_cost("tick").unwrap();
+    assert_eq!(tick.total, 110);
+    assert_eq!(tick.critical_path, vec!["a", "b"]);
+}
+
+#[test]
+fn cost_over_budget_lists_expensive_events() {
+    let analysis = analyze(&build_tick_graph(), &cost_provider());
+
+    assert_eq!(analysis.over_budget(100), vec!["tick"]);
+    assert_eq!(analysis.over_budget(155), Vec::<&str>::new());
+    assert_eq!(CostAnalysis::default().over_budget(0), Vec::<&str>::new());
+}
+
+#[test]
+fn cost_unknown_nodes_are_free() {
+    let mut graph = build_tick_graph();
+    exec_node(&mut graph, "mystery", "does_not_exist", &["exec_out"]);
+    graph.add_connection(Connection::execution("raycast", "exec_out", "mystery", "exec_in"));
+
+    let analysis = analyze(&graph, &cost_provider());
+    assert_eq!(analysis.event_cost("tick").unwrap().total, 155);
+}
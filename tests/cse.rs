@@ -0,0 +1,197 @@
+//! Tests for common subexpression elimination of pure nodes.
+
+mod common;
+
+use common::*;
+use graphy::analysis::{CommonSubexpressionPass, CommonSubexpressions, DataFlowPass};
+use graphy::*;
+
+fn cse_provider() -> TestMetadataProvider {
+    let mut provider = TestMetadataProvider::comprehensive();
+    provider.add(
+        NodeMetadata::new("random", NodeTypes::pure, "math")
+            .with_return_type("i64")
+            .with_volatile(true),
+    );
+    provider
+}
+
+/// Adds a pure `a op b` node with constant inputs (`None` leaves the input open)
+fn pure_node(graph: &mut GraphDescription, id: &str, node_type: &str, a: Option<i64>, b: Option<i64>) {
+    let mut node = NodeInstance::new(id, node_type, Position::zero());
+    node.add_input_pin("a", DataType::Typed("i64".into()));
+    node.add_input_pin("b", DataType::Typed("i64".into()));
+    node.add_output_pin("result", DataType::Typed("i64".into()));
+    for (pin, value) in [("a", a), ("b", b)] {
+        if let Some(value) = value {
+            node.set_property(pin, PropertyValue::Integer(value));
+        }
+    }
+    graph.add_node(node);
+}
+
+fn random_node(graph: &mut GraphDescription, id: &str) {
+    let mut node = NodeInstance::new(id, "random", Position::zero());
+    node.add_output_pin("result", DataType::Typed("i64".into()));
+    graph.add_node(node);
+}
+
+fn analyze(graph: &GraphDescription) -> (DataResolver, CommonSubexpressions) {
+    let provider = cse_provider();
+    let resolver = DataResolver::build(graph, &provider).unwrap();
+    let cse = CommonSubexpressions::compute(graph, &provider, &resolver);
+    (resolver, cse)
+}
+
+// ===========================================================================
+// Equivalence
+// ===========================================================================
+
+#[test]
+fn cse_merges_nodes_with_equal_inputs() {
+    let mut graph = GraphDescription::new("cse");
+    pure_node(&mut graph, "add_2", "add", Some(2), Some(3));
+    pure_node(&mut graph, "add_1", "add", Some(2), Some(3));
+    pure_node(&mut graph, "add_3", "add", Some(2), Some(4));
+    pure_node(&mut graph, "mul_1", "multiply", Some(2), Some(3));
+
+    let (_, cse) = analyze(&graph);
+    assert_eq!(cse.duplicates(), ["add_2"]);
+    assert_eq!(cse.len(), 1);
+    assert_eq!(cse.representative("add_2"), "add_1");
+    assert_eq!(cse.replacement("add_2"), Some("add_1"));
+    assert_eq!(cse.representative("add_3"), "add_3");
+    assert_eq!(cse.representative("mul_1"), "mul_1");
+    assert!(!cse.is_duplicate("add_1"));
+}
+
+#[test]
+fn cse_merges_whole_subtrees() {
+    // (2 + 3) * 4 built twice, plus (2 + 4) * 4
+    let mut graph = GraphDescription::new("cse");
+    for (add, mul, b) in [("add_1", "mul_1", 3), ("add_2", "mul_2", 3), ("add_3", "mul_3", 4)] {
+        pure_node(&mut graph, add, "add", Some(2), Some(b));
+        pure_node(&mut graph, mul, "multiply", None, Some(4));
+        graph.add_connection(Connection::data(add, "result", mul, "a"));
+    }
+
+    let (_, cse) = analyze(&graph);
+    assert_eq!(cse.duplicates(), ["add_2", "mul_2"]);
+    assert_eq!(cse.representative("mul_2"), "mul_1");
+    assert_eq!(cse.representative("mul_3"), "mul_3");
+}
+
+#[test]
+fn cse_distinguishes_input_order() {
+    let mut graph = GraphDescription::new("cse");
+    pure_node(&mut graph, "sub_1", "add", Some(2), Some(3));
+    pure_node(&mut graph, "sub_2", "add", Some(3), Some(2));
+
+    let (_, cse) = analyze(&graph);
+    assert!(cse.is_empty());
+}
+
+#[test]
+fn cse_never_merges_volatile_nodes() {
+    let mut graph = GraphDescription::new("cse");
+    random_node(&mut graph, "random_1");
+    random_node(&mut graph, "random_2");
+    for (random, add) in [("random_1", "add_1"), ("random_2", "add_2")] {
+        pure_node(&mut graph, add, "add", None, Some(1));
+        graph.add_connection(Connection::data(random, "result", add, "a"));
+    }
+
+    let (_, cse) = analyze(&graph);
+    assert!(cse.is_empty(), "{:?}", cse.duplicates());
+}
+
+#[test]
+fn cse_merges_diamond_branches() {
+    // Both branches compute `node_a + 2`
+    let (_, cse) = analyze(&build_diamond_graph());
+    assert_eq!(cse.duplicates(), ["node_c"]);
+    assert_eq!(cse.representative("node_c"), "node_b");
+}
+
+// ===========================================================================
+// Applying to the data resolver
+// ===========================================================================
+
+#[test]
+fn cse_rewires_data_resolver() {
+    let mut graph = build_exec_chain(2);
+    pure_node(&mut graph, "add_1", "add", Some(2), Some(3));
+    pure_node(&mut graph, "add_2", "add", Some(2), Some(3));
+    graph.add_connection(Connection::data("add_1", "result", "fn_0", "message"));
+    graph.add_connection(Connection::data("add_2", "result", "fn_1", "message"));
+
+    let (mut resolver, cse) = analyze(&graph);
+    resolver.apply_common_subexpressions(&cse);
+
+    match resolver.get_input_source("fn_1", "message") {
+        Some(DataSource::Connection { source_node_id, source_pin }) => {
+            assert_eq!((source_node_id.as_str(), source_pin.as_str()), ("add_1", "result"));
+        }
+        other => panic!("unexpected source {:?}", other),
+    }
+    assert_eq!(resolver.get_pure_evaluation_order(), ["add_1"]);
+    assert_eq!(resolver.get_dependents("add_1"), ["fn_0", "fn_1"]);
+    assert!(resolver.get_dependents("add_2").is_empty());
+    assert_eq!(resolver.get_dependencies("fn_1"), ["add_1"]);
+}
+
+#[test]
+fn cse_rewires_merged_dependents() {
+    let graph = build_diamond_graph();
+    let (mut resolver, cse) = analyze(&graph);
+    resolver.apply_common_subexpressions(&cse);
+
+    assert_eq!(resolver.get_pure_evaluation_order(), ["node_a", "node_b", "node_d"]);
+    assert_eq!(resolver.get_dependents("node_a"), ["node_b"]);
+    assert_eq!(resolver.get_dependencies("node_d"), ["node_b"]);
+    assert_eq!(resolver.get_transitive_dependents("node_a"), ["node_b", "node_d"]);
+}
+
+#[test]
+fn cse_apply_empty_is_noop() {
+    let mut graph = build_exec_chain(2);
+    pure_node(&mut graph, "add_1", "add", Some(2), Some(3));
+    graph.add_connection(Connection::data("add_1", "result", "fn_0", "message"));
+    let (mut resolver, cse) = analyze(&graph);
+    assert!(cse.is_empty());
+
+    resolver.apply_common_subexpressions(&cse);
+    assert_eq!(resolver.get_pure_evaluation_order(), ["add_1"]);
+    assert_eq!(resolver.get_dependents("add_1"), ["fn_0"]);
+}
+
+// ===========================================================================
+// Pass
+// ===========================================================================
+
+#[test]
+fn cse_pass_applies_to_stored_resolver() {
+    let mut graph = GraphDescription::new("cse");
+    pure_node(&mut graph, "add_1", "add", Some(2), Some(3));
+    pure_node(&mut graph, "add_2", "add", Some(2), Some(3));
+
+    let provider = cse_provider();
+    let mut manager = PassManager::new();
+    manager.add_pass(DataFlowPass::new(&provider));
+    manager.add_pass(CommonSubexpressionPass::new(&provider));
+    let analysis = manager.run(&graph).unwrap();
+
+    assert_eq!(analysis.get::<CommonSubexpressions>().unwrap().duplicates(), ["add_2"]);
+    assert_eq!(analysis.get::<DataResolver>().unwrap().get_pure_evaluation_order(), ["add_1"]);
+    assert_eq!(analysis.completed_passes(), ["data_flow", "cse"]);
+}
+
+#[test]
+fn cse_pass_requires_data_flow() {
+    let provider = cse_provider();
+    let mut manager = PassManager::new();
+    manager.add_pass(CommonSubexpressionPass::new(&provider));
+
+    let error = manager.run(&GraphDescription::new("cse")).err().unwrap();
+    assert!(error.to_string().contains("data_flow"), "{}", error);
+}
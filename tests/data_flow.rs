@@ -5,6 +5,8 @@ mod common;
 
 use common::*;
 use graphy::*;
+use graphy::analysis::{BuildStrategy, EvaluationOrder, ParallelThreshold};
+use graphy::utils::RustProfile;
 
 // ===========================================================================
 // DataResolver - Basic Building
@@ -37,8 +39,8 @@ fn data_resolver_single_node_with_constants() {
     // Both inputs should be constants
     let a = resolver.get_input_source("add_1", "a").unwrap();
     let b = resolver.get_input_source("add_1", "b").unwrap();
-    assert!(matches!(a, DataSource::Constant(_)));
-    assert!(matches!(b, DataSource::Constant(_)));
+    assert!(matches!(a, DataSource::Constant(..)));
+    assert!(matches!(b, DataSource::Constant(..)));
 
     // Should have a result variable
     let var = resolver.get_result_variable("add_1");
@@ -115,6 +117,82 @@ fn data_resolver_connection_overrides_property() {
     assert!(matches!(source, DataSource::Connection { .. }));
 }
 
+/// `tick_1` (on_tick) whose `delta_time` feeds `add_1.a`
+fn build_event_param_graph() -> GraphDescription {
+    let mut graph = GraphDescription::new("test");
+
+    let mut tick = NodeInstance::new("tick_1", "on_tick", Position::zero());
+    tick.add_output_pin("exec", DataType::Execution);
+    tick.add_output_pin("delta_time", DataType::Typed("f64".into()));
+    graph.add_node(tick);
+
+    let mut add = NodeInstance::new("add_1", "add", Position::zero());
+    add.add_input_pin("a", DataType::Typed("i64".into()));
+    add.add_input_pin("b", DataType::Typed("i64".into()));
+    graph.add_node(add);
+
+    graph.add_connection(Connection::data("tick_1", "delta_time", "add_1", "a"));
+    graph
+}
+
+#[test]
+fn data_resolver_event_param_source() {
+    let graph = build_event_param_graph();
+    let provider = TestMetadataProvider::comprehensive();
+    let resolver = DataResolver::build(&graph, &provider).unwrap();
+
+    match resolver.get_input_source("add_1", "a").unwrap() {
+        DataSource::EventParam { event_node, param } => {
+            assert_eq!(event_node, "tick_1");
+            assert_eq!(param, "delta_time");
+        }
+        other => panic!("expected EventParam source, got {:?}", other),
+    }
+    assert_eq!(
+        resolver.get_event_param_variable("tick_1", "delta_time").unwrap(),
+        "event_tick_1_delta_time"
+    );
+}
+
+#[test]
+fn data_resolver_event_param_needs_metadata() {
+    // Without metadata the event is an ordinary node
+    let graph = build_event_param_graph();
+    let resolver = DataResolver::build(&graph, &TestMetadataProvider::empty()).unwrap();
+
+    assert!(matches!(resolver.get_input_source("add_1", "a"), Some(DataSource::Connection { .. })));
+    assert!(resolver.get_event_param_variable("tick_1", "delta_time").is_none());
+}
+
+#[test]
+fn data_resolver_parallel_event_param_source() {
+    let graph = build_event_param_graph();
+    let provider = TestMetadataProvider::comprehensive();
+    let resolver = DataResolver::build_parallel(&graph, &provider).unwrap();
+
+    assert!(matches!(resolver.get_input_source("add_1", "a"), Some(DataSource::EventParam { .. })));
+    assert!(resolver.get_event_param_variable("tick_1", "delta_time").is_some());
+}
+
+#[test]
+fn event_param_pin_type_from_metadata() {
+    let mut graph = build_event_param_graph();
+    graph.nodes.get_mut("tick_1").unwrap().outputs.retain(|pin| pin.id != "delta_time");
+    let provider = TestMetadataProvider::comprehensive();
+
+    let connection = Connection::data("tick_1", "delta_time", "add_1", "a");
+    let (source, _) = graph.resolve_connection_types(&connection, &provider).unwrap();
+    assert_eq!(source, DataType::Typed("f64".into()));
+
+    // Event params are outputs only
+    graph.nodes.get_mut("add_1").unwrap().add_output_pin("result", DataType::Typed("f64".into()));
+    let backwards = Connection::data("add_1", "result", "tick_1", "delta_time");
+    assert!(matches!(
+        graph.resolve_connection_types(&backwards, &provider),
+        Err(GraphyError::InvalidConnection { reason: core::ConnectionErrorReason::MissingPin { node, .. }, .. }) if node == "tick_1"
+    ));
+}
+
 // ===========================================================================
 // DataResolver - Variable Names
 // ===========================================================================
@@ -279,6 +357,101 @@ fn data_resolver_three_node_cycle() {
     assert!(result.is_err());
 }
 
+#[test]
+fn data_resolver_cycle_error_lists_nodes() {
+    let provider = TestMetadataProvider::with_math_nodes();
+    let graph = build_cycle_graph(&["cyc_1", "cyc_2", "cyc_3"]);
+
+    match DataResolver::build(&graph, &provider) {
+        Err(GraphyError::CyclicDependency { nodes }) => {
+            assert_eq!(nodes, vec!["cyc_1", "cyc_2", "cyc_3"]);
+        }
+        other => panic!("expected cyclic dependency, got {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn data_resolver_parallel_cycle_error_lists_nodes() {
+    let provider = TestMetadataProvider::with_math_nodes();
+    let graph = build_cycle_graph(&["p", "q"]);
+
+    let err = DataResolver::build_parallel(&graph, &provider).err().unwrap();
+    assert!(err.to_string().contains("p -> q -> p"), "{}", err);
+}
+
+// ===========================================================================
+// find_cycles
+// ===========================================================================
+
+/// Pure add nodes connected result -> a in a ring, in the given order.
+fn build_cycle_graph(ids: &[&str]) -> GraphDescription {
+    let mut graph = GraphDescription::new("cycle");
+    for id in ids {
+        let mut node = NodeInstance::new(*id, "add", Position::zero());
+        node.add_input_pin("a", DataType::Typed("i64".into()));
+        node.add_input_pin("b", DataType::Typed("i64".into()));
+        node.add_output_pin("result", DataType::Typed("i64".into()));
+        graph.add_node(node);
+    }
+    for (i, id) in ids.iter().enumerate() {
+        let next = ids[(i + 1) % ids.len()];
+        graph.add_connection(Connection::data(*id, "result", next, "a"));
+    }
+    graph
+}
+
+#[test]
+fn find_cycles_acyclic_graph_is_empty() {
+    let provider = TestMetadataProvider::with_math_nodes();
+    let graph = build_diamond_graph();
+    assert!(graphy::analysis::find_cycles(&graph, &provider).is_empty());
+}
+
+#[test]
+fn find_cycles_self_loop() {
+    let provider = TestMetadataProvider::with_math_nodes();
+    let graph = build_cycle_graph(&["solo"]);
+    assert_eq!(graphy::analysis::find_cycles(&graph, &provider), vec![vec!["solo".to_string()]]);
+}
+
+#[test]
+fn find_cycles_reports_path_in_data_flow_order() {
+    let provider = TestMetadataProvider::with_math_nodes();
+    let graph = build_cycle_graph(&["c", "a", "b"]);
+
+    // Starts from the smallest ID and follows the data connections
+    let cycles = graphy::analysis::find_cycles(&graph, &provider);
+    assert_eq!(cycles, vec![vec!["a", "b", "c"]]);
+}
+
+#[test]
+fn find_cycles_reports_independent_cycles() {
+    let provider = TestMetadataProvider::with_math_nodes();
+    let mut graph = build_cycle_graph(&["a1", "a2"]);
+    for (id, node) in build_cycle_graph(&["b1", "b2", "b3"]).nodes {
+        graph.nodes.insert(id, node);
+    }
+    graph.connections.extend(build_cycle_graph(&["b1", "b2", "b3"]).connections);
+
+    let cycles = graphy::analysis::find_cycles(&graph, &provider);
+    assert_eq!(cycles.len(), 2);
+    assert_eq!(cycles[0], vec!["a1", "a2"]);
+    assert_eq!(cycles[1], vec!["b1", "b2", "b3"]);
+}
+
+#[test]
+fn find_cycles_ignores_non_pure_nodes() {
+    let provider = TestMetadataProvider::comprehensive();
+    let mut graph = GraphDescription::new("impure");
+    for id in ["f1", "f2"] {
+        graph.add_node(NodeInstance::new(id, "print_string", Position::zero()));
+    }
+    graph.add_connection(Connection::data("f1", "out", "f2", "message"));
+    graph.add_connection(Connection::data("f2", "out", "f1", "message"));
+
+    assert!(graphy::analysis::find_cycles(&graph, &provider).is_empty());
+}
+
 // ===========================================================================
 // DataResolver - Non-pure nodes are excluded from topological sort
 // ===========================================================================
@@ -311,6 +484,127 @@ fn data_resolver_excludes_non_pure_from_order() {
     assert!(!order.contains(&"fn_1".to_string()));
 }
 
+// ===========================================================================
+// DataResolver - Evaluation Order
+// ===========================================================================
+
+/// Two independent chains `a1 -> a2 -> a3` and `b1 -> b2 -> b3` of adds
+fn build_two_chains() -> GraphDescription {
+    let mut graph = GraphDescription::new("chains");
+    for chain in ["a", "b"] {
+        for i in 1..=3 {
+            add_pure_node(&mut graph, &format!("{}{}", chain, i));
+        }
+        graph.add_connection(Connection::data(format!("{}1", chain), "result", format!("{}2", chain), "a"));
+        graph.add_connection(Connection::data(format!("{}2", chain), "result", format!("{}3", chain), "a"));
+    }
+    graph
+}
+
+fn add_pure_node(graph: &mut GraphDescription, id: &str) {
+    let mut node = NodeInstance::new(id, "add", Position::zero());
+    node.add_input_pin("a", DataType::Typed("i64".into()));
+    node.add_input_pin("b", DataType::Typed("i64".into()));
+    node.add_output_pin("result", DataType::Typed("i64".into()));
+    graph.add_node(node);
+}
+
+fn order_of(graph: &GraphDescription, order: &EvaluationOrder) -> Vec<String> {
+    let provider = TestMetadataProvider::with_math_nodes();
+    let resolver = DataResolver::build_with_order(graph, &provider, order).unwrap();
+    resolver.get_pure_evaluation_order().to_vec()
+}
+
+#[test]
+fn evaluation_order_default_is_breadth_first() {
+    let graph = build_two_chains();
+    let provider = TestMetadataProvider::with_math_nodes();
+    let resolver = DataResolver::build(&graph, &provider).unwrap();
+    assert_eq!(resolver.get_pure_evaluation_order(), ["a1", "b1", "a2", "b2", "a3", "b3"]);
+    assert_eq!(order_of(&graph, &EvaluationOrder::BreadthFirst), ["a1", "b1", "a2", "b2", "a3", "b3"]);
+}
+
+#[test]
+fn evaluation_order_by_id_takes_smallest_ready_node() {
+    let graph = build_two_chains();
+    assert_eq!(order_of(&graph, &EvaluationOrder::ById), ["a1", "a2", "a3", "b1", "b2", "b3"]);
+
+    // Dependencies still come first
+    let mut graph = graph;
+    add_pure_node(&mut graph, "a0");
+    graph.add_connection(Connection::data("b3", "result", "a0", "a"));
+    assert_eq!(order_of(&graph, &EvaluationOrder::ById), ["a1", "a2", "a3", "b1", "b2", "b3", "a0"]);
+}
+
+#[test]
+fn evaluation_order_by_id_keeps_unrelated_nodes_in_place() {
+    let old = build_two_chains();
+    let mut new = old.clone();
+    add_pure_node(&mut new, "b0");
+    new.add_connection(Connection::data("b0", "result", "b2", "b"));
+
+    let old_order = order_of(&old, &EvaluationOrder::ById);
+    let new_order = order_of(&new, &EvaluationOrder::ById);
+    assert_eq!(new_order, ["a1", "a2", "a3", "b0", "b1", "b2", "b3"]);
+    let unchanged: Vec<&String> = new_order.iter().filter(|id| *id != "b0").collect();
+    assert_eq!(unchanged, old_order.iter().collect::<Vec<_>>());
+}
+
+#[test]
+fn evaluation_order_previous_keeps_the_last_order() {
+    let old = build_two_chains();
+    let previous = order_of(&old, &EvaluationOrder::BreadthFirst);
+
+    // A node inserted upstream of a1 delays the whole a chain breadth-first
+    let mut new = old.clone();
+    add_pure_node(&mut new, "n");
+    new.add_connection(Connection::data("n", "result", "a1", "b"));
+    assert_eq!(order_of(&new, &EvaluationOrder::BreadthFirst), ["b1", "n", "b2", "a1", "b3", "a2", "a3"]);
+
+    let hinted = order_of(&new, &EvaluationOrder::Previous(previous));
+    assert_eq!(hinted, ["n", "a1", "b1", "a2", "b2", "a3", "b3"]);
+}
+
+#[test]
+fn evaluation_order_previous_places_new_nodes_once_ready() {
+    let old = build_two_chains();
+    let previous = EvaluationOrder::Previous(order_of(&old, &EvaluationOrder::BreadthFirst));
+
+    let mut new = old.clone();
+    new.remove_node("b2");
+    add_pure_node(&mut new, "c");
+    new.add_connection(Connection::data("a2", "result", "c", "a"));
+    assert_eq!(order_of(&new, &previous), ["a1", "b1", "a2", "c", "a3", "b3"]);
+}
+
+#[test]
+fn evaluation_order_previous_still_detects_cycles() {
+    let graph = build_cycle_graph(&["x", "y"]);
+    let provider = TestMetadataProvider::with_math_nodes();
+    let order = EvaluationOrder::Previous(vec!["y".into(), "x".into()]);
+    assert!(matches!(
+        DataResolver::build_with_order(&graph, &provider, &order),
+        Err(GraphyError::CyclicDependency { .. })
+    ));
+}
+
+#[test]
+fn evaluation_order_from_compile_options() {
+    use graphy::generation::{CompileOptions, SessionAnalysis};
+    use std::sync::Arc;
+
+    let graph = build_two_chains();
+    let options = CompileOptions::new().with_evaluation_order(EvaluationOrder::ById);
+    let analysis = SessionAnalysis::analyze(
+        &graph,
+        Arc::new(TestMetadataProvider::with_math_nodes()),
+        Arc::new(graphy::utils::RustProfile),
+        &options,
+    )
+    .unwrap();
+    assert_eq!(analysis.data_resolver().get_pure_evaluation_order(), ["a1", "a2", "a3", "b1", "b2", "b3"]);
+}
+
 // ===========================================================================
 // DataResolver - Parallel Build
 // ===========================================================================
@@ -388,8 +682,103 @@ fn data_resolver_parallel_diamond() {
     assert!(pos("node_a") < pos("node_d"));
 }
 
+#[test]
+fn data_resolver_parallel_order_is_layered_by_id() {
+    let provider = TestMetadataProvider::with_math_nodes();
+    let graph = build_diamond_graph();
+    let resolver = DataResolver::build_parallel(&graph, &provider).unwrap();
+
+    assert_eq!(resolver.get_pure_evaluation_order(), ["node_a", "node_b", "node_c", "node_d"]);
+}
+
+#[test]
+fn data_resolver_parallel_order_is_deterministic_on_grids() {
+    let provider = TestMetadataProvider::with_math_nodes();
+    let size = 30;
+
+    // Each cell depends on its left and upper neighbour
+    let mut graph = GraphDescription::new("grid");
+    let id = |row: usize, col: usize| format!("cell_{}_{}", row, col);
+    for row in 0..size {
+        for col in 0..size {
+            let mut node = NodeInstance::new(id(row, col), "add", Position::zero());
+            node.add_input_pin("a", DataType::Typed("i64".into()));
+            node.add_input_pin("b", DataType::Typed("i64".into()));
+            node.add_output_pin("result", DataType::Typed("i64".into()));
+            graph.add_node(node);
+            if col > 0 {
+                graph.add_connection(Connection::data(id(row, col - 1), "result", id(row, col), "a"));
+            }
+            if row > 0 {
+                graph.add_connection(Connection::data(id(row - 1, col), "result", id(row, col), "b"));
+            }
+        }
+    }
+
+    let first = DataResolver::build_parallel(&graph, &provider).unwrap();
+    let order = first.get_pure_evaluation_order();
+    assert_eq!(order.len(), size * size);
+
+    let position: std::collections::HashMap<&str, usize> =
+        order.iter().enumerate().map(|(i, node)| (node.as_str(), i)).collect();
+    for connection in &graph.connections {
+        assert!(position[connection.source_node.as_str()] < position[connection.target_node.as_str()]);
+    }
+
+    for _ in 0..5 {
+        let again = DataResolver::build_parallel(&graph, &provider).unwrap();
+        assert_eq!(again.get_pure_evaluation_order(), order);
+    }
+}
+
+// ===========================================================================
+// DataResolver - Automatic build strategy
+// ===========================================================================
+
+#[test]
+fn data_resolver_threshold_needs_size_and_threads() {
+    let threshold = ParallelThreshold::new(100);
+    assert_eq!(threshold.min_connections, 200);
+    assert_eq!(threshold.strategy(99, 199, 8), BuildStrategy::Sequential);
+    assert_eq!(threshold.strategy(100, 0, 8), BuildStrategy::Parallel);
+    assert_eq!(threshold.strategy(0, 200, 8), BuildStrategy::Parallel);
+    assert_eq!(threshold.strategy(100_000, 100_000, 1), BuildStrategy::Sequential);
+
+    assert_eq!(ParallelThreshold::default(), ParallelThreshold::new(5_000));
+    assert_eq!(ParallelThreshold::never().strategy(usize::MAX - 1, 0, 8), BuildStrategy::Sequential);
+    let by_connections = ParallelThreshold::new(100).with_min_connections(usize::MAX);
+    assert_eq!(by_connections.strategy(0, 100_000, 8), BuildStrategy::Sequential);
+}
+
+#[test]
+fn data_resolver_build_auto_follows_the_threshold() {
+    let provider = TestMetadataProvider::with_math_nodes();
+    let graph = build_diamond_graph();
+
+    let sequential = DataResolver::build_auto(&graph, &provider, &ParallelThreshold::never()).unwrap();
+    assert_eq!(
+        sequential.get_pure_evaluation_order(),
+        DataResolver::build(&graph, &provider).unwrap().get_pure_evaluation_order()
+    );
+
+    let threshold = ParallelThreshold::new(1);
+    let expected = match threshold.select(&graph) {
+        BuildStrategy::Parallel => DataResolver::build_parallel(&graph, &provider).unwrap(),
+        BuildStrategy::Sequential => DataResolver::build(&graph, &provider).unwrap(),
+    };
+    let auto = DataResolver::build_auto(&graph, &provider, &threshold).unwrap();
+    assert_eq!(auto.get_pure_evaluation_order(), expected.get_pure_evaluation_order());
+}
+
+#[test]
+fn data_resolver_sync_providers_allow_parallel_analysis() {
+    assert!(NodeRegistry::new().as_sync().is_some());
+    assert!(std::sync::Arc::new(NodeRegistry::new()).as_sync().is_some());
+    assert!(TestMetadataProvider::empty().as_sync().is_none());
+}
+
 // ===========================================================================
-// DataResolver - Property value string conversion
+// DataResolver - Typed constants
 // ===========================================================================
 
 #[test]
@@ -406,7 +795,7 @@ fn data_resolver_constant_string_value() {
 
     let source = resolver.get_input_source("n", "message").unwrap();
     match source {
-        DataSource::Constant(s) => assert!(s.contains("hello world")),
+        DataSource::Constant(value, _) => assert_eq!(value, &PropertyValue::String("hello world".into())),
         _ => panic!("expected Constant"),
     }
 }
@@ -425,7 +814,7 @@ fn data_resolver_constant_boolean_value() {
 
     let source = resolver.get_input_source("n", "condition").unwrap();
     match source {
-        DataSource::Constant(s) => assert_eq!(s, "true"),
+        DataSource::Constant(value, _) => assert_eq!(value, &PropertyValue::Boolean(true)),
         _ => panic!("expected Constant"),
     }
 }
@@ -444,12 +833,13 @@ fn data_resolver_constant_integer_number() {
 
     let source = resolver.get_input_source("n", "a").unwrap();
     match source {
-        DataSource::Constant(s) => assert_eq!(s, "42"),
+        DataSource::Constant(value, _) => assert_eq!(value, &PropertyValue::Number(42.0)),
         _ => panic!("expected Constant"),
     }
 }
 
 #[test]
+#[allow(clippy::approx_constant)]
 fn data_resolver_constant_float_number() {
     let mut graph = GraphDescription::new("test");
 
@@ -463,7 +853,7 @@ fn data_resolver_constant_float_number() {
 
     let source = resolver.get_input_source("n", "a").unwrap();
     match source {
-        DataSource::Constant(s) => assert!(s.contains("3.14")),
+        DataSource::Constant(value, _) => assert_eq!(value, &PropertyValue::Number(3.14)),
         _ => panic!("expected Constant"),
     }
 }
@@ -482,10 +872,436 @@ fn data_resolver_constant_vector2_value() {
 
     let source = resolver.get_input_source("n", "v").unwrap();
     match source {
-        DataSource::Constant(s) => {
-            assert!(s.contains("1"));
-            assert!(s.contains("2"));
-        }
+        DataSource::Constant(value, _) => assert_eq!(value, &PropertyValue::Vector2(1.0, 2.0)),
+        _ => panic!("expected Constant"),
+    }
+}
+
+/// The Rust literal generated for a constant input
+fn constant_source_for(value: PropertyValue) -> String {
+    let mut graph = GraphDescription::new("test");
+
+    let mut node = NodeInstance::new("n", "any", Position::zero());
+    node.add_input_pin("v", DataType::Any);
+    node.set_property("v", value);
+    graph.add_node(node);
+
+    let provider = TestMetadataProvider::empty();
+    let resolver = DataResolver::build(&graph, &provider).unwrap();
+
+    resolver.get_input_source("n", "v").unwrap().constant_literal(&RustProfile).unwrap().expect("expected Constant")
+}
+
+#[test]
+fn data_resolver_constant_integer_value_is_exact() {
+    assert_eq!(constant_source_for(PropertyValue::Integer(i64::MIN)), i64::MIN.to_string());
+}
+
+#[test]
+fn data_resolver_constant_array_value() {
+    let value = PropertyValue::Array(vec![PropertyValue::Integer(1), PropertyValue::Integer(2)]);
+    assert_eq!(constant_source_for(value), "vec![1, 2]");
+}
+
+#[test]
+fn data_resolver_constant_map_value_sorted() {
+    let mut map = std::collections::HashMap::new();
+    map.insert("b".to_string(), PropertyValue::Integer(2));
+    map.insert("a".to_string(), PropertyValue::Boolean(true));
+
+    assert_eq!(
+        constant_source_for(PropertyValue::Map(map)),
+        r#"std::collections::HashMap::from([(String::from("a"), true), (String::from("b"), 2)])"#
+    );
+}
+
+#[test]
+fn data_resolver_constant_enum_value() {
+    let unit = PropertyValue::Enum {
+        variant: "Direction::Left".into(),
+        payload: None,
+    };
+    assert_eq!(constant_source_for(unit), "Direction::Left");
+
+    let tuple = PropertyValue::Enum {
+        variant: "Some".into(),
+        payload: Some(Box::new(PropertyValue::String("x".into()))),
+    };
+    assert_eq!(constant_source_for(tuple), r#"Some("x")"#);
+}
+
+/// The type recorded for the constant feeding `n.v`, a pin of the given type
+fn constant_type_for(data_type: DataType) -> Option<String> {
+    let mut graph = GraphDescription::new("test");
+    let mut node = NodeInstance::new("n", "any", Position::zero());
+    node.add_input_pin("v", data_type);
+    node.set_property("v", PropertyValue::Number(2.0));
+    graph.add_node(node);
+
+    let resolver = DataResolver::build(&graph, &TestMetadataProvider::empty()).unwrap();
+    match resolver.get_input_source("n", "v").unwrap() {
+        DataSource::Constant(_, value_type) => value_type.as_ref().map(|value_type| value_type.type_string.clone()),
         _ => panic!("expected Constant"),
     }
 }
+
+#[test]
+fn data_resolver_constant_takes_the_pin_type_without_metadata() {
+    assert_eq!(constant_type_for(DataType::Typed("i64".into())).as_deref(), Some("i64"));
+    assert_eq!(constant_type_for(DataType::Number).as_deref(), Some("f64"));
+    assert_eq!(constant_type_for(DataType::Any), None);
+}
+
+#[test]
+fn data_resolver_constant_literal_follows_the_type() {
+    let literal = |value, value_type: &str| {
+        DataSource::Constant(value, Some(TypeInfo::new(value_type)))
+            .constant_literal(&RustProfile)
+            .unwrap()
+            .unwrap()
+    };
+    assert_eq!(literal(PropertyValue::Number(42.0), "i64"), "42");
+    assert_eq!(literal(PropertyValue::Number(42.0), "f32"), "42.0");
+    assert_eq!(literal(PropertyValue::Integer(3), "f64"), "3.0");
+    assert_eq!(literal(PropertyValue::Vector2(1.0, 2.0), "(f32, f32)"), "(1.0, 2.0)");
+
+    let untyped = DataSource::Constant(PropertyValue::Number(42.0), None);
+    assert_eq!(untyped.constant_literal(&RustProfile).unwrap().as_deref(), Some("42"));
+    assert_eq!(DataSource::Default.constant_literal(&RustProfile).unwrap(), None);
+
+    let fractional = DataSource::Constant(PropertyValue::Number(2.5), Some(TypeInfo::new("i64")));
+    assert!(matches!(fractional.constant_literal(&RustProfile), Err(GraphyError::CodeGeneration(_))));
+}
+
+// ===========================================================================
+// DataResolver - Metadata defaults and required inputs
+// ===========================================================================
+
+/// Provider with a `scale` function whose params have defaults or are required
+fn defaults_provider() -> TestMetadataProvider {
+    let mut provider = TestMetadataProvider::comprehensive();
+    provider.add(
+        NodeMetadata::new("scale", NodeTypes::fn_, "math")
+            .with_params(vec![
+                ParamInfo::new("value", "f64").with_required(true),
+                ParamInfo::new("factor", "f64").with_default(PropertyValue::Number(2.5)),
+                ParamInfo::new("offset", "f64"),
+            ])
+            .with_exec_outputs(vec!["then".to_string()]),
+    );
+    provider
+}
+
+fn scale_node(declare_pins: bool) -> NodeInstance {
+    let mut node = NodeInstance::new("scale_1", "scale", Position::zero());
+    if declare_pins {
+        for pin in ["value", "factor", "offset"] {
+            node.add_input_pin(pin, DataType::Typed("f64".into()));
+        }
+    }
+    node
+}
+
+#[test]
+fn data_resolver_uses_metadata_default_for_unconnected_pin() {
+    let mut graph = GraphDescription::new("test");
+    graph.add_node(scale_node(true));
+
+    let resolver = DataResolver::build(&graph, &defaults_provider()).unwrap();
+
+    assert!(matches!(resolver.get_input_source("scale_1", "factor"), Some(DataSource::Constant(PropertyValue::Number(n), _)) if *n == 2.5));
+    assert!(matches!(resolver.get_input_source("scale_1", "offset"), Some(DataSource::Default)));
+}
+
+#[test]
+fn data_resolver_uses_metadata_default_for_undeclared_pin() {
+    let mut graph = GraphDescription::new("test");
+    graph.add_node(scale_node(false));
+
+    let resolver = DataResolver::build(&graph, &defaults_provider()).unwrap();
+
+    assert!(matches!(
+        resolver.get_input_source("scale_1", "factor"),
+        Some(DataSource::Constant(PropertyValue::Number(n), Some(value_type))) if *n == 2.5 && value_type.type_string == "f64"
+    ));
+    assert!(resolver.get_input_source("scale_1", "offset").is_none());
+}
+
+#[test]
+fn data_resolver_prefers_property_over_metadata_default() {
+    let mut graph = GraphDescription::new("test");
+    let mut node = scale_node(true);
+    node.set_property("factor", PropertyValue::Number(4.25));
+    graph.add_node(node);
+
+    let resolver = DataResolver::build(&graph, &defaults_provider()).unwrap();
+
+    assert!(matches!(resolver.get_input_source("scale_1", "factor"), Some(DataSource::Constant(PropertyValue::Number(n), _)) if *n == 4.25));
+}
+
+#[test]
+fn data_resolver_parallel_uses_metadata_defaults() {
+    let mut graph = GraphDescription::new("test");
+    graph.add_node(scale_node(true));
+    let mut undeclared = scale_node(false);
+    undeclared.id = "scale_2".into();
+    graph.add_node(undeclared);
+
+    let resolver = DataResolver::build_parallel(&graph, &defaults_provider()).unwrap();
+
+    for node in ["scale_1", "scale_2"] {
+        assert!(matches!(resolver.get_input_source(node, "factor"), Some(DataSource::Constant(PropertyValue::Number(n), _)) if *n == 2.5));
+    }
+}
+
+#[test]
+fn data_resolver_ignores_defaults_of_event_params() {
+    let mut provider = TestMetadataProvider::comprehensive();
+    provider.add(
+        NodeMetadata::new("on_hit", NodeTypes::event, "events")
+            .with_params(vec![ParamInfo::new("damage", "f64").with_default(PropertyValue::Number(1.0))])
+            .with_exec_outputs(vec!["exec".to_string()]),
+    );
+    let mut graph = GraphDescription::new("test");
+    graph.add_node(NodeInstance::new("hit", "on_hit", Position::zero()));
+
+    let resolver = DataResolver::build(&graph, &provider).unwrap();
+
+    assert!(resolver.get_input_source("hit", "damage").is_none());
+}
+
+#[test]
+fn required_inputs_must_be_connected() {
+    let provider = defaults_provider();
+    let mut graph = GraphDescription::new("test");
+    let mut node = scale_node(true);
+    // A property doesn't satisfy a required input
+    node.set_property("value", PropertyValue::Number(1.0));
+    graph.add_node(node);
+
+    let resolver = DataResolver::build(&graph, &provider).unwrap();
+
+    assert_eq!(
+        resolver.unconnected_required_inputs(&graph, &provider),
+        vec![("scale_1".to_string(), "value".to_string())]
+    );
+    let error = resolver.validate_required_inputs(&graph, &provider).unwrap_err();
+    assert!(matches!(error, GraphyError::UnconnectedInput { ref node, ref pin } if node == "scale_1" && pin == "value"));
+    assert_eq!(error.to_string(), "Required input scale_1.value is not connected");
+}
+
+#[test]
+fn connected_required_inputs_pass_validation() {
+    let provider = defaults_provider();
+    let mut graph = GraphDescription::new("test");
+    graph.add_node(scale_node(true));
+    let mut source = NodeInstance::new("add_1", "add", Position::zero());
+    source.add_output_pin("result", DataType::Typed("i64".into()));
+    graph.add_node(source);
+    graph.add_connection(Connection::data("add_1", "result", "scale_1", "value"));
+
+    let resolver = DataResolver::build(&graph, &provider).unwrap();
+
+    assert!(resolver.unconnected_required_inputs(&graph, &provider).is_empty());
+    assert!(resolver.validate_required_inputs(&graph, &provider).is_ok());
+}
+
+// ===========================================================================
+// DataResolver - Dependency queries
+// ===========================================================================
+
+#[test]
+fn data_resolver_direct_dependencies() {
+    let graph = build_diamond_graph();
+    let provider = TestMetadataProvider::with_math_nodes();
+    let resolver = DataResolver::build(&graph, &provider).unwrap();
+
+    assert_eq!(resolver.get_dependencies("node_d"), &["node_b", "node_c"]);
+    assert_eq!(resolver.get_dependents("node_a"), &["node_b", "node_c"]);
+    assert!(resolver.get_dependencies("node_a").is_empty());
+    assert!(resolver.get_dependents("node_d").is_empty());
+    assert!(resolver.get_dependencies("missing").is_empty());
+}
+
+#[test]
+fn data_resolver_dependencies_are_deduplicated() {
+    let mut graph = build_diamond_graph();
+    graph.add_connection(Connection::data("node_a", "result", "node_b", "b"));
+    let provider = TestMetadataProvider::with_math_nodes();
+    let resolver = DataResolver::build(&graph, &provider).unwrap();
+
+    assert_eq!(resolver.get_dependencies("node_b"), &["node_a"]);
+    assert_eq!(resolver.get_dependents("node_a"), &["node_b", "node_c"]);
+}
+
+#[test]
+fn data_resolver_transitive_dependencies() {
+    let graph = build_diamond_graph();
+    let provider = TestMetadataProvider::with_math_nodes();
+    let resolver = DataResolver::build(&graph, &provider).unwrap();
+
+    assert_eq!(resolver.get_transitive_dependencies("node_d"), vec!["node_b", "node_c", "node_a"]);
+    assert_eq!(resolver.get_transitive_dependents("node_a"), vec!["node_b", "node_c", "node_d"]);
+    assert_eq!(resolver.get_transitive_dependents("node_b"), vec!["node_d"]);
+    assert!(resolver.get_transitive_dependencies("node_a").is_empty());
+}
+
+#[test]
+fn data_resolver_transitive_queries_on_long_chain() {
+    let provider = TestMetadataProvider::with_math_nodes();
+    let graph = build_linear_chain(50, &provider);
+    let resolver = DataResolver::build(&graph, &provider).unwrap();
+
+    let upstream = resolver.get_transitive_dependencies("node_49");
+    assert_eq!(upstream.len(), 49);
+    assert_eq!(upstream[0], "node_48");
+    assert_eq!(upstream[48], "node_0");
+    assert_eq!(resolver.get_transitive_dependents("node_0").len(), 49);
+}
+
+#[test]
+fn data_resolver_parallel_dependencies_match_sequential() {
+    let graph = build_diamond_graph();
+    let provider = TestMetadataProvider::with_math_nodes();
+    let sequential = DataResolver::build(&graph, &provider).unwrap();
+    let parallel = DataResolver::build_parallel(&graph, &provider).unwrap();
+
+    for id in ["node_a", "node_b", "node_c", "node_d"] {
+        assert_eq!(sequential.get_dependencies(id), parallel.get_dependencies(id));
+        assert_eq!(sequential.get_dependents(id), parallel.get_dependents(id));
+    }
+}
+
+// ===========================================================================
+// Symbol interning
+// ===========================================================================
+
+#[test]
+fn symbol_table_from_graph_interns_nodes_and_pins() {
+    let graph = build_diamond_graph();
+    let symbols = analysis::SymbolTable::from_graph(&graph);
+
+    // 4 nodes plus the a, b and result pins
+    assert_eq!(symbols.len(), 7);
+    for name in ["node_a", "node_d", "a", "b", "result"] {
+        let id = symbols.get(name).unwrap();
+        assert_eq!(symbols.resolve(id), name);
+    }
+    assert!(symbols.get("missing").is_none());
+    assert!(symbols.get_pair("node_a", "missing").is_none());
+}
+
+#[test]
+fn symbol_table_ids_are_dense_and_stable() {
+    let mut symbols = analysis::SymbolTable::new();
+    assert!(symbols.is_empty());
+
+    let first = symbols.intern("first");
+    let second = symbols.intern("second");
+    assert_eq!((first.index(), second.index()), (0, 1));
+    assert_eq!(symbols.intern("first"), first);
+    assert_eq!(symbols.intern_pair("second", "first"), (second, first));
+    assert_eq!(symbols.len(), 2);
+}
+
+#[test]
+fn data_resolver_lookups_by_symbols_match_str_lookups() {
+    let graph = build_diamond_graph();
+    let provider = TestMetadataProvider::with_math_nodes();
+
+    for resolver in [
+        DataResolver::build(&graph, &provider).unwrap(),
+        DataResolver::build_parallel(&graph, &provider).unwrap(),
+    ] {
+        let key = resolver.symbols().get_pair("node_d", "b").unwrap();
+        match resolver.get_input_source_by_symbols(key) {
+            Some(DataSource::Connection { source_node_id, .. }) => assert_eq!(source_node_id, "node_c"),
+            other => panic!("expected a connection, got {:?}", other),
+        }
+        assert!(matches!(resolver.get_input_source("node_a", "a"), Some(DataSource::Constant(..))));
+        assert!(resolver.get_input_source("node_a", "unknown_pin").is_none());
+        assert!(resolver.get_input_source("unknown_node", "a").is_none());
+    }
+}
+
+// ===========================================================================
+// Compact graph
+// ===========================================================================
+
+#[test]
+fn compact_graph_indexes_nodes_in_id_order() {
+    let graph = build_diamond_graph();
+    let compact = analysis::CompactGraph::from_graph(&graph);
+
+    assert_eq!(compact.node_count(), 4);
+    assert_eq!(compact.edge_count(ConnectionType::Data), 4);
+    assert_eq!(compact.edge_count(ConnectionType::Execution), 0);
+
+    let ids: Vec<&str> = compact.indices().map(|i| compact.node_id(i)).collect();
+    assert_eq!(ids, ["node_a", "node_b", "node_c", "node_d"]);
+    for index in compact.indices() {
+        assert_eq!(compact.index_of(compact.node_id(index)), Some(index));
+        assert_eq!(compact.node(index).unwrap().id, compact.node_id(index));
+    }
+    assert!(compact.index_of("missing").is_none());
+}
+
+#[test]
+fn compact_graph_neighbours_follow_connection_order() {
+    let graph = build_diamond_graph();
+    let compact = analysis::CompactGraph::from_graph(&graph);
+    let [a, b, c, d] = ["node_a", "node_b", "node_c", "node_d"].map(|id| compact.index_of(id).unwrap());
+
+    assert_eq!(compact.successors(ConnectionType::Data, a), [b, c]);
+    assert_eq!(compact.predecessors(ConnectionType::Data, d), [b, c]);
+    assert!(compact.predecessors(ConnectionType::Data, a).is_empty());
+    assert!(compact.successors(ConnectionType::Execution, a).is_empty());
+
+    let pins: Vec<&str> = compact
+        .incoming(ConnectionType::Data, d)
+        .map(|connection| connection.target_pin.as_str())
+        .collect();
+    assert_eq!(pins, ["a", "b"]);
+    assert!(compact.outgoing(ConnectionType::Data, a).all(|connection| connection.source_node == "node_a"));
+}
+
+#[test]
+fn compact_graph_indexes_dangling_connection_endpoints() {
+    let mut graph = build_diamond_graph();
+    graph.add_connection(Connection::data("ghost", "result", "node_a", "a"));
+    let compact = analysis::CompactGraph::from_graph(&graph);
+
+    let ghost = compact.index_of("ghost").unwrap();
+    assert_eq!(compact.node_count(), 5);
+    assert!(compact.node(ghost).is_none());
+    assert_eq!(compact.successors(ConnectionType::Data, ghost), [compact.index_of("node_a").unwrap()]);
+}
+
+#[test]
+fn data_resolver_build_from_compact_matches_build() {
+    let graph = build_diamond_graph();
+    let provider = TestMetadataProvider::with_math_nodes();
+
+    let resolver = DataResolver::build(&graph, &provider).unwrap();
+    let compact = analysis::CompactGraph::from_graph(&graph);
+    let from_compact = DataResolver::build_from_compact(&compact, &provider, &graphy::utils::RustProfile).unwrap();
+
+    assert_eq!(resolver.get_pure_evaluation_order(), from_compact.get_pure_evaluation_order());
+    for id in ["node_a", "node_b", "node_c", "node_d"] {
+        assert_eq!(resolver.get_dependencies(id), from_compact.get_dependencies(id));
+        assert_eq!(resolver.get_result_variable(id), from_compact.get_result_variable(id));
+    }
+}
+
+#[test]
+fn find_cycles_compact_reports_data_cycles() {
+    let mut graph = build_diamond_graph();
+    graph.add_connection(Connection::data("node_d", "result", "node_a", "a"));
+    let provider = TestMetadataProvider::with_math_nodes();
+
+    let compact = analysis::CompactGraph::from_graph(&graph);
+    let cycles = analysis::find_cycles_compact(&compact, &provider);
+    assert_eq!(cycles, analysis::find_cycles(&graph, &provider));
+    assert_eq!(cycles.len(), 1);
+    assert_eq!(cycles[0].len(), 3);
+}
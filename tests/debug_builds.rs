@@ -0,0 +1,207 @@
+//! Tests for breakpoint and trace flags and the debug hooks generated for them.
+
+mod common;
+
+use common::*;
+use graphy::core::VariableDecl;
+use graphy::generation::{compile, compile_with_options, CompilationSession, CompileOptions, DebugHooks};
+use graphy::utils::diff::GraphDiff;
+use graphy::*;
+use std::sync::Arc;
+
+/// `start -> print_1(negate(7)) -> set_1(score = add(2, 3))`
+fn build_graph() -> GraphDescription {
+    let mut graph = GraphDescription::new("debug");
+    graph.add_variable("score", VariableDecl::new("i64"));
+
+    event_node(&mut graph, "start", "on_start");
+    exec_node(&mut graph, "print_1", "print_string", &["then"]);
+    let set = graph.create_set_variable_node("set_1", "score", Position::zero()).unwrap();
+    graph.add_node(set);
+
+    graph.add_node(NodeInstance::new("negate_1", "negate", Position::zero()));
+    set_input(&mut graph, "negate_1", "value", PropertyValue::Integer(7));
+    graph.add_node(NodeInstance::new("add_1", "add", Position::zero()));
+    set_input(&mut graph, "add_1", "a", PropertyValue::Integer(2));
+    set_input(&mut graph, "add_1", "b", PropertyValue::Integer(3));
+
+    graph.add_connection(Connection::execution("start", "exec", "print_1", "exec_in"));
+    graph.add_connection(Connection::execution("print_1", "then", "set_1", "exec_in"));
+    graph.add_connection(Connection::data("negate_1", "result", "print_1", "message"));
+    graph.add_connection(Connection::data("add_1", "result", "set_1", "value"));
+    graph
+}
+
+fn flag(graph: &mut GraphDescription, id: &str, breakpoint: bool, trace: bool) {
+    let node = graph.get_node_mut(id).unwrap();
+    node.breakpoint = breakpoint;
+    node.trace = trace;
+}
+
+fn debug_options() -> CompileOptions {
+    CompileOptions::new().with_debug(DebugHooks::new())
+}
+
+fn compile_debug(graph: &GraphDescription, options: &CompileOptions) -> String {
+    compile_with_options(graph, &TestMetadataProvider::comprehensive(), &mut CallGenerator::new(), options)
+        .unwrap()
+        .code
+}
+
+// ============================================================================
+// Hooks
+// ============================================================================
+
+#[test]
+fn debug_flags_are_ignored_without_the_option() {
+    let mut graph = build_graph();
+    let plain = compile(&graph, &TestMetadataProvider::comprehensive(), &mut CallGenerator::new()).unwrap();
+    flag(&mut graph, "print_1", true, true);
+    flag(&mut graph, "set_1", true, true);
+
+    assert_eq!(compile_debug(&graph, &CompileOptions::new()), plain);
+    assert!(!plain.contains("__graphy_debug"), "{}", plain);
+}
+
+#[test]
+fn debug_builds_without_flags_are_unchanged() {
+    let graph = build_graph();
+    assert_eq!(compile_debug(&graph, &debug_options()), compile_debug(&graph, &CompileOptions::new()));
+}
+
+#[test]
+fn debug_breakpoints_hit_before_the_node() {
+    let mut graph = build_graph();
+    flag(&mut graph, "print_1", true, false);
+    let code = compile_debug(&graph, &debug_options());
+
+    assert!(
+        code.ends_with("__graphy_debug_hit(\"print_1\");\nprint_string(negate(7));\nstate.score = add(2, 3);\n"),
+        "{}",
+        code
+    );
+}
+
+#[test]
+fn debug_traces_bind_and_report_inputs() {
+    let mut graph = build_graph();
+    flag(&mut graph, "print_1", true, true);
+    let code = compile_debug(&graph, &debug_options());
+
+    assert!(
+        code.ends_with(
+            "let trace_print_1_message = negate(7);\n\
+             __graphy_debug_trace(\"print_1\", \"message\", &trace_print_1_message);\n\
+             __graphy_debug_hit(\"print_1\");\n\
+             print_string(trace_print_1_message);\n\
+             state.score = add(2, 3);\n"
+        ),
+        "{}",
+        code
+    );
+}
+
+#[test]
+fn debug_traces_report_variable_writes() {
+    let mut graph = build_graph();
+    flag(&mut graph, "set_1", false, true);
+    let code = compile_debug(&graph, &debug_options());
+
+    assert!(
+        code.ends_with(
+            "let trace_set_1_value = add(2, 3);\n\
+             __graphy_debug_trace(\"set_1\", \"value\", &trace_set_1_value);\n\
+             state.score = trace_set_1_value;\n"
+        ),
+        "{}",
+        code
+    );
+    assert!(!code.contains("__graphy_debug_hit"), "{}", code);
+}
+
+#[test]
+fn debug_hooks_can_be_renamed() {
+    let mut graph = build_graph();
+    flag(&mut graph, "print_1", true, true);
+    let hooks = DebugHooks::new().with_hit_function("host::pause").with_trace_function("host::watch");
+    let code = compile_debug(&graph, &CompileOptions::new().with_debug(hooks));
+
+    assert!(code.contains("host::watch(\"print_1\", \"message\", &trace_print_1_message);\n"), "{}", code);
+    assert!(code.contains("host::pause(\"print_1\");\n"), "{}", code);
+}
+
+#[test]
+fn debug_hooks_escape_node_ids() {
+    let node = NodeInstance::new("say \"hi\"\\", "print_string", Position::zero());
+    assert_eq!(DebugHooks::new().hit_call(&node), r#"__graphy_debug_hit("say \"hi\"\\")"#);
+}
+
+#[test]
+fn debug_traces_map_to_their_node_and_pin() {
+    let mut graph = build_graph();
+    flag(&mut graph, "print_1", false, true);
+    let provider = TestMetadataProvider::comprehensive();
+    let output = compile_with_options(&graph, &provider, &mut CallGenerator::new(), &debug_options()).unwrap();
+
+    let line = output.code.lines().position(|line| line.starts_with("let trace_print_1_message")).unwrap() + 1;
+    let location = output.source_map.resolve(line, 1).unwrap();
+    assert_eq!(location.node_id, "print_1");
+    assert_eq!(location.pin.as_deref(), Some("message"));
+}
+
+#[test]
+fn debug_builds_match_in_sessions() {
+    let mut graph = build_graph();
+    flag(&mut graph, "print_1", true, true);
+    let options = debug_options();
+    let provider = Arc::new(TestMetadataProvider::comprehensive());
+
+    let session = CompilationSession::new(&graph, provider, &CallGenerator::new(), &options).unwrap();
+    assert_eq!(session.finish(&mut CallGenerator::new()).unwrap().code, compile_debug(&graph, &options));
+}
+
+// ============================================================================
+// Flags
+// ============================================================================
+
+#[test]
+fn debug_flags_serialize_only_when_set() {
+    let mut node = NodeInstance::new("print_1", "print_string", Position::zero());
+    let document = serde_json::to_value(&node).unwrap();
+    assert!(document.get("breakpoint").is_none());
+    assert!(document.get("trace").is_none());
+
+    node.trace = true;
+    let document = serde_json::to_value(&node).unwrap();
+    assert_eq!(document["trace"], serde_json::json!(true));
+    assert!(document.get("breakpoint").is_none());
+    assert_eq!(serde_json::from_value::<NodeInstance>(document).unwrap(), node);
+}
+
+#[test]
+fn debug_flags_change_the_content_hash() {
+    let graph = build_graph();
+    let mut flagged = graph.clone();
+    flag(&mut flagged, "print_1", true, false);
+    assert_ne!(graph.content_hash(), flagged.content_hash());
+
+    flag(&mut flagged, "print_1", false, false);
+    assert_eq!(graph.content_hash(), flagged.content_hash());
+}
+
+#[test]
+fn debug_flags_round_trip_through_diffs() {
+    let graph = build_graph();
+    let mut flagged = graph.clone();
+    flag(&mut flagged, "print_1", true, false);
+    flag(&mut flagged, "set_1", false, true);
+
+    let diff = GraphDiff::compute(&graph, &flagged);
+    assert!(!diff.is_empty());
+    let mut applied = graph.clone();
+    diff.apply(&mut applied).unwrap();
+    assert_eq!(applied, flagged);
+
+    diff.invert().apply(&mut applied).unwrap();
+    assert_eq!(applied, graph);
+}
@@ -0,0 +1,126 @@
+//! Tests for node context on errors and the diagnostics renderer.
+
+mod common;
+
+use common::*;
+use graphy::generation::{compile_with_options, CompileOptions};
+use graphy::utils::Diagnostics;
+use graphy::*;
+
+/// Generator following execution flow and failing on `print_true`
+fn flow_generator() -> HookGenerator {
+    HookGenerator::new().with_statement(|node, _| match node.id.as_str() {
+        "print_true" => Err(GraphyError::CodeGeneration("print is not supported".into())),
+        _ => Ok(node.id.clone()),
+    })
+}
+
+fn compile_error(graph: &GraphDescription) -> GraphyError {
+    compile_with_options(graph, &TestMetadataProvider::comprehensive(), &mut flow_generator(), &CompileOptions::new())
+        .unwrap_err()
+}
+
+// ============================================================================
+// Error context
+// ============================================================================
+
+#[test]
+fn error_context_wraps_once() {
+    let error = GraphyError::CodeGeneration("no template".into()).with_pin("add_1", "a");
+    assert_eq!(error.to_string(), "At pin add_1.a: Code generation error: no template");
+    assert_eq!((error.node(), error.pin()), (Some("add_1"), Some("a")));
+
+    // The innermost context wins
+    let error = error.with_node("branch_1");
+    assert_eq!(error.node(), Some("add_1"));
+    assert!(matches!(error.root(), GraphyError::CodeGeneration(message) if message == "no template"));
+    assert!(matches!(GraphyError::Cancelled.with_node("add_1"), GraphyError::Cancelled));
+}
+
+#[test]
+fn error_context_falls_back_to_named_nodes() {
+    let error = GraphyError::UnconnectedInput {
+        node: "add_1".into(),
+        pin: "b".into(),
+    };
+    assert_eq!((error.node(), error.pin()), (Some("add_1"), Some("b")));
+    assert_eq!(error.with_node("other").node(), Some("add_1"));
+
+    let error = GraphyError::InGraph {
+        graph: "main".into(),
+        source: Box::new(GraphyError::Custom("boom".into()).with_node("add_1")),
+    };
+    assert_eq!(error.node(), Some("add_1"));
+    assert!(error.pin().is_none());
+    assert!(GraphyError::Custom("boom".into()).node().is_none());
+}
+
+#[test]
+fn compile_errors_name_the_failing_node() {
+    let error = compile_error(&build_branch_graph());
+    assert_eq!(error.node(), Some("print_true"));
+    assert!(matches!(error.root(), GraphyError::CodeGeneration(_)));
+    assert_eq!(error.to_string(), "At node print_true: Code generation error: print is not supported");
+}
+
+#[test]
+fn compile_errors_name_pure_nodes() {
+    let mut graph = build_branch_graph();
+    graph.connections.retain(|connection| connection.target_node != "print_true");
+    let mut mystery = NodeInstance::new("mystery", "does_not_exist", Position::zero());
+    mystery.add_output_pin("result", DataType::Typed("String".into()));
+    graph.add_node(mystery);
+    graph.add_connection(Connection::data("mystery", "result", "print_false", "message"));
+
+    let options = CompileOptions::new().with_unknown_node_policy(graphy::analysis::UnknownNodePolicy::Skip);
+    let provider = TestMetadataProvider::comprehensive();
+    let error = compile_with_options(&graph, &provider, &mut flow_generator(), &options).unwrap_err();
+    assert_eq!(error.node(), Some("mystery"), "{}", error);
+}
+
+// ============================================================================
+// Rendering
+// ============================================================================
+
+#[test]
+fn diagnostics_render_node_and_connections() {
+    let graph = build_branch_graph();
+    let error = compile_error(&graph);
+    assert_eq!(
+        Diagnostics::new(&graph).render(&error),
+        "error: Code generation error: print is not supported\n  \
+         --> print_true (print_string) at (400, -100)\n  \
+         | in:  branch_1.True -> exec_in\n"
+    );
+}
+
+#[test]
+fn diagnostics_render_pins_and_limit_connections() {
+    let graph = build_branch_graph();
+    let error = GraphyError::Custom("bad".into()).with_pin("branch_1", "condition");
+    assert_eq!(
+        Diagnostics::new(&graph).with_max_connections(2).render(&error),
+        "error: bad\n  \
+         --> branch_1.condition (branch) at (200, 0)\n  \
+         | in:  start.exec -> exec_in\n  \
+         | out: True -> print_true.exec_in\n  \
+         | ... and 1 more\n"
+    );
+}
+
+#[test]
+fn diagnostics_render_missing_nodes_and_graphs() {
+    let graph = build_branch_graph();
+    let error = GraphyError::InGraph {
+        graph: "main".into(),
+        source: Box::new(GraphyError::NodeNotFound("ghost".into())),
+    };
+    assert_eq!(
+        Diagnostics::new(&graph).render(&error),
+        "error: Node not found: ghost\n  --> ghost (not in the graph)\n  = in graph 'main'\n"
+    );
+    assert_eq!(
+        Diagnostics::new(&graph).render(&GraphyError::Cancelled),
+        "error: Operation was cancelled\n"
+    );
+}
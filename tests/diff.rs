@@ -0,0 +1,298 @@
+//! Tests for GraphDiff: computing, applying, inverting, and serializing diffs.
+
+mod common;
+
+use common::*;
+use graphy::core::{GraphComment, GraphGroup};
+use graphy::utils::diff::{GraphDiff, PropertyChange};
+use graphy::*;
+
+fn comment(text: &str) -> GraphComment {
+    GraphComment {
+        text: text.to_string(),
+        position: Position::zero(),
+        size: (100.0, 50.0),
+        attached_node_ids: Vec::new(),
+    }
+}
+
+// ===========================================================================
+// GraphDiff - Compute
+// ===========================================================================
+
+#[test]
+fn diff_identical_graphs_is_empty() {
+    let graph = build_diamond_graph();
+    let diff = GraphDiff::compute(&graph, &graph.clone());
+    assert!(diff.is_empty());
+}
+
+#[test]
+fn diff_added_and_removed_nodes() {
+    let old = build_diamond_graph();
+    let mut new = old.clone();
+    new.nodes.remove("node_d");
+    new.add_node(NodeInstance::new("node_e", "negate", Position::zero()));
+
+    let diff = GraphDiff::compute(&old, &new);
+    assert_eq!(diff.added_nodes.len(), 1);
+    assert_eq!(diff.added_nodes[0].id, "node_e");
+    assert_eq!(diff.removed_nodes.len(), 1);
+    assert_eq!(diff.removed_nodes[0].id, "node_d");
+    assert!(diff.modified_nodes.is_empty());
+}
+
+#[test]
+fn diff_modified_node_properties_and_position() {
+    let old = build_diamond_graph();
+    let mut new = old.clone();
+    {
+        let node = new.get_node_mut("node_a").unwrap();
+        node.position = Position::new(10.0, 20.0);
+        node.set_property("a", PropertyValue::Number(7.0));
+        node.properties.remove("b");
+        node.set_property("c", PropertyValue::Boolean(true));
+    }
+
+    let diff = GraphDiff::compute(&old, &new);
+    assert_eq!(diff.modified_nodes.len(), 1);
+
+    let change = &diff.modified_nodes[0];
+    assert_eq!(change.node_id, "node_a");
+    assert!(change.position.is_some());
+    assert!(change.node_type.is_none());
+    assert_eq!(change.properties.len(), 3);
+    assert!(matches!(&change.properties[0], PropertyChange::Modified { key, .. } if key == "a"));
+    assert!(matches!(&change.properties[1], PropertyChange::Removed { key, .. } if key == "b"));
+    assert!(matches!(&change.properties[2], PropertyChange::Added { key, .. } if key == "c"));
+}
+
+#[test]
+fn diff_connections_and_comments() {
+    let old = build_diamond_graph();
+    let mut new = old.clone();
+    new.connections.retain(|c| c.target_node != "node_d");
+    new.add_connection(Connection::data("node_b", "result", "node_c", "b"));
+    new.comments.push(comment("note"));
+
+    let diff = GraphDiff::compute(&old, &new);
+    assert_eq!(diff.removed_connections.len(), 2);
+    assert_eq!(diff.added_connections, vec![Connection::data("node_b", "result", "node_c", "b")]);
+    assert_eq!(diff.added_comments.len(), 1);
+    assert!(diff.removed_comments.is_empty());
+}
+
+#[test]
+fn diff_duplicate_connections_counted() {
+    let old = build_diamond_graph();
+    let mut new = old.clone();
+    new.add_connection(Connection::data("node_a", "result", "node_b", "a"));
+
+    let diff = GraphDiff::compute(&old, &new);
+    assert_eq!(diff.added_connections.len(), 1);
+    assert!(diff.removed_connections.is_empty());
+}
+
+// ===========================================================================
+// GraphDiff - Apply / Invert
+// ===========================================================================
+
+#[test]
+fn diff_apply_reproduces_new_graph() {
+    let old = build_diamond_graph();
+    let mut new = old.clone();
+    new.nodes.remove("node_d");
+    new.connections.retain(|c| c.target_node != "node_d");
+    new.get_node_mut("node_b").unwrap().set_property("b", PropertyValue::Number(9.0));
+    new.add_node(NodeInstance::new("node_e", "negate", Position::zero()));
+    new.add_connection(Connection::data("node_c", "result", "node_e", "value"));
+    new.comments.push(comment("note"));
+
+    let diff = GraphDiff::compute(&old, &new);
+    let mut graph = old.clone();
+    diff.apply(&mut graph).unwrap();
+
+    assert!(GraphDiff::compute(&graph, &new).is_empty());
+}
+
+#[test]
+fn diff_invert_undoes_apply() {
+    let old = build_branch_graph();
+    let mut new = old.clone();
+    new.nodes.remove("print_false");
+    new.connections.retain(|c| c.target_node != "print_false");
+    new.get_node_mut("branch_1").unwrap().node_type = "branch_v2".to_string();
+
+    let diff = GraphDiff::compute(&old, &new);
+    let mut graph = old.clone();
+    diff.apply(&mut graph).unwrap();
+    diff.invert().apply(&mut graph).unwrap();
+
+    assert!(GraphDiff::compute(&graph, &old).is_empty());
+}
+
+#[test]
+fn diff_apply_missing_node_leaves_graph_untouched() {
+    let old = build_diamond_graph();
+    let mut new = old.clone();
+    new.nodes.remove("node_d");
+    new.add_node(NodeInstance::new("node_e", "negate", Position::zero()));
+    let diff = GraphDiff::compute(&old, &new);
+
+    let mut graph = GraphDescription::new("other");
+    let result = diff.apply(&mut graph);
+
+    assert!(matches!(result, Err(GraphyError::NodeNotFound(id)) if id == "node_d"));
+    assert!(graph.nodes.is_empty());
+}
+
+#[test]
+fn diff_apply_missing_connection_fails() {
+    let old = build_diamond_graph();
+    let mut new = old.clone();
+    new.connections.clear();
+    let diff = GraphDiff::compute(&old, &new);
+
+    let mut graph = old.clone();
+    graph.connections.pop();
+    let result = diff.apply(&mut graph);
+
+    assert!(matches!(
+        result,
+        Err(GraphyError::InvalidConnection { reason: core::ConnectionErrorReason::NotFound, .. })
+    ));
+    assert_eq!(graph.connections.len(), 3);
+}
+
+// ===========================================================================
+// GraphDiff - Groups
+// ===========================================================================
+
+fn group(id: &str, node_ids: &[&str]) -> GraphGroup {
+    let mut group = GraphGroup::new(id, id.to_uppercase());
+    group.node_ids = node_ids.iter().map(|id| id.to_string()).collect();
+    group
+}
+
+#[test]
+fn diff_detects_group_changes() {
+    let mut old = build_diamond_graph();
+    old.add_group(group("left", &["node_a", "node_b"])).unwrap();
+    old.add_group(group("gone", &["node_d"])).unwrap();
+
+    let mut new = old.clone();
+    new.remove_group("gone");
+    new.add_node_to_group("left", "node_c").unwrap();
+    new.add_group(group("new", &["node_d"])).unwrap();
+
+    let diff = GraphDiff::compute(&old, &new);
+    assert_eq!(diff.added_groups.len(), 1);
+    assert_eq!(diff.added_groups[0].id, "new");
+    assert_eq!(diff.removed_groups.len(), 1);
+    assert_eq!(diff.removed_groups[0].id, "gone");
+    assert_eq!(diff.modified_groups.len(), 1);
+    assert_eq!(diff.modified_groups[0].new.node_ids, vec!["node_a", "node_b", "node_c"]);
+}
+
+#[test]
+fn diff_group_apply_and_invert() {
+    let mut old = build_diamond_graph();
+    old.add_group(group("g1", &["node_a"])).unwrap();
+
+    let mut new = old.clone();
+    new.get_group_mut("g1").unwrap().title = "Renamed".into();
+    new.add_group(group("g2", &["node_b"])).unwrap();
+
+    let diff = GraphDiff::compute(&old, &new);
+    let mut graph = old.clone();
+    diff.apply(&mut graph).unwrap();
+    assert!(GraphDiff::compute(&graph, &new).is_empty());
+
+    diff.invert().apply(&mut graph).unwrap();
+    assert!(GraphDiff::compute(&graph, &old).is_empty());
+}
+
+#[test]
+fn diff_apply_missing_group_fails() {
+    let mut old = build_diamond_graph();
+    old.add_group(group("g1", &["node_a"])).unwrap();
+    let mut new = old.clone();
+    new.remove_group("g1");
+    let diff = GraphDiff::compute(&old, &new);
+
+    let mut graph = build_diamond_graph();
+    assert!(matches!(diff.apply(&mut graph), Err(GraphyError::Custom(_))));
+}
+
+// ===========================================================================
+// GraphDiff - Connection IDs
+// ===========================================================================
+
+#[test]
+fn diff_connections_with_same_id_are_modified() {
+    let mut old = build_diamond_graph();
+    old.assign_connection_ids();
+    let mut new = old.clone();
+    {
+        let connection = new.get_connection_mut("conn_4").unwrap();
+        connection.target_pin = "a".into();
+        connection.waypoints.push(Position::new(10.0, 10.0));
+    }
+    new.get_connection_mut("conn_1").unwrap().label = Some("fork".into());
+
+    let diff = GraphDiff::compute(&old, &new);
+    assert!(diff.added_connections.is_empty());
+    assert!(diff.removed_connections.is_empty());
+    assert_eq!(diff.modified_connections.len(), 2);
+
+    let mut graph = old.clone();
+    diff.apply(&mut graph).unwrap();
+    assert_eq!(graph.connections, new.connections);
+
+    diff.invert().apply(&mut graph).unwrap();
+    assert_eq!(graph.connections, old.connections);
+}
+
+#[test]
+fn diff_connections_without_id_are_replaced() {
+    let old = build_diamond_graph();
+    let mut new = old.clone();
+    new.connections[0].label = Some("fork".into());
+
+    let diff = GraphDiff::compute(&old, &new);
+    assert!(diff.modified_connections.is_empty());
+    assert_eq!(diff.removed_connections, vec![old.connections[0].clone()]);
+    assert_eq!(diff.added_connections, vec![new.connections[0].clone()]);
+}
+
+#[test]
+fn diff_apply_fails_on_missing_modified_connection() {
+    let mut old = build_diamond_graph();
+    old.assign_connection_ids();
+    let mut new = old.clone();
+    new.get_connection_mut("conn_1").unwrap().color = Some("blue".into());
+    let diff = GraphDiff::compute(&old, &new);
+
+    let mut graph = build_diamond_graph();
+    let err = diff.apply(&mut graph).unwrap_err();
+    assert!(err.to_string().contains("conn_1: node_a.result -> node_b.a"), "{}", err);
+    assert!(graph.semantically_equals(&build_diamond_graph()));
+}
+
+// ===========================================================================
+// GraphDiff - Serialization
+// ===========================================================================
+
+#[test]
+fn diff_serialization_roundtrip() {
+    let old = build_diamond_graph();
+    let mut new = old.clone();
+    new.get_node_mut("node_c").unwrap().position = Position::new(5.0, 5.0);
+    new.add_connection(Connection::data("node_b", "result", "node_c", "b"));
+
+    let diff = GraphDiff::compute(&old, &new);
+    let json = serde_json::to_string(&diff).unwrap();
+    let restored: GraphDiff = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(restored, diff);
+}
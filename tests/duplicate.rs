@@ -0,0 +1,171 @@
+//! Tests for duplicating nodes and generating collision-free IDs.
+
+mod common;
+
+use common::*;
+use graphy::*;
+
+fn ids(copies: &[String]) -> Vec<&str> {
+    copies.iter().map(String::as_str).collect()
+}
+
+// ===========================================================================
+// ID strategies
+// ===========================================================================
+
+#[test]
+fn suffix_ids_use_the_first_free_number() {
+    let mut graph = GraphDescription::new("graph");
+    for id in ["add", "add_1", "add_3", "print_10"] {
+        graph.add_node(NodeInstance::new(id, "math.add", Position::zero()));
+    }
+
+    assert_eq!(IdStrategy::Suffix.generate(&graph, "add"), "add_2");
+    assert_eq!(IdStrategy::Suffix.generate(&graph, "add_3"), "add_2");
+    assert_eq!(IdStrategy::Suffix.generate(&graph, "print_10"), "print_1");
+    assert_eq!(IdStrategy::Suffix.generate(&graph, "node_a"), "node_a_1");
+    assert_eq!(IdStrategy::Suffix.generate(&graph, "_7"), "_7_1");
+    assert_eq!(IdStrategy::default(), IdStrategy::Suffix);
+}
+
+#[test]
+fn uuid_ids_are_deterministic_and_well_formed() {
+    let graph = build_diamond_graph();
+    let id = IdStrategy::Uuid.generate(&graph, "node_a");
+    assert_eq!(id, IdStrategy::Uuid.generate(&graph.clone(), "node_a"));
+    assert_ne!(id, IdStrategy::Uuid.generate(&graph, "node_b"));
+
+    let groups: Vec<&str> = id.split('-').collect();
+    assert_eq!(groups.iter().map(|group| group.len()).collect::<Vec<_>>(), [8, 4, 4, 4, 12]);
+    assert!(id.chars().all(|c| c == '-' || c.is_ascii_hexdigit() && !c.is_ascii_uppercase()));
+    assert!(groups[2].starts_with('4'));
+    assert!(matches!(groups[3].chars().next(), Some('8' | '9' | 'a' | 'b')));
+}
+
+#[test]
+fn uuid_ids_skip_taken_ids() {
+    let mut graph = build_diamond_graph();
+    let taken = IdStrategy::Uuid.generate(&graph, "node_a");
+    // Keep the node count, so the first candidate is the same
+    let mut node = graph.remove_node("node_d").unwrap();
+    node.id = taken.clone();
+    graph.add_node(node);
+
+    let id = IdStrategy::Uuid.generate(&graph, "node_a");
+    assert_ne!(id, taken);
+    assert!(!graph.nodes.contains_key(&id));
+}
+
+// ===========================================================================
+// Duplication
+// ===========================================================================
+
+#[test]
+fn duplicates_keep_internal_connections() {
+    let mut graph = build_diamond_graph();
+    let copies = graph
+        .duplicate_nodes(&["node_a", "node_b", "node_d"], &IdStrategy::Suffix, Position::zero())
+        .unwrap();
+    assert_eq!(ids(&copies), ["node_a_1", "node_b_1", "node_d_1"]);
+    assert_eq!(graph.nodes.len(), 7);
+
+    // a -> b and b -> d are inside the selection; a -> c and c -> d are not
+    let copied: Vec<(&str, &str, &str)> = graph.connections[4..]
+        .iter()
+        .map(|c| (c.source_node.as_str(), c.target_node.as_str(), c.target_pin.as_str()))
+        .collect();
+    assert_eq!(copied, [("node_a_1", "node_b_1", "a"), ("node_b_1", "node_d_1", "a")]);
+
+    let original = &graph.nodes["node_b"];
+    let copy = &graph.nodes["node_b_1"];
+    assert_eq!(copy.node_type, original.node_type);
+    assert_eq!(copy.inputs, original.inputs);
+    assert_eq!(copy.properties, original.properties);
+}
+
+#[test]
+fn duplicates_are_offset() {
+    let mut graph = GraphDescription::new("graph");
+    graph.add_node(NodeInstance::new("a", "math.add", Position::new(10.0, 20.0)));
+    graph.add_node(NodeInstance::new("b", "print", Position::new(200.0, 20.0)));
+    let mut connection = Connection::data("a", "result", "b", "value").with_id("conn_1");
+    connection.waypoints.push(Position::new(100.0, 50.0));
+    graph.add_connection(connection);
+
+    let copies = graph
+        .duplicate_nodes(&["a", "b"], &IdStrategy::Suffix, Position::new(30.0, -5.0))
+        .unwrap();
+    assert_eq!(graph.nodes[&copies[0]].position, Position::new(40.0, 15.0));
+    assert_eq!(graph.nodes[&copies[1]].position, Position::new(230.0, 15.0));
+    assert_eq!(graph.nodes["a"].position, Position::new(10.0, 20.0));
+
+    let copy = &graph.connections[1];
+    assert_eq!(copy.waypoints, [Position::new(130.0, 45.0)]);
+    assert_eq!(copy.id, None);
+    assert_eq!(graph.assign_connection_ids(), 1);
+}
+
+#[test]
+fn duplicating_twice_never_collides() {
+    let mut graph = build_diamond_graph();
+    let selection = ["node_a", "node_b", "node_c", "node_d"];
+    let first = graph
+        .duplicate_nodes(&selection, &IdStrategy::Suffix, Position::zero())
+        .unwrap();
+    let second = graph
+        .duplicate_nodes(&first, &IdStrategy::Suffix, Position::zero())
+        .unwrap();
+    assert_eq!(ids(&second), ["node_a_2", "node_b_2", "node_c_2", "node_d_2"]);
+    assert_eq!(graph.nodes.len(), 12);
+    assert_eq!(graph.connections.len(), 12);
+
+    let uuids = graph
+        .duplicate_nodes(&selection, &IdStrategy::Uuid, Position::zero())
+        .unwrap();
+    assert_eq!(graph.nodes.len(), 16);
+    assert!(uuids.iter().all(|id| id.len() == 36));
+}
+
+#[test]
+fn duplicating_is_reproducible() {
+    let duplicate = |strategy: &IdStrategy| {
+        let mut graph = build_diamond_graph();
+        graph
+            .duplicate_nodes(&["node_c", "node_a"], strategy, Position::zero())
+            .unwrap()
+    };
+    assert_eq!(duplicate(&IdStrategy::Uuid), duplicate(&IdStrategy::Uuid));
+    assert_eq!(ids(&duplicate(&IdStrategy::Suffix)), ["node_c_1", "node_a_1"]);
+}
+
+#[test]
+fn repeated_ids_are_copied_once() {
+    let mut graph = build_diamond_graph();
+    let copies = graph
+        .duplicate_nodes(&["node_a", "node_a"], &IdStrategy::Suffix, Position::zero())
+        .unwrap();
+    assert_eq!(ids(&copies), ["node_a_1"]);
+    assert_eq!(graph.nodes.len(), 5);
+}
+
+#[test]
+fn missing_nodes_leave_the_graph_unchanged() {
+    let mut graph = build_diamond_graph();
+    let before = graph.clone();
+    let result = graph.duplicate_nodes(&["node_a", "missing"], &IdStrategy::Suffix, Position::zero());
+    assert!(matches!(result, Err(GraphyError::NodeNotFound(id)) if id == "missing"));
+    assert_eq!(graph.nodes.len(), before.nodes.len());
+    assert_eq!(graph.connections, before.connections);
+}
+
+#[test]
+fn copies_are_not_grouped() {
+    let mut graph = build_diamond_graph();
+    graph.add_group(GraphGroup::new("group", "Top")).unwrap();
+    graph.add_node_to_group("group", "node_a").unwrap();
+    let copies = graph
+        .duplicate_nodes(&["node_a"], &IdStrategy::Suffix, Position::zero())
+        .unwrap();
+    assert!(graph.group_of(&copies[0]).is_none());
+    assert!(graph.group_of("node_a").is_some());
+}
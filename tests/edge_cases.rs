@@ -4,6 +4,8 @@
 mod common;
 
 use common::*;
+use graphy::analysis::UnknownNodePolicy;
+use graphy::core::GRAPH_INPUT_NODE_TYPE;
 use graphy::*;
 
 // ===========================================================================
@@ -231,6 +233,27 @@ fn edge_node_type_not_in_provider() {
     assert!(resolver.get_result_variable("unknown_1").is_some());
 }
 
+#[test]
+fn edge_unknown_node_policies() {
+    let mut graph = GraphDescription::new("test");
+    graph.add_node(NodeInstance::new("unknown_1", "unknown_type", Position::zero()));
+    graph.add_node(NodeInstance::new("add_1", "add", Position::zero()));
+    graph.add_node(NodeInstance::new("input", GRAPH_INPUT_NODE_TYPE, Position::zero()));
+    let provider = TestMetadataProvider::with_math_nodes();
+
+    // Builtin nodes have no metadata, but aren't unknown
+    let unknown: Vec<&str> = graph.unknown_nodes(&provider).iter().map(|node| node.id.as_str()).collect();
+    assert_eq!(unknown, ["unknown_1"]);
+
+    let error = UnknownNodePolicy::default().enforce(&graph, &provider).unwrap_err();
+    assert_eq!(error.to_string(), "Node unknown_1 has unknown type 'unknown_type'");
+    assert!(UnknownNodePolicy::Warn.enforce(&graph, &provider).is_ok());
+    assert!(UnknownNodePolicy::Skip.enforce(&graph, &provider).is_ok());
+
+    graph.remove_node("unknown_1");
+    assert!(UnknownNodePolicy::Error.enforce(&graph, &provider).is_ok());
+}
+
 // ===========================================================================
 // Graph with only execution connections (no data)
 // ===========================================================================
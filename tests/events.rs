@@ -0,0 +1,312 @@
+//! Tests for event analysis and generated event dispatchers.
+
+mod common;
+
+use common::*;
+use graphy::analysis::{DataFlowPass, DataResolver, EventAnalysis, EventAnalysisPass, ExecutionRouting, ExecutionRoutingPass};
+use graphy::core::VariableDecl;
+use graphy::generation::targets::CGenerator;
+use graphy::generation::{
+    compile_with_options, generate_exec_output, CodeGenerator, CompilationSession, CompileOptions, EventDispatcher,
+};
+use graphy::*;
+use std::sync::Arc;
+
+/// Generator emitting handlers named after their event, taking the state first
+struct HandlerGenerator;
+
+impl CodeGenerator for HandlerGenerator {
+    fn supports_async(&self) -> bool {
+        true
+    }
+
+    fn generate_event<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        metadata: &NodeMetadata,
+    ) -> Result<()> {
+        let mut params = Vec::new();
+        if !ctx.graph.variables.is_empty() {
+            params.push("state: &mut GraphState".to_string());
+        }
+        params.extend(metadata.params.iter().map(|param| format!("{}: {}", param.name, param.param_type)));
+        let asyncness = if ctx.is_async_event(&node.id) { "async " } else { "" };
+        ctx.emit(&format!("{}fn {}({}) {{\n", asyncness, node.id, params.join(", ")));
+        ctx.push_indent();
+        for pin in &metadata.exec_outputs {
+            generate_exec_output(self, ctx, &node.id, pin)?;
+        }
+        ctx.pop_indent();
+        ctx.emit("}\n");
+        Ok(())
+    }
+
+    fn generate_function_node<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        metadata: &NodeMetadata,
+        args: &[String],
+    ) -> Result<()> {
+        let mut call = format!("{}({})", node.node_type, args.join(", "));
+        if metadata.is_async {
+            call = self.generate_await(ctx, node, &call)?;
+        }
+        let line = format!("{}{};\n", ctx.indent(), call);
+        ctx.emit(&line);
+        Ok(())
+    }
+
+    fn generate_control_flow<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &mut CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        _metadata: &NodeMetadata,
+        _args: &[String],
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn generate_pure_expression<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        _metadata: &NodeMetadata,
+        args: &[String],
+    ) -> Result<String> {
+        Ok(format!("{}({})", node.node_type, args.join(", ")))
+    }
+}
+
+fn provider() -> TestMetadataProvider {
+    let mut provider = TestMetadataProvider::comprehensive();
+    provider.add(
+        NodeMetadata::new("fetch", NodeTypes::fn_, "network")
+            .with_params(vec![ParamInfo::new("value", "i64")])
+            .with_exec_outputs(vec!["then".to_string()])
+            .with_async(true),
+    );
+    provider
+}
+
+fn call_node(id: &str, node_type: &str) -> NodeInstance {
+    let mut node = NodeInstance::new(id, node_type, Position::zero());
+    node.add_input_pin("exec_in", DataType::Execution);
+    node.add_input_pin("value", DataType::Typed("i64".into()));
+    node.add_output_pin("then", DataType::Execution);
+    node
+}
+
+/// `start -> set_1` and `tick -> set_2 -> fetch_1`, both setters reading `add_1`
+fn build_setter_graph() -> GraphDescription {
+    let mut graph = GraphDescription::new("events");
+
+    let mut start = NodeInstance::new("start", "on_start", Position::zero());
+    start.add_output_pin("exec", DataType::Execution);
+    graph.add_node(start);
+    let mut tick = NodeInstance::new("tick", "on_tick", Position::zero());
+    tick.add_output_pin("exec", DataType::Execution);
+    graph.add_node(tick);
+
+    let mut add = NodeInstance::new("add_1", "add", Position::zero());
+    add.add_input_pin("a", DataType::Typed("i64".into()));
+    add.add_input_pin("b", DataType::Typed("i64".into()));
+    add.add_output_pin("result", DataType::Typed("i64".into()));
+    add.set_property("a", PropertyValue::Integer(1));
+    add.set_property("b", PropertyValue::Integer(2));
+    graph.add_node(add);
+
+    graph.add_node(call_node("set_1", "set_variable"));
+    graph.add_node(call_node("set_2", "set_variable"));
+    graph.add_node(call_node("fetch_1", "fetch"));
+
+    graph.add_connection(Connection::execution("start", "exec", "set_1", "exec_in"));
+    graph.add_connection(Connection::execution("tick", "exec", "set_2", "exec_in"));
+    graph.add_connection(Connection::execution("set_2", "then", "fetch_1", "exec_in"));
+    graph.add_connection(Connection::data("add_1", "result", "set_1", "value"));
+    graph.add_connection(Connection::data("add_1", "result", "set_2", "value"));
+    graph
+}
+
+fn analyze(graph: &GraphDescription, provider: &TestMetadataProvider) -> EventAnalysis {
+    let data_resolver = DataResolver::build(graph, provider).unwrap();
+    let exec_routing = ExecutionRouting::build_from_graph(graph);
+    EventAnalysis::compute(graph, provider, &exec_routing, &data_resolver)
+}
+
+fn dispatcher_options() -> CompileOptions {
+    CompileOptions::new().with_dispatcher(EventDispatcher::new("GameEvents"))
+}
+
+// ============================================================================
+// Event analysis
+// ============================================================================
+
+#[test]
+fn events_are_found_in_id_order() {
+    let events = analyze(&build_setter_graph(), &provider());
+
+    let ids: Vec<&str> = events.events().iter().map(|event| event.node_id.as_str()).collect();
+    assert_eq!(ids, ["start", "tick"]);
+    assert_eq!(events.len(), 2);
+    assert_eq!(events.get("tick").unwrap().node_type, "on_tick");
+    assert!(events.get("set_1").is_none());
+}
+
+#[test]
+fn events_list_the_params_of_their_metadata() {
+    let events = analyze(&build_setter_graph(), &provider());
+
+    assert!(events.get("start").unwrap().params.is_empty());
+    let params = &events.get("tick").unwrap().params;
+    assert_eq!(params.len(), 1);
+    assert_eq!((params[0].name.as_str(), params[0].param_type.as_str()), ("delta_time", "f64"));
+}
+
+#[test]
+fn events_include_the_nodes_they_run_and_read() {
+    let events = analyze(&build_setter_graph(), &provider());
+
+    assert_eq!(events.get("start").unwrap().nodes, ["add_1", "set_1", "start"]);
+    assert_eq!(events.get("tick").unwrap().nodes, ["add_1", "fetch_1", "set_2", "tick"]);
+    assert_eq!(events.events_running("add_1"), ["start", "tick"]);
+    assert_eq!(events.events_running("fetch_1"), ["tick"]);
+    assert_eq!(events.shared_nodes(), ["add_1"]);
+}
+
+#[test]
+fn events_running_async_nodes_are_async() {
+    let events = analyze(&build_setter_graph(), &provider());
+
+    assert!(!events.get("start").unwrap().is_async);
+    assert!(events.get("tick").unwrap().is_async);
+}
+
+#[test]
+fn events_of_unknown_types_are_ignored() {
+    let mut graph = build_setter_graph();
+    graph.add_node(NodeInstance::new("other", "on_unknown", Position::zero()));
+
+    let events = analyze(&graph, &provider());
+    assert!(events.get("other").is_none());
+    assert_eq!(events.len(), 2);
+}
+
+// ============================================================================
+// Pass
+// ============================================================================
+
+#[test]
+fn events_pass_reads_the_builtin_passes() {
+    let graph = build_setter_graph();
+    let provider = provider();
+
+    let mut manager = PassManager::new();
+    manager.add_pass(DataFlowPass::new(&provider));
+    manager.add_pass(ExecutionRoutingPass);
+    manager.add_pass(EventAnalysisPass::new(&provider));
+
+    let analysis = manager.run(&graph).unwrap();
+    let events = analysis.get::<EventAnalysis>().unwrap();
+    assert_eq!(events.shared_nodes(), ["add_1"]);
+}
+
+#[test]
+fn events_pass_requires_the_builtin_passes() {
+    let graph = build_setter_graph();
+    let provider = provider();
+
+    let mut manager = PassManager::new();
+    manager.add_pass(EventAnalysisPass::new(&provider));
+
+    let result = manager.run(&graph);
+    assert!(matches!(result, Err(GraphyError::Custom(ref message)) if message.contains("data_flow and exec_routing")));
+}
+
+// ============================================================================
+// Dispatcher
+// ============================================================================
+
+#[test]
+fn events_dispatcher_forwards_to_handlers() {
+    let graph = build_setter_graph();
+    let output = compile_with_options(&graph, &provider(), &mut HandlerGenerator, &dispatcher_options()).unwrap();
+    let code = output.code;
+
+    assert!(code.contains("pub struct GraphDispatcher;\n"), "{}", code);
+    assert!(code.contains("impl GameEvents for GraphDispatcher {\n"), "{}", code);
+    assert!(code.contains("    fn start(&mut self) {\n        start()\n    }\n"), "{}", code);
+    assert!(
+        code.contains("    async fn tick(&mut self, delta_time: f64) {\n        tick(delta_time).await\n    }\n"),
+        "{}",
+        code
+    );
+    assert!(!code.contains("pub trait GameEvents"), "{}", code);
+}
+
+#[test]
+fn events_dispatcher_owns_the_state() {
+    let mut graph = build_setter_graph();
+    graph.add_variable("score", VariableDecl::new("i64"));
+    let output = compile_with_options(&graph, &provider(), &mut HandlerGenerator, &dispatcher_options()).unwrap();
+    let code = output.code;
+
+    assert!(code.contains("pub struct GraphDispatcher {\n    pub state: GraphState,\n}\n"), "{}", code);
+    assert!(code.contains("        start(&mut self.state)\n"), "{}", code);
+    assert!(code.contains("        tick(&mut self.state, delta_time).await\n"), "{}", code);
+}
+
+#[test]
+fn events_dispatcher_can_declare_its_trait() {
+    let options = CompileOptions::new().with_dispatcher(
+        EventDispatcher::new("GameEvents")
+            .with_struct_name("Game")
+            .with_trait_declaration(true),
+    );
+    let output = compile_with_options(&build_setter_graph(), &provider(), &mut HandlerGenerator, &options).unwrap();
+
+    assert!(
+        output.code.contains(
+            "pub trait GameEvents {\n    fn start(&mut self);\n    async fn tick(&mut self, delta_time: f64);\n}\n"
+        ),
+        "{}",
+        output.code
+    );
+    assert!(output.code.contains("impl GameEvents for Game {\n"), "{}", output.code);
+}
+
+#[test]
+fn events_dispatcher_follows_the_handlers() {
+    let output = compile_with_options(&build_setter_graph(), &provider(), &mut HandlerGenerator, &dispatcher_options())
+        .unwrap();
+
+    let last_handler = output.code.rfind("fn tick(delta_time: f64)").unwrap();
+    assert!(output.code.find("pub struct GraphDispatcher").unwrap() > last_handler);
+}
+
+#[test]
+fn events_dispatcher_is_off_by_default() {
+    let output =
+        compile_with_options(&build_setter_graph(), &provider(), &mut HandlerGenerator, &CompileOptions::new()).unwrap();
+    assert!(!output.code.contains("GraphDispatcher"));
+}
+
+#[test]
+fn events_dispatcher_matches_in_sessions() {
+    let graph = build_setter_graph();
+    let provider = Arc::new(provider());
+    let options = dispatcher_options();
+
+    let compiled = compile_with_options(&graph, provider.as_ref(), &mut HandlerGenerator, &options).unwrap();
+    let session = CompilationSession::new(&graph, provider, &HandlerGenerator, &options).unwrap();
+    assert_eq!(session.finish(&mut HandlerGenerator).unwrap().code, compiled.code);
+}
+
+#[test]
+fn events_dispatcher_is_not_supported_by_the_c_backend() {
+    let mut graph = build_setter_graph();
+    graph.remove_node("fetch_1");
+    let result = compile_with_options(&graph, &provider(), &mut CGenerator::new("game"), &dispatcher_options());
+    assert!(matches!(result, Err(GraphyError::CodeGeneration(ref message)) if message.contains("GameEvents")));
+}
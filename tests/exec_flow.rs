@@ -3,6 +3,7 @@
 mod common;
 
 use common::*;
+use graphy::analysis::ExecPath;
 use graphy::*;
 
 // ===========================================================================
@@ -156,6 +157,17 @@ fn exec_routing_get_output_pins_none() {
     assert!(pins.is_empty());
 }
 
+#[test]
+fn exec_routing_lookups_by_symbols() {
+    let graph = build_branch_graph();
+    let routing = ExecutionRouting::build_from_graph(&graph);
+
+    let key = routing.symbols().get_pair("branch_1", "True").unwrap();
+    assert_eq!(routing.get_connected_nodes_by_symbols(key), routing.get_connected_nodes("branch_1", "True"));
+    assert!(!routing.get_connected_nodes_by_symbols(key).is_empty());
+    assert!(routing.get_connected_nodes("branch_1", "Maybe").is_empty());
+}
+
 // ===========================================================================
 // ExecutionRouting - Ignores data connections
 // ===========================================================================
@@ -215,3 +227,214 @@ fn exec_routing_diamond_execution() {
     let from_c = routing.get_connected_nodes("c", "exec_out");
     assert_eq!(from_c, &["d"]);
 }
+
+// ===========================================================================
+// ExecutionRouting - Loops
+// ===========================================================================
+
+/// start -> while_1 -(body)-> step -> while_1, while_1 -(done)-> end
+fn build_while_loop_graph() -> GraphDescription {
+    let mut graph = GraphDescription::new("while_loop");
+
+    for id in ["start", "while_1", "step", "end"] {
+        let mut node = NodeInstance::new(id, "fn", Position::zero());
+        node.add_input_pin("exec_in", DataType::Execution);
+        node.add_output_pin("exec_out", DataType::Execution);
+        graph.add_node(node);
+    }
+
+    graph.add_connection(Connection::execution("start", "exec_out", "while_1", "exec_in"));
+    graph.add_connection(Connection::execution("while_1", "body", "step", "exec_in"));
+    graph.add_connection(Connection::execution("step", "exec_out", "while_1", "exec_in"));
+    graph.add_connection(Connection::execution("while_1", "done", "end", "exec_in"));
+
+    graph
+}
+
+#[test]
+fn exec_routing_acyclic_has_no_loops() {
+    let graph = build_branch_graph();
+    let routing = ExecutionRouting::build_from_graph(&graph);
+
+    assert!(routing.get_loops().is_empty());
+    assert!(routing.get_back_edges().is_empty());
+    assert!(!routing.is_in_loop("branch_1"));
+}
+
+#[test]
+fn exec_routing_detects_while_loop() {
+    let graph = build_while_loop_graph();
+    let routing = ExecutionRouting::build_from_graph(&graph);
+
+    assert_eq!(routing.get_loops(), &[vec!["step".to_string(), "while_1".to_string()]]);
+    assert_eq!(routing.get_back_edges().len(), 1);
+    assert!(routing.is_back_edge("step", "exec_out", "while_1"));
+    assert!(!routing.is_back_edge("start", "exec_out", "while_1"));
+    assert!(routing.is_loop_header("while_1"));
+    assert!(!routing.is_loop_header("step"));
+
+    assert!(routing.is_in_loop("step"));
+    assert!(!routing.is_in_loop("start"));
+    assert!(!routing.is_in_loop("end"));
+    assert_eq!(routing.get_loop("while_1").unwrap().len(), 2);
+}
+
+#[test]
+fn exec_routing_forward_nodes_skip_back_edges() {
+    let graph = build_while_loop_graph();
+    let routing = ExecutionRouting::build_from_graph(&graph);
+
+    assert_eq!(routing.get_connected_nodes("step", "exec_out"), &["while_1"]);
+    assert!(routing.get_forward_connected_nodes("step", "exec_out").is_empty());
+    assert_eq!(routing.get_forward_connected_nodes("while_1", "body"), vec!["step"]);
+}
+
+#[test]
+fn exec_routing_self_loop() {
+    let mut graph = build_exec_chain(2);
+    graph.add_connection(Connection::execution("fn_1", "repeat", "fn_1", "exec_in"));
+
+    let routing = ExecutionRouting::build_from_graph(&graph);
+    assert_eq!(routing.get_loops(), &[vec!["fn_1".to_string()]]);
+    assert!(routing.is_back_edge("fn_1", "repeat", "fn_1"));
+}
+
+#[test]
+fn exec_routing_cycle_without_entry_is_deterministic() {
+    let mut graph = GraphDescription::new("ring");
+    graph.add_connection(Connection::execution("a", "exec_out", "b", "exec_in"));
+    graph.add_connection(Connection::execution("b", "exec_out", "c", "exec_in"));
+    graph.add_connection(Connection::execution("c", "exec_out", "a", "exec_in"));
+
+    let routing = ExecutionRouting::build_from_graph(&graph);
+    assert_eq!(routing.get_loops().len(), 1);
+    assert_eq!(routing.get_back_edges().len(), 1);
+    assert!(routing.is_back_edge("c", "exec_out", "a"));
+}
+
+#[test]
+fn exec_routing_long_chain_loop_is_stack_safe() {
+    let mut graph = build_exec_chain(50_000);
+    graph.add_connection(Connection::execution("fn_49999", "exec_out", "fn_0", "exec_in"));
+
+    let routing = ExecutionRouting::build_from_graph(&graph);
+    assert_eq!(routing.get_loops().len(), 1);
+    assert_eq!(routing.get_loops()[0].len(), 50_000);
+}
+
+#[test]
+fn exec_loops_do_not_affect_data_cycle_detection() {
+    let graph = build_while_loop_graph();
+    let provider = TestMetadataProvider::empty();
+    assert!(DataResolver::build(&graph, &provider).is_ok());
+}
+
+// ===========================================================================
+// ExecutionRouting - Path queries
+// ===========================================================================
+
+#[test]
+fn exec_routing_get_incoming() {
+    let graph = build_while_loop_graph();
+    let routing = ExecutionRouting::build_from_graph(&graph);
+
+    let incoming = routing.get_incoming("while_1");
+    assert_eq!(incoming.len(), 2);
+    assert_eq!(incoming[0].source_node, "start");
+    assert_eq!(incoming[1].source_node, "step");
+    assert!(routing.get_incoming("start").is_empty());
+    assert_eq!(routing.get_outgoing("while_1").len(), 2);
+}
+
+#[test]
+fn exec_routing_predecessors() {
+    let graph = build_while_loop_graph();
+    let routing = ExecutionRouting::build_from_graph(&graph);
+
+    assert_eq!(routing.get_predecessors("while_1"), &["start", "step"]);
+    assert_eq!(routing.incoming_exec_count("while_1"), 2);
+    assert_eq!(routing.get_predecessors("end"), &["while_1"]);
+    assert_eq!(routing.incoming_exec_count("end"), 1);
+    assert!(routing.get_predecessors("start").is_empty());
+    assert_eq!(routing.incoming_exec_count("missing"), 0);
+}
+
+#[test]
+fn exec_routing_predecessors_are_deduplicated() {
+    // Both outputs of a branch converge on the same node
+    let mut graph = build_branch_graph();
+    graph.add_connection(Connection::execution("branch_1", "True", "print_false", "exec_in"));
+    let routing = ExecutionRouting::build_from_graph(&graph);
+
+    assert_eq!(routing.get_predecessors("print_false"), &["branch_1"]);
+    assert_eq!(routing.incoming_exec_count("print_false"), 2);
+}
+
+#[test]
+fn exec_routing_trace_branch_paths() {
+    let graph = build_branch_graph();
+    let routing = ExecutionRouting::build_from_graph(&graph);
+
+    let paths = routing.trace_from("start");
+    assert_eq!(paths.len(), 2);
+    assert_eq!(paths[0].nodes, vec!["start", "branch_1", "print_true"]);
+    assert_eq!(paths[0].pins, vec!["exec", "True"]);
+    assert_eq!(paths[1].end(), Some("print_false"));
+    assert!(paths.iter().all(|p| p.start() == Some("start") && p.loops_back_to.is_none()));
+}
+
+#[test]
+fn exec_routing_empty_path_has_no_ends() {
+    let path: ExecPath = serde_json::from_str(r#"{ "nodes": [], "pins": [], "loops_back_to": null }"#).unwrap();
+    assert_eq!((path.start(), path.end()), (None, None));
+    assert!(!path.contains("start"));
+}
+
+#[test]
+fn exec_routing_trace_stops_at_back_edges() {
+    let graph = build_while_loop_graph();
+    let routing = ExecutionRouting::build_from_graph(&graph);
+
+    let paths = routing.trace_from("start");
+    assert_eq!(paths.len(), 2);
+
+    let looping = paths.iter().find(|p| p.loops_back_to.is_some()).unwrap();
+    assert_eq!(looping.nodes, vec!["start", "while_1", "step"]);
+    assert_eq!(looping.pins, vec!["exec_out", "body", "exec_out"]);
+    assert_eq!(looping.loops_back_to.as_deref(), Some("while_1"));
+
+    let done = paths.iter().find(|p| p.loops_back_to.is_none()).unwrap();
+    assert!(done.contains("end"));
+}
+
+#[test]
+fn exec_routing_trace_isolated_node() {
+    let routing = ExecutionRouting::build_from_graph(&GraphDescription::new("empty"));
+    let paths = routing.trace_from("lonely");
+    assert_eq!(paths.len(), 1);
+    assert_eq!(paths[0].nodes, vec!["lonely"]);
+    assert!(paths[0].pins.is_empty());
+}
+
+#[test]
+fn exec_routing_is_reachable() {
+    let graph = build_while_loop_graph();
+    let routing = ExecutionRouting::build_from_graph(&graph);
+
+    assert!(routing.is_reachable("start", "end"));
+    assert!(routing.is_reachable("step", "end"));
+    assert!(routing.is_reachable("step", "step"));
+    assert!(!routing.is_reachable("end", "start"));
+    assert!(!routing.is_reachable("while_1", "start"));
+}
+
+#[test]
+fn exec_routing_find_unreachable_nodes() {
+    let mut graph = build_branch_graph();
+    graph.add_connection(Connection::execution("orphan_a", "exec_out", "orphan_b", "exec_in"));
+    let routing = ExecutionRouting::build_from_graph(&graph);
+
+    assert_eq!(routing.find_unreachable_nodes(["start"]), vec!["orphan_a", "orphan_b"]);
+    assert_eq!(routing.find_unreachable_nodes(["start", "orphan_a"]), Vec::<String>::new());
+    assert_eq!(routing.find_unreachable_nodes([]).len(), 6);
+}
@@ -0,0 +1,218 @@
+//! Tests for ExecWalk and stack-safe generation of long execution flows.
+
+mod common;
+
+use common::*;
+use graphy::generation::ir::{lower_graph, IrOp};
+use graphy::generation::targets::{compile_c, CGenerator};
+use graphy::generation::{compile, compile_with_source_map, CodeGenerator, ExecStep, ExecWalk};
+use graphy::utils::RustProfile;
+use graphy::*;
+
+/// Length of the chains that used to overflow the stack
+const LONG_CHAIN: usize = 10_000;
+
+/// Generator scheduling branches instead of recursing into them
+struct SchedulingGenerator;
+
+impl CodeGenerator for SchedulingGenerator {
+    fn generate_event<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        metadata: &NodeMetadata,
+    ) -> Result<()> {
+        ctx.emit(&format!("fn {}() {{\n", node.id));
+        let mut steps = vec![ExecStep::Indent];
+        steps.extend(metadata.exec_outputs.iter().map(|pin| ExecStep::output(node.id.as_str(), pin.as_str())));
+        steps.extend([ExecStep::Dedent, ExecStep::Emit("}\n".into())]);
+        ctx.schedule(steps);
+        Ok(())
+    }
+
+    fn generate_function_node<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        _metadata: &NodeMetadata,
+        args: &[String],
+    ) -> Result<()> {
+        let line = format!("{}{}({});\n", ctx.indent(), node.node_type, args.join(", "));
+        ctx.emit(&line);
+        Ok(())
+    }
+
+    fn generate_control_flow<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        _metadata: &NodeMetadata,
+        args: &[String],
+    ) -> Result<()> {
+        let indent = ctx.indent();
+        ctx.emit(&format!("{}if {} {{\n", indent, args[0]));
+        ctx.schedule([
+            ExecStep::Indent,
+            ExecStep::output(node.id.as_str(), "True"),
+            ExecStep::Dedent,
+            ExecStep::Emit(format!("{}}} else {{\n", indent)),
+            ExecStep::Indent,
+            ExecStep::output(node.id.as_str(), "False"),
+            ExecStep::Dedent,
+            ExecStep::Emit(format!("{}}}\n", indent)),
+        ]);
+        Ok(())
+    }
+
+    fn generate_pure_expression<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        _metadata: &NodeMetadata,
+        args: &[String],
+    ) -> Result<String> {
+        Ok(format!("{}({})", node.node_type, args.join(", ")))
+    }
+}
+
+/// `start` running a chain of `n` prints
+fn long_chain(n: usize) -> GraphDescription {
+    let mut graph = build_exec_chain(n);
+    let mut start = NodeInstance::new("start", "on_start", Position::zero());
+    start.add_output_pin("exec", DataType::Execution);
+    graph.add_node(start);
+    graph.add_connection(Connection::execution("start", "exec", "fn_0", "exec_in"));
+    graph
+}
+
+/// `start` running `depth` nested branches, each printing on its False side
+fn nested_branches(depth: usize) -> GraphDescription {
+    let mut graph = GraphDescription::new("nested");
+    let mut start = NodeInstance::new("start", "on_start", Position::zero());
+    start.add_output_pin("exec", DataType::Execution);
+    graph.add_node(start);
+
+    let mut previous = ("start".to_string(), "exec");
+    for i in 0..depth {
+        let mut branch = NodeInstance::new(format!("branch_{}", i), "branch", Position::zero());
+        branch.add_input_pin("exec_in", DataType::Execution);
+        branch.add_input_pin("condition", DataType::Typed("bool".into()));
+        branch.add_output_pin("True", DataType::Execution);
+        branch.add_output_pin("False", DataType::Execution);
+        branch.set_property("condition", PropertyValue::Boolean(true));
+        graph.add_node(branch);
+
+        let mut print = NodeInstance::new(format!("print_{}", i), "print_string", Position::zero());
+        print.add_input_pin("exec_in", DataType::Execution);
+        print.add_input_pin("message", DataType::Typed("String".into()));
+        print.set_property("message", PropertyValue::String(format!("depth {}", i)));
+        graph.add_node(print);
+
+        let id = format!("branch_{}", i);
+        graph.add_connection(Connection::execution(previous.0.as_str(), previous.1, id.as_str(), "exec_in"));
+        graph.add_connection(Connection::execution(id.as_str(), "False", format!("print_{}", i), "exec_in"));
+        previous = (id, "True");
+    }
+    graph
+}
+
+// ============================================================================
+// ExecWalk
+// ============================================================================
+
+#[test]
+fn exec_walk_runs_pushed_steps_first() {
+    let mut walk = ExecWalk::default();
+    walk.push_steps([ExecStep::output("a", "then"), ExecStep::Emit("}".into())]);
+    assert_eq!(walk.len(), 2);
+    assert_eq!(walk.pop(), Some(ExecStep::output("a", "then")));
+
+    walk.push_steps([ExecStep::Indent, ExecStep::Node("b".into()), ExecStep::Dedent]);
+    let rest: Vec<ExecStep> = std::iter::from_fn(|| walk.pop()).collect();
+    assert_eq!(
+        rest,
+        [ExecStep::Indent, ExecStep::Node("b".into()), ExecStep::Dedent, ExecStep::Emit("}".into())]
+    );
+    assert!(walk.is_empty());
+}
+
+#[test]
+fn exec_walk_works_with_other_steps() {
+    let mut walk: ExecWalk<u32> = ExecWalk::new();
+    walk.push_steps(1..=3);
+    assert_eq!(walk.pop(), Some(1));
+    walk.push_steps([10]);
+    assert_eq!(std::iter::from_fn(|| walk.pop()).collect::<Vec<_>>(), [10, 2, 3]);
+}
+
+// ============================================================================
+// Scheduled steps
+// ============================================================================
+
+#[test]
+fn exec_walk_scheduled_branches_match_recursion() {
+    let provider = TestMetadataProvider::comprehensive();
+    let graph = nested_branches(3);
+    let mut recursing = HookGenerator::new().with_event_functions(true).with_branches(Branches::IfElse);
+    let scheduled = compile(&graph, &provider, &mut SchedulingGenerator).unwrap();
+    let recursive = compile(&graph, &provider, &mut recursing).unwrap();
+    assert_eq!(scheduled, recursive);
+    assert!(scheduled.starts_with("fn start() {\n    if true {\n        if true {\n"), "{}", scheduled);
+    assert!(scheduled.ends_with("    } else {\n        print_string(\"depth 0\");\n    }\n}\n"), "{}", scheduled);
+}
+
+#[test]
+fn exec_walk_scheduled_code_stays_in_the_node_region() {
+    let provider = TestMetadataProvider::comprehensive();
+    let (code, source_map) =
+        compile_with_source_map(&nested_branches(1), &provider, &mut SchedulingGenerator).unwrap();
+    let closing_line = code.lines().count() - 1;
+    let branch = source_map.mappings_for_node("branch_0").next().unwrap();
+    assert_eq!(branch.start.line, 2);
+    assert_eq!(branch.end.line, closing_line + 1, "{}", code);
+    assert_eq!(source_map.resolve(3, 5).unwrap().node_id, "branch_0");
+    assert_eq!(source_map.resolve(4, 9).unwrap().node_id, "print_0");
+}
+
+// ============================================================================
+// Long flows
+// ============================================================================
+
+#[test]
+fn exec_walk_compiles_long_chains() {
+    let provider = TestMetadataProvider::comprehensive();
+    let code = compile(&long_chain(LONG_CHAIN), &provider, &mut SchedulingGenerator).unwrap();
+    assert_eq!(code.lines().count(), LONG_CHAIN + 2);
+    assert!(code.contains("print_string(\"step 9999\");"));
+}
+
+#[test]
+fn exec_walk_compiles_deeply_nested_scheduled_branches() {
+    let provider = TestMetadataProvider::comprehensive();
+    let code = compile(&nested_branches(2_000), &provider, &mut SchedulingGenerator).unwrap();
+    assert!(code.contains("print_string(\"depth 1999\");"));
+}
+
+#[test]
+fn exec_walk_lowers_long_chains() {
+    let provider = TestMetadataProvider::comprehensive();
+    let program = lower_graph(&long_chain(LONG_CHAIN), &provider, &RustProfile).unwrap();
+    let IrOp::EmitEvent { body, .. } = &program.ops[0] else {
+        panic!("expected an event, got {:?}", program.ops[0]);
+    };
+    assert_eq!(body.len(), LONG_CHAIN);
+}
+
+#[test]
+fn exec_walk_c_backend_handles_long_chains() {
+    let mut provider = TestMetadataProvider::comprehensive();
+    provider.add(
+        NodeMetadata::new("print_string", NodeTypes::fn_, "io")
+            .with_params(vec![ParamInfo::new("message", "String")])
+            .with_source("puts({{message}});")
+            .with_source_kind(SourceKind::Template)
+            .with_exec_outputs(vec!["then".into()]),
+    );
+    let sources = compile_c(&long_chain(LONG_CHAIN), &provider, &mut CGenerator::new("chain")).unwrap();
+    assert_eq!(sources.source.matches("puts(").count(), LONG_CHAIN);
+}
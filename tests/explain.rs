@@ -0,0 +1,176 @@
+//! Tests for execution order explanations.
+
+mod common;
+
+use common::*;
+use graphy::analysis::{explain, DataDependency, ExecutionExplanation, OrderReason};
+use graphy::*;
+
+fn explain_graph(graph: &GraphDescription, provider: &TestMetadataProvider) -> ExecutionExplanation {
+    let resolver = DataResolver::build(graph, provider).unwrap();
+    let routing = ExecutionRouting::build_from_graph(graph);
+    explain(graph, &resolver, &routing)
+}
+
+fn chain(nodes: &[&str]) -> Vec<String> {
+    nodes.iter().map(|node| node.to_string()).collect()
+}
+
+/// `start` runs `loop_1`, whose `body` runs `step_1` and loops back, and
+/// whose `completed` runs `done`
+fn build_loop_graph() -> GraphDescription {
+    let mut graph = GraphDescription::new("loop");
+    let mut start = NodeInstance::new("start", "on_start", Position::zero());
+    start.add_output_pin("exec", DataType::Execution);
+    graph.add_node(start);
+    for (id, outputs) in [("loop_1", &["body", "completed"][..]), ("step_1", &["then"]), ("done", &["then"])] {
+        let mut node = NodeInstance::new(id, "print_string", Position::zero());
+        node.add_input_pin("exec_in", DataType::Execution);
+        for output in outputs {
+            node.add_output_pin(*output, DataType::Execution);
+        }
+        graph.add_node(node);
+    }
+    graph.add_connection(Connection::execution("start", "exec", "loop_1", "exec_in"));
+    graph.add_connection(Connection::execution("loop_1", "body", "step_1", "exec_in"));
+    graph.add_connection(Connection::execution("step_1", "then", "loop_1", "exec_in"));
+    graph.add_connection(Connection::execution("loop_1", "completed", "done", "exec_in"));
+    graph
+}
+
+// ============================================================================
+// Execution flow
+// ============================================================================
+
+#[test]
+fn explain_lists_event_flow_depth_first() {
+    let explanation = explain_graph(&build_branch_graph(), &TestMetadataProvider::comprehensive());
+    assert_eq!(explanation.events.len(), 1);
+    let event = &explanation.events[0];
+    assert_eq!(event.event, "start");
+
+    let steps: Vec<(&str, usize)> = event.steps.iter().map(|step| (step.node.as_str(), step.depth)).collect();
+    assert_eq!(steps, [("start", 0), ("branch_1", 1), ("print_true", 2), ("print_false", 2)]);
+    assert!(event.steps[0].reached_by.is_none());
+    let edge = event.steps[3].reached_by.as_ref().unwrap();
+    assert_eq!((edge.source_node.as_str(), edge.source_pin.as_str()), ("branch_1", "False"));
+    assert!(event.loop_edges.is_empty());
+}
+
+#[test]
+fn explain_stops_at_loop_back_edges() {
+    let explanation = explain_graph(&build_loop_graph(), &TestMetadataProvider::comprehensive());
+    let event = &explanation.events[0];
+    let nodes: Vec<&str> = event.steps.iter().map(|step| step.node.as_str()).collect();
+    assert_eq!(nodes, ["start", "loop_1", "step_1", "done"]);
+    assert_eq!(event.loop_edges.len(), 1);
+    assert_eq!(event.loop_edges[0].source_node, "step_1");
+    assert_eq!(event.loop_edges[0].target_node, "loop_1");
+}
+
+#[test]
+fn explain_without_events_has_no_flows() {
+    let explanation = explain_graph(&build_exec_chain(3), &TestMetadataProvider::comprehensive());
+    assert!(explanation.events.is_empty());
+}
+
+// ============================================================================
+// Pure evaluation order
+// ============================================================================
+
+#[test]
+fn explain_gives_the_inputs_pure_nodes_wait_for() {
+    let explanation = explain_graph(&build_diamond_graph(), &TestMetadataProvider::with_math_nodes());
+    let nodes: Vec<&str> = explanation.pure_order.iter().map(|step| step.node.as_str()).collect();
+    assert_eq!(nodes.first(), Some(&"node_a"));
+    assert_eq!(nodes.last(), Some(&"node_d"));
+
+    assert!(explanation.pure_order[0].waits_for.is_empty());
+    assert_eq!(
+        explanation.pure_order[3].waits_for,
+        vec![
+            DataDependency {
+                input: "a".into(),
+                source_node: "node_b".into(),
+                source_pin: "result".into(),
+            },
+            DataDependency {
+                input: "b".into(),
+                source_node: "node_c".into(),
+                source_pin: "result".into(),
+            },
+        ]
+    );
+}
+
+// ============================================================================
+// Reasons
+// ============================================================================
+
+#[test]
+fn explain_reasons_follow_data_flow() {
+    let explanation = explain_graph(&build_diamond_graph(), &TestMetadataProvider::with_math_nodes());
+    assert_eq!(
+        explanation.reason_before("node_a", "node_d"),
+        Some(OrderReason::DataFlow {
+            chain: chain(&["node_a", "node_b", "node_d"])
+        })
+    );
+    assert_eq!(
+        explanation.reason_before("node_c", "node_d"),
+        Some(OrderReason::DataFlow {
+            chain: chain(&["node_c", "node_d"])
+        })
+    );
+    assert_eq!(explanation.reason_before("node_b", "node_c"), None);
+    assert_eq!(explanation.reason_before("node_d", "node_a"), None);
+}
+
+#[test]
+fn explain_reasons_follow_execution_flow() {
+    let explanation = explain_graph(&build_branch_graph(), &TestMetadataProvider::comprehensive());
+    assert_eq!(
+        explanation.reason_before("start", "print_false"),
+        Some(OrderReason::ExecutionFlow {
+            event: "start".into(),
+            chain: chain(&["start", "branch_1", "print_false"]),
+        })
+    );
+    assert_eq!(explanation.reason_before("print_true", "print_false"), None);
+    assert_eq!(explanation.reason_before("start", "start"), None);
+    assert_eq!(explanation.reason_before("start", "missing"), None);
+}
+
+// ============================================================================
+// Output
+// ============================================================================
+
+#[test]
+fn explain_renders_text() {
+    let explanation = explain_graph(&build_branch_graph(), &TestMetadataProvider::comprehensive());
+    assert_eq!(
+        explanation.to_text(),
+        "Event start\n  start\n    branch_1 (start.exec)\n      print_true (branch_1.True)\n      print_false (branch_1.False)\n\nPure evaluation order\n"
+    );
+
+    let text = explain_graph(&build_loop_graph(), &TestMetadataProvider::comprehensive()).to_text();
+    assert!(text.contains("  loops back: step_1.then -> loop_1\n"), "{}", text);
+
+    let math = TestMetadataProvider::with_math_nodes();
+    let text = explain_graph(&build_linear_chain(2, &math), &math).to_text();
+    assert!(text.ends_with("  1. node_0\n  2. node_1 (a <- node_0.result)\n"), "{}", text);
+}
+
+#[test]
+fn explain_serializes() {
+    let explanation = explain_graph(&build_branch_graph(), &TestMetadataProvider::comprehensive());
+    let json = serde_json::to_value(&explanation).unwrap();
+    assert!(json["events"][0]["steps"][0].get("reached_by").is_none());
+    assert_eq!(json["events"][0]["steps"][1]["reached_by"]["source_pin"], "exec");
+
+    let loaded: ExecutionExplanation = serde_json::from_value(json).unwrap();
+    assert_eq!(loaded, explanation);
+
+    let reason = serde_json::to_value(OrderReason::DataFlow { chain: chain(&["a", "b"]) }).unwrap();
+    assert_eq!(reason, serde_json::json!({ "data_flow": { "chain": ["a", "b"] } }));
+}
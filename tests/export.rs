@@ -0,0 +1,154 @@
+//! Tests for DOT and Mermaid export.
+
+mod common;
+
+use common::*;
+use graphy::core::{GraphComment, GraphGroup};
+use graphy::export::{to_dot, to_mermaid, Direction, ExportOptions};
+use graphy::*;
+
+/// Branch graph plus a pure `add` feeding the branch condition
+fn build_export_graph() -> GraphDescription {
+    let mut graph = build_branch_graph();
+    let mut add = NodeInstance::new("add_1", "add", Position::new(100.0, 200.0));
+    add.add_input_pin("a", DataType::Typed("f64".into()));
+    add.add_output_pin("result", DataType::Typed("f64".into()));
+    graph.add_node(add);
+    graph.add_connection(Connection::data("add_1", "result", "branch_1", "condition"));
+    graph
+}
+
+// ===========================================================================
+// Node kinds
+// ===========================================================================
+
+#[test]
+fn export_node_kind_inferred_from_pins() {
+    let graph = build_export_graph();
+    let options = ExportOptions::new();
+
+    assert_eq!(options.node_kind(&graph.nodes["start"]), Some(NodeTypes::event));
+    assert_eq!(options.node_kind(&graph.nodes["branch_1"]), Some(NodeTypes::control_flow));
+    assert_eq!(options.node_kind(&graph.nodes["print_true"]), Some(NodeTypes::fn_));
+    assert_eq!(options.node_kind(&graph.nodes["add_1"]), Some(NodeTypes::pure));
+    assert_eq!(options.node_kind(&NodeInstance::new("x", "unknown", Position::zero())), None);
+}
+
+#[test]
+fn export_node_kind_prefers_metadata() {
+    let provider = TestMetadataProvider::comprehensive();
+    let options = ExportOptions::new().with_metadata_provider(&provider);
+
+    // No pins, but the provider knows `add` is pure
+    let node = NodeInstance::new("x", "add", Position::zero());
+    assert_eq!(options.node_kind(&node), Some(NodeTypes::pure));
+}
+
+// ===========================================================================
+// DOT
+// ===========================================================================
+
+#[test]
+fn dot_styles_nodes_and_edges() {
+    let graph = build_export_graph();
+    let dot = to_dot(&graph, &ExportOptions::new());
+
+    assert!(dot.starts_with("digraph \"branch_graph\" {\n    rankdir=LR;\n"), "{}", dot);
+    assert!(dot.contains("\"add_1\" [label=\"add_1\\nadd\", fillcolor=\"#A5D6A7\"];"), "{}", dot);
+    assert!(dot.contains("\"start\" -> \"branch_1\" [style=dashed, taillabel=\"exec\", headlabel=\"exec_in\"];"));
+    assert!(dot.contains("\"add_1\" -> \"branch_1\" [taillabel=\"result\", headlabel=\"condition\"];"));
+    assert!(dot.ends_with("}\n"));
+}
+
+#[test]
+fn dot_connection_label_and_color() {
+    let mut graph = build_export_graph();
+    graph.connections.retain(|c| c.source_node != "add_1");
+    graph.add_connection(
+        Connection::data("add_1", "result", "branch_1", "condition")
+            .with_label("is_ready")
+            .with_color("#ff8800"),
+    );
+    let dot = to_dot(&graph, &ExportOptions::new().with_pins(false));
+
+    assert!(dot.contains("\"add_1\" -> \"branch_1\" [label=\"is_ready\", color=\"#ff8800\"];"), "{}", dot);
+}
+
+#[test]
+fn dot_options_change_output() {
+    let graph = build_export_graph();
+    let options = ExportOptions::new()
+        .with_direction(Direction::TopToBottom)
+        .with_pins(false)
+        .with_properties(true);
+    let dot = to_dot(&graph, &options);
+
+    assert!(dot.contains("rankdir=TB;"));
+    assert!(dot.contains("\"add_1\" -> \"branch_1\";"), "{}", dot);
+    assert!(dot.contains("message = \\\"true branch\\\""), "{}", dot);
+}
+
+#[test]
+fn dot_clusters_groups_and_comments() {
+    let mut graph = build_export_graph();
+    let mut group = GraphGroup::new("g1", "Printing").with_color("#ff0000");
+    group.node_ids = vec!["print_true".into(), "print_false".into()];
+    graph.add_group(group).unwrap();
+    graph.comments.push(GraphComment {
+        text: "Math".to_string(),
+        position: Position::new(50.0, 150.0),
+        size: (100.0, 100.0),
+        attached_node_ids: Vec::new(),
+    });
+
+    let dot = to_dot(&graph, &ExportOptions::new());
+    let group_start = dot.find("subgraph cluster_0 {").unwrap();
+    let comment_start = dot.find("subgraph cluster_1 {").unwrap();
+    assert!(dot[group_start..comment_start].contains("label=\"Printing\";"));
+    assert!(dot[group_start..comment_start].contains("\"print_false\" ["));
+    assert!(dot[comment_start..].contains("label=\"Math\";"));
+    assert_eq!(dot.matches("\"add_1\" [").count(), 1);
+
+    let flat = to_dot(&graph, &ExportOptions::new().with_clusters(false));
+    assert!(!flat.contains("subgraph"));
+}
+
+#[test]
+fn dot_escapes_quotes() {
+    let mut graph = GraphDescription::new("say \"hi\"");
+    graph.add_node(NodeInstance::new("a\"b", "print", Position::zero()));
+    let dot = to_dot(&graph, &ExportOptions::new());
+    assert!(dot.contains("digraph \"say \\\"hi\\\"\""));
+    assert!(dot.contains("\"a\\\"b\" [label=\"a\\\"b\\nprint\""), "{}", dot);
+}
+
+// ===========================================================================
+// Mermaid
+// ===========================================================================
+
+#[test]
+fn mermaid_aliases_nodes_and_styles_edges() {
+    let graph = build_export_graph();
+    let mermaid = to_mermaid(&graph, &ExportOptions::new());
+
+    // Sorted IDs: add_1, branch_1, print_false, print_true, start
+    assert!(mermaid.starts_with("flowchart LR\n"));
+    assert!(mermaid.contains("    n0[\"add_1<br/>add\"]\n"), "{}", mermaid);
+    assert!(mermaid.contains("    n4 -. \"exec → exec_in\" .-> n1\n"), "{}", mermaid);
+    assert!(mermaid.contains("    n0 -- \"result → condition\" --> n1\n"), "{}", mermaid);
+    assert!(mermaid.contains("    class n0 pure\n"));
+    assert!(mermaid.contains("    class n2,n3 function\n"));
+}
+
+#[test]
+fn mermaid_subgraphs_and_plain_edges() {
+    let mut graph = build_export_graph();
+    let mut group = GraphGroup::new("g1", "Printing");
+    group.node_ids = vec!["print_true".into()];
+    graph.add_group(group).unwrap();
+
+    let mermaid = to_mermaid(&graph, &ExportOptions::new().with_pins(false));
+    assert!(mermaid.contains("    subgraph cluster_0 [\"Printing\"]\n        n3[\"print_true<br/>print_string\"]\n    end\n"));
+    assert!(mermaid.contains("    n1 -.-> n3\n"));
+    assert!(mermaid.contains("    n0 --> n1\n"));
+}
@@ -0,0 +1,189 @@
+//! Tests for external function and type declarations.
+
+mod common;
+
+use common::*;
+use graphy::core::{ExternalDecl, ExternalIssue, ExternalKind, ExternalRegistry};
+use graphy::generation::{compile, compile_with_options, external_imports, CompileOptions};
+use graphy::utils::diff::GraphDiff;
+use graphy::*;
+
+fn play_sound() -> ExternalDecl {
+    ExternalDecl::function("engine::audio", "play_sound")
+        .with_param("SoundId")
+        .with_param("f32")
+}
+
+fn engine_registry() -> ExternalRegistry {
+    ExternalRegistry::new()
+        .with_external(play_sound())
+        .with_external(ExternalDecl::type_("engine::audio", "SoundId"))
+        .with_external(ExternalDecl::function("engine::time", "now").with_return_type("f64"))
+}
+
+fn graph_with_externals() -> GraphDescription {
+    let mut graph = GraphDescription::new("door");
+    graph.add_external(play_sound());
+    graph.add_external(ExternalDecl::type_("engine::audio", "SoundId"));
+    graph
+}
+
+// ============================================================================
+// Declarations
+// ============================================================================
+
+#[test]
+fn externals_describe_functions_and_types() {
+    let external = play_sound().with_return_type("bool");
+    assert_eq!(external.kind, ExternalKind::Function);
+    assert_eq!(external.path(), "engine::audio::play_sound");
+    assert_eq!(external.signature(), "fn(SoundId, f32) -> bool");
+
+    let sound = ExternalDecl::type_("engine::audio", "SoundId");
+    assert_eq!(sound.signature(), "type");
+    assert_eq!(ExternalDecl::function("", "print").path(), "print");
+    assert_eq!(ExternalDecl::function("", "print").signature(), "fn()");
+}
+
+#[test]
+fn externals_are_keyed_by_path() {
+    let mut graph = graph_with_externals();
+    assert!(graph.add_external(play_sound().with_return_type("bool")).is_some());
+    assert_eq!(graph.externals.len(), 2);
+    assert_eq!(graph.externals[0].return_type, Some("bool".into()));
+    assert!(graph.external("engine::audio::SoundId").is_some());
+
+    assert!(graph.remove_external("engine::audio::SoundId").is_some());
+    assert!(graph.remove_external("engine::audio::SoundId").is_none());
+    assert_eq!(graph.externals.len(), 1);
+}
+
+// ============================================================================
+// Validation
+// ============================================================================
+
+#[test]
+fn externals_check_against_registry() {
+    let registry = engine_registry();
+    assert_eq!(registry.len(), 3);
+    assert!(graph_with_externals().check_externals(&registry).is_empty());
+
+    let mut graph = graph_with_externals();
+    graph.add_external(ExternalDecl::function("engine::audio", "stop_sound"));
+    graph.add_external(ExternalDecl::function("engine::time", "now").with_return_type("f32"));
+    let issues = graph.check_externals(&registry);
+    assert_eq!(
+        issues,
+        vec![
+            ExternalIssue::Unknown {
+                path: "engine::audio::stop_sound".into()
+            },
+            ExternalIssue::Mismatch {
+                path: "engine::time::now".into(),
+                declared: "fn() -> f32".into(),
+                provided: "fn() -> f64".into(),
+            },
+        ]
+    );
+    assert_eq!(
+        issues[0].to_string(),
+        "External engine::audio::stop_sound is not provided by the engine"
+    );
+    assert_eq!(
+        issues[1].to_string(),
+        "External engine::time::now is declared as fn() -> f32, but the engine provides fn() -> f64"
+    );
+}
+
+#[test]
+fn externals_kind_must_match() {
+    let registry = engine_registry();
+    let mut graph = GraphDescription::new("door");
+    graph.add_external(ExternalDecl::function("engine::audio", "SoundId"));
+    assert!(matches!(
+        &graph.check_externals(&registry)[..],
+        [ExternalIssue::Mismatch { provided, .. }] if provided == "type"
+    ));
+}
+
+#[test]
+fn externals_compile_fails_on_unresolved() {
+    let provider = TestMetadataProvider::comprehensive();
+    let mut graph = build_branch_graph();
+    graph.add_external(ExternalDecl::function("engine::audio", "stop_sound"));
+
+    // Unchecked without a registry
+    assert!(compile(&graph, &provider, &mut NoopGenerator::new()).is_ok());
+
+    let options = CompileOptions::new().with_externals(engine_registry());
+    let error = compile_with_options(&graph, &provider, &mut NoopGenerator::new(), &options).unwrap_err();
+    assert!(
+        matches!(&error, GraphyError::UnknownExternal { path } if path == "engine::audio::stop_sound"),
+        "{:?}",
+        error
+    );
+}
+
+// ============================================================================
+// Code generation
+// ============================================================================
+
+#[test]
+fn externals_become_use_statements() {
+    let mut graph = build_branch_graph();
+    graph.add_external(play_sound());
+    graph.add_external(ExternalDecl::type_("engine::audio", "SoundId"));
+    graph.add_external(ExternalDecl::function("", "print"));
+
+    let options = CompileOptions::new().with_externals(engine_registry().with_external(ExternalDecl::function("", "print")));
+    let output =
+        compile_with_options(&graph, &TestMetadataProvider::comprehensive(), &mut NoopGenerator::new(), &options).unwrap();
+    assert_eq!(output.code, "use engine::audio::SoundId;\nuse engine::audio::play_sound;\n\n");
+}
+
+#[test]
+fn externals_absent_emit_nothing() {
+    assert_eq!(external_imports(&GraphDescription::new("empty")), "");
+    let code = compile(&build_branch_graph(), &TestMetadataProvider::comprehensive(), &mut NoopGenerator::new()).unwrap();
+    assert!(code.is_empty(), "{}", code);
+}
+
+// ============================================================================
+// Persistence and editing
+// ============================================================================
+
+#[test]
+fn externals_serialize() {
+    let graph = graph_with_externals();
+    let json = serde_json::to_value(&graph).unwrap();
+    assert_eq!(json["externals"][1], serde_json::json!({ "name": "SoundId", "module": "engine::audio", "kind": "type" }));
+
+    let loaded: GraphDescription = serde_json::from_value(json).unwrap();
+    assert_eq!(loaded.externals, graph.externals);
+
+    let json = serde_json::to_value(GraphDescription::new("plain")).unwrap();
+    assert!(json.get("externals").is_none());
+}
+
+#[test]
+fn externals_are_part_of_semantics() {
+    let plain = build_branch_graph();
+    let mut with_externals = plain.clone();
+    with_externals.add_external(play_sound());
+    assert!(!plain.semantically_equals(&with_externals));
+    assert_ne!(plain.content_hash(), with_externals.content_hash());
+}
+
+#[test]
+fn externals_diff_and_undo() {
+    let old = GraphDescription::new("door");
+    let new = graph_with_externals();
+    let diff = GraphDiff::compute(&old, &new);
+    assert!(diff.externals.is_some());
+
+    let mut graph = old.clone();
+    diff.apply(&mut graph).unwrap();
+    assert_eq!(graph.externals, new.externals);
+    diff.invert().apply(&mut graph).unwrap();
+    assert!(graph.externals.is_empty());
+}
@@ -0,0 +1,187 @@
+//! Tests for reading and writing graphs in other file formats.
+
+mod common;
+
+use common::*;
+use graphy::core::{GraphComment, GraphGroup, VariableDecl};
+use graphy::io::{load_graph, load_graph_file, save_graph, save_graph_file, Format};
+use graphy::*;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// A graph using most of the document model: every property kind,
+/// comments, groups, variables, and connections without labels
+fn build_rich_graph() -> GraphDescription {
+    let mut graph = build_diamond_graph();
+    let node = graph.nodes.get_mut("node_a").unwrap();
+    node.set_property("label", PropertyValue::String("a: \"quoted\"\nline".into()));
+    node.set_property("enabled", PropertyValue::Boolean(false));
+    node.set_property("offset", PropertyValue::Vector2(0.5, -1.25));
+    node.set_property("scale", PropertyValue::Vector3(1.0, 2.0, 3.0));
+    node.set_property("tint", PropertyValue::Color(0.1, 0.2, 0.3, 1.0));
+    node.set_property("count", PropertyValue::Integer(-42));
+    node.set_property(
+        "items",
+        PropertyValue::Array(vec![PropertyValue::Integer(1), PropertyValue::String("two".into())]),
+    );
+    node.set_property(
+        "lookup",
+        PropertyValue::Map(HashMap::from([("key".to_string(), PropertyValue::Number(3.5))])),
+    );
+
+    graph.comments.push(GraphComment {
+        text: "Sums things".into(),
+        position: Position::new(10.0, 20.0),
+        size: (100.0, 40.0),
+        attached_node_ids: Vec::new(),
+    });
+    graph
+        .add_group(GraphGroup::new("group_1", "Math").with_color("#ff0000"))
+        .unwrap();
+    graph.add_variable("score", VariableDecl::new("i64").with_default(PropertyValue::Integer(0)));
+    graph
+}
+
+/// Serialized form, for comparing graphs
+fn document(graph: &GraphDescription) -> Value {
+    serde_json::to_value(graph).unwrap()
+}
+
+fn assert_round_trips(format: Format) {
+    let graph = build_rich_graph();
+    let bytes = save_graph(&graph, format).unwrap();
+    assert_eq!(document(&load_graph(&bytes, format).unwrap()), document(&graph), "{}", format);
+}
+
+// ===========================================================================
+// Formats
+// ===========================================================================
+
+#[test]
+fn formats_are_found_by_extension() {
+    assert_eq!(Format::from_extension("yml"), Some(Format::Yaml));
+    assert_eq!(Format::from_extension("MPK"), Some(Format::MessagePack));
+    assert_eq!(Format::from_extension("txt"), None);
+    assert_eq!(Format::from_path("graphs/level.toml"), Some(Format::Toml));
+    assert_eq!(Format::from_path("graphs/level"), None);
+
+    for format in Format::ALL {
+        assert_eq!(Format::from_extension(format.extension()), Some(format));
+    }
+    assert!(Format::Json.is_available());
+    assert!(Format::MessagePack.is_binary());
+    assert_eq!(Format::Yaml.feature(), Some("yaml"));
+}
+
+#[test]
+fn json_round_trips() {
+    assert_round_trips(Format::Json);
+}
+
+#[test]
+fn unavailable_formats_are_errors() {
+    for format in Format::ALL.into_iter().filter(|format| !format.is_available()) {
+        let error = save_graph(&build_rich_graph(), format).unwrap_err();
+        assert!(error.to_string().contains(format.feature().unwrap()), "{}", error);
+        assert!(load_graph(b"", format).is_err());
+    }
+}
+
+#[test]
+fn loading_migrates_old_graphs() {
+    let v1 = json!({
+        "metadata": { "name": "legacy" },
+        "nodes": {
+            "a": {
+                "id": "a",
+                "node_type": "math.add",
+                "position": { "x": 0.0, "y": 0.0 },
+                "inputs": [{ "id": "x", "pin": { "id": "x", "data_type": "Number", "pin_type": "Input" } }],
+                "outputs": []
+            }
+        }
+    });
+
+    for format in Format::ALL.into_iter().filter(Format::is_available) {
+        let bytes = format.write_value(&v1).unwrap();
+        let graph = load_graph(&bytes, format).unwrap();
+        assert_eq!(graph.format_version, graphy::core::CURRENT_FORMAT_VERSION, "{}", format);
+        assert_eq!(graph.nodes["a"].inputs[0].pin.data_type, DataType::Typed("f64".into()));
+    }
+}
+
+#[test]
+fn files_use_the_format_of_their_extension() {
+    let dir = std::env::temp_dir().join(format!("graphy_formats_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let graph = build_rich_graph();
+
+    for format in Format::ALL.into_iter().filter(Format::is_available) {
+        let path = dir.join(format!("graph.{}", format.extension()));
+        save_graph_file(&graph, &path).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), save_graph(&graph, format).unwrap());
+        assert_eq!(document(&load_graph_file(&path).unwrap()), document(&graph));
+    }
+
+    assert!(matches!(
+        save_graph_file(&graph, dir.join("graph.txt")),
+        Err(GraphyError::Serialization(_))
+    ));
+    assert!(matches!(load_graph_file(dir.join("missing.json")), Err(GraphyError::Io(_))));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn other_documents_use_any_format() {
+    let provider = TestMetadataProvider::comprehensive();
+    for format in Format::ALL.into_iter().filter(Format::is_available) {
+        let bytes = format.serialize(&provider.metadata).unwrap();
+        let registry: NodeRegistry = format.deserialize(&bytes).unwrap();
+        assert_eq!(registry.len(), provider.metadata.len(), "{}", format);
+    }
+}
+
+// ===========================================================================
+// Cross-format round trips
+// ===========================================================================
+
+#[cfg(feature = "yaml")]
+#[test]
+fn yaml_round_trips() {
+    assert_round_trips(Format::Yaml);
+
+    let yaml = String::from_utf8(save_graph(&build_rich_graph(), Format::Yaml).unwrap()).unwrap();
+    assert!(yaml.contains("name: diamond"), "{}", yaml);
+}
+
+#[cfg(feature = "msgpack")]
+#[test]
+fn msgpack_round_trips() {
+    assert_round_trips(Format::MessagePack);
+
+    // Much smaller than the pretty-printed JSON
+    let graph = build_rich_graph();
+    let packed = save_graph(&graph, Format::MessagePack).unwrap();
+    assert!(packed.len() < save_graph(&graph, Format::Json).unwrap().len());
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn toml_round_trips() {
+    assert_round_trips(Format::Toml);
+    assert!(Format::Toml.serialize(&[1, 2, 3]).is_err());
+}
+
+#[test]
+fn graphs_survive_every_conversion() {
+    let graph = build_rich_graph();
+    let formats: Vec<Format> = Format::ALL.into_iter().filter(Format::is_available).collect();
+
+    for &from in &formats {
+        for &to in &formats {
+            let loaded = load_graph(&save_graph(&graph, from).unwrap(), from).unwrap();
+            let converted = load_graph(&save_graph(&loaded, to).unwrap(), to).unwrap();
+            assert_eq!(document(&converted), document(&graph), "{} -> {}", from, to);
+        }
+    }
+}
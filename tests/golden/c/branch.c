@@ -0,0 +1,9 @@
+#include "canonical.h"
+
+void canonical_start(void) {
+    if (true) {
+        print(1.0);
+    } else {
+        print(0.0);
+    }
+}
@@ -0,0 +1,6 @@
+#include "canonical.h"
+
+void canonical_start(void) {
+    int64_t node_node_a_result = add(1.0, 2.0);
+    print(add(multiply(node_node_a_result, 2.0), multiply(node_node_a_result, 3.0)));
+}
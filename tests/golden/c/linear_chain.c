@@ -0,0 +1,5 @@
+#include "canonical.h"
+
+void canonical_start(void) {
+    print(add(add(add(add(0.0, 1.0), 1.0), 1.0), 1.0));
+}
@@ -0,0 +1,8 @@
+#include "canonical.h"
+
+void canonical_start(void) {
+    for (int64_t i = 0.0; i < 3.0; i++) {
+        print(multiply(2.0, 21.0));
+    }
+        print(3.0);
+}
@@ -1,5 +1,8 @@
-//! Tests for GraphDescription, GraphMetadata, GraphComment, and graph manipulation.
+//! Tests for GraphDescription, GraphMetadata, GraphComment, GraphGroup, and graph manipulation.
 
+mod common;
+
+use common::*;
 use graphy::*;
 
 // ===========================================================================
@@ -147,6 +150,103 @@ fn graph_add_multiple_connections() {
     assert_eq!(graph.connections.len(), 3);
 }
 
+// ===========================================================================
+// GraphDescription - Mutation helpers
+// ===========================================================================
+
+/// `a -> b -> c` data chain plus `a -> c`
+fn build_chain() -> GraphDescription {
+    let mut graph = GraphDescription::new("chain");
+    for id in ["a", "b", "c"] {
+        let mut node = NodeInstance::new(id, "add", Position::zero());
+        node.add_input_pin("a", DataType::Typed("f64".into()));
+        node.add_input_pin("b", DataType::Typed("f64".into()));
+        node.add_output_pin("result", DataType::Typed("f64".into()));
+        graph.add_node(node);
+    }
+    graph.add_connection(Connection::data("a", "result", "b", "a"));
+    graph.add_connection(Connection::data("b", "result", "c", "a"));
+    graph.add_connection(Connection::data("a", "result", "c", "b"));
+    graph
+}
+
+#[test]
+fn graph_remove_node_drops_connections_and_group_entry() {
+    let mut graph = build_chain();
+    let mut group = GraphGroup::new("g", "Group");
+    group.node_ids = vec!["a".into(), "b".into()];
+    graph.add_group(group).unwrap();
+
+    let removed = graph.remove_node("b").unwrap();
+    assert_eq!(removed.id, "b");
+    assert_eq!(graph.connections, vec![Connection::data("a", "result", "c", "b")]);
+    assert_eq!(graph.get_group("g").unwrap().node_ids, vec!["a".to_string()]);
+
+    assert!(graph.remove_node("b").is_none());
+    assert_eq!(graph.connections.len(), 1);
+}
+
+#[test]
+fn graph_remove_nodes_in_batch() {
+    let mut graph = build_chain();
+    let removed = graph.remove_nodes(&["a", "missing", "c"]);
+    let ids: Vec<&str> = removed.iter().map(|n| n.id.as_str()).collect();
+    assert_eq!(ids, ["a", "c"]);
+    assert!(graph.connections.is_empty());
+    assert_eq!(graph.nodes.len(), 1);
+}
+
+#[test]
+fn graph_remove_connection_and_disconnect_pin() {
+    let mut graph = build_chain();
+    assert!(graph.remove_connection("a", "result", "b", "a"));
+    assert!(!graph.remove_connection("a", "result", "b", "a"));
+    assert_eq!(graph.connections.len(), 2);
+
+    let removed = graph.disconnect_pin("c", "b");
+    assert_eq!(removed, vec![Connection::data("a", "result", "c", "b")]);
+    assert_eq!(graph.connections, vec![Connection::data("b", "result", "c", "a")]);
+}
+
+#[test]
+fn graph_replace_node_renames_pins() {
+    let mut graph = build_chain();
+    graph.nodes.get_mut("b").unwrap().set_property("b", PropertyValue::Number(2.0));
+
+    let dropped = graph
+        .replace_node("b", "negate", &[("b", "value"), ("result", "negated")])
+        .unwrap();
+    assert_eq!(dropped, vec![Connection::data("a", "result", "b", "a")]);
+
+    let node = &graph.nodes["b"];
+    assert_eq!(node.node_type, "negate");
+    let inputs: Vec<&str> = node.inputs.iter().map(|p| p.id.as_str()).collect();
+    assert_eq!(inputs, ["value"]);
+    assert_eq!(node.outputs[0].pin.id, "negated");
+    assert_eq!(node.outputs[0].pin.name, "negated");
+    assert_eq!(node.get_property("value"), Some(&PropertyValue::Number(2.0)));
+    assert!(graph.connections.contains(&Connection::data("b", "negated", "c", "a")));
+
+    assert!(matches!(
+        graph.replace_node("missing", "negate", &[]),
+        Err(GraphyError::NodeNotFound(_))
+    ));
+}
+
+#[test]
+fn graph_rewire_moves_outgoing_connections() {
+    let mut graph = build_chain();
+    let moved = graph.rewire(("a", "result"), ("b", "result")).unwrap();
+    assert_eq!(moved, 2);
+    assert!(graph.connections.iter().all(|c| c.source_node == "b"));
+
+    assert!(matches!(
+        graph.rewire(("b", "result"), ("missing", "result")),
+        Err(GraphyError::NodeNotFound(_))
+    ));
+    assert!(graph.connections.iter().all(|c| c.source_node == "b"));
+}
+
 // ===========================================================================
 // GraphDescription - Comments
 // ===========================================================================
@@ -159,6 +259,7 @@ fn graph_comments() {
         text: "This is a math section".to_string(),
         position: Position::new(100.0, 50.0),
         size: (300.0, 100.0),
+        attached_node_ids: Vec::new(),
     });
 
     assert_eq!(graph.comments.len(), 1);
@@ -168,6 +269,111 @@ fn graph_comments() {
     assert_eq!(graph.comments[0].size.1, 100.0);
 }
 
+// ===========================================================================
+// GraphDescription - Groups
+// ===========================================================================
+
+#[test]
+fn graph_add_and_get_group() {
+    let mut graph = graph_with_nodes(&["a", "b"]);
+    let mut group = GraphGroup::new("g1", "Math").with_color("#ff0000");
+    group.node_ids = vec!["a".into(), "b".into()];
+    graph.add_group(group).unwrap();
+
+    let group = graph.get_group("g1").unwrap();
+    assert_eq!(group.title, "Math");
+    assert_eq!(group.color.as_deref(), Some("#ff0000"));
+    assert_eq!(graph.group_of("b").unwrap().id, "g1");
+}
+
+#[test]
+fn graph_add_group_duplicate_id_fails() {
+    let mut graph = GraphDescription::new("groups");
+    graph.add_group(GraphGroup::new("g1", "One")).unwrap();
+    assert!(matches!(graph.add_group(GraphGroup::new("g1", "Two")), Err(GraphyError::Custom(_))));
+}
+
+#[test]
+fn graph_add_group_with_missing_node_fails() {
+    let mut graph = graph_with_nodes(&["a"]);
+    let mut group = GraphGroup::new("g1", "Bad");
+    group.node_ids = vec!["a".into(), "ghost".into()];
+
+    assert!(matches!(graph.add_group(group), Err(GraphyError::NodeNotFound(id)) if id == "ghost"));
+    assert!(graph.groups.is_empty());
+}
+
+#[test]
+fn graph_node_belongs_to_one_group() {
+    let mut graph = graph_with_nodes(&["a"]);
+    graph.add_group(GraphGroup::new("g1", "One")).unwrap();
+    graph.add_group(GraphGroup::new("g2", "Two")).unwrap();
+
+    graph.add_node_to_group("g1", "a").unwrap();
+    graph.add_node_to_group("g2", "a").unwrap();
+
+    assert!(graph.get_group("g1").unwrap().node_ids.is_empty());
+    assert_eq!(graph.get_group("g2").unwrap().node_ids, vec!["a"]);
+}
+
+#[test]
+fn graph_add_node_to_group_errors() {
+    let mut graph = graph_with_nodes(&["a"]);
+    graph.add_group(GraphGroup::new("g1", "One")).unwrap();
+
+    assert!(matches!(graph.add_node_to_group("g1", "ghost"), Err(GraphyError::NodeNotFound(_))));
+    assert!(matches!(graph.add_node_to_group("nope", "a"), Err(GraphyError::Custom(_))));
+}
+
+#[test]
+fn graph_remove_node_from_group_and_remove_group() {
+    let mut graph = graph_with_nodes(&["a"]);
+    graph.add_group(GraphGroup::new("g1", "One")).unwrap();
+    graph.add_node_to_group("g1", "a").unwrap();
+
+    assert!(graph.remove_node_from_group("g1", "a"));
+    assert!(!graph.remove_node_from_group("g1", "a"));
+
+    assert!(graph.remove_group("g1").is_some());
+    assert!(graph.remove_group("g1").is_none());
+    assert!(graph.get_node("a").is_some());
+}
+
+#[test]
+fn graph_group_to_subgraph_keeps_internal_connections() {
+    let mut graph = graph_with_nodes(&["a", "b", "outside"]);
+    graph.add_connection(Connection::data("a", "result", "b", "a"));
+    graph.add_connection(Connection::data("b", "result", "outside", "a"));
+
+    let mut group = GraphGroup::new("g1", "Pair");
+    group.node_ids = vec!["a".into(), "b".into()];
+    graph.add_group(group).unwrap();
+
+    let subgraph = graph.group_to_subgraph("g1").unwrap();
+    assert_eq!(subgraph.metadata.name, "Pair");
+    assert_eq!(subgraph.nodes.len(), 2);
+    assert_eq!(subgraph.connections, vec![Connection::data("a", "result", "b", "a")]);
+    assert_eq!(graph.nodes.len(), 3);
+
+    assert!(graph.group_to_subgraph("missing").is_err());
+}
+
+#[test]
+fn graph_groups_serialization() {
+    let mut graph = graph_with_nodes(&["a"]);
+    let mut group = GraphGroup::new("g1", "One");
+    group.node_ids.push("a".into());
+    graph.add_group(group).unwrap();
+
+    let json = serde_json::to_string(&graph).unwrap();
+    let restored: GraphDescription = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.groups, graph.groups);
+
+    // Graphs without groups don't serialize the field
+    let json = serde_json::to_string(&GraphDescription::new("empty")).unwrap();
+    assert!(!json.contains("groups"));
+}
+
 // ===========================================================================
 // GraphDescription - Clone
 // ===========================================================================
@@ -0,0 +1,183 @@
+//! Tests for graph-level inputs/outputs and the graph.input/graph.output pseudo-nodes.
+
+mod common;
+
+use common::*;
+use graphy::core::{GRAPH_INPUT_NODE_TYPE, GRAPH_OUTPUT_NODE_TYPE};
+use graphy::generation::graph_function_signature;
+use graphy::utils::diff::GraphDiff;
+use graphy::*;
+
+/// fn scale(value: i64, factor: i64) -> i64 { value * factor }
+fn build_scale_graph() -> GraphDescription {
+    let mut graph = GraphDescription::new("scale");
+    graph.add_graph_input("value", "i64");
+    graph.add_graph_input("factor", "i64");
+    graph.add_graph_output("result", "i64");
+
+    let inputs = graph.create_graph_input_node("inputs", Position::zero());
+    graph.add_node(inputs);
+    let outputs = graph.create_graph_output_node("outputs", Position::new(400.0, 0.0));
+    graph.add_node(outputs);
+
+    let mut mul = NodeInstance::new("mul", "multiply", Position::new(200.0, 0.0));
+    mul.add_input_pin("a", DataType::Typed("i64".into()));
+    mul.add_input_pin("b", DataType::Typed("i64".into()));
+    mul.add_output_pin("result", DataType::Typed("i64".into()));
+    graph.add_node(mul);
+
+    graph.add_connection(Connection::data("inputs", "value", "mul", "a"));
+    graph.add_connection(Connection::data("inputs", "factor", "mul", "b"));
+    graph.add_connection(Connection::data("mul", "result", "outputs", "result"));
+
+    graph
+}
+
+// ===========================================================================
+// Interface declaration
+// ===========================================================================
+
+#[test]
+fn graph_interface_declaration() {
+    let graph = build_scale_graph();
+    assert!(graph.is_function_graph());
+    assert_eq!(graph.graph_inputs.len(), 2);
+    assert_eq!(graph.graph_outputs[0].type_info.type_string, "i64");
+    assert!(!GraphDescription::new("plain").is_function_graph());
+}
+
+#[test]
+fn graph_interface_pseudo_nodes_have_pins() {
+    let graph = build_scale_graph();
+
+    let inputs = graph.get_node("inputs").unwrap();
+    assert_eq!(inputs.node_type, GRAPH_INPUT_NODE_TYPE);
+    assert!(inputs.is_graph_input());
+    let pins: Vec<&str> = inputs.outputs.iter().map(|p| p.id.as_str()).collect();
+    assert_eq!(pins, vec!["value", "factor"]);
+
+    let outputs = graph.get_node("outputs").unwrap();
+    assert_eq!(outputs.node_type, GRAPH_OUTPUT_NODE_TYPE);
+    assert!(outputs.is_graph_output());
+    assert_eq!(outputs.inputs[0].id, "result");
+}
+
+// ===========================================================================
+// DataResolver
+// ===========================================================================
+
+#[test]
+fn data_resolver_graph_input_source() {
+    let graph = build_scale_graph();
+    let provider = TestMetadataProvider::with_math_nodes();
+    let resolver = DataResolver::build(&graph, &provider).unwrap();
+
+    match resolver.get_input_source("mul", "a").unwrap() {
+        DataSource::GraphInput { name } => assert_eq!(name, "value"),
+        other => panic!("expected GraphInput, got {:?}", other),
+    }
+}
+
+#[test]
+fn data_resolver_graph_input_source_parallel() {
+    let graph = build_scale_graph();
+    let provider = TestMetadataProvider::with_math_nodes();
+    let resolver = DataResolver::build_parallel(&graph, &provider).unwrap();
+
+    assert!(matches!(
+        resolver.get_input_source("mul", "b"),
+        Some(DataSource::GraphInput { name }) if name == "factor"
+    ));
+}
+
+#[test]
+fn data_resolver_graph_output_source() {
+    let graph = build_scale_graph();
+    let provider = TestMetadataProvider::with_math_nodes();
+    let resolver = DataResolver::build(&graph, &provider).unwrap();
+
+    match resolver.get_graph_output_source(&graph, "result").unwrap() {
+        DataSource::Connection { source_node_id, source_pin } => {
+            assert_eq!(source_node_id, "mul");
+            assert_eq!(source_pin, "result");
+        }
+        other => panic!("expected Connection, got {:?}", other),
+    }
+    assert!(resolver.get_graph_output_source(&graph, "missing").is_none());
+}
+
+#[test]
+fn data_resolver_pseudo_nodes_not_in_pure_order() {
+    let graph = build_scale_graph();
+    let provider = TestMetadataProvider::with_math_nodes();
+    let resolver = DataResolver::build(&graph, &provider).unwrap();
+
+    assert_eq!(resolver.get_pure_evaluation_order(), &["mul"]);
+}
+
+// ===========================================================================
+// Code generation
+// ===========================================================================
+
+#[test]
+fn graph_function_signature_variants() {
+    let graph = build_scale_graph();
+    assert_eq!(
+        graph_function_signature(&graph, "scale"),
+        "fn scale(value: i64, factor: i64) -> i64"
+    );
+
+    let mut multi = GraphDescription::new("split");
+    multi.add_graph_input("v", "(f32, f32)");
+    multi.add_graph_output("x", "f32");
+    multi.add_graph_output("y", "f32");
+    assert_eq!(
+        graph_function_signature(&multi, "split-vec"),
+        "fn split_vec(v: (f32, f32)) -> (f32, f32)"
+    );
+
+    let empty = GraphDescription::new("noop");
+    assert_eq!(graph_function_signature(&empty, "noop"), "fn noop()");
+}
+
+// ===========================================================================
+// Serialization / diff
+// ===========================================================================
+
+#[test]
+fn graph_interface_serialization_roundtrip() {
+    let graph = build_scale_graph();
+    let json = serde_json::to_string(&graph).unwrap();
+    let restored: GraphDescription = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(restored.graph_inputs, graph.graph_inputs);
+    assert_eq!(restored.graph_outputs, graph.graph_outputs);
+}
+
+#[test]
+fn graph_without_interface_loads_from_old_json() {
+    let json = serde_json::to_string(&GraphDescription::new("old")).unwrap();
+    assert!(!json.contains("graph_inputs"));
+
+    let restored: GraphDescription = serde_json::from_str(&json).unwrap();
+    assert!(restored.graph_inputs.is_empty());
+    assert!(restored.graph_outputs.is_empty());
+}
+
+#[test]
+fn graph_diff_tracks_interface_changes() {
+    let old = build_scale_graph();
+    let mut new = old.clone();
+    new.add_graph_output("overflowed", "bool");
+
+    let diff = GraphDiff::compute(&old, &new);
+    assert!(diff.graph_outputs.is_some());
+    assert!(diff.graph_inputs.is_none());
+
+    let mut graph = old.clone();
+    diff.apply(&mut graph).unwrap();
+    assert_eq!(graph.graph_outputs.len(), 2);
+
+    diff.invert().apply(&mut graph).unwrap();
+    assert_eq!(graph.graph_outputs.len(), 1);
+}
@@ -0,0 +1,235 @@
+//! Tests for projects of graphs calling each other.
+
+mod common;
+
+use common::*;
+use graphy::core::{GraphProject, GraphReference, GRAPH_CALL_CATEGORY};
+use graphy::generation::{compile_project, CompileOptions};
+use graphy::*;
+
+/// Graph taking `a` and `b` and returning `result`
+fn interface_graph(name: &str) -> GraphDescription {
+    let mut graph = GraphDescription::new(name);
+    graph.add_graph_input("a", "i64");
+    graph.add_graph_input("b", "i64");
+    graph.add_graph_output("result", "i64");
+    graph
+}
+
+/// Adds a node calling `target` to the graph `caller`
+fn call(project: &mut GraphProject, caller: &str, target: &str, node_id: &str) {
+    let provider = project.provider(TestMetadataProvider::with_math_nodes());
+    project
+        .graph_mut(caller)
+        .unwrap()
+        .add_node_of_type(target, node_id, &provider)
+        .unwrap();
+}
+
+/// `main` calls `mix`, which calls `sum`; `other` calls nothing
+fn build_project() -> GraphProject {
+    let mut project = GraphProject::new("game")
+        .with_graph(interface_graph("sum"))
+        .with_graph(interface_graph("mix"))
+        .with_graph(GraphDescription::new("main"))
+        .with_graph(GraphDescription::new("other"));
+    call(&mut project, "mix", "sum", "sum_1");
+    call(&mut project, "main", "mix", "mix_1");
+    call(&mut project, "main", "sum", "sum_1");
+    project
+}
+
+// ============================================================================
+// Graphs
+// ============================================================================
+
+#[test]
+fn project_keys_graphs_by_name() {
+    let mut project = GraphProject::new("game").with_graph(GraphDescription::new("a"));
+    assert_eq!(project.len(), 1);
+    assert!(project.graph("a").is_some());
+
+    let mut replacement = GraphDescription::new("a");
+    replacement.add_graph_input("x", "f64");
+    assert!(project.add_graph(replacement).is_some());
+    assert_eq!(project.len(), 1);
+    assert_eq!(project.graph("a").unwrap().graph_inputs.len(), 1);
+
+    assert!(project.remove_graph("a").is_some());
+    assert!(project.is_empty());
+}
+
+#[test]
+fn project_provider_creates_call_nodes() {
+    let project = build_project();
+    let main = project.graph("main").unwrap();
+    let node = &main.nodes["mix_1"];
+    assert_eq!(node.node_type, "mix");
+    let inputs: Vec<&str> = node.inputs.iter().map(|pin| pin.id.as_str()).collect();
+    assert_eq!(inputs, ["a", "b"]);
+    assert_eq!(node.outputs[0].id, "result");
+
+    // Library node types stay available
+    let provider = project.provider(TestMetadataProvider::with_math_nodes());
+    assert!(provider.get_node_metadata("add").is_some());
+}
+
+// ============================================================================
+// References
+// ============================================================================
+
+#[test]
+fn project_lists_references() {
+    let project = build_project();
+    assert_eq!(
+        project.references(),
+        vec![
+            GraphReference {
+                graph: "main".into(),
+                node: "mix_1".into(),
+                target: "mix".into(),
+            },
+            GraphReference {
+                graph: "main".into(),
+                node: "sum_1".into(),
+                target: "sum".into(),
+            },
+            GraphReference {
+                graph: "mix".into(),
+                node: "sum_1".into(),
+                target: "sum".into(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn project_lists_dependencies_and_dependents() {
+    let project = build_project();
+    assert_eq!(project.dependencies("main"), ["mix", "sum"]);
+    assert_eq!(project.dependencies("sum"), Vec::<&str>::new());
+    assert_eq!(project.dependencies("missing"), Vec::<&str>::new());
+    assert_eq!(project.dependents("sum"), ["main", "mix"]);
+    assert_eq!(project.dependents("main"), Vec::<&str>::new());
+}
+
+// ============================================================================
+// Dependency order
+// ============================================================================
+
+#[test]
+fn project_orders_callees_first() {
+    let project = build_project();
+    assert_eq!(project.dependency_order().unwrap(), ["sum", "mix", "main", "other"]);
+}
+
+#[test]
+fn project_detects_cycles() {
+    let mut project = build_project();
+    call(&mut project, "sum", "main", "main_1");
+
+    let error = project.dependency_order().unwrap_err();
+    match &error {
+        GraphyError::CyclicGraphReference { graphs } => assert_eq!(graphs, &["main", "mix", "sum"]),
+        other => panic!("Expected a cyclic graph reference, got {:?}", other),
+    }
+    assert!(error.to_string().starts_with("Graphs call each other in a cycle"), "{}", error);
+}
+
+#[test]
+fn project_detects_graphs_calling_themselves() {
+    let mut project = GraphProject::new("game").with_graph(interface_graph("fib"));
+    call(&mut project, "fib", "fib", "fib_1");
+    assert!(matches!(
+        project.dependency_order(),
+        Err(GraphyError::CyclicGraphReference { graphs }) if graphs == ["fib"]
+    ));
+}
+
+// ============================================================================
+// Call metadata
+// ============================================================================
+
+#[test]
+fn call_metadata_follows_graph_interface() {
+    let metadata = interface_graph("sum").call_metadata();
+    assert_eq!(metadata.name, "sum");
+    assert_eq!(metadata.category, GRAPH_CALL_CATEGORY);
+    assert_eq!(metadata.node_type, NodeTypes::pure);
+    assert_eq!(metadata.params.len(), 2);
+    assert_eq!(metadata.return_type.as_ref().map(|ty| ty.to_string()), Some("i64".to_string()));
+
+    let mut pair = interface_graph("pair");
+    pair.add_graph_output("flag", "bool");
+    let metadata = pair.call_metadata();
+    assert_eq!(metadata.return_type.as_ref().map(|ty| ty.to_string()), Some("(i64, bool)".to_string()));
+}
+
+#[test]
+fn call_metadata_of_execution_graphs_is_a_function() {
+    let mut graph = build_branch_graph();
+    graph.add_graph_input("flag", "bool");
+    let metadata = graph.call_metadata();
+    assert_eq!(metadata.node_type, NodeTypes::fn_);
+    assert_eq!(metadata.exec_outputs, ["then"]);
+    assert!(metadata.return_type.is_none());
+}
+
+// ============================================================================
+// Compilation
+// ============================================================================
+
+#[test]
+fn compile_project_compiles_in_dependency_order() {
+    let project = build_project();
+    let provider = TestMetadataProvider::with_math_nodes();
+    let output = compile_project(&project, &provider, &mut NoopGenerator::new().with_headers(true), &CompileOptions::new()).unwrap();
+
+    let names: Vec<&str> = output.graphs.iter().map(|compiled| compiled.name.as_str()).collect();
+    assert_eq!(names, ["sum", "mix", "main", "other"]);
+    assert!(output.get("mix").unwrap().code.contains("// mix"));
+    assert!(output.get("missing").is_none());
+
+    let code = output.code();
+    assert!(code.find("// sum").unwrap() < code.find("// main").unwrap(), "{}", code);
+}
+
+#[test]
+fn compile_project_rejects_cycles_before_compiling() {
+    let mut project = build_project();
+    call(&mut project, "sum", "mix", "mix_1");
+    let provider = TestMetadataProvider::with_math_nodes();
+    let result = compile_project(&project, &provider, &mut NoopGenerator::new().with_headers(true), &CompileOptions::new());
+    assert!(matches!(result, Err(GraphyError::CyclicGraphReference { .. })));
+}
+
+#[test]
+fn compile_project_names_the_failing_graph() {
+    let mut project = build_project();
+    project.remove_graph("sum");
+    let provider = TestMetadataProvider::with_math_nodes();
+    let error = compile_project(&project, &provider, &mut NoopGenerator::new().with_headers(true), &CompileOptions::new()).unwrap_err();
+
+    match &error {
+        GraphyError::InGraph { graph, source } => {
+            assert_eq!(graph, "mix");
+            assert!(source.to_string().contains("sum"), "{}", source);
+        }
+        other => panic!("Expected an error in a graph, got {:?}", other),
+    }
+    assert!(error.to_string().starts_with("In graph 'mix': "), "{}", error);
+}
+
+// ============================================================================
+// Serialization
+// ============================================================================
+
+#[test]
+fn project_serializes() {
+    let project = build_project();
+    let json = serde_json::to_string(&project).unwrap();
+    let loaded: GraphProject = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.name, "game");
+    assert_eq!(loaded.graphs.keys().collect::<Vec<_>>(), ["main", "mix", "other", "sum"]);
+    assert_eq!(loaded.references(), project.references());
+}
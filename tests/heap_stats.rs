@@ -0,0 +1,90 @@
+//! Tests for allocation tracking (`heap-stats` feature).
+#![cfg(feature = "heap-stats")]
+
+mod common;
+
+use common::*;
+use graphy::utils::heap_stats::{self, HeapScope, HeapSnapshot, TrackingAllocator};
+use graphy::*;
+
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator = TrackingAllocator::system();
+
+// ===========================================================================
+// Heap stats - Tracking
+// ===========================================================================
+
+#[test]
+fn tracking_allocator_is_installed() {
+    let warm_up = String::from("allocate");
+    assert!(!warm_up.is_empty());
+    assert!(heap_stats::is_installed());
+
+    let snapshot = HeapSnapshot::now().unwrap();
+    assert!(snapshot.peak_bytes >= snapshot.current_bytes);
+}
+
+#[test]
+fn track_counts_allocations() {
+    let (buffer, usage) = heap_stats::track(|| vec![0u8; 4096]);
+    let usage = usage.unwrap();
+
+    assert!(usage.allocations >= 1);
+    assert!(usage.allocated_bytes >= 4096);
+    drop(buffer);
+}
+
+#[test]
+fn heap_scope_reports_usage() {
+    let scope = HeapScope::start();
+    let strings: Vec<String> = (0..100).map(|i| i.to_string()).collect();
+    let usage = scope.finish().unwrap();
+
+    assert!(usage.allocations >= 100);
+    assert_eq!(strings.len(), 100);
+}
+
+// ===========================================================================
+// Heap stats - DataResolver
+// ===========================================================================
+
+#[test]
+fn data_resolver_records_build_allocations() {
+    let provider = TestMetadataProvider::with_math_nodes();
+    let graph = build_linear_chain(100, &provider);
+
+    for resolver in [
+        DataResolver::build(&graph, &provider).unwrap(),
+        DataResolver::build_parallel(&graph, &provider).unwrap(),
+    ] {
+        let usage = resolver.build_heap_usage().unwrap();
+        assert!(usage.allocations > 100);
+        assert!(usage.allocated_bytes > 0);
+    }
+}
+
+#[test]
+fn data_resolver_lookups_dont_allocate() {
+    let provider = TestMetadataProvider::comprehensive();
+    let mut graph = build_linear_chain(10, &provider);
+    let mut tick = NodeInstance::new("tick_1", "on_tick", Position::zero());
+    tick.add_output_pin("exec", DataType::Execution);
+    tick.add_output_pin("delta_time", DataType::Typed("f64".into()));
+    graph.add_node(tick);
+    graph.add_connection(Connection::data("tick_1", "delta_time", "node_0", "a"));
+    let resolver = DataResolver::build(&graph, &provider).unwrap();
+
+    // Counters are process-wide, so other tests may add a few allocations;
+    // a lookup that allocated would add one per iteration
+    const LOOKUPS: usize = 10_000;
+    let (found, usage) = heap_stats::track(|| {
+        let mut found = 0;
+        for _ in 0..LOOKUPS {
+            found += usize::from(resolver.get_input_source("node_5", "a").is_some());
+            found += usize::from(resolver.get_event_param_variable("tick_1", "delta_time").is_some());
+        }
+        found
+    });
+    assert_eq!(found, 2 * LOOKUPS);
+    assert!(usage.unwrap().allocations < LOOKUPS / 10);
+}
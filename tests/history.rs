@@ -0,0 +1,196 @@
+//! Tests for recording graph edits and navigating their history.
+
+mod common;
+
+use common::*;
+use graphy::io::Format;
+use graphy::utils::diff::GraphDiff;
+use graphy::utils::{GraphHistory, DEFAULT_CHECKPOINT_INTERVAL};
+use graphy::*;
+
+/// The graph after adding nodes `node_1` to `node_n`
+fn graph_after(n: usize) -> GraphDescription {
+    let ids: Vec<String> = (1..=n).map(|i| format!("node_{}", i)).collect();
+    graph_with_nodes(&ids)
+}
+
+/// A history of `n` edits, each adding one node
+fn history_of(n: usize, checkpoint_interval: usize) -> GraphHistory {
+    let mut history = GraphHistory::new(graph_after(0)).with_checkpoint_interval(checkpoint_interval);
+    for i in 1..=n {
+        assert!(history.record_change(&graph_after(i), format!("Add node {}", i)).unwrap());
+    }
+    history
+}
+
+// ============================================================================
+// Recording
+// ============================================================================
+
+#[test]
+fn history_records_edits_in_order() {
+    let history = history_of(3, DEFAULT_CHECKPOINT_INTERVAL);
+
+    assert_eq!(history.len(), 3);
+    assert_eq!(history.position(), 3);
+    assert_eq!(history.current(), &graph_after(3));
+    assert_eq!(history.initial(), &graph_after(0));
+    let labels: Vec<&str> = history.entries().iter().map(|entry| entry.label.as_str()).collect();
+    assert_eq!(labels, ["Add node 1", "Add node 2", "Add node 3"]);
+    assert!(history.entries().iter().all(|entry| entry.timestamp > 0));
+}
+
+#[test]
+fn history_skips_unchanged_graphs() {
+    let mut history = history_of(1, DEFAULT_CHECKPOINT_INTERVAL);
+    assert!(!history.record_change(&graph_after(1), "Nothing").unwrap());
+    assert_eq!(history.len(), 1);
+}
+
+#[test]
+fn history_keeps_given_timestamps() {
+    let mut history = GraphHistory::new(graph_after(0));
+    history.record_at(GraphDiff::compute(&graph_after(0), &graph_after(1)), "Add", 42).unwrap();
+    assert_eq!(history.entries()[0].timestamp, 42);
+}
+
+#[test]
+fn history_rejects_diffs_that_dont_apply() {
+    let mut history = history_of(1, DEFAULT_CHECKPOINT_INTERVAL);
+    let stale = GraphDiff::compute(&graph_after(0), &graph_after(1));
+
+    assert!(history.record(stale, "Add node 1 again").is_err());
+    assert_eq!(history.len(), 1);
+    assert_eq!(history.current(), &graph_after(1));
+}
+
+// ============================================================================
+// Undo and redo
+// ============================================================================
+
+#[test]
+fn history_undo_and_redo_walk_the_edits() {
+    let mut history = history_of(2, DEFAULT_CHECKPOINT_INTERVAL);
+
+    assert_eq!(history.undo_label(), Some("Add node 2"));
+    assert!(history.undo().unwrap());
+    assert_eq!(history.current(), &graph_after(1));
+    assert_eq!(history.redo_label(), Some("Add node 2"));
+
+    assert!(history.undo().unwrap());
+    assert!(!history.undo().unwrap());
+    assert!(!history.can_undo());
+    assert_eq!(history.current(), &graph_after(0));
+
+    assert!(history.redo().unwrap());
+    assert!(history.redo().unwrap());
+    assert!(!history.redo().unwrap());
+    assert_eq!(history.current(), &graph_after(2));
+}
+
+#[test]
+fn history_drops_undone_edits_on_a_new_edit() {
+    let mut history = history_of(3, 2);
+    history.undo().unwrap();
+    history.undo().unwrap();
+
+    let mut branch = graph_after(1);
+    branch.add_node(NodeInstance::new("other", "print", Position::zero()));
+    history.record_change(&branch, "Add other").unwrap();
+
+    assert_eq!(history.len(), 2);
+    assert!(!history.can_redo());
+    assert_eq!(history.snapshot(2).unwrap(), branch);
+}
+
+// ============================================================================
+// Time travel
+// ============================================================================
+
+#[test]
+fn history_snapshots_match_every_position() {
+    for interval in [1, 3, DEFAULT_CHECKPOINT_INTERVAL] {
+        let mut history = history_of(10, interval);
+        history.go_to(4).unwrap();
+        for position in 0..=10 {
+            assert_eq!(history.snapshot(position).unwrap(), graph_after(position), "interval {}", interval);
+        }
+        assert_eq!(history.position(), 4);
+    }
+}
+
+#[test]
+fn history_go_to_moves_the_current_graph() {
+    let mut history = history_of(10, 4);
+
+    history.go_to(2).unwrap();
+    assert_eq!(history.current(), &graph_after(2));
+    assert_eq!(history.redo_label(), Some("Add node 3"));
+
+    history.go_to(9).unwrap();
+    assert_eq!(history.current(), &graph_after(9));
+    assert!(history.go_to(11).is_err());
+    assert_eq!(history.position(), 9);
+}
+
+// ============================================================================
+// Serialization
+// ============================================================================
+
+#[test]
+fn history_round_trips_with_its_position() {
+    let mut history = history_of(5, 2);
+    history.go_to(3).unwrap();
+
+    for format in Format::ALL.into_iter().filter(Format::is_available) {
+        let bytes = format.serialize(&history).unwrap();
+        let mut loaded: GraphHistory = format.deserialize(&bytes).unwrap();
+
+        assert_eq!(loaded.position(), 3, "{}", format);
+        assert_eq!(loaded.entries(), history.entries());
+        assert_eq!(loaded.checkpoint_interval(), 2);
+        assert_eq!(loaded.current(), &graph_after(3));
+        loaded.go_to(5).unwrap();
+        assert_eq!(loaded.current(), &graph_after(5));
+    }
+}
+
+#[test]
+fn history_serializes_only_the_edit_log() {
+    let history = history_of(4, 2);
+    let document = serde_json::to_value(&history).unwrap();
+
+    let mut keys: Vec<&str> = document.as_object().unwrap().keys().map(String::as_str).collect();
+    keys.sort_unstable();
+    assert_eq!(keys, ["checkpoint_interval", "entries", "initial", "position"]);
+}
+
+#[test]
+fn history_rejects_inconsistent_files() {
+    let history = history_of(2, 2);
+    let mut document = serde_json::to_value(&history).unwrap();
+    document["position"] = serde_json::json!(5);
+    assert!(serde_json::from_value::<GraphHistory>(document).is_err());
+
+    let mut document = serde_json::to_value(&history).unwrap();
+    document["entries"].as_array_mut().unwrap().remove(0);
+    assert!(serde_json::from_value::<GraphHistory>(document).is_err());
+}
+
+#[test]
+fn history_files_recover_the_session() {
+    let dir = temp_dir("history");
+    let path = dir.join("session.json");
+    let mut history = history_of(3, 2);
+    history.undo().unwrap();
+
+    history.save(&path).unwrap();
+    assert!(!dir.join("session.json.tmp").exists());
+    let recovered = GraphHistory::load(&path).unwrap();
+    assert_eq!(recovered.current(), &graph_after(2));
+    assert_eq!(recovered.redo_label(), Some("Add node 3"));
+
+    assert!(matches!(history.save(dir.join("session.txt")), Err(GraphyError::Serialization(_))));
+    assert!(matches!(GraphHistory::load(dir.join("missing.json")), Err(GraphyError::Io(_))));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
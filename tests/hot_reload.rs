@@ -0,0 +1,237 @@
+//! Tests for provider versions, change listeners, and stale results.
+
+mod common;
+
+use common::*;
+use graphy::cache::{compile_cached, provider_fingerprint, CompilationCache, FingerprintMemo, MemoryCache};
+use graphy::core::{CompositeProvider, MetadataChange, OverlayProvider};
+use graphy::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Counts full listings, which fingerprinting needs
+struct CountingProvider {
+    registry: NodeRegistry,
+    listings: AtomicUsize,
+}
+
+impl NodeMetadataProvider for CountingProvider {
+    fn get_node_metadata(&self, node_type: &str) -> Option<&NodeMetadata> {
+        self.registry.get_node_metadata(node_type)
+    }
+
+    fn get_all_nodes(&self) -> Vec<&NodeMetadata> {
+        self.listings.fetch_add(1, Ordering::Relaxed);
+        self.registry.get_all_nodes()
+    }
+
+    fn get_nodes_by_category(&self, category: &str) -> Vec<&NodeMetadata> {
+        self.registry.get_nodes_by_category(category)
+    }
+
+    fn version(&self) -> Option<ProviderVersion> {
+        self.registry.version()
+    }
+}
+
+fn registry() -> NodeRegistry {
+    TestMetadataProvider::comprehensive().metadata
+}
+
+/// An event printing a constant
+fn build_print_graph() -> GraphDescription {
+    let mut graph = GraphDescription::new("hot_reload");
+    let mut event = NodeInstance::new("start", "on_start", Position::zero());
+    event.add_output_pin("exec", DataType::Execution);
+    graph.add_node(event);
+
+    let mut print = NodeInstance::new("print_1", "print_string", Position::zero());
+    print.add_input_pin("exec_in", DataType::Execution);
+    print.add_input_pin("message", DataType::Typed("String".into()));
+    print.set_property("message", PropertyValue::String("hi".into()));
+    graph.add_node(print);
+    graph.add_connection(Connection::execution("start", "exec", "print_1", "exec_in"));
+    graph
+}
+
+// ===========================================================================
+// Versions
+// ===========================================================================
+
+#[test]
+fn registry_changes_advance_the_version() {
+    let mut registry = NodeRegistry::new();
+    let empty = registry.version();
+    assert!(empty.is_some());
+
+    registry.register(NodeMetadata::new("add", NodeTypes::pure, "Math"));
+    let registered = registry.version();
+    assert_ne!(registered, empty);
+
+    // Removing a missing node type changes nothing
+    assert!(registry.unregister("missing").is_none());
+    assert_eq!(registry.version(), registered);
+
+    registry.register_enum("Direction", ["North", "South"]);
+    let with_enum = registry.version();
+    assert_ne!(with_enum, registered);
+    registry.unregister("add");
+    assert_ne!(registry.version(), with_enum);
+}
+
+#[test]
+fn fresh_versions_are_unique() {
+    let mut first = NodeRegistry::new();
+    let mut second = NodeRegistry::new();
+    first.register(NodeMetadata::new("add", NodeTypes::pure, "Math"));
+    second.register(NodeMetadata::new("add", NodeTypes::pure, "Math"));
+    assert_ne!(first.version(), second.version());
+    assert_ne!(ProviderVersion::fresh(), ProviderVersion::fresh());
+}
+
+#[test]
+fn combinators_track_changes() {
+    let mut overlay = OverlayProvider::new(registry());
+    let before = overlay.version();
+    overlay.set_override(NodeMetadata::new("add", NodeTypes::pure, "Plugin"));
+    assert_ne!(overlay.version(), before);
+    let overridden = overlay.version();
+    overlay.hide("multiply");
+    assert_ne!(overlay.version(), overridden);
+
+    let mut composite = CompositeProvider::new();
+    let empty = composite.version();
+    assert!(empty.is_some());
+    composite.add_provider(0, registry());
+    assert_ne!(composite.version(), empty);
+
+    // Wrappers forward the wrapped provider's version
+    let shared = Arc::new(registry());
+    assert_eq!(NodeMetadataProvider::version(&shared), shared.as_ref().version());
+    assert_eq!(TestMetadataProvider::comprehensive().version(), None);
+}
+
+// ===========================================================================
+// Listeners
+// ===========================================================================
+
+#[test]
+fn listeners_see_every_change() {
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let mut registry = NodeRegistry::new();
+    let seen = Arc::clone(&changes);
+    registry.add_listener(move |change: &MetadataChange, version: ProviderVersion| {
+        seen.lock().unwrap().push((change.clone(), version));
+    });
+
+    registry.register(NodeMetadata::new("add", NodeTypes::pure, "Math"));
+    registry.register(NodeMetadata::new("add", NodeTypes::pure, "Plugin"));
+    registry.unregister("add");
+    registry.register_enum("Direction", ["North"]);
+
+    let changes = changes.lock().unwrap();
+    let kinds: Vec<_> = changes.iter().map(|(change, _)| change.clone()).collect();
+    assert_eq!(
+        kinds,
+        [
+            MetadataChange::Registered("add".into()),
+            MetadataChange::Registered("add".into()),
+            MetadataChange::Unregistered("add".into()),
+            MetadataChange::EnumRegistered("Direction".into()),
+        ]
+    );
+    assert_eq!(changes.last().unwrap().1, registry.version().unwrap());
+    assert_eq!(kinds[3].name(), "Direction");
+}
+
+#[test]
+fn clones_have_no_listeners() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let mut registry = NodeRegistry::new();
+    let counter = Arc::clone(&calls);
+    registry.add_listener(move |_: &MetadataChange, _: ProviderVersion| {
+        counter.fetch_add(1, Ordering::Relaxed);
+    });
+
+    let mut clone = registry.clone();
+    assert_eq!(clone.version(), registry.version());
+    clone.register(NodeMetadata::new("add", NodeTypes::pure, "Math"));
+    assert_eq!(calls.load(Ordering::Relaxed), 0);
+
+    registry.register(NodeMetadata::new("add", NodeTypes::pure, "Math"));
+    registry.clear_listeners();
+    registry.unregister("add");
+    assert_eq!(calls.load(Ordering::Relaxed), 1);
+}
+
+// ===========================================================================
+// Stale results
+// ===========================================================================
+
+#[test]
+fn data_resolver_detects_changed_provider() {
+    let graph = build_print_graph();
+    let mut registry = registry();
+    let resolver = DataResolver::build(&graph, &registry).unwrap();
+    assert_eq!(resolver.provider_version(), registry.version());
+    assert!(!resolver.is_stale(&registry));
+
+    registry.register(
+        NodeMetadata::new("print_string", NodeTypes::fn_, "io")
+            .with_params(vec![ParamInfo::new("message", "String"), ParamInfo::new("level", "u8")]),
+    );
+    assert!(resolver.is_stale(&registry));
+    assert!(!DataResolver::build(&graph, &registry).unwrap().is_stale(&registry));
+}
+
+#[test]
+fn unversioned_providers_are_never_stale() {
+    let mut provider = TestMetadataProvider::comprehensive();
+    let resolver = DataResolver::build(&build_print_graph(), &provider).unwrap();
+    assert_eq!(resolver.provider_version(), None);
+
+    provider.add(NodeMetadata::new("extra", NodeTypes::pure, "math"));
+    assert!(!resolver.is_stale(&provider));
+}
+
+#[test]
+fn fingerprint_memo_reuses_fingerprint_until_provider_changes() {
+    let mut provider = CountingProvider {
+        registry: registry(),
+        listings: AtomicUsize::new(0),
+    };
+    let mut memo = FingerprintMemo::new();
+
+    let first = memo.fingerprint(&provider);
+    assert_eq!(memo.fingerprint(&provider), first);
+    assert_eq!(provider.listings.load(Ordering::Relaxed), 1);
+    assert_eq!(memo.version(), provider.version());
+
+    provider.registry.register(NodeMetadata::new("extra", NodeTypes::pure, "math"));
+    let second = memo.fingerprint(&provider);
+    assert_ne!(second, first);
+    assert_eq!(second, provider_fingerprint(&provider));
+    assert_eq!(provider.listings.load(Ordering::Relaxed), 3);
+}
+
+#[test]
+fn cached_compilation_is_rebuilt_after_metadata_changes() {
+    let graph = build_print_graph();
+    let mut registry = registry();
+    let mut cache = MemoryCache::new();
+    let mut generator = CountingGenerator::default();
+
+    let first = compile_cached(&graph, &registry, &mut generator, &mut cache).unwrap();
+    compile_cached(&graph, &registry, &mut generator, &mut cache).unwrap();
+    assert_eq!(generator.events, 1);
+    assert_eq!(cache.fingerprint_memo().unwrap().version(), registry.version());
+
+    registry.register(
+        NodeMetadata::new("print_string", NodeTypes::fn_, "io")
+            .with_params(vec![ParamInfo::new("message", "String").with_default(PropertyValue::String("x".into()))]),
+    );
+    let second = compile_cached(&graph, &registry, &mut generator, &mut cache).unwrap();
+    assert_eq!(generator.events, 2);
+    assert_eq!(second.code, first.code);
+    assert_eq!(cache.len(), 2);
+}
@@ -0,0 +1,251 @@
+//! Tests for the node search index.
+
+use graphy::core::GraphComment;
+use graphy::utils::diff::GraphDiff;
+use graphy::utils::GraphIndex;
+use graphy::*;
+
+fn node(id: &str, node_type: &str, kind: &str) -> NodeInstance {
+    let mut node = NodeInstance::new(id, node_type, Position::zero());
+    node.set_property("kind", PropertyValue::String(kind.into()));
+    node
+}
+
+/// Two spawners and a print, with a comment on the boss
+fn build_graph() -> GraphDescription {
+    let mut graph = GraphDescription::new("index");
+    graph.add_node(node("spawn_enemy", "spawn", "orc"));
+    graph.add_node(node("spawn_boss", "spawn", "dragon"));
+    graph.add_node(node("print_1", "print", "orc"));
+    graph.add_connection(Connection::execution("spawn_enemy", "then", "spawn_boss", "exec"));
+    graph.add_connection(Connection::execution("spawn_boss", "then", "print_1", "exec"));
+    graph.add_connection(Connection::data("spawn_boss", "health", "print_1", "value"));
+    graph.add_comment(GraphComment::new("Final Fight", Position::zero(), (0.0, 0.0)).with_attached_nodes(["spawn_boss"]));
+    graph.add_comment(GraphComment::new("Debug output", Position::zero(), (0.0, 0.0)));
+    graph
+}
+
+fn types(index: &GraphIndex, node_type: &str) -> Vec<String> {
+    index.nodes_of_type(node_type).map(String::from).collect()
+}
+
+fn with_kind(index: &GraphIndex, kind: &str) -> Vec<String> {
+    index.nodes_with_property("kind", &PropertyValue::String(kind.into())).map(String::from).collect()
+}
+
+/// Checks that an updated index answers like one built from scratch
+fn assert_matches_rebuild(index: &GraphIndex, graph: &GraphDescription) {
+    let rebuilt = GraphIndex::build(graph);
+    assert_eq!(index.node_count(), rebuilt.node_count());
+
+    for node in graph.nodes.values() {
+        assert_eq!(types(index, &node.node_type), types(&rebuilt, &node.node_type));
+        for (key, value) in &node.properties {
+            let found: Vec<&str> = index.nodes_with_property(key, value).collect();
+            assert_eq!(found, rebuilt.nodes_with_property(key, value).collect::<Vec<_>>());
+        }
+        assert_eq!(index.connections_from(&node.id), rebuilt.connections_from(&node.id));
+        assert_eq!(index.connections_to(&node.id), rebuilt.connections_to(&node.id));
+        assert_eq!(
+            index.connections_to(&node.id),
+            graph.connections_to_node(&node.id).cloned().collect::<Vec<_>>()
+        );
+    }
+
+    for query in ["", "s", "spawn", "boss", "fight", "debug", "_1"] {
+        assert_eq!(index.search(query), rebuilt.search(query), "{:?}", query);
+        assert_eq!(index.search_comments(query), rebuilt.search_comments(query), "{:?}", query);
+    }
+}
+
+/// Applies an edit to `graph` through its diff, and the diff to `index`
+///
+/// Diffs move modified comments to the end, so the graph is replayed from
+/// the diff to keep the same comment order as the index.
+fn edit(graph: &mut GraphDescription, index: &mut GraphIndex, change: impl FnOnce(&mut GraphDescription)) {
+    let mut edited = graph.clone();
+    change(&mut edited);
+    let diff = GraphDiff::compute(graph, &edited);
+
+    diff.apply(graph).unwrap();
+    index.update(&diff);
+    assert_matches_rebuild(index, graph);
+}
+
+// ============================================================================
+// Queries
+// ============================================================================
+
+#[test]
+fn index_finds_nodes_by_type() {
+    let index = GraphIndex::build(&build_graph());
+
+    assert_eq!(index.node_count(), 3);
+    assert_eq!(types(&index, "spawn"), ["spawn_boss", "spawn_enemy"]);
+    assert_eq!(types(&index, "print"), ["print_1"]);
+    assert!(types(&index, "missing").is_empty());
+}
+
+#[test]
+fn index_finds_nodes_by_property_value() {
+    let mut graph = build_graph();
+    graph.get_node_mut("print_1").unwrap().set_property("scale", PropertyValue::Number(-0.0));
+    let index = GraphIndex::build(&graph);
+
+    assert_eq!(with_kind(&index, "orc"), ["print_1", "spawn_enemy"]);
+    assert_eq!(with_kind(&index, "dragon"), ["spawn_boss"]);
+    assert!(with_kind(&index, "elf").is_empty());
+    assert!(index.nodes_with_property("missing", &PropertyValue::Integer(1)).next().is_none());
+
+    // Values are matched by equality, so `0.0` finds `-0.0`
+    let found: Vec<&str> = index.nodes_with_property("scale", &PropertyValue::Number(0.0)).collect();
+    assert_eq!(found, ["print_1"]);
+}
+
+#[test]
+fn index_finds_connections_by_endpoint() {
+    let graph = build_graph();
+    let index = GraphIndex::build(&graph);
+
+    let pins: Vec<&str> = index.connections_to("print_1").iter().map(|c| c.target_pin.as_str()).collect();
+    assert_eq!(pins, ["exec", "value"]);
+    assert_eq!(index.connections_from("spawn_boss").len(), 2);
+    assert!(index.connections_to("spawn_enemy").is_empty());
+    assert!(index.connections_from("missing").is_empty());
+}
+
+// ============================================================================
+// Search
+// ============================================================================
+
+#[test]
+fn index_search_matches_node_ids_ignoring_case() {
+    let index = GraphIndex::build(&build_graph());
+
+    assert_eq!(index.search("SPAWN"), ["spawn_boss", "spawn_enemy"]);
+    assert_eq!(index.search("nem"), ["spawn_enemy"]);
+    assert_eq!(index.search("_1"), ["print_1"]);
+    assert_eq!(index.search(""), ["print_1", "spawn_boss", "spawn_enemy"]);
+    assert!(index.search("spawn_enemies").is_empty());
+}
+
+#[test]
+fn index_search_matches_attached_comments() {
+    let index = GraphIndex::build(&build_graph());
+
+    assert_eq!(index.search("fight"), ["spawn_boss"]);
+    // Free-standing comments match no node
+    assert!(index.search("debug").is_empty());
+}
+
+#[test]
+fn index_search_finds_comments_in_graph_order() {
+    let index = GraphIndex::build(&build_graph());
+
+    let texts: Vec<&str> = index.search_comments("t").iter().map(|c| c.text.as_str()).collect();
+    assert_eq!(texts, ["Final Fight", "Debug output"]);
+    assert_eq!(index.search_comments("OUTPUT")[0].text, "Debug output");
+}
+
+#[test]
+fn index_search_handles_non_ascii_text() {
+    let mut graph = GraphDescription::new("unicode");
+    graph.add_node(NodeInstance::new("größe_1", "math.add", Position::zero()));
+    let index = GraphIndex::build(&graph);
+
+    assert_eq!(index.search("GRÖSSE"), Vec::<&str>::new());
+    assert_eq!(index.search("GRÖẞE"), ["größe_1"]);
+    assert_eq!(index.search("öß"), ["größe_1"]);
+}
+
+// ============================================================================
+// Incremental updates
+// ============================================================================
+
+#[test]
+fn index_update_follows_added_and_removed_nodes() {
+    let mut graph = build_graph();
+    let mut index = GraphIndex::build(&graph);
+
+    edit(&mut graph, &mut index, |graph| graph.add_node(node("spawn_minion", "spawn", "goblin")));
+    assert_eq!(types(&index, "spawn"), ["spawn_boss", "spawn_enemy", "spawn_minion"]);
+    assert_eq!(index.search("minion"), ["spawn_minion"]);
+
+    edit(&mut graph, &mut index, |graph| {
+        graph.remove_node("spawn_boss");
+    });
+    assert_eq!(types(&index, "spawn"), ["spawn_enemy", "spawn_minion"]);
+    assert!(with_kind(&index, "dragon").is_empty());
+    assert!(index.connections_to("print_1").is_empty());
+    assert!(index.search("fight").is_empty());
+}
+
+#[test]
+fn index_update_follows_modified_nodes() {
+    let mut graph = build_graph();
+    let mut index = GraphIndex::build(&graph);
+
+    edit(&mut graph, &mut index, |graph| {
+        let node = graph.get_node_mut("spawn_enemy").unwrap();
+        node.node_type = "spawn_group".into();
+        node.set_property("kind", PropertyValue::String("dragon".into()));
+        node.set_property("count", PropertyValue::Integer(3));
+        graph.get_node_mut("print_1").unwrap().properties.remove("kind");
+    });
+
+    assert_eq!(types(&index, "spawn"), ["spawn_boss"]);
+    assert_eq!(types(&index, "spawn_group"), ["spawn_enemy"]);
+    assert_eq!(with_kind(&index, "dragon"), ["spawn_boss", "spawn_enemy"]);
+    assert!(with_kind(&index, "orc").is_empty());
+}
+
+#[test]
+fn index_update_follows_connections_and_comments() {
+    let mut graph = build_graph();
+    let mut index = GraphIndex::build(&graph);
+
+    edit(&mut graph, &mut index, |graph| {
+        graph.remove_connection("spawn_boss", "health", "print_1", "value");
+        graph.add_connection(Connection::data("spawn_enemy", "health", "print_1", "value"));
+    });
+    assert_eq!(index.connections_from("spawn_enemy").len(), 2);
+
+    edit(&mut graph, &mut index, |graph| {
+        graph.comments.remove(0);
+        graph.add_comment(GraphComment::new("Intro fight", Position::zero(), (0.0, 0.0)).with_attached_nodes(["spawn_enemy"]));
+    });
+    assert_eq!(index.search("fight"), ["spawn_enemy"]);
+    let texts: Vec<&str> = index.search_comments("").iter().map(|c| c.text.as_str()).collect();
+    assert_eq!(texts, ["Debug output", "Intro fight"]);
+}
+
+#[test]
+fn index_update_follows_connections_with_ids() {
+    let mut graph = build_graph();
+    graph.add_connection(Connection::data("spawn_enemy", "health", "print_1", "value").with_id("wire"));
+    let mut index = GraphIndex::build(&graph);
+
+    edit(&mut graph, &mut index, |graph| {
+        graph.connections.last_mut().unwrap().source_node = "spawn_boss".into();
+    });
+    assert_eq!(index.connections_from("spawn_enemy").len(), 1);
+    assert_eq!(index.connections_from("spawn_boss").len(), 3);
+
+    edit(&mut graph, &mut index, |graph| {
+        graph.connections.last_mut().unwrap().label = Some("health".into());
+    });
+    assert_eq!(index.connections_to("print_1").last().unwrap().label.as_deref(), Some("health"));
+}
+
+#[test]
+fn index_update_follows_undo() {
+    let original = build_graph();
+    let mut graph = original.clone();
+    graph.remove_node("print_1");
+    graph.add_node(node("print_2", "print", "elf"));
+    let diff = GraphDiff::compute(&original, &graph);
+
+    let mut index = GraphIndex::build(&graph);
+    index.update(&diff.invert());
+    assert_matches_rebuild(&index, &original);
+}
@@ -0,0 +1,135 @@
+//! Tests for breaking long pure chains into bindings with inline limits.
+
+mod common;
+
+use common::*;
+use graphy::generation::{
+    compile, compile_with_options, CompilationSession, CompileOptions, ExpressionPolicy, InlineLimits,
+};
+use graphy::*;
+use std::sync::Arc;
+
+/// `start -> print_1(add_{length - 1})`, each `add_i` adding 1 to `add_{i - 1}`
+fn chain_graph(length: usize) -> GraphDescription {
+    let mut graph = GraphDescription::new("chain");
+    event_node(&mut graph, "start", "on_start");
+    exec_node(&mut graph, "print_1", "print_string", &["then"]);
+    graph.add_connection(Connection::execution("start", "exec", "print_1", "exec_in"));
+
+    for index in 0..length {
+        let id = format!("add_{}", index);
+        graph.add_node(NodeInstance::new(id.as_str(), "add", Position::zero()));
+        if index == 0 {
+            set_input(&mut graph, &id, "a", PropertyValue::Integer(1));
+        } else {
+            graph.add_connection(Connection::data(format!("add_{}", index - 1), "result", id.as_str(), "a"));
+        }
+        set_input(&mut graph, &id, "b", PropertyValue::Integer(1));
+    }
+    graph.add_connection(Connection::data(format!("add_{}", length - 1), "result", "print_1", "message"));
+    graph
+}
+
+fn compile_chain(graph: &GraphDescription, limits: InlineLimits) -> String {
+    let options = CompileOptions::new().with_inline_limits(limits);
+    compile_with_options(graph, &TestMetadataProvider::comprehensive(), &mut CallGenerator::default(), &options)
+        .unwrap()
+        .code
+}
+
+/// Deepest nesting of calls on one line
+fn max_nesting(code: &str) -> usize {
+    code.lines().map(|line| line.matches("add(").count()).max().unwrap_or(0)
+}
+
+// ============================================================================
+// Depth
+// ============================================================================
+
+#[test]
+fn inline_limits_bind_every_max_depth_nodes() {
+    let code = compile_chain(&chain_graph(10), InlineLimits::new().with_max_depth(4));
+
+    assert_eq!(
+        code,
+        "let node_add_3_result = add(add(add(add(1, 1), 1), 1), 1);\n\
+         let node_add_7_result = add(add(add(add(node_add_3_result, 1), 1), 1), 1);\n\
+         print_string(add(add(node_add_7_result, 1), 1));\n"
+    );
+}
+
+#[test]
+fn inline_limits_break_long_chains_by_default() {
+    let provider = TestMetadataProvider::comprehensive();
+    let code = compile(&chain_graph(100), &provider, &mut CallGenerator::default()).unwrap();
+
+    assert_eq!(code.matches("let ").count(), 100 / 32);
+    assert_eq!(max_nesting(&code), 32);
+    assert!(code.contains("let node_add_31_result = "), "{}", code);
+}
+
+#[test]
+fn inline_limits_unlimited_inline_everything() {
+    let code = compile_chain(&chain_graph(100), InlineLimits::unlimited());
+
+    assert!(!code.contains("let "), "{}", code);
+    assert_eq!(max_nesting(&code), 100);
+}
+
+#[test]
+fn inline_limits_apply_to_always_inline() {
+    let mut generator = CallGenerator::new().with_policy(ExpressionPolicy::AlwaysInline);
+    let options = CompileOptions::new().with_inline_limits(InlineLimits::new().with_max_depth(4));
+    let code = compile_with_options(&chain_graph(10), &TestMetadataProvider::comprehensive(), &mut generator, &options)
+        .unwrap()
+        .code;
+
+    assert_eq!(code, compile_chain(&chain_graph(10), InlineLimits::new().with_max_depth(4)));
+}
+
+// ============================================================================
+// Length
+// ============================================================================
+
+#[test]
+fn inline_limits_bind_long_expressions() {
+    let code = compile_chain(&chain_graph(3), InlineLimits::unlimited().with_max_length(20));
+
+    // `add(add(add(1, 1), 1), 1)` is 25 bytes
+    assert_eq!(
+        code,
+        "let node_add_2_result = add(add(add(1, 1), 1), 1);\nprint_string(node_add_2_result);\n"
+    );
+}
+
+#[test]
+fn inline_limits_check_expressions() {
+    let limits = InlineLimits::new().with_max_depth(3).with_max_length(10);
+    assert!(!limits.exceeded_by(2, "add(1, 1)"));
+    assert!(limits.exceeded_by(3, "add(1, 1)"));
+    assert!(limits.exceeded_by(1, "add(x, 1, 1)"));
+    assert!(!InlineLimits::unlimited().exceeded_by(1000, &"x".repeat(10_000)));
+}
+
+// ============================================================================
+// Options
+// ============================================================================
+
+#[test]
+fn inline_limits_match_in_sessions() {
+    let graph = chain_graph(10);
+    let options = CompileOptions::new().with_inline_limits(InlineLimits::new().with_max_depth(4));
+    let provider = Arc::new(TestMetadataProvider::comprehensive());
+
+    let session = CompilationSession::new(&graph, provider, &CallGenerator::default(), &options).unwrap();
+    let code = session.finish(&mut CallGenerator::default()).unwrap().code;
+    assert_eq!(code, compile_chain(&graph, options.inline_limits));
+}
+
+#[test]
+fn inline_limits_load_from_options() {
+    let json = serde_json::json!({ "inline_limits": { "max_depth": 4 } });
+    let options: CompileOptions = serde_json::from_value(json).unwrap();
+    assert_eq!(options.inline_limits, InlineLimits::new().with_max_depth(4));
+    assert_eq!(CompileOptions::new().inline_limits, InlineLimits::default());
+}
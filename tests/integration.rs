@@ -93,7 +93,7 @@ fn pipeline_branch_graph_full() {
 
     // Branch condition from property
     let cond = resolver.get_input_source("branch_1", "condition").unwrap();
-    assert!(matches!(cond, DataSource::Constant(_)));
+    assert!(matches!(cond, DataSource::Constant(..)));
 
     // Execution routing
     let routing = ExecutionRouting::build_from_graph(&graph);
@@ -283,16 +283,23 @@ fn error_display_type_mismatch() {
 
 #[test]
 fn error_display_cyclic() {
-    let err = GraphyError::CyclicDependency;
+    let err = GraphyError::CyclicDependency {
+        nodes: vec!["a".to_string(), "b".to_string()],
+    };
     let msg = format!("{}", err);
     assert!(msg.contains("Cyclic") || msg.contains("cyclic"));
+    assert!(msg.contains("a -> b -> a"));
 }
 
 #[test]
 fn error_display_invalid_connection() {
-    let err = GraphyError::InvalidConnection("bad wire".to_string());
+    let connection = Connection::data("a", "out", "b", "in").with_id("bad_wire");
+    let err = GraphyError::InvalidConnection {
+        connection: Box::new(core::ConnectionRef::new(&connection, Some(2))),
+        reason: core::ConnectionErrorReason::NotFound,
+    };
     let msg = format!("{}", err);
-    assert!(msg.contains("bad wire"));
+    assert_eq!(msg, "Invalid connection bad_wire: a.out -> b.in (#2): not found in the graph");
 }
 
 #[test]
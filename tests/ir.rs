@@ -0,0 +1,500 @@
+//! Tests for lowering graphs to the intermediate representation.
+
+mod common;
+
+use common::*;
+use graphy::core::{VariableDecl, ERROR_EXEC_PIN, ERROR_VALUE_PIN};
+use graphy::generation::ir::{lower_graph, lower_graph_with_options, BranchKind, IrOp, IrProgram, IrValue};
+use graphy::generation::{CompileOptions, OptLevel};
+use graphy::utils::RustProfile;
+use graphy::*;
+
+fn ir_provider() -> TestMetadataProvider {
+    let mut provider = TestMetadataProvider::comprehensive();
+    provider.add(
+        NodeMetadata::new("read_file", NodeTypes::fn_, "io")
+            .with_params(vec![ParamInfo::new("path", "String")])
+            .with_return_type("Result<String, String>")
+            .with_exec_outputs(vec!["then".to_string()]),
+    );
+    provider.add(
+        NodeMetadata::new("while_loop", NodeTypes::control_flow, "flow")
+            .with_params(vec![ParamInfo::new("condition", "bool")])
+            .with_exec_outputs(vec!["body".to_string(), "completed".to_string()]),
+    );
+    provider.add(
+        NodeMetadata::new("switch", NodeTypes::control_flow, "flow")
+            .with_params(vec![ParamInfo::new("value", "Direction")])
+            .with_switch(true),
+    );
+    provider.add(
+        NodeMetadata::new("get_score", NodeTypes::fn_, "game")
+            .with_return_type("i64")
+            .with_exec_outputs(vec!["then".to_string()]),
+    );
+    provider.add(NodeMetadata::new("current_direction", NodeTypes::pure, "game").with_return_type("Direction"));
+    provider.metadata.register_enum("Direction", ["North", "South"]);
+    provider
+}
+
+fn start_graph(name: &str) -> GraphDescription {
+    let mut graph = GraphDescription::new(name);
+    event_node(&mut graph, "start", "on_start");
+    graph
+}
+
+fn lower(graph: &GraphDescription) -> IrProgram {
+    lower_graph(graph, &ir_provider(), &RustProfile).unwrap()
+}
+
+fn event_body(program: &IrProgram) -> &[IrOp] {
+    match program.ops.as_slice() {
+        [IrOp::EmitEvent { body, .. }] => body,
+        ops => panic!("expected one event, got {:?}", ops),
+    }
+}
+
+// ===========================================================================
+// Events and functions
+// ===========================================================================
+
+#[test]
+fn ir_lowers_events_in_id_order() {
+    let mut graph = start_graph("events");
+    let mut tick = NodeInstance::new("a_tick", "on_tick", Position::zero());
+    tick.add_output_pin("exec", DataType::Execution);
+    graph.add_node(tick);
+
+    let program = lower(&graph);
+    let events: Vec<_> = program
+        .ops
+        .iter()
+        .map(|op| match op {
+            IrOp::EmitEvent { event, body, .. } => {
+                assert!(body.is_empty());
+                event.as_str()
+            }
+            op => panic!("expected an event, got {:?}", op),
+        })
+        .collect();
+    assert_eq!(events, ["a_tick", "start"]);
+}
+
+#[test]
+fn ir_evaluates_pure_inputs_before_call() {
+    let mut graph = start_graph("linear");
+    exec_node(&mut graph, "print_1", "print_string", &["then"]);
+    graph.add_node(NodeInstance::new("add_1", "add", Position::zero()));
+    set_input(&mut graph, "add_1", "a", PropertyValue::Integer(1));
+    graph.add_connection(Connection::execution("start", "exec", "print_1", "exec_in"));
+    graph.add_connection(Connection::data("add_1", "result", "print_1", "message"));
+
+    let program = lower(&graph);
+    let body = event_body(&program);
+    assert_eq!(body.len(), 2);
+    let IrOp::EvalPure { node, args, variable, .. } = &body[0] else {
+        panic!("expected a pure evaluation, got {:?}", body[0]);
+    };
+    assert_eq!(node, "add_1");
+    assert_eq!(args, &[IrValue::Constant(PropertyValue::Integer(1)), IrValue::Default("i64".into())]);
+
+    let IrOp::CallFunction { node, args, result, .. } = &body[1] else {
+        panic!("expected a call, got {:?}", body[1]);
+    };
+    assert_eq!(node, "print_1");
+    assert_eq!(
+        args,
+        &[IrValue::Result {
+            node: "add_1".into(),
+            variable: variable.clone(),
+        }]
+    );
+    assert_eq!(result, &None);
+}
+
+#[test]
+fn ir_evaluates_shared_pure_node_once() {
+    let mut graph = start_graph("shared");
+    exec_node(&mut graph, "print_1", "print_string", &["then"]);
+    exec_node(&mut graph, "print_2", "print_string", &["then"]);
+    graph.add_node(NodeInstance::new("add_1", "add", Position::zero()));
+    graph.add_connection(Connection::execution("start", "exec", "print_1", "exec_in"));
+    graph.add_connection(Connection::execution("print_1", "then", "print_2", "exec_in"));
+    graph.add_connection(Connection::data("add_1", "result", "print_1", "message"));
+    graph.add_connection(Connection::data("add_1", "result", "print_2", "message"));
+
+    let program = lower(&graph);
+    let evaluations = event_body(&program)
+        .iter()
+        .filter(|op| matches!(op, IrOp::EvalPure { .. }))
+        .count();
+    assert_eq!(evaluations, 1);
+}
+
+// ===========================================================================
+// Branches
+// ===========================================================================
+
+#[test]
+fn ir_scopes_pure_evaluations_to_arms() {
+    let mut graph = start_graph("branch");
+    exec_node(&mut graph, "branch_1", "branch", &["True", "False"]);
+    set_input(&mut graph, "branch_1", "condition", PropertyValue::Boolean(true));
+    exec_node(&mut graph, "print_t", "print_string", &["then"]);
+    exec_node(&mut graph, "print_f", "print_string", &["then"]);
+    graph.add_node(NodeInstance::new("add_1", "add", Position::zero()));
+    graph.add_connection(Connection::execution("start", "exec", "branch_1", "exec_in"));
+    graph.add_connection(Connection::execution("branch_1", "True", "print_t", "exec_in"));
+    graph.add_connection(Connection::execution("branch_1", "False", "print_f", "exec_in"));
+    graph.add_connection(Connection::data("add_1", "result", "print_t", "message"));
+    graph.add_connection(Connection::data("add_1", "result", "print_f", "message"));
+
+    let program = lower(&graph);
+    let [IrOp::Branch { kind, args, arms, .. }] = event_body(&program) else {
+        panic!("expected a branch");
+    };
+    assert_eq!(kind, &BranchKind::ControlFlow);
+    assert_eq!(args, &[IrValue::Constant(PropertyValue::Boolean(true))]);
+    let pins: Vec<_> = arms.iter().map(|arm| arm.pin.as_str()).collect();
+    assert_eq!(pins, ["True", "False"]);
+    for arm in arms {
+        assert!(matches!(arm.body.as_slice(), [IrOp::EvalPure { .. }, IrOp::CallFunction { .. }]), "{:?}", arm);
+    }
+}
+
+#[test]
+fn ir_lowers_switch_without_default_when_exhaustive() {
+    let mut graph = start_graph("switch");
+    let mut direction = NodeInstance::new("dir", "current_direction", Position::zero());
+    direction.add_output_pin("result", DataType::Typed("Direction".into()));
+    graph.add_node(direction);
+    let mut switch = NodeInstance::new("switch_1", "switch", Position::zero());
+    switch.add_input_pin("exec_in", DataType::Execution);
+    switch.add_input_pin("value", DataType::Typed("Direction".into()));
+    graph.add_node(switch);
+    graph.add_connection(Connection::execution("start", "exec", "switch_1", "exec_in"));
+    graph.add_connection(Connection::data("dir", "result", "switch_1", "value"));
+
+    let program = lower(&graph);
+    let [IrOp::EvalPure { .. }, IrOp::Branch { kind, arms, .. }] = event_body(&program) else {
+        panic!("expected a switch, got {:?}", program);
+    };
+    assert_eq!(kind, &BranchKind::Switch { value_type: "Direction".into() });
+    let pins: Vec<_> = arms.iter().map(|arm| arm.pin.as_str()).collect();
+    assert_eq!(pins, ["North", "South"]);
+}
+
+#[test]
+fn ir_lowers_fallible_node_with_error_arm() {
+    let mut graph = start_graph("fallible");
+    exec_node(&mut graph, "read_1", "read_file", &["then", ERROR_EXEC_PIN]);
+    exec_node(&mut graph, "print_ok", "print_string", &["then"]);
+    exec_node(&mut graph, "print_err", "print_string", &["then"]);
+    graph.add_connection(Connection::execution("start", "exec", "read_1", "exec_in"));
+    graph.add_connection(Connection::execution("read_1", "then", "print_ok", "exec_in"));
+    graph.add_connection(Connection::execution("read_1", ERROR_EXEC_PIN, "print_err", "exec_in"));
+    graph.add_connection(Connection::data("read_1", ERROR_VALUE_PIN, "print_err", "message"));
+
+    let program = lower(&graph);
+    let [IrOp::Branch { kind, arms, .. }] = event_body(&program) else {
+        panic!("expected a fallible branch, got {:?}", program);
+    };
+    let BranchKind::Fallible { result, error } = kind else {
+        panic!("expected a fallible kind, got {:?}", kind);
+    };
+    assert_eq!(result, &None);
+    let error = error.clone().expect("error value is read");
+
+    assert_eq!(arms[0].pin, "then");
+    assert_eq!(arms[1].pin, ERROR_EXEC_PIN);
+    let [IrOp::CallFunction { args, .. }] = arms[1].body.as_slice() else {
+        panic!("expected a call in the error arm");
+    };
+    assert_eq!(args, &[IrValue::Error { node: "read_1".into(), variable: error }]);
+}
+
+#[test]
+fn ir_rejects_pure_node_on_exec_flow() {
+    let mut graph = start_graph("invalid");
+    exec_node(&mut graph, "add_1", "add", &[]);
+    graph.add_connection(Connection::execution("start", "exec", "add_1", "exec_in"));
+
+    let error = lower_graph(&graph, &ir_provider(), &RustProfile).unwrap_err();
+    assert!(matches!(error.root(), GraphyError::CodeGeneration(_)), "{}", error);
+    assert_eq!(error.node(), Some("add_1"));
+}
+
+// ===========================================================================
+// Loops and variables
+// ===========================================================================
+
+#[test]
+fn ir_wraps_loop_headers_and_back_edges() {
+    let mut graph = start_graph("loop");
+    exec_node(&mut graph, "while_1", "while_loop", &["body", "completed"]);
+    exec_node(&mut graph, "step", "print_string", &["then"]);
+    exec_node(&mut graph, "done", "print_string", &["then"]);
+    graph.add_connection(Connection::execution("start", "exec", "while_1", "exec_in"));
+    graph.add_connection(Connection::execution("while_1", "body", "step", "exec_in"));
+    graph.add_connection(Connection::execution("step", "then", "while_1", "exec_in"));
+    graph.add_connection(Connection::execution("while_1", "completed", "done", "exec_in"));
+
+    let program = lower(&graph);
+    let [IrOp::Loop { node, body }] = event_body(&program) else {
+        panic!("expected a loop, got {:?}", program);
+    };
+    assert_eq!(node, "while_1");
+    let [IrOp::Branch { arms, .. }] = body.as_slice() else {
+        panic!("expected the loop header, got {:?}", body);
+    };
+    assert!(matches!(
+        arms[0].body.as_slice(),
+        [IrOp::CallFunction { .. }, IrOp::Continue { node }] if node == "while_1"
+    ));
+    assert!(matches!(arms[1].body.as_slice(), [IrOp::CallFunction { node, .. }] if node == "done"));
+}
+
+#[test]
+fn ir_reevaluates_variable_readers_after_set() {
+    let mut graph = start_graph("variables");
+    graph.add_variable("score", VariableDecl::new("i64"));
+    let get = graph.create_get_variable_node("get_score", "score", Position::zero()).unwrap();
+    graph.add_node(get);
+    let set = graph.create_set_variable_node("set_score", "score", Position::zero()).unwrap();
+    graph.add_node(set);
+    graph.add_node(NodeInstance::new("add_1", "add", Position::zero()));
+    set_input(&mut graph, "add_1", "b", PropertyValue::Integer(1));
+    exec_node(&mut graph, "print_1", "print_string", &["then"]);
+
+    graph.add_connection(Connection::execution("start", "exec", "set_score", "exec_in"));
+    graph.add_connection(Connection::execution("set_score", "exec_out", "print_1", "exec_in"));
+    graph.add_connection(Connection::data("get_score", "value", "add_1", "a"));
+    graph.add_connection(Connection::data("add_1", "result", "set_score", "value"));
+    graph.add_connection(Connection::data("add_1", "result", "print_1", "message"));
+
+    let program = lower(&graph);
+    let body = event_body(&program);
+    assert!(
+        matches!(
+            body,
+            [IrOp::EvalPure { .. }, IrOp::SetVariable { variable, .. }, IrOp::EvalPure { .. }, IrOp::CallFunction { .. }]
+                if variable == "score"
+        ),
+        "{:?}",
+        body
+    );
+    let IrOp::EvalPure { args, .. } = &body[0] else { unreachable!() };
+    assert_eq!(args[0], IrValue::Variable("score".into()));
+}
+
+// ===========================================================================
+// Display
+// ===========================================================================
+
+#[test]
+fn ir_pretty_prints_program() {
+    let mut graph = start_graph("display");
+    exec_node(&mut graph, "branch_1", "branch", &["True", "False"]);
+    exec_node(&mut graph, "print_1", "print_string", &["then"]);
+    set_input(&mut graph, "print_1", "message", PropertyValue::String("hi".into()));
+    graph.add_connection(Connection::execution("start", "exec", "branch_1", "exec_in"));
+    graph.add_connection(Connection::execution("branch_1", "True", "print_1", "exec_in"));
+
+    let expected = "\
+event start (on_start)
+  branch branch_1 = branch(default<bool>)
+    True:
+      call print_1 = print_string(\"hi\")
+    False:
+";
+    assert_eq!(lower(&graph).to_string(), expected);
+}
+
+// ===========================================================================
+// Optimization
+// ===========================================================================
+
+/// `start -> branch_1(condition)`, printing "yes" on True and "no" on False
+fn build_constant_branch_graph(condition: bool) -> GraphDescription {
+    let mut graph = start_graph("constant_branch");
+    exec_node(&mut graph, "branch_1", "branch", &["True", "False"]);
+    set_input(&mut graph, "branch_1", "condition", PropertyValue::Boolean(condition));
+    exec_node(&mut graph, "print_yes", "print_string", &["then"]);
+    set_input(&mut graph, "print_yes", "message", PropertyValue::String("yes".into()));
+    exec_node(&mut graph, "print_no", "print_string", &["then"]);
+    set_input(&mut graph, "print_no", "message", PropertyValue::String("no".into()));
+    graph.add_connection(Connection::execution("start", "exec", "branch_1", "exec_in"));
+    graph.add_connection(Connection::execution("branch_1", "True", "print_yes", "exec_in"));
+    graph.add_connection(Connection::execution("branch_1", "False", "print_no", "exec_in"));
+    graph
+}
+
+fn optimized(graph: &GraphDescription, level: OptLevel) -> IrProgram {
+    let options = CompileOptions::new().with_opt_level(level);
+    lower_graph_with_options(graph, &ir_provider(), &RustProfile, &options).unwrap()
+}
+
+#[test]
+fn opt_level_defaults_to_none() {
+    assert_eq!(CompileOptions::default().opt_level, OptLevel::None);
+
+    let graph = build_constant_branch_graph(true);
+    assert_eq!(optimized(&graph, OptLevel::None), lower(&graph));
+    let mut program = lower(&graph);
+    assert_eq!(program.optimize(&ir_provider(), OptLevel::None), Default::default());
+}
+
+#[test]
+fn opt_simplifies_constant_branches() {
+    for (condition, taken) in [(true, "print_yes"), (false, "print_no")] {
+        let mut program = lower(&build_constant_branch_graph(condition));
+        let stats = program.optimize(&ir_provider(), OptLevel::Basic);
+        assert_eq!(stats.simplified_branches, 1);
+        assert!(
+            matches!(event_body(&program), [IrOp::CallFunction { node, .. }] if node == taken),
+            "{}",
+            program
+        );
+    }
+}
+
+#[test]
+fn opt_keeps_branches_on_runtime_values() {
+    let mut graph = build_constant_branch_graph(true);
+    graph.nodes.get_mut("branch_1").unwrap().properties.clear();
+
+    let mut program = lower(&graph);
+    let before = program.clone();
+    assert_eq!(program.optimize(&ir_provider(), OptLevel::Aggressive).simplified_branches, 0);
+    assert_eq!(program, before);
+}
+
+#[test]
+fn opt_simplifies_switch_on_constant() {
+    let mut graph = start_graph("constant_switch");
+    let mut switch = NodeInstance::new("switch_1", "switch", Position::zero());
+    switch.add_input_pin("exec_in", DataType::Execution);
+    switch.add_input_pin("value", DataType::Typed("Direction".into()));
+    switch.set_property(
+        "value",
+        PropertyValue::Enum {
+            variant: "Direction::South".into(),
+            payload: None,
+        },
+    );
+    graph.add_node(switch);
+    exec_node(&mut graph, "print_south", "print_string", &["then"]);
+    graph.add_connection(Connection::execution("start", "exec", "switch_1", "exec_in"));
+    graph.add_connection(Connection::execution("switch_1", "South", "print_south", "exec_in"));
+
+    let program = optimized(&graph, OptLevel::Basic);
+    assert!(
+        matches!(event_body(&program), [IrOp::CallFunction { node, .. }] if node == "print_south"),
+        "{}",
+        program
+    );
+}
+
+#[test]
+fn opt_unbinds_results_read_only_in_removed_arms() {
+    let mut graph = build_constant_branch_graph(false);
+    exec_node(&mut graph, "score_1", "get_score", &["then"]);
+    graph.remove_connection("start", "exec", "branch_1", "exec_in");
+    graph.add_connection(Connection::execution("start", "exec", "score_1", "exec_in"));
+    graph.add_connection(Connection::execution("score_1", "then", "branch_1", "exec_in"));
+    graph.nodes.get_mut("print_yes").unwrap().properties.clear();
+    graph.add_connection(Connection::data("score_1", "result", "print_yes", "message"));
+
+    let lowered = lower(&graph);
+    assert!(matches!(&event_body(&lowered)[0], IrOp::CallFunction { result: Some(_), .. }));
+
+    let mut program = lowered.clone();
+    let stats = program.optimize(&ir_provider(), OptLevel::Basic);
+    assert_eq!(stats.dead_stores, 1);
+    assert!(
+        matches!(
+            event_body(&program),
+            [IrOp::CallFunction { result: None, .. }, IrOp::CallFunction { node, .. }] if node == "print_no"
+        ),
+        "{}",
+        program
+    );
+}
+
+#[test]
+fn opt_removes_unread_pure_evaluations_transitively() {
+    let eval = |node: &str, args: Vec<IrValue>| IrOp::EvalPure {
+        node: node.into(),
+        node_type: "negate".into(),
+        args,
+        variable: format!("{}_result", node),
+    };
+    let read = |node: &str| IrValue::Result {
+        node: node.into(),
+        variable: format!("{}_result", node),
+    };
+    let mut program = IrProgram {
+        ops: vec![IrOp::EmitEvent {
+            event: "start".into(),
+            node_type: "on_start".into(),
+            body: vec![
+                eval("a", vec![IrValue::Constant(PropertyValue::Integer(1))]),
+                eval("b", vec![read("a")]),
+                eval("c", vec![IrValue::Constant(PropertyValue::Integer(2))]),
+                IrOp::CallFunction {
+                    node: "print_1".into(),
+                    node_type: "print_string".into(),
+                    args: vec![read("c")],
+                    result: None,
+                },
+            ],
+        }],
+    };
+
+    let stats = program.optimize(&ir_provider(), OptLevel::Basic);
+    assert_eq!(stats.dead_stores, 2);
+    assert!(matches!(
+        event_body(&program),
+        [IrOp::EvalPure { node, .. }, IrOp::CallFunction { .. }] if node == "c"
+    ));
+}
+
+#[test]
+fn opt_aggressive_merges_straight_line_calls() {
+    let mut graph = start_graph("calls");
+    exec_node(&mut graph, "print_1", "print_string", &["then"]);
+    exec_node(&mut graph, "print_2", "print_string", &["then"]);
+    exec_node(&mut graph, "branch_1", "branch", &["True", "False"]);
+    exec_node(&mut graph, "print_3", "print_string", &["then"]);
+    graph.add_connection(Connection::execution("start", "exec", "print_1", "exec_in"));
+    graph.add_connection(Connection::execution("print_1", "then", "print_2", "exec_in"));
+    graph.add_connection(Connection::execution("print_2", "then", "branch_1", "exec_in"));
+    graph.add_connection(Connection::execution("branch_1", "True", "print_3", "exec_in"));
+
+    assert_eq!(event_body(&optimized(&graph, OptLevel::Basic)).len(), 3);
+
+    let program = optimized(&graph, OptLevel::Aggressive);
+    let [IrOp::Sequence { ops }, IrOp::Branch { arms, .. }] = event_body(&program) else {
+        panic!("expected a sequence and a branch, got {}", program);
+    };
+    assert_eq!(ops.len(), 2);
+    assert!(matches!(arms[0].body.as_slice(), [IrOp::CallFunction { .. }]));
+}
+
+#[test]
+fn opt_aggressive_unwraps_loops_that_cannot_repeat() {
+    let mut graph = build_constant_branch_graph(false);
+    graph.add_connection(Connection::execution("print_yes", "then", "branch_1", "exec_in"));
+
+    let basic = optimized(&graph, OptLevel::Basic);
+    assert!(matches!(event_body(&basic), [IrOp::Loop { .. }]), "{}", basic);
+
+    let mut program = basic.clone();
+    let stats = program.optimize(&ir_provider(), OptLevel::Aggressive);
+    assert_eq!(stats.merged_blocks, 1);
+    assert!(
+        matches!(event_body(&program), [IrOp::CallFunction { node, .. }] if node == "print_no"),
+        "{}",
+        program
+    );
+}
@@ -0,0 +1,215 @@
+//! Tests for LanguageProfile and its Rust and WGSL implementations.
+
+mod common;
+
+use common::*;
+use graphy::utils::{LanguageProfile, RustProfile, VariableNameGenerator, WgslProfile};
+use graphy::*;
+use std::collections::HashMap;
+
+// ===========================================================================
+// Identifiers
+// ===========================================================================
+
+#[test]
+fn rust_sanitize_keeps_valid_identifiers() {
+    assert_eq!(RustProfile.sanitize_identifier("my_var"), "my_var");
+    assert_eq!(RustProfile.sanitize_identifier("café"), "café");
+}
+
+#[test]
+fn rust_sanitize_escapes_reserved_words() {
+    assert_eq!(RustProfile.sanitize_identifier("type"), "type_");
+    assert_eq!(RustProfile.sanitize_identifier("self"), "self_");
+    assert_eq!(RustProfile.sanitize_identifier("_"), "__");
+    assert!(!RustProfile.is_reserved("vec3"));
+}
+
+#[test]
+fn rust_sanitize_fixes_leading_digit_and_empty() {
+    assert_eq!(RustProfile.sanitize_identifier("1st"), "_1st");
+    assert_eq!(RustProfile.sanitize_identifier(""), "__");
+    assert_eq!(RustProfile.sanitize_identifier("a-b"), "a_b");
+}
+
+#[test]
+fn wgsl_sanitize_restricts_to_ascii() {
+    assert_eq!(WgslProfile.sanitize_identifier("café"), "caf_");
+    assert_eq!(WgslProfile.result_variable_name("узел_1"), "node______1_result");
+}
+
+#[test]
+fn wgsl_sanitize_avoids_double_underscore_prefix() {
+    assert_eq!(WgslProfile.sanitize_identifier("__x"), "v__x");
+    assert_eq!(WgslProfile.sanitize_identifier("_1"), "_1");
+    assert_eq!(WgslProfile.sanitize_identifier("_"), "v__");
+}
+
+#[test]
+fn wgsl_sanitize_escapes_reserved_words() {
+    assert_eq!(WgslProfile.sanitize_identifier("var"), "var_");
+    assert_eq!(WgslProfile.sanitize_identifier("struct"), "struct_");
+    assert_eq!(WgslProfile.sanitize_identifier("position"), "position");
+}
+
+// ===========================================================================
+// Defaults and literals
+// ===========================================================================
+
+#[test]
+fn rust_defaults_match_variable_gen() {
+    for ty in ["f32", "i64", "bool", "String", "(f32, bool)", "Vec<u8>"] {
+        assert_eq!(RustProfile.default_value(ty), utils::get_default_value_for_type(ty));
+    }
+}
+
+#[test]
+fn wgsl_defaults() {
+    assert_eq!(WgslProfile.default_value("f32"), "0.0");
+    assert_eq!(WgslProfile.default_value("i32"), "0i");
+    assert_eq!(WgslProfile.default_value("u32"), "0u");
+    assert_eq!(WgslProfile.default_value("bool"), "false");
+    assert_eq!(WgslProfile.default_value("vec4<f32>"), "vec4<f32>()");
+}
+
+#[test]
+fn rust_literals() -> Result<()> {
+    assert_eq!(RustProfile.format_literal(&PropertyValue::Number(3.0))?, "3");
+    assert_eq!(RustProfile.format_literal(&PropertyValue::String("hi".into()))?, "\"hi\"");
+    assert_eq!(RustProfile.format_literal(&PropertyValue::Vector2(1.0, 2.5))?, "Vec2::new(1.0, 2.5)");
+    assert_eq!(RustProfile.format_literal(&PropertyValue::Vector3(0.0, -1.0, 3.5))?, "Vec3::new(0.0, -1.0, 3.5)");
+    assert_eq!(RustProfile.format_literal(&PropertyValue::Color(1.0, 0.5, 0.0, 1.0))?, "Vec4::new(1.0, 0.5, 0.0, 1.0)");
+    assert_eq!(
+        RustProfile.format_literal(&PropertyValue::Array(vec![PropertyValue::Integer(1), PropertyValue::Integer(2)]))?,
+        "vec![1, 2]"
+    );
+    Ok(())
+}
+
+#[test]
+fn rust_literals_are_valid_for_every_number() -> Result<()> {
+    assert_eq!(RustProfile.format_literal(&PropertyValue::Number(-2.0))?, "-2");
+    assert_eq!(RustProfile.format_literal(&PropertyValue::Number(0.1))?, "0.1");
+    assert_eq!(RustProfile.format_literal(&PropertyValue::Number(1e300))?, "1e300");
+    assert_eq!(RustProfile.format_literal(&PropertyValue::Number(f64::NAN))?, "f64::NAN");
+    assert_eq!(RustProfile.format_literal(&PropertyValue::Number(f64::NEG_INFINITY))?, "f64::NEG_INFINITY");
+    assert_eq!(
+        RustProfile.format_literal(&PropertyValue::Vector2(f64::INFINITY, 1e-7))?,
+        "Vec2::new(f32::INFINITY, 1e-7)"
+    );
+    Ok(())
+}
+
+#[test]
+fn rust_typed_literals() -> Result<()> {
+    let vector = PropertyValue::Vector2(1.0, 2.5);
+    assert_eq!(RustProfile.format_typed_literal(&vector, "Vec2")?, "Vec2::new(1.0, 2.5)");
+    assert_eq!(RustProfile.format_typed_literal(&vector, "(f32, f32)")?, "(1.0, 2.5)");
+    assert_eq!(RustProfile.format_typed_literal(&PropertyValue::Vector2(1.0, 2.0), "(i32, i32)")?, "(1, 2)");
+    assert_eq!(RustProfile.format_typed_literal(&vector, "(f32, f32, f32)")?, "Vec2::new(1.0, 2.5)");
+    assert_eq!(RustProfile.format_typed_literal(&PropertyValue::Number(3.0), "f32")?, "3.0");
+    assert_eq!(RustProfile.format_typed_literal(&PropertyValue::Number(3.0), "i64")?, "3");
+    assert_eq!(WgslProfile.format_typed_literal(&vector, "vec2<f32>")?, "vec2<f32>(1.0, 2.5)");
+    Ok(())
+}
+
+#[test]
+fn rust_typed_literals_fit_their_type() -> Result<()> {
+    let number = |n: f64, type_str: &str| RustProfile.format_typed_literal(&PropertyValue::Number(n), type_str);
+    assert_eq!(number(-128.0, "i8")?, "-128");
+    assert_eq!(number(-0.0, "u8")?, "0");
+    assert_eq!(number(1e20, "u128")?, "100000000000000000000");
+    assert_eq!(number(f64::NAN, "f32")?, "f32::NAN");
+    assert_eq!(number(f64::NEG_INFINITY, "f64")?, "f64::NEG_INFINITY");
+    assert_eq!(
+        RustProfile.format_typed_literal(&PropertyValue::Vector2(f64::NAN, 1.0), "(f32, f64)")?,
+        "(f32::NAN, 1.0)"
+    );
+
+    for (n, type_str) in [(2.5, "i64"), (256.0, "u8"), (-1.0, "usize"), (9.3e18, "i64"), (f64::INFINITY, "i32")] {
+        assert!(matches!(number(n, type_str), Err(GraphyError::CodeGeneration(_))), "{} as {}", n, type_str);
+    }
+    assert!(RustProfile.format_typed_literal(&PropertyValue::Vector2(0.5, 1.0), "(i32, i32)").is_err());
+    Ok(())
+}
+
+#[test]
+fn wgsl_literals() -> Result<()> {
+    assert_eq!(WgslProfile.format_literal(&PropertyValue::Number(3.0))?, "3.0");
+    assert_eq!(WgslProfile.format_literal(&PropertyValue::Integer(-4))?, "-4");
+    assert_eq!(
+        WgslProfile.format_literal(&PropertyValue::Vector3(1.0, 0.0, 0.5))?,
+        "vec3<f32>(1.0, 0.0, 0.5)"
+    );
+    assert_eq!(
+        WgslProfile.format_literal(&PropertyValue::Color(1.0, 1.0, 1.0, 1.0))?,
+        "vec4<f32>(1.0, 1.0, 1.0, 1.0)"
+    );
+    assert_eq!(
+        WgslProfile.format_literal(&PropertyValue::Array(vec![PropertyValue::Number(1.0), PropertyValue::Number(2.0)]))?,
+        "array(1.0, 2.0)"
+    );
+    Ok(())
+}
+
+#[test]
+fn wgsl_rejects_unrepresentable_literals() {
+    assert!(WgslProfile.format_literal(&PropertyValue::String("hi".into())).is_err());
+    assert!(WgslProfile.format_literal(&PropertyValue::Map(HashMap::new())).is_err());
+    assert!(WgslProfile.format_literal(&PropertyValue::Array(Vec::new())).is_err());
+}
+
+// ===========================================================================
+// Consumers
+// ===========================================================================
+
+#[test]
+fn variable_gen_uses_profile() {
+    let mut gen = VariableNameGenerator::new().with_profile(WgslProfile);
+    assert_eq!(gen.profile().name(), "wgsl");
+    assert_eq!(gen.generate_for_node("é"), "node___result");
+    assert_eq!(gen.generate_for_node("é"), "node___result_1");
+}
+
+#[test]
+fn data_resolver_uses_profile() -> Result<()> {
+    let provider = TestMetadataProvider::with_math_nodes();
+    let mut graph = GraphDescription::new("wgsl");
+    let mut node = NodeInstance::new("add_é", "add", Position::zero());
+    node.add_input_pin("a", DataType::Typed("i64".into()));
+    node.add_input_pin("b", DataType::Typed("i64".into()));
+    node.add_output_pin("result", DataType::Typed("i64".into()));
+    node.set_property("a", PropertyValue::Number(2.0));
+    graph.add_node(node);
+
+    let rust = DataResolver::build(&graph, &provider)?;
+    assert_eq!(rust.get_result_variable("add_é").unwrap(), "node_add_é_result");
+    assert!(matches!(rust.get_input_source("add_é", "a"), Some(DataSource::Constant(PropertyValue::Number(n), _)) if *n == 2.0));
+
+    let wgsl = DataResolver::build_with_profile(&graph, &provider, &WgslProfile)?;
+    assert_eq!(wgsl.get_result_variable("add_é").unwrap(), "node_add___result");
+    assert!(matches!(wgsl.get_input_source("add_é", "a"), Some(DataSource::Constant(PropertyValue::Number(n), _)) if *n == 2.0));
+    Ok(())
+}
+
+#[test]
+fn data_resolver_profile_keeps_unrepresentable_property() -> Result<()> {
+    let provider = TestMetadataProvider::with_function_nodes();
+    let mut graph = GraphDescription::new("wgsl");
+    let mut node = NodeInstance::new("print_1", "print_string", Position::zero());
+    node.add_input_pin("message", DataType::Typed("String".into()));
+    node.set_property("message", PropertyValue::String("hello".into()));
+    graph.add_node(node);
+
+    // Literals are only written by the generator, which reports the error
+    for resolver in [
+        DataResolver::build_with_profile(&graph, &provider, &WgslProfile)?,
+        DataResolver::build_parallel_with_profile(&graph, &provider, &WgslProfile)?,
+    ] {
+        assert!(matches!(
+            resolver.get_input_source("print_1", "message"),
+            Some(DataSource::Constant(PropertyValue::String(s), _)) if s == "hello"
+        ));
+    }
+    Ok(())
+}
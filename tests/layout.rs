@@ -0,0 +1,203 @@
+//! Tests for automatic layered layout.
+
+mod common;
+
+use common::*;
+use graphy::export::Direction;
+use graphy::layout::{auto_layout, compute_layout, LayoutOptions};
+use graphy::*;
+
+fn node(graph: &mut GraphDescription, id: &str) {
+    graph.add_node(NodeInstance::new(id, "add", Position::zero()));
+}
+
+fn edge(graph: &mut GraphDescription, source: &str, target: &str) {
+    graph.add_connection(Connection::data(source, "result", target, "a"));
+}
+
+fn position(graph: &GraphDescription, id: &str) -> Position {
+    graph.get_node(id).unwrap().position
+}
+
+/// Checks that no two nodes share a layer closer than the node spacing
+fn assert_no_overlap(graph: &GraphDescription, options: &LayoutOptions) {
+    let positions: Vec<Position> = graph.nodes.values().map(|n| n.position).collect();
+    for (i, a) in positions.iter().enumerate() {
+        for b in &positions[i + 1..] {
+            if a.x == b.x {
+                assert!((a.y - b.y).abs() >= options.node_spacing - 1e-9, "{:?} overlaps {:?}", a, b);
+            }
+        }
+    }
+}
+
+// ===========================================================================
+// Layering
+// ===========================================================================
+
+#[test]
+fn layout_empty_graph() {
+    let mut graph = GraphDescription::new("empty");
+    auto_layout(&mut graph, LayoutOptions::new());
+    assert!(compute_layout(&graph, &LayoutOptions::new()).is_empty());
+}
+
+#[test]
+fn layout_exec_chain_in_a_row() {
+    let mut graph = build_exec_chain(5);
+    for node in graph.nodes.values_mut() {
+        node.position = Position::zero();
+    }
+
+    let options = LayoutOptions::new();
+    auto_layout(&mut graph, options);
+    for i in 0..5 {
+        let p = position(&graph, &format!("fn_{}", i));
+        assert_eq!(p, Position::new(i as f64 * options.layer_spacing, 0.0));
+    }
+}
+
+#[test]
+fn layout_diamond() {
+    let mut graph = build_diamond_graph();
+    let options = LayoutOptions::new();
+    auto_layout(&mut graph, options);
+
+    let (a, b, c, d) = (
+        position(&graph, "node_a"),
+        position(&graph, "node_b"),
+        position(&graph, "node_c"),
+        position(&graph, "node_d"),
+    );
+    assert!(a.x < b.x && b.x == c.x && c.x < d.x);
+    assert_eq!((b.y - c.y).abs(), options.node_spacing);
+    // The join sits between the two branches, level with the fork
+    assert_eq!(d.y, (b.y + c.y) / 2.0);
+    assert_eq!(a.y, d.y);
+}
+
+#[test]
+fn layout_places_pure_inputs_next_to_consumer() {
+    let mut graph = build_exec_chain(4);
+    node(&mut graph, "value");
+    graph.add_connection(Connection::data("value", "result", "fn_3", "message"));
+
+    let options = LayoutOptions::new();
+    auto_layout(&mut graph, options);
+    assert_eq!(position(&graph, "value").x, position(&graph, "fn_2").x);
+}
+
+#[test]
+fn layout_breaks_cycles() {
+    let mut graph = GraphDescription::new("loop");
+    for id in ["start", "head", "body"] {
+        node(&mut graph, id);
+    }
+    edge(&mut graph, "start", "head");
+    edge(&mut graph, "head", "body");
+    edge(&mut graph, "body", "head");
+
+    auto_layout(&mut graph, LayoutOptions::new());
+    let (start, head, body) = (position(&graph, "start"), position(&graph, "head"), position(&graph, "body"));
+    assert!(start.x < head.x && head.x < body.x);
+}
+
+#[test]
+fn layout_ignores_self_loops_and_dangling_connections() {
+    let mut graph = GraphDescription::new("odd");
+    node(&mut graph, "a");
+    edge(&mut graph, "a", "a");
+    edge(&mut graph, "a", "missing");
+
+    auto_layout(&mut graph, LayoutOptions::new());
+    assert_eq!(position(&graph, "a"), Position::zero());
+}
+
+// ===========================================================================
+// Ordering and placement
+// ===========================================================================
+
+#[test]
+fn layout_reduces_crossings() {
+    // Sorted by ID, the second layer would start crossed: z1 first, y2 second
+    let mut graph = GraphDescription::new("crossed");
+    for id in ["a1", "a2", "y2", "z1"] {
+        node(&mut graph, id);
+    }
+    edge(&mut graph, "a1", "z1");
+    edge(&mut graph, "a2", "y2");
+
+    auto_layout(&mut graph, LayoutOptions::new());
+    let (a1, a2) = (position(&graph, "a1"), position(&graph, "a2"));
+    let (z1, y2) = (position(&graph, "z1"), position(&graph, "y2"));
+    assert!((a1.y < a2.y) == (z1.y < y2.y));
+}
+
+#[test]
+fn layout_long_edges_do_not_overlap_nodes() {
+    let mut graph = build_exec_chain(4);
+    node(&mut graph, "source");
+    edge(&mut graph, "source", "fn_0");
+    edge(&mut graph, "source", "fn_3");
+    for i in 0..4 {
+        node(&mut graph, &format!("side_{}", i));
+        edge(&mut graph, &format!("fn_{}", i), &format!("side_{}", i));
+    }
+
+    let options = LayoutOptions::new();
+    auto_layout(&mut graph, options);
+    assert_no_overlap(&graph, &options);
+}
+
+#[test]
+fn layout_many_disconnected_nodes() {
+    let mut graph = GraphDescription::new("scattered");
+    for i in 0..10 {
+        node(&mut graph, &format!("n{}", i));
+    }
+
+    let options = LayoutOptions::new();
+    auto_layout(&mut graph, options);
+    assert_no_overlap(&graph, &options);
+    assert!(graph.nodes.values().all(|n| n.position.x == 0.0));
+}
+
+#[test]
+fn layout_is_deterministic() {
+    let graph = build_diamond_graph();
+    let options = LayoutOptions::new();
+    assert_eq!(compute_layout(&graph, &options), compute_layout(&graph.clone(), &options));
+}
+
+// ===========================================================================
+// Options
+// ===========================================================================
+
+#[test]
+fn layout_top_to_bottom_swaps_axes() {
+    let graph = build_diamond_graph();
+    let horizontal = compute_layout(&graph, &LayoutOptions::new());
+    let vertical = compute_layout(&graph, &LayoutOptions::new().with_direction(Direction::TopToBottom));
+
+    for (id, p) in &horizontal {
+        assert_eq!(vertical[id], Position::new(p.y, p.x));
+    }
+}
+
+#[test]
+fn layout_origin_and_spacing() {
+    let mut graph = build_exec_chain(2);
+    node(&mut graph, "other");
+    edge(&mut graph, "other", "fn_1");
+
+    let options = LayoutOptions::new()
+        .with_origin(Position::new(100.0, 50.0))
+        .with_spacing(300.0, 80.0);
+    auto_layout(&mut graph, options);
+
+    let (fn_0, other, fn_1) = (position(&graph, "fn_0"), position(&graph, "other"), position(&graph, "fn_1"));
+    assert_eq!(fn_0.x, 100.0);
+    assert_eq!(fn_1.x, 400.0);
+    assert_eq!((fn_0.y - other.y).abs(), 80.0);
+    assert_eq!(fn_0.y.min(other.y), 50.0);
+}
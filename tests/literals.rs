@@ -0,0 +1,99 @@
+//! Tests for writing constant inputs as literals of the generator's language.
+
+mod common;
+
+use common::*;
+use graphy::generation::compile;
+use graphy::utils::{RustProfile, WgslProfile};
+use graphy::*;
+
+fn provider() -> TestMetadataProvider {
+    let mut provider = TestMetadataProvider::comprehensive();
+    provider.add(
+        NodeMetadata::new("move_to", NodeTypes::fn_, "scene")
+            .with_params(vec![ParamInfo::new("target", "Vec2"), ParamInfo::new("speed", "f32")])
+            .with_exec_outputs(vec!["then".to_string()]),
+    );
+    provider
+}
+
+/// `start -> move_1(target, speed)`, both inputs set by properties
+fn build_graph(target: PropertyValue, speed: PropertyValue) -> GraphDescription {
+    let mut graph = GraphDescription::new("literals");
+    event_node(&mut graph, "start", "on_start");
+
+    let mut node = NodeInstance::new("move_1", "move_to", Position::zero());
+    node.add_input_pin("exec_in", DataType::Execution);
+    node.add_input_pin("target", DataType::Typed("Vec2".into()));
+    node.add_input_pin("speed", DataType::Typed("f32".into()));
+    node.add_output_pin("then", DataType::Execution);
+    node.set_property("target", target);
+    node.set_property("speed", speed);
+    graph.add_node(node);
+
+    graph.add_connection(Connection::execution("start", "exec", "move_1", "exec_in"));
+    graph
+}
+
+fn compile_with(graph: &GraphDescription, generator: &mut CallGenerator) -> Result<String> {
+    compile(graph, &provider(), generator)
+}
+
+// ============================================================================
+// Languages
+// ============================================================================
+
+#[test]
+fn literals_follow_the_generator_language() -> Result<()> {
+    let graph = build_graph(PropertyValue::Vector2(1.0, 2.0), PropertyValue::Number(3.0));
+
+    let rust = compile_with(&graph, &mut CallGenerator::new().with_language(RustProfile))?;
+    assert!(rust.ends_with("move_to(Vec2::new(1.0, 2.0), 3.0);\n"), "{}", rust);
+
+    let wgsl = compile_with(&graph, &mut CallGenerator::new().with_language(WgslProfile))?;
+    assert!(wgsl.ends_with("move_to(vec2<f32>(1.0, 2.0), 3.0);\n"), "{}", wgsl);
+    Ok(())
+}
+
+#[test]
+fn literals_can_be_overridden_by_type() -> Result<()> {
+    let graph = build_graph(PropertyValue::Vector2(0.5, -1.0), PropertyValue::Number(3.0));
+    let mut generator = CallGenerator::new().with_language(RustProfile).with_f32_suffix(true);
+
+    let code = compile_with(&graph, &mut generator)?;
+    assert!(code.ends_with("move_to(Vec2::new(0.5, -1.0), 3.0f32);\n"), "{}", code);
+    Ok(())
+}
+
+#[test]
+fn literals_metadata_defaults_use_the_language() -> Result<()> {
+    let mut graph = build_graph(PropertyValue::Vector2(1.0, 2.0), PropertyValue::Number(1.0));
+    graph.get_node_mut("move_1").unwrap().properties.remove("speed");
+    let mut provider = provider();
+    provider.add(
+        NodeMetadata::new("move_to", NodeTypes::fn_, "scene")
+            .with_params(vec![
+                ParamInfo::new("target", "Vec2"),
+                ParamInfo::new("speed", "f32").with_default(PropertyValue::Number(0.25)),
+            ])
+            .with_exec_outputs(vec!["then".to_string()]),
+    );
+
+    let code = compile(&graph, &provider, &mut CallGenerator::new().with_language(WgslProfile))?;
+    assert!(code.ends_with("move_to(vec2<f32>(1.0, 2.0), 0.25);\n"), "{}", code);
+    Ok(())
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+#[test]
+fn literals_unrepresentable_fail_at_their_pin() {
+    let graph = build_graph(PropertyValue::String("north".into()), PropertyValue::Number(1.0));
+
+    let error = compile_with(&graph, &mut CallGenerator::new().with_language(WgslProfile)).unwrap_err();
+    assert!(matches!(error.root(), GraphyError::CodeGeneration(_)), "{:?}", error);
+    assert_eq!(error.node(), Some("move_1"));
+    assert_eq!(error.pin(), Some("target"));
+}
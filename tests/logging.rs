@@ -0,0 +1,91 @@
+//! Tests for logging verbosity and compile log output.
+
+mod common;
+
+use common::*;
+use graphy::generation::{compile_with_options, CompileOptions};
+use graphy::utils::logging::{self, Verbosity};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+/// Log sink shared between a test and its subscriber
+#[derive(Clone, Default)]
+struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for LogBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Compiles the branch graph with a trace-level subscriber and returns the logs
+fn compile_logs(verbosity: Verbosity) -> String {
+    let buffer = LogBuffer::default();
+    let writer = buffer.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::TRACE)
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+
+    let graph = build_branch_graph();
+    let provider = TestMetadataProvider::comprehensive();
+    let options = CompileOptions::new().with_verbosity(verbosity);
+    tracing::subscriber::with_default(subscriber, || {
+        let mut generator = NoopGenerator::new();
+        compile_with_options(&graph, &provider, &mut generator, &options).unwrap();
+    });
+
+    let logs = buffer.0.lock().unwrap();
+    String::from_utf8(logs.clone()).unwrap()
+}
+
+// ===========================================================================
+// Verbosity scopes
+// ===========================================================================
+
+#[test]
+fn verbosity_defaults_to_normal() {
+    assert_eq!(logging::verbosity(), Verbosity::Normal);
+    assert!(!logging::is_verbose());
+}
+
+#[test]
+fn with_verbosity_restores_previous_level() {
+    logging::with_verbosity(Verbosity::Verbose, || {
+        assert!(logging::is_verbose());
+        logging::with_verbosity(Verbosity::Quiet, || assert_eq!(logging::verbosity(), Verbosity::Quiet));
+        assert!(logging::is_verbose());
+    });
+    assert_eq!(logging::verbosity(), Verbosity::Normal);
+}
+
+// ===========================================================================
+// Compile logs
+// ===========================================================================
+
+#[test]
+fn compile_normal_logs_phases_without_per_item_logs() {
+    let logs = compile_logs(Verbosity::Normal);
+
+    assert!(logs.contains("[ROUTING] Built execution routing table"), "{}", logs);
+    assert!(logs.contains("compile{graph="), "{}", logs);
+    assert!(logs.contains("analysis{pass=\"exec_routing\"}"), "{}", logs);
+    assert!(!logs.contains("[ROUTING]   ("), "{}", logs);
+}
+
+#[test]
+fn compile_verbose_logs_every_route() {
+    let logs = compile_logs(Verbosity::Verbose);
+    assert!(logs.contains("[ROUTING]   (branch_1, True) -> [\"print_true\"]"), "{}", logs);
+}
+
+#[test]
+fn compile_quiet_logs_nothing() {
+    assert!(compile_logs(Verbosity::Quiet).is_empty());
+}
@@ -0,0 +1,185 @@
+//! Tests for memoized pure nodes.
+
+mod common;
+
+use common::*;
+use graphy::generation::{compile, generate_exec_output, CodeGenerator, MemoKey, MemoizedCall};
+use graphy::*;
+
+/// `on_start`, `log` (takes an `f64`), and `noise` (takes `x` of `param_type`)
+fn registry(param_type: &str, memoize: bool) -> NodeRegistry {
+    let mut registry = NodeRegistry::new();
+    registry.register(NodeMetadata::new("on_start", NodeTypes::event, "Events").with_exec_outputs(vec!["exec".into()]));
+    registry.register(
+        NodeMetadata::new("log", NodeTypes::fn_, "Debug")
+            .with_params(vec![ParamInfo::new("value", "f64")])
+            .with_exec_outputs(vec!["exec_out".into()]),
+    );
+    registry.register(
+        NodeMetadata::new("noise", NodeTypes::pure, "Noise")
+            .with_params(vec![ParamInfo::new("x", param_type)])
+            .with_return_type("f64")
+            .with_memoize(memoize),
+    );
+    registry
+}
+
+/// `start -> log_1`, with `noise_1` feeding `log_1.value`
+fn noise_graph(registry: &NodeRegistry) -> GraphDescription {
+    let mut graph = GraphDescription::new("terrain");
+    graph.add_node_of_type("on_start", "start", registry).unwrap();
+    graph.add_node_of_type("log", "log_1", registry).unwrap();
+    graph.add_node_of_type("noise", "noise_1", registry).unwrap();
+    graph.nodes.get_mut("noise_1").unwrap().set_property("x", PropertyValue::Number(0.5));
+    graph.add_connection(Connection::execution("start", "exec", "log_1", "exec_in"));
+    graph.add_connection(Connection::data("noise_1", "result", "log_1", "value"));
+    graph
+}
+
+// ============================================================================
+// Metadata
+// ============================================================================
+
+#[test]
+fn memoize_serializes_only_when_set() {
+    let plain = serde_json::to_value(NodeMetadata::new("noise", NodeTypes::pure, "Noise")).unwrap();
+    assert!(plain.get("memoize").is_none());
+
+    let memoized = NodeMetadata::new("noise", NodeTypes::pure, "Noise").with_memoize(true);
+    let json = serde_json::to_value(&memoized).unwrap();
+    assert_eq!(json["memoize"], true);
+    assert!(serde_json::from_value::<NodeMetadata>(json).unwrap().memoize);
+}
+
+// ============================================================================
+// Code generation
+// ============================================================================
+
+#[test]
+fn memoize_wraps_calls_in_a_cache() {
+    let registry = registry("f64", true);
+    let code = compile(&noise_graph(&registry), &registry, &mut CallGenerator::new()).unwrap();
+
+    assert!(
+        code.contains("static MEMO_NOISE_1: ::std::cell::RefCell<::std::collections::HashMap<(u64,), f64>>"),
+        "{}",
+        code
+    );
+    assert!(code.contains("let (memo_0,) = (0.5,);"), "{}", code);
+    assert!(
+        code.contains("MEMO_NOISE_1.with(|cache| cache.borrow_mut().entry((memo_0.to_bits(),)).or_insert_with(|| noise(memo_0)).clone())"),
+        "{}",
+        code
+    );
+    assert!(code.trim_end().ends_with("});"), "{}", code);
+}
+
+#[test]
+fn memoize_off_generates_plain_calls() {
+    let registry = registry("f64", false);
+    let code = compile(&noise_graph(&registry), &registry, &mut CallGenerator::new()).unwrap();
+    assert_eq!(code, "log(noise(0.5));\n");
+}
+
+#[test]
+fn memoize_skips_unhashable_inputs() {
+    let registry = registry("Vec3", true);
+    let mut graph = noise_graph(&registry);
+    graph.nodes.get_mut("noise_1").unwrap().properties.clear();
+    let code = compile(&graph, &registry, &mut CallGenerator::new()).unwrap();
+    assert!(!code.contains("MEMO_"), "{}", code);
+    assert!(code.starts_with("log(noise("), "{}", code);
+}
+
+#[test]
+fn memoize_skips_volatile_nodes() {
+    let mut registry = registry("f64", true);
+    let volatile = registry.get_node_metadata("noise").unwrap().clone().with_volatile(true);
+    registry.register(volatile);
+    let code = compile(&noise_graph(&registry), &registry, &mut CallGenerator::new()).unwrap();
+    assert_eq!(code, "log(noise(0.5));\n");
+}
+
+#[test]
+fn memo_keys_follow_rust_types() {
+    let generator = CallGenerator::new();
+    let key = |type_string: &str| generator.memo_key(type_string, "v");
+    assert_eq!(
+        key("i64"),
+        Some(MemoKey {
+            key_type: "i64".into(),
+            expr: "v".into()
+        })
+    );
+    assert_eq!(key("String").unwrap().expr, "v.clone()");
+    assert_eq!(key("f32").unwrap().key_type, "u32");
+    assert!(key("Vec3").is_none());
+    assert!(key("Vec<i64>").is_none());
+}
+
+/// Generator opting out of memoization entirely
+struct PlainGenerator;
+
+impl CodeGenerator for PlainGenerator {
+    fn generate_event<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        metadata: &NodeMetadata,
+    ) -> Result<()> {
+        for pin in &metadata.exec_outputs {
+            generate_exec_output(self, ctx, &node.id, pin)?;
+        }
+        Ok(())
+    }
+
+    fn generate_function_node<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        metadata: &NodeMetadata,
+        args: &[String],
+    ) -> Result<()> {
+        CallGenerator::new().generate_function_node(ctx, node, metadata, args)
+    }
+
+    fn generate_control_flow<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &mut CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        _metadata: &NodeMetadata,
+        _args: &[String],
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn generate_pure_expression<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        metadata: &NodeMetadata,
+        args: &[String],
+    ) -> Result<String> {
+        CallGenerator::new().generate_pure_expression(ctx, node, metadata, args)
+    }
+
+    fn memo_key(&self, _type_string: &str, _value: &str) -> Option<MemoKey> {
+        None
+    }
+
+    fn generate_memoized<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        _call: &MemoizedCall,
+    ) -> Result<String> {
+        unreachable!("no input can be cached")
+    }
+}
+
+#[test]
+fn memoize_opt_out_through_memo_key() {
+    let registry = registry("f64", true);
+    let code = compile(&noise_graph(&registry), &registry, &mut PlainGenerator).unwrap();
+    assert_eq!(code, "log(noise(0.5));\n");
+}
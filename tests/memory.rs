@@ -0,0 +1,117 @@
+//! Tests for memory accounting: HeapSize, MemoryStats and the graph and
+//! DataResolver reports.
+
+mod common;
+
+use common::*;
+use graphy::utils::{HeapSize, MemoryStats};
+use graphy::*;
+
+// ===========================================================================
+// HeapSize
+// ===========================================================================
+
+#[test]
+fn heap_size_counts_capacity() {
+    let text = String::with_capacity(64);
+    assert_eq!(text.heap_bytes(), 64);
+
+    let numbers: Vec<u64> = Vec::with_capacity(10);
+    assert_eq!(numbers.heap_bytes(), 80);
+
+    let names = vec![String::from("abc"), String::from("de")];
+    assert_eq!(names.heap_bytes(), 2 * std::mem::size_of::<String>() + 5);
+}
+
+#[test]
+fn heap_size_follows_nested_property_values() {
+    let flat = PropertyValue::Number(1.0);
+    assert_eq!(flat.heap_bytes(), 0);
+
+    let nested = PropertyValue::Array(vec![
+        PropertyValue::String("hello".into()),
+        PropertyValue::Array(vec![PropertyValue::String("world".into())]),
+    ]);
+    assert!(nested.heap_bytes() > 10);
+}
+
+#[test]
+fn heap_size_of_empty_map_is_zero() {
+    let map: std::collections::HashMap<String, String> = Default::default();
+    assert_eq!(map.heap_bytes(), 0);
+}
+
+// ===========================================================================
+// MemoryStats
+// ===========================================================================
+
+#[test]
+fn memory_stats_sums_components() {
+    let mut stats = MemoryStats::new();
+    stats.add("a", 10);
+    stats.add("b", 32);
+
+    assert_eq!(stats.total_bytes(), 42);
+    assert_eq!(stats.get("b"), Some(32));
+    assert_eq!(stats.get("c"), None);
+    assert!(stats.to_string().ends_with("total: 42 bytes"));
+}
+
+// ===========================================================================
+// GraphDescription::approx_memory_bytes
+// ===========================================================================
+
+#[test]
+fn graph_memory_grows_with_nodes_and_connections() {
+    let provider = TestMetadataProvider::with_math_nodes();
+    let small = build_linear_chain(10, &provider);
+    let large = build_linear_chain(1000, &provider);
+
+    assert!(small.approx_memory_bytes() >= std::mem::size_of::<GraphDescription>());
+    assert!(large.approx_memory_bytes() > small.approx_memory_bytes() * 50);
+
+    let stats = large.memory_stats();
+    assert_eq!(stats.total_bytes(), large.approx_memory_bytes());
+    assert!(stats.get("nodes").unwrap() > stats.get("connections").unwrap());
+    assert_eq!(stats.get("comments"), Some(0));
+}
+
+#[test]
+fn graph_memory_counts_node_properties() {
+    let mut graph = GraphDescription::new("properties");
+    graph.add_node(NodeInstance::new("node", "add", Position::zero()));
+    let before = graph.approx_memory_bytes();
+
+    graph
+        .get_node_mut("node")
+        .unwrap()
+        .set_property("label", PropertyValue::String("x".repeat(1000)));
+    assert!(graph.approx_memory_bytes() >= before + 1000);
+}
+
+// ===========================================================================
+// DataResolver::memory_stats
+// ===========================================================================
+
+#[test]
+fn data_resolver_memory_stats_report_tables() {
+    let provider = TestMetadataProvider::with_math_nodes();
+    let graph = build_linear_chain(100, &provider);
+    let resolver = DataResolver::build(&graph, &provider).unwrap();
+
+    let stats = resolver.memory_stats();
+    for table in ["symbols", "input_sources", "result_variables", "pure_evaluation_order", "dependencies"] {
+        assert!(stats.get(table).unwrap() > 0, "{} should use memory", table);
+    }
+    assert_eq!(stats.get("error_variables"), Some(0));
+    assert_eq!(stats.get("resolver"), Some(std::mem::size_of::<DataResolver>()));
+}
+
+#[test]
+fn data_resolver_memory_grows_with_graph() {
+    let provider = TestMetadataProvider::with_math_nodes();
+    let small = DataResolver::build(&build_linear_chain(10, &provider), &provider).unwrap();
+    let large = DataResolver::build_parallel(&build_linear_chain(1000, &provider), &provider).unwrap();
+
+    assert!(large.memory_stats().total_bytes() > small.memory_stats().total_bytes() * 10);
+}
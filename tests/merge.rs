@@ -0,0 +1,389 @@
+//! Tests for three-way graph merging.
+
+mod common;
+
+use common::*;
+use graphy::core::{GraphComment, GraphGroup, VariableDecl};
+use graphy::utils::{merge_graphs, merge_graphs_with, MergeConflict, MergeSide};
+use graphy::*;
+
+fn comment(text: &str) -> GraphComment {
+    GraphComment {
+        text: text.to_string(),
+        position: Position::zero(),
+        size: (100.0, 50.0),
+        attached_node_ids: Vec::new(),
+    }
+}
+
+fn set_property(graph: &mut GraphDescription, node_id: &str, key: &str, value: f64) {
+    graph
+        .get_node_mut(node_id)
+        .unwrap()
+        .set_property(key, PropertyValue::Number(value));
+}
+
+fn property(graph: &GraphDescription, node_id: &str, key: &str) -> Option<PropertyValue> {
+    graph.get_node(node_id).unwrap().get_property(key).cloned()
+}
+
+// ===========================================================================
+// Clean merges
+// ===========================================================================
+
+#[test]
+fn merge_unchanged_sides_returns_base() {
+    let base = build_diamond_graph();
+    let merged = merge_graphs(&base, &base, &base).unwrap();
+    assert!(merged.semantically_equals(&base));
+}
+
+#[test]
+fn merge_takes_one_sided_changes() {
+    let base = build_diamond_graph();
+    let mut ours = base.clone();
+    ours.add_node(NodeInstance::new("node_e", "add", Position::zero()));
+    ours.add_connection(Connection::data("node_d", "result", "node_e", "a"));
+
+    let mut theirs = base.clone();
+    theirs.remove_node("node_c");
+
+    let merged = merge_graphs(&base, &ours, &theirs).unwrap();
+    assert!(merged.get_node("node_e").is_some());
+    assert!(merged.get_node("node_c").is_none());
+    assert_eq!(merged.connections.len(), 3);
+    assert!(merged.connections.contains(&Connection::data("node_d", "result", "node_e", "a")));
+    assert!(!merged.connections.iter().any(|c| c.source_node == "node_c" || c.target_node == "node_c"));
+}
+
+#[test]
+fn merge_is_symmetric_without_conflicts() {
+    let base = build_diamond_graph();
+    let mut ours = base.clone();
+    set_property(&mut ours, "node_a", "a", 5.0);
+    let mut theirs = base.clone();
+    theirs.add_node(NodeInstance::new("node_e", "add", Position::zero()));
+
+    let left = merge_graphs(&base, &ours, &theirs).unwrap();
+    let right = merge_graphs(&base, &theirs, &ours).unwrap();
+    assert!(left.semantically_equals(&right));
+}
+
+#[test]
+fn merge_properties_of_same_node_independently() {
+    let base = build_diamond_graph();
+    let mut ours = base.clone();
+    set_property(&mut ours, "node_a", "a", 10.0);
+    let mut theirs = base.clone();
+    set_property(&mut theirs, "node_a", "b", 20.0);
+    theirs.get_node_mut("node_a").unwrap().position = Position::new(5.0, 5.0);
+
+    let merged = merge_graphs(&base, &ours, &theirs).unwrap();
+    assert_eq!(property(&merged, "node_a", "a"), Some(PropertyValue::Number(10.0)));
+    assert_eq!(property(&merged, "node_a", "b"), Some(PropertyValue::Number(20.0)));
+    assert_eq!(merged.get_node("node_a").unwrap().position, Position::new(5.0, 5.0));
+}
+
+#[test]
+fn merge_identical_changes_on_both_sides() {
+    let base = build_diamond_graph();
+    let mut ours = base.clone();
+    set_property(&mut ours, "node_b", "a", 3.0);
+    ours.add_node(NodeInstance::new("node_e", "add", Position::zero()));
+    ours.add_connection(Connection::data("node_d", "result", "node_e", "a"));
+    let theirs = ours.clone();
+
+    let merged = merge_graphs(&base, &ours, &theirs).unwrap();
+    assert_eq!(merged.connections.len(), base.connections.len() + 1);
+    assert_eq!(property(&merged, "node_b", "a"), Some(PropertyValue::Number(3.0)));
+}
+
+#[test]
+fn merge_connections_from_both_sides() {
+    let base = build_diamond_graph();
+    let mut ours = base.clone();
+    ours.connections.retain(|c| c.target_node != "node_d" || c.target_pin != "a");
+    let mut theirs = base.clone();
+    theirs.add_connection(Connection::data("node_a", "result", "node_d", "a"));
+
+    let merged = merge_graphs(&base, &ours, &theirs).unwrap();
+    assert!(!merged.connections.contains(&Connection::data("node_b", "result", "node_d", "a")));
+    assert!(merged.connections.contains(&Connection::data("node_a", "result", "node_d", "a")));
+    assert_eq!(merged.connections.len(), 4);
+}
+
+#[test]
+fn merge_comments_groups_and_variables() {
+    let mut base = build_diamond_graph();
+    base.comments.push(comment("old"));
+    base.add_group(GraphGroup::new("g1", "Inputs")).unwrap();
+
+    let mut ours = base.clone();
+    ours.comments.push(comment("ours"));
+    ours.add_variable("score", VariableDecl::new("i64"));
+    ours.get_group_mut("g1").unwrap().title = "Sources".into();
+
+    let mut theirs = base.clone();
+    theirs.comments.clear();
+    theirs.comments.push(comment("theirs"));
+    theirs.add_group(GraphGroup::new("g2", "Outputs")).unwrap();
+
+    let merged = merge_graphs(&base, &ours, &theirs).unwrap();
+    let texts: Vec<&str> = merged.comments.iter().map(|c| c.text.as_str()).collect();
+    assert_eq!(texts, ["ours", "theirs"]);
+    assert!(merged.variables.contains_key("score"));
+    let groups: Vec<(&str, &str)> = merged.groups.iter().map(|g| (g.id.as_str(), g.title.as_str())).collect();
+    assert_eq!(groups, [("g1", "Sources"), ("g2", "Outputs")]);
+}
+
+#[test]
+fn merge_metadata_fields() {
+    let mut base = GraphDescription::new("graph");
+    base.metadata.modified_at = "2024-01-01T00:00:00Z".into();
+    let mut ours = base.clone();
+    ours.metadata.description = "Player movement".into();
+    ours.metadata.modified_at = "2024-02-01T00:00:00Z".into();
+    let mut theirs = base.clone();
+    theirs.metadata.version = "1.1.0".into();
+    theirs.metadata.modified_at = "2024-03-01T00:00:00Z".into();
+
+    let merged = merge_graphs(&base, &ours, &theirs).unwrap();
+    assert_eq!(merged.metadata.description, "Player movement");
+    assert_eq!(merged.metadata.version, "1.1.0");
+    assert_eq!(merged.metadata.modified_at, "2024-03-01T00:00:00Z");
+}
+
+// ===========================================================================
+// Conflicts
+// ===========================================================================
+
+#[test]
+fn conflicting_property_edits() {
+    let base = build_diamond_graph();
+    let mut ours = base.clone();
+    set_property(&mut ours, "node_a", "a", 10.0);
+    let mut theirs = base.clone();
+    set_property(&mut theirs, "node_a", "a", 20.0);
+
+    let conflicts = merge_graphs(&base, &ours, &theirs).unwrap_err();
+    assert_eq!(
+        conflicts,
+        vec![MergeConflict::Property {
+            node_id: "node_a".into(),
+            key: "a".into(),
+            base: Some(PropertyValue::Number(1.0)),
+            ours: Some(PropertyValue::Number(10.0)),
+            theirs: Some(PropertyValue::Number(20.0)),
+        }]
+    );
+    assert_eq!(conflicts[0].to_string(), "Both sides changed property node_a.a");
+}
+
+#[test]
+fn conflicting_node_fields() {
+    let base = build_diamond_graph();
+    let mut ours = base.clone();
+    ours.get_node_mut("node_b").unwrap().node_type = "subtract".into();
+    let mut theirs = base.clone();
+    theirs.get_node_mut("node_b").unwrap().node_type = "divide".into();
+
+    let conflicts = merge_graphs(&base, &ours, &theirs).unwrap_err();
+    assert_eq!(
+        conflicts,
+        vec![MergeConflict::NodeField {
+            node_id: "node_b".into(),
+            field: "node_type".into(),
+        }]
+    );
+}
+
+#[test]
+fn conflicting_node_additions() {
+    let base = GraphDescription::new("graph");
+    let mut ours = base.clone();
+    ours.add_node(NodeInstance::new("node_1", "add", Position::zero()));
+    let mut theirs = base.clone();
+    theirs.add_node(NodeInstance::new("node_1", "multiply", Position::zero()));
+
+    let conflicts = merge_graphs(&base, &ours, &theirs).unwrap_err();
+    assert_eq!(conflicts, vec![MergeConflict::NodeAdded { node_id: "node_1".into() }]);
+}
+
+#[test]
+fn modify_remove_conflict() {
+    let base = build_diamond_graph();
+    let mut ours = base.clone();
+    ours.remove_node("node_b");
+    let mut theirs = base.clone();
+    set_property(&mut theirs, "node_b", "b", 7.0);
+
+    let conflicts = merge_graphs(&base, &ours, &theirs).unwrap_err();
+    assert_eq!(
+        conflicts,
+        vec![MergeConflict::NodeRemoved {
+            node_id: "node_b".into(),
+            removed_by: MergeSide::Ours,
+        }]
+    );
+
+    // Keeping the modified node keeps the connections only ours removed
+    let outcome = merge_graphs_with(&base, &ours, &theirs, MergeSide::Theirs);
+    assert_eq!(property(&outcome.graph, "node_b", "b"), Some(PropertyValue::Number(7.0)));
+
+    let outcome = merge_graphs_with(&base, &ours, &theirs, MergeSide::Ours);
+    assert!(outcome.graph.get_node("node_b").is_none());
+    assert!(!outcome.graph.connections.iter().any(|c| c.source_node == "node_b" || c.target_node == "node_b"));
+}
+
+#[test]
+fn connection_to_removed_node_is_dangling() {
+    let base = build_diamond_graph();
+    let mut ours = base.clone();
+    ours.add_node(NodeInstance::new("node_e", "add", Position::zero()));
+    ours.add_connection(Connection::data("node_c", "result", "node_e", "a"));
+    let mut theirs = base.clone();
+    theirs.remove_node("node_c");
+
+    let conflicts = merge_graphs(&base, &ours, &theirs).unwrap_err();
+    assert_eq!(
+        conflicts,
+        vec![MergeConflict::DanglingConnection {
+            connection: Connection::data("node_c", "result", "node_e", "a"),
+            missing_node: "node_c".into(),
+        }]
+    );
+
+    let outcome = merge_graphs_with(&base, &ours, &theirs, MergeSide::Ours);
+    assert!(outcome.graph.get_node("node_c").is_none());
+    assert!(outcome.graph.get_node("node_e").is_some());
+    let graph = &outcome.graph;
+    assert!(graph
+        .connections
+        .iter()
+        .all(|c| graph.nodes.contains_key(&c.source_node) && graph.nodes.contains_key(&c.target_node)));
+}
+
+#[test]
+fn graph_level_conflicts() {
+    let mut base = GraphDescription::new("graph");
+    base.add_variable("speed", VariableDecl::new("f64"));
+    let mut ours = base.clone();
+    ours.metadata.name = "ours".into();
+    ours.add_variable("speed", VariableDecl::new("f32"));
+    ours.add_graph_input("x", "f64");
+    let mut theirs = base.clone();
+    theirs.metadata.name = "theirs".into();
+    theirs.add_variable("speed", VariableDecl::new("i64"));
+    theirs.add_graph_input("y", "f64");
+
+    let conflicts = merge_graphs(&base, &ours, &theirs).unwrap_err();
+    assert_eq!(
+        conflicts,
+        vec![
+            MergeConflict::GraphField { field: "name".into() },
+            MergeConflict::GraphField {
+                field: "graph_inputs".into()
+            },
+            MergeConflict::Variable { name: "speed".into() },
+        ]
+    );
+}
+
+#[test]
+fn resolve_conflicts_toward_preferred_side() {
+    let base = build_diamond_graph();
+    let mut ours = base.clone();
+    set_property(&mut ours, "node_a", "a", 10.0);
+    set_property(&mut ours, "node_d", "b", 4.0);
+    let mut theirs = base.clone();
+    set_property(&mut theirs, "node_a", "a", 20.0);
+
+    let outcome = merge_graphs_with(&base, &ours, &theirs, MergeSide::Theirs);
+    assert!(!outcome.is_clean());
+    assert_eq!(outcome.conflicts.len(), 1);
+    assert_eq!(property(&outcome.graph, "node_a", "a"), Some(PropertyValue::Number(20.0)));
+    // Non-conflicting changes are still merged
+    assert_eq!(property(&outcome.graph, "node_d", "b"), Some(PropertyValue::Number(4.0)));
+
+    let outcome = merge_graphs_with(&base, &ours, &theirs, MergeSide::Ours);
+    assert_eq!(property(&outcome.graph, "node_a", "a"), Some(PropertyValue::Number(10.0)));
+}
+
+#[test]
+fn merge_edits_to_connections_with_ids() {
+    let mut base = build_diamond_graph();
+    base.assign_connection_ids();
+    let mut ours = base.clone();
+    ours.get_connection_mut("conn_1").unwrap().label = Some("fork".into());
+    let mut theirs = base.clone();
+    theirs.get_connection_mut("conn_2").unwrap().waypoints.push(Position::new(1.0, 2.0));
+    theirs.get_connection_mut("conn_1").unwrap().label = Some("fork".into());
+
+    let merged = merge_graphs(&base, &ours, &theirs).unwrap();
+    assert_eq!(merged.get_connection("conn_1").unwrap().label.as_deref(), Some("fork"));
+    assert_eq!(merged.get_connection("conn_2").unwrap().waypoints, vec![Position::new(1.0, 2.0)]);
+    assert_eq!(merged.connections.len(), 4);
+}
+
+#[test]
+fn conflicting_connection_edits() {
+    let mut base = build_diamond_graph();
+    base.assign_connection_ids();
+    let mut ours = base.clone();
+    ours.get_connection_mut("conn_1").unwrap().color = Some("red".into());
+    ours.get_connection_mut("conn_3").unwrap().color = Some("red".into());
+    let mut theirs = base.clone();
+    theirs.get_connection_mut("conn_1").unwrap().color = Some("blue".into());
+    theirs.remove_connection_by_id("conn_3");
+
+    let conflicts = merge_graphs(&base, &ours, &theirs).unwrap_err();
+    assert_eq!(
+        conflicts,
+        vec![
+            MergeConflict::Connection {
+                connection_id: "conn_1".into()
+            },
+            MergeConflict::Connection {
+                connection_id: "conn_3".into()
+            },
+        ]
+    );
+
+    let outcome = merge_graphs_with(&base, &ours, &theirs, MergeSide::Theirs);
+    assert_eq!(outcome.graph.get_connection("conn_1").unwrap().color.as_deref(), Some("blue"));
+    assert!(outcome.graph.get_connection("conn_3").is_none());
+
+    let outcome = merge_graphs_with(&base, &ours, &theirs, MergeSide::Ours);
+    assert_eq!(outcome.graph.get_connection("conn_1").unwrap().color.as_deref(), Some("red"));
+    assert_eq!(outcome.graph.get_connection("conn_3").unwrap().color.as_deref(), Some("red"));
+}
+
+#[test]
+fn modified_connection_removed_by_ours() {
+    let mut base = build_diamond_graph();
+    base.assign_connection_ids();
+    let mut ours = base.clone();
+    ours.remove_connection_by_id("conn_2");
+    let mut theirs = base.clone();
+    theirs.get_connection_mut("conn_2").unwrap().label = Some("kept".into());
+
+    let outcome = merge_graphs_with(&base, &ours, &theirs, MergeSide::Theirs);
+    assert_eq!(
+        outcome.conflicts,
+        vec![MergeConflict::Connection {
+            connection_id: "conn_2".into()
+        }]
+    );
+    assert_eq!(outcome.graph.get_connection("conn_2").unwrap().label.as_deref(), Some("kept"));
+}
+
+#[test]
+fn conflicts_serialize() {
+    let conflict = MergeConflict::NodeRemoved {
+        node_id: "node_b".into(),
+        removed_by: MergeSide::Theirs,
+    };
+    let json = serde_json::to_string(&conflict).unwrap();
+    assert_eq!(serde_json::from_str::<MergeConflict>(&json).unwrap(), conflict);
+    assert_eq!(conflict.to_string(), "Node node_b was removed in theirs but modified in ours");
+}
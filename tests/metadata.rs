@@ -33,6 +33,37 @@ fn param_info_clone() {
     assert_eq!(cloned.param_type, "i32");
 }
 
+#[test]
+fn param_info_defaults_to_optional_without_default() {
+    let p = ParamInfo::new("x", "i32");
+    assert!(p.default.is_none());
+    assert!(!p.required);
+}
+
+#[test]
+fn param_info_default_and_required_builders() {
+    let p = ParamInfo::new("scale", "f64")
+        .with_default(PropertyValue::Number(1.5))
+        .with_required(true);
+    assert_eq!(p.default, Some(PropertyValue::Number(1.5)));
+    assert!(p.required);
+}
+
+#[test]
+fn param_info_serde_omits_unset_fields() {
+    let plain = serde_json::to_string(&ParamInfo::new("x", "i32")).unwrap();
+    assert_eq!(plain, r#"{"name":"x","param_type":"i32"}"#);
+
+    // Libraries saved before defaults existed still load
+    let loaded: ParamInfo = serde_json::from_str(r#"{"name":"x","param_type":"i32"}"#).unwrap();
+    assert!(loaded.default.is_none() && !loaded.required);
+
+    let full = ParamInfo::new("x", "i32").with_default(PropertyValue::Integer(3)).with_required(true);
+    let loaded: ParamInfo = serde_json::from_str(&serde_json::to_string(&full).unwrap()).unwrap();
+    assert_eq!(loaded.default, Some(PropertyValue::Integer(3)));
+    assert!(loaded.required);
+}
+
 // ===========================================================================
 // NodeMetadata - Construction
 // ===========================================================================
@@ -100,6 +131,26 @@ fn node_metadata_clone() {
     assert!(cloned.return_type.is_some());
 }
 
+#[test]
+fn node_metadata_exec_payloads() {
+    let meta = NodeMetadata::new("for_loop", NodeTypes::control_flow, "Flow")
+        .with_exec_outputs(vec!["body".into(), "completed".into()])
+        .with_exec_payload("body", vec![ParamInfo::new("index", "i64")]);
+
+    assert_eq!(meta.exec_payload("body").len(), 1);
+    assert_eq!(meta.exec_payload("body")[0].param_type, "i64");
+    assert!(meta.exec_payload("completed").is_empty());
+    assert!(meta.exec_payload("missing").is_empty());
+
+    let json = serde_json::to_string(&meta).unwrap();
+    let restored: NodeMetadata = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.exec_payload("body")[0].name, "index");
+
+    // Omitted when no output passes payloads
+    let plain = serde_json::to_string(&NodeMetadata::new("branch", NodeTypes::control_flow, "Flow")).unwrap();
+    assert!(!plain.contains("exec_payloads"));
+}
+
 // ===========================================================================
 // NodeMetadataProvider
 // ===========================================================================
@@ -160,3 +211,675 @@ fn provider_comprehensive_has_all_types() {
     assert!(has_cf);
     assert!(has_ev);
 }
+
+// ===========================================================================
+// NodeMetadata - Fallible nodes
+// ===========================================================================
+
+#[test]
+fn fallible_requires_function_returning_result() {
+    let read = NodeMetadata::new("read", NodeTypes::fn_, "io").with_return_type("Result<String, std::io::Error>");
+    assert!(read.is_fallible());
+    assert_eq!(read.error_type(), Some("std::io::Error"));
+
+    let aliased = NodeMetadata::new("write", NodeTypes::fn_, "io").with_return_type("std::io::Result<()>");
+    assert!(aliased.is_fallible());
+    assert_eq!(aliased.error_type(), None);
+
+    let pure = NodeMetadata::new("parse", NodeTypes::pure, "text").with_return_type("Result<i64, String>");
+    assert!(!pure.is_fallible());
+    assert_eq!(pure.error_type(), None);
+
+    assert!(!NodeMetadata::new("print", NodeTypes::fn_, "io").is_fallible());
+}
+
+#[test]
+fn fallible_pins_resolve_types() {
+    let mut provider = TestMetadataProvider::comprehensive();
+    provider.add(
+        NodeMetadata::new("read", NodeTypes::fn_, "io")
+            .with_return_type("Result<String, std::io::Error>")
+            .with_exec_outputs(vec!["then".to_string()]),
+    );
+
+    let mut graph = GraphDescription::new("fallible");
+    graph.add_node(NodeInstance::new("read_1", "read", Position::zero()));
+    let mut print = NodeInstance::new("print_1", "print_string", Position::zero());
+    print.add_input_pin("exec_in", DataType::Execution);
+    graph.add_node(print);
+
+    let exec = Connection::execution("read_1", core::ERROR_EXEC_PIN, "print_1", "exec_in");
+    let (source, _) = graph.resolve_connection_types(&exec, &provider).unwrap();
+    assert!(source.is_execution());
+
+    let data = Connection::data("read_1", core::ERROR_VALUE_PIN, "print_1", "message");
+    let (source, _) = graph.resolve_connection_types(&data, &provider).unwrap();
+    assert_eq!(source, DataType::Typed("std::io::Error".into()));
+}
+
+// ===========================================================================
+// Provider combinators
+// ===========================================================================
+
+#[test]
+fn composite_provider_uses_priority_order() {
+    let mut math_override = TestMetadataProvider::empty();
+    math_override.add(NodeMetadata::new("add", NodeTypes::pure, "fast_math"));
+
+    let provider = core::CompositeProvider::new()
+        .with_provider(0, TestMetadataProvider::comprehensive())
+        .with_provider(5, math_override);
+    assert_eq!(provider.len(), 2);
+
+    assert_eq!(provider.get_node_metadata("add").unwrap().category, "fast_math");
+    assert!(provider.get_node_metadata("multiply").is_some());
+    assert!(provider.get_node_metadata("missing").is_none());
+
+    let all = provider.get_all_nodes();
+    assert_eq!(all.iter().filter(|m| m.name == "add").count(), 1);
+    assert_eq!(all.len(), TestMetadataProvider::comprehensive().get_all_nodes().len());
+
+    // `add` moved out of "math"
+    let math: Vec<&str> = provider.get_nodes_by_category("math").iter().map(|m| m.name.as_str()).collect();
+    assert!(!math.contains(&"add"));
+    assert!(math.contains(&"multiply"));
+}
+
+#[test]
+fn composite_provider_ties_keep_insertion_order() {
+    let mut first = TestMetadataProvider::empty();
+    first.add(NodeMetadata::new("node", NodeTypes::pure, "first"));
+    let mut second = TestMetadataProvider::empty();
+    second.add(NodeMetadata::new("node", NodeTypes::pure, "second"));
+
+    let provider = core::CompositeProvider::new()
+        .with_provider(1, first)
+        .with_provider(1, second);
+    assert_eq!(provider.get_node_metadata("node").unwrap().category, "first");
+}
+
+#[test]
+fn overlay_provider_overrides_and_hides() {
+    let base = TestMetadataProvider::with_math_nodes();
+    let mut overlay = core::OverlayProvider::new(&base)
+        .with_override(NodeMetadata::new("add", NodeTypes::fn_, "math"))
+        .with_override(NodeMetadata::new("subtract", NodeTypes::pure, "math"))
+        .with_hidden("negate");
+
+    assert_eq!(overlay.get_node_metadata("add").unwrap().node_type, NodeTypes::fn_);
+    assert!(overlay.get_node_metadata("subtract").is_some());
+    assert!(overlay.get_node_metadata("negate").is_none());
+    assert_eq!(overlay.get_nodes_by_category("math").len(), 3);
+
+    overlay.remove_override("add");
+    assert_eq!(overlay.get_node_metadata("add").unwrap().node_type, NodeTypes::pure);
+
+    overlay.set_override(NodeMetadata::new("negate", NodeTypes::pure, "math"));
+    assert!(overlay.get_node_metadata("negate").is_some());
+}
+
+#[test]
+fn cached_provider_returns_same_results() {
+    let base = TestMetadataProvider::comprehensive();
+    let cached = core::CachedProvider::new(&base);
+
+    let first = cached.get_node_metadata("add").unwrap();
+    let second = cached.get_node_metadata("add").unwrap();
+    assert!(std::ptr::eq(first, second));
+    assert!(std::ptr::eq(first, base.get_node_metadata("add").unwrap()));
+    assert!(cached.get_node_metadata("missing").is_none());
+
+    assert_eq!(cached.get_nodes_by_category("math").len(), base.get_nodes_by_category("math").len());
+    cached.clear();
+    assert!(cached.get_node_metadata("add").is_some());
+}
+
+#[test]
+fn combined_providers_drive_analysis() {
+    let base = TestMetadataProvider::comprehensive();
+    let provider = core::CachedProvider::new(&base);
+    let graph = common::build_diamond_graph();
+
+    let resolver = DataResolver::build_parallel(&graph, &provider).unwrap();
+    assert_eq!(resolver.get_pure_evaluation_order().len(), 4);
+}
+
+// ===========================================================================
+// NodeRegistry
+// ===========================================================================
+
+#[test]
+fn registry_register_and_unregister() {
+    let mut registry = NodeRegistry::new();
+    assert!(registry.register(NodeMetadata::new("add", NodeTypes::pure, "Math")).is_none());
+    assert!(registry.register(NodeMetadata::new("sub", NodeTypes::pure, "Math")).is_none());
+    assert_eq!(registry.len(), 2);
+    assert!(registry.contains("add"));
+
+    let previous = registry.register(NodeMetadata::new("add", NodeTypes::fn_, "Math")).unwrap();
+    assert_eq!(previous.node_type, NodeTypes::pure);
+    assert_eq!(registry.len(), 2);
+
+    assert_eq!(registry.unregister("add").unwrap().node_type, NodeTypes::fn_);
+    assert!(registry.unregister("add").is_none());
+    assert!(registry.get_node_metadata("add").is_none());
+}
+
+#[test]
+fn registry_category_index_follows_changes() {
+    let mut registry: NodeRegistry = vec![
+        NodeMetadata::new("sub", NodeTypes::pure, "Math"),
+        NodeMetadata::new("add", NodeTypes::pure, "Math"),
+        NodeMetadata::new("print", NodeTypes::fn_, "IO"),
+    ]
+    .into_iter()
+    .collect();
+
+    assert_eq!(registry.categories(), vec!["IO", "Math"]);
+    assert_eq!(registry.node_types_in_category("Math"), ["add".to_string(), "sub".to_string()]);
+
+    // Moving a node to another category updates both lists
+    registry.register(NodeMetadata::new("add", NodeTypes::pure, "Arithmetic"));
+    assert_eq!(registry.node_types_in_category("Math"), ["sub".to_string()]);
+    assert_eq!(registry.get_nodes_by_category("Arithmetic")[0].name, "add");
+
+    registry.unregister("print");
+    assert_eq!(registry.categories(), vec!["Arithmetic", "Math"]);
+    assert!(registry.get_nodes_by_category("IO").is_empty());
+}
+
+#[test]
+fn registry_register_as_custom_node_type() {
+    let mut registry = NodeRegistry::new();
+    registry.register_as("math.add", NodeMetadata::new("add", NodeTypes::pure, "Math"));
+
+    assert_eq!(registry.get_node_metadata("math.add").unwrap().name, "add");
+    assert!(registry.get_node_metadata("add").is_none());
+    assert_eq!(registry.iter().next().unwrap().0, "math.add");
+}
+
+#[test]
+fn registry_json_round_trip() {
+    let mut registry = NodeRegistry::new();
+    registry.register_as(
+        "math.add",
+        NodeMetadata::new("add", NodeTypes::pure, "Math")
+            .with_params(vec![ParamInfo::new("a", "f64"), ParamInfo::new("b", "f64")])
+            .with_return_type("f64"),
+    );
+    registry.register(NodeMetadata::new("print", NodeTypes::fn_, "IO"));
+
+    let json = registry.to_json().unwrap();
+    assert!(json.find("\"math.add\"").unwrap() < json.find("\"print\"").unwrap());
+
+    let loaded = NodeRegistry::from_json(&json).unwrap();
+    assert_eq!(loaded.len(), 2);
+    assert_eq!(loaded.get_node_metadata("math.add").unwrap().params.len(), 2);
+    assert_eq!(loaded.categories(), vec!["IO", "Math"]);
+
+    assert!(matches!(NodeRegistry::from_json("[1, 2]"), Err(GraphyError::Serialization(_))));
+}
+
+// ===========================================================================
+// NodeMetadata - From source
+// ===========================================================================
+
+#[test]
+fn from_fn_source_pure_node() {
+    let source = r#"
+        #[graphy::node(category = "Math", pure)]
+        fn lerp(a: f64, b: f64, t: f64) -> f64 {
+            a + (b - a) * t
+        }
+    "#;
+
+    let meta = NodeMetadata::from_fn_source(source).unwrap();
+    assert_eq!(meta.name, "lerp");
+    assert_eq!(meta.node_type, NodeTypes::pure);
+    assert_eq!(meta.category, "Math");
+    let params: Vec<(&str, &str)> = meta
+        .params
+        .iter()
+        .map(|p| (p.name.as_str(), p.param_type.as_str()))
+        .collect();
+    assert_eq!(params, [("a", "f64"), ("b", "f64"), ("t", "f64")]);
+    assert_eq!(meta.return_type.unwrap().type_string, "f64");
+    assert!(meta.exec_outputs.is_empty());
+    assert!(!meta.function_source.contains("graphy"));
+    assert!(meta.function_source.starts_with("fn lerp"));
+}
+
+#[test]
+fn from_fn_source_defaults_without_attribute() {
+    let meta = NodeMetadata::from_fn_source("fn log(message: &str) { println!(\"{}\", message); }").unwrap();
+    assert_eq!(meta.node_type, NodeTypes::fn_);
+    assert_eq!(meta.category, "General");
+    assert_eq!(meta.params[0].param_type, "&str");
+    assert!(meta.return_type.is_none());
+    assert_eq!(meta.exec_outputs, vec!["then"]);
+}
+
+#[test]
+fn from_fn_source_attribute_arguments() {
+    let source = r#"
+        #[node(name = "write_file", import = "use std::io::Write;", import = "use std::fs::File;")]
+        fn write(path: &'static str, data: Vec<(u8, bool)>, out: &mut dyn Write) -> std::io::Result<()> {
+            todo!()
+        }
+    "#;
+
+    let meta = NodeMetadata::from_fn_source(source).unwrap();
+    assert_eq!(meta.name, "write_file");
+    assert_eq!(meta.imports, vec!["use std::io::Write;", "use std::fs::File;"]);
+    let types: Vec<&str> = meta.params.iter().map(|p| p.param_type.as_str()).collect();
+    assert_eq!(types, ["&'static str", "Vec<(u8, bool)>", "&mut dyn Write"]);
+    assert_eq!(meta.return_type.unwrap().type_string, "std::io::Result<()>");
+}
+
+#[test]
+fn from_fn_source_event_and_loop_outputs() {
+    let event = NodeMetadata::from_fn_source("#[graphy::node(event)] fn on_tick(delta_time: f64) {}").unwrap();
+    assert_eq!(event.node_type, NodeTypes::event);
+    assert_eq!(event.exec_outputs, vec!["exec"]);
+    assert_eq!(event.event_param("delta_time").unwrap().param_type, "f64");
+
+    let source = r#"
+        #[graphy::node(control_flow)]
+        fn for_loop(start: i32, end: i32) {
+            for i in start..end {
+                exec_output!("Body");
+            }
+            exec_output!("Completed");
+            exec_output!("Completed");
+        }
+    "#;
+    let looped = NodeMetadata::from_fn_source(source).unwrap();
+    assert_eq!(looped.exec_outputs, vec!["Body", "Completed"]);
+}
+
+#[test]
+fn from_fn_source_rejects_invalid_functions() {
+    let errors = [
+        "not a function",
+        "#[graphy::node(unknown)] fn a() {}",
+        "#[graphy::node(category = 5)] fn a() {}",
+        "fn a((x, y): (f64, f64)) {}",
+    ];
+    for source in errors {
+        assert!(
+            matches!(NodeMetadata::from_fn_source(source), Err(GraphyError::AstParsing(_))),
+            "{}",
+            source
+        );
+    }
+}
+
+#[test]
+fn from_source_file_collects_annotated_functions() {
+    let source = r#"
+        use std::fmt;
+
+        #[graphy::node(category = "Math", pure)]
+        fn add(a: f64, b: f64) -> f64 { a + b }
+
+        fn helper() {}
+
+        #[graphy::node(category = "IO")]
+        fn print(value: String) { println!("{}", value); }
+    "#;
+
+    let nodes = NodeMetadata::from_source_file(source).unwrap();
+    let names: Vec<&str> = nodes.iter().map(|n| n.name.as_str()).collect();
+    assert_eq!(names, ["add", "print"]);
+
+    let registry: NodeRegistry = nodes.into_iter().collect();
+    assert_eq!(registry.categories(), vec!["IO", "Math"]);
+}
+
+#[test]
+fn from_fn_source_async_function() {
+    let meta = NodeMetadata::from_fn_source("async fn fetch(url: String) -> Vec<u8> { todo!() }").unwrap();
+    assert!(meta.is_async);
+    assert_eq!(meta.return_type.unwrap().type_string, "Vec<u8>");
+
+    let sync = NodeMetadata::from_fn_source("fn fetch(url: String) {}").unwrap();
+    assert!(!sync.is_async);
+}
+
+#[test]
+fn async_flag_serialization() {
+    let sync = NodeMetadata::new("print", NodeTypes::fn_, "io");
+    let json = serde_json::to_string(&sync).unwrap();
+    assert!(!json.contains("is_async"));
+    assert!(!serde_json::from_str::<NodeMetadata>(&json).unwrap().is_async);
+
+    let json = serde_json::to_string(&sync.with_async(true)).unwrap();
+    assert!(serde_json::from_str::<NodeMetadata>(&json).unwrap().is_async);
+}
+
+#[test]
+fn from_fn_source_result_is_fallible() {
+    let meta = NodeMetadata::from_fn_source("fn parse(text: String) -> Result<i64, ParseIntError> { text.parse() }").unwrap();
+    assert!(meta.is_fallible());
+    assert_eq!(meta.error_type(), Some("ParseIntError"));
+    assert_eq!(meta.exec_outputs, vec!["then"]);
+}
+
+// ===========================================================================
+// Editor hints
+// ===========================================================================
+
+fn hinted_registry() -> NodeRegistry {
+    let mut registry = NodeRegistry::new();
+    registry.register(
+        NodeMetadata::new("mix", NodeTypes::pure, "Color")
+            .with_description("Blends two colors")
+            .with_params(vec![
+                ParamInfo::new("a", "Color").with_display(core::DisplayHint::ColorPicker),
+                ParamInfo::new("b", "Color").with_display(core::DisplayHint::ColorPicker),
+                ParamInfo::new("t", "f64")
+                    .with_description("Blend factor")
+                    .with_range(core::ValueRange::new(0.0, 1.0).with_step(0.05))
+                    .with_default(PropertyValue::Number(0.5)),
+            ])
+            .with_return_type("Color"),
+    );
+    registry.register(
+        NodeMetadata::new("tick", NodeTypes::event, "Events").with_params(vec![ParamInfo::new("delta_time", "f64")]),
+    );
+    registry
+}
+
+#[test]
+fn value_range_bounds() {
+    let range = core::ValueRange::new(0.0, 1.0);
+    assert!(range.is_bounded());
+    assert!(range.contains(0.0) && range.contains(1.0));
+    assert!(!range.contains(1.5));
+    assert_eq!(range.clamp(-2.0), 0.0);
+    assert_eq!(range.clamp(3.0), 1.0);
+
+    let open = core::ValueRange::at_most(10.0);
+    assert!(!open.is_bounded());
+    assert!(open.contains(-1e9));
+    assert_eq!(open.clamp(11.0), 10.0);
+    assert!(core::ValueRange::default().contains(f64::MAX));
+}
+
+#[test]
+fn display_hint_defaults_to_slider_for_bounded_range() {
+    assert_eq!(ParamInfo::new("x", "f64").display_hint(), core::DisplayHint::Field);
+    assert_eq!(
+        ParamInfo::new("x", "f64").with_range(core::ValueRange::new(0.0, 1.0)).display_hint(),
+        core::DisplayHint::Slider
+    );
+    assert_eq!(
+        ParamInfo::new("x", "f64").with_range(core::ValueRange::at_least(0.0)).display_hint(),
+        core::DisplayHint::Field
+    );
+    assert_eq!(
+        ParamInfo::new("x", "f64")
+            .with_range(core::ValueRange::new(0.0, 1.0))
+            .with_display(core::DisplayHint::Multiline)
+            .display_hint(),
+        core::DisplayHint::Multiline
+    );
+}
+
+#[test]
+fn tooltip_falls_back_to_description() {
+    let param = ParamInfo::new("x", "f64").with_description("The x coordinate");
+    assert_eq!(param.tooltip_text(), Some("The x coordinate"));
+    assert_eq!(param.with_tooltip("X").tooltip_text(), Some("X"));
+    assert_eq!(ParamInfo::new("x", "f64").tooltip_text(), None);
+
+    let meta = NodeMetadata::new("mix", NodeTypes::pure, "Color").with_description("Blends two colors");
+    assert_eq!(meta.tooltip_text(), Some("Blends two colors"));
+}
+
+#[test]
+fn editor_hints_serialization() {
+    let plain = NodeMetadata::new("add", NodeTypes::pure, "Math").with_params(vec![ParamInfo::new("a", "f64")]);
+    let json = serde_json::to_string(&plain).unwrap();
+    for field in ["description", "tooltip", "range", "display"] {
+        assert!(!json.contains(field), "{} should be omitted", field);
+    }
+
+    let registry = hinted_registry();
+    let mix = registry.get_node_metadata("mix").unwrap();
+    let json = serde_json::to_string(mix).unwrap();
+    assert!(json.contains(r#""display":"color_picker""#));
+    assert!(json.contains(r#""range":{"min":0.0,"max":1.0,"step":0.05}"#));
+
+    let back: NodeMetadata = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.description.as_deref(), Some("Blends two colors"));
+    let t = back.param("t").unwrap();
+    assert_eq!(t.range, Some(core::ValueRange::new(0.0, 1.0).with_step(0.05)));
+    assert_eq!(t.description.as_deref(), Some("Blend factor"));
+    assert_eq!(back.param("a").unwrap().display, core::DisplayHint::ColorPicker);
+}
+
+#[test]
+fn editor_hints_deserialize_from_older_metadata() {
+    let json = r#"{"name":"add","node_type":"pure","category":"Math",
+        "params":[{"name":"a","param_type":"f64"}],"return_type":null,
+        "exec_outputs":[],"imports":[],"function_source":""}"#;
+    let meta: NodeMetadata = serde_json::from_str(json).unwrap();
+    assert_eq!(meta.description, None);
+    let a = meta.param("a").unwrap();
+    assert_eq!(a.range, None);
+    assert_eq!(a.display, core::DisplayHint::Field);
+}
+
+#[test]
+fn inspector_fields_report_values_and_connections() {
+    let registry = hinted_registry();
+    let mut graph = GraphDescription::new("inspect");
+    let mut mix = NodeInstance::new("mix_1", "mix", Position::zero());
+    mix.set_property("a", PropertyValue::String("#ff0000".into()));
+    graph.add_node(mix);
+    graph.add_node(NodeInstance::new("mix_0", "mix", Position::zero()));
+    graph.add_connection(Connection::data("mix_0", "result", "mix_1", "b"));
+
+    let fields = graph.inspector_fields("mix_1", &registry).unwrap();
+    let names: Vec<&str> = fields.iter().map(|f| f.param.name.as_str()).collect();
+    assert_eq!(names, ["a", "b", "t"]);
+
+    assert!(fields[0].is_set && !fields[0].connected);
+    assert_eq!(fields[0].display, core::DisplayHint::ColorPicker);
+    assert!(fields[1].connected && fields[1].value.is_none());
+    assert!(!fields[2].is_set);
+    assert_eq!(fields[2].value, Some(&PropertyValue::Number(0.5)));
+    assert_eq!(fields[2].display, core::DisplayHint::Slider);
+    assert_eq!(fields[2].tooltip, Some("Blend factor"));
+}
+
+#[test]
+fn inspector_fields_range_check() {
+    let registry = hinted_registry();
+    let mut graph = GraphDescription::new("inspect");
+    let mut mix = NodeInstance::new("mix_1", "mix", Position::zero());
+    mix.set_property("t", PropertyValue::Number(1.5));
+    graph.add_node(mix);
+
+    let fields = graph.inspector_fields("mix_1", &registry).unwrap();
+    assert!(fields[0].is_in_range());
+    assert!(!fields[2].is_in_range());
+}
+
+#[test]
+fn inspector_fields_errors_and_events() {
+    let registry = hinted_registry();
+    let mut graph = GraphDescription::new("inspect");
+    graph.add_node(NodeInstance::new("tick_1", "tick", Position::zero()));
+    graph.add_node(NodeInstance::new("ghost_1", "ghost", Position::zero()));
+
+    assert!(graph.inspector_fields("tick_1", &registry).unwrap().is_empty());
+    assert!(matches!(graph.inspector_fields("missing", &registry), Err(GraphyError::NodeNotFound(_))));
+    assert!(graph.inspector_fields("ghost_1", &registry).is_err());
+}
+
+#[test]
+fn from_fn_source_doc_comment_becomes_description() {
+    let source = r#"
+        /// Adds two numbers.
+        ///
+        /// Wraps on overflow.
+        #[graphy::node(category = "Math", pure)]
+        fn add(a: i32, b: i32) -> i32 { a.wrapping_add(b) }
+    "#;
+    let meta = NodeMetadata::from_fn_source(source).unwrap();
+    assert_eq!(meta.description.as_deref(), Some("Adds two numbers.\n\nWraps on overflow."));
+
+    let undocumented = NodeMetadata::from_fn_source("fn noop() {}").unwrap();
+    assert_eq!(undocumented.description, None);
+}
+
+// ===========================================================================
+// Metadata validation
+// ===========================================================================
+
+fn issue_names(meta: &NodeMetadata) -> Vec<String> {
+    meta.validate().iter().map(|issue| issue.to_string()).collect()
+}
+
+#[test]
+fn validate_consistent_metadata() {
+    let source = r#"
+        #[graphy::node(category = "Flow", control_flow)]
+        fn branch(condition: bool) {
+            if condition { exec_output!("True"); } else { exec_output!("False"); }
+        }
+    "#;
+    assert!(NodeMetadata::from_fn_source(source).unwrap().validate().is_empty());
+
+    let print = NodeMetadata::new("print", NodeTypes::fn_, "IO")
+        .with_params(vec![ParamInfo::new("message", "String")])
+        .with_exec_outputs(vec!["then".into()])
+        .with_source("fn print(message: String) { println!(\"{}\", message); }");
+    assert!(print.validate().is_empty());
+
+    let expression = NodeMetadata::new("add", NodeTypes::pure, "Math")
+        .with_params(vec![ParamInfo::new("a", "f64"), ParamInfo::new("b", "f64")])
+        .with_return_type("f64")
+        .with_source("a + b");
+    assert!(expression.validate().is_empty());
+
+    assert!(NodeMetadata::new("empty", NodeTypes::fn_, "IO").validate().is_empty());
+}
+
+#[test]
+fn validate_exec_outputs_against_source() {
+    let meta = NodeMetadata::new("gate", NodeTypes::control_flow, "Flow")
+        .with_params(vec![ParamInfo::new("open", "bool")])
+        .with_exec_outputs(vec!["Open".into(), "Closed".into(), "Open".into()])
+        .with_source(
+            r#"fn gate(open: bool) {
+                if open { exec_output!("Open"); } else { exec_output!("Shut"); exec_output!("Shut"); }
+            }"#,
+        );
+
+    assert_eq!(
+        meta.validate(),
+        vec![
+            core::MetadataIssue::DuplicateExecOutput {
+                node: "gate".into(),
+                label: "Open".into(),
+            },
+            core::MetadataIssue::ExecOutputNotDeclared {
+                node: "gate".into(),
+                label: "Shut".into(),
+            },
+            core::MetadataIssue::ExecOutputNotInSource {
+                node: "gate".into(),
+                label: "Closed".into(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn validate_implicit_exec_outputs() {
+    let event = NodeMetadata::new("on_tick", NodeTypes::event, "Events")
+        .with_params(vec![ParamInfo::new("delta_time", "f64")])
+        .with_exec_outputs(vec!["exec".into()])
+        .with_source("fn on_tick(delta_time: f64) {}");
+    assert!(event.validate().is_empty());
+
+    // A pure node never has execution outputs
+    let pure = NodeMetadata::new("double", NodeTypes::pure, "Math")
+        .with_params(vec![ParamInfo::new("x", "f64")])
+        .with_return_type("f64")
+        .with_exec_outputs(vec!["then".into()])
+        .with_source("fn double(x: f64) -> f64 { x * 2.0 }");
+    assert_eq!(issue_names(&pure), ["double: exec output \"then\" is never emitted by the source"]);
+}
+
+#[test]
+fn validate_params_against_source() {
+    let meta = NodeMetadata::new("lerp", NodeTypes::pure, "Math")
+        .with_params(vec![
+            ParamInfo::new("a", "f64"),
+            ParamInfo::new("b", "f32"),
+            ParamInfo::new("alpha", "f64"),
+        ])
+        .with_return_type("f64")
+        .with_source("fn lerp(a: f64, b: f64, t: f64) -> f64 { a + (b - a) * t }");
+
+    assert_eq!(
+        issue_names(&meta),
+        [
+            "lerp: param b is declared as f32 but the source takes f64",
+            "lerp: source parameter t is missing from params",
+            "lerp: param alpha is not a parameter of the source function",
+        ]
+    );
+}
+
+#[test]
+fn validate_param_order_and_type_spelling() {
+    let meta = NodeMetadata::new("concat", NodeTypes::pure, "String")
+        .with_params(vec![ParamInfo::new("b", "Vec < u8 >"), ParamInfo::new("a", "Vec<u8>")])
+        .with_return_type("Vec<u8>")
+        .with_source("fn concat(a: Vec<u8>, b: Vec<u8>) -> Vec<u8> { [a, b].concat() }");
+
+    assert_eq!(meta.validate(), vec![core::MetadataIssue::ParamOrderMismatch { node: "concat".into() }]);
+}
+
+#[test]
+fn validate_return_type_and_invalid_source() {
+    let meta = NodeMetadata::new("parse", NodeTypes::fn_, "String")
+        .with_params(vec![ParamInfo::new("text", "String")])
+        .with_exec_outputs(vec!["then".into()])
+        .with_source("fn parse(text: String) -> i64 { text.parse().unwrap() }");
+    assert_eq!(
+        meta.validate(),
+        vec![core::MetadataIssue::ReturnTypeMismatch {
+            node: "parse".into(),
+            declared: None,
+            source: Some("i64".into()),
+        }]
+    );
+    assert_eq!(issue_names(&meta), ["parse: return type is declared as () but the source returns i64"]);
+
+    let broken = NodeMetadata::new("broken", NodeTypes::fn_, "IO").with_source("fn broken( {");
+    assert!(matches!(broken.validate().as_slice(), [core::MetadataIssue::InvalidSource { .. }]));
+}
+
+#[test]
+fn validate_all_aggregates_by_node() {
+    let mut registry = NodeRegistry::new();
+    registry.register(
+        NodeMetadata::new("zeta", NodeTypes::pure, "Math")
+            .with_params(vec![ParamInfo::new("x", "f64"), ParamInfo::new("x", "f64")]),
+    );
+    registry.register(NodeMetadata::new("alpha", NodeTypes::fn_, "IO").with_source("fn alpha(y: i32) {}"));
+    registry.register(NodeMetadata::new("ok", NodeTypes::fn_, "IO"));
+
+    let issues = core::validate_all(&registry);
+    let nodes: Vec<&str> = issues.iter().map(|issue| issue.node()).collect();
+    assert_eq!(nodes, ["alpha", "zeta"]);
+
+    let dyn_provider: &dyn NodeMetadataProvider = &registry;
+    assert_eq!(core::validate_all(dyn_provider), issues);
+}
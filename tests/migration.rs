@@ -0,0 +1,157 @@
+//! Tests for format versioning and graph migration.
+
+use graphy::core::CURRENT_FORMAT_VERSION;
+use graphy::io::{format_version_of, load_with_migration, MigrationStep, Migrator};
+use graphy::*;
+use serde_json::{json, Value};
+
+/// A graph as written before format versioning
+fn v1_graph() -> Value {
+    json!({
+        "metadata": { "name": "legacy" },
+        "nodes": {
+            "a": {
+                "id": "a",
+                "node_type": "math.add",
+                "position": { "x": 1.0, "y": 2.0 },
+                "inputs": [
+                    { "id": "x", "pin": { "id": "x", "data_type": "Number", "pin_type": "Input" } },
+                    { "id": "any", "pin": { "id": "any", "name": "Any", "data_type": "Any", "pin_type": "Input" } }
+                ],
+                "outputs": [
+                    { "id": "out", "pin": { "id": "out", "name": "Out", "data_type": "Vector2", "pin_type": "Output" } },
+                    { "id": "exec", "pin": { "id": "exec", "name": "Exec", "data_type": "Execution", "pin_type": "Output" } }
+                ]
+            }
+        }
+    })
+}
+
+// ===========================================================================
+// format_version
+// ===========================================================================
+
+#[test]
+fn new_graph_has_current_version() {
+    let graph = GraphDescription::new("g");
+    assert_eq!(graph.format_version, CURRENT_FORMAT_VERSION);
+
+    let value = serde_json::to_value(&graph).unwrap();
+    assert_eq!(value["format_version"], json!(CURRENT_FORMAT_VERSION));
+}
+
+#[test]
+fn missing_version_reads_as_v1() {
+    let mut value = serde_json::to_value(GraphDescription::new("g")).unwrap();
+    value.as_object_mut().unwrap().remove("format_version");
+
+    assert_eq!(format_version_of(&value).unwrap(), 1);
+    let graph: GraphDescription = serde_json::from_value(value).unwrap();
+    assert_eq!(graph.format_version, 1);
+}
+
+#[test]
+fn invalid_version_is_rejected() {
+    assert!(format_version_of(&json!({ "format_version": "two" })).is_err());
+    assert!(format_version_of(&json!({ "format_version": -1 })).is_err());
+    assert!(format_version_of(&json!([])).is_err());
+}
+
+// ===========================================================================
+// v1 -> v2
+// ===========================================================================
+
+#[test]
+fn v1_graph_fails_without_migration() {
+    let result = serde_json::from_value::<GraphDescription>(v1_graph());
+    assert!(result.is_err());
+}
+
+#[test]
+fn v1_graph_loads_with_migration() -> Result<()> {
+    let graph = load_with_migration(&v1_graph().to_string())?;
+
+    assert_eq!(graph.format_version, CURRENT_FORMAT_VERSION);
+    assert_eq!(graph.metadata.name, "legacy");
+    assert_eq!(graph.metadata.version, "1.0.0");
+    assert!(graph.connections.is_empty());
+    assert!(graph.comments.is_empty());
+
+    let node = &graph.nodes["a"];
+    assert!(node.properties.is_empty());
+    assert_eq!(node.inputs[0].pin.name, "x");
+    assert_eq!(node.inputs[0].pin.data_type, DataType::Typed("f64".into()));
+    assert_eq!(node.inputs[1].pin.data_type, DataType::Any);
+    assert_eq!(node.outputs[0].pin.data_type, DataType::Typed("(f64, f64)".into()));
+    assert_eq!(node.outputs[1].pin.data_type, DataType::Execution);
+    Ok(())
+}
+
+#[test]
+fn current_graph_loads_unchanged() -> Result<()> {
+    let mut graph = GraphDescription::new("current");
+    let mut node = NodeInstance::new("a", "math.add", Position::zero());
+    node.add_input_pin("x", DataType::Typed("i64".into()));
+    graph.add_node(node);
+
+    let json = serde_json::to_string(&graph).unwrap();
+    let loaded = load_with_migration(&json)?;
+    assert_eq!(loaded.nodes["a"].inputs, graph.nodes["a"].inputs);
+    assert_eq!(loaded.format_version, CURRENT_FORMAT_VERSION);
+    Ok(())
+}
+
+#[test]
+fn newer_version_is_rejected() {
+    let mut value = serde_json::to_value(GraphDescription::new("future")).unwrap();
+    value["format_version"] = json!(CURRENT_FORMAT_VERSION + 1);
+
+    let result = load_with_migration(&value.to_string());
+    assert!(matches!(result, Err(GraphyError::Serialization(_))));
+}
+
+// ===========================================================================
+// Migrator
+// ===========================================================================
+
+fn rename_to_upgraded(document: &mut Value) -> Result<()> {
+    document["metadata"]["name"] = json!("upgraded");
+    Ok(())
+}
+
+#[test]
+fn migrator_returns_original_version() {
+    let mut document = v1_graph();
+    let original = Migrator::new().migrate(&mut document).unwrap();
+
+    assert_eq!(original, 1);
+    assert_eq!(format_version_of(&document).unwrap(), CURRENT_FORMAT_VERSION);
+}
+
+#[test]
+fn migrator_runs_registered_steps() -> Result<()> {
+    let target = CURRENT_FORMAT_VERSION + 1;
+    let mut migrator = Migrator::new().with_target_version(target);
+    migrator.register(MigrationStep::new(CURRENT_FORMAT_VERSION, "rename", rename_to_upgraded))?;
+
+    let mut document = v1_graph();
+    migrator.migrate(&mut document)?;
+    assert_eq!(format_version_of(&document)?, target);
+    assert_eq!(document["metadata"]["name"], json!("upgraded"));
+    assert_eq!(migrator.steps().len(), 2);
+    Ok(())
+}
+
+#[test]
+fn migrator_rejects_duplicate_steps() {
+    let mut migrator = Migrator::new();
+    let result = migrator.register(MigrationStep::new(1, "duplicate", rename_to_upgraded));
+    assert!(matches!(result, Err(GraphyError::Custom(_))));
+}
+
+#[test]
+fn migrator_fails_on_missing_step() {
+    let mut document = v1_graph();
+    let result = Migrator::empty().migrate(&mut document);
+    assert!(matches!(result, Err(GraphyError::Serialization(_))));
+}
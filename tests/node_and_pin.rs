@@ -1,5 +1,6 @@
 //! Tests for NodeInstance, Pin, PinInstance, and PinType.
 
+use graphy::core::{EXEC_INPUT_PIN, RESULT_PIN};
 use graphy::*;
 
 // ===========================================================================
@@ -218,3 +219,165 @@ fn node_clone_preserves_all_data() {
     assert_eq!(cloned.outputs.len(), 1);
     assert!(cloned.get_property("a").is_some());
 }
+
+// ===========================================================================
+// NodeInstance - From Metadata
+// ===========================================================================
+
+fn typed(type_string: &str) -> DataType {
+    DataType::Typed(type_string.into())
+}
+
+fn pins(pins: &[PinInstance]) -> Vec<(&str, DataType)> {
+    pins.iter().map(|pin| (pin.id.as_str(), pin.pin.data_type.clone())).collect()
+}
+
+fn typed_registry() -> NodeRegistry {
+    let mut registry = NodeRegistry::new();
+    registry.register(
+        NodeMetadata::new("on_tick", NodeTypes::event, "Events")
+            .with_params(vec![ParamInfo::new("delta_time", "f32")])
+            .with_exec_outputs(vec!["exec".into()]),
+    );
+    registry.register_as(
+        "math.add",
+        NodeMetadata::new("add", NodeTypes::pure, "Math")
+            .with_params(vec![ParamInfo::new("a", "f32"), ParamInfo::new("b", "f32")])
+            .with_return_type("f32"),
+    );
+    registry.register(
+        NodeMetadata::new("print", NodeTypes::fn_, "IO")
+            .with_params(vec![ParamInfo::new("value", "f32")])
+            .with_return_type("()")
+            .with_exec_outputs(vec!["then".into()]),
+    );
+    registry.register(
+        NodeMetadata::new("read_file", NodeTypes::fn_, "IO")
+            .with_params(vec![ParamInfo::new("path", "String")])
+            .with_return_type("Result<String, std::io::Error>")
+            .with_exec_outputs(vec!["then".into()]),
+    );
+    registry.register(
+        NodeMetadata::new("branch", NodeTypes::control_flow, "Flow")
+            .with_params(vec![ParamInfo::new("condition", "bool")])
+            .with_exec_outputs(vec!["True".into(), "False".into()]),
+    );
+    registry.register(
+        NodeMetadata::new("switch", NodeTypes::control_flow, "Flow")
+            .with_params(vec![ParamInfo::new("value", "Direction")])
+            .with_switch(true),
+    );
+    registry.register_enum("Direction", ["North", "South"]);
+    registry
+}
+
+#[test]
+fn node_from_pure_metadata() {
+    let registry = typed_registry();
+    let node = NodeInstance::from_metadata("add_1", registry.get_node_metadata("math.add").unwrap(), Position::new(1.0, 2.0));
+    assert_eq!(node.node_type, "add");
+    assert_eq!(node.position, Position::new(1.0, 2.0));
+    assert_eq!(pins(&node.inputs), [("a", typed("f32")), ("b", typed("f32"))]);
+    assert_eq!(pins(&node.outputs), [(RESULT_PIN, typed("f32"))]);
+    assert!(node.properties.is_empty());
+}
+
+#[test]
+fn node_from_function_metadata() {
+    let registry = typed_registry();
+    let print = NodeInstance::from_metadata("print_1", registry.get_node_metadata("print").unwrap(), Position::zero());
+    assert_eq!(pins(&print.inputs), [(EXEC_INPUT_PIN, DataType::Execution), ("value", typed("f32"))]);
+    // `()` returns nothing
+    assert_eq!(pins(&print.outputs), [("then", DataType::Execution)]);
+
+    let read = NodeInstance::from_metadata("read_1", registry.get_node_metadata("read_file").unwrap(), Position::zero());
+    assert_eq!(
+        pins(&read.outputs),
+        [
+            ("then", DataType::Execution),
+            (RESULT_PIN, typed("String")),
+            (core::ERROR_EXEC_PIN, DataType::Execution),
+            (core::ERROR_VALUE_PIN, typed("std::io::Error")),
+        ]
+    );
+}
+
+#[test]
+fn node_from_event_and_control_flow_metadata() {
+    let registry = typed_registry();
+    let tick = NodeInstance::from_metadata("tick", registry.get_node_metadata("on_tick").unwrap(), Position::zero());
+    assert!(tick.inputs.is_empty());
+    assert_eq!(pins(&tick.outputs), [("exec", DataType::Execution), ("delta_time", typed("f32"))]);
+
+    let branch = NodeInstance::from_metadata("branch_1", registry.get_node_metadata("branch").unwrap(), Position::zero());
+    assert_eq!(pins(&branch.inputs), [(EXEC_INPUT_PIN, DataType::Execution), ("condition", typed("bool"))]);
+    assert_eq!(pins(&branch.outputs), [("True", DataType::Execution), ("False", DataType::Execution)]);
+
+    let switch = NodeInstance::from_metadata("switch_1", registry.get_node_metadata("switch").unwrap(), Position::zero());
+    assert_eq!(pins(&switch.outputs), [(core::SWITCH_DEFAULT_PIN, DataType::Execution)]);
+    assert!(switch.switch_cases().is_empty());
+}
+
+// ===========================================================================
+// GraphDescription - Nodes of a Type
+// ===========================================================================
+
+#[test]
+fn add_node_of_type_keeps_the_registered_type() {
+    let registry = typed_registry();
+    let mut graph = GraphDescription::new("typed");
+    let add = graph.add_node_of_type("math.add", "add_1", &registry).unwrap();
+    add.position = Position::new(50.0, 0.0);
+    add.set_property("b", PropertyValue::Number(2.0));
+
+    let add = &graph.nodes["add_1"];
+    assert_eq!(add.node_type, "math.add");
+    assert_eq!(add.position, Position::new(50.0, 0.0));
+    assert_eq!(add.inputs.len(), 2);
+}
+
+#[test]
+fn add_node_of_type_adds_switch_cases() {
+    let registry = typed_registry();
+    let mut graph = GraphDescription::new("typed");
+    let switch = graph.add_node_of_type("switch", "switch_1", &registry).unwrap();
+    assert_eq!(switch.switch_cases(), ["North", "South"]);
+}
+
+#[test]
+fn add_node_of_type_rejects_unknown_types_and_taken_ids() {
+    let registry = typed_registry();
+    let mut graph = GraphDescription::new("typed");
+    assert!(matches!(
+        graph.add_node_of_type("teleport", "t_1", &registry),
+        Err(GraphyError::Custom(message)) if message.contains("teleport")
+    ));
+    assert!(graph.nodes.is_empty());
+
+    graph.add_node_of_type("print", "print_1", &registry).unwrap();
+    graph.nodes.get_mut("print_1").unwrap().set_property("value", PropertyValue::Number(1.0));
+    assert!(graph.add_node_of_type("math.add", "print_1", &registry).is_err());
+    assert_eq!(graph.nodes["print_1"].node_type, "print");
+}
+
+#[test]
+fn nodes_of_a_type_connect_and_compile() {
+    let registry = typed_registry();
+    let mut graph = GraphDescription::new("typed");
+    for (node_type, id) in [("on_tick", "tick"), ("math.add", "add_1"), ("print", "print_1")] {
+        graph.add_node_of_type(node_type, id, &registry).unwrap();
+    }
+
+    for connection in [
+        Connection::execution("tick", "exec", "print_1", EXEC_INPUT_PIN),
+        Connection::data("tick", "delta_time", "add_1", "a"),
+        Connection::data("add_1", RESULT_PIN, "print_1", "value"),
+    ] {
+        graph.try_add_connection(connection, &registry).unwrap();
+    }
+    let mismatched = Connection::data("add_1", RESULT_PIN, "tick", "delta_time");
+    assert!(graph.try_add_connection(mismatched, &registry).is_err());
+
+    let resolver = DataResolver::build(&graph, &registry).unwrap();
+    assert!(resolver.unconnected_required_inputs(&graph, &registry).is_empty());
+}
@@ -0,0 +1,220 @@
+//! Tests for namespaced node type IDs and version requirements.
+
+use graphy::core::{CompositeProvider, NodeTypeId, OverlayProvider, Version, VersionReq};
+use graphy::*;
+
+fn add_v(major: u64, minor: u64, category: &str) -> NodeMetadata {
+    NodeMetadata::new("add", NodeTypes::pure, category)
+        .with_params(vec![ParamInfo::new("a", "i64"), ParamInfo::new("b", "i64")])
+        .with_return_type("i64")
+        .with_version(Version::new(major, minor, 0))
+}
+
+/// `math.add` in versions 1.0, 1.4, and 2.0
+fn versioned_registry() -> NodeRegistry {
+    let mut registry = NodeRegistry::new();
+    for (major, minor) in [(1, 4), (2, 0), (1, 0)] {
+        registry.register_versioned("math.add", add_v(major, minor, &format!("v{}.{}", major, minor)));
+    }
+    registry
+}
+
+fn requirement(text: &str) -> VersionReq {
+    VersionReq::parse(text).unwrap()
+}
+
+fn node_requiring(id: &str, requirement: &str) -> NodeInstance {
+    let mut node = NodeInstance::new(id, "math.add", Position::zero());
+    node.version_req = Some(VersionReq::parse(requirement).unwrap());
+    node
+}
+
+// ===========================================================================
+// NodeTypeId
+// ===========================================================================
+
+#[test]
+fn type_ids_parse_namespace_name_and_version() {
+    let id = NodeTypeId::parse("math.add@1.2").unwrap();
+    assert_eq!(id, NodeTypeId::new("math", "add").with_version(requirement("^1.2")));
+    assert_eq!(id.qualified_name(), "math.add");
+    assert!(id.accepts(&Version::new(1, 9, 0)));
+    assert!(!id.accepts(&Version::new(2, 0, 0)));
+
+    let nested: NodeTypeId = "acme.physics.raycast@>=2, <4".parse().unwrap();
+    assert_eq!((nested.namespace.as_str(), nested.name.as_str()), ("acme.physics", "raycast"));
+    assert!(nested.accepts(&Version::new(3, 1, 0)));
+
+    let bare = NodeTypeId::parse("print").unwrap();
+    assert_eq!((bare.namespace.as_str(), bare.name.as_str(), bare.version.as_ref()), ("", "print", None));
+    assert!(bare.accepts(&Version::new(99, 0, 0)));
+}
+
+#[test]
+fn type_ids_display_and_serialize_as_strings() {
+    for text in ["math.add", "print", "math.add@^1.2", "acme.physics.raycast@=2.0.1"] {
+        let id = NodeTypeId::parse(text).unwrap();
+        assert_eq!(id.to_string(), text);
+        assert_eq!(NodeTypeId::parse(&id.to_string()).unwrap(), id);
+    }
+
+    let id = NodeTypeId::parse("math.add@1.2").unwrap();
+    let json = serde_json::to_string(&id).unwrap();
+    assert_eq!(json, "\"math.add@^1.2\"");
+    assert_eq!(serde_json::from_str::<NodeTypeId>(&json).unwrap(), id);
+    assert!(serde_json::from_str::<NodeTypeId>("\"math.\"").is_err());
+}
+
+#[test]
+fn invalid_type_ids_are_errors() {
+    for text in ["", "math.", ".add", "math..add", "math.add@", "math.add@one"] {
+        let error = NodeTypeId::parse(text).unwrap_err();
+        assert!(error.to_string().contains("Invalid node type ID"), "{}: {}", text, error);
+    }
+}
+
+#[test]
+fn nodes_convert_to_and_from_type_ids() {
+    let mut node = NodeInstance::new("add_1", "old", Position::zero());
+    node.set_type_id(&NodeTypeId::parse("math.add@~1.4").unwrap());
+    assert_eq!(node.node_type, "math.add");
+    assert_eq!(node.version_req, Some(requirement("~1.4")));
+    assert_eq!(node.type_id().to_string(), "math.add@~1.4");
+
+    // Requirements are saved with the node, and only when set
+    let json = serde_json::to_value(&node).unwrap();
+    assert_eq!(json["version_req"], "~1.4");
+    let loaded: NodeInstance = serde_json::from_value(json).unwrap();
+    assert_eq!(loaded, node);
+    let plain = serde_json::to_value(NodeInstance::new("n", "print", Position::zero())).unwrap();
+    assert!(plain.get("version_req").is_none());
+}
+
+// ===========================================================================
+// Provider lookups
+// ===========================================================================
+
+#[test]
+fn registries_pick_the_newest_matching_version() {
+    let registry = versioned_registry();
+    let category = |metadata: Option<&NodeMetadata>| metadata.map(|metadata| metadata.category.clone());
+
+    assert_eq!(category(registry.get_node_metadata_matching("math.add", &requirement("^1"))), Some("v1.4".into()));
+    assert_eq!(category(registry.get_node_metadata_matching("math.add", &requirement("~1.0"))), Some("v1.0".into()));
+    assert_eq!(category(registry.get_node_metadata_matching("math.add", &requirement("*"))), Some("v2.0".into()));
+    assert_eq!(category(registry.get_node_metadata_matching("math.add", &requirement("^3"))), None);
+
+    // Without a requirement: the newest version, or an exact registration
+    assert_eq!(category(registry.get_node_metadata("math.add")), Some("v2.0".into()));
+    assert_eq!(category(registry.get_node_metadata("math.add@1.0.0")), Some("v1.0".into()));
+
+    let versions = registry.node_versions("math.add");
+    assert_eq!(versions, [Version::new(1, 0, 0), Version::new(1, 4, 0), Version::new(2, 0, 0)]);
+    assert!(registry.node_versions("math.sub").is_empty());
+}
+
+#[test]
+fn unregistering_a_version_keeps_the_others() {
+    let mut registry = versioned_registry();
+    assert!(registry.unregister("math.add@2.0.0").is_some());
+    assert_eq!(registry.get_node_metadata("math.add").unwrap().category, "v1.4");
+    assert_eq!(registry.node_versions("math.add").len(), 2);
+
+    // Saved libraries keep every version
+    let loaded = NodeRegistry::from_json(&registry.to_json().unwrap()).unwrap();
+    assert_eq!(loaded.node_versions("math.add"), registry.node_versions("math.add"));
+}
+
+#[test]
+fn unversioned_metadata_satisfies_no_requirement() {
+    let mut registry = NodeRegistry::new();
+    registry.register_as("math.add", NodeMetadata::new("add", NodeTypes::pure, "Math"));
+
+    assert!(registry.metadata_for_node(&NodeInstance::new("a", "math.add", Position::zero())).is_some());
+    assert!(registry.metadata_for_node(&node_requiring("a", "*")).is_none());
+}
+
+#[test]
+fn combinators_honor_requirements() {
+    let composite = CompositeProvider::new()
+        .with_provider(10, {
+            let mut newer = NodeRegistry::new();
+            newer.register_versioned("math.add", add_v(2, 0, "new"));
+            newer
+        })
+        .with_provider(0, {
+            let mut older = NodeRegistry::new();
+            older.register_versioned("math.add", add_v(1, 0, "old"));
+            older
+        });
+    assert_eq!(composite.metadata_for_node(&node_requiring("a", "^1")).unwrap().category, "old");
+    assert_eq!(composite.metadata_for_node(&node_requiring("a", "^2")).unwrap().category, "new");
+    assert_eq!(composite.node_versions("math.add"), [Version::new(1, 0, 0), Version::new(2, 0, 0)]);
+
+    let mut overlay = OverlayProvider::new(versioned_registry());
+    overlay.set_override(add_v(3, 0, "override"));
+    assert_eq!(overlay.get_node_metadata_matching("add", &requirement("^3")).unwrap().category, "override");
+    assert!(overlay.get_node_metadata_matching("add", &requirement("^1")).is_none());
+    assert_eq!(overlay.metadata_for_node(&node_requiring("a", "^1")).unwrap().category, "v1.4");
+    overlay.hide("math.add");
+    assert!(overlay.metadata_for_node(&node_requiring("a", "^1")).is_none());
+}
+
+// ===========================================================================
+// Graphs
+// ===========================================================================
+
+#[test]
+fn nodes_of_versioned_types_require_compatible_versions() {
+    let registry = versioned_registry();
+    let mut graph = GraphDescription::new("graph");
+
+    let newest = graph.add_node_of_type("math.add", "newest", &registry).unwrap();
+    assert_eq!(newest.version_req, Some(requirement("^2.0.0")));
+    let pinned = graph.add_node_of_type("math.add@~1.0", "pinned", &registry).unwrap();
+    assert_eq!((pinned.node_type.as_str(), pinned.version_req.clone()), ("math.add", Some(requirement("~1.0"))));
+    assert_eq!(registry.metadata_for_node(&graph.nodes["pinned"]).unwrap().category, "v1.0");
+
+    assert!(graph.add_node_of_type("math.add@^5", "missing", &registry).is_err());
+    assert!(graph.add_node_of_type("math.add@five", "invalid", &registry).is_err());
+    assert_eq!(graph.nodes.len(), 2);
+}
+
+#[test]
+fn unsatisfied_requirements_are_reported() {
+    let registry = versioned_registry();
+    let mut graph = GraphDescription::new("graph");
+    graph.add_node(node_requiring("b_too_new", "^3"));
+    graph.add_node(node_requiring("a_fine", "^1.2"));
+    graph.add_node(NodeInstance::new("c_unknown", "math.sub", Position::zero()));
+    let mut unknown = node_requiring("d_unknown_versioned", "^1");
+    unknown.node_type = "math.sub".into();
+    graph.add_node(unknown);
+
+    let issues = graph.check_node_versions(&registry);
+    let nodes: Vec<&str> = issues.iter().map(|issue| issue.node.as_str()).collect();
+    assert_eq!(nodes, ["b_too_new", "d_unknown_versioned"]);
+    assert_eq!(issues[0].available.len(), 3);
+    assert_eq!(
+        issues[0].to_string(),
+        "Node b_too_new requires math.add ^3, but only 1.0.0, 1.4.0, 2.0.0 available"
+    );
+    assert_eq!(
+        issues[1].to_string(),
+        "Node d_unknown_versioned requires math.sub ^1, but no version is available"
+    );
+
+    let error = GraphyError::from(issues[0].clone());
+    assert!(matches!(error, GraphyError::UnsatisfiedVersion { ref node, .. } if node == "b_too_new"));
+}
+
+#[test]
+fn version_requirements_are_part_of_the_content() {
+    let mut graph = GraphDescription::new("graph");
+    graph.add_node(NodeInstance::new("add", "math.add", Position::zero()));
+    let before = graph.clone();
+
+    graph.nodes.get_mut("add").unwrap().version_req = Some(requirement("^1"));
+    assert!(!graph.semantically_equals(&before));
+    assert_ne!(graph.content_hash(), before.content_hash());
+}
@@ -0,0 +1,233 @@
+//! Tests for side-effect ordering between execution and data flow.
+
+mod common;
+
+use common::*;
+use graphy::analysis::{CrossingEdge, OrderingViolation, SideEffectOrdering};
+use graphy::core::{ERROR_EXEC_PIN, ERROR_VALUE_PIN};
+use graphy::*;
+
+fn add_branch(graph: &mut GraphDescription) {
+    let mut branch = NodeInstance::new("branch_1", "branch", Position::zero());
+    branch.add_input_pin("exec_in", DataType::Execution);
+    branch.add_input_pin("condition", DataType::Typed("bool".into()));
+    branch.add_output_pin("True", DataType::Execution);
+    branch.add_output_pin("False", DataType::Execution);
+    branch.set_property("condition", PropertyValue::Boolean(true));
+    graph.add_node(branch);
+}
+
+fn analyze(graph: &GraphDescription) -> SideEffectOrdering {
+    let provider = ordering_provider();
+    let resolver = DataResolver::build(graph, &provider).unwrap();
+    let routing = ExecutionRouting::build_from_graph(graph);
+    SideEffectOrdering::compute(graph, &provider, &routing, &resolver)
+}
+
+fn violation(node: &str, producer: &str, pin: &str) -> OrderingViolation {
+    OrderingViolation {
+        event: "start".into(),
+        node: node.into(),
+        producer: producer.into(),
+        pin: pin.into(),
+    }
+}
+
+// ===========================================================================
+// Crossing edges
+// ===========================================================================
+
+#[test]
+fn ordering_records_crossing_edges() {
+    let mut graph = build_getter_graph();
+    let mut negate = NodeInstance::new("negate_1", "negate", Position::zero());
+    negate.add_input_pin("value", DataType::Typed("i64".into()));
+    negate.add_output_pin("result", DataType::Typed("i64".into()));
+    graph.add_node(negate);
+    graph.add_connection(Connection::data("add_1", "result", "negate_1", "value"));
+
+    let ordering = analyze(&graph);
+    // Pure -> pure and pure -> function edges don't cross
+    assert_eq!(
+        ordering.crossing_edges(),
+        [CrossingEdge {
+            source_node: "read_1".into(),
+            source_pin: "result".into(),
+            target_node: "add_1".into(),
+            target_pin: "a".into(),
+        }]
+    );
+}
+
+#[test]
+fn ordering_without_producers_is_ordered() {
+    let ordering = analyze(&build_exec_chain(3));
+    assert!(ordering.crossing_edges().is_empty());
+    assert!(ordering.is_ordered());
+    assert!(ordering.validate().is_ok());
+}
+
+// ===========================================================================
+// Sequential flow
+// ===========================================================================
+
+#[test]
+fn ordering_getter_after_setter() {
+    let mut graph = build_getter_graph();
+    graph.add_connection(Connection::execution("start", "exec", "read_1", "exec_in"));
+    graph.add_connection(Connection::execution("read_1", "then", "use_1", "exec_in"));
+
+    assert!(analyze(&graph).is_ordered());
+}
+
+#[test]
+fn ordering_getter_before_setter_fails() {
+    let mut graph = build_getter_graph();
+    graph.add_connection(Connection::execution("start", "exec", "use_1", "exec_in"));
+    graph.add_connection(Connection::execution("use_1", "then", "read_1", "exec_in"));
+
+    let ordering = analyze(&graph);
+    assert_eq!(ordering.violations(), [violation("use_1", "read_1", "result")]);
+
+    let error = ordering.validate().unwrap_err();
+    assert!(matches!(error, GraphyError::AmbiguousOrdering { ref node, .. } if node == "use_1"));
+    assert!(error.to_string().contains("read_1.result"), "{}", error);
+}
+
+#[test]
+fn ordering_direct_read_before_producer_fails() {
+    let mut graph = build_getter_graph();
+    graph.connections.retain(|c| c.target_node != "use_1");
+    graph.add_connection(Connection::data("read_1", "result", "use_1", "value"));
+    graph.add_connection(Connection::execution("start", "exec", "use_1", "exec_in"));
+    graph.add_connection(Connection::execution("use_1", "then", "read_1", "exec_in"));
+
+    assert_eq!(analyze(&graph).violations(), [violation("use_1", "read_1", "result")]);
+}
+
+#[test]
+fn ordering_producer_never_run_fails() {
+    let mut graph = build_getter_graph();
+    graph.add_connection(Connection::execution("start", "exec", "use_1", "exec_in"));
+
+    assert_eq!(analyze(&graph).violations(), [violation("use_1", "read_1", "result")]);
+}
+
+#[test]
+fn ordering_ignores_unreachable_readers() {
+    let graph = build_getter_graph();
+    assert!(analyze(&graph).is_ordered());
+}
+
+// ===========================================================================
+// Branches
+// ===========================================================================
+
+#[test]
+fn ordering_producer_before_branch() {
+    let mut graph = build_getter_graph();
+    add_branch(&mut graph);
+    fn_node(&mut graph, "use_2", "use_value");
+    graph.add_connection(Connection::data("add_1", "result", "use_2", "value"));
+    graph.add_connection(Connection::execution("start", "exec", "read_1", "exec_in"));
+    graph.add_connection(Connection::execution("read_1", "then", "branch_1", "exec_in"));
+    graph.add_connection(Connection::execution("branch_1", "True", "use_1", "exec_in"));
+    graph.add_connection(Connection::execution("branch_1", "False", "use_2", "exec_in"));
+
+    assert!(analyze(&graph).is_ordered());
+}
+
+#[test]
+fn ordering_producer_on_sibling_branch_fails() {
+    let mut graph = build_getter_graph();
+    add_branch(&mut graph);
+    graph.add_connection(Connection::execution("start", "exec", "branch_1", "exec_in"));
+    graph.add_connection(Connection::execution("branch_1", "True", "read_1", "exec_in"));
+    graph.add_connection(Connection::execution("branch_1", "False", "use_1", "exec_in"));
+
+    assert_eq!(analyze(&graph).violations(), [violation("use_1", "read_1", "result")]);
+}
+
+#[test]
+fn ordering_producer_on_one_side_of_join_fails() {
+    // use_1 runs after read_1 on the True side, but directly on the False side
+    let mut graph = build_getter_graph();
+    add_branch(&mut graph);
+    graph.add_connection(Connection::execution("start", "exec", "branch_1", "exec_in"));
+    graph.add_connection(Connection::execution("branch_1", "True", "read_1", "exec_in"));
+    graph.add_connection(Connection::execution("read_1", "then", "use_1", "exec_in"));
+    graph.add_connection(Connection::execution("branch_1", "False", "use_1", "exec_in"));
+
+    assert_eq!(analyze(&graph).violations(), [violation("use_1", "read_1", "result")]);
+}
+
+#[test]
+fn ordering_producer_on_both_sides_of_join() {
+    let mut graph = build_getter_graph();
+    add_branch(&mut graph);
+    fn_node(&mut graph, "read_2", "read_value");
+    graph.connections.retain(|c| c.source_node != "read_1");
+    graph.add_connection(Connection::data("read_2", "result", "add_1", "a"));
+    graph.add_connection(Connection::data("add_1", "result", "use_1", "value"));
+    graph.add_connection(Connection::execution("start", "exec", "branch_1", "exec_in"));
+    graph.add_connection(Connection::execution("branch_1", "True", "read_1", "exec_in"));
+    graph.add_connection(Connection::execution("branch_1", "False", "read_2", "exec_in"));
+    graph.add_connection(Connection::execution("read_1", "then", "read_2", "exec_in"));
+    graph.add_connection(Connection::execution("read_2", "then", "use_1", "exec_in"));
+
+    // read_2 runs on both paths into use_1
+    assert!(analyze(&graph).is_ordered());
+}
+
+// ===========================================================================
+// Fallible nodes and events
+// ===========================================================================
+
+#[test]
+fn ordering_fallible_values_follow_their_flow() {
+    let mut graph = build_getter_graph();
+    graph.nodes.remove("read_1");
+    fn_node(&mut graph, "read_1", "try_read");
+    graph.nodes.get_mut("read_1").unwrap().add_output_pin(ERROR_EXEC_PIN, DataType::Execution);
+    fn_node(&mut graph, "use_2", "use_value");
+    fn_node(&mut graph, "use_3", "use_value");
+    graph.add_connection(Connection::data("read_1", "result", "use_2", "value"));
+    graph.add_connection(Connection::data("read_1", ERROR_VALUE_PIN, "use_3", "value"));
+    graph.add_connection(Connection::execution("start", "exec", "read_1", "exec_in"));
+    graph.add_connection(Connection::execution("read_1", "then", "use_1", "exec_in"));
+    graph.add_connection(Connection::execution("read_1", ERROR_EXEC_PIN, "use_2", "exec_in"));
+    graph.add_connection(Connection::execution("use_2", "then", "use_3", "exec_in"));
+
+    // The result only exists on success, the error only on failure
+    assert_eq!(analyze(&graph).violations(), [violation("use_2", "read_1", "result")]);
+}
+
+#[test]
+fn ordering_event_params_of_other_events_fail() {
+    let mut graph = build_exec_chain(1);
+    let mut start = NodeInstance::new("start", "on_start", Position::zero());
+    start.add_output_pin("exec", DataType::Execution);
+    graph.add_node(start);
+    graph.add_connection(Connection::execution("start", "exec", "fn_0", "exec_in"));
+    let mut tick = NodeInstance::new("tick", "on_tick", Position::zero());
+    tick.add_output_pin("exec", DataType::Execution);
+    tick.add_output_pin("delta_time", DataType::Typed("f64".into()));
+    graph.add_node(tick);
+    graph.add_connection(Connection::data("tick", "delta_time", "fn_0", "message"));
+
+    assert_eq!(analyze(&graph).violations(), [violation("fn_0", "tick", "delta_time")]);
+}
+
+#[test]
+fn ordering_event_params_in_own_event() {
+    let mut graph = GraphDescription::new("ordering");
+    let mut tick = NodeInstance::new("tick", "on_tick", Position::zero());
+    tick.add_output_pin("exec", DataType::Execution);
+    tick.add_output_pin("delta_time", DataType::Typed("f64".into()));
+    graph.add_node(tick);
+    fn_node(&mut graph, "use_1", "use_value");
+    graph.add_connection(Connection::execution("tick", "exec", "use_1", "exec_in"));
+    graph.add_connection(Connection::data("tick", "delta_time", "use_1", "value"));
+
+    assert!(analyze(&graph).is_ordered());
+}
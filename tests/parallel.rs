@@ -1,5 +1,7 @@
 //! Tests for ThreadPoolConfig and parallel thread pool management.
 
+#![cfg(feature = "parallel")]
+
 use graphy::parallel::*;
 
 // ===========================================================================
@@ -0,0 +1,205 @@
+//! Tests for evaluating expensive pure inputs in parallel.
+
+mod common;
+
+use common::*;
+use graphy::generation::{
+    compile, compile_with_options, compile_with_source_map, CompilationSession, CompileOptions, ExpressionPolicy,
+    ParallelCodegen,
+};
+use graphy::*;
+use std::sync::Arc;
+
+/// `on_start`, `mix` (a function of three `f64`s), pure `noise` and `blur`
+/// (cost 50), `scale` (cost 1), and volatile `random` (cost 50)
+fn registry() -> NodeRegistry {
+    let mut registry = NodeRegistry::new();
+    registry.register(NodeMetadata::new("on_start", NodeTypes::event, "Events").with_exec_outputs(vec!["exec".into()]));
+    registry.register(
+        NodeMetadata::new("mix", NodeTypes::fn_, "Image")
+            .with_params(vec![
+                ParamInfo::new("a", "f64"),
+                ParamInfo::new("b", "f64"),
+                ParamInfo::new("c", "f64"),
+            ])
+            .with_exec_outputs(vec!["exec_out".into()]),
+    );
+    for (name, cost) in [("noise", 50), ("blur", 50), ("scale", 1), ("random", 50)] {
+        registry.register(
+            NodeMetadata::new(name, NodeTypes::pure, "Image")
+                .with_params(vec![ParamInfo::new("x", "f64")])
+                .with_return_type("f64")
+                .with_cost_hint(cost)
+                .with_volatile(name == "random"),
+        );
+    }
+    registry
+}
+
+/// `start -> mix_1`, with a node of each type in `inputs` feeding `a`, `b`,
+/// and `c` in order (`x` set to 0.5)
+fn mix_graph(registry: &NodeRegistry, inputs: &[&str]) -> GraphDescription {
+    let mut graph = GraphDescription::new("image");
+    graph.add_node_of_type("on_start", "start", registry).unwrap();
+    graph.add_node_of_type("mix", "mix_1", registry).unwrap();
+    graph.add_connection(Connection::execution("start", "exec", "mix_1", "exec_in"));
+    for (node_type, pin) in inputs.iter().zip(["a", "b", "c"]) {
+        let id = format!("{}_1", node_type);
+        graph.add_node_of_type(node_type, &id, registry).unwrap();
+        graph.nodes.get_mut(&id).unwrap().set_property("x", PropertyValue::Number(0.5));
+        graph.add_connection(Connection::data(id.as_str(), "result", "mix_1", pin));
+    }
+    graph
+}
+
+/// Generator emitting calls, with the default parallel scaffolding
+fn join_generator() -> CallGenerator {
+    CallGenerator::new().with_parallel(true)
+}
+
+fn options() -> CompileOptions {
+    CompileOptions::new().with_parallel_codegen(ParallelCodegen::new(40))
+}
+
+fn compile_parallel(graph: &GraphDescription, registry: &NodeRegistry, generator: &mut CallGenerator) -> String {
+    compile_with_options(graph, registry, generator, &options()).unwrap().code
+}
+
+// ============================================================================
+// Joins
+// ============================================================================
+
+#[test]
+fn parallel_codegen_joins_expensive_inputs() {
+    let registry = registry();
+    let graph = mix_graph(&registry, &["noise", "blur"]);
+    let code = compile_parallel(&graph, &registry, &mut join_generator());
+
+    assert_eq!(
+        code,
+        "let (node_noise_1_result, node_blur_1_result) = ::rayon::join(|| {\n    \
+         noise(0.5)\n\
+         }, || {\n    \
+         blur(0.5)\n\
+         });\n\
+         mix(node_noise_1_result, node_blur_1_result, 0.0);\n"
+    );
+}
+
+#[test]
+fn parallel_codegen_nests_joins_for_more_inputs() {
+    let registry = registry();
+    let mut graph = mix_graph(&registry, &["noise", "blur"]);
+    graph.add_node_of_type("noise", "noise_2", &registry).unwrap();
+    graph.add_connection(Connection::data("noise_2", "result", "mix_1", "c"));
+    let code = compile_parallel(&graph, &registry, &mut join_generator());
+
+    assert!(
+        code.starts_with(
+            "let (node_noise_1_result, (node_blur_1_result, node_noise_2_result)) = ::rayon::join(|| {\n    \
+             noise(0.5)\n\
+             }, || ::rayon::join(|| {\n    \
+             blur(0.5)\n\
+             }, || {\n    \
+             noise(0.0)\n\
+             }));\n"
+        ),
+        "{}",
+        code
+    );
+    assert!(code.ends_with("mix(node_noise_1_result, node_blur_1_result, node_noise_2_result);\n"), "{}", code);
+}
+
+#[test]
+fn parallel_codegen_keeps_subtrees_in_their_branch() {
+    let registry = registry();
+    let mut graph = mix_graph(&registry, &["noise", "blur"]);
+    graph.add_node_of_type("scale", "scale_1", &registry).unwrap();
+    graph.add_connection(Connection::data("scale_1", "result", "noise_1", "x"));
+
+    let mut generator = join_generator().with_policy(ExpressionPolicy::HoistToLet);
+    let code = compile_parallel(&graph, &registry, &mut generator);
+    assert!(
+        code.starts_with(
+            "let (node_noise_1_result, node_blur_1_result) = ::rayon::join(|| {\n    \
+             let node_scale_1_result = scale(0.0);\n    \
+             noise(node_scale_1_result)\n"
+        ),
+        "{}",
+        code
+    );
+}
+
+// ============================================================================
+// Eligibility
+// ============================================================================
+
+#[test]
+fn parallel_codegen_needs_two_expensive_inputs() {
+    let registry = registry();
+    let graph = mix_graph(&registry, &["noise", "scale"]);
+    let code = compile_parallel(&graph, &registry, &mut join_generator());
+    assert_eq!(code, "mix(noise(0.5), scale(0.5), 0.0);\n");
+}
+
+#[test]
+fn parallel_codegen_is_opt_in() {
+    let registry = registry();
+    let graph = mix_graph(&registry, &["noise", "blur"]);
+    let plain = "mix(noise(0.5), blur(0.5), 0.0);\n";
+
+    assert_eq!(compile(&graph, &registry, &mut join_generator()).unwrap(), plain);
+    let mut unsupported = join_generator().with_parallel(false);
+    assert_eq!(compile_parallel(&graph, &registry, &mut unsupported), plain);
+}
+
+#[test]
+fn parallel_codegen_skips_volatile_subtrees() {
+    let registry = registry();
+    let graph = mix_graph(&registry, &["noise", "random"]);
+    let code = compile_parallel(&graph, &registry, &mut join_generator());
+    assert!(!code.contains("rayon"), "{}", code);
+}
+
+#[test]
+fn parallel_codegen_skips_shared_subtrees() {
+    let registry = registry();
+    let mut graph = mix_graph(&registry, &["noise", "blur"]);
+    // Both inputs read the same node, so neither can compute it alone
+    graph.add_node_of_type("scale", "scale_1", &registry).unwrap();
+    graph.add_connection(Connection::data("scale_1", "result", "noise_1", "x"));
+    graph.add_connection(Connection::data("scale_1", "result", "blur_1", "x"));
+
+    let code = compile_parallel(&graph, &registry, &mut join_generator());
+    assert!(!code.contains("rayon"), "{}", code);
+}
+
+// ============================================================================
+// Source maps and sessions
+// ============================================================================
+
+#[test]
+fn parallel_codegen_maps_branches_to_their_input() {
+    let registry = registry();
+    let graph = mix_graph(&registry, &["noise", "blur"]);
+    let mut generator = join_generator();
+    let output = compile_with_options(&graph, &registry, &mut generator, &options()).unwrap();
+
+    assert_eq!(output.source_map.resolve(2, 5).unwrap().node_id, "noise_1");
+    assert_eq!(output.source_map.resolve(4, 5).unwrap().node_id, "blur_1");
+    assert_eq!(output.source_map.resolve(1, 1).unwrap().node_id, "mix_1");
+
+    let (plain, _) = compile_with_source_map(&graph, &registry, &mut generator).unwrap();
+    assert!(!plain.contains("rayon"), "{}", plain);
+}
+
+#[test]
+fn parallel_codegen_applies_to_sessions() {
+    let registry = registry();
+    let graph = mix_graph(&registry, &["noise", "blur"]);
+    let mut generator = join_generator();
+    let session = CompilationSession::new(&graph, Arc::new(registry.clone()), &generator, &options()).unwrap();
+
+    let output = session.finish(&mut generator).unwrap();
+    assert_eq!(output.code, compile_parallel(&graph, &registry, &mut join_generator()));
+}
@@ -0,0 +1,258 @@
+//! Tests for connection multiplicity rules on input pins.
+
+use graphy::core::{MultiplicityIssue, PinMultiplicity};
+use graphy::*;
+
+/// `literal` (returns a `String`), `concat` (any number of `parts`),
+/// `pair` (at most 2 `items`), and `upper` (one `text`)
+fn text_registry() -> NodeRegistry {
+    let mut registry = NodeRegistry::new();
+    registry.register(NodeMetadata::new("literal", NodeTypes::pure, "Text").with_return_type("String"));
+    let text_node = |name: &str, param: ParamInfo| {
+        NodeMetadata::new(name, NodeTypes::pure, "Text")
+            .with_params(vec![param])
+            .with_return_type("String")
+    };
+    registry.register(text_node(
+        "concat",
+        ParamInfo::new("parts", "String").with_multiplicity(PinMultiplicity::Multi),
+    ));
+    registry.register(text_node(
+        "pair",
+        ParamInfo::new("items", "String").with_multiplicity(PinMultiplicity::AtMost(2)),
+    ));
+    registry.register(text_node("upper", ParamInfo::new("text", "String")));
+    registry
+}
+
+/// Literals `lit_0` to `lit_{n-1}` and one node of the given type, `target`
+fn text_graph(target_type: &str, literals: usize, registry: &NodeRegistry) -> GraphDescription {
+    let mut graph = GraphDescription::new("text");
+    for i in 0..literals {
+        graph.add_node_of_type("literal", format!("lit_{}", i), registry).unwrap();
+    }
+    graph.add_node_of_type(target_type, "target", registry).unwrap();
+    graph
+}
+
+fn literal_to(i: usize, pin: &str) -> Connection {
+    Connection::data(format!("lit_{}", i), "result", "target", pin)
+}
+
+// ===========================================================================
+// PinMultiplicity
+// ===========================================================================
+
+#[test]
+fn multiplicity_limits_connections() {
+    assert_eq!(PinMultiplicity::default(), PinMultiplicity::Single);
+    assert!(PinMultiplicity::Single.accepts(1));
+    assert!(!PinMultiplicity::Single.accepts(2));
+    assert!(PinMultiplicity::Multi.accepts(1000));
+    assert!(PinMultiplicity::AtMost(3).accepts(3));
+    assert!(!PinMultiplicity::AtMost(3).accepts(4));
+
+    assert_eq!(PinMultiplicity::Single.max_connections(), Some(1));
+    assert_eq!(PinMultiplicity::Multi.max_connections(), None);
+    assert!(!PinMultiplicity::Single.accepts_several());
+    assert!(!PinMultiplicity::AtMost(1).accepts_several());
+    assert!(PinMultiplicity::AtMost(2).accepts_several());
+}
+
+#[test]
+fn multiplicity_serializes_only_when_set() {
+    let single = serde_json::to_value(ParamInfo::new("text", "String")).unwrap();
+    assert!(single.get("multiplicity").is_none());
+    let pin = serde_json::to_value(Pin::new("text", "Text", DataType::String, PinType::Input)).unwrap();
+    assert!(pin.get("multiplicity").is_none());
+
+    let param = ParamInfo::new("items", "String").with_multiplicity(PinMultiplicity::AtMost(2));
+    let json = serde_json::to_value(&param).unwrap();
+    assert_eq!(json["multiplicity"], serde_json::json!({ "at_most": 2 }));
+    let loaded: ParamInfo = serde_json::from_value(json).unwrap();
+    assert_eq!(loaded.multiplicity, PinMultiplicity::AtMost(2));
+
+    let multi: PinMultiplicity = serde_json::from_str("\"multi\"").unwrap();
+    assert_eq!(multi, PinMultiplicity::Multi);
+}
+
+#[test]
+fn multiplicity_is_copied_to_node_pins() {
+    let registry = text_registry();
+    let graph = text_graph("concat", 0, &registry);
+    assert_eq!(graph.nodes["target"].inputs[0].pin.multiplicity, PinMultiplicity::Multi);
+
+    let graph = text_graph("upper", 0, &registry);
+    assert_eq!(graph.nodes["target"].inputs[0].pin.multiplicity, PinMultiplicity::Single);
+}
+
+#[test]
+fn multiplicity_of_metadata_wins_over_pins() {
+    let registry = text_registry();
+    let concat = registry.get_node_metadata("concat");
+
+    // A graph saved before the param became multi
+    let mut node = NodeInstance::new("concat_1", "concat", Position::zero());
+    node.add_input_pin("parts", DataType::Typed("String".into()));
+    assert_eq!(node.input_multiplicity("parts", concat), PinMultiplicity::Multi);
+
+    // Without metadata, the pin decides
+    let mut custom = NodeInstance::new("custom_1", "custom", Position::zero());
+    custom.inputs.push(PinInstance::new(
+        "values",
+        Pin::new("values", "Values", DataType::Number, PinType::Input).with_multiplicity(PinMultiplicity::AtMost(4)),
+    ));
+    assert_eq!(custom.input_multiplicity("values", None), PinMultiplicity::AtMost(4));
+    assert_eq!(custom.input_multiplicity("missing", None), PinMultiplicity::Single);
+}
+
+#[test]
+fn multiplicity_changes_the_content_hash() {
+    let registry = text_registry();
+    let graph = text_graph("upper", 0, &registry);
+    let mut changed = graph.clone();
+    changed.nodes.get_mut("target").unwrap().inputs[0].pin.multiplicity = PinMultiplicity::Multi;
+    assert_ne!(graph.content_hash(), changed.content_hash());
+}
+
+// ===========================================================================
+// Validation
+// ===========================================================================
+
+#[test]
+fn multiplicity_limits_try_add_connection() {
+    let registry = text_registry();
+
+    let mut graph = text_graph("upper", 2, &registry);
+    graph.try_add_connection(literal_to(0, "text"), &registry).unwrap();
+    let error = graph.try_add_connection(literal_to(1, "text"), &registry).unwrap_err();
+    assert!(
+        matches!(
+            error,
+            GraphyError::InvalidConnection { ref connection, reason: core::ConnectionErrorReason::TooManyConnections { connections: 2, max: 1 } }
+                if connection.target_node == "target" && connection.target_pin == "text" && connection.index.is_none()
+        ),
+        "{:?}",
+        error
+    );
+    assert!(error.to_string().ends_with("target.text: the input would have 2 connections, but accepts at most 1"), "{}", error);
+    assert_eq!(graph.connections.len(), 1);
+
+    let mut graph = text_graph("pair", 3, &registry);
+    graph.try_add_connection(literal_to(0, "items"), &registry).unwrap();
+    graph.try_add_connection(literal_to(1, "items"), &registry).unwrap();
+    assert!(graph.try_add_connection(literal_to(2, "items"), &registry).is_err());
+
+    let mut graph = text_graph("concat", 5, &registry);
+    for i in 0..5 {
+        graph.try_add_connection(literal_to(i, "parts"), &registry).unwrap();
+    }
+}
+
+#[test]
+fn multiplicity_validation_ignores_the_connection_itself() {
+    let registry = text_registry();
+    let mut graph = text_graph("upper", 1, &registry);
+    graph.try_add_connection(literal_to(0, "text"), &registry).unwrap();
+    assert!(graph.validate_connection(&literal_to(0, "text"), &registry).is_ok());
+}
+
+#[test]
+fn multiplicity_violations_are_listed() {
+    let registry = text_registry();
+    let mut graph = text_graph("pair", 3, &registry);
+    graph.add_node_of_type("upper", "upper_1", &registry).unwrap();
+    for i in 0..3 {
+        graph.add_connection(literal_to(i, "items"));
+    }
+    graph.add_connection(Connection::data("lit_0", "result", "upper_1", "text"));
+    assert_eq!(graph.check_pin_multiplicity(&registry).len(), 1);
+
+    graph.add_connection(Connection::data("lit_1", "result", "upper_1", "text"));
+    let issues = graph.check_pin_multiplicity(&registry);
+    assert_eq!(
+        issues,
+        vec![
+            MultiplicityIssue {
+                node: "target".to_string(),
+                pin: "items".to_string(),
+                multiplicity: PinMultiplicity::AtMost(2),
+                connections: 3,
+            },
+            MultiplicityIssue {
+                node: "upper_1".to_string(),
+                pin: "text".to_string(),
+                multiplicity: PinMultiplicity::Single,
+                connections: 2,
+            },
+        ]
+    );
+    assert_eq!(issues[0].to_string(), "Input target.items has 3 connections, but accepts at most 2");
+}
+
+// ===========================================================================
+// DataResolver
+// ===========================================================================
+
+fn source_nodes(sources: &[DataSource]) -> Vec<&str> {
+    sources
+        .iter()
+        .map(|source| match source {
+            DataSource::Connection { source_node_id, .. } => source_node_id.as_str(),
+            other => panic!("not a connection: {:?}", other),
+        })
+        .collect()
+}
+
+#[test]
+fn multiplicity_resolver_returns_every_source_in_order() {
+    let registry = text_registry();
+    let mut graph = text_graph("concat", 3, &registry);
+    for i in [2, 0, 1] {
+        graph.add_connection(literal_to(i, "parts"));
+    }
+
+    let resolver = DataResolver::build(&graph, &registry).unwrap();
+    assert_eq!(source_nodes(resolver.get_input_sources_multi("target", "parts")), ["lit_2", "lit_0", "lit_1"]);
+    assert_eq!(resolver.get_dependencies("target").len(), 3);
+    assert!(resolver.get_input_sources_multi("target", "missing").is_empty());
+
+    // Every literal is evaluated before the node reading them
+    let order = resolver.get_pure_evaluation_order();
+    assert_eq!(order.last().map(String::as_str), Some("target"));
+}
+
+#[test]
+fn multiplicity_resolver_falls_back_to_the_single_source() {
+    let registry = text_registry();
+    let mut graph = text_graph("upper", 1, &registry);
+    graph.add_connection(literal_to(0, "text"));
+    let resolver = DataResolver::build(&graph, &registry).unwrap();
+    assert_eq!(source_nodes(resolver.get_input_sources_multi("target", "text")), ["lit_0"]);
+
+    // Unconnected multi inputs get their constant, like any other input
+    let mut graph = text_graph("concat", 0, &registry);
+    graph
+        .nodes
+        .get_mut("target")
+        .unwrap()
+        .set_property("parts", PropertyValue::String("x".into()));
+    let resolver = DataResolver::build(&graph, &registry).unwrap();
+    assert!(matches!(resolver.get_input_sources_multi("target", "parts"), [DataSource::Constant(..)]));
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn multiplicity_parallel_resolver_matches_sequential() {
+    let registry = text_registry();
+    let mut graph = text_graph("concat", 4, &registry);
+    for i in [3, 1, 0, 2] {
+        graph.add_connection(literal_to(i, "parts"));
+    }
+    let sequential = DataResolver::build(&graph, &registry).unwrap();
+    let parallel = DataResolver::build_parallel(&graph, &registry).unwrap();
+    assert_eq!(
+        source_nodes(sequential.get_input_sources_multi("target", "parts")),
+        source_nodes(parallel.get_input_sources_multi("target", "parts"))
+    );
+}
@@ -0,0 +1,158 @@
+//! Tests for diffing generated code between two versions of a graph.
+
+mod common;
+
+use common::*;
+use graphy::*;
+use graphy::generation::{preview_diff, PreviewDiff};
+
+/// Rust-like generator with a branch and inline pure expressions
+fn preview_generator() -> HookGenerator {
+    HookGenerator::new().with_event_functions(true).with_branches(Branches::IfElse)
+}
+
+fn preview(old: &GraphDescription, new: &GraphDescription) -> PreviewDiff {
+    let provider = TestMetadataProvider::comprehensive();
+    preview_diff(old, new, &provider, &mut preview_generator()).unwrap()
+}
+
+/// An exec chain of `n` prints, run by a `start` event
+fn started_chain(n: usize) -> GraphDescription {
+    let mut graph = build_exec_chain(n);
+    let mut start = NodeInstance::new("start", "on_start", Position::zero());
+    start.add_output_pin("exec", DataType::Execution);
+    graph.add_node(start);
+    graph.add_connection(Connection::execution("start", "exec", "fn_0", "exec_in"));
+    graph
+}
+
+fn set_message(graph: &mut GraphDescription, node_id: &str, message: &str) {
+    graph
+        .nodes
+        .get_mut(node_id)
+        .unwrap()
+        .set_property("message", PropertyValue::String(message.into()));
+}
+
+// ============================================================================
+// Chunks
+// ============================================================================
+
+#[test]
+fn preview_unchanged_graph_is_empty() {
+    let graph = build_branch_graph();
+    let diff = preview(&graph, &graph);
+    assert!(diff.is_empty());
+    assert_eq!(diff.old_code, diff.new_code);
+    assert!(diff.changed_nodes().is_empty());
+}
+
+#[test]
+fn preview_property_edit_replaces_one_line() {
+    let old = build_branch_graph();
+    let mut new = old.clone();
+    set_message(&mut new, "print_false", "otherwise");
+
+    let diff = preview(&old, &new);
+    assert_eq!(diff.chunks.len(), 1, "{:#?}", diff.chunks);
+    let chunk = &diff.chunks[0];
+    assert_eq!((chunk.old_start, chunk.new_start), (5, 5));
+    assert_eq!(chunk.removed.len(), 1);
+    assert_eq!(chunk.added.len(), 1);
+    assert_eq!(chunk.added[0].line, 5);
+    assert!(chunk.added[0].text.contains("otherwise"));
+    assert_eq!(chunk.added[0].nodes, vec!["print_false"]);
+    assert_eq!(chunk.nodes().into_iter().collect::<Vec<_>>(), vec!["print_false"]);
+    assert!(diff.new_code.contains("otherwise"));
+}
+
+#[test]
+fn preview_separate_edits_are_separate_chunks() {
+    let old = build_branch_graph();
+    let mut new = old.clone();
+    set_message(&mut new, "print_true", "yes");
+    set_message(&mut new, "print_false", "no");
+
+    let diff = preview(&old, &new);
+    assert_eq!(diff.chunks.len(), 2);
+    assert_eq!(diff.chunks[0].nodes().into_iter().collect::<Vec<_>>(), vec!["print_true"]);
+    assert_eq!(diff.chunks[1].nodes().into_iter().collect::<Vec<_>>(), vec!["print_false"]);
+}
+
+#[test]
+fn preview_added_node_is_an_insertion() {
+    let diff = preview(&started_chain(2), &started_chain(3));
+    assert_eq!(diff.chunks.len(), 1);
+    let chunk = &diff.chunks[0];
+    assert!(chunk.removed.is_empty());
+    assert_eq!(chunk.added.len(), 1);
+    assert_eq!(chunk.old_start, 4);
+    assert_eq!(chunk.added[0].nodes, vec!["fn_2"]);
+    assert_eq!(diff.changed_lines("fn_2"), 1);
+    assert_eq!(diff.changed_lines("fn_0"), 0);
+}
+
+// ============================================================================
+// Attribution
+// ============================================================================
+
+#[test]
+fn preview_attributes_control_flow_lines_to_the_branch() {
+    let old = build_branch_graph();
+    let mut new = old.clone();
+    new.nodes
+        .get_mut("branch_1")
+        .unwrap()
+        .set_property("condition", PropertyValue::Boolean(false));
+
+    let diff = preview(&old, &new);
+    assert_eq!(diff.chunks.len(), 1);
+    assert_eq!(diff.chunks[0].removed[0].text, "    if true {");
+    assert_eq!(diff.changed_nodes().into_iter().collect::<Vec<_>>(), vec!["branch_1"]);
+    assert_eq!(diff.chunks_for_node("branch_1").count(), 1);
+    assert_eq!(diff.chunks_for_node("print_true").count(), 0);
+}
+
+#[test]
+fn preview_attributes_pure_nodes_to_their_readers() {
+    let provider = TestMetadataProvider::comprehensive();
+    let build = |b: i64| {
+        let mut graph = started_chain(1);
+        let mut add = NodeInstance::new("add_1", "add", Position::zero());
+        add.add_input_pin("a", DataType::Typed("i64".into()));
+        add.add_input_pin("b", DataType::Typed("i64".into()));
+        add.add_output_pin("result", DataType::Typed("i64".into()));
+        add.set_property("a", PropertyValue::Number(1.0));
+        add.set_property("b", PropertyValue::Number(b as f64));
+        graph.add_node(add);
+        graph.add_connection(Connection::data("add_1", "result", "fn_0", "message"));
+        graph
+    };
+
+    let diff = preview_diff(&build(2), &build(3), &provider, &mut preview_generator()).unwrap();
+    assert_eq!(diff.chunks.len(), 1);
+    assert!(diff.chunks[0].added[0].text.contains("add("), "{}", diff.new_code);
+    assert_eq!(diff.chunks[0].added[0].nodes, vec!["fn_0"]);
+}
+
+#[test]
+fn preview_serializes_for_editors() {
+    let old = build_branch_graph();
+    let mut new = old.clone();
+    set_message(&mut new, "print_true", "yes");
+
+    let diff = preview(&old, &new);
+    let json = serde_json::to_string(&diff).unwrap();
+    assert_eq!(serde_json::from_str::<PreviewDiff>(&json).unwrap(), diff);
+}
+
+#[test]
+fn preview_fails_when_either_graph_fails() {
+    let old = build_branch_graph();
+    let mut new = old.clone();
+    new.add_node(NodeInstance::new("ghost", "not_registered", Position::zero()));
+    new.add_connection(Connection::execution("print_true", "exec_out", "ghost", "exec_in"));
+
+    let provider = TestMetadataProvider::comprehensive();
+    assert!(preview_diff(&old, &new, &provider, &mut preview_generator()).is_err());
+}
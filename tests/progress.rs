@@ -0,0 +1,157 @@
+//! Tests for progress reporting during analysis and code generation.
+
+mod common;
+
+use common::*;
+use graphy::analysis::CompactGraph;
+use graphy::generation::{compile_with_options, CompilationSession, CompileOptions};
+use graphy::utils::{
+    CancellationToken, ProgressSink, RustProfile, PHASE_CODEGEN, PHASE_DATA_FLOW, PHASE_EVALUATION_ORDER,
+    PHASE_EXEC_ROUTING, PHASE_EXPANSION,
+};
+use graphy::*;
+use std::sync::{Arc, Mutex};
+
+/// Records every update it receives.
+#[derive(Default)]
+struct RecordingSink {
+    updates: Mutex<Vec<(String, usize, usize)>>,
+}
+
+impl ProgressSink for RecordingSink {
+    fn report(&self, phase: &str, processed: usize, total: usize) {
+        self.updates.lock().unwrap().push((phase.to_string(), processed, total));
+    }
+}
+
+impl RecordingSink {
+    fn updates(&self, phase: &str) -> Vec<(usize, usize)> {
+        self.updates
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(name, _, _)| name == phase)
+            .map(|&(_, processed, total)| (processed, total))
+            .collect()
+    }
+
+    /// Phases in the order they were first reported
+    fn phases(&self) -> Vec<String> {
+        let mut phases: Vec<String> = Vec::new();
+        for (phase, _, _) in self.updates.lock().unwrap().iter() {
+            if !phases.contains(phase) {
+                phases.push(phase.clone());
+            }
+        }
+        phases
+    }
+}
+
+/// Every phase starts at zero, never goes back, and ends at its total
+fn assert_complete(updates: &[(usize, usize)], total: usize) {
+    assert_eq!(updates.first(), Some(&(0, total)), "{:?}", updates);
+    assert_eq!(updates.last(), Some(&(total, total)), "{:?}", updates);
+    assert!(updates.windows(2).all(|pair| pair[0].0 <= pair[1].0), "{:?}", updates);
+}
+
+// ===========================================================================
+// Sinks
+// ===========================================================================
+
+#[test]
+fn closures_are_sinks() {
+    let count = Mutex::new(0);
+    let sink = |_: &str, processed: usize, _: usize| *count.lock().unwrap() += processed;
+    sink.report(PHASE_DATA_FLOW, 2, 4);
+    sink.report(PHASE_DATA_FLOW, 4, 4);
+    assert_eq!(*count.lock().unwrap(), 6);
+}
+
+// ===========================================================================
+// Analysis
+// ===========================================================================
+
+#[test]
+fn data_resolver_reports_each_phase() {
+    let provider = TestMetadataProvider::comprehensive();
+    let graph = build_linear_chain(20, &provider);
+    let sink = RecordingSink::default();
+
+    DataResolver::build_with_progress(&graph, &provider, &RustProfile, &CancellationToken::new(), &sink).unwrap();
+    assert_eq!(sink.phases(), [PHASE_DATA_FLOW, PHASE_EVALUATION_ORDER]);
+    assert_complete(&sink.updates(PHASE_DATA_FLOW), 20);
+    assert_complete(&sink.updates(PHASE_EVALUATION_ORDER), 20);
+}
+
+#[test]
+fn parallel_data_resolver_reports_each_phase() {
+    let provider = TestMetadataProvider::comprehensive();
+    let graph = build_linear_chain(20, &provider);
+    let sink = RecordingSink::default();
+
+    DataResolver::build_parallel_with_progress(&graph, &provider, &RustProfile, &CancellationToken::new(), &sink)
+        .unwrap();
+    assert_complete(&sink.updates(PHASE_DATA_FLOW), 20);
+    // One update per layer of the chain
+    let order = sink.updates(PHASE_EVALUATION_ORDER);
+    assert_complete(&order, 20);
+    assert_eq!(order.len(), 21);
+}
+
+#[test]
+fn large_phases_report_at_intervals() {
+    let provider = TestMetadataProvider::comprehensive();
+    let graph = build_linear_chain(1000, &provider);
+    let sink = RecordingSink::default();
+
+    DataResolver::build_with_progress(&graph, &provider, &RustProfile, &CancellationToken::new(), &sink).unwrap();
+    let updates = sink.updates(PHASE_DATA_FLOW);
+    assert_complete(&updates, 1000);
+    assert!(updates.len() <= 101, "{} updates", updates.len());
+}
+
+#[test]
+fn exec_routing_counts_connections() {
+    let graph = build_exec_chain(5);
+    let sink = RecordingSink::default();
+
+    ExecutionRouting::build_from_compact_with_progress(&CompactGraph::from_graph(&graph), &sink);
+    assert_complete(&sink.updates(PHASE_EXEC_ROUTING), 4);
+}
+
+// ===========================================================================
+// Compilation
+// ===========================================================================
+
+#[test]
+fn compile_reports_phases_in_order() {
+    let provider = TestMetadataProvider::comprehensive();
+    let sink = Arc::new(RecordingSink::default());
+    let options = CompileOptions::new().with_progress(sink.clone());
+
+    compile_with_options(&build_two_event_graph(), &provider, &mut LineGenerator::default(), &options).unwrap();
+    assert_eq!(
+        sink.phases(),
+        [PHASE_EXPANSION, PHASE_DATA_FLOW, PHASE_EVALUATION_ORDER, PHASE_EXEC_ROUTING, PHASE_CODEGEN]
+    );
+    assert_complete(&sink.updates(PHASE_EXPANSION), 4);
+    assert_eq!(sink.updates(PHASE_CODEGEN), [(0, 2), (1, 2), (2, 2)]);
+}
+
+#[test]
+fn session_reports_each_event() {
+    let provider = Arc::new(TestMetadataProvider::comprehensive());
+    let sink = Arc::new(RecordingSink::default());
+    let options = CompileOptions::new().with_progress(sink.clone());
+
+    let mut generator = LineGenerator::default();
+    let mut session = CompilationSession::new(&build_two_event_graph(), provider, &generator, &options).unwrap();
+    assert_complete(&sink.updates(PHASE_DATA_FLOW), 4);
+    assert!(sink.updates(PHASE_CODEGEN).is_empty());
+
+    session.step(&mut generator).unwrap();
+    session.step(&mut generator).unwrap();
+    assert_eq!(sink.updates(PHASE_CODEGEN), [(0, 2), (1, 2)]);
+    session.finish(&mut generator).unwrap();
+    assert_eq!(sink.updates(PHASE_CODEGEN).last(), Some(&(2, 2)));
+}
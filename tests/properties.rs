@@ -0,0 +1,239 @@
+//! Tests for checking property values against param types.
+
+use graphy::analysis::{check_properties, PropertyCheckPass, PropertyIssue};
+use graphy::core::{TypeCoercion, VariadicGroup};
+use graphy::utils::Diagnostics;
+use graphy::*;
+
+/// Pure `sample`, with one param per checked type, and `join` with a
+/// variadic `i32` group
+fn registry() -> NodeRegistry {
+    let mut registry = NodeRegistry::new();
+    registry.register(
+        NodeMetadata::new("sample", NodeTypes::pure, "Test")
+            .with_params(vec![
+                ParamInfo::new("count", "i64"),
+                ParamInfo::new("small", "u8"),
+                ParamInfo::new("scale", "f32"),
+                ParamInfo::new("label", "String"),
+                ParamInfo::new("enabled", "bool"),
+                ParamInfo::new("offset", "(f32, f32, f32)"),
+                ParamInfo::new("tint", "(f32, f32, f32, f32)"),
+                ParamInfo::new("weights", "Vec<f64>"),
+                ParamInfo::new("corners", "[i32; 4]"),
+                ParamInfo::new("names", "HashMap<String, String>"),
+                ParamInfo::new("target", "T"),
+            ])
+            .with_return_type("f64"),
+    );
+    registry.register(
+        NodeMetadata::new("join", NodeTypes::pure, "Test")
+            .with_variadic(VariadicGroup::new("item", "i32").with_min(2))
+            .with_return_type("i32"),
+    );
+    registry
+}
+
+fn graph_with(registry: &NodeRegistry, properties: &[(&str, PropertyValue)]) -> GraphDescription {
+    let mut graph = GraphDescription::new("properties");
+    graph.add_node_of_type("sample", "sample_1", registry).unwrap();
+    let node = graph.get_node_mut("sample_1").unwrap();
+    for (name, value) in properties {
+        node.set_property(*name, value.clone());
+    }
+    graph
+}
+
+fn issues(properties: &[(&str, PropertyValue)]) -> Vec<PropertyIssue> {
+    let registry = registry();
+    check_properties(&graph_with(&registry, properties), &registry, &TypeCoercion::rust())
+}
+
+// ============================================================================
+// Scalars
+// ============================================================================
+
+#[test]
+fn properties_accept_matching_values() {
+    let issues = issues(&[
+        ("count", PropertyValue::Integer(-4)),
+        ("small", PropertyValue::Number(255.0)),
+        ("scale", PropertyValue::Number(0.5)),
+        ("label", PropertyValue::String("hi".into())),
+        ("enabled", PropertyValue::Boolean(true)),
+        ("offset", PropertyValue::Vector3(1.0, 2.0, 3.0)),
+        ("tint", PropertyValue::Color(1.0, 0.5, 0.0, 1.0)),
+        ("target", PropertyValue::String("anything".into())),
+    ]);
+    assert_eq!(issues, vec![]);
+}
+
+#[test]
+fn properties_reject_wrong_types() {
+    let issues = issues(&[
+        ("count", PropertyValue::String("hi".into())),
+        ("enabled", PropertyValue::Number(1.0)),
+    ]);
+    assert_eq!(
+        issues,
+        vec![
+            PropertyIssue::TypeMismatch {
+                node: "sample_1".into(),
+                property: "count".into(),
+                expected: "i64".into(),
+                actual: "String".into(),
+            },
+            PropertyIssue::TypeMismatch {
+                node: "sample_1".into(),
+                property: "enabled".into(),
+                expected: "bool".into(),
+                actual: "f64".into(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn properties_check_integer_values() {
+    let issues = issues(&[
+        ("count", PropertyValue::Number(2.5)),
+        ("small", PropertyValue::Integer(300)),
+    ]);
+    let messages: Vec<String> = issues.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "Property sample_1.count is f64, but the param is i64",
+            "Property sample_1.small is 300, which doesn't fit in u8",
+        ]
+    );
+}
+
+#[test]
+fn properties_accept_coercible_values() {
+    // i64 -> f32 and anything -> String are Rust coercions
+    let coercible = [
+        ("scale", PropertyValue::Integer(3)),
+        ("label", PropertyValue::Number(1.5)),
+    ];
+    assert_eq!(issues(&coercible), vec![]);
+
+    let registry = registry();
+    let graph = graph_with(&registry, &coercible);
+    assert_eq!(check_properties(&graph, &registry, &TypeCoercion::new()).len(), 2);
+}
+
+// ============================================================================
+// Vectors, colors, and collections
+// ============================================================================
+
+#[test]
+fn properties_check_component_counts() {
+    let issues = issues(&[
+        ("offset", PropertyValue::Vector2(1.0, 2.0)),
+        ("tint", PropertyValue::Vector3(1.0, 0.0, 0.0)),
+        ("scale", PropertyValue::Vector2(1.0, 1.0)),
+    ]);
+    let messages: Vec<String> = issues.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "Property sample_1.scale is (f64, f64), but the param is f32",
+            "Property sample_1.offset has 2 components, but (f32, f32, f32) has 3",
+            "Property sample_1.tint has 3 components, but (f32, f32, f32, f32) has 4",
+        ]
+    );
+}
+
+#[test]
+fn properties_check_collection_items() {
+    let numbers = |values: &[i64]| PropertyValue::Array(values.iter().map(|n| PropertyValue::Integer(*n)).collect());
+    assert_eq!(issues(&[("weights", numbers(&[1, 2])), ("corners", numbers(&[0, 0, 4, 4]))]), vec![]);
+
+    let issues = issues(&[
+        ("weights", PropertyValue::Array(vec![PropertyValue::Boolean(true)])),
+        ("corners", numbers(&[0, 0])),
+        (
+            "names",
+            PropertyValue::Map([("first".to_string(), PropertyValue::Boolean(false))].into()),
+        ),
+    ]);
+    assert!(matches!(
+        &issues[0],
+        PropertyIssue::TypeMismatch { property, expected, .. } if property == "weights" && expected == "f64"
+    ));
+    assert!(matches!(
+        &issues[1],
+        PropertyIssue::ArityMismatch { property, expected: 4, actual: 2, .. } if property == "corners"
+    ));
+    // Anything converts to String, so the map is fine
+    assert_eq!(issues.len(), 2);
+}
+
+// ============================================================================
+// Which properties are checked
+// ============================================================================
+
+#[test]
+fn properties_of_connected_inputs_are_ignored() {
+    let registry = registry();
+    let mut graph = graph_with(&registry, &[("count", PropertyValue::String("stale".into()))]);
+    graph.add_node_of_type("join", "join_1", &registry).unwrap();
+    graph.add_connection(Connection::data("join_1", "result", "sample_1", "count"));
+
+    assert_eq!(check_properties(&graph, &registry, &TypeCoercion::rust()), vec![]);
+}
+
+#[test]
+fn properties_of_variadic_inputs_are_checked() {
+    let registry = registry();
+    let mut graph = GraphDescription::new("variadic");
+    graph.add_node_of_type("join", "join_1", &registry).unwrap();
+    let join = graph.get_node_mut("join_1").unwrap();
+    join.set_property("item_0", PropertyValue::Integer(1));
+    join.set_property("item_1", PropertyValue::Boolean(true));
+    // Not a param of the node
+    join.set_property("item_5", PropertyValue::Boolean(true));
+
+    let issues = check_properties(&graph, &registry, &TypeCoercion::rust());
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].property(), "item_1");
+}
+
+// ============================================================================
+// Pass and diagnostics
+// ============================================================================
+
+#[test]
+fn properties_pass_stores_or_denies_issues() {
+    let registry = registry();
+    let coercion = TypeCoercion::rust();
+    let graph = graph_with(&registry, &[("count", PropertyValue::Boolean(true))]);
+
+    let mut manager = PassManager::new();
+    manager.add_pass(PropertyCheckPass::new(&registry, &coercion));
+    let analysis = manager.run(&graph).unwrap();
+    assert_eq!(analysis.get::<Vec<PropertyIssue>>().unwrap().len(), 1);
+
+    let mut manager = PassManager::new();
+    manager.add_pass(PropertyCheckPass::new(&registry, &coercion).deny_issues());
+    let Err(error) = manager.run(&graph) else {
+        panic!("expected an invalid property");
+    };
+    assert!(matches!(error, GraphyError::InvalidProperty { .. }));
+    assert_eq!(error.node(), Some("sample_1"));
+    assert_eq!(error.pin(), Some("count"));
+}
+
+#[test]
+fn properties_render_as_node_diagnostics() {
+    let registry = registry();
+    let graph = graph_with(&registry, &[("count", PropertyValue::Boolean(true))]);
+    let issue = check_properties(&graph, &registry, &TypeCoercion::rust()).remove(0);
+
+    assert_eq!(
+        Diagnostics::new(&graph).render(&issue.into()),
+        "error: Property sample_1.count is bool, but the param is i64\n  \
+         --> sample_1.count (sample) at (0, 0)\n"
+    );
+}
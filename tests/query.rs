@@ -0,0 +1,231 @@
+//! Tests for graph queries and structural pattern matching.
+
+mod common;
+
+use common::*;
+use graphy::core::{Pattern, PatternConnection, PatternNode};
+use graphy::*;
+
+/// Two constants feeding a multiply, whose result feeds an add with a
+/// third constant
+fn build_constant_graph() -> GraphDescription {
+    let mut graph = GraphDescription::new("constants");
+    for (id, node_type) in [
+        ("two", "math.constant"),
+        ("three", "math.constant"),
+        ("four", "math.constant"),
+        ("mul", "math.multiply"),
+        ("add", "math.add"),
+    ] {
+        graph.add_node(NodeInstance::new(id, node_type, Position::zero()));
+    }
+    graph
+        .nodes
+        .get_mut("two")
+        .unwrap()
+        .set_property("value", PropertyValue::Number(2.0));
+    graph
+        .nodes
+        .get_mut("three")
+        .unwrap()
+        .set_property("value", PropertyValue::Number(3.0));
+    graph
+        .nodes
+        .get_mut("four")
+        .unwrap()
+        .set_property("value", PropertyValue::Number(4.0));
+
+    graph.add_connection(Connection::data("two", "result", "mul", "a"));
+    graph.add_connection(Connection::data("three", "result", "mul", "b"));
+    graph.add_connection(Connection::data("mul", "result", "add", "a"));
+    graph.add_connection(Connection::data("four", "result", "add", "b"));
+    graph
+}
+
+/// A multiply fed by two constants, one per input
+fn constant_multiply() -> Pattern {
+    Pattern::new()
+        .with_node("mul", PatternNode::of_type("math.multiply"))
+        .with_node("lhs", PatternNode::of_type("math.constant"))
+        .with_node("rhs", PatternNode::of_type("math.constant"))
+        .with_connection(PatternConnection::new("lhs", "mul").to_pin("a"))
+        .with_connection(PatternConnection::new("rhs", "mul").to_pin("b"))
+}
+
+// ===========================================================================
+// Queries
+// ===========================================================================
+
+#[test]
+fn find_nodes_is_sorted_by_id() {
+    let graph = build_diamond_graph();
+    let ids: Vec<&str> = graph
+        .find_nodes(|node| node.node_type == "add" || node.node_type == "multiply")
+        .iter()
+        .map(|node| node.id.as_str())
+        .collect();
+    assert_eq!(ids, ["node_a", "node_b", "node_c", "node_d"]);
+
+    let multiplies: Vec<&str> = graph
+        .nodes_of_type("multiply")
+        .iter()
+        .map(|node| node.id.as_str())
+        .collect();
+    assert_eq!(multiplies, ["node_b", "node_c"]);
+    assert!(graph.find_nodes(|node| node.inputs.len() > 10).is_empty());
+}
+
+#[test]
+fn connection_queries_follow_direction_and_pins() {
+    let graph = build_diamond_graph();
+    let targets: Vec<&str> = graph
+        .connections_from("node_a")
+        .map(|c| c.target_node.as_str())
+        .collect();
+    assert_eq!(targets, ["node_b", "node_c"]);
+    assert_eq!(graph.connections_from_pin("node_a", "result").count(), 2);
+    assert_eq!(graph.connections_from_pin("node_a", "a").count(), 0);
+
+    let sources: Vec<&str> = graph
+        .connections_to_node("node_d")
+        .map(|c| c.source_node.as_str())
+        .collect();
+    assert_eq!(sources, ["node_b", "node_c"]);
+    let into_b: Vec<&str> = graph
+        .connections_to("node_d", "b")
+        .map(|c| c.source_node.as_str())
+        .collect();
+    assert_eq!(into_b, ["node_c"]);
+
+    assert_eq!(graph.connections_from("missing").count(), 0);
+    assert_eq!(graph.connections_to("node_a", "a").count(), 0);
+}
+
+// ===========================================================================
+// Patterns
+// ===========================================================================
+
+#[test]
+fn patterns_bind_every_node() {
+    let graph = build_constant_graph();
+    let matches = constant_multiply().find_matches(&graph);
+    assert_eq!(matches.len(), 1);
+
+    let found = &matches[0];
+    assert_eq!(found.node("mul"), Some("mul"));
+    assert_eq!(found.node("lhs"), Some("two"));
+    assert_eq!(found.node("rhs"), Some("three"));
+    assert_eq!(found.node("missing"), None);
+    assert_eq!(
+        found.iter().collect::<Vec<_>>(),
+        [("mul", "mul"), ("lhs", "two"), ("rhs", "three")]
+    );
+    assert!(found.contains_node("two"));
+    assert!(!found.contains_node("four"));
+}
+
+#[test]
+fn symmetric_patterns_match_once_per_assignment() {
+    let graph = build_constant_graph();
+    let pattern = Pattern::new()
+        .with_node("mul", PatternNode::of_type("math.multiply"))
+        .with_node("x", PatternNode::of_type("math.constant"))
+        .with_node("y", PatternNode::of_type("math.constant"))
+        .with_connection(PatternConnection::new("x", "mul"))
+        .with_connection(PatternConnection::new("y", "mul"));
+
+    let matches = pattern.find_matches(&graph);
+    let bindings: Vec<(&str, &str)> = matches
+        .iter()
+        .map(|found| (found.node("x").unwrap(), found.node("y").unwrap()))
+        .collect();
+    assert_eq!(bindings, [("three", "two"), ("two", "three")]);
+}
+
+#[test]
+fn pattern_nodes_bind_distinct_graph_nodes() {
+    let mut graph = GraphDescription::new("graph");
+    graph.add_node(NodeInstance::new("c", "math.constant", Position::zero()));
+    graph.add_node(NodeInstance::new("mul", "math.multiply", Position::zero()));
+    graph.add_connection(Connection::data("c", "result", "mul", "a"));
+    graph.add_connection(Connection::data("c", "result", "mul", "b"));
+
+    // One constant feeding both inputs isn't two constants
+    assert!(!constant_multiply().is_match(&graph));
+}
+
+#[test]
+fn patterns_check_properties_and_predicates() {
+    let graph = build_constant_graph();
+    let pattern = Pattern::new()
+        .with_node(
+            "c",
+            PatternNode::of_type("math.constant")
+                .with_property("value", PropertyValue::Number(4.0)),
+        )
+        .with_node("add", PatternNode::any())
+        .with_connection(PatternConnection::new("c", "add").of_type(ConnectionType::Data));
+    let matches = pattern.find_matches(&graph);
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].node("add"), Some("add"));
+
+    let fed_by_multiply = Pattern::new()
+        .with_node(
+            "user",
+            PatternNode::any().with_predicate(|node| node.id.starts_with('a')),
+        )
+        .with_node("mul", PatternNode::of_type("math.multiply"))
+        .with_connection(
+            PatternConnection::new("mul", "user")
+                .from_pin("result")
+                .to_pin("a"),
+        );
+    assert_eq!(
+        fed_by_multiply.find_first(&graph).unwrap().node("user"),
+        Some("add")
+    );
+
+    let wrong_type = PatternConnection::new("mul", "user").of_type(ConnectionType::Execution);
+    assert!(!Pattern::new()
+        .with_node("user", PatternNode::any())
+        .with_node("mul", PatternNode::any())
+        .with_connection(wrong_type)
+        .is_match(&graph));
+}
+
+#[test]
+fn chained_patterns_follow_connections() {
+    let graph = build_constant_graph();
+    // A constant feeding a multiply that feeds an add
+    let pattern = Pattern::new()
+        .with_node("add", PatternNode::of_type("math.add"))
+        .with_node("mul", PatternNode::of_type("math.multiply"))
+        .with_node("c", PatternNode::of_type("math.constant"))
+        .with_connection(PatternConnection::new("mul", "add"))
+        .with_connection(PatternConnection::new("c", "mul"));
+
+    let matches = pattern.find_matches(&graph);
+    let constants: Vec<&str> = matches
+        .iter()
+        .map(|found| found.node("c").unwrap())
+        .collect();
+    assert_eq!(constants, ["three", "two"]);
+}
+
+#[test]
+fn degenerate_patterns_match_nothing_or_everything() {
+    let graph = build_diamond_graph();
+    assert!(Pattern::new().find_matches(&graph).is_empty());
+
+    let unknown = Pattern::new()
+        .with_node("a", PatternNode::any())
+        .with_connection(PatternConnection::new("a", "missing"));
+    assert!(!unknown.is_match(&graph));
+
+    // Unconnected pattern nodes range over every node
+    let single = Pattern::new().with_node("n", PatternNode::any());
+    assert_eq!(single.find_matches(&graph).len(), graph.nodes.len());
+    // ... but never bind the same node twice
+    let pairs = single.with_node("m", PatternNode::of_type("multiply"));
+    assert_eq!(pairs.find_matches(&graph).len(), 6);
+}
@@ -0,0 +1,137 @@
+//! Tests for Reachability analysis and dead node pruning.
+
+mod common;
+
+use common::*;
+use graphy::analysis::{Reachability, ReachabilityPass};
+use graphy::*;
+
+/// Branch graph plus an orphaned print node and an unused pure node.
+fn build_graph_with_dead_nodes() -> GraphDescription {
+    let mut graph = build_branch_graph();
+
+    let mut orphan = NodeInstance::new("orphan_print", "print_string", Position::zero());
+    orphan.add_input_pin("exec_in", DataType::Execution);
+    orphan.add_input_pin("message", DataType::Typed("String".into()));
+    graph.add_node(orphan);
+
+    let mut unused = NodeInstance::new("unused_add", "add", Position::zero());
+    unused.add_output_pin("result", DataType::Typed("i64".into()));
+    graph.add_node(unused);
+
+    graph.add_connection(Connection::data("unused_add", "result", "orphan_print", "message"));
+
+    graph
+}
+
+// ===========================================================================
+// Reachability - Compute
+// ===========================================================================
+
+#[test]
+fn reachability_follows_exec_flow_from_events() {
+    let graph = build_branch_graph();
+    let provider = TestMetadataProvider::comprehensive();
+
+    let reachability = Reachability::compute(&graph, &provider);
+    assert_eq!(reachability.reachable_count(), 4);
+    assert!(reachability.is_reachable("start"));
+    assert!(reachability.is_reachable("print_false"));
+    assert!(reachability.unreachable_nodes(&graph).is_empty());
+}
+
+#[test]
+fn reachability_follows_data_dependencies() {
+    let mut graph = build_branch_graph();
+    let mut cond = NodeInstance::new("cond", "add", Position::zero());
+    cond.add_output_pin("result", DataType::Typed("i64".into()));
+    graph.add_node(cond);
+    graph.add_connection(Connection::data("cond", "result", "branch_1", "condition"));
+
+    let provider = TestMetadataProvider::comprehensive();
+    let reachability = Reachability::compute(&graph, &provider);
+    assert!(reachability.is_reachable("cond"));
+}
+
+#[test]
+fn reachability_reports_dead_nodes() {
+    let graph = build_graph_with_dead_nodes();
+    let provider = TestMetadataProvider::comprehensive();
+
+    let reachability = Reachability::compute(&graph, &provider);
+    assert_eq!(reachability.unreachable_nodes(&graph), vec!["orphan_print", "unused_add"]);
+}
+
+#[test]
+fn reachability_without_events_is_empty() {
+    let graph = build_diamond_graph();
+    let provider = TestMetadataProvider::comprehensive();
+
+    let reachability = Reachability::compute(&graph, &provider);
+    assert_eq!(reachability.reachable_count(), 0);
+}
+
+#[test]
+fn reachability_from_explicit_roots() {
+    let graph = build_diamond_graph();
+
+    let reachability = Reachability::compute_from(&graph, ["node_d", "missing"]);
+    assert_eq!(reachability.reachable_count(), 4);
+    assert!(!reachability.is_reachable("missing"));
+}
+
+#[test]
+fn reachability_compact_matches_compute_and_skips_dangling_nodes() {
+    let mut graph = build_graph_with_dead_nodes();
+    graph.add_connection(Connection::execution("print_true", "exec_out", "ghost", "exec_in"));
+    let provider = TestMetadataProvider::comprehensive();
+
+    let compact = graphy::analysis::CompactGraph::from_graph(&graph);
+    let from_compact = Reachability::compute_compact(&compact, &provider);
+    let reachability = Reachability::compute(&graph, &provider);
+
+    assert_eq!(from_compact.unreachable_nodes(&graph), reachability.unreachable_nodes(&graph));
+    assert_eq!(from_compact.reachable_count(), 4);
+    assert!(!from_compact.is_reachable("ghost"));
+}
+
+// ===========================================================================
+// GraphDescription::prune_unreachable
+// ===========================================================================
+
+#[test]
+fn prune_unreachable_removes_dead_nodes_and_connections() {
+    let mut graph = build_graph_with_dead_nodes();
+    let provider = TestMetadataProvider::comprehensive();
+
+    let removed = graph.prune_unreachable(&provider);
+    assert_eq!(removed, vec!["orphan_print", "unused_add"]);
+    assert_eq!(graph.nodes.len(), 4);
+    assert_eq!(graph.connections.len(), 3);
+    assert!(graph
+        .connections
+        .iter()
+        .all(|c| graph.get_node(&c.source_node).is_some() && graph.get_node(&c.target_node).is_some()));
+}
+
+#[test]
+fn prune_unreachable_keeps_live_graph_intact() {
+    let mut graph = build_branch_graph();
+    let provider = TestMetadataProvider::comprehensive();
+
+    assert!(graph.prune_unreachable(&provider).is_empty());
+    assert_eq!(graph.nodes.len(), 4);
+}
+
+#[test]
+fn reachability_pass_stores_result() {
+    let graph = build_graph_with_dead_nodes();
+    let provider = TestMetadataProvider::comprehensive();
+
+    let mut manager = PassManager::new();
+    manager.add_pass(ReachabilityPass::new(&provider));
+    let analysis = manager.run(&graph).unwrap();
+
+    let reachability = analysis.get::<Reachability>().unwrap();
+    assert!(!reachability.is_reachable("orphan_print"));
+}
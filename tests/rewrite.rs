@@ -0,0 +1,195 @@
+//! Tests for rule-based graph rewriting.
+
+use graphy::core::{Pattern, PatternConnection, PatternNode};
+use graphy::utils::{apply_rules, apply_rules_with_limit, Replacement, RewriteRule};
+use graphy::*;
+
+fn constant(id: &str, value: f64) -> NodeInstance {
+    let mut node = NodeInstance::new(id, "math.constant", Position::zero());
+    node.set_property("value", PropertyValue::Number(value));
+    node
+}
+
+/// `(x * 1) * 1`, printed
+fn build_multiply_chain() -> GraphDescription {
+    let mut graph = GraphDescription::new("chain");
+    graph.add_node(NodeInstance::new("x", "input", Position::zero()));
+    graph.add_node(constant("one_a", 1.0));
+    graph.add_node(constant("one_b", 1.0));
+    graph.add_node(NodeInstance::new("mul_a", "math.multiply", Position::zero()));
+    graph.add_node(NodeInstance::new("mul_b", "math.multiply", Position::zero()));
+    graph.add_node(NodeInstance::new("print", "print", Position::zero()));
+
+    graph.add_connection(Connection::data("x", "result", "mul_a", "a"));
+    graph.add_connection(Connection::data("one_a", "result", "mul_a", "b"));
+    graph.add_connection(Connection::data("mul_a", "result", "mul_b", "a"));
+    graph.add_connection(Connection::data("one_b", "result", "mul_b", "b"));
+    graph.add_connection(Connection::data("mul_b", "result", "print", "value"));
+    graph
+}
+
+/// `x * 1 => x`
+fn multiply_by_one() -> RewriteRule {
+    RewriteRule::new(
+        "multiply_by_one",
+        Pattern::new()
+            .with_node("mul", PatternNode::of_type("math.multiply"))
+            .with_node(
+                "one",
+                PatternNode::of_type("math.constant").with_property("value", PropertyValue::Number(1.0)),
+            )
+            .with_connection(PatternConnection::new("one", "mul").to_pin("b")),
+        Replacement::new().bypass("mul", "a", "result").remove("one"),
+    )
+}
+
+fn retype(name: &str, from: &str, to: &str) -> RewriteRule {
+    RewriteRule::new(
+        name,
+        Pattern::new().with_node("node", PatternNode::of_type(from)),
+        Replacement::new().retype("node", to, &[("a", "a"), ("b", "b"), ("result", "result")]),
+    )
+}
+
+// ===========================================================================
+// Fixed point
+// ===========================================================================
+
+#[test]
+fn rules_apply_until_nothing_matches() {
+    let mut graph = build_multiply_chain();
+    let applied = apply_rules(&mut graph, &[multiply_by_one()]).unwrap();
+
+    assert_eq!(applied.len(), 2);
+    assert!(applied.iter().all(|rewrite| rewrite.rule == "multiply_by_one"));
+    assert_eq!(applied[0].matched.node("mul"), Some("mul_a"));
+    assert_eq!(applied[1].matched.node("mul"), Some("mul_b"));
+
+    let mut ids: Vec<&str> = graph.nodes.keys().map(String::as_str).collect();
+    ids.sort_unstable();
+    assert_eq!(ids, ["print", "x"]);
+    assert_eq!(graph.connections, [Connection::data("x", "result", "print", "value")]);
+}
+
+#[test]
+fn rules_without_matches_change_nothing() {
+    let mut graph = build_multiply_chain();
+    graph.nodes.get_mut("one_a").unwrap().set_property("value", PropertyValue::Number(2.0));
+    graph.nodes.get_mut("one_b").unwrap().set_property("value", PropertyValue::Number(2.0));
+    let before = graph.clone();
+
+    assert!(apply_rules(&mut graph, &[multiply_by_one()]).unwrap().is_empty());
+    assert!(graph.semantically_equals(&before));
+    assert!(apply_rules(&mut graph, &[]).unwrap().is_empty());
+}
+
+#[test]
+fn later_rules_see_earlier_rewrites() {
+    // Migrate the old multiply first; the peephole rule only knows the new one
+    let mut graph = build_multiply_chain();
+    for id in ["mul_a", "mul_b"] {
+        graph.nodes.get_mut(id).unwrap().node_type = "legacy.mul".into();
+    }
+    let rules = [multiply_by_one(), retype("migrate_mul", "legacy.mul", "math.multiply")];
+    let applied = apply_rules(&mut graph, &rules).unwrap();
+
+    let names: Vec<&str> = applied.iter().map(|rewrite| rewrite.rule.as_str()).collect();
+    assert_eq!(names, ["migrate_mul", "multiply_by_one", "migrate_mul", "multiply_by_one"]);
+    assert_eq!(graph.nodes.len(), 2);
+}
+
+#[test]
+fn no_op_rewrites_are_skipped() {
+    let mut graph = build_multiply_chain();
+    let move_node = RewriteRule::new(
+        "move",
+        Pattern::new().with_node("node", PatternNode::of_type("print")),
+        Replacement::new().custom(|graph, found| {
+            let node = graph.get_node_mut(found.node("node").unwrap()).unwrap();
+            node.position = Position::new(node.position.x + 10.0, 0.0);
+            Ok(())
+        }),
+    );
+    let mark = RewriteRule::new(
+        "mark",
+        Pattern::new().with_node("node", PatternNode::of_type("input")),
+        Replacement::new().set_property("node", "checked", PropertyValue::Boolean(true)),
+    );
+
+    // Moving doesn't change the content hash; setting the same property twice doesn't either
+    let applied = apply_rules(&mut graph, &[move_node, mark]).unwrap();
+    assert_eq!(applied.len(), 1);
+    assert_eq!(applied[0].rule, "mark");
+    assert_eq!(graph.nodes["x"].get_property("checked"), Some(&PropertyValue::Boolean(true)));
+}
+
+// ===========================================================================
+// Cycle protection
+// ===========================================================================
+
+#[test]
+fn rules_that_undo_each_other_are_an_error() {
+    let mut graph = build_multiply_chain();
+    let before = graph.clone();
+    let rules = [
+        retype("to_new", "math.multiply", "math.mul"),
+        retype("to_old", "math.mul", "math.multiply"),
+    ];
+
+    let error = apply_rules(&mut graph, &rules).unwrap_err();
+    assert!(error.to_string().contains("cycle"), "{}", error);
+    assert!(graph.semantically_equals(&before));
+}
+
+/// Adds a node to the graph for each `input` node matching `input`
+fn grow(input: PatternNode) -> RewriteRule {
+    RewriteRule::new(
+        "grow",
+        Pattern::new().with_node("x", input),
+        Replacement::new()
+            .custom(|graph, _| {
+                let id = format!("extra_{}", graph.nodes.len());
+                graph.add_node(NodeInstance::new(id, "extra", Position::zero()));
+                Ok(())
+            })
+            .set_property("x", "grown", PropertyValue::Boolean(true)),
+    )
+}
+
+#[test]
+fn rewrites_stop_at_the_limit() {
+    // Every rewrite grows the graph, so it never reaches a fixed point
+    let mut graph = build_multiply_chain();
+    let error = apply_rules_with_limit(&mut graph, &[grow(PatternNode::of_type("input"))], 5).unwrap_err();
+    assert!(error.to_string().contains("5 rewrites"), "{}", error);
+    assert_eq!(graph.nodes.len(), 6);
+
+    // Once grown, the node no longer matches
+    let once = PatternNode::of_type("input").with_predicate(|node| node.get_property("grown").is_none());
+    assert_eq!(apply_rules_with_limit(&mut graph, &[grow(once)], 5).unwrap().len(), 1);
+    assert_eq!(graph.nodes.len(), 7);
+}
+
+// ===========================================================================
+// Failures
+// ===========================================================================
+
+#[test]
+fn failing_replacements_leave_the_graph_unchanged() {
+    let mut graph = build_multiply_chain();
+    graph.connections.retain(|c| c.target_node != "mul_b" || c.target_pin != "a");
+    let before = graph.clone();
+
+    // mul_a is bypassed, then mul_b has nothing on its `a` input
+    let error = apply_rules(&mut graph, &[multiply_by_one()]).unwrap_err();
+    assert!(matches!(error, GraphyError::UnconnectedInput { ref node, ref pin } if node == "mul_b" && pin == "a"));
+    assert!(graph.semantically_equals(&before));
+
+    let typo = RewriteRule::new(
+        "typo",
+        Pattern::new().with_node("node", PatternNode::of_type("print")),
+        Replacement::new().remove("nod"),
+    );
+    let error = apply_rules(&mut graph, &[typo]).unwrap_err();
+    assert!(error.to_string().contains("'nod'"), "{}", error);
+}
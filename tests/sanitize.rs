@@ -0,0 +1,243 @@
+//! Tests for sanitizing graphs imported from other tools.
+
+use graphy::core::ConnectionErrorReason;
+use graphy::utils::{sanitize, SanitizeFix, SanitizeOptions, SanitizeReport};
+use graphy::*;
+
+/// `on_start`, function `log` (an `f64` input), and pure `negate`
+fn registry() -> NodeRegistry {
+    let mut registry = NodeRegistry::new();
+    registry.register(NodeMetadata::new("on_start", NodeTypes::event, "Events").with_exec_outputs(vec!["exec".into()]));
+    registry.register(
+        NodeMetadata::new("log", NodeTypes::fn_, "Debug")
+            .with_params(vec![ParamInfo::new("value", "f64")])
+            .with_exec_outputs(vec!["exec_out".into()]),
+    );
+    registry.register(
+        NodeMetadata::new("negate", NodeTypes::pure, "Math")
+            .with_params(vec![ParamInfo::new("x", "f64")])
+            .with_return_type("f64"),
+    );
+    registry
+}
+
+/// `start -> log_1`, with `negate_1` feeding the logged value
+fn clean_graph(registry: &NodeRegistry) -> GraphDescription {
+    let mut graph = GraphDescription::new("imported");
+    graph.add_node_of_type("on_start", "start", registry).unwrap();
+    graph.add_node_of_type("log", "log_1", registry).unwrap();
+    graph.add_node_of_type("negate", "negate_1", registry).unwrap();
+    graph.add_connection(Connection::execution("start", "exec", "log_1", "exec_in"));
+    graph.add_connection(Connection::data("negate_1", "result", "log_1", "value"));
+    graph
+}
+
+fn sanitize_all(graph: &mut GraphDescription, registry: &NodeRegistry) -> SanitizeReport {
+    sanitize(graph, registry, SanitizeOptions::default())
+}
+
+// ============================================================================
+// Connections
+// ============================================================================
+
+#[test]
+fn sanitize_leaves_clean_graphs_alone() {
+    let registry = registry();
+    let mut graph = clean_graph(&registry);
+    let original = graph.clone();
+
+    let report = sanitize_all(&mut graph, &registry);
+    assert!(report.is_clean());
+    assert_eq!(graph, original);
+}
+
+#[test]
+fn sanitize_keeps_connections_of_nodes_without_pins() {
+    let registry = registry();
+    // Nodes made with `NodeInstance::new` only have the pins of their metadata
+    let mut graph = GraphDescription::new("imported");
+    graph.add_node(NodeInstance::new("start", "on_start", Position::zero()));
+    graph.add_node(NodeInstance::new("log_1", "log", Position::zero()));
+    graph.add_node(NodeInstance::new("negate_1", "negate", Position::zero()));
+    graph.add_connection(Connection::execution("start", "exec", "log_1", "exec_in"));
+    graph.add_connection(Connection::data("negate_1", "result", "log_1", "value"));
+    let original = graph.clone();
+
+    let report = sanitize_all(&mut graph, &registry);
+    assert!(report.is_clean(), "{:?}", report.fixes);
+    assert_eq!(graph, original);
+}
+
+#[test]
+fn sanitize_removes_duplicate_connections() {
+    let registry = registry();
+    let mut graph = clean_graph(&registry);
+    // Editor metadata doesn't make a connection distinct
+    let duplicate = Connection::data("negate_1", "result", "log_1", "value").with_id("imported_7");
+    graph.add_connection(duplicate.clone());
+
+    let report = sanitize_all(&mut graph, &registry);
+    assert_eq!(report.fixes, vec![SanitizeFix::DuplicateConnection { connection: duplicate }]);
+    assert_eq!(graph.connections, clean_graph(&registry).connections);
+}
+
+#[test]
+fn sanitize_removes_self_loops() {
+    let registry = registry();
+    let mut graph = clean_graph(&registry);
+    let looped = Connection::data("negate_1", "result", "negate_1", "x");
+    graph.add_connection(looped.clone());
+
+    let report = sanitize_all(&mut graph, &registry);
+    assert_eq!(report.fixes, vec![SanitizeFix::SelfLoop { connection: looped }]);
+    assert_eq!(report.removed_connections(), 1);
+}
+
+#[test]
+fn sanitize_removes_dangling_connections() {
+    let registry = registry();
+    let mut graph = clean_graph(&registry);
+    graph.add_connection(Connection::data("ghost", "result", "log_1", "value"));
+    graph.add_connection(Connection::data("negate_1", "result", "log_1", "missing"));
+    graph.add_connection(Connection::data("log_1", "value", "negate_1", "x"));
+    graph.add_connection(Connection::data("start", "exec", "negate_1", "x"));
+
+    let report = sanitize_all(&mut graph, &registry);
+    let reasons: Vec<&ConnectionErrorReason> = report
+        .fixes
+        .iter()
+        .map(|fix| match fix {
+            SanitizeFix::DanglingConnection { reason, .. } => reason,
+            other => panic!("unexpected fix {}", other),
+        })
+        .collect();
+    assert!(matches!(reasons[0], ConnectionErrorReason::MissingNode { node } if node == "ghost"));
+    assert!(matches!(reasons[1], ConnectionErrorReason::MissingPin { pin, .. } if pin == "missing"));
+    assert!(matches!(reasons[2], ConnectionErrorReason::WrongDirection { pin, .. } if pin == "value"));
+    assert!(matches!(reasons[3], ConnectionErrorReason::ConnectionTypeMismatch { .. }));
+    assert_eq!(graph.connections, clean_graph(&registry).connections);
+}
+
+#[test]
+fn sanitize_keeps_type_mismatches() {
+    // Incompatible data types are a compile error, not broken wiring
+    let mut registry = registry();
+    registry.register(NodeMetadata::new("name", NodeTypes::pure, "Text").with_return_type("String"));
+    let mut graph = clean_graph(&registry);
+    graph.add_node_of_type("name", "name_1", &registry).unwrap();
+    graph.add_connection(Connection::data("name_1", "result", "negate_1", "x"));
+
+    assert!(sanitize_all(&mut graph, &registry).is_clean());
+    assert_eq!(graph.connections.len(), 3);
+}
+
+// ============================================================================
+// Pin types
+// ============================================================================
+
+#[test]
+fn sanitize_normalizes_pin_types_to_metadata() {
+    let registry = registry();
+    let mut graph = clean_graph(&registry);
+    let negate = graph.get_node_mut("negate_1").unwrap();
+    negate.inputs[0].pin.data_type = DataType::Number;
+    negate.outputs[0].pin.data_type = DataType::Typed("f32".into());
+
+    let report = sanitize_all(&mut graph, &registry);
+    assert_eq!(
+        report.fixes,
+        vec![
+            SanitizeFix::PinTypeNormalized {
+                node_id: "negate_1".into(),
+                pin_id: "x".into(),
+                from: DataType::Number,
+                to: DataType::Typed("f64".into()),
+            },
+            SanitizeFix::PinTypeNormalized {
+                node_id: "negate_1".into(),
+                pin_id: "result".into(),
+                from: DataType::Typed("f32".into()),
+                to: DataType::Typed("f64".into()),
+            },
+        ]
+    );
+    assert_eq!(report.removed_connections(), 0);
+    assert_eq!(graph, clean_graph(&registry));
+}
+
+#[test]
+fn sanitize_checks_connections_against_normalized_types() {
+    let registry = registry();
+    let mut graph = clean_graph(&registry);
+    // Imported as an execution pin, so the data connection looks mismatched
+    graph.get_node_mut("log_1").unwrap().inputs[1].pin.data_type = DataType::Execution;
+
+    let report = sanitize_all(&mut graph, &registry);
+    assert_eq!(report.fixes.len(), 1);
+    assert_eq!(graph, clean_graph(&registry));
+}
+
+#[test]
+fn sanitize_skips_unknown_nodes() {
+    let registry = registry();
+    let mut graph = clean_graph(&registry);
+    let mut custom = NodeInstance::new("custom_1", "custom", Position::zero());
+    custom.add_output_pin("out", DataType::Number);
+    graph.add_node(custom);
+    graph.add_connection(Connection::data("custom_1", "out", "negate_1", "x"));
+
+    assert!(sanitize_all(&mut graph, &registry).is_clean());
+    assert_eq!(graph.connections.len(), 3);
+}
+
+// ============================================================================
+// Options and reports
+// ============================================================================
+
+#[test]
+fn sanitize_options_disable_repairs() {
+    let registry = registry();
+    let mut graph = clean_graph(&registry);
+    graph.add_connection(Connection::data("negate_1", "result", "log_1", "value"));
+    graph.add_connection(Connection::data("negate_1", "result", "negate_1", "x"));
+    graph.add_connection(Connection::data("ghost", "result", "log_1", "value"));
+    graph.get_node_mut("negate_1").unwrap().inputs[0].pin.data_type = DataType::Number;
+    let original = graph.clone();
+
+    let options = SanitizeOptions {
+        remove_duplicates: false,
+        remove_self_loops: false,
+        remove_dangling: false,
+        normalize_pin_types: false,
+    };
+    assert!(sanitize(&mut graph, &registry, options).is_clean());
+    assert_eq!(graph, original);
+
+    let options = SanitizeOptions {
+        remove_dangling: false,
+        ..SanitizeOptions::default()
+    };
+    assert_eq!(sanitize(&mut graph, &registry, options).fixes.len(), 3);
+    assert_eq!(graph.connections.len(), 3);
+}
+
+#[test]
+fn sanitize_report_describes_fixes() {
+    let registry = registry();
+    let mut graph = clean_graph(&registry);
+    graph.add_connection(Connection::data("negate_1", "result", "negate_1", "x"));
+    graph.get_node_mut("negate_1").unwrap().inputs[0].pin.data_type = DataType::Typed("i32".into());
+
+    let report = sanitize_all(&mut graph, &registry);
+    let lines: Vec<String> = report.fixes.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        lines,
+        vec![
+            "Changed type of negate_1.x from i32 to f64",
+            "Removed self-loop negate_1.result -> negate_1.x",
+        ]
+    );
+
+    let json = serde_json::to_string(&report).unwrap();
+    assert_eq!(serde_json::from_str::<SanitizeReport>(&json).unwrap(), report);
+}
@@ -0,0 +1,163 @@
+//! Tests for semantic graph equality and content hashing.
+
+mod common;
+
+use common::*;
+use graphy::core::{GraphComment, GraphGroup, VariableDecl};
+use graphy::*;
+
+// ===========================================================================
+// semantically_equals - Ignored differences
+// ===========================================================================
+
+#[test]
+fn semantic_equality_ignores_positions() {
+    let graph = build_branch_graph();
+    let mut moved = graph.clone();
+    for node in moved.nodes.values_mut() {
+        node.position = Position::new(node.position.x + 50.0, -20.0);
+    }
+
+    assert!(graph.semantically_equals(&moved));
+    assert_eq!(graph, moved);
+    assert_eq!(graph.content_hash(), moved.content_hash());
+}
+
+#[test]
+fn semantic_equality_ignores_connection_order_and_duplicates() {
+    let graph = build_diamond_graph();
+    let mut reordered = graph.clone();
+    reordered.connections.reverse();
+    let duplicate = reordered.connections[0].clone();
+    reordered.add_connection(duplicate);
+
+    assert_eq!(graph, reordered);
+    assert_eq!(graph.content_hash(), reordered.content_hash());
+}
+
+#[test]
+fn semantic_equality_ignores_editor_state() {
+    let graph = build_diamond_graph();
+    let mut edited = graph.clone();
+    edited.metadata.name = "renamed".into();
+    edited.metadata.description = "layout pass".into();
+    edited.comments.push(GraphComment {
+        text: "note".into(),
+        position: Position::zero(),
+        size: (100.0, 40.0),
+        attached_node_ids: Vec::new(),
+    });
+    edited.add_group(GraphGroup::new("group_1", "Math")).unwrap();
+    edited.add_node_to_group("group_1", "node_a").unwrap();
+
+    assert_eq!(graph, edited);
+    assert_eq!(graph.content_hash(), edited.content_hash());
+}
+
+#[test]
+fn semantic_equality_ignores_property_insertion_order() {
+    let mut first = GraphDescription::new("graph");
+    let mut node = NodeInstance::new("node", "add", Position::zero());
+    node.set_property("a", PropertyValue::Integer(1));
+    node.set_property("b", PropertyValue::Integer(2));
+    first.add_node(node);
+
+    let mut second = GraphDescription::new("graph");
+    let mut node = NodeInstance::new("node", "add", Position::zero());
+    node.set_property("b", PropertyValue::Integer(2));
+    node.set_property("a", PropertyValue::Integer(1));
+    second.add_node(node);
+
+    assert_eq!(first, second);
+    assert_eq!(first.content_hash(), second.content_hash());
+}
+
+// ===========================================================================
+// semantically_equals - Program changes
+// ===========================================================================
+
+#[test]
+fn semantic_equality_detects_property_changes() {
+    let graph = build_diamond_graph();
+    let mut changed = graph.clone();
+    changed
+        .get_node_mut("node_b")
+        .unwrap()
+        .set_property("b", PropertyValue::Number(3.0));
+
+    assert_ne!(graph, changed);
+    assert_ne!(graph.content_hash(), changed.content_hash());
+}
+
+#[test]
+fn semantic_equality_detects_rewiring() {
+    let graph = build_diamond_graph();
+    let mut rewired = graph.clone();
+    rewired.connections[0].target_pin = "b".into();
+
+    assert_ne!(graph, rewired);
+    assert_ne!(graph.content_hash(), rewired.content_hash());
+}
+
+#[test]
+fn semantic_equality_detects_node_type_and_variable_changes() {
+    let graph = build_diamond_graph();
+
+    let mut retyped = graph.clone();
+    retyped.get_node_mut("node_d").unwrap().node_type = "multiply".into();
+    assert_ne!(graph, retyped);
+    assert_ne!(graph.content_hash(), retyped.content_hash());
+
+    let mut with_variable = graph.clone();
+    with_variable.add_variable("health", VariableDecl::new("i64"));
+    assert_ne!(graph, with_variable);
+    assert_ne!(graph.content_hash(), with_variable.content_hash());
+}
+
+#[test]
+fn node_semantic_equality_ignores_position_only() {
+    let node = NodeInstance::new("node", "add", Position::zero());
+    let mut moved = node.clone();
+    moved.position = Position::new(10.0, 10.0);
+
+    assert!(node.semantically_equals(&moved));
+    assert_ne!(node, moved);
+}
+
+// ===========================================================================
+// content_hash
+// ===========================================================================
+
+#[test]
+fn content_hash_is_stable_across_clones_and_serialization() {
+    let graph = build_branch_graph();
+    let json = serde_json::to_string(&graph).unwrap();
+    let restored: GraphDescription = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(graph.content_hash(), graph.clone().content_hash());
+    assert_eq!(graph.content_hash(), restored.content_hash());
+}
+
+#[test]
+fn content_hash_of_empty_graph_is_fixed() {
+    // Persisted hashes must not change between runs or platforms
+    assert_eq!(GraphDescription::new("a").content_hash(), GraphDescription::new("b").content_hash());
+    assert_eq!(GraphDescription::new("a").content_hash(), 0x40d6_9e0c_f0f6_5c45);
+}
+
+#[test]
+fn content_hash_treats_negative_zero_as_zero() {
+    let mut positive = GraphDescription::new("graph");
+    let mut node = NodeInstance::new("node", "add", Position::zero());
+    node.set_property("a", PropertyValue::Number(0.0));
+    positive.add_node(node);
+
+    let mut negative = positive.clone();
+    negative
+        .get_node_mut("node")
+        .unwrap()
+        .set_property("a", PropertyValue::Number(-0.0));
+
+    assert_eq!(positive, negative);
+    assert_eq!(positive.content_hash(), negative.content_hash());
+}
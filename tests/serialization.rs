@@ -95,6 +95,7 @@ fn serde_property_string() {
 }
 
 #[test]
+#[allow(clippy::approx_constant)]
 fn serde_property_number() {
     let pv = PropertyValue::Number(3.1415926);
     let json = serde_json::to_string(&pv).unwrap();
@@ -161,6 +162,62 @@ fn serde_property_color() {
     }
 }
 
+#[test]
+fn serde_property_integer() {
+    let pv = PropertyValue::Integer(-9_007_199_254_740_993);
+    let json = serde_json::to_string(&pv).unwrap();
+    let deserialized: PropertyValue = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, pv);
+}
+
+#[test]
+fn serde_property_array_map_enum() {
+    let mut map = std::collections::HashMap::new();
+    map.insert("speed".to_string(), PropertyValue::Number(1.5));
+    map.insert(
+        "dir".to_string(),
+        PropertyValue::Enum {
+            variant: "Direction::Left".into(),
+            payload: None,
+        },
+    );
+
+    let pv = PropertyValue::Array(vec![
+        PropertyValue::Map(map),
+        PropertyValue::Enum {
+            variant: "Some".into(),
+            payload: Some(Box::new(PropertyValue::Integer(3))),
+        },
+    ]);
+
+    let json = serde_json::to_string(&pv).unwrap();
+    let deserialized: PropertyValue = serde_json::from_str(&json).unwrap();
+    assert_eq!(deserialized, pv);
+}
+
+#[test]
+fn serde_property_enum_without_payload_field() {
+    let json = r#"{"Enum":{"variant":"Mode::Fast"}}"#;
+    let deserialized: PropertyValue = serde_json::from_str(json).unwrap();
+    assert_eq!(
+        deserialized,
+        PropertyValue::Enum {
+            variant: "Mode::Fast".into(),
+            payload: None,
+        }
+    );
+}
+
+#[test]
+fn serde_property_legacy_format_still_loads() {
+    // Format written before Integer/Array/Map/Enum were added
+    let json = r#"[{"Number":5.0},{"String":"hi"},{"Vector2":[1.0,2.0]}]"#;
+    let values: Vec<PropertyValue> = serde_json::from_str(json).unwrap();
+    assert_eq!(values[0], PropertyValue::Number(5.0));
+    assert_eq!(values[1], PropertyValue::String("hi".into()));
+    assert_eq!(values[2], PropertyValue::Vector2(1.0, 2.0));
+}
+
 // ===========================================================================
 // Position serialization
 // ===========================================================================
@@ -252,6 +309,7 @@ fn serde_full_graph_round_trip() {
         text: "Math section".into(),
         position: Position::zero(),
         size: (200.0, 100.0),
+        attached_node_ids: Vec::new(),
     });
 
     // Serialize
@@ -352,3 +410,155 @@ fn serde_large_graph() {
     assert_eq!(deserialized.nodes.len(), 100);
     assert_eq!(deserialized.connections.len(), 99);
 }
+
+// ===========================================================================
+// Streaming reader/writer
+// ===========================================================================
+
+fn build_stream_graph() -> GraphDescription {
+    let mut graph = GraphDescription::new("stream");
+    graph.add_graph_input("x", "i64");
+
+    for i in 0..10 {
+        let mut node = NodeInstance::new(format!("n{}", i), "add", Position::zero());
+        node.add_input_pin("a", DataType::Typed("i64".into()));
+        node.add_output_pin("result", DataType::Typed("i64".into()));
+        graph.add_node(node);
+    }
+    for i in 0..9 {
+        graph.add_connection(Connection::data(format!("n{}", i), "result", format!("n{}", i + 1), "a"));
+    }
+    graph.comments.push(graphy::core::GraphComment {
+        text: "hello".into(),
+        position: Position::zero(),
+        size: (1.0, 1.0),
+        attached_node_ids: Vec::new(),
+    });
+    let mut group = GraphGroup::new("g1", "Group");
+    group.node_ids.push("n0".into());
+    graph.add_group(group).unwrap();
+
+    graph
+}
+
+#[derive(Default)]
+struct CountingVisitor {
+    name: String,
+    nodes: usize,
+    connections: usize,
+    comments: usize,
+    inputs: usize,
+    groups: usize,
+}
+
+impl graphy::core::GraphVisitor for CountingVisitor {
+    fn visit_metadata(&mut self, metadata: GraphMetadata) -> Result<()> {
+        self.name = metadata.name;
+        Ok(())
+    }
+
+    fn visit_node(&mut self, _node: NodeInstance) -> Result<()> {
+        self.nodes += 1;
+        Ok(())
+    }
+
+    fn visit_connection(&mut self, _connection: Connection) -> Result<()> {
+        self.connections += 1;
+        Ok(())
+    }
+
+    fn visit_comment(&mut self, _comment: graphy::core::GraphComment) -> Result<()> {
+        self.comments += 1;
+        Ok(())
+    }
+
+    fn visit_graph_input(&mut self, _param: GraphParam) -> Result<()> {
+        self.inputs += 1;
+        Ok(())
+    }
+
+    fn visit_group(&mut self, _group: GraphGroup) -> Result<()> {
+        self.groups += 1;
+        Ok(())
+    }
+}
+
+#[test]
+fn serde_writer_reader_round_trip() {
+    let graph = build_stream_graph();
+
+    let mut bytes = Vec::new();
+    graph.to_writer(&mut bytes).unwrap();
+    let restored = GraphDescription::from_reader(bytes.as_slice()).unwrap();
+
+    assert_eq!(restored.nodes, graph.nodes);
+    assert_eq!(restored.connections, graph.connections);
+    assert_eq!(restored.graph_inputs, graph.graph_inputs);
+    assert_eq!(restored.groups, graph.groups);
+}
+
+#[test]
+fn serde_from_reader_invalid_json() {
+    let result = GraphDescription::from_reader(&b"{\"metadata\": "[..]);
+    assert!(matches!(result, Err(GraphyError::Serialization(_))));
+}
+
+#[test]
+fn serde_stream_visits_every_element() {
+    let graph = build_stream_graph();
+    let json = serde_json::to_vec(&graph).unwrap();
+
+    let mut visitor = CountingVisitor::default();
+    GraphDescription::stream_from_reader(json.as_slice(), &mut visitor).unwrap();
+
+    assert_eq!(visitor.name, "stream");
+    assert_eq!(visitor.nodes, 10);
+    assert_eq!(visitor.connections, 9);
+    assert_eq!(visitor.comments, 1);
+    assert_eq!(visitor.inputs, 1);
+    assert_eq!(visitor.groups, 1);
+}
+
+#[test]
+fn serde_stream_skips_unknown_fields() {
+    let json = r#"{"extra": {"nested": [1, 2]}, "nodes": {}, "connections": [], "version": 3}"#;
+    let mut visitor = CountingVisitor::default();
+    GraphDescription::stream_from_reader(json.as_bytes(), &mut visitor).unwrap();
+    assert_eq!(visitor.nodes, 0);
+}
+
+#[test]
+fn serde_stream_visitor_error_is_propagated() {
+    struct FailOnThirdNode(usize);
+
+    impl graphy::core::GraphVisitor for FailOnThirdNode {
+        fn visit_node(&mut self, node: NodeInstance) -> Result<()> {
+            self.0 += 1;
+            if self.0 == 3 {
+                return Err(GraphyError::NodeNotFound(node.id));
+            }
+            Ok(())
+        }
+
+        fn visit_connection(&mut self, _connection: Connection) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    let json = serde_json::to_vec(&build_stream_graph()).unwrap();
+    let mut visitor = FailOnThirdNode(0);
+    let result = GraphDescription::stream_from_reader(json.as_slice(), &mut visitor);
+
+    assert!(matches!(result, Err(GraphyError::NodeNotFound(_))));
+    assert_eq!(visitor.0, 3);
+}
+
+#[test]
+fn serde_stream_truncated_input() {
+    let json = serde_json::to_vec(&build_stream_graph()).unwrap();
+    let truncated = &json[..json.len() / 2];
+
+    let mut visitor = CountingVisitor::default();
+    let result = GraphDescription::stream_from_reader(truncated, &mut visitor);
+    assert!(matches!(result, Err(GraphyError::Serialization(_))));
+}
@@ -0,0 +1,128 @@
+//! Tests for owned, resumable compilation sessions.
+
+mod common;
+
+use common::*;
+use graphy::core::VariableDecl;
+use graphy::generation::{compile, CompilationSession, CompileOptions, SessionAnalysis};
+use graphy::utils::RustProfile;
+use graphy::*;
+use std::sync::Arc;
+
+fn provider() -> Arc<TestMetadataProvider> {
+    let mut provider = TestMetadataProvider::comprehensive();
+    provider.add(
+        NodeMetadata::new("fetch", NodeTypes::fn_, "net")
+            .with_async(true)
+            .with_exec_outputs(vec!["then".to_string()]),
+    );
+    Arc::new(provider)
+}
+
+// ===========================================================================
+// Steps
+// ===========================================================================
+
+#[test]
+fn session_output_matches_compile() {
+    let mut graph = build_two_event_graph();
+    graph.add_variable("score", VariableDecl::new("i64"));
+    let provider = provider();
+    let expected = compile(&graph, provider.as_ref(), &mut LineGenerator::default()).unwrap();
+
+    let mut generator = LineGenerator::default();
+    let session = CompilationSession::new(&graph, provider, &generator, &CompileOptions::new()).unwrap();
+    let output = session.finish(&mut generator).unwrap();
+    assert_eq!(output.code, expected);
+    assert!(output.code.starts_with("// begin\n// state\n"), "{}", output.code);
+    assert!(output.report.is_none());
+    assert!(output.source_map.mappings_for_node("print_start").next().is_some());
+}
+
+#[test]
+fn session_steps_through_events() {
+    let mut generator = LineGenerator::default();
+    let mut session =
+        CompilationSession::new(&build_two_event_graph(), provider(), &generator, &CompileOptions::new()).unwrap();
+    assert_eq!(session.analysis().events(), ["start", "tick"]);
+    assert_eq!(session.remaining_events(), 2);
+
+    // Prelude, two events, epilogue
+    assert!(session.step(&mut generator).unwrap());
+    assert_eq!(session.remaining_events(), 2);
+    assert!(session.step(&mut generator).unwrap());
+    assert_eq!(session.remaining_events(), 1);
+    assert!(session.step(&mut generator).unwrap());
+    assert_eq!(session.remaining_events(), 0);
+    assert!(!session.is_finished());
+    assert!(!session.step(&mut generator).unwrap());
+    assert!(session.is_finished());
+    assert!(!session.step(&mut generator).unwrap());
+
+    let code = session.finish(&mut generator).unwrap().code;
+    assert_eq!(code.matches("// end").count(), 1, "{}", code);
+}
+
+#[test]
+fn session_resumes_on_another_thread() {
+    fn assert_send<T: Send>(_: &T) {}
+
+    let graph = build_two_event_graph();
+    let provider = provider();
+    let expected = compile(&graph, provider.as_ref(), &mut LineGenerator::default()).unwrap();
+
+    let mut generator = LineGenerator::default();
+    let mut session = CompilationSession::new(&graph, provider, &generator, &CompileOptions::new()).unwrap();
+    session.step(&mut generator).unwrap();
+    session.step(&mut generator).unwrap();
+    assert_send(&session);
+
+    let output = std::thread::spawn(move || session.finish(&mut generator))
+        .join()
+        .unwrap()
+        .unwrap();
+    assert_eq!(output.code, expected);
+}
+
+// ===========================================================================
+// Shared analysis
+// ===========================================================================
+
+#[test]
+fn sessions_share_one_analysis() {
+    let analysis = Arc::new(
+        SessionAnalysis::analyze(&build_two_event_graph(), provider(), Arc::new(RustProfile), &CompileOptions::new())
+            .unwrap(),
+    );
+    assert_eq!(analysis.graph().nodes.len(), 4);
+
+    let mut first_generator = LineGenerator::default();
+    let mut first = CompilationSession::from_analysis(Arc::clone(&analysis), &first_generator).unwrap();
+    let mut second_generator = LineGenerator::default();
+    let second = CompilationSession::from_analysis(Arc::clone(&analysis), &second_generator).unwrap();
+    assert_eq!(Arc::strong_count(&analysis), 3);
+
+    // Interleaving sessions doesn't mix their output
+    first.step(&mut first_generator).unwrap();
+    let second_code = second.finish(&mut second_generator).unwrap().code;
+    let first_code = first.finish(&mut first_generator).unwrap().code;
+    assert_eq!(first_code, second_code);
+}
+
+#[test]
+fn session_rejects_async_without_generator_support() {
+    let mut graph = build_two_event_graph();
+    let mut fetch = NodeInstance::new("fetch_1", "fetch", Position::zero());
+    fetch.add_input_pin("exec_in", DataType::Execution);
+    fetch.add_output_pin("then", DataType::Execution);
+    graph.add_node(fetch);
+    graph.add_connection(Connection::execution("print_start", "then", "fetch_1", "exec_in"));
+
+    let error = CompilationSession::new(&graph, provider(), &LineGenerator::default(), &CompileOptions::new())
+        .err()
+        .unwrap();
+    assert!(error.to_string().contains("async"), "{}", error);
+
+    let generator = LineGenerator { supports_async: true };
+    assert!(CompilationSession::new(&graph, provider(), &generator, &CompileOptions::new()).is_ok());
+}
@@ -0,0 +1,204 @@
+//! Tests for SourceMap, CodeBuffer, and source mapping through CodeGeneratorContext.
+
+mod common;
+
+use common::*;
+use graphy::*;
+use graphy::generation::{CodeBuffer, LineColumn};
+
+// ===========================================================================
+// CodeBuffer - Position tracking
+// ===========================================================================
+
+#[test]
+fn buffer_starts_at_line_one_column_one() {
+    let buffer = CodeBuffer::new();
+    assert_eq!(buffer.position(), LineColumn::new(1, 1));
+    assert!(buffer.source_map().is_empty());
+}
+
+#[test]
+fn buffer_tracks_lines_and_columns() {
+    let mut buffer = CodeBuffer::new();
+    buffer.push_str("fn main() {\n    let");
+    assert_eq!(buffer.position(), LineColumn::new(2, 8));
+}
+
+#[test]
+fn buffer_counts_columns_in_characters() {
+    let mut buffer = CodeBuffer::new();
+    buffer.push_str("\"héllo\"");
+    assert_eq!(buffer.position(), LineColumn::new(1, 8));
+}
+
+#[test]
+fn write_node_records_region() {
+    let mut buffer = CodeBuffer::new();
+    buffer.push_str("fn main() {\n    ");
+    buffer.write_node("add_1", Some("result"), "let x = 1 + 2;");
+    buffer.push_str("\n}\n");
+
+    let (code, map) = buffer.finish();
+    assert_eq!(code, "fn main() {\n    let x = 1 + 2;\n}\n");
+
+    let mapping = &map.mappings()[0];
+    assert_eq!(mapping.start, LineColumn::new(2, 5));
+    assert_eq!(mapping.end, LineColumn::new(2, 19));
+    assert_eq!(mapping.node_id, "add_1");
+    assert_eq!(mapping.pin.as_deref(), Some("result"));
+}
+
+#[test]
+fn finish_closes_open_regions() {
+    let mut buffer = CodeBuffer::new();
+    buffer.begin_node("outer", None);
+    buffer.begin_node("inner", None);
+    buffer.push_str("code");
+
+    let (_, map) = buffer.finish();
+    assert_eq!(map.mappings().len(), 2);
+}
+
+#[test]
+fn end_node_without_open_region_is_noop() {
+    let mut buffer = CodeBuffer::new();
+    buffer.end_node();
+    assert!(buffer.source_map().is_empty());
+}
+
+// ===========================================================================
+// SourceMap - Resolution
+// ===========================================================================
+
+#[test]
+fn resolve_outside_any_region_returns_none() {
+    let mut buffer = CodeBuffer::new();
+    buffer.push_str("fn main() {\n");
+    buffer.write_node("print_1", None, "    println!();\n");
+    buffer.push_str("}\n");
+
+    let (_, map) = buffer.finish();
+    assert!(map.resolve(1, 1).is_none());
+    assert!(map.resolve(3, 1).is_none());
+    assert_eq!(map.resolve(2, 5).unwrap().node_id, "print_1");
+}
+
+#[test]
+fn resolve_span_end_is_exclusive() {
+    let mut buffer = CodeBuffer::new();
+    buffer.write_node("a", None, "abc");
+    buffer.write_node("b", None, "def");
+
+    let (_, map) = buffer.finish();
+    assert_eq!(map.resolve(1, 3).unwrap().node_id, "a");
+    assert_eq!(map.resolve(1, 4).unwrap().node_id, "b");
+}
+
+#[test]
+fn resolve_prefers_innermost_region() {
+    let mut buffer = CodeBuffer::new();
+    buffer.begin_node("print_1", None);
+    buffer.push_str("print_string(");
+    buffer.write_node("add_1", Some("result"), "1 + 2");
+    buffer.push_str(");");
+    buffer.end_node();
+
+    let (_, map) = buffer.finish();
+    assert_eq!(map.resolve(1, 1).unwrap().node_id, "print_1");
+    assert_eq!(map.resolve(1, 15).unwrap().node_id, "add_1");
+    assert_eq!(map.resolve(1, 19).unwrap().node_id, "print_1");
+}
+
+#[test]
+fn mappings_for_node_filters_by_id() {
+    let mut buffer = CodeBuffer::new();
+    buffer.write_node("a", Some("x"), "1");
+    buffer.write_node("b", None, "2");
+    buffer.write_node("a", Some("y"), "3");
+
+    let (_, map) = buffer.finish();
+    let pins: Vec<_> = map.mappings_for_node("a").map(|m| m.pin.clone().unwrap()).collect();
+    assert_eq!(pins, vec!["x", "y"]);
+}
+
+#[test]
+fn source_map_serialization_roundtrip() {
+    let mut buffer = CodeBuffer::new();
+    buffer.write_node("a", Some("x"), "line\nnext");
+
+    let (_, map) = buffer.finish();
+    let json = serde_json::to_string(&map).unwrap();
+    let restored: SourceMap = serde_json::from_str(&json).unwrap();
+    assert_eq!(map, restored);
+}
+
+// ===========================================================================
+// SourceMap - rustc diagnostics
+// ===========================================================================
+
+fn two_line_map() -> SourceMap {
+    let mut buffer = CodeBuffer::new();
+    buffer.write_node("add_1", None, "let x = 1 + \"a\";\n");
+    buffer.write_node("print_1", Some("message"), "println!(\"{}\", x);\n");
+    buffer.finish().1
+}
+
+#[test]
+fn resolve_rustc_error_arrow_location() {
+    let map = two_line_map();
+    let stderr = "error[E0277]: cannot add `&str` to `{integer}`\n \
+                  --> src/main.rs:1:11\n  |\n1 | let x = 1 + \"a\";\n";
+
+    assert_eq!(map.resolve_rustc_error(stderr).unwrap().node_id, "add_1");
+}
+
+#[test]
+fn resolve_rustc_error_panic_location() {
+    let map = two_line_map();
+    let stderr = "thread 'main' panicked at src/main.rs:2:1:\nexplicit panic";
+
+    let mapping = map.resolve_rustc_error(stderr).unwrap();
+    assert_eq!(mapping.node_id, "print_1");
+    assert_eq!(mapping.pin.as_deref(), Some("message"));
+}
+
+#[test]
+fn resolve_rustc_error_windows_path() {
+    let map = two_line_map();
+    let stderr = "error: oops\n --> C:\\proj\\src\\main.rs:2:3\n";
+    assert_eq!(map.resolve_rustc_error(stderr).unwrap().node_id, "print_1");
+}
+
+#[test]
+fn resolve_rustc_error_without_location_returns_none() {
+    let map = two_line_map();
+    assert!(map.resolve_rustc_error("error: linking with `cc` failed").is_none());
+}
+
+// ===========================================================================
+// CodeGeneratorContext - Output
+// ===========================================================================
+
+#[test]
+fn context_emits_code_with_source_map() {
+    let graph = build_exec_chain(2);
+    let provider = TestMetadataProvider::with_function_nodes();
+    let resolver = DataResolver::build(&graph, &provider).unwrap();
+    let routing = ExecutionRouting::build_from_graph(&graph);
+
+    let mut ctx = CodeGeneratorContext::new(&graph, &provider, &resolver, &routing);
+    ctx.emit("fn main() {\n");
+    ctx.emit_for_node("fn_0", None, "    print_string(\"a\");\n");
+    ctx.emit_for_node("fn_1", None, "    print_string(\"b\");\n");
+    ctx.emit("}\n");
+
+    assert_eq!(ctx.source_map().mappings().len(), 2);
+
+    let (code, map) = ctx.take_output();
+    assert!(code.starts_with("fn main() {\n"));
+    assert_eq!(map.resolve(3, 5).unwrap().node_id, "fn_1");
+
+    // Output is reset after taking it
+    assert!(ctx.output.code().is_empty());
+    assert!(ctx.source_map().is_empty());
+}
@@ -0,0 +1,241 @@
+//! Tests for extract_subgraph and extract_group.
+
+mod common;
+
+use common::*;
+use graphy::core::GraphGroup;
+use graphy::utils::{extract_group, extract_subgraph, SUBGRAPH_NODE_TYPE_PREFIX};
+use graphy::*;
+
+/// on_start -> print_1 -> print_2 -> print_3, with add_1 feeding print_2 and print_3.
+fn build_pipeline() -> GraphDescription {
+    let mut graph = GraphDescription::new("pipeline");
+
+    let mut event = NodeInstance::new("start", "on_start", Position::zero());
+    event.add_output_pin("exec", DataType::Execution);
+    graph.add_node(event);
+
+    let mut add = NodeInstance::new("add_1", "add", Position::new(0.0, 200.0));
+    add.add_input_pin("a", DataType::Typed("i64".into()));
+    add.add_input_pin("b", DataType::Typed("i64".into()));
+    add.add_output_pin("result", DataType::Typed("i64".into()));
+    graph.add_node(add);
+
+    for (i, x) in [(1, 200.0), (2, 400.0), (3, 600.0)] {
+        let mut print = NodeInstance::new(format!("print_{}", i), "print_string", Position::new(x, 0.0));
+        print.add_input_pin("exec_in", DataType::Execution);
+        print.add_input_pin("message", DataType::Typed("String".into()));
+        print.add_output_pin("exec_out", DataType::Execution);
+        print.add_output_pin("length", DataType::Typed("usize".into()));
+        graph.add_node(print);
+    }
+
+    graph.add_connection(Connection::execution("start", "exec", "print_1", "exec_in"));
+    graph.add_connection(Connection::execution("print_1", "exec_out", "print_2", "exec_in"));
+    graph.add_connection(Connection::execution("print_2", "exec_out", "print_3", "exec_in"));
+    graph.add_connection(Connection::data("add_1", "result", "print_2", "message"));
+    graph.add_connection(Connection::data("add_1", "result", "print_3", "message"));
+    graph.add_connection(Connection::data("print_2", "length", "add_1", "a"));
+
+    graph
+}
+
+fn has_connection(graph: &GraphDescription, source: &str, source_pin: &str, target: &str, target_pin: &str) -> bool {
+    graph.connections.iter().any(|c| {
+        c.source_node == source && c.source_pin == source_pin && c.target_node == target && c.target_pin == target_pin
+    })
+}
+
+// ===========================================================================
+// extract_subgraph - Errors
+// ===========================================================================
+
+#[test]
+fn extract_empty_selection_fails() {
+    let mut graph = build_pipeline();
+    let result = extract_subgraph(&mut graph, Vec::<String>::new());
+    assert!(matches!(result, Err(GraphyError::Custom(_))));
+}
+
+#[test]
+fn extract_missing_node_leaves_graph_untouched() {
+    let mut graph = build_pipeline();
+    let before = graph.clone();
+
+    let result = extract_subgraph(&mut graph, ["print_2", "ghost"]);
+    assert!(matches!(result, Err(GraphyError::NodeNotFound(id)) if id == "ghost"));
+    assert_eq!(graph.nodes, before.nodes);
+    assert_eq!(graph.connections, before.connections);
+}
+
+#[test]
+fn extract_untyped_boundary_fails() {
+    let mut graph = GraphDescription::new("untyped");
+    graph.add_node(NodeInstance::new("a", "add", Position::zero()));
+    graph.add_node(NodeInstance::new("b", "add", Position::zero()));
+    graph.add_connection(Connection::data("a", "result", "b", "a"));
+
+    assert!(matches!(extract_subgraph(&mut graph, ["b"]), Err(GraphyError::Custom(_))));
+    assert_eq!(graph.nodes.len(), 2);
+}
+
+// ===========================================================================
+// extract_subgraph - Structure
+// ===========================================================================
+
+#[test]
+fn extract_moves_nodes_and_internal_connections() {
+    let mut graph = build_pipeline();
+    let (subgraph, composite) = extract_subgraph(&mut graph, ["print_2", "print_3"]).unwrap();
+
+    assert!(graph.get_node("print_2").is_none());
+    assert!(graph.get_node("print_3").is_none());
+    assert!(subgraph.get_node("print_2").is_some());
+    assert!(subgraph.get_node("print_3").is_some());
+    assert!(has_connection(&subgraph, "print_2", "exec_out", "print_3", "exec_in"));
+
+    assert_eq!(composite.id, "subgraph_1");
+    assert_eq!(composite.node_type, format!("{}subgraph_1", SUBGRAPH_NODE_TYPE_PREFIX));
+    assert_eq!(subgraph.metadata.name, "subgraph_1");
+    assert_eq!(graph.get_node("subgraph_1").unwrap().node_type, composite.node_type);
+}
+
+#[test]
+fn extract_infers_data_inputs_and_outputs() {
+    let mut graph = build_pipeline();
+    let (subgraph, composite) = extract_subgraph(&mut graph, ["print_2", "print_3"]).unwrap();
+
+    // add_1.result feeds both print_2 and print_3 -> one shared input,
+    // typed after the outer source pin
+    assert_eq!(subgraph.graph_inputs, vec![GraphParam::new("message", "i64")]);
+    assert_eq!(subgraph.graph_outputs, vec![GraphParam::new("length", "usize")]);
+
+    assert!(has_connection(&graph, "add_1", "result", &composite.id, "message"));
+    assert!(has_connection(&graph, &composite.id, "length", "add_1", "a"));
+
+    let input_node = subgraph.nodes.values().find(|n| n.is_graph_input()).unwrap();
+    assert!(has_connection(&subgraph, &input_node.id, "message", "print_2", "message"));
+    assert!(has_connection(&subgraph, &input_node.id, "message", "print_3", "message"));
+
+    let output_node = subgraph.nodes.values().find(|n| n.is_graph_output()).unwrap();
+    assert!(has_connection(&subgraph, "print_2", "length", &output_node.id, "length"));
+}
+
+#[test]
+fn extract_infers_execution_pins() {
+    let mut graph = build_pipeline();
+    let (subgraph, composite) = extract_subgraph(&mut graph, ["print_2"]).unwrap();
+
+    assert!(composite.inputs.iter().any(|p| p.id == "exec_in" && p.pin.data_type.is_execution()));
+    assert!(composite.outputs.iter().any(|p| p.id == "exec_out" && p.pin.data_type.is_execution()));
+    assert!(has_connection(&graph, "print_1", "exec_out", &composite.id, "exec_in"));
+    assert!(has_connection(&graph, &composite.id, "exec_out", "print_3", "exec_in"));
+
+    // Execution pins are not declared as graph parameters
+    assert!(subgraph.graph_inputs.iter().all(|p| p.name != "exec_in"));
+
+    let input_node = subgraph.nodes.values().find(|n| n.is_graph_input()).unwrap();
+    assert!(has_connection(&subgraph, &input_node.id, "exec_in", "print_2", "exec_in"));
+}
+
+#[test]
+fn extract_deduplicates_pin_names() {
+    let mut graph = GraphDescription::new("names");
+    for id in ["src_1", "src_2", "inner"] {
+        let mut node = NodeInstance::new(id, "add", Position::zero());
+        node.add_input_pin("a", DataType::Typed("i64".into()));
+        node.add_input_pin("b", DataType::Typed("i64".into()));
+        node.add_output_pin("result", DataType::Typed("i64".into()));
+        graph.add_node(node);
+    }
+    let mut other = NodeInstance::new("other", "add", Position::zero());
+    other.add_input_pin("a", DataType::Typed("i64".into()));
+    other.add_output_pin("result", DataType::Typed("i64".into()));
+    graph.add_node(other);
+
+    graph.add_connection(Connection::data("src_1", "result", "inner", "a"));
+    graph.add_connection(Connection::data("src_2", "result", "other", "a"));
+    graph.add_connection(Connection::data("src_2", "result", "inner", "b"));
+
+    let (subgraph, _) = extract_subgraph(&mut graph, ["inner", "other"]).unwrap();
+    let names: Vec<&str> = subgraph.graph_inputs.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, vec!["a", "a_2"]);
+}
+
+#[test]
+fn extract_without_boundary_has_no_pseudo_nodes() {
+    let mut graph = build_diamond_graph();
+    let (subgraph, composite) =
+        extract_subgraph(&mut graph, ["node_a", "node_b", "node_c", "node_d"]).unwrap();
+
+    assert_eq!(subgraph.nodes.len(), 4);
+    assert_eq!(subgraph.connections.len(), 4);
+    assert!(!subgraph.is_function_graph());
+    assert!(composite.inputs.is_empty() && composite.outputs.is_empty());
+    assert_eq!(graph.nodes.len(), 1);
+    assert!(graph.connections.is_empty());
+}
+
+#[test]
+fn extract_composite_is_centered() {
+    let mut graph = build_pipeline();
+    let (_, composite) = extract_subgraph(&mut graph, ["print_2", "print_3"]).unwrap();
+    assert_eq!(composite.position, Position::new(500.0, 0.0));
+}
+
+#[test]
+fn extract_twice_uses_unique_ids() {
+    let mut graph = build_pipeline();
+    let (_, first) = extract_subgraph(&mut graph, ["print_3"]).unwrap();
+    let (_, second) = extract_subgraph(&mut graph, ["print_2"]).unwrap();
+    assert_ne!(first.id, second.id);
+}
+
+#[test]
+fn extract_then_reconnect_preserves_connection_count() {
+    let mut graph = build_pipeline();
+    let (subgraph, _) = extract_subgraph(&mut graph, ["print_2"]).unwrap();
+
+    // print_1 -> print_2, print_2 -> print_3, add_1 -> print_2, print_2 -> add_1
+    let outer_boundary = graph
+        .connections
+        .iter()
+        .filter(|c| c.source_node.starts_with("subgraph") || c.target_node.starts_with("subgraph"))
+        .count();
+    assert_eq!(outer_boundary, 4);
+    assert_eq!(subgraph.connections.len(), 4);
+}
+
+// ===========================================================================
+// extract_group
+// ===========================================================================
+
+#[test]
+fn extract_group_uses_title_and_removes_group() {
+    let mut graph = build_pipeline();
+    let mut group = GraphGroup::new("g1", "Print Block");
+    group.node_ids = vec!["print_2".into(), "print_3".into()];
+    graph.add_group(group).unwrap();
+
+    let (subgraph, composite) = extract_group(&mut graph, "g1").unwrap();
+    assert_eq!(composite.id, "Print_Block_1");
+    assert_eq!(subgraph.metadata.name, "Print_Block_1");
+    assert!(graph.get_group("g1").is_none());
+}
+
+#[test]
+fn extract_removes_nodes_from_other_groups() {
+    let mut graph = build_pipeline();
+    let mut group = GraphGroup::new("g1", "Mixed");
+    group.node_ids = vec!["print_1".into(), "print_2".into()];
+    graph.add_group(group).unwrap();
+
+    extract_subgraph(&mut graph, ["print_2"]).unwrap();
+    assert_eq!(graph.get_group("g1").unwrap().node_ids, vec!["print_1"]);
+}
+
+#[test]
+fn extract_missing_group_fails() {
+    let mut graph = build_pipeline();
+    assert!(matches!(extract_group(&mut graph, "nope"), Err(GraphyError::Custom(_))));
+}
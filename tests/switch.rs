@@ -0,0 +1,339 @@
+//! Tests for switch nodes: dynamic cases, validation, and match generation.
+
+mod common;
+
+use common::*;
+use graphy::core::{resolve_switch_cases, CompositeProvider, NodeRegistry, SWITCH_DEFAULT_PIN};
+use graphy::generation::compile;
+use graphy::*;
+
+/// Rust generator for events and function calls
+fn switch_generator() -> HookGenerator {
+    HookGenerator::new().with_event_functions(true)
+}
+
+/// Comprehensive provider plus a `switch` node and a `Direction` enum
+fn switch_provider() -> TestMetadataProvider {
+    let mut provider = TestMetadataProvider::comprehensive();
+    provider.add(
+        NodeMetadata::new("switch", NodeTypes::control_flow, "flow")
+            .with_params(vec![ParamInfo::new("value", "Direction")])
+            .with_switch(true),
+    );
+    provider.add(NodeMetadata::new("current_direction", NodeTypes::pure, "game").with_return_type("Direction"));
+    provider.metadata.register_enum("Direction", ["North", "East", "South", "West"]);
+    provider
+}
+
+/// `on_start -> switch(current_direction())` with a print per given case
+fn build_switch_graph(cases: &[&str]) -> GraphDescription {
+    let mut graph = GraphDescription::new("switch_graph");
+
+    let mut start = NodeInstance::new("start", "on_start", Position::zero());
+    start.add_output_pin("exec", DataType::Execution);
+    graph.add_node(start);
+
+    let mut direction = NodeInstance::new("dir", "current_direction", Position::zero());
+    direction.add_output_pin("result", DataType::Typed("Direction".into()));
+    graph.add_node(direction);
+
+    let mut switch = NodeInstance::new("switch_1", "switch", Position::zero());
+    switch.add_input_pin("exec_in", DataType::Execution);
+    switch.add_input_pin("value", DataType::Typed("Direction".into()));
+    for case in cases {
+        switch.add_switch_case(*case);
+    }
+    graph.add_node(switch);
+
+    graph.add_connection(Connection::execution("start", "exec", "switch_1", "exec_in"));
+    graph.add_connection(Connection::data("dir", "result", "switch_1", "value"));
+    for case in cases {
+        let id = format!("print_{}", case.to_lowercase());
+        graph.add_node(print_node(&id, case));
+        graph.add_connection(Connection::execution("switch_1", *case, id, "exec_in"));
+    }
+
+    graph
+}
+
+// ===========================================================================
+// Metadata and instances
+// ===========================================================================
+
+#[test]
+fn switch_value_requires_switch_control_flow() {
+    let plain = NodeMetadata::new("branch", NodeTypes::control_flow, "flow")
+        .with_params(vec![ParamInfo::new("condition", "bool")]);
+    assert!(plain.switch_value().is_none());
+
+    let pure = NodeMetadata::new("pick", NodeTypes::pure, "flow")
+        .with_params(vec![ParamInfo::new("value", "Direction")])
+        .with_switch(true);
+    assert!(pure.switch_value().is_none());
+
+    let provider = switch_provider();
+    let switch = provider.get_node_metadata("switch").unwrap();
+    assert_eq!(switch.switch_value().unwrap().param_type, "Direction");
+}
+
+#[test]
+fn is_switch_is_omitted_from_json_unless_set() {
+    let plain = NodeMetadata::new("branch", NodeTypes::control_flow, "flow");
+    assert!(!serde_json::to_string(&plain).unwrap().contains("is_switch"));
+
+    let switch = plain.with_switch(true);
+    let loaded: NodeMetadata = serde_json::from_str(&serde_json::to_string(&switch).unwrap()).unwrap();
+    assert!(loaded.is_switch);
+}
+
+#[test]
+fn add_switch_case_skips_existing_pins() {
+    let mut node = NodeInstance::new("switch_1", "switch", Position::zero());
+    assert!(node.add_switch_case("North"));
+    assert!(!node.add_switch_case("North"));
+    assert!(node.add_switch_case(SWITCH_DEFAULT_PIN));
+    node.add_output_pin("debug", DataType::String);
+
+    assert_eq!(node.switch_cases(), vec!["North"]);
+}
+
+#[test]
+fn switch_value_type_prefers_typed_pin() {
+    let provider = switch_provider();
+    let metadata = provider.get_node_metadata("switch").unwrap();
+
+    let mut node = NodeInstance::new("switch_1", "switch", Position::zero());
+    assert_eq!(node.switch_value_type(metadata), Some("Direction"));
+
+    node.add_input_pin("value", DataType::Typed("Weather".into()));
+    assert_eq!(node.switch_value_type(metadata), Some("Weather"));
+}
+
+#[test]
+fn cases_derive_from_enum_unless_declared() {
+    let provider = switch_provider();
+    let metadata = provider.get_node_metadata("switch").unwrap();
+
+    let mut node = NodeInstance::new("switch_1", "switch", Position::zero());
+    assert_eq!(
+        resolve_switch_cases(&node, metadata, &provider),
+        vec!["North", "East", "South", "West"]
+    );
+
+    node.add_switch_case("West");
+    node.add_switch_case("North");
+    assert_eq!(resolve_switch_cases(&node, metadata, &provider), vec!["West", "North"]);
+}
+
+#[test]
+fn cases_are_empty_for_unknown_types() {
+    let provider = switch_provider();
+    let metadata = provider.get_node_metadata("switch").unwrap();
+    let mut node = NodeInstance::new("switch_1", "switch", Position::zero());
+    node.add_input_pin("value", DataType::Typed("Unknown".into()));
+
+    assert!(resolve_switch_cases(&node, metadata, &provider).is_empty());
+}
+
+// ===========================================================================
+// Providers
+// ===========================================================================
+
+#[test]
+fn registry_registers_enums() {
+    let mut registry = NodeRegistry::new();
+    assert!(registry.enum_variants("Direction").is_none());
+    assert!(registry.register_enum("Direction", ["North"]).is_none());
+    let previous = registry.register_enum("Direction", ["North", "South"]);
+
+    assert_eq!(previous, Some(vec!["North".to_string()]));
+    assert_eq!(registry.enum_variants("Direction").unwrap(), ["North", "South"]);
+}
+
+#[test]
+fn combinators_forward_enum_variants() {
+    let mut low = NodeRegistry::new();
+    low.register_enum("Direction", ["Up", "Down"]);
+    let mut high = NodeRegistry::new();
+    high.register_enum("Direction", ["North", "South"]);
+
+    let provider = CompositeProvider::new().with_provider(0, low).with_provider(10, high);
+    assert_eq!(provider.enum_variants("Direction").unwrap(), ["North", "South"]);
+
+    let boxed: Box<dyn NodeMetadataProvider> = Box::new(provider);
+    assert_eq!(NodeMetadataProvider::enum_variants(&boxed, "Direction").unwrap(), ["North", "South"]);
+}
+
+// ===========================================================================
+// Validation
+// ===========================================================================
+
+#[test]
+fn connections_from_derived_cases_validate() {
+    let provider = switch_provider();
+    let mut graph = build_switch_graph(&[]);
+    graph.add_node(print_node("print_east", "east"));
+    graph.add_node(print_node("print_other", "other"));
+
+    // Neither pin is declared on the instance
+    assert!(graph
+        .try_add_connection(Connection::execution("switch_1", "East", "print_east", "exec_in"), &provider)
+        .is_ok());
+    assert!(graph
+        .try_add_connection(
+            Connection::execution("switch_1", SWITCH_DEFAULT_PIN, "print_other", "exec_in"),
+            &provider
+        )
+        .is_ok());
+}
+
+#[test]
+fn connections_from_unknown_cases_fail() {
+    let provider = switch_provider();
+    let mut graph = build_switch_graph(&[]);
+    graph.add_node(print_node("print_up", "up"));
+
+    let result = graph.try_add_connection(Connection::execution("switch_1", "Up", "print_up", "exec_in"), &provider);
+    assert!(matches!(
+        result,
+        Err(GraphyError::InvalidConnection { reason: core::ConnectionErrorReason::MissingPin { .. }, .. })
+    ));
+}
+
+#[test]
+fn sync_switch_cases_matches_enum() {
+    let provider = switch_provider();
+    let mut graph = build_switch_graph(&["South", "Up"]);
+    graph.get_node_mut("switch_1").unwrap().add_switch_case(SWITCH_DEFAULT_PIN);
+
+    let removed = graph.sync_switch_cases("switch_1", &provider).unwrap();
+
+    assert_eq!(removed, vec!["Up"]);
+    let switch = graph.get_node("switch_1").unwrap();
+    assert_eq!(switch.switch_cases(), vec!["South", "North", "East", "West"]);
+    assert!(switch.outputs.iter().any(|pin| pin.id == SWITCH_DEFAULT_PIN));
+    assert!(!graph.connections.iter().any(|c| c.source_pin == "Up"));
+    assert!(graph.connections.iter().any(|c| c.source_pin == "South"));
+}
+
+#[test]
+fn sync_switch_cases_rejects_non_switches_and_unknown_types() {
+    let provider = switch_provider();
+    let mut graph = build_switch_graph(&[]);
+
+    assert!(matches!(
+        graph.sync_switch_cases("missing", &provider),
+        Err(GraphyError::NodeNotFound(_))
+    ));
+    assert!(matches!(
+        graph.sync_switch_cases("dir", &provider),
+        Err(GraphyError::Custom(_))
+    ));
+
+    let switch = graph.get_node_mut("switch_1").unwrap();
+    switch.inputs.clear();
+    switch.add_input_pin("value", DataType::Typed("Unknown".into()));
+    let error = graph.sync_switch_cases("switch_1", &provider).unwrap_err();
+    assert!(error.to_string().contains("Unknown"));
+}
+
+// ===========================================================================
+// Code generation
+// ===========================================================================
+
+#[test]
+fn compile_emits_arm_per_case() {
+    let provider = switch_provider();
+    let graph = build_switch_graph(&["North", "East", "South", "West"]);
+
+    let code = compile(&graph, &provider, &mut switch_generator()).unwrap();
+
+    assert!(code.contains("match current_direction() {"), "{}", code);
+    let arms: Vec<usize> = ["North", "East", "South", "West"]
+        .iter()
+        .map(|case| code.find(&format!("Direction::{} => {{", case)).unwrap())
+        .collect();
+    assert!(arms.windows(2).all(|pair| pair[0] < pair[1]), "arms out of order:\n{}", code);
+    assert!(code.contains("print_string(\"East\");"), "{}", code);
+    // Every variant is covered, so there is no wildcard arm
+    assert!(!code.contains("_ =>"), "{}", code);
+}
+
+#[test]
+fn compile_adds_wildcard_for_missing_variants() {
+    let provider = switch_provider();
+    let graph = build_switch_graph(&["North"]);
+
+    let code = compile(&graph, &provider, &mut switch_generator()).unwrap();
+
+    assert!(code.contains("Direction::North => {"));
+    assert!(code.contains("_ => {"), "{}", code);
+}
+
+#[test]
+fn compile_puts_default_flow_in_wildcard() {
+    let provider = switch_provider();
+    let mut graph = build_switch_graph(&["North", "East", "South", "West"]);
+    graph.add_node(print_node("print_default", "default"));
+    graph.add_connection(Connection::execution("switch_1", SWITCH_DEFAULT_PIN, "print_default", "exec_in"));
+
+    let code = compile(&graph, &provider, &mut switch_generator()).unwrap();
+
+    let wildcard = code.find("_ => {").expect("wildcard arm");
+    assert!(code[wildcard..].contains("print_string(\"default\");"), "{}", code);
+}
+
+#[test]
+fn compile_derives_arms_from_enum_without_case_pins() {
+    let provider = switch_provider();
+    let mut graph = build_switch_graph(&[]);
+    graph.add_node(print_node("print_west", "west"));
+    graph.add_connection(Connection::execution("switch_1", "West", "print_west", "exec_in"));
+
+    let code = compile(&graph, &provider, &mut switch_generator()).unwrap();
+
+    for case in ["North", "East", "South", "West"] {
+        assert!(code.contains(&format!("Direction::{} => {{", case)), "{}", code);
+    }
+    let west = code.find("Direction::West").unwrap();
+    assert!(code[west..].contains("print_string(\"west\");"));
+}
+
+#[test]
+fn compile_rejects_cases_outside_enum() {
+    let provider = switch_provider();
+    let graph = build_switch_graph(&["North", "Up"]);
+
+    let error = compile(&graph, &provider, &mut switch_generator()).unwrap_err();
+
+    assert!(matches!(error.root(), GraphyError::CodeGeneration(_)), "{:?}", error);
+    assert!(error.to_string().contains("'Up'"), "{}", error);
+}
+
+#[test]
+fn compile_uses_literal_patterns_for_non_enum_cases() {
+    let mut provider = switch_provider();
+    provider.add(
+        NodeMetadata::new("switch_int", NodeTypes::control_flow, "flow")
+            .with_params(vec![ParamInfo::new("value", "i64")])
+            .with_switch(true),
+    );
+
+    let mut graph = build_switch_graph(&[]);
+    let switch = graph.get_node_mut("switch_1").unwrap();
+    switch.node_type = "switch_int".into();
+    switch.inputs.retain(|pin| pin.id != "value");
+    switch.set_property("value", PropertyValue::Integer(2));
+    switch.add_switch_case("1");
+    switch.add_switch_case("2");
+    graph.connections.retain(|c| c.target_pin != "value");
+    graph.add_node(print_node("print_two", "two"));
+    graph.add_connection(Connection::execution("switch_1", "2", "print_two", "exec_in"));
+
+    let code = compile(&graph, &provider, &mut switch_generator()).unwrap();
+
+    assert!(code.contains("1 => {"), "{}", code);
+    assert!(code.contains("2 => {"), "{}", code);
+    assert!(code.contains("_ => {"), "{}", code);
+    assert!(!code.contains("i64::"), "{}", code);
+}
@@ -0,0 +1,166 @@
+//! Tests for text template sources.
+
+use graphy::core::MetadataIssue;
+use graphy::generation::{inline_node_source, render_template, SourceTemplate};
+use graphy::*;
+use std::collections::HashMap;
+
+fn map(entries: &[(&str, &str)]) -> HashMap<String, String> {
+    entries.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+fn template_node(name: &str, node_type: NodeTypes, source: &str) -> NodeMetadata {
+    NodeMetadata::new(name, node_type, "Templates")
+        .with_source(source)
+        .with_source_kind(SourceKind::Template)
+}
+
+// ===========================================================================
+// Parsing
+// ===========================================================================
+
+#[test]
+fn template_lists_params_and_exec_labels() {
+    let template = SourceTemplate::parse("{{a}} + {{ b }} * {{a}}; {{exec:then}} {{ exec: Done }}").unwrap();
+    assert_eq!(template.params(), ["a", "b"]);
+    assert_eq!(template.exec_labels(), ["then", "Done"]);
+}
+
+#[test]
+fn template_without_placeholders_renders_as_is() {
+    let template = SourceTemplate::parse("gl_FragColor = vec4(1.0);").unwrap();
+    assert!(template.params().is_empty());
+    assert_eq!(template.render(&HashMap::new(), &HashMap::new()).unwrap(), "gl_FragColor = vec4(1.0);");
+}
+
+#[test]
+fn template_escapes_braces() {
+    let result = render_template("f\"{{{{x}}}\" {{y}}", &HashMap::new(), &map(&[("y", "1")])).unwrap();
+    assert_eq!(result, "f\"{{x}}}\" 1");
+}
+
+#[test]
+fn template_rejects_malformed_placeholders() {
+    for source in ["{{a", "x = {{}}", "{{exec:}}"] {
+        let error = SourceTemplate::parse(source).unwrap_err();
+        assert!(matches!(error, GraphyError::CodeGeneration(_)), "{}: {}", source, error);
+    }
+}
+
+// ===========================================================================
+// Rendering
+// ===========================================================================
+
+#[test]
+fn template_substitutes_params() {
+    let result = render_template(
+        "vec3 {{out}} = mix({{a}}, {{b}}, 0.5);",
+        &HashMap::new(),
+        &map(&[("out", "color"), ("a", "red"), ("b", "blue")]),
+    )
+    .unwrap();
+    assert_eq!(result, "vec3 color = mix(red, blue, 0.5);");
+}
+
+#[test]
+fn template_missing_param_fails() {
+    let error = render_template("{{a}} + {{b}}", &HashMap::new(), &map(&[("a", "1")])).unwrap_err();
+    assert!(error.to_string().contains("'b'"), "{}", error);
+}
+
+#[test]
+fn template_missing_exec_renders_nothing() {
+    let result = render_template("before(); {{exec:then}}", &HashMap::new(), &HashMap::new()).unwrap();
+    assert_eq!(result, "before(); ");
+}
+
+#[test]
+fn template_indents_exec_code_like_placeholder() {
+    let template = "if {{condition}}:\n    {{exec:True}}\nelse:\n    {{exec:False}}";
+    let result = render_template(
+        template,
+        &map(&[("True", "print(x)\n\nx = 0"), ("False", "pass")]),
+        &map(&[("condition", "x > 5")]),
+    )
+    .unwrap();
+    assert_eq!(result, "if x > 5:\n    print(x)\n\n    x = 0\nelse:\n    pass");
+}
+
+#[test]
+fn template_inline_exec_code_keeps_lines() {
+    let result = render_template("run(); {{exec:then}}", &map(&[("then", "a();\nb();")]), &HashMap::new()).unwrap();
+    assert_eq!(result, "run(); a();\nb();");
+}
+
+// ===========================================================================
+// Source kinds
+// ===========================================================================
+
+#[test]
+fn inline_node_source_dispatches_on_kind() {
+    let template = template_node("py_print", NodeTypes::fn_, "print({{message}})\n{{exec:then}}");
+    let result = inline_node_source(
+        &template,
+        map(&[("then", "done()")]),
+        map(&[("message", "\"hi\"")]),
+    )
+    .unwrap();
+    assert_eq!(result, "print(\"hi\")\ndone()");
+
+    let rust = NodeMetadata::new("print", NodeTypes::fn_, "IO")
+        .with_source("fn print(message: String) { println!(\"{}\", message); exec_output!(\"then\"); }");
+    let result = inline_node_source(&rust, map(&[("then", "done();")]), map(&[("message", "msg")])).unwrap();
+    assert!(result.contains("done ()") || result.contains("done()"), "{}", result);
+    assert!(!result.contains("exec_output"), "{}", result);
+}
+
+#[test]
+fn source_kind_serializes_only_templates() {
+    let rust = NodeMetadata::new("add", NodeTypes::pure, "Math");
+    assert!(!serde_json::to_string(&rust).unwrap().contains("source_kind"));
+
+    let template = template_node("mix", NodeTypes::pure, "mix({{a}}, {{b}}, 0.5)");
+    let json = serde_json::to_string(&template).unwrap();
+    assert!(json.contains("\"source_kind\":\"template\""), "{}", json);
+    let restored: NodeMetadata = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.source_kind, SourceKind::Template);
+}
+
+// ===========================================================================
+// Validation
+// ===========================================================================
+
+#[test]
+fn template_metadata_validates_without_rust_parsing() {
+    let meta = template_node("glsl_branch", NodeTypes::control_flow, "if ({{cond}}) { {{exec:True}} } else { {{exec:False}} }")
+        .with_params(vec![ParamInfo::new("cond", "bool")])
+        .with_exec_outputs(vec!["True".into(), "False".into()]);
+    assert!(meta.validate().is_empty(), "{:?}", meta.validate());
+}
+
+#[test]
+fn template_metadata_reports_mismatches() {
+    let meta = template_node("py_call", NodeTypes::control_flow, "call({{value}})\n{{exec:Done}}")
+        .with_params(vec![ParamInfo::new("unused", "i64")])
+        .with_exec_outputs(vec!["Failed".into()]);
+
+    let issues = meta.validate();
+    let node = || "py_call".to_string();
+    assert!(issues.contains(&MetadataIssue::ParamNotDeclared { node: node(), param: "value".into() }));
+    assert!(issues.contains(&MetadataIssue::ParamNotInSource { node: node(), param: "unused".into() }));
+    assert!(issues.contains(&MetadataIssue::ExecOutputNotDeclared { node: node(), label: "Done".into() }));
+    assert!(issues.contains(&MetadataIssue::ExecOutputNotInSource { node: node(), label: "Failed".into() }));
+}
+
+#[test]
+fn template_metadata_reports_malformed_template() {
+    let meta = template_node("broken", NodeTypes::pure, "{{a + 1");
+    assert!(matches!(meta.validate().as_slice(), [MetadataIssue::InvalidSource { .. }]));
+}
+
+#[test]
+fn template_metadata_allows_implicit_then() {
+    let meta = template_node("log", NodeTypes::fn_, "console.log({{message}});")
+        .with_params(vec![ParamInfo::new("message", "String")]);
+    assert!(meta.validate().is_empty(), "{:?}", meta.validate());
+}
@@ -0,0 +1,133 @@
+//! Tests for the golden testing utilities and canonical graphs.
+
+mod common;
+
+use common::*;
+use graphy::core::OverlayProvider;
+use graphy::generation::targets::CGenerator;
+use graphy::testing::*;
+use graphy::*;
+use std::path::PathBuf;
+
+/// Generator writing Rust-like pseudo code
+fn pseudo_generator() -> HookGenerator {
+    HookGenerator::new().with_event_functions(true).with_branches(Branches::Arms)
+}
+
+/// Checked-in golden files
+fn golden_dir(backend: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(backend)
+}
+
+// ===========================================================================
+// Canonical graphs
+// ===========================================================================
+
+#[test]
+fn canonical_graphs_use_the_canonical_registry() {
+    let registry = canonical_registry();
+    let graphs = canonical_graphs();
+    let names: Vec<&str> = graphs.iter().map(|graph| graph.metadata.name.as_str()).collect();
+    assert_eq!(names, ["linear_chain", "diamond", "branch", "loop"]);
+
+    for graph in &graphs {
+        assert!(graph.unknown_nodes(&registry).is_empty(), "{}", graph.metadata.name);
+        let resolver = DataResolver::build(graph, &registry).unwrap();
+        assert!(resolver.unconnected_required_inputs(graph, &registry).is_empty());
+    }
+}
+
+#[test]
+fn linear_chains_nest_their_nodes() {
+    let graph = linear_chain_graph(3);
+    assert_eq!(graph.nodes_of_type("add").len(), 3);
+    let code = compile(&graph, &canonical_registry(), &mut pseudo_generator()).unwrap();
+    assert!(code.contains("print(add(add(add(0, 1), 1), 1));"), "{}", code);
+
+    let empty = compile(&linear_chain_graph(0), &canonical_registry(), &mut pseudo_generator()).unwrap();
+    assert!(empty.contains("print(0);"), "{}", empty);
+}
+
+// ===========================================================================
+// Golden files
+// ===========================================================================
+
+#[test]
+fn canonical_graphs_match_golden_files() {
+    assert_canonical_goldens(golden_dir("pseudo"), "txt", &canonical_registry(), &mut pseudo_generator());
+}
+
+#[test]
+fn c_backend_matches_golden_files() {
+    let mut provider = OverlayProvider::new(canonical_registry());
+    let registry = canonical_registry();
+    let template = |node_type: &str, source: &str| {
+        registry
+            .get_node_metadata(node_type)
+            .unwrap()
+            .clone()
+            .with_source(source)
+            .with_source_kind(SourceKind::Template)
+    };
+    provider.set_override(template(
+        "branch",
+        "if ({{condition}}) {\n    {{exec:True}}\n} else {\n    {{exec:False}}\n}",
+    ));
+    provider.set_override(template(
+        "for_loop",
+        "for (int64_t i = {{start}}; i < {{end}}; i++) {\n    {{exec:body}}\n}\n{{exec:completed}}",
+    ));
+
+    assert_canonical_goldens(golden_dir("c"), "c", &provider, &mut CGenerator::new("canonical"));
+}
+
+#[test]
+fn mismatches_fail_with_a_diff() {
+    let dir = temp_dir("golden_mismatch");
+    let path = dir.join("out.txt");
+    check_golden(&path, "one\ntwo\nthree\n", GoldenMode::Update).unwrap();
+    check_golden(&path, "one\r\ntwo\r\nthree\r\n", GoldenMode::Compare).unwrap();
+
+    let error = check_golden(&path, "one\n2\nthree\n", GoldenMode::Compare).unwrap_err().to_string();
+    assert!(error.contains("@@ line 1 @@\n one\n-two\n+2\n three\n"), "{}", error);
+    assert!(error.contains(UPDATE_GOLDEN_ENV), "{}", error);
+
+    let error = check_golden(&path, "one\ntwo\nthree", GoldenMode::Compare).unwrap_err().to_string();
+    assert!(error.contains("only the final newline differs"), "{}", error);
+
+    // Updating overwrites the file
+    check_golden(&path, "one\n2\nthree\n", GoldenMode::Update).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "one\n2\nthree\n");
+}
+
+#[test]
+fn missing_golden_files_are_errors() {
+    let dir = temp_dir("golden_missing");
+    let error = check_golden(dir.join("none.txt"), "code", GoldenMode::Compare).unwrap_err();
+    assert!(matches!(error, GraphyError::Io(ref message) if message.contains("doesn't exist")), "{}", error);
+
+    // Every failing graph is listed
+    let mut generator = pseudo_generator();
+    let error = check_canonical_goldens(&dir, "txt", &canonical_registry(), &mut generator, GoldenMode::Compare)
+        .unwrap_err()
+        .to_string();
+    for name in ["linear_chain", "diamond", "branch", "loop"] {
+        assert!(error.contains(&format!("{}: I/O error: Golden file", name)), "{}", error);
+    }
+
+    check_canonical_goldens(&dir, "txt", &canonical_registry(), &mut generator, GoldenMode::Update).unwrap();
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 4);
+}
+
+#[test]
+fn diffs_keep_context_around_changes() {
+    let expected: String = (1..=10).map(|i| format!("line {}\n", i)).collect();
+    let actual = expected.replace("line 2\n", "").replace("line 9", "line nine");
+
+    assert_eq!(
+        diff_lines(&expected, &actual),
+        "@@ line 1 @@\n line 1\n-line 2\n line 3\n line 4\n\
+         @@ line 7 @@\n line 7\n line 8\n-line 9\n+line nine\n line 10\n"
+    );
+    assert_eq!(diff_lines(&expected, &expected), "");
+}
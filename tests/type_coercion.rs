@@ -0,0 +1,150 @@
+//! Tests for TypeCoercion rules, TypeCheckPass, and coercion in code generation.
+
+mod common;
+
+use common::*;
+use graphy::analysis::{CoercionTable, DataFlowPass, ExecutionRoutingPass, TypeCheckPass};
+use graphy::core::TypeCoercion;
+use graphy::*;
+
+fn typed(type_string: &str) -> DataType {
+    DataType::Typed(type_string.into())
+}
+
+/// Graph where an i64 output feeds an f64 input.
+fn build_mixed_graph(target_type: &str) -> GraphDescription {
+    let mut graph = GraphDescription::new("mixed");
+
+    let mut source = NodeInstance::new("source", "add", Position::zero());
+    source.add_output_pin("result", typed("i64"));
+    graph.add_node(source);
+
+    let mut sink = NodeInstance::new("sink", "scale", Position::zero());
+    sink.add_input_pin("value", typed(target_type));
+    graph.add_node(sink);
+
+    graph.add_connection(Connection::data("source", "result", "sink", "value"));
+    graph
+}
+
+// ===========================================================================
+// TypeCoercion registry
+// ===========================================================================
+
+#[test]
+fn rust_coercion_numeric_widening() {
+    let rust = TypeCoercion::rust();
+
+    assert!(rust.can_coerce(&typed("i32"), &typed("i64")));
+    assert!(rust.can_coerce(&typed("u8"), &typed("i16")));
+    assert!(rust.can_coerce(&typed("f32"), &typed("f64")));
+    assert!(rust.can_coerce(&typed("usize"), &typed("f64")));
+
+    // Narrowing is not implicit
+    assert!(!rust.can_coerce(&typed("i64"), &typed("i32")));
+    assert!(!rust.can_coerce(&typed("f64"), &typed("f32")));
+    assert!(!rust.can_coerce(&typed("i8"), &typed("u8")));
+}
+
+#[test]
+fn rust_coercion_expressions() {
+    let rust = TypeCoercion::rust();
+
+    assert_eq!(rust.coerce_expression("x", &typed("i64"), &typed("f64")).unwrap(), "(x) as f64");
+    assert_eq!(rust.coerce_expression("x", &typed("i64"), &typed("i64")).unwrap(), "x");
+    assert_eq!(
+        rust.coerce_expression("flag", &typed("bool"), &DataType::String).unwrap(),
+        "(flag).to_string()"
+    );
+    assert!(rust.coerce_expression("x", &typed("String"), &typed("i64")).is_none());
+}
+
+#[test]
+fn coercion_never_applies_to_execution_pins() {
+    let rust = TypeCoercion::rust();
+    assert!(!rust.can_coerce(&DataType::Execution, &typed("String")));
+}
+
+#[test]
+fn custom_coercion_rules() {
+    let mut wgsl = TypeCoercion::new();
+    wgsl.register("i32", "f32", "f32({expr})");
+
+    assert_eq!(wgsl.coerce_expression("n", &typed("i32"), &typed("f32")).unwrap(), "f32(n)");
+    assert!(!wgsl.can_coerce(&typed("i32"), &typed("f64")));
+}
+
+#[test]
+fn exact_rule_overrides_any_source_rule() {
+    let mut coercion = TypeCoercion::rust();
+    coercion.register("char", "String", "String::from({expr})");
+
+    assert_eq!(
+        coercion.coerce_expression("c", &typed("char"), &typed("String")).unwrap(),
+        "String::from(c)"
+    );
+}
+
+#[test]
+fn coercion_ignores_type_whitespace() {
+    let mut coercion = TypeCoercion::new();
+    coercion.register("(i32, i32)", "(f32,f32)", "to_f32({expr})");
+
+    assert!(coercion.can_coerce(&typed("(i32,i32)"), &typed("(f32, f32)")));
+}
+
+// ===========================================================================
+// TypeCheckPass
+// ===========================================================================
+
+#[test]
+fn type_check_records_coercions() {
+    let graph = build_mixed_graph("f64");
+    let provider = TestMetadataProvider::empty();
+    let coercion = TypeCoercion::rust();
+
+    let table = TypeCheckPass::new(&provider, &coercion).check(&graph).unwrap();
+    assert_eq!(table.len(), 1);
+    assert_eq!(table.apply("sink", "value", "v"), "(v) as f64");
+    assert_eq!(table.apply("source", "a", "v"), "v");
+}
+
+#[test]
+fn type_check_rejects_uncoercible_connection() {
+    let graph = build_mixed_graph("i32");
+    let provider = TestMetadataProvider::empty();
+    let coercion = TypeCoercion::rust();
+
+    let result = TypeCheckPass::new(&provider, &coercion).check(&graph);
+    assert!(matches!(
+        result,
+        Err(GraphyError::InvalidConnection { reason: core::ConnectionErrorReason::TypeMismatch { .. }, .. })
+    ));
+}
+
+#[test]
+fn type_check_without_rules_is_strict() {
+    let graph = build_mixed_graph("f64");
+    let provider = TestMetadataProvider::empty();
+    let coercion = TypeCoercion::new();
+
+    assert!(TypeCheckPass::new(&provider, &coercion).check(&graph).is_err());
+}
+
+#[test]
+fn code_generator_context_applies_coercion() {
+    let graph = build_mixed_graph("f64");
+    let provider = TestMetadataProvider::empty();
+    let coercion = TypeCoercion::rust();
+
+    let mut manager = PassManager::new();
+    manager.add_pass(DataFlowPass::new(&provider));
+    manager.add_pass(ExecutionRoutingPass);
+    manager.add_pass(TypeCheckPass::new(&provider, &coercion));
+    let analysis = manager.run(&graph).unwrap();
+
+    assert_eq!(analysis.get::<CoercionTable>().unwrap().len(), 1);
+
+    let ctx = CodeGeneratorContext::from_analysis(&graph, &provider, &analysis).unwrap();
+    assert_eq!(ctx.coerce_input("sink", "value", "node_source_result"), "(node_source_result) as f64");
+}
@@ -0,0 +1,343 @@
+//! Tests for watched pins and the value probes generated for them.
+
+mod common;
+
+use common::*;
+use graphy::core::{VariableDecl, ERROR_VALUE_PIN, RESULT_PIN};
+use graphy::generation::targets::CGenerator;
+use graphy::generation::{
+    bind_event_params, compile, compile_with_options, generate_exec_output, CodeGenerator, CompilationSession,
+    CompileOptions, ValueProbes,
+};
+use graphy::utils::diff::GraphDiff;
+use graphy::utils::merge_graphs;
+use graphy::*;
+use std::sync::Arc;
+
+/// Generator emitting handlers with their params, binding read or probed results
+struct ProbeGenerator;
+
+impl CodeGenerator for ProbeGenerator {
+    fn generate_event<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        metadata: &NodeMetadata,
+    ) -> Result<()> {
+        let params: Vec<String> = metadata.params.iter().map(|param| format!("{}: {}", param.name, param.param_type)).collect();
+        ctx.emit(&format!("fn {}({}) {{\n", node.id, params.join(", ")));
+        ctx.push_indent();
+        bind_event_params(self, ctx, node, metadata)?;
+        for pin in &metadata.exec_outputs {
+            generate_exec_output(self, ctx, &node.id, pin)?;
+        }
+        ctx.pop_indent();
+        ctx.emit("}\n");
+        Ok(())
+    }
+
+    fn generate_function_node<P: NodeMetadataProvider>(
+        &mut self,
+        ctx: &mut CodeGeneratorContext<'_, P>,
+        node: &NodeInstance,
+        metadata: &NodeMetadata,
+        args: &[String],
+    ) -> Result<()> {
+        let bound = !ctx.data_resolver.get_dependents(&node.id).is_empty() || ctx.is_probed(&node.id, RESULT_PIN);
+        let binding = match ctx.data_resolver.get_result_variable(&node.id) {
+            Some(variable) if bound && metadata.return_type.is_some() => format!("let {} = ", variable),
+            _ => String::new(),
+        };
+        let line = format!("{}{}{}({});\n", ctx.indent(), binding, metadata.name, args.join(", "));
+        ctx.emit(&line);
+        Ok(())
+    }
+
+    fn generate_control_flow<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &mut CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        _metadata: &NodeMetadata,
+        _args: &[String],
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn generate_pure_expression<P: NodeMetadataProvider>(
+        &mut self,
+        _ctx: &CodeGeneratorContext<'_, P>,
+        _node: &NodeInstance,
+        metadata: &NodeMetadata,
+        args: &[String],
+    ) -> Result<String> {
+        Ok(format!("{}({})", metadata.name, args.join(", ")))
+    }
+}
+
+/// Comprehensive provider plus `roll`, returning an `i64`, and a fallible `read_file`
+fn provider() -> TestMetadataProvider {
+    let mut provider = TestMetadataProvider::comprehensive();
+    provider.add(
+        NodeMetadata::new("roll", NodeTypes::fn_, "game")
+            .with_return_type("i64")
+            .with_exec_outputs(vec!["then".into()]),
+    );
+    provider.add(
+        NodeMetadata::new("read_file", NodeTypes::fn_, "io")
+            .with_params(vec![ParamInfo::new("path", "String")])
+            .with_return_type("Result<String, String>")
+            .with_exec_outputs(vec!["then".into()]),
+    );
+    provider
+}
+
+/// `tick -> roll_1 -> print_1(negate(add(roll_1, score)))`
+fn build_graph() -> GraphDescription {
+    let mut graph = GraphDescription::new("probes");
+    graph.add_variable("score", VariableDecl::new("i64"));
+
+    event_node(&mut graph, "tick", "on_tick");
+    exec_node(&mut graph, "roll_1", "roll", &["then"]);
+    exec_node(&mut graph, "print_1", "print_string", &["then"]);
+    graph.add_node(NodeInstance::new("add_1", "add", Position::zero()));
+    graph.add_node(NodeInstance::new("negate_1", "negate", Position::zero()));
+    let get = graph.create_get_variable_node("get_1", "score", Position::zero()).unwrap();
+    graph.add_node(get);
+
+    graph.add_connection(Connection::execution("tick", "exec", "roll_1", "exec_in"));
+    graph.add_connection(Connection::execution("roll_1", "then", "print_1", "exec_in"));
+    graph.add_connection(Connection::data("roll_1", "result", "add_1", "a"));
+    graph.add_connection(Connection::data("get_1", "value", "add_1", "b"));
+    graph.add_connection(Connection::data("add_1", "result", "negate_1", "value"));
+    graph.add_connection(Connection::data("negate_1", "result", "print_1", "message"));
+    graph
+}
+
+fn watch(graph: &mut GraphDescription, id: &str, pin: &str) {
+    graph.get_node_mut(id).unwrap().watch_pin(pin);
+}
+
+fn probe_options() -> CompileOptions {
+    CompileOptions::new().with_probes(ValueProbes::new())
+}
+
+fn compile_probes(graph: &GraphDescription, options: &CompileOptions) -> String {
+    compile_with_options(graph, &provider(), &mut ProbeGenerator, options).unwrap().code
+}
+
+// ============================================================================
+// Probes
+// ============================================================================
+
+#[test]
+fn probes_are_ignored_without_the_option() {
+    let mut graph = build_graph();
+    let plain = compile(&graph, &provider(), &mut ProbeGenerator).unwrap();
+    watch(&mut graph, "add_1", RESULT_PIN);
+    watch(&mut graph, "tick", "delta_time");
+
+    assert_eq!(compile_probes(&graph, &CompileOptions::new()), plain);
+    assert_eq!(compile_probes(&build_graph(), &probe_options()), plain);
+    assert!(!plain.contains("on_value"), "{}", plain);
+}
+
+#[test]
+fn probes_hoist_watched_pure_nodes() {
+    let mut graph = build_graph();
+    watch(&mut graph, "add_1", RESULT_PIN);
+    let code = compile_probes(&graph, &probe_options());
+
+    assert!(
+        code.contains(
+            "    let node_add_1_result = add(node_roll_1_result, state.score);\n    \
+             graphy_runtime::sink().on_value(\"add_1\", \"result\", &node_add_1_result);\n    \
+             print_string(negate(node_add_1_result));\n"
+        ),
+        "{}",
+        code
+    );
+}
+
+#[test]
+fn probes_report_variable_reads() {
+    let mut graph = build_graph();
+    watch(&mut graph, "get_1", "value");
+    let code = compile_probes(&graph, &probe_options());
+
+    assert!(
+        code.contains(
+            "    let node_get_1_result = state.score;\n    \
+             graphy_runtime::sink().on_value(\"get_1\", \"value\", &node_get_1_result);\n    \
+             print_string(negate(add(node_roll_1_result, node_get_1_result)));\n"
+        ),
+        "{}",
+        code
+    );
+}
+
+#[test]
+fn probes_report_function_results_after_the_call() {
+    let mut graph = build_graph();
+    watch(&mut graph, "roll_1", RESULT_PIN);
+    let code = compile_probes(&graph, &probe_options());
+    assert!(
+        code.contains(
+            "    let node_roll_1_result = roll();\n    \
+             graphy_runtime::sink().on_value(\"roll_1\", \"result\", &node_roll_1_result);\n"
+        ),
+        "{}",
+        code
+    );
+
+    // Bound for the probe even if nothing reads it
+    graph.remove_connection("roll_1", "result", "add_1", "a");
+    let code = compile_probes(&graph, &probe_options());
+    assert!(code.contains("    let node_roll_1_result = roll();\n"), "{}", code);
+}
+
+#[test]
+fn probes_report_event_params() {
+    let mut graph = build_graph();
+    watch(&mut graph, "tick", "delta_time");
+    let code = compile_probes(&graph, &probe_options());
+
+    assert!(
+        code.contains(
+            "fn tick(delta_time: f64) {\n    graphy_runtime::sink().on_value(\"tick\", \"delta_time\", &delta_time);\n"
+        ),
+        "{}",
+        code
+    );
+}
+
+#[test]
+fn probes_report_fallible_values_in_their_branch() {
+    let mut graph = GraphDescription::new("fallible");
+    event_node(&mut graph, "start", "on_start");
+    exec_node(&mut graph, "read_1", "read_file", &["then"]);
+    graph.add_connection(Connection::execution("start", "exec", "read_1", "exec_in"));
+    watch(&mut graph, "read_1", RESULT_PIN);
+    watch(&mut graph, "read_1", ERROR_VALUE_PIN);
+    let code = compile_probes(&graph, &probe_options());
+
+    let expected = "    match read_file(String::new()) {
+        Ok(node_read_1_result) => {
+            graphy_runtime::sink().on_value(\"read_1\", \"result\", &node_read_1_result);
+        }
+        Err(node_read_1_error) => {
+            graphy_runtime::sink().on_value(\"read_1\", \"error\", &node_read_1_error);
+        }
+    }
+";
+    assert!(code.contains(expected), "{}", code);
+}
+
+#[test]
+fn probes_can_report_to_another_sink() {
+    let mut graph = build_graph();
+    watch(&mut graph, "add_1", RESULT_PIN);
+    let code = compile_probes(&graph, &CompileOptions::new().with_probes(ValueProbes::new().with_sink("state.probes")));
+
+    assert!(code.contains("state.probes.on_value(\"add_1\", \"result\", &node_add_1_result);\n"), "{}", code);
+}
+
+#[test]
+fn probes_map_to_their_node_and_pin() {
+    let mut graph = build_graph();
+    watch(&mut graph, "add_1", RESULT_PIN);
+    let output = compile_with_options(&graph, &provider(), &mut ProbeGenerator, &probe_options()).unwrap();
+
+    let line = output.code.lines().position(|line| line.contains("on_value")).unwrap() + 1;
+    let location = output.source_map.resolve(line, 5).unwrap();
+    assert_eq!(location.node_id, "add_1");
+    assert_eq!(location.pin.as_deref(), Some(RESULT_PIN));
+}
+
+#[test]
+fn probes_match_in_sessions() {
+    let mut graph = build_graph();
+    watch(&mut graph, "add_1", RESULT_PIN);
+    watch(&mut graph, "tick", "delta_time");
+    let options = probe_options();
+
+    let session = CompilationSession::new(&graph, Arc::new(provider()), &ProbeGenerator, &options).unwrap();
+    assert_eq!(session.finish(&mut ProbeGenerator).unwrap().code, compile_probes(&graph, &options));
+}
+
+#[test]
+fn probes_are_not_supported_by_the_c_backend() {
+    let mut graph = build_graph();
+    watch(&mut graph, "roll_1", RESULT_PIN);
+    let error = compile_with_options(&graph, &provider(), &mut CGenerator::new("game"), &probe_options()).unwrap_err();
+
+    assert!(matches!(error.root(), GraphyError::CodeGeneration(message) if message.contains("value probes")), "{:?}", error);
+    assert_eq!((error.node(), error.pin()), (Some("roll_1"), Some(RESULT_PIN)));
+}
+
+// ============================================================================
+// Watched pins
+// ============================================================================
+
+#[test]
+fn watched_pins_are_a_set() {
+    let mut node = NodeInstance::new("add_1", "add", Position::zero());
+    assert!(node.watch_pin(RESULT_PIN));
+    assert!(!node.watch_pin(RESULT_PIN));
+    assert!(node.is_watched(RESULT_PIN));
+
+    assert!(node.unwatch_pin(RESULT_PIN));
+    assert!(!node.unwatch_pin(RESULT_PIN));
+    assert!(!node.is_watched(RESULT_PIN));
+}
+
+#[test]
+fn watched_pins_serialize_only_when_set() {
+    let mut node = NodeInstance::new("add_1", "add", Position::zero());
+    assert!(serde_json::to_value(&node).unwrap().get("watched_pins").is_none());
+
+    node.watch_pin(RESULT_PIN);
+    let document = serde_json::to_value(&node).unwrap();
+    assert_eq!(document["watched_pins"], serde_json::json!(["result"]));
+    assert_eq!(serde_json::from_value::<NodeInstance>(document).unwrap(), node);
+}
+
+#[test]
+fn watched_pins_change_the_content_hash() {
+    let graph = build_graph();
+    let mut watched = graph.clone();
+    watch(&mut watched, "add_1", RESULT_PIN);
+    assert_ne!(graph.content_hash(), watched.content_hash());
+
+    watched.get_node_mut("add_1").unwrap().unwatch_pin(RESULT_PIN);
+    assert_eq!(graph.content_hash(), watched.content_hash());
+}
+
+#[test]
+fn watched_pins_round_trip_through_diffs() {
+    let graph = build_graph();
+    let mut watched = graph.clone();
+    watch(&mut watched, "add_1", RESULT_PIN);
+
+    let diff = GraphDiff::compute(&graph, &watched);
+    let mut applied = graph.clone();
+    diff.apply(&mut applied).unwrap();
+    assert_eq!(applied, watched);
+
+    diff.invert().apply(&mut applied).unwrap();
+    assert_eq!(applied, graph);
+}
+
+#[test]
+fn watched_pins_merge_pin_by_pin() {
+    let mut base = build_graph();
+    watch(&mut base, "tick", "delta_time");
+    let mut ours = base.clone();
+    watch(&mut ours, "tick", "exec");
+    let mut theirs = base.clone();
+    theirs.get_node_mut("tick").unwrap().unwatch_pin("delta_time");
+    watch(&mut theirs, "tick", "other");
+
+    let merged = merge_graphs(&base, &ours, &theirs).unwrap();
+    let pins: Vec<&str> = merged.get_node("tick").unwrap().watched_pins.iter().map(String::as_str).collect();
+    assert_eq!(pins, ["exec", "other"]);
+}
@@ -0,0 +1,172 @@
+//! Tests for graph variables: declarations, get/set nodes, usage analysis,
+//! and diffs.
+
+mod common;
+
+use common::*;
+use graphy::analysis::{VariableCheckPass, VariableUsage};
+use graphy::core::VariableDecl;
+use graphy::generation::graph_state_struct;
+use graphy::utils::diff::GraphDiff;
+use graphy::*;
+
+fn build_health_graph() -> GraphDescription {
+    let mut graph = GraphDescription::new("player");
+    graph.add_variable("health", VariableDecl::new("i64").with_default(PropertyValue::Integer(100)));
+    graph.add_variable("name", VariableDecl::new("String"));
+
+    let get = graph.create_get_variable_node("get_1", "health", Position::zero()).unwrap();
+    graph.add_node(get);
+    let set = graph.create_set_variable_node("set_1", "health", Position::zero()).unwrap();
+    graph.add_node(set);
+    graph
+}
+
+// ===========================================================================
+// Declarations and nodes
+// ===========================================================================
+
+#[test]
+fn variable_declarations() {
+    let mut graph = build_health_graph();
+    assert_eq!(graph.get_variable("health").unwrap().default, Some(PropertyValue::Integer(100)));
+
+    let removed = graph.remove_variable("name").unwrap();
+    assert_eq!(removed.type_info.type_string, "String");
+    assert!(graph.get_variable("name").is_none());
+}
+
+#[test]
+fn variable_nodes_have_typed_value_pins() {
+    let graph = build_health_graph();
+
+    let get = &graph.nodes["get_1"];
+    assert!(get.is_get_variable());
+    assert_eq!(get.variable_name(), Some("health"));
+    assert_eq!(get.outputs[0].id, "value");
+    assert_eq!(get.outputs[0].pin.data_type, DataType::Typed("i64".into()));
+
+    let set = &graph.nodes["set_1"];
+    assert!(set.is_set_variable());
+    let inputs: Vec<&str> = set.inputs.iter().map(|pin| pin.id.as_str()).collect();
+    assert_eq!(inputs, ["exec_in", "value"]);
+    assert_eq!(set.inputs[1].pin.data_type, DataType::Typed("i64".into()));
+}
+
+#[test]
+fn variable_node_for_undeclared_variable_fails() {
+    let graph = build_health_graph();
+    let result = graph.create_get_variable_node("get_2", "mana", Position::zero());
+    assert!(matches!(
+        result,
+        Err(GraphyError::UndeclaredVariable { ref node, ref variable }) if node == "get_2" && variable == "mana"
+    ));
+}
+
+#[test]
+fn variables_round_trip_through_json() {
+    let graph = build_health_graph();
+    let json = serde_json::to_string(&graph).unwrap();
+    let loaded: GraphDescription = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.variables, graph.variables);
+
+    let empty = serde_json::to_string(&GraphDescription::new("empty")).unwrap();
+    assert!(!empty.contains("variables"));
+}
+
+// ===========================================================================
+// VariableUsage
+// ===========================================================================
+
+#[test]
+fn variable_usage_records_readers_and_writers() {
+    let graph = build_health_graph();
+    let usage = VariableUsage::analyze(&graph).unwrap();
+
+    assert_eq!(usage.get_readers("health"), ["get_1".to_string()]);
+    assert_eq!(usage.get_writers("health"), ["set_1".to_string()]);
+    assert_eq!(usage.unused_variables(&graph), vec!["name"]);
+}
+
+#[test]
+fn variable_usage_rejects_undeclared_variable() {
+    let mut graph = build_health_graph();
+    graph.remove_variable("health");
+    assert!(matches!(
+        VariableUsage::analyze(&graph),
+        Err(GraphyError::UndeclaredVariable { .. })
+    ));
+}
+
+#[test]
+fn variable_usage_rejects_mismatched_value_type() {
+    let mut graph = build_health_graph();
+    graph.add_variable("health", VariableDecl::new("bool"));
+    assert!(matches!(VariableUsage::analyze(&graph), Err(GraphyError::TypeMismatch { .. })));
+}
+
+#[test]
+fn variable_usage_rejects_missing_name() {
+    let mut graph = build_health_graph();
+    graph.nodes.get_mut("get_1").unwrap().properties.clear();
+    assert!(matches!(VariableUsage::analyze(&graph), Err(GraphyError::Custom(_))));
+}
+
+#[test]
+fn variable_check_pass_stores_usage() {
+    let graph = build_health_graph();
+    let mut manager = PassManager::new();
+    manager.add_pass(VariableCheckPass);
+
+    let ctx = manager.run(&graph).unwrap();
+    assert_eq!(ctx.get::<VariableUsage>().unwrap().get_readers("health").len(), 1);
+}
+
+#[test]
+fn variable_pin_types_resolve_from_declaration() {
+    let mut graph = build_health_graph();
+    let mut get = NodeInstance::new("get_2", "graph.get_variable", Position::zero());
+    get.set_property("variable", PropertyValue::String("health".into()));
+    graph.add_node(get);
+
+    // get_2 has no pins: its value type comes from the declaration
+    let provider = TestMetadataProvider::comprehensive();
+    let connection = Connection::data("get_2", "value", "set_1", "value");
+    let (source, target) = graph.resolve_connection_types(&connection, &provider).unwrap();
+    assert_eq!(source, DataType::Typed("i64".into()));
+    assert_eq!(target, source);
+}
+
+// ===========================================================================
+// Diffs and state struct
+// ===========================================================================
+
+#[test]
+fn diff_tracks_variable_changes() {
+    let old = build_health_graph();
+    let mut new = old.clone();
+    new.add_variable("mana", VariableDecl::new("f64"));
+
+    let diff = GraphDiff::compute(&old, &new);
+    assert!(diff.variables.is_some());
+
+    let mut patched = old.clone();
+    diff.apply(&mut patched).unwrap();
+    assert_eq!(patched.variables, new.variables);
+
+    diff.invert().apply(&mut patched).unwrap();
+    assert_eq!(patched.variables, old.variables);
+}
+
+#[test]
+fn state_struct_sorts_fields_and_uses_defaults() {
+    let graph = build_health_graph();
+    let code = graph_state_struct(&graph, "PlayerState").unwrap();
+
+    let health = code.find("pub health: i64,").unwrap();
+    let name = code.find("pub name: String,").unwrap();
+    assert!(health < name);
+    assert!(code.contains("            health: 100,\n"), "{}", code);
+    assert!(code.contains("            name: String::new(),\n"), "{}", code);
+    assert!(code.contains("impl Default for PlayerState {"));
+}
@@ -0,0 +1,276 @@
+//! Tests for variadic node inputs.
+
+use graphy::core::{export_catalog, MetadataIssue, VariadicGroup};
+use graphy::export::catalog_to_markdown;
+use graphy::generation::collect_node_arguments;
+use graphy::generation::targets::{compile_c, CGenerator};
+use graphy::*;
+
+/// `on_tick`, `max_of` (2 to 4 `value`s), `report` (any number of `arg`s,
+/// template), `first` (at least one `item`, pure template), and `literal`
+fn variadic_registry() -> NodeRegistry {
+    let mut registry = NodeRegistry::new();
+    registry.register(NodeMetadata::new("on_tick", NodeTypes::event, "Events").with_exec_outputs(vec!["exec".into()]));
+    registry.register(
+        NodeMetadata::new("max_of", NodeTypes::pure, "Math")
+            .with_variadic(VariadicGroup::new("value", "i32").with_min(2).with_max(4))
+            .with_return_type("i32"),
+    );
+    registry.register(
+        NodeMetadata::new("report", NodeTypes::fn_, "Debug")
+            .with_params(vec![ParamInfo::new("level", "u8")])
+            .with_variadic(VariadicGroup::new("arg", "i32"))
+            .with_source("report({{level}}, {{arg}});")
+            .with_source_kind(SourceKind::Template)
+            .with_exec_outputs(vec!["then".into()]),
+    );
+    registry.register(
+        NodeMetadata::new("first", NodeTypes::pure, "Math")
+            .with_variadic(VariadicGroup::new("item", "i32").with_min(1))
+            .with_source("{{item_0}}")
+            .with_source_kind(SourceKind::Template)
+            .with_return_type("i32"),
+    );
+    registry.register(NodeMetadata::new("literal", NodeTypes::pure, "Math").with_return_type("i32"));
+    registry
+}
+
+fn input_ids(graph: &GraphDescription, node_id: &str) -> Vec<String> {
+    graph.nodes[node_id].inputs.iter().map(|pin| pin.id.clone()).collect()
+}
+
+// ===========================================================================
+// VariadicGroup
+// ===========================================================================
+
+#[test]
+fn variadic_group_names_its_pins() {
+    let group = VariadicGroup::new("element", "i64").with_min(1).with_max(3);
+    assert_eq!(group.pin_name(0), "element_0");
+    assert_eq!(group.pin_index("element_2"), Some(2));
+    assert_eq!(group.pin_index("element_02"), None);
+    assert_eq!(group.pin_index("elements_0"), None);
+    assert_eq!(group.pin_index("element"), None);
+    let param = group.param(1);
+    assert_eq!((param.name.as_str(), param.param_type.as_str()), ("element_1", "i64"));
+
+    assert!(!group.accepts(0));
+    assert!(group.accepts(3));
+    assert!(!group.accepts(4));
+    assert!(VariadicGroup::new("element", "i64").accepts(1000));
+}
+
+#[test]
+fn variadic_nodes_start_with_the_minimum() {
+    let registry = variadic_registry();
+    let mut graph = GraphDescription::new("graph");
+    graph.add_node_of_type("max_of", "max_1", &registry).unwrap();
+    graph.add_node_of_type("report", "report_1", &registry).unwrap();
+
+    assert_eq!(graph.nodes["max_1"].variadic_count, 2);
+    assert_eq!(input_ids(&graph, "max_1"), ["value_0", "value_1"]);
+    assert_eq!(graph.nodes["report_1"].variadic_count, 0);
+    assert_eq!(input_ids(&graph, "report_1"), ["exec_in", "level"]);
+}
+
+// ===========================================================================
+// set_variadic_count
+// ===========================================================================
+
+#[test]
+fn variadic_count_adds_pins_in_order() {
+    let registry = variadic_registry();
+    let mut graph = GraphDescription::new("graph");
+    graph.add_node_of_type("report", "report_1", &registry).unwrap();
+
+    graph.set_variadic_count("report_1", 3, &registry).unwrap();
+    assert_eq!(graph.nodes["report_1"].variadic_count, 3);
+    assert_eq!(input_ids(&graph, "report_1"), ["exec_in", "level", "arg_0", "arg_1", "arg_2"]);
+    assert_eq!(graph.nodes["report_1"].inputs[4].pin.data_type, DataType::Typed("i32".into()));
+}
+
+#[test]
+fn variadic_count_removes_the_last_pins_with_their_connections() {
+    let registry = variadic_registry();
+    let mut graph = GraphDescription::new("graph");
+    graph.add_node_of_type("literal", "lit", &registry).unwrap();
+    graph.add_node_of_type("max_of", "max_1", &registry).unwrap();
+    graph.set_variadic_count("max_1", 4, &registry).unwrap();
+    graph.add_connection(Connection::data("lit", "result", "max_1", "value_0"));
+    graph.add_connection(Connection::data("lit", "result", "max_1", "value_3"));
+    let node = graph.nodes.get_mut("max_1").unwrap();
+    node.set_property("value_1", PropertyValue::Integer(1));
+    node.set_property("value_2", PropertyValue::Integer(2));
+
+    graph.set_variadic_count("max_1", 2, &registry).unwrap();
+    assert_eq!(input_ids(&graph, "max_1"), ["value_0", "value_1"]);
+    assert_eq!(graph.connections.len(), 1);
+    assert_eq!(graph.connections[0].target_pin, "value_0");
+    let node = &graph.nodes["max_1"];
+    assert!(node.properties.contains_key("value_1"));
+    assert!(!node.properties.contains_key("value_2"));
+}
+
+#[test]
+fn variadic_count_is_checked() {
+    let registry = variadic_registry();
+    let mut graph = GraphDescription::new("graph");
+    graph.add_node_of_type("max_of", "max_1", &registry).unwrap();
+    graph.add_node_of_type("literal", "lit", &registry).unwrap();
+
+    let error = graph.set_variadic_count("max_1", 5, &registry).unwrap_err();
+    assert_eq!(error.to_string(), "Node 'max_1' takes 2 to 4 'value' inputs, not 5");
+    assert!(graph.set_variadic_count("max_1", 1, &registry).is_err());
+    assert_eq!(graph.nodes["max_1"].variadic_count, 2);
+
+    assert!(matches!(graph.set_variadic_count("lit", 1, &registry), Err(GraphyError::Custom(_))));
+    assert!(matches!(
+        graph.set_variadic_count("missing", 1, &registry),
+        Err(GraphyError::NodeNotFound(_))
+    ));
+}
+
+// ===========================================================================
+// Serialization and identity
+// ===========================================================================
+
+#[test]
+fn variadic_fields_serialize_only_when_used() {
+    let node = serde_json::to_value(NodeInstance::new("lit", "literal", Position::zero())).unwrap();
+    assert!(node.get("variadic_count").is_none());
+    let metadata = serde_json::to_value(NodeMetadata::new("literal", NodeTypes::pure, "Math")).unwrap();
+    assert!(metadata.get("variadic").is_none());
+
+    let group = serde_json::to_value(VariadicGroup::new("arg", "i32")).unwrap();
+    assert_eq!(group, serde_json::json!({ "name": "arg", "param_type": "i32" }));
+}
+
+#[test]
+fn variadic_nodes_survive_serialization() {
+    let registry = variadic_registry();
+    let mut graph = GraphDescription::new("graph");
+    graph.add_node_of_type("report", "report_1", &registry).unwrap();
+    graph.set_variadic_count("report_1", 12, &registry).unwrap();
+
+    let json = serde_json::to_string(&graph).unwrap();
+    let loaded: GraphDescription = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.nodes["report_1"].variadic_count, 12);
+    assert_eq!(input_ids(&loaded, "report_1"), input_ids(&graph, "report_1"));
+    assert!(loaded.semantically_equals(&graph));
+    assert_eq!(serde_json::to_string(&loaded).unwrap(), json);
+}
+
+#[test]
+fn variadic_count_changes_the_content_hash() {
+    let registry = variadic_registry();
+    let mut graph = GraphDescription::new("graph");
+    graph.add_node_of_type("report", "report_1", &registry).unwrap();
+    let mut changed = graph.clone();
+    changed.nodes.get_mut("report_1").unwrap().variadic_count = 1;
+    assert_ne!(graph.content_hash(), changed.content_hash());
+    assert!(!graph.semantically_equals(&changed));
+}
+
+// ===========================================================================
+// Arguments
+// ===========================================================================
+
+#[test]
+fn variadic_arguments_follow_the_params_in_order() {
+    let registry = variadic_registry();
+    let metadata = registry.get_node_metadata("report").unwrap();
+    let mut graph = GraphDescription::new("graph");
+    graph.add_node_of_type("report", "report_1", &registry).unwrap();
+    graph.set_variadic_count("report_1", 3, &registry).unwrap();
+    let node = graph.nodes.get_mut("report_1").unwrap();
+    node.set_property("arg_2", PropertyValue::Integer(7));
+
+    let params: Vec<String> = metadata.instance_params(node).iter().map(|param| param.name.clone()).collect();
+    assert_eq!(params, ["level", "arg_0", "arg_1", "arg_2"]);
+
+    let args = collect_node_arguments(node, metadata).unwrap();
+    assert_eq!(args.len(), 4);
+    assert_eq!(args[1], "0");
+    assert_eq!(args[3], format!("{:?}", PropertyValue::Integer(7)));
+}
+
+#[test]
+fn variadic_nodes_compile_to_c() {
+    let registry = variadic_registry();
+    let mut graph = GraphDescription::new("variadic");
+    graph.add_node_of_type("on_tick", "tick", &registry).unwrap();
+    graph.add_node_of_type("report", "report_1", &registry).unwrap();
+    graph.add_node_of_type("max_of", "max_1", &registry).unwrap();
+    graph.set_variadic_count("report_1", 3, &registry).unwrap();
+    graph.set_variadic_count("max_1", 3, &registry).unwrap();
+
+    let report = graph.nodes.get_mut("report_1").unwrap();
+    report.set_property("level", PropertyValue::Integer(2));
+    report.set_property("arg_0", PropertyValue::Integer(1));
+    report.set_property("arg_2", PropertyValue::Integer(9));
+    let max = graph.nodes.get_mut("max_1").unwrap();
+    for (pin, value) in [("value_0", 4), ("value_1", 8), ("value_2", 6)] {
+        max.set_property(pin, PropertyValue::Integer(value));
+    }
+    graph.add_connection(Connection::execution("tick", "exec", "report_1", "exec_in"));
+    graph.add_connection(Connection::data("max_1", "result", "report_1", "arg_1"));
+
+    let sources = compile_c(&graph, &registry, &mut CGenerator::new("variadic")).unwrap();
+    assert!(sources.header.contains("int32_t max_of(...);"), "{}", sources.header);
+    assert!(sources.source.contains("max_of(4, 8, 6)"), "{}", sources.source);
+    assert!(sources.source.contains("report(2, 1, "), "{}", sources.source);
+    assert!(sources.source.contains(", 9);\n"), "{}", sources.source);
+}
+
+#[test]
+fn variadic_templates_are_inlined_with_static_helpers() {
+    let registry = variadic_registry();
+    let mut graph = GraphDescription::new("variadic");
+    graph.add_node_of_type("on_tick", "tick", &registry).unwrap();
+    graph.add_node_of_type("report", "report_1", &registry).unwrap();
+    graph.add_node_of_type("first", "first_1", &registry).unwrap();
+    graph.set_variadic_count("report_1", 1, &registry).unwrap();
+    graph.nodes.get_mut("first_1").unwrap().set_property("item_0", PropertyValue::Integer(5));
+    graph.add_connection(Connection::execution("tick", "exec", "report_1", "exec_in"));
+    graph.add_connection(Connection::data("first_1", "result", "report_1", "arg_0"));
+
+    let mut generator = CGenerator::new("variadic").with_static_helpers(true);
+    let sources = compile_c(&graph, &registry, &mut generator).unwrap();
+    assert!(!sources.source.contains("static inline"), "{}", sources.source);
+    assert!(sources.source.contains("(5)"), "{}", sources.source);
+}
+
+// ===========================================================================
+// Metadata
+// ===========================================================================
+
+#[test]
+fn variadic_templates_validate() {
+    let registry = variadic_registry();
+    for name in ["report", "first"] {
+        assert!(registry.get_node_metadata(name).unwrap().validate().is_empty(), "{}", name);
+    }
+
+    let clash = NodeMetadata::new("clash", NodeTypes::pure, "Math")
+        .with_params(vec![ParamInfo::new("value", "i32")])
+        .with_variadic(VariadicGroup::new("value", "i32"));
+    assert_eq!(
+        clash.validate(),
+        vec![MetadataIssue::DuplicateParam {
+            node: "clash".into(),
+            param: "value".into()
+        }]
+    );
+}
+
+#[test]
+fn variadic_groups_are_in_the_catalog() {
+    let catalog = export_catalog(&variadic_registry());
+    let max_of = catalog.node("max_of").unwrap();
+    assert_eq!(max_of.variadic, Some(VariadicGroup::new("value", "i32").with_min(2).with_max(4)));
+    assert!(catalog.types.contains(&"i32".to_string()));
+
+    let markdown = catalog_to_markdown(&catalog, "Nodes");
+    assert!(markdown.contains("**Variadic inputs:** `value_<n>` of type `i32`, 2 to 4"), "{}", markdown);
+    assert!(markdown.contains("**Variadic inputs:** `arg_<n>` of type `i32`, any number"), "{}", markdown);
+}
@@ -0,0 +1,180 @@
+//! Tests for rebuilding graph files as they change.
+
+mod common;
+
+use common::*;
+use graphy::cache::MemoryCache;
+use graphy::io::save_graph_file;
+use graphy::watch::{WatchEvent, WatchService};
+use graphy::*;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+
+type Service = WatchService<TestMetadataProvider, CountingGenerator>;
+
+/// Fresh, empty graph directory unique to a test
+fn graph_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("graphy-watch-{}-{}", test, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn service(dir: &Path) -> (Service, Receiver<WatchEvent>) {
+    WatchService::new(dir, TestMetadataProvider::comprehensive(), CountingGenerator::default(), MemoryCache::new())
+}
+
+/// Events sent so far, as `(kind, file name)`
+fn drain(events: &Receiver<WatchEvent>) -> Vec<(&'static str, String)> {
+    events
+        .try_iter()
+        .map(|event| {
+            let kind = match event {
+                WatchEvent::Compiled { .. } => "compiled",
+                WatchEvent::Failed { .. } => "failed",
+                WatchEvent::Removed { .. } => "removed",
+            };
+            (kind, event.path().file_name().unwrap().to_string_lossy().into_owned())
+        })
+        .collect()
+}
+
+/// Branch graph running an unknown node, which fails to compile
+fn broken_graph() -> GraphDescription {
+    let mut graph = build_branch_graph();
+    graph.get_node_mut("print_true").unwrap().node_type = "plugin.missing".into();
+    graph
+}
+
+// ===========================================================================
+// WatchService
+// ===========================================================================
+
+#[test]
+fn scan_builds_every_graph_file() {
+    let dir = graph_dir("scan");
+    save_graph_file(&build_branch_graph(), dir.join("b.json")).unwrap();
+    std::fs::create_dir_all(dir.join("nested")).unwrap();
+    save_graph_file(&build_branch_graph(), dir.join("nested/a.json")).unwrap();
+    save_graph_file(&broken_graph(), dir.join("broken.json")).unwrap();
+    std::fs::create_dir_all(dir.join(".cache")).unwrap();
+    save_graph_file(&build_branch_graph(), dir.join(".cache/entry.json")).unwrap();
+    std::fs::write(dir.join("notes.txt"), "not a graph").unwrap();
+
+    let (mut service, events) = service(&dir);
+    assert_eq!(service.scan().unwrap(), 3);
+    assert_eq!(
+        drain(&events),
+        [("compiled", "b.json".into()), ("failed", "broken.json".into()), ("compiled", "a.json".into())]
+    );
+    assert_eq!(service.graph_files().len(), 3);
+    assert!(!service.is_graph_file(&dir.join(".cache/entry.json")));
+    assert!(!service.is_graph_file(Path::new("/elsewhere/graph.json")));
+
+    // Scanning again only rebuilds what changed
+    assert_eq!(service.scan().unwrap(), 3);
+    assert!(drain(&events).is_empty());
+}
+
+#[test]
+fn only_changed_content_is_rebuilt() {
+    let dir = graph_dir("changes");
+    let path = dir.join("graph.json");
+    let mut graph = build_branch_graph();
+    save_graph_file(&graph, &path).unwrap();
+    let (mut service, events) = service(&dir);
+    assert!(service.file_changed(&path));
+
+    // Moving a node isn't a change
+    graph.get_node_mut("branch_1").unwrap().position = Position::new(10.0, 20.0);
+    save_graph_file(&graph, &path).unwrap();
+    assert!(!service.file_changed(&path));
+    assert!(!service.file_changed(&dir.join("other.txt")));
+
+    graph.remove_node("print_false");
+    save_graph_file(&graph, &path).unwrap();
+    assert!(service.file_changed(&path));
+    let event = events.try_iter().last().unwrap();
+    let WatchEvent::Compiled { output, .. } = event else {
+        panic!("{:?}", event);
+    };
+    assert!(!output.code.contains("print_false"), "{}", output.code);
+    assert_eq!(service.generator().events, 2);
+
+    // Undoing the edit is served from the cache
+    save_graph_file(&build_branch_graph(), &path).unwrap();
+    assert!(service.file_changed(&path));
+    assert_eq!(service.generator().events, 2);
+    assert_eq!(service.cache().len(), 2);
+}
+
+#[test]
+fn failures_and_removals_are_reported() {
+    let dir = graph_dir("failures");
+    let path = dir.join("graph.json");
+    std::fs::write(&path, "{ not json").unwrap();
+    let (mut service, events) = service(&dir);
+
+    assert!(service.file_changed(&path));
+    let event = events.try_recv().unwrap();
+    assert!(matches!(event, WatchEvent::Failed { ref diagnostics, .. } if matches!(diagnostics[..], [GraphyError::Serialization(_)])));
+
+    save_graph_file(&broken_graph(), &path).unwrap();
+    service.file_changed(&path);
+    let event = events.try_recv().unwrap();
+    assert!(matches!(event, WatchEvent::Failed { ref diagnostics, .. } if matches!(diagnostics[..], [GraphyError::UnknownNodeType { .. }])));
+
+    save_graph_file(&build_branch_graph(), &path).unwrap();
+    service.file_changed(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert!(service.file_changed(&path));
+    assert_eq!(drain(&events), [("compiled", "graph.json".into()), ("removed", "graph.json".into())]);
+    assert!(service.graph_files().is_empty());
+
+    // Files never seen aren't removed
+    assert!(!service.file_changed(&dir.join("never.json")));
+}
+
+#[test]
+fn rebuild_all_uses_the_new_metadata() {
+    let dir = graph_dir("rebuild");
+    save_graph_file(&broken_graph(), dir.join("graph.json")).unwrap();
+    let (mut service, events) = service(&dir);
+    service.scan().unwrap();
+    assert_eq!(drain(&events), [("failed", "graph.json".into())]);
+
+    service
+        .provider_mut()
+        .add(NodeMetadata::new("plugin.missing", NodeTypes::fn_, "plugin").with_exec_outputs(vec!["then".into()]));
+    service.rebuild_all();
+    assert_eq!(drain(&events), [("compiled", "graph.json".into())]);
+}
+
+// ===========================================================================
+// GraphWatcher
+// ===========================================================================
+
+#[cfg(feature = "watch")]
+#[test]
+fn watcher_rebuilds_changed_files() {
+    use graphy::watch::GraphWatcher;
+    use std::time::Duration;
+
+    let dir = graph_dir("watcher");
+    let path = dir.join("graph.json");
+    save_graph_file(&build_branch_graph(), &path).unwrap();
+    let (service, events) = service(&dir);
+    let watcher = GraphWatcher::start(service).unwrap();
+
+    let next = || events.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert!(matches!(next(), WatchEvent::Compiled { .. }));
+
+    // Rebuilt once the file is written completely
+    save_graph_file(&broken_graph(), &path).unwrap();
+    let event = next();
+    assert!(matches!(event, WatchEvent::Failed { ref diagnostics, .. } if matches!(diagnostics[..], [GraphyError::UnknownNodeType { .. }])), "{:?}", event);
+
+    drop(watcher);
+    std::fs::remove_file(&path).unwrap();
+    assert!(events.iter().all(|event| !matches!(event, WatchEvent::Removed { .. })));
+}